@@ -0,0 +1,86 @@
+//! Accounts deleted by their users; see
+//! [`roomler_ai_services::account_deletion`]. [`delete`] disables the
+//! account and ends its memberships and connections; a periodic sweep
+//! anonymizes the profiles of accounts whose grace period ended.
+
+use std::time::Duration;
+
+use axum::extract::ws::Message;
+use bson::{DateTime, doc, oid::ObjectId};
+use futures::SinkExt;
+use roomler_ai_services::dao::base::DaoResult;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Spawn the purge sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.account_deletion.sweep_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match purge_due(&state, DateTime::now()).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Anonymized deleted accounts"),
+                Err(e) => warn!(%e, "Account purge sweep failed"),
+            }
+        }
+    });
+}
+
+/// Delete `user_id`'s account: drop its credentials, refuse its tokens,
+/// and take it out of its tenants, channels and calls. Returns when its
+/// profile will be anonymized, or `None` if it was already deleted.
+pub async fn delete(state: &AppState, user_id: ObjectId) -> DaoResult<Option<DateTime>> {
+    let grace = chrono::Duration::days(i64::from(state.settings.account_deletion.grace_days));
+    let purge_at = DateTime::from_chrono(chrono::Utc::now() + grace);
+    if !state.users.soft_delete_account(user_id, purge_at).await? {
+        return Ok(None);
+    }
+    state.deleted_accounts.mark_deleted(user_id);
+
+    for tenant in state.tenants.find_user_tenants(user_id).await? {
+        let Some(tid) = tenant.id else { continue };
+        for room in state.rooms.find_user_rooms(tid, user_id).await? {
+            let Some(rid) = room.id else { continue };
+            if state.rooms.is_active_participant(rid, user_id).await? {
+                state.rooms.leave_participant(rid, user_id).await?;
+            }
+            state.rooms.leave(tid, rid, user_id).await?;
+        }
+        state.tenants.remove_member(tid, user_id).await?;
+        crate::billing_events::check_limits(state, tid).await;
+    }
+    state
+        .push_subscriptions
+        .base
+        .hard_delete(doc! { "user_id": user_id })
+        .await?;
+
+    // Closing the sockets takes the user out of live calls like any
+    // disconnect; reconnecting is refused from now on.
+    for sender in state.ws_storage.get_senders(&user_id) {
+        let _ = sender.lock().await.send(Message::Close(None)).await;
+    }
+    Ok(Some(purge_at))
+}
+
+/// Anonymize every deleted account whose grace period ended by `now`.
+/// Returns how many were anonymized.
+pub async fn purge_due(state: &AppState, now: DateTime) -> DaoResult<u64> {
+    let mut purged = 0;
+    for user in state.users.find_due_purges(now).await? {
+        let Some(user_id) = user.id else { continue };
+        // Another instance may have purged it since the query.
+        if state.users.anonymize(user_id).await? {
+            state
+                .notifications
+                .base
+                .hard_delete(doc! { "user_id": user_id })
+                .await?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
@@ -0,0 +1,117 @@
+//! Monthly analytics reports. Once a month has ended, a periodic sweep
+//! emails its CSV to the admins (members with `MANAGE_TENANT`) of each
+//! tenant that turned the report on, once per tenant and month; see
+//! [`crate::routes::analytics`]. Nothing is sent, or marked sent, without
+//! an email service.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use chrono::{NaiveDate, Utc};
+use roomler_ai_db::models::{Tenant, role::permissions};
+use roomler_ai_services::{analytics, dao::base::DaoResult};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Spawn the monthly report sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.analytics.report_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match send_due(&state, Utc::now().date_naive()).await {
+                Ok(0) => {}
+                Ok(sent) => info!(sent, "Sent monthly analytics reports"),
+                Err(e) => warn!(%e, "Monthly analytics report sweep failed"),
+            }
+        }
+    });
+}
+
+/// Send the report for the month before `today` to every tenant still
+/// waiting for it. Returns how many tenants were sent one.
+pub async fn send_due(state: &AppState, today: NaiveDate) -> DaoResult<u64> {
+    if state.email.is_none() {
+        return Ok(0);
+    }
+    let (from, to, month) = analytics::previous_month(today);
+    let mut sent = 0;
+    for tenant in state.tenants.find_due_analytics_reports(&month).await? {
+        let Some(tid) = tenant.id else { continue };
+        // Another instance may have claimed it since the query.
+        if state.tenants.claim_analytics_report(tid, &month).await? {
+            send(state, &tenant, tid, from, to, &month).await;
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+async fn send(
+    state: &AppState,
+    tenant: &Tenant,
+    tid: ObjectId,
+    from: NaiveDate,
+    to: NaiveDate,
+    month: &str,
+) {
+    let Some(email) = &state.email else { return };
+    let rows = match state.analytics.daily_activity(tid, from, to).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(%tid, %e, "Monthly analytics report: failed to aggregate");
+            return;
+        }
+    };
+    let csv = analytics::to_csv(&rows);
+    let file_name = format!("analytics-{}.csv", month);
+    let subject = format!("{} analytics for {}", tenant.name, month);
+    let html = format!(
+        r#"<div style="font-family: sans-serif; max-width: 600px; margin: 0 auto;">
+<h2>{tenant} — {month}</h2>
+<p>Attached are last month's daily active users, messages and conference minutes.</p>
+<p>Active users on the busiest day: <strong>{peak}</strong>. Messages: <strong>{messages}</strong>. Conference minutes: <strong>{minutes}</strong>.</p>
+<p style="color: #999; font-size: 12px; margin-top: 32px;">— The Roomler Team</p>
+</div>"#,
+        tenant = tenant.name,
+        month = month,
+        peak = rows.iter().map(|r| r.active_users).max().unwrap_or(0),
+        messages = rows.iter().map(|r| r.messages).sum::<u64>(),
+        minutes = rows.iter().map(|r| r.conference_minutes).sum::<u64>(),
+    );
+
+    for user_id in admin_ids(state, tid).await {
+        let Ok(user) = state.users.base.find_by_id(user_id).await else {
+            continue;
+        };
+        if user.deleted_at.is_some() {
+            continue;
+        }
+        if let Err(e) = email
+            .send_with_attachment(&user.email, &subject, &html, &file_name, "text/csv", &csv)
+            .await
+        {
+            warn!(%tid, %user_id, %e, "Monthly analytics report: email failed");
+        }
+    }
+}
+
+/// Members allowed to manage the tenant.
+pub(crate) async fn admin_ids(state: &AppState, tid: ObjectId) -> Vec<ObjectId> {
+    let mut admins = Vec::new();
+    for user_id in state
+        .tenants
+        .find_member_user_ids(tid)
+        .await
+        .unwrap_or_default()
+    {
+        if let Ok(perms) = state.tenants.get_member_permissions(tid, user_id).await
+            && permissions::has(perms, permissions::MANAGE_TENANT)
+        {
+            admins.push(user_id);
+        }
+    }
+    admins
+}
@@ -0,0 +1,135 @@
+//! Bot API call counters for the quotas in
+//! [`roomler_ai_services::api_quota`]. Counts live in Redis, one hash per
+//! tenant and UTC day holding the tenant's total and a count per bot, so
+//! every instance enforces the same quota. Without Redis each instance
+//! counts on its own.
+
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use tracing::{info, warn};
+
+const KEY_PREFIX: &str = "roomler:quota";
+const TOTAL_FIELD: &str = "total";
+/// Days kept, so the developer settings can show the past week.
+const RETENTION_DAYS: i64 = 8;
+
+/// Calls made on one day.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DayUsage {
+    pub total: u64,
+    pub per_bot: HashMap<ObjectId, u64>,
+}
+
+pub struct ApiUsage {
+    redis: Option<ConnectionManager>,
+    /// `(tenant, day)` counters when Redis is unavailable.
+    local: DashMap<(ObjectId, String), DayUsage>,
+}
+
+impl ApiUsage {
+    pub async fn new(redis_url: &str) -> Self {
+        let redis = match redis::Client::open(redis_url) {
+            Ok(client) => ConnectionManager::new(client).await,
+            Err(e) => Err(e),
+        };
+        let redis = match redis {
+            Ok(conn) => {
+                info!("API usage counters kept in Redis");
+                Some(conn)
+            }
+            Err(e) => {
+                warn!(%e, "Redis unavailable — API usage counted per instance");
+                None
+            }
+        };
+        Self {
+            redis,
+            local: DashMap::new(),
+        }
+    }
+
+    /// Count a call by `bot_id` on `day`. Returns the tenant's calls that
+    /// day, this one included.
+    pub async fn record(&self, tenant_id: ObjectId, bot_id: ObjectId, day: &str) -> u64 {
+        if let Some(conn) = &self.redis {
+            let key = key(tenant_id, day);
+            let result = redis::pipe()
+                .atomic()
+                .cmd("HINCRBY")
+                .arg(&key)
+                .arg(TOTAL_FIELD)
+                .arg(1)
+                .cmd("HINCRBY")
+                .arg(&key)
+                .arg(bot_id.to_hex())
+                .arg(1)
+                .ignore()
+                .cmd("EXPIRE")
+                .arg(&key)
+                .arg(RETENTION_DAYS * 24 * 3600)
+                .ignore()
+                .query_async::<(u64,)>(&mut conn.clone())
+                .await;
+            match result {
+                Ok((total,)) => return total,
+                Err(e) => warn!(%e, "Failed to count API call in Redis, counting locally"),
+            }
+        }
+
+        if !self.local.contains_key(&(tenant_id, day.to_string())) {
+            self.prune(day);
+        }
+        let mut usage = self.local.entry((tenant_id, day.to_string())).or_default();
+        usage.total += 1;
+        *usage.per_bot.entry(bot_id).or_default() += 1;
+        usage.total
+    }
+
+    /// The tenant's calls on `day`.
+    pub async fn usage(&self, tenant_id: ObjectId, day: &str) -> DayUsage {
+        if let Some(conn) = &self.redis {
+            let result = redis::cmd("HGETALL")
+                .arg(key(tenant_id, day))
+                .query_async::<HashMap<String, u64>>(&mut conn.clone())
+                .await;
+            match result {
+                Ok(fields) => {
+                    let mut usage = DayUsage::default();
+                    for (field, count) in fields {
+                        if field == TOTAL_FIELD {
+                            usage.total = count;
+                        } else if let Ok(bot_id) = ObjectId::parse_str(&field) {
+                            usage.per_bot.insert(bot_id, count);
+                        }
+                    }
+                    return usage;
+                }
+                Err(e) => warn!(%e, "Failed to read API usage from Redis"),
+            }
+        }
+
+        self.local
+            .get(&(tenant_id, day.to_string()))
+            .map(|usage| usage.clone())
+            .unwrap_or_default()
+    }
+
+    /// Drop local counters older than the retention window. Days are
+    /// `YYYY-MM-DD`, so they compare as strings.
+    fn prune(&self, today: &str) {
+        let Ok(today) = chrono::NaiveDate::parse_from_str(today, "%Y-%m-%d") else {
+            return;
+        };
+        let oldest = (today - chrono::Duration::days(RETENTION_DAYS - 1))
+            .format("%Y-%m-%d")
+            .to_string();
+        self.local.retain(|(_, day), _| *day >= oldest);
+    }
+}
+
+fn key(tenant_id: ObjectId, day: &str) -> String {
+    format!("{KEY_PREFIX}:{}:{day}", tenant_id.to_hex())
+}
@@ -0,0 +1,93 @@
+//! Tenant audit log.
+//!
+//! Admin actions (rooms created or deleted, members added or removed, role
+//! changes, invites, billing, retention overrides) are recorded through
+//! [`record`] with the actor, target and client IP; system actions such as
+//! retention purges through [`record_system`]. Admins read them back from
+//! `GET /api/tenant/{tenant_id}/audit`. Recording is best-effort: a failed
+//! write is logged and never fails the action that caused it.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ActorType, AuditChange, AuditMetadata};
+use roomler_ai_services::dao::audit_log::AuditEntry;
+use tracing::warn;
+
+use crate::{extractors::client::ClientInfo, state::AppState};
+
+/// Record `action` by `actor_id` on `target_id`.
+pub async fn record(
+    state: &AppState,
+    tenant_id: ObjectId,
+    actor_id: ObjectId,
+    client: &ClientInfo,
+    action: &str,
+    target_id: Option<ObjectId>,
+    changes: Vec<AuditChange>,
+) {
+    write(
+        state,
+        tenant_id,
+        AuditEntry {
+            actor_id: Some(actor_id),
+            actor_type: ActorType::User,
+            action: action.to_string(),
+            target_id,
+            changes,
+            metadata: metadata(client),
+        },
+    )
+    .await;
+}
+
+/// Record an action with no user behind it, such as a billing webhook or a
+/// retention purge.
+pub async fn record_system(
+    state: &AppState,
+    tenant_id: ObjectId,
+    actor_type: ActorType,
+    action: &str,
+    target_id: Option<ObjectId>,
+    changes: Vec<AuditChange>,
+) {
+    write(
+        state,
+        tenant_id,
+        AuditEntry {
+            actor_id: None,
+            actor_type,
+            action: action.to_string(),
+            target_id,
+            changes,
+            metadata: AuditMetadata::default(),
+        },
+    )
+    .await;
+}
+
+/// A changed field, for `changes`.
+pub fn change(
+    field: &str,
+    old_value: Option<serde_json::Value>,
+    new_value: Option<serde_json::Value>,
+) -> AuditChange {
+    AuditChange {
+        field: field.to_string(),
+        old_value,
+        new_value,
+    }
+}
+
+fn metadata(client: &ClientInfo) -> AuditMetadata {
+    AuditMetadata {
+        ip: client.ip.map(|ip| ip.to_string()),
+        user_agent: client.user_agent.clone(),
+        reason: None,
+    }
+}
+
+async fn write(state: &AppState, tenant_id: ObjectId, entry: AuditEntry) {
+    let action = entry.action.clone();
+    if let Err(e) = state.audit_log.record(tenant_id, entry).await {
+        warn!(%e, ?tenant_id, %action, "Failed to record audit entry");
+    }
+}
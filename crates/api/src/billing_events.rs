@@ -0,0 +1,116 @@
+//! Billing events: a tenant's plan, subscription status or usage-limit
+//! state changing, sent to the tenant's outgoing webhooks (see
+//! [`crate::webhooks`]).
+//!
+//! Plan and subscription changes arrive through the Stripe webhook, which
+//! audits them and calls [`billing_changed`]. The usage-limit state, which
+//! plan limits the tenant is over, is kept on the tenant and re-checked by
+//! [`check_limits`] after billing changes and after admin actions that add
+//! or remove members or channels. Each change of it is audited as
+//! `billing.limits_change`.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ActorType, Tenant, actions, webhook_events};
+use roomler_ai_services::{
+    dao::base::DaoResult,
+    plan_usage::{PlanUsage, exceeded_limits},
+};
+use tracing::warn;
+
+use crate::{audit, state::AppState, webhooks};
+
+/// Send what changed between `before` and `after` to the tenant's
+/// webhooks, then re-check its limits against the plan it is on now.
+pub async fn billing_changed(state: &AppState, before: &Tenant, after: &Tenant) {
+    let Some(tenant_id) = after.id else { return };
+    if before.plan != after.plan {
+        webhooks::dispatch(
+            state,
+            tenant_id,
+            webhook_events::BILLING_PLAN_CHANGE,
+            serde_json::json!({
+                "old_plan": before.plan,
+                "new_plan": after.plan,
+                "downgrade": after.plan < before.plan,
+                "limits": after.plan.limits(),
+            }),
+        );
+    }
+
+    let subscription = |t: &Tenant| {
+        t.billing
+            .as_ref()
+            .map(|b| (b.status.clone(), b.cancel_at_period_end))
+    };
+    if subscription(before) != subscription(after) {
+        let billing = after.billing.as_ref();
+        webhooks::dispatch(
+            state,
+            tenant_id,
+            webhook_events::BILLING_SUBSCRIPTION_CHANGE,
+            serde_json::json!({
+                "old_status": before.billing.as_ref().map(|b| &b.status),
+                "new_status": billing.map(|b| &b.status),
+                "cancel_at_period_end": billing.is_some_and(|b| b.cancel_at_period_end),
+                "current_period_end": billing
+                    .and_then(|b| b.current_period_end)
+                    .and_then(|t| t.try_to_rfc3339_string().ok()),
+            }),
+        );
+    }
+
+    check_limits(state, tenant_id).await;
+}
+
+/// Re-check which plan limits the tenant is over, and audit and send a
+/// change. Best-effort: a failure is logged and never fails the caller.
+pub async fn check_limits(state: &AppState, tenant_id: ObjectId) {
+    if let Err(e) = update_limits(state, tenant_id).await {
+        warn!(%e, %tenant_id, "Plan limit check failed");
+    }
+}
+
+async fn update_limits(state: &AppState, tenant_id: ObjectId) -> DaoResult<()> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let usage = PlanUsage {
+        members: state.tenants.count_members(tenant_id).await?,
+        channels: state.rooms.count_channels(tenant_id).await?,
+    };
+    let limits = tenant.plan.limits();
+    let exceeded = exceeded_limits(&limits, &usage);
+    if exceeded == tenant.exceeded_limits
+        || !state
+            .tenants
+            .swap_exceeded_limits(tenant_id, &tenant.exceeded_limits, &exceeded)
+            .await?
+    {
+        return Ok(());
+    }
+
+    audit::record_system(
+        state,
+        tenant_id,
+        ActorType::System,
+        actions::BILLING_LIMITS_CHANGE,
+        Some(tenant_id),
+        vec![audit::change(
+            "exceeded_limits",
+            Some(serde_json::json!(tenant.exceeded_limits)),
+            Some(serde_json::json!(exceeded)),
+        )],
+    )
+    .await;
+    webhooks::dispatch(
+        state,
+        tenant_id,
+        webhook_events::BILLING_LIMITS_CHANGE,
+        serde_json::json!({
+            "plan": tenant.plan,
+            "exceeded": exceeded,
+            "previously_exceeded": tenant.exceeded_limits,
+            "usage": usage,
+            "limits": limits,
+        }),
+    );
+    Ok(())
+}
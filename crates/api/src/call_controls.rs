@@ -0,0 +1,58 @@
+//! In-call controls from a room's [`ConferenceSettings`]: mute on entry,
+//! self-unmute, chat, reactions, attendee screen sharing, the waitlist for
+//! full conferences and the lobby. Organizers set them and are never
+//! restricted by them.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::ConferenceSettings;
+use roomler_ai_services::dao::base::DaoResult;
+use serde::Serialize;
+
+use crate::state::AppState;
+
+/// The controls as clients see them; the rest of the conference settings
+/// (schedule, passcode) stay private.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallControls {
+    pub mute_on_entry: bool,
+    pub allow_unmute: bool,
+    pub chat_enabled: bool,
+    pub reactions_enabled: bool,
+    pub attendee_screen_share: bool,
+    pub waitlist_enabled: bool,
+    pub lobby_enabled: bool,
+}
+
+impl From<&ConferenceSettings> for CallControls {
+    fn from(s: &ConferenceSettings) -> Self {
+        Self {
+            mute_on_entry: s.mute_on_entry,
+            allow_unmute: s.allow_unmute,
+            chat_enabled: s.chat_enabled,
+            reactions_enabled: s.reactions_enabled,
+            attendee_screen_share: s.attendee_screen_share,
+            waitlist_enabled: s.waitlist_enabled,
+            lobby_enabled: s.lobby_enabled,
+        }
+    }
+}
+
+/// The controls that bind `user_id` in `room_id`, or `None` for the room's
+/// organizers.
+pub async fn restrictions(
+    state: &AppState,
+    room_id: ObjectId,
+    user_id: ObjectId,
+) -> DaoResult<Option<CallControls>> {
+    let room = state.rooms.base.find_by_id(room_id).await?;
+    if room.organizer_ids().contains(&user_id) {
+        return Ok(None);
+    }
+    Ok(Some(CallControls::from(&room.conference())))
+}
+
+/// Screen-share producers have a `screen` source, including any audio
+/// captured with the shared screen (`screen_audio`).
+pub fn is_screen_share(source: &str) -> bool {
+    source.starts_with("screen")
+}
@@ -0,0 +1,137 @@
+//! Daily channel digests. A periodic sweep posts, once per UTC day and
+//! after the hour each channel chose, a system message with the channel's
+//! top threads, most reacted messages and files shared over the last 24
+//! hours, plus a Claude summary when asked for and available; see
+//! [`crate::routes::channel_digest`]. Quiet days post nothing.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bson::DateTime;
+use chrono::{Timelike, Utc};
+use roomler_ai_db::models::Room;
+use roomler_ai_services::{channel_digest, dao::base::DaoResult};
+use tracing::{info, warn};
+
+use crate::routes::message::{author_names, collect_author_ids};
+use crate::state::AppState;
+
+/// Messages aggregated per digest; busier days are digested from their
+/// most recent messages.
+const MAX_MESSAGES: i64 = 5000;
+
+/// Spawn the digest sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.channel_digest.sweep_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match post_due(&state, Utc::now()).await {
+                Ok(0) => {}
+                Ok(posted) => info!(posted, "Posted channel digests"),
+                Err(e) => warn!(%e, "Channel digest sweep failed"),
+            }
+        }
+    });
+}
+
+/// Post the digest of every channel due at `now`. Returns how many were
+/// posted.
+pub async fn post_due(state: &AppState, now: chrono::DateTime<Utc>) -> DaoResult<u64> {
+    let day = channel_digest::day(now);
+    let mut posted = 0;
+    for room in state.rooms.find_due_digests(&day, now.hour()).await? {
+        let Some(rid) = room.id else { continue };
+        // Another instance may have claimed it since the query.
+        if state.rooms.claim_digest(rid, &day).await? && post(state, &room, now).await {
+            posted += 1;
+        }
+    }
+    Ok(posted)
+}
+
+/// Returns whether a digest was posted.
+async fn post(state: &AppState, room: &Room, now: chrono::DateTime<Utc>) -> bool {
+    let (Some(rid), Some(digest)) = (room.id, room.digest.as_ref()) else {
+        return false;
+    };
+    let since = DateTime::from_chrono(now - chrono::Duration::hours(24));
+    let messages = match state.messages.find_since(rid, since, MAX_MESSAGES).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!(%rid, %e, "Channel digest: failed to load messages");
+            return false;
+        }
+    };
+    let roots: HashMap<_, _> = state
+        .messages
+        .base
+        .find_by_ids(&channel_digest::thread_ids(&messages))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|m| Some((m.id?, m)))
+        .collect();
+    let highlights = channel_digest::highlights(&messages, &roots);
+    if highlights.message_count == 0 {
+        return false;
+    }
+
+    let mut author_ids = collect_author_ids(&messages);
+    author_ids.extend(roots.values().map(|m| m.author_id));
+    author_ids.push(digest.configured_by);
+    let names = author_names(state, &author_ids).await;
+
+    let summary = if digest.summarize && state.recognition.is_available() {
+        match state
+            .recognition
+            .complete_text(channel_digest::build_prompt(&messages, &names))
+            .await
+        {
+            Ok(summary) => Some(summary),
+            Err(e) => {
+                warn!(%rid, %e, "Channel digest: summary failed, posting highlights only");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let content = channel_digest::render(&highlights, &names, summary.as_deref());
+
+    let (tenant_id, author_id) = (room.tenant_id, digest.configured_by);
+    let message =
+        match crate::profanity::post(state, tenant_id, rid, author_id, content, |content| {
+            state
+                .messages
+                .create_system(tenant_id, rid, author_id, content)
+        })
+        .await
+        {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(%rid, %e, "Channel digest: failed to post system message");
+                return false;
+            }
+        };
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": crate::routes::message::to_response(message, &names, None),
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        room.tenant_id,
+        &member_ids,
+        &event,
+    )
+    .await;
+    true
+}
@@ -0,0 +1,143 @@
+//! Conference chat retention, configured per tenant in
+//! `settings.conference_chat` and kept apart from channel messages.
+//!
+//! A periodic sweep purges conference chat older than the tenant's
+//! `retention_days`, and [`discard_at_call_end`] drops a call's chat when it
+//! ends if the tenant asks for that and the room's organizers haven't chosen
+//! to keep it. Admins can exempt a room from the sweep or give it a shorter
+//! period with a [`RetentionOverride`](roomler_ai_db::models::RetentionOverride).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{ActorType, Room, actions};
+use roomler_ai_services::dao::base::DaoResult;
+use tracing::{info, warn};
+
+use crate::{audit, state::AppState};
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Spawn the retention sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.conference_chat.purge_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match purge_expired(&state).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Purged expired conference chat"),
+                Err(e) => warn!(%e, "Conference chat purge failed"),
+            }
+        }
+    });
+}
+
+/// Delete conference chat past each tenant's retention period, or past a
+/// room's own period where admins set a retention override. Returns the
+/// number of messages removed; each room purged is recorded in the tenant's
+/// audit log.
+pub async fn purge_expired(state: &AppState) -> DaoResult<u64> {
+    let now = DateTime::now().timestamp_millis();
+    let cutoff = |days: u32| DateTime::from_millis(now - i64::from(days) * MILLIS_PER_DAY);
+
+    let overridden: HashMap<ObjectId, Room> = state
+        .rooms
+        .find_with_retention_override()
+        .await?
+        .into_iter()
+        .filter_map(|room| Some((room.id?, room)))
+        .collect();
+    let mut tenant_days = HashMap::new();
+    let mut purged = 0;
+
+    for tenant in state.tenants.find_with_conference_chat_retention().await? {
+        let (Some(tid), Some(days)) = (tenant.id, tenant.settings.conference_chat.retention_days)
+        else {
+            continue;
+        };
+        tenant_days.insert(tid, days);
+        let before = cutoff(days);
+        for rid in state.rooms.rooms_with_chat_before(tid, before).await? {
+            if !overridden.contains_key(&rid) {
+                purged += purge_room(state, tid, rid, days, before).await?;
+            }
+        }
+    }
+
+    for (rid, room) in &overridden {
+        let days = room
+            .retention_override
+            .as_ref()
+            .and_then(|o| o.effective_days(tenant_days.get(&room.tenant_id).copied()));
+        if let Some(days) = days {
+            purged += purge_room(state, room.tenant_id, *rid, days, cutoff(days)).await?;
+        }
+    }
+    Ok(purged)
+}
+
+async fn purge_room(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    retention_days: u32,
+    before: DateTime,
+) -> DaoResult<u64> {
+    let purged = state
+        .rooms
+        .purge_chat_messages_before(room_id, before)
+        .await?;
+    if purged > 0 {
+        audit::record_system(
+            state,
+            tenant_id,
+            ActorType::System,
+            actions::ROOM_CHAT_PURGE,
+            Some(room_id),
+            vec![
+                audit::change("purged", None, Some(purged.into())),
+                audit::change("retention_days", None, Some(retention_days.into())),
+                audit::change(
+                    "before",
+                    None,
+                    before.try_to_rfc3339_string().ok().map(Into::into),
+                ),
+            ],
+        )
+        .await;
+    }
+    Ok(purged)
+}
+
+/// Drop the chat of the call that just ended in `room` when its tenant
+/// discards conference chat at call end. `room` is the document read before
+/// the call was ended, so `actual_start_time` still marks this call's start
+/// and chat kept from earlier calls is left alone.
+pub async fn discard_at_call_end(state: &AppState, room: &Room) {
+    let Some(rid) = room.id else { return };
+    if room.keep_conference_chat {
+        return;
+    }
+    let discard = match state.tenants.base.find_by_id(room.tenant_id).await {
+        Ok(tenant) => tenant.settings.conference_chat.discard_at_call_end,
+        Err(e) => {
+            warn!(%rid, %e, "Conference chat: tenant lookup failed");
+            return;
+        }
+    };
+    if !discard {
+        return;
+    }
+    match state
+        .rooms
+        .delete_chat_messages(rid, room.actual_start_time)
+        .await
+    {
+        Ok(0) => {}
+        Ok(n) => info!(%rid, discarded = n, "Discarded conference chat at call end"),
+        Err(e) => warn!(%rid, %e, "Conference chat: discard failed"),
+    }
+}
@@ -0,0 +1,57 @@
+//! Structured conference event feed.
+//!
+//! Call handlers report what happens in a call (joins, leaves, producers,
+//! mute toggles, recordings, transcription) through [`record`], and
+//! integrations read it back in order from
+//! `GET /api/tenant/{tenant_id}/room/{room_id}/call/event`. Recording is
+//! best-effort: a failed write is logged and never fails the action that
+//! caused it.
+//!
+//! Call starts and ends are also sent to the tenant's webhooks as
+//! `conference.start` and `conference.end`.
+
+use bson::{Bson, Document, oid::ObjectId};
+use roomler_ai_db::models::{ConferenceEventType, webhook_events};
+use tracing::warn;
+
+use crate::state::AppState;
+
+pub async fn record(
+    state: &AppState,
+    room_id: ObjectId,
+    event_type: ConferenceEventType,
+    user_id: Option<ObjectId>,
+    data: Document,
+) {
+    let tenant_id = match state.rooms.base.find_by_id(room_id).await {
+        Ok(room) => room.tenant_id,
+        Err(e) => {
+            warn!(%e, ?room_id, ?event_type, "Conference event for unknown room");
+            return;
+        }
+    };
+    let webhook_event = match event_type {
+        ConferenceEventType::CallStarted => Some(webhook_events::CONFERENCE_START),
+        ConferenceEventType::CallEnded => Some(webhook_events::CONFERENCE_END),
+        _ => None,
+    };
+    if let Some(webhook_event) = webhook_event {
+        crate::webhooks::dispatch(
+            state,
+            tenant_id,
+            webhook_event,
+            serde_json::json!({
+                "room_id": room_id.to_hex(),
+                "user_id": user_id.map(|id| id.to_hex()),
+                "data": Bson::Document(data.clone()).into_relaxed_extjson(),
+            }),
+        );
+    }
+    if let Err(e) = state
+        .conference_events
+        .record(tenant_id, room_id, event_type, user_id, data)
+        .await
+    {
+        warn!(%e, ?room_id, ?event_type, "Failed to record conference event");
+    }
+}
@@ -0,0 +1,363 @@
+//! Enforcement of plan-based conference limits.
+//!
+//! Every `conference_limits.check_interval_secs` the sweep walks the media
+//! rooms hosted on this instance, warns organizers (`conference:limit_warning`)
+//! once per limit as a cutoff approaches, and auto-ends conferences that hit
+//! their max duration or idle timeout.
+//!
+//! Participant caps are checked on join by [`admit`]. Past the cap a joiner
+//! is turned away or, with the room's waitlist on, queued and told with
+//! `conference:waitlisted`; [`fill_open_slots`] lets waiters in as others
+//! leave.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use bson::{doc, oid::ObjectId};
+use roomler_ai_db::models::{ConferenceEventType, Room};
+use roomler_ai_services::conference_limits::{self, JoinDecision, LimitDecision, LimitKind};
+use roomler_ai_services::dao::base::DaoResult;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Per-room bookkeeping carried between sweeps.
+#[derive(Default)]
+pub struct RoomTracker {
+    idle_since: Option<Instant>,
+    warned: HashSet<LimitKind>,
+}
+
+/// Spawn the periodic sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.conference_limits.check_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut trackers = HashMap::new();
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            sweep(&state, &mut trackers).await;
+        }
+    });
+}
+
+/// Evaluate every active media room once.
+pub async fn sweep(state: &AppState, trackers: &mut HashMap<ObjectId, RoomTracker>) {
+    let room_ids = state.room_manager.room_ids();
+    trackers.retain(|rid, _| room_ids.contains(rid));
+
+    let settings = &state.settings.conference_limits;
+    let warn_before = Duration::from_secs(settings.warn_before_secs);
+
+    for rid in room_ids {
+        let tracker = trackers.entry(rid).or_default();
+        if state.room_manager.has_producers(&rid) {
+            tracker.idle_since = None;
+        } else {
+            tracker.idle_since.get_or_insert_with(Instant::now);
+        }
+
+        let room = match state.rooms.base.find_by_id(rid).await {
+            Ok(room) => room,
+            Err(e) => {
+                warn!(%rid, %e, "Conference limits: room lookup failed");
+                continue;
+            }
+        };
+        if room.conference_status.as_deref() != Some("in_progress") {
+            continue;
+        }
+        let plan = match state.tenants.base.find_by_id(room.tenant_id).await {
+            Ok(tenant) => tenant.plan,
+            Err(e) => {
+                warn!(%rid, %e, "Conference limits: tenant lookup failed");
+                continue;
+            }
+        };
+
+        let started_ms = room
+            .actual_start_time
+            .unwrap_or(room.updated_at)
+            .timestamp_millis();
+        let elapsed_ms = bson::DateTime::now().timestamp_millis() - started_ms;
+        let elapsed = Duration::from_millis(elapsed_ms.max(0) as u64);
+        let idle_for = tracker.idle_since.map(|since| since.elapsed());
+
+        let limits = conference_limits::limits_for_plan(settings, &plan);
+        match conference_limits::evaluate(limits, warn_before, elapsed, idle_for) {
+            LimitDecision::Continue => {}
+            LimitDecision::Warn { kind, remaining } => {
+                if tracker.warned.insert(kind) {
+                    warn_organizers(state, &room, kind, remaining).await;
+                }
+            }
+            LimitDecision::End { kind } => {
+                end_conference(state, &room, kind).await;
+                trackers.remove(&rid);
+            }
+        }
+    }
+}
+
+async fn warn_organizers(state: &AppState, room: &Room, kind: LimitKind, remaining: Duration) {
+    let Some(rid) = room.id else { return };
+    let event = serde_json::json!({
+        "type": "conference:limit_warning",
+        "data": {
+            "room_id": rid.to_hex(),
+            "kind": kind,
+            "remaining_secs": remaining.as_secs(),
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &room.organizer_ids(),
+        &event,
+    )
+    .await;
+}
+
+/// End a conference the way `call_end` does, then finalize its recordings
+/// and leave a system message in the room explaining why.
+async fn end_conference(state: &AppState, room: &Room, kind: LimitKind) {
+    let Some(rid) = room.id else { return };
+    info!(%rid, ?kind, "Conference limit reached, ending call");
+
+    let participants = state.room_manager.get_participant_user_ids(&rid);
+    if let Err(e) = state.rooms.end_call(rid).await {
+        warn!(%rid, %e, "Conference limits: failed to end call");
+        return;
+    }
+    state.conference_waitlist.clear(&rid);
+    state.conference_lobby.clear(&rid);
+    // Dropping the media room also drops its RTP taps, which closes the
+    // transcription streams.
+    state.room_manager.remove_room(&rid);
+    crate::presence::broadcast_activity(state, rid, &participants).await;
+    crate::conference_chat::discard_at_call_end(state, room).await;
+    crate::conference_polls::close_at_call_end(state, rid).await;
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::CallEnded,
+        None,
+        doc! { "reason": bson::to_bson(&kind).unwrap_or_default() },
+    )
+    .await;
+
+    match state.recording_uploads.complete_for_room(rid).await {
+        Ok(n) if n > 0 => info!(%rid, finalized = n, "Finalized recordings"),
+        Ok(_) => {}
+        Err(e) => warn!(%rid, %e, "Conference limits: failed to finalize recordings"),
+    }
+
+    if !participants.is_empty() {
+        let event = serde_json::json!({
+            "type": "media:room_closed",
+            "data": { "room_id": rid.to_hex() }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &participants,
+            &event,
+        )
+        .await;
+    }
+
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "room:call_ended",
+        "data": {
+            "room_id": rid.to_hex(),
+            "reason": kind,
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &member_ids,
+        &event,
+    )
+    .await;
+
+    let author_id = room.organizer_id.unwrap_or(room.creator_id);
+    let content = format!(
+        "The call was ended automatically because {}.",
+        kind.describe()
+    );
+    match state
+        .messages
+        .create_system(room.tenant_id, rid, author_id, content)
+        .await
+    {
+        Ok(message) => {
+            let names = state
+                .users
+                .find_display_names(&[author_id])
+                .await
+                .unwrap_or_default();
+            let event = serde_json::json!({
+                "type": "message:create",
+                "data": crate::routes::message::to_response(message, &names, None),
+            });
+            crate::ws::dispatcher::broadcast_in_tenant(
+                &state.ws_storage,
+                &state.redis_pubsub,
+                &state.delivery_metrics,
+                room.tenant_id,
+                &member_ids,
+                &event,
+            )
+            .await;
+        }
+        Err(e) => warn!(%rid, %e, "Conference limits: failed to post system message"),
+    }
+}
+
+/// Whether a joiner gets into a conference; see [`admit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Joined,
+    Waitlisted { position: usize },
+    Full,
+}
+
+/// Check a join against the plan's participant cap. Organizers and admitted
+/// waiters always get in; anyone else gets in while there is room left after
+/// the waiters queued before them. Callers skip this for users already in
+/// the call.
+pub async fn admit(state: &AppState, room: &Room, user_id: ObjectId) -> DaoResult<Admission> {
+    let Some(rid) = room.id else {
+        return Ok(Admission::Joined);
+    };
+    let waitlist = &state.conference_waitlist;
+    if room.organizer_ids().contains(&user_id) || waitlist.take_pass(&rid, &user_id) {
+        return Ok(Admission::Joined);
+    }
+
+    let cap = max_participants(state, room).await?;
+    let occupied = occupied(state, rid).await?;
+    let ahead = waitlist.ahead_of(&rid, &user_id);
+    let enabled = room.conference().waitlist_enabled;
+    match conference_limits::join_decision(cap, occupied, ahead, enabled) {
+        JoinDecision::Admit => {
+            if waitlist.remove(&rid, &user_id) {
+                waitlist_updated(state, room).await;
+            }
+            Ok(Admission::Joined)
+        }
+        JoinDecision::Waitlist => {
+            let position = waitlist.enqueue(rid, user_id);
+            let event = serde_json::json!({
+                "type": "conference:waitlisted",
+                "data": { "room_id": rid.to_hex(), "position": position }
+            });
+            crate::ws::dispatcher::send_to_user_with_redis(
+                &state.ws_storage,
+                &state.redis_pubsub,
+                &user_id,
+                &event,
+            )
+            .await;
+            waitlist_updated(state, room).await;
+            Ok(Admission::Waitlisted { position })
+        }
+        JoinDecision::Full => Ok(Admission::Full),
+    }
+}
+
+/// Someone left: hand the freed slots to the head of the waitlist.
+pub async fn fill_open_slots(state: &AppState, room_id: ObjectId) {
+    if state.conference_waitlist.waiting(&room_id).is_empty() {
+        return;
+    }
+    let room = match state.rooms.base.find_by_id(room_id).await {
+        Ok(room) => room,
+        Err(e) => {
+            warn!(%room_id, %e, "Waitlist: room lookup failed");
+            return;
+        }
+    };
+    let slots = match (
+        max_participants(state, &room).await,
+        occupied(state, room_id).await,
+    ) {
+        (Ok(Some(cap)), Ok(occupied)) => (cap as usize).saturating_sub(occupied),
+        (Ok(None), _) => usize::MAX,
+        (Err(e), _) | (_, Err(e)) => {
+            warn!(%room_id, %e, "Waitlist: occupancy lookup failed");
+            return;
+        }
+    };
+    let admitted = state.conference_waitlist.admit_next(&room_id, slots);
+    if !admitted.is_empty() {
+        notify_admitted(state, &room, &admitted).await;
+    }
+}
+
+/// Tell admitted waiters they can join now, with `conference:admitted`.
+pub async fn notify_admitted(state: &AppState, room: &Room, user_ids: &[ObjectId]) {
+    let Some(rid) = room.id else { return };
+    let event = serde_json::json!({
+        "type": "conference:admitted",
+        "data": { "room_id": rid.to_hex() }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        user_ids,
+        &event,
+    )
+    .await;
+    waitlist_updated(state, room).await;
+}
+
+/// Organizers see the queue length change with `conference:waitlist_updated`.
+pub async fn waitlist_updated(state: &AppState, room: &Room) {
+    let Some(rid) = room.id else { return };
+    let event = serde_json::json!({
+        "type": "conference:waitlist_updated",
+        "data": {
+            "room_id": rid.to_hex(),
+            "waiting": state.conference_waitlist.waiting(&rid).len(),
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &room.organizer_ids(),
+        &event,
+    )
+    .await;
+}
+
+/// The participant cap of `room`'s plan and the seats taken, as [`admit`]
+/// counts them.
+pub async fn seats(state: &AppState, room: &Room) -> DaoResult<(Option<u32>, usize)> {
+    let cap = max_participants(state, room).await?;
+    let taken = match room.id {
+        Some(rid) => occupied(state, rid).await?,
+        None => 0,
+    };
+    Ok((cap, taken))
+}
+
+async fn max_participants(state: &AppState, room: &Room) -> DaoResult<Option<u32>> {
+    let tenant = state.tenants.base.find_by_id(room.tenant_id).await?;
+    let settings = &state.settings.conference_limits;
+    Ok(conference_limits::limits_for_plan(settings, &tenant.plan).max_participants)
+}
+
+/// Participants in the call, by REST session or media connection, plus
+/// slots held for admitted waiters.
+async fn occupied(state: &AppState, room_id: ObjectId) -> DaoResult<usize> {
+    let in_call = state.rooms.count_active_participants(room_id).await? as usize;
+    let in_media = state.room_manager.get_participant_user_ids(&room_id).len();
+    Ok(in_call.max(in_media) + state.conference_waitlist.passes(&room_id))
+}
@@ -0,0 +1,151 @@
+//! Conference waiting rooms, for rooms with `lobby_enabled`.
+//!
+//! A joiner who isn't an organizer is held by [`check`] until let in:
+//! they get `media:lobby_waiting` and the organizers `media:join_request`.
+//! [`admit`] sends them `media:admitted`, after which their next
+//! `media:join` creates transports; [`deny`] sends `media:join_denied`.
+//! Organizers follow the lobby size with `media:lobby_updated`.
+
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::Room;
+
+use crate::state::AppState;
+
+/// Whether `user_id` may join `room` now. If not they are put in the lobby,
+/// told so, and the organizers are asked. Callers skip this for users
+/// already in the call.
+pub async fn check(state: &AppState, room: &Room, user_id: ObjectId) -> bool {
+    let Some(rid) = room.id else { return true };
+    let lobby = &state.conference_lobby;
+    if !room.conference().lobby_enabled
+        || room.organizer_ids().contains(&user_id)
+        || lobby.is_admitted(&rid, &user_id)
+    {
+        return true;
+    }
+
+    send(state, &[user_id], "media:lobby_waiting", rid).await;
+    if lobby.request(rid, user_id) {
+        let names = display_names(state, rid, &[user_id]).await;
+        let event = serde_json::json!({
+            "type": "media:join_request",
+            "data": {
+                "room_id": rid.to_hex(),
+                "user_id": user_id.to_hex(),
+                "display_name": names.get(&user_id),
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &room.organizer_ids(),
+            &event,
+        )
+        .await;
+        updated(state, room).await;
+    }
+    false
+}
+
+/// Let a waiting user in. Returns whether they were waiting.
+pub async fn admit(state: &AppState, room: &Room, user_id: ObjectId) -> bool {
+    let Some(rid) = room.id else { return false };
+    if !state.conference_lobby.admit(&rid, &user_id) {
+        return false;
+    }
+    send(state, &[user_id], "media:admitted", rid).await;
+    updated(state, room).await;
+    true
+}
+
+/// Turn a waiting user away. Returns whether they were waiting.
+pub async fn deny(state: &AppState, room: &Room, user_id: ObjectId) -> bool {
+    let Some(rid) = room.id else { return false };
+    if !state.conference_lobby.remove(&rid, &user_id) {
+        return false;
+    }
+    send(state, &[user_id], "media:join_denied", rid).await;
+    updated(state, room).await;
+    true
+}
+
+/// The lobby was turned off: let everyone waiting in.
+pub async fn open(state: &AppState, room: &Room) {
+    let Some(rid) = room.id else { return };
+    let admitted = state.conference_lobby.admit_all(&rid);
+    if !admitted.is_empty() {
+        send(state, &admitted, "media:admitted", rid).await;
+        updated(state, room).await;
+    }
+}
+
+/// A waiting user gave up.
+pub async fn cancel(state: &AppState, room: &Room, user_id: ObjectId) {
+    let Some(rid) = room.id else { return };
+    if state.conference_lobby.remove(&rid, &user_id) {
+        updated(state, room).await;
+    }
+}
+
+async fn send(state: &AppState, user_ids: &[ObjectId], event_type: &str, room_id: ObjectId) {
+    let event = serde_json::json!({
+        "type": event_type,
+        "data": { "room_id": room_id.to_hex() }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        user_ids,
+        &event,
+    )
+    .await;
+}
+
+/// Names of joiners waiting in `room_id`'s lobby: users' display names, and
+/// for conference guests the name they joined with.
+pub async fn display_names(
+    state: &AppState,
+    room_id: ObjectId,
+    user_ids: &[ObjectId],
+) -> HashMap<ObjectId, String> {
+    let mut names = state
+        .users
+        .find_display_names(user_ids)
+        .await
+        .unwrap_or_default();
+    let guests: Vec<ObjectId> = user_ids
+        .iter()
+        .filter(|id| !names.contains_key(id))
+        .copied()
+        .collect();
+    if !guests.is_empty() {
+        names.extend(
+            state
+                .rooms
+                .find_guest_names(room_id, &guests)
+                .await
+                .unwrap_or_default(),
+        );
+    }
+    names
+}
+
+async fn updated(state: &AppState, room: &Room) {
+    let Some(rid) = room.id else { return };
+    let event = serde_json::json!({
+        "type": "media:lobby_updated",
+        "data": {
+            "room_id": rid.to_hex(),
+            "waiting": state.conference_lobby.pending(&rid).len(),
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &room.organizer_ids(),
+        &event,
+    )
+    .await;
+}
@@ -0,0 +1,254 @@
+//! Raised hands and moderation in a call.
+//!
+//! Participants raise and lower their hand with `media:raise_hand` and
+//! `media:lower_hand`; organizers can lower someone else's hand by naming
+//! them with `user_id`. An organizer's `media:mute_request` asks a
+//! participant to mute themselves, while `media:force_mute` pauses their
+//! microphone on the server. Hand and mute state is kept on the
+//! participant's room membership, and each change reaches everyone in the
+//! call as `media:participant_state`.
+
+use bson::{doc, oid::ObjectId};
+use roomler_ai_db::models::ConferenceEventType;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// `media:raise_hand` from a participant.
+pub async fn raise_hand(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    set_hand(state, rid, user_id, user_id, connection_id, true).await
+}
+
+/// `media:lower_hand`, for the sender's own hand or, from an organizer, the
+/// hand of the participant named by `user_id`.
+pub async fn lower_hand(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let target = match data.and_then(|d| d.get("user_id")) {
+        Some(_) => target_user(state, rid, data)?,
+        None => user_id,
+    };
+    if target != user_id {
+        require_organizer(state, rid, user_id, "Only organizers can lower other hands").await?;
+    }
+    set_hand(state, rid, target, user_id, connection_id, false).await
+}
+
+/// `media:mute_request` from an organizer: the participant is asked to mute
+/// and stays in control of their microphone.
+pub async fn mute_request(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let target = target_user(state, rid, data)?;
+    require_organizer(state, rid, user_id, "Only organizers can ask to mute").await?;
+
+    let event = serde_json::json!({
+        "type": "media:mute_request",
+        "data": {
+            "room_id": rid.to_hex(),
+            "requested_by": user_id.to_hex(),
+        }
+    });
+    for conn_id in state.room_manager.user_connection_ids(&rid, &target) {
+        crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+    Ok(())
+}
+
+/// `media:force_mute` from an organizer: every microphone producer of the
+/// participant is paused on the server. Whether they may unmute again
+/// follows the room's `allow_unmute` setting.
+pub async fn force_mute(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let target = target_user(state, rid, data)?;
+    require_organizer(state, rid, user_id, "Only organizers can mute others").await?;
+
+    let paused = state
+        .room_manager
+        .pause_user_audio(&rid, &target)
+        .await
+        .map_err(|e| format!("Failed to mute: {}", e))?;
+    for (producer_conn_id, producer_id, source) in paused {
+        let event = serde_json::json!({
+            "type": "media:producer_paused",
+            "data": {
+                "producer_id": producer_id.to_string(),
+                "user_id": target.to_hex(),
+                "connection_id": producer_conn_id,
+                "kind": "audio",
+                "source": &source,
+            }
+        });
+        for conn_id in state
+            .room_manager
+            .get_other_connection_ids(&rid, &producer_conn_id)
+        {
+            crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+        }
+        crate::conference_events::record(
+            state,
+            rid,
+            ConferenceEventType::MuteToggled,
+            Some(target),
+            doc! {
+                "producer_id": producer_id.to_string(),
+                "connection_id": producer_conn_id,
+                "kind": "audio",
+                "source": source,
+                "muted": true,
+                "forced_by": user_id,
+            },
+        )
+        .await;
+    }
+
+    if let Err(e) = state.rooms.set_participant_muted(rid, target, true).await {
+        warn!(%rid, %e, "Failed to persist forced mute");
+    }
+    broadcast_state(
+        state,
+        rid,
+        connection_id,
+        target,
+        user_id,
+        serde_json::json!({ "is_muted": true }),
+    )
+    .await;
+    Ok(())
+}
+
+async fn set_hand(
+    state: &AppState,
+    rid: ObjectId,
+    target: ObjectId,
+    actor: ObjectId,
+    connection_id: &str,
+    raised: bool,
+) -> Result<(), String> {
+    state
+        .rooms
+        .set_hand_raised(rid, target, raised)
+        .await
+        .map_err(|e| format!("Failed to update hand: {}", e))?;
+    crate::conference_events::record(
+        state,
+        rid,
+        if raised {
+            ConferenceEventType::HandRaised
+        } else {
+            ConferenceEventType::HandLowered
+        },
+        Some(target),
+        doc! { "by": actor },
+    )
+    .await;
+    broadcast_state(
+        state,
+        rid,
+        connection_id,
+        target,
+        actor,
+        serde_json::json!({ "is_hand_raised": raised }),
+    )
+    .await;
+    Ok(())
+}
+
+/// Sends `media:participant_state` with the changed `fields` to everyone in
+/// the call, the sender's connection included.
+async fn broadcast_state(
+    state: &AppState,
+    rid: ObjectId,
+    connection_id: &str,
+    target: ObjectId,
+    actor: ObjectId,
+    fields: Value,
+) {
+    let mut data = serde_json::json!({
+        "room_id": rid.to_hex(),
+        "user_id": target.to_hex(),
+        "changed_by": actor.to_hex(),
+    });
+    if let (Some(data), Value::Object(fields)) = (data.as_object_mut(), fields) {
+        data.extend(fields);
+    }
+    let event = serde_json::json!({ "type": "media:participant_state", "data": data });
+    crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+}
+
+async fn require_organizer(
+    state: &AppState,
+    rid: ObjectId,
+    user_id: ObjectId,
+    denied: &str,
+) -> Result<(), String> {
+    let room = state
+        .rooms
+        .base
+        .find_by_id(rid)
+        .await
+        .map_err(|_| "Room does not exist".to_string())?;
+    if !room.organizer_ids().contains(&user_id) {
+        return Err(denied.to_string());
+    }
+    Ok(())
+}
+
+fn in_call(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<ObjectId, String> {
+    let rid = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+        .ok_or_else(|| "Invalid room_id".to_string())?;
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return Err("Not in this call".to_string());
+    }
+    Ok(rid)
+}
+
+/// The participant named by `user_id`, who must be in the call.
+fn target_user(state: &AppState, rid: ObjectId, data: Option<&Value>) -> Result<ObjectId, String> {
+    let target = data
+        .and_then(|d| d.get("user_id"))
+        .and_then(|u| u.as_str())
+        .and_then(|u| ObjectId::parse_str(u).ok())
+        .ok_or_else(|| "Invalid user_id".to_string())?;
+    if state
+        .room_manager
+        .user_connection_ids(&rid, &target)
+        .is_empty()
+    {
+        return Err("Participant is not in this call".to_string());
+    }
+    Ok(target)
+}
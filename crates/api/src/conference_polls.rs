@@ -0,0 +1,333 @@
+//! Live polls and quizzes in a call.
+//!
+//! An organizer sends `media:poll_start`; everyone in the call gets it as
+//! `media:poll_start`. Participants answer with `media:poll_vote` and get
+//! `media:poll_voted` back, while `media:poll_results` streams the running
+//! tally to the organizers, or to the whole call for polls started with
+//! `share_results`. `media:poll_end` closes the poll and sends everyone
+//! the final tally, with the right answer for quizzes. Polls still open
+//! when the call ends are closed then. Starts and ends go into the call's
+//! event feed, and `GET .../call/poll` lists a room's polls afterwards.
+
+use bson::{DateTime, doc, oid::ObjectId};
+use roomler_ai_db::models::{ConferenceEventType, ConferencePoll, PollStatus};
+use roomler_ai_services::conference_polls;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct PollResponse {
+    pub id: String,
+    pub room_id: String,
+    pub question: String,
+    pub options: Vec<String>,
+    pub is_quiz: bool,
+    /// Only once the poll is closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correct_option: Option<u32>,
+    pub share_results: bool,
+    pub status: PollStatus,
+    /// Votes per option, when the viewer may see them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counts: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_votes: Option<usize>,
+    pub created_by: String,
+    pub created_at: String,
+    pub closed_at: Option<String>,
+}
+
+/// Organizers always see results; others once the poll closes, or live
+/// for `share_results` polls.
+pub fn to_response(poll: ConferencePoll, organizer: bool) -> PollResponse {
+    let closed = poll.status == PollStatus::Closed;
+    let with_results = organizer || closed || poll.share_results;
+    PollResponse {
+        id: poll.id.map(|id| id.to_hex()).unwrap_or_default(),
+        room_id: poll.room_id.to_hex(),
+        is_quiz: poll.is_quiz(),
+        correct_option: poll.correct_option.filter(|_| closed),
+        share_results: poll.share_results,
+        status: poll.status,
+        counts: with_results.then(|| conference_polls::tally(poll.options.len(), &poll.votes)),
+        total_votes: with_results.then_some(poll.votes.len()),
+        question: poll.question,
+        options: poll.options,
+        created_by: poll.created_by.to_hex(),
+        created_at: poll.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        closed_at: poll
+            .closed_at
+            .and_then(|at| at.try_to_rfc3339_string().ok()),
+    }
+}
+
+/// `media:poll_start` from an organizer in the call.
+pub async fn start(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let room = state
+        .rooms
+        .base
+        .find_by_id(rid)
+        .await
+        .map_err(|_| "Room does not exist".to_string())?;
+    if !room.organizer_ids().contains(&user_id) {
+        return Err("Only organizers can start polls".to_string());
+    }
+
+    let question = data
+        .and_then(|d| d.get("question"))
+        .and_then(|q| q.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let options: Vec<String> = data
+        .and_then(|d| d.get("options"))
+        .and_then(|o| o.as_array())
+        .map(|o| {
+            o.iter()
+                .map(|v| v.as_str().unwrap_or_default().trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let correct_option = data
+        .and_then(|d| d.get("correct_option"))
+        .and_then(|c| c.as_u64())
+        .map(|c| c.min(u64::from(u32::MAX)) as u32);
+    let share_results = data
+        .and_then(|d| d.get("share_results"))
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false);
+    conference_polls::validate(&question, &options, correct_option)?;
+
+    let now = DateTime::now();
+    let poll = state
+        .conference_polls
+        .create(&ConferencePoll {
+            id: None,
+            tenant_id: room.tenant_id,
+            room_id: rid,
+            call_started_at: room.actual_start_time,
+            created_by: user_id,
+            question,
+            options,
+            correct_option,
+            share_results,
+            votes: Vec::new(),
+            status: PollStatus::Open,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .map_err(|e| format!("Failed to start poll: {}", e))?;
+
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::PollStarted,
+        Some(user_id),
+        doc! { "poll_id": poll.id, "question": poll.question.clone() },
+    )
+    .await;
+    let event = serde_json::json!({
+        "type": "media:poll_start",
+        "data": to_response(poll, false),
+    });
+    send_to_call(state, rid, connection_id, &event).await;
+    Ok(())
+}
+
+/// `media:poll_vote` from anyone in the call.
+pub async fn vote(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let poll = find_poll(state, rid, data).await?;
+    let poll_id = poll.id.ok_or_else(|| "Poll not found".to_string())?;
+    let option = data
+        .and_then(|d| d.get("option"))
+        .and_then(|o| o.as_u64())
+        .filter(|o| (*o as usize) < poll.options.len())
+        .ok_or_else(|| "Invalid option".to_string())? as u32;
+
+    if !state
+        .conference_polls
+        .vote(poll_id, user_id, option)
+        .await
+        .map_err(|e| format!("Failed to vote: {}", e))?
+    {
+        return Err("The poll is closed".to_string());
+    }
+    let ack = serde_json::json!({
+        "type": "media:poll_voted",
+        "data": {
+            "room_id": rid.to_hex(),
+            "poll_id": poll_id.to_hex(),
+            "option": option,
+        }
+    });
+    crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, &ack).await;
+
+    let Ok(poll) = state.conference_polls.base.find_by_id(poll_id).await else {
+        return Ok(());
+    };
+    let results = serde_json::json!({
+        "type": "media:poll_results",
+        "data": {
+            "room_id": rid.to_hex(),
+            "poll_id": poll_id.to_hex(),
+            "counts": conference_polls::tally(poll.options.len(), &poll.votes),
+            "total_votes": poll.votes.len(),
+        }
+    });
+    if poll.share_results {
+        send_to_call(state, rid, connection_id, &results).await;
+    } else if let Ok(room) = state.rooms.base.find_by_id(rid).await {
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &room.organizer_ids(),
+            &results,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// `media:poll_end` from an organizer in the call.
+pub async fn end(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = in_call(state, connection_id, data)?;
+    let room = state
+        .rooms
+        .base
+        .find_by_id(rid)
+        .await
+        .map_err(|_| "Room does not exist".to_string())?;
+    if !room.organizer_ids().contains(&user_id) {
+        return Err("Only organizers can end polls".to_string());
+    }
+    let poll = find_poll(state, rid, data).await?;
+    let Some(poll) = close(state, poll, Some(user_id)).await else {
+        return Err("The poll is closed".to_string());
+    };
+
+    let event = serde_json::json!({
+        "type": "media:poll_ended",
+        "data": to_response(poll, false),
+    });
+    send_to_call(state, rid, connection_id, &event).await;
+    Ok(())
+}
+
+/// Close the polls a call left open. Called when the call ends.
+pub async fn close_at_call_end(state: &AppState, room_id: ObjectId) {
+    let polls = match state.conference_polls.find_open(room_id).await {
+        Ok(polls) => polls,
+        Err(e) => {
+            warn!(%room_id, %e, "Failed to load open polls at call end");
+            return;
+        }
+    };
+    for poll in polls {
+        close(state, poll, None).await;
+    }
+}
+
+/// Close `poll` and record its final tally. Returns the closed poll, or
+/// `None` if it was closed already.
+async fn close(
+    state: &AppState,
+    poll: ConferencePoll,
+    user_id: Option<ObjectId>,
+) -> Option<ConferencePoll> {
+    let poll_id = poll.id?;
+    match state.conference_polls.close(poll_id).await {
+        Ok(true) => {}
+        Ok(false) => return None,
+        Err(e) => {
+            warn!(%poll_id, %e, "Failed to close poll");
+            return None;
+        }
+    }
+    let poll = state.conference_polls.base.find_by_id(poll_id).await.ok()?;
+    let counts = conference_polls::tally(poll.options.len(), &poll.votes);
+    crate::conference_events::record(
+        state,
+        poll.room_id,
+        ConferenceEventType::PollEnded,
+        user_id,
+        doc! {
+            "poll_id": poll_id,
+            "question": poll.question.clone(),
+            "options": poll.options.clone(),
+            "counts": counts.iter().map(|c| i64::from(*c)).collect::<Vec<_>>(),
+            "correct_option": poll.correct_option.map(i64::from),
+        },
+    )
+    .await;
+    Some(poll)
+}
+
+/// The room this connection is in, checked against `data.room_id`.
+fn in_call(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<ObjectId, String> {
+    let rid = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+        .ok_or_else(|| "Invalid room_id".to_string())?;
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return Err("Not in this call".to_string());
+    }
+    Ok(rid)
+}
+
+async fn find_poll(
+    state: &AppState,
+    room_id: ObjectId,
+    data: Option<&Value>,
+) -> Result<ConferencePoll, String> {
+    let poll_id = data
+        .and_then(|d| d.get("poll_id"))
+        .and_then(|p| p.as_str())
+        .and_then(|p| ObjectId::parse_str(p).ok())
+        .ok_or_else(|| "Invalid poll_id".to_string())?;
+    state
+        .conference_polls
+        .base
+        .find_one(doc! { "_id": poll_id, "room_id": room_id })
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| "Poll not found".to_string())
+}
+
+/// Every connection in the call, `connection_id` included.
+async fn send_to_call(state: &AppState, room_id: ObjectId, connection_id: &str, event: &Value) {
+    crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, event).await;
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&room_id, connection_id)
+    {
+        crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, event).await;
+    }
+}
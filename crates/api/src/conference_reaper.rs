@@ -0,0 +1,103 @@
+//! Ends conferences nobody is left in.
+//!
+//! When a room's last media participant leaves or disconnects, [`schedule`]
+//! waits `mediasoup.empty_room_grace_secs`. If nobody joined in the meantime
+//! the conference is ended, the open sessions left behind by dropped
+//! connections are closed, the mediasoup router is removed, and members get
+//! `media:room_closed` and `room:call_ended`.
+
+use std::time::Duration;
+
+use bson::{doc, oid::ObjectId};
+use roomler_ai_db::models::ConferenceEventType;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Start the grace timer if the room's call just emptied.
+pub fn schedule(state: &AppState, room_id: ObjectId) {
+    if state.room_manager.empty_for(&room_id).is_none() {
+        return;
+    }
+    let state = state.clone();
+    let grace = Duration::from_secs(state.settings.mediasoup.empty_room_grace_secs);
+    tokio::spawn(async move {
+        tokio::time::sleep(grace).await;
+        reap(&state, room_id, grace).await;
+    });
+}
+
+/// End the conference if the room has been empty for at least `grace`.
+/// Someone joining resets the room's empty time, so a timer started by an
+/// earlier departure finds it too recent and leaves it to the later one.
+pub async fn reap(state: &AppState, room_id: ObjectId, grace: Duration) -> bool {
+    if state
+        .room_manager
+        .empty_for(&room_id)
+        .is_none_or(|empty_for| empty_for < grace)
+    {
+        return false;
+    }
+    let room = match state.rooms.base.find_by_id(room_id).await {
+        Ok(room) => room,
+        Err(e) => {
+            warn!(%room_id, %e, "Empty conference: room lookup failed");
+            return false;
+        }
+    };
+    if room.conference_status.as_deref() != Some("in_progress") {
+        // Ended some other way; only the router is left.
+        state.room_manager.remove_room(&room_id);
+        return false;
+    }
+    info!(%room_id, "Conference empty, ending call");
+
+    if let Err(e) = state.rooms.end_call(room_id).await {
+        warn!(%room_id, %e, "Empty conference: failed to end call");
+        return false;
+    }
+    if let Err(e) = state.rooms.close_open_sessions(room_id).await {
+        warn!(%room_id, %e, "Empty conference: failed to close sessions");
+    }
+    state.conference_waitlist.clear(&room_id);
+    state.conference_lobby.clear(&room_id);
+    state.room_manager.remove_room(&room_id);
+    crate::conference_chat::discard_at_call_end(state, &room).await;
+    crate::conference_polls::close_at_call_end(state, room_id).await;
+    crate::conference_events::record(
+        state,
+        room_id,
+        ConferenceEventType::CallEnded,
+        None,
+        doc! { "reason": "empty" },
+    )
+    .await;
+
+    match state.recording_uploads.complete_for_room(room_id).await {
+        Ok(n) if n > 0 => info!(%room_id, finalized = n, "Finalized recordings"),
+        Ok(_) => {}
+        Err(e) => warn!(%room_id, %e, "Empty conference: failed to finalize recordings"),
+    }
+
+    // Nobody is in the call, but members may still be on its page, in the
+    // lobby or on the waitlist.
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .unwrap_or_default();
+    for event_type in ["media:room_closed", "room:call_ended"] {
+        let event = serde_json::json!({
+            "type": event_type,
+            "data": { "room_id": room_id.to_hex(), "reason": "empty" }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &member_ids,
+            &event,
+        )
+        .await;
+    }
+    true
+}
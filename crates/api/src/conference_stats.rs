@@ -0,0 +1,93 @@
+//! Periodic `media:stats` for connections that ask for them.
+//!
+//! A participant debugging a frozen video sends `media:stats_subscribe`
+//! with an optional `interval_ms`; from then on its connection receives its
+//! own transport, producer and consumer statistics as `media:stats` until
+//! it sends `media:stats_unsubscribe` or leaves the call. A reconnect ends
+//! the subscription, so the client subscribes again after `media:rejoin`.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// Interval when the client gives none.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `media:stats_subscribe`. Subscribing again only changes the interval.
+pub async fn subscribe(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = room_id(data)?;
+    let interval = data
+        .and_then(|d| d.get("interval_ms"))
+        .and_then(|i| i.as_u64())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_INTERVAL)
+        .clamp(MIN_INTERVAL, MAX_INTERVAL);
+    let was_subscribed = state
+        .room_manager
+        .subscribe_stats(&rid, connection_id, Some(interval))
+        .ok_or_else(|| "Not in this call".to_string())?;
+    if !was_subscribed {
+        spawn_sender(state.clone(), rid, connection_id.to_string());
+    }
+    Ok(())
+}
+
+/// `media:stats_unsubscribe`.
+pub async fn unsubscribe(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let rid = room_id(data)?;
+    state
+        .room_manager
+        .subscribe_stats(&rid, connection_id, None)
+        .ok_or_else(|| "Not in this call".to_string())?;
+    Ok(())
+}
+
+/// Sends the connection its stats every interval while it stays
+/// subscribed. The first report goes out right away.
+fn spawn_sender(state: AppState, rid: ObjectId, connection_id: String) {
+    tokio::spawn(async move {
+        while let Some(interval) = state.room_manager.stats_interval(&rid, &connection_id) {
+            match state.room_manager.stats(&rid, Some(&connection_id)).await {
+                Ok(mut stats) if !stats.is_empty() => {
+                    let event = serde_json::json!({
+                        "type": "media:stats",
+                        "data": {
+                            "room_id": rid.to_hex(),
+                            "stats": stats.remove(0),
+                        }
+                    });
+                    crate::ws::dispatcher::send_to_connection(
+                        &state.ws_storage,
+                        &connection_id,
+                        &event,
+                    )
+                    .await;
+                }
+                Ok(_) => break,
+                Err(e) => warn!(%rid, %connection_id, %e, "Failed to read call stats"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+fn room_id(data: Option<&Value>) -> Result<ObjectId, String> {
+    data.and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+        .ok_or_else(|| "Invalid room_id".to_string())
+}
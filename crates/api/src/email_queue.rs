@@ -0,0 +1,276 @@
+//! Outgoing email, queued as background tasks so a provider outage only
+//! delays it. [`send`] queues a template, rendered when it is delivered;
+//! [`schedule_mention_digest`] queues one digest per user and tenant,
+//! sent `email.mention_digest_delay_secs` later with the mentions still
+//! unread by then. [`spawn`] runs the sweep sending queued emails as they
+//! come due and retrying failed ones with backoff.
+//!
+//! Emails are only queued when an email provider is configured, and the
+//! recipient's email preferences are checked when an email is queued and
+//! again when it is sent.
+
+use std::time::Duration;
+
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{BackgroundTask, TaskCategory};
+use roomler_ai_services::{
+    dao::base::{DaoError, DaoResult},
+    email::{EmailTemplate, templates::DigestMention},
+    webhooks::backoff,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Task type of a queued template.
+const EMAIL: &str = "email";
+/// Task type of a queued mention digest.
+const MENTION_DIGEST: &str = "mention_digest";
+/// Emails sent per task type and sweep; the rest wait for the next tick.
+const BATCH: u64 = 100;
+/// How long a claimed email is kept from other sweeps, well past a
+/// delivery's timeout; after that it is handed out again.
+const LEASE: Duration = Duration::from_secs(120);
+/// Most mentions listed in a digest.
+const DIGEST_MENTIONS: u64 = 10;
+
+/// The params of an [`EMAIL`] task.
+#[derive(Debug, Serialize, Deserialize)]
+struct QueuedEmail {
+    to: String,
+    template: EmailTemplate,
+}
+
+/// Queue `template` for `to`. `user_id` is who the email is sent for or
+/// on behalf of, e.g. the inviter of an invite. Failing to queue is
+/// logged, not returned: no request fails because of its email.
+pub async fn send(
+    state: &AppState,
+    tenant_id: Option<ObjectId>,
+    user_id: ObjectId,
+    to: &str,
+    template: EmailTemplate,
+) {
+    if state.email.is_none() || !wanted(state, to, &template).await {
+        return;
+    }
+    let params = match serde_json::to_value(QueuedEmail {
+        to: to.to_string(),
+        template,
+    }) {
+        Ok(params) => params,
+        Err(e) => return warn!(%e, "Failed to queue email"),
+    };
+    if let Err(e) = state
+        .tasks
+        .enqueue(
+            tenant_id,
+            user_id,
+            EMAIL,
+            TaskCategory::Email,
+            params,
+            DateTime::now(),
+        )
+        .await
+    {
+        warn!(%e, %user_id, "Failed to queue email");
+    }
+}
+
+/// Queue a digest of `user_id`'s unread mentions in `tenant_id` made
+/// since `since`, when the mention prompting it was, unless one is already
+/// waiting to go out.
+pub async fn schedule_mention_digest(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    since: DateTime,
+) {
+    if state.email.is_none() {
+        return;
+    }
+    if let Err(e) = queue_digest(state, tenant_id, user_id, since).await {
+        warn!(%e, %user_id, "Failed to queue mention digest");
+    }
+}
+
+async fn queue_digest(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    since: DateTime,
+) -> DaoResult<()> {
+    let user = state.users.base.find_by_id(user_id).await?;
+    if !(user.notification_preferences.email && user.email_preferences.mention_digest) {
+        return Ok(());
+    }
+    if state
+        .tasks
+        .find_queued(MENTION_DIGEST, Some(tenant_id), user_id)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+    let delay = Duration::from_secs(state.settings.email.mention_digest_delay_secs);
+    state
+        .tasks
+        .enqueue(
+            Some(tenant_id),
+            user_id,
+            MENTION_DIGEST,
+            TaskCategory::Email,
+            serde_json::json!({ "since": since.timestamp_millis() }),
+            after(delay),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Start the sweep sending queued emails every
+/// `email.queue_interval_secs`.
+pub fn spawn(state: AppState) {
+    if state.email.is_none() {
+        return;
+    }
+    let period = Duration::from_secs(state.settings.email.queue_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match send_due(&state).await {
+                Ok(0) => {}
+                Ok(attempted) => info!(attempted, "Sent queued emails"),
+                Err(e) => warn!(%e, "Email queue sweep failed"),
+            }
+        }
+    });
+}
+
+/// Try each queued email that is due. Returns how many were tried.
+pub async fn send_due(state: &AppState) -> DaoResult<u64> {
+    let mut attempted = 0;
+    for task_type in [EMAIL, MENTION_DIGEST] {
+        let mut claimed = 0;
+        while claimed < BATCH {
+            let Some(task) = state.tasks.claim_due(task_type, after(LEASE)).await? else {
+                break;
+            };
+            claimed += 1;
+            attempt(state, task).await?;
+        }
+        attempted += claimed;
+    }
+    Ok(attempted)
+}
+
+/// Send `task`'s email once and store the outcome: completed when sent or
+/// no longer wanted, failed after the last attempt, otherwise queued again
+/// after the backoff.
+async fn attempt(state: &AppState, task: BackgroundTask) -> DaoResult<()> {
+    let Some(id) = task.id else { return Ok(()) };
+    let store = state.tasks.store();
+    let outcome = match task.task_type.as_str() {
+        EMAIL => match serde_json::from_value::<QueuedEmail>(task.params.clone()) {
+            Ok(queued) => deliver(state, &queued.to, &queued.template).await,
+            Err(e) => return store.fail(id, format!("Invalid queued email: {e}")).await,
+        },
+        _ => match digest(state, &task).await? {
+            Some((to, template)) => deliver(state, &to, &template).await,
+            None => Ok(()),
+        },
+    };
+    let settings = &state.settings.email;
+    match outcome {
+        Ok(()) => store.complete(id, None, None, None).await,
+        Err(error) if task.attempts >= settings.max_attempts => {
+            warn!(task_id = %id, attempts = task.attempts, %error, "Giving up on email");
+            store.fail(id, error).await
+        }
+        Err(error) => {
+            let wait = backoff(settings.retry_base_secs, task.attempts);
+            store.retry_at(id, error, after(wait)).await
+        }
+    }
+}
+
+/// Send `template` to `to` if they still want it.
+async fn deliver(state: &AppState, to: &str, template: &EmailTemplate) -> Result<(), String> {
+    let Some(email) = &state.email else {
+        return Err("Email is not configured".to_string());
+    };
+    if !wanted(state, to, template).await {
+        return Ok(());
+    }
+    email
+        .send_template(to, template)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The recipient and digest of a [`MENTION_DIGEST`] task, or `None` when
+/// every mention it covers has been read.
+async fn digest(
+    state: &AppState,
+    task: &BackgroundTask,
+) -> DaoResult<Option<(String, EmailTemplate)>> {
+    let Some(tenant_id) = task.tenant_id else {
+        return Ok(None);
+    };
+    let user = match state.users.base.find_by_id(task.user_id).await {
+        Ok(user) if user.deleted_at.is_none() => user,
+        Ok(_) | Err(DaoError::NotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let since = task.params["since"]
+        .as_i64()
+        .map_or(task.created_at, DateTime::from_millis);
+    let (mentions, total) = state
+        .notifications
+        .unread_mentions_since(tenant_id, task.user_id, since, DIGEST_MENTIONS)
+        .await?;
+    if mentions.is_empty() {
+        return Ok(None);
+    }
+    let actor_ids: Vec<ObjectId> = mentions.iter().filter_map(|n| n.source.actor_id).collect();
+    let names = state.users.find_display_names(&actor_ids).await?;
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let base_url = &state.settings.oauth.base_url;
+    let mentions: Vec<DigestMention> = mentions
+        .into_iter()
+        .map(|n| DigestMention {
+            author_name: n
+                .source
+                .actor_id
+                .and_then(|id| names.get(&id).cloned())
+                .unwrap_or_default(),
+            context: n.title,
+            preview: n.body,
+            url: format!("{base_url}{}", n.link.unwrap_or_default()),
+        })
+        .collect();
+    let more = total - mentions.len() as u64;
+    Ok(Some((
+        user.email,
+        EmailTemplate::MentionDigest {
+            tenant_name: tenant.name,
+            mentions,
+            more,
+            inbox_url: format!("{base_url}/tenant/{}/mentions", tenant_id.to_hex()),
+        },
+    )))
+}
+
+/// Whether the recipient's preferences allow `template`. Addresses with
+/// no account, like invitees, get every email.
+async fn wanted(state: &AppState, to: &str, template: &EmailTemplate) -> bool {
+    match state.users.find_by_email(to).await {
+        Ok(user) => template.allowed_by(&user.email_preferences, &user.notification_preferences),
+        Err(_) => true,
+    }
+}
+
+fn after(wait: Duration) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + wait.as_millis() as i64)
+}
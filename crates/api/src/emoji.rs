@@ -0,0 +1,108 @@
+//! `:shortcode:` expansion for messages and reactions.
+//!
+//! Message content and reaction emoji are normalized before they are stored
+//! (see [`roomler_ai_services::emoji`]): bundled shortcodes become Unicode,
+//! the tenant's custom emoji keep their lowercased `:name:`, and unknown
+//! shortcodes fail with 422 and the closest names. Custom emoji are only
+//! loaded when a shortcode isn't in the bundled set.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{CustomEmoji, EmojiRef, EmojiType};
+use roomler_ai_services::dao::base::DaoError;
+use roomler_ai_services::emoji::{self, Resolved};
+
+use crate::{error::ApiError, state::AppState};
+
+/// `content` with its shortcodes expanded.
+pub async fn expand_content(
+    state: &AppState,
+    tenant_id: ObjectId,
+    content: &str,
+) -> Result<String, ApiError> {
+    let custom = if emoji::needs_custom(content) {
+        state.custom_emojis.find_for_tenant(tenant_id).await?
+    } else {
+        Vec::new()
+    };
+    emoji::expand(content, &names(&custom)).map_err(|e| ApiError::Validation(e.to_string()))
+}
+
+/// The emoji a reaction stores for `value`: Unicode as sent, or a shortcode
+/// resolved to a bundled or custom emoji.
+pub async fn reaction_emoji(
+    state: &AppState,
+    tenant_id: ObjectId,
+    value: &str,
+) -> Result<EmojiRef, ApiError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(ApiError::BadRequest("Emoji is required".to_string()));
+    }
+    let Some(name) = emoji::as_shortcode(value) else {
+        return Ok(unicode(value.to_string()));
+    };
+    if let Some(bundled) = emoji::lookup(&name) {
+        return Ok(unicode(bundled.to_string()));
+    }
+    let custom = state.custom_emojis.find_for_tenant(tenant_id).await?;
+    match emoji::resolve(&name, &names(&custom)) {
+        Ok(Resolved::Unicode(bundled)) => Ok(unicode(bundled.to_string())),
+        Ok(Resolved::Custom(name)) => Ok(EmojiRef {
+            emoji_type: EmojiType::Custom,
+            custom_emoji_id: custom
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&name))
+                .and_then(|c| c.id),
+            value: format!(":{name}:"),
+        }),
+        Err(e) => Err(ApiError::Validation(e.to_string())),
+    }
+}
+
+/// The emoji a reaction stores for one of the tenant's custom emoji, picked
+/// by id rather than shortcode.
+pub async fn custom_reaction_emoji(
+    state: &AppState,
+    tenant_id: ObjectId,
+    custom_emoji_id: &str,
+) -> Result<EmojiRef, ApiError> {
+    let id = ObjectId::parse_str(custom_emoji_id)
+        .map_err(|_| ApiError::BadRequest("Invalid custom_emoji_id".to_string()))?;
+    let custom = state
+        .custom_emojis
+        .base
+        .find_by_id_in_tenant(tenant_id, id)
+        .await
+        .map_err(|e| match e {
+            DaoError::NotFound => ApiError::Validation("Unknown custom emoji".to_string()),
+            e => e.into(),
+        })?;
+    Ok(EmojiRef {
+        emoji_type: EmojiType::Custom,
+        value: format!(":{}:", custom.name),
+        custom_emoji_id: Some(id),
+    })
+}
+
+/// The stored value a reaction was added under, for removing it by the
+/// same Unicode or shortcode the client sent.
+pub fn reaction_value(value: &str) -> String {
+    match emoji::as_shortcode(value) {
+        Some(name) => emoji::lookup(&name)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!(":{name}:")),
+        None => value.to_string(),
+    }
+}
+
+fn unicode(value: String) -> EmojiRef {
+    EmojiRef {
+        emoji_type: EmojiType::Unicode,
+        value,
+        custom_emoji_id: None,
+    }
+}
+
+fn names(custom: &[CustomEmoji]) -> Vec<String> {
+    custom.iter().map(|c| c.name.clone()).collect()
+}
@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use roomler_ai_services::auth::AuthError;
@@ -16,6 +16,8 @@ pub enum ApiError {
     Conflict(String),
     Internal(String),
     Validation(String),
+    /// A rate-limit budget ran out; retry after this many seconds.
+    TooManyRequests(u64),
 }
 
 impl std::fmt::Display for ApiError {
@@ -28,6 +30,7 @@ impl std::fmt::Display for ApiError {
             ApiError::Conflict(msg) => write!(f, "Conflict: {msg}"),
             ApiError::Internal(msg) => write!(f, "Internal error: {msg}"),
             ApiError::Validation(msg) => write!(f, "Validation: {msg}"),
+            ApiError::TooManyRequests(secs) => write!(f, "Too many requests: retry in {secs}s"),
         }
     }
 }
@@ -51,6 +54,10 @@ impl IntoResponse for ApiError {
         if let ApiError::Internal(msg) = &self {
             tracing::error!(message = %msg, "ApiError::Internal -> 500");
         }
+        let retry_after = match &self {
+            ApiError::TooManyRequests(secs) => Some(*secs),
+            _ => None,
+        };
         let (status, error_type, message) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
@@ -59,6 +66,11 @@ impl IntoResponse for ApiError {
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal", msg),
             ApiError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, "validation", msg),
+            ApiError::TooManyRequests(secs) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_requests",
+                format!("Too many requests, retry in {secs} seconds"),
+            ),
         };
 
         let body = ErrorResponse {
@@ -66,7 +78,13 @@ impl IntoResponse for ApiError {
             message,
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -97,6 +115,26 @@ impl From<AuthError> for ApiError {
     }
 }
 
+impl From<roomler_ai_services::recording_upload::RecordingUploadError> for ApiError {
+    fn from(err: roomler_ai_services::recording_upload::RecordingUploadError) -> Self {
+        match err {
+            roomler_ai_services::recording_upload::RecordingUploadError::Dao(e) => e.into(),
+            other => ApiError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<roomler_ai_services::object_storage::StorageError> for ApiError {
+    fn from(err: roomler_ai_services::object_storage::StorageError) -> Self {
+        use roomler_ai_services::object_storage::StorageError;
+        match err {
+            StorageError::NotFound => ApiError::NotFound("File not found in storage".to_string()),
+            StorageError::TooLarge { .. } => ApiError::Validation(err.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        }
+    }
+}
+
 impl From<roomler_ai_services::oauth::OAuthError> for ApiError {
     fn from(err: roomler_ai_services::oauth::OAuthError) -> Self {
         match err {
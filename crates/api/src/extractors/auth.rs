@@ -1,6 +1,6 @@
 use axum::{
     extract::FromRequestParts,
-    http::{header, request::Parts},
+    http::{HeaderMap, header, request::Parts},
 };
 use bson::oid::ObjectId;
 use roomler_ai_services::auth::Claims;
@@ -27,32 +27,21 @@ where
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let app_state = AppState::from_ref(state);
 
-        // Try Authorization header first
-        let token = parts
-            .headers
-            .get(header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| v.strip_prefix("Bearer "))
-            .map(|s| s.to_string())
-            // Then try cookie
-            .or_else(|| {
-                parts
-                    .headers
-                    .get(header::COOKIE)
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|cookies| {
-                        cookies.split(';').find_map(|cookie| {
-                            let cookie = cookie.trim();
-                            cookie.strip_prefix("access_token=").map(|s| s.to_string())
-                        })
-                    })
-            })
+        let token = access_token(&parts.headers)
             .ok_or_else(|| ApiError::Unauthorized("No token provided".to_string()))?;
 
         let claims = app_state.auth.verify_access_token(&token)?;
 
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| ApiError::Unauthorized("Invalid user ID in token".to_string()))?;
+        // Tokens outlive a deleted account; refuse them.
+        if app_state
+            .deleted_accounts
+            .is_deleted(&app_state.users, user_id)
+            .await?
+        {
+            return Err(ApiError::Unauthorized("Account deleted".to_string()));
+        }
 
         Ok(AuthUser {
             user_id,
@@ -63,6 +52,68 @@ where
     }
 }
 
+/// A conference guest, from the guest token in `Authorization: Bearer`.
+/// Only good for the guest routes of the conference it was issued for.
+#[derive(Debug, Clone)]
+pub struct GuestAuth {
+    pub guest_id: ObjectId,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub display_name: String,
+}
+
+impl<S> FromRequestParts<S> for GuestAuth
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = AppState::from_ref(state);
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::Unauthorized("No token provided".to_string()))?;
+        let claims = app_state.auth.verify_guest_token(token)?;
+
+        let parse = |id: &str| {
+            ObjectId::parse_str(id)
+                .map_err(|_| ApiError::Unauthorized("Invalid guest token".to_string()))
+        };
+        Ok(GuestAuth {
+            guest_id: parse(&claims.sub)?,
+            tenant_id: parse(&claims.tenant_id)?,
+            room_id: parse(&claims.room_id)?,
+            display_name: claims.name,
+        })
+    }
+}
+
+/// The access token of a request: the `Authorization: Bearer` header first,
+/// then the `access_token` cookie.
+pub fn access_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .or_else(|| {
+            headers
+                .get(header::COOKIE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|cookies| {
+                    cookies.split(';').find_map(|cookie| {
+                        let cookie = cookie.trim();
+                        cookie.strip_prefix("access_token=").map(|s| s.to_string())
+                    })
+                })
+        })
+}
+
 /// Optional auth extractor — returns `Option<AuthUser>`, never rejects.
 /// Use for endpoints that behave differently for authenticated vs unauthenticated users.
 pub struct OptionalAuthUser(pub Option<AuthUser>);
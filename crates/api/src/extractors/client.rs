@@ -0,0 +1,132 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{Extensions, HeaderMap, header, request::Parts},
+};
+
+use super::auth::FromRef;
+use crate::state::AppState;
+
+/// Where a request came from, for audit records.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = AppState::from_ref(state);
+        Ok(ClientInfo {
+            ip: client_ip(
+                &parts.headers,
+                &parts.extensions,
+                &app_state.trusted_proxies,
+            ),
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string()),
+        })
+    }
+}
+
+/// The reverse proxies of `app.trusted_proxies`.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    /// Network address and prefix length.
+    ranges: Vec<(IpAddr, u8)>,
+}
+
+impl TrustedProxies {
+    /// Parse a comma-separated list of addresses and CIDR ranges.
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut ranges = Vec::new();
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let invalid = || format!("Invalid trusted proxy {entry}");
+            let (ip, prefix) = match entry.split_once('/') {
+                Some((ip, prefix)) => (
+                    ip.parse::<IpAddr>().map_err(|_| invalid())?,
+                    Some(prefix.parse::<u8>().map_err(|_| invalid())?),
+                ),
+                None => (entry.parse::<IpAddr>().map_err(|_| invalid())?, None),
+            };
+            let ip = ip.to_canonical();
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            let prefix = prefix.unwrap_or(max);
+            if prefix > max {
+                return Err(invalid());
+            }
+            ranges.push((ip, prefix));
+        }
+        Ok(Self { ranges })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.ranges
+            .iter()
+            .any(|&(network, prefix)| match (network, ip) {
+                (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                    let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+                    u32::from(network) & mask == u32::from(ip) & mask
+                }
+                (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                    let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+                    u128::from(network) & mask == u128::from(ip) & mask
+                }
+                _ => false,
+            })
+    }
+}
+
+/// The client's IP. Forwarding headers are only believed from a trusted
+/// proxy: the right-most `X-Forwarded-For` entry that isn't one, as every
+/// entry left of it could have been sent by the client, or `X-Real-IP`
+/// when there is no `X-Forwarded-For`. Otherwise the peer address.
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted: &TrustedProxies,
+) -> Option<IpAddr> {
+    let mut hop = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical())?;
+    if !trusted.contains(hop) {
+        return Some(hop);
+    }
+
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect();
+    if forwarded.is_empty() {
+        return headers
+            .get("x-real-ip")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|ip| ip.trim().parse().ok())
+            .or(Some(hop));
+    }
+    for entry in forwarded.iter().rev() {
+        // A garbled entry can't be traced further; the hop that added it
+        // is the best we know.
+        let Ok(ip) = entry.trim().parse::<IpAddr>() else {
+            return Some(hop);
+        };
+        hop = ip.to_canonical();
+        if !trusted.contains(hop) {
+            return Some(hop);
+        }
+    }
+    Some(hop)
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use bson::oid::ObjectId;
+use roomler_ai_services::feature_flags::FlagSet;
+
+use super::auth::{FromRef, OptionalAuthUser};
+use crate::{error::ApiError, state::AppState};
+
+/// Feature flags evaluated for the caller. Picks up `{tenant_id}` from the
+/// route when present and the JWT user when authenticated, so the same
+/// extractor works on tenant-scoped, user-scoped and public routes.
+#[derive(Debug, Clone)]
+pub struct Flags(pub FlagSet);
+
+impl Flags {
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.0.is_enabled(key)
+    }
+}
+
+impl<S> FromRequestParts<S> for Flags
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = AppState::from_ref(state);
+
+        let tenant_id = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .ok()
+            .and_then(|Path(params)| params.get("tenant_id").cloned())
+            .and_then(|tid| ObjectId::parse_str(tid).ok());
+
+        let OptionalAuthUser(auth) = match OptionalAuthUser::from_request_parts(parts, state).await
+        {
+            Ok(a) => a,
+            Err(never) => match never {},
+        };
+        let user_id = auth.map(|a| a.user_id);
+
+        let set = app_state.feature_flags.evaluate(tenant_id, user_id).await?;
+        Ok(Flags(set))
+    }
+}
@@ -1,2 +1,6 @@
 pub mod auth;
+pub mod client;
+pub mod feature_flags;
+pub mod permission;
+pub mod service;
 pub mod tenant;
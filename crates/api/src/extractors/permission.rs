@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use bson::oid::ObjectId;
+use roomler_ai_db::models::role::permissions;
+
+use super::auth::{AuthUser, FromRef};
+use crate::{error::ApiError, state::AppState};
+
+/// The authenticated caller, checked to hold the permission bits `P` in the
+/// route's `{tenant_id}`. Rejects with 401 without a valid token, 400 for a
+/// malformed tenant id and 403 for non-members or missing permissions.
+#[derive(Debug, Clone)]
+pub struct RequirePermission<const P: u64> {
+    pub auth: AuthUser,
+    pub tenant_id: ObjectId,
+    /// Everything the caller's roles grant in the tenant, not just `P`.
+    pub permissions: u64,
+}
+
+impl<S, const P: u64> FromRequestParts<S> for RequirePermission<P>
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth = AuthUser::from_request_parts(parts, state).await?;
+        let app_state = AppState::from_ref(state);
+
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::Internal("Route has no tenant_id".to_string()))?;
+        let tenant_id = params
+            .get("tenant_id")
+            .ok_or_else(|| ApiError::Internal("Route has no tenant_id".to_string()))?;
+        let tenant_id = ObjectId::parse_str(tenant_id)
+            .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+        let perms = app_state
+            .tenants
+            .get_member_permissions(tenant_id, auth.user_id)
+            .await?;
+        if !permissions::has(perms, P) {
+            return Err(ApiError::Forbidden(format!(
+                "Missing {} permission",
+                permissions::names(P & !perms).join(", ")
+            )));
+        }
+
+        Ok(RequirePermission {
+            auth,
+            tenant_id,
+            permissions: perms,
+        })
+    }
+}
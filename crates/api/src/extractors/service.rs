@@ -0,0 +1,41 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+use roomler_ai_services::auth::internal::ServiceClaims;
+
+use crate::{error::ApiError, extractors::auth::FromRef, state::AppState};
+
+/// Extracts the internal service calling the API from a service token in
+/// the `Authorization: Bearer` header. User access tokens and cookies are
+/// never accepted.
+#[derive(Debug, Clone)]
+pub struct ServiceCaller {
+    pub service: String,
+    pub claims: ServiceClaims,
+}
+
+impl<S> FromRequestParts<S> for ServiceCaller
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = AppState::from_ref(state);
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::Unauthorized("No service token provided".to_string()))?;
+
+        let claims = app_state.internal_auth.verify(token)?;
+        Ok(ServiceCaller {
+            service: claims.sub.clone(),
+            claims,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+//! Reminders for call follow-ups. A periodic sweep posts a system message
+//! into the room of each open follow-up that has come due, once; see
+//! [`crate::routes::follow_up`].
+
+use std::time::Duration;
+
+use bson::DateTime;
+use roomler_ai_db::models::FollowUp;
+use roomler_ai_services::dao::base::DaoResult;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Reminders posted per sweep; the rest wait for the next tick.
+const BATCH: i64 = 200;
+
+/// Spawn the reminder sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.follow_up.reminder_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match send_due_reminders(&state).await {
+                Ok(0) => {}
+                Ok(sent) => info!(sent, "Posted follow-up reminders"),
+                Err(e) => warn!(%e, "Follow-up reminder sweep failed"),
+            }
+        }
+    });
+}
+
+/// Post a reminder for each open follow-up due by now. Returns how many
+/// were posted.
+pub async fn send_due_reminders(state: &AppState) -> DaoResult<u64> {
+    let mut sent = 0;
+    for follow_up in state.follow_ups.find_due(DateTime::now(), BATCH).await? {
+        let Some(id) = follow_up.id else { continue };
+        // Another instance may have claimed it since the query.
+        if state.follow_ups.mark_reminded(id).await? {
+            remind(state, &follow_up).await;
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+async fn remind(state: &AppState, follow_up: &FollowUp) {
+    let rid = follow_up.room_id;
+    let assignee = follow_up.assignee_id;
+    let names = state
+        .users
+        .find_display_names(&[assignee, follow_up.created_by])
+        .await
+        .unwrap_or_default();
+    let assignee_name = names.get(&assignee).map_or("someone", String::as_str);
+    let content = format!(
+        "Reminder: \"{}\", assigned to {}, is due.",
+        follow_up.title, assignee_name
+    );
+
+    let (tenant_id, author_id) = (follow_up.tenant_id, follow_up.created_by);
+    let message =
+        match crate::profanity::post(state, tenant_id, rid, author_id, content, |content| {
+            state
+                .messages
+                .create_system(tenant_id, rid, author_id, content)
+        })
+        .await
+        {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(%rid, %e, "Follow-up reminder: failed to post system message");
+                return;
+            }
+        };
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": crate::routes::message::to_response(message, &names, None),
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        follow_up.tenant_id,
+        &member_ids,
+        &event,
+    )
+    .await;
+}
@@ -0,0 +1,219 @@
+//! Health endpoints for load balancers and Kubernetes probes.
+//! `/health/live` only says the process serves requests; `/health/ready`
+//! checks each dependency and reports its status and latency, failing
+//! while a required one is down. `/health` keeps its older, index-only
+//! answer.
+
+use std::{future::Future, time::Duration};
+
+use axum::{Json, extract::State, http::StatusCode};
+use bson::doc;
+use roomler_ai_db::{indexes::IndexHealth, models::StorageProvider};
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::state::AppState;
+
+/// How long a dependency gets to answer before it counts as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ok`, `degraded` when only an optional dependency is down, or
+    /// `unavailable`.
+    pub status: &'static str,
+    pub version: &'static str,
+    pub indexes: IndexHealth,
+    pub checks: Checks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Checks {
+    pub mongo: Check,
+    pub mediasoup: Check,
+    pub s3: Check,
+    /// Optional: calls and chat work without it.
+    pub transcription: Check,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub status: CheckStatus,
+    /// `None` for disabled dependencies and in-process checks.
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    /// Not configured on this instance.
+    Disabled,
+}
+
+impl Check {
+    fn disabled() -> Self {
+        Self {
+            status: CheckStatus::Disabled,
+            latency_ms: None,
+            detail: None,
+            error: None,
+        }
+    }
+
+    fn is_down(&self) -> bool {
+        self.status == CheckStatus::Failed
+    }
+}
+
+/// Time `probe`, giving up after [`CHECK_TIMEOUT`]. The probe returns a
+/// detail on success.
+async fn timed<F>(probe: F) -> Check
+where
+    F: Future<Output = Result<Option<String>, String>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, probe)
+        .await
+        .unwrap_or_else(|_| {
+            Err(format!(
+                "No answer within {} seconds",
+                CHECK_TIMEOUT.as_secs()
+            ))
+        });
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+    match result {
+        Ok(detail) => Check {
+            status: CheckStatus::Ok,
+            latency_ms,
+            detail,
+            error: None,
+        },
+        Err(error) => Check {
+            status: CheckStatus::Failed,
+            latency_ms,
+            detail: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// GET /health — 503 while a required index has failed to build; builds
+/// still in progress and failed optional indexes leave the instance
+/// healthy.
+pub async fn check(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let indexes = state.indexes.health();
+    let (code, status) = if indexes.is_healthy() {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    (
+        code,
+        Json(serde_json::json!({
+            "status": status,
+            "version": env!("CARGO_PKG_VERSION"),
+            "indexes": indexes,
+        })),
+    )
+}
+
+/// GET /health/live — answers as long as the server handles requests.
+pub async fn live() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+/// GET /health/ready — 503 while Mongo, every mediasoup worker, S3 (when
+/// it stores new files) or a required index is down. A transcription
+/// backend that is down or hasn't loaded its model only degrades it.
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let (mongo, s3, transcription) = tokio::join!(
+        timed(async {
+            state
+                .db
+                .run_command(doc! { "ping": 1 })
+                .await
+                .map(|_| None)
+                .map_err(|e| e.to_string())
+        }),
+        s3(&state),
+        transcription(&state),
+    );
+    let (alive, total) = state.room_manager.worker_health();
+    let mediasoup = Check {
+        status: if alive > 0 {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Failed
+        },
+        latency_ms: None,
+        detail: Some(format!("{alive} of {total} workers alive")),
+        error: (alive == 0).then(|| "No mediasoup worker is alive".to_string()),
+    };
+    let checks = Checks {
+        mongo,
+        mediasoup,
+        s3,
+        transcription,
+    };
+    let indexes = state.indexes.health();
+
+    let (code, status) = if checks.mongo.is_down()
+        || checks.mediasoup.is_down()
+        || checks.s3.is_down()
+        || !indexes.is_healthy()
+    {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else if checks.transcription.is_down() {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "ok")
+    };
+    (
+        code,
+        Json(ReadinessResponse {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            indexes,
+            checks,
+        }),
+    )
+}
+
+async fn s3(state: &AppState) -> Check {
+    if state.object_store.default_provider() != StorageProvider::S3 {
+        return Check::disabled();
+    }
+    timed(async {
+        state
+            .object_store
+            .ping_s3()
+            .await
+            .map(|()| None)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+async fn transcription(state: &AppState) -> Check {
+    if !state.transcription.is_available() {
+        return Check::disabled();
+    }
+    timed(async {
+        let status = state.transcription.status().await;
+        match (status.model_loaded, status.error) {
+            (Some(true), _) => Ok(Some(format!("Model {} loaded", status.model))),
+            (Some(false), _) => Err(format!("Model {} isn't loaded", status.model)),
+            (None, error) => Err(error.unwrap_or_else(|| "Unreachable".to_string())),
+        }
+    })
+    .await
+}
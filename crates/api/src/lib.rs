@@ -1,19 +1,47 @@
+pub mod account_deletion;
+pub mod analytics_reports;
+pub mod api_quota;
+pub mod audit;
+pub mod billing_events;
+pub mod call_controls;
+pub mod channel_digests;
+pub mod conference_chat;
+pub mod conference_events;
+pub mod conference_limits;
+pub mod conference_lobby;
+pub mod conference_moderation;
+pub mod conference_polls;
+pub mod conference_reaper;
+pub mod conference_stats;
+pub mod email_queue;
+pub mod emoji;
 pub mod error;
 pub mod extractors;
+pub mod follow_ups;
+pub mod health;
+pub mod message_archive;
+pub mod message_retention;
 pub mod middleware;
+pub mod presence;
+pub mod profanity;
+pub mod reaction_rules;
+pub mod relay_usage;
 pub mod routes;
+pub mod sessions;
+pub mod shared_drafts;
 pub mod state;
+pub mod transcripts;
+pub mod webhooks;
 pub mod ws;
 
 use axum::{
     Router,
     extract::DefaultBodyLimit,
+    middleware::from_fn_with_state,
     routing::{delete, get, post, put},
 };
+use middleware::{rate_limit, sandbox};
 use state::AppState;
-use tower_governor::{
-    GovernorLayer, governor::GovernorConfigBuilder, key_extractor::SmartIpKeyExtractor,
-};
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
@@ -38,26 +66,39 @@ fn build_cors_layer(origins: &[String]) -> CorsLayer {
 pub fn build_router(state: AppState) -> Router {
     let cors = build_cors_layer(&state.settings.app.cors_origins);
 
-    // Rate limiting: 60 requests per minute per IP (1 token/sec, burst up to 60)
-    let governor_conf = GovernorConfigBuilder::default()
-        .per_second(1)
-        .burst_size(60)
-        .key_extractor(SmartIpKeyExtractor)
-        .finish()
-        .unwrap();
-    let governor_layer = GovernorLayer {
-        config: governor_conf.into(),
-    };
+    // Credential routes draw from the tighter login budget
+    let login_routes = Router::new()
+        .route("/register", post(routes::auth::register))
+        .route("/login", post(routes::auth::login))
+        .route("/activate", post(routes::auth::activate))
+        .route("/password/forgot", post(routes::auth::forgot_password))
+        .route("/password/reset", post(routes::auth::reset_password))
+        .route("/email/confirm", post(routes::auth::confirm_email))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit::login));
 
     // Auth routes (no tenant prefix)
     let auth_routes = Router::new()
-        .route("/register", post(routes::auth::register))
-        .route("/login", post(routes::auth::login))
+        .merge(login_routes)
         .route("/logout", post(routes::auth::logout))
         .route("/refresh", post(routes::auth::refresh))
-        .route("/activate", post(routes::auth::activate))
         .route("/me", get(routes::auth::me))
-        .route("/me", put(routes::auth::me));
+        .route("/me", put(routes::auth::me))
+        .route("/me", delete(routes::auth::delete_me))
+        .route("/me/sessions", get(routes::auth::sessions))
+        .route(
+            "/me/email",
+            get(routes::auth::get_email)
+                .put(routes::auth::change_email)
+                .delete(routes::auth::cancel_email_change),
+        )
+        .route(
+            "/me/preferences",
+            get(routes::auth::get_preferences).put(routes::auth::update_preferences),
+        )
+        .route(
+            "/me/calendar-feed",
+            post(routes::calendar::create_feed).delete(routes::calendar::revoke_feed),
+        );
 
     // Tenant routes
     let tenant_routes = Router::new()
@@ -66,10 +107,13 @@ pub fn build_router(state: AppState) -> Router {
         .route("/{tenant_id}", get(routes::tenant::get));
 
     // Member routes (under tenant)
-    let member_routes = Router::new().route(
-        "/",
-        get(routes::user::list_members).post(routes::invite::add_member),
-    );
+    let member_routes = Router::new()
+        .route(
+            "/",
+            get(routes::user::list_members).post(routes::invite::add_member),
+        )
+        .route("/import", post(routes::invite::import_members))
+        .route("/{user_id}", delete(routes::invite::remove_member));
 
     // Room routes (under tenant) — replaces channel + conference
     let room_routes = Router::new()
@@ -81,7 +125,12 @@ pub fn build_router(state: AppState) -> Router {
         .route("/{room_id}", delete(routes::room::delete))
         .route("/{room_id}/join", post(routes::room::join))
         .route("/{room_id}/leave", post(routes::room::leave))
+        .route("/{room_id}/read", post(routes::message::mark_room_read))
         .route("/{room_id}/member", get(routes::room::members))
+        .route(
+            "/{room_id}/member/{user_id}/role",
+            put(routes::room::grant_role).delete(routes::room::revoke_role),
+        )
         // Call endpoints
         .route("/{room_id}/call/start", post(routes::room::call_start))
         .route("/{room_id}/call/join", post(routes::room::call_join))
@@ -91,11 +140,84 @@ pub fn build_router(state: AppState) -> Router {
             "/{room_id}/call/participant",
             get(routes::room::participants),
         )
+        .route("/{room_id}/call/waitlist", get(routes::room::call_waitlist))
+        .route(
+            "/{room_id}/call/waitlist/{user_id}/admit",
+            post(routes::room::admit_waiter),
+        )
+        .route(
+            "/{room_id}/conference-chat-retention",
+            get(routes::conference_chat::get_room)
+                .put(routes::conference_chat::set_room)
+                .delete(routes::conference_chat::clear_room),
+        )
+        .route(
+            "/{room_id}/message-retention",
+            get(routes::message_retention::get_room)
+                .put(routes::message_retention::set_room)
+                .delete(routes::message_retention::clear_room),
+        )
+        .route(
+            "/{room_id}/digest",
+            get(routes::channel_digest::get)
+                .put(routes::channel_digest::set)
+                .delete(routes::channel_digest::clear),
+        )
+        .route(
+            "/{room_id}/public",
+            get(routes::public_channel::get)
+                .put(routes::public_channel::share)
+                .delete(routes::public_channel::unshare),
+        )
+        .route("/{room_id}/call/lobby", get(routes::room::call_lobby))
+        .route(
+            "/{room_id}/call/lobby/{user_id}/admit",
+            post(routes::room::admit_from_lobby),
+        )
+        .route(
+            "/{room_id}/call/lobby/{user_id}/deny",
+            post(routes::room::deny_from_lobby),
+        )
+        .route("/{room_id}/call/event", get(routes::room::call_events))
+        .route("/{room_id}/call/poll", get(routes::room::call_polls))
+        .route(
+            "/{room_id}/call/transcript",
+            get(routes::room::call_transcript),
+        )
         .route(
             "/{room_id}/call/message",
             get(routes::room::call_messages).post(routes::room::create_call_message),
+        )
+        .route(
+            "/{room_id}/call/message/voice",
+            post(routes::room::create_call_voice_note),
+        )
+        .route(
+            "/{room_id}/call/settings",
+            get(routes::room::call_settings).put(routes::room::update_call_settings),
+        )
+        .route(
+            "/{room_id}/call/guest-link",
+            post(routes::guest::create_link).delete(routes::guest::revoke_link),
+        )
+        .route(
+            "/{room_id}/call/message/keep",
+            put(routes::room::keep_call_messages),
+        )
+        .route(
+            "/{room_id}/call/follow-up",
+            get(routes::follow_up::list_room).post(routes::follow_up::create),
+        )
+        .route(
+            "/{room_id}/integrations",
+            get(routes::integration::room_integrations),
         );
 
+    // Direct message routes (under tenant); messages use the room routes
+    let dm_routes = Router::new()
+        .route("/", get(routes::dm::list))
+        .route("/", post(routes::dm::open));
+
     // Message routes (under tenant/room)
     let message_routes = Router::new()
         .route("/", get(routes::message::list))
@@ -105,6 +227,15 @@ pub fn build_router(state: AppState) -> Router {
         .route("/{message_id}", delete(routes::message::delete))
         .route("/{message_id}/pin", put(routes::message::toggle_pin))
         .route("/{message_id}/thread", get(routes::message::thread_replies))
+        .route(
+            "/{message_id}/thread/summarize",
+            post(routes::message::summarize_thread),
+        )
+        .route(
+            "/{message_id}/thread/subscription",
+            put(routes::thread::subscribe).delete(routes::thread::unsubscribe),
+        )
+        .route("/{message_id}/thread/read", post(routes::thread::mark_read))
         .route("/{message_id}/reaction", post(routes::reaction::add))
         .route(
             "/{message_id}/reaction/{emoji}",
@@ -113,11 +244,63 @@ pub fn build_router(state: AppState) -> Router {
         .route("/read", post(routes::message::mark_read))
         .route("/unread-count", get(routes::message::unread_count));
 
+    // Shared drafts (under room); editing is over the WebSocket
+    let shared_draft_routes = Router::new()
+        .route(
+            "/",
+            get(routes::shared_draft::list).post(routes::shared_draft::create),
+        )
+        .route(
+            "/{draft_id}",
+            get(routes::shared_draft::get).delete(routes::shared_draft::discard),
+        )
+        .route("/{draft_id}/publish", post(routes::shared_draft::publish));
+
     // Recording routes (under room)
     let recording_routes = Router::new()
         .route("/", get(routes::recording::list))
         .route("/", post(routes::recording::create))
-        .route("/{recording_id}", delete(routes::recording::delete));
+        .route("/{recording_id}", delete(routes::recording::delete))
+        .route(
+            "/{recording_id}/part/{part_number}",
+            put(routes::recording::upload_part),
+        )
+        .route(
+            "/{recording_id}/complete",
+            post(routes::recording::complete),
+        )
+        .route("/{recording_id}/download", get(routes::recording::download))
+        .route("/{recording_id}/stream", get(routes::recording::stream))
+        .route(
+            "/{recording_id}/playback-url",
+            get(routes::recording::playback_url),
+        )
+        .route(
+            "/{recording_id}/clip",
+            get(routes::recording::list_clips).post(routes::recording::create_clip),
+        )
+        .route(
+            "/{recording_id}/access",
+            get(routes::recording::get_access).put(routes::recording::update_access),
+        )
+        .route(
+            "/{recording_id}/share",
+            post(routes::recording::create_share_link),
+        )
+        .route(
+            "/{recording_id}/share/{token}",
+            delete(routes::recording::revoke_share_link),
+        )
+        .layer(DefaultBodyLimit::max(100 * 1024 * 1024));
+
+    // Public recording share links (no auth)
+    let shared_recording_routes = Router::new()
+        .route("/{token}", get(routes::recording::shared_info))
+        .route("/{token}/stream", get(routes::recording::shared_stream));
+    let signed_recording_routes = Router::new().route(
+        "/{recording_id}/stream",
+        get(routes::recording::signed_stream),
+    );
 
     // Room file routes (100 MB body limit for audio uploads)
     let room_file_routes = Router::new()
@@ -129,6 +312,8 @@ pub fn build_router(state: AppState) -> Router {
     let file_by_id_routes = Router::new()
         .route("/", get(routes::file::list_tenant_files))
         .route("/upload", post(routes::file::upload))
+        .route("/presign", post(routes::file::presign))
+        .route("/confirm", post(routes::file::confirm))
         .route("/{file_id}", get(routes::file::get))
         .route("/{file_id}/download", get(routes::file::download))
         .route("/{file_id}", delete(routes::file::delete))
@@ -153,17 +338,44 @@ pub fn build_router(state: AppState) -> Router {
         .route(
             "/conversation-pdf",
             post(routes::integration::export_conversation_pdf),
-        );
+        )
+        .route("/analytics", post(routes::analytics::export));
 
     // Public invite routes (no auth required for info, auth required for accept)
     let public_invite_routes = Router::new()
         .route("/{code}", get(routes::invite::get_invite_info))
-        .route("/{code}/accept", post(routes::invite::accept_invite));
+        .route("/{code}/accept", post(routes::invite::accept_invite))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit::invite));
+
+    // Public channel views (no auth required)
+    let public_channel_routes = Router::new()
+        .route("/{token}", get(routes::public_channel::info))
+        .route("/{token}/message", get(routes::public_channel::messages))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit::public));
+
+    // Public conference join info (no auth required)
+    let join_routes = Router::new()
+        .route("/{meeting_code}/info", get(routes::join::info))
+        .route("/{meeting_code}/prejoin", get(routes::join::prejoin))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit::invite));
+
+    // Conference guests: joining is public, chat takes the guest token
+    let guest_routes = Router::new()
+        .route(
+            "/conference/{meeting_code}/join",
+            post(routes::guest::join)
+                .route_layer(from_fn_with_state(state.clone(), rate_limit::invite)),
+        )
+        .route(
+            "/conference/{meeting_code}/message",
+            get(routes::guest::messages).post(routes::guest::create_message),
+        );
 
     // Role routes (under tenant)
     let role_routes = Router::new()
         .route("/", get(routes::role::list))
         .route("/", post(routes::role::create))
+        .route("/permissions", get(routes::role::list_permissions))
         .route("/{role_id}", put(routes::role::update))
         .route("/{role_id}", delete(routes::role::delete))
         .route("/{role_id}/assign/{user_id}", post(routes::role::assign))
@@ -179,10 +391,28 @@ pub fn build_router(state: AppState) -> Router {
         .route("/batch", post(routes::invite::batch_create_invite))
         .route("/{invite_id}", delete(routes::invite::revoke_invite));
 
+    // Feature flag routes (under tenant)
+    let feature_flag_routes = Router::new()
+        .route("/", get(routes::feature_flag::list))
+        .route("/override", get(routes::feature_flag::list_overrides))
+        .route(
+            "/{key}",
+            put(routes::feature_flag::set_override).delete(routes::feature_flag::clear_override),
+        );
+
+    // Onboarding checklist routes (under tenant)
+    let onboarding_routes = Router::new()
+        .route("/", get(routes::onboarding::get))
+        .route("/dismiss", post(routes::onboarding::dismiss));
+
     // OAuth routes (no auth required)
     let oauth_routes = Router::new()
         .route("/{provider}", get(routes::oauth::oauth_redirect))
-        .route("/callback/{provider}", get(routes::oauth::oauth_callback));
+        .route("/callback/{provider}", get(routes::oauth::oauth_callback))
+        .route("/sso", get(routes::sso::discover))
+        .route("/sso/{tenant_slug}", get(routes::sso::login))
+        .route("/sso/{tenant_slug}/callback", get(routes::sso::callback))
+        .route("/sso/{tenant_slug}/link", post(routes::sso::link));
 
     // Stripe routes
     let stripe_routes = Router::new()
@@ -266,6 +496,9 @@ pub fn build_router(state: AppState) -> Router {
         get(routes::remote_control::turn_credentials),
     );
 
+    // Internal service callbacks (service token, no user JWT)
+    let internal_routes = Router::new().route("/whoami", get(routes::internal::whoami));
+
     // Compose API
     let api = Router::new()
         .nest("/auth", auth_routes)
@@ -273,18 +506,235 @@ pub fn build_router(state: AppState) -> Router {
         .nest("/oauth", oauth_routes)
         .nest("/stripe", stripe_routes)
         .nest("/invite", public_invite_routes)
+        .nest("/join", join_routes)
+        .nest("/guest", guest_routes)
+        .route(
+            "/conference/code/{meeting_code}",
+            get(routes::join::resolve)
+                .route_layer(from_fn_with_state(state.clone(), rate_limit::invite)),
+        )
+        .route(
+            "/calendar/{token}",
+            get(routes::calendar::feed)
+                .route_layer(from_fn_with_state(state.clone(), rate_limit::public)),
+        )
+        .nest("/public/channel", public_channel_routes)
+        .nest("/recording/shared", shared_recording_routes)
+        .nest("/recording/signed", signed_recording_routes)
+        .route("/asset/{tenant_id}/{checksum}", get(routes::asset::serve))
         .nest("/giphy", giphy_routes)
         .nest("/push", push_routes)
         .nest("/notification", notification_routes)
         .nest("/agent", public_agent_routes)
         .nest("/turn", turn_routes)
+        .nest("/internal", internal_routes)
         .nest("/tenant", tenant_routes)
         .nest("/tenant/{tenant_id}/member", member_routes)
         .nest("/tenant/{tenant_id}/role", role_routes)
         .nest("/tenant/{tenant_id}/invite", tenant_invite_routes)
         .nest("/tenant/{tenant_id}/search", search_routes)
+        .route(
+            "/tenant/{tenant_id}/quick-switch",
+            get(routes::quick_switch::search),
+        )
+        .route(
+            "/tenant/{tenant_id}/thread/subscribed",
+            get(routes::thread::subscribed),
+        )
+        .route("/tenant/{tenant_id}/mentions", get(routes::mention::list))
+        .nest("/tenant/{tenant_id}/feature-flag", feature_flag_routes)
+        .nest("/tenant/{tenant_id}/onboarding", onboarding_routes)
+        .route("/tenant/{tenant_id}/audit", get(routes::audit::list))
+        .route(
+            "/tenant/{tenant_id}/analytics/report",
+            get(routes::analytics::get_report).put(routes::analytics::set_report),
+        )
+        .route(
+            "/tenant/{tenant_id}/analytics/moderation",
+            get(routes::analytics::moderation),
+        )
+        .route(
+            "/tenant/{tenant_id}/analytics/relay",
+            get(routes::analytics::relay),
+        )
+        .route(
+            "/tenant/{tenant_id}/profanity-filter",
+            get(routes::profanity_filter::get).put(routes::profanity_filter::set),
+        )
+        .route(
+            "/tenant/{tenant_id}/sso",
+            get(routes::sso::get)
+                .put(routes::sso::set)
+                .delete(routes::sso::remove),
+        )
+        .route(
+            "/tenant/{tenant_id}/sso/domain",
+            get(routes::sso::list_domains).post(routes::sso::add_domain),
+        )
+        .route(
+            "/tenant/{tenant_id}/sso/domain/{domain}/verify",
+            post(routes::sso::verify_domain),
+        )
+        .route(
+            "/tenant/{tenant_id}/assets",
+            get(routes::asset::get_settings).put(routes::asset::set_settings),
+        )
+        .route(
+            "/tenant/{tenant_id}/delivery-metrics",
+            get(routes::delivery_metrics::get),
+        )
+        .route(
+            "/tenant/{tenant_id}/media-constraints",
+            get(routes::media_constraints::get).put(routes::media_constraints::set),
+        )
+        .route(
+            "/tenant/{tenant_id}/video-effects",
+            get(routes::video_effects::get).put(routes::video_effects::set),
+        )
+        .route(
+            "/tenant/{tenant_id}/video-effects/background",
+            post(routes::video_effects::create_background),
+        )
+        .route(
+            "/tenant/{tenant_id}/video-effects/background/{background_id}",
+            delete(routes::video_effects::delete_background),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference-chat-retention",
+            get(routes::conference_chat::get).put(routes::conference_chat::set),
+        )
+        .route(
+            "/tenant/{tenant_id}/message-retention",
+            get(routes::message_retention::get).put(routes::message_retention::set),
+        )
+        .route(
+            "/tenant/{tenant_id}/message-retention/run",
+            post(routes::message_retention::run),
+        )
+        .route(
+            "/tenant/{tenant_id}/presence",
+            get(routes::user::tenant_presence),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference/preflight",
+            get(routes::preflight::get),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference/preflight/connect",
+            post(routes::preflight::connect),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference/preflight/report",
+            get(routes::preflight::list_reports).post(routes::preflight::report),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference/{conference_id}/stats",
+            get(routes::conference_stats::get),
+        )
+        .route(
+            "/tenant/{tenant_id}/conference/{conference_id}/ics",
+            get(routes::calendar::conference),
+        )
+        .route(
+            "/tenant/{tenant_id}/config/export",
+            get(routes::tenant_config::export),
+        )
+        .route(
+            "/tenant/{tenant_id}/config/diff",
+            post(routes::tenant_config::diff),
+        )
+        .route(
+            "/tenant/{tenant_id}/config/apply",
+            post(routes::tenant_config::apply),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/media-ports",
+            get(routes::admin::media_ports),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/indexes",
+            get(routes::admin::indexes),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/outbound",
+            get(routes::admin::outbound),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/transcription/backends",
+            get(routes::admin::transcription_backends),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/transcription/samples",
+            get(routes::admin::transcription_samples),
+        )
+        .route(
+            "/tenant/{tenant_id}/admin/transcription/samples/{sample_id}/audio",
+            get(routes::admin::transcription_sample_audio),
+        )
+        .route(
+            "/tenant/{tenant_id}/emoji",
+            get(routes::emoji::list).post(routes::emoji::create),
+        )
+        .route(
+            "/tenant/{tenant_id}/emoji/{emoji_id}",
+            delete(routes::emoji::delete),
+        )
+        .route(
+            "/tenant/{tenant_id}/reaction-rule",
+            get(routes::reaction_rule::list).post(routes::reaction_rule::create),
+        )
+        .route(
+            "/tenant/{tenant_id}/reaction-rule/{rule_id}",
+            put(routes::reaction_rule::update).delete(routes::reaction_rule::delete),
+        )
+        .route(
+            "/tenant/{tenant_id}/bot",
+            get(routes::bot::list).post(routes::bot::create),
+        )
+        .route(
+            "/tenant/{tenant_id}/bot/{bot_id}",
+            delete(routes::bot::revoke),
+        )
+        .route("/hook/{bot_token}", post(routes::bot::post_message))
+        .route(
+            "/tenant/{tenant_id}/developer",
+            put(routes::developer::update),
+        )
+        .route(
+            "/tenant/{tenant_id}/developer/usage",
+            get(routes::developer::usage),
+        )
+        .route(
+            "/tenant/{tenant_id}/webhook",
+            get(routes::webhook::list).post(routes::webhook::create),
+        )
+        .route(
+            "/tenant/{tenant_id}/webhook/{webhook_id}",
+            put(routes::webhook::update).delete(routes::webhook::delete),
+        )
+        .route(
+            "/tenant/{tenant_id}/webhook/{webhook_id}/delivery",
+            get(routes::webhook::deliveries),
+        )
+        .route(
+            "/tenant/{tenant_id}/follow-up",
+            get(routes::follow_up::list_mine),
+        )
+        .route(
+            "/tenant/{tenant_id}/follow-up/{follow_up_id}",
+            put(routes::follow_up::update),
+        )
+        .route(
+            "/tenant/{tenant_id}/sandbox/reset",
+            post(routes::sandbox::reset),
+        )
         .nest("/tenant/{tenant_id}/room", room_routes)
+        .nest("/tenant/{tenant_id}/dm", dm_routes)
         .nest("/tenant/{tenant_id}/room/{room_id}/message", message_routes)
+        .nest(
+            "/tenant/{tenant_id}/room/{room_id}/draft",
+            shared_draft_routes,
+        )
         .nest(
             "/tenant/{tenant_id}/room/{room_id}/recording",
             recording_routes,
@@ -296,11 +746,17 @@ pub fn build_router(state: AppState) -> Router {
         .nest("/tenant/{tenant_id}/agent", agent_routes)
         .nest("/tenant/{tenant_id}/session", remote_session_routes);
 
-    // Health check
-    let health = Router::new().route("/health", get(health_check));
+    // Health checks
+    let health = Router::new()
+        .route("/health", get(health::check))
+        .route("/health/live", get(health::live))
+        .route("/health/ready", get(health::ready));
 
     // Apply rate limiting only to API routes (not health/ws which need unrestricted access)
-    let rate_limited_api = Router::new().nest("/api", api).layer(governor_layer);
+    let rate_limited_api = Router::new()
+        .nest("/api", api)
+        .layer(from_fn_with_state(state.clone(), rate_limit::api))
+        .layer(from_fn_with_state(state.clone(), sandbox::mark));
 
     Router::new()
         .merge(rate_limited_api)
@@ -310,10 +766,3 @@ pub fn build_router(state: AppState) -> Router {
         .layer(cors)
         .with_state(state)
 }
-
-async fn health_check() -> axum::Json<serde_json::Value> {
-    axum::Json(serde_json::json!({
-        "status": "ok",
-        "version": env!("CARGO_PKG_VERSION"),
-    }))
-}
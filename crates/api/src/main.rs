@@ -1,11 +1,14 @@
 use bson::oid::ObjectId;
 use roomler_ai_api::{
-    build_router,
+    account_deletion, analytics_reports, build_router, channel_digests, conference_chat,
+    conference_limits, email_queue, follow_ups, message_archive, message_retention, presence,
+    relay_usage,
     state::AppState,
+    webhooks,
     ws::{dispatcher, redis_pubsub::RedisPubSub},
 };
 use roomler_ai_config::Settings;
-use roomler_ai_db::{connect, indexes::ensure_indexes};
+use roomler_ai_db::connect;
 use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -41,30 +44,27 @@ async fn main() -> anyhow::Result<()> {
     // Connect to MongoDB
     let db = connect(&settings).await?;
 
-    // Ensure indexes
-    ensure_indexes(&db).await?;
-
     // Build app state (async: spawns mediasoup workers)
     let app_state = AppState::new(db.clone(), settings.clone()).await?;
 
-    // Clean up ALL stale calls — no calls can be active at server startup
+    // Build indexes in the background; /health fails if a required one fails
     {
-        let rooms_coll = db.collection::<bson::Document>("rooms");
-        let result = rooms_coll
-            .update_many(
-                bson::doc! { "conference_status": "in_progress" },
-                bson::doc! { "$set": { "conference_status": "ended", "participant_count": 0_i32 } },
-            )
-            .await
-            .ok();
-        if let Some(res) = result
-            && res.modified_count > 0
-        {
+        let indexes = app_state.indexes.clone();
+        tokio::spawn(async move {
+            indexes.run().await;
+        });
+    }
+
+    // Close out recordings whose upload was cut off by the previous process
+    match app_state.recording_uploads.recover_interrupted().await {
+        Ok(report) if report.partial > 0 || report.failed > 0 => {
             info!(
-                "Cleaned up {} stale calls (all in_progress reset to ended)",
-                res.modified_count
+                "Recovered interrupted recordings: {} partial, {} failed",
+                report.partial, report.failed
             );
         }
+        Ok(_) => {}
+        Err(e) => error!("Failed to recover interrupted recordings: {}", e),
     }
 
     // Fix thread metadata for existing thread roots with null metadata
@@ -150,6 +150,42 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Enforce plan-based conference duration / idle limits
+    conference_limits::spawn(app_state.clone());
+
+    // Move old messages into monthly archive partitions
+    message_archive::spawn(app_state.clone());
+
+    // Purge conference chat past each tenant's retention period
+    conference_chat::spawn(app_state.clone());
+
+    // Purge channel messages past retention policies and plan history caps
+    message_retention::spawn(app_state.clone());
+
+    // Decay the presence of connected users who went quiet
+    presence::spawn(app_state.clone());
+
+    // Remind rooms of call follow-ups that come due
+    follow_ups::spawn(app_state.clone());
+
+    // Post daily digests into channels that configured one
+    channel_digests::spawn(app_state.clone());
+
+    // Anonymize deleted accounts whose grace period ended
+    account_deletion::spawn(app_state.clone());
+
+    // Send queued emails, retrying failed ones
+    email_queue::spawn(app_state.clone());
+
+    // Retry failed outgoing webhook deliveries
+    webhooks::spawn(app_state.clone());
+
+    // Email last month's analytics to tenants that asked for it
+    analytics_reports::spawn(app_state.clone());
+
+    // Account for media relayed through TURN and alert over-budget tenants
+    relay_usage::spawn(app_state.clone());
+
     // Build router
     let app = build_router(app_state);
 
@@ -158,7 +194,11 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     info!("Listening on {}", addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,31 @@
+//! Periodic archiving of old messages into monthly partitions. Runs only
+//! when `message_archive.enabled` is set; see
+//! [`roomler_ai_services::message_archive`].
+
+use std::time::Duration;
+
+use roomler_ai_services::message_archive::MessageArchiver;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Spawn the archive sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let settings = &state.settings.message_archive;
+    if !settings.enabled {
+        return;
+    }
+    let period = Duration::from_secs(settings.check_interval_secs.max(1));
+    let archiver = MessageArchiver::new(&state.db, settings);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match archiver.sweep(archiver.cutoff()).await {
+                Ok(0) => {}
+                Ok(moved) => info!(moved, "Archived old messages"),
+                Err(e) => warn!(%e, "Message archive sweep failed"),
+            }
+        }
+    });
+}
@@ -0,0 +1,168 @@
+//! Channel message retention, configured per tenant in
+//! `settings.message_retention` and per room in `message_retention`, and
+//! the plan's message history cap.
+//!
+//! A periodic sweep applies them, and admins can run it for their tenant
+//! as a background task. Each room follows its own policy, or else the
+//! tenant's, and loses messages older than `max_age_days` and beyond its
+//! newest `max_count`. A plan with a history cap then keeps only that many
+//! of the tenant's newest messages, whatever the policies say. Every purge
+//! is recorded in the tenant's audit log. The removal itself is in
+//! [`roomler_ai_services::message_retention`].
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use bson::{DateTime, doc, oid::ObjectId};
+use roomler_ai_db::models::{ActorType, Plan, actions};
+use roomler_ai_services::message_retention::{PurgeReport, PurgeResult, age_cutoff};
+use tracing::{info, warn};
+
+use crate::{audit, state::AppState};
+
+/// Spawn the retention sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.message_retention.purge_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match purge_expired(&state).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Purged messages past retention"),
+                Err(e) => warn!(%e, "Message retention purge failed"),
+            }
+        }
+    });
+}
+
+/// Apply retention in every tenant with a policy or a capped plan. A
+/// tenant that fails is logged and skipped. Returns the number of messages
+/// removed.
+pub async fn purge_expired(state: &AppState) -> PurgeResult<u64> {
+    let capped: Vec<Plan> = [Plan::Free, Plan::Pro, Plan::Business, Plan::Enterprise]
+        .into_iter()
+        .filter(|plan| plan.limits().max_message_history >= 0)
+        .collect();
+    let mut tenant_ids: BTreeSet<ObjectId> = state
+        .tenants
+        .find_with_message_retention(&capped)
+        .await?
+        .into_iter()
+        .filter_map(|tenant| tenant.id)
+        .collect();
+    tenant_ids.extend(
+        state
+            .rooms
+            .find_with_message_retention()
+            .await?
+            .into_iter()
+            .map(|room| room.tenant_id),
+    );
+
+    let mut purged = 0;
+    for tenant_id in tenant_ids {
+        match purge_tenant(state, tenant_id).await {
+            Ok(report) => purged += report.purged,
+            Err(e) => warn!(%tenant_id, %e, "Message retention purge failed for tenant"),
+        }
+    }
+    Ok(purged)
+}
+
+/// Apply retention in one tenant.
+pub async fn purge_tenant(state: &AppState, tenant_id: ObjectId) -> PurgeResult<PurgeReport> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let default = &tenant.settings.message_retention;
+    let now = DateTime::now();
+    let mut report = PurgeReport::default();
+
+    for room in state.rooms.find_live_in_tenant(tenant_id).await? {
+        let policy = room.message_retention.as_ref().unwrap_or(default);
+        let Some(room_id) = room.id else { continue };
+        if !policy.has_limits() {
+            continue;
+        }
+        let scope = doc! { "room_id": room_id };
+        let mut before = policy.max_age_days.map(|days| age_cutoff(now, days));
+        if let Some(keep) = policy.max_count {
+            before = before.max(
+                state
+                    .message_purger
+                    .count_cutoff(&scope, u64::from(keep))
+                    .await?,
+            );
+        }
+        let Some(before) = before else { continue };
+        let purged = state
+            .message_purger
+            .purge_before(&scope, before, policy.archive)
+            .await?;
+        record_purge(
+            state,
+            tenant_id,
+            actions::ROOM_MESSAGE_PURGE,
+            Some(room_id),
+            &purged,
+            before,
+        )
+        .await;
+        report.add(purged);
+    }
+
+    let cap = tenant.plan.limits().max_message_history;
+    if cap >= 0 {
+        let scope = doc! { "tenant_id": tenant_id };
+        if let Some(before) = state
+            .message_purger
+            .count_cutoff(&scope, cap as u64)
+            .await?
+        {
+            let purged = state
+                .message_purger
+                .purge_before(&scope, before, default.archive)
+                .await?;
+            record_purge(
+                state,
+                tenant_id,
+                actions::TENANT_MESSAGE_PURGE,
+                None,
+                &purged,
+                before,
+            )
+            .await;
+            report.add(purged);
+        }
+    }
+    Ok(report)
+}
+
+async fn record_purge(
+    state: &AppState,
+    tenant_id: ObjectId,
+    action: &str,
+    target_id: Option<ObjectId>,
+    report: &PurgeReport,
+    before: DateTime,
+) {
+    if report.purged == 0 {
+        return;
+    }
+    audit::record_system(
+        state,
+        tenant_id,
+        ActorType::System,
+        action,
+        target_id,
+        vec![
+            audit::change("purged", None, Some(report.purged.into())),
+            audit::change("archived", None, Some(report.archived.len().into())),
+            audit::change(
+                "before",
+                None,
+                before.try_to_rfc3339_string().ok().map(Into::into),
+            ),
+        ],
+    )
+    .await;
+}
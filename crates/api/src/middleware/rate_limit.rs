@@ -0,0 +1,170 @@
+//! Token-bucket rate limiting for `/api`.
+//!
+//! Each request is keyed by the user of a valid access token, or by client
+//! IP when there is none (see [`client_ip`]: forwarding headers count only
+//! from `app.trusted_proxies`). Every `/api` request draws from the `api`
+//! budget, or the roomier `sandbox` one when it targets a sandbox tenant;
+//! login, invite and public channel routes additionally draw from their
+//! own, tighter budgets. An empty bucket answers 429 with `Retry-After`.
+
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_config::RateBudget;
+
+use crate::{
+    error::ApiError,
+    extractors::{auth::access_token, client::client_ip},
+    state::AppState,
+};
+
+/// Full buckets are dropped once every this many checks.
+const PRUNE_EVERY: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Budget {
+    Api,
+    Login,
+    Invite,
+    Sandbox,
+    Public,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateKey {
+    User(ObjectId),
+    Ip(IpAddr),
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: DashMap<(Budget, RateKey), Bucket>,
+    checks: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take one token from `key`'s bucket, or say how long until one is
+    /// available.
+    pub fn check(
+        &self,
+        budget: Budget,
+        key: RateKey,
+        limits: &RateBudget,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        if self.checks.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+            self.prune(budget, limits, now);
+        }
+
+        let burst = f64::from(limits.burst.max(1));
+        let per_sec = f64::from(limits.per_minute.max(1)) / 60.0;
+
+        let mut bucket = self.buckets.entry((budget, key)).or_insert_with(|| Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * per_sec).min(burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_sec))
+        }
+    }
+
+    /// Drop `budget`'s buckets that have had time to refill completely; a
+    /// fresh bucket behaves the same.
+    fn prune(&self, budget: Budget, limits: &RateBudget, now: Instant) {
+        let refill = Duration::from_secs_f64(
+            f64::from(limits.burst.max(1)) * 60.0 / f64::from(limits.per_minute.max(1)),
+        );
+        self.buckets.retain(|(b, _), bucket| {
+            *b != budget || now.saturating_duration_since(bucket.refilled_at) < refill
+        });
+    }
+}
+
+/// Every `/api` request.
+pub async fn api(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if super::sandbox::targets_sandbox(&state, req.uri().path()).await {
+        let limits = state.settings.rate_limit.sandbox;
+        return limit(&state, Budget::Sandbox, &limits, req, next).await;
+    }
+    let limits = state.settings.rate_limit.api;
+    limit(&state, Budget::Api, &limits, req, next).await
+}
+
+/// Login, registration and account activation.
+pub async fn login(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let limits = state.settings.rate_limit.login;
+    limit(&state, Budget::Login, &limits, req, next).await
+}
+
+/// Invite lookup and acceptance, and meeting code lookups.
+pub async fn invite(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let limits = state.settings.rate_limit.invite;
+    limit(&state, Budget::Invite, &limits, req, next).await
+}
+
+/// Unauthenticated views of public channels.
+pub async fn public(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let limits = state.settings.rate_limit.public;
+    limit(&state, Budget::Public, &limits, req, next).await
+}
+
+async fn limit(
+    state: &AppState,
+    budget: Budget,
+    limits: &RateBudget,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !state.settings.rate_limit.enabled {
+        return next.run(req).await;
+    }
+
+    let Some(key) = request_key(state, &req) else {
+        return next.run(req).await;
+    };
+    match state
+        .rate_limiter
+        .check(budget, key, limits, Instant::now())
+    {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            ApiError::TooManyRequests(secs.max(1)).into_response()
+        }
+    }
+}
+
+fn request_key(state: &AppState, req: &Request) -> Option<RateKey> {
+    let user = access_token(req.headers())
+        .and_then(|token| state.auth.verify_access_token(&token).ok())
+        .and_then(|claims| ObjectId::parse_str(&claims.sub).ok());
+    if let Some(user_id) = user {
+        return Some(RateKey::User(user_id));
+    }
+
+    client_ip(req.headers(), req.extensions(), &state.trusted_proxies).map(RateKey::Ip)
+}
@@ -0,0 +1,45 @@
+//! Marks every response from a sandbox tenant's routes with
+//! `X-Roomler-Sandbox: true`, so sandbox data can't pass for production
+//! data. See [`roomler_ai_services::sandbox`].
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use bson::oid::ObjectId;
+
+use crate::state::AppState;
+
+pub const SANDBOX_HEADER: &str = "x-roomler-sandbox";
+
+pub async fn mark(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let sandbox = targets_sandbox(&state, req.uri().path()).await;
+    let mut resp = next.run(req).await;
+    if sandbox {
+        resp.headers_mut()
+            .insert(SANDBOX_HEADER, HeaderValue::from_static("true"));
+    }
+    resp
+}
+
+/// Whether `path` is under `/api/tenant/{tenant_id}` of a sandbox tenant.
+pub async fn targets_sandbox(state: &AppState, path: &str) -> bool {
+    let Some(tenant_id) = path_tenant(path) else {
+        return false;
+    };
+    state
+        .sandbox_tenants
+        .is_sandbox(&state.tenants, tenant_id)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(%e, %tenant_id, "Failed to look up sandbox flag");
+            false
+        })
+}
+
+fn path_tenant(path: &str) -> Option<ObjectId> {
+    let rest = path.strip_prefix("/api/tenant/")?;
+    ObjectId::parse_str(rest.split('/').next()?).ok()
+}
@@ -0,0 +1,284 @@
+//! Presence: each user's status and their call, screen-share and recording
+//! activity.
+//!
+//! Status (`online`, `idle`, `dnd`, `offline`, `invisible`) is tracked by the
+//! [`PresenceTracker`] for users connected to this instance: the socket
+//! handler reports connects, disconnects, client activity and explicit
+//! choices, and [`spawn`] decays idle users. Every transition goes through
+//! [`announce`], which persists it on the user and pushes a
+//! `presence:update` to the members of the user's tenants.
+//!
+//! Activity is derived from the `RoomManager` on demand rather than stored,
+//! so it can't drift from the media state. Handlers that change a user's
+//! call state call [`broadcast_activity`], which pushes a `presence:update`
+//! with the new `activity` to the room's tenant members outside the call.
+//! Users who hide their presence never have activity pushed or listed for
+//! anyone but themselves.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::Presence;
+use roomler_ai_services::presence::visible_to_others;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PresenceActivity {
+    pub in_call: bool,
+    pub room_id: Option<String>,
+    pub screen_sharing: bool,
+    /// The call the user is in is being recorded.
+    pub recording: bool,
+}
+
+/// Activity of every user currently in a call on this instance. Users not in
+/// the map are idle.
+pub async fn activities(state: &AppState) -> HashMap<ObjectId, PresenceActivity> {
+    let calls = state.room_manager.call_activities();
+    if calls.is_empty() {
+        return HashMap::new();
+    }
+    let recording_rooms: HashSet<ObjectId> = state
+        .recordings
+        .find_processing(None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| r.room_id)
+        .collect();
+
+    calls
+        .into_iter()
+        .map(|(user_id, call)| {
+            let activity = PresenceActivity {
+                in_call: true,
+                room_id: Some(call.room_id.to_hex()),
+                screen_sharing: call.screen_sharing,
+                recording: recording_rooms.contains(&call.room_id),
+            };
+            (user_id, activity)
+        })
+        .collect()
+}
+
+pub async fn activity_for(state: &AppState, user_id: ObjectId) -> PresenceActivity {
+    activities(state).await.remove(&user_id).unwrap_or_default()
+}
+
+/// [`activities`] as `viewer` may see them: users hiding their presence
+/// appear idle to everyone else.
+pub async fn visible_activities(
+    state: &AppState,
+    viewer: ObjectId,
+) -> HashMap<ObjectId, PresenceActivity> {
+    let mut current = activities(state).await;
+    let ids: Vec<ObjectId> = current.keys().copied().collect();
+    for id in hidden_presence(state, &ids).await {
+        if id != viewer {
+            current.remove(&id);
+        }
+    }
+    current
+}
+
+/// Which of `user_ids` hide their presence. Everyone counts as hidden if the
+/// settings can't be loaded.
+pub async fn hidden_presence(state: &AppState, user_ids: &[ObjectId]) -> HashSet<ObjectId> {
+    match state.users.find_privacy(user_ids).await {
+        Ok(prefs) => prefs
+            .into_iter()
+            .filter(|(_, p)| p.hide_presence)
+            .map(|(id, _)| id)
+            .collect(),
+        Err(e) => {
+            warn!(%e, "Presence: privacy lookup failed");
+            user_ids.iter().copied().collect()
+        }
+    }
+}
+
+/// Push the current activity of `user_ids` to the members of `room_id`'s
+/// tenant who aren't in that call (or among `user_ids`). Call after any
+/// change to who is in a call or what they share.
+pub async fn broadcast_activity(state: &AppState, room_id: ObjectId, user_ids: &[ObjectId]) {
+    let hidden = hidden_presence(state, user_ids).await;
+    let user_ids: Vec<ObjectId> = user_ids
+        .iter()
+        .copied()
+        .filter(|id| !hidden.contains(id))
+        .collect();
+    if user_ids.is_empty() {
+        return;
+    }
+    let tenant_id = match state.rooms.base.find_by_id(room_id).await {
+        Ok(room) => room.tenant_id,
+        Err(e) => {
+            warn!(%room_id, %e, "Presence: room lookup failed");
+            return;
+        }
+    };
+    // People in the call already follow it through media signaling and the
+    // subjects know their own state; this is for everyone else's member list.
+    let in_call = state.room_manager.get_participant_user_ids(&room_id);
+    let recipients: Vec<ObjectId> = match state.tenants.find_member_user_ids(tenant_id).await {
+        Ok(ids) => ids
+            .into_iter()
+            .filter(|id| !in_call.contains(id) && !user_ids.contains(id))
+            .collect(),
+        Err(e) => {
+            warn!(%tenant_id, %e, "Presence: member lookup failed");
+            return;
+        }
+    };
+
+    let mut current = activities(state).await;
+    for user_id in &user_ids {
+        let event = serde_json::json!({
+            "type": "presence:update",
+            "data": {
+                "user_id": user_id.to_hex(),
+                "activity": current.remove(user_id).unwrap_or_default(),
+            }
+        });
+        crate::ws::dispatcher::broadcast_in_tenant(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &state.delivery_metrics,
+            tenant_id,
+            &recipients,
+            &event,
+        )
+        .await;
+    }
+}
+
+/// Spawn the idle sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.presence.sweep_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            for (user_id, presence) in state.presence.sweep(Instant::now()) {
+                announce(&state, user_id, presence, true).await;
+            }
+        }
+    });
+}
+
+/// A connection of `user_id` opened.
+pub async fn connected(state: &AppState, user_id: ObjectId) {
+    if let Some(presence) = state.presence.connected(user_id, Instant::now()) {
+        announce(state, user_id, presence, false).await;
+    }
+}
+
+/// The last connection of `user_id` on this instance closed.
+pub async fn disconnected(state: &AppState, user_id: ObjectId) {
+    if let Some(presence) = state.presence.disconnected(user_id) {
+        announce(state, user_id, presence, false).await;
+    }
+}
+
+/// `user_id` sent something other than a keepalive.
+pub async fn touch(state: &AppState, user_id: ObjectId) {
+    if let Some(presence) = state.presence.touch(user_id, Instant::now()) {
+        announce(state, user_id, presence, true).await;
+    }
+}
+
+/// `user_id` picked a presence.
+pub async fn set(state: &AppState, user_id: ObjectId, presence: Presence) {
+    if let Some(presence) = state.presence.set(user_id, presence, Instant::now()) {
+        announce(state, user_id, presence, true).await;
+    }
+}
+
+/// Persist a presence transition and push it to the members of the user's
+/// tenants; invisible users are announced as offline. With `include_self`
+/// the user's own connections get it too, so their other tabs follow a
+/// change they didn't make themselves. Users hiding their presence are only
+/// ever announced to themselves.
+pub async fn announce(state: &AppState, user_id: ObjectId, presence: Presence, include_self: bool) {
+    if let Err(e) = state.users.update_presence(user_id, presence.clone()).await {
+        warn!(%user_id, %e, "Presence: persisting failed");
+    }
+    let activity = activity_for(state, user_id).await;
+    let event = |presence: &Presence| {
+        serde_json::json!({
+            "type": "presence:update",
+            "data": {
+                "user_id": user_id.to_hex(),
+                "presence": presence,
+                "activity": activity,
+            }
+        })
+    };
+
+    if include_self {
+        crate::ws::dispatcher::send_to_user_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &user_id,
+            &event(&presence),
+        )
+        .await;
+    }
+    if hidden_presence(state, &[user_id]).await.contains(&user_id) {
+        return;
+    }
+    let others: Vec<ObjectId> = match state.tenants.find_co_member_user_ids(user_id).await {
+        Ok(ids) => ids.into_iter().filter(|id| *id != user_id).collect(),
+        Err(e) => {
+            warn!(%user_id, %e, "Presence: member lookup failed");
+            return;
+        }
+    };
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &others,
+        &event(&visible_to_others(presence)),
+    )
+    .await;
+}
+
+/// Presence of `user_ids` as `viewer` may see it. Users connected here are
+/// answered by the tracker, others by what was last persisted.
+pub async fn visible_presence(
+    state: &AppState,
+    viewer: ObjectId,
+    user_ids: &[ObjectId],
+) -> HashMap<ObjectId, Presence> {
+    let mut stored = state
+        .users
+        .find_presence(user_ids)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(%e, "Presence: lookup failed");
+            HashMap::new()
+        });
+    let hidden = hidden_presence(state, user_ids).await;
+    user_ids
+        .iter()
+        .map(|id| {
+            let presence = state
+                .presence
+                .get(id)
+                .or_else(|| stored.remove(id))
+                .unwrap_or_default();
+            let presence = if *id == viewer {
+                presence
+            } else if hidden.contains(id) {
+                Presence::Offline
+            } else {
+                visible_to_others(presence)
+            };
+            (*id, presence)
+        })
+        .collect()
+}
@@ -0,0 +1,176 @@
+//! The tenant profanity filter on channel messages; see
+//! [`roomler_ai_services::profanity`]. [`screen`] runs on new and edited
+//! content before it is written: masked content replaces what was sent,
+//! blocked messages fail with 422, and flagged terms are stored on the
+//! message for moderators. Each action is recorded in `moderation_events`
+//! for the moderation analytics. Messages written on the server's side
+//! (bot posts, drafts, clips, rule and reminder posts) go through [`post`],
+//! which screens, writes and records in one step; conference chat, from
+//! members and guests alike, goes through [`post_call`].
+
+use std::future::Future;
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{CallChatMessage, Message, ModerationAction};
+use roomler_ai_services::{
+    dao::base::DaoResult,
+    profanity::{self, Verdict},
+};
+use tracing::warn;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Content that passed the filter.
+pub struct Screened {
+    /// What to store: the content as sent, or masked.
+    pub content: String,
+    /// Terms to store on the message; only set in flag mode.
+    pub flagged_terms: Vec<String>,
+    action: Option<(ModerationAction, Vec<String>)>,
+}
+
+/// Screen `content` that `user_id` is posting to `room_id`.
+pub async fn screen(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    content: String,
+) -> Result<Screened, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let settings = &tenant.settings;
+    let (content, flagged_terms, action) = match profanity::screen(
+        &settings.profanity_filter,
+        &settings.default_locale,
+        &content,
+    ) {
+        Verdict::Clean => (content, Vec::new(), None),
+        Verdict::Masked { content, terms } => {
+            (content, Vec::new(), Some((ModerationAction::Masked, terms)))
+        }
+        Verdict::Flagged { terms } => (
+            content,
+            terms.clone(),
+            Some((ModerationAction::Flagged, terms)),
+        ),
+        Verdict::Blocked { terms } => {
+            record(
+                state,
+                tenant_id,
+                room_id,
+                user_id,
+                None,
+                ModerationAction::Blocked,
+                terms,
+            )
+            .await;
+            return Err(ApiError::Validation(
+                "The message contains words this workspace doesn't allow".to_string(),
+            ));
+        }
+    };
+    Ok(Screened {
+        content,
+        flagged_terms,
+        action,
+    })
+}
+
+/// Screen `content` that `user_id` is posting to `room_id`, write what
+/// passed with `create`, then store flagged terms on the message and record
+/// what the filter did.
+pub async fn post<F, Fut>(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    content: String,
+    create: F,
+) -> Result<Message, ApiError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = DaoResult<Message>>,
+{
+    let screened = screen(state, tenant_id, room_id, user_id, content).await?;
+    let mut message = create(screened.content.clone()).await?;
+    if let Some(message_id) = message.id {
+        if !screened.flagged_terms.is_empty() {
+            state
+                .messages
+                .flag(&[message_id], &screened.flagged_terms)
+                .await?;
+            message.flagged_terms = screened.flagged_terms.clone();
+        }
+        screened
+            .record(state, tenant_id, room_id, user_id, message_id)
+            .await;
+    }
+    Ok(message)
+}
+
+/// Like [`post`], for conference chat. Call messages aren't reviewed by
+/// moderators, so flagged terms are only recorded, not stored on them.
+pub async fn post_call<F, Fut>(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    author_id: ObjectId,
+    content: String,
+    create: F,
+) -> Result<CallChatMessage, ApiError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = DaoResult<CallChatMessage>>,
+{
+    let screened = screen(state, tenant_id, room_id, author_id, content).await?;
+    let message = create(screened.content.clone()).await?;
+    if let Some(message_id) = message.id {
+        screened
+            .record(state, tenant_id, room_id, author_id, message_id)
+            .await;
+    }
+    Ok(message)
+}
+
+impl Screened {
+    /// Record what the filter did, once the message is written.
+    pub async fn record(
+        &self,
+        state: &AppState,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        message_id: ObjectId,
+    ) {
+        if let Some((action, terms)) = &self.action {
+            record(
+                state,
+                tenant_id,
+                room_id,
+                user_id,
+                Some(message_id),
+                *action,
+                terms.clone(),
+            )
+            .await;
+        }
+    }
+}
+
+async fn record(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    message_id: Option<ObjectId>,
+    action: ModerationAction,
+    terms: Vec<String>,
+) {
+    if let Err(e) = state
+        .moderation_events
+        .record(tenant_id, room_id, user_id, message_id, action, terms)
+        .await
+    {
+        warn!(%e, ?tenant_id, "Failed to record moderation event");
+    }
+}
@@ -0,0 +1,139 @@
+//! Runs tenants' reaction rules; see [`roomler_ai_services::reaction_rules`]
+//! for the loop safeguards. Reaction routes call [`spawn`] after a reaction
+//! is added or removed, so a slow webhook never holds up the reaction.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{AuthorType, ReactionRule, ReactionTrigger, RuleAction};
+use roomler_ai_services::{dao::base::DaoResult, reaction_rules};
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// A reaction that was added or removed.
+#[derive(Debug, Clone)]
+pub struct ReactionEvent {
+    pub trigger: ReactionTrigger,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub message_id: ObjectId,
+    pub user_id: ObjectId,
+    /// Stored reaction value.
+    pub emoji: String,
+}
+
+/// Run the rules matching `event` in the background.
+pub fn spawn(state: &AppState, event: ReactionEvent) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = fire(&state, &event).await {
+            warn!(%e, message_id = %event.message_id, "Reaction rules failed");
+        }
+    });
+}
+
+/// Run the rules matching `event`. Returns how many fired.
+pub async fn fire(state: &AppState, event: &ReactionEvent) -> DaoResult<usize> {
+    let rules = state
+        .reaction_rules
+        .find_matching(event.tenant_id, event.room_id, &event.emoji, event.trigger)
+        .await?;
+    if rules.is_empty() {
+        return Ok(0);
+    }
+    // Rules post system messages; reacting to those never fires a rule.
+    let message = state.messages.base.find_by_id(event.message_id).await?;
+    if matches!(message.author_type, AuthorType::System) {
+        return Ok(0);
+    }
+    let names = state
+        .users
+        .find_display_names(&[event.user_id])
+        .await
+        .unwrap_or_default();
+    let user_name = names.get(&event.user_id).cloned().unwrap_or_default();
+
+    let mut fired = 0;
+    for rule in rules {
+        let Some(rule_id) = rule.id else { continue };
+        if !state
+            .reaction_rule_engine
+            .admit(rule_id, event.message_id, event.user_id)
+        {
+            continue;
+        }
+        fired += 1;
+        match &rule.action {
+            RuleAction::Webhook { url } => {
+                let body = serde_json::json!({
+                    "event": match event.trigger {
+                        ReactionTrigger::Add => "reaction.add",
+                        ReactionTrigger::Remove => "reaction.remove",
+                    },
+                    "rule_id": rule_id.to_hex(),
+                    "tenant_id": event.tenant_id.to_hex(),
+                    "room_id": event.room_id.to_hex(),
+                    "message_id": event.message_id.to_hex(),
+                    "message_author_id": message.author_id.to_hex(),
+                    "message_content": &message.content,
+                    "user_id": event.user_id.to_hex(),
+                    "user_name": &user_name,
+                    "emoji": &event.emoji,
+                });
+                if let Err(e) = state
+                    .reaction_rule_engine
+                    .deliver_webhook(url, &rule.secret, body.to_string().into_bytes())
+                    .await
+                {
+                    warn!(%rule_id, %e, "Reaction rule webhook failed");
+                }
+            }
+            RuleAction::PostMessage { room_id, content } => {
+                let content =
+                    reaction_rules::render(content, &user_name, &event.emoji, event.message_id);
+                post_message(state, &rule, room_id.unwrap_or(event.room_id), content).await;
+            }
+        }
+    }
+    Ok(fired)
+}
+
+async fn post_message(state: &AppState, rule: &ReactionRule, room_id: ObjectId, content: String) {
+    let (tenant_id, author_id) = (rule.tenant_id, rule.created_by);
+    let message =
+        match crate::profanity::post(state, tenant_id, room_id, author_id, content, |content| {
+            state
+                .messages
+                .create_system(tenant_id, room_id, author_id, content)
+        })
+        .await
+        {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(%room_id, %e, "Reaction rule: failed to post message");
+                return;
+            }
+        };
+    let names = state
+        .users
+        .find_display_names(&[rule.created_by])
+        .await
+        .unwrap_or_default();
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": crate::routes::message::to_response(message, &names, None),
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        rule.tenant_id,
+        &member_ids,
+        &event,
+    )
+    .await;
+}
@@ -0,0 +1,167 @@
+//! TURN relay accounting; see [`roomler_ai_services::relay_usage`].
+//!
+//! Every `turn.accounting_interval_secs` the sampler reads the transports of
+//! the conferences hosted on this instance and adds what went through the
+//! TURN server to `relay_usage`, per conference and day. Media relayed after
+//! the last sample of a participant who left isn't counted. When a tenant's
+//! usage in the current month goes over `turn.monthly_budget_mb`, a warning
+//! is logged and its admins are emailed, once per tenant and month.
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use chrono::{NaiveDate, Utc};
+use roomler_ai_config::TurnSettings;
+use roomler_ai_db::models::Tenant;
+use roomler_ai_services::analytics;
+use roomler_ai_services::dao::base::DaoResult;
+use roomler_ai_services::relay_usage::{RelayMeter, megabytes, parse_relay_ips, turn_host};
+use tracing::{info, warn};
+
+use crate::analytics_reports::admin_ids;
+use crate::state::AppState;
+
+/// Spawn the sampler. Runs for the lifetime of the process unless relay
+/// accounting is off or there is no TURN server to account for.
+pub fn spawn(state: AppState) {
+    let interval = state.settings.turn.accounting_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let Some(relay_ips) = relay_ips(&state.settings.turn).await else {
+            return;
+        };
+        info!(?relay_ips, "TURN relay accounting enabled");
+        let mut meter = RelayMeter::default();
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = sample(&state, &mut meter, &relay_ips).await {
+                warn!(%e, "TURN relay accounting failed");
+            }
+        }
+    });
+}
+
+/// `turn.relay_ips`, or else the addresses the TURN server's host resolves
+/// to. `None` without either.
+async fn relay_ips(turn: &TurnSettings) -> Option<HashSet<IpAddr>> {
+    let ips = match parse_relay_ips(&turn.relay_ips) {
+        Ok(ips) => ips,
+        Err(e) => {
+            warn!(%e, "TURN relay accounting is off");
+            return None;
+        }
+    };
+    if !ips.is_empty() {
+        return Some(ips);
+    }
+    let (host, port) = turn.url.as_deref().and_then(turn_host)?;
+    match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addrs) => Some(addrs.map(|a| a.ip()).collect()),
+        Err(e) => {
+            warn!(%host, %e, "Failed to resolve the TURN server; relay accounting is off");
+            None
+        }
+    }
+}
+
+/// Record what was relayed since the previous sample, then check the
+/// budgets of the tenants that used the relay.
+pub async fn sample(
+    state: &AppState,
+    meter: &mut RelayMeter,
+    relay_ips: &HashSet<IpAddr>,
+) -> DaoResult<()> {
+    let samples = state.room_manager.transport_samples().await;
+    let relayed = meter.sample(&samples, relay_ips);
+    let today = Utc::now().date_naive();
+    let mut tenants = HashSet::new();
+    for (room_id, bytes) in relayed {
+        let room = match state.rooms.base.find_by_id(room_id).await {
+            Ok(room) => room,
+            Err(e) => {
+                warn!(%room_id, %e, "TURN relay accounting: room not found");
+                continue;
+            }
+        };
+        state
+            .relay_usage
+            .record(room.tenant_id, room_id, today, bytes)
+            .await?;
+        tenants.insert(room.tenant_id);
+    }
+    for tenant_id in tenants {
+        check_budget(state, tenant_id, today).await?;
+    }
+    Ok(())
+}
+
+/// Alert the tenant's admins if its relay usage in the month of `today`
+/// is over the budget and they haven't been alerted for that month yet.
+/// Returns whether they were alerted.
+pub async fn check_budget(
+    state: &AppState,
+    tenant_id: ObjectId,
+    today: NaiveDate,
+) -> DaoResult<bool> {
+    let budget = megabytes(state.settings.turn.monthly_budget_mb);
+    if budget == 0 {
+        return Ok(false);
+    }
+    let (first, month) = analytics::current_month(today);
+    let used = state.relay_usage.total(tenant_id, first, today).await?;
+    if used <= budget
+        || !state
+            .tenants
+            .claim_relay_budget_alert(tenant_id, &month)
+            .await?
+    {
+        return Ok(false);
+    }
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    warn!(%tenant_id, used, budget, %month, "Tenant went over its TURN relay budget");
+    email_admins(state, &tenant, tenant_id, used, budget, &month).await;
+    Ok(true)
+}
+
+async fn email_admins(
+    state: &AppState,
+    tenant: &Tenant,
+    tenant_id: ObjectId,
+    used: u64,
+    budget: u64,
+    month: &str,
+) {
+    let Some(email) = &state.email else { return };
+    let subject = format!(
+        "{}: TURN relay usage over budget for {}",
+        tenant.name, month
+    );
+    let html = format!(
+        r#"<div style="font-family: sans-serif; max-width: 600px; margin: 0 auto;">
+<h2>{tenant} — {month}</h2>
+<p>Calls relayed <strong>{used} MB</strong> through the TURN server this month, over the budget of <strong>{budget} MB</strong>.</p>
+<p>Relay usage per day and per conference is in the analytics of your workspace.</p>
+<p style="color: #999; font-size: 12px; margin-top: 32px;">— The Roomler Team</p>
+</div>"#,
+        tenant = tenant.name,
+        month = month,
+        used = used / 1_000_000,
+        budget = budget / 1_000_000,
+    );
+    for user_id in admin_ids(state, tenant_id).await {
+        let Ok(user) = state.users.base.find_by_id(user_id).await else {
+            continue;
+        };
+        if user.deleted_at.is_some() {
+            continue;
+        }
+        if let Err(e) = email.send(&user.email, &subject, &html).await {
+            warn!(%tenant_id, %user_id, %e, "TURN relay budget alert: email failed");
+        }
+    }
+}
@@ -2,3 +2,214 @@
 // GET /api/tenant/:tid/admin/audit-log
 // GET /api/tenant/:tid/admin/stats
 // Admin-only access controlled by RBAC.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::{indexes::IndexReport, models::role::permissions};
+use roomler_ai_services::{
+    media::room_manager::WorkerPortUsage, outbound::ServiceSnapshot, transcription::BackendStatus,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct MediaPortsResponse {
+    pub workers: Vec<WorkerPortUsage>,
+    pub total_capacity: u32,
+    pub total_transports: u32,
+}
+
+/// GET /api/tenant/{tenant_id}/admin/media-ports — RTC port utilization of
+/// the serving instance's mediasoup workers, for diagnosing "ICE failed"
+/// reports caused by port exhaustion.
+pub async fn media_ports(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<MediaPortsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    let workers = state.room_manager.port_usage();
+    Ok(Json(MediaPortsResponse {
+        total_capacity: workers.iter().map(|w| w.capacity).sum(),
+        total_transports: workers.iter().map(|w| w.transports).sum(),
+        workers,
+    }))
+}
+
+/// GET /api/tenant/{tenant_id}/admin/indexes — how far the serving
+/// instance got building the MongoDB indexes since startup.
+pub async fn indexes(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<IndexReport>, ApiError> {
+    managed_tenant(&state, &auth, &tenant_id).await?;
+    Ok(Json(state.indexes.report()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutboundResponse {
+    pub services: Vec<ServiceSnapshot>,
+}
+
+/// GET /api/tenant/{tenant_id}/admin/outbound — the serving instance's
+/// calls to third parties since startup: attempts, failures, retries,
+/// calls refused by an open circuit breaker, and average latency, per
+/// service.
+pub async fn outbound(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<OutboundResponse>, ApiError> {
+    managed_tenant(&state, &auth, &tenant_id).await?;
+    Ok(Json(OutboundResponse {
+        services: state.outbound.snapshot(),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptionBackendsResponse {
+    pub backends: Vec<BackendStatus>,
+}
+
+/// GET /api/tenant/{tenant_id}/admin/transcription/backends — the ASR
+/// backend's reachability, whether it serves the configured model, and
+/// request counts with average latency since startup.
+pub async fn transcription_backends(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<TranscriptionBackendsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    Ok(Json(TranscriptionBackendsResponse {
+        backends: vec![state.transcription.status().await],
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AsrSampleResponse {
+    pub id: String,
+    pub room_id: String,
+    pub user_id: String,
+    pub text: String,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    pub rms_dbfs: f64,
+    pub vad_ms: u64,
+    pub language_score_delta: Option<f64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsrSamplesQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/tenant/{tenant_id}/admin/transcription/samples — low-confidence
+/// segments kept for model evaluation, newest first, without their audio.
+pub async fn transcription_samples(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<AsrSamplesQuery>,
+) -> Result<Json<Vec<AsrSampleResponse>>, ApiError> {
+    let tid = managed_tenant(&state, &auth, &tenant_id).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let samples = state.asr_metrics.list_samples(tid, limit).await?;
+    Ok(Json(
+        samples
+            .into_iter()
+            .map(|s| AsrSampleResponse {
+                id: s.id.map(|id| id.to_hex()).unwrap_or_default(),
+                room_id: s.room_id.to_hex(),
+                user_id: s.user_id.to_hex(),
+                text: s.text,
+                language: s.language,
+                confidence: s.confidence,
+                rms_dbfs: s.rms_dbfs,
+                vad_ms: s.vad_ms,
+                language_score_delta: s.language_score_delta,
+                created_at: s.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            })
+            .collect(),
+    ))
+}
+
+/// GET /api/tenant/{tenant_id}/admin/transcription/samples/{sample_id}/audio
+pub async fn transcription_sample_audio(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, sample_id)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let tid = managed_tenant(&state, &auth, &tenant_id).await?;
+    let sid = ObjectId::parse_str(&sample_id)
+        .map_err(|_| ApiError::BadRequest("Invalid sample_id".to_string()))?;
+    let sample = state
+        .asr_metrics
+        .samples
+        .find_by_id_in_tenant(tid, sid)
+        .await?;
+    let audio = sample
+        .audio
+        .ok_or_else(|| ApiError::NotFound("Sample audio not found".to_string()))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "audio/wav".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"asr-sample-{}.wav\"", sid),
+            ),
+        ],
+        audio.bytes,
+    )
+        .into_response())
+}
+
+async fn managed_tenant(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+) -> Result<ObjectId, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(tid)
+}
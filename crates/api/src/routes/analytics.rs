@@ -0,0 +1,305 @@
+//! Tenant analytics as CSV: on demand for a date range as a background
+//! task, or emailed to the admins each month (see
+//! [`crate::analytics_reports`]).
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{TaskCategory, role::permissions};
+use roomler_ai_services::analytics::{self, ModerationActivity, RelayActivity};
+use roomler_ai_services::relay_usage::megabytes;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError, extractors::permission::RequirePermission, routes::export::SLOT_RETRY_SECS,
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsExportRequest {
+    /// First day, `YYYY-MM-DD` (UTC).
+    pub from: String,
+    /// Last day, inclusive.
+    pub to: String,
+}
+
+/// POST /api/tenant/{tenant_id}/export/analytics — daily actives, messages
+/// and conference minutes for each day in the range. Answers `202` with a
+/// task to poll; the CSV is its download.
+pub async fn export(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    Json(body): Json<AnalyticsExportRequest>,
+) -> Result<Response, ApiError> {
+    let (from, to) = analytics::parse_range(
+        &body.from,
+        &body.to,
+        state.settings.analytics.max_range_days,
+    )
+    .map_err(ApiError::Validation)?;
+
+    let permit = state
+        .export_slots
+        .try_acquire(tid)
+        .ok_or(ApiError::TooManyRequests(SLOT_RETRY_SECS))?;
+
+    let task = state
+        .tasks
+        .create_task(
+            tid,
+            auth.user_id,
+            "export_analytics".to_string(),
+            TaskCategory::Export,
+            serde_json::json!({ "from": body.from, "to": body.to }),
+        )
+        .await?;
+    let task_id = task.id.unwrap();
+
+    let analytics_dao = Arc::clone(&state.analytics);
+    let task_store = Arc::clone(state.tasks.store());
+    let object_store = Arc::clone(&state.object_store);
+
+    state.tasks.spawn_task(task_id, async move {
+        let _permit = permit;
+        let rows = analytics_dao
+            .daily_activity(tid, from, to)
+            .await
+            .map_err(|e| format!("Failed to aggregate analytics: {}", e))?;
+        task_store
+            .update_progress(task_id, 80, Some("Aggregated activity".to_string()))
+            .await
+            .map_err(|e| format!("Failed to update progress: {}", e))?;
+
+        let file_name = format!("analytics-{}-{}.csv", from, to);
+        let key = format!("exports/{}-{}", task_id.to_hex(), file_name);
+        let storage_provider = object_store
+            .put(&key, analytics::to_csv(&rows))
+            .await
+            .map_err(|e| format!("Failed to write export file: {}", e))?;
+
+        task_store
+            .complete(task_id, Some(key), Some(file_name), Some(storage_provider))
+            .await
+            .map_err(|e| format!("Failed to complete task: {}", e))?;
+
+        Ok(())
+    });
+
+    let task_url = format!("/api/tenant/{}/task/{}", tid.to_hex(), task_id.to_hex());
+    Ok((
+        StatusCode::ACCEPTED,
+        [("Location", task_url.clone())],
+        Json(serde_json::json!({
+            "task_id": task_id.to_hex(),
+            "status": "pending",
+            "task_url": task_url,
+            "download_url": format!("{}/download", task_url),
+        })),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonthlyReport {
+    pub enabled: bool,
+    /// Month (`YYYY-MM`) of the last report sent.
+    #[serde(default, skip_deserializing)]
+    pub last_sent_for: Option<String>,
+}
+
+/// GET /api/tenant/{tenant_id}/analytics/report
+pub async fn get_report(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<MonthlyReport>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(MonthlyReport {
+        enabled: tenant.settings.monthly_analytics_report,
+        last_sent_for: tenant.analytics_report_sent_for,
+    }))
+}
+
+/// PUT /api/tenant/{tenant_id}/analytics/report — turn the monthly email of
+/// last month's CSV to the tenant's admins on or off.
+pub async fn set_report(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Json(body): Json<MonthlyReport>,
+) -> Result<Json<MonthlyReport>, ApiError> {
+    state
+        .tenants
+        .set_monthly_analytics_report(tid, body.enabled)
+        .await?;
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(MonthlyReport {
+        enabled: tenant.settings.monthly_analytics_report,
+        last_sent_for: tenant.analytics_report_sent_for,
+    }))
+}
+
+/// Entries in the `top_terms` and `flagged_messages` of the moderation
+/// report.
+const MODERATION_LIST_LIMIT: i64 = 20;
+
+/// A `YYYY-MM-DD` range of days, both inclusive.
+#[derive(Debug, Deserialize)]
+pub struct RangeQuery {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModerationReport {
+    pub days: Vec<ModerationActivity>,
+    pub masked: u64,
+    pub blocked: u64,
+    pub flagged: u64,
+    /// The filter entries matched most often in the range.
+    pub top_terms: Vec<TermCount>,
+    /// The latest flagged messages, newest first, regardless of the range.
+    pub flagged_messages: Vec<FlaggedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermCount {
+    pub term: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlaggedMessage {
+    pub message_id: Option<String>,
+    pub room_id: String,
+    pub user_id: String,
+    pub terms: Vec<String>,
+    pub created_at: String,
+}
+
+/// GET /api/tenant/{tenant_id}/analytics/moderation?from=&to= — what the
+/// profanity filter masked, blocked and flagged each day.
+pub async fn moderation(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<ModerationReport>, ApiError> {
+    let (from, to) = analytics::parse_range(
+        &query.from,
+        &query.to,
+        state.settings.analytics.max_range_days,
+    )
+    .map_err(ApiError::Validation)?;
+
+    let days = state.moderation_events.daily_counts(tid, from, to).await?;
+    let top_terms = state
+        .moderation_events
+        .top_terms(tid, from, to, MODERATION_LIST_LIMIT)
+        .await?
+        .into_iter()
+        .map(|(term, count)| TermCount { term, count })
+        .collect();
+    let flagged_messages = state
+        .moderation_events
+        .recent_flagged(tid, MODERATION_LIST_LIMIT)
+        .await?
+        .into_iter()
+        .map(|e| FlaggedMessage {
+            message_id: e.message_id.map(|id| id.to_hex()),
+            room_id: e.room_id.to_hex(),
+            user_id: e.user_id.to_hex(),
+            terms: e.terms,
+            created_at: e.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(ModerationReport {
+        masked: days.iter().map(|d| d.masked).sum(),
+        blocked: days.iter().map(|d| d.blocked).sum(),
+        flagged: days.iter().map(|d| d.flagged).sum(),
+        days,
+        top_terms,
+        flagged_messages,
+    }))
+}
+
+/// Conferences listed in `top_rooms` of the relay report.
+const RELAY_ROOM_LIMIT: i64 = 20;
+
+#[derive(Debug, Serialize)]
+pub struct RelayReport {
+    pub days: Vec<RelayActivity>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// The conferences that relayed the most in the range.
+    pub top_rooms: Vec<RoomRelay>,
+    /// Relayed so far in the current month, regardless of the range.
+    pub month_to_date_bytes: u64,
+    /// `turn.monthly_budget_mb` in bytes; `None` without a budget.
+    pub monthly_budget_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomRelay {
+    pub room_id: String,
+    pub room_name: Option<String>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// GET /api/tenant/{tenant_id}/analytics/relay?from=&to= — media the
+/// tenant's calls relayed through the TURN server each day.
+pub async fn relay(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<RelayReport>, ApiError> {
+    let (from, to) = analytics::parse_range(
+        &query.from,
+        &query.to,
+        state.settings.analytics.max_range_days,
+    )
+    .map_err(ApiError::Validation)?;
+
+    let days = state.relay_usage.daily(tid, from, to).await?;
+    let top = state
+        .relay_usage
+        .top_rooms(tid, from, to, RELAY_ROOM_LIMIT)
+        .await?;
+    let room_ids: Vec<ObjectId> = top.iter().map(|(id, _)| *id).collect();
+    let rooms = state.rooms.base.find_by_ids(&room_ids).await?;
+    let top_rooms = top
+        .into_iter()
+        .map(|(room_id, bytes)| RoomRelay {
+            room_id: room_id.to_hex(),
+            room_name: rooms
+                .iter()
+                .find(|r| r.id == Some(room_id))
+                .map(|r| r.name.clone()),
+            bytes_sent: bytes.sent,
+            bytes_received: bytes.received,
+        })
+        .collect();
+
+    let today = chrono::Utc::now().date_naive();
+    let (month_start, _) = analytics::current_month(today);
+    let budget = megabytes(state.settings.turn.monthly_budget_mb);
+    Ok(Json(RelayReport {
+        bytes_sent: days.iter().map(|d| d.bytes_sent).sum(),
+        bytes_received: days.iter().map(|d| d.bytes_received).sum(),
+        days,
+        top_rooms,
+        month_to_date_bytes: state.relay_usage.total(tid, month_start, today).await?,
+        monthly_budget_bytes: (budget > 0).then_some(budget),
+    }))
+}
@@ -0,0 +1,173 @@
+//! Avatars, custom emoji and thumbnails by content hash. See
+//! [`roomler_ai_services::assets`] for the URL scheme.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{File, Tenant, role::permissions};
+use roomler_ai_services::assets;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::permission::RequirePermission, state::AppState};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct AssetQuery {
+    pub expires: Option<i64>,
+    pub sig: Option<String>,
+}
+
+/// GET /api/asset/{tenant_id}/{checksum} — no auth; private tenants need a
+/// signed URL.
+pub async fn serve(
+    State(state): State<AppState>,
+    Path((tenant_id, checksum)): Path<(String, String)>,
+    Query(query): Query<AssetQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let checksum = checksum.to_ascii_lowercase();
+    if !assets::is_checksum(&checksum) {
+        return Err(ApiError::NotFound("Asset not found".to_string()));
+    }
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    if tenant.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Asset not found".to_string()));
+    }
+    let now = chrono::Utc::now().timestamp();
+    let cache_control = if tenant.settings.private_assets {
+        let (Some(expires), Some(sig)) = (query.expires, query.sig.as_deref()) else {
+            return Err(ApiError::Forbidden("Signed URL required".to_string()));
+        };
+        if !assets::verify(
+            &state.settings.assets.signing_secret,
+            tid,
+            &checksum,
+            expires,
+            sig,
+            now,
+        ) {
+            return Err(ApiError::Forbidden(
+                "Invalid or expired signature".to_string(),
+            ));
+        }
+        format!("private, max-age={}, immutable", expires - now)
+    } else {
+        format!("public, max-age={}, immutable", assets::MAX_AGE_SECS)
+    };
+
+    let file = state
+        .files
+        .find_by_checksum(tid, &checksum)
+        .await?
+        .filter(|f| assets::is_servable(&f.content_type))
+        .ok_or_else(|| ApiError::NotFound("Asset not found".to_string()))?;
+
+    let etag = format!("\"{}\"", checksum);
+    let builder = Response::builder()
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::ETAG, &etag)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        // SVGs can carry scripts; never run them on our origin.
+        .header(
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'none'; style-src 'unsafe-inline'; sandbox",
+        );
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if not_modified {
+        return Ok(builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    let stream = state
+        .object_store
+        .get_stream(file.storage_provider, &file.storage_key)
+        .await?;
+    Ok(builder
+        .header(header::CONTENT_TYPE, &file.content_type)
+        .header(header::CONTENT_LENGTH, file.size)
+        .body(Body::from_stream(stream))
+        .unwrap())
+}
+
+/// The asset URL for `file`, signed when the tenant's assets are private.
+/// None for files that aren't served as assets.
+pub(crate) fn url(state: &AppState, tenant: &Tenant, file: &File) -> Option<String> {
+    let checksum = file.checksum.as_deref()?;
+    if !assets::is_servable(&file.content_type) {
+        return None;
+    }
+    Some(for_tenant(
+        state,
+        tenant,
+        &assets::path(file.tenant_id, checksum),
+    ))
+}
+
+/// `url` as the tenant's clients can load it: signed when it is one of the
+/// tenant's asset paths and its assets are private, unchanged otherwise.
+pub(crate) fn for_tenant(state: &AppState, tenant: &Tenant, url: &str) -> String {
+    let Some(tenant_id) = tenant.id else {
+        return url.to_string();
+    };
+    if !tenant.settings.private_assets {
+        return url.to_string();
+    }
+    let Some(checksum) = assets::checksum_in(url, tenant_id) else {
+        return url.to_string();
+    };
+    let settings = &state.settings.assets;
+    let (path, _) = assets::signed_path(
+        &settings.signing_secret,
+        tenant_id,
+        checksum,
+        chrono::Utc::now().timestamp(),
+        settings.signed_url_ttl_secs,
+    );
+    path
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetSettings {
+    /// Serve assets only through signed, expiring URLs.
+    pub private: bool,
+}
+
+/// GET /api/tenant/{tenant_id}/assets
+pub async fn get_settings(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<AssetSettings>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(AssetSettings {
+        private: tenant.settings.private_assets,
+    }))
+}
+
+/// PUT /api/tenant/{tenant_id}/assets — require signed asset URLs or not.
+/// Public URLs handed out earlier stop working once assets are private.
+pub async fn set_settings(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Json(body): Json<AssetSettings>,
+) -> Result<Json<AssetSettings>, ApiError> {
+    state.tenants.set_private_assets(tid, body.private).await?;
+    Ok(Json(body))
+}
@@ -0,0 +1,117 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{ActorType, AuditChange, AuditLog, role::permissions};
+use roomler_ai_services::dao::{audit_log::AuditFilter, base::PaginationParams};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Exact action, e.g. `member.remove`.
+    pub action: Option<String>,
+    /// Everything done to one kind of target, e.g. `role`.
+    pub target_type: Option<String>,
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    /// RFC 3339; entries at or after this time.
+    pub from: Option<String>,
+    /// RFC 3339; entries before this time.
+    pub to: Option<String>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryResponse {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub actor_type: ActorType,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub changes: Vec<AuditChange>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+}
+
+/// GET /api/tenant/{tenant_id}/audit — the tenant's admin actions, newest
+/// first. Needs MANAGE_TENANT.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    let filter = AuditFilter {
+        action: query.action,
+        target_type: query.target_type,
+        actor_id: parse_id(query.actor_id.as_deref(), "actor_id")?,
+        target_id: parse_id(query.target_id.as_deref(), "target_id")?,
+        from: parse_time(query.from.as_deref(), "from")?,
+        to: parse_time(query.to.as_deref(), "to")?,
+    };
+    let defaults = PaginationParams::default();
+    let params = PaginationParams {
+        page: query.page.unwrap_or(defaults.page).max(1),
+        per_page: query.per_page.unwrap_or(defaults.per_page),
+        before: None,
+    };
+
+    let result = state.audit_log.find(tid, &filter, &params).await?;
+    let items: Vec<AuditEntryResponse> = result.items.into_iter().map(to_response).collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "total": result.total,
+        "page": result.page,
+        "per_page": result.per_page,
+        "total_pages": result.total_pages,
+    })))
+}
+
+fn parse_id(value: Option<&str>, name: &str) -> Result<Option<ObjectId>, ApiError> {
+    value
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid {name}")))
+}
+
+fn parse_time(value: Option<&str>, name: &str) -> Result<Option<DateTime>, ApiError> {
+    value
+        .map(DateTime::parse_rfc3339_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid {name}, expected RFC 3339")))
+}
+
+fn to_response(entry: AuditLog) -> AuditEntryResponse {
+    AuditEntryResponse {
+        id: entry.id.unwrap().to_hex(),
+        actor_id: entry.actor_id.map(|id| id.to_hex()),
+        actor_type: entry.actor_type,
+        action: entry.action,
+        target_type: entry.target_type,
+        target_id: entry.target_id.map(|id| id.to_hex()),
+        changes: entry.changes,
+        ip: entry.metadata.ip,
+        user_agent: entry.metadata.user_agent,
+        created_at: entry.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
@@ -3,11 +3,16 @@ use axum::{
     extract::State,
     http::{HeaderMap, StatusCode, header},
 };
+use chrono::SecondsFormat;
 use nanoid::nanoid;
+use roomler_ai_db::models::{CodePurpose, EmailPrefs, NotificationPrefs, PrivacyPrefs};
+use roomler_ai_services::email::EmailTemplate;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    email_queue, error::ApiError, extractors::auth::AuthUser, routes::sso, state::AppState,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct RegisterRequest {
@@ -52,6 +57,18 @@ pub struct ActivateRequest {
     pub token: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub user_id: String,
+    pub token: String,
+    pub password: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MessageResponse {
     pub message: String,
@@ -75,6 +92,7 @@ pub async fn register(
     State(state): State<AppState>,
     Json(body): Json<RegisterRequest>,
 ) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    sso::ensure_not_captured(&state, &body.email).await?;
     let password_hash = state.auth.hash_password(&body.password)?;
 
     let user = state
@@ -91,42 +109,41 @@ pub async fn register(
 
     // Generate activation code and send email
     let token = nanoid!(7);
+    let ttl_minutes = state.settings.email.activation_token_ttl_minutes;
     if let Err(e) = state
         .activation_codes
-        .create(
-            user_id,
-            token.clone(),
-            state.settings.email.activation_token_ttl_minutes,
-        )
+        .create(user_id, CodePurpose::Activation, token.clone(), ttl_minutes)
         .await
     {
         warn!("Failed to create activation code: {:?}", e);
-    } else if let Some(ref email_svc) = state.email {
+    } else {
         let activation_url = format!(
             "{}/auth/activate?userId={}&token={}",
             state.settings.app.frontend_url,
             user_id.to_hex(),
             token
         );
-        if let Err(e) = email_svc
-            .send_activation(
-                &body.email,
-                &body.display_name,
-                &activation_url,
-                state.settings.email.activation_token_ttl_minutes,
-            )
-            .await
-        {
-            warn!("Failed to send activation email: {:?}", e);
-        }
+        let template = EmailTemplate::Activation {
+            display_name: body.display_name.clone(),
+            activation_url,
+            ttl_minutes,
+        };
+        email_queue::send(&state, None, user_id, &body.email, template).await;
     }
 
     // Create a default tenant if requested
     if let (Some(tenant_name), Some(tenant_slug)) = (body.tenant_name, body.tenant_slug) {
-        state
+        let tenant = state
             .tenants
-            .create(tenant_name, tenant_slug, user_id)
+            .create(tenant_name, tenant_slug, user_id, false)
             .await?;
+        if let Err(e) = state
+            .onboarding
+            .provision_tenant(tenant.id.unwrap(), user_id)
+            .await
+        {
+            warn!("Failed to provision default channels: {:?}", e);
+        }
     }
 
     // Auto-accept invite if invite_code provided
@@ -172,6 +189,7 @@ pub async fn login(
     if !valid {
         return Err(ApiError::Unauthorized("Invalid credentials".to_string()));
     }
+    sso::ensure_not_captured(&state, &user.email).await?;
 
     if !user.is_verified {
         return Err(ApiError::Unauthorized(
@@ -230,6 +248,216 @@ pub async fn me(
     }))
 }
 
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub connection_id: String,
+    pub connected_at: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionsResponse {
+    /// Open WebSocket connections on this instance.
+    pub active: usize,
+    /// Connections the user's plans allow at once.
+    pub limit: u32,
+    /// What a connection past the limit does: `evict_oldest` or `reject`.
+    pub policy: &'static str,
+    /// Oldest first.
+    pub items: Vec<SessionResponse>,
+}
+
+/// GET /api/auth/me/sessions — the caller's open WebSocket connections and
+/// their session limit.
+pub async fn sessions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<SessionsResponse>, ApiError> {
+    let items: Vec<SessionResponse> = state
+        .ws_storage
+        .user_connections(&auth.user_id)
+        .into_iter()
+        .map(|(connection_id, c)| SessionResponse {
+            connection_id,
+            connected_at: c.connected_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ip: c.client.ip.map(|ip| ip.to_string()),
+            user_agent: c.client.user_agent,
+        })
+        .collect();
+
+    Ok(Json(SessionsResponse {
+        active: items.len(),
+        limit: crate::sessions::limit(&state, auth.user_id).await,
+        policy: state.settings.ws.session_limit_policy.as_str(),
+        items,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountRequest {
+    /// Required when the account has a password.
+    pub password: Option<String>,
+}
+
+/// DELETE /api/auth/me — delete the caller's account. Sign-in and all
+/// tokens stop working at once and the user leaves every tenant, channel
+/// and call; messages stay, shown as from a deleted user. The profile is
+/// anonymized after the grace period. Owners must hand their tenants over
+/// first.
+pub async fn delete_me(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<DeleteAccountRequest>,
+) -> Result<(HeaderMap, Json<serde_json::Value>), ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    if let Some(hash) = &user.password_hash {
+        let password = body.password.as_deref().unwrap_or_default();
+        if !state.auth.verify_password(password, hash)? {
+            return Err(ApiError::Unauthorized("Invalid password".to_string()));
+        }
+    }
+    let owned: Vec<String> = state
+        .tenants
+        .find_user_tenants(auth.user_id)
+        .await?
+        .into_iter()
+        .filter(|t| t.owner_id == auth.user_id)
+        .map(|t| t.name)
+        .collect();
+    if !owned.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Transfer ownership of {} before deleting your account",
+            owned.join(", ")
+        )));
+    }
+
+    let purge_at = crate::account_deletion::delete(&state, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Account already deleted".to_string()))?;
+
+    let mut headers = HeaderMap::new();
+    let cookie = "access_token=; HttpOnly; Path=/; SameSite=Lax; Max-Age=0";
+    headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+    Ok((
+        headers,
+        Json(serde_json::json!({
+            "deleted": true,
+            "purge_at": purge_at.try_to_rfc3339_string().unwrap_or_default(),
+        })),
+    ))
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreferencesResponse {
+    pub notifications: NotificationPrefs,
+    pub privacy: PrivacyPrefs,
+    pub email: EmailPrefs,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    /// Replaces the notification preferences as a whole.
+    pub notifications: Option<NotificationPrefs>,
+    pub privacy: Option<UpdatePrivacyRequest>,
+    pub email: Option<UpdateEmailPrefsRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEmailPrefsRequest {
+    pub invites: Option<bool>,
+    pub mention_digest: Option<bool>,
+    pub billing_receipts: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePrivacyRequest {
+    pub hide_read_receipts: Option<bool>,
+    pub hide_typing: Option<bool>,
+    pub hide_presence: Option<bool>,
+    pub share_voice_samples: Option<bool>,
+}
+
+/// GET /api/auth/me/preferences
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<PreferencesResponse>, ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+
+    Ok(Json(PreferencesResponse {
+        notifications: user.notification_preferences,
+        privacy: user.privacy,
+        email: user.email_preferences,
+    }))
+}
+
+/// PUT /api/auth/me/preferences
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<UpdatePreferencesRequest>,
+) -> Result<Json<PreferencesResponse>, ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+
+    let mut privacy = user.privacy;
+    if let Some(p) = &body.privacy {
+        privacy.hide_read_receipts = p.hide_read_receipts.unwrap_or(privacy.hide_read_receipts);
+        privacy.hide_typing = p.hide_typing.unwrap_or(privacy.hide_typing);
+        privacy.hide_presence = p.hide_presence.unwrap_or(privacy.hide_presence);
+        privacy.share_voice_samples = p.share_voice_samples.unwrap_or(privacy.share_voice_samples);
+    }
+    let notifications = body.notifications.unwrap_or(user.notification_preferences);
+    let mut email = user.email_preferences;
+    if let Some(e) = &body.email {
+        email.invites = e.invites.unwrap_or(email.invites);
+        email.mention_digest = e.mention_digest.unwrap_or(email.mention_digest);
+        email.billing_receipts = e.billing_receipts.unwrap_or(email.billing_receipts);
+    }
+
+    state
+        .users
+        .update_preferences(
+            auth.user_id,
+            Some(&notifications),
+            Some(&privacy),
+            Some(&email),
+        )
+        .await?;
+
+    // Going dark: tell everyone else the user is now offline and idle.
+    if privacy.hide_presence && !user.privacy.hide_presence {
+        let others: Vec<_> = state
+            .tenants
+            .find_co_member_user_ids(auth.user_id)
+            .await?
+            .into_iter()
+            .filter(|id| *id != auth.user_id)
+            .collect();
+        let event = serde_json::json!({
+            "type": "presence:update",
+            "data": {
+                "user_id": auth.user_id.to_hex(),
+                "presence": "offline",
+                "activity": crate::presence::PresenceActivity::default(),
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &others,
+            &event,
+        )
+        .await;
+    }
+
+    Ok(Json(PreferencesResponse {
+        notifications,
+        privacy,
+        email,
+    }))
+}
+
 pub async fn refresh(
     State(state): State<AppState>,
     Json(body): Json<RefreshRequest>,
@@ -240,6 +468,9 @@ pub async fn refresh(
         .map_err(|_| ApiError::Unauthorized("Invalid user ID".to_string()))?;
 
     let user = state.users.base.find_by_id(user_id).await?;
+    if user.deleted_at.is_some() {
+        return Err(ApiError::Unauthorized("Account deleted".to_string()));
+    }
 
     let tokens = state
         .auth
@@ -278,7 +509,7 @@ pub async fn activate(
 
     let _code = state
         .activation_codes
-        .find_valid(user_id, &body.token)
+        .find_valid(user_id, CodePurpose::Activation, &body.token)
         .await
         .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
         .ok_or_else(|| ApiError::BadRequest("Invalid or expired activation token".to_string()))?;
@@ -292,27 +523,274 @@ pub async fn activate(
         .map_err(|e| ApiError::Internal(format!("Failed to activate user: {}", e)))?;
 
     // Delete used activation code
-    let _ = state.activation_codes.delete_for_user(user_id).await;
-
-    // Send success email (non-fatal)
-    if let Some(ref email_svc) = state.email {
-        let user = state
-            .users
-            .base
-            .find_by_id(user_id)
-            .await
-            .map_err(|e| ApiError::Internal(format!("User not found: {}", e)))?;
-        let login_url = format!("{}/auth/login", state.settings.app.frontend_url);
-        if let Err(e) = email_svc
-            .send_activation_success(&user.email, &user.display_name, &login_url)
-            .await
-        {
-            warn!("Failed to send activation success email: {:?}", e);
+    let _ = state
+        .activation_codes
+        .delete_for_user(user_id, CodePurpose::Activation)
+        .await;
+
+    let user = state
+        .users
+        .base
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| ApiError::Internal(format!("User not found: {}", e)))?;
+    let template = EmailTemplate::ActivationSuccess {
+        display_name: user.display_name,
+        login_url: format!("{}/auth/login", state.settings.app.frontend_url),
+    };
+    email_queue::send(&state, None, user_id, &user.email, template).await;
+
+    Ok(Json(MessageResponse {
+        message: "Account activated successfully. You can now sign in.".to_string(),
+    }))
+}
+
+/// POST /api/auth/password/forgot — email a password reset link. Answers
+/// the same whether or not the address has an account.
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(body): Json<ForgotPasswordRequest>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    if let Ok(user) = state.users.find_by_email(body.email.trim()).await
+        && user.deleted_at.is_none()
+        && let Some(user_id) = user.id
+    {
+        let token = nanoid!(32);
+        let ttl_minutes = state.settings.email.password_reset_ttl_minutes;
+        state
+            .activation_codes
+            .create(
+                user_id,
+                CodePurpose::PasswordReset,
+                token.clone(),
+                ttl_minutes,
+            )
+            .await?;
+        let reset_url = format!(
+            "{}/auth/reset-password?userId={}&token={}",
+            state.settings.app.frontend_url,
+            user_id.to_hex(),
+            token
+        );
+        let template = EmailTemplate::PasswordReset {
+            display_name: user.display_name,
+            reset_url,
+            ttl_minutes,
+        };
+        email_queue::send(&state, None, user_id, &user.email, template).await;
+    }
+
+    Ok(Json(MessageResponse {
+        message: "If an account exists for this email, a reset link has been sent.".to_string(),
+    }))
+}
+
+/// POST /api/auth/password/reset — set a new password with the token from
+/// a reset email. The token works once.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(body): Json<ResetPasswordRequest>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let user_id = bson::oid::ObjectId::parse_str(&body.user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user ID".to_string()))?;
+    if body.password.is_empty() {
+        return Err(ApiError::Validation("Password is required".to_string()));
+    }
+
+    state
+        .activation_codes
+        .find_valid(user_id, CodePurpose::PasswordReset, &body.token)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Invalid or expired reset token".to_string()))?;
+
+    let password_hash = state.auth.hash_password(&body.password)?;
+    // Following the emailed link proves the address, too.
+    state
+        .users
+        .base
+        .update_by_id(
+            user_id,
+            bson::doc! { "$set": { "password_hash": password_hash, "is_verified": true } },
+        )
+        .await?;
+    state
+        .activation_codes
+        .delete_for_user(user_id, CodePurpose::PasswordReset)
+        .await?;
+
+    Ok(Json(MessageResponse {
+        message: "Password changed. You can now sign in.".to_string(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeEmailRequest {
+    pub email: String,
+    /// Required when the account has a password.
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmEmailRequest {
+    pub user_id: String,
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailResponse {
+    pub email: String,
+    /// The change waiting for the new address to be confirmed.
+    pub pending: Option<PendingEmailResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingEmailResponse {
+    pub email: String,
+    pub expires_at: String,
+}
+
+async fn email_response(
+    state: &AppState,
+    user_id: bson::oid::ObjectId,
+    email: String,
+) -> Result<EmailResponse, ApiError> {
+    let pending = state
+        .activation_codes
+        .find_pending_email_change(user_id)
+        .await?
+        .and_then(|code| {
+            Some(PendingEmailResponse {
+                email: code.new_email?,
+                expires_at: code
+                    .valid_to
+                    .to_chrono()
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+            })
+        });
+    Ok(EmailResponse { email, pending })
+}
+
+/// GET /api/auth/me/email — the caller's email and their pending change.
+pub async fn get_email(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<EmailResponse>, ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    Ok(Json(
+        email_response(&state, auth.user_id, user.email).await?,
+    ))
+}
+
+/// PUT /api/auth/me/email — start changing the caller's email. A link is
+/// sent to the new address; the email only changes once it is followed.
+/// Asking again replaces the pending change.
+pub async fn change_email(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<ChangeEmailRequest>,
+) -> Result<(StatusCode, Json<EmailResponse>), ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    if let Some(hash) = &user.password_hash {
+        let password = body.password.as_deref().unwrap_or_default();
+        if !state.auth.verify_password(password, hash)? {
+            return Err(ApiError::Unauthorized("Invalid password".to_string()));
         }
     }
+    let new_email = body.email.trim();
+    let valid = new_email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        && !new_email.contains(char::is_whitespace);
+    if !valid {
+        return Err(ApiError::Validation("Invalid email address".to_string()));
+    }
+    if new_email.eq_ignore_ascii_case(&user.email) {
+        return Err(ApiError::Validation(
+            "This is already your email".to_string(),
+        ));
+    }
+    if state.users.find_by_email(new_email).await.is_ok() {
+        return Err(ApiError::Conflict("Email is already in use".to_string()));
+    }
+
+    let token = nanoid!(32);
+    let ttl_minutes = state.settings.email.email_change_ttl_minutes;
+    state
+        .activation_codes
+        .create_email_change(
+            auth.user_id,
+            new_email.to_string(),
+            token.clone(),
+            ttl_minutes,
+        )
+        .await?;
+    let confirm_url = format!(
+        "{}/auth/confirm-email?userId={}&token={}",
+        state.settings.app.frontend_url,
+        auth.user_id.to_hex(),
+        token
+    );
+    let template = EmailTemplate::EmailChange {
+        display_name: user.display_name,
+        new_email: new_email.to_string(),
+        confirm_url,
+        ttl_minutes,
+    };
+    email_queue::send(&state, None, auth.user_id, new_email, template).await;
+
+    let response = email_response(&state, auth.user_id, user.email).await?;
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// DELETE /api/auth/me/email — cancel the caller's pending email change.
+pub async fn cancel_email_change(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    state
+        .activation_codes
+        .delete_for_user(auth.user_id, CodePurpose::EmailChange)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/email/confirm — switch to the new email with the token
+/// sent to it. The old address is told about the change. Signed-in
+/// sessions keep working.
+pub async fn confirm_email(
+    State(state): State<AppState>,
+    Json(body): Json<ConfirmEmailRequest>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let user_id = bson::oid::ObjectId::parse_str(&body.user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user ID".to_string()))?;
+    let new_email = state
+        .activation_codes
+        .find_valid(user_id, CodePurpose::EmailChange, &body.token)
+        .await?
+        .and_then(|code| code.new_email)
+        .ok_or_else(|| ApiError::BadRequest("Invalid or expired confirmation token".to_string()))?;
+
+    let user = state.users.base.find_by_id(user_id).await?;
+    // The unique email index settles a race with a sign-up or another
+    // change to the same address.
+    if !state.users.change_email(user_id, &new_email).await? {
+        return Err(ApiError::BadRequest(
+            "Invalid or expired confirmation token".to_string(),
+        ));
+    }
+    state
+        .activation_codes
+        .delete_for_user(user_id, CodePurpose::EmailChange)
+        .await?;
+
+    let template = EmailTemplate::EmailChanged {
+        display_name: user.display_name,
+        new_email,
+    };
+    email_queue::send(&state, None, user_id, &user.email, template).await;
 
     Ok(Json(MessageResponse {
-        message: "Account activated successfully. You can now sign in.".to_string(),
+        message: "Email changed. Sign in with your new email from now on.".to_string(),
     }))
 }
 
@@ -339,29 +817,7 @@ async fn auto_accept_invite(
         ));
     }
 
-    // Determine roles
-    let role_ids = if invite.assign_role_ids.is_empty() {
-        let member_role = state
-            .tenants
-            .get_role_by_name(invite.tenant_id, "member")
-            .await?;
-        vec![member_role.id.unwrap()]
-    } else {
-        invite.assign_role_ids.clone()
-    };
-
-    // Add the user to the tenant
-    state
-        .tenants
-        .add_member(invite.tenant_id, user_id, role_ids, Some(invite.inviter_id))
-        .await?;
-
-    // Increment use count
-    state
-        .invites
-        .increment_use_count(invite.id.unwrap())
-        .await
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    crate::routes::invite::redeem(state, &invite, user_id).await?;
 
     let tenant = state.tenants.base.find_by_id(invite.tenant_id).await?;
 
@@ -5,8 +5,8 @@ use axum::{
     response::Response,
 };
 use bson::oid::ObjectId;
+use roomler_ai_db::models::StorageProvider;
 use serde::Serialize;
-use tokio::io::AsyncReadExt;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 use roomler_ai_services::dao::base::PaginationParams;
@@ -106,19 +106,33 @@ pub async fn download(
         .ok_or_else(|| ApiError::NotFound("Task has no file".to_string()))?;
     let file_name = task.file_name.unwrap_or_else(|| "download".to_string());
 
-    let mut contents = Vec::new();
-    let mut f = tokio::fs::File::open(&file_path)
-        .await
-        .map_err(|_| ApiError::NotFound("File not found on disk".to_string()))?;
-    f.read_to_end(&mut contents)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to read file: {}", e)))?;
+    // Older tasks have no provider and an absolute local path, which the
+    // local backend resolves as-is.
+    let stream = state
+        .object_store
+        .get_stream(
+            task.storage_provider.unwrap_or(StorageProvider::Local),
+            &file_path,
+        )
+        .await?;
 
     // Determine content type from file name
     let content_type = if file_name.ends_with(".xlsx") {
         "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
     } else if file_name.ends_with(".pdf") {
         "application/pdf"
+    } else if file_name.ends_with(".csv") {
+        "text/csv"
+    } else if file_name.ends_with(".json") {
+        "application/json"
+    } else if file_name.ends_with(".md") {
+        "text/markdown; charset=utf-8"
+    } else if file_name.ends_with(".html") {
+        "text/html; charset=utf-8"
+    } else if file_name.ends_with(".webm") {
+        "video/webm"
+    } else if file_name.ends_with(".mp4") {
+        "video/mp4"
     } else {
         "application/octet-stream"
     };
@@ -129,6 +143,6 @@ pub async fn download(
             "Content-Disposition",
             format!("attachment; filename=\"{}\"", file_name),
         )
-        .body(Body::from(contents))
+        .body(Body::from_stream(stream))
         .unwrap())
 }
@@ -0,0 +1,286 @@
+//! Bot tokens and the inbound hook they post through. Managing tokens
+//! requires MANAGE_TENANT; `POST /api/hook/{bot_token}` needs nothing but
+//! the token, so CI systems and other services can post into the token's
+//! room without a user account. Hook calls count against the tenant's
+//! daily quota (see [`roomler_ai_services::api_quota`]); responses carry
+//! `X-Quota-Limit`, `X-Quota-Remaining` and `X-Quota-Reset`, and calls over
+//! the quota get 429 until the next UTC midnight.
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{BotToken, Embed, actions, role::permissions, webhook_events};
+use roomler_ai_services::{
+    api_quota::{self, QuotaStatus},
+    bot_tokens, embeds,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBotRequest {
+    pub name: String,
+    pub room_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BotResponse {
+    pub id: String,
+    pub name: String,
+    pub room_id: String,
+    /// The full token, only in the response to its creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub token_hint: String,
+    pub created_by: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HookMessageRequest {
+    pub text: String,
+    /// Bold heading above `text`.
+    pub title: Option<String>,
+    /// Link for the heading.
+    pub url: Option<String>,
+    /// Key-value cards and other embeds; see
+    /// [`roomler_ai_services::embeds`].
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+}
+
+impl From<BotToken> for BotResponse {
+    fn from(b: BotToken) -> Self {
+        let rfc3339 = |t: Option<DateTime>| t.and_then(|t| t.try_to_rfc3339_string().ok());
+        Self {
+            id: b.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: b.name,
+            room_id: b.room_id.to_hex(),
+            token: None,
+            token_hint: b.token_hint,
+            created_by: b.created_by.to_hex(),
+            last_used_at: rfc3339(b.last_used_at),
+            revoked_at: rfc3339(b.revoked_at),
+            created_at: b.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/bot — newest first, revoked ones included.
+pub async fn list(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<Vec<BotResponse>>, ApiError> {
+    let bots = state.bot_tokens.find_for_tenant(tid).await?;
+    Ok(Json(bots.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/bot — the response carries the token; it
+/// can't be read again.
+pub async fn create(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<CreateBotRequest>,
+) -> Result<(StatusCode, Json<BotResponse>), ApiError> {
+    let rid = ObjectId::parse_str(&body.room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.is_dm() {
+        return Err(ApiError::Validation(
+            "Bots can't post into direct messages".to_string(),
+        ));
+    }
+    let name = body.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > bot_tokens::MAX_NAME_LEN {
+        return Err(ApiError::Validation(format!(
+            "name must be 1-{} characters",
+            bot_tokens::MAX_NAME_LEN
+        )));
+    }
+
+    let token = bot_tokens::generate();
+    let now = DateTime::now();
+    let bot = state
+        .bot_tokens
+        .create(&BotToken {
+            id: None,
+            tenant_id: tid,
+            room_id: rid,
+            name,
+            token_hash: bot_tokens::hash(&token),
+            token_hint: bot_tokens::hint(&token),
+            created_by: auth.user_id,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::BOT_CREATE,
+        bot.id,
+        vec![
+            audit::change("name", None, Some(bot.name.clone().into())),
+            audit::change("room_id", None, Some(rid.to_hex().into())),
+        ],
+    )
+    .await;
+
+    let mut response = BotResponse::from(bot);
+    response.token = Some(token);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/bot/{bot_id} — revoke the token. Its
+/// messages stay.
+pub async fn revoke(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, bot_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let id = ObjectId::parse_str(&bot_id)
+        .map_err(|_| ApiError::BadRequest("Invalid bot_id".to_string()))?;
+    let bot = state.bot_tokens.base.find_by_id_in_tenant(tid, id).await?;
+
+    if state.bot_tokens.revoke(tid, id).await? {
+        audit::record(
+            &state,
+            tid,
+            auth.user_id,
+            &client,
+            actions::BOT_REVOKE,
+            Some(id),
+            vec![audit::change("name", Some(bot.name.into()), None)],
+        )
+        .await;
+    }
+
+    Ok(Json(serde_json::json!({ "revoked": true })))
+}
+
+/// POST /api/hook/{bot_token} — post `text` into the token's room.
+pub async fn post_message(
+    State(state): State<AppState>,
+    Path(bot_token): Path<String>,
+    Json(body): Json<HookMessageRequest>,
+) -> Result<Response, ApiError> {
+    let bot = state
+        .bot_tokens
+        .find_active(&bot_tokens::hash(&bot_token))
+        .await?
+        .ok_or_else(|| ApiError::Unauthorized("Invalid or revoked bot token".to_string()))?;
+    let bot_id = bot
+        .id
+        .ok_or_else(|| ApiError::NotFound("Bot not found".to_string()))?;
+
+    let tenant = state.tenants.base.find_by_id(bot.tenant_id).await?;
+    let now = chrono::Utc::now();
+    // Refused calls count too, so a bot retrying in a loop stays refused.
+    let used = state
+        .api_usage
+        .record(bot.tenant_id, bot_id, &api_quota::day(now))
+        .await;
+    let quota = QuotaStatus::new(api_quota::daily_limit(&tenant), used, now);
+    let headers = quota_headers(&quota, now);
+    if quota.exceeded() {
+        let error = ApiError::TooManyRequests(quota.reset_in_secs);
+        return Ok((headers, error).into_response());
+    }
+
+    let content =
+        bot_tokens::format_message(&body.text, body.title.as_deref(), body.url.as_deref())
+            .map_err(ApiError::Validation)?;
+    embeds::validate(&body.embeds).map_err(ApiError::Validation)?;
+    // The room may have been deleted since the token was made.
+    state
+        .rooms
+        .base
+        .find_by_id_in_tenant(bot.tenant_id, bot.room_id)
+        .await?;
+
+    let message = crate::profanity::post(
+        &state,
+        bot.tenant_id,
+        bot.room_id,
+        bot_id,
+        content,
+        |content| {
+            state
+                .messages
+                .create_bot(bot.tenant_id, bot.room_id, bot_id, content, body.embeds)
+        },
+    )
+    .await?;
+    state.bot_tokens.touch(bot_id).await?;
+
+    let names = HashMap::from([(bot_id, bot.name)]);
+    let response = super::message::to_response(message, &names, None);
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(bot.room_id)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": &response,
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        bot.tenant_id,
+        &member_ids,
+        &event,
+    )
+    .await;
+    crate::webhooks::dispatch(
+        &state,
+        bot.tenant_id,
+        webhook_events::MESSAGE_CREATE,
+        serde_json::to_value(&response).unwrap_or_default(),
+    );
+
+    Ok((StatusCode::CREATED, headers, Json(response)).into_response())
+}
+
+fn quota_headers(quota: &QuotaStatus, now: chrono::DateTime<chrono::Utc>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in [
+        ("x-quota-limit", quota.limit),
+        ("x-quota-remaining", quota.remaining),
+        ("x-quota-reset", api_quota::reset_at(now).timestamp() as u64),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
+    }
+    headers
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use chrono::Utc;
+use roomler_ai_db::models::{Room, User};
+use roomler_ai_services::calendar::{self, Event, Method, Person};
+use serde::Serialize;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+/// Most attendees listed on a conference; the organizer isn't counted.
+const MAX_ATTENDEES: usize = 100;
+
+/// Most conferences in a calendar feed, soonest scheduled first.
+const MAX_FEED_EVENTS: usize = 500;
+
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedResponse {
+    pub url: String,
+    /// The same feed for calendar apps that subscribe to `webcal://` links.
+    pub webcal_url: String,
+}
+
+/// GET /api/tenant/{tenant_id}/conference/{conference_id}/ics — the
+/// scheduled conference in the room `conference_id` as an iCalendar file.
+/// Organizers get every member as an attendee; other members only
+/// themselves.
+pub async fn conference(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, conference_id)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&conference_id)
+        .map_err(|_| ApiError::BadRequest("Invalid conference_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let is_organizer = room.organizer_ids().contains(&auth.user_id);
+    if !is_organizer && !state.rooms.is_member(rid, auth.user_id).await? {
+        return Err(ApiError::Forbidden(
+            "Not a member of this conference".to_string(),
+        ));
+    }
+    if calendar::schedule(&room.conference()).is_none() {
+        return Err(ApiError::NotFound(
+            "The conference isn't scheduled".to_string(),
+        ));
+    }
+
+    let organizer_id = room.organizer_ids()[0];
+    let attendee_ids: Vec<ObjectId> = if is_organizer {
+        state
+            .rooms
+            .find_member_user_ids(rid)
+            .await?
+            .into_iter()
+            .filter(|id| *id != organizer_id)
+            .take(MAX_ATTENDEES)
+            .collect()
+    } else {
+        vec![auth.user_id]
+    };
+    let mut people = people(&state, &[&attendee_ids[..], &[organizer_id]].concat()).await?;
+    let organizer = people.remove(&organizer_id);
+    let attendees = attendee_ids
+        .iter()
+        .filter_map(|id| people.remove(id))
+        .collect();
+
+    let events: Vec<Event> = conference_event(&state, &room, organizer, attendees)
+        .into_iter()
+        .collect();
+    let body = calendar::render(&room.name, Method::Publish, &events, Utc::now());
+    Ok(ics_response(body, Some(format!("conference-{}.ics", rid))))
+}
+
+/// POST /api/auth/me/calendar-feed — the URL of the caller's calendar
+/// feed, turning it on on first use. Anyone with the URL can read the
+/// feed, so it is only shown to its owner.
+pub async fn create_feed(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<CalendarFeedResponse>, ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    let token = match user.calendar_feed_token {
+        Some(token) => token,
+        None => {
+            let token = nanoid::nanoid!(32);
+            state
+                .users
+                .set_calendar_feed_token(auth.user_id, Some(&token))
+                .await?;
+            token
+        }
+    };
+
+    let url = format!("{}/api/calendar/{}", state.settings.oauth.base_url, token);
+    let webcal_url = match url.split_once("://") {
+        Some((_, rest)) => format!("webcal://{rest}"),
+        None => url.clone(),
+    };
+    Ok(Json(CalendarFeedResponse { url, webcal_url }))
+}
+
+/// DELETE /api/auth/me/calendar-feed — turn the caller's calendar feed off.
+/// The URL stops working; turning it on again gives a new one.
+pub async fn revoke_feed(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    state
+        .users
+        .set_calendar_feed_token(auth.user_id, None)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/calendar/{token} — public. The feed owner's upcoming scheduled
+/// conferences, across their tenants, for calendar apps to subscribe to.
+pub async fn feed(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, ApiError> {
+    let user = state
+        .users
+        .find_by_calendar_feed_token(&token)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Calendar feed not found".to_string()))?;
+    let user_id = user
+        .id
+        .ok_or_else(|| ApiError::Internal("User has no id".to_string()))?;
+
+    let now = Utc::now();
+    let rooms: Vec<Room> = state
+        .rooms
+        .find_scheduled_for_user(user_id)
+        .await?
+        .into_iter()
+        .filter(|room| calendar::is_upcoming(&room.conference(), now))
+        .take(MAX_FEED_EVENTS)
+        .collect();
+    let organizer_ids: Vec<ObjectId> = rooms.iter().map(|r| r.organizer_ids()[0]).collect();
+    let organizers = people(&state, &organizer_ids).await?;
+    let owner = person(&user);
+
+    let events: Vec<Event> = rooms
+        .iter()
+        .filter_map(|room| {
+            let organizer = organizers.get(&room.organizer_ids()[0]).cloned();
+            let attendees = match &organizer {
+                Some(organizer) if *organizer == owner => Vec::new(),
+                _ => vec![owner.clone()],
+            };
+            conference_event(&state, room, organizer, attendees)
+        })
+        .collect();
+    Ok(ics_response(
+        calendar::render("Roomler", Method::Publish, &events, now),
+        None,
+    ))
+}
+
+/// An invitation from the organizers of the upcoming scheduled conferences
+/// among `room_ids` to `email`, for invite emails. `None` when there are
+/// none; failing to build it is logged, so the email still goes out.
+pub(crate) async fn invitation(
+    state: &AppState,
+    room_ids: &[ObjectId],
+    email: &str,
+) -> Option<String> {
+    if room_ids.is_empty() {
+        return None;
+    }
+    match build_invitation(state, room_ids, email).await {
+        Ok(invitation) => invitation,
+        Err(e) => {
+            tracing::warn!(%e, "Failed to build the conference invitation");
+            None
+        }
+    }
+}
+
+async fn build_invitation(
+    state: &AppState,
+    room_ids: &[ObjectId],
+    email: &str,
+) -> Result<Option<String>, ApiError> {
+    let now = Utc::now();
+    let rooms: Vec<Room> = state
+        .rooms
+        .base
+        .find_by_ids(room_ids)
+        .await?
+        .into_iter()
+        .filter(|room| room.deleted_at.is_none() && calendar::is_upcoming(&room.conference(), now))
+        .collect();
+    if rooms.is_empty() {
+        return Ok(None);
+    }
+    let organizer_ids: Vec<ObjectId> = rooms.iter().map(|r| r.organizer_ids()[0]).collect();
+    let organizers = people(state, &organizer_ids).await?;
+    let invitee = Person {
+        name: email.to_string(),
+        email: email.to_string(),
+    };
+
+    let events: Vec<Event> = rooms
+        .iter()
+        .filter_map(|room| {
+            let organizer = organizers.get(&room.organizer_ids()[0]).cloned();
+            conference_event(state, room, organizer, vec![invitee.clone()])
+        })
+        .collect();
+    Ok(Some(calendar::render(
+        "Roomler",
+        Method::Request,
+        &events,
+        now,
+    )))
+}
+
+/// `room`'s conference as a calendar event, or `None` when it isn't
+/// scheduled.
+pub(crate) fn conference_event(
+    state: &AppState,
+    room: &Room,
+    organizer: Option<Person>,
+    attendees: Vec<Person>,
+) -> Option<Event> {
+    let settings = room.conference();
+    let (start, end) = calendar::schedule(&settings)?;
+    let url = room
+        .join_url
+        .as_ref()
+        .map(|path| format!("{}{}", state.settings.app.frontend_url, path));
+    let description = room
+        .topic
+        .iter()
+        .chain(room.purpose.iter())
+        .cloned()
+        .chain(url.iter().map(|url| format!("Join: {url}")))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    Some(Event {
+        uid: format!("{}@roomler", room.id?.to_hex()),
+        start,
+        end,
+        recurrence: calendar::recurrence_rule(settings.recurrence.as_deref()),
+        summary: room.name.clone(),
+        description: (!description.is_empty()).then_some(description),
+        url,
+        organizer,
+        attendees,
+        last_modified: room.updated_at.to_chrono(),
+    })
+}
+
+/// The live users among `ids` as calendar people.
+pub(crate) async fn people(
+    state: &AppState,
+    ids: &[ObjectId],
+) -> Result<HashMap<ObjectId, Person>, ApiError> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let users = state.users.base.find_by_ids(ids).await?;
+    Ok(users
+        .iter()
+        .filter(|u| u.deleted_at.is_none())
+        .filter_map(|u| Some((u.id?, person(u))))
+        .collect())
+}
+
+fn person(user: &User) -> Person {
+    Person {
+        name: user.display_name.clone(),
+        email: user.email.clone(),
+    }
+}
+
+fn ics_response(body: String, file_name: Option<String>) -> Response {
+    let content_type = (
+        header::CONTENT_TYPE,
+        "text/calendar; charset=utf-8".to_string(),
+    );
+    match file_name {
+        Some(name) => (
+            [
+                content_type,
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", name),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        None => ([content_type], body).into_response(),
+    }
+}
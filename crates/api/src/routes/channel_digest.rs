@@ -0,0 +1,117 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ChannelAction, ChannelDigest};
+use serde::{Deserialize, Serialize};
+
+use super::room::require_channel_action;
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct SetDigestRequest {
+    /// UTC hour, 0-23, from which each day's digest is posted.
+    pub hour_utc: u32,
+    #[serde(default)]
+    pub summarize: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestResponse {
+    pub enabled: bool,
+    pub hour_utc: Option<u32>,
+    pub summarize: bool,
+    /// Whether Claude summaries can be added on this server.
+    pub summaries_available: bool,
+    pub last_posted_on: Option<String>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/digest
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<DigestResponse>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::ReadHistory).await?;
+
+    Ok(Json(to_response(&state, room.digest.as_ref())))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/digest — post a daily digest
+/// into the channel, authored by the caller. Changing the hour doesn't
+/// repost a digest already posted today.
+pub async fn set(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<SetDigestRequest>,
+) -> Result<Json<DigestResponse>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    if body.hour_utc > 23 {
+        return Err(ApiError::Validation(
+            "hour_utc must be between 0 and 23".to_string(),
+        ));
+    }
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::UpdateRoom).await?;
+    if room.is_dm() {
+        return Err(ApiError::Validation(
+            "Direct messages have no digest".to_string(),
+        ));
+    }
+
+    let digest = ChannelDigest {
+        hour_utc: body.hour_utc,
+        summarize: body.summarize,
+        configured_by: auth.user_id,
+        last_posted_on: room.digest.and_then(|d| d.last_posted_on),
+    };
+    state.rooms.set_digest(tid, rid, Some(&digest)).await?;
+    Ok(Json(to_response(&state, Some(&digest))))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/digest — stop the digest.
+pub async fn clear(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<DigestResponse>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::UpdateRoom).await?;
+
+    if room.digest.is_some() {
+        state.rooms.set_digest(tid, rid, None).await?;
+    }
+    Ok(Json(to_response(&state, None)))
+}
+
+fn to_response(state: &AppState, digest: Option<&ChannelDigest>) -> DigestResponse {
+    DigestResponse {
+        enabled: digest.is_some(),
+        hour_utc: digest.map(|d| d.hour_utc),
+        summarize: digest.is_some_and(|d| d.summarize),
+        summaries_available: state.recognition.is_available(),
+        last_posted_on: digest.and_then(|d| d.last_posted_on.clone()),
+    }
+}
+
+fn parse_ids(tenant_id: &str, room_id: &str) -> Result<(ObjectId, ObjectId), ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    Ok((tid, rid))
+}
@@ -0,0 +1,231 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{
+    ConferenceChatRetention, RetentionOverride, Room, actions, role::permissions,
+};
+use serde::Serialize;
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+/// GET /api/tenant/{tenant_id}/conference-chat-retention
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<ConferenceChatRetention>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(tenant.settings.conference_chat))
+}
+
+/// PUT /api/tenant/{tenant_id}/conference-chat-retention — replace how long
+/// conference chat is kept. Channel messages are unaffected.
+pub async fn set(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<ConferenceChatRetention>,
+) -> Result<Json<ConferenceChatRetention>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    if body.retention_days == Some(0) {
+        return Err(ApiError::Validation(
+            "retention_days must be greater than zero".to_string(),
+        ));
+    }
+
+    state
+        .tenants
+        .set_conference_chat_retention(tid, &body)
+        .await?;
+
+    Ok(Json(body))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomRetentionResponse {
+    #[serde(rename = "override")]
+    pub retention_override: Option<RetentionOverride>,
+    pub tenant_retention_days: Option<u32>,
+    /// How long the room's conference chat is actually kept; `None` is
+    /// forever.
+    pub effective_retention_days: Option<u32>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/conference-chat-retention
+pub async fn get_room(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(room_retention(
+        room.retention_override,
+        tenant.settings.conference_chat.retention_days,
+    )))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/conference-chat-retention —
+/// exempt the room's conference chat from the tenant's retention period
+/// (`{ "exempt": true }`) or purge it sooner (`{ "retention_days": n }`).
+pub async fn set_room(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+    Json(body): Json<RetentionOverride>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let tenant_days = state
+        .tenants
+        .base
+        .find_by_id(tid)
+        .await?
+        .settings
+        .conference_chat
+        .retention_days;
+
+    match (body.exempt, body.retention_days) {
+        (true, Some(_)) => {
+            return Err(ApiError::Validation(
+                "Set either exempt or retention_days, not both".to_string(),
+            ));
+        }
+        (false, None) => {
+            return Err(ApiError::Validation(
+                "Set exempt or retention_days".to_string(),
+            ));
+        }
+        (false, Some(0)) => {
+            return Err(ApiError::Validation(
+                "retention_days must be greater than zero".to_string(),
+            ));
+        }
+        (false, Some(days)) if tenant_days.is_some_and(|t| days >= t) => {
+            return Err(ApiError::Validation(format!(
+                "retention_days must be shorter than the tenant's {} days",
+                tenant_days.unwrap_or_default()
+            )));
+        }
+        _ => {}
+    }
+
+    update_override(&state, &auth, &client, &room, Some(body.clone())).await?;
+    Ok(Json(room_retention(Some(body), tenant_days)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/conference-chat-retention —
+/// put the room back under the tenant's retention period.
+pub async fn clear_room(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+
+    if room.retention_override.is_some() {
+        update_override(&state, &auth, &client, &room, None).await?;
+    }
+    Ok(Json(room_retention(
+        None,
+        tenant.settings.conference_chat.retention_days,
+    )))
+}
+
+async fn update_override(
+    state: &AppState,
+    auth: &AuthUser,
+    client: &ClientInfo,
+    room: &Room,
+    retention: Option<RetentionOverride>,
+) -> Result<(), ApiError> {
+    let rid = room.id.unwrap();
+    state
+        .rooms
+        .set_retention_override(room.tenant_id, rid, retention.as_ref())
+        .await?;
+    let as_json =
+        |r: &Option<RetentionOverride>| r.as_ref().and_then(|r| serde_json::to_value(r).ok());
+    audit::record(
+        state,
+        room.tenant_id,
+        auth.user_id,
+        client,
+        actions::ROOM_RETENTION_UPDATE,
+        Some(rid),
+        vec![audit::change(
+            "retention_override",
+            as_json(&room.retention_override),
+            as_json(&retention),
+        )],
+    )
+    .await;
+    Ok(())
+}
+
+fn room_retention(
+    retention_override: Option<RetentionOverride>,
+    tenant_retention_days: Option<u32>,
+) -> RoomRetentionResponse {
+    let effective_retention_days = match &retention_override {
+        Some(o) => o.effective_days(tenant_retention_days),
+        None => tenant_retention_days,
+    };
+    RoomRetentionResponse {
+        retention_override,
+        tenant_retention_days,
+        effective_retention_days,
+    }
+}
@@ -0,0 +1,59 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::role::permissions;
+use roomler_ai_services::media::stats::ParticipantStats;
+use serde::Serialize;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct ConferenceStatsResponse {
+    pub conference_id: String,
+    pub participants: Vec<ParticipantStats>,
+}
+
+/// GET /api/tenant/{tenant_id}/conference/{conference_id}/stats — RTP
+/// statistics of the call in the room `conference_id`, per connection.
+/// Organizers and tenant managers see every participant; everyone else
+/// sees only their own connections.
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, conference_id)): Path<(String, String)>,
+) -> Result<Json<ConferenceStatsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&conference_id)
+        .map_err(|_| ApiError::BadRequest("Invalid conference_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !state.room_manager.has_room(&rid) {
+        return Err(ApiError::NotFound(
+            "Conference is not running on this instance".to_string(),
+        ));
+    }
+    let sees_all = room.organizer_ids().contains(&auth.user_id)
+        || permissions::has(perms, permissions::MANAGE_TENANT);
+
+    let mut participants = state
+        .room_manager
+        .stats(&rid, None)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read stats: {}", e)))?;
+    if !sees_all {
+        let own = auth.user_id.to_hex();
+        participants.retain(|p| p.user_id == own);
+    }
+
+    Ok(Json(ConferenceStatsResponse {
+        conference_id: rid.to_hex(),
+        participants,
+    }))
+}
@@ -0,0 +1,34 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::role::permissions;
+
+use crate::{
+    error::ApiError, extractors::auth::AuthUser, state::AppState, ws::metrics::DeliverySnapshot,
+};
+
+/// GET /api/tenant/{tenant_id}/delivery-metrics — real-time delivery stats
+/// for this tenant as seen by the serving instance: broadcast fan-out delay
+/// and client-reported `latency:probe` round trips.
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<DeliverySnapshot>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    Ok(Json(state.delivery_metrics.snapshot(tid)))
+}
@@ -0,0 +1,113 @@
+//! Developer settings: the tenant's bot API quota and how much of it each
+//! bot token used. Requires MANAGE_TENANT.
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use roomler_ai_db::models::role::permissions;
+use roomler_ai_services::api_quota::{self, QuotaStatus};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::permission::RequirePermission, state::AppState};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// `YYYY-MM-DD`; today (UTC) by default.
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeveloperSettingsRequest {
+    /// Daily cap on bot API calls; `null` leaves just the plan's quota.
+    pub daily_cap: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenUsage {
+    pub bot_id: String,
+    pub name: String,
+    pub token_hint: String,
+    pub revoked: bool,
+    pub calls: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub date: String,
+    pub plan_limit: u64,
+    pub daily_cap: Option<u64>,
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    /// When today's counters start over, RFC 3339.
+    pub reset_at: String,
+    /// Busiest first; tokens without calls that day are left out.
+    pub tokens: Vec<TokenUsage>,
+}
+
+/// GET /api/tenant/{tenant_id}/developer/usage?date=YYYY-MM-DD — bot API
+/// calls on a day of the past week, per token.
+pub async fn usage(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, ApiError> {
+    let now = chrono::Utc::now();
+    let date = match query.date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| ApiError::BadRequest("date must be YYYY-MM-DD".to_string()))?
+            .format("%Y-%m-%d")
+            .to_string(),
+        None => api_quota::day(now),
+    };
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    let usage = state.api_usage.usage(tid, &date).await;
+    let quota = QuotaStatus::new(api_quota::daily_limit(&tenant), usage.total, now);
+
+    let mut tokens: Vec<TokenUsage> = state
+        .bot_tokens
+        .find_for_tenant(tid)
+        .await?
+        .into_iter()
+        .filter_map(|bot| {
+            let calls = *usage.per_bot.get(&bot.id?)?;
+            Some(TokenUsage {
+                bot_id: bot.id?.to_hex(),
+                name: bot.name,
+                token_hint: bot.token_hint,
+                revoked: bot.revoked_at.is_some(),
+                calls,
+            })
+        })
+        .collect();
+    tokens.sort_by_key(|t| std::cmp::Reverse(t.calls));
+
+    Ok(Json(UsageResponse {
+        date,
+        plan_limit: tenant.plan.limits().bot_api_calls_per_day,
+        daily_cap: tenant.settings.bot_api_daily_cap,
+        limit: quota.limit,
+        used: quota.used,
+        remaining: quota.remaining,
+        reset_at: api_quota::reset_at(now).to_rfc3339(),
+        tokens,
+    }))
+}
+
+/// PUT /api/tenant/{tenant_id}/developer — cap the tenant's bot API calls
+/// a day below its plan's quota. Applies to the current day at once.
+pub async fn update(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Json(body): Json<DeveloperSettingsRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state
+        .tenants
+        .set_bot_api_daily_cap(tid, body.daily_cap)
+        .await?;
+    Ok(Json(serde_json::json!({ "daily_cap": body.daily_cap })))
+}
@@ -0,0 +1,176 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::Room;
+
+/// Largest group DM, the caller included.
+const MAX_DM_PARTICIPANTS: usize = 9;
+
+#[derive(Debug, Deserialize)]
+pub struct OpenDmRequest {
+    /// The other participants; the caller is always included.
+    pub user_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DmParticipant {
+    pub user_id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DmResponse {
+    /// Room id; messages go through the regular room message routes.
+    pub id: String,
+    pub is_group: bool,
+    pub participants: Vec<DmParticipant>,
+    pub message_count: u64,
+    /// Messages from the other participants the caller hasn't read.
+    pub unread_count: u64,
+    pub last_activity_at: Option<String>,
+    pub created_at: String,
+}
+
+/// GET /api/tenant/{tenant_id}/dm — the caller's direct messages, most
+/// recently active first.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<DmResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let rooms = state.rooms.find_user_dms(tid, auth.user_id).await?;
+    let mut response = Vec::with_capacity(rooms.len());
+    for room in rooms {
+        response.push(to_response(&state, room, auth.user_id).await?);
+    }
+
+    Ok(Json(response))
+}
+
+/// POST /api/tenant/{tenant_id}/dm — the DM between the caller and
+/// `user_ids`, created on first use. Asking again for the same set of
+/// people returns the same conversation.
+pub async fn open(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<OpenDmRequest>,
+) -> Result<Json<DmResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let mut participant_ids = vec![auth.user_id];
+    for user_id in &body.user_ids {
+        let uid = ObjectId::parse_str(user_id)
+            .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+        if !participant_ids.contains(&uid) {
+            participant_ids.push(uid);
+        }
+    }
+    if participant_ids.len() < 2 {
+        return Err(ApiError::Validation(
+            "A direct message needs at least one other participant".to_string(),
+        ));
+    }
+    if participant_ids.len() > MAX_DM_PARTICIPANTS {
+        return Err(ApiError::Validation(format!(
+            "A direct message can have at most {} participants",
+            MAX_DM_PARTICIPANTS
+        )));
+    }
+    for uid in &participant_ids[1..] {
+        if !state.tenants.is_member(tid, *uid).await? {
+            return Err(ApiError::NotFound(format!(
+                "User {} is not a member of this tenant",
+                uid.to_hex()
+            )));
+        }
+    }
+
+    let names = state
+        .users
+        .find_display_names(&participant_ids)
+        .await
+        .unwrap_or_default();
+    let name = participant_ids
+        .iter()
+        .map(|id| names.get(id).cloned().unwrap_or_else(|| id.to_hex()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let (room, created) = state
+        .rooms
+        .find_or_create_dm(tid, auth.user_id, &participant_ids, name)
+        .await?;
+    let response = to_response(&state, room, auth.user_id).await?;
+
+    if created {
+        let event = serde_json::json!({
+            "type": "dm:created",
+            "data": &response,
+        });
+        crate::ws::dispatcher::broadcast_in_tenant(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &state.delivery_metrics,
+            tid,
+            &participant_ids[1..],
+            &event,
+        )
+        .await;
+    }
+
+    Ok(Json(response))
+}
+
+async fn to_response(
+    state: &AppState,
+    room: Room,
+    viewer_id: ObjectId,
+) -> Result<DmResponse, ApiError> {
+    let room_id = room
+        .id
+        .ok_or_else(|| ApiError::Internal("Direct message without id".to_string()))?;
+    let user_ids = state.rooms.find_member_user_ids(room_id).await?;
+    let names: HashMap<ObjectId, String> = state
+        .users
+        .find_display_names(&user_ids)
+        .await
+        .unwrap_or_default();
+    let participants = user_ids
+        .iter()
+        .map(|id| DmParticipant {
+            user_id: id.to_hex(),
+            display_name: names.get(id).cloned().unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(DmResponse {
+        id: room_id.to_hex(),
+        is_group: user_ids.len() > 2,
+        participants,
+        message_count: room.message_count,
+        unread_count: state.read_states.unread_count(room_id, viewer_id).await?,
+        last_activity_at: room
+            .last_activity_at
+            .and_then(|t| t.try_to_rfc3339_string().ok()),
+        created_at: room.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    })
+}
@@ -0,0 +1,206 @@
+//! The tenant's custom emoji. Any member can add one from an image they
+//! uploaded through the file routes; its creator or a tenant manager can
+//! remove it. Once added, `:name:` works in messages and reactions (see
+//! [`crate::emoji`]).
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{CustomEmoji, Tenant, actions, role::permissions};
+use roomler_ai_services::{assets, dao::base::DaoError, emoji};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo},
+    state::AppState,
+};
+
+/// Largest image a custom emoji may use.
+const MAX_IMAGE_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateEmojiRequest {
+    /// With or without colons; stored lowercased.
+    pub name: String,
+    /// An image uploaded to the tenant.
+    pub file_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmojiResponse {
+    pub id: String,
+    pub name: String,
+    pub shortcode: String,
+    pub image_url: String,
+    pub is_animated: bool,
+    pub creator_id: String,
+    pub created_at: String,
+}
+
+fn to_response(state: &AppState, tenant: &Tenant, e: CustomEmoji) -> EmojiResponse {
+    EmojiResponse {
+        id: e.id.map(|id| id.to_hex()).unwrap_or_default(),
+        shortcode: format!(":{}:", e.name),
+        name: e.name,
+        image_url: super::asset::for_tenant(state, tenant, &e.image_url),
+        is_animated: e.is_animated,
+        creator_id: e.creator_id.to_hex(),
+        created_at: e.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/emoji — by name.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<EmojiResponse>>, ApiError> {
+    let tenant = member_tenant(&state, &auth, &tenant_id).await?;
+    let tid = tenant.id.unwrap_or_default();
+    let items = state
+        .custom_emojis
+        .find_for_tenant(tid)
+        .await?
+        .into_iter()
+        .map(|e| to_response(&state, &tenant, e))
+        .collect();
+    Ok(Json(items))
+}
+
+/// POST /api/tenant/{tenant_id}/emoji
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateEmojiRequest>,
+) -> Result<(StatusCode, Json<EmojiResponse>), ApiError> {
+    let tenant = member_tenant(&state, &auth, &tenant_id).await?;
+    let tid = tenant.id.unwrap_or_default();
+
+    let name = emoji::custom_name(&body.name).map_err(ApiError::Validation)?;
+    if state.custom_emojis.count_for_tenant(tid).await? >= emoji::MAX_CUSTOM_PER_TENANT {
+        return Err(ApiError::Validation(format!(
+            "A tenant can have at most {} custom emoji",
+            emoji::MAX_CUSTOM_PER_TENANT
+        )));
+    }
+
+    let fid = ObjectId::parse_str(&body.file_id)
+        .map_err(|_| ApiError::BadRequest("Invalid file_id".to_string()))?;
+    let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    if file.deleted_at.is_some() {
+        return Err(ApiError::NotFound("File not found".to_string()));
+    }
+    let checksum = match &file.checksum {
+        Some(checksum) if assets::is_servable(&file.content_type) => checksum,
+        _ => {
+            return Err(ApiError::Validation(
+                "Custom emoji must be an image".to_string(),
+            ));
+        }
+    };
+    if file.size > MAX_IMAGE_BYTES {
+        return Err(ApiError::Validation(format!(
+            "Custom emoji images can be at most {} KB",
+            MAX_IMAGE_BYTES / 1024
+        )));
+    }
+
+    let created = state
+        .custom_emojis
+        .create(
+            tid,
+            name.clone(),
+            assets::path(tid, checksum),
+            fid,
+            file.content_type == "image/gif",
+            auth.user_id,
+        )
+        .await
+        .map_err(|e| match e {
+            DaoError::DuplicateKey(_) => ApiError::Conflict(format!(":{name}: already exists")),
+            e => e.into(),
+        })?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::EMOJI_CREATE,
+        created.id,
+        vec![audit::change(
+            "name",
+            None,
+            Some(created.name.clone().into()),
+        )],
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(to_response(&state, &tenant, created)),
+    ))
+}
+
+/// DELETE /api/tenant/{tenant_id}/emoji/{emoji_id}
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Path((tenant_id, emoji_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tenant = member_tenant(&state, &auth, &tenant_id).await?;
+    let tid = tenant.id.unwrap_or_default();
+    let id = ObjectId::parse_str(&emoji_id)
+        .map_err(|_| ApiError::BadRequest("Invalid emoji_id".to_string()))?;
+    let existing = state
+        .custom_emojis
+        .base
+        .find_by_id_in_tenant(tid, id)
+        .await?;
+
+    if existing.creator_id != auth.user_id {
+        let perms = state
+            .tenants
+            .get_member_permissions(tid, auth.user_id)
+            .await?;
+        if !permissions::has(perms, permissions::MANAGE_TENANT) {
+            return Err(ApiError::Forbidden(
+                "Only the emoji's creator or a tenant manager can remove it".to_string(),
+            ));
+        }
+    }
+
+    state.custom_emojis.delete(tid, id).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::EMOJI_DELETE,
+        Some(id),
+        vec![audit::change("name", Some(existing.name.into()), None)],
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+async fn member_tenant(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+) -> Result<Tenant, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    Ok(state.tenants.base.find_by_id(tid).await?)
+}
@@ -1,126 +1,350 @@
+//! Conversation exports as XLSX, PDF, JSON, CSV, Markdown or HTML. Rooms up to `export.sync_max_messages` are rendered
+//! in the request and returned as the file; larger ones become background
+//! tasks and answer `202` with a link to poll. Either way the export holds
+//! one of the tenant's `export.max_concurrent_per_tenant` slots while it
+//! runs.
+
 use axum::{
     Json,
-    extract::{Path, State},
+    body::Body,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
 use bson::oid::ObjectId;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::sync::Arc;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::TaskCategory;
+use crate::{error::ApiError, extractors::permission::RequirePermission, state::AppState};
+use roomler_ai_db::models::{TaskCategory, role::permissions};
+use roomler_ai_services::background::task_store::TaskStore;
 use roomler_ai_services::dao::base::PaginationParams;
+use roomler_ai_services::export::html::{self, EmbeddedAsset};
+use roomler_ai_services::export::redact::Anonymizer;
+
+/// Suggested wait before retrying when the tenant has no free export slot.
+pub(crate) const SLOT_RETRY_SECS: u64 = 30;
 
 #[derive(Debug, Deserialize)]
 pub struct ExportConversationRequest {
     pub room_id: String,
+    /// Replace identities with stable pseudonyms and strip emails/phone numbers.
+    #[serde(default)]
+    pub anonymize: bool,
+    #[serde(default)]
+    pub format: ExportFormat,
 }
 
 pub async fn export_conversation(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    RequirePermission {
+        auth, tenant_id, ..
+    }: RequirePermission<{ permissions::READ_HISTORY }>,
     Json(body): Json<ExportConversationRequest>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
-    let rid = ObjectId::parse_str(&body.room_id)
+) -> Result<Response, ApiError> {
+    run_export(
+        &state,
+        auth.user_id,
+        tenant_id,
+        &body.room_id,
+        body.anonymize,
+        body.format,
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Xlsx,
+    Pdf,
+    Json,
+    Csv,
+    Markdown,
+    /// A single page with image attachments embedded.
+    Html,
+}
+
+impl ExportFormat {
+    fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "markdown",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    fn task_type(self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "export_conversation_pdf",
+            _ => "export_conversation",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            _ => self.name(),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            ExportFormat::Pdf => "application/pdf",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// Shared by the conversation and PDF export routes.
+pub(crate) async fn run_export(
+    state: &AppState,
+    user_id: ObjectId,
+    tid: ObjectId,
+    room_id: &str,
+    anonymize: bool,
+    format: ExportFormat,
+) -> Result<Response, ApiError> {
+    let rid = ObjectId::parse_str(room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
+    let permit = state
+        .export_slots
+        .try_acquire(tid)
+        .ok_or(ApiError::TooManyRequests(SLOT_RETRY_SECS))?;
+
+    let message_count = state.messages.count_in_room(rid).await?;
+    if message_count <= state.settings.export.sync_max_messages {
+        let bytes = build_export(state, tid, rid, anonymize, format, None)
+            .await
+            .map_err(ApiError::Internal)?;
+
+        let file_name = format!(
+            "conversation-export-{}.{}",
+            rid.to_hex(),
+            format.extension()
+        );
+        return Ok(Response::builder()
+            .header("Content-Type", format.content_type())
+            .header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", file_name),
+            )
+            .body(Body::from(bytes))
+            .unwrap());
     }
 
-    // Create background task
+    let params = serde_json::json!({
+        "room_id": room_id,
+        "anonymize": anonymize,
+        "format": format.name(),
+        "message_count": message_count,
+    });
     let task = state
         .tasks
         .create_task(
             tid,
-            auth.user_id,
-            "export_conversation".to_string(),
+            user_id,
+            format.task_type().to_string(),
             TaskCategory::Export,
-            serde_json::json!({ "room_id": body.room_id }),
+            params,
         )
         .await?;
 
     let task_id = task.id.unwrap();
 
     // Spawn async export work
-    let messages_dao = Arc::clone(&state.messages);
-    let users_dao = Arc::clone(&state.users);
-    let task_store = Arc::clone(state.tasks.store());
+    let task_state = state.clone();
 
     state.tasks.spawn_task(task_id, async move {
-        // Fetch all messages in room (up to 10000)
-        let params = PaginationParams {
-            page: 1,
-            per_page: 10000,
-            before: None,
-        };
-        let result = messages_dao
-            .find_in_room(rid, &params)
-            .await
-            .map_err(|e| format!("Failed to fetch messages: {}", e))?;
-
-        task_store
-            .update_progress(task_id, 30, Some("Fetched messages".to_string()))
-            .await
-            .map_err(|e| format!("Failed to update progress: {}", e))?;
-
-        // Collect unique author IDs and fetch users
-        let author_ids: Vec<ObjectId> = result
-            .items
-            .iter()
-            .map(|m| m.author_id)
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-
-        let mut user_map = HashMap::new();
-        for uid in &author_ids {
-            if let Ok(user) = users_dao.base.find_by_id(*uid).await {
-                user_map.insert(*uid, user);
-            }
-        }
-
-        task_store
-            .update_progress(task_id, 60, Some("Fetched user data".to_string()))
-            .await
-            .map_err(|e| format!("Failed to update progress: {}", e))?;
-
-        // Generate Excel
-        let bytes =
-            roomler_ai_services::export::excel::export_conversation(&result.items, &user_map)
-                .map_err(|e| format!("Excel export failed: {}", e))?;
-
-        // Write to temp file
-        let export_dir = std::env::var("ROOMLER_UPLOAD_DIR")
-            .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string());
-        let export_dir = std::path::PathBuf::from(export_dir).join("exports");
-        tokio::fs::create_dir_all(&export_dir)
-            .await
-            .map_err(|e| format!("Failed to create export dir: {}", e))?;
+        let _permit = permit;
+        let state = task_state;
+        let task_store = state.tasks.store();
+        let bytes = build_export(
+            &state,
+            tid,
+            rid,
+            anonymize,
+            format,
+            Some((task_store.as_ref(), task_id)),
+        )
+        .await?;
 
-        let file_name = format!("conversation-export-{}.xlsx", task_id.to_hex());
-        let file_path = export_dir.join(&file_name);
-        tokio::fs::write(&file_path, &bytes)
+        let file_name = format!(
+            "conversation-export-{}.{}",
+            task_id.to_hex(),
+            format.extension()
+        );
+        let key = format!("exports/{}", file_name);
+        let storage_provider = state
+            .object_store
+            .put(&key, bytes)
             .await
             .map_err(|e| format!("Failed to write export file: {}", e))?;
 
         task_store
-            .complete(
-                task_id,
-                Some(file_path.to_string_lossy().to_string()),
-                Some(file_name),
-            )
+            .complete(task_id, Some(key), Some(file_name), Some(storage_provider))
             .await
             .map_err(|e| format!("Failed to complete task: {}", e))?;
 
         Ok(())
     });
 
-    Ok(Json(serde_json::json!({
-        "task_id": task_id.to_hex(),
-        "status": "pending",
-    })))
+    let task_url = format!("/api/tenant/{}/task/{}", tid.to_hex(), task_id.to_hex());
+    Ok((
+        StatusCode::ACCEPTED,
+        [("Location", task_url.clone())],
+        Json(serde_json::json!({
+            "task_id": task_id.to_hex(),
+            "status": "pending",
+            "task_url": task_url,
+            "download_url": format!("{}/download", task_url),
+        })),
+    )
+        .into_response())
+}
+
+/// Fetch, optionally anonymize and render a room's messages. Progress goes
+/// to `task` when running in the background.
+async fn build_export(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    anonymize: bool,
+    format: ExportFormat,
+    task: Option<(&TaskStore, ObjectId)>,
+) -> Result<Vec<u8>, String> {
+    let report = |progress: u8, log: &'static str| async move {
+        if let Some((task_store, task_id)) = task {
+            task_store
+                .update_progress(task_id, progress, Some(log.to_string()))
+                .await
+                .map_err(|e| format!("Failed to update progress: {}", e))?;
+        }
+        Ok::<_, String>(())
+    };
+
+    // Fetch all messages in room (up to 10000)
+    let params = PaginationParams {
+        page: 1,
+        per_page: 10000,
+        before: None,
+    };
+    let result = state
+        .messages
+        .find_in_room(rid, &params)
+        .await
+        .map_err(|e| format!("Failed to fetch messages: {}", e))?;
+
+    report(30, "Fetched messages").await?;
+
+    // Collect unique author IDs and fetch users
+    let author_ids: Vec<ObjectId> = result
+        .items
+        .iter()
+        .flat_map(|m| {
+            // Mentioned users need pseudonyms too when anonymizing.
+            let mentioned: &[ObjectId] = if anonymize { &m.mentions.users } else { &[] };
+            std::iter::once(m.author_id).chain(mentioned.iter().copied())
+        })
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut user_map = HashMap::new();
+    for uid in &author_ids {
+        if let Ok(user) = state.users.base.find_by_id(*uid).await {
+            user_map.insert(*uid, user);
+        }
+    }
+
+    report(60, "Fetched user data").await?;
+
+    let (messages, user_map) = if anonymize {
+        Anonymizer::new(tid, &user_map).apply(&result.items, &user_map)
+    } else {
+        (result.items, user_map)
+    };
+
+    match format {
+        ExportFormat::Xlsx => {
+            roomler_ai_services::export::excel::export_conversation(&messages, &user_map)
+                .map_err(|e| format!("Excel export failed: {}", e))
+        }
+        ExportFormat::Pdf => {
+            roomler_ai_services::export::pdf::export_conversation(&messages, &user_map)
+        }
+        ExportFormat::Json => {
+            roomler_ai_services::export::json::export_conversation(&messages, &user_map)
+        }
+        ExportFormat::Csv => Ok(roomler_ai_services::export::csv::export_conversation(
+            &messages, &user_map,
+        )),
+        ExportFormat::Markdown => Ok(roomler_ai_services::export::markdown::export_conversation(
+            &messages, &user_map,
+        )),
+        ExportFormat::Html => {
+            // Attachments of anonymized exports are not embedded: images
+            // can identify people in ways redaction can't catch.
+            let assets = if anonymize {
+                HashMap::new()
+            } else {
+                embedded_assets(state, tid, &messages).await
+            };
+            report(80, "Embedded attachments").await?;
+            Ok(html::export_conversation(&messages, &user_map, &assets))
+        }
+    }
+}
+
+/// Image attachments small enough to inline into an HTML export. Files that
+/// are gone or unreadable are left out and listed by name instead.
+async fn embedded_assets(
+    state: &AppState,
+    tid: ObjectId,
+    messages: &[roomler_ai_db::models::Message],
+) -> HashMap<ObjectId, EmbeddedAsset> {
+    let mut assets = HashMap::new();
+    for attachment in messages.iter().flat_map(|m| &m.attachments) {
+        if assets.contains_key(&attachment.file_id)
+            || !html::embeds(&attachment.content_type, attachment.size)
+        {
+            continue;
+        }
+        let Ok(file) = state
+            .files
+            .base
+            .find_by_id_in_tenant(tid, attachment.file_id)
+            .await
+        else {
+            continue;
+        };
+        if !html::embeds(&file.content_type, file.size) {
+            continue;
+        }
+        if let Ok(bytes) = state
+            .object_store
+            .get(file.storage_provider, &file.storage_key)
+            .await
+        {
+            assets.insert(
+                attachment.file_id,
+                EmbeddedAsset {
+                    content_type: file.content_type,
+                    bytes,
+                },
+            );
+        }
+    }
+    assets
 }
@@ -0,0 +1,158 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{FeatureFlagOverride, role::permissions};
+use roomler_ai_services::feature_flags::FlagSource;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError,
+    extractors::{auth::AuthUser, feature_flags::Flags, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Serialize)]
+pub struct FlagResponse {
+    pub key: String,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverrideResponse {
+    pub key: String,
+    pub user_id: Option<String>,
+    pub enabled: bool,
+    pub set_by: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetOverrideRequest {
+    pub enabled: bool,
+    /// Target a single user within the tenant instead of the whole tenant.
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearOverrideQuery {
+    pub user_id: Option<String>,
+}
+
+/// GET /api/tenant/{tenant_id}/feature-flag — flags as evaluated for the
+/// calling user in this tenant. Drives client-side gating.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    flags: Flags,
+) -> Result<Json<Vec<FlagResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let response = flags
+        .0
+        .iter()
+        .map(|(key, v)| FlagResponse {
+            key: key.clone(),
+            enabled: v.enabled,
+            source: v.source,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// GET /api/tenant/{tenant_id}/feature-flag/override — admin view of every
+/// tenant- and user-level override in this tenant.
+pub async fn list_overrides(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<Vec<OverrideResponse>>, ApiError> {
+    let overrides = state.feature_flags.dao.list_tenant_overrides(tid).await?;
+    Ok(Json(
+        overrides.into_iter().map(to_override_response).collect(),
+    ))
+}
+
+/// PUT /api/tenant/{tenant_id}/feature-flag/{key} — flip a flag for the
+/// tenant, or for one member when `user_id` is set. Takes effect on the
+/// next evaluation; nothing is cached.
+pub async fn set_override(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    Path((_, key)): Path<(String, String)>,
+    Json(body): Json<SetOverrideRequest>,
+) -> Result<Json<OverrideResponse>, ApiError> {
+    let uid = body
+        .user_id
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    if !state.feature_flags.is_known(&key).await? {
+        return Err(ApiError::NotFound(format!("Unknown feature flag: {key}")));
+    }
+    if let Some(uid) = uid
+        && !state.tenants.is_member(tid, uid).await?
+    {
+        return Err(ApiError::BadRequest(
+            "Target user is not a member".to_string(),
+        ));
+    }
+
+    let row = state
+        .feature_flags
+        .dao
+        .set_override(&key, tid, uid, body.enabled, auth.user_id)
+        .await?;
+
+    Ok(Json(to_override_response(row)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/feature-flag/{key}[?user_id=] — drop an
+/// override so the flag falls back to the next level down.
+pub async fn clear_override(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Path((_, key)): Path<(String, String)>,
+    Query(query): Query<ClearOverrideQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let uid = query
+        .user_id
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let cleared = state
+        .feature_flags
+        .dao
+        .clear_override(&key, tid, uid)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "cleared": cleared })))
+}
+
+fn to_override_response(o: FeatureFlagOverride) -> OverrideResponse {
+    OverrideResponse {
+        key: o.key,
+        user_id: o.user_id.map(|u| u.to_hex()),
+        enabled: o.enabled,
+        set_by: o.set_by.to_hex(),
+        updated_at: o.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
@@ -5,13 +5,15 @@ use axum::{
     response::Response,
 };
 use bson::oid::ObjectId;
-use serde::Serialize;
+use futures::StreamExt;
+use roomler_ai_services::object_storage::{ByteStream, StorageError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use tokio::io::AsyncReadExt;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::{FileContext, FileContextType};
+use roomler_ai_db::models::{FileContext, FileContextType, StorageProvider};
 use roomler_ai_services::dao::base::PaginationParams;
 
 #[derive(Debug, Serialize)]
@@ -27,6 +29,24 @@ pub struct FileResponse {
     pub room_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub room_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// The same content was already uploaded to the tenant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<DuplicateOf>,
+    /// Cacheable content-hash URL for images; see [`super::asset`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_url: Option<String>,
+}
+
+/// The earlier upload a file turned out to duplicate.
+#[derive(Debug, Serialize)]
+pub struct DuplicateOf {
+    pub file_id: String,
+    pub filename: String,
+    pub uploaded_by: String,
+    pub uploaded_by_name: Option<String>,
+    pub uploaded_at: String,
 }
 
 fn to_response(f: roomler_ai_db::models::File) -> FileResponse {
@@ -41,9 +61,60 @@ fn to_response(f: roomler_ai_db::models::File) -> FileResponse {
         created_at: f.created_at.try_to_rfc3339_string().unwrap_or_default(),
         room_id,
         room_name: None,
+        checksum: f.checksum,
+        duplicate_of: None,
+        asset_url: None,
     }
 }
 
+/// `to_response` plus its asset URL and who uploaded the same content
+/// first, while that upload is still around.
+async fn to_response_with_duplicate(
+    state: &AppState,
+    f: roomler_ai_db::models::File,
+) -> FileResponse {
+    let tid = f.tenant_id;
+    let original_id = f.duplicate_of;
+    let asset_url = match state.tenants.base.find_by_id(tid).await {
+        Ok(tenant) => super::asset::url(state, &tenant, &f),
+        Err(_) => None,
+    };
+    let mut resp = to_response(f);
+    resp.asset_url = asset_url;
+    let Some(original_id) = original_id else {
+        return resp;
+    };
+    let Ok(original) = state
+        .files
+        .base
+        .find_by_id_in_tenant(tid, original_id)
+        .await
+    else {
+        return resp;
+    };
+    if original.deleted_at.is_some() {
+        return resp;
+    }
+    let uploaded_by_name = state
+        .users
+        .base
+        .find_by_id(original.uploaded_by)
+        .await
+        .ok()
+        .map(|u| u.display_name);
+    resp.duplicate_of = Some(DuplicateOf {
+        file_id: original_id.to_hex(),
+        filename: original.filename,
+        uploaded_by: original.uploaded_by.to_hex(),
+        uploaded_by_name,
+        uploaded_at: original
+            .created_at
+            .try_to_rfc3339_string()
+            .unwrap_or_default(),
+    });
+    resp
+}
+
 /// List files for a room.
 pub async fn list(
     State(state): State<AppState>,
@@ -59,6 +130,7 @@ pub async fn list(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    super::room::visible_room(&state, tid, rid, auth.user_id).await?;
 
     let result = state.files.find_by_room(tid, rid, &params).await?;
 
@@ -127,46 +199,95 @@ pub async fn list_tenant_files(
 }
 
 /// Shared upload logic used by both `upload` and `upload_room`.
-async fn do_upload(
+pub(crate) async fn do_upload(
     state: &AppState,
     tid: ObjectId,
     rid: ObjectId,
     user_id: ObjectId,
-    file_data: (String, String, Vec<u8>),
+    filename: String,
+    content_type: String,
+    body: ByteStream<'_>,
 ) -> Result<FileResponse, ApiError> {
-    let (filename, content_type, bytes) = file_data;
-    let size = bytes.len() as u64;
-
-    let upload_dir = upload_dir();
-    tokio::fs::create_dir_all(&upload_dir)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to create upload dir: {}", e)))?;
+    let storage_key = room_storage_key(tid, rid);
+    let mut hasher = Sha256::new();
+    let body = body
+        .inspect(|chunk| {
+            if let Ok(bytes) = chunk {
+                hasher.update(bytes);
+            }
+        })
+        .boxed();
+    let (storage_provider, size) = state.object_store.put_stream(&storage_key, body).await?;
+    let checksum = hex::encode(hasher.finalize());
+    register_file(
+        state,
+        tid,
+        rid,
+        user_id,
+        filename,
+        content_type,
+        size,
+        storage_provider,
+        storage_key,
+        checksum,
+    )
+    .await
+}
 
-    let storage_key = format!(
+fn room_storage_key(tid: ObjectId, rid: ObjectId) -> String {
+    format!(
         "{}/room/{}/{}",
         tid.to_hex(),
         rid.to_hex(),
         uuid::Uuid::new_v4()
-    );
-    let file_path = upload_dir.join(&storage_key);
-
-    if let Some(parent) = file_path.parent() {
-        tokio::fs::create_dir_all(parent)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to create dirs: {}", e)))?;
-    }
-
-    tokio::fs::write(&file_path, &bytes)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to write file: {}", e)))?;
+    )
+}
 
+/// Record a stored object as a room file. Every upload path ends here, so
+/// new files all start out with `scan_status: pending`. When the tenant
+/// already stores the same content, the new object is dropped and the file
+/// points at the existing one instead.
+#[allow(clippy::too_many_arguments)]
+async fn register_file(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    user_id: ObjectId,
+    filename: String,
+    content_type: String,
+    size: u64,
+    storage_provider: StorageProvider,
+    storage_key: String,
+    checksum: String,
+) -> Result<FileResponse, ApiError> {
     let context = FileContext {
         context_type: FileContextType::Room,
         entity_id: rid,
         room_id: Some(rid),
     };
 
-    let file = state
+    let original = state.files.find_by_checksum(tid, &checksum).await?;
+    let blob = state
+        .files
+        .retain_blob(
+            tid,
+            &checksum,
+            storage_provider,
+            &state.object_store.bucket_name(storage_provider),
+            &storage_key,
+            size,
+        )
+        .await?;
+    if (blob.storage_provider != storage_provider || blob.storage_key != storage_key)
+        && let Err(e) = state
+            .object_store
+            .delete(storage_provider, &storage_key)
+            .await
+    {
+        tracing::warn!(%e, key = %storage_key, "Failed to delete duplicate upload");
+    }
+
+    let file = match state
         .files
         .create(
             tid,
@@ -175,11 +296,21 @@ async fn do_upload(
             filename,
             content_type,
             size,
-            "local".to_string(),
-            storage_key,
+            blob.storage_provider,
+            blob.storage_bucket,
+            blob.storage_key,
             String::new(),
+            Some(checksum.clone()),
+            original.and_then(|f| f.id),
         )
-        .await?;
+        .await
+    {
+        Ok(file) => file,
+        Err(e) => {
+            release_content(state, tid, &checksum).await?;
+            return Err(e.into());
+        }
+    };
 
     let file_id_hex = file.id.unwrap().to_hex();
     let url = format!("/api/tenant/{}/file/{}/download", tid.to_hex(), file_id_hex);
@@ -192,11 +323,39 @@ async fn do_upload(
         )
         .await?;
 
-    let mut resp = to_response(file);
+    let mut resp = to_response_with_duplicate(state, file).await;
     resp.url = url;
     Ok(resp)
 }
 
+/// Drop a file's reference on its stored content, deleting the object once
+/// no file points at it any more.
+async fn release_content(state: &AppState, tid: ObjectId, checksum: &str) -> Result<(), ApiError> {
+    if let Some(blob) = state.files.release_blob(tid, checksum).await?
+        && let Err(e) = state
+            .object_store
+            .delete(blob.storage_provider, &blob.storage_key)
+            .await
+    {
+        tracing::warn!(%e, key = %blob.storage_key, "Failed to delete unreferenced object");
+    }
+    Ok(())
+}
+
+/// Hex SHA-256 of a stored object.
+async fn object_checksum(
+    state: &AppState,
+    provider: StorageProvider,
+    key: &str,
+) -> Result<String, ApiError> {
+    let mut stream = state.object_store.get_stream(provider, key).await?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = stream.next().await {
+        hasher.update(chunk.map_err(StorageError::from)?);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
 /// Upload a file via multipart form data.
 /// Fields: `file` (binary), `room_id` (text)
 pub async fn upload(
@@ -252,7 +411,9 @@ pub async fn upload(
     let rid = ObjectId::parse_str(&room_id_val)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    let resp = do_upload(&state, tid, rid, auth.user_id, data).await?;
+    let (filename, content_type, bytes) = data;
+    let body = futures::stream::once(async move { Ok(bytes) }).boxed();
+    let resp = do_upload(&state, tid, rid, auth.user_id, filename, content_type, body).await?;
     Ok(Json(resp))
 }
 
@@ -271,7 +432,7 @@ pub async fn get(
     }
 
     let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
-    Ok(Json(to_response(file)))
+    Ok(Json(to_response_with_duplicate(&state, file).await))
 }
 
 pub async fn download(
@@ -289,15 +450,10 @@ pub async fn download(
     }
 
     let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
-    let file_path = upload_dir().join(&file.storage_key);
-
-    let mut contents = Vec::new();
-    let mut f = tokio::fs::File::open(&file_path)
-        .await
-        .map_err(|_| ApiError::NotFound("File not found on disk".to_string()))?;
-    f.read_to_end(&mut contents)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to read file: {}", e)))?;
+    let stream = state
+        .object_store
+        .get_stream(file.storage_provider, &file.storage_key)
+        .await?;
 
     Ok(Response::builder()
         .header("Content-Type", &file.content_type)
@@ -305,7 +461,7 @@ pub async fn download(
             "Content-Disposition",
             format!("attachment; filename=\"{}\"", file.filename),
         )
-        .body(Body::from(contents))
+        .body(Body::from_stream(stream))
         .unwrap())
 }
 
@@ -323,7 +479,12 @@ pub async fn delete(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    state.files.soft_delete(tid, fid).await?;
+    if state.files.soft_delete(tid, fid).await? {
+        let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+        if let Some(checksum) = &file.checksum {
+            release_content(&state, tid, checksum).await?;
+        }
+    }
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
@@ -342,37 +503,230 @@ pub async fn upload_room(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
-
-    let mut file_data: Option<(String, String, Vec<u8>)> = None;
+    super::room::visible_room(&state, tid, rid, auth.user_id).await?;
 
     while let Some(field) = multipart
         .next_field()
         .await
         .map_err(|e| ApiError::BadRequest(format!("Multipart error: {}", e)))?
     {
-        let name = field.name().unwrap_or("").to_string();
-
-        if name == "file" {
+        if field.name() == Some("file") {
             let filename = field.file_name().unwrap_or("unnamed").to_string();
             let content_type = field
                 .content_type()
                 .unwrap_or("application/octet-stream")
                 .to_string();
-            let bytes = field
-                .bytes()
-                .await
-                .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {}", e)))?;
-            file_data = Some((filename, content_type, bytes.to_vec()));
+            // Stream straight into storage instead of buffering the body.
+            let body = field
+                .map(|chunk| chunk.map(|b| b.to_vec()).map_err(std::io::Error::other))
+                .boxed();
+            let resp =
+                do_upload(&state, tid, rid, auth.user_id, filename, content_type, body).await?;
+            return Ok(Json(resp));
         }
     }
 
-    let data = file_data.ok_or_else(|| ApiError::BadRequest("Missing 'file' field".to_string()))?;
+    Err(ApiError::BadRequest("Missing 'file' field".to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PresignRequest {
+    pub room_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+}
 
-    let resp = do_upload(&state, tid, rid, auth.user_id, data).await?;
+#[derive(Debug, Serialize)]
+pub struct PresignResponse {
+    /// Pass back to `POST /file/confirm` once the upload has finished.
+    pub upload_id: String,
+    pub method: &'static str,
+    pub url: String,
+    /// Headers the `PUT` must carry.
+    pub headers: HashMap<&'static str, String>,
+    pub expires_at: String,
+}
+
+/// POST /api/tenant/{tenant_id}/file/presign
+///
+/// A pre-signed URL the client uploads the file body to directly, so it
+/// doesn't pass through the API. Only available with the S3 backend.
+pub async fn presign(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<PresignRequest>,
+) -> Result<Json<PresignResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&body.room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    super::room::visible_room(&state, tid, rid, auth.user_id).await?;
+
+    let filename = body.filename.trim();
+    if filename.is_empty() {
+        return Err(ApiError::Validation("filename is required".to_string()));
+    }
+    if !is_mime_type(&body.content_type) {
+        return Err(ApiError::Validation("Invalid content_type".to_string()));
+    }
+    let max = state.object_store.max_object_size();
+    if body.size == 0 || body.size > max {
+        return Err(ApiError::Validation(format!(
+            "size must be between 1 and {} bytes",
+            max
+        )));
+    }
+
+    let storage_key = room_storage_key(tid, rid);
+    let (url, ttl) = state
+        .object_store
+        .presign_put(&storage_key)
+        .ok_or_else(|| {
+            ApiError::BadRequest("Direct uploads need the s3 storage backend".to_string())
+        })?;
+    let expires_at = bson::DateTime::from_chrono(
+        chrono::Utc::now() + chrono::Duration::seconds(ttl.as_secs() as i64),
+    );
+
+    let upload = state
+        .files
+        .create_pending_upload(
+            tid,
+            rid,
+            auth.user_id,
+            filename.to_string(),
+            body.content_type.clone(),
+            body.size,
+            storage_key,
+            expires_at,
+        )
+        .await?;
+
+    Ok(Json(PresignResponse {
+        upload_id: upload.id.map(|id| id.to_hex()).unwrap_or_default(),
+        method: "PUT",
+        url,
+        headers: HashMap::from([("Content-Type", body.content_type)]),
+        expires_at: expires_at.try_to_rfc3339_string().unwrap_or_default(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmUploadRequest {
+    pub upload_id: String,
+}
+
+/// POST /api/tenant/{tenant_id}/file/confirm
+///
+/// Checks a direct upload against what was declared at presign time and
+/// records it as a room file. An object that doesn't match is deleted.
+pub async fn confirm(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<ConfirmUploadRequest>,
+) -> Result<Json<FileResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let upload_id = ObjectId::parse_str(&body.upload_id)
+        .map_err(|_| ApiError::BadRequest("Invalid upload_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let upload = state
+        .files
+        .find_pending_upload(tid, upload_id, auth.user_id)
+        .await
+        .map_err(|_| ApiError::NotFound("Upload not found or expired".to_string()))?;
+
+    let info = match state.object_store.head_s3(&upload.storage_key).await {
+        Ok(info) => info,
+        Err(StorageError::NotFound) => {
+            return Err(ApiError::BadRequest(
+                "Nothing has been uploaded yet".to_string(),
+            ));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let stored_type = mime_essence(info.content_type.as_deref().unwrap_or_default());
+    let declared_type = mime_essence(&upload.content_type);
+    let problem = if info.size != upload.size {
+        Some(format!(
+            "Uploaded {} bytes but {} were declared",
+            info.size, upload.size
+        ))
+    } else if !stored_type.eq_ignore_ascii_case(declared_type) {
+        Some(format!(
+            "Uploaded content type {} doesn't match {}",
+            stored_type, declared_type
+        ))
+    } else {
+        None
+    };
+    if let Some(problem) = problem {
+        state.files.delete_pending_upload(upload_id).await?;
+        if let Err(e) = state
+            .object_store
+            .delete(StorageProvider::S3, &upload.storage_key)
+            .await
+        {
+            tracing::warn!(%e, key = %upload.storage_key, "Failed to delete rejected upload");
+        }
+        return Err(ApiError::Validation(problem));
+    }
+
+    if !state.files.delete_pending_upload(upload_id).await? {
+        return Err(ApiError::Conflict("Upload already confirmed".to_string()));
+    }
+
+    let checksum = object_checksum(&state, StorageProvider::S3, &upload.storage_key).await?;
+    let resp = register_file(
+        &state,
+        tid,
+        upload.room_id,
+        auth.user_id,
+        upload.filename,
+        upload.content_type,
+        info.size,
+        StorageProvider::S3,
+        upload.storage_key,
+        checksum,
+    )
+    .await?;
     Ok(Json(resp))
 }
 
-fn upload_dir() -> PathBuf {
+/// `type/subtype` without parameters.
+fn mime_essence(value: &str) -> &str {
+    value.split(';').next().unwrap_or_default().trim()
+}
+
+/// `type/subtype`, optionally with parameters.
+fn is_mime_type(value: &str) -> bool {
+    match mime_essence(value).split_once('/') {
+        Some((kind, subtype)) => {
+            let valid = |part: &str| {
+                !part.is_empty()
+                    && part
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&b))
+            };
+            valid(kind) && valid(subtype)
+        }
+        None => false,
+    }
+}
+
+pub fn upload_dir() -> PathBuf {
     let dir = std::env::var("ROOMLER_UPLOAD_DIR")
         .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string());
     PathBuf::from(dir)
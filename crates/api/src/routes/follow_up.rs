@@ -0,0 +1,192 @@
+//! Follow-up tasks agreed on during a call. Room members create them for any
+//! tenant member; each assignee sees theirs across rooms, and the reminder
+//! sweep in [`crate::follow_ups`] posts into the room when one comes due.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{FollowUp, FollowUpStatus};
+use roomler_ai_services::dao::base::PaginationParams;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+const MAX_TITLE_LEN: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFollowUpRequest {
+    pub title: String,
+    pub assignee_id: String,
+    /// RFC 3339.
+    pub due_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFollowUpRequest {
+    pub status: FollowUpStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FollowUpListQuery {
+    pub status: Option<FollowUpStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FollowUpResponse {
+    pub id: String,
+    pub room_id: String,
+    pub created_by: String,
+    pub assignee_id: String,
+    pub title: String,
+    pub due_at: Option<String>,
+    pub status: FollowUpStatus,
+    pub reminded_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<FollowUp> for FollowUpResponse {
+    fn from(f: FollowUp) -> Self {
+        let rfc3339 = |t: Option<DateTime>| t.and_then(|t| t.try_to_rfc3339_string().ok());
+        Self {
+            id: f.id.map(|id| id.to_hex()).unwrap_or_default(),
+            room_id: f.room_id.to_hex(),
+            created_by: f.created_by.to_hex(),
+            assignee_id: f.assignee_id.to_hex(),
+            title: f.title,
+            due_at: rfc3339(f.due_at),
+            status: f.status,
+            reminded_at: rfc3339(f.reminded_at),
+            completed_at: rfc3339(f.completed_at),
+            created_at: f.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/follow-up — room
+/// members assign a follow-up to a tenant member.
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<CreateFollowUpRequest>,
+) -> Result<(StatusCode, Json<FollowUpResponse>), ApiError> {
+    let (tid, rid) = require_room_member(&state, &auth, &tenant_id, &room_id).await?;
+
+    let title = body.title.trim().to_string();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_LEN {
+        return Err(ApiError::Validation(format!(
+            "title must be 1-{MAX_TITLE_LEN} characters"
+        )));
+    }
+    let assignee_id = ObjectId::parse_str(&body.assignee_id)
+        .map_err(|_| ApiError::BadRequest("Invalid assignee_id".to_string()))?;
+    if !state.tenants.is_member(tid, assignee_id).await? {
+        return Err(ApiError::Validation(
+            "assignee_id is not a member of this tenant".to_string(),
+        ));
+    }
+    let due_at = body
+        .due_at
+        .as_deref()
+        .map(DateTime::parse_rfc3339_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid due_at, expected RFC 3339".to_string()))?;
+
+    let follow_up = state
+        .follow_ups
+        .create(tid, rid, auth.user_id, assignee_id, title, due_at)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(follow_up.into())))
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/follow-up — the room's
+/// follow-ups, newest first.
+pub async fn list_room(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<FollowUpResponse>>, ApiError> {
+    let (tid, rid) = require_room_member(&state, &auth, &tenant_id, &room_id).await?;
+
+    let follow_ups = state.follow_ups.find_in_room(tid, rid).await?;
+    Ok(Json(follow_ups.into_iter().map(Into::into).collect()))
+}
+
+/// GET /api/tenant/{tenant_id}/follow-up — the caller's follow-ups, soonest
+/// due first, optionally filtered by `status`.
+pub async fn list_mine(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<FollowUpListQuery>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let result = state
+        .follow_ups
+        .find_for_assignee(tid, auth.user_id, query.status, &params)
+        .await?;
+    let items: Vec<FollowUpResponse> = result.items.into_iter().map(Into::into).collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "total": result.total,
+        "page": result.page,
+        "per_page": result.per_page,
+        "total_pages": result.total_pages,
+    })))
+}
+
+/// PUT /api/tenant/{tenant_id}/follow-up/{follow_up_id} — mark done or
+/// reopen. Only the assignee and the creator can.
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, follow_up_id)): Path<(String, String)>,
+    Json(body): Json<UpdateFollowUpRequest>,
+) -> Result<Json<FollowUpResponse>, ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    let id = ObjectId::parse_str(&follow_up_id)
+        .map_err(|_| ApiError::BadRequest("Invalid follow_up_id".to_string()))?;
+
+    let follow_up = state.follow_ups.base.find_by_id_in_tenant(tid, id).await?;
+    if auth.user_id != follow_up.assignee_id && auth.user_id != follow_up.created_by {
+        return Err(ApiError::Forbidden(
+            "Only the assignee or creator can update a follow-up".to_string(),
+        ));
+    }
+
+    state.follow_ups.set_status(tid, id, body.status).await?;
+    let follow_up = state.follow_ups.base.find_by_id(id).await?;
+    Ok(Json(follow_up.into()))
+}
+
+async fn require_room_member(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+) -> Result<(ObjectId, ObjectId), ApiError> {
+    let tid = parse_tenant(tenant_id)?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !state.rooms.is_member(rid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a room member".to_string()));
+    }
+    Ok((tid, rid))
+}
+
+fn parse_tenant(tenant_id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))
+}
@@ -0,0 +1,272 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ConferenceSettings, Room};
+use roomler_ai_services::dao::base::PaginationParams;
+use serde::{Deserialize, Serialize};
+
+use super::room::CreateCallMessageRequest;
+use crate::{
+    error::ApiError,
+    extractors::auth::{AuthUser, GuestAuth},
+    profanity,
+    state::AppState,
+};
+
+/// How long a guest token lasts. Guests join again rather than refresh.
+const GUEST_TOKEN_TTL_SECS: u64 = 2 * 60 * 60;
+
+/// Longest guest display name, in characters.
+const MAX_GUEST_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Serialize)]
+pub struct GuestLinkResponse {
+    pub url: String,
+    pub meeting_code: String,
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GuestJoinRequest {
+    pub display_name: String,
+    /// The `guest` parameter of the guest link.
+    pub key: String,
+    pub passcode: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuestJoinResponse {
+    /// Connect to `/ws?token=…&role=guest` with it, and send it as a
+    /// Bearer token to the guest chat routes.
+    pub token: String,
+    pub expires_in: u64,
+    pub guest_id: String,
+    pub tenant_id: String,
+    pub room_id: String,
+    pub display_name: String,
+    pub ice_servers: Vec<serde_json::Value>,
+    pub force_relay: bool,
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/guest-link — let people
+/// without an account join the conference. Returns the room's guest link,
+/// creating it on first use. Organizers only.
+pub async fn create_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<GuestLinkResponse>, ApiError> {
+    let (room, mut settings) = organized_conference(&state, &auth, &tenant_id, &room_id).await?;
+    let meeting_code = room
+        .meeting_code
+        .clone()
+        .ok_or_else(|| ApiError::Validation("The room has no conference".to_string()))?;
+
+    let key = match settings.guest_link_key.clone() {
+        Some(key) => key,
+        None => {
+            let key = nanoid::nanoid!(32);
+            settings.guest_link_key = Some(key.clone());
+            state
+                .rooms
+                .set_conference_settings(room.tenant_id, room_id_of(&room)?, &settings)
+                .await?;
+            key
+        }
+    };
+
+    Ok(Json(GuestLinkResponse {
+        url: format!(
+            "{}/join/{}?guest={}",
+            state.settings.app.frontend_url, meeting_code, key
+        ),
+        meeting_code,
+        key,
+    }))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/call/guest-link — turn
+/// guest access off. The link stops working and guests can no longer
+/// connect or chat; a new link gets a new key. Organizers only.
+pub async fn revoke_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let (room, mut settings) = organized_conference(&state, &auth, &tenant_id, &room_id).await?;
+    if settings.guest_link_key.take().is_some() {
+        state
+            .rooms
+            .set_conference_settings(room.tenant_id, room_id_of(&room)?, &settings)
+            .await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/guest/conference/{meeting_code}/join — public. Joins through
+/// a guest link under a display name and returns a short-lived token that
+/// only reaches this conference's media signaling and chat.
+pub async fn join(
+    State(state): State<AppState>,
+    Path(meeting_code): Path<String>,
+    Json(body): Json<GuestJoinRequest>,
+) -> Result<Json<GuestJoinResponse>, ApiError> {
+    let (room, _) = super::join::find_meeting(&state, &meeting_code).await?;
+    let rid = room_id_of(&room)?;
+    let settings = room.conference();
+    if settings.guest_link_key.as_deref() != Some(body.key.as_str()) {
+        return Err(ApiError::Forbidden("Invalid guest link".to_string()));
+    }
+    if let Some(passcode) = &settings.passcode
+        && body.passcode.as_deref() != Some(passcode.as_str())
+    {
+        return Err(ApiError::Forbidden("Wrong passcode".to_string()));
+    }
+    let display_name = body.display_name.trim().to_string();
+    if display_name.is_empty() || display_name.chars().count() > MAX_GUEST_NAME_CHARS {
+        return Err(ApiError::Validation(format!(
+            "Display name must be 1-{} characters",
+            MAX_GUEST_NAME_CHARS
+        )));
+    }
+
+    let guest_id = ObjectId::new();
+    state
+        .rooms
+        .add_guest(room.tenant_id, rid, guest_id, display_name.clone())
+        .await?;
+    let token = state.auth.issue_guest_token(
+        guest_id,
+        room.tenant_id,
+        rid,
+        &display_name,
+        GUEST_TOKEN_TTL_SECS,
+    )?;
+
+    Ok(Json(GuestJoinResponse {
+        token,
+        expires_in: GUEST_TOKEN_TTL_SECS,
+        guest_id: guest_id.to_hex(),
+        tenant_id: room.tenant_id.to_hex(),
+        room_id: rid.to_hex(),
+        display_name,
+        ice_servers: crate::ws::handler::conference_ice_servers(&state, &guest_id),
+        force_relay: state.settings.turn.force_relay.unwrap_or(false),
+    }))
+}
+
+/// GET /api/guest/conference/{meeting_code}/message — the conference chat,
+/// for a guest of the conference.
+pub async fn messages(
+    State(state): State<AppState>,
+    guest: GuestAuth,
+    Path(meeting_code): Path<String>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    guest_conference(&state, &guest, &meeting_code).await?;
+
+    let result = state
+        .rooms
+        .find_chat_messages(guest.room_id, &params)
+        .await?;
+    let items: Vec<serde_json::Value> = result
+        .items
+        .iter()
+        .map(super::room::call_message_json)
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "total": result.total,
+        "page": result.page,
+        "per_page": result.per_page,
+        "total_pages": result.total_pages,
+    })))
+}
+
+/// POST /api/guest/conference/{meeting_code}/message — post to the
+/// conference chat as a guest, unless the organizer turned chat off.
+pub async fn create_message(
+    State(state): State<AppState>,
+    guest: GuestAuth,
+    Path(meeting_code): Path<String>,
+    Json(body): Json<CreateCallMessageRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    guest_conference(&state, &guest, &meeting_code).await?;
+    super::room::require_chat_enabled(&state, guest.room_id, guest.guest_id).await?;
+
+    let msg = profanity::post_call(
+        &state,
+        guest.tenant_id,
+        guest.room_id,
+        guest.guest_id,
+        body.content,
+        |content| {
+            state.rooms.create_chat_message(
+                guest.tenant_id,
+                guest.room_id,
+                guest.guest_id,
+                guest.display_name.clone(),
+                content,
+                None,
+            )
+        },
+    )
+    .await?;
+
+    let response = super::room::call_message_json(&msg);
+    super::room::broadcast_call_message(&state, guest.room_id, &response).await;
+
+    Ok(Json(response))
+}
+
+/// The room and conference settings, checking the caller organizes it.
+async fn organized_conference(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+) -> Result<(Room, ConferenceSettings), ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can manage guest access".to_string(),
+        ));
+    }
+    let settings = room.conference();
+    Ok((room, settings))
+}
+
+/// Check the guest's token is for the conference behind `meeting_code` and
+/// that guests are still let in.
+async fn guest_conference(
+    state: &AppState,
+    guest: &GuestAuth,
+    meeting_code: &str,
+) -> Result<(), ApiError> {
+    let (room, _) = super::join::find_meeting(state, meeting_code).await?;
+    if room.id != Some(guest.room_id) {
+        return Err(ApiError::Forbidden(
+            "The token is for another conference".to_string(),
+        ));
+    }
+    if room.conference().guest_link_key.is_none() {
+        return Err(ApiError::Forbidden(
+            "Guest access has been turned off".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn room_id_of(room: &Room) -> Result<ObjectId, ApiError> {
+    room.id
+        .ok_or_else(|| ApiError::Internal("Room has no id".to_string()))
+}
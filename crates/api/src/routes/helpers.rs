@@ -1,4 +1,4 @@
-use bson::oid::ObjectId;
+use bson::{DateTime, oid::ObjectId};
 use roomler_ai_db::models::{NotificationSource, NotificationType};
 
 use crate::state::AppState;
@@ -100,42 +100,8 @@ fn spawn_push_for_offline(
     }
 }
 
-/// Send email notification for a single offline user about a mention (spawns a background task).
-fn spawn_mention_email(
-    state: &AppState,
-    user_id: ObjectId,
-    mentioner_name: String,
-    room_name: String,
-    preview: String,
-    tenant_id_str: &str,
-    room_id_str: &str,
-) {
-    if let Some(ref email_svc) = state.email {
-        let email_svc = email_svc.clone();
-        let users = state.users.clone();
-        let link_url = format!(
-            "{}/tenant/{}/room/{}",
-            state.settings.oauth.base_url, tenant_id_str, room_id_str
-        );
-        tokio::spawn(async move {
-            if let Ok(user) = users.base.find_by_id(user_id).await
-                && let Err(e) = email_svc
-                    .send_mention_notification(
-                        &user.email,
-                        &mentioner_name,
-                        &room_name,
-                        &preview,
-                        &link_url,
-                    )
-                    .await
-            {
-                tracing::warn!(%e, "Failed to send mention email");
-            }
-        });
-    }
-}
-
-/// Create notifications and send push/email for mentioned users in a message.
+/// Create notifications, send `notification:mention` and push/email for
+/// mentioned users in a message.
 #[allow(clippy::too_many_arguments)]
 pub async fn notify_mentions(
     state: &AppState,
@@ -169,7 +135,22 @@ pub async fn notify_mentions(
         ws_type_label: "mention",
     };
 
+    // Lets clients bump their mentions inbox without refetching it.
+    let mention_event = serde_json::json!({
+        "type": "notification:mention",
+        "data": {
+            "tenant_id": tenant_id_str,
+            "room_id": room_id_str,
+            "room_name": room_name,
+            "message_id": message_id.to_hex(),
+            "author_id": author_id.to_hex(),
+            "author_name": mentioner_name,
+            "preview": params.body,
+        }
+    });
     let mut offline_ids = Vec::new();
+    // Offline users get these mentions, and any that follow, in a digest.
+    let since = DateTime::now();
 
     for user_id in mentioned_user_ids {
         if *user_id == author_id {
@@ -177,17 +158,16 @@ pub async fn notify_mentions(
         }
 
         create_and_send_notification(state, &params, *user_id).await;
+        ws::dispatcher::send_to_user_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            user_id,
+            &mention_event,
+        )
+        .await;
 
         if !state.ws_storage.is_connected(user_id) {
-            spawn_mention_email(
-                state,
-                *user_id,
-                mentioner_name.to_string(),
-                room_name.to_string(),
-                params.body.clone(),
-                tenant_id_str,
-                room_id_str,
-            );
+            crate::email_queue::schedule_mention_digest(state, tenant_id, *user_id, since).await;
             offline_ids.push(*user_id);
         }
     }
@@ -1,13 +1,67 @@
 use axum::{
     Json,
     extract::{Path, State},
+    response::Response,
 };
 use bson::oid::ObjectId;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::TaskCategory;
+use super::reaction_rule::ReactionRuleResponse;
+use crate::{
+    error::ApiError,
+    extractors::{auth::AuthUser, permission::RequirePermission},
+    state::AppState,
+};
+use roomler_ai_db::models::{TaskCategory, role::permissions};
+
+/// Everything automated in one room, for a channel settings page.
+#[derive(Debug, Serialize)]
+pub struct RoomIntegrationsResponse {
+    pub room_id: String,
+    /// Whether the caller may change these integrations; only then are
+    /// their secrets included.
+    pub can_manage: bool,
+    /// Rules watching this room or every room, and rules posting into it.
+    pub reaction_rules: Vec<ReactionRuleResponse>,
+}
+
+/// GET /api/tenant/:tid/room/:rid/integrations
+/// Room members and tenant managers may look.
+pub async fn room_integrations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomIntegrationsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    let can_manage = permissions::has(perms, permissions::MANAGE_TENANT);
+    if !can_manage && !state.rooms.is_member(rid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a room member".to_string()));
+    }
+
+    let reaction_rules = state
+        .reaction_rules
+        .find_for_room(tid, rid)
+        .await?
+        .into_iter()
+        .map(|rule| super::reaction_rule::to_response(rule, can_manage))
+        .collect();
+
+    Ok(Json(RoomIntegrationsResponse {
+        room_id: rid.to_hex(),
+        can_manage,
+        reaction_rules,
+    }))
+}
 
 /// POST /api/tenant/:tid/file/:fid/recognize
 /// Trigger AI document recognition for an uploaded file.
@@ -50,9 +104,9 @@ pub async fn recognize_file(
     let files_dao = Arc::clone(&state.files);
     let task_store = Arc::clone(state.tasks.store());
 
-    let upload_dir = std::env::var("ROOMLER_UPLOAD_DIR")
-        .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string());
-    let file_path = std::path::PathBuf::from(upload_dir).join(&file.storage_key);
+    let object_store = Arc::clone(&state.object_store);
+    let storage_provider = file.storage_provider;
+    let storage_key = file.storage_key.clone();
     let content_type = file.content_type.clone();
 
     state.tasks.spawn_task(task_id, async move {
@@ -61,7 +115,8 @@ pub async fn recognize_file(
             .await
             .map_err(|e| format!("{}", e))?;
 
-        let file_bytes = tokio::fs::read(&file_path)
+        let file_bytes = object_store
+            .get(storage_provider, &storage_key)
             .await
             .map_err(|e| format!("Failed to read file: {}", e))?;
 
@@ -99,7 +154,7 @@ pub async fn recognize_file(
             .map_err(|e| format!("Failed to update file: {}", e))?;
 
         task_store
-            .complete(task_id, None, None)
+            .complete(task_id, None, None, None)
             .await
             .map_err(|e| format!("{}", e))?;
 
@@ -113,109 +168,30 @@ pub async fn recognize_file(
 }
 
 /// POST /api/tenant/:tid/export/conversation-pdf
-/// Export conversation as PDF (background task).
+/// Export conversation as PDF; see [`super::export`] for when it runs in the
+/// background.
 #[derive(Debug, Deserialize)]
 pub struct ExportPdfRequest {
     pub room_id: String,
+    /// Replace identities with stable pseudonyms and strip emails/phone numbers.
+    #[serde(default)]
+    pub anonymize: bool,
 }
 
 pub async fn export_conversation_pdf(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    RequirePermission {
+        auth, tenant_id, ..
+    }: RequirePermission<{ permissions::READ_HISTORY }>,
     Json(body): Json<ExportPdfRequest>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
-    let rid = ObjectId::parse_str(&body.room_id)
-        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
-
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
-
-    let task = state
-        .tasks
-        .create_task(
-            tid,
-            auth.user_id,
-            "export_conversation_pdf".to_string(),
-            TaskCategory::Export,
-            serde_json::json!({ "room_id": body.room_id, "format": "pdf" }),
-        )
-        .await?;
-
-    let task_id = task.id.unwrap();
-    let messages_dao = Arc::clone(&state.messages);
-    let users_dao = Arc::clone(&state.users);
-    let task_store = Arc::clone(state.tasks.store());
-
-    state.tasks.spawn_task(task_id, async move {
-        let params = roomler_ai_services::dao::base::PaginationParams {
-            page: 1,
-            per_page: 10000,
-            before: None,
-        };
-        let result = messages_dao
-            .find_in_room(rid, &params)
-            .await
-            .map_err(|e| format!("Failed to fetch messages: {}", e))?;
-
-        task_store
-            .update_progress(task_id, 30, Some("Fetched messages".to_string()))
-            .await
-            .map_err(|e| format!("{}", e))?;
-
-        let author_ids: Vec<ObjectId> = result
-            .items
-            .iter()
-            .map(|m| m.author_id)
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-
-        let mut user_map = std::collections::HashMap::new();
-        for uid in &author_ids {
-            if let Ok(user) = users_dao.base.find_by_id(*uid).await {
-                user_map.insert(*uid, user);
-            }
-        }
-
-        task_store
-            .update_progress(task_id, 60, Some("Generating PDF".to_string()))
-            .await
-            .map_err(|e| format!("{}", e))?;
-
-        let bytes =
-            roomler_ai_services::export::pdf::export_conversation(&result.items, &user_map)?;
-
-        let export_dir = std::env::var("ROOMLER_UPLOAD_DIR")
-            .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string());
-        let export_dir = std::path::PathBuf::from(export_dir).join("exports");
-        tokio::fs::create_dir_all(&export_dir)
-            .await
-            .map_err(|e| format!("Failed to create export dir: {}", e))?;
-
-        let file_name = format!("conversation-export-{}.pdf", task_id.to_hex());
-        let file_path = export_dir.join(&file_name);
-        tokio::fs::write(&file_path, &bytes)
-            .await
-            .map_err(|e| format!("Failed to write PDF: {}", e))?;
-
-        task_store
-            .complete(
-                task_id,
-                Some(file_path.to_string_lossy().to_string()),
-                Some(file_name),
-            )
-            .await
-            .map_err(|e| format!("{}", e))?;
-
-        Ok(())
-    });
-
-    Ok(Json(serde_json::json!({
-        "task_id": task_id.to_hex(),
-        "status": "pending",
-    })))
+) -> Result<Response, ApiError> {
+    super::export::run_export(
+        &state,
+        auth.user_id,
+        tenant_id,
+        &body.room_id,
+        body.anonymize,
+        super::export::ExportFormat::Pdf,
+    )
+    .await
 }
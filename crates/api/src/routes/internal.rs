@@ -0,0 +1,13 @@
+use axum::Json;
+
+use crate::extractors::service::ServiceCaller;
+
+/// GET /api/internal/whoami — lets a worker check its service token
+/// against this API.
+pub async fn whoami(caller: ServiceCaller) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "service": caller.service,
+        "audience": caller.claims.aud,
+        "expires_at": caller.claims.exp,
+    }))
+}
@@ -7,12 +7,23 @@ use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audit, email_queue,
     error::ApiError,
-    extractors::auth::{AuthUser, OptionalAuthUser},
+    extractors::{
+        auth::{AuthUser, OptionalAuthUser},
+        client::ClientInfo,
+        permission::RequirePermission,
+    },
+    routes::role::{self, require_assignable},
     state::AppState,
 };
-use roomler_ai_db::models::role::permissions;
-use roomler_ai_services::dao::{base::PaginationParams, invite::CreateInviteParams};
+use roomler_ai_db::models::{AuditChange, Invite, Room, TaskCategory, actions, role::permissions};
+use roomler_ai_services::{
+    dao::{base::PaginationParams, invite::CreateInviteParams},
+    email::EmailTemplate,
+    member_import::{self, ImportRow, RowOutcome, RowResult},
+};
+use std::{collections::HashMap, sync::Arc};
 
 // ─── Response types ──────────────────────────────────────────────
 
@@ -24,6 +35,12 @@ pub struct InviteInfoResponse {
     pub inviter_name: String,
     pub is_valid: bool,
     pub status: String,
+    pub max_uses: Option<u32>,
+    /// Acceptances left; `null` when unlimited.
+    pub remaining_uses: Option<u32>,
+    pub expires_at: Option<String>,
+    /// Channels joined on acceptance, besides the default ones.
+    pub rooms: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub already_member: Option<bool>,
 }
@@ -37,8 +54,10 @@ pub struct InviteResponse {
     pub target_email: Option<String>,
     pub max_uses: Option<u32>,
     pub use_count: u32,
+    pub remaining_uses: Option<u32>,
     pub status: String,
     pub assign_role_ids: Vec<String>,
+    pub join_room_ids: Vec<String>,
     pub expires_at: Option<String>,
     pub created_at: String,
 }
@@ -59,6 +78,9 @@ pub struct CreateInviteRequest {
     pub expires_in_hours: Option<u64>,
     #[serde(default)]
     pub assign_role_ids: Vec<String>,
+    /// Channels to join on acceptance.
+    #[serde(default)]
+    pub join_room_ids: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -121,15 +143,32 @@ pub async fn get_invite_info(
     };
 
     let status = format!("{:?}", invite.status).to_lowercase();
+    let rooms = state
+        .rooms
+        .base
+        .find_by_ids(&invite.join_room_ids)
+        .await?
+        .into_iter()
+        .filter(|r| {
+            r.tenant_id == invite.tenant_id && r.is_open && !r.is_archived && r.deleted_at.is_none()
+        })
+        .map(|r| r.name)
+        .collect();
 
     Ok(Json(InviteInfoResponse {
-        code: invite.code,
         tenant_name: tenant.name,
         tenant_slug: tenant.slug,
         inviter_name: inviter.display_name,
         is_valid,
         status,
+        max_uses: invite.max_uses,
+        remaining_uses: invite.remaining_uses(),
+        expires_at: invite
+            .expires_at
+            .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
+        rooms,
         already_member,
+        code: invite.code,
     }))
 }
 
@@ -137,6 +176,7 @@ pub async fn get_invite_info(
 pub async fn accept_invite(
     State(state): State<AppState>,
     auth: AuthUser,
+    client: ClientInfo,
     Path(code): Path<String>,
 ) -> Result<Json<AcceptInviteResponse>, ApiError> {
     let invite = state.invites.find_by_code(&code).await?;
@@ -167,34 +207,22 @@ pub async fn accept_invite(
         ));
     }
 
-    // Determine roles to assign (default to "member" role if none specified)
-    let role_ids = if invite.assign_role_ids.is_empty() {
-        let member_role = state
-            .tenants
-            .get_role_by_name(invite.tenant_id, "member")
-            .await?;
-        vec![member_role.id.unwrap()]
-    } else {
-        invite.assign_role_ids.clone()
-    };
-
-    // Add the user to the tenant
-    state
-        .tenants
-        .add_member(
-            invite.tenant_id,
-            auth.user_id,
-            role_ids,
-            Some(invite.inviter_id),
-        )
-        .await?;
-
-    // Atomically increment the use count
-    state
-        .invites
-        .increment_use_count(invite.id.unwrap())
-        .await
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    redeem(&state, &invite, auth.user_id).await?;
+    audit::record(
+        &state,
+        invite.tenant_id,
+        auth.user_id,
+        &client,
+        actions::MEMBER_ADD,
+        Some(auth.user_id),
+        vec![audit::change(
+            "invite_code",
+            None,
+            Some(invite.code.clone().into()),
+        )],
+    )
+    .await;
+    crate::billing_events::check_limits(&state, invite.tenant_id).await;
 
     let tenant = state.tenants.base.find_by_id(invite.tenant_id).await?;
 
@@ -207,16 +235,14 @@ pub async fn accept_invite(
 
 // ─── Tenant-scoped handlers (require INVITE_MEMBERS) ───────────
 
+type InviteMembers = RequirePermission<{ permissions::INVITE_MEMBERS }>;
+
 /// GET /api/tenant/{tenant_id}/invite — list tenant invites
 pub async fn list_invites(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    InviteMembers { tenant_id: tid, .. }: InviteMembers,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = parse_oid(&tenant_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
-
     let result = state.invites.list_by_tenant(tid, &params).await?;
 
     let items: Vec<InviteResponse> = result.items.into_iter().map(invite_to_response).collect();
@@ -233,18 +259,26 @@ pub async fn list_invites(
 /// POST /api/tenant/{tenant_id}/invite — create invite
 pub async fn create_invite(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    InviteMembers {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: InviteMembers,
+    client: ClientInfo,
     Json(body): Json<CreateInviteRequest>,
 ) -> Result<(StatusCode, Json<InviteResponse>), ApiError> {
-    let tid = parse_oid(&tenant_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
-
     let assign_role_ids: Vec<ObjectId> = body
         .assign_role_ids
         .iter()
         .map(|s| parse_oid(s))
         .collect::<Result<Vec<_>, _>>()?;
+    require_assignable(&state, tid, caller, &assign_role_ids).await?;
+    let join_room_ids = resolve_join_rooms(&state, tid, auth.user_id, &body.join_room_ids).await?;
+    if body.max_uses == Some(0) {
+        return Err(ApiError::Validation(
+            "max_uses must be at least 1".to_string(),
+        ));
+    }
 
     let expires_in_hours = body.expires_in_hours.or(Some(168)); // default 7 days
 
@@ -260,28 +294,31 @@ pub async fn create_invite(
                 max_uses: body.max_uses,
                 expires_in_hours,
                 assign_role_ids,
+                join_room_ids,
             },
         )
         .await?;
-
-    // Send invite email if target_email is set and email service is configured
-    if let (Some(email_addr), Some(email_svc)) = (&target_email, &state.email) {
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::INVITE_CREATE,
+        invite.id,
+        invite_changes(&invite),
+    )
+    .await;
+
+    if let Some(email_addr) = &target_email {
         let inviter = state.users.base.find_by_id(auth.user_id).await.ok();
-        let inviter_name = inviter.map(|u| u.display_name).unwrap_or_default();
         let tenant = state.tenants.base.find_by_id(tid).await.ok();
-        let tenant_name = tenant.map(|t| t.name).unwrap_or_default();
-        let invite_url = format!("{}/invite/{}", state.settings.oauth.base_url, invite.code,);
-        let email_svc = email_svc.clone();
-        let email_addr = email_addr.clone();
-        // Fire-and-forget — don't block the response on email delivery
-        tokio::spawn(async move {
-            if let Err(e) = email_svc
-                .send_invite(&email_addr, &inviter_name, &tenant_name, &invite_url)
-                .await
-            {
-                tracing::warn!(%e, "Failed to send invite email");
-            }
-        });
+        let template = EmailTemplate::InviteCreated {
+            inviter_name: inviter.map(|u| u.display_name).unwrap_or_default(),
+            tenant_name: tenant.map(|t| t.name).unwrap_or_default(),
+            invite_url: format!("{}/invite/{}", state.settings.oauth.base_url, invite.code),
+            calendar: super::calendar::invitation(&state, &invite.join_room_ids, email_addr).await,
+        };
+        email_queue::send(&state, Some(tid), auth.user_id, email_addr, template).await;
     }
 
     Ok((StatusCode::CREATED, Json(invite_to_response(invite))))
@@ -290,13 +327,14 @@ pub async fn create_invite(
 /// POST /api/tenant/{tenant_id}/invite/batch — create multiple invites
 pub async fn batch_create_invite(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    InviteMembers {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: InviteMembers,
+    client: ClientInfo,
     Json(body): Json<BatchCreateInviteRequest>,
 ) -> Result<(StatusCode, Json<BatchCreateInviteResponse>), ApiError> {
-    let tid = parse_oid(&tenant_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
-
     if body.invites.is_empty() {
         return Err(ApiError::BadRequest("No invites provided".to_string()));
     }
@@ -311,9 +349,24 @@ pub async fn batch_create_invite(
     for item in body.invites {
         let assign_role_ids: Result<Vec<ObjectId>, _> =
             item.assign_role_ids.iter().map(|s| parse_oid(s)).collect();
+        let assign_role_ids = match assign_role_ids {
+            Ok(ids) => require_assignable(&state, tid, caller, &ids)
+                .await
+                .map(|()| ids),
+            Err(e) => Err(e),
+        };
+        let targets = match assign_role_ids {
+            Ok(_) if item.max_uses == Some(0) => Err(ApiError::Validation(
+                "max_uses must be at least 1".to_string(),
+            )),
+            Ok(role_ids) => resolve_join_rooms(&state, tid, auth.user_id, &item.join_room_ids)
+                .await
+                .map(|room_ids| (role_ids, room_ids)),
+            Err(e) => Err(e),
+        };
 
-        match assign_role_ids {
-            Ok(role_ids) => {
+        match targets {
+            Ok((role_ids, join_room_ids)) => {
                 let expires_in_hours = item.expires_in_hours.or(Some(168));
                 match state
                     .invites
@@ -325,15 +378,28 @@ pub async fn batch_create_invite(
                             max_uses: item.max_uses,
                             expires_in_hours,
                             assign_role_ids: role_ids,
+                            join_room_ids,
                         },
                     )
                     .await
                 {
-                    Ok(invite) => results.push(BatchInviteResult {
-                        invite: Some(invite_to_response(invite)),
-                        error: None,
-                        target_email: item.target_email,
-                    }),
+                    Ok(invite) => {
+                        audit::record(
+                            &state,
+                            tid,
+                            auth.user_id,
+                            &client,
+                            actions::INVITE_CREATE,
+                            invite.id,
+                            invite_changes(&invite),
+                        )
+                        .await;
+                        results.push(BatchInviteResult {
+                            invite: Some(invite_to_response(invite)),
+                            error: None,
+                            target_email: item.target_email,
+                        })
+                    }
                     Err(e) => results.push(BatchInviteResult {
                         invite: None,
                         error: Some(e.to_string()),
@@ -365,14 +431,27 @@ pub async fn batch_create_invite(
 /// DELETE /api/tenant/{tenant_id}/invite/{invite_id} — revoke invite
 pub async fn revoke_invite(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, invite_id)): Path<(String, String)>,
+    InviteMembers {
+        auth,
+        tenant_id: tid,
+        ..
+    }: InviteMembers,
+    client: ClientInfo,
+    Path((_, invite_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = parse_oid(&tenant_id)?;
     let iid = parse_oid(&invite_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
 
     state.invites.revoke(iid, tid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::INVITE_REVOKE,
+        Some(iid),
+        Vec::new(),
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "revoked": true })))
 }
@@ -380,13 +459,14 @@ pub async fn revoke_invite(
 /// POST /api/tenant/{tenant_id}/member — direct add member
 pub async fn add_member(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    InviteMembers {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: InviteMembers,
+    client: ClientInfo,
     Json(body): Json<AddMemberRequest>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
-    let tid = parse_oid(&tenant_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
-
     let user_id = parse_oid(&body.user_id)?;
 
     // Check not already a member
@@ -403,11 +483,28 @@ pub async fn add_member(
             .map(|s| parse_oid(s))
             .collect::<Result<Vec<_>, _>>()?
     };
+    require_assignable(&state, tid, caller, &role_ids).await?;
 
     let member = state
         .tenants
         .add_member(tid, user_id, role_ids, Some(auth.user_id))
         .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::MEMBER_ADD,
+        Some(user_id),
+        vec![audit::change(
+            "role_ids",
+            None,
+            Some(role_ids_json(&member.role_ids)),
+        )],
+    )
+    .await;
+    state.onboarding.join_default_rooms(tid, user_id).await;
+    crate::billing_events::check_limits(&state, tid).await;
 
     Ok((
         StatusCode::CREATED,
@@ -419,30 +516,444 @@ pub async fn add_member(
     ))
 }
 
-// ─── Helpers ────────────────────────────────────────────────────
+/// DELETE /api/tenant/{tenant_id}/member/{user_id} — remove a member from
+/// the tenant and its rooms. Needs KICK_MEMBERS; the owner can't be removed.
+pub async fn remove_member(
+    State(state): State<AppState>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::KICK_MEMBERS }>,
+    client: ClientInfo,
+    Path((_, user_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let uid = parse_oid(&user_id)?;
 
-fn parse_oid(s: &str) -> Result<ObjectId, ApiError> {
-    ObjectId::parse_str(s).map_err(|_| ApiError::BadRequest(format!("Invalid ObjectId: {}", s)))
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    if tenant.owner_id == uid {
+        return Err(ApiError::BadRequest(
+            "The tenant owner can't be removed".to_string(),
+        ));
+    }
+    let member = state
+        .tenants
+        .find_member(tid, uid)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
+
+    for room in state.rooms.find_user_rooms(tid, uid).await? {
+        state.rooms.leave(tid, room.id.unwrap(), uid).await?;
+    }
+    state.tenants.remove_member(tid, uid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::MEMBER_REMOVE,
+        Some(uid),
+        vec![audit::change(
+            "role_ids",
+            Some(role_ids_json(&member.role_ids)),
+            None,
+        )],
+    )
+    .await;
+    crate::billing_events::check_limits(&state, tid).await;
+
+    Ok(Json(serde_json::json!({ "removed": true })))
+}
+
+/// POST /api/tenant/{tenant_id}/member/import — bulk-add members from a CSV
+/// body (`email`, optional `name` and `;`-separated `roles`). Rows are
+/// validated up front; existing users are added and everyone else gets an
+/// invite in a background task whose download is a per-row CSV report.
+pub async fn import_members(
+    State(state): State<AppState>,
+    InviteMembers {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: InviteMembers,
+    client: ClientInfo,
+    body: String,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let parsed = member_import::parse(&body).map_err(ApiError::Validation)?;
+
+    let role_ids: HashMap<String, (ObjectId, u64)> = state
+        .tenants
+        .roles
+        .find_many(bson::doc! { "tenant_id": tid }, None)
+        .await?
+        .into_iter()
+        .filter_map(|r| Some((r.name.to_lowercase(), (r.id?, r.permissions))))
+        .collect();
+    let mut results = parsed.invalid;
+    let mut rows: Vec<(ImportRow, Vec<ObjectId>)> = Vec::with_capacity(parsed.rows.len());
+    for row in parsed.rows {
+        if let Some(unknown) = row
+            .roles
+            .iter()
+            .find(|name| !role_ids.contains_key(name.as_str()))
+        {
+            results.push(RowResult::invalid(
+                row.line,
+                &row.email,
+                format!("Unknown role {:?}", unknown),
+            ));
+        } else if let Some(name) = row
+            .roles
+            .iter()
+            .find(|name| !role::grantable(caller, role_ids[name.as_str()].1))
+        {
+            results.push(RowResult::invalid(
+                row.line,
+                &row.email,
+                format!("Can't assign role {:?}", name),
+            ));
+        } else {
+            let mut ids: Vec<ObjectId> = row.roles.iter().map(|n| role_ids[n].0).collect();
+            ids.sort();
+            ids.dedup();
+            rows.push((row, ids));
+        }
+    }
+    let (total, invalid) = (rows.len() + results.len(), results.len());
+
+    let task = state
+        .tasks
+        .create_task(
+            tid,
+            auth.user_id,
+            "import_members".to_string(),
+            TaskCategory::Import,
+            serde_json::json!({ "rows": total, "invalid": invalid }),
+        )
+        .await?;
+    let task_id = task.id.unwrap();
+
+    let users = Arc::clone(&state.users);
+    let tenants = Arc::clone(&state.tenants);
+    let invites = Arc::clone(&state.invites);
+    let onboarding = Arc::clone(&state.onboarding);
+    let task_store = Arc::clone(state.tasks.store());
+    let object_store = Arc::clone(&state.object_store);
+    let inviter_id = auth.user_id;
+    let inviter_name = state
+        .users
+        .base
+        .find_by_id(auth.user_id)
+        .await
+        .map(|u| u.display_name)
+        .unwrap_or_default();
+    let tenant_name = state
+        .tenants
+        .base
+        .find_by_id(tid)
+        .await
+        .map(|t| t.name)
+        .unwrap_or_default();
+    let base_url = state.settings.oauth.base_url.clone();
+    let audit_state = state.clone();
+
+    state.tasks.spawn_task(task_id, async move {
+        let count = rows.len().max(1);
+        for (i, (row, role_ids)) in rows.into_iter().enumerate() {
+            let result = |outcome, detail: Option<String>| RowResult {
+                line: row.line,
+                email: row.email.clone(),
+                outcome,
+                detail,
+            };
+            let existing = users.find_by_email(&row.email).await.ok();
+            let outcome = match existing.and_then(|u| u.id) {
+                Some(user_id) => match tenants.is_member(tid, user_id).await {
+                    Ok(true) => result(RowOutcome::AlreadyMember, None),
+                    Ok(false) => match tenants
+                        .add_member(tid, user_id, role_ids, Some(inviter_id))
+                        .await
+                    {
+                        Ok(member) => {
+                            onboarding.join_default_rooms(tid, user_id).await;
+                            audit::record(
+                                &audit_state,
+                                tid,
+                                inviter_id,
+                                &client,
+                                actions::MEMBER_ADD,
+                                Some(user_id),
+                                vec![audit::change(
+                                    "role_ids",
+                                    None,
+                                    Some(role_ids_json(&member.role_ids)),
+                                )],
+                            )
+                            .await;
+                            result(RowOutcome::Added, None)
+                        }
+                        Err(e) => result(RowOutcome::Failed, Some(e.to_string())),
+                    },
+                    Err(e) => result(RowOutcome::Failed, Some(e.to_string())),
+                },
+                None => match invites
+                    .create(
+                        tid,
+                        inviter_id,
+                        CreateInviteParams {
+                            target_email: Some(row.email.clone()),
+                            max_uses: None,
+                            expires_in_hours: Some(168),
+                            assign_role_ids: role_ids,
+                            join_room_ids: Vec::new(),
+                        },
+                    )
+                    .await
+                {
+                    Ok(invite) => {
+                        audit::record(
+                            &audit_state,
+                            tid,
+                            inviter_id,
+                            &client,
+                            actions::INVITE_CREATE,
+                            invite.id,
+                            invite_changes(&invite),
+                        )
+                        .await;
+                        let template = EmailTemplate::InviteCreated {
+                            inviter_name: inviter_name.clone(),
+                            tenant_name: tenant_name.clone(),
+                            invite_url: format!("{}/invite/{}", base_url, invite.code),
+                            calendar: None,
+                        };
+                        email_queue::send(
+                            &audit_state,
+                            Some(tid),
+                            inviter_id,
+                            &row.email,
+                            template,
+                        )
+                        .await;
+                        result(RowOutcome::Invited, Some(invite.code))
+                    }
+                    Err(e) => result(RowOutcome::Failed, Some(e.to_string())),
+                },
+            };
+            results.push(outcome);
+
+            let progress = ((i + 1) * 90 / count) as u8;
+            task_store
+                .update_progress(task_id, progress, None)
+                .await
+                .map_err(|e| format!("Failed to update progress: {}", e))?;
+        }
+        if results.iter().any(|r| r.outcome == RowOutcome::Added) {
+            crate::billing_events::check_limits(&audit_state, tid).await;
+        }
+
+        let summary = [
+            RowOutcome::Added,
+            RowOutcome::Invited,
+            RowOutcome::AlreadyMember,
+            RowOutcome::Invalid,
+            RowOutcome::Failed,
+        ]
+        .iter()
+        .map(|o| {
+            let n = results.iter().filter(|r| r.outcome == *o).count();
+            format!("{} {}", n, o.as_str())
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+        task_store
+            .update_progress(task_id, 95, Some(summary))
+            .await
+            .map_err(|e| format!("Failed to update progress: {}", e))?;
+
+        let file_name = format!("member-import-{}.csv", task_id.to_hex());
+        let key = format!("imports/{}", file_name);
+        let storage_provider = object_store
+            .put(&key, member_import::report_csv(&results).into_bytes())
+            .await
+            .map_err(|e| format!("Failed to write import report: {}", e))?;
+
+        task_store
+            .complete(task_id, Some(key), Some(file_name), Some(storage_provider))
+            .await
+            .map_err(|e| format!("Failed to complete task: {}", e))?;
+
+        Ok(())
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "task_id": task_id.to_hex(),
+            "status": "pending",
+            "rows": total,
+            "invalid": invalid,
+        })),
+    ))
 }
 
-async fn require_invite_permission(
+// ─── Helpers ────────────────────────────────────────────────────
+
+/// Make `user_id` a member through `invite`, with its roles (the `member`
+/// role when it names none) and channels. The use is claimed before the
+/// membership is added, so concurrent acceptances can't go past
+/// `max_uses`; it is handed back if adding the member fails.
+pub(crate) async fn redeem(
     state: &AppState,
-    tenant_id: ObjectId,
+    invite: &Invite,
     user_id: ObjectId,
 ) -> Result<(), ApiError> {
-    let perms = state
+    let role_ids = if invite.assign_role_ids.is_empty() {
+        let member_role = state
+            .tenants
+            .get_role_by_name(invite.tenant_id, "member")
+            .await?;
+        vec![member_role.id.unwrap()]
+    } else {
+        invite.assign_role_ids.clone()
+    };
+
+    let invite_id = invite.id.unwrap();
+    state
+        .invites
+        .claim_use(invite_id)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    if let Err(e) = state
         .tenants
-        .get_member_permissions(tenant_id, user_id)
-        .await?;
-    if !permissions::has(perms, permissions::INVITE_MEMBERS) {
-        return Err(ApiError::Forbidden(
-            "Missing INVITE_MEMBERS permission".to_string(),
-        ));
+        .add_member(invite.tenant_id, user_id, role_ids, Some(invite.inviter_id))
+        .await
+    {
+        if let Err(release) = state.invites.release_use(invite_id).await {
+            tracing::warn!(%invite_id, %release, "Failed to release invite use");
+        }
+        return Err(e.into());
     }
+
+    state
+        .onboarding
+        .join_default_rooms(invite.tenant_id, user_id)
+        .await;
+    let join_room_ids = joinable_rooms(state, invite).await?;
+    state
+        .onboarding
+        .join_rooms(invite.tenant_id, user_id, &join_room_ids)
+        .await;
     Ok(())
 }
 
+/// The invite's channels that can still be joined through it: live,
+/// unarchived and open, or private with the inviter still a member.
+async fn joinable_rooms(state: &AppState, invite: &Invite) -> Result<Vec<ObjectId>, ApiError> {
+    let mut ids = Vec::with_capacity(invite.join_room_ids.len());
+    for room in state.rooms.base.find_by_ids(&invite.join_room_ids).await? {
+        let Some(id) = room.id else {
+            continue;
+        };
+        if room.tenant_id == invite.tenant_id
+            && room.deleted_at.is_none()
+            && !room.is_archived
+            && !room.is_dm()
+            && can_share(state, &room, invite.inviter_id).await?
+        {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Whether `inviter_id` may hand out `room`: anyone can share an open
+/// channel, only its members a private one.
+async fn can_share(state: &AppState, room: &Room, inviter_id: ObjectId) -> Result<bool, ApiError> {
+    if room.is_open {
+        return Ok(true);
+    }
+    match room.id {
+        Some(id) => Ok(state.rooms.is_member(id, inviter_id).await?),
+        None => Ok(false),
+    }
+}
+
+/// At most this many channels per invite.
+const MAX_JOIN_ROOMS: usize = 20;
+
+/// Parse an invite's channels: each must be a live, unarchived channel of
+/// the tenant, open or one `inviter_id` is a member of.
+async fn resolve_join_rooms(
+    state: &AppState,
+    tenant_id: ObjectId,
+    inviter_id: ObjectId,
+    room_ids: &[String],
+) -> Result<Vec<ObjectId>, ApiError> {
+    let mut ids: Vec<ObjectId> = Vec::with_capacity(room_ids.len());
+    for s in room_ids {
+        let id = parse_oid(s)?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.len() > MAX_JOIN_ROOMS {
+        return Err(ApiError::Validation(format!(
+            "At most {} channels per invite",
+            MAX_JOIN_ROOMS
+        )));
+    }
+    let rooms = state.rooms.base.find_by_ids(&ids).await?;
+    for &id in &ids {
+        let room = rooms
+            .iter()
+            .find(|r| r.id == Some(id) && r.tenant_id == tenant_id && r.deleted_at.is_none())
+            .ok_or_else(|| ApiError::Validation(format!("Unknown room {}", id)))?;
+        // A private channel the inviter isn't in is reported as unknown,
+        // so its name doesn't leak.
+        if !can_share(state, room, inviter_id).await? {
+            return Err(ApiError::Validation(format!("Unknown room {}", id)));
+        }
+        if room.is_dm() || room.is_archived {
+            return Err(ApiError::Validation(format!(
+                "{} can't be joined through an invite",
+                room.name
+            )));
+        }
+    }
+    Ok(ids)
+}
+
+fn role_ids_json(role_ids: &[ObjectId]) -> serde_json::Value {
+    role_ids.iter().map(|id| id.to_hex()).collect()
+}
+
+fn invite_changes(invite: &Invite) -> Vec<AuditChange> {
+    vec![
+        audit::change(
+            "target_email",
+            None,
+            invite.target_email.clone().map(Into::into),
+        ),
+        audit::change(
+            "assign_role_ids",
+            None,
+            Some(role_ids_json(&invite.assign_role_ids)),
+        ),
+        audit::change(
+            "join_room_ids",
+            None,
+            Some(role_ids_json(&invite.join_room_ids)),
+        ),
+    ]
+}
+
+fn parse_oid(s: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(s).map_err(|_| ApiError::BadRequest(format!("Invalid ObjectId: {}", s)))
+}
+
 fn invite_to_response(invite: roomler_ai_db::models::Invite) -> InviteResponse {
+    let remaining_uses = invite.remaining_uses();
     InviteResponse {
         id: invite.id.unwrap().to_hex(),
         code: invite.code,
@@ -451,12 +962,14 @@ fn invite_to_response(invite: roomler_ai_db::models::Invite) -> InviteResponse {
         target_email: invite.target_email,
         max_uses: invite.max_uses,
         use_count: invite.use_count,
+        remaining_uses,
         status: format!("{:?}", invite.status).to_lowercase(),
         assign_role_ids: invite
             .assign_role_ids
             .iter()
             .map(|id| id.to_hex())
             .collect(),
+        join_room_ids: invite.join_room_ids.iter().map(|id| id.to_hex()).collect(),
         expires_at: invite
             .expires_at
             .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
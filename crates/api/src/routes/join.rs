@@ -0,0 +1,330 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Room, Tenant};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::OptionalAuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct JoinInfoResponse {
+    pub meeting_code: String,
+    pub subject: String,
+    /// `not_started`, `in_progress` or `ended`.
+    pub status: String,
+    pub passcode_required: bool,
+    pub lobby_enabled: bool,
+    pub scheduled_start: Option<String>,
+    pub tenant: JoinTenantBranding,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JoinTenantBranding {
+    pub name: String,
+    pub slug: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrejoinResponse {
+    #[serde(flatten)]
+    pub info: JoinInfoResponse,
+    /// `organizer`, `member`, `signed_in` (an account outside the tenant)
+    /// or `guest` (not signed in). Organizers and members join as
+    /// themselves, anyone else through the conference's guest link.
+    pub join_as: &'static str,
+    /// Whether the caller can join: as an organizer or member, or with the
+    /// guest link's key as `?guest=`.
+    pub can_join: bool,
+    /// Members join at `/api/tenant/{tenant_id}/room/{room_id}/call/join`.
+    pub tenant_id: Option<String>,
+    pub room_id: Option<String>,
+    /// Whether the conference lets people in through a guest link.
+    pub guest_link_enabled: bool,
+    /// Where a caller with a valid `?guest=` key joins as a guest,
+    /// `/api/guest/conference/{meeting_code}/join`.
+    pub guest_join_url: Option<String>,
+    pub display_name: DisplayNameRequirement,
+    /// Empty unless the caller can join.
+    pub ice_servers: Vec<serde_json::Value>,
+    pub force_relay: bool,
+    pub capabilities: PrejoinCapabilities,
+    pub waiting_room: WaitingRoomStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisplayNameRequirement {
+    /// Whether the caller has to enter a name; signed-in callers join
+    /// under their profile's.
+    pub required: bool,
+    pub suggested: Option<String>,
+}
+
+/// What the caller will be able to do in the call, with the in-call
+/// controls that bind them applied.
+#[derive(Debug, Serialize)]
+pub struct PrejoinCapabilities {
+    pub audio: bool,
+    pub video: bool,
+    pub screen_share: bool,
+    pub recording: bool,
+    pub captions: bool,
+    pub chat: bool,
+    pub reactions: bool,
+    /// The microphone starts muted.
+    pub start_muted: bool,
+    pub can_unmute: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaitingRoomStatus {
+    pub lobby_enabled: bool,
+    /// Whether the caller would wait in the lobby to be let in.
+    pub will_wait_in_lobby: bool,
+    pub waitlist_enabled: bool,
+    pub participants: usize,
+    /// The plan's participant cap; `None` when unlimited.
+    pub max_participants: Option<u32>,
+    pub full: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrejoinQuery {
+    /// The `guest` parameter of the conference's guest link.
+    pub guest: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveQuery {
+    pub passcode: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedConference {
+    pub meeting_code: String,
+    pub join_url: Option<String>,
+    pub tenant_id: String,
+    pub tenant_slug: String,
+    pub room_id: String,
+    pub subject: String,
+    /// `not_started`, `in_progress` or `ended`.
+    pub status: String,
+    /// As in prejoin: `organizer`, `member`, `signed_in` or `guest`.
+    pub join_as: &'static str,
+    pub waiting_room: WaitingRoomStatus,
+}
+
+/// The meeting and its tenant, as long as both are open.
+pub(crate) async fn find_meeting(
+    state: &AppState,
+    meeting_code: &str,
+) -> Result<(Room, Tenant), ApiError> {
+    let not_found = || ApiError::NotFound("Meeting not found".to_string());
+
+    let room = state
+        .rooms
+        .find_by_meeting_code(meeting_code)
+        .await
+        .map_err(|_| not_found())?;
+    if room.is_archived {
+        return Err(not_found());
+    }
+    let tenant = state
+        .tenants
+        .base
+        .find_by_id(room.tenant_id)
+        .await
+        .map_err(|_| not_found())?;
+    if tenant.is_archived || tenant.deleted_at.is_some() {
+        return Err(not_found());
+    }
+    Ok((room, tenant))
+}
+
+fn join_info(meeting_code: String, room: &Room, tenant: &Tenant) -> JoinInfoResponse {
+    let conference = room.conference_settings.as_ref();
+    JoinInfoResponse {
+        meeting_code,
+        subject: room.name.clone(),
+        status: room
+            .conference_status
+            .clone()
+            .unwrap_or_else(|| "not_started".to_string()),
+        passcode_required: conference.is_some_and(|c| c.passcode.is_some()),
+        lobby_enabled: conference.is_some_and(|c| c.lobby_enabled),
+        scheduled_start: conference
+            .and_then(|c| c.scheduled_start)
+            .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
+        tenant: JoinTenantBranding {
+            name: tenant.name.clone(),
+            slug: tenant.slug.clone(),
+            icon: tenant.icon.clone(),
+        },
+    }
+}
+
+/// GET /api/join/{meeting_code}/info — public (no auth), so a join page can
+/// render the conference before the visitor logs in or joins as a guest.
+pub async fn info(
+    State(state): State<AppState>,
+    Path(meeting_code): Path<String>,
+) -> Result<Json<JoinInfoResponse>, ApiError> {
+    let (room, tenant) = find_meeting(&state, &meeting_code).await?;
+    Ok(Json(join_info(meeting_code, &room, &tenant)))
+}
+
+/// GET /api/join/{meeting_code}/prejoin — everything the pre-join screen
+/// needs in one call: the meeting, how the caller would join, ICE servers
+/// for the device and connectivity check, what they can do in the call and
+/// whether they'd wait to get in. Public; signing in fills in the rest, and
+/// callers outside the tenant pass the guest link's key to join as guests.
+pub async fn prejoin(
+    State(state): State<AppState>,
+    OptionalAuthUser(auth): OptionalAuthUser,
+    Path(meeting_code): Path<String>,
+    Query(query): Query<PrejoinQuery>,
+) -> Result<Json<PrejoinResponse>, ApiError> {
+    let (room, tenant) = find_meeting(&state, &meeting_code).await?;
+    let rid = room
+        .id
+        .ok_or_else(|| ApiError::Internal("Room has no id".to_string()))?;
+    let tid = room.tenant_id;
+
+    let user = match &auth {
+        Some(auth) => Some(state.users.base.find_by_id(auth.user_id).await?),
+        None => None,
+    };
+    let organizer = auth
+        .as_ref()
+        .is_some_and(|a| room.organizer_ids().contains(&a.user_id));
+    let join_as = match &auth {
+        Some(_) if organizer => "organizer",
+        Some(a) if state.tenants.is_member(tid, a.user_id).await? => "member",
+        Some(_) => "signed_in",
+        None => "guest",
+    };
+    let member_join = matches!(join_as, "organizer" | "member");
+    let controls = room.conference();
+    let guest_join =
+        !member_join && controls.guest_link_key.is_some() && controls.guest_link_key == query.guest;
+    let can_join = member_join || guest_join;
+    let guest_join_url = guest_join.then(|| format!("/api/guest/conference/{meeting_code}/join"));
+
+    let media = room.media_settings.as_ref();
+    let waiting_room = waiting_room(&state, &room, auth.as_ref().map(|a| a.user_id)).await?;
+
+    Ok(Json(PrejoinResponse {
+        info: join_info(meeting_code, &room, &tenant),
+        join_as,
+        can_join,
+        tenant_id: member_join.then(|| tid.to_hex()),
+        room_id: member_join.then(|| rid.to_hex()),
+        guest_link_enabled: controls.guest_link_key.is_some(),
+        guest_join_url,
+        display_name: DisplayNameRequirement {
+            // Guests, signed in or not, join under the name they enter.
+            required: !member_join,
+            suggested: user.map(|u| u.display_name),
+        },
+        // A guest gets an id of their own when joining; this one only
+        // serves the connectivity check.
+        ice_servers: match &auth {
+            Some(a) if member_join => {
+                crate::ws::handler::conference_ice_servers(&state, &a.user_id)
+            }
+            _ if guest_join => crate::ws::handler::conference_ice_servers(&state, &ObjectId::new()),
+            _ => Vec::new(),
+        },
+        force_relay: state.settings.turn.force_relay.unwrap_or(false),
+        capabilities: PrejoinCapabilities {
+            audio: media.is_some_and(|m| m.audio_enabled),
+            video: media.is_some_and(|m| m.video_enabled),
+            screen_share: media.is_some_and(|m| m.screen_share_enabled)
+                && (organizer || controls.attendee_screen_share),
+            recording: media.is_some_and(|m| m.recording_enabled),
+            captions: state.transcription.is_available(),
+            chat: organizer || controls.chat_enabled,
+            reactions: organizer || controls.reactions_enabled,
+            start_muted: !organizer && controls.mute_on_entry,
+            can_unmute: organizer || controls.allow_unmute,
+        },
+        waiting_room,
+    }))
+}
+
+/// GET /api/conference/code/{meeting_code} — resolve a meeting code, as in
+/// the room's `join_url`, to its tenant and conference. Callers outside the
+/// tenant must pass the conference's passcode, if it has one, as
+/// `?passcode=`. Signing in is optional.
+pub async fn resolve(
+    State(state): State<AppState>,
+    OptionalAuthUser(auth): OptionalAuthUser,
+    Path(meeting_code): Path<String>,
+    Query(query): Query<ResolveQuery>,
+) -> Result<Json<ResolvedConference>, ApiError> {
+    let (room, tenant) = find_meeting(&state, &meeting_code).await?;
+    let rid = room
+        .id
+        .ok_or_else(|| ApiError::Internal("Room has no id".to_string()))?;
+    let tid = room.tenant_id;
+    let user_id = auth.as_ref().map(|a| a.user_id);
+
+    let join_as = match user_id {
+        Some(uid) if room.organizer_ids().contains(&uid) => "organizer",
+        Some(uid) if state.tenants.is_member(tid, uid).await? => "member",
+        Some(_) => "signed_in",
+        None => "guest",
+    };
+    if !matches!(join_as, "organizer" | "member")
+        && let Some(passcode) = room.conference().passcode
+    {
+        match query.passcode {
+            None => return Err(ApiError::Forbidden("Passcode required".to_string())),
+            Some(given) if given != passcode => {
+                return Err(ApiError::Forbidden("Wrong passcode".to_string()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(Json(ResolvedConference {
+        waiting_room: waiting_room(&state, &room, user_id).await?,
+        meeting_code,
+        join_url: room.join_url.clone(),
+        tenant_id: tid.to_hex(),
+        tenant_slug: tenant.slug,
+        room_id: rid.to_hex(),
+        subject: room.name.clone(),
+        status: room
+            .conference_status
+            .clone()
+            .unwrap_or_else(|| "not_started".to_string()),
+        join_as,
+    }))
+}
+
+/// Whether `user_id` (or an anonymous caller) would wait to get into
+/// `room`'s call, and how full it is.
+async fn waiting_room(
+    state: &AppState,
+    room: &Room,
+    user_id: Option<ObjectId>,
+) -> Result<WaitingRoomStatus, ApiError> {
+    let controls = room.conference();
+    let organizer = user_id.is_some_and(|uid| room.organizer_ids().contains(&uid));
+    let admitted = match (room.id, user_id) {
+        (Some(rid), Some(uid)) => state.conference_lobby.is_admitted(&rid, &uid),
+        _ => false,
+    };
+    let (max_participants, participants) = crate::conference_limits::seats(state, room).await?;
+    Ok(WaitingRoomStatus {
+        lobby_enabled: controls.lobby_enabled,
+        will_wait_in_lobby: controls.lobby_enabled && !organizer && !admitted,
+        waitlist_enabled: controls.waitlist_enabled,
+        participants,
+        max_participants,
+        full: max_participants.is_some_and(|cap| participants >= cap as usize),
+    })
+}
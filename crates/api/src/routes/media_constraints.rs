@@ -0,0 +1,85 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{MediaConstraintOverrides, MediaConstraints, role::permissions};
+use serde::Serialize;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct MediaConstraintsResponse {
+    /// What clients receive in `media:router_capabilities`.
+    pub effective: MediaConstraints,
+    /// The plan's defaults before tenant overrides.
+    pub plan_defaults: MediaConstraints,
+    pub overrides: MediaConstraintOverrides,
+}
+
+/// GET /api/tenant/{tenant_id}/media-constraints
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<MediaConstraintsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    Ok(Json(load(&state, tid).await?))
+}
+
+/// PUT /api/tenant/{tenant_id}/media-constraints — replace the tenant's
+/// overrides. Omitted fields fall back to the plan default.
+pub async fn set(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<MediaConstraintOverrides>,
+) -> Result<Json<MediaConstraintsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    if [
+        body.max_video_width,
+        body.max_video_height,
+        body.max_frame_rate,
+    ]
+    .contains(&Some(0))
+    {
+        return Err(ApiError::Validation(
+            "Video limits must be greater than zero".to_string(),
+        ));
+    }
+
+    state
+        .tenants
+        .set_media_constraint_overrides(tid, &body)
+        .await?;
+
+    Ok(Json(load(&state, tid).await?))
+}
+
+async fn load(state: &AppState, tid: ObjectId) -> Result<MediaConstraintsResponse, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    let plan_defaults = tenant.plan.media_constraints();
+    let overrides = tenant.settings.media_constraints;
+    Ok(MediaConstraintsResponse {
+        effective: plan_defaults.clone().with_overrides(&overrides),
+        plan_defaults,
+        overrides,
+    })
+}
@@ -0,0 +1,91 @@
+//! The mentions inbox. Messages mention users through `mentions` in the
+//! create request or by `@username`, `@channel` and `@here` in their
+//! content; see [`roomler_ai_services::mentions`]. Mentioned room members
+//! get a `notification:mention` event, and `GET .../mentions` lists the
+//! messages mentioning the caller with how many they haven't read.
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_services::dao::base::PaginationParams;
+use serde::Serialize;
+
+use super::message::{MessageResponse, author_names, collect_author_ids, to_response};
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct MentionItem {
+    pub room_name: String,
+    /// Past the caller's read marker in the room.
+    pub unread: bool,
+    pub message: MessageResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MentionInboxResponse {
+    pub unread_count: u64,
+    pub items: Vec<MentionItem>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+}
+
+/// GET /api/tenant/{tenant_id}/mentions — messages mentioning the caller
+/// in rooms they belong to, newest first.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<MentionInboxResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let inbox = state
+        .read_states
+        .mentions(tid, auth.user_id, &params)
+        .await?;
+    let page = inbox.page;
+
+    let messages: Vec<_> = page.items.iter().map(|(m, _)| m.clone()).collect();
+    let names = author_names(&state, &collect_author_ids(&messages)).await;
+    let mut room_ids: Vec<ObjectId> = messages.iter().map(|m| m.room_id).collect();
+    room_ids.sort();
+    room_ids.dedup();
+    let room_names: HashMap<ObjectId, String> = state
+        .rooms
+        .base
+        .find_by_ids(&room_ids)
+        .await?
+        .into_iter()
+        .filter_map(|r| r.id.map(|id| (id, r.name)))
+        .collect();
+
+    let items = page
+        .items
+        .into_iter()
+        .map(|(m, unread)| MentionItem {
+            room_name: room_names.get(&m.room_id).cloned().unwrap_or_default(),
+            unread,
+            message: to_response(m, &names, Some(auth.user_id)),
+        })
+        .collect();
+
+    Ok(Json(MentionInboxResponse {
+        unread_count: inbox.unread,
+        items,
+        total: page.total,
+        page: page.page,
+        per_page: page.per_page,
+        total_pages: page.total_pages,
+    }))
+}
@@ -6,9 +6,18 @@ use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::room::{
+    effective_channel_role, posting_action, require_channel_action,
+    require_outside_read_only_window, visible_room,
+};
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::{Mentions, MessageAttachment};
+use roomler_ai_db::models::{
+    ChannelAction, Embed, Mentions, MessageAttachment, OnboardingStep, webhook_events,
+};
 use roomler_ai_services::dao::base::PaginationParams;
+use roomler_ai_services::embeds;
+use roomler_ai_services::mentions;
+use roomler_ai_services::thread_summary::{self, SummaryDecision};
 
 #[derive(Debug, Deserialize)]
 pub struct MentionRequest {
@@ -25,12 +34,28 @@ pub struct CreateMessageRequest {
     pub content: String,
     pub thread_id: Option<String>,
     pub referenced_message_id: Option<String>,
+    /// Client-chosen id of the send. Retrying with the same nonce returns
+    /// the message it created instead of posting it again.
     pub nonce: Option<String>,
     pub mentions: Option<MentionRequest>,
     #[serde(default)]
     pub attachment_ids: Vec<String>,
+    /// Location pins, contact cards and key-value cards; see
+    /// [`roomler_ai_services::embeds`].
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+    /// Other rooms to post a copy to; copies share a cross-post group, and
+    /// editing or deleting one changes them all.
+    #[serde(default)]
+    pub cross_post_room_ids: Vec<String>,
 }
 
+/// Most rooms a message can be cross-posted to, besides its own.
+const MAX_CROSS_POSTS: usize = 10;
+
+/// Longest client nonce, in characters.
+const MAX_NONCE_CHARS: usize = 64;
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateMessageRequest {
     pub content: String,
@@ -60,8 +85,19 @@ pub struct MessageResponse {
     pub is_thread_root: bool,
     pub thread_id: Option<String>,
     pub referenced_message_id: Option<String>,
+    /// The nonce the author sent it with, for clients to match the echo
+    /// to their pending send.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cross_post_group_id: Option<String>,
+    /// The other copies, in the response to a cross-posting create.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cross_posts: Vec<CrossPostResponse>,
     pub reaction_summary: Vec<ReactionSummaryResponse>,
     pub attachments: Vec<AttachmentResponse>,
+    pub embeds: Vec<Embed>,
+    pub mentions: MentionsResponse,
     pub is_read: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_count: Option<u32>,
@@ -69,14 +105,34 @@ pub struct MessageResponse {
     pub last_reply_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_reply_user_id: Option<String>,
+    /// Bumped by each reply and reply edit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_activity: Option<u32>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct MentionsResponse {
+    pub users: Vec<String>,
+    pub everyone: bool,
+    pub here: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CrossPostResponse {
+    pub room_id: String,
+    pub message_id: String,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct ReactionSummaryResponse {
     pub emoji: String,
     pub count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_emoji_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
 }
 
 pub async fn list(
@@ -93,15 +149,12 @@ pub async fn list(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
     let result = state.messages.find_in_room(rid, &params).await?;
 
     let author_ids = collect_author_ids(&result.items);
-    let names = state
-        .users
-        .find_display_names(&author_ids)
-        .await
-        .unwrap_or_default();
+    let names = author_names(&state, &author_ids).await;
     let viewer_id = Some(auth.user_id);
 
     let items: Vec<MessageResponse> = result
@@ -133,6 +186,27 @@ pub async fn create(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let role =
+        require_channel_action(&state, tid, &room, auth.user_id, posting_action(&room)).await?;
+    require_outside_read_only_window(&room, role)?;
+    let nonce = match body.nonce.as_deref() {
+        None | Some("") => None,
+        Some(nonce) if nonce.chars().count() > MAX_NONCE_CHARS => {
+            return Err(ApiError::Validation(format!(
+                "nonce must be at most {} characters",
+                MAX_NONCE_CHARS
+            )));
+        }
+        Some(nonce) => Some(nonce.to_string()),
+    };
+    if let Some(original) = original_response(&state, rid, auth.user_id, nonce.as_deref()).await? {
+        return Ok(Json(original));
+    }
+    embeds::validate(&body.embeds).map_err(ApiError::Validation)?;
+    let content = crate::emoji::expand_content(&state, tid, &body.content).await?;
+    let screened = crate::profanity::screen(&state, tid, rid, auth.user_id, content).await?;
+    let content = screened.content.clone();
 
     let thread_id = body
         .thread_id
@@ -148,23 +222,53 @@ pub async fn create(
         .transpose()
         .map_err(|_| ApiError::BadRequest("Invalid referenced_message_id".to_string()))?;
 
-    // Parse mentions from request
-    let mentions = if let Some(ref mention_req) = body.mentions {
-        let user_ids: Vec<ObjectId> = mention_req
+    // Every cross-post target is checked before anything is written.
+    let cross_post_ids = cross_post_targets(&body.cross_post_room_ids, rid)?;
+    if !cross_post_ids.is_empty() && (thread_id.is_some() || ref_msg_id.is_some()) {
+        return Err(ApiError::BadRequest(
+            "Thread replies and replies can't be cross-posted".to_string(),
+        ));
+    }
+    for target_id in &cross_post_ids {
+        let target = state
+            .rooms
+            .base
+            .find_by_id_in_tenant(tid, *target_id)
+            .await?;
+        let role =
+            require_channel_action(&state, tid, &target, auth.user_id, posting_action(&target))
+                .await?;
+        require_outside_read_only_window(&target, role)?;
+    }
+
+    // Mentions sent with the request plus those written in the content.
+    // Only room members can be mentioned.
+    let all_member_ids = state.rooms.find_member_user_ids(rid).await?;
+    let parsed = mentions::parse(&content);
+    let mut mentioned_ids: Vec<ObjectId> = body
+        .mentions
+        .iter()
+        .flat_map(|m| &m.users)
+        .filter_map(|s| ObjectId::parse_str(s).ok())
+        .collect();
+    mentioned_ids.extend(
+        state
             .users
-            .iter()
-            .filter_map(|s| ObjectId::parse_str(s).ok())
-            .collect();
-        Some(Mentions {
-            users: user_ids,
-            roles: Vec::new(),
-            rooms: Vec::new(),
-            everyone: mention_req.everyone,
-            here: mention_req.here,
-        })
-    } else {
-        None
-    };
+            .find_ids_by_usernames(&all_member_ids, &parsed.usernames)
+            .await?,
+    );
+    mentioned_ids.retain(|id| all_member_ids.contains(id));
+    mentioned_ids.sort();
+    mentioned_ids.dedup();
+    let everyone = parsed.everyone || body.mentions.as_ref().is_some_and(|m| m.everyone);
+    let here = parsed.here || body.mentions.as_ref().is_some_and(|m| m.here);
+    let mentions = (!mentioned_ids.is_empty() || everyone || here).then(|| Mentions {
+        users: mentioned_ids.clone(),
+        roles: Vec::new(),
+        rooms: Vec::new(),
+        everyone,
+        here,
+    });
 
     // Fetch file records for attachments (tenant-scoped to prevent cross-tenant access)
     let attachments = if !body.attachment_ids.is_empty() {
@@ -189,22 +293,65 @@ pub async fn create(
         Vec::new()
     };
 
-    let message = state
-        .messages
-        .create_with_attachments(
-            tid,
-            rid,
-            auth.user_id,
-            body.content.clone(),
-            thread_id,
-            ref_msg_id,
-            body.nonce,
-            mentions,
-            attachments,
-        )
-        .await?;
+    let created = if cross_post_ids.is_empty() {
+        state
+            .messages
+            .create_with_attachments(
+                tid,
+                rid,
+                auth.user_id,
+                content.clone(),
+                thread_id,
+                ref_msg_id,
+                nonce.clone(),
+                mentions,
+                attachments,
+                body.embeds,
+            )
+            .await
+            .map(|message| (message, Vec::new()))
+    } else {
+        let mut room_ids = vec![rid];
+        room_ids.extend(&cross_post_ids);
+        state
+            .messages
+            .create_cross_posts(
+                tid,
+                &room_ids,
+                auth.user_id,
+                content.clone(),
+                nonce.clone(),
+                mentions,
+                attachments,
+                body.embeds,
+            )
+            .await
+            .map(|mut copies| (copies.remove(0), copies))
+    };
+    let (message, copies) = match created {
+        Ok(created) => created,
+        // A retry that raced the original: the nonce index kept one.
+        Err(e) => match original_response(&state, rid, auth.user_id, nonce.as_deref()).await? {
+            Some(original) => return Ok(Json(original)),
+            None => return Err(e.into()),
+        },
+    };
+    if !screened.flagged_terms.is_empty() {
+        let ids: Vec<ObjectId> = std::iter::once(&message)
+            .chain(&copies)
+            .filter_map(|m| m.id)
+            .collect();
+        state.messages.flag(&ids, &screened.flagged_terms).await?;
+    }
+    state
+        .onboarding
+        .complete(tid, auth.user_id, OnboardingStep::SentMessage)
+        .await;
 
     let message_id = message.id.unwrap();
+    screened
+        .record(&state, tid, rid, auth.user_id, message_id)
+        .await;
 
     // Fetch author display name for the response
     let names = state
@@ -213,33 +360,67 @@ pub async fn create(
         .await
         .unwrap_or_default();
 
-    // Fetch room member IDs once and reuse for WS broadcast, thread update, and notifications
-    let all_member_ids = state.rooms.find_member_user_ids(rid).await?;
+    // Room member IDs are reused for WS broadcast, thread update, and notifications
     let member_ids_excluding_sender: Vec<ObjectId> = all_member_ids
         .iter()
         .filter(|id| **id != auth.user_id)
         .copied()
         .collect();
 
+    // Copies go to their own rooms; mentions are notified once, below.
+    let mut cross_posts = Vec::with_capacity(copies.len());
+    for copy in copies {
+        let copy_room_id = copy.room_id;
+        let copy_response = to_response(copy, &names, Some(auth.user_id));
+        cross_posts.push(CrossPostResponse {
+            room_id: copy_response.room_id.clone(),
+            message_id: copy_response.id.clone(),
+        });
+        broadcast_to_room(
+            &state,
+            tid,
+            copy_room_id,
+            auth.user_id,
+            &serde_json::json!({ "type": "message:create", "data": &copy_response }),
+        )
+        .await?;
+        crate::webhooks::dispatch(
+            &state,
+            tid,
+            webhook_events::MESSAGE_CREATE,
+            serde_json::to_value(&copy_response).unwrap_or_default(),
+        );
+    }
+
     // Broadcast via WebSocket to room members (exclude sender)
-    let response = to_response(message, &names, Some(auth.user_id));
+    let mut response = to_response(message, &names, Some(auth.user_id));
+    response.cross_posts = cross_posts;
     let event = serde_json::json!({
         "type": "message:create",
         "data": &response,
     });
-    crate::ws::dispatcher::broadcast_with_redis(
+    crate::ws::dispatcher::broadcast_in_tenant(
         &state.ws_storage,
         &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
         &member_ids_excluding_sender,
         &event,
     )
     .await;
+    crate::webhooks::dispatch(
+        &state,
+        tid,
+        webhook_events::MESSAGE_CREATE,
+        serde_json::to_value(&response).unwrap_or_default(),
+    );
 
     // If this was a thread reply, broadcast an update for the parent message
     // so other users see the updated is_thread_root + reply_count
     if let Some(parent_id) = thread_id
         && let Ok(parent_msg) = state.messages.base.find_by_id(parent_id).await
     {
+        super::thread::on_reply(&state, &parent_msg, auth.user_id).await;
         let parent_author_ids = vec![parent_msg.author_id];
         let parent_names = state
             .users
@@ -252,9 +433,11 @@ pub async fn create(
             "data": &parent_response,
         });
         // Broadcast to ALL members (including sender, so sender's UI also updates)
-        crate::ws::dispatcher::broadcast_with_redis(
+        crate::ws::dispatcher::broadcast_in_tenant(
             &state.ws_storage,
             &state.redis_pubsub,
+            &state.delivery_metrics,
+            tid,
             &all_member_ids,
             &parent_event,
         )
@@ -262,26 +445,19 @@ pub async fn create(
     }
 
     // Create notifications for mentioned users via helper
-    if let Some(ref mention_req) = body.mentions {
-        let mentioned_user_ids: Vec<ObjectId> = if mention_req.everyone {
-            // @everyone: notify all room members except sender
-            member_ids_excluding_sender.clone()
-        } else {
-            mention_req
-                .users
-                .iter()
-                .filter_map(|s| ObjectId::parse_str(s).ok())
-                .filter(|id| *id != auth.user_id)
-                .collect()
-        };
-
-        let room_name = state
-            .rooms
-            .base
-            .find_by_id(rid)
-            .await
-            .map(|r| r.name)
-            .unwrap_or_default();
+    let mentioned_user_ids: Vec<ObjectId> = if everyone {
+        // @everyone: notify all room members except sender
+        member_ids_excluding_sender.clone()
+    } else {
+        // @here: members with a live connection, as for mention emails
+        member_ids_excluding_sender
+            .iter()
+            .filter(|id| mentioned_ids.contains(id) || (here && state.ws_storage.is_connected(id)))
+            .copied()
+            .collect()
+    };
+    if !mentioned_user_ids.is_empty() {
+        let room_name = room.name.clone();
 
         let mentioner_name = names
             .get(&auth.user_id)
@@ -296,7 +472,7 @@ pub async fn create(
             auth.user_id,
             &mentioned_user_ids,
             &room_name,
-            &body.content,
+            &content,
             &mentioner_name,
             &tenant_id,
             &room_id,
@@ -323,14 +499,73 @@ pub async fn update(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(
+        &state,
+        tid,
+        &room,
+        auth.user_id,
+        ChannelAction::PostMessages,
+    )
+    .await?;
+    let content = crate::emoji::expand_content(&state, tid, &body.content).await?;
+    let screened = crate::profanity::screen(&state, tid, rid, auth.user_id, content).await?;
 
-    state
+    let edited = state
         .messages
-        .update_content(tid, mid, auth.user_id, body.content.clone())
+        .update_content(
+            tid,
+            mid,
+            auth.user_id,
+            screened.content.clone(),
+            &screened.flagged_terms,
+        )
         .await?;
+    if edited {
+        screened.record(&state, tid, rid, auth.user_id, mid).await;
+    }
 
     // Re-fetch the updated message for the full response
     let updated = state.messages.base.find_by_id(mid).await?;
+    if edited && let Some(group_id) = updated.cross_post_group_id {
+        state
+            .messages
+            .update_cross_post_content(
+                tid,
+                group_id,
+                auth.user_id,
+                updated.content.clone(),
+                &updated.flagged_terms,
+            )
+            .await?;
+        for copy in state.messages.find_cross_posts(group_id).await? {
+            if copy.id == Some(mid) {
+                continue;
+            }
+            let copy_room_id = copy.room_id;
+            let names = state
+                .users
+                .find_display_names(&[copy.author_id])
+                .await
+                .unwrap_or_default();
+            let copy_response = to_response(copy, &names, Some(auth.user_id));
+            broadcast_to_room(
+                &state,
+                tid,
+                copy_room_id,
+                auth.user_id,
+                &serde_json::json!({ "type": "message:update", "data": &copy_response }),
+            )
+            .await?;
+        }
+    }
+    if edited
+        && let Some(root_id) = updated.thread_id
+        && state.messages.bump_thread_activity(root_id).await?
+        && let Ok(root) = state.messages.base.find_by_id(root_id).await
+    {
+        super::thread::notify(&state, &root, auth.user_id).await;
+    }
     let names = state
         .users
         .find_display_names(&[updated.author_id])
@@ -350,9 +585,11 @@ pub async fn update(
         "type": "message:update",
         "data": &response,
     });
-    crate::ws::dispatcher::broadcast_with_redis(
+    crate::ws::dispatcher::broadcast_in_tenant(
         &state.ws_storage,
         &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
         &member_ids,
         &event,
     )
@@ -377,15 +614,46 @@ pub async fn delete(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    // Verify ownership: only the author can delete their message (tenant-scoped)
+    // Authors can delete their own messages; moderators anyone's (tenant-scoped)
     let message = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
     if message.author_id != auth.user_id {
-        return Err(ApiError::Forbidden(
-            "Only the author can delete this message".to_string(),
-        ));
+        let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+        let role = effective_channel_role(&state, tid, &room, auth.user_id).await?;
+        if !role.allows(ChannelAction::DeleteAnyMessage) {
+            return Err(ApiError::Forbidden(
+                "Only the author or a moderator can delete this message".to_string(),
+            ));
+        }
     }
 
+    // The author's delete takes every cross-posted copy with it; a
+    // moderator's only the one in their room.
+    let copies = match message.cross_post_group_id {
+        Some(group_id) if message.author_id == auth.user_id => {
+            let copies = state.messages.find_cross_posts(group_id).await?;
+            state
+                .messages
+                .soft_delete_cross_posts(tid, group_id)
+                .await?;
+            copies
+        }
+        _ => Vec::new(),
+    };
     state.messages.base.soft_delete_in_tenant(tid, mid).await?;
+    for copy in copies.iter().filter(|c| c.id != Some(mid)) {
+        let (copy_id, copy_room_id) = (copy.id.unwrap(), copy.room_id);
+        broadcast_to_room(
+            &state,
+            tid,
+            copy_room_id,
+            auth.user_id,
+            &serde_json::json!({
+                "type": "message:delete",
+                "data": { "id": copy_id.to_hex(), "room_id": copy_room_id.to_hex() }
+            }),
+        )
+        .await?;
+    }
 
     let member_ids: Vec<ObjectId> = state
         .rooms
@@ -401,9 +669,11 @@ pub async fn delete(
             "room_id": room_id,
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
+    crate::ws::dispatcher::broadcast_in_tenant(
         &state.ws_storage,
         &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
         &member_ids,
         &event,
     )
@@ -425,14 +695,11 @@ pub async fn pinned(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
     let messages = state.messages.find_pinned(rid).await?;
     let author_ids = collect_author_ids(&messages);
-    let names = state
-        .users
-        .find_display_names(&author_ids)
-        .await
-        .unwrap_or_default();
+    let names = author_names(&state, &author_ids).await;
     let response: Vec<MessageResponse> = messages
         .into_iter()
         .map(|m| to_response(m, &names, Some(auth.user_id)))
@@ -462,6 +729,8 @@ pub async fn toggle_pin(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::PinMessages).await?;
 
     state.messages.toggle_pin(tid, mid, body.pinned).await?;
 
@@ -474,9 +743,11 @@ pub async fn toggle_pin(
             "pinned": body.pinned,
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
+    crate::ws::dispatcher::broadcast_in_tenant(
         &state.ws_storage,
         &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
         &member_ids,
         &event,
     )
@@ -488,26 +759,28 @@ pub async fn toggle_pin(
 pub async fn thread_replies(
     State(state): State<AppState>,
     auth: AuthUser,
-    Path((tenant_id, _room_id, message_id)): Path<(String, String, String)>,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
     let mid = ObjectId::parse_str(&message_id)
         .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
 
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
     let result = state.messages.find_thread_replies(mid, &params).await?;
+    if result.items.iter().any(|m| m.room_id != rid) {
+        return Err(ApiError::NotFound("Thread not found".to_string()));
+    }
 
     let author_ids = collect_author_ids(&result.items);
-    let names = state
-        .users
-        .find_display_names(&author_ids)
-        .await
-        .unwrap_or_default();
+    let names = author_names(&state, &author_ids).await;
     let viewer_id = Some(auth.user_id);
 
     let items: Vec<MessageResponse> = result
@@ -525,7 +798,184 @@ pub async fn thread_replies(
     })))
 }
 
-fn to_response(
+/// POST /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/summarize
+///
+/// Summarize a long thread with Claude and pin the result inside the thread.
+/// The summary is cached on the thread root and only regenerated once
+/// `thread_summary.regenerate_after_replies` new replies have arrived.
+pub async fn summarize_thread(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mid = ObjectId::parse_str(&message_id)
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let root = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
+    if root.room_id != rid || root.thread_id.is_some() {
+        return Err(ApiError::NotFound("Thread not found".to_string()));
+    }
+    visible_room(&state, tid, rid, auth.user_id).await?;
+    let reply_count = root.thread_metadata.as_ref().map_or(0, |tm| tm.reply_count);
+    let settings = &state.settings.thread_summary;
+
+    let cached = match thread_summary::decide(settings, reply_count, root.thread_summary.as_ref()) {
+        SummaryDecision::TooShort => {
+            return Err(ApiError::Validation(format!(
+                "Threads need at least {} replies to be summarized",
+                settings.min_replies
+            )));
+        }
+        SummaryDecision::UseCached => root.thread_summary.as_ref().map(|s| s.message_id),
+        SummaryDecision::Generate => None,
+    };
+    if let Some(summary_id) = cached
+        && let Ok(summary) = state.messages.base.find_by_id(summary_id).await
+        && summary.deleted_at.is_none()
+    {
+        let names = state
+            .users
+            .find_display_names(&[summary.author_id])
+            .await
+            .unwrap_or_default();
+        return Ok(Json(serde_json::json!({
+            "summary": to_response(summary, &names, Some(auth.user_id)),
+            "cached": true,
+        })));
+    }
+
+    if !state.recognition.is_available() {
+        return Err(ApiError::BadRequest(
+            "Thread summaries not configured (missing Claude API key)".to_string(),
+        ));
+    }
+
+    let replies = state.messages.find_all_thread_replies(mid).await?;
+    let mut author_ids = collect_author_ids(&replies);
+    author_ids.push(root.author_id);
+    let names = author_names(&state, &author_ids).await;
+    let content = state
+        .recognition
+        .complete_text(thread_summary::build_prompt(&root, &replies, &names))
+        .await
+        .map_err(|e| {
+            tracing::warn!(%mid, %e, "Thread summary generation failed");
+            ApiError::Internal("Failed to generate thread summary".to_string())
+        })?;
+    let content = format!("**Thread summary**\n\n{}", content.trim());
+
+    // Regenerate in place so the thread keeps a single pinned summary.
+    let existing = root.thread_summary.as_ref().map(|s| s.message_id);
+    let (summary, event_type) = match existing {
+        Some(summary_id) => match state
+            .messages
+            .update_thread_summary(mid, summary_id, content.clone(), reply_count)
+            .await
+        {
+            Ok(summary) => (summary, "message:update"),
+            Err(_) => (
+                state
+                    .messages
+                    .create_thread_summary(&root, auth.user_id, content, reply_count)
+                    .await?,
+                "message:create",
+            ),
+        },
+        None => (
+            state
+                .messages
+                .create_thread_summary(&root, auth.user_id, content, reply_count)
+                .await?,
+            "message:create",
+        ),
+    };
+
+    let names = state
+        .users
+        .find_display_names(&[summary.author_id])
+        .await
+        .unwrap_or_default();
+    let response = to_response(summary, &names, None);
+    let member_ids = state.rooms.find_member_user_ids(rid).await?;
+    let event = serde_json::json!({
+        "type": event_type,
+        "data": &response,
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
+        &member_ids,
+        &event,
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({
+        "summary": response,
+        "cached": false,
+    })))
+}
+
+/// The response to the caller's message already sent to `room_id` with
+/// `nonce`, copies included, so a retried create doesn't post it twice.
+async fn original_response(
+    state: &AppState,
+    room_id: ObjectId,
+    author_id: ObjectId,
+    nonce: Option<&str>,
+) -> Result<Option<MessageResponse>, ApiError> {
+    let Some(nonce) = nonce else {
+        return Ok(None);
+    };
+    let Some(original) = state
+        .messages
+        .find_by_nonce(room_id, author_id, nonce)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if original.deleted_at.is_some() {
+        return Err(ApiError::Conflict(
+            "The message sent with this nonce was deleted".to_string(),
+        ));
+    }
+
+    let cross_posts = match original.cross_post_group_id {
+        Some(group_id) => state
+            .messages
+            .find_cross_posts(group_id)
+            .await?
+            .into_iter()
+            .filter(|copy| copy.id != original.id)
+            .filter_map(|copy| {
+                Some(CrossPostResponse {
+                    room_id: copy.room_id.to_hex(),
+                    message_id: copy.id?.to_hex(),
+                })
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let names = state
+        .users
+        .find_display_names(&[author_id])
+        .await
+        .unwrap_or_default();
+    let mut response = to_response(original, &names, Some(author_id));
+    response.cross_posts = cross_posts;
+    Ok(Some(response))
+}
+
+pub(crate) fn to_response(
     m: roomler_ai_db::models::Message,
     names: &HashMap<ObjectId, String>,
     viewer_id: Option<ObjectId>,
@@ -535,15 +985,17 @@ fn to_response(
         .cloned()
         .unwrap_or_else(|| m.author_id.to_hex());
     let is_read = viewer_id.is_some_and(|uid| m.readby.iter().any(|r| r == &uid));
-    let (reply_count, last_reply_at, last_reply_user_id) = match &m.thread_metadata {
+    let (reply_count, last_reply_at, last_reply_user_id, thread_activity) = match &m.thread_metadata
+    {
         Some(tm) => (
             Some(tm.reply_count),
             tm.last_reply_at
                 .as_ref()
                 .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
             tm.last_reply_user_id.map(|u| u.to_hex()),
+            Some(tm.activity_count),
         ),
-        None => (None, None, None),
+        None => (None, None, None, None),
     };
     MessageResponse {
         id: m.id.unwrap().to_hex(),
@@ -557,12 +1009,17 @@ fn to_response(
         is_thread_root: m.is_thread_root,
         thread_id: m.thread_id.map(|t| t.to_hex()),
         referenced_message_id: m.referenced_message_id.map(|r| r.to_hex()),
+        nonce: m.nonce,
+        cross_post_group_id: m.cross_post_group_id.map(|g| g.to_hex()),
+        cross_posts: Vec::new(),
         reaction_summary: m
             .reaction_summary
             .into_iter()
             .map(|r| ReactionSummaryResponse {
                 emoji: r.emoji,
                 count: r.count,
+                custom_emoji_id: r.custom_emoji_id.map(|id| id.to_hex()),
+                image_url: r.image_url,
             })
             .collect(),
         attachments: m
@@ -577,17 +1034,94 @@ fn to_response(
                 thumbnail_url: a.thumbnail_url,
             })
             .collect(),
+        embeds: m.embeds,
+        mentions: MentionsResponse {
+            users: m.mentions.users.iter().map(|u| u.to_hex()).collect(),
+            everyone: m.mentions.everyone,
+            here: m.mentions.here,
+        },
         is_read,
         reply_count,
         last_reply_at,
         last_reply_user_id,
+        thread_activity,
         created_at: m.created_at.try_to_rfc3339_string().unwrap_or_default(),
         updated_at: m.updated_at.try_to_rfc3339_string().unwrap_or_default(),
     }
 }
 
+/// The rooms a new message is also posted to: parsed, deduplicated and
+/// without `origin`.
+fn cross_post_targets(room_ids: &[String], origin: ObjectId) -> Result<Vec<ObjectId>, ApiError> {
+    let mut targets: Vec<ObjectId> = Vec::with_capacity(room_ids.len());
+    for room_id in room_ids {
+        let target = ObjectId::parse_str(room_id)
+            .map_err(|_| ApiError::BadRequest("Invalid cross_post_room_ids".to_string()))?;
+        if target != origin && !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    if targets.len() > MAX_CROSS_POSTS {
+        return Err(ApiError::Validation(format!(
+            "A message can be cross-posted to at most {} rooms",
+            MAX_CROSS_POSTS
+        )));
+    }
+    Ok(targets)
+}
+
+/// Sends `event` to the members of `room_id`, except `exclude`.
+async fn broadcast_to_room(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    exclude: ObjectId,
+    event: &serde_json::Value,
+) -> Result<(), ApiError> {
+    let member_ids: Vec<ObjectId> = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await?
+        .into_iter()
+        .filter(|id| *id != exclude)
+        .collect();
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tenant_id,
+        &member_ids,
+        event,
+    )
+    .await;
+    Ok(())
+}
+
+/// Display names of message authors; bots go by their name.
+pub(crate) async fn author_names(
+    state: &AppState,
+    author_ids: &[ObjectId],
+) -> HashMap<ObjectId, String> {
+    let mut names = state
+        .users
+        .find_display_names(author_ids)
+        .await
+        .unwrap_or_default();
+    let missing: Vec<ObjectId> = author_ids
+        .iter()
+        .filter(|id| !names.contains_key(id))
+        .copied()
+        .collect();
+    if !missing.is_empty()
+        && let Ok(bots) = state.bot_tokens.find_names(&missing).await
+    {
+        names.extend(bots);
+    }
+    names
+}
+
 /// Collect unique author IDs from a slice of messages
-fn collect_author_ids(messages: &[roomler_ai_db::models::Message]) -> Vec<ObjectId> {
+pub(crate) fn collect_author_ids(messages: &[roomler_ai_db::models::Message]) -> Vec<ObjectId> {
     let mut ids: Vec<ObjectId> = messages.iter().map(|m| m.author_id).collect();
     ids.sort();
     ids.dedup();
@@ -613,6 +1147,7 @@ pub async fn mark_read(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
     let message_ids: Vec<ObjectId> = body
         .message_ids
@@ -625,9 +1160,160 @@ pub async fn mark_read(
         .mark_read(rid, auth.user_id, &message_ids)
         .await?;
 
+    // Reading a message also reads everything before it.
+    let mut last_read = None;
+    if let Some(newest) = state
+        .read_states
+        .newest_message_id(rid, Some(message_ids.as_slice()))
+        .await?
+        && state
+            .read_states
+            .mark_read_up_to(rid, auth.user_id, newest)
+            .await?
+    {
+        last_read = Some(newest);
+    }
+
+    if modified > 0 || last_read.is_some() {
+        broadcast_read_receipt(
+            &state,
+            tid,
+            rid,
+            auth.user_id,
+            serde_json::json!({
+                "room_id": room_id,
+                "user_id": auth.user_id.to_hex(),
+                "message_ids": message_ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>(),
+                "last_read_message_id": last_read.map(|id| id.to_hex()),
+            }),
+        )
+        .await?;
+    }
+
     Ok(Json(serde_json::json!({ "marked": modified })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MarkRoomReadRequest {
+    /// Read up to and including this message; the newest one when omitted.
+    pub message_id: Option<String>,
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/read
+///
+/// Moves the caller's read marker forward. It never moves back, so a late
+/// request from another device can't resurrect unread messages.
+pub async fn mark_room_read(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    body: Option<Json<MarkRoomReadRequest>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    visible_room(&state, tid, rid, auth.user_id).await?;
+    if !state.rooms.is_member(rid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a room member".to_string()));
+    }
+
+    let requested = body
+        .and_then(|Json(b)| b.message_id)
+        .map(|id| ObjectId::parse_str(&id))
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+    let target = match requested {
+        Some(mid) => Some(
+            state
+                .read_states
+                .newest_message_id(rid, Some(std::slice::from_ref(&mid)))
+                .await?
+                .ok_or_else(|| ApiError::NotFound("Message not found".to_string()))?,
+        ),
+        None => state.read_states.newest_message_id(rid, None).await?,
+    };
+
+    let advanced = match target {
+        Some(mid) => {
+            state
+                .read_states
+                .mark_read_up_to(rid, auth.user_id, mid)
+                .await?
+        }
+        None => false,
+    };
+    if let (true, Some(mid)) = (advanced, target) {
+        broadcast_read_receipt(
+            &state,
+            tid,
+            rid,
+            auth.user_id,
+            serde_json::json!({
+                "room_id": room_id,
+                "user_id": auth.user_id.to_hex(),
+                "message_ids": [],
+                "last_read_message_id": mid.to_hex(),
+            }),
+        )
+        .await?;
+    }
+
+    let unread_count = state.read_states.unread_count(rid, auth.user_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "advanced": advanced,
+        "last_read_message_id": target.map(|id| id.to_hex()),
+        "unread_count": unread_count,
+    })))
+}
+
+/// `message:read` to the other room members, unless the reader hides read
+/// receipts.
+async fn broadcast_read_receipt(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    reader_id: ObjectId,
+    data: serde_json::Value,
+) -> Result<(), ApiError> {
+    if !crate::ws::dispatcher::privacy_allows(
+        &state.users,
+        reader_id,
+        crate::ws::dispatcher::PrivacyScope::ReadReceipts,
+    )
+    .await
+    {
+        return Ok(());
+    }
+
+    let member_ids: Vec<ObjectId> = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await?
+        .into_iter()
+        .filter(|id| *id != reader_id)
+        .collect();
+    let event = serde_json::json!({
+        "type": "message:read",
+        "data": data,
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tenant_id,
+        &member_ids,
+        &event,
+    )
+    .await;
+    Ok(())
+}
+
 pub async fn unread_count(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -641,8 +1327,9 @@ pub async fn unread_count(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
-    let count = state.messages.unread_count(rid, auth.user_id).await?;
+    let count = state.read_states.unread_count(rid, auth.user_id).await?;
 
     Ok(Json(serde_json::json!({ "count": count })))
 }
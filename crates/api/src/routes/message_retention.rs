@@ -0,0 +1,300 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{MessageRetention, Plan, TaskCategory, actions, role::permissions};
+use serde::Serialize;
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Serialize)]
+pub struct TenantRetentionResponse {
+    #[serde(flatten)]
+    pub retention: MessageRetention,
+    /// The plan keeps at most this many of the tenant's newest messages;
+    /// `None` is unlimited.
+    pub plan_max_messages: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomRetentionResponse {
+    /// The room's own policy, which replaces the tenant's.
+    #[serde(rename = "override")]
+    pub retention_override: Option<MessageRetention>,
+    pub tenant: MessageRetention,
+    /// The policy the room actually follows.
+    pub effective: MessageRetention,
+    pub plan_max_messages: Option<u64>,
+}
+
+/// GET /api/tenant/{tenant_id}/message-retention
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<TenantRetentionResponse>, ApiError> {
+    let tid = parse_id(&tenant_id, "tenant_id")?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(TenantRetentionResponse {
+        plan_max_messages: plan_max_messages(&tenant.plan),
+        retention: tenant.settings.message_retention,
+    }))
+}
+
+/// PUT /api/tenant/{tenant_id}/message-retention — replace the default
+/// policy of rooms without their own.
+pub async fn set(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<MessageRetention>,
+) -> Result<Json<TenantRetentionResponse>, ApiError> {
+    validate(&body)?;
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    state.tenants.set_message_retention(tid, &body).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::TENANT_MESSAGE_RETENTION_UPDATE,
+        Some(tid),
+        vec![audit::change(
+            "message_retention",
+            serde_json::to_value(&tenant.settings.message_retention).ok(),
+            serde_json::to_value(&body).ok(),
+        )],
+    )
+    .await;
+
+    Ok(Json(TenantRetentionResponse {
+        plan_max_messages: plan_max_messages(&tenant.plan),
+        retention: body,
+    }))
+}
+
+/// POST /api/tenant/{tenant_id}/message-retention/run — apply retention
+/// now, in the background, instead of waiting for the next sweep.
+pub async fn run(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+) -> Result<Response, ApiError> {
+    let task = state
+        .tasks
+        .create_task(
+            tid,
+            auth.user_id,
+            "message_retention".to_string(),
+            TaskCategory::Retention,
+            serde_json::json!({}),
+        )
+        .await?;
+    let task_id = task.id.unwrap();
+
+    let task_state = state.clone();
+    state.tasks.spawn_task(task_id, async move {
+        let report = crate::message_retention::purge_tenant(&task_state, tid)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        let task_store = task_state.tasks.store();
+        task_store
+            .update_progress(
+                task_id,
+                100,
+                Some(format!(
+                    "Removed {} messages, archived {} batches",
+                    report.purged,
+                    report.archived.len()
+                )),
+            )
+            .await
+            .map_err(|e| format!("{}", e))?;
+        task_store
+            .complete(task_id, None, None, None)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        Ok(())
+    });
+
+    let task_url = format!("/api/tenant/{}/task/{}", tid.to_hex(), task_id.to_hex());
+    Ok((
+        StatusCode::ACCEPTED,
+        [("Location", task_url.clone())],
+        Json(serde_json::json!({
+            "task_id": task_id.to_hex(),
+            "status": "pending",
+            "task_url": task_url,
+        })),
+    )
+        .into_response())
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/message-retention
+pub async fn get_room(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let tid = parse_id(&tenant_id, "tenant_id")?;
+    let rid = parse_id(&room_id, "room_id")?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    room_response(&state, tid, room.message_retention).await
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/message-retention — give the
+/// room its own policy in place of the tenant's.
+pub async fn set_room(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+    Json(body): Json<MessageRetention>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let rid = parse_id(&room_id, "room_id")?;
+    validate(&body)?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    update_room(
+        &state,
+        &auth,
+        &client,
+        tid,
+        rid,
+        room.message_retention,
+        Some(body.clone()),
+    )
+    .await?;
+    room_response(&state, tid, Some(body)).await
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/message-retention — put
+/// the room back under the tenant's policy.
+pub async fn clear_room(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let rid = parse_id(&room_id, "room_id")?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.message_retention.is_some() {
+        update_room(
+            &state,
+            &auth,
+            &client,
+            tid,
+            rid,
+            room.message_retention,
+            None,
+        )
+        .await?;
+    }
+    room_response(&state, tid, None).await
+}
+
+async fn update_room(
+    state: &AppState,
+    auth: &AuthUser,
+    client: &ClientInfo,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    old: Option<MessageRetention>,
+    new: Option<MessageRetention>,
+) -> Result<(), ApiError> {
+    state
+        .rooms
+        .set_message_retention(tenant_id, room_id, new.as_ref())
+        .await?;
+    let as_json =
+        |r: &Option<MessageRetention>| r.as_ref().and_then(|r| serde_json::to_value(r).ok());
+    audit::record(
+        state,
+        tenant_id,
+        auth.user_id,
+        client,
+        actions::ROOM_MESSAGE_RETENTION_UPDATE,
+        Some(room_id),
+        vec![audit::change(
+            "message_retention",
+            as_json(&old),
+            as_json(&new),
+        )],
+    )
+    .await;
+    Ok(())
+}
+
+async fn room_response(
+    state: &AppState,
+    tenant_id: ObjectId,
+    retention_override: Option<MessageRetention>,
+) -> Result<Json<RoomRetentionResponse>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let tenant_retention = tenant.settings.message_retention;
+    Ok(Json(RoomRetentionResponse {
+        effective: retention_override
+            .clone()
+            .unwrap_or_else(|| tenant_retention.clone()),
+        retention_override,
+        tenant: tenant_retention,
+        plan_max_messages: plan_max_messages(&tenant.plan),
+    }))
+}
+
+fn validate(retention: &MessageRetention) -> Result<(), ApiError> {
+    if retention.max_age_days == Some(0) {
+        return Err(ApiError::Validation(
+            "max_age_days must be greater than zero".to_string(),
+        ));
+    }
+    if retention.max_count == Some(0) {
+        return Err(ApiError::Validation(
+            "max_count must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn plan_max_messages(plan: &Plan) -> Option<u64> {
+    u64::try_from(plan.limits().max_message_history).ok()
+}
+
+fn parse_id(id: &str, name: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(id).map_err(|_| ApiError::BadRequest(format!("Invalid {}", name)))
+}
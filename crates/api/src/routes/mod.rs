@@ -1,24 +1,57 @@
 pub mod admin;
 pub mod agent_release;
+pub mod analytics;
+pub mod asset;
+pub mod audit;
 pub mod auth;
 pub mod background_task;
+pub mod bot;
+pub mod calendar;
+pub mod channel_digest;
+pub mod conference_chat;
+pub mod conference_stats;
+pub mod delivery_metrics;
+pub mod developer;
+pub mod dm;
+pub mod emoji;
 pub mod export;
+pub mod feature_flag;
 pub mod file;
+pub mod follow_up;
 pub mod giphy;
+pub mod guest;
 pub(crate) mod helpers;
 pub mod integration;
+pub mod internal;
 pub mod invite;
+pub mod join;
+pub mod media_constraints;
+pub mod mention;
 pub mod message;
+pub mod message_retention;
 pub mod notification;
 pub mod oauth;
+pub mod onboarding;
+pub mod preflight;
+pub mod profanity_filter;
+pub mod public_channel;
 pub mod push;
+pub mod quick_switch;
 pub mod reaction;
+pub mod reaction_rule;
 pub mod recording;
 pub mod remote_control;
 pub mod role;
 pub mod room;
+pub mod sandbox;
+pub mod shared_draft;
+pub mod sso;
 pub mod stripe;
 pub mod tenant;
+pub mod tenant_config;
+pub mod thread;
+pub mod video_effects;
+pub mod webhook;
 
 pub mod search;
 pub mod user;
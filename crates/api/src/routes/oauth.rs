@@ -3,6 +3,7 @@ use axum::{
     http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Redirect, Response},
 };
+use roomler_ai_db::models::User;
 use serde::Deserialize;
 use uuid::Uuid;
 
@@ -56,6 +57,8 @@ pub async fn oauth_callback(
         ));
     }
 
+    crate::routes::sso::ensure_not_captured(&state, &user_info.email).await?;
+
     // Find or create user
     let user = state
         .users
@@ -68,6 +71,16 @@ pub async fn oauth_callback(
         )
         .await?;
 
+    signed_in_redirect(&state, &user, None)
+}
+
+/// Sign `user` in: set the access token cookie, plus `extra_cookie` when
+/// given, and send the browser to the frontend with the token.
+pub(crate) fn signed_in_redirect(
+    state: &AppState,
+    user: &User,
+    extra_cookie: Option<&str>,
+) -> Result<Response, ApiError> {
     let user_id = user.id.unwrap();
 
     // Generate JWT tokens
@@ -90,6 +103,9 @@ pub async fn oauth_callback(
 
     let mut headers = HeaderMap::new();
     headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+    if let Some(extra) = extra_cookie {
+        headers.append(header::SET_COOKIE, extra.parse().unwrap());
+    }
     headers.insert(header::LOCATION, redirect_url.parse().unwrap());
 
     Ok((StatusCode::FOUND, headers).into_response())
@@ -0,0 +1,78 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{OnboardingProgress, OnboardingStep};
+use serde::Serialize;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct OnboardingStepResponse {
+    pub key: OnboardingStep,
+    pub done: bool,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnboardingResponse {
+    pub steps: Vec<OnboardingStepResponse>,
+    pub completed: bool,
+    pub dismissed: bool,
+}
+
+/// GET /api/tenant/{tenant_id}/onboarding — the calling user's checklist.
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<OnboardingResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let progress = state.onboarding.dao.get(tid, auth.user_id).await?;
+    Ok(Json(to_response(&progress)))
+}
+
+/// POST /api/tenant/{tenant_id}/onboarding/dismiss — hide the checklist
+/// without completing it.
+pub async fn dismiss(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<OnboardingResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    state.onboarding.dao.dismiss(tid, auth.user_id).await?;
+    let progress = state.onboarding.dao.get(tid, auth.user_id).await?;
+    Ok(Json(to_response(&progress)))
+}
+
+fn to_response(progress: &OnboardingProgress) -> OnboardingResponse {
+    let steps: Vec<OnboardingStepResponse> = OnboardingStep::ALL
+        .iter()
+        .map(|step| {
+            let at = step.completed_at(progress);
+            OnboardingStepResponse {
+                key: *step,
+                done: at.is_some(),
+                completed_at: at.and_then(|t| t.try_to_rfc3339_string().ok()),
+            }
+        })
+        .collect();
+    OnboardingResponse {
+        completed: steps.iter().all(|s| s.done),
+        dismissed: progress.dismissed_at.is_some(),
+        steps,
+    }
+}
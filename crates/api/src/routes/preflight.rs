@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+};
+use bson::oid::ObjectId;
+use mediasoup::prelude::DtlsParameters;
+use roomler_ai_db::models::{PreflightReport, role::permissions};
+use roomler_ai_services::{dao::base::PaginationParams, media::room_manager::TransportOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+/// How long a probe transport stays open. Enough for ICE to run over UDP,
+/// TCP and TURN in turn.
+const PROBE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize)]
+pub struct PreflightResponse {
+    pub ice_servers: Vec<serde_json::Value>,
+    pub force_relay: bool,
+    pub probe_transport: TransportOptions,
+    pub probe_expires_in_secs: u64,
+    pub expected_ports: ExpectedPorts,
+}
+
+/// Where media will flow, so clients (and their network admins) know what
+/// has to be reachable. TURN endpoints are in `ice_servers`.
+#[derive(Debug, Serialize)]
+pub struct ExpectedPorts {
+    pub announced_ip: Option<String>,
+    pub udp: PortRange,
+    pub tcp: PortRange,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConnectProbeRequest {
+    pub transport_id: String,
+    pub dtls_parameters: DtlsParameters,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreflightReportRequest {
+    pub probe_transport_id: Option<String>,
+    pub udp_ok: Option<bool>,
+    pub tcp_ok: Option<bool>,
+    pub relay_ok: Option<bool>,
+    pub selected_candidate_type: Option<String>,
+    pub rtt_ms: Option<f64>,
+    pub camera_ok: Option<bool>,
+    pub microphone_ok: Option<bool>,
+    pub speaker_ok: Option<bool>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportListQuery {
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreflightReportResponse {
+    pub id: String,
+    pub user_id: String,
+    pub connected: bool,
+    pub probe_transport_id: Option<String>,
+    pub udp_ok: Option<bool>,
+    pub tcp_ok: Option<bool>,
+    pub relay_ok: Option<bool>,
+    pub selected_candidate_type: Option<String>,
+    pub rtt_ms: Option<f64>,
+    pub camera_ok: Option<bool>,
+    pub microphone_ok: Option<bool>,
+    pub speaker_ok: Option<bool>,
+    pub errors: Vec<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+}
+
+impl From<PreflightReport> for PreflightReportResponse {
+    fn from(r: PreflightReport) -> Self {
+        Self {
+            id: r.id.map(|id| id.to_hex()).unwrap_or_default(),
+            user_id: r.user_id.to_hex(),
+            connected: r.connected(),
+            probe_transport_id: r.probe_transport_id,
+            udp_ok: r.udp_ok,
+            tcp_ok: r.tcp_ok,
+            relay_ok: r.relay_ok,
+            selected_candidate_type: r.selected_candidate_type,
+            rtt_ms: r.rtt_ms,
+            camera_ok: r.camera_ok,
+            microphone_ok: r.microphone_ok,
+            speaker_ok: r.speaker_ok,
+            errors: r.errors,
+            user_agent: r.user_agent,
+            created_at: r.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/conference/preflight — everything a client
+/// needs to test connectivity before joining: ICE servers, a probe
+/// transport on the media server and the ports media will use.
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<PreflightResponse>, ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let probe_transport = state
+        .room_manager
+        .create_probe_transport(auth.user_id, PROBE_TTL)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let mediasoup = &state.settings.mediasoup;
+    let announced_ip = if mediasoup.announced_ip.is_empty() {
+        None
+    } else {
+        Some(mediasoup.announced_ip.clone())
+    };
+    // UDP and TCP listeners draw from the same range.
+    let range = || PortRange {
+        min: mediasoup.rtc_min_port,
+        max: mediasoup.rtc_max_port,
+    };
+
+    Ok(Json(PreflightResponse {
+        ice_servers: crate::ws::handler::conference_ice_servers(&state, &auth.user_id),
+        force_relay: state.settings.turn.force_relay.unwrap_or(false),
+        probe_transport,
+        probe_expires_in_secs: PROBE_TTL.as_secs(),
+        expected_ports: ExpectedPorts {
+            announced_ip,
+            udp: range(),
+            tcp: range(),
+        },
+    }))
+}
+
+/// POST /api/tenant/{tenant_id}/conference/preflight/connect — DTLS
+/// parameters for the caller's probe transport, as in `media:connect_transport`.
+pub async fn connect(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<ConnectProbeRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    state
+        .room_manager
+        .connect_probe_transport(auth.user_id, &body.transport_id, body.dtls_parameters)
+        .await
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+
+    Ok(Json(serde_json::json!({ "connected": true })))
+}
+
+/// POST /api/tenant/{tenant_id}/conference/preflight/report — store the
+/// client's results for support.
+pub async fn report(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<PreflightReportRequest>,
+) -> Result<(StatusCode, Json<PreflightReportResponse>), ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    if body.errors.len() > 50 || body.errors.iter().any(|e| e.len() > 1000) {
+        return Err(ApiError::Validation(
+            "At most 50 errors of up to 1000 characters each".to_string(),
+        ));
+    }
+
+    let report = PreflightReport {
+        id: None,
+        tenant_id: tid,
+        user_id: auth.user_id,
+        probe_transport_id: body.probe_transport_id,
+        udp_ok: body.udp_ok,
+        tcp_ok: body.tcp_ok,
+        relay_ok: body.relay_ok,
+        selected_candidate_type: body.selected_candidate_type,
+        rtt_ms: body.rtt_ms,
+        camera_ok: body.camera_ok,
+        microphone_ok: body.microphone_ok,
+        speaker_ok: body.speaker_ok,
+        errors: body.errors,
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        created_at: bson::DateTime::now(),
+    };
+    let report = state.preflight_reports.create(&report).await?;
+
+    Ok((StatusCode::CREATED, Json(report.into())))
+}
+
+/// GET /api/tenant/{tenant_id}/conference/preflight/report — stored reports,
+/// newest first. Requires MANAGE_TENANT.
+pub async fn list_reports(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<ReportListQuery>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_tenant(&tenant_id)?;
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    let user_id = query
+        .user_id
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let result = state
+        .preflight_reports
+        .find_for_tenant(tid, user_id, &params)
+        .await?;
+    let items: Vec<PreflightReportResponse> = result.items.into_iter().map(Into::into).collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "total": result.total,
+        "page": result.page,
+        "per_page": result.per_page,
+        "total_pages": result.total_pages,
+    })))
+}
+
+fn parse_tenant(tenant_id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))
+}
@@ -0,0 +1,58 @@
+//! The tenant's profanity filter for channel messages; see
+//! [`crate::profanity`].
+
+use axum::{Json, extract::State};
+use roomler_ai_db::models::{ProfanityFilter, actions, role::permissions};
+use roomler_ai_services::profanity;
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+/// GET /api/tenant/{tenant_id}/profanity-filter
+pub async fn get(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<ProfanityFilter>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(tenant.settings.profanity_filter))
+}
+
+/// PUT /api/tenant/{tenant_id}/profanity-filter — replace the filter. It
+/// applies to messages posted or edited from now on.
+pub async fn set(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<ProfanityFilter>,
+) -> Result<Json<ProfanityFilter>, ApiError> {
+    profanity::validate(&body).map_err(ApiError::Validation)?;
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    state.tenants.set_profanity_filter(tid, &body).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::TENANT_PROFANITY_FILTER_UPDATE,
+        Some(tid),
+        vec![audit::change(
+            "profanity_filter",
+            serde_json::to_value(&tenant.settings.profanity_filter).ok(),
+            serde_json::to_value(&body).ok(),
+        )],
+    )
+    .await;
+
+    Ok(Json(body))
+}
@@ -0,0 +1,259 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, PublicShare, Room, actions, role::permissions};
+use roomler_ai_services::dao::base::PaginationParams;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct ShareRequest {
+    #[serde(default)]
+    pub indexable: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareResponse {
+    pub public: bool,
+    pub token: Option<String>,
+    pub url: Option<String>,
+    pub indexable: bool,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicChannelResponse {
+    pub name: String,
+    pub topic: Option<String>,
+    pub purpose: Option<String>,
+    pub message_count: u64,
+    /// Content for the page's `<meta name="robots">`.
+    pub robots: &'static str,
+}
+
+/// A message as shown on the public page: no author, mentions, reactions
+/// or attachments.
+#[derive(Debug, Serialize)]
+pub struct PublicMessageResponse {
+    pub id: String,
+    pub content: String,
+    pub reply_count: u32,
+    pub is_edited: bool,
+    pub created_at: String,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/public
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let tid = parse_id(&tenant_id, "tenant_id")?;
+    let rid = parse_id(&room_id, "room_id")?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    Ok(Json(to_share_response(&state, room.public_share.as_ref())))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/public — let anyone with the
+/// link read the channel. Sharing again keeps the link and updates
+/// `indexable`.
+pub async fn share(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+    Json(body): Json<ShareRequest>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let rid = parse_id(&room_id, "room_id")?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.is_dm() {
+        return Err(ApiError::Validation(
+            "Direct messages cannot be made public".to_string(),
+        ));
+    }
+    let share = match room.public_share.clone() {
+        Some(share) => PublicShare {
+            indexable: body.indexable,
+            ..share
+        },
+        None => PublicShare {
+            token: nanoid::nanoid!(32),
+            indexable: body.indexable,
+            created_by: auth.user_id,
+            created_at: bson::DateTime::now(),
+        },
+    };
+    state.rooms.set_public_share(tid, rid, Some(&share)).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROOM_PUBLIC_SHARE,
+        Some(rid),
+        vec![audit::change(
+            "indexable",
+            room.public_share.map(|s| s.indexable.into()),
+            Some(share.indexable.into()),
+        )],
+    )
+    .await;
+
+    Ok(Json(to_share_response(&state, Some(&share))))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/public — revoke public
+/// access; the link stops working at once.
+pub async fn unshare(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, room_id)): Path<(String, String)>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let rid = parse_id(&room_id, "room_id")?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.public_share.is_some() {
+        state.rooms.set_public_share(tid, rid, None).await?;
+        audit::record(
+            &state,
+            tid,
+            auth.user_id,
+            &client,
+            actions::ROOM_PUBLIC_UNSHARE,
+            Some(rid),
+            Vec::new(),
+        )
+        .await;
+    }
+    Ok(Json(to_share_response(&state, None)))
+}
+
+/// GET /api/public/channel/{token} — no auth.
+pub async fn info(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, ApiError> {
+    let (room, share) = find_public(&state, &token).await?;
+    Ok(public_response(
+        &share,
+        Json(PublicChannelResponse {
+            name: room.name,
+            topic: room.topic,
+            purpose: room.purpose,
+            message_count: room.message_count,
+            robots: robots(&share),
+        }),
+    ))
+}
+
+/// GET /api/public/channel/{token}/message — no auth. Top-level messages,
+/// newest first, paginated like the member view. System messages (joins,
+/// leaves, pins, calls) name members and are left out of the page.
+pub async fn messages(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, ApiError> {
+    let (room, share) = find_public(&state, &token).await?;
+    let rid = room
+        .id
+        .ok_or_else(|| ApiError::NotFound("Channel not found".to_string()))?;
+    let result = state.messages.find_public_in_room(rid, &params).await?;
+    let items: Vec<PublicMessageResponse> =
+        result.items.into_iter().map(to_public_message).collect();
+    Ok(public_response(
+        &share,
+        Json(serde_json::json!({
+            "items": items,
+            "total": result.total,
+            "page": result.page,
+            "per_page": result.per_page,
+            "total_pages": result.total_pages,
+        })),
+    ))
+}
+
+async fn find_public(state: &AppState, token: &str) -> Result<(Room, PublicShare), ApiError> {
+    let not_found = || ApiError::NotFound("Channel not found".to_string());
+    let room = state
+        .rooms
+        .find_by_public_token(token)
+        .await?
+        .ok_or_else(not_found)?;
+    let share = room.public_share.clone().ok_or_else(not_found)?;
+    Ok((room, share))
+}
+
+/// Public responses are never cached, so revoking takes effect at once,
+/// and carry the share's robots directive.
+fn public_response(share: &PublicShare, body: impl IntoResponse) -> Response {
+    (
+        [
+            (header::CACHE_CONTROL, "no-store"),
+            (
+                header::HeaderName::from_static("x-robots-tag"),
+                robots(share),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn robots(share: &PublicShare) -> &'static str {
+    if share.indexable {
+        "index, follow"
+    } else {
+        "noindex, nofollow"
+    }
+}
+
+fn to_public_message(m: Message) -> PublicMessageResponse {
+    PublicMessageResponse {
+        id: m.id.map(|id| id.to_hex()).unwrap_or_default(),
+        content: m.content,
+        reply_count: m.thread_metadata.map(|t| t.reply_count).unwrap_or(0),
+        is_edited: m.is_edited,
+        created_at: m.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+fn to_share_response(state: &AppState, share: Option<&PublicShare>) -> ShareResponse {
+    ShareResponse {
+        public: share.is_some(),
+        token: share.map(|s| s.token.clone()),
+        url: share.map(|s| format!("{}/public/{}", state.settings.app.frontend_url, s.token)),
+        indexable: share.is_some_and(|s| s.indexable),
+        created_at: share.and_then(|s| s.created_at.try_to_rfc3339_string().ok()),
+    }
+}
+
+fn parse_id(id: &str, name: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(id).map_err(|_| ApiError::BadRequest(format!("Invalid {}", name)))
+}
@@ -0,0 +1,256 @@
+//! Data for the Cmd+K quick switcher: one ranked list of channels, DMs,
+//! members and recent conferences. Scoring lives in
+//! [`roomler_ai_services::quick_switch`].
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::{DateTime, doc, oid::ObjectId};
+use roomler_ai_services::quick_switch::{self, Candidate, CandidateKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+/// Conferences that ended longer ago than this aren't offered.
+const RECENT_CONFERENCE_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct QuickSwitchQuery {
+    #[serde(default)]
+    pub q: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuickSwitchItem {
+    pub kind: CandidateKind,
+    pub id: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Room to open; for members, their 1:1 DM with the caller if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<String>,
+    pub score: f64,
+}
+
+/// GET /api/tenant/{tenant_id}/quick-switch?q= — best matches first. An
+/// empty `q` lists what the caller used most recently.
+pub async fn search(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<QuickSwitchQuery>,
+) -> Result<Json<Vec<QuickSwitchItem>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let candidates = match state.quick_switch.get(tid, auth.user_id) {
+        Some(candidates) => candidates,
+        None => {
+            let candidates = load_candidates(&state, tid, auth.user_id).await?;
+            state.quick_switch.insert(tid, auth.user_id, candidates)
+        }
+    };
+
+    let now_ms = DateTime::now().timestamp_millis();
+    let items = quick_switch::rank(&candidates, &query.q, now_ms, query.limit.min(50))
+        .into_iter()
+        .map(|(c, score)| QuickSwitchItem {
+            kind: c.kind,
+            id: c.id.to_hex(),
+            label: c.label.clone(),
+            detail: c.detail.clone(),
+            room_id: c.room_id.map(|id| id.to_hex()),
+            score,
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// Everything `user_id` can switch to in the tenant, with popularity
+/// normalised per kind.
+async fn load_candidates(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<Vec<Candidate>, ApiError> {
+    let now_ms = DateTime::now().timestamp_millis();
+    let mut candidates = Vec::new();
+
+    // When the caller last read each of their rooms.
+    let last_read: HashMap<ObjectId, Option<i64>> = state
+        .rooms
+        .members
+        .find_many(doc! { "tenant_id": tenant_id, "user_id": user_id }, None)
+        .await?
+        .into_iter()
+        .map(|m| (m.room_id, m.last_read_at.map(|at| at.timestamp_millis())))
+        .collect();
+
+    // Channels the caller is in or could join, and their recent calls.
+    let rooms: Vec<_> = state
+        .rooms
+        .find_by_tenant(tenant_id)
+        .await?
+        .into_iter()
+        .filter(|r| r.is_open || r.id.is_some_and(|id| last_read.contains_key(&id)))
+        .collect();
+    let max_messages = rooms.iter().map(|r| r.message_count).max().unwrap_or(0);
+    let max_peak = rooms
+        .iter()
+        .map(|r| u64::from(r.peak_participant_count))
+        .max()
+        .unwrap_or(0);
+    let conference_cutoff = now_ms - RECENT_CONFERENCE_DAYS * 24 * 3600 * 1000;
+    for room in rooms {
+        let Some(room_id) = room.id else { continue };
+        let live = room.conference_status.as_deref() == Some("in_progress");
+        let last_call_ms = if live {
+            Some(now_ms)
+        } else {
+            room.actual_end_time
+                .or(room.actual_start_time)
+                .map(|at| at.timestamp_millis())
+                .filter(|at| *at >= conference_cutoff)
+        };
+        if last_call_ms.is_some() {
+            candidates.push(Candidate {
+                kind: CandidateKind::Conference,
+                id: room_id,
+                label: room.name.clone(),
+                aliases: Vec::new(),
+                detail: live.then(|| "live".to_string()),
+                room_id: Some(room_id),
+                popularity: quick_switch::popularity(
+                    u64::from(room.peak_participant_count),
+                    max_peak,
+                ),
+                last_active_ms: last_call_ms,
+            });
+        }
+        candidates.push(Candidate {
+            kind: CandidateKind::Channel,
+            id: room_id,
+            label: room.name,
+            aliases: Vec::new(),
+            detail: room.purpose.or(room.topic),
+            room_id: Some(room_id),
+            popularity: quick_switch::popularity(room.message_count, max_messages),
+            last_active_ms: last_read.get(&room_id).copied().flatten(),
+        });
+    }
+
+    // DMs, labelled with the other participants.
+    let dms = state.rooms.find_user_dms(tenant_id, user_id).await?;
+    let dm_ids: Vec<ObjectId> = dms.iter().filter_map(|r| r.id).collect();
+    let mut participants: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+    if !dm_ids.is_empty() {
+        for member in state
+            .rooms
+            .members
+            .find_many(doc! { "room_id": { "$in": &dm_ids } }, None)
+            .await?
+        {
+            if let Some(uid) = member.user_id
+                && uid != user_id
+            {
+                participants.entry(member.room_id).or_default().push(uid);
+            }
+        }
+    }
+    let others: Vec<ObjectId> = participants.values().flatten().copied().collect();
+    let names = state
+        .users
+        .find_display_names(&others)
+        .await
+        .unwrap_or_default();
+    let max_dm_messages = dms.iter().map(|r| r.message_count).max().unwrap_or(0);
+    // Per member: their 1:1 DM, how many DMs they share, the latest activity.
+    let mut shared: HashMap<ObjectId, (Option<ObjectId>, u64, Option<i64>)> = HashMap::new();
+    for dm in dms {
+        let Some(room_id) = dm.id else { continue };
+        let last_active_ms = dm.last_activity_at.map(|at| at.timestamp_millis());
+        let with = participants.remove(&room_id).unwrap_or_default();
+        for uid in &with {
+            let entry = shared.entry(*uid).or_default();
+            if with.len() == 1 {
+                entry.0 = Some(room_id);
+            }
+            entry.1 += 1;
+            entry.2 = entry.2.max(last_active_ms);
+        }
+        let label = if with.is_empty() {
+            dm.name
+        } else {
+            with.iter()
+                .map(|id| names.get(id).cloned().unwrap_or_else(|| id.to_hex()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        candidates.push(Candidate {
+            kind: CandidateKind::Dm,
+            id: room_id,
+            label,
+            aliases: Vec::new(),
+            detail: None,
+            room_id: Some(room_id),
+            popularity: quick_switch::popularity(dm.message_count, max_dm_messages),
+            last_active_ms,
+        });
+    }
+
+    // Members, most talked-to first.
+    let member_ids: Vec<ObjectId> = state
+        .tenants
+        .find_member_user_ids(tenant_id)
+        .await?
+        .into_iter()
+        .filter(|id| *id != user_id)
+        .collect();
+    if !member_ids.is_empty() {
+        let users = state
+            .users
+            .base
+            .find_many(
+                doc! { "_id": { "$in": &member_ids }, "deleted_at": null },
+                None,
+            )
+            .await?;
+        let max_shared = shared.values().map(|s| s.1).max().unwrap_or(0);
+        for user in users {
+            let Some(uid) = user.id else { continue };
+            let (dm_id, dm_count, last_active_ms) = shared.get(&uid).copied().unwrap_or_default();
+            let label = if user.display_name.is_empty() {
+                user.username.clone()
+            } else {
+                user.display_name
+            };
+            candidates.push(Candidate {
+                kind: CandidateKind::Member,
+                id: uid,
+                label,
+                detail: Some(format!("@{}", user.username)),
+                aliases: vec![user.username],
+                room_id: dm_id,
+                popularity: quick_switch::popularity(dm_count, max_shared),
+                last_active_ms,
+            });
+        }
+    }
+
+    Ok(candidates)
+}
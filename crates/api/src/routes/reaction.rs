@@ -3,13 +3,20 @@ use axum::{
     extract::{Path, State},
 };
 use bson::oid::ObjectId;
+use roomler_ai_db::models::ReactionTrigger;
 use serde::Deserialize;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError, extractors::auth::AuthUser, reaction_rules::ReactionEvent, state::AppState,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct AddReactionRequest {
+    /// Unicode or a `:shortcode:`.
+    #[serde(default)]
     pub emoji: String,
+    /// One of the tenant's custom emoji; takes precedence over `emoji`.
+    pub custom_emoji_id: Option<String>,
 }
 
 pub async fn add(
@@ -28,12 +35,29 @@ pub async fn add(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    super::room::visible_room(&state, tid, rid, auth.user_id).await?;
+    let emoji = match &body.custom_emoji_id {
+        Some(id) => crate::emoji::custom_reaction_emoji(&state, tid, id).await?,
+        None => crate::emoji::reaction_emoji(&state, tid, &body.emoji).await?,
+    };
 
     let reaction = state
         .reactions
-        .add_and_update_summary(&state.messages, tid, rid, mid, auth.user_id, body.emoji)
+        .add_and_update_summary(&state.messages, tid, rid, mid, auth.user_id, emoji)
         .await?;
 
+    crate::reaction_rules::spawn(
+        &state,
+        ReactionEvent {
+            trigger: ReactionTrigger::Add,
+            tenant_id: tid,
+            room_id: rid,
+            message_id: mid,
+            user_id: auth.user_id,
+            emoji: reaction.emoji.value.clone(),
+        },
+    );
+
     let member_ids = state.rooms.find_member_user_ids(rid).await?;
     let event = serde_json::json!({
         "type": "message:reaction",
@@ -43,11 +67,14 @@ pub async fn add(
             "room_id": room_id,
             "user_id": auth.user_id.to_hex(),
             "emoji": reaction.emoji.value,
+            "custom_emoji_id": reaction.emoji.custom_emoji_id.map(|id| id.to_hex()),
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
+    crate::ws::dispatcher::broadcast_in_tenant(
         &state.ws_storage,
         &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
         &member_ids,
         &event,
     )
@@ -69,6 +96,7 @@ pub async fn remove(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let emoji = crate::emoji::reaction_value(&emoji);
 
     let removed = state
         .reactions
@@ -78,6 +106,17 @@ pub async fn remove(
     if removed {
         let rid = ObjectId::parse_str(&room_id)
             .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+        crate::reaction_rules::spawn(
+            &state,
+            ReactionEvent {
+                trigger: ReactionTrigger::Remove,
+                tenant_id: tid,
+                room_id: rid,
+                message_id: mid,
+                user_id: auth.user_id,
+                emoji: emoji.clone(),
+            },
+        );
         let member_ids = state.rooms.find_member_user_ids(rid).await?;
         let event = serde_json::json!({
             "type": "message:reaction",
@@ -89,9 +128,11 @@ pub async fn remove(
                 "emoji": emoji,
             }
         });
-        crate::ws::dispatcher::broadcast_with_redis(
+        crate::ws::dispatcher::broadcast_in_tenant(
             &state.ws_storage,
             &state.redis_pubsub,
+            &state.delivery_metrics,
+            tid,
             &member_ids,
             &event,
         )
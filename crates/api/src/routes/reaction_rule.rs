@@ -0,0 +1,335 @@
+//! Tenant reaction rules ("when someone reacts with ✅ in #approvals, call
+//! this webhook"). Managing them requires MANAGE_TENANT; they fire through
+//! [`crate::reaction_rules`].
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::{DateTime, Document, oid::ObjectId};
+use roomler_ai_db::models::{
+    ReactionRule, ReactionTrigger, RuleAction, actions, role::permissions,
+};
+use roomler_ai_services::reaction_rules;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReactionRuleRequest {
+    pub name: String,
+    /// Omit to match every room.
+    pub room_id: Option<String>,
+    pub emoji: String,
+    #[serde(default = "default_trigger")]
+    pub trigger: ReactionTrigger,
+    pub action: RuleActionRequest,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateReactionRuleRequest {
+    pub name: Option<String>,
+    pub action: Option<RuleActionRequest>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleActionRequest {
+    Webhook {
+        url: String,
+    },
+    PostMessage {
+        room_id: Option<String>,
+        content: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReactionRuleResponse {
+    pub id: String,
+    pub name: String,
+    pub room_id: Option<String>,
+    pub emoji: String,
+    pub trigger: ReactionTrigger,
+    pub action: RuleActionRequest,
+    pub enabled: bool,
+    /// Key for verifying `X-Roomler-Signature` on webhook deliveries; only
+    /// shown to tenant managers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+fn default_trigger() -> ReactionTrigger {
+    ReactionTrigger::Add
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn to_response(rule: ReactionRule, with_secret: bool) -> ReactionRuleResponse {
+    ReactionRuleResponse {
+        id: rule.id.map(|id| id.to_hex()).unwrap_or_default(),
+        name: rule.name,
+        room_id: rule.room_id.map(|id| id.to_hex()),
+        emoji: rule.emoji,
+        trigger: rule.trigger,
+        action: match rule.action {
+            RuleAction::Webhook { url } => RuleActionRequest::Webhook { url },
+            RuleAction::PostMessage { room_id, content } => RuleActionRequest::PostMessage {
+                room_id: room_id.map(|id| id.to_hex()),
+                content,
+            },
+        },
+        enabled: rule.enabled,
+        secret: with_secret.then_some(rule.secret),
+        created_by: rule.created_by.to_hex(),
+        created_at: rule.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/reaction-rule — oldest first.
+pub async fn list(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<Vec<ReactionRuleResponse>>, ApiError> {
+    let rules = state.reaction_rules.find_for_tenant(tid).await?;
+    Ok(Json(
+        rules
+            .into_iter()
+            .map(|rule| to_response(rule, true))
+            .collect(),
+    ))
+}
+
+/// POST /api/tenant/{tenant_id}/reaction-rule
+pub async fn create(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<CreateReactionRuleRequest>,
+) -> Result<(StatusCode, Json<ReactionRuleResponse>), ApiError> {
+    let max_rules = state.settings.reaction_rules.max_rules_per_tenant;
+    if state.reaction_rules.count_for_tenant(tid).await? >= u64::from(max_rules) {
+        return Err(ApiError::Validation(format!(
+            "A tenant can have at most {max_rules} reaction rules"
+        )));
+    }
+    let room_id = match body.room_id.as_deref() {
+        Some(room_id) => Some(tenant_room(&state, tid, room_id).await?),
+        None => None,
+    };
+    let emoji = crate::emoji::reaction_emoji(&state, tid, &body.emoji)
+        .await?
+        .value;
+    let action = parse_action(&state, tid, body.action).await?;
+    let name = body.name.trim().to_string();
+    reaction_rules::validate(&name, &action).map_err(ApiError::Validation)?;
+
+    let now = DateTime::now();
+    let rule = state
+        .reaction_rules
+        .create(&ReactionRule {
+            id: None,
+            tenant_id: tid,
+            name,
+            room_id,
+            emoji,
+            trigger: body.trigger,
+            action,
+            enabled: body.enabled,
+            secret: nanoid::nanoid!(32),
+            created_by: auth.user_id,
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::REACTION_RULE_CREATE,
+        rule.id,
+        vec![
+            audit::change("name", None, Some(rule.name.clone().into())),
+            audit::change("emoji", None, Some(rule.emoji.clone().into())),
+        ],
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(to_response(rule, true))))
+}
+
+/// PUT /api/tenant/{tenant_id}/reaction-rule/{rule_id} — omitted fields
+/// are kept.
+pub async fn update(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, rule_id)): Path<(String, String)>,
+    Json(body): Json<UpdateReactionRuleRequest>,
+) -> Result<Json<ReactionRuleResponse>, ApiError> {
+    let id = parse_rule(&rule_id)?;
+    let rule = state
+        .reaction_rules
+        .base
+        .find_by_id_in_tenant(tid, id)
+        .await?;
+
+    let name = body
+        .name
+        .map(|n| n.trim().to_string())
+        .unwrap_or_else(|| rule.name.clone());
+    let action = match body.action {
+        Some(action) => parse_action(&state, tid, action).await?,
+        None => rule.action.clone(),
+    };
+    reaction_rules::validate(&name, &action).map_err(ApiError::Validation)?;
+
+    let mut set = Document::new();
+    let mut changes = Vec::new();
+    if name != rule.name {
+        changes.push(audit::change(
+            "name",
+            Some(rule.name.clone().into()),
+            Some(name.clone().into()),
+        ));
+        set.insert("name", name);
+    }
+    if action != rule.action {
+        changes.push(audit::change(
+            "action",
+            serde_json::to_value(&rule.action).ok(),
+            serde_json::to_value(&action).ok(),
+        ));
+        set.insert(
+            "action",
+            bson::to_bson(&action).map_err(|e| ApiError::Internal(e.to_string()))?,
+        );
+    }
+    if let Some(enabled) = body.enabled
+        && enabled != rule.enabled
+    {
+        changes.push(audit::change(
+            "enabled",
+            Some(rule.enabled.into()),
+            Some(enabled.into()),
+        ));
+        set.insert("enabled", enabled);
+    }
+    if !set.is_empty() {
+        state.reaction_rules.update(tid, id, set).await?;
+        audit::record(
+            &state,
+            tid,
+            auth.user_id,
+            &client,
+            actions::REACTION_RULE_UPDATE,
+            Some(id),
+            changes,
+        )
+        .await;
+    }
+
+    let rule = state.reaction_rules.base.find_by_id(id).await?;
+    Ok(Json(to_response(rule, true)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/reaction-rule/{rule_id}
+pub async fn delete(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, rule_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let id = parse_rule(&rule_id)?;
+    let rule = state
+        .reaction_rules
+        .base
+        .find_by_id_in_tenant(tid, id)
+        .await?;
+
+    state.reaction_rules.delete(tid, id).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::REACTION_RULE_DELETE,
+        Some(id),
+        vec![audit::change("name", Some(rule.name.into()), None)],
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+async fn parse_action(
+    state: &AppState,
+    tenant_id: ObjectId,
+    action: RuleActionRequest,
+) -> Result<RuleAction, ApiError> {
+    Ok(match action {
+        RuleActionRequest::Webhook { url } => {
+            state
+                .outbound
+                .check_public_url(&url)
+                .await
+                .map_err(ApiError::Validation)?;
+            RuleAction::Webhook { url }
+        }
+        RuleActionRequest::PostMessage { room_id, content } => RuleAction::PostMessage {
+            room_id: match room_id.as_deref() {
+                Some(room_id) => Some(tenant_room(state, tenant_id, room_id).await?),
+                None => None,
+            },
+            content,
+        },
+    })
+}
+
+/// A room of the tenant, by id.
+async fn tenant_room(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: &str,
+) -> Result<ObjectId, ApiError> {
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    state
+        .rooms
+        .base
+        .find_by_id_in_tenant(tenant_id, rid)
+        .await?;
+    Ok(rid)
+}
+
+fn parse_rule(rule_id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(rule_id).map_err(|_| ApiError::BadRequest("Invalid rule_id".to_string()))
+}
@@ -1,12 +1,33 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
 use axum::{
     Json,
+    body::{Body, Bytes},
     extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use bson::oid::ObjectId;
+use bson::{doc, oid::ObjectId};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_services::dao::base::PaginationParams;
+use roomler_ai_db::models::{
+    ConferenceEventType, MessageAttachment, Recording, RecordingShareLink, RecordingStatus,
+    RecordingType, Room, TaskCategory, Visibility, webhook_events,
+};
+use roomler_ai_services::{
+    dao::base::PaginationParams,
+    recording_access::{self, RecordingAccess, Viewer},
+    recording_clip, recording_playback,
+};
+
+/// Share links expire after a week unless the caller asks otherwise.
+const DEFAULT_SHARE_LINK_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_SHARE_LINK_TTL_SECS: u64 = 90 * 24 * 3600;
+/// Read size when streaming a recording from disk.
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
 
 #[derive(Debug, Serialize)]
 pub struct RecordingResponse {
@@ -17,6 +38,9 @@ pub struct RecordingResponse {
     pub content_type: String,
     pub size: u64,
     pub duration: u32,
+    pub part_count: usize,
+    pub visibility: Visibility,
+    pub allow_download: bool,
     pub created_at: String,
 }
 
@@ -31,11 +55,14 @@ pub async fn list(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let viewer = viewer(&state, &room, auth.user_id).await?;
+    let filter = (!recording_access::manages_room(&room, &viewer)).then_some(&viewer);
 
-    let result = state.recordings.find_by_room(rid, &params).await?;
+    let result = state
+        .recordings
+        .find_by_room_for_viewer(rid, filter, &params)
+        .await?;
     let items: Vec<RecordingResponse> = result.items.into_iter().map(to_response).collect();
 
     Ok(Json(serde_json::json!({
@@ -63,8 +90,10 @@ pub async fn create(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let viewer = viewer(&state, &room, auth.user_id).await?;
+    if !viewer.is_room_member && !recording_access::manages_room(&room, &viewer) {
+        return Err(ApiError::Forbidden("Not a room member".to_string()));
     }
 
     let recording_type = match body.recording_type.as_deref() {
@@ -85,29 +114,970 @@ pub async fn create(
         resolution: None,
     };
 
+    // Everyone in the call when it starts can watch it back; later joiners
+    // are added as they connect.
+    let participants = state.room_manager.get_participant_user_ids(&rid);
+    let mut acl_participants = participants.clone();
+    if !acl_participants.contains(&auth.user_id) {
+        acl_participants.push(auth.user_id);
+    }
     let recording = state
         .recordings
-        .create(tid, rid, recording_type, storage_file, now, now)
+        .create(
+            tid,
+            rid,
+            recording_type,
+            storage_file,
+            now,
+            now,
+            acl_participants,
+            auth.user_id,
+        )
+        .await?;
+    crate::presence::broadcast_activity(&state, rid, &participants).await;
+    let response = to_response(recording);
+    crate::conference_events::record(
+        &state,
+        rid,
+        ConferenceEventType::RecordingStarted,
+        Some(auth.user_id),
+        doc! { "recording_id": &response.id, "recording_type": &response.recording_type },
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+/// Upload one chunk of a recording. The part is persisted in the
+/// recording's segment manifest before responding. Only whoever started
+/// the recording, or someone managing it, may upload.
+pub async fn upload_part(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id, part_number)): Path<(String, String, String, u32)>,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_uploader(&recording, access, auth.user_id)?;
+    let part = state
+        .recording_uploads
+        .upload_part(&recording, part_number, &body)
         .await?;
 
+    Ok(Json(serde_json::json!({
+        "part_number": part.part_number,
+        "etag": part.etag,
+        "size": part.size,
+    })))
+}
+
+/// Assemble the uploaded parts and mark the recording available. Same
+/// access as uploading a part.
+pub async fn complete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Json<RecordingResponse>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_uploader(&recording, access, auth.user_id)?;
+    // Whoever is still in the call saw the end of it.
+    let participants = state
+        .room_manager
+        .get_participant_user_ids(&recording.room_id);
+    if let Err(e) = state
+        .recordings
+        .add_recording_participants(recording.id.unwrap(), &participants)
+        .await
+    {
+        tracing::warn!(%e, "Failed to add call participants to recording ACL");
+    }
+    if !state
+        .recording_uploads
+        .complete(&recording, RecordingStatus::Available)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Recording is already finalized".to_string(),
+        ));
+    }
+    crate::presence::broadcast_activity(&state, recording.room_id, &participants).await;
+    crate::conference_events::record(
+        &state,
+        recording.room_id,
+        ConferenceEventType::RecordingStopped,
+        Some(auth.user_id),
+        doc! { "recording_id": recording.id.map(|id| id.to_hex()) },
+    )
+    .await;
+
+    let recording = state
+        .recordings
+        .base
+        .find_by_id(recording.id.unwrap())
+        .await?;
     Ok(Json(to_response(recording)))
 }
 
-pub async fn delete(
+/// GET .../recording/{recording_id}/download — the whole file as an
+/// attachment. Viewers need `allow_download`; managers always may.
+pub async fn download(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Response, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_download(&recording, access)?;
+    serve_file(&state, &recording, &HeaderMap::new(), true).await
+}
+
+/// GET .../recording/{recording_id}/stream — inline playback with HTTP
+/// range support. Needs the same access as downloading.
+pub async fn stream(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_download(&recording, access)?;
+    serve_file(&state, &recording, &headers, false).await
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaybackUrlResponse {
+    pub url: String,
+    pub expires_at: String,
+}
+
+/// GET .../recording/{recording_id}/playback-url — an expiring URL to
+/// embed in a `<video>` element, which can't send the bearer token. Needs
+/// the same access as streaming.
+pub async fn playback_url(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Json<PlaybackUrlResponse>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_download(&recording, access)?;
+    if matches!(recording.status, RecordingStatus::Processing) {
+        return Err(ApiError::Conflict(
+            "Recording is still being uploaded".to_string(),
+        ));
+    }
+    let settings = &state.settings.recording_playback;
+    let now = chrono::Utc::now().timestamp();
+    let (url, expires) = match state.object_store.presign_get(
+        recording.file.storage_provider,
+        &recording.file.key,
+        Duration::from_secs(settings.url_ttl_secs.max(1)),
+    ) {
+        Some(url) => (url, now + settings.url_ttl_secs.max(1) as i64),
+        None => recording_playback::signed_path(
+            &settings.signing_secret,
+            recording.id.unwrap(),
+            now,
+            settings.url_ttl_secs,
+        ),
+    };
+    Ok(Json(PlaybackUrlResponse {
+        url,
+        expires_at: bson::DateTime::from_millis(expires * 1000)
+            .try_to_rfc3339_string()
+            .unwrap_or_default(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedStreamQuery {
+    pub expires: i64,
+    pub sig: String,
+}
+
+/// GET /api/recording/signed/{recording_id}/stream — playback through a
+/// URL from `playback-url`, no auth.
+pub async fn signed_stream(
+    State(state): State<AppState>,
+    Path(recording_id): Path<String>,
+    Query(query): Query<SignedStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let rec_id = ObjectId::parse_str(&recording_id)
+        .map_err(|_| ApiError::BadRequest("Invalid recording_id".to_string()))?;
+    if !recording_playback::verify(
+        &state.settings.recording_playback.signing_secret,
+        rec_id,
+        query.expires,
+        &query.sig,
+        chrono::Utc::now().timestamp(),
+    ) {
+        return Err(ApiError::Forbidden(
+            "Invalid or expired signature".to_string(),
+        ));
+    }
+    let recording = state.recordings.base.find_by_id(rec_id).await?;
+    if recording.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Recording not found".to_string()));
+    }
+    serve_file(&state, &recording, &headers, false).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateClipRequest {
+    /// Seconds into the recording.
+    pub start_secs: f64,
+    pub end_secs: f64,
+    /// Post the clip to this room, with the transcript of the range.
+    pub post_to_room_id: Option<String>,
+    /// Shown above the transcript excerpt when posting.
+    pub comment: Option<String>,
+}
+
+/// POST .../recording/{recording_id}/clip — cut `start_secs..end_secs`
+/// into a file of its own with ffmpeg. Answers `202` with a task to poll;
+/// the clip is its download and is listed under `.../clip` once cut.
+/// Needs the same access as downloading the recording.
+pub async fn create_clip(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+    Json(body): Json<CreateClipRequest>,
+) -> Result<Response, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_download(&recording, access)?;
+    match recording.status {
+        RecordingStatus::Processing => {
+            return Err(ApiError::Conflict(
+                "Recording is still being uploaded".to_string(),
+            ));
+        }
+        RecordingStatus::Failed => {
+            return Err(ApiError::Validation("Recording has no media".to_string()));
+        }
+        _ => {}
+    }
+    if matches!(recording.recording_type, RecordingType::ChatLog) {
+        return Err(ApiError::Validation(
+            "Chat logs can't be clipped".to_string(),
+        ));
+    }
+    let settings = state.settings.recording_clips.clone();
+    recording_clip::validate_range(
+        body.start_secs,
+        body.end_secs,
+        recording.file.duration,
+        settings.max_clip_secs,
+    )
+    .map_err(ApiError::Validation)?;
+
+    let tid = recording.tenant_id;
+    let post_to = match body.post_to_room_id.as_deref() {
+        Some(id) => {
+            let target_id = ObjectId::parse_str(id)
+                .map_err(|_| ApiError::BadRequest("Invalid post_to_room_id".to_string()))?;
+            let room = super::room::visible_room(&state, tid, target_id, auth.user_id).await?;
+            let role = super::room::require_channel_action(
+                &state,
+                tid,
+                &room,
+                auth.user_id,
+                super::room::posting_action(&room),
+            )
+            .await?;
+            super::room::require_outside_read_only_window(&room, role)?;
+            Some(target_id)
+        }
+        None => None,
+    };
+
+    let task = state
+        .tasks
+        .create_task(
+            tid,
+            auth.user_id,
+            "recording_clip".to_string(),
+            TaskCategory::Recording,
+            serde_json::json!({
+                "recording_id": recording_id,
+                "start_secs": body.start_secs,
+                "end_secs": body.end_secs,
+                "post_to_room_id": body.post_to_room_id,
+            }),
+        )
+        .await?;
+    let task_id = task.id.unwrap();
+
+    let task_store = Arc::clone(state.tasks.store());
+    let input = state.recording_uploads.object_path(&recording);
+    let extension = recording_clip::extension(&recording.file.content_type).to_string();
+    let user_id = auth.user_id;
+    let task_state = state.clone();
+
+    state.tasks.spawn_task(task_id, async move {
+        let state = task_state;
+        let (start, end) = (body.start_secs, body.end_secs);
+        let output = std::env::temp_dir().join(format!("clip-{}.{}", task_id.to_hex(), extension));
+        let cut = recording_clip::cut(
+            &settings.ffmpeg_path,
+            &input,
+            &output,
+            start,
+            end,
+            Duration::from_secs(settings.timeout_secs),
+        )
+        .await;
+        if let Err(e) = cut {
+            let _ = tokio::fs::remove_file(&output).await;
+            return Err(e);
+        }
+        task_store
+            .update_progress(task_id, 60, Some("Clip cut".to_string()))
+            .await
+            .map_err(|e| format!("Failed to update progress: {}", e))?;
+
+        let filename = format!(
+            "recording-{}-{}-{}.{}",
+            recording.id.unwrap().to_hex(),
+            start as u64,
+            end as u64,
+            extension
+        );
+        let stored = store_clip(&state, &recording, user_id, &output, &filename).await;
+        let _ = tokio::fs::remove_file(&output).await;
+        let file = stored?;
+        let file_id = file.id.unwrap();
+        state
+            .files
+            .link_to_recording(
+                tid,
+                file_id,
+                recording.id.unwrap(),
+                (end - start).ceil() as u32,
+            )
+            .await
+            .map_err(|e| format!("Failed to link clip: {}", e))?;
+        task_store
+            .update_progress(
+                task_id,
+                80,
+                Some(format!("Saved clip as file {}", file_id.to_hex())),
+            )
+            .await
+            .map_err(|e| format!("Failed to update progress: {}", e))?;
+
+        if let Some(room_id) = post_to {
+            post_clip(&state, &recording, room_id, user_id, &file, &body).await?;
+        }
+
+        task_store
+            .complete(
+                task_id,
+                Some(file.storage_key),
+                Some(filename),
+                Some(file.storage_provider),
+            )
+            .await
+            .map_err(|e| format!("Failed to complete task: {}", e))?;
+        Ok(())
+    });
+
+    let task_url = format!("/api/tenant/{}/task/{}", tid.to_hex(), task_id.to_hex());
+    Ok((
+        StatusCode::ACCEPTED,
+        [("Location", task_url.clone())],
+        Json(serde_json::json!({
+            "task_id": task_id.to_hex(),
+            "status": "pending",
+            "task_url": task_url,
+            "download_url": format!("{}/download", task_url),
+        })),
+    )
+        .into_response())
+}
+
+/// Upload the cut clip as a room file of the recording's room.
+async fn store_clip(
+    state: &AppState,
+    recording: &Recording,
+    user_id: ObjectId,
+    path: &std::path::Path,
+    filename: &str,
+) -> Result<roomler_ai_db::models::File, String> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to read clip: {}", e))?;
+    let body = futures::stream::try_unfold(file, |mut file| async move {
+        let mut buf = vec![0; 64 * 1024];
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.truncate(n);
+        Ok::<_, std::io::Error>(Some((buf, file)))
+    })
+    .boxed();
+    let uploaded = super::file::do_upload(
+        state,
+        recording.tenant_id,
+        recording.room_id,
+        user_id,
+        filename.to_string(),
+        recording.file.content_type.clone(),
+        body,
+    )
+    .await
+    .map_err(|e| format!("Failed to store clip: {}", e))?;
+    let file_id =
+        ObjectId::parse_str(&uploaded.id).map_err(|_| "Stored clip has no id".to_string())?;
+    state
+        .files
+        .base
+        .find_by_id(file_id)
+        .await
+        .map_err(|e| format!("Failed to load clip: {}", e))
+}
+
+/// Post the clip to `room_id` with what was said during it.
+async fn post_clip(
+    state: &AppState,
+    recording: &Recording,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    file: &roomler_ai_db::models::File,
+    body: &CreateClipRequest,
+) -> Result<(), String> {
+    let tid = recording.tenant_id;
+    let (from, to) =
+        recording_clip::transcript_window(recording.started_at, body.start_secs, body.end_secs);
+    let segments = state
+        .transcripts
+        .find_between(tid, recording.room_id, "original", from, to, 500)
+        .await
+        .map_err(|e| format!("Failed to load transcript: {}", e))?;
+    let excerpt = recording_clip::transcript_excerpt(&segments);
+    let content = recording_clip::message(
+        body.start_secs,
+        body.end_secs,
+        body.comment.as_deref(),
+        excerpt.as_deref(),
+    );
+
+    let message = crate::profanity::post(state, tid, room_id, user_id, content, |content| {
+        state.messages.create_with_attachments(
+            tid,
+            room_id,
+            user_id,
+            content,
+            None,
+            None,
+            None,
+            None,
+            vec![MessageAttachment {
+                file_id: file.id.unwrap(),
+                filename: file.filename.clone(),
+                content_type: file.content_type.clone(),
+                size: file.size,
+                url: file.url.clone(),
+                thumbnail_url: None,
+                is_spoiler: false,
+            }],
+            Vec::new(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to post clip: {}", e))?;
+
+    let names: HashMap<ObjectId, String> = state
+        .users
+        .find_display_names(&[user_id])
+        .await
+        .unwrap_or_default();
+    let response = super::message::to_response(message, &names, None);
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .unwrap_or_default();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": &response,
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
+        &member_ids,
+        &event,
+    )
+    .await;
+    crate::webhooks::dispatch(
+        state,
+        tid,
+        webhook_events::MESSAGE_CREATE,
+        serde_json::to_value(&response).unwrap_or_default(),
+    );
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipResponse {
+    pub file_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+    pub duration: u32,
+    pub url: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// GET .../recording/{recording_id}/clip — newest first.
+pub async fn list_clips(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Json<Vec<ClipResponse>>, ApiError> {
+    let (recording, _) = find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    let clips = state
+        .files
+        .find_by_recording(recording.tenant_id, recording.id.unwrap())
+        .await?
+        .into_iter()
+        .map(|f| ClipResponse {
+            file_id: f.id.map(|id| id.to_hex()).unwrap_or_default(),
+            filename: f.filename,
+            content_type: f.content_type,
+            size: f.size,
+            duration: f.duration.unwrap_or_default(),
+            url: f.url,
+            created_by: f.uploaded_by.to_hex(),
+            created_at: f.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        })
+        .collect();
+    Ok(Json(clips))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareLinkResponse {
+    pub token: String,
+    pub url: String,
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordingAclResponse {
+    pub visibility: Visibility,
+    pub allow_download: bool,
+    pub participant_ids: Vec<String>,
+    pub user_ids: Vec<String>,
+    pub role_ids: Vec<String>,
+    pub share_links: Vec<ShareLinkResponse>,
+}
+
+/// GET .../recording/{recording_id}/access
+pub async fn get_access(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Json<RecordingAclResponse>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_manage(access)?;
+    Ok(Json(to_acl_response(&state, recording)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAccessRequest {
+    #[serde(default)]
+    pub user_ids: Vec<String>,
+    #[serde(default)]
+    pub role_ids: Vec<String>,
+    pub visibility: Option<Visibility>,
+    /// Whether viewers may download and stream; unchanged when omitted.
+    pub allow_download: Option<bool>,
+}
+
+/// PUT .../recording/{recording_id}/access — replace the explicit member
+/// and role grants, and optionally the visibility and `allow_download`.
+/// Call participants keep their default access.
+pub async fn update_access(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+    Json(body): Json<UpdateAccessRequest>,
+) -> Result<Json<RecordingAclResponse>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_manage(access)?;
+
+    let user_ids = parse_ids(&body.user_ids, "user_ids")?;
+    for user_id in &user_ids {
+        if !state
+            .tenants
+            .is_member(recording.tenant_id, *user_id)
+            .await?
+        {
+            return Err(ApiError::Validation(format!(
+                "User {} is not a member of this tenant",
+                user_id.to_hex()
+            )));
+        }
+    }
+    let role_ids = parse_ids(&body.role_ids, "role_ids")?;
+    let tenant_roles = state.roles.find_for_tenant(recording.tenant_id).await?;
+    if let Some(unknown) = role_ids
+        .iter()
+        .find(|id| !tenant_roles.iter().any(|r| r.id == Some(**id)))
+    {
+        return Err(ApiError::Validation(format!(
+            "Role {} does not exist in this tenant",
+            unknown.to_hex()
+        )));
+    }
+    let visibility = body.visibility.unwrap_or(recording.visibility.clone());
+    let allow_download = body.allow_download.unwrap_or(recording.allow_download);
+
+    let id = recording.id.unwrap();
+    state
+        .recordings
+        .set_access(id, &user_ids, &role_ids, &visibility, allow_download)
+        .await?;
+    let recording = state.recordings.base.find_by_id(id).await?;
+    Ok(Json(to_acl_response(&state, recording)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShareLinkRequest {
+    pub expires_in_secs: Option<u64>,
+}
+
+/// POST .../recording/{recording_id}/share — a link that streams the
+/// recording without signing in, until it expires or is revoked.
+pub async fn create_share_link(
     State(state): State<AppState>,
     auth: AuthUser,
-    Path((tenant_id, _room_id, recording_id)): Path<(String, String, String)>,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+    Json(body): Json<CreateShareLinkRequest>,
+) -> Result<Json<ShareLinkResponse>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_manage(access)?;
+
+    let ttl = body.expires_in_secs.unwrap_or(DEFAULT_SHARE_LINK_TTL_SECS);
+    if ttl == 0 || ttl > MAX_SHARE_LINK_TTL_SECS {
+        return Err(ApiError::Validation(format!(
+            "expires_in_secs must be between 1 and {}",
+            MAX_SHARE_LINK_TTL_SECS
+        )));
+    }
+    let now = bson::DateTime::now();
+    let link = RecordingShareLink {
+        token: nanoid::nanoid!(32),
+        created_by: auth.user_id,
+        expires_at: Some(bson::DateTime::from_millis(
+            now.timestamp_millis() + ttl as i64 * 1000,
+        )),
+        created_at: now,
+    };
+    state
+        .recordings
+        .add_share_link(recording.id.unwrap(), &link)
+        .await?;
+    Ok(Json(to_share_link_response(&state, &link)))
+}
+
+/// DELETE .../recording/{recording_id}/share/{token}
+pub async fn revoke_share_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id, token)): Path<(String, String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_manage(access)?;
+
+    if !state
+        .recordings
+        .remove_share_link(recording.id.unwrap(), &token)
+        .await?
+    {
+        return Err(ApiError::NotFound("Share link not found".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "revoked": true })))
+}
+
+/// GET /api/recording/shared/{token} — public metadata for a share link.
+pub async fn shared_info(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<RecordingResponse>, ApiError> {
+    let recording = find_shared(&state, &token).await?;
+    Ok(Json(to_response(recording)))
+}
+
+/// GET /api/recording/shared/{token}/stream — public playback for a share
+/// link, unless downloads are disabled.
+pub async fn shared_stream(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let recording = find_shared(&state, &token).await?;
+    require_download(&recording, RecordingAccess::View)?;
+    serve_file(&state, &recording, &headers, false).await
+}
+
+async fn find_shared(state: &AppState, token: &str) -> Result<Recording, ApiError> {
+    state
+        .recordings
+        .find_by_share_token(token)
+        .await
+        .map_err(|_| ApiError::NotFound("Share link not found or expired".to_string()))
+}
+
+/// The caller's standing among the room's recordings.
+async fn viewer(state: &AppState, room: &Room, user_id: ObjectId) -> Result<Viewer, ApiError> {
+    let member = state
+        .tenants
+        .find_member(room.tenant_id, user_id)
+        .await?
+        .ok_or_else(|| ApiError::Forbidden("Not a member".to_string()))?;
+    let permissions = state
+        .tenants
+        .get_member_permissions(room.tenant_id, user_id)
+        .await?;
+    let is_room_member = match room.id {
+        Some(rid) => state.rooms.is_member(rid, user_id).await?,
+        None => false,
+    };
+    Ok(Viewer {
+        user_id,
+        role_ids: member.role_ids,
+        permissions,
+        is_room_member,
+    })
+}
+
+/// Load a recording and the caller's access to it. Recordings the caller
+/// can't see are reported as missing rather than forbidden.
+async fn find_recording(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+    recording_id: &str,
+) -> Result<(Recording, RecordingAccess), ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
-    let rec_id = ObjectId::parse_str(&recording_id)
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let rec_id = ObjectId::parse_str(recording_id)
         .map_err(|_| ApiError::BadRequest("Invalid recording_id".to_string()))?;
 
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    state.recordings.soft_delete(tid, rec_id).await?;
+    let recording = state
+        .recordings
+        .base
+        .find_by_id_in_tenant(tid, rec_id)
+        .await?;
+    if recording.room_id != rid || recording.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Recording not found".to_string()));
+    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let viewer = viewer(state, &room, auth.user_id).await?;
+    match recording_access::access(&room, &recording, &viewer) {
+        RecordingAccess::None => Err(ApiError::NotFound("Recording not found".to_string())),
+        access => Ok((recording, access)),
+    }
+}
+
+/// The recording's creator or someone managing it.
+fn require_uploader(
+    recording: &Recording,
+    access: RecordingAccess,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if recording.created_by != Some(user_id) && access < RecordingAccess::Manage {
+        return Err(ApiError::Forbidden(
+            "Only whoever started the recording or an organizer can upload it".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Streaming hands over the whole file as surely as downloading does, so
+/// both need `allow_download` unless the caller manages the recording.
+fn require_download(recording: &Recording, access: RecordingAccess) -> Result<(), ApiError> {
+    if access < RecordingAccess::Manage && !recording.allow_download {
+        return Err(ApiError::Forbidden(
+            "Downloads are disabled for this recording".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn require_manage(access: RecordingAccess) -> Result<(), ApiError> {
+    if access < RecordingAccess::Manage {
+        return Err(ApiError::Forbidden(
+            "Only organizers can manage this recording".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_ids(ids: &[String], field: &str) -> Result<Vec<ObjectId>, ApiError> {
+    let mut parsed = Vec::with_capacity(ids.len());
+    for id in ids {
+        let id = ObjectId::parse_str(id)
+            .map_err(|_| ApiError::BadRequest(format!("Invalid id in {}", field)))?;
+        if !parsed.contains(&id) {
+            parsed.push(id);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Send the assembled recording, honouring a single `Range: bytes=` request.
+/// The body is streamed from disk. Recordings kept on S3 redirect to a
+/// short-lived pre-signed URL, where S3 serves ranges itself.
+async fn serve_file(
+    state: &AppState,
+    recording: &Recording,
+    headers: &HeaderMap,
+    attachment: bool,
+) -> Result<Response, ApiError> {
+    if matches!(recording.status, RecordingStatus::Processing) {
+        return Err(ApiError::Conflict(
+            "Recording is still being uploaded".to_string(),
+        ));
+    }
+    if let Some(url) = state.object_store.presign_get(
+        recording.file.storage_provider,
+        &recording.file.key,
+        Duration::from_secs(state.settings.recording_playback.url_ttl_secs.max(1)),
+    ) {
+        return Ok(Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(header::LOCATION, url)
+            .body(Body::empty())
+            .unwrap());
+    }
+    let path = state.recording_uploads.object_path(recording);
+    let mut file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|_| ApiError::NotFound("Recording file not found".to_string()))?;
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read recording: {}", e)))?
+        .len();
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| parse_range(v, len));
+    let (status, start, end) = match range {
+        None => (StatusCode::OK, 0, len.saturating_sub(1)),
+        Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(None) => {
+            return Ok(Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .body(Body::empty())
+                .unwrap());
+        }
+    };
+    let body_len = if len == 0 { 0 } else { end - start + 1 };
+    file.seek(std::io::SeekFrom::Start(start))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read recording: {}", e)))?;
+    let body = futures::stream::unfold(file.take(body_len), |mut reader| async move {
+        let mut chunk = vec![0; STREAM_CHUNK_SIZE];
+        match reader.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(n) => {
+                chunk.truncate(n);
+                Some((Ok::<_, std::io::Error>(chunk), reader))
+            }
+            Err(e) => Some((Err(e), reader)),
+        }
+    });
+
+    let disposition = if attachment { "attachment" } else { "inline" };
+    let extension = recording
+        .file
+        .content_type
+        .split('/')
+        .nth(1)
+        .unwrap_or("bin");
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, &recording.file.content_type)
+        .header(header::CONTENT_LENGTH, body_len)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "{}; filename=\"recording-{}.{}\"",
+                disposition,
+                recording.id.unwrap().to_hex(),
+                extension
+            ),
+        );
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, len),
+        );
+    }
+    Ok(response.body(Body::from_stream(body)).unwrap())
+}
+
+/// Parse a single `bytes=` range against a body of `len` bytes into an
+/// inclusive `(start, end)`. `None` means unsatisfiable.
+fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, recording_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (recording, access) =
+        find_recording(&state, &auth, &tenant_id, &room_id, &recording_id).await?;
+    require_manage(access)?;
+
+    state
+        .recordings
+        .soft_delete(recording.tenant_id, recording.id.unwrap())
+        .await?;
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
@@ -120,6 +1090,42 @@ fn to_response(r: roomler_ai_db::models::Recording) -> RecordingResponse {
         content_type: r.file.content_type,
         size: r.file.size,
         duration: r.file.duration,
+        part_count: r.manifest.map(|m| m.parts.len()).unwrap_or(0),
+        visibility: r.visibility,
+        allow_download: r.allow_download,
         created_at: r.created_at.try_to_rfc3339_string().unwrap_or_default(),
     }
 }
+
+fn to_share_link_response(state: &AppState, link: &RecordingShareLink) -> ShareLinkResponse {
+    ShareLinkResponse {
+        token: link.token.clone(),
+        url: format!(
+            "{}/api/recording/shared/{}/stream",
+            state.settings.oauth.base_url, link.token
+        ),
+        expires_at: link
+            .expires_at
+            .and_then(|at| at.try_to_rfc3339_string().ok()),
+        created_at: link.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+fn to_acl_response(state: &AppState, r: Recording) -> RecordingAclResponse {
+    let hex = |ids: &[ObjectId]| ids.iter().map(|id| id.to_hex()).collect();
+    let now = bson::DateTime::now();
+    RecordingAclResponse {
+        visibility: r.visibility,
+        allow_download: r.allow_download,
+        participant_ids: hex(&r.acl.participant_ids),
+        user_ids: hex(&r.acl.user_ids),
+        role_ids: hex(&r.acl.role_ids),
+        share_links: r
+            .acl
+            .share_links
+            .iter()
+            .filter(|l| !l.is_expired(now))
+            .map(|l| to_share_link_response(state, l))
+            .collect(),
+    }
+}
@@ -3,9 +3,17 @@ use axum::{
     extract::{Path, State},
 };
 use bson::oid::ObjectId;
+use roomler_ai_db::models::{actions, role::permissions};
 use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageRoles = RequirePermission<{ permissions::MANAGE_ROLES }>;
 
 #[derive(Debug, Serialize)]
 pub struct RoleResponse {
@@ -21,6 +29,12 @@ pub struct RoleResponse {
     pub is_mentionable: bool,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PermissionResponse {
+    pub name: &'static str,
+    pub bit: u64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateRoleRequest {
     pub name: String,
@@ -57,12 +71,13 @@ pub async fn list(
     Ok(Json(response))
 }
 
-pub async fn create(
+/// GET /api/tenant/{tenant_id}/role/permissions — the permissions a role
+/// can be given, in bit order.
+pub async fn list_permissions(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(tenant_id): Path<String>,
-    Json(body): Json<CreateRoleRequest>,
-) -> Result<Json<RoleResponse>, ApiError> {
+) -> Result<Json<Vec<PermissionResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
 
@@ -70,6 +85,26 @@ pub async fn create(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
+    Ok(Json(
+        permissions::NAMED
+            .iter()
+            .map(|&(name, bit)| PermissionResponse { name, bit })
+            .collect(),
+    ))
+}
+
+pub async fn create(
+    State(state): State<AppState>,
+    ManageRoles {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: ManageRoles,
+    client: ClientInfo,
+    Json(body): Json<CreateRoleRequest>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    require_grantable(caller, body.permissions.unwrap_or(0))?;
+
     let role = state
         .roles
         .create(
@@ -83,23 +118,77 @@ pub async fn create(
             body.position.unwrap_or(100),
         )
         .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROLE_CREATE,
+        role.id,
+        vec![
+            audit::change("name", None, Some(role.name.clone().into())),
+            audit::change("permissions", None, Some(role.permissions.into())),
+        ],
+    )
+    .await;
 
     Ok(Json(to_response(role)))
 }
 
 pub async fn update(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, role_id)): Path<(String, String)>,
+    ManageRoles {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: ManageRoles,
+    client: ClientInfo,
+    Path((_, role_id)): Path<(String, String)>,
     Json(body): Json<UpdateRoleRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&role_id)
         .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
+    let before = state.roles.base.find_by_id_in_tenant(tid, rid).await?;
+    require_grantable(caller, before.permissions)?;
+    if let Some(perms) = body.permissions {
+        require_grantable(caller, perms)?;
+    }
+    let mut changes = Vec::new();
+    if let Some(name) = &body.name {
+        changes.push(audit::change(
+            "name",
+            Some(before.name.into()),
+            Some(name.clone().into()),
+        ));
+    }
+    if let Some(description) = &body.description {
+        changes.push(audit::change(
+            "description",
+            before.description.map(Into::into),
+            Some(description.clone().into()),
+        ));
+    }
+    if let Some(color) = body.color {
+        changes.push(audit::change(
+            "color",
+            before.color.map(Into::into),
+            Some(color.into()),
+        ));
+    }
+    if let Some(perms) = body.permissions {
+        changes.push(audit::change(
+            "permissions",
+            Some(before.permissions.into()),
+            Some(perms.into()),
+        ));
+    }
+    if let Some(position) = body.position {
+        changes.push(audit::change(
+            "position",
+            Some(before.position.into()),
+            Some(position.into()),
+        ));
     }
 
     state
@@ -114,71 +203,158 @@ pub async fn update(
             body.position,
         )
         .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROLE_UPDATE,
+        Some(rid),
+        changes,
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "updated": true })))
 }
 
 pub async fn delete(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, role_id)): Path<(String, String)>,
+    ManageRoles {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: ManageRoles,
+    client: ClientInfo,
+    Path((_, role_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&role_id)
         .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
-
+    let role = state.roles.base.find_by_id_in_tenant(tid, rid).await?;
+    require_grantable(caller, role.permissions)?;
     state.roles.delete(rid, tid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROLE_DELETE,
+        Some(rid),
+        vec![audit::change("name", Some(role.name.into()), None)],
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
 pub async fn assign(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, role_id, user_id)): Path<(String, String, String)>,
+    ManageRoles {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: ManageRoles,
+    client: ClientInfo,
+    Path((_, role_id, user_id)): Path<(String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&role_id)
         .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
     let uid = ObjectId::parse_str(&user_id)
         .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    let role = state.roles.base.find_by_id_in_tenant(tid, rid).await?;
+    require_grantable(caller, role.permissions)?;
 
     state.tenants.assign_role(tid, uid, rid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::MEMBER_ROLE_ASSIGN,
+        Some(uid),
+        vec![audit::change("role_id", None, Some(rid.to_hex().into()))],
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "assigned": true })))
 }
 
 pub async fn unassign(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, role_id, user_id)): Path<(String, String, String)>,
+    ManageRoles {
+        auth,
+        tenant_id: tid,
+        permissions: caller,
+    }: ManageRoles,
+    client: ClientInfo,
+    Path((_, role_id, user_id)): Path<(String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&role_id)
         .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
     let uid = ObjectId::parse_str(&user_id)
         .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    let role = state.roles.base.find_by_id_in_tenant(tid, rid).await?;
+    require_grantable(caller, role.permissions)?;
 
     state.tenants.remove_role(tid, uid, rid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::MEMBER_ROLE_UNASSIGN,
+        Some(uid),
+        vec![audit::change("role_id", Some(rid.to_hex().into()), None)],
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "removed": true })))
 }
 
+/// Whether a caller with `caller` can hand out `perms`: only what they hold
+/// themselves, so MANAGE_ROLES and INVITE_MEMBERS can't be used to
+/// escalate. ADMINISTRATOR holds everything.
+pub(crate) fn grantable(caller: u64, perms: u64) -> bool {
+    caller & permissions::ADMINISTRATOR != 0 || perms & !caller == 0
+}
+
+fn require_grantable(caller: u64, perms: u64) -> Result<(), ApiError> {
+    if perms & !permissions::ALL != 0 {
+        return Err(ApiError::Validation(format!(
+            "Unknown permission bits: {:#x}",
+            perms & !permissions::ALL
+        )));
+    }
+    if !grantable(caller, perms) {
+        return Err(ApiError::Forbidden(format!(
+            "Can't grant permissions you don't have: {}",
+            permissions::names(perms & !caller).join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Fail with 403 unless a caller with `caller` can give out all of
+/// `role_ids`, e.g. on an invite.
+pub(crate) async fn require_assignable(
+    state: &AppState,
+    tenant_id: ObjectId,
+    caller: u64,
+    role_ids: &[ObjectId],
+) -> Result<(), ApiError> {
+    for &role_id in role_ids {
+        let role = state
+            .roles
+            .base
+            .find_by_id_in_tenant(tenant_id, role_id)
+            .await?;
+        require_grantable(caller, role.permissions)?;
+    }
+    Ok(())
+}
+
 fn to_response(r: roomler_ai_db::models::Role) -> RoleResponse {
     RoleResponse {
         id: r.id.unwrap().to_hex(),
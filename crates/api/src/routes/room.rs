@@ -1,13 +1,31 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
 };
-use bson::oid::ObjectId;
+use bson::{doc, oid::ObjectId};
+use chrono::{SecondsFormat, Utc};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::MediaSettings;
-use roomler_ai_services::dao::base::PaginationParams;
+use crate::{
+    audit,
+    call_controls::CallControls,
+    conference_limits::Admission,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo, permission::RequirePermission},
+    profanity,
+    state::AppState,
+};
+use roomler_ai_db::models::{
+    CallChatMessage, ChannelAction, ChannelRole, ConferenceEventType, MediaSettings,
+    OnboardingStep, Presence, ReadOnlyWindow, Room, RoomType, TranscriptStatus, VoiceNote, actions,
+    role::permissions, webhook_events,
+};
+use roomler_ai_services::{
+    dao::{base::PaginationParams, room::MemberFilter},
+    media::captions,
+    read_only_schedule,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct CreateRoomRequest {
@@ -24,46 +42,76 @@ pub struct RoomResponse {
     pub name: String,
     pub path: String,
     pub parent_id: Option<String>,
+    pub room_type: RoomType,
     pub is_open: bool,
+    pub is_read_only: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub read_only_windows: Vec<ReadOnlyWindow>,
+    /// The scheduled window in effect right now, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_read_only_window: Option<ActiveReadOnlyWindowResponse>,
+    /// Whether the caller may post right now; only on room GET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_post: Option<bool>,
     pub member_count: u32,
     pub message_count: u64,
     pub has_media: bool,
     pub conference_status: Option<String>,
     pub meeting_code: Option<String>,
     pub participant_count: u32,
+    pub keep_conference_chat: bool,
+    /// Caller's unread top-level messages; only in listings, and only for
+    /// rooms they have joined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActiveReadOnlyWindowResponse {
+    pub ends_at: String,
+    pub min_role: ChannelRole,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 pub async fn list(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::VIEW_CHANNELS }>,
 ) -> Result<Json<Vec<RoomResponse>>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
-
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
-
     let rooms = state.rooms.find_by_tenant(tid).await?;
-    let response: Vec<RoomResponse> = rooms.into_iter().map(to_response).collect();
+    let room_ids: Vec<ObjectId> = rooms.iter().filter_map(|r| r.id).collect();
+    let unread = state
+        .read_states
+        .unread_counts(auth.user_id, &room_ids)
+        .await?;
+    let response: Vec<RoomResponse> = rooms
+        .into_iter()
+        .map(|r| {
+            let unread_count = r.id.and_then(|id| unread.get(&id).copied());
+            RoomResponse {
+                unread_count,
+                ..to_response(r)
+            }
+        })
+        .collect();
 
     Ok(Json(response))
 }
 
 pub async fn create(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(tenant_id): Path<String>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::SEND_MESSAGES }>,
+    client: ClientInfo,
     Json(body): Json<CreateRoomRequest>,
 ) -> Result<Json<RoomResponse>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
-
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
-
     let parent_id = body
         .parent_id
         .as_ref()
@@ -83,21 +131,53 @@ pub async fn create(
             None,
         )
         .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROOM_CREATE,
+        room.id,
+        vec![audit::change("name", None, Some(room.name.clone().into()))],
+    )
+    .await;
+    crate::billing_events::check_limits(&state, tid).await;
 
     Ok(Json(to_response(room)))
 }
 
 pub async fn join(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, room_id)): Path<(String, String)>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::VIEW_CHANNELS }>,
+    Path((_, room_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.is_dm() {
+        return Err(ApiError::Forbidden(
+            "Direct messages can't be joined".to_string(),
+        ));
+    }
 
     state.rooms.join(tid, rid, auth.user_id).await?;
+    state
+        .onboarding
+        .complete(tid, auth.user_id, OnboardingStep::JoinedChannel)
+        .await;
+    crate::webhooks::dispatch(
+        &state,
+        tid,
+        webhook_events::MEMBER_JOIN,
+        serde_json::json!({
+            "room_id": rid.to_hex(),
+            "user_id": auth.user_id.to_hex(),
+        }),
+    );
 
     Ok(Json(serde_json::json!({ "joined": true })))
 }
@@ -112,6 +192,25 @@ pub async fn leave(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
+    // A direct message is keyed by its participant set, which stays fixed.
+    if state
+        .rooms
+        .base
+        .find_by_id_in_tenant(tid, rid)
+        .await?
+        .is_dm()
+    {
+        return Err(ApiError::Forbidden(
+            "Direct messages can't be left".to_string(),
+        ));
+    }
+    // Leaving would drop the membership document and with it the demotion.
+    if state.rooms.channel_role(rid, auth.user_id).await? == Some(ChannelRole::Guest) {
+        return Err(ApiError::Forbidden(
+            "Guests can't leave until a moderator restores their role".to_string(),
+        ));
+    }
+
     state.rooms.leave(tid, rid, auth.user_id).await?;
 
     Ok(Json(serde_json::json!({ "left": true })))
@@ -119,21 +218,25 @@ pub async fn leave(
 
 pub async fn get(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, room_id)): Path<(String, String)>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::VIEW_CHANNELS }>,
+    Path((_, room_id)): Path<(String, String)>,
 ) -> Result<Json<RoomResponse>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
-
-    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let room = visible_room(&state, tid, rid, auth.user_id).await?;
+    let role = effective_channel_role(&state, tid, &room, auth.user_id).await?;
+    let can_post =
+        role.allows(posting_action(&room)) && require_outside_read_only_window(&room, role).is_ok();
 
-    Ok(Json(to_response(room)))
+    Ok(Json(RoomResponse {
+        can_post: Some(can_post),
+        ..to_response(room)
+    }))
 }
 
 #[derive(Debug, Deserialize)]
@@ -144,6 +247,8 @@ pub struct UpdateRoomRequest {
     pub is_open: Option<bool>,
     pub is_archived: Option<bool>,
     pub is_read_only: Option<bool>,
+    /// Replaces the room's scheduled read-only windows; `[]` clears them.
+    pub read_only_windows: Option<Vec<ReadOnlyWindow>>,
 }
 
 pub async fn update(
@@ -160,7 +265,21 @@ pub async fn update(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
-
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let role =
+        require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::UpdateRoom).await?;
+
+    if let Some(windows) = &body.read_only_windows {
+        read_only_schedule::validate(windows).map_err(ApiError::Validation)?;
+        // Nobody can schedule a window that would silence them.
+        if windows.iter().any(|w| w.min_role > role) {
+            return Err(ApiError::Forbidden(format!(
+                "Windows can require at most your own {} role",
+                role.as_str()
+            )));
+        }
+        state.rooms.set_read_only_windows(tid, rid, windows).await?;
+    }
     state
         .rooms
         .update(
@@ -181,6 +300,7 @@ pub async fn update(
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
+    client: ClientInfo,
     Path((tenant_id, room_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -191,28 +311,102 @@ pub async fn delete(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    require_channel_action(&state, tid, &room, auth.user_id, ChannelAction::DeleteRoom).await?;
 
     state.rooms.cascade_delete(tid, rid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::ROOM_DELETE,
+        Some(rid),
+        vec![audit::change("name", Some(room.name.into()), None)],
+    )
+    .await;
+    crate::billing_events::check_limits(&state, tid).await;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MemberListQuery {
+    /// Comma-separated channel roles, e.g. `moderator,owner`.
+    pub role: Option<String>,
+    pub status: Option<Presence>,
+    pub q: Option<String>,
+    /// Return only `{ "total" }`.
+    #[serde(default)]
+    pub count_only: bool,
+}
+
+impl MemberListQuery {
+    fn filter(self) -> Result<MemberFilter, ApiError> {
+        let roles = self
+            .role
+            .iter()
+            .flat_map(|r| r.split(','))
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                [
+                    ChannelRole::Guest,
+                    ChannelRole::Member,
+                    ChannelRole::Moderator,
+                    ChannelRole::Owner,
+                ]
+                .into_iter()
+                .find(|role| role.as_str() == r)
+                .ok_or_else(|| ApiError::Validation(format!("Unknown channel role: {r}")))
+            })
+            .collect::<Result<_, _>>()?;
+        if self.status == Some(Presence::Invisible) {
+            return Err(ApiError::Validation(
+                "status must be online, idle, dnd or offline".to_string(),
+            ));
+        }
+        let search = self
+            .q
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty());
+        if search.as_ref().is_some_and(|q| q.chars().count() > 100) {
+            return Err(ApiError::Validation(
+                "q must be at most 100 characters".to_string(),
+            ));
+        }
+        Ok(MemberFilter {
+            roles,
+            presence: self.status,
+            search,
+        })
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/member — a page of members,
+/// optionally filtered by channel role, presence and name.
 pub async fn members(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, room_id)): Path<(String, String)>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::VIEW_CHANNELS }>,
+    Path((_, room_id)): Path<(String, String)>,
     Query(params): Query<PaginationParams>,
+    Query(query): Query<MemberListQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
+    let count_only = query.count_only;
+    let filter = query.filter()?;
+    if count_only {
+        let total = state.rooms.count_members(rid, &filter).await?;
+        return Ok(Json(serde_json::json!({ "total": total })));
     }
-
-    let result = state.rooms.list_members(rid, &params).await?;
+    let result = state.rooms.list_members(rid, &filter, &params).await?;
 
     // Batch-fetch user details (username, avatar) for member user IDs
     let user_ids: Vec<ObjectId> = result.items.iter().filter_map(|m| m.user_id).collect();
@@ -250,6 +444,7 @@ pub async fn members(
                 "joined_at": m.joined_at.try_to_rfc3339_string().unwrap_or_default(),
                 "unread_count": m.unread_count,
                 "is_muted": m.is_muted,
+                "channel_role": m.channel_role,
             })
         })
         .collect();
@@ -264,23 +459,240 @@ pub async fn members(
 }
 
 #[derive(Debug, Deserialize)]
-pub struct ExploreQuery {
-    pub q: String,
+pub struct GrantRoleRequest {
+    pub role: ChannelRole,
 }
 
-pub async fn explore(
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/member/{user_id}/role
+pub async fn grant_role(
     State(state): State<AppState>,
     auth: AuthUser,
-    Path(tenant_id): Path<String>,
-    Query(query): Query<ExploreQuery>,
-) -> Result<Json<Vec<RoomResponse>>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+    Json(body): Json<GrantRoleRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_member_role(&state, &auth, &tenant_id, &room_id, &user_id, body.role).await
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/member/{user_id}/role —
+/// back to a plain member.
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_member_role(
+        &state,
+        &auth,
+        &tenant_id,
+        &room_id,
+        &user_id,
+        ChannelRole::Member,
+    )
+    .await
+}
+
+async fn set_member_role(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: &str,
+    role: ChannelRole,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let uid = ObjectId::parse_str(user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
 
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let actor_role =
+        require_channel_action(state, tid, &room, auth.user_id, ChannelAction::ManageRoles).await?;
+
+    if uid == room.creator_id {
+        return Err(ApiError::Forbidden(
+            "The room creator is always an owner".to_string(),
+        ));
+    }
+    let current = state
+        .rooms
+        .channel_role(rid, uid)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User is not a member of this room".to_string()))?;
+    if !actor_role.can_assign(current, role) {
+        return Err(ApiError::Forbidden(format!(
+            "A {} can't change a {} to {}",
+            actor_role.as_str(),
+            current.as_str(),
+            role.as_str()
+        )));
+    }
+
+    state.rooms.set_channel_role(rid, uid, role).await?;
+
+    let member_ids = state.rooms.find_member_user_ids(rid).await?;
+    let event = serde_json::json!({
+        "type": "room:member_role",
+        "data": {
+            "room_id": room_id,
+            "user_id": user_id,
+            "channel_role": role,
+        }
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
+        &member_ids,
+        &event,
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({
+        "user_id": user_id,
+        "channel_role": role,
+    })))
+}
+
+/// Loads a room of the tenant, failing with 403 when it is a direct message
+/// the caller doesn't take part in. Every other room is visible to all
+/// tenant members.
+pub(crate) async fn visible_room(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<Room, ApiError> {
+    let room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(tenant_id, room_id)
+        .await?;
+    require_dm_participant(state, &room, user_id).await?;
+    Ok(room)
+}
+
+async fn require_dm_participant(
+    state: &AppState,
+    room: &Room,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !room.is_dm() {
+        return Ok(());
+    }
+    let is_participant = match room.id {
+        Some(room_id) => state.rooms.is_member(room_id, user_id).await?,
+        None => false,
+    };
+    if !is_participant {
+        return Err(ApiError::Forbidden(
+            "Not a participant in this conversation".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The caller's effective role in `room`. Tenant members with
+/// MANAGE_CHANNELS and the room's creator act as owners; tenant members who
+/// haven't joined act as members, as before channel roles existed. Direct
+/// message participants are all plain members and nobody else has a role.
+pub(crate) async fn effective_channel_role(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room: &Room,
+    user_id: ObjectId,
+) -> Result<ChannelRole, ApiError> {
+    if room.is_dm() {
+        require_dm_participant(state, room, user_id).await?;
+        return Ok(ChannelRole::Member);
+    }
+    if room.creator_id == user_id {
+        return Ok(ChannelRole::Owner);
+    }
+    let perms = state
+        .tenants
+        .get_member_permissions(tenant_id, user_id)
+        .await?;
+    if permissions::has(perms, permissions::MANAGE_CHANNELS) {
+        return Ok(ChannelRole::Owner);
+    }
+    let Some(room_id) = room.id else {
+        return Ok(ChannelRole::Member);
+    };
+    Ok(state
+        .rooms
+        .channel_role(room_id, user_id)
+        .await?
+        .unwrap_or_default())
+}
+
+/// Fail with 403 unless the caller's channel role allows `action`.
+pub(crate) async fn require_channel_action(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room: &Room,
+    user_id: ObjectId,
+    action: ChannelAction,
+) -> Result<ChannelRole, ApiError> {
+    let role = effective_channel_role(state, tenant_id, room, user_id).await?;
+    if !role.allows(action) {
+        return Err(ApiError::Forbidden(format!(
+            "Requires the {} channel role",
+            action.min_role().as_str()
+        )));
+    }
+    Ok(role)
+}
+
+/// What posting in `room` requires outside scheduled windows.
+pub(crate) fn posting_action(room: &Room) -> ChannelAction {
+    if room.is_read_only {
+        ChannelAction::PostInReadOnly
+    } else {
+        ChannelAction::PostMessages
+    }
+}
+
+/// Fail with 403 while a scheduled read-only window keeps `role` from
+/// posting, saying why and until when.
+pub(crate) fn require_outside_read_only_window(
+    room: &Room,
+    role: ChannelRole,
+) -> Result<(), ApiError> {
+    let Some(active) = read_only_schedule::active_window(&room.read_only_windows, Utc::now())
+    else {
+        return Ok(());
+    };
+    if role >= active.window.min_role {
+        return Ok(());
+    }
+    Err(ApiError::Forbidden(format!(
+        "{} until {}: only the {} channel role and above can post",
+        active
+            .window
+            .note
+            .as_deref()
+            .unwrap_or("This room is read-only"),
+        active.ends_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        active.window.min_role.as_str()
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExploreQuery {
+    pub q: String,
+}
 
+pub async fn explore(
+    State(state): State<AppState>,
+    RequirePermission { tenant_id: tid, .. }: RequirePermission<{ permissions::VIEW_CHANNELS }>,
+    Query(query): Query<ExploreQuery>,
+) -> Result<Json<Vec<RoomResponse>>, ApiError> {
     let rooms = state.rooms.explore(tid, &query.q).await?;
     let response: Vec<RoomResponse> = rooms.into_iter().map(to_response).collect();
 
@@ -291,17 +703,16 @@ pub async fn explore(
 
 pub async fn call_start(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, room_id)): Path<(String, String)>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::CONNECT_VOICE }>,
+    Path((_, room_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
-
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    visible_room(&state, tid, rid, auth.user_id).await?;
 
     state.rooms.start_call(rid).await?;
     let rtp_capabilities = state
@@ -309,6 +720,18 @@ pub async fn call_start(
         .create_room(rid)
         .await
         .map_err(|e| ApiError::Internal(format!("Failed to create media room: {}", e)))?;
+    crate::conference_events::record(
+        &state,
+        rid,
+        ConferenceEventType::CallStarted,
+        Some(auth.user_id),
+        doc! {},
+    )
+    .await;
+    state
+        .onboarding
+        .complete(tid, auth.user_id, OnboardingStep::StartedCall)
+        .await;
 
     // Notify all room members about the call
     let member_ids = state
@@ -354,7 +777,7 @@ pub async fn call_start(
             &member_ids,
             &room_name,
             &caller_name,
-            &tenant_id,
+            &tid.to_hex(),
             &room_id,
         )
         .await;
@@ -368,16 +791,42 @@ pub async fn call_start(
 
 pub async fn call_join(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((tenant_id, room_id)): Path<(String, String)>,
+    RequirePermission {
+        auth,
+        tenant_id: tid,
+        ..
+    }: RequirePermission<{ permissions::CONNECT_VOICE }>,
+    Path((_, room_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let tid = ObjectId::parse_str(&tenant_id)
-        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
-
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
+    let room = visible_room(&state, tid, rid, auth.user_id).await?;
+
+    let in_call = state.rooms.is_active_participant(rid, auth.user_id).await?
+        || state
+            .room_manager
+            .get_participant_user_ids(&rid)
+            .contains(&auth.user_id);
+    if !in_call {
+        if !crate::conference_lobby::check(&state, &room, auth.user_id).await {
+            return Ok(Json(serde_json::json!({
+                "joined": false,
+                "status": "lobby",
+            })));
+        }
+        match crate::conference_limits::admit(&state, &room, auth.user_id).await? {
+            Admission::Joined => {}
+            Admission::Waitlisted { position } => {
+                return Ok(Json(serde_json::json!({
+                    "joined": false,
+                    "status": "waitlisted",
+                    "position": position,
+                })));
+            }
+            Admission::Full => {
+                return Err(ApiError::Conflict("Conference is full".to_string()));
+            }
+        }
     }
 
     let user = state.users.base.find_by_id(auth.user_id).await?;
@@ -386,6 +835,10 @@ pub async fn call_join(
         .rooms
         .join_participant(tid, rid, auth.user_id, user.display_name, "web".to_string())
         .await?;
+    state
+        .recordings
+        .add_participants(rid, &[auth.user_id])
+        .await?;
 
     // Notify room members about updated participant count
     let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
@@ -437,6 +890,7 @@ pub async fn call_leave(
     state
         .room_manager
         .close_participant_by_user(&rid, &auth.user_id);
+    crate::presence::broadcast_activity(&state, rid, &[auth.user_id]).await;
 
     // Broadcast peer_left to remaining participants
     let remaining = state.room_manager.get_participant_user_ids(&rid);
@@ -458,6 +912,18 @@ pub async fn call_leave(
     }
 
     state.rooms.leave_participant(rid, auth.user_id).await?;
+    if state.conference_waitlist.remove(&rid, &auth.user_id)
+        && let Ok(room) = state.rooms.base.find_by_id_in_tenant(tid, rid).await
+    {
+        // Gave up a place in the queue or an unclaimed slot
+        crate::conference_limits::waitlist_updated(&state, &room).await;
+    }
+    if state.conference_lobby.is_pending(&rid, &auth.user_id)
+        && let Ok(room) = state.rooms.base.find_by_id_in_tenant(tid, rid).await
+    {
+        crate::conference_lobby::cancel(&state, &room, auth.user_id).await;
+    }
+    crate::conference_limits::fill_open_slots(&state, rid).await;
 
     // Check if this was the last participant — if so, auto-end the call
     let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
@@ -467,6 +933,18 @@ pub async fn call_leave(
     {
         state.rooms.end_call(rid).await?;
         state.room_manager.remove_room(&rid);
+        state.conference_waitlist.clear(&rid);
+        state.conference_lobby.clear(&rid);
+        crate::conference_chat::discard_at_call_end(&state, room).await;
+        crate::conference_polls::close_at_call_end(&state, rid).await;
+        crate::conference_events::record(
+            &state,
+            rid,
+            ConferenceEventType::CallEnded,
+            None,
+            doc! { "reason": "last_participant_left" },
+        )
+        .await;
 
         // Notify all room members that the call has ended
         let member_ids = state
@@ -490,6 +968,9 @@ pub async fn call_leave(
             .await;
         }
     }
+    // Sessions of dropped connections can keep the count up after the
+    // media room emptied.
+    crate::conference_reaper::schedule(&state, rid);
 
     Ok(Json(serde_json::json!({ "left": true })))
 }
@@ -508,10 +989,25 @@ pub async fn call_end(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    // Collect participants before the media room (and its state) is dropped.
+    let remaining = state.room_manager.get_participant_user_ids(&rid);
     state.rooms.end_call(rid).await?;
     state.room_manager.remove_room(&rid);
+    state.conference_waitlist.clear(&rid);
+    state.conference_lobby.clear(&rid);
+    crate::presence::broadcast_activity(&state, rid, &remaining).await;
+    crate::conference_chat::discard_at_call_end(&state, &room).await;
+    crate::conference_polls::close_at_call_end(&state, rid).await;
+    crate::conference_events::record(
+        &state,
+        rid,
+        ConferenceEventType::CallEnded,
+        Some(auth.user_id),
+        doc! {},
+    )
+    .await;
 
-    let remaining = state.room_manager.get_participant_user_ids(&rid);
     if !remaining.is_empty() {
         let event = serde_json::json!({
             "type": "media:room_closed",
@@ -573,6 +1069,7 @@ pub async fn participants(
                 "id": p.id.unwrap().to_hex(),
                 "user_id": p.user_id.map(|u| u.to_hex()),
                 "display_name": p.display_name,
+                "is_external": p.is_external,
                 "role": p.role.as_ref().map(|r| format!("{:?}", r)),
                 "is_muted": p.is_muted,
                 "is_video_on": p.is_video_on,
@@ -585,6 +1082,163 @@ pub async fn participants(
     Ok(Json(items))
 }
 
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/waitlist — joiners queued
+/// for a full conference, head first. Organizers only.
+pub async fn call_waitlist(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<serde_json::Value>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can see the waitlist".to_string(),
+        ));
+    }
+
+    let waiting = state.conference_waitlist.waiting(&rid);
+    let names = state
+        .users
+        .find_display_names(&waiting)
+        .await
+        .unwrap_or_default();
+    let items = waiting
+        .iter()
+        .enumerate()
+        .map(|(i, user_id)| {
+            serde_json::json!({
+                "user_id": user_id.to_hex(),
+                "display_name": names.get(user_id),
+                "position": i + 1,
+            })
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/waitlist/{user_id}/admit
+/// — let a waiter in ahead of the queue, even past the participant cap.
+/// Organizers only.
+pub async fn admit_waiter(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let uid = ObjectId::parse_str(&user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can admit from the waitlist".to_string(),
+        ));
+    }
+    if state.conference_waitlist.position(&rid, &uid).is_none() {
+        return Err(ApiError::NotFound("User is not waiting".to_string()));
+    }
+
+    state.conference_waitlist.admit(rid, uid);
+    crate::conference_limits::notify_admitted(&state, &room, &[uid]).await;
+
+    Ok(Json(serde_json::json!({ "admitted": true })))
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/lobby — joiners waiting
+/// to be let in, in arrival order. Organizers only.
+pub async fn call_lobby(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<serde_json::Value>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can see the lobby".to_string(),
+        ));
+    }
+
+    let pending = state.conference_lobby.pending(&rid);
+    let names = crate::conference_lobby::display_names(&state, rid, &pending).await;
+    let items = pending
+        .iter()
+        .map(|user_id| {
+            serde_json::json!({
+                "user_id": user_id.to_hex(),
+                "display_name": names.get(user_id),
+            })
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/lobby/{user_id}/admit —
+/// let a waiting joiner in. Organizers only.
+pub async fn admit_from_lobby(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (room, uid) = lobby_target(&state, &auth, &tenant_id, &room_id, &user_id).await?;
+    if !crate::conference_lobby::admit(&state, &room, uid).await {
+        return Err(ApiError::NotFound("User is not in the lobby".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "admitted": true })))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/lobby/{user_id}/deny —
+/// turn a waiting joiner away. Organizers only.
+pub async fn deny_from_lobby(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (room, uid) = lobby_target(&state, &auth, &tenant_id, &room_id, &user_id).await?;
+    if !crate::conference_lobby::deny(&state, &room, uid).await {
+        return Err(ApiError::NotFound("User is not in the lobby".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "denied": true })))
+}
+
+/// The room and user of a lobby decision, checking the caller organizes it.
+async fn lobby_target(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: &str,
+) -> Result<(Room, ObjectId), ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let uid = ObjectId::parse_str(user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can admit or deny joiners".to_string(),
+        ));
+    }
+    Ok((room, uid))
+}
+
 // ── Call chat message endpoints ─────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -608,20 +1262,7 @@ pub async fn call_messages(
     }
 
     let result = state.rooms.find_chat_messages(rid, &params).await?;
-    let items: Vec<serde_json::Value> = result
-        .items
-        .iter()
-        .map(|m| {
-            serde_json::json!({
-                "id": m.id.unwrap().to_hex(),
-                "room_id": m.room_id.to_hex(),
-                "author_id": m.author_id.to_hex(),
-                "display_name": m.display_name,
-                "content": m.content,
-                "created_at": m.created_at.try_to_rfc3339_string().unwrap_or_default(),
-            })
-        })
-        .collect();
+    let items: Vec<serde_json::Value> = result.items.iter().map(call_message_json).collect();
 
     Ok(Json(serde_json::json!({
         "items": items,
@@ -632,6 +1273,319 @@ pub async fn call_messages(
     })))
 }
 
+pub(crate) async fn require_chat_enabled(
+    state: &AppState,
+    rid: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    let restrictions = crate::call_controls::restrictions(state, rid, user_id).await?;
+    if restrictions.is_some_and(|c| !c.chat_enabled) {
+        return Err(ApiError::Forbidden(
+            "Chat is disabled by the organizer".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCallSettingsRequest {
+    pub mute_on_entry: Option<bool>,
+    pub allow_unmute: Option<bool>,
+    pub chat_enabled: Option<bool>,
+    pub reactions_enabled: Option<bool>,
+    pub attendee_screen_share: Option<bool>,
+    pub waitlist_enabled: Option<bool>,
+    pub lobby_enabled: Option<bool>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/settings — the room's
+/// in-call controls.
+pub async fn call_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<CallControls>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    Ok(Json(CallControls::from(&room.conference())))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/call/settings — change the
+/// in-call controls; omitted fields keep their value. Organizers only.
+/// Participants are told with `room:call_settings_updated`. Turning the
+/// lobby off lets everyone waiting in.
+pub async fn update_call_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<UpdateCallSettingsRequest>,
+) -> Result<Json<CallControls>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can change call settings".to_string(),
+        ));
+    }
+
+    let mut settings = room.conference();
+    let lobby_was_enabled = settings.lobby_enabled;
+    let fields = [
+        (body.mute_on_entry, &mut settings.mute_on_entry),
+        (body.allow_unmute, &mut settings.allow_unmute),
+        (body.chat_enabled, &mut settings.chat_enabled),
+        (body.reactions_enabled, &mut settings.reactions_enabled),
+        (
+            body.attendee_screen_share,
+            &mut settings.attendee_screen_share,
+        ),
+        (body.waitlist_enabled, &mut settings.waitlist_enabled),
+        (body.lobby_enabled, &mut settings.lobby_enabled),
+    ];
+    for (value, field) in fields {
+        if let Some(value) = value {
+            *field = value;
+        }
+    }
+    state
+        .rooms
+        .set_conference_settings(tid, rid, &settings)
+        .await?;
+    if lobby_was_enabled && !settings.lobby_enabled {
+        crate::conference_lobby::open(&state, &room).await;
+    }
+
+    let controls = CallControls::from(&settings);
+    let participants = state.room_manager.get_participant_user_ids(&rid);
+    if !participants.is_empty() {
+        let event = serde_json::json!({
+            "type": "room:call_settings_updated",
+            "data": {
+                "room_id": rid.to_hex(),
+                "settings": &controls,
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &participants,
+            &event,
+        )
+        .await;
+    }
+
+    Ok(Json(controls))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeepCallMessagesRequest {
+    pub keep: bool,
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/call/message/keep — exempt the
+/// room's conference chat from the tenant's discard-at-call-end setting.
+/// Organizers only.
+pub async fn keep_call_messages(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<KeepCallMessagesRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if !room.organizer_ids().contains(&auth.user_id) {
+        return Err(ApiError::Forbidden(
+            "Only organizers can keep conference chat".to_string(),
+        ));
+    }
+
+    state
+        .rooms
+        .set_keep_conference_chat(tid, rid, body.keep)
+        .await?;
+
+    Ok(Json(
+        serde_json::json!({ "keep_conference_chat": body.keep }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConferenceEventQuery {
+    /// Only events after this event id: the `next_after` of the previous
+    /// page.
+    pub after: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/event — the room's
+/// call timeline, oldest first, for audit and integration replay.
+pub async fn call_events(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Query(query): Query<ConferenceEventQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let after = query
+        .after
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid after".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    visible_room(&state, tid, rid, auth.user_id).await?;
+
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let events = state
+        .conference_events
+        .find_after(tid, rid, after, limit)
+        .await?;
+    let next_after = (events.len() as i64 == limit)
+        .then(|| events.last().and_then(|e| e.id).map(|id| id.to_hex()))
+        .flatten();
+    let items: Vec<serde_json::Value> = events
+        .into_iter()
+        .map(|e| {
+            serde_json::json!({
+                "id": e.id.map(|id| id.to_hex()),
+                "type": e.event_type,
+                "user_id": e.user_id.map(|u| u.to_hex()),
+                "data": bson::Bson::Document(e.data).into_relaxed_extjson(),
+                "created_at": e.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "next_after": next_after,
+    })))
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/poll — the room's
+/// polls and quizzes, newest first. Results of open polls are left out for
+/// non-organizers unless the poll shares them.
+pub async fn call_polls(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<crate::conference_polls::PollResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = visible_room(&state, tid, rid, auth.user_id).await?;
+    let organizer = room.organizer_ids().contains(&auth.user_id);
+
+    let polls = state.conference_polls.find_for_room(rid).await?;
+    Ok(Json(
+        polls
+            .into_iter()
+            .map(|poll| crate::conference_polls::to_response(poll, organizer))
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallTranscriptQuery {
+    /// Caption track; defaults to `original`.
+    pub track: Option<String>,
+    /// Only segments after this segment id: the `next_after` of the
+    /// previous page.
+    pub after: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/transcript — the live
+/// transcript of the room's current or most recent call, oldest first.
+pub async fn call_transcript(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Query(query): Query<CallTranscriptQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let after = query
+        .after
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid after".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = visible_room(&state, tid, rid, auth.user_id).await?;
+    let track = match query.track.as_deref() {
+        Some(requested) => captions::resolve_track(requested, room.media_settings.as_ref())
+            .ok_or_else(|| ApiError::BadRequest("Caption track not available".to_string()))?,
+        None => captions::ORIGINAL_TRACK.to_string(),
+    };
+
+    let limit = query.limit.unwrap_or(500).clamp(1, 1000);
+    let segments = state
+        .transcripts
+        .find_after(tid, rid, &track, room.actual_start_time, after, limit)
+        .await?;
+    let next_after = (segments.len() as i64 == limit)
+        .then(|| segments.last().and_then(|s| s.id).map(|id| id.to_hex()))
+        .flatten();
+    let items: Vec<serde_json::Value> = segments
+        .into_iter()
+        .map(|s| {
+            serde_json::json!({
+                "id": s.id.map(|id| id.to_hex()),
+                "user_id": s.user_id.to_hex(),
+                "speaker_name": s.speaker_name,
+                "text": s.text,
+                "language": s.language,
+                "confidence": s.confidence,
+                "start_time": s.start_time,
+                "end_time": s.end_time,
+                "created_at": s.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "track": track,
+        "started_at": room
+            .actual_start_time
+            .and_then(|t| t.try_to_rfc3339_string().ok()),
+        "items": items,
+        "next_after": next_after,
+    })))
+}
+
 pub async fn create_call_message(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -646,6 +1600,156 @@ pub async fn create_call_message(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    require_chat_enabled(&state, rid, auth.user_id).await?;
+
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    let msg = profanity::post_call(&state, tid, rid, auth.user_id, body.content, |content| {
+        state.rooms.create_chat_message(
+            tid,
+            rid,
+            auth.user_id,
+            user.display_name.clone(),
+            content,
+            None,
+        )
+    })
+    .await?;
+
+    let response = call_message_json(&msg);
+    broadcast_call_message(&state, rid, &response).await;
+
+    Ok(Json(response))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/call/message/voice — a voice
+/// note for conference chat. Multipart fields: `file` (audio) and optional
+/// `duration_ms`. The audio is stored like any room file and transcribed
+/// before the message is broadcast.
+pub async fn create_call_voice_note(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    require_chat_enabled(&state, rid, auth.user_id).await?;
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    let max_bytes = state.settings.asr.max_voice_note_bytes;
+    let mut audio: Option<(String, String, Vec<u8>)> = None;
+    let mut duration_ms: Option<u64> = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Multipart error: {}", e)))?
+    {
+        match field.name() {
+            Some("file") => {
+                let filename = field.file_name().unwrap_or("voice-note").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                if !content_type.starts_with("audio/") {
+                    return Err(ApiError::BadRequest(
+                        "Voice notes must be audio".to_string(),
+                    ));
+                }
+                let mut bytes = Vec::new();
+                while let Some(chunk) = field
+                    .chunk()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {}", e)))?
+                {
+                    bytes.extend_from_slice(&chunk);
+                    if bytes.len() as u64 > max_bytes {
+                        return Err(ApiError::Validation(format!(
+                            "Voice notes are limited to {} bytes",
+                            max_bytes
+                        )));
+                    }
+                }
+                audio = Some((filename, content_type, bytes));
+            }
+            Some("duration_ms") => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Failed to read field: {}", e)))?;
+                duration_ms = Some(
+                    text.trim()
+                        .parse()
+                        .map_err(|_| ApiError::BadRequest("Invalid duration_ms".to_string()))?,
+                );
+            }
+            _ => {}
+        }
+    }
+    let (filename, content_type, bytes) =
+        audio.ok_or_else(|| ApiError::BadRequest("Missing 'file' field".to_string()))?;
+    if bytes.is_empty() {
+        return Err(ApiError::BadRequest("Empty voice note".to_string()));
+    }
+
+    let (transcript, transcript_language, transcript_status) = if state.transcription.is_available()
+    {
+        match state
+            .transcription
+            .transcribe(bytes.clone(), &filename, &content_type)
+            .await
+        {
+            Ok(t) => (Some(t.text), t.language, TranscriptStatus::Completed),
+            Err(e) => {
+                tracing::warn!(%rid, error = %e, "Voice note transcription failed");
+                (None, None, TranscriptStatus::Failed)
+            }
+        }
+    } else {
+        (None, None, TranscriptStatus::Unavailable)
+    };
+
+    // A transcript the filter blocks refuses the note before it is stored.
+    let screened = match transcript {
+        Some(text) => Some(profanity::screen(&state, tid, rid, auth.user_id, text).await?),
+        None => None,
+    };
+    let transcript = screened.as_ref().map(|screened| screened.content.clone());
+
+    let body = futures::stream::once({
+        let bytes = bytes.clone();
+        async move { Ok(bytes) }
+    })
+    .boxed();
+    let file = super::file::do_upload(
+        &state,
+        tid,
+        rid,
+        auth.user_id,
+        filename.clone(),
+        content_type.clone(),
+        body,
+    )
+    .await?;
+
+    let file_id = ObjectId::parse_str(&file.id)
+        .map_err(|_| ApiError::Internal("Stored file has no id".to_string()))?;
+    let voice_note = VoiceNote {
+        file_id,
+        url: file.url,
+        content_type,
+        size: file.size,
+        duration_ms,
+        transcript,
+        transcript_language,
+        transcript_status,
+    };
 
     let user = state.users.base.find_by_id(auth.user_id).await?;
     let msg = state
@@ -655,29 +1759,64 @@ pub async fn create_call_message(
             rid,
             auth.user_id,
             user.display_name.clone(),
-            body.content,
+            String::new(),
+            Some(voice_note),
         )
         .await?;
+    if let (Some(screened), Some(message_id)) = (&screened, msg.id) {
+        screened
+            .record(&state, tid, rid, auth.user_id, message_id)
+            .await;
+    }
 
-    let response = serde_json::json!({
-        "id": msg.id.unwrap().to_hex(),
-        "room_id": msg.room_id.to_hex(),
-        "author_id": msg.author_id.to_hex(),
-        "display_name": msg.display_name,
-        "content": msg.content,
-        "created_at": msg.created_at.try_to_rfc3339_string().unwrap_or_default(),
-    });
+    let response = call_message_json(&msg);
+    broadcast_call_message(&state, rid, &response).await;
 
-    // Broadcast to other room members via WS
-    let member_ids = state
+    Ok(Json(response))
+}
+
+pub(crate) fn call_message_json(m: &CallChatMessage) -> serde_json::Value {
+    serde_json::json!({
+        "id": m.id.map(|id| id.to_hex()).unwrap_or_default(),
+        "room_id": m.room_id.to_hex(),
+        "author_id": m.author_id.to_hex(),
+        "display_name": m.display_name,
+        "content": m.content,
+        "voice_note": m.voice_note.as_ref().map(|v| serde_json::json!({
+            "file_id": v.file_id.to_hex(),
+            "url": v.url,
+            "content_type": v.content_type,
+            "size": v.size,
+            "duration_ms": v.duration_ms,
+            "transcript": v.transcript,
+            "transcript_language": v.transcript_language,
+            "transcript_status": v.transcript_status,
+        })),
+        "created_at": m.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    })
+}
+
+/// Send `call:message:create` to the room's members and to whoever is in
+/// the call, conference guests included.
+pub(crate) async fn broadcast_call_message(
+    state: &AppState,
+    rid: ObjectId,
+    response: &serde_json::Value,
+) {
+    let mut member_ids = state
         .rooms
         .find_member_user_ids(rid)
         .await
         .unwrap_or_default();
+    for user_id in state.room_manager.get_participant_user_ids(&rid) {
+        if !member_ids.contains(&user_id) {
+            member_ids.push(user_id);
+        }
+    }
     if !member_ids.is_empty() {
         let event = serde_json::json!({
             "type": "call:message:create",
-            "data": &response,
+            "data": response,
         });
         crate::ws::dispatcher::broadcast_with_redis(
             &state.ws_storage,
@@ -687,8 +1826,6 @@ pub async fn create_call_message(
         )
         .await;
     }
-
-    Ok(Json(response))
 }
 
 fn to_response(r: roomler_ai_db::models::Room) -> RoomResponse {
@@ -707,12 +1844,27 @@ fn to_response(r: roomler_ai_db::models::Room) -> RoomResponse {
         name: r.name,
         path: r.path,
         parent_id: r.parent_id.map(|p| p.to_hex()),
+        room_type: r.room_type,
         is_open: r.is_open,
+        is_read_only: r.is_read_only,
+        active_read_only_window: read_only_schedule::active_window(
+            &r.read_only_windows,
+            Utc::now(),
+        )
+        .map(|active| ActiveReadOnlyWindowResponse {
+            ends_at: active.ends_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            min_role: active.window.min_role,
+            note: active.window.note.clone(),
+        }),
+        read_only_windows: r.read_only_windows,
+        can_post: None,
         member_count: r.member_count,
         message_count: r.message_count,
         has_media: r.media_settings.is_some(),
         conference_status: r.conference_status,
         meeting_code: r.meeting_code,
         participant_count: r.participant_count,
+        keep_conference_chat: r.keep_conference_chat,
+        unread_count: None,
     }
 }
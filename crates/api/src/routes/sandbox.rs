@@ -0,0 +1,63 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{actions, role::permissions};
+use roomler_ai_services::sandbox::{self, ResetReport};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo},
+    state::AppState,
+};
+
+/// POST /api/tenant/{tenant_id}/sandbox/reset — wipe a sandbox tenant's
+/// messages and files, keeping members, rooms and configuration.
+pub async fn reset(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<ResetReport>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    if !state
+        .sandbox_tenants
+        .is_sandbox(&state.tenants, tid)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Only sandbox tenants can be reset".to_string(),
+        ));
+    }
+
+    let report = sandbox::reset_tenant(&state.db, &state.object_store, tid).await?;
+
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::TENANT_SANDBOX_RESET,
+        Some(tid),
+        vec![
+            audit::change("messages", Some(report.messages.into()), None),
+            audit::change("files", Some(report.files.into()), None),
+        ],
+    )
+    .await;
+
+    Ok(Json(report))
+}
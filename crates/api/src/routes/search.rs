@@ -3,6 +3,7 @@ use axum::{
     extract::{Path, Query, State},
 };
 use bson::{doc, oid::ObjectId};
+use roomler_ai_services::media::captions;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -15,6 +16,8 @@ pub struct SearchQuery {
     pub q: String,
     #[serde(default = "default_limit")]
     pub limit: u64,
+    /// Search one channel: its messages and call transcripts only.
+    pub room_id: Option<String>,
 }
 
 fn default_limit() -> u64 {
@@ -32,6 +35,23 @@ pub struct SearchMessageResult {
     pub created_at: String,
 }
 
+/// A line said in a channel's call, linking to its conference page.
+#[derive(Serialize)]
+pub struct SearchTranscriptResult {
+    /// Always `transcript`, so clients can tell the hits apart.
+    pub result_type: &'static str,
+    pub id: String,
+    pub room_id: String,
+    pub room_name: String,
+    pub speaker_id: String,
+    pub speaker_name: String,
+    pub content_preview: String,
+    /// Seconds from the start of the speaker's audio stream.
+    pub start_time: f64,
+    pub created_at: String,
+    pub link: String,
+}
+
 #[derive(Serialize)]
 pub struct SearchRoomResult {
     pub id: String,
@@ -53,6 +73,7 @@ pub struct SearchUserResult {
 #[derive(Serialize)]
 pub struct SearchResults {
     pub messages: Vec<SearchMessageResult>,
+    pub transcripts: Vec<SearchTranscriptResult>,
     pub rooms: Vec<SearchRoomResult>,
     pub users: Vec<SearchUserResult>,
 }
@@ -74,6 +95,7 @@ pub async fn search(
     if q.is_empty() {
         return Ok(Json(SearchResults {
             messages: Vec::new(),
+            transcripts: Vec::new(),
             rooms: Vec::new(),
             users: Vec::new(),
         }));
@@ -81,22 +103,47 @@ pub async fn search(
 
     let limit = query.limit.min(50) as i64;
 
-    // Search messages in tenant
-    let msg_filter = doc! {
+    let scope = match query.room_id.as_deref() {
+        Some(room_id) => {
+            let rid = ObjectId::parse_str(room_id)
+                .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+            super::room::visible_room(&state, tid, rid, auth.user_id).await?;
+            Some(rid)
+        }
+        None => None,
+    };
+
+    // Messages and call transcripts have their own text indexes
+    let mut msg_filter = doc! {
         "tenant_id": tid,
         "deleted_at": null,
         "thread_id": null,
     };
-    let messages = state
-        .messages
-        .base
-        .text_search(q, msg_filter, limit)
-        .await
-        .unwrap_or_default();
+    let mut transcript_filter = doc! {
+        "tenant_id": tid,
+        "track": captions::ORIGINAL_TRACK,
+    };
+    if let Some(rid) = scope {
+        msg_filter.insert("room_id", rid);
+        transcript_filter.insert("room_id", rid);
+    }
+    let (messages, segments) = tokio::join!(
+        state.messages.base.text_search(q, msg_filter, limit),
+        state
+            .transcripts
+            .base
+            .text_search(q, transcript_filter, limit),
+    );
+    let messages = messages.unwrap_or_default();
+    let segments = segments.unwrap_or_default();
 
     // Collect author IDs and room IDs from messages for enrichment
     let author_ids: Vec<ObjectId> = messages.iter().map(|m| m.author_id).collect();
-    let msg_room_ids: Vec<ObjectId> = messages.iter().map(|m| m.room_id).collect();
+    let msg_room_ids: Vec<ObjectId> = messages
+        .iter()
+        .map(|m| m.room_id)
+        .chain(segments.iter().map(|s| s.room_id))
+        .collect();
 
     let author_names = state
         .users
@@ -107,8 +154,24 @@ pub async fn search(
     // Batch-fetch room names for message enrichment
     let room_name_map = fetch_room_names(&state, &msg_room_ids).await;
 
+    // Direct messages only show up for their participants
+    let dm_room_ids = fetch_dm_room_ids(&state, &msg_room_ids).await;
+    let my_dm_room_ids: Vec<ObjectId> = if dm_room_ids.is_empty() {
+        Vec::new()
+    } else {
+        state
+            .rooms
+            .find_user_dms(tid, auth.user_id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|r| r.id)
+            .collect()
+    };
+
     let message_results: Vec<SearchMessageResult> = messages
         .into_iter()
+        .filter(|m| !dm_room_ids.contains(&m.room_id) || my_dm_room_ids.contains(&m.room_id))
         .map(|m| {
             let room_name = room_name_map.get(&m.room_id).cloned().unwrap_or_default();
             let author_name = author_names
@@ -133,9 +196,45 @@ pub async fn search(
         })
         .collect();
 
+    let transcript_results: Vec<SearchTranscriptResult> = segments
+        .into_iter()
+        .filter(|s| !dm_room_ids.contains(&s.room_id) || my_dm_room_ids.contains(&s.room_id))
+        .map(|s| {
+            // Speech is often non-ASCII, so cut on a character boundary
+            let content_preview = if s.text.chars().count() > 200 {
+                format!("{}...", s.text.chars().take(200).collect::<String>())
+            } else {
+                s.text.clone()
+            };
+            SearchTranscriptResult {
+                result_type: "transcript",
+                id: s.id.unwrap().to_hex(),
+                room_id: s.room_id.to_hex(),
+                room_name: room_name_map.get(&s.room_id).cloned().unwrap_or_default(),
+                speaker_id: s.user_id.to_hex(),
+                speaker_name: s.speaker_name,
+                content_preview,
+                start_time: s.start_time,
+                created_at: s.created_at.try_to_rfc3339_string().unwrap_or_default(),
+                link: format!("/tenant/{}/room/{}/call", tenant_id, s.room_id.to_hex()),
+            }
+        })
+        .collect();
+
+    // A channel's search covers what was written and said in it
+    if scope.is_some() {
+        return Ok(Json(SearchResults {
+            messages: message_results,
+            transcripts: transcript_results,
+            rooms: Vec::new(),
+            users: Vec::new(),
+        }));
+    }
+
     // Search rooms in tenant
     let room_filter = doc! {
         "tenant_id": tid,
+        "room_type": { "$ne": "dm" },
         "deleted_at": null,
     };
     let rooms = state
@@ -180,6 +279,7 @@ pub async fn search(
 
     Ok(Json(SearchResults {
         messages: message_results,
+        transcripts: transcript_results,
         rooms: room_results,
         users: user_results,
     }))
@@ -217,6 +317,22 @@ async fn fetch_room_names(state: &AppState, room_ids: &[ObjectId]) -> HashMap<Ob
     result
 }
 
+/// The direct message rooms among `room_ids`.
+async fn fetch_dm_room_ids(state: &AppState, room_ids: &[ObjectId]) -> Vec<ObjectId> {
+    if room_ids.is_empty() {
+        return Vec::new();
+    }
+    state
+        .rooms
+        .base
+        .find_many(doc! { "_id": { "$in": room_ids }, "room_type": "dm" }, None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|r| r.id)
+        .collect()
+}
+
 /// Get all user IDs that are members of a tenant.
 async fn get_tenant_member_user_ids(state: &AppState, tenant_id: ObjectId) -> Vec<ObjectId> {
     use futures::TryStreamExt;
@@ -0,0 +1,236 @@
+//! REST side of shared drafts; the editing is on the WebSocket, see
+//! [`crate::shared_drafts`].
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ChannelAction, webhook_events};
+use roomler_ai_services::shared_drafts::MAX_CONTENT_CHARS;
+use serde::Deserialize;
+
+use super::message::MessageResponse;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    shared_drafts::{DraftResponse, require_editor, to_response},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDraftRequest {
+    #[serde(default)]
+    pub content: String,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/draft — unpublished drafts,
+/// newest first.
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<DraftResponse>>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    require_editor(&state, tid, rid, auth.user_id).await?;
+
+    let drafts = state
+        .shared_drafts
+        .find_open_for_room(tid, rid)
+        .await?
+        .into_iter()
+        .map(|d| to_response(&state, d))
+        .collect();
+    Ok(Json(drafts))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/draft
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<CreateDraftRequest>,
+) -> Result<(StatusCode, Json<DraftResponse>), ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    require_editor(&state, tid, rid, auth.user_id).await?;
+    if body.content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(ApiError::Validation(format!(
+            "Drafts can be at most {MAX_CONTENT_CHARS} characters"
+        )));
+    }
+
+    let draft = state
+        .shared_drafts
+        .create(tid, rid, auth.user_id, body.content)
+        .await?;
+    Ok((StatusCode::CREATED, Json(to_response(&state, draft))))
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/draft/{draft_id}
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, draft_id)): Path<(String, String, String)>,
+) -> Result<Json<DraftResponse>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    require_editor(&state, tid, rid, auth.user_id).await?;
+    let draft = find_draft(&state, tid, rid, &draft_id).await?;
+    Ok(Json(to_response(&state, draft)))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/draft/{draft_id}/publish —
+/// post the draft's current content as a message by the caller. Edits
+/// still in flight are refused.
+pub async fn publish(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, draft_id)): Path<(String, String, String)>,
+) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    let (room, role) = require_editor(&state, tid, rid, auth.user_id).await?;
+    super::room::require_outside_read_only_window(&room, role)?;
+    let draft = find_draft(&state, tid, rid, &draft_id).await?;
+    let id = draft.id.unwrap();
+    if draft.published_at.is_some() {
+        return Err(ApiError::Conflict("Draft is already published".to_string()));
+    }
+
+    let draft = state
+        .shared_drafts
+        .claim_for_publish(tid, id)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Draft is already published".to_string()))?;
+    let posted = post(&state, tid, rid, auth.user_id, &draft.content).await;
+    let response = match posted {
+        Ok(response) => response,
+        Err(e) => {
+            if let Err(e) = state.shared_drafts.reopen(id).await {
+                tracing::warn!(%id, %e, "Failed to reopen draft");
+            }
+            return Err(e);
+        }
+    };
+    let message_id = ObjectId::parse_str(&response.id)
+        .map_err(|_| ApiError::Internal("Message has no id".to_string()))?;
+    state
+        .shared_drafts
+        .set_published_message(id, message_id)
+        .await?;
+    crate::shared_drafts::closed(&state, id, Some(message_id)).await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/draft/{draft_id} — by its
+/// creator or whoever may delete any message in the room.
+pub async fn discard(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, draft_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (tid, rid) = parse_ids(&tenant_id, &room_id)?;
+    let (room, _) = require_editor(&state, tid, rid, auth.user_id).await?;
+    let draft = find_draft(&state, tid, rid, &draft_id).await?;
+    let id = draft.id.unwrap();
+    if draft.created_by != auth.user_id {
+        super::room::require_channel_action(
+            &state,
+            tid,
+            &room,
+            auth.user_id,
+            ChannelAction::DeleteAnyMessage,
+        )
+        .await?;
+    }
+
+    if !state.shared_drafts.delete(tid, id).await? {
+        return Err(ApiError::Conflict("Draft is already published".to_string()));
+    }
+    crate::shared_drafts::closed(&state, id, None).await;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Post `content` to the room as `user_id` and tell everyone else.
+async fn post(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    user_id: ObjectId,
+    content: &str,
+) -> Result<MessageResponse, ApiError> {
+    if content.trim().is_empty() {
+        return Err(ApiError::Validation("Draft is empty".to_string()));
+    }
+    let content = crate::emoji::expand_content(state, tid, content).await?;
+    let message = crate::profanity::post(state, tid, rid, user_id, content, |content| {
+        state
+            .messages
+            .create(tid, rid, user_id, content, None, None, None, None)
+    })
+    .await?;
+
+    let names: HashMap<ObjectId, String> = state
+        .users
+        .find_display_names(&[user_id])
+        .await
+        .unwrap_or_default();
+    let response = super::message::to_response(message, &names, Some(user_id));
+    let member_ids: Vec<ObjectId> = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|id| *id != user_id)
+        .collect();
+    let event = serde_json::json!({
+        "type": "message:create",
+        "data": &response,
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        tid,
+        &member_ids,
+        &event,
+    )
+    .await;
+    crate::webhooks::dispatch(
+        state,
+        tid,
+        webhook_events::MESSAGE_CREATE,
+        serde_json::to_value(&response).unwrap_or_default(),
+    );
+    Ok(response)
+}
+
+async fn find_draft(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    draft_id: &str,
+) -> Result<roomler_ai_db::models::SharedDraft, ApiError> {
+    let id = ObjectId::parse_str(draft_id)
+        .map_err(|_| ApiError::BadRequest("Invalid draft_id".to_string()))?;
+    let draft = state
+        .shared_drafts
+        .base
+        .find_by_id_in_tenant(tid, id)
+        .await?;
+    if draft.room_id != rid {
+        return Err(ApiError::NotFound("Draft not found".to_string()));
+    }
+    Ok(draft)
+}
+
+fn parse_ids(tenant_id: &str, room_id: &str) -> Result<(ObjectId, ObjectId), ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    Ok((tid, rid))
+}
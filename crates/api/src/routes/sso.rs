@@ -0,0 +1,757 @@
+//! Tenant single sign-on through an OpenID Connect provider; see
+//! [`roomler_ai_services::sso`]. Admins configure it under
+//! `/api/tenant/{id}/sso`; members start at `/api/oauth/sso/{tenant_slug}`.
+//! First-time users get an account and membership (JIT provisioning), and
+//! with `enforce` on, addresses in the tenant's domains can't sign in or
+//! register any other way.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{
+    DomainVerification, Plan, Tenant, TenantSso, User, actions, role::permissions,
+};
+use roomler_ai_services::sso::{self, STATE_TTL, SsoIdentity};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{client::ClientInfo, permission::RequirePermission},
+    routes::{
+        auth::{AuthResponse, UserResponse},
+        oauth::signed_in_redirect,
+    },
+    state::AppState,
+};
+
+/// Most email domains a tenant can verify.
+const MAX_DOMAINS: usize = 20;
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+/// Carries the `state` of a sign-in in progress, so a callback only
+/// completes in the browser that started it.
+const STATE_COOKIE: &str = "roomler_sso_state";
+
+#[derive(Debug, Serialize)]
+pub struct SsoResponse {
+    pub issuer: String,
+    pub client_id: String,
+    pub has_client_secret: bool,
+    pub domains: Vec<String>,
+    pub enforce: bool,
+    pub jit_provisioning: bool,
+    /// False while the plan doesn't include SSO; the configuration is kept.
+    pub active: bool,
+    /// Where members start signing in.
+    pub login_url: String,
+    /// To register with the provider.
+    pub redirect_uri: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSsoRequest {
+    pub issuer: String,
+    pub client_id: String,
+    /// Keeps the current secret when omitted.
+    pub client_secret: Option<String>,
+    pub domains: Vec<String>,
+    #[serde(default)]
+    pub enforce: bool,
+    #[serde(default = "default_true")]
+    pub jit_provisioning: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddDomainRequest {
+    pub domain: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainResponse {
+    pub domain: String,
+    /// Where to publish the TXT record.
+    pub record_name: String,
+    /// What it holds.
+    pub record_value: String,
+    pub verified: bool,
+    pub verified_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiscoverQuery {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiscoverResponse {
+    pub tenant_slug: String,
+    pub tenant_name: String,
+    /// Whether the address can only sign in through SSO.
+    pub enforced: bool,
+    pub login_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkRequest {
+    /// The `sso_link` parameter the callback redirected with.
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set by the provider when the sign-in failed or was cancelled.
+    pub error: Option<String>,
+}
+
+/// GET /api/tenant/{tenant_id}/sso
+pub async fn get(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<SsoResponse>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    let response = to_response(&state, &tenant)
+        .ok_or_else(|| ApiError::NotFound("SSO is not configured".to_string()))?;
+    Ok(Json(response))
+}
+
+/// PUT /api/tenant/{tenant_id}/sso — configure the tenant's provider.
+/// Needs the Business plan, and every domain must be verified.
+pub async fn set(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<SetSsoRequest>,
+) -> Result<Json<SsoResponse>, ApiError> {
+    let tenant = business_tenant(&state, tid).await?;
+    let issuer = body.issuer.trim().trim_end_matches('/').to_string();
+    state
+        .outbound
+        .check_public_url(&issuer)
+        .await
+        .map_err(|e| ApiError::Validation(format!("issuer: {e}")))?;
+    let client_id = body.client_id.trim().to_string();
+    if client_id.is_empty() {
+        return Err(ApiError::Validation("client_id is required".to_string()));
+    }
+    let client_secret = match body.client_secret.filter(|s| !s.is_empty()) {
+        Some(secret) => secret,
+        None => tenant
+            .sso
+            .as_ref()
+            .map(|sso| sso.client_secret.clone())
+            .ok_or_else(|| ApiError::Validation("client_secret is required".to_string()))?,
+    };
+    let domains = sso::normalize_domains(&body.domains).map_err(ApiError::Validation)?;
+
+    for domain in &domains {
+        let verified = tenant
+            .domain_verifications
+            .iter()
+            .any(|v| v.domain == *domain && v.verified_at.is_some());
+        if !verified {
+            return Err(ApiError::Validation(format!(
+                "{domain} isn't verified; publish its TXT record and verify it first"
+            )));
+        }
+    }
+    if let Some(other) = state
+        .tenants
+        .find_by_sso_domain(&domains, Some(tid))
+        .await?
+    {
+        let taken = other
+            .sso
+            .map(|sso| sso.domains)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|d| domains.contains(d))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(ApiError::Conflict(format!(
+            "Another tenant already uses {taken}"
+        )));
+    }
+
+    let sso = TenantSso {
+        issuer,
+        client_id,
+        client_secret,
+        domains,
+        enforce: body.enforce,
+        jit_provisioning: body.jit_provisioning,
+        updated_at: DateTime::now(),
+    };
+    state.tenants.set_sso(tid, &sso).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::TENANT_SSO_UPDATE,
+        Some(tid),
+        vec![audit::change(
+            "sso",
+            tenant.sso.as_ref().map(audit_value),
+            Some(audit_value(&sso)),
+        )],
+    )
+    .await;
+
+    let tenant = Tenant {
+        sso: Some(sso),
+        ..tenant
+    };
+    Ok(Json(
+        to_response(&state, &tenant).expect("SSO was just set"),
+    ))
+}
+
+/// DELETE /api/tenant/{tenant_id}/sso — turn SSO and domain capture off.
+/// Accounts created through it keep working with a password reset.
+pub async fn remove(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+) -> Result<StatusCode, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    let Some(old) = tenant.sso else {
+        return Err(ApiError::NotFound("SSO is not configured".to_string()));
+    };
+    state.tenants.clear_sso(tid).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::TENANT_SSO_REMOVE,
+        Some(tid),
+        vec![audit::change("sso", Some(audit_value(&old)), None)],
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/tenant/{tenant_id}/sso/domain — the tenant's email domains,
+/// verified or waiting for their TXT record.
+pub async fn list_domains(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<Vec<DomainResponse>>, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(
+        tenant
+            .domain_verifications
+            .iter()
+            .map(domain_response)
+            .collect(),
+    ))
+}
+
+/// POST /api/tenant/{tenant_id}/sso/domain — start proving the tenant
+/// owns an email domain. Returns the TXT record to publish; asking again
+/// returns the same one. Needs the Business plan.
+pub async fn add_domain(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Json(body): Json<AddDomainRequest>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    let tenant = business_tenant(&state, tid).await?;
+    let domain = sso::normalize_domains(&[body.domain])
+        .map_err(ApiError::Validation)?
+        .remove(0);
+    if let Some(existing) = tenant
+        .domain_verifications
+        .iter()
+        .find(|v| v.domain == domain)
+    {
+        return Ok(Json(domain_response(existing)));
+    }
+    if tenant.domain_verifications.len() >= MAX_DOMAINS {
+        return Err(ApiError::Validation(format!(
+            "A tenant can verify at most {MAX_DOMAINS} domains"
+        )));
+    }
+
+    let verification = DomainVerification {
+        domain,
+        token: nanoid::nanoid!(32),
+        verified_at: None,
+        created_at: DateTime::now(),
+    };
+    if !state
+        .tenants
+        .add_domain_verification(tid, &verification)
+        .await?
+    {
+        // Added by a concurrent request
+        let tenant = state.tenants.base.find_by_id(tid).await?;
+        let existing = tenant
+            .domain_verifications
+            .iter()
+            .find(|v| v.domain == verification.domain)
+            .ok_or_else(|| ApiError::Internal("Domain verification vanished".to_string()))?;
+        return Ok(Json(domain_response(existing)));
+    }
+    Ok(Json(domain_response(&verification)))
+}
+
+/// POST /api/tenant/{tenant_id}/sso/domain/{domain}/verify — look up the
+/// domain's TXT record and mark it verified when it holds the token.
+pub async fn verify_domain(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Path((_, domain)): Path<(String, String)>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    let tenant = business_tenant(&state, tid).await?;
+    let domain = domain.to_lowercase();
+    let verification = tenant
+        .domain_verifications
+        .into_iter()
+        .find(|v| v.domain == domain)
+        .ok_or_else(|| ApiError::NotFound("The domain hasn't been added".to_string()))?;
+    if verification.verified_at.is_some() {
+        return Ok(Json(domain_response(&verification)));
+    }
+
+    let proven = state
+        .sso
+        .domain_proven(&verification.domain, &verification.token)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    if !proven {
+        return Err(ApiError::Validation(format!(
+            "No TXT record {} found at {}",
+            sso::challenge_value(&verification.token),
+            sso::challenge_record(&verification.domain)
+        )));
+    }
+    state.tenants.mark_domain_verified(tid, &domain).await?;
+    Ok(Json(domain_response(&DomainVerification {
+        verified_at: Some(DateTime::now()),
+        ..verification
+    })))
+}
+
+/// GET /api/oauth/sso?email= — the tenant whose SSO covers the address,
+/// for login forms to offer (or insist on) it.
+pub async fn discover(
+    State(state): State<AppState>,
+    Query(query): Query<DiscoverQuery>,
+) -> Result<Json<DiscoverResponse>, ApiError> {
+    let not_found = || ApiError::NotFound("No single sign-on for this address".to_string());
+    let domain = sso::email_domain(&query.email).ok_or_else(not_found)?;
+    let tenant = state
+        .tenants
+        .find_by_sso_domain(&[domain], None)
+        .await?
+        .ok_or_else(not_found)?;
+    let enforced = tenant.active_sso().ok_or_else(not_found)?.enforce;
+    Ok(Json(DiscoverResponse {
+        login_url: login_path(&tenant.slug),
+        tenant_slug: tenant.slug,
+        tenant_name: tenant.name,
+        enforced,
+    }))
+}
+
+/// GET /api/oauth/sso/{tenant_slug} — send the browser to the tenant's
+/// provider.
+pub async fn login(
+    State(state): State<AppState>,
+    Path(tenant_slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let tenant = state.tenants.find_by_slug(&tenant_slug).await?;
+    let config = tenant
+        .active_sso()
+        .ok_or_else(|| ApiError::NotFound("The tenant has no single sign-on".to_string()))?;
+
+    let pending = state.sso.begin(tenant.id.unwrap());
+    let url = state
+        .sso
+        .authorization_url(config, &redirect_uri(&state, &tenant.slug), &pending)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let cookie = format!(
+        "{STATE_COOKIE}={}; HttpOnly; Path=/api/oauth/sso; SameSite=Lax; Max-Age={}",
+        pending.state,
+        STATE_TTL.as_secs()
+    );
+    let mut response = Redirect::temporary(&url).into_response();
+    response
+        .headers_mut()
+        .insert(header::SET_COOKIE, cookie.parse().unwrap());
+    Ok(response)
+}
+
+/// GET /api/oauth/sso/{tenant_slug}/callback — finish the sign-in,
+/// provisioning the account and membership on a first visit. Only
+/// identities linked to the tenant's provider sign in; when the address
+/// belongs to an account that isn't, the browser goes to the frontend's
+/// `/oauth/callback?sso_link=…` to [`link`] it.
+pub async fn callback(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    headers: HeaderMap,
+    Path(tenant_slug): Path<String>,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Response, ApiError> {
+    if let Some(error) = query.error {
+        return Err(ApiError::BadRequest(format!(
+            "The identity provider refused the sign-in: {error}"
+        )));
+    }
+    let (Some(code), Some(returned_state)) = (query.code, query.state) else {
+        return Err(ApiError::BadRequest(
+            "code and state are required".to_string(),
+        ));
+    };
+    if state_cookie(&headers).as_deref() != Some(returned_state.as_str()) {
+        return Err(ApiError::BadRequest(
+            "The sign-in was started in another browser or has expired".to_string(),
+        ));
+    }
+
+    let tenant = state.tenants.find_by_slug(&tenant_slug).await?;
+    let tid = tenant.id.unwrap();
+    let config = tenant
+        .active_sso()
+        .ok_or_else(|| ApiError::NotFound("The tenant has no single sign-on".to_string()))?;
+    let nonce = state
+        .sso
+        .verify_state(&returned_state, tid)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let identity = state
+        .sso
+        .authenticate(config, &code, &redirect_uri(&state, &tenant.slug), &nonce)
+        .await
+        .map_err(|e| ApiError::Unauthorized(e.to_string()))?;
+
+    ensure_in_domains(&tenant, config, &identity.email)?;
+
+    let provider = provider(tid);
+    let user = match state
+        .users
+        .find_by_oauth(&provider, &identity.subject)
+        .await?
+    {
+        Some(user) => user,
+        // The provider vouches for the address, but not for whoever holds
+        // the account: its owner links it by signing in to it.
+        None if state.users.find_by_email(&identity.email).await.is_ok() => {
+            let token = state.sso.link_token(tid, &identity);
+            let url = format!(
+                "{}/oauth/callback?sso_link={}&tenant_slug={}",
+                state.settings.app.frontend_url, token, tenant.slug
+            );
+            return Ok((
+                StatusCode::FOUND,
+                [
+                    (header::LOCATION, url),
+                    (header::SET_COOKIE, clear_state_cookie()),
+                ],
+            )
+                .into_response());
+        }
+        None => {
+            if !config.jit_provisioning {
+                return Err(ApiError::Forbidden(format!(
+                    "{} has no account; ask an admin of {} for an invite",
+                    identity.email, tenant.name
+                )));
+            }
+            state
+                .users
+                .create_by_oauth(
+                    &provider,
+                    &identity.subject,
+                    &identity.email,
+                    &display_name(&identity),
+                    None,
+                )
+                .await?
+        }
+    };
+    join_tenant(&state, &client, &tenant, config, &user).await?;
+
+    signed_in_redirect(&state, &user, Some(&clear_state_cookie()))
+}
+
+/// POST /api/oauth/sso/{tenant_slug}/link — link the account at an
+/// address to the tenant's provider, after a sign-in there found the
+/// account already existing. `token` comes from that sign-in and
+/// `password` is the account's, so only its owner can link it. Signs in
+/// like a password login.
+pub async fn link(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Path(tenant_slug): Path<String>,
+    Json(body): Json<LinkRequest>,
+) -> Result<(HeaderMap, Json<AuthResponse>), ApiError> {
+    let tenant = state.tenants.find_by_slug(&tenant_slug).await?;
+    let tid = tenant.id.unwrap();
+    let config = tenant
+        .active_sso()
+        .ok_or_else(|| ApiError::NotFound("The tenant has no single sign-on".to_string()))?;
+    let identity = state
+        .sso
+        .verify_link_token(&body.token, tid)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    ensure_in_domains(&tenant, config, &identity.email)?;
+
+    let invalid = || ApiError::Unauthorized("Invalid credentials".to_string());
+    let user = state
+        .users
+        .find_by_email(&identity.email)
+        .await
+        .map_err(|_| invalid())?;
+    let password_hash = user.password_hash.as_ref().ok_or_else(|| {
+        ApiError::Unauthorized(
+            "The account has no password; reset it to link the account".to_string(),
+        )
+    })?;
+    if !state.auth.verify_password(&body.password, password_hash)? {
+        return Err(invalid());
+    }
+
+    let provider = provider(tid);
+    let user_id = user.id.unwrap();
+    match state
+        .users
+        .find_by_oauth(&provider, &identity.subject)
+        .await?
+    {
+        Some(linked) if linked.id != Some(user_id) => {
+            return Err(ApiError::Conflict(
+                "The identity is linked to another account".to_string(),
+            ));
+        }
+        Some(_) => {}
+        None => {
+            state
+                .users
+                .link_oauth(user_id, &provider, &identity.subject)
+                .await?;
+        }
+    }
+    join_tenant(&state, &client, &tenant, config, &user).await?;
+
+    let tokens = state
+        .auth
+        .generate_tokens(user_id, &user.email, &user.username)?;
+    let mut headers = HeaderMap::new();
+    let cookie = format!(
+        "access_token={}; HttpOnly; Path=/; SameSite=Lax; Max-Age={}",
+        tokens.access_token, tokens.expires_in
+    );
+    headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+    Ok((
+        headers,
+        Json(AuthResponse {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
+            user: UserResponse {
+                id: user_id.to_hex(),
+                email: user.email,
+                username: user.username,
+                display_name: user.display_name,
+                avatar: user.avatar,
+            },
+            invite_tenant: None,
+        }),
+    ))
+}
+
+/// Make `user` a member of `tenant` on their first SSO sign-in, when
+/// `jit_provisioning` allows it.
+async fn join_tenant(
+    state: &AppState,
+    client: &ClientInfo,
+    tenant: &Tenant,
+    config: &TenantSso,
+    user: &User,
+) -> Result<(), ApiError> {
+    let tid = tenant.id.unwrap();
+    let user_id = user.id.unwrap();
+    if state.tenants.is_member(tid, user_id).await? {
+        return Ok(());
+    }
+    if !config.jit_provisioning {
+        return Err(ApiError::Forbidden(format!(
+            "You are not a member of {}; ask an admin for an invite",
+            tenant.name
+        )));
+    }
+    let member_role = state.tenants.get_role_by_name(tid, "member").await?;
+    state
+        .tenants
+        .add_member(tid, user_id, vec![member_role.id.unwrap()], None)
+        .await?;
+    state.onboarding.join_default_rooms(tid, user_id).await;
+    audit::record(
+        state,
+        tid,
+        user_id,
+        client,
+        actions::MEMBER_ADD,
+        Some(user_id),
+        vec![audit::change(
+            "provisioned_by",
+            None,
+            Some(serde_json::json!("sso")),
+        )],
+    )
+    .await;
+    Ok(())
+}
+
+fn ensure_in_domains(tenant: &Tenant, config: &TenantSso, email: &str) -> Result<(), ApiError> {
+    let in_domains =
+        sso::email_domain(email).is_some_and(|domain| config.domains.contains(&domain));
+    if !in_domains {
+        return Err(ApiError::Forbidden(format!(
+            "{} is not in a domain {} signs in with",
+            email, tenant.name
+        )));
+    }
+    Ok(())
+}
+
+/// What a user signed in through a tenant's provider is linked by.
+fn provider(tenant_id: ObjectId) -> String {
+    format!("sso:{}", tenant_id.to_hex())
+}
+
+fn display_name(identity: &SsoIdentity) -> String {
+    identity
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| {
+            identity
+                .email
+                .split('@')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+}
+
+fn clear_state_cookie() -> String {
+    format!("{STATE_COOKIE}=; HttpOnly; Path=/api/oauth/sso; Max-Age=0")
+}
+
+/// Refuse `email` when a tenant captured its domain: it can only sign in
+/// through that tenant's SSO.
+pub async fn ensure_not_captured(state: &AppState, email: &str) -> Result<(), ApiError> {
+    let Some(domain) = sso::email_domain(email) else {
+        return Ok(());
+    };
+    let Some(tenant) = state.tenants.find_by_sso_domain(&[domain], None).await? else {
+        return Ok(());
+    };
+    if tenant.active_sso().is_some_and(|sso| sso.enforce) {
+        return Err(ApiError::Forbidden(format!(
+            "Accounts at this domain sign in through {}'s single sign-on at {}",
+            tenant.name,
+            login_path(&tenant.slug)
+        )));
+    }
+    Ok(())
+}
+
+fn to_response(state: &AppState, tenant: &Tenant) -> Option<SsoResponse> {
+    let sso = tenant.sso.as_ref()?;
+    Some(SsoResponse {
+        issuer: sso.issuer.clone(),
+        client_id: sso.client_id.clone(),
+        has_client_secret: !sso.client_secret.is_empty(),
+        domains: sso.domains.clone(),
+        enforce: sso.enforce,
+        jit_provisioning: sso.jit_provisioning,
+        active: tenant.active_sso().is_some(),
+        login_url: login_path(&tenant.slug),
+        redirect_uri: redirect_uri(state, &tenant.slug),
+        updated_at: sso.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+    })
+}
+
+/// The configuration without its secret.
+fn audit_value(sso: &TenantSso) -> serde_json::Value {
+    serde_json::json!({
+        "issuer": sso.issuer,
+        "client_id": sso.client_id,
+        "domains": sso.domains,
+        "enforce": sso.enforce,
+        "jit_provisioning": sso.jit_provisioning,
+    })
+}
+
+fn login_path(slug: &str) -> String {
+    format!("/api/oauth/sso/{slug}")
+}
+
+fn redirect_uri(state: &AppState, slug: &str) -> String {
+    format!(
+        "{}/api/oauth/sso/{slug}/callback",
+        state.settings.oauth.base_url
+    )
+}
+
+fn state_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            (name == STATE_COOKIE).then(|| value.to_string())
+        })
+}
+
+/// The tenant, checking its plan includes SSO.
+async fn business_tenant(state: &AppState, tenant_id: ObjectId) -> Result<Tenant, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    if tenant.plan < Plan::Business {
+        return Err(ApiError::Forbidden(
+            "Single sign-on is not included in the tenant's plan".to_string(),
+        ));
+    }
+    Ok(tenant)
+}
+
+fn domain_response(verification: &DomainVerification) -> DomainResponse {
+    DomainResponse {
+        domain: verification.domain.clone(),
+        record_name: sso::challenge_record(&verification.domain),
+        record_value: sso::challenge_value(&verification.token),
+        verified: verification.verified_at.is_some(),
+        verified_at: verification
+            .verified_at
+            .and_then(|at| at.try_to_rfc3339_string().ok()),
+    }
+}
@@ -7,9 +7,17 @@ use axum::{
 use bson::oid::ObjectId;
 use serde::Deserialize;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::role::permissions;
-use roomler_ai_services::stripe::{StripeEvent, StripeService};
+use crate::{
+    analytics_reports, audit, billing_events, email_queue,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo},
+    state::AppState,
+};
+use roomler_ai_db::models::{ActorType, AuditChange, Tenant, actions, role::permissions};
+use roomler_ai_services::{
+    email::EmailTemplate,
+    stripe::{StripeEvent, StripeService},
+};
 
 // ---- Request types -------------------------------------------------------
 
@@ -38,12 +46,13 @@ pub async fn get_plans() -> Json<Vec<roomler_ai_services::stripe::PlanInfo>> {
 pub async fn create_checkout(
     State(state): State<AppState>,
     auth: AuthUser,
+    client: ClientInfo,
     Json(body): Json<CheckoutRequest>,
 ) -> Result<Json<roomler_ai_services::stripe::CheckoutResponse>, ApiError> {
     let tenant_id = parse_oid(&body.tenant_id)?;
     require_manage_tenant(&state, tenant_id, auth.user_id).await?;
 
-    let stripe = StripeService::new(&state.settings.stripe);
+    let stripe = StripeService::new(&state.settings.stripe, &state.outbound);
     let result = stripe
         .create_checkout_session(
             &state.db,
@@ -55,6 +64,16 @@ pub async fn create_checkout(
         )
         .await
         .map_err(stripe_err)?;
+    audit::record(
+        &state,
+        tenant_id,
+        auth.user_id,
+        &client,
+        actions::BILLING_CHECKOUT,
+        Some(tenant_id),
+        vec![audit::change("plan", None, Some(body.plan.into()))],
+    )
+    .await;
 
     Ok(Json(result))
 }
@@ -69,7 +88,7 @@ pub async fn create_portal(
     let tenant_id = parse_oid(&body.tenant_id)?;
     require_manage_tenant(&state, tenant_id, auth.user_id).await?;
 
-    let stripe = StripeService::new(&state.settings.stripe);
+    let stripe = StripeService::new(&state.settings.stripe, &state.outbound);
     let result = stripe
         .create_portal_session(&state.db, &tenant_id, &body.return_url)
         .await
@@ -98,18 +117,84 @@ pub async fn webhook(
     let event: StripeEvent = serde_json::from_slice(&body)
         .map_err(|e| ApiError::BadRequest(format!("Invalid event payload: {e}")))?;
 
+    let action = match event.event_type.as_str() {
+        "checkout.session.completed" => Some(actions::BILLING_PLAN_CHANGE),
+        "customer.subscription.updated" => Some(actions::BILLING_SUBSCRIPTION_UPDATE),
+        "customer.subscription.deleted" => Some(actions::BILLING_SUBSCRIPTION_CANCEL),
+        "invoice.payment_failed" => Some(actions::BILLING_PAYMENT_FAILED),
+        _ => None,
+    };
+    let before = StripeService::event_tenant(&state.db, &event)
+        .await
+        .map_err(stripe_err)?;
+
     // Process event
-    let stripe = StripeService::new(&state.settings.stripe);
+    let stripe = StripeService::new(&state.settings.stripe, &state.outbound);
     stripe
         .handle_webhook_event(&state.db, &event)
         .await
         .map_err(stripe_err)?;
 
+    if let Some(before) = before
+        && let Some(tenant_id) = before.id
+    {
+        let after = state.tenants.base.find_by_id(tenant_id).await?;
+        if let Some(action) = action {
+            audit::record_system(
+                &state,
+                tenant_id,
+                ActorType::Webhook,
+                action,
+                Some(tenant_id),
+                billing_changes(&before, &after),
+            )
+            .await;
+        }
+        billing_events::billing_changed(&state, &before, &after).await;
+        if let Some(receipt) = StripeService::invoice_receipt(&after, &event) {
+            send_receipt(&state, tenant_id, receipt).await;
+        }
+    }
+
     Ok(StatusCode::OK)
 }
 
 // ---- Helpers -------------------------------------------------------------
 
+/// Plan and subscription status fields a webhook changed.
+fn billing_changes(before: &Tenant, after: &Tenant) -> Vec<AuditChange> {
+    let plan = |t: &Tenant| serde_json::to_value(&t.plan).ok();
+    let status = |t: &Tenant| {
+        t.billing
+            .as_ref()
+            .and_then(|b| serde_json::to_value(&b.status).ok())
+    };
+    [
+        ("plan", plan(before), plan(after)),
+        ("billing.status", status(before), status(after)),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| audit::change(field, old, new))
+    .collect()
+}
+
+/// Email a paid invoice's receipt to the tenant's admins.
+async fn send_receipt(state: &AppState, tenant_id: ObjectId, receipt: EmailTemplate) {
+    for admin_id in analytics_reports::admin_ids(state, tenant_id).await {
+        if let Ok(admin) = state.users.base.find_by_id(admin_id).await {
+            email_queue::send(
+                state,
+                Some(tenant_id),
+                admin_id,
+                &admin.email,
+                receipt.clone(),
+            )
+            .await;
+        }
+    }
+}
+
 fn parse_oid(s: &str) -> Result<ObjectId, ApiError> {
     ObjectId::parse_str(s).map_err(|_| ApiError::BadRequest(format!("Invalid ObjectId: {s}")))
 }
@@ -1,5 +1,6 @@
 use axum::{Json, extract::State};
 use bson::oid::ObjectId;
+use roomler_ai_db::models::Tenant;
 use serde::{Deserialize, Serialize};
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
@@ -8,6 +9,9 @@ use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 pub struct CreateTenantRequest {
     pub name: String,
     pub slug: String,
+    /// Create a sandbox for integration testing. Can't be changed later.
+    #[serde(default)]
+    pub sandbox: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -17,6 +21,19 @@ pub struct TenantResponse {
     pub slug: String,
     pub owner_id: String,
     pub plan: String,
+    /// Sandbox tenants hold test data; see `POST /sandbox/reset`.
+    pub sandbox: bool,
+}
+
+fn to_response(t: Tenant) -> TenantResponse {
+    TenantResponse {
+        id: t.id.unwrap().to_hex(),
+        name: t.name,
+        slug: t.slug,
+        owner_id: t.owner_id.to_hex(),
+        plan: format!("{:?}", t.plan),
+        sandbox: t.is_sandbox,
+    }
 }
 
 pub async fn list(
@@ -25,16 +42,7 @@ pub async fn list(
 ) -> Result<Json<Vec<TenantResponse>>, ApiError> {
     let tenants = state.tenants.find_user_tenants(auth.user_id).await?;
 
-    let response: Vec<TenantResponse> = tenants
-        .into_iter()
-        .map(|t| TenantResponse {
-            id: t.id.unwrap().to_hex(),
-            name: t.name,
-            slug: t.slug,
-            owner_id: t.owner_id.to_hex(),
-            plan: format!("{:?}", t.plan),
-        })
-        .collect();
+    let response: Vec<TenantResponse> = tenants.into_iter().map(to_response).collect();
 
     Ok(Json(response))
 }
@@ -46,16 +54,18 @@ pub async fn create(
 ) -> Result<Json<TenantResponse>, ApiError> {
     let tenant = state
         .tenants
-        .create(body.name, body.slug, auth.user_id)
+        .create(body.name, body.slug, auth.user_id, body.sandbox)
         .await?;
 
-    Ok(Json(TenantResponse {
-        id: tenant.id.unwrap().to_hex(),
-        name: tenant.name,
-        slug: tenant.slug,
-        owner_id: tenant.owner_id.to_hex(),
-        plan: format!("{:?}", tenant.plan),
-    }))
+    if let Err(e) = state
+        .onboarding
+        .provision_tenant(tenant.id.unwrap(), auth.user_id)
+        .await
+    {
+        tracing::warn!(%e, "Failed to provision default channels");
+    }
+
+    Ok(Json(to_response(tenant)))
 }
 
 pub async fn get(
@@ -73,11 +83,5 @@ pub async fn get(
 
     let tenant = state.tenants.base.find_by_id(tid).await?;
 
-    Ok(Json(TenantResponse {
-        id: tenant.id.unwrap().to_hex(),
-        name: tenant.name,
-        slug: tenant.slug,
-        owner_id: tenant.owner_id.to_hex(),
-        plan: format!("{:?}", tenant.plan),
-    }))
+    Ok(Json(to_response(tenant)))
 }
@@ -0,0 +1,138 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::role::permissions;
+use roomler_ai_services::tenant_config::{ConfigDiff, TenantConfigBundle};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: BundleFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApplyResponse {
+    pub applied: bool,
+    #[serde(flatten)]
+    pub diff: ConfigDiff,
+}
+
+/// GET /api/tenant/{tenant_id}/config/export?format=json|yaml
+pub async fn export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    let tid = authorize(&state, &auth, &tenant_id).await?;
+    let bundle = state.tenant_config.export(tid).await?;
+
+    let (body, content_type, ext) = match query.format {
+        BundleFormat::Json => (
+            serde_json::to_string_pretty(&bundle).map_err(|e| ApiError::Internal(e.to_string()))?,
+            "application/json",
+            "json",
+        ),
+        BundleFormat::Yaml => (
+            serde_yaml::to_string(&bundle).map_err(|e| ApiError::Internal(e.to_string()))?,
+            "application/yaml",
+            "yaml",
+        ),
+    };
+    let disposition = format!("attachment; filename=\"tenant-config-{}.{}\"", tid, ext);
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// POST /api/tenant/{tenant_id}/config/diff — what applying the bundle in
+/// the body would change. JSON, or YAML with a YAML content type.
+pub async fn diff(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ConfigDiff>, ApiError> {
+    let tid = authorize(&state, &auth, &tenant_id).await?;
+    let bundle = parse_bundle(&headers, &body)?;
+    Ok(Json(state.tenant_config.plan(tid, &bundle).await?))
+}
+
+/// POST /api/tenant/{tenant_id}/config/apply — create and update roles and
+/// rooms to match the bundle. Nothing is deleted.
+pub async fn apply(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApplyResponse>, ApiError> {
+    let tid = authorize(&state, &auth, &tenant_id).await?;
+    let bundle = parse_bundle(&headers, &body)?;
+    let diff = state
+        .tenant_config
+        .apply(tid, auth.user_id, &bundle)
+        .await?;
+    if diff.has_changes() {
+        crate::billing_events::check_limits(&state, tid).await;
+    }
+    Ok(Json(ApplyResponse {
+        applied: diff.has_changes(),
+        diff,
+    }))
+}
+
+async fn authorize(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+) -> Result<ObjectId, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(tid)
+}
+
+fn parse_bundle(headers: &HeaderMap, body: &[u8]) -> Result<TenantConfigBundle, ApiError> {
+    let is_yaml = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("yaml"));
+    let parsed = if is_yaml {
+        serde_yaml::from_slice(body).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_slice(body).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| ApiError::BadRequest(format!("Invalid configuration bundle: {}", e)))
+}
@@ -0,0 +1,248 @@
+//! Following threads. A thread's author and everyone who replies follow it
+//! automatically; others can follow or unfollow it by hand. Followers get
+//! `thread:update` whenever a reply is posted or edited, and
+//! `GET .../thread/subscribed` lists the threads they follow with replies
+//! they haven't read.
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::Message;
+use serde::Serialize;
+use tracing::warn;
+
+use super::message::{MessageResponse, author_names, collect_author_ids, to_response};
+use super::room::visible_room;
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize)]
+pub struct SubscribedThread {
+    pub thread_id: String,
+    pub room_id: String,
+    pub unread_count: u64,
+    pub last_read_at: String,
+    pub root: MessageResponse,
+}
+
+/// GET /api/tenant/{tenant_id}/thread/subscribed — followed threads with
+/// unread replies, most recently active first.
+pub async fn subscribed(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<SubscribedThread>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let subscriptions = state
+        .thread_subscriptions
+        .find_for_user(tid, auth.user_id)
+        .await?;
+    let unread = state
+        .thread_subscriptions
+        .unread_counts(auth.user_id, &subscriptions)
+        .await?;
+    let subscriptions: Vec<_> = subscriptions
+        .into_iter()
+        .filter(|s| unread.contains_key(&s.thread_id))
+        .collect();
+    if subscriptions.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let root_ids: Vec<ObjectId> = subscriptions.iter().map(|s| s.thread_id).collect();
+    let mut roots: HashMap<ObjectId, Message> = state
+        .messages
+        .base
+        .find_many(
+            bson::doc! { "_id": { "$in": &root_ids }, "deleted_at": null },
+            None,
+        )
+        .await?
+        .into_iter()
+        .filter_map(|m| m.id.map(|id| (id, m)))
+        .collect();
+
+    // Rooms the caller can no longer see drop out.
+    let mut visible: HashMap<ObjectId, bool> = HashMap::new();
+    for s in &subscriptions {
+        if let std::collections::hash_map::Entry::Vacant(entry) = visible.entry(s.room_id) {
+            let ok = visible_room(&state, tid, s.room_id, auth.user_id)
+                .await
+                .is_ok();
+            entry.insert(ok);
+        }
+    }
+
+    let mut threads: Vec<(Message, _)> = subscriptions
+        .into_iter()
+        .filter(|s| visible.get(&s.room_id).copied().unwrap_or(false))
+        .filter_map(|s| roots.remove(&s.thread_id).map(|root| (root, s)))
+        .collect();
+    threads.sort_by_key(|(root, _)| {
+        std::cmp::Reverse(
+            root.thread_metadata
+                .as_ref()
+                .and_then(|tm| tm.last_reply_at)
+                .unwrap_or(root.created_at),
+        )
+    });
+
+    let roots: Vec<Message> = threads.iter().map(|(root, _)| root.clone()).collect();
+    let names = author_names(&state, &collect_author_ids(&roots)).await;
+    let items = threads
+        .into_iter()
+        .map(|(root, s)| SubscribedThread {
+            thread_id: s.thread_id.to_hex(),
+            room_id: s.room_id.to_hex(),
+            unread_count: unread.get(&s.thread_id).copied().unwrap_or(0),
+            last_read_at: s.last_read_at.try_to_rfc3339_string().unwrap_or_default(),
+            root: to_response(root, &names, Some(auth.user_id)),
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/subscription
+pub async fn subscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let root = thread_root(&state, auth.user_id, &tenant_id, &room_id, &message_id).await?;
+    state
+        .thread_subscriptions
+        .subscribe(&root, auth.user_id, false)
+        .await?;
+    Ok(Json(serde_json::json!({ "subscribed": true })))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/subscription
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let root = thread_root(&state, auth.user_id, &tenant_id, &room_id, &message_id).await?;
+    state
+        .thread_subscriptions
+        .unsubscribe(&root, auth.user_id)
+        .await?;
+    Ok(Json(serde_json::json!({ "subscribed": false })))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/read
+pub async fn mark_read(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let root = thread_root(&state, auth.user_id, &tenant_id, &room_id, &message_id).await?;
+    if let Some(thread_id) = root.id {
+        state
+            .thread_subscriptions
+            .mark_read(thread_id, auth.user_id)
+            .await?;
+    }
+    Ok(Json(serde_json::json!({ "read": true })))
+}
+
+/// A top-level message in a room the caller can see.
+async fn thread_root(
+    state: &AppState,
+    user_id: ObjectId,
+    tenant_id: &str,
+    room_id: &str,
+    message_id: &str,
+) -> Result<Message, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mid = ObjectId::parse_str(message_id)
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    visible_room(state, tid, rid, user_id).await?;
+
+    let message = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
+    if message.room_id != rid || message.thread_id.is_some() || message.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Thread not found".to_string()));
+    }
+    Ok(message)
+}
+
+/// After `replier` posted in the thread under `root`: they follow it, as
+/// does the thread's author unless they unfollowed it, and the other
+/// followers hear about it.
+pub(crate) async fn on_reply(state: &AppState, root: &Message, replier: ObjectId) {
+    let subscriptions = &state.thread_subscriptions;
+    // The author hasn't read the first reply yet.
+    if root.author_id != replier
+        && let Err(e) = subscriptions
+            .subscribe_if_new(root, root.author_id, root.created_at)
+            .await
+    {
+        warn!(thread_id = ?root.id, %e, "Failed to subscribe thread author");
+    }
+    if let Err(e) = subscriptions.subscribe(root, replier, true).await {
+        warn!(thread_id = ?root.id, %e, "Failed to subscribe thread replier");
+    }
+    notify(state, root, replier).await;
+}
+
+/// Send `thread:update` for `root` to its followers other than `actor`.
+pub(crate) async fn notify(state: &AppState, root: &Message, actor: ObjectId) {
+    let Some(thread_id) = root.id else { return };
+    let subscriber_ids: Vec<ObjectId> = match state
+        .thread_subscriptions
+        .find_subscriber_ids(thread_id)
+        .await
+    {
+        Ok(ids) => ids.into_iter().filter(|id| *id != actor).collect(),
+        Err(e) => {
+            warn!(%thread_id, %e, "Failed to load thread subscribers");
+            return;
+        }
+    };
+    if subscriber_ids.is_empty() {
+        return;
+    }
+
+    let tm = root.thread_metadata.as_ref();
+    let event = serde_json::json!({
+        "type": "thread:update",
+        "data": {
+            "thread_id": thread_id.to_hex(),
+            "room_id": root.room_id.to_hex(),
+            "reply_count": tm.map_or(0, |tm| tm.reply_count),
+            "activity_count": tm.map_or(0, |tm| tm.activity_count),
+            "last_reply_at": tm
+                .and_then(|tm| tm.last_reply_at)
+                .and_then(|at| at.try_to_rfc3339_string().ok()),
+            "last_reply_user_id": tm
+                .and_then(|tm| tm.last_reply_user_id)
+                .map(|id| id.to_hex()),
+        }
+    });
+    crate::ws::dispatcher::broadcast_in_tenant(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &state.delivery_metrics,
+        root.tenant_id,
+        &subscriber_ids,
+        &event,
+    )
+    .await;
+}
@@ -5,7 +5,10 @@ use axum::{
 use bson::{doc, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError, extractors::auth::AuthUser, presence::PresenceActivity, state::AppState,
+};
+use roomler_ai_db::models::Presence;
 use roomler_ai_services::dao::base::PaginationParams;
 
 #[derive(Debug, Serialize)]
@@ -15,6 +18,14 @@ pub struct MemberResponse {
     pub nickname: Option<String>,
     pub role_ids: Vec<String>,
     pub joined_at: String,
+    pub activity: PresenceActivity,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberPresenceResponse {
+    pub user_id: String,
+    pub presence: Presence,
+    pub activity: PresenceActivity,
 }
 
 #[derive(Debug, Serialize)]
@@ -60,6 +71,7 @@ pub async fn list_members(
         )
         .await?;
 
+    let mut activities = crate::presence::visible_activities(&state, auth.user_id).await;
     let items: Vec<MemberResponse> = result
         .items
         .into_iter()
@@ -69,6 +81,7 @@ pub async fn list_members(
             nickname: m.nickname,
             role_ids: m.role_ids.iter().map(|r| r.to_hex()).collect(),
             joined_at: m.joined_at.try_to_rfc3339_string().unwrap_or_default(),
+            activity: activities.remove(&m.user_id).unwrap_or_default(),
         })
         .collect();
 
@@ -81,15 +94,49 @@ pub async fn list_members(
     })))
 }
 
+/// GET /api/tenant/{tenant_id}/presence — presence of every member, for
+/// clients to start from before following `presence:update`.
+pub async fn tenant_presence(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<MemberPresenceResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let member_ids = state.tenants.find_member_user_ids(tid).await?;
+    let mut presence = crate::presence::visible_presence(&state, auth.user_id, &member_ids).await;
+    let mut activities = crate::presence::visible_activities(&state, auth.user_id).await;
+    let items = member_ids
+        .into_iter()
+        .map(|id| MemberPresenceResponse {
+            user_id: id.to_hex(),
+            presence: presence.remove(&id).unwrap_or_default(),
+            activity: activities.remove(&id).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
 pub async fn get_profile(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(user_id): Path<String>,
 ) -> Result<Json<ProfileResponse>, ApiError> {
     let uid = ObjectId::parse_str(&user_id)
         .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
 
     let user = state.users.base.find_by_id(uid).await?;
+    let presence = if user.privacy.hide_presence && uid != auth.user_id {
+        Presence::Offline
+    } else {
+        user.presence
+    };
 
     Ok(Json(ProfileResponse {
         id: user.id.unwrap().to_hex(),
@@ -97,7 +144,7 @@ pub async fn get_profile(
         display_name: user.display_name,
         avatar: user.avatar,
         bio: user.bio,
-        presence: format!("{:?}", user.presence).to_lowercase(),
+        presence: format!("{:?}", presence).to_lowercase(),
         created_at: user.created_at.try_to_rfc3339_string().unwrap_or_default(),
     }))
 }
@@ -0,0 +1,279 @@
+//! Camera effects a tenant's conference clients may offer: background blur
+//! and virtual backgrounds gated by plan, the capture resolution cap, and the
+//! background images tenant managers have approved. Clients receive the same
+//! data as `video_effects` in `media:router_capabilities`.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{
+    Tenant, VideoEffectOverrides, VideoEffects, VirtualBackground, actions, role::permissions,
+};
+use roomler_ai_services::assets;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{auth::AuthUser, client::ClientInfo},
+    state::AppState,
+};
+
+/// Largest image a virtual background may use.
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Most backgrounds a tenant can approve.
+const MAX_BACKGROUNDS: usize = 50;
+
+/// Longest background name.
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Serialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackgroundResponse {
+    pub id: String,
+    pub name: String,
+    pub image_url: String,
+    pub creator_id: String,
+    pub created_at: String,
+}
+
+/// What clients receive as `video_effects` in `media:router_capabilities`.
+#[derive(Debug, Serialize)]
+pub struct VideoCapabilities {
+    pub background_blur: bool,
+    pub virtual_backgrounds: bool,
+    /// The effective media constraints' video cap.
+    pub max_resolution: Resolution,
+    /// Approved images; empty while virtual backgrounds are off.
+    pub backgrounds: Vec<BackgroundResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VideoEffectsResponse {
+    pub effective: VideoCapabilities,
+    /// The plan's effects before tenant overrides.
+    pub plan_defaults: VideoEffects,
+    pub overrides: VideoEffectOverrides,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBackgroundRequest {
+    pub name: String,
+    /// An image uploaded to the tenant.
+    pub file_id: String,
+}
+
+fn to_response(state: &AppState, tenant: &Tenant, b: &VirtualBackground) -> BackgroundResponse {
+    BackgroundResponse {
+        id: b.id.to_hex(),
+        name: b.name.clone(),
+        image_url: super::asset::for_tenant(state, tenant, &b.image_url),
+        creator_id: b.creator_id.to_hex(),
+        created_at: b.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// The camera effects `tenant`'s clients may offer.
+pub(crate) fn capabilities(state: &AppState, tenant: &Tenant) -> VideoCapabilities {
+    let effects = tenant.video_effects();
+    let constraints = tenant.media_constraints();
+    let backgrounds = if effects.virtual_backgrounds {
+        tenant
+            .settings
+            .virtual_backgrounds
+            .iter()
+            .map(|b| to_response(state, tenant, b))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    VideoCapabilities {
+        background_blur: effects.background_blur,
+        virtual_backgrounds: effects.virtual_backgrounds,
+        max_resolution: Resolution {
+            width: constraints.max_video_width,
+            height: constraints.max_video_height,
+        },
+        backgrounds,
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/video-effects
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<VideoEffectsResponse>, ApiError> {
+    let tid = parse_tenant_id(&tenant_id)?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    Ok(Json(load(&state, tid).await?))
+}
+
+/// PUT /api/tenant/{tenant_id}/video-effects — replace the tenant's
+/// overrides. Omitted fields fall back to the plan default.
+pub async fn set(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<VideoEffectOverrides>,
+) -> Result<Json<VideoEffectsResponse>, ApiError> {
+    let tid = parse_tenant_id(&tenant_id)?;
+    require_manager(&state, tid, &auth).await?;
+    state.tenants.set_video_effect_overrides(tid, &body).await?;
+    Ok(Json(load(&state, tid).await?))
+}
+
+/// POST /api/tenant/{tenant_id}/video-effects/background
+pub async fn create_background(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateBackgroundRequest>,
+) -> Result<(StatusCode, Json<BackgroundResponse>), ApiError> {
+    let tid = parse_tenant_id(&tenant_id)?;
+    require_manager(&state, tid, &auth).await?;
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+
+    if !tenant.plan.video_effects().virtual_backgrounds {
+        return Err(ApiError::Forbidden(
+            "Virtual backgrounds are not included in the tenant's plan".to_string(),
+        ));
+    }
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(ApiError::Validation(format!(
+            "Background names are 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    if tenant.settings.virtual_backgrounds.len() >= MAX_BACKGROUNDS {
+        return Err(ApiError::Validation(format!(
+            "A tenant can have at most {MAX_BACKGROUNDS} backgrounds"
+        )));
+    }
+
+    let fid = ObjectId::parse_str(&body.file_id)
+        .map_err(|_| ApiError::BadRequest("Invalid file_id".to_string()))?;
+    let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    if file.deleted_at.is_some() {
+        return Err(ApiError::NotFound("File not found".to_string()));
+    }
+    let checksum = match &file.checksum {
+        Some(checksum) if assets::is_servable(&file.content_type) => checksum,
+        _ => {
+            return Err(ApiError::Validation(
+                "Backgrounds must be images".to_string(),
+            ));
+        }
+    };
+    if file.size > MAX_IMAGE_BYTES {
+        return Err(ApiError::Validation(format!(
+            "Background images can be at most {} MB",
+            MAX_IMAGE_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let background = VirtualBackground {
+        id: ObjectId::new(),
+        name: name.to_string(),
+        image_url: assets::path(tid, checksum),
+        file_id: fid,
+        creator_id: auth.user_id,
+        created_at: DateTime::now(),
+    };
+    state
+        .tenants
+        .add_virtual_background(tid, &background)
+        .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::VIRTUAL_BACKGROUND_CREATE,
+        Some(background.id),
+        vec![audit::change(
+            "name",
+            None,
+            Some(background.name.clone().into()),
+        )],
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(to_response(&state, &tenant, &background)),
+    ))
+}
+
+/// DELETE /api/tenant/{tenant_id}/video-effects/background/{background_id}
+pub async fn delete_background(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Path((tenant_id, background_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_tenant_id(&tenant_id)?;
+    require_manager(&state, tid, &auth).await?;
+    let id = ObjectId::parse_str(&background_id)
+        .map_err(|_| ApiError::BadRequest("Invalid background_id".to_string()))?;
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    let existing = tenant
+        .settings
+        .virtual_backgrounds
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| ApiError::NotFound("Background not found".to_string()))?;
+
+    state.tenants.remove_virtual_background(tid, id).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::VIRTUAL_BACKGROUND_DELETE,
+        Some(id),
+        vec![audit::change("name", Some(existing.name.into()), None)],
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+async fn load(state: &AppState, tid: ObjectId) -> Result<VideoEffectsResponse, ApiError> {
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(VideoEffectsResponse {
+        effective: capabilities(state, &tenant),
+        plan_defaults: tenant.plan.video_effects(),
+        overrides: tenant.settings.video_effects,
+    })
+}
+
+fn parse_tenant_id(tenant_id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))
+}
+
+async fn require_manager(state: &AppState, tid: ObjectId, auth: &AuthUser) -> Result<(), ApiError> {
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(())
+}
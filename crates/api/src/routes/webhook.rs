@@ -0,0 +1,329 @@
+//! Tenant outgoing webhooks and their delivery logs. Everything here
+//! requires MANAGE_TENANT; events are sent by [`crate::webhooks`].
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use bson::{DateTime, Document, oid::ObjectId};
+use roomler_ai_db::models::{DeliveryStatus, Webhook, WebhookDelivery, actions, role::permissions};
+use roomler_ai_services::{dao::base::PaginationParams, webhooks};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit,
+    error::ApiError,
+    extractors::{client::ClientInfo, permission::RequirePermission},
+    state::AppState,
+};
+
+type ManageTenant = RequirePermission<{ permissions::MANAGE_TENANT }>;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub enabled: bool,
+    /// Key for verifying `X-Roomler-Signature`.
+    pub secret: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub next_attempt_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(w: Webhook) -> Self {
+        Self {
+            id: w.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: w.name,
+            url: w.url,
+            events: w.events,
+            enabled: w.enabled,
+            secret: w.secret,
+            created_by: w.created_by.to_hex(),
+            created_at: w.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(d: WebhookDelivery) -> Self {
+        Self {
+            id: d.id.map(|id| id.to_hex()).unwrap_or_default(),
+            event: d.event,
+            payload: serde_json::from_str(&d.payload).unwrap_or(serde_json::Value::Null),
+            status: d.status,
+            attempts: d.attempts,
+            response_status: d.response_status,
+            error: d.error,
+            next_attempt_at: d
+                .next_attempt_at
+                .and_then(|t| t.try_to_rfc3339_string().ok()),
+            created_at: d.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            updated_at: d.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/webhook — oldest first.
+pub async fn list(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+) -> Result<Json<Vec<WebhookResponse>>, ApiError> {
+    let webhooks = state.webhooks.find_for_tenant(tid).await?;
+    Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/webhook
+pub async fn create(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), ApiError> {
+    let max_webhooks = state.settings.webhooks.max_webhooks_per_tenant;
+    if state.webhooks.count_for_tenant(tid).await? >= u64::from(max_webhooks) {
+        return Err(ApiError::Validation(format!(
+            "A tenant can have at most {max_webhooks} webhooks"
+        )));
+    }
+    let name = body.name.trim().to_string();
+    let events = dedup(body.events);
+    webhooks::validate(&name, &body.url, &events).map_err(ApiError::Validation)?;
+    state
+        .outbound
+        .check_public_url(&body.url)
+        .await
+        .map_err(ApiError::Validation)?;
+
+    let now = DateTime::now();
+    let webhook = state
+        .webhooks
+        .create(&Webhook {
+            id: None,
+            tenant_id: tid,
+            name,
+            url: body.url,
+            events,
+            enabled: body.enabled,
+            secret: nanoid::nanoid!(32),
+            created_by: auth.user_id,
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::WEBHOOK_CREATE,
+        webhook.id,
+        vec![
+            audit::change("name", None, Some(webhook.name.clone().into())),
+            audit::change("url", None, Some(webhook.url.clone().into())),
+        ],
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(webhook.into())))
+}
+
+/// PUT /api/tenant/{tenant_id}/webhook/{webhook_id} — omitted fields are
+/// kept.
+pub async fn update(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, webhook_id)): Path<(String, String)>,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, ApiError> {
+    let id = parse_webhook(&webhook_id)?;
+    let webhook = state.webhooks.base.find_by_id_in_tenant(tid, id).await?;
+
+    let name = body
+        .name
+        .map(|n| n.trim().to_string())
+        .unwrap_or_else(|| webhook.name.clone());
+    let url = body.url.unwrap_or_else(|| webhook.url.clone());
+    let events = body
+        .events
+        .map(dedup)
+        .unwrap_or_else(|| webhook.events.clone());
+    webhooks::validate(&name, &url, &events).map_err(ApiError::Validation)?;
+    if url != webhook.url {
+        state
+            .outbound
+            .check_public_url(&url)
+            .await
+            .map_err(ApiError::Validation)?;
+    }
+
+    let mut set = Document::new();
+    let mut changes = Vec::new();
+    if name != webhook.name {
+        changes.push(audit::change(
+            "name",
+            Some(webhook.name.clone().into()),
+            Some(name.clone().into()),
+        ));
+        set.insert("name", name);
+    }
+    if url != webhook.url {
+        changes.push(audit::change(
+            "url",
+            Some(webhook.url.clone().into()),
+            Some(url.clone().into()),
+        ));
+        set.insert("url", url);
+    }
+    if events != webhook.events {
+        changes.push(audit::change(
+            "events",
+            Some(webhook.events.clone().into()),
+            Some(events.clone().into()),
+        ));
+        set.insert("events", events);
+    }
+    if let Some(enabled) = body.enabled
+        && enabled != webhook.enabled
+    {
+        changes.push(audit::change(
+            "enabled",
+            Some(webhook.enabled.into()),
+            Some(enabled.into()),
+        ));
+        set.insert("enabled", enabled);
+    }
+    if !set.is_empty() {
+        state.webhooks.update(tid, id, set).await?;
+        audit::record(
+            &state,
+            tid,
+            auth.user_id,
+            &client,
+            actions::WEBHOOK_UPDATE,
+            Some(id),
+            changes,
+        )
+        .await;
+    }
+
+    let webhook = state.webhooks.base.find_by_id(id).await?;
+    Ok(Json(webhook.into()))
+}
+
+/// DELETE /api/tenant/{tenant_id}/webhook/{webhook_id} — with its delivery
+/// log.
+pub async fn delete(
+    State(state): State<AppState>,
+    ManageTenant {
+        auth,
+        tenant_id: tid,
+        ..
+    }: ManageTenant,
+    client: ClientInfo,
+    Path((_, webhook_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let id = parse_webhook(&webhook_id)?;
+    let webhook = state.webhooks.base.find_by_id_in_tenant(tid, id).await?;
+
+    state.webhooks.delete(tid, id).await?;
+    audit::record(
+        &state,
+        tid,
+        auth.user_id,
+        &client,
+        actions::WEBHOOK_DELETE,
+        Some(id),
+        vec![audit::change("name", Some(webhook.name.into()), None)],
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// GET /api/tenant/{tenant_id}/webhook/{webhook_id}/delivery — newest
+/// first.
+pub async fn deliveries(
+    State(state): State<AppState>,
+    ManageTenant { tenant_id: tid, .. }: ManageTenant,
+    Path((_, webhook_id)): Path<(String, String)>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let id = parse_webhook(&webhook_id)?;
+    state.webhooks.base.find_by_id_in_tenant(tid, id).await?;
+
+    let result = state.webhooks.find_deliveries(tid, id, &params).await?;
+    let items: Vec<WebhookDeliveryResponse> = result.items.into_iter().map(Into::into).collect();
+
+    Ok(Json(serde_json::json!({
+        "items": items,
+        "total": result.total,
+        "page": result.page,
+        "per_page": result.per_page,
+        "total_pages": result.total_pages,
+    })))
+}
+
+/// Events in the order given, each once.
+fn dedup(events: Vec<String>) -> Vec<String> {
+    let mut unique = Vec::with_capacity(events.len());
+    for event in events {
+        let event = event.trim().to_string();
+        if !unique.contains(&event) {
+            unique.push(event);
+        }
+    }
+    unique
+}
+
+fn parse_webhook(webhook_id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(webhook_id)
+        .map_err(|_| ApiError::BadRequest("Invalid webhook_id".to_string()))
+}
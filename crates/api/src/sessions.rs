@@ -0,0 +1,91 @@
+//! Per-user caps on concurrent WebSocket sessions.
+//!
+//! A user can hold as many connections at once as the most generous plan
+//! among their tenants allows (`max_sessions_per_user`), counted per
+//! instance. A connection past the limit is handled by
+//! `ws.session_limit_policy`: `evict_oldest` closes the user's oldest
+//! connections with `session:evicted`, `reject` closes the new one with
+//! `session:rejected`. Either way the closed connection is told why and
+//! closed with [`SESSION_LIMIT_CLOSE_CODE`].
+
+use axum::extract::ws::{CloseFrame, Message};
+use bson::oid::ObjectId;
+use futures::SinkExt;
+use roomler_ai_config::SessionLimitPolicy;
+use roomler_ai_db::models::Plan;
+use tracing::{info, warn};
+
+use crate::{state::AppState, ws::dispatcher};
+
+/// Close code for connections closed by the session limit.
+pub const SESSION_LIMIT_CLOSE_CODE: u16 = 4002;
+
+/// How many connections `user_id` can hold at once.
+pub async fn limit(state: &AppState, user_id: ObjectId) -> u32 {
+    let plans = match state.tenants.find_user_tenants(user_id).await {
+        Ok(tenants) => tenants.into_iter().map(|t| t.plan).collect(),
+        Err(e) => {
+            warn!(%e, ?user_id, "Session limit: tenant lookup failed");
+            Vec::new()
+        }
+    };
+    plans
+        .iter()
+        .map(|plan| plan.limits().max_sessions_per_user)
+        .max()
+        .unwrap_or_else(|| Plan::Free.limits().max_sessions_per_user)
+}
+
+/// Apply the limit after `connection_id` was added for `user_id`. Returns
+/// `false` when the new connection was rejected; it has been closed and
+/// the caller must drop it.
+pub async fn enforce(state: &AppState, user_id: ObjectId, connection_id: &str) -> bool {
+    let connections = state.ws_storage.user_connections(&user_id);
+    let limit = limit(state, user_id).await;
+    let excess = connections.len().saturating_sub(limit as usize);
+    if excess == 0 {
+        return true;
+    }
+
+    match state.settings.ws.session_limit_policy {
+        SessionLimitPolicy::Reject => {
+            info!(?user_id, %connection_id, limit, "Session limit: new connection rejected");
+            let message = format!(
+                "Your plan allows {limit} sessions at once. Close another tab or device and try again."
+            );
+            close(state, connection_id, "session:rejected", limit, &message).await;
+            false
+        }
+        SessionLimitPolicy::EvictOldest => {
+            let message =
+                format!("Signed out here because you opened more than {limit} sessions at once.");
+            for (id, _) in connections
+                .iter()
+                .filter(|(id, _)| id != connection_id)
+                .take(excess)
+            {
+                info!(?user_id, connection_id = %id, limit, "Session limit: oldest connection evicted");
+                close(state, id, "session:evicted", limit, &message).await;
+            }
+            true
+        }
+    }
+}
+
+async fn close(state: &AppState, connection_id: &str, event_type: &str, limit: u32, message: &str) {
+    let event = serde_json::json!({
+        "type": event_type,
+        "data": { "limit": limit, "message": message },
+    });
+    dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    if let Some(sender) = state.ws_storage.get_sender_by_connection(connection_id) {
+        let _ = sender
+            .lock()
+            .await
+            .send(Message::Close(Some(CloseFrame {
+                code: SESSION_LIMIT_CLOSE_CODE,
+                reason: "Session limit reached".into(),
+            })))
+            .await;
+    }
+}
@@ -0,0 +1,261 @@
+//! Shared drafts: announcements several members of a room write together.
+//!
+//! Drafts are created, listed, published and discarded over REST (see
+//! [`crate::routes::shared_draft`]); the editing happens on the WebSocket.
+//! An editor sends `draft:join` and gets `draft:state` with the content and
+//! version, then sends one `draft:op` per edit with the version it was made
+//! against. The server rebases it (see [`roomler_ai_services::shared_drafts`]),
+//! answers `draft:ack` with the new version and the ops as applied, and
+//! sends the other editors `draft:op`. `draft:presence` lists the editors
+//! whenever someone joins or leaves, and `draft:closed` tells them the
+//! draft was published or discarded. Failures come back as `draft:error`.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{ChannelRole, DraftOp, DraftRevision, Room, SharedDraft};
+use roomler_ai_services::shared_drafts;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Tries at applying an op before giving up on a busy draft.
+const MAX_APPLY_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Serialize)]
+pub struct DraftResponse {
+    pub id: String,
+    pub room_id: String,
+    pub created_by: String,
+    pub content: String,
+    pub version: u64,
+    /// Users with the draft open.
+    pub editor_ids: Vec<String>,
+    pub published_message_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+pub fn to_response(state: &AppState, draft: SharedDraft) -> DraftResponse {
+    let editor_ids = draft
+        .id
+        .map(|id| editor_ids(state, &id))
+        .unwrap_or_default();
+    DraftResponse {
+        id: draft.id.map(|id| id.to_hex()).unwrap_or_default(),
+        room_id: draft.room_id.to_hex(),
+        created_by: draft.created_by.to_hex(),
+        content: draft.content,
+        version: draft.version,
+        editor_ids,
+        published_message_id: draft.published_message_id.map(|id| id.to_hex()),
+        created_at: draft.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        updated_at: draft.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// Anyone who may post in the room may write its drafts.
+pub(crate) async fn require_editor(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(Room, ChannelRole), ApiError> {
+    if !state.tenants.is_member(tenant_id, user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let room = crate::routes::room::visible_room(state, tenant_id, room_id, user_id).await?;
+    let role = crate::routes::room::require_channel_action(
+        state,
+        tenant_id,
+        &room,
+        user_id,
+        crate::routes::room::posting_action(&room),
+    )
+    .await?;
+    Ok((room, role))
+}
+
+/// `draft:join` — open a draft for editing.
+pub async fn join(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let draft = find_open(state, data).await?;
+    let draft_id = draft.id.ok_or_else(|| "Draft not found".to_string())?;
+    require_editor(state, draft.tenant_id, draft.room_id, user_id)
+        .await
+        .map_err(|_| "Draft not found".to_string())?;
+
+    let joined = state.draft_editors.join(draft_id, connection_id, user_id);
+    let event = serde_json::json!({
+        "type": "draft:state",
+        "data": to_response(state, draft),
+    });
+    crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    if joined {
+        send_presence(state, draft_id, Some(connection_id)).await;
+    }
+    Ok(())
+}
+
+/// `draft:leave`
+pub async fn leave(state: &AppState, connection_id: &str, data: Option<&Value>) {
+    if let Ok(draft_id) = draft_id(data)
+        && state.draft_editors.leave(&draft_id, connection_id)
+    {
+        send_presence(state, draft_id, None).await;
+    }
+}
+
+/// `draft:op` — one edit, made against `version`.
+pub async fn apply_op(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    data: Option<&Value>,
+) -> Result<(), String> {
+    let draft_id = draft_id(data)?;
+    if !state.draft_editors.is_editing(&draft_id, connection_id) {
+        return Err("Join the draft first".to_string());
+    }
+    let version = data
+        .and_then(|d| d.get("version"))
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Invalid version".to_string())?;
+    let op: DraftOp = data
+        .and_then(|d| d.get("op"))
+        .and_then(|op| serde_json::from_value(op.clone()).ok())
+        .ok_or_else(|| "Invalid op".to_string())?;
+
+    for _ in 0..MAX_APPLY_ATTEMPTS {
+        let draft = state
+            .shared_drafts
+            .base
+            .find_by_id(draft_id)
+            .await
+            .map_err(|_| "Draft not found".to_string())?;
+        if draft.published_at.is_some() {
+            return Err("The draft was published".to_string());
+        }
+        let ops = shared_drafts::rebase(op.clone(), version, draft.version, &draft.history)?;
+        let content = shared_drafts::apply(&draft.content, &ops)?;
+        let revision = DraftRevision {
+            version: draft.version + 1,
+            user_id,
+            ops,
+        };
+        // Another edit may have landed since we read the draft; rebase
+        // onto it and try again.
+        if !state
+            .shared_drafts
+            .apply(draft_id, content, &revision)
+            .await
+            .map_err(|e| format!("Failed to save edit: {}", e))?
+        {
+            continue;
+        }
+
+        let data = serde_json::json!({
+            "draft_id": draft_id.to_hex(),
+            "version": revision.version,
+            "user_id": user_id.to_hex(),
+            "ops": revision.ops,
+        });
+        let ack = serde_json::json!({ "type": "draft:ack", "data": &data });
+        crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, &ack).await;
+        let event = serde_json::json!({ "type": "draft:op", "data": &data });
+        send_to_editors(state, draft_id, Some(connection_id), &event).await;
+        return Ok(());
+    }
+    Err("The draft is busy; try again".to_string())
+}
+
+/// A connection closed: it leaves every draft it had open.
+pub async fn disconnected(state: &AppState, connection_id: &str) {
+    for draft_id in state.draft_editors.disconnect(connection_id) {
+        send_presence(state, draft_id, None).await;
+    }
+}
+
+/// Tell the draft's editors it was published as `message_id`, or
+/// discarded, and stop tracking them.
+pub async fn closed(state: &AppState, draft_id: ObjectId, message_id: Option<ObjectId>) {
+    let event = serde_json::json!({
+        "type": "draft:closed",
+        "data": {
+            "draft_id": draft_id.to_hex(),
+            "reason": if message_id.is_some() { "published" } else { "discarded" },
+            "message_id": message_id.map(|id| id.to_hex()),
+        }
+    });
+    for conn_id in state.draft_editors.close(&draft_id) {
+        crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+}
+
+pub async fn send_error(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&Value>,
+    message: &str,
+) {
+    let event = serde_json::json!({
+        "type": "draft:error",
+        "data": {
+            "draft_id": data.and_then(|d| d.get("draft_id")),
+            "message": message,
+        }
+    });
+    crate::ws::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+}
+
+fn editor_ids(state: &AppState, draft_id: &ObjectId) -> Vec<String> {
+    state
+        .draft_editors
+        .user_ids(draft_id)
+        .iter()
+        .map(|id| id.to_hex())
+        .collect()
+}
+
+/// `draft:presence` to the draft's editors, except `skip`'s own
+/// connection, which learns the editors from `draft:state`.
+async fn send_presence(state: &AppState, draft_id: ObjectId, skip: Option<&str>) {
+    let event = serde_json::json!({
+        "type": "draft:presence",
+        "data": {
+            "draft_id": draft_id.to_hex(),
+            "editor_ids": editor_ids(state, &draft_id),
+        }
+    });
+    send_to_editors(state, draft_id, skip, &event).await;
+}
+
+async fn send_to_editors(state: &AppState, draft_id: ObjectId, skip: Option<&str>, event: &Value) {
+    for conn_id in state.draft_editors.connection_ids(&draft_id) {
+        if Some(conn_id.as_str()) != skip {
+            crate::ws::dispatcher::send_to_connection(&state.ws_storage, &conn_id, event).await;
+        }
+    }
+}
+
+fn draft_id(data: Option<&Value>) -> Result<ObjectId, String> {
+    data.and_then(|d| d.get("draft_id"))
+        .and_then(|d| d.as_str())
+        .and_then(|d| ObjectId::parse_str(d).ok())
+        .ok_or_else(|| "Invalid draft_id".to_string())
+}
+
+async fn find_open(state: &AppState, data: Option<&Value>) -> Result<SharedDraft, String> {
+    let draft_id = draft_id(data)?;
+    state
+        .shared_drafts
+        .base
+        .find_by_id(draft_id)
+        .await
+        .ok()
+        .filter(|d| d.published_at.is_none())
+        .ok_or_else(|| "Draft not found".to_string())
+}
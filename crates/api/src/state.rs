@@ -1,21 +1,53 @@
 use mongodb::Database;
 use roomler_ai_config::Settings;
+use roomler_ai_db::indexes::IndexBuilder;
 use roomler_ai_remote_control::{Hub, audit::AuditSink, turn_creds::TurnConfig};
 use roomler_ai_services::{
-    AuthService, EmailService, GiphyService, OAuthService, PushService, RecognitionService,
-    TaskService,
+    AuthService, EmailService, FeatureFlagService, GiphyService, OAuthService, ObjectStore,
+    OnboardingService, PushService, RecognitionService, RecordingUploadService, TaskService,
+    TenantConfigService, TranscriptionService,
+    account_deletion::DeletedAccounts,
+    auth::internal::InternalAuthService,
+    conference_lobby::ConferenceLobby,
+    conference_waitlist::ConferenceWaitlist,
     dao::{
-        activation_code::ActivationCodeDao, agent::AgentDao, file::FileDao, invite::InviteDao,
-        message::MessageDao, notification::NotificationDao, push_subscription::PushSubscriptionDao,
-        reaction::ReactionDao, recording::RecordingDao, remote_audit::RemoteAuditDao,
-        remote_session::RemoteSessionDao, role::RoleDao, room::RoomDao, tenant::TenantDao,
-        user::UserDao,
+        activation_code::ActivationCodeDao, agent::AgentDao, analytics::AnalyticsDao,
+        asr_metric::AsrMetricDao, audit_log::AuditLogDao, bot_token::BotTokenDao,
+        conference_event::ConferenceEventDao, conference_poll::ConferencePollDao,
+        custom_emoji::CustomEmojiDao, file::FileDao, follow_up::FollowUpDao, invite::InviteDao,
+        message::MessageDao, moderation_event::ModerationEventDao, notification::NotificationDao,
+        preflight_report::PreflightReportDao, push_subscription::PushSubscriptionDao,
+        reaction::ReactionDao, reaction_rule::ReactionRuleDao, read_state::ReadStateDao,
+        recording::RecordingDao, relay_usage::RelayUsageDao, remote_audit::RemoteAuditDao,
+        remote_session::RemoteSessionDao, role::RoleDao, room::RoomDao,
+        shared_draft::SharedDraftDao, tenant::TenantDao,
+        thread_subscription::ThreadSubscriptionDao, transcript::TranscriptDao, user::UserDao,
+        webhook::WebhookDao,
     },
-    media::{room_manager::RoomManager, worker_pool::WorkerPool},
+    export::limits::ExportSlots,
+    media::{
+        asr_quality::SampleRetention, room_manager::RoomManager, transcript_feed::TranscriptFeed,
+        worker_pool::WorkerPool,
+    },
+    message_retention::MessagePurger,
+    outbound::Outbound,
+    presence::PresenceTracker,
+    quick_switch::QuickSwitchCache,
+    reaction_rules::ReactionRuleEngine,
+    reconciliation,
+    sandbox::SandboxTenants,
+    shared_drafts::DraftEditors,
+    sso::SsoService,
+    webhooks::WebhookSender,
 };
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::api_quota::ApiUsage;
+use crate::extractors::client::TrustedProxies;
+use crate::middleware::rate_limit::RateLimiter;
+use crate::ws::metrics::DeliveryMetrics;
 use crate::ws::redis_pubsub::RedisPubSub;
 use crate::ws::storage::WsStorage;
 
@@ -23,28 +55,88 @@ use crate::ws::storage::WsStorage;
 pub struct AppState {
     pub db: Database,
     pub settings: Settings,
+    /// Index builds after startup; see [`roomler_ai_db::indexes`].
+    pub indexes: IndexBuilder,
     pub auth: Arc<AuthService>,
+    /// Signs and checks service tokens, with a key separate from `auth`'s.
+    pub internal_auth: Arc<InternalAuthService>,
     pub users: Arc<UserDao>,
+    /// Cached account deletions; see [`crate::account_deletion`].
+    pub deleted_accounts: Arc<DeletedAccounts>,
     pub activation_codes: Arc<ActivationCodeDao>,
     pub tenants: Arc<TenantDao>,
+    /// Cached sandbox flags; see [`crate::middleware::sandbox`].
+    pub sandbox_tenants: Arc<SandboxTenants>,
     pub rooms: Arc<RoomDao>,
     pub invites: Arc<InviteDao>,
     pub messages: Arc<MessageDao>,
+    pub read_states: Arc<ReadStateDao>,
+    /// Thread followers; see [`crate::routes::thread`].
+    pub thread_subscriptions: Arc<ThreadSubscriptionDao>,
+    /// Co-edited announcements; see [`crate::shared_drafts`].
+    pub shared_drafts: Arc<SharedDraftDao>,
+    pub draft_editors: Arc<DraftEditors>,
     pub notifications: Arc<NotificationDao>,
     pub reactions: Arc<ReactionDao>,
+    pub reaction_rules: Arc<ReactionRuleDao>,
+    /// Fires reaction rules; see [`crate::reaction_rules`].
+    pub reaction_rule_engine: Arc<ReactionRuleEngine>,
+    pub webhooks: Arc<WebhookDao>,
+    pub bot_tokens: Arc<BotTokenDao>,
+    /// Sends outgoing webhooks; see [`crate::webhooks`].
+    pub webhook_sender: Arc<WebhookSender>,
+    pub custom_emojis: Arc<CustomEmojiDao>,
     pub roles: Arc<RoleDao>,
     pub files: Arc<FileDao>,
+    pub object_store: Arc<ObjectStore>,
+    /// Applies message retention; see [`crate::message_retention`].
+    pub message_purger: Arc<MessagePurger>,
     pub recordings: Arc<RecordingDao>,
+    pub recording_uploads: Arc<RecordingUploadService>,
+    pub feature_flags: Arc<FeatureFlagService>,
+    pub onboarding: Arc<OnboardingService>,
+    pub tenant_config: Arc<TenantConfigService>,
 
     pub tasks: Arc<TaskService>,
+    /// Exports running per tenant; see [`crate::routes::export`].
+    pub export_slots: Arc<ExportSlots>,
     pub room_manager: Arc<RoomManager>,
+    /// Joiners queued for full conferences; see [`crate::conference_limits`].
+    pub conference_waitlist: Arc<ConferenceWaitlist>,
+    /// Joiners waiting for an organizer; see [`crate::conference_lobby`].
+    pub conference_lobby: Arc<ConferenceLobby>,
     pub ws_storage: Arc<WsStorage>,
+    /// Status of users connected here; see [`crate::presence`].
+    pub presence: Arc<PresenceTracker>,
+    /// Request budgets; see [`crate::middleware::rate_limit`].
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Bot API calls counted against daily quotas; see [`crate::api_quota`].
+    pub api_usage: Arc<ApiUsage>,
+    pub delivery_metrics: Arc<DeliveryMetrics>,
     pub recognition: RecognitionService,
+    pub transcription: TranscriptionService,
+    /// Live transcript segments; see [`crate::transcripts`].
+    pub transcript_feed: Arc<TranscriptFeed>,
+    pub transcripts: Arc<TranscriptDao>,
+    pub asr_metrics: Arc<AsrMetricDao>,
     pub oauth: Option<Arc<OAuthService>>,
     pub giphy: Option<Arc<GiphyService>>,
     pub email: Option<Arc<EmailService>>,
     pub push: Option<Arc<PushService>>,
     pub push_subscriptions: Arc<PushSubscriptionDao>,
+    pub preflight_reports: Arc<PreflightReportDao>,
+    pub conference_events: Arc<ConferenceEventDao>,
+    /// In-call polls; see [`crate::conference_polls`].
+    pub conference_polls: Arc<ConferencePollDao>,
+    pub follow_ups: Arc<FollowUpDao>,
+    /// Daily activity for analytics exports; see [`crate::analytics_reports`].
+    pub analytics: Arc<AnalyticsDao>,
+    /// What the profanity filter did; see [`crate::profanity`].
+    pub moderation_events: Arc<ModerationEventDao>,
+    /// Media relayed through TURN; see [`crate::relay_usage`].
+    pub relay_usage: Arc<RelayUsageDao>,
+    /// Admin actions; see [`crate::audit`].
+    pub audit_log: Arc<AuditLogDao>,
     pub redis_pubsub: Option<Arc<RedisPubSub>>,
 
     // Remote-control subsystem
@@ -58,33 +150,128 @@ pub struct AppState {
     /// per hour vs N-agents-each-once-per-cycle. See
     /// `routes::agent_release` for the lifecycle.
     pub latest_release_cache: Arc<crate::routes::agent_release::LatestReleaseCache>,
+    /// Per-user candidates for `/api/tenant/{id}/quick-switch`.
+    pub quick_switch: Arc<QuickSwitchCache>,
+    /// HTTP calls to third parties; see [`roomler_ai_services::outbound`].
+    pub outbound: Outbound,
+    /// Tenants' OpenID Connect sign-in; see [`crate::routes::sso`].
+    pub sso: Arc<SsoService>,
+    /// Proxies whose forwarding headers name the client; see
+    /// [`crate::extractors::client::client_ip`].
+    pub trusted_proxies: Arc<TrustedProxies>,
 }
 
 impl AppState {
     pub async fn new(db: Database, settings: Settings) -> anyhow::Result<Self> {
+        let indexes = IndexBuilder::new(db.clone(), settings.database.index_build);
         let auth = Arc::new(AuthService::new(settings.jwt.clone()));
+        let internal_auth = Arc::new(InternalAuthService::new(settings.internal_auth.clone()));
+        let outbound = Outbound::new(&settings.outbound);
+        let trusted_proxies = Arc::new(
+            TrustedProxies::parse(&settings.app.trusted_proxies).map_err(anyhow::Error::msg)?,
+        );
         let users = Arc::new(UserDao::new(&db));
         let activation_codes = Arc::new(ActivationCodeDao::new(&db));
         let tenants = Arc::new(TenantDao::new(&db));
         let rooms = Arc::new(RoomDao::new(&db));
         let invites = Arc::new(InviteDao::new(&db));
         let messages = Arc::new(MessageDao::new(&db));
+        let read_states = Arc::new(ReadStateDao::new(&db));
+        let thread_subscriptions = Arc::new(ThreadSubscriptionDao::new(&db));
+        let shared_drafts = Arc::new(SharedDraftDao::new(&db));
         let notifications = Arc::new(NotificationDao::new(&db));
         let reactions = Arc::new(ReactionDao::new(&db));
+        let reaction_rules = Arc::new(ReactionRuleDao::new(&db));
+        let reaction_rule_engine =
+            Arc::new(ReactionRuleEngine::new(&settings.reaction_rules, &outbound));
+        let webhooks = Arc::new(WebhookDao::new(&db));
+        let bot_tokens = Arc::new(BotTokenDao::new(&db));
+        let webhook_sender = Arc::new(WebhookSender::new(&settings.webhooks, &outbound));
+        let custom_emojis = Arc::new(CustomEmojiDao::new(&db));
         let roles = Arc::new(RoleDao::new(&db));
         let files = Arc::new(FileDao::new(&db));
+        let object_store = Arc::new(ObjectStore::new(
+            &db,
+            crate::routes::file::upload_dir(),
+            &settings.storage,
+            &settings.s3,
+        ));
+        let message_purger = Arc::new(MessagePurger::new(
+            &db,
+            object_store.clone(),
+            &settings.message_retention,
+        ));
         let recordings = Arc::new(RecordingDao::new(&db));
+        let recording_uploads = Arc::new(RecordingUploadService::new(
+            &db,
+            crate::routes::file::upload_dir(),
+        ));
+        let feature_flags = Arc::new(FeatureFlagService::new(&db, &settings.features));
+        let onboarding = Arc::new(OnboardingService::new(&db, &settings.onboarding));
+        let tenant_config = Arc::new(TenantConfigService::new(&db));
         let tasks = Arc::new(TaskService::new(&db));
+        let export_slots = Arc::new(ExportSlots::new(settings.export.max_concurrent_per_tenant));
 
         let worker_pool = Arc::new(WorkerPool::new(&settings.mediasoup).await?);
         let room_manager = Arc::new(RoomManager::new(worker_pool, &settings.mediasoup));
 
-        let ws_storage = Arc::new(WsStorage::new());
+        // Conferences left in_progress by a previous process reference media
+        // rooms that died with it.
+        match reconciliation::reconcile_conferences(
+            &rooms,
+            &room_manager,
+            Duration::from_secs(settings.reconciliation.recreate_within_secs),
+        )
+        .await
+        {
+            Ok(report) if !report.is_empty() => tracing::info!(
+                recreated = ?report.recreated,
+                ended = ?report.ended,
+                closed_sessions = report.closed_sessions,
+                "Reconciled conferences after restart"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Conference reconciliation failed: {}", e),
+        }
+
+        let ws_storage = Arc::new(WsStorage::new(&settings.ws));
+        let presence = Arc::new(PresenceTracker::new(&settings.presence));
         let recognition = RecognitionService::new(
             settings.claude.api_key.clone(),
             settings.claude.model.clone(),
             settings.claude.max_tokens,
+            &outbound,
         );
+        let transcription = TranscriptionService::new(&settings.asr, &outbound);
+        let transcript_feed = Arc::new(TranscriptFeed::new());
+        let transcripts = Arc::new(TranscriptDao::new(&db));
+        crate::transcripts::spawn(
+            &transcript_feed,
+            rooms.clone(),
+            transcripts.clone(),
+            room_manager.clone(),
+        );
+        crate::transcripts::spawn_delivery(
+            &transcript_feed,
+            ws_storage.clone(),
+            room_manager.clone(),
+        );
+        let asr_metrics = Arc::new(AsrMetricDao::new(&db));
+        if settings.asr.metrics_max_bytes > 0 {
+            match asr_metrics
+                .ensure_capped(settings.asr.metrics_max_bytes)
+                .await
+            {
+                Ok(()) => crate::transcripts::spawn_quality(
+                    &transcript_feed,
+                    rooms.clone(),
+                    users.clone(),
+                    asr_metrics.clone(),
+                    SampleRetention::new(&settings.asr),
+                ),
+                Err(e) => tracing::error!("Failed to create the ASR metrics collection: {}", e),
+            }
+        }
 
         let oauth = if !settings.oauth.google.client_id.is_empty()
             || !settings.oauth.facebook.client_id.is_empty()
@@ -92,22 +279,30 @@ impl AppState {
             || !settings.oauth.linkedin.client_id.is_empty()
             || !settings.oauth.microsoft.client_id.is_empty()
         {
-            Some(Arc::new(OAuthService::new(settings.oauth.clone())))
-        } else {
-            None
-        };
-
-        let email = if !settings.email.api_key.is_empty() {
-            Some(Arc::new(EmailService::new(
-                settings.email.api_key.clone(),
-                settings.email.from_email.clone(),
-                settings.email.from_name.clone(),
+            Some(Arc::new(OAuthService::new(
+                settings.oauth.clone(),
+                &outbound,
             )))
         } else {
             None
         };
 
+        let email = EmailService::from_settings(&settings.email, &outbound).map(Arc::new);
+        let sso = Arc::new(SsoService::new(
+            &settings.jwt.secret,
+            &settings.sso,
+            &outbound,
+        ));
+
         let push_subscriptions = Arc::new(PushSubscriptionDao::new(&db));
+        let preflight_reports = Arc::new(PreflightReportDao::new(&db));
+        let conference_events = Arc::new(ConferenceEventDao::new(&db));
+        let conference_polls = Arc::new(ConferencePollDao::new(&db));
+        let follow_ups = Arc::new(FollowUpDao::new(&db));
+        let analytics = Arc::new(AnalyticsDao::new(&db));
+        let moderation_events = Arc::new(ModerationEventDao::new(&db));
+        let relay_usage = Arc::new(RelayUsageDao::new(&db));
+        let audit_log = Arc::new(AuditLogDao::new(&db));
         let push = if !settings.push.vapid_private_key.is_empty() {
             match PushService::new(
                 &settings.push.vapid_private_key,
@@ -134,8 +329,13 @@ impl AppState {
             }
         };
 
+        let api_usage = Arc::new(ApiUsage::new(&settings.redis.url).await);
+
         let giphy = if !settings.giphy.api_key.is_empty() {
-            Some(Arc::new(GiphyService::new(settings.giphy.api_key.clone())))
+            Some(Arc::new(GiphyService::new(
+                settings.giphy.api_key.clone(),
+                &outbound,
+            )))
         } else {
             None
         };
@@ -148,38 +348,82 @@ impl AppState {
         let turn_cfg = build_turn_config(&settings.turn);
         let (audit_sink, _audit_handle) = AuditSink::spawn(db.clone());
         let rc_hub = Arc::new(Hub::new(audit_sink, turn_cfg));
+        let quick_switch = Arc::new(QuickSwitchCache::new(settings.quick_switch.cache_ttl_secs));
 
         Ok(Self {
             db,
             settings,
+            indexes,
             auth,
+            internal_auth,
             users,
             activation_codes,
             tenants,
+            sandbox_tenants: Arc::new(SandboxTenants::new()),
+            deleted_accounts: Arc::new(DeletedAccounts::new()),
             rooms,
             invites,
             messages,
+            read_states,
+            thread_subscriptions,
+            shared_drafts,
+            draft_editors: Arc::new(DraftEditors::new()),
             notifications,
             reactions,
+            reaction_rules,
+            reaction_rule_engine,
+            webhooks,
+            webhook_sender,
+            bot_tokens,
+            custom_emojis,
             roles,
             files,
+            object_store,
+            message_purger,
             recordings,
+            recording_uploads,
+            feature_flags,
+            onboarding,
+            tenant_config,
 
             tasks,
+            export_slots,
             room_manager,
+            conference_waitlist: Arc::new(ConferenceWaitlist::new()),
+            conference_lobby: Arc::new(ConferenceLobby::new()),
             ws_storage,
+            presence,
+            rate_limiter: Arc::new(RateLimiter::new()),
+            api_usage,
+            delivery_metrics: Arc::new(DeliveryMetrics::new()),
             recognition,
+            transcription,
+            transcript_feed,
+            transcripts,
+            asr_metrics,
             oauth,
             giphy,
             email,
             push,
             push_subscriptions,
+            preflight_reports,
+            conference_events,
+            conference_polls,
+            follow_ups,
+            analytics,
+            moderation_events,
+            relay_usage,
+            audit_log,
             redis_pubsub,
             agents,
             remote_sessions,
             remote_audit,
             rc_hub,
             latest_release_cache: crate::routes::agent_release::LatestReleaseCache::new(),
+            quick_switch,
+            outbound,
+            sso,
+            trusted_proxies,
         })
     }
 }
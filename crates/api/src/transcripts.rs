@@ -0,0 +1,226 @@
+//! Delivery and persistence of live call transcripts.
+//!
+//! Three tasks subscribe to the [`TranscriptFeed`]. [`spawn_delivery`] sends
+//! each segment as `media:transcript` to the call connections following its
+//! caption track; with private captions that is only the participants who
+//! opted in, while transcription itself keeps running. [`spawn`] writes
+//! every final segment to `transcript_segments`, from where
+//! `GET /api/tenant/{tenant_id}/room/{room_id}/call/transcript` serves
+//! them after the call. [`spawn_quality`] logs the quality measurements of
+//! final segments and keeps evaluation samples; see
+//! [`roomler_ai_services::media::asr_quality`]. A failed write is logged and
+//! the segment skipped. Delivery and persistence drop segments of a model
+//! that has stopped draining after a switch; see
+//! [`roomler_ai_services::media::transcription_session`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bson::oid::ObjectId;
+use roomler_ai_services::{
+    dao::{asr_metric::AsrMetricDao, room::RoomDao, transcript::TranscriptDao, user::UserDao},
+    media::{
+        asr_quality::SampleRetention,
+        room_manager::RoomManager,
+        transcript_feed::{TranscriptEvent, TranscriptFeed, TranscriptScope},
+    },
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::ws::{dispatcher, storage::WsStorage};
+
+/// Spawn the persistence task. It subscribes before returning, so nothing
+/// published afterwards is missed, and runs until the feed is dropped.
+pub fn spawn(
+    feed: &TranscriptFeed,
+    rooms: Arc<RoomDao>,
+    transcripts: Arc<TranscriptDao>,
+    room_manager: Arc<RoomManager>,
+) {
+    let mut rx = feed.subscribe();
+    tokio::spawn(async move {
+        let mut tenants: HashMap<ObjectId, ObjectId> = HashMap::new();
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Transcript persistence fell behind; segments lost");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if !event.is_final || !is_current(&room_manager, &event) {
+                continue;
+            }
+            let Some(tenant_id) = tenant_of(&mut tenants, &rooms, event.room_id).await else {
+                continue;
+            };
+            if let Err(e) = transcripts.record(tenant_id, &event).await {
+                warn!(%e, room_id = ?event.room_id, "Failed to persist transcript segment");
+            }
+        }
+    });
+}
+
+/// Spawn the quality task. Final segments on the `original` track that
+/// carry measurements are logged, once each, to the capped `asr_metrics`
+/// collection and as a structured `asr_quality` event; translated tracks
+/// repeat the same audio and are skipped. With `retention` enabled, the
+/// audio of a qualifying segment is kept in `asr_samples` when the speaker
+/// has agreed to share voice samples.
+pub fn spawn_quality(
+    feed: &TranscriptFeed,
+    rooms: Arc<RoomDao>,
+    users: Arc<UserDao>,
+    metrics: Arc<AsrMetricDao>,
+    retention: SampleRetention,
+) {
+    let mut rx = feed.subscribe();
+    tokio::spawn(async move {
+        let mut tenants: HashMap<ObjectId, ObjectId> = HashMap::new();
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "ASR quality logging fell behind; segments lost");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let Some(quality) = &event.quality else {
+                continue;
+            };
+            if !event.is_final || event.track != "original" {
+                continue;
+            }
+            info!(
+                target: "asr_quality",
+                room_id = %event.room_id,
+                user_id = %event.user_id,
+                language = event.language.as_deref().unwrap_or(""),
+                confidence = event.confidence,
+                rms_dbfs = quality.rms_dbfs,
+                vad_ms = quality.vad_ms,
+                language_score_delta = quality.language_score_delta,
+                "Transcript segment quality"
+            );
+            let Some(tenant_id) = tenant_of(&mut tenants, &rooms, event.room_id).await else {
+                continue;
+            };
+            if let Err(e) = metrics.record(tenant_id, &event, quality).await {
+                warn!(%e, room_id = ?event.room_id, "Failed to log ASR quality");
+            }
+
+            let Some(audio) = &quality.audio else {
+                continue;
+            };
+            if !retention.qualifies(event.confidence, audio.len()) {
+                continue;
+            }
+            let consented = match users.find_privacy(&[event.user_id]).await {
+                Ok(privacy) => privacy
+                    .get(&event.user_id)
+                    .is_some_and(|p| p.share_voice_samples),
+                Err(e) => {
+                    warn!(%e, "ASR samples: privacy lookup failed");
+                    false
+                }
+            };
+            if !consented {
+                continue;
+            }
+            if let Err(e) = metrics
+                .record_sample(tenant_id, &event, quality, audio)
+                .await
+            {
+                warn!(%e, room_id = ?event.room_id, "Failed to keep ASR sample");
+            }
+        }
+    });
+}
+
+/// Whether the model that produced `event` is still the room's, or the
+/// speaker's for speaker-scoped segments, or still draining after a switch.
+/// Untagged segments always are.
+fn is_current(room_manager: &RoomManager, event: &TranscriptEvent) -> bool {
+    event
+        .model
+        .as_deref()
+        .is_none_or(|model| match event.scope {
+            TranscriptScope::Room => room_manager.accepts_transcript(&event.room_id, model),
+            TranscriptScope::Speaker => {
+                room_manager.accepts_self_transcript(&event.room_id, &event.user_id, model)
+            }
+        })
+}
+
+/// The tenant of `room_id`, looked up once per room since rooms never
+/// change tenant. `None` for an unknown room.
+async fn tenant_of(
+    tenants: &mut HashMap<ObjectId, ObjectId>,
+    rooms: &RoomDao,
+    room_id: ObjectId,
+) -> Option<ObjectId> {
+    if let Some(tenant_id) = tenants.get(&room_id) {
+        return Some(*tenant_id);
+    }
+    match rooms.base.find_by_id(room_id).await {
+        Ok(room) => Some(*tenants.entry(room_id).or_insert(room.tenant_id)),
+        Err(e) => {
+            warn!(%e, ?room_id, "Transcript segment for unknown room");
+            None
+        }
+    }
+}
+
+/// Spawn the delivery task. Subscriptions are read per segment from the
+/// connections' current caption tracks, so a switch or opt-in applies to
+/// the very next segment.
+pub fn spawn_delivery(
+    feed: &TranscriptFeed,
+    ws_storage: Arc<WsStorage>,
+    room_manager: Arc<RoomManager>,
+) {
+    let mut rx = feed.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Transcript delivery fell behind; segments lost");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if !is_current(&room_manager, &event) {
+                continue;
+            }
+            let segment = serde_json::json!({
+                "type": "media:transcript",
+                "data": {
+                    "room_id": event.room_id.to_hex(),
+                    "track": &event.track,
+                    "user_id": event.user_id.to_hex(),
+                    "speaker_name": &event.speaker_name,
+                    "text": &event.text,
+                    "language": &event.language,
+                    "confidence": event.confidence,
+                    "start_time": event.start_time,
+                    "end_time": event.end_time,
+                    "is_final": event.is_final,
+                    "model": &event.model,
+                    "scope": event.scope,
+                }
+            });
+            dispatcher::send_caption_segment(
+                &ws_storage,
+                &room_manager,
+                &event.room_id,
+                &event.track,
+                &segment,
+            )
+            .await;
+        }
+    });
+}
@@ -0,0 +1,172 @@
+//! Tenants' outgoing webhooks; see [`roomler_ai_services::webhooks`].
+//! Handlers call [`dispatch`] once an event happened, which logs and sends
+//! a delivery per subscribed webhook in the background. [`spawn`] runs the
+//! sweep retrying failed deliveries as they come due.
+
+use std::time::Duration;
+
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{DeliveryStatus, Webhook, WebhookDelivery};
+use roomler_ai_services::{
+    dao::base::{DaoError, DaoResult},
+    webhooks,
+};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Retries sent per sweep; the rest wait for the next tick.
+const BATCH: u64 = 100;
+
+/// Send `event` with `data` to the tenant's webhooks subscribed to it.
+pub fn dispatch(
+    state: &AppState,
+    tenant_id: ObjectId,
+    event: &'static str,
+    data: serde_json::Value,
+) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = deliver_event(&state, tenant_id, event, data).await {
+            warn!(%e, %tenant_id, event, "Webhook dispatch failed");
+        }
+    });
+}
+
+async fn deliver_event(
+    state: &AppState,
+    tenant_id: ObjectId,
+    event: &'static str,
+    data: serde_json::Value,
+) -> DaoResult<()> {
+    let subscribed = state.webhooks.find_subscribed(tenant_id, event).await?;
+    if subscribed.is_empty() {
+        return Ok(());
+    }
+    let payload = serde_json::json!({
+        "event": event,
+        "tenant_id": tenant_id.to_hex(),
+        "created_at": DateTime::now().try_to_rfc3339_string().unwrap_or_default(),
+        "data": data,
+    })
+    .to_string();
+    for webhook in subscribed {
+        let Some(webhook_id) = webhook.id else {
+            continue;
+        };
+        let delivery = state
+            .webhooks
+            .create_delivery(
+                tenant_id,
+                webhook_id,
+                event,
+                payload.clone(),
+                lease_until(state),
+            )
+            .await?;
+        attempt(state, &webhook, delivery).await?;
+    }
+    Ok(())
+}
+
+/// Spawn the retry sweep. Runs for the lifetime of the process.
+pub fn spawn(state: AppState) {
+    let period = Duration::from_secs(state.settings.webhooks.retry_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match retry_due(&state).await {
+                Ok(0) => {}
+                Ok(retried) => info!(retried, "Retried webhook deliveries"),
+                Err(e) => warn!(%e, "Webhook retry sweep failed"),
+            }
+        }
+    });
+}
+
+/// Retry each pending delivery that is due. Returns how many were tried.
+pub async fn retry_due(state: &AppState) -> DaoResult<u64> {
+    let mut retried = 0;
+    while retried < BATCH {
+        let Some(delivery) = state
+            .webhooks
+            .claim_due(DateTime::now(), lease_until(state))
+            .await?
+        else {
+            break;
+        };
+        retried += 1;
+        match state.webhooks.base.find_by_id(delivery.webhook_id).await {
+            Ok(webhook) if webhook.enabled => attempt(state, &webhook, delivery).await?,
+            Ok(_) | Err(DaoError::NotFound) => {
+                let Some(id) = delivery.id else { continue };
+                state
+                    .webhooks
+                    .record_attempt(
+                        id,
+                        DeliveryStatus::Failed,
+                        delivery.attempts,
+                        delivery.response_status,
+                        Some("Webhook disabled or deleted".to_string()),
+                        None,
+                    )
+                    .await?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(retried)
+}
+
+/// Send `delivery` once and store the outcome: done on success, failed
+/// after the last attempt, otherwise due again after the backoff.
+async fn attempt(state: &AppState, webhook: &Webhook, delivery: WebhookDelivery) -> DaoResult<()> {
+    let Some(id) = delivery.id else { return Ok(()) };
+    let outcome = state
+        .webhook_sender
+        .send(
+            &webhook.url,
+            &webhook.secret,
+            &delivery.event,
+            &delivery.payload,
+        )
+        .await;
+    let attempts = delivery.attempts + 1;
+    let settings = &state.settings.webhooks;
+    let (status, next_attempt_at) = if outcome.succeeded() {
+        (DeliveryStatus::Succeeded, None)
+    } else if attempts >= settings.max_attempts {
+        (DeliveryStatus::Failed, None)
+    } else {
+        let wait = webhooks::backoff(settings.retry_base_secs, attempts);
+        (DeliveryStatus::Pending, Some(after(wait)))
+    };
+    if let Some(error) = &outcome.error {
+        warn!(webhook_id = %delivery.webhook_id, attempts, %error, "Webhook delivery failed");
+    }
+    state
+        .webhooks
+        .record_attempt(
+            id,
+            status,
+            attempts,
+            outcome.response_status,
+            outcome.error,
+            next_attempt_at,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Until when a delivery being sent is kept from the retry sweep: long
+/// enough for one attempt to time out.
+fn lease_until(state: &AppState) -> DateTime {
+    after(Duration::from_secs(
+        state.settings.webhooks.timeout_secs.max(1) * 2,
+    ))
+}
+
+fn after(wait: Duration) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + wait.as_millis() as i64)
+}
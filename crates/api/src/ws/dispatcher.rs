@@ -1,28 +1,82 @@
 use axum::extract::ws::Message;
 use bson::oid::ObjectId;
 use futures::SinkExt;
+use roomler_ai_db::models::PrivacyPrefs;
+use roomler_ai_services::dao::user::UserDao;
+use roomler_ai_services::media::room_manager::RoomManager;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, warn};
 
+use super::metrics::DeliveryMetrics;
 use super::redis_pubsub::RedisPubSub;
 use super::storage::WsStorage;
 
+/// Outcome of a local fan-out: connections written to and send failures.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FanoutResult {
+    pub delivered: u64,
+    pub failed: u64,
+}
+
+/// Events about a user that the user's privacy settings can withhold from
+/// everyone else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyScope {
+    ReadReceipts,
+    Typing,
+    Presence,
+}
+
+impl PrivacyScope {
+    pub fn hidden_by(self, prefs: &PrivacyPrefs) -> bool {
+        match self {
+            PrivacyScope::ReadReceipts => prefs.hide_read_receipts,
+            PrivacyScope::Typing => prefs.hide_typing,
+            PrivacyScope::Presence => prefs.hide_presence,
+        }
+    }
+}
+
+/// Whether `scope` events about `subject` may be sent to other users. Fails
+/// closed: when the settings can't be loaded the event is withheld.
+pub async fn privacy_allows(users: &UserDao, subject: ObjectId, scope: PrivacyScope) -> bool {
+    match users.find_privacy(&[subject]).await {
+        Ok(prefs) => !prefs.get(&subject).is_some_and(|p| scope.hidden_by(p)),
+        Err(e) => {
+            warn!(?subject, %e, "Privacy lookup failed; withholding event");
+            false
+        }
+    }
+}
+
 /// Broadcasts a JSON message to all connections of the specified users.
-pub async fn broadcast(ws_storage: &WsStorage, user_ids: &[ObjectId], message: &serde_json::Value) {
-    let text = serde_json::to_string(message).unwrap_or_default();
+/// Non-transient events carry the user's next `seq` and are kept for
+/// `resume`, including for users who are briefly disconnected.
+pub async fn broadcast(
+    ws_storage: &WsStorage,
+    user_ids: &[ObjectId],
+    message: &serde_json::Value,
+) -> FanoutResult {
+    let mut result = FanoutResult::default();
 
     for user_id in user_ids {
+        // Numbered per user, so each user's copy is serialized separately.
+        let text = ws_storage.stamp(user_id, message);
         let senders = ws_storage.get_senders(user_id);
         for sender in senders {
             let text = text.clone();
             let mut guard = sender.lock().await;
             if let Err(e) = guard.send(Message::text(text)).await {
                 warn!(?user_id, %e, "Failed to send WS message");
+                result.failed += 1;
             } else {
                 debug!(?user_id, "WS message sent");
+                result.delivered += 1;
             }
         }
     }
+    result
 }
 
 /// Sends a JSON message to a specific user's connections.
@@ -42,7 +96,15 @@ pub async fn broadcast_with_redis(
     // Local broadcast (same instance)
     broadcast(ws_storage, user_ids, message).await;
 
-    // Cross-instance broadcast via Redis Pub/Sub
+    publish_to_redis(redis_pubsub, user_ids, message).await;
+}
+
+/// Cross-instance broadcast via Redis Pub/Sub.
+async fn publish_to_redis(
+    redis_pubsub: &Option<Arc<RedisPubSub>>,
+    user_ids: &[ObjectId],
+    message: &serde_json::Value,
+) {
     if let Some(pubsub) = redis_pubsub {
         let envelope = serde_json::json!({
             "user_ids": user_ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>(),
@@ -54,6 +116,28 @@ pub async fn broadcast_with_redis(
     }
 }
 
+/// [`broadcast_with_redis`] for tenant-scoped events, additionally recording
+/// the local fan-out delay into the tenant's delivery metrics.
+pub async fn broadcast_in_tenant(
+    ws_storage: &WsStorage,
+    redis_pubsub: &Option<Arc<RedisPubSub>>,
+    metrics: &DeliveryMetrics,
+    tenant_id: ObjectId,
+    user_ids: &[ObjectId],
+    message: &serde_json::Value,
+) {
+    let started = Instant::now();
+    let result = broadcast(ws_storage, user_ids, message).await;
+    metrics.record_fanout(
+        tenant_id,
+        started.elapsed(),
+        result.delivered,
+        result.failed,
+    );
+
+    publish_to_redis(redis_pubsub, user_ids, message).await;
+}
+
 /// Sends a JSON message to a specific user locally AND via Redis for cross-instance delivery.
 pub async fn send_to_user_with_redis(
     ws_storage: &WsStorage,
@@ -64,6 +148,20 @@ pub async fn send_to_user_with_redis(
     broadcast_with_redis(ws_storage, redis_pubsub, &[*user_id], message).await;
 }
 
+/// Sends a `media:transcript` segment to the call connections following
+/// `track` in a room; connections on other caption tracks don't receive it.
+pub async fn send_caption_segment(
+    ws_storage: &WsStorage,
+    room_manager: &RoomManager,
+    room_id: &ObjectId,
+    track: &str,
+    segment: &serde_json::Value,
+) {
+    for conn_id in room_manager.caption_track_connection_ids(room_id, track) {
+        send_to_connection(ws_storage, &conn_id, segment).await;
+    }
+}
+
 /// Sends a JSON message to a specific connection by connection_id.
 /// Used for media signaling responses that should target a single tab/device.
 pub async fn send_to_connection(
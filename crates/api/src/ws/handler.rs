@@ -1,30 +1,43 @@
 use axum::{
     extract::{
         Query, State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        ws::{CloseFrame, Message, WebSocket},
     },
     response::Response,
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use bson::oid::ObjectId;
+use bson::{doc, oid::ObjectId};
 use futures::{SinkExt, StreamExt};
 use hmac::{Hmac, Mac};
 use mediasoup::prelude::*;
+use roomler_ai_db::models::{ConferenceEventType, Room};
+use roomler_ai_services::{
+    connection_auth::{AuthDeadline, ConnectionAuth},
+    media::{captions, simulcast, transcription_session::Handover},
+};
 use serde::Deserialize;
 use sha1::Sha1;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::state::AppState;
+use super::storage::ResumeOutcome;
+use crate::{conference_limits::Admission, extractors::client::ClientInfo, state::AppState};
+
+/// Close code for connections whose access token expired.
+const AUTH_EXPIRED_CLOSE_CODE: u16 = 4001;
+
+/// Longest `media:reaction` emoji, in characters (allows ZWJ sequences).
+const MAX_REACTION_CHARS: usize = 16;
 
 #[derive(Debug, Deserialize)]
 pub struct WsParams {
     pub token: String,
     /// Optional connection role. Defaults to `"user"` to preserve existing
-    /// browser behaviour. Set to `"agent"` by the native remote-control agent.
+    /// browser behaviour. Set to `"agent"` by the native remote-control agent
+    /// and to `"guest"` by conference guests.
     #[serde(default)]
     pub role: Option<String>,
 }
@@ -32,15 +45,22 @@ pub struct WsParams {
 pub async fn ws_upgrade(
     State(state): State<AppState>,
     Query(params): Query<WsParams>,
+    client: ClientInfo,
     ws: WebSocketUpgrade,
 ) -> Response {
     match params.role.as_deref() {
         Some("agent") => ws_upgrade_agent(state, params.token, ws),
-        _ => ws_upgrade_user(state, params.token, ws),
+        Some("guest") => ws_upgrade_guest(state, params.token, client, ws).await,
+        _ => ws_upgrade_user(state, params.token, client, ws).await,
     }
 }
 
-fn ws_upgrade_user(state: AppState, token: String, ws: WebSocketUpgrade) -> Response {
+async fn ws_upgrade_user(
+    state: AppState,
+    token: String,
+    client: ClientInfo,
+    ws: WebSocketUpgrade,
+) -> Response {
     let claims = match state.auth.verify_access_token(&token) {
         Ok(c) => c,
         Err(_) => {
@@ -60,9 +80,74 @@ fn ws_upgrade_user(state: AppState, token: String, ws: WebSocketUpgrade) -> Resp
                 .unwrap();
         }
     };
+    if state
+        .deleted_accounts
+        .is_deleted(&state.users, user_id)
+        .await
+        .unwrap_or(false)
+    {
+        return Response::builder()
+            .status(401)
+            .body("Account deleted".into())
+            .unwrap();
+    }
     let username = claims.username.clone();
+    let auth = ConnectionAuth::new(claims.exp);
+
+    ws.on_upgrade(move |socket| handle_socket(socket, state, user_id, username, auth, client, None))
+}
+
+/// A conference guest's connection, limited to that conference's media
+/// signaling. Refused once the organizers turn guest access off.
+async fn ws_upgrade_guest(
+    state: AppState,
+    token: String,
+    client: ClientInfo,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let claims = match state.auth.verify_guest_token(&token) {
+        Ok(c) => c,
+        Err(_) => {
+            return Response::builder()
+                .status(401)
+                .body("Unauthorized (guest)".into())
+                .unwrap();
+        }
+    };
+    let (Ok(guest_id), Ok(room_id)) = (
+        ObjectId::parse_str(&claims.sub),
+        ObjectId::parse_str(&claims.room_id),
+    ) else {
+        return Response::builder()
+            .status(400)
+            .body("Invalid guest token".into())
+            .unwrap();
+    };
+    let guests_allowed = state
+        .rooms
+        .base
+        .find_by_id(room_id)
+        .await
+        .is_ok_and(|room| room.conference().guest_link_key.is_some());
+    if !guests_allowed {
+        return Response::builder()
+            .status(403)
+            .body("Guest access is off".into())
+            .unwrap();
+    }
+    let auth = ConnectionAuth::new(claims.exp);
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, user_id, username))
+    ws.on_upgrade(move |socket| {
+        handle_socket(
+            socket,
+            state,
+            guest_id,
+            claims.name,
+            auth,
+            client,
+            Some(room_id),
+        )
+    })
 }
 
 fn ws_upgrade_agent(state: AppState, token: String, ws: WebSocketUpgrade) -> Response {
@@ -126,30 +211,59 @@ fn ws_upgrade_agent(state: AppState, token: String, ws: WebSocketUpgrade) -> Res
     })
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, username: String) {
+/// `guest_room` is set for conference guests, whose `user_id` is their
+/// guest id: they only signal that conference, and have no sessions,
+/// presence or remote control.
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    user_id: ObjectId,
+    mut username: String,
+    mut auth: ConnectionAuth,
+    client: ClientInfo,
+    guest_room: Option<ObjectId>,
+) {
     let connection_id = Uuid::new_v4().to_string();
-    info!(?user_id, %connection_id, "WebSocket connected");
+    info!(?user_id, %connection_id, guest = guest_room.is_some(), "WebSocket connected");
 
     let (sender, mut receiver) = socket.split();
     let sender = Arc::new(Mutex::new(sender));
 
     state
         .ws_storage
-        .add(user_id, connection_id.clone(), sender.clone());
-
-    // Register this tab with the remote-control Hub so `rc:*` replies find us.
-    // Each browser tab gets its own controller tx; the Hub routes by tx, not
-    // by user id, so multiple tabs don't cross signals.
-    let (rc_controller_tx, rc_controller_rx) = state.rc_hub.register_controller(user_id);
-    let rc_pump = tokio::spawn(crate::ws::remote_control::pump_server_messages(
-        rc_controller_rx,
-        sender.clone(),
-    ));
+        .add(user_id, connection_id.clone(), sender.clone(), client);
+    let mut rc = None;
+    if guest_room.is_none() {
+        if !crate::sessions::enforce(&state, user_id, &connection_id).await {
+            state.ws_storage.remove(&user_id, &connection_id, &sender);
+            return;
+        }
+        crate::presence::connected(&state, user_id).await;
+
+        // Register this tab with the remote-control Hub so `rc:*` replies find us.
+        // Each browser tab gets its own controller tx; the Hub routes by tx, not
+        // by user id, so multiple tabs don't cross signals.
+        let (rc_controller_tx, rc_controller_rx) = state.rc_hub.register_controller(user_id);
+        let rc_pump = tokio::spawn(crate::ws::remote_control::pump_server_messages(
+            rc_controller_rx,
+            sender.clone(),
+        ));
+        rc = Some((rc_controller_tx, rc_pump));
+    }
 
     {
+        // Where this user's event numbering stands; a client that reconnects
+        // sends these back in `resume` to get what it missed.
+        let (stream_id, seq) = state
+            .ws_storage
+            .stream_position(&user_id)
+            .unwrap_or_default();
         let msg = serde_json::json!({
             "type": "connected",
             "user_id": user_id.to_hex(),
+            "stream_id": stream_id,
+            "seq": seq,
+            "expires_at": auth.expires_at(),
         });
         let mut guard = sender.lock().await;
         let _ = guard
@@ -157,18 +271,70 @@ async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, us
             .await;
     }
 
-    while let Some(msg) = receiver.next().await {
+    let warn_before = state.settings.ws.auth_expiry_warning_secs;
+    loop {
+        let deadline = auth.next(warn_before);
+        let wait =
+            Duration::from_secs((deadline.at() - chrono::Utc::now().timestamp()).max(0) as u64);
+        let msg = tokio::select! {
+            msg = receiver.next() => msg,
+            _ = tokio::time::sleep(wait) => {
+                if let AuthDeadline::Warn(_) = deadline {
+                    auth.mark_warned();
+                    let event = serde_json::json!({
+                        "type": "auth:expiring",
+                        "data": { "expires_at": auth.expires_at() },
+                    });
+                    super::dispatcher::send_to_connection(&state.ws_storage, &connection_id, &event)
+                        .await;
+                    continue;
+                }
+                info!(?user_id, %connection_id, "WebSocket token expired");
+                let event = serde_json::json!({ "type": "auth:expired" });
+                super::dispatcher::send_to_connection(&state.ws_storage, &connection_id, &event)
+                    .await;
+                let _ = sender
+                    .lock()
+                    .await
+                    .send(Message::Close(Some(CloseFrame {
+                        code: AUTH_EXPIRED_CLOSE_CODE,
+                        reason: "Token expired".into(),
+                    })))
+                    .await;
+                break;
+            }
+        };
+        let Some(msg) = msg else { break };
         match msg {
             Ok(Message::Text(text)) => {
-                handle_client_message(
-                    &state,
-                    &user_id,
-                    &connection_id,
-                    &username,
-                    &rc_controller_tx,
-                    &text,
-                )
-                .await;
+                if let Some(room_id) = guest_room {
+                    handle_guest_message(&state, &user_id, room_id, &connection_id, &text).await;
+                    continue;
+                }
+                // Swaps this connection's token, so it isn't routed below.
+                if text.contains("\"auth:refresh\"")
+                    && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&text)
+                    && parsed["type"] == "auth:refresh"
+                {
+                    let token = parsed["data"]["token"].as_str();
+                    if let Some(name) =
+                        refresh_auth(&state, user_id, &connection_id, &mut auth, token).await
+                    {
+                        username = name;
+                    }
+                    continue;
+                }
+                if let Some((rc_controller_tx, _)) = &rc {
+                    handle_client_message(
+                        &state,
+                        &user_id,
+                        &connection_id,
+                        &username,
+                        rc_controller_tx,
+                        &text,
+                    )
+                    .await;
+                }
             }
             Ok(Message::Ping(data)) => {
                 let mut guard = sender.lock().await;
@@ -186,39 +352,281 @@ async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, us
     }
 
     // Cleanup
-    state
-        .rc_hub
-        .unregister_controller(user_id, &rc_controller_tx);
-    rc_pump.abort();
+    if let Some((rc_controller_tx, rc_pump)) = rc {
+        state
+            .rc_hub
+            .unregister_controller(user_id, &rc_controller_tx);
+        rc_pump.abort();
+    }
     state.ws_storage.remove(&user_id, &connection_id, &sender);
+    crate::shared_drafts::disconnected(&state, &connection_id).await;
 
     if let Some(room_id) = state.room_manager.get_connection_room(&connection_id) {
-        let remaining_conns = state
-            .room_manager
-            .get_other_connection_ids(&room_id, &connection_id);
-
-        state
-            .room_manager
-            .close_participant(&room_id, &connection_id);
-
-        if !remaining_conns.is_empty() {
-            let event = serde_json::json!({
-                "type": "media:peer_left",
-                "data": {
+        let grace = Duration::from_secs(state.settings.mediasoup.reconnect_grace_secs);
+        if !grace.is_zero()
+            && state
+                .room_manager
+                .mark_reconnecting(&room_id, &connection_id)
+        {
+            // Keep the media state for a `media:rejoin`; peers keep the tile.
+            notify_peers(
+                &state,
+                room_id,
+                &connection_id,
+                "media:peer_reconnecting",
+                serde_json::json!({
                     "user_id": user_id.to_hex(),
                     "connection_id": connection_id,
                     "room_id": room_id.to_hex(),
+                }),
+            )
+            .await;
+            let state = state.clone();
+            let connection_id = connection_id.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(grace).await;
+                if state.room_manager.is_reconnecting(&room_id, &connection_id) {
+                    drop_participant(&state, room_id, user_id, &connection_id).await;
+                    if guest_room.is_some() {
+                        guest_left(&state, room_id, user_id).await;
+                    }
                 }
             });
-            for conn_id in &remaining_conns {
-                super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
+        } else {
+            drop_participant(&state, room_id, user_id, &connection_id).await;
+            if guest_room.is_some() {
+                guest_left(&state, room_id, user_id).await;
             }
         }
     }
 
+    if guest_room.is_none() && !state.ws_storage.is_connected(&user_id) {
+        crate::presence::disconnected(&state, user_id).await;
+    }
+
     info!(?user_id, %connection_id, "WebSocket disconnected");
 }
 
+/// `auth:refresh` with `data.token`, a fresh access token for the same
+/// user. Answers `auth:refreshed` with the new expiry, or `auth:error`
+/// leaving the current token in place. Returns the token's username.
+async fn refresh_auth(
+    state: &AppState,
+    user_id: ObjectId,
+    connection_id: &str,
+    auth: &mut ConnectionAuth,
+    token: Option<&str>,
+) -> Option<String> {
+    let result = match token {
+        None => Err("token is required"),
+        Some(token) => match state.auth.verify_access_token(token) {
+            Err(_) => Err("Invalid or expired token"),
+            Ok(claims) if claims.sub != user_id.to_hex() => {
+                Err("The token belongs to another user")
+            }
+            Ok(claims) => {
+                if state
+                    .deleted_accounts
+                    .is_deleted(&state.users, user_id)
+                    .await
+                    .unwrap_or(false)
+                {
+                    Err("Account deleted")
+                } else if !auth.refresh(claims.exp) {
+                    Err("The token expires before the current one")
+                } else {
+                    Ok(claims.username)
+                }
+            }
+        },
+    };
+
+    let event = match &result {
+        Ok(_) => serde_json::json!({
+            "type": "auth:refreshed",
+            "data": { "expires_at": auth.expires_at() },
+        }),
+        Err(message) => serde_json::json!({
+            "type": "auth:error",
+            "data": { "message": message },
+        }),
+    };
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    result.ok()
+}
+
+/// Close the media state of a connection that dropped without leaving and
+/// tell the rest of the call.
+async fn drop_participant(
+    state: &AppState,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    connection_id: &str,
+) {
+    let remaining_conns = state
+        .room_manager
+        .get_other_connection_ids(&room_id, connection_id);
+
+    state
+        .room_manager
+        .close_participant(&room_id, connection_id);
+    crate::presence::broadcast_activity(state, room_id, &[user_id]).await;
+    crate::conference_events::record(
+        state,
+        room_id,
+        ConferenceEventType::ParticipantLeft,
+        Some(user_id),
+        doc! { "connection_id": connection_id, "reason": "disconnected" },
+    )
+    .await;
+
+    if !remaining_conns.is_empty() {
+        let event = serde_json::json!({
+            "type": "media:peer_left",
+            "data": {
+                "user_id": user_id.to_hex(),
+                "connection_id": connection_id,
+                "room_id": room_id.to_hex(),
+            }
+        });
+        for conn_id in &remaining_conns {
+            super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
+        }
+    }
+    crate::conference_limits::fill_open_slots(state, room_id).await;
+    crate::conference_reaper::schedule(state, room_id);
+}
+
+/// Signaling from a conference guest: the media messages of an attendee,
+/// for the guest's own conference only. Anything else is refused with
+/// `media:error`.
+async fn handle_guest_message(
+    state: &AppState,
+    guest_id: &ObjectId,
+    guest_room: ObjectId,
+    connection_id: &str,
+    text: &str,
+) {
+    let parsed: serde_json::Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    let msg_type = parsed.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    let data = parsed.get("data");
+
+    debug!(?guest_id, %connection_id, msg_type, "Guest WS message received");
+
+    if let Some(room_id) = data.and_then(|d| d.get("room_id")).and_then(|r| r.as_str())
+        && ObjectId::parse_str(room_id).ok() != Some(guest_room)
+    {
+        send_media_error(
+            state,
+            guest_id,
+            "Guests can only take part in their own conference",
+        )
+        .await;
+        return;
+    }
+
+    let result = match msg_type {
+        "ping" => {
+            let pong = serde_json::json!({ "type": "pong" });
+            super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &pong).await;
+            Ok(())
+        }
+        "media:join" => {
+            if handle_media_join(state, guest_id, connection_id, data).await
+                && let Err(e) = state.rooms.join_guest(guest_room, *guest_id).await
+            {
+                warn!(?guest_id, %guest_room, %e, "Failed to record guest session");
+            }
+            Ok(())
+        }
+        "media:connect_transport" => {
+            handle_media_connect_transport(state, connection_id, data).await;
+            Ok(())
+        }
+        "media:produce" => {
+            handle_media_produce(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:consume" => {
+            handle_media_consume(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:producer_close" => {
+            handle_media_producer_close(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:replace_producer" => {
+            handle_media_replace_producer(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:rejoin" => {
+            handle_media_rejoin(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:leave" => {
+            handle_media_leave(state, guest_id, connection_id, data).await;
+            guest_left(state, guest_room, *guest_id).await;
+            Ok(())
+        }
+        "media:set_preferred_layers" => {
+            handle_set_preferred_layers(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:producer_pause" => {
+            handle_producer_pause(state, guest_id, connection_id, data, true).await;
+            Ok(())
+        }
+        "media:producer_resume" => {
+            handle_producer_pause(state, guest_id, connection_id, data, false).await;
+            Ok(())
+        }
+        "media:reaction" => {
+            handle_media_reaction(state, guest_id, connection_id, data).await;
+            Ok(())
+        }
+        "media:raise_hand" => {
+            crate::conference_moderation::raise_hand(state, *guest_id, connection_id, data).await
+        }
+        "media:lower_hand" => {
+            crate::conference_moderation::lower_hand(state, *guest_id, connection_id, data).await
+        }
+        "media:poll_vote" => {
+            crate::conference_polls::vote(state, *guest_id, connection_id, data).await
+        }
+        _ => Err(format!("{} is not available to guests", msg_type)),
+    };
+    if let Err(e) = result {
+        send_media_error(state, guest_id, &e).await;
+    }
+}
+
+/// Close a conference guest's call session once they are out of the call.
+async fn guest_left(state: &AppState, room_id: ObjectId, guest_id: ObjectId) {
+    if let Err(e) = state.rooms.leave_guest(room_id, guest_id).await {
+        warn!(?guest_id, %room_id, %e, "Failed to close guest session");
+    }
+}
+
+/// Send `msg_type` to every other connection in the room's call.
+async fn notify_peers(
+    state: &AppState,
+    room_id: ObjectId,
+    connection_id: &str,
+    msg_type: &str,
+    data: serde_json::Value,
+) {
+    let event = serde_json::json!({ "type": msg_type, "data": data });
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&room_id, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+}
+
 async fn handle_client_message(
     state: &AppState,
     user_id: &ObjectId,
@@ -253,14 +661,35 @@ async fn handle_client_message(
 
     debug!(?user_id, %connection_id, msg_type, "WS message received");
 
+    // Keepalives and reconnect bookkeeping aren't the user doing anything;
+    // `presence:update` counts through `presence::set`.
+    if !matches!(
+        msg_type,
+        "ping" | "resume" | "latency:probe" | "presence:update"
+    ) {
+        crate::presence::touch(state, *user_id).await;
+    }
+
     match msg_type {
         "ping" => {
             let pong = serde_json::json!({ "type": "pong" });
             super::dispatcher::send_to_user(&state.ws_storage, user_id, &pong).await;
         }
+        "resume" => {
+            handle_resume(state, user_id, connection_id, &parsed).await;
+        }
+        "latency:probe" => {
+            handle_latency_probe(state, user_id, connection_id, data).await;
+        }
         "typing:start" | "typing:stop" => {
             if let Some(room_id_str) = data.and_then(|d| d.get("room_id")).and_then(|c| c.as_str())
                 && let Ok(rid) = ObjectId::parse_str(room_id_str)
+                && super::dispatcher::privacy_allows(
+                    &state.users,
+                    *user_id,
+                    super::dispatcher::PrivacyScope::Typing,
+                )
+                .await
                 && let Ok(member_ids) = state.rooms.find_member_user_ids(rid).await
             {
                 let recipients: Vec<ObjectId> =
@@ -284,23 +713,9 @@ async fn handle_client_message(
         "presence:update" => {
             if let Some(presence) = data
                 .and_then(|d| d.get("presence"))
-                .and_then(|p| p.as_str())
+                .and_then(|p| serde_json::from_value(p.clone()).ok())
             {
-                let all_users = state.ws_storage.all_user_ids();
-                let event = serde_json::json!({
-                    "type": "presence:update",
-                    "data": {
-                        "user_id": user_id.to_hex(),
-                        "presence": presence,
-                    }
-                });
-                super::dispatcher::broadcast_with_redis(
-                    &state.ws_storage,
-                    &state.redis_pubsub,
-                    &all_users,
-                    &event,
-                )
-                .await;
+                crate::presence::set(state, *user_id, presence).await;
             }
         }
         "media:join" => {
@@ -318,6 +733,12 @@ async fn handle_client_message(
         "media:producer_close" => {
             handle_media_producer_close(state, user_id, connection_id, data).await;
         }
+        "media:replace_producer" => {
+            handle_media_replace_producer(state, user_id, connection_id, data).await;
+        }
+        "media:rejoin" => {
+            handle_media_rejoin(state, user_id, connection_id, data).await;
+        }
         "media:leave" => {
             handle_media_leave(state, user_id, connection_id, data).await;
         }
@@ -327,12 +748,144 @@ async fn handle_client_message(
         "media:stop_audio" => {
             handle_stop_audio(state, user_id, connection_id, data).await;
         }
+        "media:set_preferred_layers" => {
+            handle_set_preferred_layers(state, user_id, connection_id, data).await;
+        }
+        "media:producer_pause" => {
+            handle_producer_pause(state, user_id, connection_id, data, true).await;
+        }
+        "media:producer_resume" => {
+            handle_producer_pause(state, user_id, connection_id, data, false).await;
+        }
+        "media:transcript_toggle" => {
+            handle_transcript_toggle(state, user_id, connection_id, data).await;
+        }
+        "media:transcribe_me" => {
+            handle_transcribe_me(state, user_id, connection_id, data).await;
+        }
+        "media:caption_track" => {
+            handle_caption_track(state, user_id, connection_id, data).await;
+        }
+        "media:reaction" => {
+            handle_media_reaction(state, user_id, connection_id, data).await;
+        }
+        "media:raise_hand" => {
+            if let Err(e) =
+                crate::conference_moderation::raise_hand(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:lower_hand" => {
+            if let Err(e) =
+                crate::conference_moderation::lower_hand(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:mute_request" => {
+            if let Err(e) =
+                crate::conference_moderation::mute_request(state, *user_id, connection_id, data)
+                    .await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:force_mute" => {
+            if let Err(e) =
+                crate::conference_moderation::force_mute(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:stats_subscribe" => {
+            if let Err(e) = crate::conference_stats::subscribe(state, connection_id, data).await {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:stats_unsubscribe" => {
+            if let Err(e) = crate::conference_stats::unsubscribe(state, connection_id, data).await {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:poll_start" => {
+            if let Err(e) =
+                crate::conference_polls::start(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:poll_vote" => {
+            if let Err(e) =
+                crate::conference_polls::vote(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "media:poll_end" => {
+            if let Err(e) = crate::conference_polls::end(state, *user_id, connection_id, data).await
+            {
+                send_media_error(state, user_id, &e).await;
+            }
+        }
+        "draft:join" => {
+            if let Err(e) = crate::shared_drafts::join(state, *user_id, connection_id, data).await {
+                crate::shared_drafts::send_error(state, connection_id, data, &e).await;
+            }
+        }
+        "draft:leave" => {
+            crate::shared_drafts::leave(state, connection_id, data).await;
+        }
+        "draft:op" => {
+            if let Err(e) =
+                crate::shared_drafts::apply_op(state, *user_id, connection_id, data).await
+            {
+                crate::shared_drafts::send_error(state, connection_id, data, &e).await;
+            }
+        }
         _ => {
             debug!(?user_id, msg_type, "Unknown WS message type");
         }
     }
 }
 
+/// Opt-in delivery probe. The client sends `latency:probe` with an `id` and
+/// the server echoes it straight back as `latency:ack` on the same
+/// connection. Clients report the round trip they measured for the previous
+/// probe as `rtt_ms`, which is aggregated into the tenant's delivery metrics.
+async fn handle_latency_probe(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let probe_id = data.and_then(|d| d.get("id")).cloned().unwrap_or_default();
+    let ack = serde_json::json!({
+        "type": "latency:ack",
+        "data": {
+            "id": probe_id,
+            "server_ts": bson::DateTime::now().timestamp_millis(),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &ack).await;
+
+    let tenant_id = data
+        .and_then(|d| d.get("tenant_id"))
+        .and_then(|t| t.as_str())
+        .and_then(|t| ObjectId::parse_str(t).ok());
+    let rtt_ms = data.and_then(|d| d.get("rtt_ms")).and_then(|r| r.as_u64());
+    if let (Some(tid), Some(rtt_ms)) = (tenant_id, rtt_ms)
+        && state
+            .tenants
+            .is_member(tid, *user_id)
+            .await
+            .unwrap_or(false)
+        && !state.delivery_metrics.record_probe_rtt(tid, rtt_ms)
+    {
+        debug!(?user_id, rtt_ms, "Discarded implausible latency probe");
+    }
+}
+
 async fn send_media_error(state: &AppState, user_id: &ObjectId, message: &str) {
     let msg = serde_json::json!({
         "type": "media:error",
@@ -341,17 +894,113 @@ async fn send_media_error(state: &AppState, user_id: &ObjectId, message: &str) {
     super::dispatcher::send_to_user(&state.ws_storage, user_id, &msg).await;
 }
 
+/// Apply the room's lobby and the plan's participant cap to a `media:join`.
+/// Users already in the call, on another device or through `call/join`, pass
+/// straight through; joiners held in the lobby or waitlisted have been told
+/// with `media:lobby_waiting` or `conference:waitlisted`.
+async fn media_admitted(state: &AppState, room: &Room, user_id: &ObjectId) -> bool {
+    let Some(rid) = room.id else { return true };
+    if state
+        .room_manager
+        .get_participant_user_ids(&rid)
+        .contains(user_id)
+        || state
+            .rooms
+            .is_active_participant(rid, *user_id)
+            .await
+            .unwrap_or(false)
+    {
+        return true;
+    }
+    if !crate::conference_lobby::check(state, room, *user_id).await {
+        return false;
+    }
+    match crate::conference_limits::admit(state, room, *user_id).await {
+        Ok(Admission::Joined) => true,
+        Ok(Admission::Waitlisted { .. }) => false,
+        Ok(Admission::Full) => {
+            send_media_error(state, user_id, "Conference is full").await;
+            false
+        }
+        Err(e) => {
+            warn!(?user_id, %rid, %e, "media:join admission check failed");
+            send_media_error(state, user_id, "Failed to join conference").await;
+            false
+        }
+    }
+}
+
+fn media_kind_str(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+    }
+}
+
+/// A reconnected client asks for the user-level events it missed while
+/// away: `{"type":"resume","last_seq":N,"stream_id":"..."}`. They are sent
+/// to this connection with their original `seq`, followed by `resume:ok`;
+/// if they are no longer buffered the client gets `resume:refetch` and
+/// reloads its state instead. Connection-targeted events (call signaling)
+/// belong to the old connection and are never replayed.
+async fn handle_resume(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    parsed: &serde_json::Value,
+) {
+    // Accept the fields at the top level or under `data`.
+    let field = |name: &str| {
+        parsed
+            .get(name)
+            .or_else(|| parsed.get("data").and_then(|d| d.get(name)))
+    };
+    let outcome = match field("last_seq").and_then(|s| s.as_u64()) {
+        Some(last_seq) => {
+            let stream_id = field("stream_id").and_then(|s| s.as_str());
+            state.ws_storage.resume(user_id, stream_id, last_seq)
+        }
+        None => ResumeOutcome::Refetch("missing_last_seq"),
+    };
+
+    let mut reply = match outcome {
+        ResumeOutcome::Replay(events) => {
+            let replayed = events.len();
+            if let Some(sender) = state.ws_storage.get_sender_by_connection(connection_id) {
+                let mut guard = sender.lock().await;
+                for text in events {
+                    if guard.send(Message::text(text)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            serde_json::json!({ "type": "resume:ok", "data": { "replayed": replayed } })
+        }
+        ResumeOutcome::Refetch(reason) => {
+            serde_json::json!({ "type": "resume:refetch", "data": { "reason": reason } })
+        }
+    };
+    let (stream_id, seq) = state
+        .ws_storage
+        .stream_position(user_id)
+        .unwrap_or_default();
+    reply["data"]["stream_id"] = stream_id.into();
+    reply["data"]["seq"] = seq.into();
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &reply).await;
+}
+
+/// Returns whether the connection joined.
 async fn handle_media_join(
     state: &AppState,
     user_id: &ObjectId,
     connection_id: &str,
     data: Option<&serde_json::Value>,
-) {
+) -> bool {
     let room_id_str = match data.and_then(|d| d.get("room_id")).and_then(|c| c.as_str()) {
         Some(s) => s,
         None => {
             send_media_error(state, user_id, "Missing room_id").await;
-            return;
+            return false;
         }
     };
 
@@ -359,7 +1008,7 @@ async fn handle_media_join(
         Ok(id) => id,
         Err(_) => {
             send_media_error(state, user_id, "Invalid room_id").await;
-            return;
+            return false;
         }
     };
 
@@ -367,12 +1016,20 @@ async fn handle_media_join(
     debug!(?user_id, %connection_id, ?rid, room_exists, "media:join room check");
     if !room_exists {
         send_media_error(state, user_id, "Room does not exist").await;
-        return;
+        return false;
     }
 
+    let room = state.rooms.base.find_by_id(rid).await.ok();
+    if let Some(room) = &room
+        && !media_admitted(state, room, user_id).await
+    {
+        return false;
+    }
+    let caption_track =
+        captions::initial_track(room.as_ref().and_then(|r| r.media_settings.as_ref()));
     let transport_pair = match state
         .room_manager
-        .create_transports(rid, *user_id, connection_id.to_string())
+        .create_transports(rid, *user_id, connection_id.to_string(), caption_track)
         .await
     {
         Ok(tp) => tp,
@@ -383,20 +1040,108 @@ async fn handle_media_join(
                 &format!("Failed to create transports: {}", e),
             )
             .await;
-            return;
+            return false;
         }
     };
 
-    if let Some(room) = state.room_manager.rooms_ref().get(&rid) {
-        let caps = serde_json::to_value(room.router.rtp_capabilities()).unwrap_or_default();
+    let caps = state
+        .room_manager
+        .rooms_ref()
+        .get(&rid)
+        .map(|room| serde_json::to_value(room.router.rtp_capabilities()).unwrap_or_default());
+    if let Some(caps) = caps {
+        // Plan/tenant capture settings and camera effects so every client
+        // calls getUserMedia with the same processing and caps and offers
+        // the same effects. Best-effort: omitted on lookup failure and
+        // clients keep their own defaults.
+        let tenant = match &room {
+            Some(room) => state.tenants.base.find_by_id(room.tenant_id).await.ok(),
+            None => None,
+        };
+        let media_constraints = tenant.as_ref().map(|t| t.media_constraints());
+        let video_effects = tenant
+            .as_ref()
+            .map(|t| crate::routes::video_effects::capabilities(state, t));
         let msg = serde_json::json!({
             "type": "media:router_capabilities",
-            "data": { "rtp_capabilities": caps }
+            "data": {
+                "rtp_capabilities": caps,
+                "media_constraints": media_constraints,
+                "video_effects": video_effects,
+            }
+        });
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    }
+
+    let ice_servers = conference_ice_servers(state, user_id);
+
+    let force_relay = state.settings.turn.force_relay.unwrap_or(false);
+
+    if force_relay {
+        info!("force_relay=true — clients will use iceTransportPolicy='relay' via TURN server");
+    }
+
+    info!(
+        %connection_id,
+        force_relay,
+        announced_ip = %state.settings.mediasoup.announced_ip,
+        turn_url = ?state.settings.turn.url,
+        send_ice_candidates = %transport_pair.send_transport.ice_candidates,
+        recv_ice_candidates = %transport_pair.recv_transport.ice_candidates,
+        "media:join transport_created ICE diagnostics"
+    );
+
+    let msg = serde_json::json!({
+        "type": "media:transport_created",
+        "data": {
+            "send_transport": transport_pair.send_transport,
+            "recv_transport": transport_pair.recv_transport,
+            "ice_servers": ice_servers,
+            "force_relay": force_relay,
+            "reconnect_token": transport_pair.reconnect_token,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+
+    let producers = state.room_manager.get_producer_ids(&rid, connection_id);
+    for (uid, conn_id, pid, kind, source) in producers {
+        let msg = serde_json::json!({
+            "type": "media:new_producer",
+            "data": {
+                "producer_id": pid.to_string(),
+                "user_id": uid.to_hex(),
+                "connection_id": conn_id,
+                "kind": media_kind_str(kind),
+                "source": source,
+            }
         });
         super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
     }
 
-    let ice_servers: Vec<serde_json::Value> = if let Some(ref url) = state.settings.turn.url {
+    // Joining mid-recording grants access to that recording.
+    if let Err(e) = state.recordings.add_participants(rid, &[*user_id]).await {
+        warn!(%e, "Failed to add participant to recording ACL");
+    }
+    crate::presence::broadcast_activity(state, rid, &[*user_id]).await;
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::ParticipantJoined,
+        Some(*user_id),
+        doc! { "connection_id": connection_id },
+    )
+    .await;
+    true
+}
+
+/// ICE servers handed to conference clients: the configured TURN server with
+/// TCP and TLS variants, using ephemeral credentials when a shared secret is
+/// set. Empty when no TURN server is configured.
+pub(crate) fn conference_ice_servers(
+    state: &AppState,
+    user_id: &ObjectId,
+) -> Vec<serde_json::Value> {
+    if let Some(ref url) = state.settings.turn.url {
         let (turn_username, turn_credential) =
             if let Some(ref secret) = state.settings.turn.shared_secret {
                 let expiry = SystemTime::now()
@@ -445,48 +1190,6 @@ async fn handle_media_join(
         })]
     } else {
         vec![]
-    };
-
-    let force_relay = state.settings.turn.force_relay.unwrap_or(false);
-
-    if force_relay {
-        info!("force_relay=true — clients will use iceTransportPolicy='relay' via TURN server");
-    }
-
-    info!(
-        %connection_id,
-        force_relay,
-        announced_ip = %state.settings.mediasoup.announced_ip,
-        turn_url = ?state.settings.turn.url,
-        send_ice_candidates = %transport_pair.send_transport.ice_candidates,
-        recv_ice_candidates = %transport_pair.recv_transport.ice_candidates,
-        "media:join transport_created ICE diagnostics"
-    );
-
-    let msg = serde_json::json!({
-        "type": "media:transport_created",
-        "data": {
-            "send_transport": transport_pair.send_transport,
-            "recv_transport": transport_pair.recv_transport,
-            "ice_servers": ice_servers,
-            "force_relay": force_relay,
-        }
-    });
-    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
-
-    let producers = state.room_manager.get_producer_ids(&rid, connection_id);
-    for (uid, conn_id, pid, kind, source) in producers {
-        let msg = serde_json::json!({
-            "type": "media:new_producer",
-            "data": {
-                "producer_id": pid.to_string(),
-                "user_id": uid.to_hex(),
-                "connection_id": conn_id,
-                "kind": match kind { MediaKind::Audio => "audio", MediaKind::Video => "video" },
-                "source": source,
-            }
-        });
-        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
     }
 }
 
@@ -561,7 +1264,7 @@ async fn handle_media_produce(
             return;
         }
     };
-    let rtp_parameters: RtpParameters = match data
+    let mut rtp_parameters: RtpParameters = match data
         .get("rtp_parameters")
         .and_then(|v| serde_json::from_value(v.clone()).ok())
     {
@@ -571,6 +1274,20 @@ async fn handle_media_produce(
             return;
         }
     };
+    // Simulcast encodings may come separately from the RTP parameters.
+    if let Some(encodings) = data.get("encodings").filter(|v| !v.is_null()) {
+        match serde_json::from_value(encodings.clone()) {
+            Ok(encodings) => rtp_parameters.encodings = encodings,
+            Err(_) => {
+                send_media_error(state, user_id, "Invalid encodings").await;
+                return;
+            }
+        }
+    }
+    if let Err(e) = simulcast::validate_encodings(kind, &rtp_parameters.encodings) {
+        send_media_error(state, user_id, e).await;
+        return;
+    }
     let source = data
         .get("source")
         .and_then(|v| v.as_str())
@@ -588,15 +1305,46 @@ async fn handle_media_produce(
         }
     };
 
+    let screen_share = crate::call_controls::is_screen_share(&source);
+    let restrictions = crate::call_controls::restrictions(state, rid, *user_id)
+        .await
+        .ok()
+        .flatten();
+    if screen_share
+        && restrictions
+            .as_ref()
+            .is_some_and(|c| !c.attendee_screen_share)
+    {
+        send_media_error(state, user_id, "Screen sharing is limited to organizers").await;
+        return;
+    }
+    // Attendee microphones start muted when they join muted or can't unmute.
+    let paused = kind == MediaKind::Audio
+        && !screen_share
+        && restrictions
+            .as_ref()
+            .is_some_and(|c| c.mute_on_entry || !c.allow_unmute);
+
     match state
         .room_manager
-        .produce(&rid, connection_id, kind, rtp_parameters, source.clone())
+        .produce(
+            &rid,
+            connection_id,
+            kind,
+            rtp_parameters,
+            source.clone(),
+            paused,
+        )
         .await
     {
-        Ok(producer_id) => {
+        Ok((producer_id, layers)) => {
             let result_msg = serde_json::json!({
                 "type": "media:produce_result",
-                "data": { "id": producer_id.to_string() }
+                "data": {
+                    "id": producer_id.to_string(),
+                    "paused": paused,
+                    "layers": layers,
+                }
             });
             super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &result_msg)
                 .await;
@@ -612,14 +1360,33 @@ async fn handle_media_produce(
                         "producer_id": producer_id.to_string(),
                         "user_id": user_id.to_hex(),
                         "connection_id": connection_id,
-                        "kind": match kind { MediaKind::Audio => "audio", MediaKind::Video => "video" },
+                        "kind": media_kind_str(kind),
                         "source": source,
+                        "paused": paused,
+                        "layers": layers,
                     }
                 });
                 for conn_id in &other_conns {
                     super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
                 }
             }
+
+            if source == "screen" {
+                crate::presence::broadcast_activity(state, rid, &[*user_id]).await;
+            }
+            crate::conference_events::record(
+                state,
+                rid,
+                ConferenceEventType::ProducerStarted,
+                Some(*user_id),
+                doc! {
+                    "producer_id": producer_id.to_string(),
+                    "connection_id": connection_id,
+                    "kind": media_kind_str(kind),
+                    "source": &source,
+                },
+            )
+            .await;
         }
         Err(e) => {
             send_media_error(state, user_id, &format!("produce failed: {}", e)).await;
@@ -627,6 +1394,98 @@ async fn handle_media_produce(
     }
 }
 
+/// A participant switched camera or microphone. The new producer takes the
+/// old one's slot, and peers get one `media:producer_replaced` instead of
+/// `media:producer_closed` followed by `media:new_producer`, so they can
+/// swap the consumer without tearing down the tile.
+async fn handle_media_replace_producer(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(old_producer_id) = data
+        .and_then(|d| d.get("producer_id"))
+        .and_then(|p| p.as_str())
+        .and_then(|p| p.parse::<ProducerId>().ok())
+    else {
+        send_media_error(state, user_id, "Invalid producer_id").await;
+        return;
+    };
+    let Some(rtp_parameters) = data
+        .and_then(|d| d.get("rtp_parameters"))
+        .and_then(|v| serde_json::from_value::<RtpParameters>(v.clone()).ok())
+    else {
+        send_media_error(state, user_id, "Invalid rtp_parameters").await;
+        return;
+    };
+
+    let (producer_id, kind, source) = match state
+        .room_manager
+        .replace_producer(&rid, connection_id, &old_producer_id, rtp_parameters)
+        .await
+    {
+        Ok(replaced) => replaced,
+        Err(e) => {
+            send_media_error(state, user_id, &format!("replace_producer failed: {}", e)).await;
+            return;
+        }
+    };
+    state
+        .room_manager
+        .remove_rtp_tap(&rid, &old_producer_id.to_string());
+
+    let result_msg = serde_json::json!({
+        "type": "media:replace_producer_result",
+        "data": {
+            "id": producer_id.to_string(),
+            "replaced_producer_id": old_producer_id.to_string(),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &result_msg).await;
+
+    let event = serde_json::json!({
+        "type": "media:producer_replaced",
+        "data": {
+            "old_producer_id": old_producer_id.to_string(),
+            "producer_id": producer_id.to_string(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "kind": media_kind_str(kind),
+            "source": &source,
+        }
+    });
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::ProducerReplaced,
+        Some(*user_id),
+        doc! {
+            "old_producer_id": old_producer_id.to_string(),
+            "producer_id": producer_id.to_string(),
+            "connection_id": connection_id,
+            "kind": media_kind_str(kind),
+            "source": &source,
+        },
+    )
+    .await;
+}
+
 async fn handle_media_consume(
     state: &AppState,
     user_id: &ObjectId,
@@ -695,6 +1554,7 @@ async fn handle_media_consume(
                     "producer_id": consumer_info.producer_id,
                     "kind": consumer_info.kind,
                     "rtp_parameters": consumer_info.rtp_parameters,
+                    "layers": consumer_info.layers,
                 }
             });
             super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
@@ -705,6 +1565,78 @@ async fn handle_media_consume(
     }
 }
 
+/// A client on a poor link asks for lower layers of a simulcast or SVC
+/// video it consumes (or higher ones once the link recovers). Answered with
+/// `media:preferred_layers_set` carrying the layers actually applied.
+async fn handle_set_preferred_layers(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(consumer_id) = data
+        .and_then(|d| d.get("consumer_id"))
+        .and_then(|c| c.as_str())
+        .and_then(|c| c.parse::<ConsumerId>().ok())
+    else {
+        send_media_error(state, user_id, "Invalid consumer_id").await;
+        return;
+    };
+    let Some(spatial_layer) = data
+        .and_then(|d| d.get("spatial_layer"))
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u8::try_from(v).ok())
+    else {
+        send_media_error(state, user_id, "Invalid spatial_layer").await;
+        return;
+    };
+    let temporal_layer = data
+        .and_then(|d| d.get("temporal_layer"))
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u8::try_from(v).ok());
+
+    match state
+        .room_manager
+        .set_preferred_layers(
+            &rid,
+            connection_id,
+            &consumer_id,
+            spatial_layer,
+            temporal_layer,
+        )
+        .await
+    {
+        Ok(Some(layers)) => {
+            let msg = serde_json::json!({
+                "type": "media:preferred_layers_set",
+                "data": {
+                    "consumer_id": consumer_id.to_string(),
+                    "spatial_layer": layers.spatial_layer,
+                    "temporal_layer": layers.temporal_layer,
+                }
+            });
+            super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+        }
+        Ok(None) => send_media_error(state, user_id, "Consumer not found").await,
+        Err(e) => {
+            send_media_error(
+                state,
+                user_id,
+                &format!("set_preferred_layers failed: {}", e),
+            )
+            .await;
+        }
+    }
+}
+
 async fn handle_media_producer_close(
     state: &AppState,
     user_id: &ObjectId,
@@ -735,6 +1667,11 @@ async fn handle_media_producer_close(
         Err(_) => return,
     };
 
+    let was_sharing = state
+        .room_manager
+        .call_activities()
+        .get(user_id)
+        .is_some_and(|a| a.screen_sharing);
     if state
         .room_manager
         .close_producer(&rid, connection_id, &producer_id)
@@ -742,6 +1679,14 @@ async fn handle_media_producer_close(
         state
             .room_manager
             .remove_rtp_tap(&rid, &producer_id.to_string());
+        crate::conference_events::record(
+            state,
+            rid,
+            ConferenceEventType::ProducerStopped,
+            Some(*user_id),
+            doc! { "producer_id": producer_id.to_string(), "connection_id": connection_id },
+        )
+        .await;
 
         let other_conns = state
             .room_manager
@@ -759,7 +1704,508 @@ async fn handle_media_producer_close(
                 super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
             }
         }
+
+        let is_sharing = state
+            .room_manager
+            .call_activities()
+            .get(user_id)
+            .is_some_and(|a| a.screen_sharing);
+        if was_sharing != is_sharing {
+            crate::presence::broadcast_activity(state, rid, &[*user_id]).await;
+        }
+    }
+}
+
+/// Mutes (`paused: true`) or unmutes one of this connection's producers and
+/// tells the other call connections so they can update the tile.
+async fn handle_producer_pause(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+    paused: bool,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(producer_id) = data
+        .and_then(|d| d.get("producer_id"))
+        .and_then(|p| p.as_str())
+        .and_then(|p| p.parse::<ProducerId>().ok())
+    else {
+        send_media_error(state, user_id, "Invalid producer_id").await;
+        return;
+    };
+
+    if !paused
+        && state
+            .room_manager
+            .producer_kind_and_source(&rid, connection_id, &producer_id)
+            .is_some_and(|(kind, source)| {
+                kind == MediaKind::Audio && !crate::call_controls::is_screen_share(&source)
+            })
+        && crate::call_controls::restrictions(state, rid, *user_id)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|c| !c.allow_unmute)
+    {
+        send_media_error(state, user_id, "Unmuting is disabled by the organizer").await;
+        return;
+    }
+
+    let (kind, source) = match state
+        .room_manager
+        .set_producer_paused(&rid, connection_id, &producer_id, paused)
+        .await
+    {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            send_media_error(state, user_id, "Producer not found").await;
+            return;
+        }
+        Err(e) => {
+            send_media_error(state, user_id, &format!("pause failed: {}", e)).await;
+            return;
+        }
+    };
+
+    if kind == MediaKind::Audio
+        && !crate::call_controls::is_screen_share(&source)
+        && let Err(e) = state
+            .rooms
+            .set_participant_muted(rid, *user_id, paused)
+            .await
+    {
+        warn!(?user_id, %rid, %e, "Failed to persist mute state");
+    }
+
+    let event = serde_json::json!({
+        "type": if paused { "media:producer_paused" } else { "media:producer_resumed" },
+        "data": {
+            "producer_id": producer_id.to_string(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "kind": media_kind_str(kind),
+            "source": &source,
+        }
+    });
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::MuteToggled,
+        Some(*user_id),
+        doc! {
+            "producer_id": producer_id.to_string(),
+            "connection_id": connection_id,
+            "kind": media_kind_str(kind),
+            "source": source,
+            "muted": paused,
+        },
+    )
+    .await;
+}
+
+/// A call participant turns live transcription on or off. Everyone in the
+/// call gets `media:transcript_status`.
+async fn handle_transcript_toggle(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(enabled) = data
+        .and_then(|d| d.get("enabled"))
+        .and_then(|e| e.as_bool())
+    else {
+        send_media_error(state, user_id, "Missing enabled").await;
+        return;
+    };
+    let model = data
+        .and_then(|d| d.get("model"))
+        .and_then(|m| m.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| state.settings.asr.model.clone());
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        send_media_error(state, user_id, "Not in this call").await;
+        return;
+    }
+
+    // Buffered speech finishes on the previous model while it drains; the
+    // status marks where the switch happened.
+    let drain = Duration::from_millis(state.settings.asr.switch_drain_ms);
+    let previous_model =
+        match state
+            .room_manager
+            .toggle_transcription(&rid, enabled, model.clone(), drain)
+        {
+            Some(Handover::Switched { previous_model } | Handover::Stopped { previous_model }) => {
+                Some(previous_model)
+            }
+            _ => None,
+        };
+    let mut data = serde_json::json!({
+        "room_id": rid.to_hex(),
+        "enabled": enabled,
+        "model": &model,
+    });
+    if let Some(previous_model) = &previous_model {
+        data["previous_model"] = serde_json::json!(previous_model);
+        data["switched_at"] = serde_json::json!(
+            bson::DateTime::now()
+                .try_to_rfc3339_string()
+                .unwrap_or_default()
+        );
+        data["drain_ms"] = serde_json::json!(state.settings.asr.switch_drain_ms);
+    }
+    let event = serde_json::json!({
+        "type": "media:transcript_status",
+        "data": data,
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::TranscriptToggled,
+        Some(*user_id),
+        doc! { "enabled": enabled, "model": model, "previous_model": previous_model },
+    )
+    .await;
+}
+
+/// A call participant turns transcription of their own microphone on or
+/// off: a single pipeline for their producer instead of the whole call.
+/// Everyone in the call gets `media:transcript_status` with `scope: self`.
+async fn handle_transcribe_me(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(enabled) = data
+        .and_then(|d| d.get("enabled"))
+        .and_then(|e| e.as_bool())
+    else {
+        send_media_error(state, user_id, "Missing enabled").await;
+        return;
+    };
+    let model = data
+        .and_then(|d| d.get("model"))
+        .and_then(|m| m.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| state.settings.asr.model.clone());
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        send_media_error(state, user_id, "Not in this call").await;
+        return;
+    }
+
+    let drain = Duration::from_millis(state.settings.asr.switch_drain_ms);
+    let Some(handover) = state.room_manager.toggle_self_transcription(
+        &rid,
+        connection_id,
+        enabled,
+        model.clone(),
+        drain,
+    ) else {
+        send_media_error(state, user_id, "Not in this call").await;
+        return;
+    };
+    let previous_model = match handover {
+        Handover::Switched { previous_model } | Handover::Stopped { previous_model } => {
+            Some(previous_model)
+        }
+        _ => None,
+    };
+    let mut data = serde_json::json!({
+        "room_id": rid.to_hex(),
+        "scope": "self",
+        "user_id": user_id.to_hex(),
+        "connection_id": connection_id,
+        "enabled": enabled,
+        "model": &model,
+    });
+    if let Some(previous_model) = &previous_model {
+        data["previous_model"] = serde_json::json!(previous_model);
+        data["drain_ms"] = serde_json::json!(state.settings.asr.switch_drain_ms);
+    }
+    let event = serde_json::json!({
+        "type": "media:transcript_status",
+        "data": data,
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
     }
+
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::TranscriptToggled,
+        Some(*user_id),
+        doc! {
+            "enabled": enabled,
+            "model": model,
+            "previous_model": previous_model,
+            "scope": "self",
+        },
+    )
+    .await;
+}
+
+/// A short-lived emoji reaction shown over the sender's tile. Nothing is
+/// stored; every connection in the call, the sender's included, gets
+/// `media:reaction`.
+async fn handle_media_reaction(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(emoji) = data
+        .and_then(|d| d.get("emoji"))
+        .and_then(|e| e.as_str())
+        .filter(|e| !e.is_empty() && e.chars().count() <= MAX_REACTION_CHARS)
+    else {
+        send_media_error(state, user_id, "Invalid emoji").await;
+        return;
+    };
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        send_media_error(state, user_id, "Not in this call").await;
+        return;
+    }
+    if crate::call_controls::restrictions(state, rid, *user_id)
+        .await
+        .ok()
+        .flatten()
+        .is_some_and(|c| !c.reactions_enabled)
+    {
+        send_media_error(state, user_id, "Reactions are disabled by the organizer").await;
+        return;
+    }
+
+    let event = serde_json::json!({
+        "type": "media:reaction",
+        "data": {
+            "room_id": rid.to_hex(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "emoji": emoji,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &event).await;
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+}
+
+/// Switches this connection's caption track, or turns captions off. Live
+/// transcript segments are then only delivered for the chosen track (see
+/// `dispatcher::send_caption_segment`).
+async fn handle_caption_track(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    // `"track": null` turns captions off for this connection.
+    let requested = match data.and_then(|d| d.get("track")) {
+        Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(t)) => Some(t.as_str()),
+        _ => {
+            send_media_error(state, user_id, "Missing track").await;
+            return;
+        }
+    };
+
+    let media_settings = match state.rooms.base.find_by_id(rid).await {
+        Ok(room) => room.media_settings,
+        Err(_) => {
+            send_media_error(state, user_id, "Room does not exist").await;
+            return;
+        }
+    };
+    let track = match requested {
+        Some(requested) => match captions::resolve_track(requested, media_settings.as_ref()) {
+            Some(track) => Some(track),
+            None => {
+                send_media_error(state, user_id, "Caption track not available").await;
+                return;
+            }
+        },
+        None => None,
+    };
+    if !state
+        .room_manager
+        .set_caption_track(&rid, connection_id, track.as_deref())
+    {
+        send_media_error(state, user_id, "Not in this call").await;
+        return;
+    }
+
+    let msg = serde_json::json!({
+        "type": "media:caption_track_selected",
+        "data": {
+            "room_id": rid.to_hex(),
+            "track": track,
+            "available_tracks": captions::available_tracks(media_settings.as_ref()),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// Take over the media state this user left behind on a dropped connection,
+/// within the reconnect grace period. Producers and consumers stay as they
+/// were; the client restarts ICE with the returned parameters.
+async fn handle_media_rejoin(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+    else {
+        send_media_error(state, user_id, "Invalid room_id").await;
+        return;
+    };
+    let Some(token) = data
+        .and_then(|d| d.get("reconnect_token"))
+        .and_then(|v| v.as_str())
+    else {
+        send_media_error(state, user_id, "Missing reconnect_token").await;
+        return;
+    };
+
+    let rebound = match state
+        .room_manager
+        .rebind_participant(&rid, *user_id, token, connection_id.to_string())
+        .await
+    {
+        Ok(Some(rebound)) => rebound,
+        Ok(None) => {
+            send_media_error(
+                state,
+                user_id,
+                "Nothing to rejoin; the reconnect window has passed",
+            )
+            .await;
+            return;
+        }
+        Err(e) => {
+            send_media_error(state, user_id, &format!("Failed to rejoin: {}", e)).await;
+            return;
+        }
+    };
+
+    let msg = serde_json::json!({
+        "type": "media:rejoined",
+        "data": {
+            "room_id": rid.to_hex(),
+            "previous_connection_id": rebound.previous_connection_id,
+            "send_ice_parameters": rebound.send_ice_parameters,
+            "recv_ice_parameters": rebound.recv_ice_parameters,
+            "ice_servers": conference_ice_servers(state, user_id),
+            "closed_producer_ids": rebound
+                .closed_producer_ids
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+
+    // Producers started while the connection was down.
+    for (uid, conn_id, pid, kind, source) in
+        state.room_manager.get_producer_ids(&rid, connection_id)
+    {
+        if rebound.consumed_producer_ids.contains(&pid) {
+            continue;
+        }
+        let msg = serde_json::json!({
+            "type": "media:new_producer",
+            "data": {
+                "producer_id": pid.to_string(),
+                "user_id": uid.to_hex(),
+                "connection_id": conn_id,
+                "kind": media_kind_str(kind),
+                "source": source,
+            }
+        });
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    }
+
+    notify_peers(
+        state,
+        rid,
+        connection_id,
+        "media:peer_reconnected",
+        serde_json::json!({
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "previous_connection_id": rebound.previous_connection_id,
+            "room_id": rid.to_hex(),
+        }),
+    )
+    .await;
 }
 
 async fn handle_media_leave(
@@ -778,11 +2224,27 @@ async fn handle_media_leave(
         Err(_) => return,
     };
 
+    if state.conference_lobby.is_pending(&rid, user_id)
+        && let Ok(room) = state.rooms.base.find_by_id(rid).await
+    {
+        // Gave up waiting in the lobby
+        crate::conference_lobby::cancel(state, &room, *user_id).await;
+    }
+
     let other_conns = state
         .room_manager
         .get_other_connection_ids(&rid, connection_id);
 
     state.room_manager.close_participant(&rid, connection_id);
+    crate::presence::broadcast_activity(state, rid, &[*user_id]).await;
+    crate::conference_events::record(
+        state,
+        rid,
+        ConferenceEventType::ParticipantLeft,
+        Some(*user_id),
+        doc! { "connection_id": connection_id, "reason": "left" },
+    )
+    .await;
 
     if !other_conns.is_empty() {
         let event = serde_json::json!({
@@ -797,6 +2259,8 @@ async fn handle_media_leave(
             super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
         }
     }
+    crate::conference_limits::fill_open_slots(state, rid).await;
+    crate::conference_reaper::schedule(state, rid);
 }
 
 async fn handle_play_audio(
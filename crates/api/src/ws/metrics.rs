@@ -0,0 +1,148 @@
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Upper bounds (ms) of the latency histogram buckets. Anything slower lands
+/// in the implicit overflow bucket.
+const BUCKET_BOUNDS_MS: [u64; 10] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// Client-reported round trips above this are treated as bogus (a
+/// backgrounded tab, a suspended laptop) and dropped.
+const MAX_PROBE_RTT_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+}
+
+impl Histogram {
+    fn record(&mut self, d: Duration) {
+        let us = d.as_micros().min(u64::MAX as u128) as u64;
+        let ms = us / 1000;
+        let idx = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|b| ms < *b)
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+        self.max_us = self.max_us.max(us);
+    }
+
+    /// Bucket upper bound containing the `q` quantile. Coarse, but cheap and
+    /// good enough to tell "fine" from "SLO breach".
+    fn quantile_ms(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(match BUCKET_BOUNDS_MS.get(i) {
+                    Some(bound) => *bound as f64,
+                    None => self.max_us as f64 / 1000.0,
+                });
+            }
+        }
+        Some(self.max_us as f64 / 1000.0)
+    }
+
+    fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            count: self.count,
+            avg_ms: (self.count > 0).then(|| self.sum_us as f64 / self.count as f64 / 1000.0),
+            max_ms: (self.count > 0).then(|| self.max_us as f64 / 1000.0),
+            p50_ms: self.quantile_ms(0.50),
+            p95_ms: self.quantile_ms(0.95),
+            p99_ms: self.quantile_ms(0.99),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TenantDeliveryStats {
+    fanout: Histogram,
+    probe_rtt: Histogram,
+    delivered: u64,
+    failed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliverySnapshot {
+    /// Time to write one event to every local recipient connection.
+    pub fanout: LatencySnapshot,
+    /// Client-measured `latency:probe` → `latency:ack` round trips.
+    pub probe_rtt: LatencySnapshot,
+    pub delivered: u64,
+    pub failed: u64,
+}
+
+/// In-memory, per-instance delivery metrics aggregated by tenant. Counters
+/// reset on restart; they are for SLO dashboards, not billing.
+#[derive(Default)]
+pub struct DeliveryMetrics {
+    tenants: DashMap<ObjectId, TenantDeliveryStats>,
+}
+
+impl DeliveryMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_fanout(
+        &self,
+        tenant_id: ObjectId,
+        elapsed: Duration,
+        delivered: u64,
+        failed: u64,
+    ) {
+        let mut stats = self.tenants.entry(tenant_id).or_default();
+        stats.fanout.record(elapsed);
+        stats.delivered += delivered;
+        stats.failed += failed;
+    }
+
+    /// Record a client-reported probe round trip. Returns `false` when the
+    /// value was rejected as implausible.
+    pub fn record_probe_rtt(&self, tenant_id: ObjectId, rtt_ms: u64) -> bool {
+        if rtt_ms > MAX_PROBE_RTT_MS {
+            return false;
+        }
+        self.tenants
+            .entry(tenant_id)
+            .or_default()
+            .probe_rtt
+            .record(Duration::from_millis(rtt_ms));
+        true
+    }
+
+    pub fn snapshot(&self, tenant_id: ObjectId) -> DeliverySnapshot {
+        let stats = self
+            .tenants
+            .get(&tenant_id)
+            .map(|s| s.clone())
+            .unwrap_or_default();
+        DeliverySnapshot {
+            fanout: stats.fanout.snapshot(),
+            probe_rtt: stats.probe_rtt.snapshot(),
+            delivered: stats.delivered,
+            failed: stats.failed,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod dispatcher;
 pub mod handler;
+pub mod metrics;
 pub mod redis_pubsub;
 pub mod remote_control;
 pub mod storage;
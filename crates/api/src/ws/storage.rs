@@ -1,52 +1,208 @@
 use axum::extract::ws::{Message, WebSocket};
 use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use futures::stream::SplitSink;
+use roomler_ai_config::WsSettings;
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::extractors::client::ClientInfo;
 
 pub type WsSender = Arc<Mutex<SplitSink<WebSocket, Message>>>;
 
+/// Event types that are only meaningful live and are never replayed.
+const TRANSIENT_TYPES: &[&str] = &["pong", "typing:start", "typing:stop"];
+
+/// A user's recent user-level events, numbered so a reconnecting client can
+/// ask for what it missed. `stream_id` changes whenever the numbering
+/// restarts (new buffer, other instance), telling the client its `last_seq`
+/// no longer applies.
+struct ReplayBuffer {
+    stream_id: String,
+    last_seq: u64,
+    events: VecDeque<(u64, String)>,
+    /// Set while the user has no connection on this instance.
+    disconnected_at: Option<Instant>,
+}
+
+impl ReplayBuffer {
+    fn new() -> Self {
+        Self {
+            stream_id: Uuid::new_v4().to_string(),
+            last_seq: 0,
+            events: VecDeque::new(),
+            disconnected_at: None,
+        }
+    }
+}
+
+/// Answer to a client's `resume`.
+pub enum ResumeOutcome {
+    /// Missed events, already serialized with their `seq`, oldest first.
+    Replay(Vec<String>),
+    /// The gap can't be filled; the client must refetch its state.
+    Refetch(&'static str),
+}
+
+/// One open connection, as listed in the user's sessions.
+#[derive(Clone)]
+pub struct Connection {
+    pub user_id: ObjectId,
+    pub sender: WsSender,
+    pub connected_at: DateTime<Utc>,
+    pub client: ClientInfo,
+}
+
 /// Tracks all active WebSocket connections by user ID and connection ID.
 /// Each user can have multiple connections (multiple tabs/devices).
 pub struct WsStorage {
-    /// user_id -> Vec of senders (for user-level broadcasts)
-    connections: DashMap<ObjectId, Vec<WsSender>>,
-    /// connection_id -> (user_id, sender) for connection-targeted sends
-    connection_map: DashMap<String, (ObjectId, WsSender)>,
+    /// user_id -> (connection_id, sender) of each connection, for
+    /// user-level broadcasts
+    connections: DashMap<ObjectId, Vec<(String, WsSender)>>,
+    /// connection_id -> connection, for connection-targeted sends
+    connection_map: DashMap<String, Connection>,
+    /// user_id -> recent user-level events for `resume`
+    replay: DashMap<ObjectId, ReplayBuffer>,
+    replay_capacity: usize,
+    resume_window: Duration,
 }
 
 impl WsStorage {
-    pub fn new() -> Self {
+    pub fn new(settings: &WsSettings) -> Self {
         Self {
             connections: DashMap::new(),
             connection_map: DashMap::new(),
+            replay: DashMap::new(),
+            replay_capacity: settings.replay_buffer_size,
+            resume_window: Duration::from_secs(settings.resume_window_secs),
         }
     }
 
-    pub fn add(&self, user_id: ObjectId, connection_id: String, sender: WsSender) {
+    pub fn add(
+        &self,
+        user_id: ObjectId,
+        connection_id: String,
+        sender: WsSender,
+        client: ClientInfo,
+    ) {
         self.connections
             .entry(user_id)
             .or_default()
-            .push(sender.clone());
-        self.connection_map.insert(connection_id, (user_id, sender));
+            .push((connection_id.clone(), sender.clone()));
+        self.connection_map.insert(
+            connection_id,
+            Connection {
+                user_id,
+                sender,
+                connected_at: Utc::now(),
+                client,
+            },
+        );
+
+        let mut buffer = self.replay.entry(user_id).or_insert_with(ReplayBuffer::new);
+        if self.expired(&buffer) {
+            *buffer = ReplayBuffer::new();
+        }
+        buffer.disconnected_at = None;
     }
 
     pub fn remove(&self, user_id: &ObjectId, connection_id: &str, sender: &WsSender) {
         if let Some(mut senders) = self.connections.get_mut(user_id) {
-            senders.retain(|s| !Arc::ptr_eq(s, sender));
+            senders.retain(|(_, s)| !Arc::ptr_eq(s, sender));
             if senders.is_empty() {
                 drop(senders);
                 self.connections.remove(user_id);
+                if let Some(mut buffer) = self.replay.get_mut(user_id) {
+                    buffer.disconnected_at = Some(Instant::now());
+                }
             }
         }
         self.connection_map.remove(connection_id);
+        self.replay.retain(|_, buffer| !self.expired(buffer));
+    }
+
+    fn expired(&self, buffer: &ReplayBuffer) -> bool {
+        buffer
+            .disconnected_at
+            .is_some_and(|at| at.elapsed() > self.resume_window)
+    }
+
+    /// Serializes a user-level event for `user_id`. Unless the event is
+    /// transient, it gets the user's next `seq` and is kept for replay.
+    pub fn stamp(&self, user_id: &ObjectId, message: &serde_json::Value) -> String {
+        let transient = message
+            .get("type")
+            .and_then(|t| t.as_str())
+            .is_some_and(|t| TRANSIENT_TYPES.contains(&t));
+        if !transient
+            && let Some(mut buffer) = self.replay.get_mut(user_id)
+            && !self.expired(&buffer)
+            && let Some(object) = message.as_object()
+        {
+            buffer.last_seq += 1;
+            let seq = buffer.last_seq;
+            let mut object = object.clone();
+            object.insert("seq".to_string(), seq.into());
+            let text = serde_json::Value::Object(object).to_string();
+            buffer.events.push_back((seq, text.clone()));
+            while buffer.events.len() > self.replay_capacity {
+                buffer.events.pop_front();
+            }
+            return text;
+        }
+        serde_json::to_string(message).unwrap_or_default()
+    }
+
+    /// The user's current stream id and last `seq`, sent on connect.
+    pub fn stream_position(&self, user_id: &ObjectId) -> Option<(String, u64)> {
+        self.replay
+            .get(user_id)
+            .map(|b| (b.stream_id.clone(), b.last_seq))
+    }
+
+    /// Events after `last_seq` in `stream_id` (when the client knows it),
+    /// or why they can't be replayed.
+    pub fn resume(
+        &self,
+        user_id: &ObjectId,
+        stream_id: Option<&str>,
+        last_seq: u64,
+    ) -> ResumeOutcome {
+        let Some(buffer) = self.replay.get(user_id) else {
+            return ResumeOutcome::Refetch("unknown_stream");
+        };
+        if stream_id.is_some_and(|id| id != buffer.stream_id) || last_seq > buffer.last_seq {
+            return ResumeOutcome::Refetch("unknown_stream");
+        }
+        if last_seq == buffer.last_seq {
+            return ResumeOutcome::Replay(Vec::new());
+        }
+        // The first missed event must still be buffered.
+        if buffer
+            .events
+            .front()
+            .is_none_or(|(seq, _)| *seq > last_seq + 1)
+        {
+            return ResumeOutcome::Refetch("gap_too_large");
+        }
+        ResumeOutcome::Replay(
+            buffer
+                .events
+                .iter()
+                .filter(|(seq, _)| *seq > last_seq)
+                .map(|(_, text)| text.clone())
+                .collect(),
+        )
     }
 
     pub fn get_senders(&self, user_id: &ObjectId) -> Vec<WsSender> {
         self.connections
             .get(user_id)
-            .map(|s| s.clone())
+            .map(|s| s.iter().map(|(_, sender)| sender.clone()).collect())
             .unwrap_or_default()
     }
 
@@ -54,7 +210,26 @@ impl WsStorage {
     pub fn get_sender_by_connection(&self, connection_id: &str) -> Option<WsSender> {
         self.connection_map
             .get(connection_id)
-            .map(|entry| entry.value().1.clone())
+            .map(|entry| entry.value().sender.clone())
+    }
+
+    /// The user's connections on this instance by connection id, oldest
+    /// first.
+    pub fn user_connections(&self, user_id: &ObjectId) -> Vec<(String, Connection)> {
+        let ids: Vec<String> = self
+            .connections
+            .get(user_id)
+            .map(|s| s.iter().map(|(id, _)| id.clone()).collect())
+            .unwrap_or_default();
+        let mut connections: Vec<(String, Connection)> = ids
+            .into_iter()
+            .filter_map(|id| {
+                let connection = self.connection_map.get(&id)?.value().clone();
+                Some((id, connection))
+            })
+            .collect();
+        connections.sort_by_key(|(_, c)| c.connected_at);
+        connections
     }
 
     /// Check if a user has any active WebSocket connections.
@@ -73,9 +248,3 @@ impl WsStorage {
         self.connections.iter().map(|r| r.value().len()).sum()
     }
 }
-
-impl Default for WsStorage {
-    fn default() -> Self {
-        Self::new()
-    }
-}
@@ -1,21 +1,50 @@
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub app: AppSettings,
     pub database: DatabaseSettings,
     pub jwt: JwtSettings,
+    pub internal_auth: InternalAuthSettings,
     pub redis: RedisSettings,
     pub s3: S3Settings,
     pub mediasoup: MediasoupSettings,
     pub turn: TurnSettings,
     pub claude: ClaudeSettings,
+    pub asr: AsrSettings,
     pub oauth: OAuthSettings,
     pub stripe: StripeSettings,
     pub giphy: GiphySettings,
     pub email: EmailSettings,
     pub push: PushSettings,
+    #[serde(default)]
+    pub features: FeatureFlagSettings,
+    pub onboarding: OnboardingSettings,
+    pub conference_limits: ConferenceLimitSettings,
+    pub reconciliation: ReconciliationSettings,
+    pub thread_summary: ThreadSummarySettings,
+    pub storage: StorageSettings,
+    pub message_archive: MessageArchiveSettings,
+    pub ws: WsSettings,
+    pub conference_chat: ConferenceChatSettings,
+    pub message_retention: MessageRetentionSettings,
+    pub presence: PresenceSettings,
+    pub rate_limit: RateLimitSettings,
+    pub export: ExportSettings,
+    pub follow_up: FollowUpSettings,
+    pub channel_digest: ChannelDigestSettings,
+    pub account_deletion: AccountDeletionSettings,
+    pub reaction_rules: ReactionRuleSettings,
+    pub webhooks: WebhookSettings,
+    pub quick_switch: QuickSwitchSettings,
+    pub analytics: AnalyticsSettings,
+    pub assets: AssetSettings,
+    pub recording_clips: RecordingClipSettings,
+    pub recording_playback: RecordingPlaybackSettings,
+    pub outbound: OutboundSettings,
+    pub sso: SsoSettings,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -41,6 +70,9 @@ pub struct AppSettings {
     pub static_dir: Option<String>,
     pub cors_origins: Vec<String>,
     pub frontend_url: String,
+    /// Reverse proxies whose `X-Forwarded-For` is believed, comma-separated
+    /// addresses or CIDR ranges. Empty uses the connecting address.
+    pub trusted_proxies: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -49,6 +81,28 @@ pub struct DatabaseSettings {
     pub name: String,
     pub max_pool_size: Option<u32>,
     pub min_pool_size: Option<u32>,
+    /// How the indexes are built after startup.
+    pub index_build: IndexBuildStrategy,
+}
+
+/// How the startup index builder issues `createIndexes`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexBuildStrategy {
+    /// One `createIndexes` per collection with `background: true`.
+    Background,
+    /// One index at a time, so a collection never has more than one build
+    /// in progress.
+    Rolling,
+}
+
+impl IndexBuildStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexBuildStrategy::Background => "background",
+            IndexBuildStrategy::Rolling => "rolling",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -59,6 +113,20 @@ pub struct JwtSettings {
     pub issuer: String,
 }
 
+/// Service tokens that internal workers (e.g. a split-out ASR service)
+/// present when calling back into the API.
+#[derive(Debug, Deserialize, Clone)]
+pub struct InternalAuthSettings {
+    /// Signing key, separate from `jwt.secret`. Empty disables service
+    /// tokens.
+    pub secret: String,
+    pub issuer: String,
+    /// Audience the API requires in service tokens.
+    pub audience: String,
+    /// Lifetime of issued tokens; tokens minted for longer are rejected.
+    pub token_ttl_secs: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct RedisSettings {
     pub url: String,
@@ -78,8 +146,20 @@ pub struct MediasoupSettings {
     pub num_workers: u32,
     pub listen_ip: String,
     pub announced_ip: String,
+    /// Split evenly across workers; each worker binds only its own slice.
     pub rtc_min_port: u16,
     pub rtc_max_port: u16,
+    /// Concurrent WebRTC transports (two per participant) the port range must
+    /// accommodate. Startup fails if a worker's slice is smaller than its share.
+    pub expected_max_transports: u32,
+    /// Set `SO_REUSEPORT` on UDP sockets.
+    pub udp_reuse_port: bool,
+    /// How long a participant whose connection dropped keeps its media state
+    /// for a reconnect. 0 closes it immediately.
+    pub reconnect_grace_secs: u64,
+    /// How long a conference whose last participant left keeps running
+    /// before it is ended and its router closed.
+    pub empty_room_grace_secs: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -89,6 +169,16 @@ pub struct TurnSettings {
     pub password: Option<String>,
     pub shared_secret: Option<String>,
     pub force_relay: Option<bool>,
+    /// Addresses the TURN server relays media from, comma-separated. A
+    /// transport whose ICE peer is one of them counts as relayed. Empty
+    /// resolves the host of `url`.
+    pub relay_ips: String,
+    /// How often relayed bytes are read from the transports. 0 turns relay
+    /// accounting off.
+    pub accounting_interval_secs: u64,
+    /// Relayed megabytes a tenant can use in a month before its admins are
+    /// alerted. 0 never alerts.
+    pub monthly_budget_mb: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -98,6 +188,36 @@ pub struct ClaudeSettings {
     pub max_tokens: u32,
 }
 
+/// Speech-to-text for voice notes, through an OpenAI-compatible
+/// `/v1/audio/transcriptions` endpoint (Whisper or a self-hosted server).
+#[derive(Debug, Deserialize, Clone)]
+pub struct AsrSettings {
+    /// Base URL of the ASR server. Empty disables transcription.
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub timeout_secs: u64,
+    /// Largest voice note accepted, in bytes.
+    pub max_voice_note_bytes: u64,
+    /// How often live transcription re-runs on speech still in progress to
+    /// publish interim captions. 0 only publishes final segments.
+    pub interim_interval_ms: u64,
+    /// Size of the capped `asr_metrics` collection that logs each live
+    /// segment's quality, in bytes. 0 turns the logging off.
+    pub metrics_max_bytes: u64,
+    /// Keep the audio of low-confidence segments from speakers who agreed
+    /// to share voice samples, as an evaluation set for model tuning.
+    pub sample_retention: bool,
+    /// Segments below this confidence qualify as samples.
+    pub sample_max_confidence: f64,
+    /// Longest sample kept, in bytes of audio.
+    pub sample_max_bytes: u64,
+    /// After a call switches ASR model, how long segments of the previous
+    /// model are still delivered, so speech buffered at the switch finishes
+    /// on it.
+    pub switch_drain_ms: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct StripeSettings {
     pub secret_key: String,
@@ -114,10 +234,50 @@ pub struct GiphySettings {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct EmailSettings {
+    pub provider: EmailProvider,
+    /// SendGrid API key; email is off with the SendGrid provider without one.
     pub api_key: String,
+    /// SMTP server; email is off with the SMTP provider without one.
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    /// Empty sends without authenticating.
+    pub smtp_username: String,
+    pub smtp_password: String,
+    pub smtp_tls: SmtpTls,
     pub from_email: String,
     pub from_name: String,
     pub activation_token_ttl_minutes: u64,
+    pub password_reset_ttl_minutes: u64,
+    pub email_change_ttl_minutes: u64,
+    /// Attempts per queued email, the first included, before it is failed.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub retry_base_secs: u64,
+    /// Time between sweeps sending queued emails that are due.
+    pub queue_interval_secs: u64,
+    /// How long after the first mention an offline user's mention digest
+    /// is sent; later mentions join the same digest.
+    pub mention_digest_delay_secs: u64,
+}
+
+/// Who delivers outgoing email.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailProvider {
+    Sendgrid,
+    Smtp,
+}
+
+/// How the SMTP connection is encrypted.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with `STARTTLS` (usually port 587).
+    Starttls,
+    /// TLS from the start (usually port 465).
+    Tls,
+    /// Unencrypted, for a relay on the local network.
+    None,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -127,6 +287,320 @@ pub struct PushSettings {
     pub contact: String,
 }
 
+/// Baseline feature-flag values. Keys are flag names (`new_search`), values
+/// the default state before any DB, tenant or user override is applied, e.g.
+/// `ROOMLER__FEATURES__DEFAULTS__NEW_SEARCH=true`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FeatureFlagSettings {
+    #[serde(default)]
+    pub defaults: HashMap<String, bool>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OnboardingSettings {
+    /// Channels created (and auto-joined by every member) when a tenant is
+    /// created. Empty disables seeding.
+    pub default_channels: Vec<String>,
+    /// Posted into the first default channel. Empty skips the message.
+    pub welcome_message: String,
+}
+
+/// Plan-based conference policies, enforced by a background sweep over
+/// active media rooms.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConferenceLimitSettings {
+    pub check_interval_secs: u64,
+    /// How long before a cutoff organizers get a `conference:limit_warning`.
+    pub warn_before_secs: u64,
+    #[serde(default)]
+    pub free: PlanConferenceLimits,
+    #[serde(default)]
+    pub pro: PlanConferenceLimits,
+    #[serde(default)]
+    pub business: PlanConferenceLimits,
+    #[serde(default)]
+    pub enterprise: PlanConferenceLimits,
+}
+
+/// `None` means unlimited.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PlanConferenceLimits {
+    pub max_duration_secs: Option<u64>,
+    /// Time with no audio/video producers before the conference is ended.
+    pub idle_timeout_secs: Option<u64>,
+    /// Participant cap, enforced on join. Mirrors the plan's
+    /// `video_max_participants`.
+    pub max_participants: Option<u32>,
+}
+
+/// Startup reconciliation of conferences left `in_progress` by a restart.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReconciliationSettings {
+    /// Conferences started at most this long ago get their media room
+    /// re-created so participants can reconnect; older ones are ended.
+    pub recreate_within_secs: u64,
+}
+
+/// AI summaries of long threads (`POST .../message/{id}/thread/summarize`).
+#[derive(Debug, Deserialize, Clone)]
+pub struct ThreadSummarySettings {
+    /// Threads with fewer replies than this can't be summarized.
+    pub min_replies: u32,
+    /// A cached summary is regenerated once this many replies arrive after it.
+    pub regenerate_after_replies: u32,
+}
+
+/// Moving old messages out of the hot `messages` collection into monthly
+/// `messages_archive_YYYY_MM` partitions.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MessageArchiveSettings {
+    pub enabled: bool,
+    /// Messages older than this are archived.
+    pub older_than_days: u32,
+    /// Messages moved per batch. A sweep runs batches until none are due.
+    pub batch_size: u32,
+    pub check_interval_secs: u64,
+}
+
+/// Purging conference chat past each tenant's retention period.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConferenceChatSettings {
+    pub purge_interval_secs: u64,
+}
+
+/// Purging channel messages past each tenant's and room's retention, and
+/// past the plan's history cap.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MessageRetentionSettings {
+    pub purge_interval_secs: u64,
+    /// Messages removed (and archived) per batch.
+    pub batch_size: u32,
+}
+
+/// Automatic presence changes for connected users who stop interacting.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PresenceSettings {
+    /// Idle time after which an online user shows as idle.
+    pub away_after_secs: u64,
+    /// Idle time after which an online or idle user shows as offline.
+    pub offline_after_secs: u64,
+    pub sweep_interval_secs: u64,
+}
+
+/// Token-bucket budgets for `/api` requests, keyed by user (by client IP
+/// when unauthenticated).
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitSettings {
+    pub enabled: bool,
+    /// Every `/api` request.
+    pub api: RateBudget,
+    /// Login, registration and account activation, on top of `api`.
+    pub login: RateBudget,
+    /// Invite lookup and acceptance, and meeting code lookups, on top of
+    /// `api`.
+    pub invite: RateBudget,
+    /// Replaces `api` for requests to a sandbox tenant's routes.
+    pub sandbox: RateBudget,
+    /// Public channel views, on top of `api`.
+    pub public: RateBudget,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct RateBudget {
+    /// Requests allowed back to back.
+    pub burst: u32,
+    /// Sustained rate the bucket refills at.
+    pub per_minute: u32,
+}
+
+/// Conversation exports: small ones are generated inline, larger ones run
+/// as background tasks.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExportSettings {
+    /// Rooms with at most this many messages are exported in the request.
+    pub sync_max_messages: u64,
+    /// Exports a tenant can have running at once on one instance.
+    pub max_concurrent_per_tenant: u32,
+}
+
+/// Reminders for call follow-ups that come due.
+#[derive(Debug, Deserialize, Clone)]
+pub struct FollowUpSettings {
+    pub reminder_interval_secs: u64,
+}
+
+/// Daily digests posted into channels that configured one.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChannelDigestSettings {
+    /// How often to check for channels whose digest is due.
+    pub sweep_interval_secs: u64,
+}
+
+/// Accounts deleted by their users.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AccountDeletionSettings {
+    /// Days a deleted account's profile is kept before it's anonymized.
+    pub grace_days: u32,
+    /// How often to look for accounts whose grace period ended.
+    pub sweep_interval_secs: u64,
+}
+
+/// Limits on tenants' reaction-triggered automations.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReactionRuleSettings {
+    pub max_rules_per_tenant: u32,
+    /// Firings allowed per rule per minute, against runaway loops.
+    pub max_fires_per_minute: u32,
+    pub webhook_timeout_secs: u64,
+}
+
+/// Tenants' outgoing webhooks and their retries.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookSettings {
+    pub max_webhooks_per_tenant: u32,
+    pub timeout_secs: u64,
+    /// Attempts per delivery, the first included, before it is failed.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub retry_base_secs: u64,
+    /// Time between sweeps retrying deliveries that are due.
+    pub retry_interval_secs: u64,
+}
+
+/// The Cmd+K quick switcher's per-user candidate cache.
+#[derive(Debug, Deserialize, Clone)]
+pub struct QuickSwitchSettings {
+    /// How long a user's channels, DMs, members and conferences are
+    /// reused before being loaded again.
+    pub cache_ttl_secs: u64,
+}
+
+/// Tenant analytics exports and the monthly emailed report.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnalyticsSettings {
+    /// How often to check whether last month's reports are due.
+    pub report_interval_secs: u64,
+    /// Longest date range a single export may cover.
+    pub max_range_days: u32,
+}
+
+/// Content-addressed image URLs (avatars, custom emoji, thumbnails).
+#[derive(Debug, Deserialize, Clone)]
+pub struct AssetSettings {
+    /// Key signing asset URLs for tenants with private assets.
+    pub signing_secret: String,
+    /// Signed URLs stay valid for at least this long, and at most twice it.
+    pub signed_url_ttl_secs: u64,
+}
+
+/// Highlights cut from conference recordings.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RecordingClipSettings {
+    /// The ffmpeg binary, by path or name on `PATH`.
+    pub ffmpeg_path: String,
+    /// Longest clip that may be cut.
+    pub max_clip_secs: u32,
+    /// ffmpeg is killed after this long.
+    pub timeout_secs: u64,
+}
+
+/// Expiring URLs for embedding recordings in a `<video>` player.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RecordingPlaybackSettings {
+    /// Key signing playback URLs of recordings on local storage.
+    pub signing_secret: String,
+    /// How long a playback URL stays valid.
+    pub url_ttl_secs: u64,
+}
+
+/// HTTP calls to third parties: Stripe, Giphy, OAuth providers, webhooks,
+/// SendGrid, the ASR backend and document recognition.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OutboundSettings {
+    /// Timeout for one attempt, for services without their own setting.
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    /// Attempts per call, the first included. Only idempotent calls are
+    /// retried.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub retry_base_ms: u64,
+    /// Consecutive failures after which calls to a host fail fast.
+    pub breaker_failure_threshold: u32,
+    /// How long a tripped host is skipped before one trial call is let
+    /// through.
+    pub breaker_open_secs: u64,
+    /// Let webhooks, reaction rule webhooks and SSO providers reach
+    /// loopback, private and link-local addresses. Only for development.
+    pub allow_private_targets: bool,
+}
+
+/// Tenant single sign-on.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SsoSettings {
+    /// DNS-over-HTTPS endpoint answering JSON queries
+    /// (`application/dns-json`), where the TXT records proving a tenant
+    /// owns an email domain are looked up.
+    pub dns_resolver_url: String,
+}
+
+/// Replay of missed WebSocket events after a brief disconnect.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsSettings {
+    /// Most recent events kept per user for `resume`.
+    pub replay_buffer_size: usize,
+    /// How long a disconnected user's buffer is kept; resuming later
+    /// requires a full refetch.
+    pub resume_window_secs: u64,
+    /// How long before a connection's access token expires it is sent
+    /// `auth:expiring`; 0 sends no warning.
+    pub auth_expiry_warning_secs: u64,
+    /// What happens to a connection past the user's plan session limit.
+    pub session_limit_policy: SessionLimitPolicy,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionLimitPolicy {
+    /// Close the user's oldest connection to make room.
+    EvictOldest,
+    /// Close the new connection.
+    Reject,
+}
+
+impl SessionLimitPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionLimitPolicy::EvictOldest => "evict_oldest",
+            SessionLimitPolicy::Reject => "reject",
+        }
+    }
+}
+
+/// Where uploaded files and generated exports are stored.
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    /// GridFS bucket name (collections `<bucket>.files` / `<bucket>.chunks`).
+    pub gridfs_bucket: String,
+    /// Writes larger than this are rejected.
+    pub max_object_size_bytes: u64,
+    /// How long a pre-signed direct upload URL stays valid.
+    pub presign_ttl_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Files under `ROOMLER_UPLOAD_DIR`.
+    Local,
+    /// MongoDB GridFS in the app database, for deployments without object
+    /// storage or a shared disk.
+    Gridfs,
+    /// The bucket in `s3.*`; clients can also upload straight to it.
+    S3,
+}
+
 impl Settings {
     pub fn load() -> Result<Self, ConfigError> {
         let config = Config::builder()
@@ -137,12 +611,18 @@ impl Settings {
             .set_default("app.port", 3000)?
             .set_default("app.cors_origins", Vec::<String>::new())?
             .set_default("app.frontend_url", "http://localhost:5173")?
+            .set_default("app.trusted_proxies", "")?
             .set_default("database.url", "mongodb://localhost:27019")?
             .set_default("database.name", "roomler-ai")?
+            .set_default("database.index_build", "background")?
             .set_default("jwt.secret", "change-me-in-production")?
             .set_default("jwt.access_token_ttl_secs", 3600)?
             .set_default("jwt.refresh_token_ttl_secs", 604800)?
             .set_default("jwt.issuer", "roomler-ai")?
+            .set_default("internal_auth.secret", "")?
+            .set_default("internal_auth.issuer", "roomler-ai-internal")?
+            .set_default("internal_auth.audience", "roomler-ai-api")?
+            .set_default("internal_auth.token_ttl_secs", 300)?
             .set_default("redis.url", "redis://127.0.0.1:6379")?
             .set_default("s3.endpoint", "http://localhost:9000")?
             .set_default("s3.access_key", "minioadmin")?
@@ -154,12 +634,29 @@ impl Settings {
             .set_default("mediasoup.announced_ip", "127.0.0.1")?
             .set_default("mediasoup.rtc_min_port", 40000)?
             .set_default("mediasoup.rtc_max_port", 49999)?
+            .set_default("mediasoup.expected_max_transports", 2000)?
+            .set_default("mediasoup.udp_reuse_port", false)?
+            .set_default("mediasoup.reconnect_grace_secs", 15u64)?
+            .set_default("mediasoup.empty_room_grace_secs", 60u64)?
             .set_default("turn.url", None::<String>)?
             .set_default("turn.username", None::<String>)?
             .set_default("turn.password", None::<String>)?
             .set_default("turn.force_relay", false)?
+            .set_default("turn.relay_ips", "")?
+            .set_default("turn.accounting_interval_secs", 60u64)?
+            .set_default("turn.monthly_budget_mb", 0u64)?
             .set_default("claude.model", "claude-sonnet-4-5-20250929")?
             .set_default("claude.max_tokens", 4096)?
+            .set_default("asr.url", "")?
+            .set_default("asr.model", "whisper-1")?
+            .set_default("asr.timeout_secs", 30u64)?
+            .set_default("asr.max_voice_note_bytes", 10_485_760u64)?
+            .set_default("asr.interim_interval_ms", 0u64)?
+            .set_default("asr.metrics_max_bytes", 67_108_864u64)?
+            .set_default("asr.sample_retention", false)?
+            .set_default("asr.sample_max_confidence", 0.6)?
+            .set_default("asr.sample_max_bytes", 1_048_576u64)?
+            .set_default("asr.switch_drain_ms", 15_000u64)?
             .set_default("oauth.base_url", "http://localhost:5001")?
             .set_default("oauth.google.client_id", "")?
             .set_default("oauth.google.client_secret", "")?
@@ -177,13 +674,109 @@ impl Settings {
             .set_default("stripe.price_pro", "")?
             .set_default("stripe.price_business", "")?
             .set_default("giphy.api_key", "")?
+            .set_default("email.provider", "sendgrid")?
             .set_default("email.api_key", "")?
+            .set_default("email.smtp_host", "")?
+            .set_default("email.smtp_port", 587)?
+            .set_default("email.smtp_username", "")?
+            .set_default("email.smtp_password", "")?
+            .set_default("email.smtp_tls", "starttls")?
             .set_default("email.from_email", "noreply@roomler.ai")?
             .set_default("email.from_name", "Roomler")?
             .set_default("email.activation_token_ttl_minutes", 5u64)?
+            .set_default("email.password_reset_ttl_minutes", 60u64)?
+            .set_default("email.email_change_ttl_minutes", 60u64)?
+            .set_default("email.max_attempts", 5u32)?
+            .set_default("email.retry_base_secs", 60u64)?
+            .set_default("email.queue_interval_secs", 10u64)?
+            .set_default("email.mention_digest_delay_secs", 900u64)?
             .set_default("push.vapid_public_key", "")?
             .set_default("push.vapid_private_key", "")?
             .set_default("push.contact", "mailto:noreply@roomler.ai")?
+            .set_default(
+                "onboarding.default_channels",
+                vec!["general".to_string(), "random".to_string()],
+            )?
+            .set_default(
+                "onboarding.welcome_message",
+                "Welcome to your new workspace! Say hi in #general, start a call, or invite your team.",
+            )?
+            .set_default("conference_limits.check_interval_secs", 30u64)?
+            .set_default("conference_limits.warn_before_secs", 300u64)?
+            .set_default("conference_limits.free.max_duration_secs", 3600u64)?
+            .set_default("conference_limits.free.idle_timeout_secs", 600u64)?
+            .set_default("conference_limits.pro.max_duration_secs", 6 * 3600u64)?
+            .set_default("conference_limits.pro.idle_timeout_secs", 1800u64)?
+            .set_default("conference_limits.business.max_duration_secs", 12 * 3600u64)?
+            .set_default("conference_limits.business.idle_timeout_secs", 1800u64)?
+            .set_default("conference_limits.enterprise.idle_timeout_secs", 3600u64)?
+            .set_default("conference_limits.pro.max_participants", 10u64)?
+            .set_default("conference_limits.business.max_participants", 100u64)?
+            .set_default("conference_limits.enterprise.max_participants", 100u64)?
+            .set_default("reconciliation.recreate_within_secs", 900u64)?
+            .set_default("thread_summary.min_replies", 20u32)?
+            .set_default("thread_summary.regenerate_after_replies", 10u32)?
+            .set_default("storage.backend", "local")?
+            .set_default("storage.gridfs_bucket", "uploads")?
+            .set_default("storage.max_object_size_bytes", 104_857_600u64)?
+            .set_default("storage.presign_ttl_secs", 900u64)?
+            .set_default("message_archive.enabled", false)?
+            .set_default("message_archive.older_than_days", 365u32)?
+            .set_default("message_archive.batch_size", 1000u32)?
+            .set_default("message_archive.check_interval_secs", 3600u64)?
+            .set_default("ws.replay_buffer_size", 256u64)?
+            .set_default("ws.resume_window_secs", 300u64)?
+            .set_default("ws.auth_expiry_warning_secs", 60u64)?
+            .set_default("ws.session_limit_policy", "evict_oldest")?
+            .set_default("conference_chat.purge_interval_secs", 3600u64)?
+            .set_default("message_retention.purge_interval_secs", 3600u64)?
+            .set_default("message_retention.batch_size", 1000u32)?
+            .set_default("presence.away_after_secs", 300u64)?
+            .set_default("presence.offline_after_secs", 3600u64)?
+            .set_default("presence.sweep_interval_secs", 30u64)?
+            .set_default("rate_limit.enabled", true)?
+            .set_default("rate_limit.api.burst", 120u32)?
+            .set_default("rate_limit.api.per_minute", 600u32)?
+            .set_default("rate_limit.login.burst", 10u32)?
+            .set_default("rate_limit.login.per_minute", 10u32)?
+            .set_default("rate_limit.invite.burst", 20u32)?
+            .set_default("rate_limit.invite.per_minute", 20u32)?
+            .set_default("rate_limit.sandbox.burst", 600u32)?
+            .set_default("rate_limit.sandbox.per_minute", 3000u32)?
+            .set_default("rate_limit.public.burst", 30u32)?
+            .set_default("rate_limit.public.per_minute", 60u32)?
+            .set_default("export.sync_max_messages", 500u64)?
+            .set_default("export.max_concurrent_per_tenant", 2u32)?
+            .set_default("follow_up.reminder_interval_secs", 60u64)?
+            .set_default("channel_digest.sweep_interval_secs", 300u64)?
+            .set_default("account_deletion.grace_days", 30u32)?
+            .set_default("account_deletion.sweep_interval_secs", 3600u64)?
+            .set_default("reaction_rules.max_rules_per_tenant", 50u32)?
+            .set_default("reaction_rules.max_fires_per_minute", 30u32)?
+            .set_default("reaction_rules.webhook_timeout_secs", 5u64)?
+            .set_default("webhooks.max_webhooks_per_tenant", 20u32)?
+            .set_default("webhooks.timeout_secs", 10u64)?
+            .set_default("webhooks.max_attempts", 5u32)?
+            .set_default("webhooks.retry_base_secs", 30u64)?
+            .set_default("webhooks.retry_interval_secs", 15u64)?
+            .set_default("quick_switch.cache_ttl_secs", 30u64)?
+            .set_default("analytics.report_interval_secs", 3600u64)?
+            .set_default("analytics.max_range_days", 366u32)?
+            .set_default("assets.signing_secret", "change-me-in-production")?
+            .set_default("assets.signed_url_ttl_secs", 86400u64)?
+            .set_default("recording_clips.ffmpeg_path", "ffmpeg")?
+            .set_default("recording_clips.max_clip_secs", 600u32)?
+            .set_default("recording_clips.timeout_secs", 300u64)?
+            .set_default("recording_playback.signing_secret", "change-me-in-production")?
+            .set_default("recording_playback.url_ttl_secs", 3600u64)?
+            .set_default("outbound.timeout_secs", 15u64)?
+            .set_default("outbound.connect_timeout_secs", 5u64)?
+            .set_default("outbound.max_attempts", 3u32)?
+            .set_default("outbound.retry_base_ms", 200u64)?
+            .set_default("outbound.breaker_failure_threshold", 5u32)?
+            .set_default("outbound.breaker_open_secs", 30u64)?
+            .set_default("outbound.allow_private_targets", false)?
+            .set_default("sso.dns_resolver_url", "https://cloudflare-dns.com/dns-query")?
             .build()?;
 
         config.try_deserialize()
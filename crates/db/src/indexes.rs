@@ -1,234 +1,671 @@
-use mongodb::{Database, IndexModel, options::IndexOptions};
-use tracing::info;
+//! The MongoDB indexes and the [`IndexBuilder`] that creates them. Builds
+//! run in a background task after startup, so a large collection can't
+//! stall boot; [`IndexBuilder::report`] shows how far they got.
+//!
+//! Unique indexes are required: writes rely on them to reject duplicates,
+//! so they are built first and health checks fail while one of them has
+//! failed. The others only speed up queries; a failed one is reported but
+//! leaves the instance healthy. Duplicates that would stop a unique index
+//! from building are cleared first (see [`IndexBuilder::prepare`]).
 
-pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error> {
-    // Tenants
-    create_indexes(
-        db,
-        "tenants",
-        vec![
-            index_unique(bson::doc! { "slug": 1 }),
-            index(bson::doc! { "owner_id": 1 }),
-        ],
-    )
-    .await?;
+use std::sync::{Arc, Mutex, PoisonError};
 
-    // Users
-    create_indexes(
-        db,
-        "users",
-        vec![
-            index_unique(bson::doc! { "email": 1 }),
-            index_unique(bson::doc! { "username": 1 }),
-            index_text(bson::doc! { "display_name": "text", "username": "text" }),
-        ],
-    )
-    .await?;
+use bson::{Bson, Document};
+use chrono::{DateTime, Utc};
+use mongodb::{Collection, Database, IndexModel, options::IndexOptions};
+use roomler_ai_config::IndexBuildStrategy;
+use serde::Serialize;
+use tracing::{info, warn};
 
-    // Tenant Members
-    create_indexes(
-        db,
-        "tenant_members",
-        vec![
-            index_unique(bson::doc! { "tenant_id": 1, "user_id": 1 }),
-            index(bson::doc! { "user_id": 1 }),
-        ],
-    )
-    .await?;
+/// Where an index's build is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexState {
+    Pending,
+    Building,
+    Ready,
+    Failed,
+}
 
-    // Roles
-    create_indexes(
-        db,
-        "roles",
-        vec![
-            index_unique(bson::doc! { "tenant_id": 1, "name": 1 }),
-            index(bson::doc! { "tenant_id": 1, "position": 1 }),
-        ],
-    )
-    .await?;
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub collection: &'static str,
+    /// The name MongoDB gives the index, e.g. `room_id_1_created_at_-1`.
+    pub name: String,
+    pub required: bool,
+    pub state: IndexState,
+    pub error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
 
-    // Rooms
-    create_indexes(
-        db,
-        "rooms",
-        vec![
-            index(bson::doc! { "tenant_id": 1, "parent_id": 1, "position": 1 }),
-            index_unique(bson::doc! { "tenant_id": 1, "path": 1 }),
-            index(bson::doc! { "tenant_id": 1, "name": 1 }),
-            index(bson::doc! { "tenant_id": 1, "is_default": 1 }),
-            index_unique_sparse(bson::doc! { "meeting_code": 1 }),
-            index_text(bson::doc! { "name": "text", "purpose": "text", "tags": "text" }),
-        ],
-    )
-    .await?;
+/// The builder's overall state, as health checks see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexHealth {
+    /// Every index is built.
+    Ready,
+    /// Builds are pending or running, and no required index failed.
+    Building,
+    /// Done, but optional indexes failed; the queries they serve are slower.
+    Degraded,
+    /// A required index failed.
+    Failed,
+}
 
-    // Room Members
-    create_indexes(
-        db,
-        "room_members",
-        vec![
-            index_unique(bson::doc! { "room_id": 1, "user_id": 1 }),
-            index(bson::doc! { "user_id": 1, "tenant_id": 1 }),
-        ],
-    )
-    .await?;
+impl IndexHealth {
+    /// Whether the instance should pass health checks.
+    pub fn is_healthy(self) -> bool {
+        self != IndexHealth::Failed
+    }
+}
 
-    // Messages
-    create_indexes(
-        db,
-        "messages",
-        vec![
-            index(bson::doc! { "room_id": 1, "created_at": -1 }),
-            index(bson::doc! { "thread_id": 1, "created_at": 1 }),
-            index(bson::doc! { "tenant_id": 1, "author_id": 1, "created_at": -1 }),
-            index(bson::doc! { "room_id": 1, "is_pinned": 1 }),
-            index(bson::doc! { "mentions.users": 1 }),
-            index_text(bson::doc! { "content": "text" }),
-        ],
-    )
-    .await?;
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexReport {
+    pub strategy: &'static str,
+    pub health: IndexHealth,
+    pub total: usize,
+    pub ready: usize,
+    pub building: usize,
+    pub pending: usize,
+    pub failed: usize,
+    pub indexes: Vec<IndexStatus>,
+}
 
-    // Reactions
-    create_indexes(
-        db,
-        "reactions",
-        vec![index_unique(
-            bson::doc! { "message_id": 1, "emoji.value": 1, "user_id": 1 },
-        )],
-    )
-    .await?;
+/// Builds the indexes in [`plan`] and tracks each one's progress. Cheap to
+/// clone; clones share the progress.
+#[derive(Clone)]
+pub struct IndexBuilder {
+    db: Database,
+    strategy: IndexBuildStrategy,
+    models: Arc<Vec<IndexModel>>,
+    statuses: Arc<Mutex<Vec<IndexStatus>>>,
+}
 
-    // Recordings
-    create_indexes(
-        db,
-        "recordings",
-        vec![
-            index(bson::doc! { "room_id": 1, "recording_type": 1 }),
-            index(bson::doc! { "tenant_id": 1, "status": 1 }),
-        ],
-    )
-    .await?;
+impl IndexBuilder {
+    pub fn new(db: Database, strategy: IndexBuildStrategy) -> Self {
+        let (models, statuses) = plan()
+            .into_iter()
+            .flat_map(|(collection, models)| {
+                models.into_iter().map(move |model| {
+                    let status = IndexStatus {
+                        collection,
+                        name: index_name(&model.keys),
+                        required: is_unique(&model),
+                        state: IndexState::Pending,
+                        error: None,
+                        started_at: None,
+                        finished_at: None,
+                    };
+                    (model, status)
+                })
+            })
+            .unzip();
+        Self {
+            db,
+            strategy,
+            models: Arc::new(models),
+            statuses: Arc::new(Mutex::new(statuses)),
+        }
+    }
 
-    // Files
-    create_indexes(
-        db,
-        "files",
-        vec![
-            index(bson::doc! { "tenant_id": 1, "context.context_type": 1, "context.entity_id": 1 }),
-            index(bson::doc! { "tenant_id": 1, "uploaded_by": 1, "created_at": -1 }),
-            index(bson::doc! { "tenant_id": 1, "context.room_id": 1, "created_at": -1 }),
-            index(bson::doc! { "external_source.provider": 1, "external_source.external_id": 1 }),
-        ],
-    )
-    .await?;
+    /// Build every index: the required ones first, then the rest, one
+    /// collection at a time. A failed build is recorded and the others
+    /// carry on; it is retried on the next start.
+    pub async fn run(&self) -> IndexHealth {
+        let statuses = self.statuses().clone();
+        for required in [true, false] {
+            let mut collections: Vec<&'static str> =
+                statuses.iter().map(|s| s.collection).collect();
+            collections.dedup();
+            for collection in collections {
+                let ids: Vec<usize> = statuses
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.collection == collection && s.required == required)
+                    .map(|(i, _)| i)
+                    .collect();
+                if ids.is_empty() {
+                    continue;
+                }
+                if required && let Err(e) = self.prepare(collection).await {
+                    warn!(collection, %e, "Clearing duplicates before index build failed");
+                }
+                match self.strategy {
+                    IndexBuildStrategy::Background => self.build(collection, &ids).await,
+                    IndexBuildStrategy::Rolling => {
+                        for id in ids {
+                            self.build(collection, &[id]).await;
+                        }
+                    }
+                }
+            }
+        }
 
-    // Invites
-    create_indexes(
-        db,
-        "invites",
-        vec![
-            index_unique(bson::doc! { "code": 1 }),
-            index(bson::doc! { "tenant_id": 1, "status": 1 }),
-        ],
-    )
-    .await?;
+        let report = self.report();
+        if report.failed == 0 {
+            info!(total = report.total, "All indexes ensured");
+        } else {
+            warn!(
+                failed = report.failed,
+                health = ?report.health,
+                "Some indexes failed to build"
+            );
+        }
+        report.health
+    }
 
-    // Background Tasks
-    create_indexes(
-        db,
-        "background_tasks",
-        vec![
-            index(bson::doc! { "tenant_id": 1, "user_id": 1, "status": 1 }),
-            index_ttl(bson::doc! { "expires_at": 1 }, 0),
-        ],
-    )
-    .await?;
+    pub fn report(&self) -> IndexReport {
+        let indexes = self.statuses().clone();
+        let count = |state| indexes.iter().filter(|s| s.state == state).count();
+        let (ready, building, pending, failed) = (
+            count(IndexState::Ready),
+            count(IndexState::Building),
+            count(IndexState::Pending),
+            count(IndexState::Failed),
+        );
+        let health = if indexes
+            .iter()
+            .any(|s| s.required && s.state == IndexState::Failed)
+        {
+            IndexHealth::Failed
+        } else if building + pending > 0 {
+            IndexHealth::Building
+        } else if failed > 0 {
+            IndexHealth::Degraded
+        } else {
+            IndexHealth::Ready
+        };
+        IndexReport {
+            strategy: self.strategy.as_str(),
+            health,
+            total: indexes.len(),
+            ready,
+            building,
+            pending,
+            failed,
+            indexes,
+        }
+    }
 
-    // Audit Logs
-    create_indexes(
-        db,
-        "audit_logs",
-        vec![
-            index(bson::doc! { "tenant_id": 1, "created_at": -1 }),
-            index(bson::doc! { "tenant_id": 1, "action": 1, "created_at": -1 }),
-            index(bson::doc! { "tenant_id": 1, "actor_id": 1, "created_at": -1 }),
-            // Auto-expire audit logs after 90 days
-            index_ttl(bson::doc! { "created_at": 1 }, 90 * 24 * 60 * 60),
-        ],
-    )
-    .await?;
+    pub fn health(&self) -> IndexHealth {
+        self.report().health
+    }
 
-    // Notifications
-    create_indexes(
-        db,
-        "notifications",
-        vec![
-            index(bson::doc! { "user_id": 1, "is_read": 1, "created_at": -1 }),
-            index(bson::doc! { "tenant_id": 1, "user_id": 1 }),
-        ],
-    )
-    .await?;
+    /// Clear data written before a unique index existed that would stop it
+    /// from building.
+    async fn prepare(&self, collection: &str) -> Result<(), mongodb::error::Error> {
+        match collection {
+            "messages" => clear_duplicate_nonces(&self.db).await,
+            _ => Ok(()),
+        }
+    }
 
-    // Custom Emojis
-    create_indexes(
-        db,
-        "custom_emojis",
-        vec![index_unique(bson::doc! { "tenant_id": 1, "name": 1 })],
-    )
-    .await?;
+    /// Build indexes `ids` of `collection` with one `createIndexes`.
+    async fn build(&self, collection: &str, ids: &[usize]) {
+        self.update(ids, IndexState::Building, None);
+        let coll = self.db.collection::<Document>(collection);
+        let models: Vec<IndexModel> = ids.iter().map(|&i| self.model(i)).collect();
+        match coll.create_indexes(models).await {
+            Ok(_) => {
+                info!(collection, count = ids.len(), "Indexes created");
+                self.update(ids, IndexState::Ready, None);
+            }
+            // Retry one at a time, so one bad index doesn't fail the rest,
+            // replacing any existing index with the same keys or name but
+            // different options (e.g. adding TTL to an existing index).
+            Err(e) if ids.len() > 1 || is_conflict(&e) => {
+                warn!(collection, %e, "Batch index build failed, retrying one at a time");
+                for &i in ids {
+                    let result = self.replace(&coll, i).await;
+                    self.finish(collection, i, result);
+                }
+            }
+            Err(e) => {
+                for &i in ids {
+                    self.finish(collection, i, Err(e.clone()));
+                }
+            }
+        }
+    }
 
-    // Activation Codes
-    create_indexes(
-        db,
-        "activation_codes",
-        vec![
-            index(bson::doc! { "user_id": 1 }),
-            // TTL: auto-expire when valid_to passes
-            index_ttl(bson::doc! { "valid_to": 1 }, 0),
-        ],
-    )
-    .await?;
+    /// Create index `i`, dropping an existing index it conflicts with first.
+    async fn replace(
+        &self,
+        coll: &Collection<Document>,
+        i: usize,
+    ) -> Result<(), mongodb::error::Error> {
+        match coll.create_index(self.model(i)).await {
+            Err(e) if is_conflict(&e) => {
+                let name = self.statuses()[i].name.clone();
+                let keys = &self.models[i].keys;
+                let mut existing = coll.list_indexes().await?;
+                while existing.advance().await? {
+                    let index = existing.deserialize_current()?;
+                    let existing_name = index.options.and_then(|o| o.name).unwrap_or_default();
+                    let same = existing_name == name || index.keys == *keys;
+                    if same && existing_name != "_id_" {
+                        coll.drop_index(existing_name).await?;
+                    }
+                }
+                coll.create_index(self.model(i)).await?;
+                info!(
+                    collection = coll.name(),
+                    index = %name,
+                    "Index recreated after conflict resolution"
+                );
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        }
+    }
 
-    // Remote-control agents
-    create_indexes(
-        db,
-        "agents",
-        vec![
-            index_unique(bson::doc! { "tenant_id": 1, "machine_id": 1 }),
-            index(bson::doc! { "tenant_id": 1, "status": 1 }),
-            index(bson::doc! { "owner_user_id": 1 }),
-        ],
-    )
-    .await?;
+    fn model(&self, i: usize) -> IndexModel {
+        let mut model = self.models[i].clone();
+        if self.strategy == IndexBuildStrategy::Background {
+            let mut options = model.options.unwrap_or_default();
+            options.background = Some(true);
+            model.options = Some(options);
+        }
+        model
+    }
+
+    fn finish(&self, collection: &str, i: usize, result: Result<(), mongodb::error::Error>) {
+        match result {
+            Ok(()) => self.update(&[i], IndexState::Ready, None),
+            Err(e) => {
+                warn!(collection, index = %self.statuses()[i].name, %e, "Index build failed");
+                self.update(&[i], IndexState::Failed, Some(e.to_string()));
+            }
+        }
+    }
 
-    // Remote-control sessions
-    create_indexes(
-        db,
-        "remote_sessions",
-        vec![
-            index(bson::doc! { "agent_id": 1, "created_at": -1 }),
-            index(bson::doc! { "controller_user_id": 1, "created_at": -1 }),
-            index(bson::doc! { "tenant_id": 1, "phase": 1 }),
-        ],
-    )
-    .await?;
+    fn update(&self, ids: &[usize], state: IndexState, error: Option<String>) {
+        let now = Utc::now();
+        let mut statuses = self.statuses();
+        for &i in ids {
+            let status = &mut statuses[i];
+            status.state = state;
+            status.error = error.clone();
+            if state == IndexState::Building {
+                status.started_at = Some(now);
+            } else {
+                status.finished_at = Some(now);
+            }
+        }
+    }
 
-    // Remote-control audit log — 90-day retention
-    create_indexes(
-        db,
-        "remote_audit",
-        vec![
-            index(bson::doc! { "session_id": 1, "at": 1 }),
-            index(bson::doc! { "tenant_id": 1, "at": -1 }),
-            index_ttl(bson::doc! { "at": 1 }, 90 * 24 * 60 * 60),
-        ],
-    )
-    .await?;
+    fn statuses(&self) -> std::sync::MutexGuard<'_, Vec<IndexStatus>> {
+        self.statuses.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
 
-    info!("All indexes ensured");
+/// Keep the `nonce` only on the earliest of an author's messages sharing one
+/// in a room; the later ones stay, but no longer answer retried sends.
+async fn clear_duplicate_nonces(db: &Database) -> Result<(), mongodb::error::Error> {
+    let messages = db.collection::<Document>("messages");
+    let pipeline = vec![
+        bson::doc! { "$match": { "nonce": { "$type": "string" } } },
+        bson::doc! { "$sort": { "_id": 1 } },
+        bson::doc! { "$group": {
+            "_id": { "room_id": "$room_id", "author_id": "$author_id", "nonce": "$nonce" },
+            "ids": { "$push": "$_id" },
+        }},
+        bson::doc! { "$match": { "ids.1": { "$exists": true } } },
+    ];
+    let mut duplicates = Vec::new();
+    let mut cursor = messages.aggregate(pipeline).await?;
+    while cursor.advance().await? {
+        let group = cursor.deserialize_current()?;
+        if let Ok(ids) = group.get_array("ids") {
+            duplicates.extend(ids.iter().skip(1).cloned());
+        }
+    }
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    let result = messages
+        .update_many(
+            bson::doc! { "_id": { "$in": duplicates } },
+            bson::doc! { "$unset": { "nonce": "" } },
+        )
+        .await?;
+    warn!(
+        cleared = result.modified_count,
+        "Cleared duplicate message nonces"
+    );
     Ok(())
 }
 
+/// Every collection's indexes.
+fn plan() -> Vec<(&'static str, Vec<IndexModel>)> {
+    vec![
+        // Tenants
+        (
+            "tenants",
+            vec![
+                index_unique(bson::doc! { "slug": 1 }),
+                index(bson::doc! { "owner_id": 1 }),
+                index_unique_sparse(bson::doc! { "sso.domains": 1 }),
+            ],
+        ),
+        // Users
+        (
+            "users",
+            vec![
+                index_unique(bson::doc! { "email": 1 }),
+                index_unique(bson::doc! { "username": 1 }),
+                index_text(bson::doc! { "display_name": "text", "username": "text" }),
+                index(bson::doc! { "purge_at": 1 }),
+                index_unique_sparse(bson::doc! { "calendar_feed_token": 1 }),
+            ],
+        ),
+        // Tenant Members
+        (
+            "tenant_members",
+            vec![
+                index_unique(bson::doc! { "tenant_id": 1, "user_id": 1 }),
+                index(bson::doc! { "user_id": 1 }),
+            ],
+        ),
+        // Roles
+        (
+            "roles",
+            vec![
+                index_unique(bson::doc! { "tenant_id": 1, "name": 1 }),
+                index(bson::doc! { "tenant_id": 1, "position": 1 }),
+            ],
+        ),
+        // Rooms
+        (
+            "rooms",
+            vec![
+                index(bson::doc! { "tenant_id": 1, "parent_id": 1, "position": 1 }),
+                index_unique(bson::doc! { "tenant_id": 1, "path": 1 }),
+                index(bson::doc! { "tenant_id": 1, "name": 1 }),
+                index(bson::doc! { "tenant_id": 1, "is_default": 1 }),
+                index_unique_sparse(bson::doc! { "meeting_code": 1 }),
+                index_unique_sparse(bson::doc! { "dm_key": 1 }),
+                index_unique_sparse(bson::doc! { "public_share.token": 1 }),
+                index(bson::doc! { "digest.hour_utc": 1 }),
+                index_text(bson::doc! { "name": "text", "purpose": "text", "tags": "text" }),
+            ],
+        ),
+        // Room Members
+        (
+            "room_members",
+            vec![
+                index_unique(bson::doc! { "room_id": 1, "user_id": 1 }),
+                index(bson::doc! { "room_id": 1, "joined_at": 1 }),
+                index(bson::doc! { "room_id": 1, "channel_role": 1, "joined_at": 1 }),
+                index(bson::doc! { "user_id": 1, "tenant_id": 1 }),
+                index(bson::doc! { "tenant_id": 1, "sessions.joined_at": 1 }),
+            ],
+        ),
+        // Messages
+        (
+            "messages",
+            vec![
+                index(bson::doc! { "room_id": 1, "created_at": -1 }),
+                index(bson::doc! { "thread_id": 1, "created_at": 1 }),
+                index(bson::doc! { "tenant_id": 1, "author_id": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "created_at": 1 }),
+                index(bson::doc! { "room_id": 1, "is_pinned": 1 }),
+                index(bson::doc! { "mentions.users": 1 }),
+                index(bson::doc! { "mentions.everyone": 1, "room_id": 1 }),
+                index(bson::doc! { "cross_post_group_id": 1 }),
+                index_unique_partial(
+                    bson::doc! { "room_id": 1, "author_id": 1, "nonce": 1 },
+                    bson::doc! { "nonce": { "$type": "string" } },
+                ),
+                index_text(bson::doc! { "content": "text" }),
+            ],
+        ),
+        // Reactions
+        (
+            "reactions",
+            vec![index_unique(
+                bson::doc! { "message_id": 1, "emoji.value": 1, "user_id": 1 },
+            )],
+        ),
+        // Reaction rules, matched on every reaction
+        (
+            "reaction_rules",
+            vec![index(
+                bson::doc! { "tenant_id": 1, "emoji": 1, "trigger": 1 },
+            )],
+        ),
+        // Outgoing webhooks, looked up per event; deliveries kept for 30 days
+        (
+            "webhooks",
+            vec![index(bson::doc! { "tenant_id": 1, "events": 1 })],
+        ),
+        (
+            "webhook_deliveries",
+            vec![
+                index(bson::doc! { "webhook_id": 1, "created_at": -1 }),
+                index(bson::doc! { "status": 1, "next_attempt_at": 1 }),
+                index_ttl(bson::doc! { "created_at": 1 }, 30 * 24 * 60 * 60),
+            ],
+        ),
+        // Bot tokens, looked up by hash on every post
+        (
+            "bot_tokens",
+            vec![
+                index_unique(bson::doc! { "token_hash": 1 }),
+                index(bson::doc! { "tenant_id": 1, "created_at": -1 }),
+            ],
+        ),
+        // Recordings
+        (
+            "recordings",
+            vec![
+                index(bson::doc! { "room_id": 1, "recording_type": 1 }),
+                index(bson::doc! { "tenant_id": 1, "status": 1 }),
+                index(bson::doc! { "acl.share_links.token": 1 }),
+            ],
+        ),
+        // Files
+        (
+            "files",
+            vec![
+                index(
+                    bson::doc! { "tenant_id": 1, "context.context_type": 1, "context.entity_id": 1 },
+                ),
+                index(bson::doc! { "tenant_id": 1, "uploaded_by": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "context.room_id": 1, "created_at": -1 }),
+                index(
+                    bson::doc! { "external_source.provider": 1, "external_source.external_id": 1 },
+                ),
+                index(bson::doc! { "tenant_id": 1, "checksum": 1, "created_at": 1 }),
+            ],
+        ),
+        // Stored objects shared by files with the same content
+        (
+            "file_blobs",
+            vec![index_unique(bson::doc! { "tenant_id": 1, "checksum": 1 })],
+        ),
+        // Pending direct uploads: dropped once their pre-signed URL has expired
+        (
+            "pending_uploads",
+            vec![index_ttl(bson::doc! { "expires_at": 1 }, 0)],
+        ),
+        // Invites
+        (
+            "invites",
+            vec![
+                index_unique(bson::doc! { "code": 1 }),
+                index(bson::doc! { "tenant_id": 1, "status": 1 }),
+            ],
+        ),
+        // Background Tasks
+        (
+            "background_tasks",
+            vec![
+                index(bson::doc! { "tenant_id": 1, "user_id": 1, "status": 1 }),
+                index(bson::doc! { "task_type": 1, "status": 1, "next_attempt_at": 1 }),
+                index_ttl(bson::doc! { "expires_at": 1 }, 0),
+            ],
+        ),
+        // Audit Logs
+        (
+            "audit_logs",
+            vec![
+                index(bson::doc! { "tenant_id": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "action": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "actor_id": 1, "created_at": -1 }),
+                // Auto-expire audit logs after 90 days
+                index_ttl(bson::doc! { "created_at": 1 }, 90 * 24 * 60 * 60),
+            ],
+        ),
+        // Notifications
+        (
+            "notifications",
+            vec![
+                index(bson::doc! { "user_id": 1, "is_read": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "user_id": 1 }),
+            ],
+        ),
+        // Custom Emojis
+        (
+            "custom_emojis",
+            vec![index_unique(bson::doc! { "tenant_id": 1, "name": 1 })],
+        ),
+        // Activation Codes
+        (
+            "activation_codes",
+            vec![
+                index(bson::doc! { "user_id": 1 }),
+                // TTL: auto-expire when valid_to passes
+                index_ttl(bson::doc! { "valid_to": 1 }, 0),
+            ],
+        ),
+        // Remote-control agents
+        (
+            "agents",
+            vec![
+                index_unique(bson::doc! { "tenant_id": 1, "machine_id": 1 }),
+                index(bson::doc! { "tenant_id": 1, "status": 1 }),
+                index(bson::doc! { "owner_user_id": 1 }),
+            ],
+        ),
+        // Remote-control sessions
+        (
+            "remote_sessions",
+            vec![
+                index(bson::doc! { "agent_id": 1, "created_at": -1 }),
+                index(bson::doc! { "controller_user_id": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "phase": 1 }),
+            ],
+        ),
+        // Remote-control audit log — 90-day retention
+        (
+            "remote_audit",
+            vec![
+                index(bson::doc! { "session_id": 1, "at": 1 }),
+                index(bson::doc! { "tenant_id": 1, "at": -1 }),
+                index_ttl(bson::doc! { "at": 1 }, 90 * 24 * 60 * 60),
+            ],
+        ),
+        // Feature flags
+        ("feature_flags", vec![index_unique(bson::doc! { "key": 1 })]),
+        // Feature flag overrides — one row per (flag, tenant) or (flag, tenant, user)
+        (
+            "feature_flag_overrides",
+            vec![
+                index_unique(bson::doc! { "key": 1, "tenant_id": 1, "user_id": 1 }),
+                index(bson::doc! { "tenant_id": 1, "user_id": 1 }),
+            ],
+        ),
+        // Onboarding checklist — one row per (tenant, user)
+        (
+            "onboarding_progress",
+            vec![index_unique(bson::doc! { "tenant_id": 1, "user_id": 1 })],
+        ),
+        // Conference preflight reports — 30-day retention
+        (
+            "preflight_reports",
+            vec![
+                index(bson::doc! { "tenant_id": 1, "created_at": -1 }),
+                index(bson::doc! { "tenant_id": 1, "user_id": 1, "created_at": -1 }),
+                index_ttl(bson::doc! { "created_at": 1 }, 30 * 24 * 60 * 60),
+            ],
+        ),
+        // Conference event feed, read oldest first per room
+        (
+            "conference_events",
+            vec![index(bson::doc! { "room_id": 1, "_id": 1 })],
+        ),
+        // Live call transcripts
+        (
+            "transcript_segments",
+            vec![
+                index(bson::doc! { "room_id": 1, "track": 1, "_id": 1 }),
+                index_text(bson::doc! { "text": "text" }),
+            ],
+        ),
+        // Conference chat: per-call history and retention purges
+        (
+            "call_chat_messages",
+            vec![
+                index(bson::doc! { "room_id": 1, "created_at": 1 }),
+                index(bson::doc! { "tenant_id": 1, "created_at": 1 }),
+            ],
+        ),
+        // Profanity filter actions, per tenant over time
+        (
+            "moderation_events",
+            vec![index(bson::doc! { "tenant_id": 1, "created_at": 1 })],
+        ),
+        // TURN relay usage: one row per conference and day, summed per tenant
+        (
+            "relay_usage",
+            vec![
+                index_unique(bson::doc! { "room_id": 1, "date": 1 }),
+                index(bson::doc! { "tenant_id": 1, "date": 1 }),
+            ],
+        ),
+        // Call follow-ups: each assignee's list, and the reminder sweep
+        (
+            "follow_ups",
+            vec![
+                index(bson::doc! { "tenant_id": 1, "assignee_id": 1, "status": 1, "due_at": 1 }),
+                index(bson::doc! { "tenant_id": 1, "room_id": 1, "created_at": -1 }),
+                index(bson::doc! { "status": 1, "reminded_at": 1, "due_at": 1 }),
+            ],
+        ),
+        // Conference polls: a room's polls, newest first
+        (
+            "conference_polls",
+            vec![index(bson::doc! { "room_id": 1, "created_at": -1 })],
+        ),
+        // Thread subscriptions
+        (
+            "thread_subscriptions",
+            vec![
+                index_unique(bson::doc! { "thread_id": 1, "user_id": 1 }),
+                index(bson::doc! { "user_id": 1, "tenant_id": 1, "subscribed": 1 }),
+            ],
+        ),
+        // Shared drafts: a room's open drafts
+        (
+            "shared_drafts",
+            vec![index(
+                bson::doc! { "room_id": 1, "published_at": 1, "created_at": -1 },
+            )],
+        ),
+        // Message archive partitions (the monthly collections get their own
+        // index when the archiver creates them)
+        (
+            "message_archive_partitions",
+            vec![
+                index_unique(bson::doc! { "room_id": 1, "month": 1 }),
+                index(bson::doc! { "tenant_id": 1 }),
+            ],
+        ),
+    ]
+}
+
 fn index(keys: bson::Document) -> IndexModel {
     IndexModel::builder().keys(keys).build()
 }
@@ -262,35 +699,42 @@ fn index_unique_sparse(keys: bson::Document) -> IndexModel {
         .build()
 }
 
-async fn create_indexes(
-    db: &Database,
-    collection: &str,
-    indexes: Vec<IndexModel>,
-) -> Result<(), mongodb::error::Error> {
-    let coll = db.collection::<bson::Document>(collection);
-    match coll.create_indexes(indexes.clone()).await {
-        Ok(_) => {
-            info!(collection, "Indexes created");
-            Ok(())
-        }
-        Err(e) => {
-            // IndexOptionsConflict (85) or IndexKeySpecsConflict (86): an existing
-            // index has the same name but different options (e.g. adding TTL to an
-            // existing index). Drop all indexes and recreate.
-            if let mongodb::error::ErrorKind::Command(ref cmd_err) = *e.kind
-                && (cmd_err.code == 85 || cmd_err.code == 86)
-            {
-                tracing::warn!(
-                    collection,
-                    "Index conflict detected, dropping conflicting indexes and retrying"
-                );
-                // Drop all non-_id indexes and recreate
-                coll.drop_indexes().await?;
-                coll.create_indexes(indexes).await?;
-                info!(collection, "Indexes recreated after conflict resolution");
-                return Ok(());
-            }
-            Err(e)
-        }
-    }
+/// Unique among the documents matching `filter`; the others aren't indexed.
+fn index_unique_partial(keys: bson::Document, filter: bson::Document) -> IndexModel {
+    IndexModel::builder()
+        .keys(keys)
+        .options(
+            IndexOptions::builder()
+                .unique(true)
+                .partial_filter_expression(filter)
+                .build(),
+        )
+        .build()
+}
+
+/// The default name MongoDB gives an index: `{ room_id: 1, created_at: -1 }`
+/// is `room_id_1_created_at_-1`.
+fn index_name(keys: &Document) -> String {
+    keys.iter()
+        .map(|(field, value)| match value {
+            Bson::String(kind) => format!("{}_{}", field, kind),
+            Bson::Int32(n) => format!("{}_{}", field, n),
+            Bson::Int64(n) => format!("{}_{}", field, n),
+            other => format!("{}_{}", field, other),
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn is_unique(model: &IndexModel) -> bool {
+    model
+        .options
+        .as_ref()
+        .and_then(|o| o.unique)
+        .unwrap_or(false)
+}
+
+/// IndexOptionsConflict (85) or IndexKeySpecsConflict (86).
+fn is_conflict(e: &mongodb::error::Error) -> bool {
+    matches!(*e.kind, mongodb::error::ErrorKind::Command(ref cmd_err) if cmd_err.code == 85 || cmd_err.code == 86)
 }
@@ -6,11 +6,27 @@ pub struct ActivationCode {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub user_id: ObjectId,
+    /// Codes stored before password resets have none and activate.
+    #[serde(default)]
+    pub purpose: CodePurpose,
     pub token: String,
+    /// The address an email change switches to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_email: Option<String>,
     pub valid_to: DateTime,
     pub created_at: DateTime,
 }
 
+/// What a code, sent by email, lets its user do.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CodePurpose {
+    #[default]
+    Activation,
+    PasswordReset,
+    EmailChange,
+}
+
 impl ActivationCode {
     pub const COLLECTION: &'static str = "activation_codes";
 }
@@ -0,0 +1,54 @@
+use bson::{Binary, DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Quality measurements of one final live transcript segment. The
+/// collection is capped, so the oldest entries make room for new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsrMetric {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    /// Audio level in dBFS.
+    pub rms_dbfs: f64,
+    /// Speech duration from voice activity detection, in ms.
+    pub vad_ms: u64,
+    /// Lead of the detected language's score over the runner-up.
+    pub language_score_delta: Option<f64>,
+    /// Characters of transcribed text.
+    pub text_chars: u64,
+    pub created_at: DateTime,
+}
+
+impl AsrMetric {
+    pub const COLLECTION: &'static str = "asr_metrics";
+}
+
+/// The audio of a low-confidence segment, kept with the speaker's consent
+/// for evaluating and tuning the ASR model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsrSample {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    /// What the model heard.
+    pub text: String,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    pub rms_dbfs: f64,
+    pub vad_ms: u64,
+    pub language_score_delta: Option<f64>,
+    /// WAV audio; left out when listing samples.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Binary>,
+    pub created_at: DateTime,
+}
+
+impl AsrSample {
+    pub const COLLECTION: &'static str = "asr_samples";
+}
@@ -46,3 +46,53 @@ pub struct AuditMetadata {
 impl AuditLog {
     pub const COLLECTION: &'static str = "audit_logs";
 }
+
+/// Recorded actions, named `<target_type>.<verb>`.
+pub mod actions {
+    pub const ROOM_CREATE: &str = "room.create";
+    pub const ROOM_DELETE: &str = "room.delete";
+    pub const ROOM_RETENTION_UPDATE: &str = "room.retention_update";
+    pub const ROOM_CHAT_PURGE: &str = "room.chat_purge";
+    pub const ROOM_MESSAGE_RETENTION_UPDATE: &str = "room.message_retention_update";
+    pub const ROOM_MESSAGE_PURGE: &str = "room.message_purge";
+    pub const ROOM_PUBLIC_SHARE: &str = "room.public_share";
+    pub const ROOM_PUBLIC_UNSHARE: &str = "room.public_unshare";
+    pub const MEMBER_ADD: &str = "member.add";
+    pub const MEMBER_REMOVE: &str = "member.remove";
+    pub const MEMBER_ROLE_ASSIGN: &str = "member.role_assign";
+    pub const MEMBER_ROLE_UNASSIGN: &str = "member.role_unassign";
+    pub const ROLE_CREATE: &str = "role.create";
+    pub const ROLE_UPDATE: &str = "role.update";
+    pub const ROLE_DELETE: &str = "role.delete";
+    pub const INVITE_CREATE: &str = "invite.create";
+    pub const INVITE_REVOKE: &str = "invite.revoke";
+    pub const BILLING_CHECKOUT: &str = "billing.checkout";
+    pub const BILLING_PLAN_CHANGE: &str = "billing.plan_change";
+    pub const BILLING_SUBSCRIPTION_UPDATE: &str = "billing.subscription_update";
+    pub const BILLING_SUBSCRIPTION_CANCEL: &str = "billing.subscription_cancel";
+    pub const BILLING_PAYMENT_FAILED: &str = "billing.payment_failed";
+    pub const BILLING_LIMITS_CHANGE: &str = "billing.limits_change";
+    pub const TENANT_SANDBOX_RESET: &str = "tenant.sandbox_reset";
+    pub const TENANT_MESSAGE_RETENTION_UPDATE: &str = "tenant.message_retention_update";
+    pub const TENANT_MESSAGE_PURGE: &str = "tenant.message_purge";
+    pub const TENANT_PROFANITY_FILTER_UPDATE: &str = "tenant.profanity_filter_update";
+    pub const TENANT_SSO_UPDATE: &str = "tenant.sso_update";
+    pub const TENANT_SSO_REMOVE: &str = "tenant.sso_remove";
+    pub const REACTION_RULE_CREATE: &str = "reaction_rule.create";
+    pub const REACTION_RULE_UPDATE: &str = "reaction_rule.update";
+    pub const REACTION_RULE_DELETE: &str = "reaction_rule.delete";
+    pub const WEBHOOK_CREATE: &str = "webhook.create";
+    pub const WEBHOOK_UPDATE: &str = "webhook.update";
+    pub const WEBHOOK_DELETE: &str = "webhook.delete";
+    pub const BOT_CREATE: &str = "bot.create";
+    pub const BOT_REVOKE: &str = "bot.revoke";
+    pub const EMOJI_CREATE: &str = "emoji.create";
+    pub const EMOJI_DELETE: &str = "emoji.delete";
+    pub const VIRTUAL_BACKGROUND_CREATE: &str = "virtual_background.create";
+    pub const VIRTUAL_BACKGROUND_DELETE: &str = "virtual_background.delete";
+
+    /// The target type an action applies to: the part before the dot.
+    pub fn target_type(action: &str) -> &str {
+        action.split_once('.').map_or(action, |(t, _)| t)
+    }
+}
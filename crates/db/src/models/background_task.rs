@@ -5,7 +5,8 @@ use serde::{Deserialize, Serialize};
 pub struct BackgroundTask {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
-    pub tenant_id: ObjectId,
+    /// `None` for tasks outside any tenant, like a password reset email.
+    pub tenant_id: Option<ObjectId>,
     pub user_id: ObjectId,
     pub task_type: String,
     pub category: TaskCategory,
@@ -19,7 +20,18 @@ pub struct BackgroundTask {
     pub progress: u8,
     pub file_path: Option<String>,
     pub file_name: Option<String>,
+    /// Backend holding `file_path`. `None` for older tasks, whose
+    /// `file_path` is an absolute local path.
+    #[serde(default)]
+    pub storage_provider: Option<super::recording::StorageProvider>,
     pub error: Option<String>,
+    /// Runs so far of a task that is retried, like a queued email.
+    #[serde(default)]
+    pub attempts: u32,
+    /// When a queued task is next due; while it runs, until when it is
+    /// kept from other workers.
+    #[serde(default)]
+    pub next_attempt_at: Option<DateTime>,
     pub started_at: Option<DateTime>,
     pub completed_at: Option<DateTime>,
     pub expires_at: DateTime,
@@ -34,6 +46,8 @@ pub enum TaskCategory {
     Export,
     Import,
     Recognition,
+    Retention,
+    Email,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -0,0 +1,28 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Lets an external service (CI, monitoring) post into one room through
+/// `POST /api/hook/{token}` without a user account. Only a hash of the
+/// token is stored; messages it posts are authored by the bot's id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// Shown as the author of the bot's messages.
+    pub name: String,
+    /// Hex SHA-256 of the token.
+    pub token_hash: String,
+    /// Last characters of the token, to tell tokens apart.
+    pub token_hint: String,
+    pub created_by: ObjectId,
+    pub last_used_at: Option<DateTime>,
+    pub revoked_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl BotToken {
+    pub const COLLECTION: &'static str = "bot_tokens";
+}
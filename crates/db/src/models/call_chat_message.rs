@@ -10,9 +10,34 @@ pub struct CallChatMessage {
     pub author_id: ObjectId,
     pub display_name: String,
     pub content: String,
+    #[serde(default)]
+    pub voice_note: Option<VoiceNote>,
     pub created_at: DateTime,
 }
 
+/// Recorded audio sent into conference chat, with its transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceNote {
+    pub file_id: ObjectId,
+    pub url: String,
+    pub content_type: String,
+    pub size: u64,
+    /// As reported by the recording client.
+    pub duration_ms: Option<u64>,
+    pub transcript: Option<String>,
+    pub transcript_language: Option<String>,
+    pub transcript_status: TranscriptStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptStatus {
+    Completed,
+    Failed,
+    /// No ASR backend is configured.
+    Unavailable,
+}
+
 impl CallChatMessage {
     pub const COLLECTION: &'static str = "call_chat_messages";
 }
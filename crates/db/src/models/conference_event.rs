@@ -0,0 +1,43 @@
+use bson::{DateTime, Document, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// One entry in a room's call timeline, kept for audit and for
+/// integrations that replay what happened in a conference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConferenceEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub event_type: ConferenceEventType,
+    /// Who caused the event; `None` for system events.
+    pub user_id: Option<ObjectId>,
+    /// Type-specific details (producer id, kind, recording id, ...).
+    #[serde(default)]
+    pub data: Document,
+    pub created_at: DateTime,
+}
+
+impl ConferenceEvent {
+    pub const COLLECTION: &'static str = "conference_events";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConferenceEventType {
+    CallStarted,
+    CallEnded,
+    ParticipantJoined,
+    ParticipantLeft,
+    ProducerStarted,
+    ProducerStopped,
+    ProducerReplaced,
+    MuteToggled,
+    RecordingStarted,
+    RecordingStopped,
+    TranscriptToggled,
+    PollStarted,
+    PollEnded,
+    HandRaised,
+    HandLowered,
+}
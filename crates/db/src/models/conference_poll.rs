@@ -0,0 +1,51 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A poll or quiz an organizer runs during a call. Votes are kept with it,
+/// so the results stay with the room after the call ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConferencePoll {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// `actual_start_time` of the call the poll ran in.
+    pub call_started_at: Option<DateTime>,
+    pub created_by: ObjectId,
+    pub question: String,
+    pub options: Vec<String>,
+    /// Index of the right answer, for quizzes.
+    pub correct_option: Option<u32>,
+    /// Stream live results to every participant, not just the organizers.
+    #[serde(default)]
+    pub share_results: bool,
+    /// One per voter; voting again replaces it.
+    #[serde(default)]
+    pub votes: Vec<PollVote>,
+    pub status: PollStatus,
+    pub closed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl ConferencePoll {
+    pub const COLLECTION: &'static str = "conference_polls";
+
+    pub fn is_quiz(&self) -> bool {
+        self.correct_option.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollVote {
+    pub user_id: ObjectId,
+    pub option: u32,
+    pub voted_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    Open,
+    Closed,
+}
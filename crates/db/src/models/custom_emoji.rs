@@ -8,6 +8,9 @@ pub struct CustomEmoji {
     pub tenant_id: ObjectId,
     pub name: String,
     pub image_url: String,
+    /// The uploaded image, when registered through the API.
+    #[serde(default)]
+    pub file_id: Option<ObjectId>,
     #[serde(default)]
     pub is_animated: bool,
     pub creator_id: ObjectId,
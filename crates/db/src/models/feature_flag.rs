@@ -0,0 +1,40 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Global definition of a feature flag. Rows here take precedence over the
+/// `features.defaults` config map; tenant and user overrides live in
+/// [`FeatureFlagOverride`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub key: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl FeatureFlag {
+    pub const COLLECTION: &'static str = "feature_flags";
+}
+
+/// Per-tenant (`user_id == None`) or per-user override of a flag.
+/// User overrides win over tenant overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagOverride {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub key: String,
+    pub tenant_id: ObjectId,
+    pub user_id: Option<ObjectId>,
+    pub enabled: bool,
+    pub set_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl FeatureFlagOverride {
+    pub const COLLECTION: &'static str = "feature_flag_overrides";
+}
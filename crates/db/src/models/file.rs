@@ -19,7 +19,13 @@ pub struct File {
     pub url: String,
     pub content_type: String,
     pub size: u64,
+    /// Hex SHA-256 of the content; files with the same one share a
+    /// [`FileBlob`](super::FileBlob).
     pub checksum: Option<String>,
+    /// Earliest live file in the tenant with the same content, at the time
+    /// this one was uploaded.
+    #[serde(default)]
+    pub duplicate_of: Option<ObjectId>,
     pub dimensions: Option<Dimensions>,
     pub duration: Option<u32>,
     #[serde(default)]
@@ -54,6 +60,8 @@ pub enum FileContextType {
     Document,
     Profile,
     Room,
+    /// A clip cut from the recording `entity_id`.
+    Recording,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
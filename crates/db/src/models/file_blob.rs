@@ -0,0 +1,27 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+use super::recording::StorageProvider;
+
+/// A stored object shared by every file in a tenant with the same content.
+/// The object is deleted once the last file referencing it is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBlob {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    /// Hex SHA-256 of the content.
+    pub checksum: String,
+    pub storage_provider: StorageProvider,
+    pub storage_bucket: String,
+    pub storage_key: String,
+    pub size: u64,
+    /// Live (not deleted) files pointing at the object.
+    pub ref_count: i64,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl FileBlob {
+    pub const COLLECTION: &'static str = "file_blobs";
+}
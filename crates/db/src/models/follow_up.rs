@@ -0,0 +1,33 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A task agreed on during a call, assigned to a tenant member. The room
+/// the call ran in is where its due reminder is posted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUp {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub created_by: ObjectId,
+    pub assignee_id: ObjectId,
+    pub title: String,
+    pub due_at: Option<DateTime>,
+    pub status: FollowUpStatus,
+    /// Set once the due reminder has been posted, so it is posted once.
+    pub reminded_at: Option<DateTime>,
+    pub completed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl FollowUp {
+    pub const COLLECTION: &'static str = "follow_ups";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FollowUpStatus {
+    Open,
+    Done,
+}
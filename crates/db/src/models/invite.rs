@@ -17,6 +17,9 @@ pub struct Invite {
     pub expires_at: Option<DateTime>,
     #[serde(default)]
     pub assign_role_ids: Vec<ObjectId>,
+    /// Channels joined on acceptance, besides the tenant's default ones.
+    #[serde(default)]
+    pub join_room_ids: Vec<ObjectId>,
     #[serde(default)]
     pub status: InviteStatus,
     pub created_at: DateTime,
@@ -35,4 +38,9 @@ pub enum InviteStatus {
 
 impl Invite {
     pub const COLLECTION: &'static str = "invites";
+
+    /// Acceptances left, or `None` when unlimited.
+    pub fn remaining_uses(&self) -> Option<u32> {
+        self.max_uses.map(|max| max.saturating_sub(self.use_count))
+    }
 }
@@ -11,6 +11,9 @@ pub struct Message {
     #[serde(default)]
     pub is_thread_root: bool,
     pub thread_metadata: Option<ThreadMetadata>,
+    /// Set on a thread root once an AI summary has been generated for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_summary: Option<ThreadSummary>,
     pub author_id: ObjectId,
     #[serde(default)]
     pub author_type: AuthorType,
@@ -28,6 +31,13 @@ pub struct Message {
     #[serde(default)]
     pub reaction_summary: Vec<ReactionSummary>,
     pub referenced_message_id: Option<ObjectId>,
+    /// Shared by the copies of a message cross-posted to several rooms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_post_group_id: Option<ObjectId>,
+    /// Words the tenant's profanity filter flagged; the message was posted
+    /// unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flagged_terms: Vec<String>,
     #[serde(default)]
     pub is_pinned: bool,
     #[serde(default)]
@@ -53,6 +63,19 @@ pub struct ThreadMetadata {
     pub is_locked: bool,
     #[serde(default)]
     pub is_archived: bool,
+    /// Bumped by every reply and reply edit, so clients can tell the
+    /// thread changed.
+    #[serde(default)]
+    pub activity_count: u32,
+}
+
+/// Cache entry pointing at the pinned summary message inside a thread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadSummary {
+    pub message_id: ObjectId,
+    /// Thread reply count when the summary was generated.
+    pub reply_count: u32,
+    pub generated_at: DateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -86,16 +109,48 @@ pub enum MessageType {
     Reply,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Embed {
-    pub embed_type: String,
-    pub url: Option<String>,
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub color: Option<u32>,
-    pub thumbnail_url: Option<String>,
-    pub author_name: Option<String>,
-    pub provider_name: Option<String>,
+/// Structured content shown with a message, tagged by `type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Embed {
+    /// A pin on a map.
+    Location {
+        latitude: f64,
+        longitude: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        address: Option<String>,
+    },
+    /// A contact card.
+    Contact {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        email: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        phone: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        organization: Option<String>,
+    },
+    /// Key-value fields under an optional title, as bots post them.
+    Fields {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<u32>,
+        fields: Vec<EmbedField>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    /// Shown side by side with neighbouring inline fields.
+    #[serde(default)]
+    pub inline: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +183,11 @@ pub struct Mentions {
 pub struct ReactionSummary {
     pub emoji: String,
     pub count: u32,
+    /// Set for custom emoji, with the image at the time of the reaction.
+    #[serde(default)]
+    pub custom_emoji_id: Option<ObjectId>,
+    #[serde(default)]
+    pub image_url: Option<String>,
 }
 
 impl Message {
@@ -0,0 +1,30 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// One room's share of a monthly archive collection. `count` covers only
+/// what the room history lists (top-level, not deleted) so paging can skip
+/// whole months without querying them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageArchivePartition {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// `YYYY_MM` of the messages' `created_at` (UTC).
+    pub month: String,
+    pub collection: String,
+    pub count: u64,
+    pub oldest_at: DateTime,
+    pub newest_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl MessageArchivePartition {
+    pub const COLLECTION: &'static str = "message_archive_partitions";
+    pub const ARCHIVE_PREFIX: &'static str = "messages_archive_";
+
+    /// Name of the archive collection for a `YYYY_MM` month.
+    pub fn collection_for(month: &str) -> String {
+        format!("{}{}", Self::ARCHIVE_PREFIX, month)
+    }
+}
@@ -1,38 +1,74 @@
+pub mod asr_metric;
 pub mod audit_log;
 pub mod background_task;
+pub mod bot_token;
 pub mod call_chat_message;
+pub mod conference_event;
+pub mod conference_poll;
 pub mod custom_emoji;
+pub mod feature_flag;
 pub mod file;
+pub mod file_blob;
+pub mod follow_up;
 pub mod invite;
 pub mod message;
+pub mod message_archive;
+pub mod moderation_event;
 pub mod notification;
+pub mod onboarding;
+pub mod pending_upload;
+pub mod preflight_report;
 pub mod push_subscription;
 pub mod reaction;
+pub mod reaction_rule;
 pub mod recording;
+pub mod relay_usage;
 pub mod role;
 pub mod room;
 pub mod room_member;
+pub mod shared_draft;
 pub mod tenant;
 pub mod tenant_member;
+pub mod thread_subscription;
+pub mod transcript_segment;
+pub mod webhook;
 
 pub mod user;
 
+pub use asr_metric::*;
 pub use audit_log::*;
 pub use background_task::*;
+pub use bot_token::*;
 pub use call_chat_message::*;
+pub use conference_event::*;
+pub use conference_poll::*;
 pub use custom_emoji::*;
+pub use feature_flag::*;
 pub use file::*;
+pub use file_blob::*;
+pub use follow_up::*;
 pub use invite::*;
 pub use message::*;
+pub use message_archive::*;
+pub use moderation_event::*;
 pub use notification::*;
+pub use onboarding::*;
+pub use pending_upload::*;
+pub use preflight_report::*;
 pub use push_subscription::*;
 pub use reaction::*;
+pub use reaction_rule::*;
 pub use recording::*;
+pub use relay_usage::*;
 pub use role::*;
 pub use room::*;
 pub use room_member::*;
+pub use shared_draft::*;
 pub use tenant::*;
 pub use tenant_member::*;
+pub use thread_subscription::*;
+pub use transcript_segment::*;
+pub use webhook::*;
 
 pub use user::*;
 
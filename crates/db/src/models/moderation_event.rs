@@ -0,0 +1,31 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A channel message the tenant's profanity filter acted on, kept for
+/// moderation analytics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    /// The message as posted; `None` when it was blocked.
+    pub message_id: Option<ObjectId>,
+    pub action: ModerationAction,
+    /// The list entries that matched, lowercased.
+    pub terms: Vec<String>,
+    pub created_at: DateTime,
+}
+
+impl ModerationEvent {
+    pub const COLLECTION: &'static str = "moderation_events";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    Masked,
+    Blocked,
+    Flagged,
+}
@@ -0,0 +1,55 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Per-user, per-tenant onboarding checklist. Each step records when it was
+/// first completed; later repeats leave the timestamp untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub user_id: ObjectId,
+    pub joined_channel_at: Option<DateTime>,
+    pub sent_message_at: Option<DateTime>,
+    pub started_call_at: Option<DateTime>,
+    pub dismissed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl OnboardingProgress {
+    pub const COLLECTION: &'static str = "onboarding_progress";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    JoinedChannel,
+    SentMessage,
+    StartedCall,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 3] = [
+        OnboardingStep::JoinedChannel,
+        OnboardingStep::SentMessage,
+        OnboardingStep::StartedCall,
+    ];
+
+    /// Field on [`OnboardingProgress`] holding this step's completion time.
+    pub fn field(&self) -> &'static str {
+        match self {
+            OnboardingStep::JoinedChannel => "joined_channel_at",
+            OnboardingStep::SentMessage => "sent_message_at",
+            OnboardingStep::StartedCall => "started_call_at",
+        }
+    }
+
+    pub fn completed_at(&self, progress: &OnboardingProgress) -> Option<DateTime> {
+        match self {
+            OnboardingStep::JoinedChannel => progress.joined_channel_at,
+            OnboardingStep::SentMessage => progress.sent_message_at,
+            OnboardingStep::StartedCall => progress.started_call_at,
+        }
+    }
+}
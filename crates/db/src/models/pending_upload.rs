@@ -0,0 +1,24 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A direct-to-storage upload the server has signed a URL for but the
+/// client hasn't confirmed yet. Expires with its URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpload {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    pub filename: String,
+    pub content_type: String,
+    /// Size the client declared; the stored object must match it.
+    pub size: u64,
+    pub storage_key: String,
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+}
+
+impl PendingUpload {
+    pub const COLLECTION: &'static str = "pending_uploads";
+}
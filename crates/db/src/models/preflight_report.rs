@@ -0,0 +1,36 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a client's pre-join connectivity and device test, kept for
+/// support diagnostics. Checks the client skipped are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub user_id: ObjectId,
+    pub probe_transport_id: Option<String>,
+    pub udp_ok: Option<bool>,
+    pub tcp_ok: Option<bool>,
+    pub relay_ok: Option<bool>,
+    /// ICE candidate type of the selected pair: `host`, `srflx`, `prflx` or
+    /// `relay`.
+    pub selected_candidate_type: Option<String>,
+    pub rtt_ms: Option<f64>,
+    pub camera_ok: Option<bool>,
+    pub microphone_ok: Option<bool>,
+    pub speaker_ok: Option<bool>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime,
+}
+
+impl PreflightReport {
+    pub const COLLECTION: &'static str = "preflight_reports";
+
+    /// Whether media could flow by any route the client tried.
+    pub fn connected(&self) -> bool {
+        [self.udp_ok, self.tcp_ok, self.relay_ok].contains(&Some(true))
+    }
+}
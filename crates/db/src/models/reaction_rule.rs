@@ -0,0 +1,48 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A tenant automation: when someone adds (or removes) `emoji` on a message,
+/// in `room_id` or any room, run `action`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionRule {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub name: String,
+    /// `None` matches every room.
+    pub room_id: Option<ObjectId>,
+    /// Stored reaction value: Unicode, or `:name:` for custom emoji.
+    pub emoji: String,
+    pub trigger: ReactionTrigger,
+    pub action: RuleAction,
+    pub enabled: bool,
+    /// Signs webhook deliveries (`X-Roomler-Signature`).
+    pub secret: String,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl ReactionRule {
+    pub const COLLECTION: &'static str = "reaction_rules";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactionTrigger {
+    Add,
+    Remove,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// POST the event as JSON to `url`.
+    Webhook { url: String },
+    /// Post `content` as a system message into `room_id`, or the room the
+    /// reaction was in. `{user}`, `{emoji}` and `{message_id}` are filled in.
+    PostMessage {
+        room_id: Option<ObjectId>,
+        content: String,
+    },
+}
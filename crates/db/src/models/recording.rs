@@ -17,6 +17,16 @@ pub struct Recording {
     #[serde(default = "bool_true")]
     pub allow_download: bool,
     pub expires_at: Option<DateTime>,
+    /// Parts uploaded so far. Persisted as each part lands so a recording
+    /// interrupted by a restart can still be finalized.
+    #[serde(default)]
+    pub manifest: Option<SegmentManifest>,
+    #[serde(default)]
+    pub acl: RecordingAcl,
+    /// Who started the recording and uploads its parts. `None` on
+    /// recordings made before it was kept.
+    #[serde(default)]
+    pub created_by: Option<ObjectId>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
@@ -38,6 +48,8 @@ pub enum RecordingStatus {
     #[default]
     Processing,
     Available,
+    /// Finalized from the parts on hand after the upload was interrupted.
+    Partial,
     Failed,
     Deleted,
 }
@@ -54,13 +66,63 @@ pub struct StorageFile {
     pub resolution: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum StorageProvider {
     S3,
     #[default]
     MinIO,
     Local,
+    #[serde(rename = "gridfs")]
+    GridFs,
+}
+
+/// Multipart upload state for a recording in progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    pub upload_id: String,
+    #[serde(default)]
+    pub parts: Vec<RecordingPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingPart {
+    pub part_number: u32,
+    pub etag: String,
+    pub size: u64,
+    pub uploaded_at: DateTime,
+}
+
+/// Who may view a recording besides the room's organizers and tenant
+/// meeting managers.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RecordingAcl {
+    /// Users who were in the call while it was being recorded.
+    #[serde(default)]
+    pub participant_ids: Vec<ObjectId>,
+    /// Explicit grants to other tenant members.
+    #[serde(default)]
+    pub user_ids: Vec<ObjectId>,
+    /// Explicit grants to everyone holding one of these tenant roles.
+    #[serde(default)]
+    pub role_ids: Vec<ObjectId>,
+    #[serde(default)]
+    pub share_links: Vec<RecordingShareLink>,
+}
+
+/// Unauthenticated access to a single recording by token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingShareLink {
+    pub token: String,
+    pub created_by: ObjectId,
+    pub expires_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+impl RecordingShareLink {
+    pub fn is_expired(&self, now: DateTime) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -0,0 +1,22 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Media a conference relayed through the TURN server on one day (UTC),
+/// as measured on the SFU's transports. `bytes_sent` went from the SFU to
+/// participants, `bytes_received` from participants to the SFU.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayUsage {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub bytes_sent: i64,
+    pub bytes_received: i64,
+    pub updated_at: DateTime,
+}
+
+impl RelayUsage {
+    pub const COLLECTION: &'static str = "relay_usage";
+}
@@ -83,9 +83,46 @@ pub mod permissions {
     /// Owner permissions (everything)
     pub const ALL: u64 = (1 << 24) - 1;
 
+    /// Every permission by name, in bit order; what custom roles pick from.
+    pub const NAMED: [(&str, u64); 24] = [
+        ("VIEW_CHANNELS", VIEW_CHANNELS),
+        ("MANAGE_CHANNELS", MANAGE_CHANNELS),
+        ("MANAGE_ROLES", MANAGE_ROLES),
+        ("MANAGE_TENANT", MANAGE_TENANT),
+        ("KICK_MEMBERS", KICK_MEMBERS),
+        ("BAN_MEMBERS", BAN_MEMBERS),
+        ("INVITE_MEMBERS", INVITE_MEMBERS),
+        ("SEND_MESSAGES", SEND_MESSAGES),
+        ("SEND_THREADS", SEND_THREADS),
+        ("EMBED_LINKS", EMBED_LINKS),
+        ("ATTACH_FILES", ATTACH_FILES),
+        ("READ_HISTORY", READ_HISTORY),
+        ("MENTION_EVERYONE", MENTION_EVERYONE),
+        ("MANAGE_MESSAGES", MANAGE_MESSAGES),
+        ("ADD_REACTIONS", ADD_REACTIONS),
+        ("CONNECT_VOICE", CONNECT_VOICE),
+        ("SPEAK", SPEAK),
+        ("STREAM_VIDEO", STREAM_VIDEO),
+        ("MUTE_MEMBERS", MUTE_MEMBERS),
+        ("DEAFEN_MEMBERS", DEAFEN_MEMBERS),
+        ("MOVE_MEMBERS", MOVE_MEMBERS),
+        ("MANAGE_MEETINGS", MANAGE_MEETINGS),
+        ("MANAGE_DOCUMENTS", MANAGE_DOCUMENTS),
+        ("ADMINISTRATOR", ADMINISTRATOR),
+    ];
+
     pub fn has(permissions: u64, flag: u64) -> bool {
         permissions & ADMINISTRATOR != 0 || permissions & flag == flag
     }
+
+    /// The names of the bits set in `flags`, in bit order.
+    pub fn names(flags: u64) -> Vec<&'static str> {
+        NAMED
+            .iter()
+            .filter(|(_, bit)| flags & bit != 0)
+            .map(|(name, _)| *name)
+            .collect()
+    }
 }
 
 impl Role {
@@ -1,6 +1,8 @@
 use bson::{DateTime, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
+use super::{room_member::ChannelRole, tenant::MessageRetention};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -9,6 +11,12 @@ pub struct Room {
     pub parent_id: Option<ObjectId>,
     pub name: String,
     pub path: String,
+    #[serde(default)]
+    pub room_type: RoomType,
+    /// Sorted participant set of a direct message; unique per tenant so
+    /// each set of users has exactly one conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dm_key: Option<String>,
     pub emoji: Option<String>,
     pub topic: Option<String>,
     pub purpose: Option<String>,
@@ -21,6 +29,9 @@ pub struct Room {
     pub is_archived: bool,
     #[serde(default)]
     pub is_read_only: bool,
+    /// Recurring periods during which the room is read-only below a role.
+    #[serde(default)]
+    pub read_only_windows: Vec<ReadOnlyWindow>,
     #[serde(default)]
     pub is_default: bool,
     #[serde(default)]
@@ -37,6 +48,23 @@ pub struct Room {
     pub organizer_id: Option<ObjectId>,
     #[serde(default)]
     pub co_organizer_ids: Vec<ObjectId>,
+    /// Exempts this room's conference chat from the tenant's
+    /// discard-at-call-end setting.
+    #[serde(default)]
+    pub keep_conference_chat: bool,
+    /// This room's exception to the tenant's conference chat retention.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_override: Option<RetentionOverride>,
+    /// Replaces the tenant's message retention for this room.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_retention: Option<MessageRetention>,
+    /// Set while the channel's messages can be read on the web without
+    /// signing in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_share: Option<PublicShare>,
+    /// Set while the system posts a daily digest into the channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<ChannelDigest>,
     pub creator_id: ObjectId,
     pub last_message_id: Option<ObjectId>,
     pub last_activity_at: Option<DateTime>,
@@ -57,6 +85,113 @@ pub struct Room {
 
 impl Room {
     pub const COLLECTION: &'static str = "rooms";
+
+    pub fn is_dm(&self) -> bool {
+        self.room_type == RoomType::Dm
+    }
+
+    /// The room's conference settings, or the defaults when it has none.
+    pub fn conference(&self) -> ConferenceSettings {
+        self.conference_settings.clone().unwrap_or_default()
+    }
+
+    /// The organizer and co-organizers, or the creator when none are set.
+    pub fn organizer_ids(&self) -> Vec<ObjectId> {
+        let mut ids: Vec<ObjectId> = self
+            .organizer_id
+            .into_iter()
+            .chain(self.co_organizer_ids.iter().copied())
+            .collect();
+        if ids.is_empty() {
+            ids.push(self.creator_id);
+        }
+        ids
+    }
+}
+
+/// Set by tenant admins on rooms whose conference chat must outlive the
+/// tenant's retention period (`exempt`, e.g. #legal) or go sooner
+/// (`retention_days`, shorter than the tenant's).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionOverride {
+    #[serde(default)]
+    pub exempt: bool,
+    pub retention_days: Option<u32>,
+}
+
+impl RetentionOverride {
+    /// Days this room's chat is kept when the tenant keeps it for
+    /// `tenant_days`; `None` keeps it forever.
+    pub fn effective_days(&self, tenant_days: Option<u32>) -> Option<u32> {
+        if self.exempt {
+            return None;
+        }
+        match (self.retention_days, tenant_days) {
+            (Some(room), Some(tenant)) => Some(room.min(tenant)),
+            (room, tenant) => room.or(tenant),
+        }
+    }
+}
+
+/// Read-only public access to a channel, through `token`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicShare {
+    pub token: String,
+    /// Lets search engines index the public view.
+    #[serde(default)]
+    pub indexable: bool,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+}
+
+/// A daily summary of the channel's last 24 hours (top threads, most
+/// reacted messages, files shared), posted as a system message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelDigest {
+    /// UTC hour, 0-23, from which the day's digest is posted.
+    pub hour_utc: u32,
+    /// Add a Claude-written summary to the highlights.
+    #[serde(default)]
+    pub summarize: bool,
+    /// Author of the digest messages.
+    pub configured_by: ObjectId,
+    /// UTC day (`YYYY-MM-DD`) of the last digest, so each day's is posted once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_posted_on: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomType {
+    /// A regular room, visible to every tenant member.
+    #[default]
+    Channel,
+    /// A 1:1 or group direct message, visible only to its participants.
+    Dm,
+}
+
+/// A weekly read-only period, e.g. weekends or overnight quiet hours.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadOnlyWindow {
+    /// ISO weekdays the window starts on, 1 (Monday) to 7 (Sunday).
+    pub weekdays: Vec<u8>,
+    /// Local start time, `HH:MM`.
+    pub start: String,
+    /// Local end time, `HH:MM`. At or before `start` means the next day.
+    pub end: String,
+    /// Offset of the local times from UTC.
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Least privileged role that may still post.
+    #[serde(default = "default_window_min_role")]
+    pub min_role: ChannelRole,
+    /// Shown to members who try to post, e.g. "Weekend quiet hours".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+fn default_window_min_role() -> ChannelRole {
+    ChannelRole::Moderator
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +215,15 @@ pub struct MediaSettings {
     #[serde(default)]
     pub recording_enabled: bool,
     pub max_participants: Option<u32>,
+    /// Languages live captions are translated into, as BCP 47 tags. Empty
+    /// means captions are only offered in the spoken language.
+    #[serde(default)]
+    pub caption_languages: Vec<String>,
+    /// Private captions: transcription still runs, but connections receive
+    /// no caption track until they pick one, so captions only reach the
+    /// participants who asked for them.
+    #[serde(default)]
+    pub private_captions: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,4 +236,55 @@ pub struct ConferenceSettings {
     pub lobby_enabled: bool,
     #[serde(default)]
     pub auto_record: bool,
+    /// Guests must enter this before joining. Never returned by the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passcode: Option<String>,
+    /// Key of the conference's guest link; people without an account can
+    /// join while it is set. Never returned by the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest_link_key: Option<String>,
+    // In-call controls, editable by organizers mid-call. Organizers
+    // themselves are never restricted by them.
+    /// Attendees' microphones start muted.
+    #[serde(default)]
+    pub mute_on_entry: bool,
+    /// Attendees may unmute themselves.
+    #[serde(default = "default_true")]
+    pub allow_unmute: bool,
+    #[serde(default = "default_true")]
+    pub chat_enabled: bool,
+    #[serde(default = "default_true")]
+    pub reactions_enabled: bool,
+    /// Attendees may share their screen.
+    #[serde(default = "default_true")]
+    pub attendee_screen_share: bool,
+    /// Joiners past the plan's participant cap queue instead of being
+    /// turned away.
+    #[serde(default)]
+    pub waitlist_enabled: bool,
+}
+
+impl Default for ConferenceSettings {
+    fn default() -> Self {
+        Self {
+            scheduled_start: None,
+            scheduled_end: None,
+            recurrence: None,
+            timezone: None,
+            lobby_enabled: false,
+            auto_record: false,
+            passcode: None,
+            guest_link_key: None,
+            mute_on_entry: false,
+            allow_unmute: true,
+            chat_enabled: true,
+            reactions_enabled: true,
+            attendee_screen_share: true,
+            waitlist_enabled: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
@@ -13,6 +13,9 @@ pub struct RoomMember {
     #[serde(default)]
     pub is_external: bool,
     pub role: Option<ParticipantRole>,
+    /// Standing in the room itself, as opposed to `role` in its conference.
+    #[serde(default)]
+    pub channel_role: ChannelRole,
     #[serde(default)]
     pub sessions: Vec<ParticipantSession>,
     pub joined_at: DateTime,
@@ -52,6 +55,74 @@ pub enum ParticipantRole {
     Attendee,
 }
 
+/// Per-room role, ordered from least to most privileged.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelRole {
+    Guest,
+    #[default]
+    Member,
+    Moderator,
+    Owner,
+}
+
+/// Room operations gated by [`ChannelRole`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelAction {
+    ReadHistory,
+    PostMessages,
+    /// Post in a room marked `is_read_only`.
+    PostInReadOnly,
+    PinMessages,
+    DeleteAnyMessage,
+    UpdateRoom,
+    ManageRoles,
+    DeleteRoom,
+}
+
+impl ChannelAction {
+    /// The permission matrix: the least privileged role allowed to act.
+    pub fn min_role(self) -> ChannelRole {
+        match self {
+            ChannelAction::ReadHistory => ChannelRole::Guest,
+            ChannelAction::PostMessages => ChannelRole::Member,
+            ChannelAction::PostInReadOnly
+            | ChannelAction::PinMessages
+            | ChannelAction::DeleteAnyMessage
+            | ChannelAction::UpdateRoom
+            | ChannelAction::ManageRoles => ChannelRole::Moderator,
+            ChannelAction::DeleteRoom => ChannelRole::Owner,
+        }
+    }
+}
+
+impl ChannelRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelRole::Guest => "guest",
+            ChannelRole::Member => "member",
+            ChannelRole::Moderator => "moderator",
+            ChannelRole::Owner => "owner",
+        }
+    }
+
+    pub fn allows(self, action: ChannelAction) -> bool {
+        self >= action.min_role()
+    }
+
+    /// Whether this role may move a member from `current` to `new`.
+    /// Moderators manage members and guests only; owners manage anyone.
+    pub fn can_assign(self, current: ChannelRole, new: ChannelRole) -> bool {
+        match self {
+            ChannelRole::Owner => true,
+            ChannelRole::Moderator => current <= ChannelRole::Member && new <= ChannelRole::Member,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParticipantSession {
     pub joined_at: DateTime,
@@ -0,0 +1,46 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// An announcement several members of a room write together before it is
+/// posted. Editors send [`DraftOp`]s over the WebSocket; each one applied
+/// bumps `version`. Publishing posts `content` as a normal message and
+/// closes the draft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedDraft {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub created_by: ObjectId,
+    pub content: String,
+    pub version: u64,
+    /// The latest revisions, oldest first, for rebasing ops made against
+    /// an earlier version.
+    #[serde(default)]
+    pub history: Vec<DraftRevision>,
+    pub published_message_id: Option<ObjectId>,
+    pub published_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl SharedDraft {
+    pub const COLLECTION: &'static str = "shared_drafts";
+}
+
+/// The ops that took a draft to `version`, applied in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftRevision {
+    pub version: u64,
+    pub user_id: ObjectId,
+    pub ops: Vec<DraftOp>,
+}
+
+/// An edit of a draft's text. Positions and lengths count characters
+/// (Unicode scalar values).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DraftOp {
+    Insert { pos: u32, text: String },
+    Delete { pos: u32, len: u32 },
+}
@@ -17,12 +17,35 @@ pub struct Tenant {
     pub integrations: Option<IntegrationSettings>,
     #[serde(default)]
     pub is_archived: bool,
+    /// Integration-testing tenant whose content can be wiped. Fixed at
+    /// creation so production data can never be reset.
+    #[serde(default)]
+    pub is_sandbox: bool,
+    /// Month ("YYYY-MM") of the last monthly analytics report sent.
+    #[serde(default)]
+    pub analytics_report_sent_for: Option<String>,
+    /// Month ("YYYY-MM") the admins were last alerted that TURN relay
+    /// usage went over `turn.monthly_budget_mb`.
+    #[serde(default)]
+    pub relay_budget_alerted_for: Option<String>,
+    /// Plan limits the tenant was over when last checked, e.g.
+    /// `max_channels` after a downgrade.
+    #[serde(default)]
+    pub exceeded_limits: Vec<String>,
+    /// Sign-in through the tenant's own identity provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sso: Option<TenantSso>,
+    /// Email domains the tenant proved, or is proving, it owns; SSO can
+    /// only claim proven ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domain_verifications: Vec<DomainVerification>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Ordered from the lowest tier up.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum Plan {
     #[default]
@@ -46,6 +69,33 @@ pub struct TenantSettings {
     pub max_members: u32,
     #[serde(default = "default_file_upload_limit")]
     pub file_upload_limit: u64,
+    /// Tenant-level tweaks on top of the plan's media constraints.
+    #[serde(default)]
+    pub media_constraints: MediaConstraintOverrides,
+    /// Tenant-level switches on top of the plan's camera effects.
+    #[serde(default)]
+    pub video_effects: VideoEffectOverrides,
+    /// Images approved for use as virtual backgrounds.
+    #[serde(default)]
+    pub virtual_backgrounds: Vec<VirtualBackground>,
+    /// Retention of conference (in-call) chat, separate from channel chat.
+    #[serde(default)]
+    pub conference_chat: ConferenceChatRetention,
+    /// Default retention of channel messages; rooms can set their own.
+    #[serde(default)]
+    pub message_retention: MessageRetention,
+    /// Email last month's analytics CSV to the tenant's admins.
+    #[serde(default)]
+    pub monthly_analytics_report: bool,
+    /// Serve avatars, emoji and thumbnails only through signed asset URLs.
+    #[serde(default)]
+    pub private_assets: bool,
+    /// Daily cap on bot API calls, below the plan's quota.
+    #[serde(default)]
+    pub bot_api_daily_cap: Option<u64>,
+    /// Screening of channel messages for profanity; off by default.
+    #[serde(default)]
+    pub profanity_filter: ProfanityFilter,
 }
 
 impl Default for TenantSettings {
@@ -57,6 +107,15 @@ impl Default for TenantSettings {
             allow_guest_access: false,
             max_members: default_max_members(),
             file_upload_limit: default_file_upload_limit(),
+            media_constraints: MediaConstraintOverrides::default(),
+            video_effects: VideoEffectOverrides::default(),
+            virtual_backgrounds: Vec::new(),
+            conference_chat: ConferenceChatRetention::default(),
+            message_retention: MessageRetention::default(),
+            monthly_analytics_report: false,
+            private_assets: false,
+            bot_api_daily_cap: None,
+            profanity_filter: ProfanityFilter::default(),
         }
     }
 }
@@ -73,6 +132,167 @@ fn default_file_upload_limit() -> u64 {
     10 * 1024 * 1024 // 10 MB
 }
 
+/// Which words channel messages are screened for and what happens to a
+/// message containing them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfanityFilter {
+    #[serde(default)]
+    pub mode: ProfanityMode,
+    /// Screen for the bundled word lists of `locales`.
+    #[serde(default = "default_true")]
+    pub use_default_list: bool,
+    /// The tenant's own words; `word*` also matches words starting with
+    /// `word`.
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Words never matched, even when a list has them.
+    #[serde(default)]
+    pub allowed_words: Vec<String>,
+    /// Languages (`en`, `de-AT`, ...) whose bundled lists apply; empty is
+    /// the tenant's default locale.
+    #[serde(default)]
+    pub locales: Vec<String>,
+}
+
+impl Default for ProfanityFilter {
+    fn default() -> Self {
+        Self {
+            mode: ProfanityMode::default(),
+            use_default_list: true,
+            words: Vec::new(),
+            allowed_words: Vec::new(),
+            locales: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityMode {
+    #[default]
+    Off,
+    /// Replace matched words with asterisks.
+    Mask,
+    /// Refuse the message.
+    Block,
+    /// Post the message unchanged and report it to moderators.
+    Flag,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// How long conference chat is kept. Channel messages are unaffected.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ConferenceChatRetention {
+    /// Conference chat older than this is purged; `None` keeps it forever.
+    pub retention_days: Option<u32>,
+    /// Delete a call's chat when the call ends, unless an organizer marked
+    /// the room's conference chat as kept.
+    #[serde(default)]
+    pub discard_at_call_end: bool,
+}
+
+/// How many channel messages are kept and for how long. Unset limits keep
+/// messages regardless; the plan's history cap applies either way.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct MessageRetention {
+    /// Messages older than this are removed.
+    pub max_age_days: Option<u32>,
+    /// Only the newest this many top-level messages of each room are kept,
+    /// with their thread replies.
+    pub max_count: Option<u32>,
+    /// Copy removed messages to object storage before deleting them.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl MessageRetention {
+    pub fn has_limits(&self) -> bool {
+        self.max_age_days.is_some() || self.max_count.is_some()
+    }
+}
+
+/// getUserMedia settings advertised to clients in `media:router_capabilities`
+/// so every participant captures with the same processing and caps.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MediaConstraints {
+    pub echo_cancellation: bool,
+    pub noise_suppression: bool,
+    pub auto_gain_control: bool,
+    pub max_video_width: u32,
+    pub max_video_height: u32,
+    pub max_frame_rate: u32,
+}
+
+/// Unset fields fall back to the plan default. Video caps can only lower
+/// the plan's limits, never raise them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct MediaConstraintOverrides {
+    pub echo_cancellation: Option<bool>,
+    pub noise_suppression: Option<bool>,
+    pub auto_gain_control: Option<bool>,
+    pub max_video_width: Option<u32>,
+    pub max_video_height: Option<u32>,
+    pub max_frame_rate: Option<u32>,
+}
+
+impl MediaConstraints {
+    pub fn with_overrides(self, o: &MediaConstraintOverrides) -> Self {
+        Self {
+            echo_cancellation: o.echo_cancellation.unwrap_or(self.echo_cancellation),
+            noise_suppression: o.noise_suppression.unwrap_or(self.noise_suppression),
+            auto_gain_control: o.auto_gain_control.unwrap_or(self.auto_gain_control),
+            max_video_width: o
+                .max_video_width
+                .map_or(self.max_video_width, |v| v.min(self.max_video_width)),
+            max_video_height: o
+                .max_video_height
+                .map_or(self.max_video_height, |v| v.min(self.max_video_height)),
+            max_frame_rate: o
+                .max_frame_rate
+                .map_or(self.max_frame_rate, |v| v.min(self.max_frame_rate)),
+        }
+    }
+}
+
+/// Camera effects clients may apply, advertised with the media constraints
+/// in `media:router_capabilities`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VideoEffects {
+    pub background_blur: bool,
+    pub virtual_backgrounds: bool,
+}
+
+/// Unset fields fall back to the plan default. Overrides can only turn an
+/// effect off, never enable one the plan doesn't include.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct VideoEffectOverrides {
+    pub background_blur: Option<bool>,
+    pub virtual_backgrounds: Option<bool>,
+}
+
+impl VideoEffects {
+    pub fn with_overrides(self, o: &VideoEffectOverrides) -> Self {
+        Self {
+            background_blur: self.background_blur && o.background_blur.unwrap_or(true),
+            virtual_backgrounds: self.virtual_backgrounds && o.virtual_backgrounds.unwrap_or(true),
+        }
+    }
+}
+
+/// A tenant-approved virtual background image, served as an asset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualBackground {
+    pub id: ObjectId,
+    pub name: String,
+    pub image_url: String,
+    pub file_id: ObjectId,
+    pub creator_id: ObjectId,
+    pub created_at: DateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum NotificationLevel {
@@ -93,7 +313,7 @@ pub struct BillingInfo {
     pub cancel_at_period_end: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum SubscriptionStatus {
     #[default]
@@ -104,6 +324,39 @@ pub enum SubscriptionStatus {
     Incomplete,
 }
 
+/// An OpenID Connect provider members can sign in through. Only active on
+/// the Business plan and up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantSso {
+    /// Issuer URL; its `/.well-known/openid-configuration` is fetched.
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Lowercase email domains the provider vouches for. Only addresses in
+    /// them can sign in through it, and no two tenants share one.
+    pub domains: Vec<String>,
+    /// Domain capture: addresses in `domains` can only sign in through the
+    /// provider, not with a password, a social login or a new account.
+    #[serde(default)]
+    pub enforce: bool,
+    /// Create the account and tenant membership on a first sign-in.
+    #[serde(default = "default_true")]
+    pub jit_provisioning: bool,
+    pub updated_at: DateTime,
+}
+
+/// Proof that a tenant owns an email domain: a DNS TXT record holding
+/// `token`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainVerification {
+    /// Lowercase.
+    pub domain: String,
+    pub token: String,
+    /// When the record was found; `None` until then.
+    pub verified_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntegrationSettings {
     pub google_drive: Option<OAuthCredential>,
@@ -120,6 +373,25 @@ pub struct OAuthCredential {
 
 impl Tenant {
     pub const COLLECTION: &'static str = "tenants";
+
+    /// The SSO configuration, unless the plan doesn't include SSO.
+    pub fn active_sso(&self) -> Option<&TenantSso> {
+        self.sso.as_ref().filter(|_| self.plan >= Plan::Business)
+    }
+
+    /// Plan media constraints with the tenant's overrides applied.
+    pub fn media_constraints(&self) -> MediaConstraints {
+        self.plan
+            .media_constraints()
+            .with_overrides(&self.settings.media_constraints)
+    }
+
+    /// Plan camera effects with the tenant's overrides applied.
+    pub fn video_effects(&self) -> VideoEffects {
+        self.plan
+            .video_effects()
+            .with_overrides(&self.settings.video_effects)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -132,6 +404,11 @@ pub struct PlanLimits {
     pub cloud_integrations: bool,
     pub ai_recognition: bool,
     pub recordings: bool,
+    /// Calls a day through bot tokens, across all of the tenant's bots.
+    pub bot_api_calls_per_day: u64,
+    /// WebSocket connections a member can hold at once; a user in several
+    /// tenants gets the most generous of their plans.
+    pub max_sessions_per_user: u32,
 }
 
 impl Plan {
@@ -146,6 +423,8 @@ impl Plan {
                 cloud_integrations: false,
                 ai_recognition: false,
                 recordings: false,
+                bot_api_calls_per_day: 1_000,
+                max_sessions_per_user: 5,
             },
             Plan::Pro => PlanLimits {
                 max_members: u32::MAX,
@@ -156,6 +435,8 @@ impl Plan {
                 cloud_integrations: true,
                 ai_recognition: false,
                 recordings: false,
+                bot_api_calls_per_day: 50_000,
+                max_sessions_per_user: 10,
             },
             Plan::Business | Plan::Enterprise => PlanLimits {
                 max_members: u32::MAX,
@@ -166,10 +447,40 @@ impl Plan {
                 cloud_integrations: true,
                 ai_recognition: true,
                 recordings: true,
+                bot_api_calls_per_day: 500_000,
+                max_sessions_per_user: 25,
             },
         }
     }
 
+    pub fn media_constraints(&self) -> MediaConstraints {
+        let (max_video_width, max_video_height, max_frame_rate) = match self {
+            Plan::Free => (640, 360, 15),
+            Plan::Pro => (1280, 720, 30),
+            Plan::Business | Plan::Enterprise => (1920, 1080, 30),
+        };
+        MediaConstraints {
+            echo_cancellation: true,
+            noise_suppression: true,
+            auto_gain_control: true,
+            max_video_width,
+            max_video_height,
+            max_frame_rate,
+        }
+    }
+
+    pub fn video_effects(&self) -> VideoEffects {
+        let (background_blur, virtual_backgrounds) = match self {
+            Plan::Free => (false, false),
+            Plan::Pro => (true, false),
+            Plan::Business | Plan::Enterprise => (true, true),
+        };
+        VideoEffects {
+            background_blur,
+            virtual_backgrounds,
+        }
+    }
+
     pub fn price_monthly_cents(&self) -> u32 {
         match self {
             Plan::Free => 0,
@@ -0,0 +1,27 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A user following a thread: they get its `thread:update` events and see
+/// it in their subscribed threads while it has replies they haven't read.
+/// Thread authors and repliers are subscribed automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadSubscription {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// The thread's root message.
+    pub thread_id: ObjectId,
+    pub user_id: ObjectId,
+    /// False once the user unfollowed, so later replies by others don't
+    /// subscribe them again.
+    pub subscribed: bool,
+    /// Replies by others after this are unread.
+    pub last_read_at: DateTime,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl ThreadSubscription {
+    pub const COLLECTION: &'static str = "thread_subscriptions";
+}
@@ -0,0 +1,27 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// One live transcript segment from a room call, kept so the transcript
+/// can be read back after the call ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// Caption track: `original` or a translation language.
+    pub track: String,
+    pub user_id: ObjectId,
+    pub speaker_name: String,
+    pub text: String,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    /// Seconds from the start of the speaker's audio stream.
+    pub start_time: f64,
+    pub end_time: f64,
+    pub created_at: DateTime,
+}
+
+impl TranscriptSegment {
+    pub const COLLECTION: &'static str = "transcript_segments";
+}
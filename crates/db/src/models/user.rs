@@ -29,9 +29,20 @@ pub struct User {
     pub oauth_providers: Vec<OAuthProvider>,
     #[serde(default)]
     pub notification_preferences: NotificationPrefs,
+    #[serde(default)]
+    pub privacy: PrivacyPrefs,
+    /// Which emails the user gets, on top of `notification_preferences.email`.
+    #[serde(default)]
+    pub email_preferences: EmailPrefs,
+    /// Secret in the URL of the user's calendar feed; set while it is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_feed_token: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
+    /// When a deleted account's profile is anonymized; cleared once done.
+    #[serde(default)]
+    pub purge_at: Option<DateTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -83,6 +94,47 @@ impl Default for NotificationPrefs {
     }
 }
 
+/// What other users get to see about this user in real time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PrivacyPrefs {
+    #[serde(default)]
+    pub hide_read_receipts: bool,
+    #[serde(default)]
+    pub hide_typing: bool,
+    /// Appear offline and idle to others, including call activity.
+    #[serde(default)]
+    pub hide_presence: bool,
+    /// Let the audio of poorly recognized speech be kept to improve
+    /// transcription. Only used while the server retains samples.
+    #[serde(default)]
+    pub share_voice_samples: bool,
+}
+
+/// Optional emails a user can turn off. Account emails, like activation
+/// and password resets, are always sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EmailPrefs {
+    /// Invitations to other tenants.
+    #[serde(default = "bool_true")]
+    pub invites: bool,
+    /// A digest of mentions received while offline.
+    #[serde(default = "bool_true")]
+    pub mention_digest: bool,
+    /// Receipts for the tenants the user administers.
+    #[serde(default = "bool_true")]
+    pub billing_receipts: bool,
+}
+
+impl Default for EmailPrefs {
+    fn default() -> Self {
+        Self {
+            invites: true,
+            mention_digest: true,
+            billing_receipts: true,
+        }
+    }
+}
+
 fn bool_true() -> bool {
     true
 }
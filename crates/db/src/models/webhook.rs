@@ -0,0 +1,79 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A tenant's outgoing webhook: every event it subscribes to is POSTed to
+/// `url` as JSON, signed with `secret`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub name: String,
+    pub url: String,
+    /// Event names, e.g. `message.create`; see `webhook_events`.
+    pub events: Vec<String>,
+    pub enabled: bool,
+    /// Signs deliveries (`X-Roomler-Signature`).
+    pub secret: String,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Webhook {
+    pub const COLLECTION: &'static str = "webhooks";
+}
+
+/// Events a webhook can subscribe to.
+pub mod webhook_events {
+    pub const MESSAGE_CREATE: &str = "message.create";
+    pub const MEMBER_JOIN: &str = "member.join";
+    pub const CONFERENCE_START: &str = "conference.start";
+    pub const CONFERENCE_END: &str = "conference.end";
+    pub const BILLING_PLAN_CHANGE: &str = "billing.plan_change";
+    pub const BILLING_SUBSCRIPTION_CHANGE: &str = "billing.subscription_change";
+    pub const BILLING_LIMITS_CHANGE: &str = "billing.limits_change";
+
+    pub const ALL: &[&str] = &[
+        MESSAGE_CREATE,
+        MEMBER_JOIN,
+        CONFERENCE_START,
+        CONFERENCE_END,
+        BILLING_PLAN_CHANGE,
+        BILLING_SUBSCRIPTION_CHANGE,
+        BILLING_LIMITS_CHANGE,
+    ];
+}
+
+/// One event sent (or being sent) to one webhook, with the outcome of its
+/// latest attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub webhook_id: ObjectId,
+    pub event: String,
+    /// The JSON body, exactly as signed and sent.
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    /// When a pending delivery is next tried.
+    pub next_attempt_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl WebhookDelivery {
+    pub const COLLECTION: &'static str = "webhook_deliveries";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
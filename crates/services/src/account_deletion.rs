@@ -0,0 +1,74 @@
+//! Deleting one's own account. Deletion disables the account at once —
+//! sign-in credentials go, tokens stop working and memberships end — while
+//! the profile is kept through a grace period and then anonymized by a
+//! purge sweep. Messages stay, attributed to [`DELETED_NAME`].
+
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+
+use crate::dao::{base::DaoResult, user::UserDao};
+
+/// Shown as the author of a deleted account's messages.
+pub const DELETED_NAME: &str = "Deleted user";
+
+/// How long a live account's status is trusted before it's checked again.
+const CHECK_TTL: Duration = Duration::from_secs(60);
+
+/// The username an account is anonymized to; unique per account.
+pub fn anonymized_username(user_id: ObjectId) -> String {
+    format!("deleted-{}", user_id.to_hex())
+}
+
+/// The email an account is anonymized to; `.invalid` can't be delivered.
+pub fn anonymized_email(user_id: ObjectId) -> String {
+    format!("deleted-{}@deleted.invalid", user_id.to_hex())
+}
+
+/// Whether the users behind access tokens have deleted their accounts,
+/// cached so token checks don't query the database on every request.
+/// Deletions made here are seen at once, others' within a minute.
+#[derive(Default)]
+pub struct DeletedAccounts {
+    /// Live accounts and when they were checked; deleted ones for good.
+    known: DashMap<ObjectId, Option<Instant>>,
+}
+
+impl DeletedAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn is_deleted(&self, users: &UserDao, user_id: ObjectId) -> DaoResult<bool> {
+        if let Some(known) = self.known.get(&user_id) {
+            match *known {
+                None => return Ok(true),
+                Some(checked) if checked.elapsed() < CHECK_TTL => return Ok(false),
+                Some(_) => {}
+            }
+        }
+        let deleted = users.is_deleted(user_id).await?;
+        self.known.insert(user_id, (!deleted).then(Instant::now));
+        Ok(deleted)
+    }
+
+    pub fn mark_deleted(&self, user_id: ObjectId) {
+        self.known.insert(user_id, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anonymized_identities_are_unique_and_undeliverable() {
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        assert_ne!(anonymized_username(a), anonymized_username(b));
+        assert_eq!(
+            anonymized_email(a),
+            format!("{}@deleted.invalid", anonymized_username(a))
+        );
+    }
+}
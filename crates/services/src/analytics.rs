@@ -0,0 +1,178 @@
+//! Tenant analytics: per-day active users, messages and conference minutes
+//! for a date range, rendered as CSV for exports and the monthly report,
+//! per-day profanity filter actions for moderators, and per-day TURN relay
+//! usage. Days are UTC. The numbers come from [`crate::dao::analytics`],
+//! [`crate::dao::moderation_event`] and [`crate::dao::relay_usage`].
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// One row of the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyActivity {
+    pub date: NaiveDate,
+    /// People who posted a message or joined a call that day.
+    pub active_users: u64,
+    pub messages: u64,
+    /// Time spent in calls, summed over participants.
+    pub conference_minutes: u64,
+}
+
+impl DailyActivity {
+    pub fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            active_users: 0,
+            messages: 0,
+            conference_minutes: 0,
+        }
+    }
+}
+
+/// What the profanity filter did on one day.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ModerationActivity {
+    pub date: NaiveDate,
+    pub masked: u64,
+    pub blocked: u64,
+    pub flagged: u64,
+}
+
+impl ModerationActivity {
+    pub fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            ..Self::default()
+        }
+    }
+}
+
+/// Media relayed through the TURN server on one day.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RelayActivity {
+    pub date: NaiveDate,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl RelayActivity {
+    pub fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            ..Self::default()
+        }
+    }
+}
+
+pub fn to_csv(rows: &[DailyActivity]) -> Vec<u8> {
+    let mut csv = String::from("date,active_users,messages,conference_minutes\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            row.date.format("%Y-%m-%d"),
+            row.active_users,
+            row.messages,
+            row.conference_minutes
+        ));
+    }
+    csv.into_bytes()
+}
+
+/// Parse an inclusive `YYYY-MM-DD` range of at most `max_days` days.
+pub fn parse_range(from: &str, to: &str, max_days: u32) -> Result<(NaiveDate, NaiveDate), String> {
+    let parse = |s: &str, field: &str| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|_| format!("{} must be a date like 2024-01-31", field))
+    };
+    let (from, to) = (parse(from, "from")?, parse(to, "to")?);
+    if to < from {
+        return Err("to must not be before from".to_string());
+    }
+    if (to - from).num_days() >= i64::from(max_days) {
+        return Err(format!("The range can cover at most {} days", max_days));
+    }
+    Ok((from, to))
+}
+
+/// Every day from `from` to `to`, inclusive.
+pub fn days(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    from.iter_days().take_while(move |d| *d <= to)
+}
+
+/// First day of the month of `today`, and its `YYYY-MM` label.
+pub fn current_month(today: NaiveDate) -> (NaiveDate, String) {
+    (
+        today.with_day(1).unwrap_or(today),
+        today.format("%Y-%m").to_string(),
+    )
+}
+
+/// First and last day of the month before `today`, and its `YYYY-MM` label.
+pub fn previous_month(today: NaiveDate) -> (NaiveDate, NaiveDate, String) {
+    let last = today.with_day(1).unwrap_or(today) - Duration::days(1);
+    let first = last.with_day(1).unwrap_or(last);
+    (first, last, last.format("%Y-%m").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn renders_csv() {
+        let rows = [
+            DailyActivity {
+                date: date("2024-03-01"),
+                active_users: 4,
+                messages: 31,
+                conference_minutes: 95,
+            },
+            DailyActivity::empty(date("2024-03-02")),
+        ];
+        assert_eq!(
+            String::from_utf8(to_csv(&rows)).unwrap(),
+            "date,active_users,messages,conference_minutes\n\
+             2024-03-01,4,31,95\n\
+             2024-03-02,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn validates_ranges() {
+        assert_eq!(
+            parse_range("2024-01-01", "2024-01-31", 31),
+            Ok((date("2024-01-01"), date("2024-01-31")))
+        );
+        assert!(parse_range("2024-01-01", "2024-02-01", 31).is_err());
+        assert!(parse_range("2024-02-01", "2024-01-01", 31).is_err());
+        assert!(parse_range("01/02/2024", "2024-01-03", 31).is_err());
+        assert_eq!(days(date("2024-02-28"), date("2024-03-01")).count(), 3);
+    }
+
+    #[test]
+    fn finds_the_previous_month() {
+        assert_eq!(
+            previous_month(date("2024-03-15")),
+            (
+                date("2024-02-01"),
+                date("2024-02-29"),
+                "2024-02".to_string()
+            )
+        );
+        assert_eq!(
+            previous_month(date("2024-01-01")),
+            (
+                date("2023-12-01"),
+                date("2023-12-31"),
+                "2023-12".to_string()
+            )
+        );
+        assert_eq!(
+            current_month(date("2024-03-15")),
+            (date("2024-03-01"), "2024-03".to_string())
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Daily quotas on bot API traffic. Every call through a bot token counts
+//! against its tenant's quota for the UTC day: the plan's
+//! `bot_api_calls_per_day`, or the tenant's own lower cap. Calls over it
+//! are refused until the next UTC midnight.
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use roomler_ai_db::models::Tenant;
+use serde::Serialize;
+
+/// A tenant's quota standing after a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuotaStatus {
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    /// Seconds until the counters start over.
+    pub reset_in_secs: u64,
+}
+
+impl QuotaStatus {
+    pub fn new(limit: u64, used: u64, now: DateTime<Utc>) -> Self {
+        Self {
+            limit,
+            used,
+            remaining: limit.saturating_sub(used),
+            reset_in_secs: (reset_at(now) - now).num_seconds().max(1) as u64,
+        }
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.used > self.limit
+    }
+}
+
+/// The tenant's daily limit: its cap when set below the plan's quota.
+pub fn daily_limit(tenant: &Tenant) -> u64 {
+    let plan = tenant.plan.limits().bot_api_calls_per_day;
+    tenant
+        .settings
+        .bot_api_daily_cap
+        .map_or(plan, |cap| cap.min(plan))
+}
+
+/// The UTC day a call at `now` counts against, `YYYY-MM-DD`.
+pub fn day(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m-%d").to_string()
+}
+
+/// The next UTC midnight after `now`.
+pub fn reset_at(now: DateTime<Utc>) -> DateTime<Utc> {
+    (now.date_naive() + Duration::days(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn quota_resets_at_utc_midnight() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 23, 59, 30).unwrap();
+        assert_eq!(day(now), "2026-03-31");
+        assert_eq!(
+            reset_at(now),
+            Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap()
+        );
+
+        let status = QuotaStatus::new(2, 2, now);
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.reset_in_secs, 30);
+        assert!(!status.exceeded());
+        let status = QuotaStatus::new(2, 3, now);
+        assert_eq!(status.remaining, 0);
+        assert!(status.exceeded());
+    }
+}
@@ -0,0 +1,149 @@
+//! Content-addressed asset URLs. Avatars, custom emoji and thumbnails are
+//! served from `/api/asset/{tenant_id}/{checksum}`, where the checksum is
+//! the hex SHA-256 of the bytes, so a URL never changes meaning and clients
+//! and CDNs may cache it forever.
+//!
+//! Tenants with private assets only serve signed URLs: `expires` (Unix
+//! seconds) and `sig`, the hex HMAC-SHA256 of tenant, checksum and expiry.
+//! Expiries are rounded up to a multiple of the TTL so the same asset keeps
+//! the same URL, and stays cacheable, for a whole window.
+
+use bson::oid::ObjectId;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Cache lifetime of public assets: a year, the most caches honour.
+pub const MAX_AGE_SECS: u64 = 365 * 24 * 3600;
+
+/// Whether `checksum` looks like a hex SHA-256.
+pub fn is_checksum(checksum: &str) -> bool {
+    checksum.len() == 64 && checksum.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Only images are served as assets; other files stay behind the
+/// authenticated download route.
+pub fn is_servable(content_type: &str) -> bool {
+    content_type.starts_with("image/")
+}
+
+pub fn path(tenant_id: ObjectId, checksum: &str) -> String {
+    format!("/api/asset/{}/{}", tenant_id.to_hex(), checksum)
+}
+
+/// The checksum in an unsigned asset path of `tenant_id`'s, if `url` is one.
+pub fn checksum_in(url: &str, tenant_id: ObjectId) -> Option<&str> {
+    let rest = url.strip_prefix("/api/asset/")?;
+    let (tid, checksum) = rest.split_once('/')?;
+    (tid == tenant_id.to_hex() && is_checksum(checksum)).then_some(checksum)
+}
+
+/// [`path`] with a signature valid until at least `now + ttl_secs`.
+pub fn signed_path(
+    secret: &str,
+    tenant_id: ObjectId,
+    checksum: &str,
+    now: i64,
+    ttl_secs: u64,
+) -> (String, i64) {
+    let ttl = ttl_secs.max(1) as i64;
+    let expires = (now + ttl).div_euclid(ttl) * ttl + ttl;
+    let sig = sign(secret, tenant_id, checksum, expires);
+    (
+        format!(
+            "{}?expires={}&sig={}",
+            path(tenant_id, checksum),
+            expires,
+            sig
+        ),
+        expires,
+    )
+}
+
+/// Whether `sig` signs this asset until `expires`, and that is after `now`.
+pub fn verify(
+    secret: &str,
+    tenant_id: ObjectId,
+    checksum: &str,
+    expires: i64,
+    sig: &str,
+    now: i64,
+) -> bool {
+    if expires <= now {
+        return false;
+    }
+    let Ok(sig) = hex::decode(sig) else {
+        return false;
+    };
+    mac(secret, tenant_id, checksum, expires)
+        .verify_slice(&sig)
+        .is_ok()
+}
+
+fn sign(secret: &str, tenant_id: ObjectId, checksum: &str, expires: i64) -> String {
+    hex::encode(
+        mac(secret, tenant_id, checksum, expires)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+fn mac(secret: &str, tenant_id: ObjectId, checksum: &str, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}/{}/{}", tenant_id.to_hex(), checksum, expires).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKSUM: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn signed_paths_verify_until_they_expire() {
+        let tid = ObjectId::new();
+        let (path, expires) = signed_path("secret", tid, CHECKSUM, 1_000, 100);
+        assert_eq!(expires, 1_200);
+        let sig = path.rsplit("sig=").next().unwrap();
+
+        assert!(verify("secret", tid, CHECKSUM, expires, sig, 1_199));
+        assert!(!verify("secret", tid, CHECKSUM, expires, sig, 1_200));
+        assert!(!verify("other", tid, CHECKSUM, expires, sig, 1_000));
+        assert!(!verify(
+            "secret",
+            ObjectId::new(),
+            CHECKSUM,
+            expires,
+            sig,
+            1_000
+        ));
+        assert!(!verify("secret", tid, CHECKSUM, expires + 100, sig, 1_000));
+        assert!(!verify("secret", tid, CHECKSUM, expires, "not-hex", 1_000));
+    }
+
+    #[test]
+    fn signed_paths_are_stable_within_a_window() {
+        let tid = ObjectId::new();
+        let first = signed_path("secret", tid, CHECKSUM, 1_000, 100);
+        assert_eq!(first, signed_path("secret", tid, CHECKSUM, 1_099, 100));
+        assert_ne!(first, signed_path("secret", tid, CHECKSUM, 1_100, 100));
+    }
+
+    #[test]
+    fn checksums_are_read_back_from_paths() {
+        let tid = ObjectId::new();
+        assert_eq!(checksum_in(&path(tid, CHECKSUM), tid), Some(CHECKSUM));
+        assert_eq!(checksum_in(&path(tid, CHECKSUM), ObjectId::new()), None);
+        assert_eq!(checksum_in("https://cdn.example.com/x.png", tid), None);
+    }
+
+    #[test]
+    fn checksums_are_hex_sha256() {
+        assert!(is_checksum(CHECKSUM));
+        assert!(!is_checksum(&CHECKSUM[1..]));
+        assert!(!is_checksum(&CHECKSUM.replace('9', "g")));
+        assert!(is_servable("image/png"));
+        assert!(!is_servable("application/pdf"));
+    }
+}
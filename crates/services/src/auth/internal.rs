@@ -0,0 +1,210 @@
+//! Service-to-service tokens.
+//!
+//! Internal workers sign short-lived JWTs with `internal_auth.secret`, which
+//! is never used for user tokens, naming themselves in `sub` and the
+//! services they call in `aud`. The API only accepts tokens addressed to
+//! `internal_auth.audience`, no longer-lived than
+//! `internal_auth.token_ttl_secs` and not issued in the future.
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use roomler_ai_config::InternalAuthSettings;
+use serde::{Deserialize, Serialize};
+
+use super::{AuthError, TokenType, uuid_v4_hex};
+
+/// How far ahead of this host's clock a token's `iat` may be.
+const CLOCK_SKEW_SECS: i64 = 30;
+
+/// Claims carried by a service token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceClaims {
+    pub sub: String, // calling service, e.g. "asr"
+    pub aud: Vec<String>,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub token_type: TokenType,
+    pub jti: String,
+}
+
+pub struct InternalAuthService {
+    settings: InternalAuthSettings,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl InternalAuthService {
+    pub fn new(settings: InternalAuthSettings) -> Self {
+        let encoding_key = EncodingKey::from_secret(settings.secret.as_bytes());
+        let decoding_key = DecodingKey::from_secret(settings.secret.as_bytes());
+        Self {
+            settings,
+            encoding_key,
+            decoding_key,
+        }
+    }
+
+    /// Whether a signing key is configured.
+    pub fn enabled(&self) -> bool {
+        !self.settings.secret.is_empty()
+    }
+
+    /// The audience this API requires.
+    pub fn audience(&self) -> &str {
+        &self.settings.audience
+    }
+
+    /// Mint a token for `service` to call `audiences`, valid for
+    /// `token_ttl_secs`.
+    pub fn issue(&self, service: &str, audiences: &[&str]) -> Result<String, AuthError> {
+        self.issue_at(service, audiences, Utc::now(), self.settings.token_ttl_secs)
+    }
+
+    fn issue_at(
+        &self,
+        service: &str,
+        audiences: &[&str],
+        now: DateTime<Utc>,
+        ttl_secs: u64,
+    ) -> Result<String, AuthError> {
+        if !self.enabled() {
+            return Err(AuthError::InvalidToken(
+                "Service tokens are not configured".to_string(),
+            ));
+        }
+        let claims = ServiceClaims {
+            sub: service.to_string(),
+            aud: audiences.iter().map(|a| a.to_string()).collect(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
+            iss: self.settings.issuer.clone(),
+            token_type: TokenType::Service,
+            jti: uuid_v4_hex(),
+        };
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    /// Verify a token addressed to this API.
+    pub fn verify(&self, token: &str) -> Result<ServiceClaims, AuthError> {
+        if !self.enabled() {
+            return Err(AuthError::InvalidToken(
+                "Service tokens are not configured".to_string(),
+            ));
+        }
+        let mut validation = Validation::default();
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        validation.set_issuer(&[&self.settings.issuer]);
+        validation.set_audience(&[&self.settings.audience]);
+        let data =
+            decode::<ServiceClaims>(token, &self.decoding_key, &validation).map_err(|e| match e
+                .kind()
+            {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                _ => AuthError::InvalidToken(e.to_string()),
+            })?;
+        let claims = data.claims;
+        if claims.token_type != TokenType::Service {
+            return Err(AuthError::InvalidToken("Not a service token".to_string()));
+        }
+        // Without this, a future `iat` would stretch the lifetime cap.
+        if claims.iat > Utc::now().timestamp() + CLOCK_SKEW_SECS {
+            return Err(AuthError::InvalidToken(
+                "Service token issued in the future".to_string(),
+            ));
+        }
+        if claims.exp - claims.iat > self.settings.token_ttl_secs as i64 {
+            return Err(AuthError::InvalidToken(
+                "Service token lifetime too long".to_string(),
+            ));
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthService;
+    use bson::oid::ObjectId;
+    use roomler_ai_config::JwtSettings;
+
+    fn settings(secret: &str) -> InternalAuthSettings {
+        InternalAuthSettings {
+            secret: secret.to_string(),
+            issuer: "roomler-ai-internal".to_string(),
+            audience: "roomler-ai-api".to_string(),
+            token_ttl_secs: 60,
+        }
+    }
+
+    fn svc() -> InternalAuthService {
+        InternalAuthService::new(settings("internal-secret-for-unit-tests"))
+    }
+
+    #[test]
+    fn service_token_roundtrip() {
+        let s = svc();
+        let token = s.issue("asr", &["roomler-ai-api", "recorder"]).unwrap();
+        let claims = s.verify(&token).unwrap();
+        assert_eq!(claims.sub, "asr");
+        assert_eq!(claims.token_type, TokenType::Service);
+        assert!(claims.exp - claims.iat <= 60);
+    }
+
+    #[test]
+    fn service_token_requires_audience() {
+        let s = svc();
+        let token = s.issue("asr", &["recorder"]).unwrap();
+        assert!(matches!(s.verify(&token), Err(AuthError::InvalidToken(_))));
+    }
+
+    #[test]
+    fn service_token_rejects_long_lifetime() {
+        let s = svc();
+        let token = s
+            .issue_at("asr", &["roomler-ai-api"], Utc::now(), 3600)
+            .unwrap();
+        assert!(matches!(s.verify(&token), Err(AuthError::InvalidToken(_))));
+    }
+
+    #[test]
+    fn service_token_rejects_future_issue_time() {
+        let s = svc();
+        let later = Utc::now() + Duration::hours(1);
+        let token = s.issue_at("asr", &["roomler-ai-api"], later, 60).unwrap();
+        assert!(matches!(s.verify(&token), Err(AuthError::InvalidToken(_))));
+
+        // A few seconds of clock drift between hosts is fine.
+        let drifted = Utc::now() + Duration::seconds(5);
+        let token = s.issue_at("asr", &["roomler-ai-api"], drifted, 60).unwrap();
+        assert!(s.verify(&token).is_ok());
+    }
+
+    #[test]
+    fn service_token_rejects_other_keys() {
+        let other = InternalAuthService::new(settings("some-other-internal-secret"));
+        let token = other.issue("asr", &["roomler-ai-api"]).unwrap();
+        assert!(svc().verify(&token).is_err());
+
+        let users = AuthService::new(JwtSettings {
+            secret: "internal-secret-for-unit-tests".to_string(),
+            access_token_ttl_secs: 60,
+            refresh_token_ttl_secs: 60,
+            issuer: "roomler-ai-internal".to_string(),
+        });
+        let pair = users
+            .generate_tokens(ObjectId::new(), "a@b.c", "u")
+            .unwrap();
+        assert!(svc().verify(&pair.access_token).is_err());
+    }
+
+    #[test]
+    fn disabled_without_secret() {
+        let s = InternalAuthService::new(settings(""));
+        assert!(!s.enabled());
+        assert!(s.issue("asr", &["roomler-ai-api"]).is_err());
+        assert!(s.verify("anything").is_err());
+    }
+}
@@ -7,6 +7,8 @@ use roomler_ai_config::JwtSettings;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod internal;
+
 #[derive(Debug, Error)]
 pub enum AuthError {
     #[error("Invalid credentials")]
@@ -39,6 +41,10 @@ pub enum TokenType {
     Enrollment,
     /// Long-lived token carried by an enrolled remote-control agent.
     Agent,
+    /// Short-lived token an internal service signs with the internal key.
+    Service,
+    /// Short-lived token a conference guest uses for one call's signaling and chat.
+    Guest,
 }
 
 /// Claims carried by a remote-control enrollment token (aud = enroll).
@@ -64,6 +70,19 @@ pub struct AgentClaims {
     pub token_type: TokenType,
 }
 
+/// Claims carried by a conference guest token (aud = one conference).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestClaims {
+    pub sub: String, // guest id hex
+    pub tenant_id: String,
+    pub room_id: String,
+    pub name: String, // display name the guest joined with
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub token_type: TokenType,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPair {
     pub access_token: String,
@@ -257,9 +276,50 @@ impl AuthService {
         }
         Ok(data.claims)
     }
+
+    // ─── Conference guest tokens ──────────────────────────────────────
+
+    /// Mint a guest token scoped to one conference.
+    pub fn issue_guest_token(
+        &self,
+        guest_id: ObjectId,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        name: &str,
+        ttl_secs: u64,
+    ) -> Result<String, AuthError> {
+        let now = Utc::now();
+        let claims = GuestClaims {
+            sub: guest_id.to_hex(),
+            tenant_id: tenant_id.to_hex(),
+            room_id: room_id.to_hex(),
+            name: name.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
+            iss: self.jwt_settings.issuer.clone(),
+            token_type: TokenType::Guest,
+        };
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    pub fn verify_guest_token(&self, token: &str) -> Result<GuestClaims, AuthError> {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.jwt_settings.issuer]);
+        let data = decode::<GuestClaims>(token, &self.decoding_key, &validation).map_err(|e| {
+            match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                _ => AuthError::InvalidToken(e.to_string()),
+            }
+        })?;
+        if data.claims.token_type != TokenType::Guest {
+            return Err(AuthError::InvalidToken("Not a guest token".to_string()));
+        }
+        Ok(data.claims)
+    }
 }
 
-fn uuid_v4_hex() -> String {
+pub(crate) fn uuid_v4_hex() -> String {
     // Use `rand` via argon2's OsRng — avoids pulling in the uuid crate here just for a nonce.
     use argon2::password_hash::rand_core::RngCore;
     let mut bytes = [0u8; 16];
@@ -324,6 +384,32 @@ mod tests {
         matches!(err, AuthError::InvalidToken(_));
     }
 
+    #[test]
+    fn guest_token_roundtrip() {
+        let s = svc();
+        let (guest, tenant, room) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let token = s
+            .issue_guest_token(guest, tenant, room, "Visitor", 60)
+            .unwrap();
+        let claims = s.verify_guest_token(&token).unwrap();
+        assert_eq!(claims.sub, guest.to_hex());
+        assert_eq!(claims.tenant_id, tenant.to_hex());
+        assert_eq!(claims.room_id, room.to_hex());
+        assert_eq!(claims.name, "Visitor");
+        assert_eq!(claims.token_type, TokenType::Guest);
+    }
+
+    #[test]
+    fn guest_token_is_not_an_access_token() {
+        let s = svc();
+        let token = s
+            .issue_guest_token(ObjectId::new(), ObjectId::new(), ObjectId::new(), "V", 60)
+            .unwrap();
+        assert!(s.verify_access_token(&token).is_err());
+        let pair = s.generate_tokens(ObjectId::new(), "a@b.c", "u").unwrap();
+        assert!(s.verify_guest_token(&pair.access_token).is_err());
+    }
+
     #[test]
     fn enrollment_tokens_have_unique_jti() {
         let s = svc();
@@ -7,6 +7,8 @@ use crate::dao::base::{DaoResult, PaginatedResult, PaginationParams};
 
 use super::task_store::TaskStore;
 
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
 pub struct TaskService {
     store: Arc<TaskStore>,
 }
@@ -30,36 +32,77 @@ impl TaskService {
         category: TaskCategory,
         params: serde_json::Value,
     ) -> DaoResult<BackgroundTask> {
-        let now = DateTime::now();
-        let expires_at = DateTime::from_millis(now.timestamp_millis() + 24 * 60 * 60 * 1000);
-        let task = BackgroundTask {
-            id: None,
-            tenant_id,
+        let task = new_task(
+            Some(tenant_id),
             user_id,
             task_type,
             category,
-            status: TaskStatus::Pending,
             params,
-            logs: Vec::new(),
-            progress: 0,
-            file_path: None,
-            file_name: None,
-            error: None,
-            started_at: None,
-            completed_at: None,
-            expires_at,
-            created_at: now,
-            updated_at: now,
-        };
-
+            DAY_MS,
+        );
         let id = self.store.insert(&task).await?;
         self.store.get(id).await
     }
 
+    /// Queue a task for a worker polling [`Self::claim_due`], due from
+    /// `run_at`. Queued tasks are kept for a week.
+    pub async fn enqueue(
+        &self,
+        tenant_id: Option<ObjectId>,
+        user_id: ObjectId,
+        task_type: &str,
+        category: TaskCategory,
+        params: serde_json::Value,
+        run_at: DateTime,
+    ) -> DaoResult<BackgroundTask> {
+        let mut task = new_task(
+            tenant_id,
+            user_id,
+            task_type.to_string(),
+            category,
+            params,
+            7 * DAY_MS,
+        );
+        task.next_attempt_at = Some(run_at);
+        let id = self.store.db_dao.insert_one(&task).await?;
+        task.id = Some(id);
+        Ok(task)
+    }
+
+    /// Take the earliest due `task_type` task, counting the attempt, and
+    /// keep it from other workers until `lease_until`. A task whose worker
+    /// died is handed out again once its lease is over.
+    pub async fn claim_due(
+        &self,
+        task_type: &str,
+        lease_until: DateTime,
+    ) -> DaoResult<Option<BackgroundTask>> {
+        self.store.claim_due(task_type, lease_until).await
+    }
+
+    /// The `task_type` task of `user_id` in `tenant_id` still waiting to run.
+    pub async fn find_queued(
+        &self,
+        task_type: &str,
+        tenant_id: Option<ObjectId>,
+        user_id: ObjectId,
+    ) -> DaoResult<Option<BackgroundTask>> {
+        self.store
+            .db_dao
+            .find_one(doc! {
+                "task_type": task_type,
+                "tenant_id": tenant_id,
+                "user_id": user_id,
+                "status": "pending",
+            })
+            .await
+    }
+
     pub async fn get_task(&self, task_id: ObjectId) -> DaoResult<BackgroundTask> {
         self.store.get(task_id).await
     }
 
+    /// The user's tasks, without the emails queued on their behalf.
     pub async fn list_user_tasks(
         &self,
         tenant_id: ObjectId,
@@ -69,7 +112,11 @@ impl TaskService {
         self.store
             .db_dao
             .find_paginated(
-                doc! { "tenant_id": tenant_id, "user_id": user_id },
+                doc! {
+                    "tenant_id": tenant_id,
+                    "user_id": user_id,
+                    "category": { "$ne": "email" },
+                },
                 Some(doc! { "created_at": -1 }),
                 params,
             )
@@ -99,3 +146,36 @@ impl TaskService {
         });
     }
 }
+
+fn new_task(
+    tenant_id: Option<ObjectId>,
+    user_id: ObjectId,
+    task_type: String,
+    category: TaskCategory,
+    params: serde_json::Value,
+    keep_ms: i64,
+) -> BackgroundTask {
+    let now = DateTime::now();
+    BackgroundTask {
+        id: None,
+        tenant_id,
+        user_id,
+        task_type,
+        category,
+        status: TaskStatus::Pending,
+        params,
+        logs: Vec::new(),
+        progress: 0,
+        file_path: None,
+        file_name: None,
+        storage_provider: None,
+        error: None,
+        attempts: 0,
+        next_attempt_at: None,
+        started_at: None,
+        completed_at: None,
+        expires_at: DateTime::from_millis(now.timestamp_millis() + keep_ms),
+        created_at: now,
+        updated_at: now,
+    }
+}
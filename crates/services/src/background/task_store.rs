@@ -1,7 +1,7 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use dashmap::DashMap;
-use mongodb::Database;
-use roomler_ai_db::models::BackgroundTask;
+use mongodb::{Database, options::ReturnDocument};
+use roomler_ai_db::models::{BackgroundTask, StorageProvider};
 
 use crate::dao::base::{BaseDao, DaoResult};
 
@@ -74,6 +74,7 @@ impl TaskStore {
         id: ObjectId,
         file_path: Option<String>,
         file_name: Option<String>,
+        storage_provider: Option<StorageProvider>,
     ) -> DaoResult<()> {
         let now = DateTime::now();
         self.db_dao
@@ -85,6 +86,7 @@ impl TaskStore {
                         "progress": 100,
                         "file_path": file_path.as_deref(),
                         "file_name": file_name.as_deref(),
+                        "storage_provider": bson::to_bson(&storage_provider)?,
                         "completed_at": now,
                         "updated_at": now,
                     }
@@ -97,6 +99,7 @@ impl TaskStore {
             task.progress = 100;
             task.file_path = file_path;
             task.file_name = file_name;
+            task.storage_provider = storage_provider;
             task.completed_at = Some(now);
             task.updated_at = now;
         }
@@ -130,6 +133,61 @@ impl TaskStore {
         Ok(())
     }
 
+    /// See [`super::TaskService::claim_due`].
+    pub async fn claim_due(
+        &self,
+        task_type: &str,
+        lease_until: DateTime,
+    ) -> DaoResult<Option<BackgroundTask>> {
+        let now = DateTime::now();
+        let task = self
+            .db_dao
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "task_type": task_type,
+                    "status": { "$in": ["pending", "processing"] },
+                    "next_attempt_at": { "$lte": now },
+                },
+                doc! {
+                    "$set": {
+                        "status": "processing",
+                        "next_attempt_at": lease_until,
+                        "started_at": now,
+                        "updated_at": now,
+                    },
+                    "$inc": { "attempts": 1 },
+                },
+            )
+            .sort(doc! { "next_attempt_at": 1 })
+            .return_document(ReturnDocument::After)
+            .await?;
+        if let Some(id) = task.as_ref().and_then(|t| t.id) {
+            self.cache.remove(&id);
+        }
+        Ok(task)
+    }
+
+    /// Put a claimed task back in the queue after a failed attempt.
+    pub async fn retry_at(&self, id: ObjectId, error: String, at: DateTime) -> DaoResult<()> {
+        self.db_dao
+            .update_by_id(
+                id,
+                doc! {
+                    "$set": {
+                        "status": "pending",
+                        "error": &error,
+                        "next_attempt_at": at,
+                        "updated_at": DateTime::now(),
+                    },
+                    "$push": { "logs": error },
+                },
+            )
+            .await?;
+        self.cache.remove(&id);
+        Ok(())
+    }
+
     pub fn cleanup_cache(&self) {
         self.cache.retain(|_, task| {
             matches!(
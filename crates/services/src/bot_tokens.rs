@@ -0,0 +1,88 @@
+//! Bot tokens for posting into a room from outside (`POST /api/hook/{token}`).
+//! Tokens are random, shown once on creation and stored only as a hash.
+
+use sha2::{Digest, Sha256};
+
+/// Prefix marking a string as a Roomler bot token.
+pub const TOKEN_PREFIX: &str = "rbt_";
+pub const MAX_NAME_LEN: usize = 80;
+pub const MAX_TEXT_LEN: usize = 4000;
+pub const MAX_TITLE_LEN: usize = 200;
+
+/// A new random token.
+pub fn generate() -> String {
+    format!("{TOKEN_PREFIX}{}", nanoid::nanoid!(40))
+}
+
+/// Hex SHA-256 of `token`, as stored.
+pub fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// The last four characters of `token`.
+pub fn hint(token: &str) -> String {
+    let skip = token.chars().count().saturating_sub(4);
+    token.chars().skip(skip).collect()
+}
+
+/// Markdown for a hook post: an optional bold `title`, linked to `url` when
+/// given, above `text`.
+pub fn format_message(
+    text: &str,
+    title: Option<&str>,
+    url: Option<&str>,
+) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > MAX_TEXT_LEN {
+        return Err(format!("text must be 1-{MAX_TEXT_LEN} characters"));
+    }
+    let title = title.map(str::trim).filter(|t| !t.is_empty());
+    let Some(title) = title else {
+        return Ok(text.to_string());
+    };
+    if title.chars().count() > MAX_TITLE_LEN || title.contains('\n') {
+        return Err(format!(
+            "title must be one line of at most {MAX_TITLE_LEN} characters"
+        ));
+    }
+    let heading = match url {
+        Some(url) => {
+            let parsed = reqwest::Url::parse(url).map_err(|_| "Invalid url".to_string())?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err("url must be http or https".to_string());
+            }
+            format!("**[{title}]({url})**")
+        }
+        None => format!("**{title}**"),
+    };
+    Ok(format!("{heading}\n{text}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_hash_stably_and_hint_their_tail() {
+        let token = generate();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(hash(&token), hash(&token));
+        assert_ne!(hash(&token), hash(&generate()));
+        assert_eq!(hint("rbt_abcdef"), "cdef");
+    }
+
+    #[test]
+    fn messages_get_an_optional_linked_title() {
+        assert_eq!(format_message(" done ", None, None).unwrap(), "done");
+        assert_eq!(
+            format_message("All green", Some("Build #12"), Some("https://ci.test/12")).unwrap(),
+            "**[Build #12](https://ci.test/12)**\nAll green"
+        );
+        assert_eq!(
+            format_message("All green", Some("Build"), None).unwrap(),
+            "**Build**\nAll green"
+        );
+        assert!(format_message("", None, None).is_err());
+        assert!(format_message("x", Some("t"), Some("javascript:alert(1)")).is_err());
+    }
+}
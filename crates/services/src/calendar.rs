@@ -0,0 +1,334 @@
+//! iCalendar (RFC 5545) files for scheduled conferences.
+//!
+//! A conference is scheduled when its settings have a `scheduled_start`;
+//! it lasts until `scheduled_end`, or an hour when that is missing, and
+//! repeats by `recurrence`, an `RRULE` value. The same events serve the
+//! single-conference download, the invitation attached to invite emails
+//! and each user's subscribable feed. Times are written in UTC.
+
+use chrono::{DateTime, Duration, Utc};
+use roomler_ai_db::models::ConferenceSettings;
+
+/// How long a conference scheduled without an end lasts.
+pub const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+const PRODID: &str = "-//Roomler//Conferences//EN";
+/// Longest content line, in octets, before it is folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// What a calendar is for, its `METHOD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// To import or subscribe to.
+    Publish,
+    /// An invitation the recipient answers.
+    Request,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Publish => "PUBLISH",
+            Self::Request => "REQUEST",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    pub email: String,
+}
+
+/// One conference, as a `VEVENT`.
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// Stays the same for the conference, so calendars update their copy.
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// An `RRULE` value, as returned by [`recurrence_rule`].
+    pub recurrence: Option<String>,
+    pub summary: String,
+    pub description: Option<String>,
+    /// Where to join.
+    pub url: Option<String>,
+    pub organizer: Option<Person>,
+    pub attendees: Vec<Person>,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// The conference's start and end, or `None` when it isn't scheduled.
+pub fn schedule(settings: &ConferenceSettings) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = settings.scheduled_start?.to_chrono();
+    let end = settings
+        .scheduled_end
+        .map(|end| end.to_chrono())
+        .filter(|end| *end > start)
+        .unwrap_or(start + Duration::minutes(DEFAULT_DURATION_MINUTES));
+    Some((start, end))
+}
+
+/// Whether a scheduled conference still has an occurrence to come:
+/// recurring ones always do, others until they end.
+pub fn is_upcoming(settings: &ConferenceSettings, now: DateTime<Utc>) -> bool {
+    match schedule(settings) {
+        Some(_) if recurrence_rule(settings.recurrence.as_deref()).is_some() => true,
+        Some((_, end)) => end > now,
+        None => false,
+    }
+}
+
+/// The `RRULE` value of a conference's `recurrence`, with or without its
+/// `RRULE:` prefix. `None` when it isn't a rule starting with `FREQ=`.
+pub fn recurrence_rule(recurrence: Option<&str>) -> Option<String> {
+    let value = recurrence?.trim();
+    let value = match value.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &value[6..],
+        _ => value,
+    };
+    let valid = value.starts_with("FREQ=")
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "=;,:+-".contains(c));
+    valid.then(|| value.to_string())
+}
+
+/// A calendar of `events`, with `CRLF` line endings.
+pub fn render(name: &str, method: Method, events: &[Event], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("METHOD:{}", method.as_str()),
+        format!("X-WR-CALNAME:{}", text(name)),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", text(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", timestamp(now)));
+        lines.push(format!("DTSTART:{}", timestamp(event.start)));
+        lines.push(format!("DTEND:{}", timestamp(event.end)));
+        if let Some(rule) = &event.recurrence {
+            lines.push(format!("RRULE:{rule}"));
+        }
+        lines.push(format!("SUMMARY:{}", text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", text(description)));
+        }
+        if let Some(url) = &event.url {
+            lines.push(format!("LOCATION:{}", text(url)));
+            lines.push(format!("URL:{}", strip_controls(url)));
+        }
+        if let Some(organizer) = &event.organizer {
+            lines.push(format!(
+                "ORGANIZER;CN={}:mailto:{}",
+                param(&organizer.name),
+                strip_controls(&organizer.email)
+            ));
+        }
+        for attendee in &event.attendees {
+            let rsvp = if method == Method::Request {
+                ";RSVP=TRUE"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "ATTENDEE;CN={};ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION{}:mailto:{}",
+                param(&attendee.name),
+                rsvp,
+                strip_controls(&attendee.email)
+            ));
+        }
+        lines.push(format!("LAST-MODIFIED:{}", timestamp(event.last_modified)));
+        lines.push("STATUS:CONFIRMED".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in &lines {
+        out.push_str(&fold(line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// A `TEXT` value: backslashes, semicolons, commas and line breaks escaped.
+fn text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A quoted parameter value, which can't hold quotes.
+fn param(value: &str) -> String {
+    format!("\"{}\"", strip_controls(value).replace('"', ""))
+}
+
+fn strip_controls(value: &str) -> String {
+    value.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Split a content line into lines of at most 75 octets, continued with a
+/// leading space, without splitting a character.
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2024-03-01T{:02}:00:00Z", hour))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn settings(
+        start: Option<u32>,
+        end: Option<u32>,
+        recurrence: Option<&str>,
+    ) -> ConferenceSettings {
+        ConferenceSettings {
+            scheduled_start: start.map(|h| bson::DateTime::from_chrono(at(h))),
+            scheduled_end: end.map(|h| bson::DateTime::from_chrono(at(h))),
+            recurrence: recurrence.map(str::to_string),
+            ..ConferenceSettings::default()
+        }
+    }
+
+    fn event() -> Event {
+        Event {
+            uid: "abc@roomler".into(),
+            start: at(9),
+            end: at(10),
+            recurrence: None,
+            summary: "Weekly sync; planning, review".into(),
+            description: Some("Agenda:\nitems".into()),
+            url: Some("https://roomler.ai/join/abc-def-ghi".into()),
+            organizer: Some(Person {
+                name: "Ada \"The\" Organizer".into(),
+                email: "ada@example.com".into(),
+            }),
+            attendees: vec![Person {
+                name: "Bob".into(),
+                email: "bob@example.com".into(),
+            }],
+            last_modified: at(8),
+        }
+    }
+
+    #[test]
+    fn schedules_default_to_an_hour() {
+        assert_eq!(schedule(&settings(None, None, None)), None);
+        assert_eq!(
+            schedule(&settings(Some(9), None, None)),
+            Some((at(9), at(10)))
+        );
+        assert_eq!(
+            schedule(&settings(Some(9), Some(11), None)),
+            Some((at(9), at(11)))
+        );
+        // An end before the start is ignored
+        assert_eq!(
+            schedule(&settings(Some(9), Some(8), None)),
+            Some((at(9), at(10)))
+        );
+    }
+
+    #[test]
+    fn upcoming_until_the_end_unless_recurring() {
+        assert!(is_upcoming(&settings(Some(9), Some(11), None), at(10)));
+        assert!(!is_upcoming(&settings(Some(9), Some(11), None), at(11)));
+        assert!(is_upcoming(
+            &settings(Some(9), Some(11), Some("FREQ=WEEKLY")),
+            at(12)
+        ));
+        assert!(!is_upcoming(
+            &settings(None, None, Some("FREQ=DAILY")),
+            at(0)
+        ));
+    }
+
+    #[test]
+    fn recurrence_rules_are_rrule_values() {
+        assert_eq!(
+            recurrence_rule(Some("RRULE:FREQ=WEEKLY;BYDAY=MO,WE")),
+            Some("FREQ=WEEKLY;BYDAY=MO,WE".to_string())
+        );
+        assert_eq!(
+            recurrence_rule(Some(" FREQ=DAILY;UNTIL=20240401T000000Z ")),
+            Some("FREQ=DAILY;UNTIL=20240401T000000Z".to_string())
+        );
+        assert_eq!(recurrence_rule(Some("weekly")), None);
+        assert_eq!(recurrence_rule(Some("FREQ=DAILY\r\nATTENDEE:x")), None);
+        assert_eq!(recurrence_rule(None), None);
+    }
+
+    #[test]
+    fn renders_events() {
+        let mut recurring = event();
+        recurring.recurrence = Some("FREQ=WEEKLY".into());
+        let ics = render("Roomler", Method::Request, &[recurring], at(7));
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        for line in [
+            "METHOD:REQUEST",
+            "UID:abc@roomler",
+            "DTSTAMP:20240301T070000Z",
+            "DTSTART:20240301T090000Z",
+            "DTEND:20240301T100000Z",
+            "RRULE:FREQ=WEEKLY",
+            "SUMMARY:Weekly sync\\; planning\\, review",
+            "DESCRIPTION:Agenda:\\nitems",
+            "URL:https://roomler.ai/join/abc-def-ghi",
+            "ORGANIZER;CN=\"Ada The Organizer\":mailto:ada@example.com",
+            "ATTENDEE;CN=\"Bob\";ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:bob@example.com",
+        ] {
+            let unfolded = ics.replace("\r\n ", "");
+            assert!(unfolded.contains(&format!("{line}\r\n")), "missing {line}");
+        }
+
+        let published = render("Roomler", Method::Publish, &[event()], at(7));
+        assert!(published.contains("METHOD:PUBLISH\r\n"));
+        assert!(!published.contains("RSVP"));
+        assert!(!published.contains("RRULE"));
+    }
+
+    #[test]
+    fn folds_long_lines_between_characters() {
+        let mut long = event();
+        long.summary = "Überprüfung ".repeat(20);
+        let ics = render("Roomler", Method::Publish, &[long], at(7));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}\r\n", "Überprüfung ".repeat(20))));
+    }
+}
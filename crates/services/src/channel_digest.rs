@@ -0,0 +1,286 @@
+//! Daily channel digests: what happened in a channel over the last day,
+//! aggregated from its messages and optionally summarized by Claude, for
+//! members who don't follow it closely. Digests and other system messages
+//! are left out of the aggregation.
+
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use roomler_ai_db::models::{AuthorType, Message};
+
+/// Entries listed per section.
+const TOP_ITEMS: usize = 3;
+const MAX_FILES: usize = 10;
+/// Characters of a message quoted in the digest.
+const SNIPPET_CHARS: usize = 80;
+/// Only the most recent messages are sent to Claude.
+const MAX_PROMPT_MESSAGES: usize = 300;
+
+/// A message singled out by the digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub author_id: ObjectId,
+    pub snippet: String,
+    /// Replies in the period for threads, reactions for messages.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedFile {
+    pub author_id: ObjectId,
+    pub filename: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Highlights {
+    pub message_count: usize,
+    pub top_threads: Vec<Highlight>,
+    pub most_reacted: Vec<Highlight>,
+    pub files: Vec<SharedFile>,
+}
+
+/// The UTC day a digest posted at `now` covers, `YYYY-MM-DD`.
+pub fn day(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m-%d").to_string()
+}
+
+/// Threads that received replies among `messages`, whose roots the caller
+/// looks up for [`highlights`].
+pub fn thread_ids(messages: &[Message]) -> Vec<ObjectId> {
+    let mut ids: Vec<ObjectId> = messages.iter().filter_map(|m| m.thread_id).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Aggregate the period's `messages`. `roots` holds the thread roots from
+/// [`thread_ids`]; threads whose root is gone are left out.
+pub fn highlights(messages: &[Message], roots: &HashMap<ObjectId, Message>) -> Highlights {
+    let messages: Vec<&Message> = messages
+        .iter()
+        .filter(|m| !matches!(m.author_type, AuthorType::System))
+        .collect();
+
+    let mut replies: HashMap<ObjectId, u32> = HashMap::new();
+    for thread_id in messages.iter().filter_map(|m| m.thread_id) {
+        *replies.entry(thread_id).or_default() += 1;
+    }
+    let mut top_threads: Vec<Highlight> = replies
+        .into_iter()
+        .filter_map(|(id, count)| {
+            roots
+                .get(&id)
+                .filter(|root| root.deleted_at.is_none())
+                .map(|root| highlight(root, count))
+        })
+        .collect();
+    top_threads.sort_by(|a, b| b.count.cmp(&a.count).then(a.snippet.cmp(&b.snippet)));
+    top_threads.truncate(TOP_ITEMS);
+
+    let mut most_reacted: Vec<Highlight> = messages
+        .iter()
+        .map(|m| highlight(m, m.reaction_summary.iter().map(|r| r.count).sum()))
+        .filter(|h| h.count > 0)
+        .collect();
+    // Stable, so ties keep the earlier message first.
+    most_reacted.sort_by_key(|h| std::cmp::Reverse(h.count));
+    most_reacted.truncate(TOP_ITEMS);
+
+    let files = messages
+        .iter()
+        .flat_map(|m| {
+            m.attachments.iter().map(|a| SharedFile {
+                author_id: m.author_id,
+                filename: a.filename.clone(),
+            })
+        })
+        .take(MAX_FILES)
+        .collect();
+
+    Highlights {
+        message_count: messages.len(),
+        top_threads,
+        most_reacted,
+        files,
+    }
+}
+
+fn highlight(message: &Message, count: u32) -> Highlight {
+    let line = message.content.lines().next().unwrap_or("").trim();
+    let mut snippet: String = line.chars().take(SNIPPET_CHARS).collect();
+    if line.chars().count() > SNIPPET_CHARS || message.content.trim().lines().count() > 1 {
+        snippet.push('…');
+    }
+    Highlight {
+        author_id: message.author_id,
+        snippet,
+        count,
+    }
+}
+
+/// The digest message, in Markdown.
+pub fn render(
+    highlights: &Highlights,
+    names: &HashMap<ObjectId, String>,
+    summary: Option<&str>,
+) -> String {
+    let name = |id: &ObjectId| names.get(id).map_or("Someone", String::as_str);
+    let plural = |n: u32, one: &str, many: &str| {
+        if n == 1 {
+            format!("1 {one}")
+        } else {
+            format!("{n} {many}")
+        }
+    };
+
+    let mut out = format!(
+        "**Daily digest** — {} in the last 24 hours\n",
+        plural(highlights.message_count as u32, "message", "messages")
+    );
+    if let Some(summary) = summary {
+        out.push_str(&format!("\n{}\n", summary.trim()));
+    }
+    if !highlights.top_threads.is_empty() {
+        out.push_str("\n**Top threads**\n");
+        for h in &highlights.top_threads {
+            out.push_str(&format!(
+                "- {}: \"{}\" — {}\n",
+                name(&h.author_id),
+                h.snippet,
+                plural(h.count, "reply", "replies")
+            ));
+        }
+    }
+    if !highlights.most_reacted.is_empty() {
+        out.push_str("\n**Most reacted**\n");
+        for h in &highlights.most_reacted {
+            out.push_str(&format!(
+                "- {}: \"{}\" — {}\n",
+                name(&h.author_id),
+                h.snippet,
+                plural(h.count, "reaction", "reactions")
+            ));
+        }
+    }
+    if !highlights.files.is_empty() {
+        out.push_str("\n**Files shared**\n");
+        for f in &highlights.files {
+            out.push_str(&format!("- {} ({})\n", f.filename, name(&f.author_id)));
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Render the period's messages as a prompt for a short summary.
+pub fn build_prompt(messages: &[Message], names: &HashMap<ObjectId, String>) -> String {
+    let name = |id: &ObjectId| names.get(id).cloned().unwrap_or_else(|| id.to_hex());
+    let messages: Vec<&Message> = messages
+        .iter()
+        .filter(|m| !matches!(m.author_type, AuthorType::System))
+        .collect();
+    let skipped = messages.len().saturating_sub(MAX_PROMPT_MESSAGES);
+
+    let mut prompt = String::from(concat!(
+        "Summarize the last day of this chat channel for a member who wasn't ",
+        "following it. Mention the main topics, decisions and anything that ",
+        "needs their attention, in at most five short Markdown bullet points. ",
+        "Reply with the summary only.\n\n",
+    ));
+    if skipped > 0 {
+        prompt.push_str(&format!("({skipped} earlier messages omitted)\n"));
+    }
+    for message in &messages[skipped..] {
+        let prefix = if message.thread_id.is_some() {
+            "  (in thread) "
+        } else {
+            ""
+        };
+        prompt.push_str(&format!(
+            "{}{}: {}\n",
+            prefix,
+            name(&message.author_id),
+            message.content
+        ));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(author_id: ObjectId, content: &str) -> Message {
+        let now = bson::DateTime::now();
+        bson::from_document(bson::doc! {
+            "tenant_id": ObjectId::new(),
+            "room_id": ObjectId::new(),
+            "author_id": author_id,
+            "content": content,
+            "created_at": now,
+            "updated_at": now,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn aggregates_threads_reactions_and_files() {
+        let (ann, bob) = (ObjectId::new(), ObjectId::new());
+        let mut root = message(ann, "Release plan for Friday\nDetails inside");
+        root.id = Some(ObjectId::new());
+        let mut replies: Vec<Message> = (0..2)
+            .map(|_| {
+                let mut reply = message(bob, "Sounds good");
+                reply.thread_id = root.id;
+                reply
+            })
+            .collect();
+        let mut popular: Message = message(bob, "Ship it");
+        popular.reaction_summary = bson::from_bson(bson::bson!([
+            { "emoji": "👍", "count": 3 },
+            { "emoji": "🎉", "count": 1 },
+        ]))
+        .unwrap();
+        popular.attachments = bson::from_bson(bson::bson!([{
+            "file_id": ObjectId::new(),
+            "filename": "plan.pdf",
+            "content_type": "application/pdf",
+            "size": 10_i64,
+            "url": "/f",
+            "thumbnail_url": null,
+        }]))
+        .unwrap();
+        let mut system = message(ann, "**Daily digest** — earlier");
+        system.author_type = AuthorType::System;
+        replies.extend([popular, system]);
+
+        let roots = HashMap::from([(root.id.unwrap(), root.clone())]);
+        assert_eq!(thread_ids(&replies), vec![root.id.unwrap()]);
+        let h = highlights(&replies, &roots);
+        assert_eq!(h.message_count, 3);
+        assert_eq!(
+            h.top_threads,
+            vec![Highlight {
+                author_id: ann,
+                snippet: "Release plan for Friday…".to_string(),
+                count: 2,
+            }]
+        );
+        assert_eq!(h.most_reacted[0].count, 4);
+        assert_eq!(h.files[0].filename, "plan.pdf");
+
+        let names = HashMap::from([(ann, "Ann".to_string()), (bob, "Bob".to_string())]);
+        let text = render(&h, &names, Some("- Friday release agreed"));
+        assert_eq!(
+            text,
+            "**Daily digest** — 3 messages in the last 24 hours\n\n\
+             - Friday release agreed\n\n\
+             **Top threads**\n\
+             - Ann: \"Release plan for Friday…\" — 2 replies\n\n\
+             **Most reacted**\n\
+             - Bob: \"Ship it\" — 4 reactions\n\n\
+             **Files shared**\n\
+             - plan.pdf (Bob)"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use roomler_ai_config::{ConferenceLimitSettings, PlanConferenceLimits};
+use roomler_ai_db::models::Plan;
+use serde::Serialize;
+
+/// Which policy a warning or cutoff comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    MaxDuration,
+    Idle,
+}
+
+impl LimitKind {
+    /// Human-readable reason used in the system message posted on cutoff.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            LimitKind::MaxDuration => "it reached the maximum duration for this plan",
+            LimitKind::Idle => "nobody was sharing audio or video",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitDecision {
+    Continue,
+    Warn {
+        kind: LimitKind,
+        remaining: Duration,
+    },
+    End {
+        kind: LimitKind,
+    },
+}
+
+/// What happens to someone joining a capped conference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinDecision {
+    Admit,
+    Waitlist,
+    Full,
+}
+
+/// Decide on a join. `occupied` counts participants plus slots reserved for
+/// admitted waiters, `ahead` the waiters queued before this joiner, who get
+/// free slots first.
+pub fn join_decision(
+    max_participants: Option<u32>,
+    occupied: usize,
+    ahead: usize,
+    waitlist_enabled: bool,
+) -> JoinDecision {
+    match max_participants {
+        Some(cap) if occupied + ahead >= cap as usize => {
+            if waitlist_enabled {
+                JoinDecision::Waitlist
+            } else {
+                JoinDecision::Full
+            }
+        }
+        _ => JoinDecision::Admit,
+    }
+}
+
+pub fn limits_for_plan<'a>(
+    settings: &'a ConferenceLimitSettings,
+    plan: &Plan,
+) -> &'a PlanConferenceLimits {
+    match plan {
+        Plan::Free => &settings.free,
+        Plan::Pro => &settings.pro,
+        Plan::Business => &settings.business,
+        Plan::Enterprise => &settings.enterprise,
+    }
+}
+
+/// Decide what to do with a running conference. `idle_for` is how long the
+/// room has had no producers (`None` while someone is producing). When both
+/// limits apply, the one that cuts off first wins.
+pub fn evaluate(
+    limits: &PlanConferenceLimits,
+    warn_before: Duration,
+    elapsed: Duration,
+    idle_for: Option<Duration>,
+) -> LimitDecision {
+    let remaining = |limit_secs: Option<u64>, spent: Option<Duration>| {
+        Some(Duration::from_secs(limit_secs?).saturating_sub(spent?))
+    };
+    let candidates = [
+        (
+            LimitKind::MaxDuration,
+            remaining(limits.max_duration_secs, Some(elapsed)),
+        ),
+        (
+            LimitKind::Idle,
+            remaining(limits.idle_timeout_secs, idle_for),
+        ),
+    ];
+
+    let Some((kind, left)) = candidates
+        .into_iter()
+        .filter_map(|(kind, left)| Some((kind, left?)))
+        .min_by_key(|(_, left)| *left)
+    else {
+        return LimitDecision::Continue;
+    };
+
+    if left.is_zero() {
+        LimitDecision::End { kind }
+    } else if left <= warn_before {
+        LimitDecision::Warn {
+            kind,
+            remaining: left,
+        }
+    } else {
+        LimitDecision::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARN: Duration = Duration::from_secs(300);
+
+    fn limits(max: Option<u64>, idle: Option<u64>) -> PlanConferenceLimits {
+        PlanConferenceLimits {
+            max_duration_secs: max,
+            idle_timeout_secs: idle,
+            max_participants: None,
+        }
+    }
+
+    #[test]
+    fn unlimited_plan_never_ends() {
+        let d = evaluate(
+            &limits(None, None),
+            WARN,
+            Duration::from_secs(100_000),
+            Some(Duration::from_secs(100_000)),
+        );
+        assert_eq!(d, LimitDecision::Continue);
+    }
+
+    #[test]
+    fn warns_inside_window_then_ends() {
+        let l = limits(Some(3600), None);
+        assert_eq!(
+            evaluate(&l, WARN, Duration::from_secs(3000), None),
+            LimitDecision::Continue
+        );
+        assert_eq!(
+            evaluate(&l, WARN, Duration::from_secs(3400), None),
+            LimitDecision::Warn {
+                kind: LimitKind::MaxDuration,
+                remaining: Duration::from_secs(200)
+            }
+        );
+        assert_eq!(
+            evaluate(&l, WARN, Duration::from_secs(3600), None),
+            LimitDecision::End {
+                kind: LimitKind::MaxDuration
+            }
+        );
+    }
+
+    #[test]
+    fn idle_only_counts_while_no_producers() {
+        let l = limits(None, Some(600));
+        assert_eq!(
+            evaluate(&l, WARN, Duration::from_secs(10_000), None),
+            LimitDecision::Continue
+        );
+        assert_eq!(
+            evaluate(
+                &l,
+                WARN,
+                Duration::from_secs(10_000),
+                Some(Duration::from_secs(600))
+            ),
+            LimitDecision::End {
+                kind: LimitKind::Idle
+            }
+        );
+    }
+
+    #[test]
+    fn earliest_cutoff_wins() {
+        let l = limits(Some(3600), Some(600));
+        let d = evaluate(
+            &l,
+            WARN,
+            Duration::from_secs(3500),
+            Some(Duration::from_secs(60)),
+        );
+        assert_eq!(
+            d,
+            LimitDecision::Warn {
+                kind: LimitKind::MaxDuration,
+                remaining: Duration::from_secs(100)
+            }
+        );
+    }
+
+    #[test]
+    fn joins_fill_the_cap_then_wait_or_bounce() {
+        assert_eq!(join_decision(None, 500, 0, false), JoinDecision::Admit);
+        assert_eq!(join_decision(Some(3), 2, 0, false), JoinDecision::Admit);
+        assert_eq!(join_decision(Some(3), 3, 0, false), JoinDecision::Full);
+        assert_eq!(join_decision(Some(3), 3, 0, true), JoinDecision::Waitlist);
+    }
+
+    #[test]
+    fn queued_waiters_get_free_slots_first() {
+        assert_eq!(join_decision(Some(3), 2, 1, true), JoinDecision::Waitlist);
+        assert_eq!(join_decision(Some(3), 1, 1, true), JoinDecision::Admit);
+    }
+}
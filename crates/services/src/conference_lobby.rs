@@ -0,0 +1,157 @@
+//! Per-conference waiting rooms.
+//!
+//! With a room's lobby on, joiners who aren't organizers wait here until an
+//! organizer admits or denies them. Admission lasts until the call ends, so
+//! a reconnect doesn't send anyone back to the lobby. Everything lives on
+//! this instance and is dropped when the call ends.
+
+use std::collections::HashSet;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+
+#[derive(Default)]
+struct Lobby {
+    pending: Vec<ObjectId>,
+    admitted: HashSet<ObjectId>,
+}
+
+#[derive(Default)]
+pub struct ConferenceLobby {
+    rooms: DashMap<ObjectId, Lobby>,
+}
+
+impl ConferenceLobby {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `user_id` in the lobby. Returns whether they weren't already
+    /// waiting.
+    pub fn request(&self, room_id: ObjectId, user_id: ObjectId) -> bool {
+        let mut lobby = self.rooms.entry(room_id).or_default();
+        if lobby.pending.contains(&user_id) {
+            return false;
+        }
+        lobby.pending.push(user_id);
+        true
+    }
+
+    pub fn is_pending(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|lobby| lobby.pending.contains(user_id))
+    }
+
+    pub fn is_admitted(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|lobby| lobby.admitted.contains(user_id))
+    }
+
+    /// Let a waiting `user_id` in for the rest of the call. Returns whether
+    /// they were waiting.
+    pub fn admit(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        let Some(mut lobby) = self.rooms.get_mut(room_id) else {
+            return false;
+        };
+        if !remove(&mut lobby.pending, user_id) {
+            return false;
+        }
+        lobby.admitted.insert(*user_id);
+        true
+    }
+
+    /// Let everyone waiting in, for when the lobby is turned off. Returns
+    /// who was let in.
+    pub fn admit_all(&self, room_id: &ObjectId) -> Vec<ObjectId> {
+        let Some(mut lobby) = self.rooms.get_mut(room_id) else {
+            return Vec::new();
+        };
+        let admitted = std::mem::take(&mut lobby.pending);
+        lobby.admitted.extend(admitted.iter().copied());
+        admitted
+    }
+
+    /// Turn a waiting `user_id` away, or drop them when they give up.
+    /// Returns whether they were waiting.
+    pub fn remove(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        self.rooms
+            .get_mut(room_id)
+            .is_some_and(|mut lobby| remove(&mut lobby.pending, user_id))
+    }
+
+    /// Who is waiting, in arrival order.
+    pub fn pending(&self, room_id: &ObjectId) -> Vec<ObjectId> {
+        self.rooms
+            .get(room_id)
+            .map(|lobby| lobby.pending.clone())
+            .unwrap_or_default()
+    }
+
+    /// The call ended: forget who waited and who was let in.
+    pub fn clear(&self, room_id: &ObjectId) {
+        self.rooms.remove(room_id);
+    }
+}
+
+fn remove(pending: &mut Vec<ObjectId>, user_id: &ObjectId) -> bool {
+    let before = pending.len();
+    pending.retain(|u| u != user_id);
+    pending.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_wait_in_arrival_order_once() {
+        let lobby = ConferenceLobby::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        assert!(lobby.request(room, a));
+        assert!(lobby.request(room, b));
+        assert!(!lobby.request(room, a));
+        assert_eq!(lobby.pending(&room), vec![a, b]);
+        assert!(lobby.is_pending(&room, &a));
+    }
+
+    #[test]
+    fn admission_lasts_until_the_call_ends() {
+        let lobby = ConferenceLobby::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        lobby.request(room, a);
+        assert!(!lobby.admit(&room, &b));
+        assert!(lobby.admit(&room, &a));
+        assert!(lobby.is_admitted(&room, &a));
+        assert!(!lobby.is_pending(&room, &a));
+        lobby.clear(&room);
+        assert!(!lobby.is_admitted(&room, &a));
+    }
+
+    #[test]
+    fn denied_users_leave_the_lobby_without_admission() {
+        let lobby = ConferenceLobby::new();
+        let room = ObjectId::new();
+        let a = ObjectId::new();
+        lobby.request(room, a);
+        assert!(lobby.remove(&room, &a));
+        assert!(!lobby.remove(&room, &a));
+        assert!(!lobby.is_admitted(&room, &a));
+        assert!(lobby.pending(&room).is_empty());
+    }
+
+    #[test]
+    fn turning_the_lobby_off_admits_everyone_waiting() {
+        let lobby = ConferenceLobby::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        lobby.request(room, a);
+        lobby.request(room, b);
+        assert_eq!(lobby.admit_all(&room), vec![a, b]);
+        assert!(lobby.is_admitted(&room, &b));
+        assert!(lobby.pending(&room).is_empty());
+    }
+}
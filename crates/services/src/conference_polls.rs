@@ -0,0 +1,80 @@
+//! Checks and tallies for in-call polls and quizzes
+//! ([`ConferencePoll`](roomler_ai_db::models::ConferencePoll)).
+
+use roomler_ai_db::models::PollVote;
+
+pub const MAX_QUESTION_LEN: usize = 300;
+pub const MAX_OPTION_LEN: usize = 100;
+pub const MIN_OPTIONS: usize = 2;
+pub const MAX_OPTIONS: usize = 10;
+
+/// Check a new poll. `correct_option` makes it a quiz.
+pub fn validate(
+    question: &str,
+    options: &[String],
+    correct_option: Option<u32>,
+) -> Result<(), String> {
+    if question.trim().is_empty() || question.chars().count() > MAX_QUESTION_LEN {
+        return Err(format!("question must be 1-{MAX_QUESTION_LEN} characters"));
+    }
+    if !(MIN_OPTIONS..=MAX_OPTIONS).contains(&options.len()) {
+        return Err(format!("A poll needs {MIN_OPTIONS}-{MAX_OPTIONS} options"));
+    }
+    if options
+        .iter()
+        .any(|o| o.trim().is_empty() || o.chars().count() > MAX_OPTION_LEN)
+    {
+        return Err(format!("options must be 1-{MAX_OPTION_LEN} characters"));
+    }
+    if correct_option.is_some_and(|c| c as usize >= options.len()) {
+        return Err("correct_option is not one of the options".to_string());
+    }
+    Ok(())
+}
+
+/// Votes per option, in option order. Votes for unknown options are
+/// ignored.
+pub fn tally(option_count: usize, votes: &[PollVote]) -> Vec<u32> {
+    let mut counts = vec![0; option_count];
+    for vote in votes {
+        if let Some(count) = counts.get_mut(vote.option as usize) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{DateTime, oid::ObjectId};
+
+    fn options(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("Option {i}")).collect()
+    }
+
+    #[test]
+    fn polls_need_a_question_and_options() {
+        assert!(validate("Lunch?", &options(2), None).is_ok());
+        assert!(validate("Capital of France?", &options(3), Some(2)).is_ok());
+        assert!(validate(" ", &options(2), None).is_err());
+        assert!(validate("Lunch?", &options(1), None).is_err());
+        assert!(validate("Lunch?", &options(11), None).is_err());
+        assert!(validate("Lunch?", &["Pizza".into(), "".into()], None).is_err());
+        assert!(validate("Quiz", &options(3), Some(3)).is_err());
+    }
+
+    #[test]
+    fn tallies_votes_per_option() {
+        let vote = |option| PollVote {
+            user_id: ObjectId::new(),
+            option,
+            voted_at: DateTime::now(),
+        };
+        assert_eq!(
+            tally(3, &[vote(0), vote(2), vote(2), vote(7)]),
+            vec![1, 0, 2]
+        );
+        assert_eq!(tally(2, &[]), vec![0, 0]);
+    }
+}
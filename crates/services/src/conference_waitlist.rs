@@ -0,0 +1,182 @@
+//! Per-conference waitlists for rooms at their participant cap.
+//!
+//! Joiners turned away by a full conference queue up in arrival order. When
+//! a slot frees the head of the queue gets a pass: a reserved slot they
+//! claim by joining the media room. Organizers can hand out passes directly,
+//! past the cap. Everything lives on this instance and is dropped when the
+//! call ends.
+
+use std::collections::{HashSet, VecDeque};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+
+#[derive(Default)]
+struct Waitlist {
+    queue: VecDeque<ObjectId>,
+    passes: HashSet<ObjectId>,
+}
+
+#[derive(Default)]
+pub struct ConferenceWaitlist {
+    rooms: DashMap<ObjectId, Waitlist>,
+}
+
+impl ConferenceWaitlist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `user_id` unless they already are. Returns their 1-based
+    /// position.
+    pub fn enqueue(&self, room_id: ObjectId, user_id: ObjectId) -> usize {
+        let mut list = self.rooms.entry(room_id).or_default();
+        if let Some(i) = list.queue.iter().position(|u| *u == user_id) {
+            return i + 1;
+        }
+        list.queue.push_back(user_id);
+        list.queue.len()
+    }
+
+    /// 1-based position of `user_id`, if queued.
+    pub fn position(&self, room_id: &ObjectId, user_id: &ObjectId) -> Option<usize> {
+        let list = self.rooms.get(room_id)?;
+        list.queue.iter().position(|u| u == user_id).map(|i| i + 1)
+    }
+
+    /// How many waiters are ahead of `user_id`: everyone queued when they
+    /// aren't.
+    pub fn ahead_of(&self, room_id: &ObjectId, user_id: &ObjectId) -> usize {
+        let Some(list) = self.rooms.get(room_id) else {
+            return 0;
+        };
+        list.queue
+            .iter()
+            .position(|u| u == user_id)
+            .unwrap_or(list.queue.len())
+    }
+
+    pub fn has_pass(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|list| list.passes.contains(user_id))
+    }
+
+    /// Claim `user_id`'s pass. Returns whether they had one.
+    pub fn take_pass(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        self.rooms
+            .get_mut(room_id)
+            .is_some_and(|mut list| list.passes.remove(user_id))
+    }
+
+    /// Slots reserved by passes not yet claimed.
+    pub fn passes(&self, room_id: &ObjectId) -> usize {
+        self.rooms.get(room_id).map_or(0, |list| list.passes.len())
+    }
+
+    /// Take `user_id` off the queue and give them a pass, whether or not
+    /// there is room.
+    pub fn admit(&self, room_id: ObjectId, user_id: ObjectId) {
+        let mut list = self.rooms.entry(room_id).or_default();
+        list.queue.retain(|u| *u != user_id);
+        list.passes.insert(user_id);
+    }
+
+    /// Give passes to up to `slots` waiters from the head of the queue.
+    /// Returns who got one.
+    pub fn admit_next(&self, room_id: &ObjectId, slots: usize) -> Vec<ObjectId> {
+        let Some(mut list) = self.rooms.get_mut(room_id) else {
+            return Vec::new();
+        };
+        let n = slots.min(list.queue.len());
+        let admitted: Vec<ObjectId> = list.queue.drain(..n).collect();
+        list.passes.extend(admitted.iter().copied());
+        admitted
+    }
+
+    /// Drop `user_id` from the queue and forfeit any pass. Returns whether
+    /// they were waiting or holding one.
+    pub fn remove(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        let Some(mut list) = self.rooms.get_mut(room_id) else {
+            return false;
+        };
+        let queued = list.queue.len();
+        list.queue.retain(|u| u != user_id);
+        let had_pass = list.passes.remove(user_id);
+        had_pass || list.queue.len() != queued
+    }
+
+    /// The queue, head first.
+    pub fn waiting(&self, room_id: &ObjectId) -> Vec<ObjectId> {
+        self.rooms
+            .get(room_id)
+            .map(|list| list.queue.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The call ended: forget its queue and passes.
+    pub fn clear(&self, room_id: &ObjectId) {
+        self.rooms.remove(room_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_in_arrival_order_once() {
+        let w = ConferenceWaitlist::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        assert_eq!(w.enqueue(room, a), 1);
+        assert_eq!(w.enqueue(room, b), 2);
+        assert_eq!(w.enqueue(room, a), 1);
+        assert_eq!(w.ahead_of(&room, &b), 1);
+        assert_eq!(w.ahead_of(&room, &ObjectId::new()), 2);
+        assert_eq!(w.waiting(&room), vec![a, b]);
+    }
+
+    #[test]
+    fn freed_slots_go_to_the_head_of_the_queue() {
+        let w = ConferenceWaitlist::new();
+        let room = ObjectId::new();
+        let (a, b, c) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        for u in [a, b, c] {
+            w.enqueue(room, u);
+        }
+        assert_eq!(w.admit_next(&room, 2), vec![a, b]);
+        assert_eq!(w.passes(&room), 2);
+        assert_eq!(w.position(&room, &c), Some(1));
+        assert!(w.take_pass(&room, &a));
+        assert!(!w.take_pass(&room, &a));
+        assert_eq!(w.passes(&room), 1);
+    }
+
+    #[test]
+    fn organizer_admit_skips_the_queue() {
+        let w = ConferenceWaitlist::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        w.enqueue(room, a);
+        w.enqueue(room, b);
+        w.admit(room, b);
+        assert!(w.has_pass(&room, &b));
+        assert_eq!(w.waiting(&room), vec![a]);
+    }
+
+    #[test]
+    fn leaving_forfeits_place_and_pass() {
+        let w = ConferenceWaitlist::new();
+        let room = ObjectId::new();
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        w.enqueue(room, a);
+        w.admit(room, b);
+        assert!(w.remove(&room, &a));
+        assert!(w.remove(&room, &b));
+        assert!(!w.remove(&room, &b));
+        assert_eq!(w.passes(&room), 0);
+        w.clear(&room);
+        assert!(w.waiting(&room).is_empty());
+    }
+}
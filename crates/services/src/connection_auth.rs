@@ -0,0 +1,84 @@
+//! How long a WebSocket connection stays authenticated. A connection is
+//! authenticated by the access token it connected with, until that token
+//! expires. Clients hand over a fresh token with `auth:refresh` to keep the
+//! connection; the server warns with `auth:expiring` ahead of time and
+//! closes connections whose token ran out.
+
+/// Expiry of a connection's current token, in Unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionAuth {
+    expires_at: i64,
+    warned: bool,
+}
+
+/// What happens next to a connection, and when (Unix seconds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthDeadline {
+    Warn(i64),
+    Expire(i64),
+}
+
+impl AuthDeadline {
+    pub fn at(&self) -> i64 {
+        match self {
+            AuthDeadline::Warn(at) | AuthDeadline::Expire(at) => *at,
+        }
+    }
+}
+
+impl ConnectionAuth {
+    pub fn new(expires_at: i64) -> Self {
+        Self {
+            expires_at,
+            warned: false,
+        }
+    }
+
+    pub fn expires_at(&self) -> i64 {
+        self.expires_at
+    }
+
+    /// The next deadline, warning `warn_before` seconds ahead of expiry;
+    /// no warning when it's zero.
+    pub fn next(&self, warn_before: u64) -> AuthDeadline {
+        if self.warned || warn_before == 0 {
+            AuthDeadline::Expire(self.expires_at)
+        } else {
+            AuthDeadline::Warn(self.expires_at - warn_before as i64)
+        }
+    }
+
+    pub fn mark_warned(&mut self) {
+        self.warned = true;
+    }
+
+    /// Switch to a refreshed token. One expiring sooner than the current
+    /// token is refused, so a stale token can't shorten the connection.
+    pub fn refresh(&mut self, expires_at: i64) -> bool {
+        if expires_at < self.expires_at {
+            return false;
+        }
+        *self = Self::new(expires_at);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_once_then_expires_until_refreshed() {
+        let mut auth = ConnectionAuth::new(1_000);
+        assert_eq!(auth.next(60), AuthDeadline::Warn(940));
+        auth.mark_warned();
+        assert_eq!(auth.next(60), AuthDeadline::Expire(1_000));
+
+        assert!(!auth.refresh(999));
+        assert_eq!(auth.next(60), AuthDeadline::Expire(1_000));
+        assert!(auth.refresh(4_600));
+        assert_eq!(auth.expires_at(), 4_600);
+        assert_eq!(auth.next(60), AuthDeadline::Warn(4_540));
+        assert_eq!(auth.next(0), AuthDeadline::Expire(4_600));
+    }
+}
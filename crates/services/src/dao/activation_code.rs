@@ -1,6 +1,6 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::ActivationCode;
+use roomler_ai_db::models::{ActivationCode, CodePurpose};
 
 use super::base::{BaseDao, DaoResult};
 
@@ -18,11 +18,42 @@ impl ActivationCodeDao {
     pub async fn create(
         &self,
         user_id: ObjectId,
+        purpose: CodePurpose,
         token: String,
         ttl_minutes: u64,
+    ) -> DaoResult<ActivationCode> {
+        self.insert(user_id, purpose, token, None, ttl_minutes)
+            .await
+    }
+
+    /// Replaces the user's pending email change, if any.
+    pub async fn create_email_change(
+        &self,
+        user_id: ObjectId,
+        new_email: String,
+        token: String,
+        ttl_minutes: u64,
+    ) -> DaoResult<ActivationCode> {
+        self.insert(
+            user_id,
+            CodePurpose::EmailChange,
+            token,
+            Some(new_email),
+            ttl_minutes,
+        )
+        .await
+    }
+
+    async fn insert(
+        &self,
+        user_id: ObjectId,
+        purpose: CodePurpose,
+        token: String,
+        new_email: Option<String>,
+        ttl_minutes: u64,
     ) -> DaoResult<ActivationCode> {
         // Delete any existing codes for this user
-        self.base.hard_delete(doc! { "user_id": user_id }).await?;
+        self.delete_for_user(user_id, purpose).await?;
 
         let now = DateTime::now();
         let valid_to_ms = now.timestamp_millis() + (ttl_minutes as i64 * 60 * 1000);
@@ -31,7 +62,9 @@ impl ActivationCodeDao {
         let code = ActivationCode {
             id: None,
             user_id,
+            purpose,
             token,
+            new_email,
             valid_to,
             created_at: now,
         };
@@ -43,18 +76,45 @@ impl ActivationCodeDao {
     pub async fn find_valid(
         &self,
         user_id: ObjectId,
+        purpose: CodePurpose,
         token: &str,
     ) -> DaoResult<Option<ActivationCode>> {
         self.base
             .find_one(doc! {
                 "user_id": user_id,
+                "purpose": purpose_filter(purpose),
                 "token": token,
                 "valid_to": { "$gt": DateTime::now() },
             })
             .await
     }
 
-    pub async fn delete_for_user(&self, user_id: ObjectId) -> DaoResult<u64> {
-        self.base.hard_delete(doc! { "user_id": user_id }).await
+    /// The user's email change waiting to be confirmed.
+    pub async fn find_pending_email_change(
+        &self,
+        user_id: ObjectId,
+    ) -> DaoResult<Option<ActivationCode>> {
+        self.base
+            .find_one(doc! {
+                "user_id": user_id,
+                "purpose": purpose_filter(CodePurpose::EmailChange),
+                "valid_to": { "$gt": DateTime::now() },
+            })
+            .await
+    }
+
+    pub async fn delete_for_user(&self, user_id: ObjectId, purpose: CodePurpose) -> DaoResult<u64> {
+        self.base
+            .hard_delete(doc! { "user_id": user_id, "purpose": purpose_filter(purpose) })
+            .await
+    }
+}
+
+/// Matches codes stored before `purpose` existed as activation codes.
+fn purpose_filter(purpose: CodePurpose) -> bson::Bson {
+    match purpose {
+        CodePurpose::Activation => doc! { "$nin": ["password_reset", "email_change"] }.into(),
+        CodePurpose::PasswordReset => "password_reset".into(),
+        CodePurpose::EmailChange => "email_change".into(),
     }
 }
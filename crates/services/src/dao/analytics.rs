@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bson::{Bson, DateTime, Document, doc, oid::ObjectId};
+use chrono::{NaiveDate, NaiveTime};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{Message, RoomMember};
+
+use super::base::{BaseDao, DaoResult};
+use crate::analytics::{self, DailyActivity};
+
+/// Daily tenant activity, aggregated from messages and call sessions.
+/// Messages already moved to archive partitions are not counted.
+pub struct AnalyticsDao {
+    pub messages: BaseDao<Message>,
+    pub members: BaseDao<RoomMember>,
+}
+
+/// Per day: who was active, messages, call milliseconds.
+type Day = (HashSet<ObjectId>, u64, i64);
+
+impl AnalyticsDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            messages: BaseDao::new(db, Message::COLLECTION),
+            members: BaseDao::new(db, RoomMember::COLLECTION),
+        }
+    }
+
+    /// One row per day from `from` to `to` inclusive, zeros for quiet days.
+    /// Calls count towards the day they were joined on.
+    pub async fn daily_activity(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DaoResult<Vec<DailyActivity>> {
+        let start = DateTime::from_chrono(from.and_time(NaiveTime::MIN).and_utc());
+        let end = DateTime::from_chrono(
+            (to + chrono::Duration::days(1))
+                .and_time(NaiveTime::MIN)
+                .and_utc(),
+        );
+        let mut days: BTreeMap<String, Day> = BTreeMap::new();
+
+        let pipeline = vec![
+            doc! { "$match": {
+                "tenant_id": tenant_id,
+                "created_at": { "$gte": start, "$lt": end },
+                "author_type": "user",
+            }},
+            doc! { "$group": {
+                "_id": { "$dateToString": { "format": "%Y-%m-%d", "date": "$created_at" } },
+                "messages": { "$sum": 1 },
+                "users": { "$addToSet": "$author_id" },
+            }},
+        ];
+        let mut cursor = self.messages.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            let Ok(date) = row.get_str("_id") else {
+                continue;
+            };
+            let day = days.entry(date.to_string()).or_default();
+            day.0.extend(user_ids(&row));
+            day.1 += as_i64(row.get("messages")).max(0) as u64;
+        }
+
+        let pipeline = vec![
+            doc! { "$match": {
+                "tenant_id": tenant_id,
+                "sessions.joined_at": { "$gte": start, "$lt": end },
+            }},
+            doc! { "$unwind": "$sessions" },
+            doc! { "$match": { "sessions.joined_at": { "$gte": start, "$lt": end } } },
+            doc! { "$group": {
+                "_id": { "$dateToString": { "format": "%Y-%m-%d", "date": "$sessions.joined_at" } },
+                "ms": { "$sum": { "$subtract": [
+                    { "$ifNull": ["$sessions.left_at", DateTime::now()] },
+                    "$sessions.joined_at",
+                ] } },
+                "users": { "$addToSet": "$user_id" },
+            }},
+        ];
+        let mut cursor = self.members.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            let Ok(date) = row.get_str("_id") else {
+                continue;
+            };
+            let day = days.entry(date.to_string()).or_default();
+            day.0.extend(user_ids(&row));
+            day.2 += as_i64(row.get("ms")).max(0);
+        }
+
+        Ok(analytics::days(from, to)
+            .map(
+                |date| match days.remove(&date.format("%Y-%m-%d").to_string()) {
+                    Some((users, messages, ms)) => DailyActivity {
+                        date,
+                        active_users: users.len() as u64,
+                        messages,
+                        conference_minutes: (ms / 60_000) as u64,
+                    },
+                    None => DailyActivity::empty(date),
+                },
+            )
+            .collect())
+    }
+}
+
+/// The `users` set of a grouped row; guests without an account drop out.
+fn user_ids(row: &Document) -> impl Iterator<Item = ObjectId> + '_ {
+    row.get_array("users")
+        .into_iter()
+        .flatten()
+        .filter_map(Bson::as_object_id)
+}
+
+fn as_i64(value: Option<&Bson>) -> i64 {
+    match value {
+        Some(Bson::Int32(n)) => i64::from(*n),
+        Some(Bson::Int64(n)) => *n,
+        Some(Bson::Double(n)) => *n as i64,
+        _ => 0,
+    }
+}
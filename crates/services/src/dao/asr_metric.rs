@@ -0,0 +1,111 @@
+use bson::{Binary, DateTime, doc, oid::ObjectId, spec::BinarySubtype};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{AsrMetric, AsrSample};
+
+use super::base::{BaseDao, DaoResult};
+use crate::media::{asr_quality::SegmentQuality, transcript_feed::TranscriptEvent};
+
+/// MongoDB's "NamespaceExists" error code.
+const NAMESPACE_EXISTS: i32 = 48;
+
+pub struct AsrMetricDao {
+    db: Database,
+    pub metrics: BaseDao<AsrMetric>,
+    pub samples: BaseDao<AsrSample>,
+}
+
+impl AsrMetricDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            db: db.clone(),
+            metrics: BaseDao::new(db, AsrMetric::COLLECTION),
+            samples: BaseDao::new(db, AsrSample::COLLECTION),
+        }
+    }
+
+    /// Create `asr_metrics` as a capped collection of `max_bytes`. An
+    /// existing collection is left as it is.
+    pub async fn ensure_capped(&self, max_bytes: u64) -> DaoResult<()> {
+        match self
+            .db
+            .create_collection(AsrMetric::COLLECTION)
+            .capped(true)
+            .size(max_bytes)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) => match *e.kind {
+                mongodb::error::ErrorKind::Command(ref cmd) if cmd.code == NAMESPACE_EXISTS => {
+                    Ok(())
+                }
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    pub async fn record(
+        &self,
+        tenant_id: ObjectId,
+        event: &TranscriptEvent,
+        quality: &SegmentQuality,
+    ) -> DaoResult<ObjectId> {
+        self.metrics
+            .insert_one(&AsrMetric {
+                id: None,
+                tenant_id,
+                room_id: event.room_id,
+                user_id: event.user_id,
+                language: event.language.clone(),
+                confidence: event.confidence,
+                rms_dbfs: quality.rms_dbfs,
+                vad_ms: quality.vad_ms,
+                language_score_delta: quality.language_score_delta,
+                text_chars: event.text.chars().count() as u64,
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    pub async fn record_sample(
+        &self,
+        tenant_id: ObjectId,
+        event: &TranscriptEvent,
+        quality: &SegmentQuality,
+        audio: &[u8],
+    ) -> DaoResult<ObjectId> {
+        self.samples
+            .insert_one(&AsrSample {
+                id: None,
+                tenant_id,
+                room_id: event.room_id,
+                user_id: event.user_id,
+                text: event.text.clone(),
+                language: event.language.clone(),
+                confidence: event.confidence,
+                rms_dbfs: quality.rms_dbfs,
+                vad_ms: quality.vad_ms,
+                language_score_delta: quality.language_score_delta,
+                audio: Some(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: audio.to_vec(),
+                }),
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    /// A tenant's samples without their audio, newest first.
+    pub async fn list_samples(&self, tenant_id: ObjectId, limit: i64) -> DaoResult<Vec<AsrSample>> {
+        Ok(self
+            .samples
+            .collection()
+            .find(doc! { "tenant_id": tenant_id })
+            .projection(doc! { "audio": 0 })
+            .sort(doc! { "_id": -1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
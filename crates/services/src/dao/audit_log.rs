@@ -0,0 +1,92 @@
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{ActorType, AuditChange, AuditLog, AuditMetadata, actions};
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+pub struct AuditLogDao {
+    pub base: BaseDao<AuditLog>,
+}
+
+/// One action to record; see [`actions`] for the names.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub actor_id: Option<ObjectId>,
+    pub actor_type: ActorType,
+    pub action: String,
+    pub target_id: Option<ObjectId>,
+    pub changes: Vec<AuditChange>,
+    pub metadata: AuditMetadata,
+}
+
+/// Narrows a tenant's audit log; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub actor_id: Option<ObjectId>,
+    pub target_id: Option<ObjectId>,
+    pub from: Option<DateTime>,
+    pub to: Option<DateTime>,
+}
+
+impl AuditLogDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, AuditLog::COLLECTION),
+        }
+    }
+
+    pub async fn record(&self, tenant_id: ObjectId, entry: AuditEntry) -> DaoResult<ObjectId> {
+        self.base
+            .insert_one(&AuditLog {
+                id: None,
+                tenant_id,
+                actor_id: entry.actor_id,
+                actor_type: entry.actor_type,
+                target_type: actions::target_type(&entry.action).to_string(),
+                action: entry.action,
+                target_id: entry.target_id,
+                changes: entry.changes,
+                metadata: entry.metadata,
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    /// A tenant's entries matching `filter`, newest first.
+    pub async fn find(
+        &self,
+        tenant_id: ObjectId,
+        filter: &AuditFilter,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<AuditLog>> {
+        let mut query = doc! { "tenant_id": tenant_id };
+        if let Some(action) = &filter.action {
+            query.insert("action", action);
+        }
+        if let Some(target_type) = &filter.target_type {
+            query.insert("target_type", target_type);
+        }
+        if let Some(actor_id) = filter.actor_id {
+            query.insert("actor_id", actor_id);
+        }
+        if let Some(target_id) = filter.target_id {
+            query.insert("target_id", target_id);
+        }
+        let mut created_at = Document::new();
+        if let Some(from) = filter.from {
+            created_at.insert("$gte", from);
+        }
+        if let Some(to) = filter.to {
+            created_at.insert("$lt", to);
+        }
+        if !created_at.is_empty() {
+            query.insert("created_at", created_at);
+        }
+
+        self.base
+            .find_paginated(query, Some(doc! { "created_at": -1, "_id": -1 }), params)
+            .await
+    }
+}
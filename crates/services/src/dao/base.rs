@@ -24,6 +24,18 @@ pub enum DaoError {
 
 pub type DaoResult<T> = Result<T, DaoError>;
 
+/// A write's error, telling unique index violations apart.
+fn write_error(e: mongodb::error::Error) -> DaoError {
+    if let mongodb::error::ErrorKind::Write(mongodb::error::WriteFailure::WriteError(
+        ref write_error,
+    )) = *e.kind
+        && write_error.code == 11000
+    {
+        return DaoError::DuplicateKey(write_error.message.clone());
+    }
+    DaoError::Mongo(e)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaginationParams {
     #[serde(default = "default_page")]
@@ -195,16 +207,7 @@ where
     }
 
     pub async fn insert_one(&self, doc: &T) -> DaoResult<ObjectId> {
-        let result = self.collection.insert_one(doc).await.map_err(|e| {
-            if let mongodb::error::ErrorKind::Write(mongodb::error::WriteFailure::WriteError(
-                ref write_error,
-            )) = *e.kind
-                && write_error.code == 11000
-            {
-                return DaoError::DuplicateKey(write_error.message.clone());
-            }
-            DaoError::Mongo(e)
-        })?;
+        let result = self.collection.insert_one(doc).await.map_err(write_error)?;
 
         let id = result
             .inserted_id
@@ -244,7 +247,11 @@ where
             final_update = merged;
         }
 
-        let result = self.collection.update_one(filter, final_update).await?;
+        let result = self
+            .collection
+            .update_one(filter, final_update)
+            .await
+            .map_err(write_error)?;
         Ok(result.modified_count > 0)
     }
 
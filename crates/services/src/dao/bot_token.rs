@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::BotToken;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct BotTokenDao {
+    pub base: BaseDao<BotToken>,
+}
+
+impl BotTokenDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, BotToken::COLLECTION),
+        }
+    }
+
+    pub async fn create(&self, bot: &BotToken) -> DaoResult<BotToken> {
+        let id = self.base.insert_one(bot).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// The tenant's bots, revoked ones included, newest first.
+    pub async fn find_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<BotToken>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "created_at": -1 }),
+            )
+            .await
+    }
+
+    /// The unrevoked bot whose token hashes to `token_hash`.
+    pub async fn find_active(&self, token_hash: &str) -> DaoResult<Option<BotToken>> {
+        self.base
+            .find_one(doc! { "token_hash": token_hash, "revoked_at": null })
+            .await
+    }
+
+    /// False when the bot was already revoked.
+    pub async fn revoke(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id, "revoked_at": null },
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+            )
+            .await
+    }
+
+    pub async fn touch(&self, id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_by_id(id, doc! { "$set": { "last_used_at": DateTime::now() } })
+            .await
+    }
+
+    /// Names of the bots among `ids`, for showing them as message authors.
+    pub async fn find_names(&self, ids: &[ObjectId]) -> DaoResult<HashMap<ObjectId, String>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(self
+            .base
+            .find_by_ids(ids)
+            .await?
+            .into_iter()
+            .filter_map(|bot| Some((bot.id?, bot.name)))
+            .collect())
+    }
+}
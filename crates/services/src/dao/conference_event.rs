@@ -0,0 +1,64 @@
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{ConferenceEvent, ConferenceEventType};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct ConferenceEventDao {
+    pub base: BaseDao<ConferenceEvent>,
+}
+
+impl ConferenceEventDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ConferenceEvent::COLLECTION),
+        }
+    }
+
+    pub async fn record(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        event_type: ConferenceEventType,
+        user_id: Option<ObjectId>,
+        data: Document,
+    ) -> DaoResult<ObjectId> {
+        self.base
+            .insert_one(&ConferenceEvent {
+                id: None,
+                tenant_id,
+                room_id,
+                event_type,
+                user_id,
+                data,
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    /// Up to `limit` events of a room, oldest first, starting after the
+    /// event `after` when given. Ids grow with insertion time, so the last
+    /// id of a page is the cursor for the next.
+    pub async fn find_after(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        after: Option<ObjectId>,
+        limit: i64,
+    ) -> DaoResult<Vec<ConferenceEvent>> {
+        let mut filter = doc! { "tenant_id": tenant_id, "room_id": room_id };
+        if let Some(after) = after {
+            filter.insert("_id", doc! { "$gt": after });
+        }
+        Ok(self
+            .base
+            .collection()
+            .find(filter)
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
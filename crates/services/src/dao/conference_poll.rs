@@ -0,0 +1,79 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{ConferencePoll, PollStatus};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct ConferencePollDao {
+    pub base: BaseDao<ConferencePoll>,
+}
+
+impl ConferencePollDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ConferencePoll::COLLECTION),
+        }
+    }
+
+    pub async fn create(&self, poll: &ConferencePoll) -> DaoResult<ConferencePoll> {
+        let id = self.base.insert_one(poll).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// A room's polls, newest first.
+    pub async fn find_for_room(&self, room_id: ObjectId) -> DaoResult<Vec<ConferencePoll>> {
+        self.base
+            .find_many(doc! { "room_id": room_id }, Some(doc! { "created_at": -1 }))
+            .await
+    }
+
+    pub async fn find_open(&self, room_id: ObjectId) -> DaoResult<Vec<ConferencePoll>> {
+        self.base
+            .find_many(
+                doc! { "room_id": room_id, "status": "open" },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Record `user_id`'s vote on an open poll, replacing an earlier one.
+    /// Returns false if the poll isn't open.
+    pub async fn vote(&self, poll_id: ObjectId, user_id: ObjectId, option: u32) -> DaoResult<bool> {
+        let now = DateTime::now();
+        // Matched rather than modified: repeating the same vote is fine.
+        let changed = self
+            .base
+            .collection()
+            .update_one(
+                doc! { "_id": poll_id, "status": "open", "votes.user_id": user_id },
+                doc! { "$set": {
+                    "votes.$.option": option,
+                    "votes.$.voted_at": now,
+                    "updated_at": now,
+                } },
+            )
+            .await?;
+        if changed.matched_count > 0 {
+            return Ok(true);
+        }
+        self.base
+            .update_one(
+                doc! { "_id": poll_id, "status": "open", "votes.user_id": { "$ne": user_id } },
+                doc! { "$push": { "votes": { "user_id": user_id, "option": option, "voted_at": now } } },
+            )
+            .await
+    }
+
+    /// Close an open poll. Returns false if it was already closed.
+    pub async fn close(&self, poll_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": poll_id, "status": "open" },
+                doc! { "$set": {
+                    "status": bson::to_bson(&PollStatus::Closed)?,
+                    "closed_at": DateTime::now(),
+                } },
+            )
+            .await
+    }
+}
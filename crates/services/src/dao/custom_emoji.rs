@@ -0,0 +1,66 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::CustomEmoji;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct CustomEmojiDao {
+    pub base: BaseDao<CustomEmoji>,
+}
+
+impl CustomEmojiDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CustomEmoji::COLLECTION),
+        }
+    }
+
+    /// Register an emoji; fails with a duplicate key error when the tenant
+    /// already has one by `name`.
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        name: String,
+        image_url: String,
+        file_id: ObjectId,
+        is_animated: bool,
+        creator_id: ObjectId,
+    ) -> DaoResult<CustomEmoji> {
+        let now = DateTime::now();
+        let emoji = CustomEmoji {
+            id: None,
+            tenant_id,
+            name,
+            image_url,
+            file_id: Some(file_id),
+            is_animated,
+            creator_id,
+            allowed_role_ids: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&emoji).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// The tenant's custom emoji, by name.
+    pub async fn find_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<CustomEmoji>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id }, Some(doc! { "name": 1 }))
+            .await
+    }
+
+    pub async fn count_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base.count(doc! { "tenant_id": tenant_id }).await
+    }
+
+    /// Remove an emoji. Messages and reactions keep its `:name:`, which no
+    /// longer resolves.
+    pub async fn delete(&self, tenant_id: ObjectId, emoji_id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": emoji_id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+}
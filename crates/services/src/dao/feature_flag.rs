@@ -0,0 +1,108 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{FeatureFlag, FeatureFlagOverride};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct FeatureFlagDao {
+    pub base: BaseDao<FeatureFlag>,
+    pub overrides: BaseDao<FeatureFlagOverride>,
+}
+
+impl FeatureFlagDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, FeatureFlag::COLLECTION),
+            overrides: BaseDao::new(db, FeatureFlagOverride::COLLECTION),
+        }
+    }
+
+    pub async fn list_flags(&self) -> DaoResult<Vec<FeatureFlag>> {
+        self.base.find_many(doc! {}, Some(doc! { "key": 1 })).await
+    }
+
+    pub async fn find_flag(&self, key: &str) -> DaoResult<Option<FeatureFlag>> {
+        self.base.find_one(doc! { "key": key }).await
+    }
+
+    /// Tenant-level overrides plus, when `user_id` is given, that user's
+    /// overrides within the tenant.
+    pub async fn find_overrides(
+        &self,
+        tenant_id: ObjectId,
+        user_id: Option<ObjectId>,
+    ) -> DaoResult<Vec<FeatureFlagOverride>> {
+        let user_filter = match user_id {
+            Some(uid) => doc! { "user_id": { "$in": [bson::Bson::Null, uid] } },
+            None => doc! { "user_id": null },
+        };
+        let mut filter = doc! { "tenant_id": tenant_id };
+        filter.extend(user_filter);
+        self.overrides.find_many(filter, None).await
+    }
+
+    pub async fn list_tenant_overrides(
+        &self,
+        tenant_id: ObjectId,
+    ) -> DaoResult<Vec<FeatureFlagOverride>> {
+        self.overrides
+            .find_many(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "key": 1, "user_id": 1 }),
+            )
+            .await
+    }
+
+    pub async fn set_override(
+        &self,
+        key: &str,
+        tenant_id: ObjectId,
+        user_id: Option<ObjectId>,
+        enabled: bool,
+        set_by: ObjectId,
+    ) -> DaoResult<FeatureFlagOverride> {
+        let filter = doc! { "key": key, "tenant_id": tenant_id, "user_id": user_id };
+
+        if let Some(existing) = self.overrides.find_one(filter.clone()).await? {
+            self.overrides
+                .update_one(
+                    filter,
+                    doc! { "$set": { "enabled": enabled, "set_by": set_by } },
+                )
+                .await?;
+            return Ok(FeatureFlagOverride {
+                enabled,
+                set_by,
+                updated_at: DateTime::now(),
+                ..existing
+            });
+        }
+
+        let now = DateTime::now();
+        let row = FeatureFlagOverride {
+            id: None,
+            key: key.to_string(),
+            tenant_id,
+            user_id,
+            enabled,
+            set_by,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.overrides.insert_one(&row).await?;
+        self.overrides.find_by_id(id).await
+    }
+
+    pub async fn clear_override(
+        &self,
+        key: &str,
+        tenant_id: ObjectId,
+        user_id: Option<ObjectId>,
+    ) -> DaoResult<bool> {
+        let deleted = self
+            .overrides
+            .hard_delete(doc! { "key": key, "tenant_id": tenant_id, "user_id": user_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+}
@@ -1,18 +1,23 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
+use mongodb::options::ReturnDocument;
 use roomler_ai_db::models::recording::{StorageProvider, Visibility};
-use roomler_ai_db::models::{self, FileContext, ScanStatus};
+use roomler_ai_db::models::{self, FileBlob, FileContext, PendingUpload, ScanStatus};
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
 
 pub struct FileDao {
     pub base: BaseDao<models::File>,
+    pub pending_uploads: BaseDao<PendingUpload>,
+    pub blobs: BaseDao<FileBlob>,
 }
 
 impl FileDao {
     pub fn new(db: &Database) -> Self {
         Self {
             base: BaseDao::new(db, models::File::COLLECTION),
+            pending_uploads: BaseDao::new(db, PendingUpload::COLLECTION),
+            blobs: BaseDao::new(db, FileBlob::COLLECTION),
         }
     }
 
@@ -25,9 +30,12 @@ impl FileDao {
         filename: String,
         content_type: String,
         size: u64,
+        storage_provider: StorageProvider,
         storage_bucket: String,
         storage_key: String,
         url: String,
+        checksum: Option<String>,
+        duplicate_of: Option<ObjectId>,
     ) -> DaoResult<models::File> {
         let now = DateTime::now();
         let file = models::File {
@@ -38,13 +46,14 @@ impl FileDao {
             filename: filename.clone(),
             display_name: Some(filename),
             description: None,
-            storage_provider: StorageProvider::MinIO,
+            storage_provider,
             storage_bucket,
             storage_key,
             url,
             content_type,
             size,
-            checksum: None,
+            checksum,
+            duplicate_of,
             dimensions: None,
             duration: None,
             thumbnails: Vec::new(),
@@ -119,7 +128,209 @@ impl FileDao {
             .await
     }
 
+    /// Clips cut from a recording, newest first.
+    pub async fn find_by_recording(
+        &self,
+        tenant_id: ObjectId,
+        recording_id: ObjectId,
+    ) -> DaoResult<Vec<models::File>> {
+        self.base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "context.context_type": "recording",
+                    "context.entity_id": recording_id,
+                    "deleted_at": null,
+                },
+                Some(doc! { "created_at": -1 }),
+            )
+            .await
+    }
+
+    /// Mark an uploaded file as a clip of `recording_id` lasting
+    /// `duration` seconds. It stays listed with its room's files.
+    pub async fn link_to_recording(
+        &self,
+        tenant_id: ObjectId,
+        file_id: ObjectId,
+        recording_id: ObjectId,
+        duration: u32,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": file_id, "tenant_id": tenant_id },
+                doc! { "$set": {
+                    "context.context_type": "recording",
+                    "context.entity_id": recording_id,
+                    "duration": duration,
+                } },
+            )
+            .await
+    }
+
+    /// False if the file was already deleted, so its blob reference is
+    /// released exactly once.
     pub async fn soft_delete(&self, tenant_id: ObjectId, file_id: ObjectId) -> DaoResult<bool> {
-        self.base.soft_delete_in_tenant(tenant_id, file_id).await
+        self.base
+            .update_one(
+                doc! { "_id": file_id, "tenant_id": tenant_id, "deleted_at": null },
+                doc! { "$set": { "deleted_at": DateTime::now() } },
+            )
+            .await
+    }
+
+    /// The earliest live file in the tenant with this content.
+    pub async fn find_by_checksum(
+        &self,
+        tenant_id: ObjectId,
+        checksum: &str,
+    ) -> DaoResult<Option<models::File>> {
+        Ok(self
+            .base
+            .collection()
+            .find_one(doc! {
+                "tenant_id": tenant_id,
+                "checksum": checksum,
+                "deleted_at": null,
+            })
+            .sort(doc! { "created_at": 1 })
+            .await?)
+    }
+
+    // ── Content dedup ───────────────────────────────────────────
+
+    /// Takes a reference on the tenant's blob for `checksum`, registering
+    /// the given object as that blob if there is none yet. The returned
+    /// blob's key differs from `storage_key` when the content was already
+    /// stored, in which case the caller's copy is redundant.
+    pub async fn retain_blob(
+        &self,
+        tenant_id: ObjectId,
+        checksum: &str,
+        storage_provider: StorageProvider,
+        storage_bucket: &str,
+        storage_key: &str,
+        size: u64,
+    ) -> DaoResult<FileBlob> {
+        let now = DateTime::now();
+        let provider = bson::to_bson(&storage_provider)?;
+        self.blobs
+            .collection()
+            .find_one_and_update(
+                doc! { "tenant_id": tenant_id, "checksum": checksum },
+                doc! {
+                    "$inc": { "ref_count": 1_i64 },
+                    "$set": { "updated_at": now },
+                    "$setOnInsert": {
+                        "storage_provider": provider,
+                        "storage_bucket": storage_bucket,
+                        "storage_key": storage_key,
+                        "size": size as i64,
+                        "created_at": now,
+                    },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    /// Drops a reference on the tenant's blob for `checksum`. Returns the
+    /// blob once nothing references it any more; the caller then deletes
+    /// the stored object.
+    pub async fn release_blob(
+        &self,
+        tenant_id: ObjectId,
+        checksum: &str,
+    ) -> DaoResult<Option<FileBlob>> {
+        let Some(blob) = self
+            .blobs
+            .collection()
+            .find_one_and_update(
+                doc! { "tenant_id": tenant_id, "checksum": checksum },
+                doc! {
+                    "$inc": { "ref_count": -1_i64 },
+                    "$set": { "updated_at": DateTime::now() },
+                },
+            )
+            .return_document(ReturnDocument::After)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if blob.ref_count > 0 {
+            return Ok(None);
+        }
+        // An upload may have retained the blob again in the meantime, in
+        // which case the count is back above zero and the row stays.
+        Ok(self
+            .blobs
+            .collection()
+            .find_one_and_delete(doc! { "_id": blob.id, "ref_count": { "$lte": 0 } })
+            .await?)
+    }
+
+    // ── Direct uploads ──────────────────────────────────────────
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_pending_upload(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        filename: String,
+        content_type: String,
+        size: u64,
+        storage_key: String,
+        expires_at: DateTime,
+    ) -> DaoResult<PendingUpload> {
+        let upload = PendingUpload {
+            id: None,
+            tenant_id,
+            room_id,
+            user_id,
+            filename,
+            content_type,
+            size,
+            storage_key,
+            expires_at,
+            created_at: DateTime::now(),
+        };
+        let id = self.pending_uploads.insert_one(&upload).await?;
+        Ok(PendingUpload {
+            id: Some(id),
+            ..upload
+        })
+    }
+
+    /// The caller's unexpired pending upload. TTL cleanup lags, so expiry is
+    /// checked here too.
+    pub async fn find_pending_upload(
+        &self,
+        tenant_id: ObjectId,
+        upload_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<PendingUpload> {
+        self.pending_uploads
+            .find_one(doc! {
+                "_id": upload_id,
+                "tenant_id": tenant_id,
+                "user_id": user_id,
+                "expires_at": { "$gt": DateTime::now() },
+            })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    /// Removes a pending upload; false if it was already gone, e.g. because
+    /// a concurrent confirm claimed it.
+    pub async fn delete_pending_upload(&self, upload_id: ObjectId) -> DaoResult<bool> {
+        let result = self
+            .pending_uploads
+            .collection()
+            .delete_one(doc! { "_id": upload_id })
+            .await?;
+        Ok(result.deleted_count > 0)
     }
 }
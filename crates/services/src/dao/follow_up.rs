@@ -0,0 +1,127 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{FollowUp, FollowUpStatus};
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+pub struct FollowUpDao {
+    pub base: BaseDao<FollowUp>,
+}
+
+impl FollowUpDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, FollowUp::COLLECTION),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        created_by: ObjectId,
+        assignee_id: ObjectId,
+        title: String,
+        due_at: Option<DateTime>,
+    ) -> DaoResult<FollowUp> {
+        let now = DateTime::now();
+        let follow_up = FollowUp {
+            id: None,
+            tenant_id,
+            room_id,
+            created_by,
+            assignee_id,
+            title,
+            due_at,
+            status: FollowUpStatus::Open,
+            reminded_at: None,
+            completed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&follow_up).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// A user's follow-ups, soonest due first; ones without a due date
+    /// come before the rest.
+    pub async fn find_for_assignee(
+        &self,
+        tenant_id: ObjectId,
+        assignee_id: ObjectId,
+        status: Option<FollowUpStatus>,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<FollowUp>> {
+        let mut filter = doc! { "tenant_id": tenant_id, "assignee_id": assignee_id };
+        if let Some(status) = status {
+            filter.insert("status", bson::to_bson(&status)?);
+        }
+        self.base
+            .find_paginated(filter, Some(doc! { "due_at": 1, "_id": 1 }), params)
+            .await
+    }
+
+    /// A room's follow-ups, newest first.
+    pub async fn find_in_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+    ) -> DaoResult<Vec<FollowUp>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "room_id": room_id },
+                Some(doc! { "created_at": -1 }),
+            )
+            .await
+    }
+
+    pub async fn set_status(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        status: FollowUpStatus,
+    ) -> DaoResult<bool> {
+        let completed_at = match status {
+            FollowUpStatus::Done => Some(DateTime::now()),
+            FollowUpStatus::Open => None,
+        };
+        self.base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": {
+                    "status": bson::to_bson(&status)?,
+                    "completed_at": completed_at,
+                } },
+            )
+            .await
+    }
+
+    /// Open follow-ups due by `now` whose reminder hasn't been posted.
+    pub async fn find_due(&self, now: DateTime, limit: i64) -> DaoResult<Vec<FollowUp>> {
+        Ok(self
+            .base
+            .collection()
+            .find(doc! {
+                "status": bson::to_bson(&FollowUpStatus::Open)?,
+                "reminded_at": null,
+                "due_at": { "$lte": now },
+            })
+            .sort(doc! { "due_at": 1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    /// Claim a follow-up's reminder. False when another instance already
+    /// did, so each reminder is posted once.
+    pub async fn mark_reminded(&self, id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": id, "reminded_at": null },
+                doc! { "$set": { "reminded_at": DateTime::now() } },
+            )
+            .await
+    }
+}
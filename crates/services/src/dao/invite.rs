@@ -13,6 +13,7 @@ pub struct CreateInviteParams {
     pub max_uses: Option<u32>,
     pub expires_in_hours: Option<u64>,
     pub assign_role_ids: Vec<ObjectId>,
+    pub join_room_ids: Vec<ObjectId>,
 }
 
 impl InviteDao {
@@ -54,6 +55,7 @@ impl InviteDao {
             use_count: 0,
             expires_at,
             assign_role_ids: params.assign_role_ids,
+            join_room_ids: params.join_room_ids,
             status: InviteStatus::Active,
             created_at: now,
             updated_at: now,
@@ -84,33 +86,51 @@ impl InviteDao {
             .await
     }
 
-    /// Atomically increment use_count. Returns the updated invite if successful.
-    /// Fails if use_count >= max_uses (invite exhausted).
-    pub async fn increment_use_count(&self, invite_id: ObjectId) -> DaoResult<Invite> {
+    /// Atomically take one of the invite's uses. Fails, without taking
+    /// one, when the invite is revoked, expired or already fully used; the
+    /// last use marks it exhausted in the same update.
+    pub async fn claim_use(&self, invite_id: ObjectId) -> DaoResult<Invite> {
         use mongodb::options::FindOneAndUpdateOptions;
         use mongodb::options::ReturnDocument;
 
-        // Atomic: only increment if status is Active and use_count < max_uses (or max_uses is null)
+        let now = DateTime::now();
         let filter = doc! {
             "_id": invite_id,
             "status": "active",
-            "$or": [
-                { "max_uses": null },
-                { "$expr": { "$lt": ["$use_count", "$max_uses"] } },
+            "$and": [
+                { "$or": [
+                    { "max_uses": null },
+                    { "$expr": { "$lt": ["$use_count", "$max_uses"] } },
+                ] },
+                { "$or": [
+                    { "expires_at": null },
+                    { "expires_at": { "$gt": now } },
+                ] },
             ],
         };
-
-        let update = doc! {
-            "$inc": { "use_count": 1_i32 },
-            "$set": { "updated_at": DateTime::now() },
-        };
+        let used = doc! { "$add": [{ "$ifNull": ["$use_count", 0] }, 1] };
+        let update = vec![doc! {
+            "$set": {
+                "use_count": used.clone(),
+                "status": {
+                    "$cond": [
+                        { "$and": [
+                            { "$isNumber": "$max_uses" },
+                            { "$gte": [used, "$max_uses"] },
+                        ] },
+                        "exhausted",
+                        "$status",
+                    ],
+                },
+                "updated_at": now,
+            },
+        }];
 
         let options = FindOneAndUpdateOptions::builder()
             .return_document(ReturnDocument::After)
             .build();
 
-        let invite = self
-            .base
+        self.base
             .collection()
             .find_one_and_update(filter, update)
             .with_options(options)
@@ -120,19 +140,27 @@ impl InviteDao {
                 DaoError::Validation(
                     "Invite cannot be used (exhausted, expired, or revoked)".to_string(),
                 )
-            })?;
-
-        // Auto-set status to Exhausted when use_count >= max_uses
-        if let Some(max) = invite.max_uses
-            && invite.use_count >= max
-        {
-            let _ = self
-                .base
-                .update_by_id(invite_id, doc! { "$set": { "status": "exhausted" } })
-                .await;
-        }
+            })
+    }
 
-        Ok(invite)
+    /// Give back a use taken by [`claim_use`](Self::claim_use) whose
+    /// acceptance then failed, reopening an exhausted invite.
+    pub async fn release_use(&self, invite_id: ObjectId) -> DaoResult<()> {
+        let update = vec![doc! {
+            "$set": {
+                "use_count": { "$subtract": ["$use_count", 1] },
+                "status": {
+                    "$cond": [{ "$eq": ["$status", "exhausted"] }, "active", "$status"],
+                },
+                "updated_at": DateTime::now(),
+            },
+        }];
+        self.base
+            .collection()
+            .update_one(doc! { "_id": invite_id, "use_count": { "$gt": 0 } }, update)
+            .await
+            .map_err(DaoError::Mongo)?;
+        Ok(())
     }
 
     pub async fn revoke(&self, invite_id: ObjectId, tenant_id: ObjectId) -> DaoResult<bool> {
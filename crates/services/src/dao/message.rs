@@ -1,19 +1,24 @@
-use bson::{DateTime, doc, oid::ObjectId};
+use bson::{DateTime, Document, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::{
-    AuthorType, ContentType, Mentions, Message, MessageAttachment, MessageType, ReactionSummary,
+    AuthorType, ContentType, Embed, Mentions, Message, MessageArchivePartition, MessageAttachment,
+    MessageType, ReactionSummary, ThreadSummary,
 };
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
 
 pub struct MessageDao {
     pub base: BaseDao<Message>,
+    pub archive_partitions: BaseDao<MessageArchivePartition>,
+    db: Database,
 }
 
 impl MessageDao {
     pub fn new(db: &Database) -> Self {
         Self {
             base: BaseDao::new(db, Message::COLLECTION),
+            archive_partitions: BaseDao::new(db, MessageArchivePartition::COLLECTION),
+            db: db.clone(),
         }
     }
 
@@ -39,6 +44,7 @@ impl MessageDao {
             nonce,
             mentions,
             Vec::new(),
+            Vec::new(),
         )
         .await
     }
@@ -55,6 +61,7 @@ impl MessageDao {
         nonce: Option<String>,
         mentions: Option<Mentions>,
         attachments: Vec<MessageAttachment>,
+        embeds: Vec<Embed>,
     ) -> DaoResult<Message> {
         let now = DateTime::now();
         let message_type = if referenced_message_id.is_some() {
@@ -70,16 +77,19 @@ impl MessageDao {
             thread_id,
             is_thread_root: false,
             thread_metadata: None,
+            thread_summary: None,
             author_id,
             author_type: AuthorType::User,
             content,
             content_type: ContentType::Markdown,
             message_type,
-            embeds: Vec::new(),
+            embeds,
             attachments,
             mentions: mentions.unwrap_or_default(),
             reaction_summary: Vec::new(),
             referenced_message_id,
+            cross_post_group_id: None,
+            flagged_terms: Vec::new(),
             is_pinned: false,
             is_edited: false,
             edited_at: None,
@@ -100,23 +110,315 @@ impl MessageDao {
         self.base.find_by_id(id).await
     }
 
+    /// Post the same message to each of `room_ids`, linked by a new
+    /// `cross_post_group_id`, and return the copies in the order of
+    /// `room_ids`. Only the first copy, in the origin room, carries the
+    /// `nonce`; it is written first, so a duplicate send fails before any
+    /// other room gets a copy. If a copy fails to write, every copy is
+    /// removed again.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_cross_posts(
+        &self,
+        tenant_id: ObjectId,
+        room_ids: &[ObjectId],
+        author_id: ObjectId,
+        content: String,
+        nonce: Option<String>,
+        mentions: Option<Mentions>,
+        attachments: Vec<MessageAttachment>,
+        embeds: Vec<Embed>,
+    ) -> DaoResult<Vec<Message>> {
+        let now = DateTime::now();
+        let group_id = ObjectId::new();
+        let messages: Vec<Message> = room_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &room_id)| Message {
+                id: Some(ObjectId::new()),
+                tenant_id,
+                room_id,
+                thread_id: None,
+                is_thread_root: false,
+                thread_metadata: None,
+                thread_summary: None,
+                author_id,
+                author_type: AuthorType::User,
+                content: content.clone(),
+                content_type: ContentType::Markdown,
+                message_type: MessageType::Default,
+                embeds: embeds.clone(),
+                attachments: attachments.clone(),
+                mentions: mentions.clone().unwrap_or_default(),
+                reaction_summary: Vec::new(),
+                referenced_message_id: None,
+                cross_post_group_id: Some(group_id),
+                flagged_terms: Vec::new(),
+                is_pinned: false,
+                is_edited: false,
+                edited_at: None,
+                nonce: if i == 0 { nonce.clone() } else { None },
+                readby: vec![author_id],
+                created_at: now,
+                updated_at: now,
+                deleted_at: None,
+            })
+            .collect();
+        let Some((origin, copies)) = messages.split_first() else {
+            return Ok(messages);
+        };
+        self.base.insert_one(origin).await?;
+        if !copies.is_empty()
+            && let Err(e) = self.base.collection().insert_many(copies).await
+        {
+            // Without a transaction, take back whatever was written so no
+            // room is left with a copy that was never broadcast.
+            self.base
+                .hard_delete(doc! { "cross_post_group_id": group_id })
+                .await?;
+            return Err(e.into());
+        }
+        Ok(messages)
+    }
+
+    /// The author's message in the room sent with the client `nonce`,
+    /// deleted or not; a unique index keeps it to one.
+    pub async fn find_by_nonce(
+        &self,
+        room_id: ObjectId,
+        author_id: ObjectId,
+        nonce: &str,
+    ) -> DaoResult<Option<Message>> {
+        self.base
+            .find_one(doc! { "room_id": room_id, "author_id": author_id, "nonce": nonce })
+            .await
+    }
+
+    /// The live copies of a cross-posted message.
+    pub async fn find_cross_posts(&self, group_id: ObjectId) -> DaoResult<Vec<Message>> {
+        self.base
+            .find_many(
+                doc! { "cross_post_group_id": group_id, "deleted_at": null },
+                Some(doc! { "_id": 1 }),
+            )
+            .await
+    }
+
+    /// Post a system-authored message (welcome notes, automated notices).
+    /// `author_id` is the user on whose behalf the system acted.
+    pub async fn create_system(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        author_id: ObjectId,
+        content: String,
+    ) -> DaoResult<Message> {
+        self.create_automated(
+            tenant_id,
+            room_id,
+            author_id,
+            AuthorType::System,
+            content,
+            Vec::new(),
+        )
+        .await
+    }
+
+    /// Post a message from a bot token; `bot_id` is the token's id.
+    pub async fn create_bot(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        bot_id: ObjectId,
+        content: String,
+        embeds: Vec<Embed>,
+    ) -> DaoResult<Message> {
+        self.create_automated(tenant_id, room_id, bot_id, AuthorType::Bot, content, embeds)
+            .await
+    }
+
+    async fn create_automated(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        author_id: ObjectId,
+        author_type: AuthorType,
+        content: String,
+        embeds: Vec<Embed>,
+    ) -> DaoResult<Message> {
+        let now = DateTime::now();
+        let message = Message {
+            id: None,
+            tenant_id,
+            room_id,
+            thread_id: None,
+            is_thread_root: false,
+            thread_metadata: None,
+            thread_summary: None,
+            author_id,
+            author_type,
+            content,
+            content_type: ContentType::Markdown,
+            message_type: MessageType::Default,
+            embeds,
+            attachments: Vec::new(),
+            mentions: Mentions::default(),
+            reaction_summary: Vec::new(),
+            referenced_message_id: None,
+            cross_post_group_id: None,
+            flagged_terms: Vec::new(),
+            is_pinned: false,
+            is_edited: false,
+            edited_at: None,
+            nonce: None,
+            readby: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+
+        let id = self.base.insert_one(&message).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// Top-level messages in a room, newest first. Once the hot collection
+    /// runs out, paging continues into the room's monthly archive
+    /// partitions (see [`crate::message_archive`]).
     pub async fn find_in_room(
         &self,
         room_id: ObjectId,
         params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<Message>> {
+        self.page_in_room(room_id, None, params).await
+    }
+
+    /// Like [`find_in_room`](Self::find_in_room), only the messages people
+    /// wrote (plain messages and replies), as a public channel shows them.
+    pub async fn find_public_in_room(
+        &self,
+        room_id: ObjectId,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<Message>> {
+        let visible = doc! { "message_type": { "$in": ["default", "reply", null] } };
+        self.page_in_room(room_id, Some(visible), params).await
+    }
+
+    /// Page top-level messages of a room matching `extra`, newest first,
+    /// into the archive partitions.
+    async fn page_in_room(
+        &self,
+        room_id: ObjectId,
+        extra: Option<Document>,
+        params: &PaginationParams,
     ) -> DaoResult<PaginatedResult<Message>> {
         let mut filter = doc! { "room_id": room_id, "deleted_at": null, "thread_id": null };
+        // A partition's stored count covers all its messages, so with
+        // `extra` each one is counted.
+        let filtered = extra.is_some();
+        filter.extend(extra.unwrap_or_default());
 
         // Support cursor-based pagination via `before` timestamp
-        if let Some(ref before) = params.before
-            && let Ok(dt) = bson::DateTime::parse_rfc3339_str(before)
-        {
+        let before = params
+            .before
+            .as_deref()
+            .and_then(|b| bson::DateTime::parse_rfc3339_str(b).ok());
+        if let Some(dt) = before {
             filter.insert("created_at", doc! { "$lt": dt });
         }
 
-        self.base
-            .find_paginated(filter, Some(doc! { "created_at": -1 }), params)
-            .await
+        let mut partition_filter = doc! { "room_id": room_id, "count": { "$gt": 0 } };
+        if let Some(dt) = before {
+            partition_filter.insert("oldest_at", doc! { "$lt": dt });
+        }
+        let partitions = self
+            .archive_partitions
+            .find_many(partition_filter, Some(doc! { "month": -1 }))
+            .await?;
+        if partitions.is_empty() {
+            return self
+                .base
+                .find_paginated(filter, Some(doc! { "created_at": -1 }), params)
+                .await;
+        }
+
+        // Segments newest to oldest: the hot collection, then each month.
+        let mut collections = vec![Message::COLLECTION.to_string()];
+        let mut counts = vec![
+            self.base
+                .collection()
+                .count_documents(filter.clone())
+                .await?,
+        ];
+        for partition in partitions {
+            let count = match before {
+                _ if filtered => {
+                    self.db
+                        .collection::<Message>(&partition.collection)
+                        .count_documents(filter.clone())
+                        .await?
+                }
+                // The cursor falls inside this month, so only part of it counts.
+                Some(dt) if partition.newest_at >= dt => {
+                    self.db
+                        .collection::<Message>(&partition.collection)
+                        .count_documents(filter.clone())
+                        .await?
+                }
+                _ => partition.count,
+            };
+            collections.push(partition.collection);
+            counts.push(count);
+        }
+
+        let per_page = params.clamped_per_page();
+        let skip = (params.page.max(1) - 1) * per_page;
+        let mut items = Vec::new();
+        for (idx, seg_skip, seg_limit) in crate::message_archive::plan_page(&counts, skip, per_page)
+        {
+            let mut cursor = self
+                .db
+                .collection::<Message>(&collections[idx])
+                .find(filter.clone())
+                .sort(doc! { "created_at": -1 })
+                .skip(seg_skip)
+                .limit(seg_limit as i64)
+                .await?;
+            use futures::TryStreamExt;
+            while let Some(message) = cursor.try_next().await? {
+                items.push(message);
+            }
+        }
+
+        let total: u64 = counts.iter().sum();
+        let total_pages = if per_page > 0 {
+            total.div_ceil(per_page)
+        } else {
+            0
+        };
+        Ok(PaginatedResult {
+            items,
+            total,
+            page: params.page,
+            per_page,
+            total_pages,
+        })
+    }
+
+    /// How many top-level messages `find_in_room` pages through, archive
+    /// partitions included.
+    pub async fn count_in_room(&self, room_id: ObjectId) -> DaoResult<u64> {
+        let hot = self
+            .base
+            .collection()
+            .count_documents(doc! { "room_id": room_id, "deleted_at": null, "thread_id": null })
+            .await?;
+        let archived: u64 = self
+            .archive_partitions
+            .find_many(doc! { "room_id": room_id, "count": { "$gt": 0 } }, None)
+            .await?
+            .iter()
+            .map(|p| p.count)
+            .sum();
+        Ok(hot + archived)
     }
 
     pub async fn find_thread_replies(
@@ -133,6 +435,133 @@ impl MessageDao {
             .await
     }
 
+    /// Every reply in a thread, oldest first (unpaginated, for summaries).
+    pub async fn find_all_thread_replies(&self, thread_id: ObjectId) -> DaoResult<Vec<Message>> {
+        self.base
+            .find_many(
+                doc! { "thread_id": thread_id, "deleted_at": null },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// A room's messages since `since`, thread replies included, oldest
+    /// first; at most `limit`, the newest kept.
+    pub async fn find_since(
+        &self,
+        room_id: ObjectId,
+        since: DateTime,
+        limit: i64,
+    ) -> DaoResult<Vec<Message>> {
+        use futures::TryStreamExt;
+        let mut messages: Vec<Message> = self
+            .base
+            .collection()
+            .find(doc! {
+                "room_id": room_id,
+                "created_at": { "$gte": since },
+                "deleted_at": null,
+            })
+            .sort(doc! { "created_at": -1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// Post a thread summary as a pinned system message inside the thread
+    /// and cache it on the root. Unlike a regular reply this doesn't bump
+    /// the thread's reply count.
+    pub async fn create_thread_summary(
+        &self,
+        root: &Message,
+        author_id: ObjectId,
+        content: String,
+        reply_count: u32,
+    ) -> DaoResult<Message> {
+        let root_id = root.id.ok_or(DaoError::NotFound)?;
+        let now = DateTime::now();
+        let message = Message {
+            id: None,
+            tenant_id: root.tenant_id,
+            room_id: root.room_id,
+            thread_id: Some(root_id),
+            is_thread_root: false,
+            thread_metadata: None,
+            thread_summary: None,
+            author_id,
+            author_type: AuthorType::System,
+            content,
+            content_type: ContentType::Markdown,
+            message_type: MessageType::Default,
+            embeds: Vec::new(),
+            attachments: Vec::new(),
+            mentions: Mentions::default(),
+            reaction_summary: Vec::new(),
+            referenced_message_id: None,
+            cross_post_group_id: None,
+            flagged_terms: Vec::new(),
+            is_pinned: true,
+            is_edited: false,
+            edited_at: None,
+            nonce: None,
+            readby: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+        let id = self.base.insert_one(&message).await?;
+        self.set_thread_summary(root_id, id, reply_count).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// Replace the content of an existing summary message and refresh the
+    /// cache entry on the root.
+    pub async fn update_thread_summary(
+        &self,
+        root_id: ObjectId,
+        summary_id: ObjectId,
+        content: String,
+        reply_count: u32,
+    ) -> DaoResult<Message> {
+        let now = DateTime::now();
+        let updated = self
+            .base
+            .update_one(
+                doc! { "_id": summary_id, "thread_id": root_id, "deleted_at": null },
+                doc! { "$set": { "content": content, "is_edited": true, "edited_at": now } },
+            )
+            .await?;
+        if !updated {
+            return Err(DaoError::NotFound);
+        }
+        self.set_thread_summary(root_id, summary_id, reply_count)
+            .await?;
+        self.base.find_by_id(summary_id).await
+    }
+
+    async fn set_thread_summary(
+        &self,
+        root_id: ObjectId,
+        message_id: ObjectId,
+        reply_count: u32,
+    ) -> DaoResult<bool> {
+        let summary = ThreadSummary {
+            message_id,
+            reply_count,
+            generated_at: DateTime::now(),
+        };
+        let summary = bson::to_bson(&summary)?;
+        self.base
+            .update_one(
+                doc! { "_id": root_id },
+                doc! { "$set": { "thread_summary": summary } },
+            )
+            .await
+    }
+
     pub async fn find_pinned(&self, room_id: ObjectId) -> DaoResult<Vec<Message>> {
         self.base
             .find_many(
@@ -142,12 +571,15 @@ impl MessageDao {
             .await
     }
 
+    /// Edit a message's content. `flagged_terms` replaces what the
+    /// profanity filter flagged in the old content.
     pub async fn update_content(
         &self,
         tenant_id: ObjectId,
         message_id: ObjectId,
         author_id: ObjectId,
         content: String,
+        flagged_terms: &[String],
     ) -> DaoResult<bool> {
         self.base
             .update_one(
@@ -160,6 +592,7 @@ impl MessageDao {
                 doc! {
                     "$set": {
                         "content": content,
+                        "flagged_terms": flagged_terms,
                         "is_edited": true,
                         "edited_at": DateTime::now(),
                     }
@@ -168,6 +601,75 @@ impl MessageDao {
             .await
     }
 
+    /// Record what the profanity filter flagged in freshly posted messages.
+    pub async fn flag(&self, message_ids: &[ObjectId], terms: &[String]) -> DaoResult<u64> {
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! { "_id": { "$in": message_ids } },
+                doc! { "$set": { "flagged_terms": terms } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    /// Edit every copy of a cross-posted message; only the author's copies
+    /// match. Returns how many changed.
+    pub async fn update_cross_post_content(
+        &self,
+        tenant_id: ObjectId,
+        group_id: ObjectId,
+        author_id: ObjectId,
+        content: String,
+        flagged_terms: &[String],
+    ) -> DaoResult<u64> {
+        let now = DateTime::now();
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! {
+                    "cross_post_group_id": group_id,
+                    "tenant_id": tenant_id,
+                    "author_id": author_id,
+                    "deleted_at": null,
+                },
+                doc! {
+                    "$set": {
+                        "content": content,
+                        "flagged_terms": flagged_terms,
+                        "is_edited": true,
+                        "edited_at": now,
+                        "updated_at": now,
+                    }
+                },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    /// Soft-delete every copy of a cross-posted message.
+    pub async fn soft_delete_cross_posts(
+        &self,
+        tenant_id: ObjectId,
+        group_id: ObjectId,
+    ) -> DaoResult<u64> {
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! {
+                    "cross_post_group_id": group_id,
+                    "tenant_id": tenant_id,
+                    "deleted_at": null,
+                },
+                doc! { "$set": { "deleted_at": DateTime::now() } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
     pub async fn toggle_pin(
         &self,
         tenant_id: ObjectId,
@@ -201,6 +703,7 @@ impl MessageDao {
                         "last_reply_at": null,
                         "last_reply_user_id": null,
                         "participant_ids": [],
+                        "activity_count": 0_i32,
                     },
                 }},
             )
@@ -217,6 +720,7 @@ impl MessageDao {
                     },
                     "$inc": {
                         "thread_metadata.reply_count": 1_i32,
+                        "thread_metadata.activity_count": 1_i32,
                     },
                     "$addToSet": {
                         "thread_metadata.participant_ids": reply_author_id,
@@ -226,6 +730,16 @@ impl MessageDao {
             .await
     }
 
+    /// Count an edit to a reply as thread activity.
+    pub async fn bump_thread_activity(&self, root_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": root_id, "thread_metadata": { "$ne": null } },
+                doc! { "$inc": { "thread_metadata.activity_count": 1_i32 } },
+            )
+            .await
+    }
+
     /// Mark messages in a room as read by a user
     pub async fn mark_read(
         &self,
@@ -248,55 +762,6 @@ impl MessageDao {
         Ok(result.modified_count)
     }
 
-    /// Count unread messages for a user in a room
-    pub async fn unread_count(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<u64> {
-        let count = self
-            .base
-            .collection()
-            .count_documents(doc! {
-                "room_id": room_id,
-                "deleted_at": null,
-                "thread_id": null,
-                "readby": { "$ne": user_id },
-            })
-            .await?;
-        Ok(count)
-    }
-
-    /// Count unread messages for a user across multiple rooms
-    pub async fn unread_counts_by_room(
-        &self,
-        room_ids: &[ObjectId],
-        user_id: ObjectId,
-    ) -> DaoResult<Vec<(ObjectId, u64)>> {
-        use bson::Bson;
-        use futures::TryStreamExt;
-
-        let pipeline = vec![
-            doc! { "$match": {
-                "room_id": { "$in": room_ids.iter().map(|id| Bson::ObjectId(*id)).collect::<Vec<_>>() },
-                "deleted_at": null,
-                "thread_id": null,
-                "readby": { "$ne": user_id },
-            }},
-            doc! { "$group": {
-                "_id": "$room_id",
-                "count": { "$sum": 1 },
-            }},
-        ];
-
-        let mut cursor = self.base.collection().aggregate(pipeline).await?;
-        let mut results = Vec::new();
-        while let Some(doc) = cursor.try_next().await? {
-            if let (Some(room_id), Some(count)) =
-                (doc.get_object_id("_id").ok(), doc.get_i32("count").ok())
-            {
-                results.push((room_id, count as u64));
-            }
-        }
-        Ok(results)
-    }
-
     pub async fn update_reaction_summary(
         &self,
         message_id: ObjectId,
@@ -1,17 +1,36 @@
 pub mod agent;
+pub mod analytics;
+pub mod asr_metric;
+pub mod audit_log;
 pub mod base;
+pub mod bot_token;
+pub mod conference_event;
+pub mod conference_poll;
+pub mod custom_emoji;
+pub mod feature_flag;
 pub mod file;
+pub mod follow_up;
 pub mod invite;
 pub mod message;
+pub mod moderation_event;
 pub mod notification;
+pub mod onboarding;
+pub mod preflight_report;
 pub mod push_subscription;
 pub mod reaction;
+pub mod reaction_rule;
+pub mod read_state;
 pub mod recording;
+pub mod relay_usage;
 pub mod remote_audit;
 pub mod remote_session;
 pub mod role;
 pub mod room;
+pub mod shared_draft;
 pub mod tenant;
+pub mod thread_subscription;
+pub mod transcript;
+pub mod webhook;
 
 pub mod activation_code;
 pub mod user;
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use bson::{Bson, DateTime, doc, oid::ObjectId};
+use chrono::{NaiveDate, NaiveTime};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{ModerationAction, ModerationEvent};
+
+use super::base::{BaseDao, DaoResult};
+use crate::analytics::{self, ModerationActivity};
+
+pub struct ModerationEventDao {
+    pub base: BaseDao<ModerationEvent>,
+}
+
+impl ModerationEventDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ModerationEvent::COLLECTION),
+        }
+    }
+
+    pub async fn record(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        message_id: Option<ObjectId>,
+        action: ModerationAction,
+        terms: Vec<String>,
+    ) -> DaoResult<ObjectId> {
+        self.base
+            .insert_one(&ModerationEvent {
+                id: None,
+                tenant_id,
+                room_id,
+                user_id,
+                message_id,
+                action,
+                terms,
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    /// One row per day from `from` to `to` inclusive, zeros for quiet days.
+    pub async fn daily_counts(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DaoResult<Vec<ModerationActivity>> {
+        let pipeline = vec![
+            doc! { "$match": { "tenant_id": tenant_id, "created_at": range(from, to) } },
+            doc! { "$group": {
+                "_id": {
+                    "date": { "$dateToString": { "format": "%Y-%m-%d", "date": "$created_at" } },
+                    "action": "$action",
+                },
+                "count": { "$sum": 1 },
+            }},
+        ];
+        let mut days: BTreeMap<String, ModerationActivity> = BTreeMap::new();
+        let mut cursor = self.base.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            let Ok(key) = row.get_document("_id") else {
+                continue;
+            };
+            let (Ok(date), Ok(action)) = (key.get_str("date"), key.get_str("action")) else {
+                continue;
+            };
+            let Ok(parsed) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            let count = as_u64(row.get("count"));
+            let day = days
+                .entry(date.to_string())
+                .or_insert_with(|| ModerationActivity::empty(parsed));
+            match action {
+                "masked" => day.masked += count,
+                "blocked" => day.blocked += count,
+                "flagged" => day.flagged += count,
+                _ => {}
+            }
+        }
+
+        Ok(analytics::days(from, to)
+            .map(|date| {
+                days.remove(&date.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| ModerationActivity::empty(date))
+            })
+            .collect())
+    }
+
+    /// The `limit` list entries matched most often in the range.
+    pub async fn top_terms(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+        limit: i64,
+    ) -> DaoResult<Vec<(String, u64)>> {
+        let pipeline = vec![
+            doc! { "$match": { "tenant_id": tenant_id, "created_at": range(from, to) } },
+            doc! { "$unwind": "$terms" },
+            doc! { "$group": { "_id": "$terms", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1, "_id": 1 } },
+            doc! { "$limit": limit },
+        ];
+        let mut terms = Vec::new();
+        let mut cursor = self.base.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            if let Ok(term) = row.get_str("_id") {
+                terms.push((term.to_string(), as_u64(row.get("count"))));
+            }
+        }
+        Ok(terms)
+    }
+
+    /// The tenant's latest flagged messages, newest first.
+    pub async fn recent_flagged(
+        &self,
+        tenant_id: ObjectId,
+        limit: i64,
+    ) -> DaoResult<Vec<ModerationEvent>> {
+        Ok(self
+            .base
+            .collection()
+            .find(doc! { "tenant_id": tenant_id, "action": "flagged" })
+            .sort(doc! { "created_at": -1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+/// `from` to `to` inclusive, as a `created_at` filter.
+fn range(from: NaiveDate, to: NaiveDate) -> bson::Document {
+    let start = DateTime::from_chrono(from.and_time(NaiveTime::MIN).and_utc());
+    let end = DateTime::from_chrono(
+        (to + chrono::Duration::days(1))
+            .and_time(NaiveTime::MIN)
+            .and_utc(),
+    );
+    doc! { "$gte": start, "$lt": end }
+}
+
+fn as_u64(value: Option<&Bson>) -> u64 {
+    match value {
+        Some(Bson::Int32(n)) => (*n).max(0) as u64,
+        Some(Bson::Int64(n)) => (*n).max(0) as u64,
+        _ => 0,
+    }
+}
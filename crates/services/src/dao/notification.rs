@@ -71,6 +71,36 @@ impl NotificationDao {
             .await
     }
 
+    /// A user's unread mentions in a tenant created since `since`, newest
+    /// first: up to `limit` of them and how many there are in all.
+    pub async fn unread_mentions_since(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+        since: DateTime,
+        limit: u64,
+    ) -> DaoResult<(Vec<Notification>, u64)> {
+        let page = self
+            .base
+            .find_paginated(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "user_id": user_id,
+                    "notification_type": "mention",
+                    "is_read": false,
+                    "created_at": { "$gte": since },
+                },
+                Some(doc! { "created_at": -1 }),
+                &PaginationParams {
+                    page: 1,
+                    per_page: limit,
+                    before: None,
+                },
+            )
+            .await?;
+        Ok((page.items, page.total))
+    }
+
     pub async fn unread_count(&self, user_id: ObjectId) -> DaoResult<u64> {
         self.base
             .collection()
@@ -0,0 +1,80 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{OnboardingProgress, OnboardingStep};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct OnboardingDao {
+    pub base: BaseDao<OnboardingProgress>,
+}
+
+impl OnboardingDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, OnboardingProgress::COLLECTION),
+        }
+    }
+
+    /// Progress row for the user, or an empty checklist if they have not
+    /// completed any step yet (rows are created lazily by [`Self::complete`]).
+    pub async fn get(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<OnboardingProgress> {
+        let existing = self
+            .base
+            .find_one(doc! { "tenant_id": tenant_id, "user_id": user_id })
+            .await?;
+        Ok(existing.unwrap_or_else(|| {
+            let now = DateTime::now();
+            OnboardingProgress {
+                id: None,
+                tenant_id,
+                user_id,
+                joined_channel_at: None,
+                sent_message_at: None,
+                started_call_at: None,
+                dismissed_at: None,
+                created_at: now,
+                updated_at: now,
+            }
+        }))
+    }
+
+    /// Record a step as done. Idempotent: the first completion time wins.
+    pub async fn complete(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+        step: OnboardingStep,
+    ) -> DaoResult<()> {
+        self.set_once(tenant_id, user_id, step.field()).await
+    }
+
+    pub async fn dismiss(&self, tenant_id: ObjectId, user_id: ObjectId) -> DaoResult<()> {
+        self.set_once(tenant_id, user_id, "dismissed_at").await
+    }
+
+    async fn set_once(&self, tenant_id: ObjectId, user_id: ObjectId, field: &str) -> DaoResult<()> {
+        let now = DateTime::now();
+        // Pipeline update so a single upsert both creates the row and keeps
+        // an existing timestamp (`$ifNull`) instead of racing find+insert.
+        let pipeline = vec![doc! {
+            "$set": {
+                field: { "$ifNull": [format!("${field}"), now] },
+                "created_at": { "$ifNull": ["$created_at", now] },
+                "updated_at": now,
+            }
+        }];
+        self.base
+            .collection()
+            .update_one(
+                doc! { "tenant_id": tenant_id, "user_id": user_id },
+                pipeline,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
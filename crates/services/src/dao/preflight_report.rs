@@ -0,0 +1,38 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::PreflightReport;
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+pub struct PreflightReportDao {
+    pub base: BaseDao<PreflightReport>,
+}
+
+impl PreflightReportDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, PreflightReport::COLLECTION),
+        }
+    }
+
+    pub async fn create(&self, report: &PreflightReport) -> DaoResult<PreflightReport> {
+        let id = self.base.insert_one(report).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// Newest first, optionally for a single user.
+    pub async fn find_for_tenant(
+        &self,
+        tenant_id: ObjectId,
+        user_id: Option<ObjectId>,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<PreflightReport>> {
+        let mut filter = doc! { "tenant_id": tenant_id };
+        if let Some(uid) = user_id {
+            filter.insert("user_id", uid);
+        }
+        self.base
+            .find_paginated(filter, Some(doc! { "created_at": -1 }), params)
+            .await
+    }
+}
@@ -1,6 +1,6 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::{EmojiRef, EmojiType, Reaction, ReactionSummary};
+use roomler_ai_db::models::{CustomEmoji, EmojiRef, Reaction, ReactionSummary};
 
 use super::base::{BaseDao, DaoError, DaoResult};
 use super::message::MessageDao;
@@ -22,7 +22,7 @@ impl ReactionDao {
         room_id: ObjectId,
         message_id: ObjectId,
         user_id: ObjectId,
-        emoji: String,
+        emoji: EmojiRef,
     ) -> DaoResult<Reaction> {
         // Check if already reacted with same emoji
         let existing = self
@@ -30,7 +30,7 @@ impl ReactionDao {
             .find_one(doc! {
                 "message_id": message_id,
                 "user_id": user_id,
-                "emoji.value": &emoji,
+                "emoji.value": &emoji.value,
             })
             .await?;
 
@@ -46,11 +46,7 @@ impl ReactionDao {
             room_id,
             message_id,
             user_id,
-            emoji: EmojiRef {
-                emoji_type: EmojiType::Unicode,
-                value: emoji,
-                custom_emoji_id: None,
-            },
+            emoji,
             created_at: DateTime::now(),
         };
 
@@ -75,12 +71,24 @@ impl ReactionDao {
         Ok(deleted > 0)
     }
 
+    /// Counts per emoji, most used first. Custom emoji carry their id and
+    /// current image.
     pub async fn get_summary(&self, message_id: ObjectId) -> DaoResult<Vec<ReactionSummary>> {
         use futures::TryStreamExt;
 
         let pipeline = vec![
             doc! { "$match": { "message_id": message_id } },
-            doc! { "$group": { "_id": "$emoji.value", "count": { "$sum": 1 } } },
+            doc! { "$group": {
+                "_id": "$emoji.value",
+                "count": { "$sum": 1 },
+                "custom_emoji_id": { "$first": "$emoji.custom_emoji_id" },
+            }},
+            doc! { "$lookup": {
+                "from": CustomEmoji::COLLECTION,
+                "localField": "custom_emoji_id",
+                "foreignField": "_id",
+                "as": "custom_emoji",
+            }},
             doc! { "$sort": { "count": -1 } },
         ];
 
@@ -95,7 +103,20 @@ impl ReactionDao {
         while let Some(doc) = cursor.try_next().await.map_err(DaoError::Mongo)? {
             let emoji = doc.get_str("_id").unwrap_or_default().to_string();
             let count = doc.get_i32("count").unwrap_or(0) as u32;
-            summaries.push(ReactionSummary { emoji, count });
+            let custom_emoji_id = doc.get_object_id("custom_emoji_id").ok();
+            let image_url = doc
+                .get_array("custom_emoji")
+                .ok()
+                .and_then(|found| found.first())
+                .and_then(|e| e.as_document())
+                .and_then(|e| e.get_str("image_url").ok())
+                .map(str::to_string);
+            summaries.push(ReactionSummary {
+                emoji,
+                count,
+                custom_emoji_id,
+                image_url,
+            });
         }
 
         Ok(summaries)
@@ -108,7 +129,7 @@ impl ReactionDao {
         room_id: ObjectId,
         message_id: ObjectId,
         user_id: ObjectId,
-        emoji: String,
+        emoji: EmojiRef,
     ) -> DaoResult<Reaction> {
         let reaction = self
             .add(tenant_id, room_id, message_id, user_id, emoji)
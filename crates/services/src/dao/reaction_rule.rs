@@ -0,0 +1,100 @@
+use bson::{Document, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{ReactionRule, ReactionTrigger};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct ReactionRuleDao {
+    pub base: BaseDao<ReactionRule>,
+}
+
+impl ReactionRuleDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ReactionRule::COLLECTION),
+        }
+    }
+
+    pub async fn create(&self, rule: &ReactionRule) -> DaoResult<ReactionRule> {
+        let id = self.base.insert_one(rule).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn find_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<ReactionRule>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Rules that watch `room_id`, every room, or post into `room_id`;
+    /// disabled ones included.
+    pub async fn find_for_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+    ) -> DaoResult<Vec<ReactionRule>> {
+        self.base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "$or": [
+                        { "room_id": { "$in": [null, room_id] } },
+                        { "action.room_id": room_id },
+                    ],
+                },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Enabled rules for `emoji` on `trigger` that apply in `room_id`.
+    pub async fn find_matching(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        emoji: &str,
+        trigger: ReactionTrigger,
+    ) -> DaoResult<Vec<ReactionRule>> {
+        self.base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "emoji": emoji,
+                    "trigger": bson::to_bson(&trigger)?,
+                    "enabled": true,
+                    "room_id": { "$in": [null, room_id] },
+                },
+                None,
+            )
+            .await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        set: Document,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": set },
+            )
+            .await
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    pub async fn count_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base.count(doc! { "tenant_id": tenant_id }).await
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use bson::{Bson, DateTime, Document, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{Message, RoomMember};
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+/// Per-member read markers. A member has read everything in a room up to
+/// and including `room_members.last_read_message_id`; message ids grow with
+/// creation time, so unread messages are the top-level ones with a greater
+/// id written by someone else. Members without a marker count from the
+/// moment they joined.
+pub struct ReadStateDao {
+    pub members: BaseDao<RoomMember>,
+    pub messages: BaseDao<Message>,
+}
+
+/// A page of a user's mentions inbox.
+pub struct MentionInbox {
+    /// Messages mentioning the user, newest first, each with whether it is
+    /// past the user's read marker in its room.
+    pub page: PaginatedResult<(Message, bool)>,
+    /// Unread mentions across all pages.
+    pub unread: u64,
+}
+
+impl ReadStateDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            members: BaseDao::new(db, RoomMember::COLLECTION),
+            messages: BaseDao::new(db, Message::COLLECTION),
+        }
+    }
+
+    /// Newest top-level message in a room, limited to `among` when given.
+    pub async fn newest_message_id(
+        &self,
+        room_id: ObjectId,
+        among: Option<&[ObjectId]>,
+    ) -> DaoResult<Option<ObjectId>> {
+        let mut filter = doc! { "room_id": room_id, "deleted_at": null, "thread_id": null };
+        if let Some(ids) = among {
+            filter.insert("_id", doc! { "$in": ids });
+        }
+        Ok(self
+            .messages
+            .collection()
+            .find_one(filter)
+            .sort(doc! { "_id": -1 })
+            .await?
+            .and_then(|m| m.id))
+    }
+
+    /// Moves the user's marker forward to `message_id`. Returns false when
+    /// they aren't a member or the marker is already there or further.
+    pub async fn mark_read_up_to(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        message_id: ObjectId,
+    ) -> DaoResult<bool> {
+        let now = DateTime::now();
+        let result = self
+            .members
+            .collection()
+            .update_one(
+                doc! {
+                    "room_id": room_id,
+                    "user_id": user_id,
+                    "$or": [
+                        { "last_read_message_id": null },
+                        { "last_read_message_id": { "$lt": message_id } },
+                    ],
+                },
+                doc! {
+                    "$set": {
+                        "last_read_message_id": message_id,
+                        "last_read_at": now,
+                        "updated_at": now,
+                    }
+                },
+            )
+            .await?;
+        Ok(result.modified_count > 0)
+    }
+
+    /// Unread messages for the user in each of `room_ids` they are a member
+    /// of. Rooms they haven't joined are left out of the map.
+    pub async fn unread_counts(
+        &self,
+        user_id: ObjectId,
+        room_ids: &[ObjectId],
+    ) -> DaoResult<HashMap<ObjectId, u64>> {
+        if room_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let memberships = self
+            .members
+            .find_many(
+                doc! { "user_id": user_id, "room_id": { "$in": room_ids } },
+                None,
+            )
+            .await?;
+        if memberships.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let since: Vec<Bson> = memberships
+            .iter()
+            .map(|m| {
+                let clause: Document = match m.last_read_message_id {
+                    Some(last) => doc! { "room_id": m.room_id, "_id": { "$gt": last } },
+                    None => doc! { "room_id": m.room_id, "created_at": { "$gt": m.joined_at } },
+                };
+                Bson::Document(clause)
+            })
+            .collect();
+        let pipeline = vec![
+            doc! { "$match": {
+                "$or": since,
+                "deleted_at": null,
+                "thread_id": null,
+                "author_id": { "$ne": user_id },
+            }},
+            doc! { "$group": { "_id": "$room_id", "count": { "$sum": 1 } } },
+        ];
+
+        let mut counts: HashMap<ObjectId, u64> =
+            memberships.iter().map(|m| (m.room_id, 0)).collect();
+        let mut cursor = self.messages.collection().aggregate(pipeline).await?;
+        while let Some(doc) = cursor.try_next().await? {
+            if let (Ok(room_id), Ok(count)) = (doc.get_object_id("_id"), doc.get_i32("count")) {
+                counts.insert(room_id, count as u64);
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Messages in the user's rooms of a tenant that mention them by name or
+    /// with `@channel`, leaving out their own. Thread replies count too, and
+    /// are unread while they are past the room's read marker.
+    pub async fn mentions(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+        params: &PaginationParams,
+    ) -> DaoResult<MentionInbox> {
+        let memberships = self
+            .members
+            .find_many(doc! { "tenant_id": tenant_id, "user_id": user_id }, None)
+            .await?;
+        let room_ids: Vec<ObjectId> = memberships.iter().map(|m| m.room_id).collect();
+        let filter = doc! {
+            "room_id": { "$in": &room_ids },
+            "deleted_at": null,
+            "author_id": { "$ne": user_id },
+            "$or": [{ "mentions.users": user_id }, { "mentions.everyone": true }],
+        };
+        let page = self
+            .messages
+            .find_paginated(filter.clone(), Some(doc! { "_id": -1 }), params)
+            .await?;
+        let markers: HashMap<ObjectId, &RoomMember> =
+            memberships.iter().map(|m| (m.room_id, m)).collect();
+        let is_unread = |message: &Message| match markers.get(&message.room_id) {
+            Some(m) => match (m.last_read_message_id, message.id) {
+                (Some(last), Some(id)) => id > last,
+                _ => message.created_at > m.joined_at,
+            },
+            None => false,
+        };
+        let since: Vec<Bson> = memberships
+            .iter()
+            .map(|m| {
+                let clause: Document = match m.last_read_message_id {
+                    Some(last) => doc! { "room_id": m.room_id, "_id": { "$gt": last } },
+                    None => doc! { "room_id": m.room_id, "created_at": { "$gt": m.joined_at } },
+                };
+                Bson::Document(clause)
+            })
+            .collect();
+        let unread = if since.is_empty() {
+            0
+        } else {
+            self.messages
+                .count(doc! { "$and": [filter, { "$or": since }] })
+                .await?
+        };
+
+        Ok(MentionInbox {
+            page: PaginatedResult {
+                items: page
+                    .items
+                    .into_iter()
+                    .map(|m| {
+                        let unread = is_unread(&m);
+                        (m, unread)
+                    })
+                    .collect(),
+                total: page.total,
+                page: page.page,
+                per_page: page.per_page,
+                total_pages: page.total_pages,
+            },
+            unread,
+        })
+    }
+
+    pub async fn unread_count(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<u64> {
+        Ok(self
+            .unread_counts(user_id, &[room_id])
+            .await?
+            .get(&room_id)
+            .copied()
+            .unwrap_or(0))
+    }
+}
@@ -2,7 +2,8 @@ use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::{self, recording::*};
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
+use crate::recording_access::{self, Viewer};
 
 pub struct RecordingDao {
     pub base: BaseDao<models::Recording>,
@@ -15,6 +16,7 @@ impl RecordingDao {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         tenant_id: ObjectId,
@@ -23,6 +25,8 @@ impl RecordingDao {
         storage_file: StorageFile,
         started_at: DateTime,
         ended_at: DateTime,
+        participant_ids: Vec<ObjectId>,
+        created_by: ObjectId,
     ) -> DaoResult<models::Recording> {
         let now = DateTime::now();
         let recording = models::Recording {
@@ -37,6 +41,15 @@ impl RecordingDao {
             visibility: Visibility::Private,
             allow_download: true,
             expires_at: None,
+            manifest: Some(SegmentManifest {
+                upload_id: uuid::Uuid::new_v4().to_string(),
+                parts: Vec::new(),
+            }),
+            acl: RecordingAcl {
+                participant_ids,
+                ..Default::default()
+            },
+            created_by: Some(created_by),
             created_at: now,
             updated_at: now,
             deleted_at: None,
@@ -60,6 +73,129 @@ impl RecordingDao {
             .await
     }
 
+    /// Like [`Self::find_by_room`], limited to what `viewer` may see. `None`
+    /// means the caller manages the room and sees everything.
+    pub async fn find_by_room_for_viewer(
+        &self,
+        room_id: ObjectId,
+        viewer: Option<&Viewer>,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<models::Recording>> {
+        let mut filter = doc! { "room_id": room_id, "deleted_at": null };
+        if let Some(viewer) = viewer {
+            filter.extend(recording_access::view_filter(viewer));
+        }
+        self.base
+            .find_paginated(filter, Some(doc! { "created_at": -1 }), params)
+            .await
+    }
+
+    /// Add users who joined the call to every recording still in progress
+    /// in the room.
+    pub async fn add_participants(
+        &self,
+        room_id: ObjectId,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<u64> {
+        if user_ids.is_empty() {
+            return Ok(0);
+        }
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! {
+                    "room_id": room_id,
+                    "status": bson::to_bson(&RecordingStatus::Processing).unwrap_or_default(),
+                    "deleted_at": null,
+                },
+                doc! { "$addToSet": { "acl.participant_ids": { "$each": user_ids } } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    /// Add users who were in the call to one recording.
+    pub async fn add_recording_participants(
+        &self,
+        recording_id: ObjectId,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<bool> {
+        if user_ids.is_empty() {
+            return Ok(false);
+        }
+        self.base
+            .update_by_id(
+                recording_id,
+                doc! { "$addToSet": { "acl.participant_ids": { "$each": user_ids } } },
+            )
+            .await
+    }
+
+    /// Replace the explicit grants and visibility. Participants and share
+    /// links are left alone.
+    pub async fn set_access(
+        &self,
+        id: ObjectId,
+        user_ids: &[ObjectId],
+        role_ids: &[ObjectId],
+        visibility: &Visibility,
+        allow_download: bool,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": {
+                    "acl.user_ids": user_ids,
+                    "acl.role_ids": role_ids,
+                    "visibility": bson::to_bson(visibility)?,
+                    "allow_download": allow_download,
+                } },
+            )
+            .await
+    }
+
+    pub async fn add_share_link(&self, id: ObjectId, link: &RecordingShareLink) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$push": { "acl.share_links": bson::to_bson(link)? } },
+            )
+            .await
+    }
+
+    pub async fn remove_share_link(&self, id: ObjectId, token: &str) -> DaoResult<bool> {
+        let result = self
+            .base
+            .collection()
+            .update_one(
+                doc! { "_id": id, "acl.share_links.token": token },
+                doc! { "$pull": { "acl.share_links": { "token": token } } },
+            )
+            .await?;
+        Ok(result.modified_count > 0)
+    }
+
+    /// The recording a share link points at, if the link exists and hasn't
+    /// expired.
+    pub async fn find_by_share_token(&self, token: &str) -> DaoResult<models::Recording> {
+        let recording = self
+            .base
+            .find_one(doc! { "acl.share_links.token": token, "deleted_at": null })
+            .await?
+            .ok_or(DaoError::NotFound)?;
+        let now = DateTime::now();
+        let live = recording
+            .acl
+            .share_links
+            .iter()
+            .any(|l| l.token == token && !l.is_expired(now));
+        if !live {
+            return Err(DaoError::NotFound);
+        }
+        Ok(recording)
+    }
+
     pub async fn update_status(&self, id: ObjectId, status: RecordingStatus) -> DaoResult<bool> {
         self.base
             .update_by_id(
@@ -69,6 +205,83 @@ impl RecordingDao {
             .await
     }
 
+    /// Recordings still being uploaded, optionally limited to one room.
+    pub async fn find_processing(
+        &self,
+        room_id: Option<ObjectId>,
+    ) -> DaoResult<Vec<models::Recording>> {
+        let mut filter = doc! {
+            "status": bson::to_bson(&RecordingStatus::Processing).unwrap_or_default(),
+            "deleted_at": null,
+        };
+        if let Some(room_id) = room_id {
+            filter.insert("room_id", room_id);
+        }
+        self.base.find_many(filter, None).await
+    }
+
+    /// Add (or replace) a part in the recording's segment manifest.
+    pub async fn record_part(&self, id: ObjectId, part: &RecordingPart) -> DaoResult<bool> {
+        let part_doc = bson::to_bson(part)?;
+        let pipeline = vec![doc! {
+            "$set": {
+                "manifest.parts": {
+                    "$concatArrays": [
+                        {
+                            "$filter": {
+                                "input": { "$ifNull": ["$manifest.parts", []] },
+                                "cond": { "$ne": ["$$this.part_number", part.part_number as i64] },
+                            }
+                        },
+                        [part_doc],
+                    ]
+                },
+                "updated_at": DateTime::now(),
+            }
+        }];
+        let result = self
+            .base
+            .collection()
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "status": bson::to_bson(&RecordingStatus::Processing).unwrap_or_default(),
+                    "manifest": { "$ne": null },
+                },
+                pipeline,
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Move a recording out of `Processing` once its parts have been
+    /// assembled. Returns `false` if another caller already finalized it.
+    pub async fn complete(
+        &self,
+        id: ObjectId,
+        status: RecordingStatus,
+        size: u64,
+        duration: u32,
+        ended_at: DateTime,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "status": bson::to_bson(&RecordingStatus::Processing).unwrap_or_default(),
+                },
+                doc! {
+                    "$set": {
+                        "status": bson::to_bson(&status).unwrap_or_default(),
+                        "file.size": size as i64,
+                        "file.duration": duration as i64,
+                        "ended_at": ended_at,
+                    }
+                },
+            )
+            .await
+    }
+
     pub async fn soft_delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
         self.base.soft_delete_in_tenant(tenant_id, id).await
     }
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use bson::{Bson, DateTime, doc, oid::ObjectId};
+use chrono::NaiveDate;
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::RelayUsage;
+
+use super::base::{BaseDao, DaoResult};
+use crate::analytics::{self, RelayActivity};
+use crate::relay_usage::RelayedBytes;
+
+/// TURN relay usage per conference and day; see [`crate::relay_usage`].
+pub struct RelayUsageDao {
+    pub base: BaseDao<RelayUsage>,
+}
+
+impl RelayUsageDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, RelayUsage::COLLECTION),
+        }
+    }
+
+    /// Add `bytes` to the conference's usage on `date`.
+    pub async fn record(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        date: NaiveDate,
+        bytes: RelayedBytes,
+    ) -> DaoResult<()> {
+        self.base
+            .collection()
+            .update_one(
+                doc! { "room_id": room_id, "date": date.format("%Y-%m-%d").to_string() },
+                doc! {
+                    "$inc": {
+                        "bytes_sent": bytes.sent as i64,
+                        "bytes_received": bytes.received as i64,
+                    },
+                    "$set": { "updated_at": DateTime::now() },
+                    "$setOnInsert": { "tenant_id": tenant_id },
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// One row per day from `from` to `to` inclusive, zeros for days
+    /// without relayed media.
+    pub async fn daily(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DaoResult<Vec<RelayActivity>> {
+        let pipeline = vec![
+            doc! { "$match": { "tenant_id": tenant_id, "date": range(from, to) } },
+            doc! { "$group": {
+                "_id": "$date",
+                "bytes_sent": { "$sum": "$bytes_sent" },
+                "bytes_received": { "$sum": "$bytes_received" },
+            }},
+        ];
+        let mut days: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+        let mut cursor = self.base.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            if let Ok(date) = row.get_str("_id") {
+                days.insert(
+                    date.to_string(),
+                    (
+                        as_u64(row.get("bytes_sent")),
+                        as_u64(row.get("bytes_received")),
+                    ),
+                );
+            }
+        }
+
+        Ok(analytics::days(from, to)
+            .map(
+                |date| match days.remove(&date.format("%Y-%m-%d").to_string()) {
+                    Some((bytes_sent, bytes_received)) => RelayActivity {
+                        date,
+                        bytes_sent,
+                        bytes_received,
+                    },
+                    None => RelayActivity::empty(date),
+                },
+            )
+            .collect())
+    }
+
+    /// The `limit` conferences that relayed the most in the range, with
+    /// what they relayed.
+    pub async fn top_rooms(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+        limit: i64,
+    ) -> DaoResult<Vec<(ObjectId, RelayedBytes)>> {
+        let pipeline = vec![
+            doc! { "$match": { "tenant_id": tenant_id, "date": range(from, to) } },
+            doc! { "$group": {
+                "_id": "$room_id",
+                "bytes_sent": { "$sum": "$bytes_sent" },
+                "bytes_received": { "$sum": "$bytes_received" },
+            }},
+            doc! { "$addFields": { "total": { "$add": ["$bytes_sent", "$bytes_received"] } } },
+            doc! { "$sort": { "total": -1, "_id": 1 } },
+            doc! { "$limit": limit },
+        ];
+        let mut rooms = Vec::new();
+        let mut cursor = self.base.collection().aggregate(pipeline).await?;
+        while let Some(row) = cursor.try_next().await? {
+            if let Ok(room_id) = row.get_object_id("_id") {
+                rooms.push((
+                    room_id,
+                    RelayedBytes {
+                        sent: as_u64(row.get("bytes_sent")),
+                        received: as_u64(row.get("bytes_received")),
+                    },
+                ));
+            }
+        }
+        Ok(rooms)
+    }
+
+    /// Bytes the tenant relayed from `from` to `to` inclusive.
+    pub async fn total(
+        &self,
+        tenant_id: ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DaoResult<u64> {
+        Ok(self
+            .daily(tenant_id, from, to)
+            .await?
+            .iter()
+            .map(|d| d.bytes_sent + d.bytes_received)
+            .sum())
+    }
+}
+
+/// `from` to `to` inclusive, as a `date` filter.
+fn range(from: NaiveDate, to: NaiveDate) -> bson::Document {
+    doc! {
+        "$gte": from.format("%Y-%m-%d").to_string(),
+        "$lte": to.format("%Y-%m-%d").to_string(),
+    }
+}
+
+fn as_u64(value: Option<&Bson>) -> u64 {
+    match value {
+        Some(Bson::Int32(n)) => (*n).max(0) as u64,
+        Some(Bson::Int64(n)) => (*n).max(0) as u64,
+        _ => 0,
+    }
+}
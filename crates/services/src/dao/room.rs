@@ -1,13 +1,27 @@
-use bson::{DateTime, doc, oid::ObjectId};
+use std::collections::HashMap;
+
+use bson::{Bson, DateTime, Document, doc, oid::ObjectId};
 use mongodb::Database;
 use rand::Rng;
 use roomler_ai_db::models::{
-    CallChatMessage, ConferenceSettings, MediaSettings, ParticipantRole, ParticipantSession, Room,
-    RoomMember,
+    CallChatMessage, ChannelDigest, ChannelRole, ConferenceSettings, MediaSettings,
+    MessageRetention, ParticipantRole, ParticipantSession, Presence, PublicShare, ReadOnlyWindow,
+    RetentionOverride, Room, RoomMember, RoomType, User, VoiceNote,
 };
 
 use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
 
+/// Narrows [`RoomDao::list_members`]; empty fields match everyone.
+#[derive(Debug, Clone, Default)]
+pub struct MemberFilter {
+    pub roles: Vec<ChannelRole>,
+    /// Presence as other users see it: hidden and invisible users are
+    /// offline.
+    pub presence: Option<Presence>,
+    /// Part of a username or display name, case-insensitive.
+    pub search: Option<String>,
+}
+
 pub struct RoomDao {
     pub base: BaseDao<Room>,
     pub members: BaseDao<RoomMember>,
@@ -61,6 +75,8 @@ impl RoomDao {
             parent_id,
             name,
             path,
+            room_type: RoomType::Channel,
+            dm_key: None,
             emoji: None,
             topic: None,
             purpose: None,
@@ -69,6 +85,7 @@ impl RoomDao {
             is_open,
             is_archived: false,
             is_read_only: false,
+            read_only_windows: Vec::new(),
             is_default: false,
             permission_overwrites: Vec::new(),
             tags: Vec::new(),
@@ -79,6 +96,11 @@ impl RoomDao {
             join_url,
             organizer_id: None,
             co_organizer_ids: Vec::new(),
+            keep_conference_chat: false,
+            retention_override: None,
+            message_retention: None,
+            public_share: None,
+            digest: None,
             creator_id,
             last_message_id: None,
             last_activity_at: None,
@@ -96,7 +118,8 @@ impl RoomDao {
         let room_id = self.base.insert_one(&room).await?;
 
         // Auto-join creator
-        self.join(tenant_id, room_id, creator_id).await?;
+        self.join_as(tenant_id, room_id, creator_id, ChannelRole::Owner)
+            .await?;
 
         self.base.find_by_id(room_id).await
     }
@@ -104,12 +127,38 @@ impl RoomDao {
     pub async fn find_by_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<Room>> {
         self.base
             .find_many(
-                doc! { "tenant_id": tenant_id, "deleted_at": null },
+                doc! { "tenant_id": tenant_id, "room_type": { "$ne": "dm" }, "deleted_at": null },
                 Some(doc! { "parent_id": 1, "position": 1 }),
             )
             .await
     }
 
+    /// Rooms flagged `is_default`, which every new tenant member auto-joins.
+    pub async fn find_default_rooms(&self, tenant_id: ObjectId) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "is_default": true, "deleted_at": null },
+                Some(doc! { "position": 1 }),
+            )
+            .await
+    }
+
+    pub async fn find_by_meeting_code(&self, meeting_code: &str) -> DaoResult<Room> {
+        self.base
+            .find_one(doc! { "meeting_code": meeting_code, "deleted_at": null })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    pub async fn set_default(&self, room_id: ObjectId, is_default: bool) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                room_id,
+                doc! { "$set": { "is_default": is_default, "updated_at": DateTime::now() } },
+            )
+            .await
+    }
+
     pub async fn find_user_rooms(
         &self,
         tenant_id: ObjectId,
@@ -128,12 +177,153 @@ impl RoomDao {
 
         self.base
             .find_many(
-                doc! { "_id": { "$in": room_ids }, "deleted_at": null },
+                doc! { "_id": { "$in": room_ids }, "room_type": { "$ne": "dm" }, "deleted_at": null },
                 Some(doc! { "parent_id": 1, "position": 1 }),
             )
             .await
     }
 
+    /// Live rooms with a scheduled conference the user is a member of,
+    /// across tenants, soonest first.
+    pub async fn find_scheduled_for_user(&self, user_id: ObjectId) -> DaoResult<Vec<Room>> {
+        let memberships = self
+            .members
+            .find_many(
+                doc! { "user_id": user_id, "is_external": { "$ne": true } },
+                None,
+            )
+            .await?;
+        let room_ids: Vec<ObjectId> = memberships.iter().map(|m| m.room_id).collect();
+        if room_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.base
+            .find_many(
+                doc! {
+                    "_id": { "$in": room_ids },
+                    "conference_settings.scheduled_start": { "$ne": null },
+                    "is_archived": { "$ne": true },
+                    "deleted_at": null,
+                },
+                Some(doc! { "conference_settings.scheduled_start": 1 }),
+            )
+            .await
+    }
+
+    // ── Direct messages ─────────────────────────────────────────
+
+    /// The direct message between exactly `participant_ids` (which must
+    /// include `creator_id`), created on first use. Returns the room and
+    /// whether it was just created.
+    pub async fn find_or_create_dm(
+        &self,
+        tenant_id: ObjectId,
+        creator_id: ObjectId,
+        participant_ids: &[ObjectId],
+        name: String,
+    ) -> DaoResult<(Room, bool)> {
+        let key = dm_key(tenant_id, participant_ids);
+        if let Some(room) = self.find_dm_by_key(&key).await? {
+            return Ok((room, false));
+        }
+
+        let id = ObjectId::new();
+        let now = DateTime::now();
+        let room = Room {
+            id: Some(id),
+            tenant_id,
+            parent_id: None,
+            name,
+            path: format!("dm-{}", id.to_hex()),
+            room_type: RoomType::Dm,
+            dm_key: Some(key.clone()),
+            emoji: None,
+            topic: None,
+            purpose: None,
+            icon: None,
+            position: 0,
+            is_open: false,
+            is_archived: false,
+            is_read_only: false,
+            read_only_windows: Vec::new(),
+            is_default: false,
+            permission_overwrites: Vec::new(),
+            tags: Vec::new(),
+            media_settings: None,
+            conference_settings: None,
+            conference_status: None,
+            meeting_code: None,
+            join_url: None,
+            organizer_id: None,
+            co_organizer_ids: Vec::new(),
+            keep_conference_chat: false,
+            retention_override: None,
+            message_retention: None,
+            public_share: None,
+            digest: None,
+            creator_id,
+            last_message_id: None,
+            last_activity_at: Some(now),
+            member_count: 0,
+            message_count: 0,
+            participant_count: 0,
+            peak_participant_count: 0,
+            actual_start_time: None,
+            actual_end_time: None,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+
+        match self.base.insert_one(&room).await {
+            Ok(_) => {}
+            // Lost a race with a concurrent request for the same set.
+            Err(DaoError::DuplicateKey(_)) => {
+                let room = self.find_dm_by_key(&key).await?.ok_or(DaoError::NotFound)?;
+                return Ok((room, false));
+            }
+            Err(e) => return Err(e),
+        }
+
+        // Participants are equals: nobody can rename, delete or moderate.
+        for user_id in participant_ids {
+            self.join_as(tenant_id, id, *user_id, ChannelRole::Member)
+                .await?;
+        }
+
+        Ok((self.base.find_by_id(id).await?, true))
+    }
+
+    async fn find_dm_by_key(&self, key: &str) -> DaoResult<Option<Room>> {
+        self.base
+            .find_one(doc! { "dm_key": key, "deleted_at": null })
+            .await
+    }
+
+    /// Direct messages `user_id` takes part in, most recently active first.
+    pub async fn find_user_dms(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Vec<Room>> {
+        let memberships = self
+            .members
+            .find_many(doc! { "tenant_id": tenant_id, "user_id": user_id }, None)
+            .await?;
+        let room_ids: Vec<ObjectId> = memberships.iter().map(|m| m.room_id).collect();
+        if room_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.base
+            .find_many(
+                doc! { "_id": { "$in": room_ids }, "room_type": "dm", "deleted_at": null },
+                Some(doc! { "last_activity_at": -1 }),
+            )
+            .await
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn update(
         &self,
@@ -179,6 +369,178 @@ impl RoomDao {
             .await
     }
 
+    pub async fn set_read_only_windows(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        windows: &[ReadOnlyWindow],
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "read_only_windows": bson::to_bson(windows)? } },
+            )
+            .await
+    }
+
+    pub async fn set_conference_settings(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        settings: &ConferenceSettings,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "conference_settings": bson::to_bson(settings)? } },
+            )
+            .await
+    }
+
+    pub async fn set_keep_conference_chat(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        keep: bool,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "keep_conference_chat": keep } },
+            )
+            .await
+    }
+
+    /// Set or, with `None`, clear a room's conference chat retention
+    /// override.
+    pub async fn set_retention_override(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        retention: Option<&RetentionOverride>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "retention_override": bson::to_bson(&retention)? } },
+            )
+            .await
+    }
+
+    /// Live rooms with a conference chat retention override.
+    pub async fn find_with_retention_override(&self) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(
+                doc! { "retention_override": { "$ne": null }, "deleted_at": null },
+                None,
+            )
+            .await
+    }
+
+    /// Set or, with `None`, clear the room's own message retention.
+    pub async fn set_message_retention(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        retention: Option<&MessageRetention>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "message_retention": bson::to_bson(&retention)? } },
+            )
+            .await
+    }
+
+    pub async fn set_public_share(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        share: Option<&PublicShare>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "public_share": bson::to_bson(&share)? } },
+            )
+            .await
+    }
+
+    pub async fn set_digest(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        digest: Option<&ChannelDigest>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "digest": bson::to_bson(&digest)? } },
+            )
+            .await
+    }
+
+    /// Live, unarchived rooms whose digest hour has come on `day` (UTC) and
+    /// that haven't had that day's digest yet, across tenants.
+    pub async fn find_due_digests(&self, day: &str, hour_utc: u32) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(
+                doc! {
+                    "digest.hour_utc": { "$lte": hour_utc },
+                    "digest.last_posted_on": { "$ne": day },
+                    "is_archived": { "$ne": true },
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await
+    }
+
+    /// Mark `day`'s digest as posted. Returns false if another instance got
+    /// there first.
+    pub async fn claim_digest(&self, room_id: ObjectId, day: &str) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "digest.last_posted_on": { "$ne": day } },
+                doc! { "$set": { "digest.last_posted_on": day } },
+            )
+            .await
+    }
+
+    /// The live channel shared publicly under `token`.
+    pub async fn find_by_public_token(&self, token: &str) -> DaoResult<Option<Room>> {
+        self.base
+            .find_one(doc! { "public_share.token": token, "deleted_at": null })
+            .await
+    }
+
+    /// Live rooms with their own message retention, across tenants.
+    pub async fn find_with_message_retention(&self) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(
+                doc! { "message_retention": { "$ne": null }, "deleted_at": null },
+                None,
+            )
+            .await
+    }
+
+    /// Every live room in the tenant, DMs included.
+    pub async fn find_live_in_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id, "deleted_at": null }, None)
+            .await
+    }
+
+    /// Live rooms in the tenant other than DMs, as counted against the
+    /// plan's channel limit.
+    pub async fn count_channels(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base
+            .count(
+                doc! { "tenant_id": tenant_id, "room_type": { "$ne": "dm" }, "deleted_at": null },
+            )
+            .await
+    }
+
     pub async fn soft_delete(&self, tenant_id: ObjectId, room_id: ObjectId) -> DaoResult<bool> {
         self.base.soft_delete_in_tenant(tenant_id, room_id).await
     }
@@ -230,16 +592,7 @@ impl RoomDao {
     }
 
     pub async fn explore(&self, tenant_id: ObjectId, query: &str) -> DaoResult<Vec<Room>> {
-        let escaped: String = query
-            .chars()
-            .flat_map(|c| {
-                if ".*+?^${}()|[]\\".contains(c) {
-                    vec!['\\', c]
-                } else {
-                    vec![c]
-                }
-            })
-            .collect();
+        let escaped = escape_regex(query);
 
         self.base
             .find_many(
@@ -296,6 +649,17 @@ impl RoomDao {
         tenant_id: ObjectId,
         room_id: ObjectId,
         user_id: ObjectId,
+    ) -> DaoResult<RoomMember> {
+        self.join_as(tenant_id, room_id, user_id, ChannelRole::Member)
+            .await
+    }
+
+    pub async fn join_as(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        channel_role: ChannelRole,
     ) -> DaoResult<RoomMember> {
         let now = DateTime::now();
         let member = RoomMember {
@@ -307,6 +671,7 @@ impl RoomDao {
             email: None,
             is_external: false,
             role: None,
+            channel_role,
             sessions: Vec::new(),
             joined_at: now,
             last_read_message_id: None,
@@ -333,6 +698,58 @@ impl RoomDao {
         self.members.find_by_id(id).await
     }
 
+    /// Join every default room of the tenant. Rooms the user already belongs
+    /// to are skipped, so this is safe to call on every membership change.
+    pub async fn join_default_rooms(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Vec<ObjectId>> {
+        let mut joined = Vec::new();
+        for room in self.find_default_rooms(tenant_id).await? {
+            let Some(room_id) = room.id else { continue };
+            match self.join(tenant_id, room_id, user_id).await {
+                Ok(_) => joined.push(room_id),
+                Err(DaoError::DuplicateKey(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(joined)
+    }
+
+    /// Join the channels among `room_ids` that still exist and aren't
+    /// archived; ones the user is already in are skipped.
+    pub async fn join_rooms(
+        &self,
+        tenant_id: ObjectId,
+        room_ids: &[ObjectId],
+        user_id: ObjectId,
+    ) -> DaoResult<Vec<ObjectId>> {
+        let rooms = self
+            .base
+            .find_many(
+                doc! {
+                    "_id": { "$in": room_ids },
+                    "tenant_id": tenant_id,
+                    "room_type": { "$ne": "dm" },
+                    "is_archived": { "$ne": true },
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await?;
+        let mut joined = Vec::new();
+        for room in rooms {
+            let Some(room_id) = room.id else { continue };
+            match self.join(tenant_id, room_id, user_id).await {
+                Ok(_) => joined.push(room_id),
+                Err(DaoError::DuplicateKey(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(joined)
+    }
+
     pub async fn leave(
         &self,
         tenant_id: ObjectId,
@@ -357,24 +774,127 @@ impl RoomDao {
         Ok(deleted > 0)
     }
 
+    /// Members of the room matching `filter`, oldest first. Filters on the
+    /// user's name or presence join `users`; the role filter alone doesn't.
     pub async fn list_members(
         &self,
         room_id: ObjectId,
+        filter: &MemberFilter,
         params: &PaginationParams,
     ) -> DaoResult<PaginatedResult<RoomMember>> {
-        self.members
-            .find_paginated(
-                doc! { "room_id": room_id },
-                Some(doc! { "joined_at": 1 }),
-                params,
+        use futures::TryStreamExt;
+
+        let Some(user_match) = user_match(filter) else {
+            return self
+                .members
+                .find_paginated(
+                    member_match(room_id, filter),
+                    Some(doc! { "joined_at": 1 }),
+                    params,
+                )
+                .await;
+        };
+
+        let per_page = params.clamped_per_page();
+        let skip = params.page.saturating_sub(1) * per_page;
+        let pipeline = vec![
+            doc! { "$match": member_match(room_id, filter) },
+            doc! { "$sort": { "joined_at": 1 } },
+            user_lookup(),
+            doc! { "$match": user_match },
+            doc! { "$project": { "user": 0 } },
+            doc! { "$facet": {
+                "total": [{ "$count": "n" }],
+                "items": [{ "$skip": skip as i64 }, { "$limit": per_page as i64 }],
+            }},
+        ];
+        let mut cursor = self.members.collection().aggregate(pipeline).await?;
+        let result = cursor.try_next().await?.unwrap_or_default();
+        let total = result
+            .get_array("total")
+            .ok()
+            .and_then(|t| t.first())
+            .and_then(Bson::as_document)
+            .map_or(0, count_of);
+        let items = result
+            .get_array("items")
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Bson::as_document)
+                    .filter_map(|d| bson::from_document::<RoomMember>(d.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(PaginatedResult {
+            items,
+            total,
+            page: params.page,
+            per_page,
+            total_pages: total.div_ceil(per_page.max(1)),
+        })
+    }
+
+    /// How many members of the room match `filter`.
+    pub async fn count_members(&self, room_id: ObjectId, filter: &MemberFilter) -> DaoResult<u64> {
+        use futures::TryStreamExt;
+
+        let Some(user_match) = user_match(filter) else {
+            return self.members.count(member_match(room_id, filter)).await;
+        };
+        let pipeline = vec![
+            doc! { "$match": member_match(room_id, filter) },
+            user_lookup(),
+            doc! { "$match": user_match },
+            doc! { "$count": "n" },
+        ];
+        let mut cursor = self.members.collection().aggregate(pipeline).await?;
+        Ok(cursor.try_next().await?.as_ref().map_or(0, count_of))
+    }
+
+    pub async fn is_member(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
+        let count = self
+            .members
+            .count(doc! { "room_id": room_id, "user_id": user_id })
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// The user's stored channel role, or `None` if they haven't joined.
+    pub async fn channel_role(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Option<ChannelRole>> {
+        Ok(self
+            .members
+            .find_one(doc! { "room_id": room_id, "user_id": user_id })
+            .await?
+            .map(|m| m.channel_role))
+    }
+
+    pub async fn set_channel_role(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        role: ChannelRole,
+    ) -> DaoResult<bool> {
+        let result = self
+            .members
+            .collection()
+            .update_one(
+                doc! { "room_id": room_id, "user_id": user_id },
+                doc! { "$set": { "channel_role": role.as_str(), "updated_at": DateTime::now() } },
             )
-            .await
+            .await?;
+        Ok(result.matched_count > 0)
     }
 
     pub async fn find_member_user_ids(&self, room_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
         use futures::TryStreamExt;
 
-        let filter = doc! { "room_id": room_id };
+        // Conference guests only ever hear about their call.
+        let filter = doc! { "room_id": room_id, "is_external": { "$ne": true } };
         let projection = doc! { "user_id": 1, "_id": 0 };
         let coll = self
             .members
@@ -421,6 +941,47 @@ impl RoomDao {
             .await
     }
 
+    /// Rooms whose conference is marked as running.
+    pub async fn find_in_progress_calls(&self) -> DaoResult<Vec<Room>> {
+        self.base
+            .find_many(
+                doc! { "conference_status": "in_progress", "deleted_at": null },
+                None,
+            )
+            .await
+    }
+
+    /// Close every open participant session in a room and zero its
+    /// `participant_count`. Used when the server lost the connections
+    /// backing those sessions. Returns the number of members touched.
+    pub async fn close_open_sessions(&self, room_id: ObjectId) -> DaoResult<u64> {
+        let now = DateTime::now();
+        let opts = mongodb::options::UpdateOptions::builder()
+            .array_filters(vec![doc! { "elem.left_at": null }])
+            .build();
+        let result = self
+            .members
+            .collection()
+            .update_many(
+                doc! { "room_id": room_id, "sessions.left_at": null },
+                doc! {
+                    "$set": {
+                        "sessions.$[elem].left_at": now,
+                        "updated_at": now,
+                    }
+                },
+            )
+            .with_options(opts)
+            .await
+            .map_err(DaoError::Mongo)?;
+
+        self.base
+            .update_by_id(room_id, doc! { "$set": { "participant_count": 0_i32 } })
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
     /// Join a call as a participant (add session, update media state on RoomMember).
     pub async fn join_participant(
         &self,
@@ -491,6 +1052,7 @@ impl RoomDao {
                             "display_name": &display_name,
                             "role": bson::to_bson(&ParticipantRole::Attendee).unwrap(),
                             "is_video_on": true,
+                            "is_hand_raised": false,
                         },
                     },
                 )
@@ -514,6 +1076,7 @@ impl RoomDao {
             email: None,
             is_external: false,
             role: Some(ParticipantRole::Attendee),
+            channel_role: ChannelRole::Member,
             sessions: vec![session],
             joined_at: now,
             last_read_message_id: None,
@@ -584,6 +1147,150 @@ impl RoomDao {
         Ok(true)
     }
 
+    // ── Conference guests ───────────────────────────────────────
+    //
+    // A guest is an external member of the room: `user_id` is an id minted
+    // for them, not a user. They never count towards `member_count`.
+
+    pub async fn add_guest(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        guest_id: ObjectId,
+        display_name: String,
+    ) -> DaoResult<RoomMember> {
+        let now = DateTime::now();
+        let member = RoomMember {
+            id: None,
+            tenant_id,
+            room_id,
+            user_id: Some(guest_id),
+            display_name: Some(display_name),
+            email: None,
+            is_external: true,
+            role: Some(ParticipantRole::Attendee),
+            channel_role: ChannelRole::Guest,
+            sessions: Vec::new(),
+            joined_at: now,
+            last_read_message_id: None,
+            last_read_at: None,
+            unread_count: 0,
+            mention_count: 0,
+            notification_override: None,
+            is_muted: false,
+            is_pinned: false,
+            is_video_on: true,
+            is_screen_sharing: false,
+            is_hand_raised: false,
+            total_duration: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.members.insert_one(&member).await?;
+        self.members.find_by_id(id).await
+    }
+
+    /// Open a call session for a guest who has none. Returns whether one
+    /// was opened.
+    pub async fn join_guest(&self, room_id: ObjectId, guest_id: ObjectId) -> DaoResult<bool> {
+        let now = DateTime::now();
+        let session = ParticipantSession {
+            joined_at: now,
+            left_at: None,
+            duration: None,
+            device_type: "web".to_string(),
+        };
+        let result = self
+            .members
+            .collection()
+            .update_one(
+                doc! {
+                    "room_id": room_id,
+                    "user_id": guest_id,
+                    "is_external": true,
+                    "sessions": { "$not": { "$elemMatch": { "left_at": null } } },
+                },
+                doc! {
+                    "$push": { "sessions": bson::to_bson(&session)? },
+                    "$set": { "updated_at": now },
+                },
+            )
+            .await
+            .map_err(DaoError::Mongo)?;
+        if result.modified_count == 0 {
+            return Ok(false);
+        }
+        self.base
+            .update_by_id(room_id, doc! { "$inc": { "participant_count": 1 } })
+            .await?;
+        Ok(true)
+    }
+
+    /// Close a guest's open call session. Returns whether one was open.
+    pub async fn leave_guest(&self, room_id: ObjectId, guest_id: ObjectId) -> DaoResult<bool> {
+        let now = DateTime::now();
+        let opts = mongodb::options::UpdateOptions::builder()
+            .array_filters(vec![doc! { "elem.left_at": null }])
+            .build();
+        let result = self
+            .members
+            .collection()
+            .update_one(
+                doc! {
+                    "room_id": room_id,
+                    "user_id": guest_id,
+                    "is_external": true,
+                    "sessions": { "$elemMatch": { "left_at": null } },
+                },
+                doc! {
+                    "$set": {
+                        "sessions.$[elem].left_at": now,
+                        "updated_at": now,
+                    }
+                },
+            )
+            .with_options(opts)
+            .await
+            .map_err(DaoError::Mongo)?;
+        if result.modified_count == 0 {
+            return Ok(false);
+        }
+        self.base
+            .collection()
+            .update_one(
+                doc! { "_id": room_id, "participant_count": { "$gt": 0 } },
+                doc! {
+                    "$inc": { "participant_count": -1 },
+                    "$set": { "updated_at": now },
+                },
+            )
+            .await
+            .map_err(DaoError::Mongo)?;
+        Ok(true)
+    }
+
+    /// Display names of the guests among `ids`.
+    pub async fn find_guest_names(
+        &self,
+        room_id: ObjectId,
+        ids: &[ObjectId],
+    ) -> DaoResult<HashMap<ObjectId, String>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let guests = self
+            .members
+            .find_many(
+                doc! { "room_id": room_id, "user_id": { "$in": ids }, "is_external": true },
+                None,
+            )
+            .await?;
+        Ok(guests
+            .into_iter()
+            .filter_map(|g| Some((g.user_id?, g.display_name?)))
+            .collect())
+    }
+
     pub async fn list_participants(&self, room_id: ObjectId) -> DaoResult<Vec<RoomMember>> {
         self.members
             .find_many(
@@ -593,6 +1300,32 @@ impl RoomDao {
             .await
     }
 
+    /// Members with an open call session.
+    pub async fn count_active_participants(&self, room_id: ObjectId) -> DaoResult<u64> {
+        self.members
+            .count(doc! {
+                "room_id": room_id,
+                "sessions": { "$elemMatch": { "left_at": null } },
+            })
+            .await
+    }
+
+    pub async fn is_active_participant(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<bool> {
+        let count = self
+            .members
+            .count(doc! {
+                "room_id": room_id,
+                "user_id": user_id,
+                "sessions": { "$elemMatch": { "left_at": null } },
+            })
+            .await?;
+        Ok(count > 0)
+    }
+
     pub async fn find_participant_user_ids(&self, room_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
         let participants = self
             .members
@@ -621,6 +1354,44 @@ impl RoomDao {
             .unwrap_or_else(|| user_id.to_hex()[..8].to_string()))
     }
 
+    pub async fn set_hand_raised(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        raised: bool,
+    ) -> DaoResult<bool> {
+        self.set_participant_flag(room_id, user_id, "is_hand_raised", raised)
+            .await
+    }
+
+    pub async fn set_participant_muted(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        muted: bool,
+    ) -> DaoResult<bool> {
+        self.set_participant_flag(room_id, user_id, "is_muted", muted)
+            .await
+    }
+
+    async fn set_participant_flag(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        field: &str,
+        value: bool,
+    ) -> DaoResult<bool> {
+        let result = self
+            .members
+            .collection()
+            .update_one(
+                doc! { "room_id": room_id, "user_id": user_id },
+                doc! { "$set": { field: value, "updated_at": DateTime::now() } },
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
     // ── Room list with call filter ──────────────────────────────
 
     pub async fn list_by_tenant(
@@ -646,6 +1417,7 @@ impl RoomDao {
         author_id: ObjectId,
         display_name: String,
         content: String,
+        voice_note: Option<VoiceNote>,
     ) -> DaoResult<CallChatMessage> {
         let msg = CallChatMessage {
             id: None,
@@ -654,6 +1426,7 @@ impl RoomDao {
             author_id,
             display_name,
             content,
+            voice_note,
             created_at: DateTime::now(),
         };
         let id = self.chat_messages.insert_one(&msg).await?;
@@ -673,6 +1446,128 @@ impl RoomDao {
             )
             .await
     }
+
+    /// Delete a room's conference chat written since `since` (the whole
+    /// history when `None`). Returns the number of messages removed.
+    pub async fn delete_chat_messages(
+        &self,
+        room_id: ObjectId,
+        since: Option<DateTime>,
+    ) -> DaoResult<u64> {
+        let mut filter = doc! { "room_id": room_id };
+        if let Some(since) = since {
+            filter.insert("created_at", doc! { "$gte": since });
+        }
+        self.chat_messages.hard_delete(filter).await
+    }
+
+    /// Rooms of a tenant with conference chat older than `before`.
+    pub async fn rooms_with_chat_before(
+        &self,
+        tenant_id: ObjectId,
+        before: DateTime,
+    ) -> DaoResult<Vec<ObjectId>> {
+        let ids = self
+            .chat_messages
+            .collection()
+            .distinct(
+                "room_id",
+                doc! { "tenant_id": tenant_id, "created_at": { "$lt": before } },
+            )
+            .await?;
+        Ok(ids.iter().filter_map(|id| id.as_object_id()).collect())
+    }
+
+    /// Delete a room's conference chat older than `before`.
+    pub async fn purge_chat_messages_before(
+        &self,
+        room_id: ObjectId,
+        before: DateTime,
+    ) -> DaoResult<u64> {
+        self.chat_messages
+            .hard_delete(doc! { "room_id": room_id, "created_at": { "$lt": before } })
+            .await
+    }
+}
+
+fn member_match(room_id: ObjectId, filter: &MemberFilter) -> Document {
+    let mut m = doc! { "room_id": room_id, "is_external": { "$ne": true } };
+    if !filter.roles.is_empty() {
+        let roles: Vec<&str> = filter.roles.iter().map(|r| r.as_str()).collect();
+        m.insert("channel_role", doc! { "$in": roles });
+    }
+    m
+}
+
+/// The member's user as `user`, with only the fields the filters read.
+fn user_lookup() -> Document {
+    doc! { "$lookup": {
+        "from": User::COLLECTION,
+        "localField": "user_id",
+        "foreignField": "_id",
+        "pipeline": [{ "$project": {
+            "username": 1,
+            "display_name": 1,
+            "presence": 1,
+            "privacy.hide_presence": 1,
+        }}],
+        "as": "user",
+    }}
+}
+
+/// The filters on the looked-up `user`, or `None` when `filter` has none.
+fn user_match(filter: &MemberFilter) -> Option<Document> {
+    let mut conditions = Vec::new();
+    if let Some(search) = filter.search.as_deref() {
+        let pattern = escape_regex(search);
+        conditions.push(doc! { "$or": [
+            { "user.username": { "$regex": &pattern, "$options": "i" } },
+            { "user.display_name": { "$regex": &pattern, "$options": "i" } },
+            { "user_id": null, "display_name": { "$regex": &pattern, "$options": "i" } },
+        ]});
+    }
+    match &filter.presence {
+        None => {}
+        Some(Presence::Offline | Presence::Invisible) => conditions.push(doc! { "$or": [
+            { "user.presence": { "$nin": ["online", "idle", "dnd"] } },
+            { "user.privacy.hide_presence": true },
+        ]}),
+        Some(presence) => conditions.push(doc! {
+            "user.presence": bson::to_bson(presence).unwrap_or_default(),
+            "user.privacy.hide_presence": { "$ne": true },
+        }),
+    }
+    (!conditions.is_empty()).then(|| doc! { "$and": conditions })
+}
+
+/// The `n` of a `$count` stage's output.
+fn count_of(doc: &Document) -> u64 {
+    match doc.get("n") {
+        Some(Bson::Int32(n)) => *n as u64,
+        Some(Bson::Int64(n)) => *n as u64,
+        _ => 0,
+    }
+}
+
+fn escape_regex(query: &str) -> String {
+    query
+        .chars()
+        .flat_map(|c| {
+            if ".*+?^${}()|[]\\".contains(c) {
+                vec!['\\', c]
+            } else {
+                vec![c]
+            }
+        })
+        .collect()
+}
+
+/// Order-independent key of a participant set within a tenant.
+pub fn dm_key(tenant_id: ObjectId, participant_ids: &[ObjectId]) -> String {
+    let mut ids: Vec<String> = participant_ids.iter().map(|id| id.to_hex()).collect();
+    ids.sort();
+    ids.dedup();
+    format!("{}:{}", tenant_id.to_hex(), ids.join(","))
 }
 
 fn generate_meeting_code() -> String {
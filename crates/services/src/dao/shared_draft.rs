@@ -0,0 +1,134 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::{Database, options::ReturnDocument};
+use roomler_ai_db::models::{DraftRevision, SharedDraft};
+
+use super::base::{BaseDao, DaoResult};
+use crate::shared_drafts::HISTORY_LIMIT;
+
+pub struct SharedDraftDao {
+    pub base: BaseDao<SharedDraft>,
+}
+
+impl SharedDraftDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, SharedDraft::COLLECTION),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        created_by: ObjectId,
+        content: String,
+    ) -> DaoResult<SharedDraft> {
+        let now = DateTime::now();
+        let draft = SharedDraft {
+            id: None,
+            tenant_id,
+            room_id,
+            created_by,
+            content,
+            version: 0,
+            history: Vec::new(),
+            published_message_id: None,
+            published_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&draft).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// A room's unpublished drafts, newest first.
+    pub async fn find_open_for_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+    ) -> DaoResult<Vec<SharedDraft>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "room_id": room_id, "published_at": null },
+                Some(doc! { "created_at": -1 }),
+            )
+            .await
+    }
+
+    /// Store the content after `revision`, if the draft is still open and
+    /// at `revision.version - 1`. False when another edit or publish got
+    /// there first.
+    pub async fn apply(
+        &self,
+        draft_id: ObjectId,
+        content: String,
+        revision: &DraftRevision,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! {
+                    "_id": draft_id,
+                    "version": revision.version as i64 - 1,
+                    "published_at": null,
+                },
+                doc! {
+                    "$set": { "content": content, "version": revision.version as i64 },
+                    "$push": { "history": {
+                        "$each": [bson::to_bson(revision)?],
+                        "$slice": -(HISTORY_LIMIT as i64),
+                    } },
+                },
+            )
+            .await
+    }
+
+    /// Close an open draft for publishing, returning it as closed: later
+    /// edits are refused. None if it was already published.
+    pub async fn claim_for_publish(
+        &self,
+        tenant_id: ObjectId,
+        draft_id: ObjectId,
+    ) -> DaoResult<Option<SharedDraft>> {
+        let now = DateTime::now();
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! { "_id": draft_id, "tenant_id": tenant_id, "published_at": null },
+                doc! { "$set": { "published_at": now, "updated_at": now } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    pub async fn set_published_message(
+        &self,
+        draft_id: ObjectId,
+        message_id: ObjectId,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": draft_id },
+                doc! { "$set": { "published_message_id": message_id } },
+            )
+            .await
+    }
+
+    /// Open a claimed draft again after its message couldn't be posted.
+    pub async fn reopen(&self, draft_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": draft_id, "published_message_id": null },
+                doc! { "$set": { "published_at": null } },
+            )
+            .await
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, draft_id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": draft_id, "tenant_id": tenant_id, "published_at": null })
+            .await?;
+        Ok(deleted > 0)
+    }
+}
@@ -1,6 +1,10 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::{Plan, Role, Tenant, TenantMember, TenantSettings, role::permissions};
+use roomler_ai_db::models::{
+    ConferenceChatRetention, DomainVerification, MediaConstraintOverrides, MediaConstraints,
+    MessageRetention, Plan, ProfanityFilter, Role, Tenant, TenantMember, TenantSettings, TenantSso,
+    VideoEffectOverrides, VirtualBackground, role::permissions,
+};
 
 use super::base::{BaseDao, DaoError, DaoResult};
 
@@ -24,6 +28,7 @@ impl TenantDao {
         name: String,
         slug: String,
         owner_id: ObjectId,
+        is_sandbox: bool,
     ) -> DaoResult<Tenant> {
         let now = DateTime::now();
         let tenant = Tenant {
@@ -38,7 +43,13 @@ impl TenantDao {
             settings: TenantSettings::default(),
             billing: None,
             integrations: None,
+            sso: None,
+            domain_verifications: Vec::new(),
             is_archived: false,
+            is_sandbox,
+            analytics_report_sent_for: None,
+            relay_budget_alerted_for: None,
+            exceeded_limits: Vec::new(),
             created_at: now,
             updated_at: now,
             deleted_at: None,
@@ -146,6 +157,295 @@ impl TenantDao {
         Ok(())
     }
 
+    /// Effective media constraints for a tenant: plan defaults with the
+    /// tenant's overrides applied.
+    pub async fn media_constraints(&self, tenant_id: ObjectId) -> DaoResult<MediaConstraints> {
+        Ok(self.base.find_by_id(tenant_id).await?.media_constraints())
+    }
+
+    pub async fn set_media_constraint_overrides(
+        &self,
+        tenant_id: ObjectId,
+        overrides: &MediaConstraintOverrides,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.media_constraints": bson::to_bson(overrides)? } },
+            )
+            .await
+    }
+
+    pub async fn set_video_effect_overrides(
+        &self,
+        tenant_id: ObjectId,
+        overrides: &VideoEffectOverrides,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.video_effects": bson::to_bson(overrides)? } },
+            )
+            .await
+    }
+
+    pub async fn add_virtual_background(
+        &self,
+        tenant_id: ObjectId,
+        background: &VirtualBackground,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$push": { "settings.virtual_backgrounds": bson::to_bson(background)? } },
+            )
+            .await
+    }
+
+    pub async fn remove_virtual_background(
+        &self,
+        tenant_id: ObjectId,
+        background_id: ObjectId,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$pull": { "settings.virtual_backgrounds": { "id": background_id } } },
+            )
+            .await
+    }
+
+    pub async fn set_conference_chat_retention(
+        &self,
+        tenant_id: ObjectId,
+        retention: &ConferenceChatRetention,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.conference_chat": bson::to_bson(retention)? } },
+            )
+            .await
+    }
+
+    /// Live tenants with a conference chat retention period.
+    pub async fn find_with_conference_chat_retention(&self) -> DaoResult<Vec<Tenant>> {
+        self.base
+            .find_many(
+                doc! {
+                    "settings.conference_chat.retention_days": { "$gt": 0 },
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await
+    }
+
+    pub async fn set_message_retention(
+        &self,
+        tenant_id: ObjectId,
+        retention: &MessageRetention,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.message_retention": bson::to_bson(retention)? } },
+            )
+            .await
+    }
+
+    /// Live tenants with a default message retention limit or on one of
+    /// `capped_plans`.
+    pub async fn find_with_message_retention(
+        &self,
+        capped_plans: &[Plan],
+    ) -> DaoResult<Vec<Tenant>> {
+        self.base
+            .find_many(
+                doc! {
+                    "$or": [
+                        { "settings.message_retention.max_age_days": { "$gt": 0 } },
+                        { "settings.message_retention.max_count": { "$gt": 0 } },
+                        { "plan": { "$in": bson::to_bson(capped_plans)? } },
+                    ],
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await
+    }
+
+    pub async fn set_profanity_filter(
+        &self,
+        tenant_id: ObjectId,
+        filter: &ProfanityFilter,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.profanity_filter": bson::to_bson(filter)? } },
+            )
+            .await
+    }
+
+    pub async fn set_monthly_analytics_report(
+        &self,
+        tenant_id: ObjectId,
+        enabled: bool,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.monthly_analytics_report": enabled } },
+            )
+            .await
+    }
+
+    pub async fn set_private_assets(&self, tenant_id: ObjectId, private: bool) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.private_assets": private } },
+            )
+            .await
+    }
+
+    pub async fn set_bot_api_daily_cap(
+        &self,
+        tenant_id: ObjectId,
+        cap: Option<u64>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.bot_api_daily_cap": cap.map(|c| c as i64) } },
+            )
+            .await
+    }
+
+    /// Fails with `DuplicateKey` when another tenant claimed one of the
+    /// domains first.
+    pub async fn set_sso(&self, tenant_id: ObjectId, sso: &TenantSso) -> DaoResult<bool> {
+        self.base
+            .update_by_id(tenant_id, doc! { "$set": { "sso": bson::to_bson(sso)? } })
+            .await
+    }
+
+    pub async fn clear_sso(&self, tenant_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_by_id(tenant_id, doc! { "$unset": { "sso": "" } })
+            .await
+    }
+
+    /// Start proving the tenant owns `verification.domain`. False if it
+    /// already is.
+    pub async fn add_domain_verification(
+        &self,
+        tenant_id: ObjectId,
+        verification: &DomainVerification,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! {
+                    "_id": tenant_id,
+                    "domain_verifications.domain": { "$ne": &verification.domain },
+                },
+                doc! { "$push": { "domain_verifications": bson::to_bson(verification)? } },
+            )
+            .await
+    }
+
+    pub async fn mark_domain_verified(&self, tenant_id: ObjectId, domain: &str) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": tenant_id, "domain_verifications.domain": domain },
+                doc! { "$set": { "domain_verifications.$.verified_at": DateTime::now() } },
+            )
+            .await
+    }
+
+    /// The tenant whose SSO covers any of `domains`, other than `except`.
+    pub async fn find_by_sso_domain(
+        &self,
+        domains: &[String],
+        except: Option<ObjectId>,
+    ) -> DaoResult<Option<Tenant>> {
+        let mut filter = doc! { "sso.domains": { "$in": domains }, "deleted_at": null };
+        if let Some(except) = except {
+            filter.insert("_id", doc! { "$ne": except });
+        }
+        self.base.find_one(filter).await
+    }
+
+    pub async fn count_members(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.members.count(doc! { "tenant_id": tenant_id }).await
+    }
+
+    /// Replace the exceeded limits if they are still `old`. Returns whether
+    /// they were, so a change is only reported once.
+    pub async fn swap_exceeded_limits(
+        &self,
+        tenant_id: ObjectId,
+        old: &[String],
+        new: &[String],
+    ) -> DaoResult<bool> {
+        let mut filter = doc! { "_id": tenant_id };
+        if old.is_empty() {
+            // Also matches tenants created before the field existed.
+            filter.insert("exceeded_limits", doc! { "$in": [null, []] });
+        } else {
+            filter.insert("exceeded_limits", old);
+        }
+        self.base
+            .update_one(filter, doc! { "$set": { "exceeded_limits": new } })
+            .await
+    }
+
+    /// Live tenants with the monthly analytics report on that haven't had
+    /// the one for `month` yet.
+    pub async fn find_due_analytics_reports(&self, month: &str) -> DaoResult<Vec<Tenant>> {
+        self.base
+            .find_many(
+                doc! {
+                    "settings.monthly_analytics_report": true,
+                    "analytics_report_sent_for": { "$ne": month },
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await
+    }
+
+    /// Mark `month`'s analytics report as sent. Returns false if another
+    /// instance got there first.
+    pub async fn claim_analytics_report(
+        &self,
+        tenant_id: ObjectId,
+        month: &str,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": tenant_id, "analytics_report_sent_for": { "$ne": month } },
+                doc! { "$set": { "analytics_report_sent_for": month } },
+            )
+            .await
+    }
+
+    /// Mark the tenant's admins as alerted about `month`'s relay usage.
+    /// Returns false if they already were.
+    pub async fn claim_relay_budget_alert(
+        &self,
+        tenant_id: ObjectId,
+        month: &str,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": tenant_id, "relay_budget_alerted_for": { "$ne": month } },
+                doc! { "$set": { "relay_budget_alerted_for": month } },
+            )
+            .await
+    }
+
     pub async fn get_role_by_name(&self, tenant_id: ObjectId, name: &str) -> DaoResult<Role> {
         self.roles
             .find_one(doc! { "tenant_id": tenant_id, "name": name })
@@ -181,6 +481,14 @@ impl TenantDao {
         self.members.find_by_id(id).await
     }
 
+    pub async fn remove_member(&self, tenant_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .members
+            .hard_delete(doc! { "tenant_id": tenant_id, "user_id": user_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
     pub async fn find_by_slug(&self, slug: &str) -> DaoResult<Tenant> {
         self.base
             .find_one(doc! { "slug": slug, "deleted_at": null })
@@ -208,6 +516,39 @@ impl TenantDao {
             .await
     }
 
+    pub async fn find_member_user_ids(&self, tenant_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
+        let members = self
+            .members
+            .find_many(doc! { "tenant_id": tenant_id }, None)
+            .await?;
+        Ok(members.into_iter().map(|m| m.user_id).collect())
+    }
+
+    /// Everyone sharing at least one tenant with `user_id`, the user included.
+    pub async fn find_co_member_user_ids(&self, user_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
+        let tenant_ids: Vec<ObjectId> = self
+            .members
+            .find_many(doc! { "user_id": user_id }, None)
+            .await?
+            .into_iter()
+            .map(|m| m.tenant_id)
+            .collect();
+        if tenant_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids: Vec<ObjectId> = self
+            .members
+            .find_many(doc! { "tenant_id": { "$in": tenant_ids } }, None)
+            .await?
+            .into_iter()
+            .map(|m| m.user_id)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
     pub async fn is_member(&self, tenant_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
         let count = self
             .members
@@ -216,6 +557,16 @@ impl TenantDao {
         Ok(count > 0)
     }
 
+    pub async fn find_member(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Option<TenantMember>> {
+        self.members
+            .find_one(doc! { "tenant_id": tenant_id, "user_id": user_id })
+            .await
+    }
+
     pub async fn assign_role(
         &self,
         tenant_id: ObjectId,
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use bson::{Bson, DateTime, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{Message, ThreadSubscription};
+
+use super::base::{BaseDao, DaoResult};
+
+/// Who follows which thread, and how far they've read it. Replies by
+/// others created after a subscriber's `last_read_at` are unread.
+pub struct ThreadSubscriptionDao {
+    pub base: BaseDao<ThreadSubscription>,
+    pub messages: BaseDao<Message>,
+}
+
+impl ThreadSubscriptionDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ThreadSubscription::COLLECTION),
+            messages: BaseDao::new(db, Message::COLLECTION),
+        }
+    }
+
+    /// Subscribe `user_id` unless they have a subscription already, even an
+    /// unfollowed one. Used for thread authors when the first reply comes in.
+    pub async fn subscribe_if_new(
+        &self,
+        root: &Message,
+        user_id: ObjectId,
+        last_read_at: DateTime,
+    ) -> DaoResult<()> {
+        let Some(thread_id) = root.id else {
+            return Ok(());
+        };
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .update_one(
+                doc! { "thread_id": thread_id, "user_id": user_id },
+                doc! { "$setOnInsert": {
+                    "tenant_id": root.tenant_id,
+                    "room_id": root.room_id,
+                    "subscribed": true,
+                    "last_read_at": last_read_at,
+                    "created_at": now,
+                    "updated_at": now,
+                } },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Follow a thread, again if they had unfollowed it. With `read`, the
+    /// thread is marked read too, as when the user just replied.
+    pub async fn subscribe(&self, root: &Message, user_id: ObjectId, read: bool) -> DaoResult<()> {
+        let Some(thread_id) = root.id else {
+            return Ok(());
+        };
+        let now = DateTime::now();
+        let mut set = doc! { "subscribed": true, "updated_at": now };
+        let mut set_on_insert = doc! {
+            "tenant_id": root.tenant_id,
+            "room_id": root.room_id,
+            "created_at": now,
+        };
+        if read {
+            set.insert("last_read_at", now);
+        } else {
+            set_on_insert.insert("last_read_at", now);
+        }
+        self.base
+            .collection()
+            .update_one(
+                doc! { "thread_id": thread_id, "user_id": user_id },
+                doc! { "$set": set, "$setOnInsert": set_on_insert },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Stop following a thread. Recorded even if they weren't following
+    /// it, so a later reply doesn't subscribe its author after all.
+    pub async fn unsubscribe(&self, root: &Message, user_id: ObjectId) -> DaoResult<()> {
+        let Some(thread_id) = root.id else {
+            return Ok(());
+        };
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .update_one(
+                doc! { "thread_id": thread_id, "user_id": user_id },
+                doc! {
+                    "$set": { "subscribed": false, "updated_at": now },
+                    "$setOnInsert": {
+                        "tenant_id": root.tenant_id,
+                        "room_id": root.room_id,
+                        "last_read_at": now,
+                        "created_at": now,
+                    },
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_read(&self, thread_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "thread_id": thread_id, "user_id": user_id },
+                doc! { "$set": { "last_read_at": DateTime::now() } },
+            )
+            .await
+    }
+
+    pub async fn find_subscriber_ids(&self, thread_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
+        Ok(self
+            .base
+            .find_many(doc! { "thread_id": thread_id, "subscribed": true }, None)
+            .await?
+            .into_iter()
+            .map(|s| s.user_id)
+            .collect())
+    }
+
+    /// Threads `user_id` follows in a tenant.
+    pub async fn find_for_user(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Vec<ThreadSubscription>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "user_id": user_id, "subscribed": true },
+                None,
+            )
+            .await
+    }
+
+    /// Unread replies per thread for the subscriber of `subscriptions`.
+    /// Threads without any are left out.
+    pub async fn unread_counts(
+        &self,
+        user_id: ObjectId,
+        subscriptions: &[ThreadSubscription],
+    ) -> DaoResult<HashMap<ObjectId, u64>> {
+        if subscriptions.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let since: Vec<Bson> = subscriptions
+            .iter()
+            .map(|s| {
+                Bson::Document(doc! {
+                    "thread_id": s.thread_id,
+                    "created_at": { "$gt": s.last_read_at },
+                })
+            })
+            .collect();
+        let pipeline = vec![
+            doc! { "$match": {
+                "$or": since,
+                "deleted_at": null,
+                "author_id": { "$ne": user_id },
+            }},
+            doc! { "$group": { "_id": "$thread_id", "count": { "$sum": 1 } } },
+        ];
+
+        let mut counts = HashMap::new();
+        let mut cursor = self.messages.collection().aggregate(pipeline).await?;
+        while let Some(doc) = cursor.try_next().await? {
+            if let (Ok(thread_id), Ok(count)) = (doc.get_object_id("_id"), doc.get_i32("count")) {
+                counts.insert(thread_id, count as u64);
+            }
+        }
+        Ok(counts)
+    }
+}
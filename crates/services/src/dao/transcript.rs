@@ -0,0 +1,98 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::TranscriptSegment;
+
+use super::base::{BaseDao, DaoResult};
+use crate::media::transcript_feed::TranscriptEvent;
+
+pub struct TranscriptDao {
+    pub base: BaseDao<TranscriptSegment>,
+}
+
+impl TranscriptDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, TranscriptSegment::COLLECTION),
+        }
+    }
+
+    pub async fn record(
+        &self,
+        tenant_id: ObjectId,
+        event: &TranscriptEvent,
+    ) -> DaoResult<ObjectId> {
+        self.base
+            .insert_one(&TranscriptSegment {
+                id: None,
+                tenant_id,
+                room_id: event.room_id,
+                track: event.track.clone(),
+                user_id: event.user_id,
+                speaker_name: event.speaker_name.clone(),
+                text: event.text.clone(),
+                language: event.language.clone(),
+                confidence: event.confidence,
+                start_time: event.start_time,
+                end_time: event.end_time,
+                created_at: DateTime::now(),
+            })
+            .await
+    }
+
+    /// Up to `limit` segments of one caption track, oldest first, written
+    /// at or after `since` and after the segment `after` when given.
+    pub async fn find_after(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        track: &str,
+        since: Option<DateTime>,
+        after: Option<ObjectId>,
+        limit: i64,
+    ) -> DaoResult<Vec<TranscriptSegment>> {
+        let mut filter = doc! { "tenant_id": tenant_id, "room_id": room_id, "track": track };
+        if let Some(since) = since {
+            filter.insert("created_at", doc! { "$gte": since });
+        }
+        if let Some(after) = after {
+            filter.insert("_id", doc! { "$gt": after });
+        }
+        Ok(self
+            .base
+            .collection()
+            .find(filter)
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    /// Up to `limit` segments of one caption track written between `from`
+    /// and `to`, oldest first.
+    pub async fn find_between(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        track: &str,
+        from: DateTime,
+        to: DateTime,
+        limit: i64,
+    ) -> DaoResult<Vec<TranscriptSegment>> {
+        Ok(self
+            .base
+            .collection()
+            .find(doc! {
+                "tenant_id": tenant_id,
+                "room_id": room_id,
+                "track": track,
+                "created_at": { "$gte": from, "$lte": to },
+            })
+            .sort(doc! { "created_at": 1, "_id": 1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
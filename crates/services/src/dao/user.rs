@@ -1,8 +1,11 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::{NotificationPrefs, OAuthProvider, Presence, User, UserStatusInfo};
+use roomler_ai_db::models::{
+    EmailPrefs, NotificationPrefs, OAuthProvider, Presence, PrivacyPrefs, User, UserStatusInfo,
+};
 
 use super::base::{BaseDao, DaoError, DaoResult};
+use crate::account_deletion::{DELETED_NAME, anonymized_email, anonymized_username};
 
 pub struct UserDao {
     pub base: BaseDao<User>,
@@ -40,9 +43,13 @@ impl UserDao {
             last_active_at: None,
             oauth_providers: Vec::new(),
             notification_preferences: NotificationPrefs::default(),
+            privacy: PrivacyPrefs::default(),
+            email_preferences: EmailPrefs::default(),
+            calendar_feed_token: None,
             created_at: now,
             updated_at: now,
             deleted_at: None,
+            purge_at: None,
         };
 
         let id = self.base.insert_one(&user).await?;
@@ -63,18 +70,14 @@ impl UserDao {
             .ok_or(DaoError::NotFound)
     }
 
+    /// Persist a presence change. Only coming online counts as activity;
+    /// decaying to idle or offline leaves `last_active_at` alone.
     pub async fn update_presence(&self, user_id: ObjectId, presence: Presence) -> DaoResult<bool> {
-        self.base
-            .update_by_id(
-                user_id,
-                doc! {
-                    "$set": {
-                        "presence": bson::to_bson(&presence)?,
-                        "last_active_at": DateTime::now(),
-                    }
-                },
-            )
-            .await
+        let mut set = doc! { "presence": bson::to_bson(&presence)? };
+        if presence == Presence::Online {
+            set.insert("last_active_at", DateTime::now());
+        }
+        self.base.update_by_id(user_id, doc! { "$set": set }).await
     }
 
     pub async fn find_or_create_by_oauth(
@@ -86,15 +89,7 @@ impl UserDao {
         avatar_url: Option<&str>,
     ) -> DaoResult<User> {
         // 1. Try to find user by OAuth provider + provider_id
-        if let Some(user) = self
-            .base
-            .find_one(doc! {
-                "oauth_providers.provider": provider,
-                "oauth_providers.provider_id": provider_id,
-                "deleted_at": null,
-            })
-            .await?
-        {
+        if let Some(user) = self.find_by_oauth(provider, provider_id).await? {
             return Ok(user);
         }
 
@@ -105,25 +100,68 @@ impl UserDao {
                 .iter()
                 .any(|p| p.provider == provider && p.provider_id == provider_id);
             if !already_linked {
-                let oauth = OAuthProvider {
-                    provider: provider.to_string(),
-                    provider_id: provider_id.to_string(),
-                    access_token: None,
-                    refresh_token: None,
-                };
-                let oauth_bson = bson::to_bson(&oauth)?;
-                self.base
-                    .update_by_id(
-                        user.id.unwrap(),
-                        doc! { "$push": { "oauth_providers": oauth_bson } },
-                    )
+                let oauth = self
+                    .link_oauth(user.id.unwrap(), provider, provider_id)
                     .await?;
                 user.oauth_providers.push(oauth);
             }
             return Ok(user);
         }
 
-        // 3. Create new user — generate unique username with retry on collision
+        // 3. Create new user
+        self.create_by_oauth(provider, provider_id, email, display_name, avatar_url)
+            .await
+    }
+
+    /// The live account signed in with `provider_id` at `provider`.
+    pub async fn find_by_oauth(
+        &self,
+        provider: &str,
+        provider_id: &str,
+    ) -> DaoResult<Option<User>> {
+        self.base
+            .find_one(doc! {
+                "oauth_providers": {
+                    "$elemMatch": { "provider": provider, "provider_id": provider_id },
+                },
+                "deleted_at": null,
+            })
+            .await
+    }
+
+    /// Let the account sign in with `provider_id` at `provider`.
+    pub async fn link_oauth(
+        &self,
+        user_id: ObjectId,
+        provider: &str,
+        provider_id: &str,
+    ) -> DaoResult<OAuthProvider> {
+        let oauth = OAuthProvider {
+            provider: provider.to_string(),
+            provider_id: provider_id.to_string(),
+            access_token: None,
+            refresh_token: None,
+        };
+        self.base
+            .update_by_id(
+                user_id,
+                doc! { "$push": { "oauth_providers": bson::to_bson(&oauth)? } },
+            )
+            .await?;
+        Ok(oauth)
+    }
+
+    /// A verified account signed in with `provider_id` at `provider`, without
+    /// looking for an existing one.
+    pub async fn create_by_oauth(
+        &self,
+        provider: &str,
+        provider_id: &str,
+        email: &str,
+        display_name: &str,
+        avatar_url: Option<&str>,
+    ) -> DaoResult<User> {
+        // Generate unique username with retry on collision
         let now = DateTime::now();
         let base_username: String = display_name
             .to_lowercase()
@@ -154,9 +192,13 @@ impl UserDao {
                 refresh_token: None,
             }],
             notification_preferences: NotificationPrefs::default(),
+            privacy: PrivacyPrefs::default(),
+            email_preferences: EmailPrefs::default(),
+            calendar_feed_token: None,
             created_at: now,
             updated_at: now,
             deleted_at: None,
+            purge_at: None,
         };
 
         // Retry up to 5 times with different suffixes on username collision
@@ -191,18 +233,20 @@ impl UserDao {
             .iter()
             .map(|id| bson::Bson::ObjectId(*id))
             .collect();
-        let filter = doc! {
-            "_id": { "$in": ids_bson },
-            "deleted_at": null,
-        };
+        let filter = doc! { "_id": { "$in": ids_bson } };
 
         // Use raw Document to avoid deserialization issues with projection
-        let projection = doc! { "_id": 1, "display_name": 1, "username": 1 };
+        let projection = doc! { "_id": 1, "display_name": 1, "username": 1, "deleted_at": 1 };
         let coll = self.base.collection().clone_with_type::<bson::Document>();
         let mut cursor = coll.find(filter).projection(projection).await?;
 
         while let Some(doc) = cursor.try_next().await? {
             if let Ok(id) = doc.get_object_id("_id") {
+                // Deleted accounts keep their messages, under a neutral name.
+                if doc.get_datetime("deleted_at").is_ok() {
+                    result.insert(id, DELETED_NAME.to_string());
+                    continue;
+                }
                 let display_name = doc.get_str("display_name").unwrap_or("").to_string();
                 let username = doc.get_str("username").unwrap_or("").to_string();
                 let name = if display_name.is_empty() {
@@ -218,6 +262,33 @@ impl UserDao {
         Ok(result)
     }
 
+    /// Those of `user_ids` whose username is one of `usernames`, compared
+    /// case-insensitively. Deleted accounts are left out.
+    pub async fn find_ids_by_usernames(
+        &self,
+        user_ids: &[ObjectId],
+        usernames: &[String],
+    ) -> DaoResult<Vec<ObjectId>> {
+        use futures::TryStreamExt;
+        if user_ids.is_empty() || usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+        let coll = self.base.collection().clone_with_type::<bson::Document>();
+        let mut cursor = coll
+            .find(doc! { "_id": { "$in": user_ids }, "deleted_at": null })
+            .projection(doc! { "_id": 1, "username": 1 })
+            .await?;
+        let mut ids = Vec::new();
+        while let Some(doc) = cursor.try_next().await? {
+            if let (Ok(id), Ok(username)) = (doc.get_object_id("_id"), doc.get_str("username"))
+                && usernames.iter().any(|u| u.eq_ignore_ascii_case(username))
+            {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
     pub async fn update_profile(
         &self,
         user_id: ObjectId,
@@ -254,4 +325,175 @@ impl UserDao {
             .update_by_id(user_id, doc! { "$set": update })
             .await
     }
+
+    pub async fn update_preferences(
+        &self,
+        user_id: ObjectId,
+        notifications: Option<&NotificationPrefs>,
+        privacy: Option<&PrivacyPrefs>,
+        email: Option<&EmailPrefs>,
+    ) -> DaoResult<bool> {
+        let mut update = doc! { "updated_at": DateTime::now() };
+        if let Some(n) = notifications {
+            update.insert("notification_preferences", bson::to_bson(n)?);
+        }
+        if let Some(p) = privacy {
+            update.insert("privacy", bson::to_bson(p)?);
+        }
+        if let Some(e) = email {
+            update.insert("email_preferences", bson::to_bson(e)?);
+        }
+        self.base
+            .update_by_id(user_id, doc! { "$set": update })
+            .await
+    }
+
+    /// Switch the user's sign-in email to `email`, which they proved to
+    /// own. Fails with a duplicate key when another account took it.
+    pub async fn change_email(&self, user_id: ObjectId, email: &str) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": user_id, "deleted_at": null },
+                doc! { "$set": { "email": email, "is_verified": true } },
+            )
+            .await
+    }
+
+    /// Turn the user's calendar feed on under `token`, or off with `None`.
+    pub async fn set_calendar_feed_token(
+        &self,
+        user_id: ObjectId,
+        token: Option<&str>,
+    ) -> DaoResult<bool> {
+        let update = match token {
+            Some(token) => doc! { "$set": { "calendar_feed_token": token } },
+            None => doc! { "$unset": { "calendar_feed_token": "" } },
+        };
+        self.base.update_by_id(user_id, update).await
+    }
+
+    /// The live account whose calendar feed is at `token`.
+    pub async fn find_by_calendar_feed_token(&self, token: &str) -> DaoResult<Option<User>> {
+        self.base
+            .find_one(doc! { "calendar_feed_token": token, "deleted_at": null })
+            .await
+    }
+
+    /// Disable an account: drop its sign-in credentials and schedule the
+    /// anonymization of its profile at `purge_at`. False if it was already
+    /// deleted.
+    pub async fn soft_delete_account(
+        &self,
+        user_id: ObjectId,
+        purge_at: DateTime,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": user_id, "deleted_at": null },
+                doc! {
+                    "$set": {
+                        "deleted_at": DateTime::now(),
+                        "purge_at": purge_at,
+                        "presence": bson::to_bson(&Presence::Offline)?,
+                        "oauth_providers": [],
+                    },
+                    "$unset": { "password_hash": "", "calendar_feed_token": "" },
+                },
+            )
+            .await
+    }
+
+    pub async fn is_deleted(&self, user_id: ObjectId) -> DaoResult<bool> {
+        let count = self
+            .base
+            .count(doc! { "_id": user_id, "deleted_at": { "$ne": null } })
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Deleted accounts whose grace period ended by `now`.
+    pub async fn find_due_purges(&self, now: DateTime) -> DaoResult<Vec<User>> {
+        self.base
+            .find_many(doc! { "purge_at": { "$lte": now } }, None)
+            .await
+    }
+
+    /// Replace a deleted account's name, email and profile with
+    /// placeholders. False if it was already anonymized, here or by
+    /// another instance.
+    pub async fn anonymize(&self, user_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": user_id, "purge_at": { "$ne": null } },
+                doc! {
+                    "$set": {
+                        "display_name": DELETED_NAME,
+                        "username": anonymized_username(user_id),
+                        "email": anonymized_email(user_id),
+                        "avatar": null,
+                        "bio": null,
+                        "status": bson::to_bson(&UserStatusInfo::default())?,
+                        "purge_at": null,
+                    },
+                },
+            )
+            .await
+    }
+
+    /// Batch-fetch stored presence. Unknown ids are left out.
+    pub async fn find_presence(
+        &self,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<std::collections::HashMap<ObjectId, Presence>> {
+        use futures::TryStreamExt;
+        let mut result = std::collections::HashMap::new();
+        if user_ids.is_empty() {
+            return Ok(result);
+        }
+
+        let coll = self.base.collection().clone_with_type::<bson::Document>();
+        let mut cursor = coll
+            .find(doc! { "_id": { "$in": user_ids } })
+            .projection(doc! { "_id": 1, "presence": 1 })
+            .await?;
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(id) = doc.get_object_id("_id") {
+                let presence = match doc.get("presence") {
+                    Some(p) => bson::from_bson(p.clone()).unwrap_or_default(),
+                    None => Presence::default(),
+                };
+                result.insert(id, presence);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Batch-fetch privacy settings. Users without any (or unknown ids)
+    /// map to the defaults, which hide nothing.
+    pub async fn find_privacy(
+        &self,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<std::collections::HashMap<ObjectId, PrivacyPrefs>> {
+        use futures::TryStreamExt;
+        let mut result = std::collections::HashMap::new();
+        if user_ids.is_empty() {
+            return Ok(result);
+        }
+
+        let coll = self.base.collection().clone_with_type::<bson::Document>();
+        let mut cursor = coll
+            .find(doc! { "_id": { "$in": user_ids } })
+            .projection(doc! { "_id": 1, "privacy": 1 })
+            .await?;
+        while let Some(doc) = cursor.try_next().await? {
+            if let Ok(id) = doc.get_object_id("_id") {
+                let privacy = match doc.get_document("privacy") {
+                    Ok(p) => bson::from_document(p.clone())?,
+                    Err(_) => PrivacyPrefs::default(),
+                };
+                result.insert(id, privacy);
+            }
+        }
+        Ok(result)
+    }
 }
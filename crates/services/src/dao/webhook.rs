@@ -0,0 +1,171 @@
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{DeliveryStatus, Webhook, WebhookDelivery};
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+pub struct WebhookDao {
+    pub base: BaseDao<Webhook>,
+    pub deliveries: BaseDao<WebhookDelivery>,
+}
+
+impl WebhookDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, Webhook::COLLECTION),
+            deliveries: BaseDao::new(db, WebhookDelivery::COLLECTION),
+        }
+    }
+
+    pub async fn create(&self, webhook: &Webhook) -> DaoResult<Webhook> {
+        let id = self.base.insert_one(webhook).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn find_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<Webhook>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Enabled webhooks of the tenant subscribed to `event`.
+    pub async fn find_subscribed(
+        &self,
+        tenant_id: ObjectId,
+        event: &str,
+    ) -> DaoResult<Vec<Webhook>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "events": event, "enabled": true },
+                None,
+            )
+            .await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        set: Document,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": set },
+            )
+            .await
+    }
+
+    /// Delete a webhook and its delivery log.
+    pub async fn delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id })
+            .await?;
+        if deleted > 0 {
+            self.deliveries
+                .hard_delete(doc! { "webhook_id": id })
+                .await?;
+        }
+        Ok(deleted > 0)
+    }
+
+    pub async fn count_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base.count(doc! { "tenant_id": tenant_id }).await
+    }
+
+    // ── Deliveries ──────────────────────────────────────────────
+
+    /// Log a pending delivery. It isn't picked up by the retry sweep until
+    /// `lease_until`, leaving the first attempt to the caller.
+    pub async fn create_delivery(
+        &self,
+        tenant_id: ObjectId,
+        webhook_id: ObjectId,
+        event: &str,
+        payload: String,
+        lease_until: DateTime,
+    ) -> DaoResult<WebhookDelivery> {
+        let now = DateTime::now();
+        let delivery = WebhookDelivery {
+            id: None,
+            tenant_id,
+            webhook_id,
+            event: event.to_string(),
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error: None,
+            next_attempt_at: Some(lease_until),
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.deliveries.insert_one(&delivery).await?;
+        self.deliveries.find_by_id(id).await
+    }
+
+    /// Claim the next pending delivery due by `now`, pushing its next
+    /// attempt to `lease_until` so no other instance sends it meanwhile.
+    pub async fn claim_due(
+        &self,
+        now: DateTime,
+        lease_until: DateTime,
+    ) -> DaoResult<Option<WebhookDelivery>> {
+        Ok(self
+            .deliveries
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "status": bson::to_bson(&DeliveryStatus::Pending)?,
+                    "next_attempt_at": { "$lte": now },
+                },
+                doc! { "$set": { "next_attempt_at": lease_until, "updated_at": now } },
+            )
+            .sort(doc! { "next_attempt_at": 1 })
+            .await?)
+    }
+
+    /// Store the outcome of an attempt.
+    pub async fn record_attempt(
+        &self,
+        id: ObjectId,
+        status: DeliveryStatus,
+        attempts: u32,
+        response_status: Option<u16>,
+        error: Option<String>,
+        next_attempt_at: Option<DateTime>,
+    ) -> DaoResult<bool> {
+        self.deliveries
+            .update_by_id(
+                id,
+                doc! { "$set": {
+                    "status": bson::to_bson(&status)?,
+                    "attempts": attempts,
+                    "response_status": response_status.map(i32::from),
+                    "error": error,
+                    "next_attempt_at": next_attempt_at,
+                } },
+            )
+            .await
+    }
+
+    /// A webhook's deliveries, newest first.
+    pub async fn find_deliveries(
+        &self,
+        tenant_id: ObjectId,
+        webhook_id: ObjectId,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<WebhookDelivery>> {
+        self.deliveries
+            .find_paginated(
+                doc! { "tenant_id": tenant_id, "webhook_id": webhook_id },
+                Some(doc! { "created_at": -1, "_id": -1 }),
+                params,
+            )
+            .await
+    }
+}
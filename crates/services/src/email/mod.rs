@@ -0,0 +1,155 @@
+//! Outgoing email. An [`EmailService`] sends through the configured
+//! [`EmailTransport`], SendGrid's API or an SMTP server, from the
+//! configured sender. The emails the app sends are [`EmailTemplate`]s,
+//! queued with retries by the API's email queue.
+
+pub mod sendgrid;
+pub mod smtp;
+pub mod templates;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use roomler_ai_config::{EmailProvider, EmailSettings};
+use tracing::{info, warn};
+
+use crate::outbound::Outbound;
+
+pub use sendgrid::SendGridTransport;
+pub use smtp::SmtpTransport;
+pub use templates::EmailTemplate;
+
+/// An email ready to send.
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+    /// Plain-text alternative to `html`.
+    pub text: Option<String>,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Who emails are sent from.
+#[derive(Debug, Clone)]
+pub struct Sender {
+    pub email: String,
+    pub name: String,
+}
+
+/// A provider that delivers email.
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn deliver(&self, from: &Sender, email: &OutgoingEmail) -> anyhow::Result<()>;
+}
+
+#[derive(Clone)]
+pub struct EmailService {
+    transport: Arc<dyn EmailTransport>,
+    from: Sender,
+}
+
+impl EmailService {
+    pub fn new(transport: Arc<dyn EmailTransport>, from_email: String, from_name: String) -> Self {
+        Self {
+            transport,
+            from: Sender {
+                email: from_email,
+                name: from_name,
+            },
+        }
+    }
+
+    /// The configured provider, or `None` when it has no API key or host.
+    pub fn from_settings(settings: &EmailSettings, outbound: &Outbound) -> Option<Self> {
+        let transport: Arc<dyn EmailTransport> = match settings.provider {
+            EmailProvider::Sendgrid if !settings.api_key.is_empty() => {
+                Arc::new(SendGridTransport::new(settings.api_key.clone(), outbound))
+            }
+            EmailProvider::Smtp if !settings.smtp_host.is_empty() => {
+                Arc::new(SmtpTransport::new(settings))
+            }
+            _ => return None,
+        };
+        Some(Self::new(
+            transport,
+            settings.from_email.clone(),
+            settings.from_name.clone(),
+        ))
+    }
+
+    pub async fn send(&self, to_email: &str, subject: &str, html_body: &str) -> anyhow::Result<()> {
+        self.deliver(OutgoingEmail {
+            to: to_email.to_string(),
+            subject: subject.to_string(),
+            html: html_body.to_string(),
+            text: None,
+            attachments: Vec::new(),
+        })
+        .await
+    }
+
+    /// Send an email with one file attached.
+    pub async fn send_with_attachment(
+        &self,
+        to_email: &str,
+        subject: &str,
+        html_body: &str,
+        file_name: &str,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> anyhow::Result<()> {
+        self.deliver(OutgoingEmail {
+            to: to_email.to_string(),
+            subject: subject.to_string(),
+            html: html_body.to_string(),
+            text: None,
+            attachments: vec![Attachment {
+                file_name: file_name.to_string(),
+                content_type: content_type.to_string(),
+                bytes: bytes.to_vec(),
+            }],
+        })
+        .await
+    }
+
+    /// Render `template` and send it to `to_email`.
+    pub async fn send_template(
+        &self,
+        to_email: &str,
+        template: &EmailTemplate,
+    ) -> anyhow::Result<()> {
+        let rendered = template.render();
+        self.deliver(OutgoingEmail {
+            to: to_email.to_string(),
+            subject: rendered.subject,
+            html: rendered.html,
+            text: Some(rendered.text),
+            attachments: rendered.attachments,
+        })
+        .await
+    }
+
+    async fn deliver(&self, email: OutgoingEmail) -> anyhow::Result<()> {
+        let provider = self.transport.name();
+        match self.transport.deliver(&self.from, &email).await {
+            Ok(()) => {
+                info!(to = %email.to, subject = %email.subject, provider, "Email sent");
+                Ok(())
+            }
+            Err(e) => {
+                warn!(to = %email.to, subject = %email.subject, provider, %e, "Email failed");
+                Err(e)
+            }
+        }
+    }
+}
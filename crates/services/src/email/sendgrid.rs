@@ -0,0 +1,121 @@
+//! Delivery through SendGrid's v3 mail API.
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Serialize;
+
+use super::{EmailTransport, OutgoingEmail, Sender};
+use crate::outbound::{Outbound, OutboundClient};
+
+pub struct SendGridTransport {
+    client: OutboundClient,
+    api_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridRequest {
+    personalizations: Vec<Personalization>,
+    from: EmailAddress,
+    subject: String,
+    content: Vec<Content>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Serialize)]
+struct Personalization {
+    to: Vec<EmailAddress>,
+}
+
+#[derive(Debug, Serialize)]
+struct EmailAddress {
+    email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Content {
+    #[serde(rename = "type")]
+    content_type: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Attachment {
+    /// Base64-encoded file contents.
+    content: String,
+    #[serde(rename = "type")]
+    content_type: String,
+    filename: String,
+}
+
+impl SendGridTransport {
+    pub fn new(api_key: String, outbound: &Outbound) -> Self {
+        Self {
+            client: outbound.client("sendgrid"),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SendGridTransport {
+    fn name(&self) -> &'static str {
+        "sendgrid"
+    }
+
+    async fn deliver(&self, from: &Sender, email: &OutgoingEmail) -> anyhow::Result<()> {
+        // SendGrid wants the plain-text part first.
+        let mut content = Vec::new();
+        if let Some(text) = &email.text {
+            content.push(Content {
+                content_type: "text/plain".to_string(),
+                value: text.clone(),
+            });
+        }
+        content.push(Content {
+            content_type: "text/html".to_string(),
+            value: email.html.clone(),
+        });
+        let request = SendGridRequest {
+            personalizations: vec![Personalization {
+                to: vec![EmailAddress {
+                    email: email.to.clone(),
+                    name: None,
+                }],
+            }],
+            from: EmailAddress {
+                email: from.email.clone(),
+                name: Some(from.name.clone()),
+            },
+            subject: email.subject.clone(),
+            content,
+            attachments: email
+                .attachments
+                .iter()
+                .map(|a| Attachment {
+                    content: base64::engine::general_purpose::STANDARD.encode(&a.bytes),
+                    content_type: a.content_type.clone(),
+                    filename: a.file_name.clone(),
+                })
+                .collect(),
+        };
+
+        let resp = self
+            .client
+            .post("https://api.sendgrid.com/v3/mail/send")
+            .bearer_auth(&self.api_key)
+            .json(&request)
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("SendGrid error {}: {}", status, body)
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Delivery to an SMTP server: `STARTTLS` or TLS from the start through
+//! the system's TLS library, `AUTH PLAIN` when a username is set, and a
+//! MIME message with base64-encoded parts.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use roomler_ai_config::{EmailSettings, SmtpTls};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_native_tls::{TlsConnector, native_tls};
+
+use super::{EmailTransport, OutgoingEmail, Sender};
+
+/// Longest one delivery may take, connecting included.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+    tls: SmtpTls,
+    credentials: Option<(String, String)>,
+}
+
+impl SmtpTransport {
+    pub fn new(settings: &EmailSettings) -> Self {
+        Self {
+            host: settings.smtp_host.clone(),
+            port: settings.smtp_port,
+            tls: settings.smtp_tls,
+            credentials: (!settings.smtp_username.is_empty()).then(|| {
+                (
+                    settings.smtp_username.clone(),
+                    settings.smtp_password.clone(),
+                )
+            }),
+        }
+    }
+
+    async fn send(&self, from: &Sender, email: &OutgoingEmail) -> anyhow::Result<()> {
+        let domain = from.email.rsplit('@').next().unwrap_or("localhost");
+        let message = message(
+            from,
+            email,
+            &format!("<{}@{}>", uuid::Uuid::new_v4().simple(), domain),
+            &chrono::Utc::now().to_rfc2822(),
+        );
+        let tcp = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        match self.tls {
+            SmtpTls::Tls => {
+                let stream = self.handshake(tcp).await?;
+                let mut conn = Connection::new(stream);
+                conn.expect(220).await?;
+                conn.command(&format!("EHLO {domain}"), 250).await?;
+                self.transaction(&mut conn, from, &email.to, &message).await
+            }
+            SmtpTls::Starttls => {
+                let mut conn = Connection::new(tcp);
+                conn.expect(220).await?;
+                conn.command(&format!("EHLO {domain}"), 250).await?;
+                conn.command("STARTTLS", 220).await?;
+                let stream = self.handshake(conn.into_inner()).await?;
+                let mut conn = Connection::new(stream);
+                conn.command(&format!("EHLO {domain}"), 250).await?;
+                self.transaction(&mut conn, from, &email.to, &message).await
+            }
+            SmtpTls::None => {
+                let mut conn = Connection::new(tcp);
+                conn.expect(220).await?;
+                conn.command(&format!("EHLO {domain}"), 250).await?;
+                self.transaction(&mut conn, from, &email.to, &message).await
+            }
+        }
+    }
+
+    async fn handshake(
+        &self,
+        tcp: TcpStream,
+    ) -> anyhow::Result<tokio_native_tls::TlsStream<TcpStream>> {
+        let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
+        Ok(connector.connect(&self.host, tcp).await?)
+    }
+
+    /// Authenticate if configured, then send `message` to `to`.
+    async fn transaction<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        conn: &mut Connection<S>,
+        from: &Sender,
+        to: &str,
+        message: &str,
+    ) -> anyhow::Result<()> {
+        if let Some((username, password)) = &self.credentials {
+            let token = BASE64.encode(format!("\0{username}\0{password}"));
+            conn.command(&format!("AUTH PLAIN {token}"), 235).await?;
+        }
+        conn.command(&format!("MAIL FROM:<{}>", clean(&from.email)), 250)
+            .await?;
+        conn.command(&format!("RCPT TO:<{}>", clean(to)), 250)
+            .await?;
+        conn.command("DATA", 354).await?;
+        conn.command(&format!("{}.", dot_stuff(message)), 250)
+            .await?;
+        // The message is accepted; a failed goodbye changes nothing.
+        let _ = conn.command("QUIT", 221).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn deliver(&self, from: &Sender, email: &OutgoingEmail) -> anyhow::Result<()> {
+        tokio::time::timeout(TIMEOUT, self.send(from, email))
+            .await
+            .map_err(|_| anyhow::anyhow!("SMTP delivery timed out"))?
+    }
+}
+
+/// One SMTP session: commands out, replies in.
+struct Connection<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    /// Read a reply, joining the lines of a multiline one.
+    async fn reply(&mut self) -> anyhow::Result<(u16, String)> {
+        let mut text = Vec::new();
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                anyhow::bail!("SMTP server closed the connection");
+            }
+            let line = line.trim_end();
+            let code = line
+                .get(..3)
+                .and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| anyhow::anyhow!("Malformed SMTP reply: {line}"))?;
+            text.push(line.get(4..).unwrap_or_default().to_string());
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok((code, text.join(" ")));
+            }
+        }
+    }
+
+    /// Read a reply and fail unless it is `code`.
+    async fn expect(&mut self, code: u16) -> anyhow::Result<()> {
+        let (got, text) = self.reply().await?;
+        // 251: the server forwards the mail itself.
+        if got != code && !(code == 250 && got == 251) {
+            anyhow::bail!("SMTP error {got}: {text}");
+        }
+        Ok(())
+    }
+
+    async fn command(&mut self, line: &str, code: u16) -> anyhow::Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(line.as_bytes()).await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+        self.expect(code).await
+    }
+}
+
+/// The message as sent after `DATA`, with CRLF line endings.
+fn message(from: &Sender, email: &OutgoingEmail, message_id: &str, date: &str) -> String {
+    let alternative = match &email.text {
+        Some(text) => multipart(
+            "alternative",
+            &[
+                leaf("text/plain; charset=utf-8", text.as_bytes(), ""),
+                leaf("text/html; charset=utf-8", email.html.as_bytes(), ""),
+            ],
+        ),
+        None => leaf("text/html; charset=utf-8", email.html.as_bytes(), ""),
+    };
+    let body = if email.attachments.is_empty() {
+        alternative
+    } else {
+        let mut parts = vec![alternative];
+        for attachment in &email.attachments {
+            let name = encode_header(&attachment.file_name.replace(['"', '\\'], ""));
+            parts.push(leaf(
+                &format!("{}; name=\"{}\"", clean(&attachment.content_type), name),
+                &attachment.bytes,
+                &format!("Content-Disposition: attachment; filename=\"{name}\"\r\n"),
+            ));
+        }
+        multipart("mixed", &parts)
+    };
+    format!(
+        "From: {} <{}>\r\nTo: <{}>\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: {}\r\nMIME-Version: 1.0\r\n{}",
+        display_name(&from.name),
+        clean(&from.email),
+        clean(&email.to),
+        encode_header(&email.subject),
+        date,
+        message_id,
+        body,
+    )
+}
+
+/// A body part: its headers, a blank line and its base64-encoded content.
+fn leaf(content_type: &str, content: &[u8], extra_headers: &str) -> String {
+    let encoded = BASE64.encode(content);
+    let mut part = format!(
+        "Content-Type: {content_type}\r\nContent-Transfer-Encoding: base64\r\n{extra_headers}\r\n"
+    );
+    for line in encoded.as_bytes().chunks(76) {
+        part.push_str(std::str::from_utf8(line).unwrap_or_default());
+        part.push_str("\r\n");
+    }
+    part
+}
+
+fn multipart(kind: &str, parts: &[String]) -> String {
+    let boundary = format!("=_{}", uuid::Uuid::new_v4().simple());
+    let mut out = format!("Content-Type: multipart/{kind}; boundary=\"{boundary}\"\r\n\r\n");
+    for part in parts {
+        out.push_str(&format!("--{boundary}\r\n{part}"));
+    }
+    out.push_str(&format!("--{boundary}--\r\n"));
+    out
+}
+
+/// A header value as is when it is printable ASCII, otherwise as RFC 2047
+/// encoded words. Line breaks are dropped so values can't add headers.
+fn encode_header(value: &str) -> String {
+    let value = clean(value);
+    if value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return value;
+    }
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        // Keeps each encoded word under the 75 character limit.
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|w| format!("=?UTF-8?B?{}?=", BASE64.encode(w)))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// The sender's name, quoted when it is ASCII.
+fn display_name(name: &str) -> String {
+    let encoded = encode_header(name);
+    if encoded.starts_with("=?") {
+        encoded
+    } else {
+        format!("\"{}\"", encoded.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn clean(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+/// Double the dot opening a line, which would otherwise end the message,
+/// and make sure it ends with a line break.
+fn dot_stuff(message: &str) -> String {
+    let mut out = String::with_capacity(message.len() + 2);
+    for line in message.split_inclusive("\r\n") {
+        if line.starts_with('.') {
+            out.push('.');
+        }
+        out.push_str(line);
+    }
+    if !out.ends_with("\r\n") {
+        out.push_str("\r\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::Attachment;
+
+    fn email(text: Option<&str>, attachments: Vec<Attachment>) -> OutgoingEmail {
+        OutgoingEmail {
+            to: "ada@example.com".into(),
+            subject: "Grüße aus Wien".into(),
+            html: "<p>Hallo</p>".into(),
+            text: text.map(Into::into),
+            attachments,
+        }
+    }
+
+    fn sender() -> Sender {
+        Sender {
+            email: "noreply@roomler.ai".into(),
+            name: "Roomler, Inc.".into(),
+        }
+    }
+
+    /// The decoded content of the first part of `content_type`.
+    fn part(message: &str, content_type: &str) -> Vec<u8> {
+        let start = message
+            .find(&format!("Content-Type: {content_type}"))
+            .unwrap();
+        let body = &message[start..];
+        let body = &body[body.find("\r\n\r\n").unwrap() + 4..];
+        let end = body.find("--").unwrap_or(body.len());
+        BASE64.decode(body[..end].replace("\r\n", "")).unwrap()
+    }
+
+    #[test]
+    fn builds_mime_messages() {
+        let attachment = Attachment {
+            file_name: "report.csv".into(),
+            content_type: "text/csv".into(),
+            bytes: b"a,b\n1,2\n".to_vec(),
+        };
+        let message = message(
+            &sender(),
+            &email(Some("Hallo"), vec![attachment]),
+            "<id@roomler.ai>",
+            "Thu, 15 Oct 2026 10:00:00 +0000",
+        );
+        assert!(message.starts_with(
+            "From: \"Roomler, Inc.\" <noreply@roomler.ai>\r\nTo: <ada@example.com>\r\nSubject: =?UTF-8?B?"
+        ));
+        assert!(message.contains("Content-Type: multipart/mixed; boundary="));
+        assert!(message.contains("Content-Type: multipart/alternative; boundary="));
+        assert_eq!(part(&message, "text/plain"), b"Hallo");
+        assert_eq!(part(&message, "text/html"), b"<p>Hallo</p>");
+        assert_eq!(part(&message, "text/csv"), b"a,b\n1,2\n");
+        assert!(message.contains("filename=\"report.csv\""));
+
+        let message = message_without_text();
+        assert!(!message.contains("multipart"));
+        assert_eq!(part(&message, "text/html"), b"<p>Hallo</p>");
+    }
+
+    fn message_without_text() -> String {
+        message(&sender(), &email(None, Vec::new()), "<id@x>", "date")
+    }
+
+    #[test]
+    fn encodes_headers_and_stuffs_dots() {
+        assert_eq!(encode_header("Plain subject"), "Plain subject");
+        assert_eq!(encode_header("Grüße"), "=?UTF-8?B?R3LDvMOfZQ==?=");
+        assert_eq!(
+            encode_header("Hi\r\nBcc: eve@example.com"),
+            "HiBcc: eve@example.com"
+        );
+        let long = encode_header(&"ü".repeat(40));
+        assert_eq!(long.matches("=?UTF-8?B?").count(), 2);
+        assert!(long.lines().all(|l| l.trim().len() <= 75));
+
+        assert_eq!(dot_stuff(".a\r\nb\r\n..c"), "..a\r\nb\r\n...c\r\n");
+    }
+
+    #[tokio::test]
+    async fn runs_an_authenticated_transaction() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let transport = SmtpTransport {
+            host: "localhost".into(),
+            port: 25,
+            tls: SmtpTls::None,
+            credentials: Some(("ada".into(), "secret".into())),
+        };
+        let server = tokio::spawn(async move {
+            let mut conn = BufReader::new(server);
+            let mut received = Vec::new();
+            for reply in ["235 ok", "250 ok", "250 ok", "354 go on"] {
+                let mut line = String::new();
+                conn.read_line(&mut line).await.unwrap();
+                received.push(line.trim_end().to_string());
+                conn.get_mut()
+                    .write_all(format!("{reply}\r\n").as_bytes())
+                    .await
+                    .unwrap();
+            }
+            let mut data = String::new();
+            while !data.ends_with("\r\n.\r\n") {
+                conn.read_line(&mut data).await.unwrap();
+            }
+            conn.get_mut()
+                .write_all(b"250-queued\r\n250 as 1\r\n")
+                .await
+                .unwrap();
+            let mut line = String::new();
+            conn.read_line(&mut line).await.unwrap();
+            received.push(line.trim_end().to_string());
+            conn.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+            (received, data)
+        });
+
+        let mut conn = Connection::new(client);
+        transport
+            .transaction(
+                &mut conn,
+                &sender(),
+                "ada@example.com",
+                ".hidden\r\nbody\r\n",
+            )
+            .await
+            .unwrap();
+        let (received, data) = server.await.unwrap();
+        assert_eq!(
+            received,
+            [
+                format!("AUTH PLAIN {}", BASE64.encode("\0ada\0secret")),
+                "MAIL FROM:<noreply@roomler.ai>".to_string(),
+                "RCPT TO:<ada@example.com>".to_string(),
+                "DATA".to_string(),
+                "QUIT".to_string(),
+            ]
+        );
+        assert_eq!(data, "..hidden\r\nbody\r\n.\r\n");
+    }
+}
@@ -0,0 +1,563 @@
+//! The emails Roomler sends. A template carries only its data, so it can
+//! be queued as JSON and rendered when it is delivered.
+
+use roomler_ai_db::models::{EmailPrefs, NotificationPrefs};
+use serde::{Deserialize, Serialize};
+
+use super::Attachment;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "template", rename_all = "snake_case")]
+pub enum EmailTemplate {
+    Activation {
+        display_name: String,
+        activation_url: String,
+        ttl_minutes: u64,
+    },
+    ActivationSuccess {
+        display_name: String,
+        login_url: String,
+    },
+    InviteCreated {
+        inviter_name: String,
+        tenant_name: String,
+        invite_url: String,
+        /// Invitation to the scheduled conferences the invite joins, as an
+        /// iCalendar file; attached as `invite.ics`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        calendar: Option<String>,
+    },
+    MentionDigest {
+        tenant_name: String,
+        mentions: Vec<DigestMention>,
+        /// Unread mentions beyond those listed.
+        more: u64,
+        inbox_url: String,
+    },
+    PasswordReset {
+        display_name: String,
+        reset_url: String,
+        ttl_minutes: u64,
+    },
+    /// Sent to the new address of an email change.
+    EmailChange {
+        display_name: String,
+        new_email: String,
+        confirm_url: String,
+        ttl_minutes: u64,
+    },
+    /// Sent to the old address once an email change is confirmed.
+    EmailChanged {
+        display_name: String,
+        new_email: String,
+    },
+    BillingReceipt {
+        tenant_name: String,
+        plan: String,
+        /// In the currency's smallest unit, as Stripe reports it.
+        amount_cents: i64,
+        currency: String,
+        invoice_number: Option<String>,
+        /// End of the billed period, as a date.
+        period_end: Option<String>,
+        invoice_url: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DigestMention {
+    pub author_name: String,
+    /// Where it happened, e.g. `Mentioned in #general`.
+    pub context: String,
+    pub preview: String,
+    pub url: String,
+}
+
+/// A rendered template.
+#[derive(Debug, Clone)]
+pub struct Rendered {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+    pub attachments: Vec<Attachment>,
+}
+
+/// Currencies Stripe amounts have no minor unit for.
+const ZERO_DECIMAL: &[&str] = &[
+    "bif", "clp", "djf", "gnf", "jpy", "kmf", "krw", "mga", "pyg", "rwf", "ugx", "vnd", "vuv",
+    "xaf", "xof", "xpf",
+];
+
+impl EmailTemplate {
+    /// Whether a user with these preferences wants this email. Account
+    /// emails are always sent.
+    pub fn allowed_by(&self, email: &EmailPrefs, notifications: &NotificationPrefs) -> bool {
+        match self {
+            Self::Activation { .. }
+            | Self::ActivationSuccess { .. }
+            | Self::PasswordReset { .. }
+            | Self::EmailChange { .. }
+            | Self::EmailChanged { .. } => true,
+            Self::InviteCreated { .. } => notifications.email && email.invites,
+            Self::MentionDigest { .. } => notifications.email && email.mention_digest,
+            Self::BillingReceipt { .. } => notifications.email && email.billing_receipts,
+        }
+    }
+
+    pub fn render(&self) -> Rendered {
+        match self {
+            Self::Activation {
+                display_name,
+                activation_url,
+                ttl_minutes,
+            } => Layout {
+                heading: format!("Welcome, {display_name}!"),
+                blocks: vec![Block::Paragraph(format!(
+                    "Please activate your account by clicking the button below. This link expires in {ttl_minutes} minutes."
+                ))],
+                button: Some(("Activate Account", activation_url)),
+                footer: "If you did not create an account, please ignore this email.",
+            }
+            .render("Activate your Roomler account".to_string()),
+            Self::ActivationSuccess {
+                display_name,
+                login_url,
+            } => Layout {
+                heading: format!("Account activated, {display_name}!"),
+                blocks: vec![Block::Paragraph(
+                    "Your Roomler account is now active.".to_string(),
+                )],
+                button: Some(("Sign In", login_url)),
+                footer: SIGNATURE,
+            }
+            .render("Your Roomler account is active".to_string()),
+            Self::InviteCreated {
+                inviter_name,
+                tenant_name,
+                invite_url,
+                calendar,
+            } => {
+                let mut blocks = vec![Block::Paragraph(format!(
+                    "{inviter_name} has invited you to join {tenant_name} on Roomler."
+                ))];
+                if calendar.is_some() {
+                    blocks.push(Block::Paragraph(
+                        "The invite includes scheduled conferences; open the attached invite.ics to add them to your calendar.".to_string(),
+                    ));
+                }
+                let mut rendered = Layout {
+                    heading: "You're invited!".to_string(),
+                    blocks,
+                    button: Some(("Accept Invitation", invite_url)),
+                    footer: SIGNATURE,
+                }
+                .render(format!("You're invited to join {tenant_name} on Roomler"));
+                if let Some(calendar) = calendar {
+                    rendered.attachments.push(Attachment {
+                        file_name: "invite.ics".to_string(),
+                        content_type: "text/calendar; method=REQUEST".to_string(),
+                        bytes: calendar.clone().into_bytes(),
+                    });
+                }
+                rendered
+            }
+            Self::MentionDigest {
+                tenant_name,
+                mentions,
+                more,
+                inbox_url,
+            } => {
+                let total = mentions.len() as u64 + more;
+                let mut blocks: Vec<Block> = mentions
+                    .iter()
+                    .map(|m| Block::Quote {
+                        author: &m.author_name,
+                        context: &m.context,
+                        url: &m.url,
+                        text: &m.preview,
+                    })
+                    .collect();
+                if *more > 0 {
+                    blocks.push(Block::Paragraph(format!("…and {more} more.")));
+                }
+                Layout {
+                    heading: "You were mentioned".to_string(),
+                    blocks,
+                    button: Some(("Open Inbox", inbox_url)),
+                    footer: SIGNATURE,
+                }
+                .render(format!(
+                    "You have {total} unread {} in {tenant_name}",
+                    if total == 1 { "mention" } else { "mentions" }
+                ))
+            }
+            Self::PasswordReset {
+                display_name,
+                reset_url,
+                ttl_minutes,
+            } => Layout {
+                heading: format!("Hi {display_name},"),
+                blocks: vec![Block::Paragraph(format!(
+                    "Someone asked to reset the password of your Roomler account. Choose a new one with the button below. This link expires in {ttl_minutes} minutes."
+                ))],
+                button: Some(("Reset Password", reset_url)),
+                footer: "If you did not ask for this, you can ignore this email; your password stays the same.",
+            }
+            .render("Reset your Roomler password".to_string()),
+            Self::EmailChange {
+                display_name,
+                new_email,
+                confirm_url,
+                ttl_minutes,
+            } => Layout {
+                heading: format!("Hi {display_name},"),
+                blocks: vec![Block::Paragraph(format!(
+                    "Confirm that you want to sign in to Roomler as {new_email} from now on. This link expires in {ttl_minutes} minutes."
+                ))],
+                button: Some(("Confirm Email", confirm_url)),
+                footer: "If you did not ask for this, you can ignore this email; nothing changes.",
+            }
+            .render("Confirm your new Roomler email".to_string()),
+            Self::EmailChanged {
+                display_name,
+                new_email,
+            } => Layout {
+                heading: format!("Hi {display_name},"),
+                blocks: vec![Block::Paragraph(format!(
+                    "The email of your Roomler account was changed to {new_email}. Sign in with it from now on."
+                ))],
+                button: None,
+                footer: "If you did not make this change, contact your workspace admin right away.",
+            }
+            .render("Your Roomler email was changed".to_string()),
+            Self::BillingReceipt {
+                tenant_name,
+                plan,
+                amount_cents,
+                currency,
+                invoice_number,
+                period_end,
+                invoice_url,
+            } => {
+                let amount = format_amount(*amount_cents, currency);
+                let mut blocks = vec![Block::Paragraph(format!(
+                    "We received your payment of {amount} for the {plan} plan of {tenant_name}."
+                ))];
+                if let Some(number) = invoice_number {
+                    blocks.push(Block::Paragraph(format!("Invoice number: {number}")));
+                }
+                if let Some(period_end) = period_end {
+                    blocks.push(Block::Paragraph(format!("Paid through: {period_end}")));
+                }
+                Layout {
+                    heading: "Thanks for your payment".to_string(),
+                    blocks,
+                    button: invoice_url.as_deref().map(|url| ("View Invoice", url)),
+                    footer: SIGNATURE,
+                }
+                .render(format!("Your Roomler receipt for {tenant_name}"))
+            }
+        }
+    }
+}
+
+const SIGNATURE: &str = "— The Roomler Team";
+
+/// The shared look of every email: a heading, paragraphs and quotes, an
+/// optional button with the link spelled out, and a footer line.
+struct Layout<'a> {
+    heading: String,
+    blocks: Vec<Block<'a>>,
+    button: Option<(&'a str, &'a str)>,
+    footer: &'a str,
+}
+
+enum Block<'a> {
+    Paragraph(String),
+    /// A quoted message with who wrote it and a link to where.
+    Quote {
+        author: &'a str,
+        context: &'a str,
+        url: &'a str,
+        text: &'a str,
+    },
+}
+
+impl Layout<'_> {
+    fn render(self, subject: String) -> Rendered {
+        let mut html = format!(
+            "<div style=\"font-family: sans-serif; max-width: 600px; margin: 0 auto;\">\n<h2>{}</h2>\n",
+            escape(&self.heading)
+        );
+        let mut text = format!("{}\n\n", self.heading);
+        for block in &self.blocks {
+            match block {
+                Block::Paragraph(paragraph) => {
+                    html.push_str(&format!("<p>{}</p>\n", escape(paragraph)));
+                    text.push_str(&format!("{paragraph}\n\n"));
+                }
+                Block::Quote {
+                    author,
+                    context,
+                    url,
+                    text: quoted,
+                } => {
+                    html.push_str(&format!(
+                        r#"<p><strong>{}</strong> · <a href="{}">{}</a></p>
+<blockquote style="border-left: 3px solid #1976d2; padding: 8px 12px; margin: 8px 0 16px; color: #333; background: #f5f5f5; border-radius: 4px;">{}</blockquote>
+"#,
+                        escape(author),
+                        escape(url),
+                        escape(context),
+                        escape(quoted),
+                    ));
+                    text.push_str(&format!(
+                        "{author} ({context}): \u{201c}{quoted}\u{201d}\n{url}\n\n"
+                    ));
+                }
+            }
+        }
+        if let Some((label, url)) = self.button {
+            html.push_str(&format!(
+                r#"<p style="margin: 24px 0;">
+  <a href="{url}" style="background: #1976d2; color: #fff; padding: 12px 24px; border-radius: 6px; text-decoration: none; font-weight: bold;">
+    {label}
+  </a>
+</p>
+<p style="color: #666; font-size: 13px;">Or copy this link: <a href="{url}">{url}</a></p>
+"#,
+                url = escape(url),
+            ));
+            text.push_str(&format!("{label}: {url}\n\n"));
+        }
+        html.push_str(&format!(
+            "<p style=\"color: #999; font-size: 12px; margin-top: 32px;\">{}</p>\n</div>",
+            escape(self.footer)
+        ));
+        text.push_str(self.footer);
+        text.push('\n');
+        Rendered {
+            subject,
+            html,
+            text,
+            attachments: Vec::new(),
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `1999, "usd"` → `19.99 USD`; `500, "jpy"` → `500 JPY`.
+fn format_amount(amount: i64, currency: &str) -> String {
+    let code = currency.to_uppercase();
+    if ZERO_DECIMAL.contains(&currency.to_lowercase().as_str()) {
+        return format!("{amount} {code}");
+    }
+    let sign = if amount < 0 { "-" } else { "" };
+    let amount = amount.unsigned_abs();
+    format!("{sign}{}.{:02} {code}", amount / 100, amount % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mention(author: &str) -> DigestMention {
+        DigestMention {
+            author_name: author.into(),
+            context: "Mentioned in #general".into(),
+            preview: "can you <b>look</b> at this?".into(),
+            url: "https://roomler.ai/tenant/t/room/r?msg=m".into(),
+        }
+    }
+
+    #[test]
+    fn renders_escaped_html_and_plain_text() {
+        let rendered = EmailTemplate::InviteCreated {
+            inviter_name: "Ada <admin>".into(),
+            tenant_name: "Acme & Co".into(),
+            invite_url: "https://roomler.ai/invite/abc".into(),
+            calendar: None,
+        }
+        .render();
+        assert_eq!(
+            rendered.subject,
+            "You're invited to join Acme & Co on Roomler"
+        );
+        assert!(
+            rendered
+                .html
+                .contains("Ada &lt;admin&gt; has invited you to join Acme &amp; Co")
+        );
+        assert!(
+            rendered
+                .html
+                .contains(r#"href="https://roomler.ai/invite/abc""#)
+        );
+        assert!(
+            rendered
+                .text
+                .contains("Ada <admin> has invited you to join Acme & Co")
+        );
+        assert!(
+            rendered
+                .text
+                .contains("Accept Invitation: https://roomler.ai/invite/abc")
+        );
+        assert!(rendered.text.ends_with("— The Roomler Team\n"));
+        assert!(rendered.attachments.is_empty());
+    }
+
+    #[test]
+    fn attaches_conference_invitations() {
+        let rendered = EmailTemplate::InviteCreated {
+            inviter_name: "Ada".into(),
+            tenant_name: "Acme".into(),
+            invite_url: "https://roomler.ai/invite/abc".into(),
+            calendar: Some("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n".into()),
+        }
+        .render();
+        assert!(rendered.text.contains("invite.ics"));
+        let [attachment] = rendered.attachments.as_slice() else {
+            panic!("expected one attachment");
+        };
+        assert_eq!(attachment.file_name, "invite.ics");
+        assert_eq!(attachment.content_type, "text/calendar; method=REQUEST");
+        assert_eq!(attachment.bytes, b"BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn renders_mention_digests() {
+        let rendered = EmailTemplate::MentionDigest {
+            tenant_name: "Acme".into(),
+            mentions: vec![mention("Ada"), mention("Grace")],
+            more: 3,
+            inbox_url: "https://roomler.ai/tenant/t/mentions".into(),
+        }
+        .render();
+        assert_eq!(rendered.subject, "You have 5 unread mentions in Acme");
+        assert_eq!(rendered.html.matches("<blockquote").count(), 2);
+        assert!(
+            rendered
+                .html
+                .contains("can you &lt;b&gt;look&lt;/b&gt; at this?")
+        );
+        assert!(rendered.html.contains("…and 3 more."));
+        assert!(
+            rendered
+                .text
+                .starts_with("You were mentioned\n\nAda (Mentioned in #general)")
+        );
+        assert!(
+            rendered
+                .text
+                .contains("Open Inbox: https://roomler.ai/tenant/t/mentions")
+        );
+
+        let one = EmailTemplate::MentionDigest {
+            tenant_name: "Acme".into(),
+            mentions: vec![mention("Ada")],
+            more: 0,
+            inbox_url: String::new(),
+        };
+        assert_eq!(one.render().subject, "You have 1 unread mention in Acme");
+    }
+
+    #[test]
+    fn formats_receipts() {
+        let receipt = |amount, currency: &str, url: Option<&str>| EmailTemplate::BillingReceipt {
+            tenant_name: "Acme".into(),
+            plan: "pro".into(),
+            amount_cents: amount,
+            currency: currency.into(),
+            invoice_number: Some("INV-0001".into()),
+            period_end: Some("2026-11-01".into()),
+            invoice_url: url.map(Into::into),
+        };
+        let rendered = receipt(1999, "usd", Some("https://pay.stripe.com/i")).render();
+        assert!(
+            rendered
+                .text
+                .contains("payment of 19.99 USD for the pro plan of Acme")
+        );
+        assert!(rendered.text.contains("Invoice number: INV-0001"));
+        assert!(rendered.html.contains("View Invoice"));
+        let rendered = receipt(500, "jpy", None).render();
+        assert!(rendered.text.contains("payment of 500 JPY"));
+        assert!(!rendered.html.contains("View Invoice"));
+        assert_eq!(format_amount(-5, "eur"), "-0.05 EUR");
+    }
+
+    #[test]
+    fn account_emails_ignore_preferences() {
+        let off = EmailPrefs {
+            invites: false,
+            mention_digest: false,
+            billing_receipts: false,
+        };
+        let no_email = NotificationPrefs {
+            email: false,
+            ..Default::default()
+        };
+        let reset = EmailTemplate::PasswordReset {
+            display_name: "Ada".into(),
+            reset_url: String::new(),
+            ttl_minutes: 60,
+        };
+        assert!(reset.allowed_by(&off, &no_email));
+        let changed = EmailTemplate::EmailChanged {
+            display_name: "Ada".into(),
+            new_email: "ada@example.com".into(),
+        };
+        assert!(changed.allowed_by(&off, &no_email));
+        let invite = EmailTemplate::InviteCreated {
+            inviter_name: "Ada".into(),
+            tenant_name: "Acme".into(),
+            invite_url: String::new(),
+            calendar: None,
+        };
+        assert!(invite.allowed_by(&EmailPrefs::default(), &NotificationPrefs::default()));
+        assert!(!invite.allowed_by(&off, &NotificationPrefs::default()));
+        assert!(!invite.allowed_by(&EmailPrefs::default(), &no_email));
+    }
+
+    #[test]
+    fn round_trips_as_json() {
+        let template = EmailTemplate::ActivationSuccess {
+            display_name: "Ada".into(),
+            login_url: "https://roomler.ai/auth/login".into(),
+        };
+        let json = serde_json::to_value(&template).unwrap();
+        assert_eq!(json["template"], "activation_success");
+        assert_eq!(
+            serde_json::from_value::<EmailTemplate>(json).unwrap(),
+            template
+        );
+
+        // Invites queued before they could carry a calendar
+        let queued = serde_json::json!({
+            "template": "invite_created",
+            "inviter_name": "Ada",
+            "tenant_name": "Acme",
+            "invite_url": "https://roomler.ai/invite/abc",
+        });
+        let EmailTemplate::InviteCreated { calendar, .. } = serde_json::from_value(queued).unwrap()
+        else {
+            panic!("expected an invite");
+        };
+        assert_eq!(calendar, None);
+    }
+}
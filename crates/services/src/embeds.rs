@@ -0,0 +1,252 @@
+//! Structured message embeds: location pins, contact cards and the
+//! key-value cards bots post. [`validate`] checks what a client or bot sent
+//! before it is stored; [`describe`] is the plain-text form used in exports.
+
+use roomler_ai_db::models::Embed;
+
+/// Most embeds on one message.
+pub const MAX_EMBEDS: usize = 10;
+/// Most bytes a message's embeds can take, as JSON.
+pub const MAX_EMBEDS_BYTES: usize = 8 * 1024;
+/// Most fields on one key-value embed.
+pub const MAX_FIELDS: usize = 25;
+/// Longest name, title, address or field name.
+pub const MAX_TEXT_LEN: usize = 256;
+/// Longest field value.
+pub const MAX_VALUE_LEN: usize = 1024;
+
+/// Check the embeds of a new message.
+pub fn validate(embeds: &[Embed]) -> Result<(), String> {
+    if embeds.len() > MAX_EMBEDS {
+        return Err(format!("A message can have at most {MAX_EMBEDS} embeds"));
+    }
+    let bytes = serde_json::to_vec(embeds).map_err(|e| e.to_string())?.len();
+    if bytes > MAX_EMBEDS_BYTES {
+        return Err(format!(
+            "Embeds can take at most {MAX_EMBEDS_BYTES} bytes, got {bytes}"
+        ));
+    }
+    for embed in embeds {
+        match embed {
+            Embed::Location {
+                latitude,
+                longitude,
+                name,
+                address,
+            } => {
+                if !(-90.0..=90.0).contains(latitude) || !(-180.0..=180.0).contains(longitude) {
+                    return Err("Location coordinates are out of range".to_string());
+                }
+                optional_text("name", name, MAX_TEXT_LEN)?;
+                optional_text("address", address, MAX_TEXT_LEN)?;
+            }
+            Embed::Contact {
+                name,
+                email,
+                phone,
+                organization,
+            } => {
+                text("name", name, MAX_TEXT_LEN)?;
+                if email.is_none() && phone.is_none() {
+                    return Err("A contact needs an email or a phone number".to_string());
+                }
+                optional_text("email", email, MAX_TEXT_LEN)?;
+                if email.as_ref().is_some_and(|e| !is_email(e)) {
+                    return Err("Invalid contact email".to_string());
+                }
+                optional_text("phone", phone, MAX_TEXT_LEN)?;
+                if phone.as_ref().is_some_and(|p| !is_phone(p)) {
+                    return Err("Invalid contact phone number".to_string());
+                }
+                optional_text("organization", organization, MAX_TEXT_LEN)?;
+            }
+            Embed::Fields {
+                title,
+                url,
+                color,
+                fields,
+            } => {
+                optional_text("title", title, MAX_TEXT_LEN)?;
+                if let Some(url) = url {
+                    let parsed = reqwest::Url::parse(url).map_err(|_| "Invalid url".to_string())?;
+                    if !matches!(parsed.scheme(), "http" | "https") {
+                        return Err("url must be http or https".to_string());
+                    }
+                }
+                if color.is_some_and(|c| c > 0xFF_FFFF) {
+                    return Err("color must be an RGB value".to_string());
+                }
+                if fields.is_empty() || fields.len() > MAX_FIELDS {
+                    return Err(format!("An embed needs 1-{MAX_FIELDS} fields"));
+                }
+                for field in fields {
+                    text("field name", &field.name, MAX_TEXT_LEN)?;
+                    text("field value", &field.value, MAX_VALUE_LEN)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One line describing `embed`, e.g. `Location: Office (48.2082, 16.3738)`.
+pub fn describe(embed: &Embed) -> String {
+    match embed {
+        Embed::Location {
+            latitude,
+            longitude,
+            name,
+            address,
+        } => {
+            let place: Vec<&str> = [name, address]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            let coordinates = format!("({latitude:.4}, {longitude:.4})");
+            if place.is_empty() {
+                format!("Location: {coordinates}")
+            } else {
+                format!("Location: {} {coordinates}", place.join(", "))
+            }
+        }
+        Embed::Contact {
+            name,
+            email,
+            phone,
+            organization,
+        } => {
+            let details: Vec<&str> = [organization, email, phone]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            format!("Contact: {name} ({})", details.join(", "))
+        }
+        Embed::Fields { title, fields, .. } => {
+            let fields = fields
+                .iter()
+                .map(|f| format!("{}: {}", f.name, f.value))
+                .collect::<Vec<_>>()
+                .join("; ");
+            match title {
+                Some(title) => format!("{title}: {fields}"),
+                None => fields,
+            }
+        }
+    }
+}
+
+fn text(name: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.trim().is_empty() || value.chars().count() > max {
+        return Err(format!("{name} must be 1-{max} characters"));
+    }
+    Ok(())
+}
+
+fn optional_text(name: &str, value: &Option<String>, max: usize) -> Result<(), String> {
+    value.as_deref().map_or(Ok(()), |v| text(name, v, max))
+}
+
+fn is_email(s: &str) -> bool {
+    s.split_once('@')
+        .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+        && !s.contains(char::is_whitespace)
+}
+
+fn is_phone(s: &str) -> bool {
+    let digits = s.chars().filter(char::is_ascii_digit).count();
+    (5..=20).contains(&digits)
+        && s.chars()
+            .all(|c| c.is_ascii_digit() || "+-(). ".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roomler_ai_db::models::EmbedField;
+
+    fn location(latitude: f64, longitude: f64) -> Embed {
+        Embed::Location {
+            latitude,
+            longitude,
+            name: Some("Office".into()),
+            address: None,
+        }
+    }
+
+    fn contact(email: Option<&str>, phone: Option<&str>) -> Embed {
+        Embed::Contact {
+            name: "Ada".into(),
+            email: email.map(Into::into),
+            phone: phone.map(Into::into),
+            organization: None,
+        }
+    }
+
+    fn fields(count: usize) -> Embed {
+        Embed::Fields {
+            title: Some("Build".into()),
+            url: None,
+            color: None,
+            fields: (0..count)
+                .map(|i| EmbedField {
+                    name: format!("k{i}"),
+                    value: "v".into(),
+                    inline: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn validates_each_kind() {
+        assert!(
+            validate(&[
+                location(48.2082, 16.3738),
+                contact(Some("ada@example.com"), None),
+                fields(2)
+            ])
+            .is_ok()
+        );
+        assert!(validate(&[location(91.0, 0.0)]).is_err());
+        assert!(validate(&[location(f64::NAN, 0.0)]).is_err());
+        assert!(validate(&[contact(None, None)]).is_err());
+        assert!(validate(&[contact(Some("not an email"), None)]).is_err());
+        assert!(validate(&[contact(None, Some("+43 (1) 234-567"))]).is_ok());
+        assert!(validate(&[contact(None, Some("call me"))]).is_err());
+        assert!(validate(&[fields(0)]).is_err());
+        assert!(validate(&[fields(MAX_FIELDS + 1)]).is_err());
+    }
+
+    #[test]
+    fn limits_count_and_size() {
+        assert!(validate(&vec![location(0.0, 0.0); MAX_EMBEDS + 1]).is_err());
+        let big = Embed::Fields {
+            title: None,
+            url: None,
+            color: None,
+            fields: (0..MAX_FIELDS)
+                .map(|i| EmbedField {
+                    name: format!("k{i}"),
+                    value: "v".repeat(MAX_VALUE_LEN),
+                    inline: true,
+                })
+                .collect(),
+        };
+        assert!(validate(&[big]).unwrap_err().contains("bytes"));
+    }
+
+    #[test]
+    fn describes_embeds() {
+        assert_eq!(
+            describe(&location(48.20821, 16.37381)),
+            "Location: Office (48.2082, 16.3738)"
+        );
+        assert_eq!(
+            describe(&contact(Some("ada@example.com"), Some("+431234567"))),
+            "Contact: Ada (ada@example.com, +431234567)"
+        );
+        assert_eq!(describe(&fields(2)), "Build: k0: v; k1: v");
+    }
+}
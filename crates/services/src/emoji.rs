@@ -0,0 +1,411 @@
+//! `:shortcode:` emoji in messages and reactions.
+//!
+//! Shortcodes are expanded on the server so every client stores and renders
+//! the same thing: a bundled shortcode becomes its Unicode emoji, a tenant's
+//! custom emoji stays as its normalized `:name:`, and anything else is
+//! rejected with the closest names as suggestions. A shortcode only counts
+//! when it stands on its own (`ok :thumbsup:`, not `key:value:x`) and
+//! outside backtick code, so times like `10:30:00` pass through untouched.
+
+use std::ops::Range;
+
+/// Longest shortcode name, colons excluded.
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Most suggestions offered for an unknown shortcode.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Most custom emoji a tenant can register.
+pub const MAX_CUSTOM_PER_TENANT: u64 = 500;
+
+/// Bundled shortcodes, with aliases sharing an emoji.
+const BUNDLED: &[(&str, &str)] = &[
+    ("+1", "\u{1f44d}"),
+    ("thumbsup", "\u{1f44d}"),
+    ("-1", "\u{1f44e}"),
+    ("thumbsdown", "\u{1f44e}"),
+    ("100", "\u{1f4af}"),
+    ("angry", "\u{1f620}"),
+    ("bell", "\u{1f514}"),
+    ("blush", "\u{1f60a}"),
+    ("boom", "\u{1f4a5}"),
+    ("broken_heart", "\u{1f494}"),
+    ("bug", "\u{1f41b}"),
+    ("bulb", "\u{1f4a1}"),
+    ("calendar", "\u{1f4c5}"),
+    ("camera", "\u{1f4f7}"),
+    ("check", "\u{2714}\u{fe0f}"),
+    ("white_check_mark", "\u{2705}"),
+    ("clap", "\u{1f44f}"),
+    ("coffee", "\u{2615}"),
+    ("confused", "\u{1f615}"),
+    ("cool", "\u{1f192}"),
+    ("cry", "\u{1f622}"),
+    ("dart", "\u{1f3af}"),
+    ("eyes", "\u{1f440}"),
+    ("facepalm", "\u{1f926}"),
+    ("fire", "\u{1f525}"),
+    ("flushed", "\u{1f633}"),
+    ("gift", "\u{1f381}"),
+    ("grin", "\u{1f601}"),
+    ("grinning", "\u{1f600}"),
+    ("hammer", "\u{1f528}"),
+    ("hand", "\u{270b}"),
+    ("raised_hand", "\u{270b}"),
+    ("handshake", "\u{1f91d}"),
+    ("heart", "\u{2764}\u{fe0f}"),
+    ("heart_eyes", "\u{1f60d}"),
+    ("hourglass", "\u{231b}"),
+    ("hugs", "\u{1f917}"),
+    ("hushed", "\u{1f62f}"),
+    ("innocent", "\u{1f607}"),
+    ("joy", "\u{1f602}"),
+    ("kiss", "\u{1f48b}"),
+    ("laughing", "\u{1f606}"),
+    ("link", "\u{1f517}"),
+    ("lock", "\u{1f512}"),
+    ("memo", "\u{1f4dd}"),
+    ("mute", "\u{1f507}"),
+    ("muscle", "\u{1f4aa}"),
+    ("neutral_face", "\u{1f610}"),
+    ("no_entry", "\u{26d4}"),
+    ("ok", "\u{1f197}"),
+    ("ok_hand", "\u{1f44c}"),
+    ("open_mouth", "\u{1f62e}"),
+    ("partying_face", "\u{1f973}"),
+    ("pensive", "\u{1f614}"),
+    ("point_down", "\u{1f447}"),
+    ("point_left", "\u{1f448}"),
+    ("point_right", "\u{1f449}"),
+    ("point_up", "\u{261d}\u{fe0f}"),
+    ("pray", "\u{1f64f}"),
+    ("pushpin", "\u{1f4cc}"),
+    ("question", "\u{2753}"),
+    ("exclamation", "\u{2757}"),
+    ("rage", "\u{1f621}"),
+    ("raised_hands", "\u{1f64c}"),
+    ("relaxed", "\u{263a}\u{fe0f}"),
+    ("relieved", "\u{1f60c}"),
+    ("rocket", "\u{1f680}"),
+    ("rofl", "\u{1f923}"),
+    ("rotating_light", "\u{1f6a8}"),
+    ("scream", "\u{1f631}"),
+    ("see_no_evil", "\u{1f648}"),
+    ("shrug", "\u{1f937}"),
+    ("sleeping", "\u{1f634}"),
+    ("slightly_smiling_face", "\u{1f642}"),
+    ("smile", "\u{1f604}"),
+    ("smiley", "\u{1f603}"),
+    ("smirk", "\u{1f60f}"),
+    ("sob", "\u{1f62d}"),
+    ("sparkles", "\u{2728}"),
+    ("speech_balloon", "\u{1f4ac}"),
+    ("star", "\u{2b50}"),
+    ("star_struck", "\u{1f929}"),
+    ("stuck_out_tongue", "\u{1f61b}"),
+    ("sunglasses", "\u{1f60e}"),
+    ("sweat_smile", "\u{1f605}"),
+    ("tada", "\u{1f389}"),
+    ("thinking", "\u{1f914}"),
+    ("thought_balloon", "\u{1f4ad}"),
+    ("trophy", "\u{1f3c6}"),
+    ("unamused", "\u{1f612}"),
+    ("upside_down_face", "\u{1f643}"),
+    ("v", "\u{270c}\u{fe0f}"),
+    ("warning", "\u{26a0}\u{fe0f}"),
+    ("wave", "\u{1f44b}"),
+    ("weary", "\u{1f629}"),
+    ("wink", "\u{1f609}"),
+    ("wrench", "\u{1f527}"),
+    ("x", "\u{274c}"),
+    ("yawning_face", "\u{1f971}"),
+    ("zany_face", "\u{1f92a}"),
+    ("zap", "\u{26a1}"),
+    ("zipper_mouth_face", "\u{1f910}"),
+];
+
+/// The bundled emoji for `name` (case-insensitive).
+pub fn lookup(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    BUNDLED
+        .iter()
+        .find(|(shortcode, _)| *shortcode == name)
+        .map(|(_, emoji)| *emoji)
+}
+
+/// A shortcode found in text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcode {
+    /// Byte range of `:name:`, colons included.
+    pub range: Range<usize>,
+    /// Lowercased name without colons.
+    pub name: String,
+}
+
+/// How a shortcode resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    /// A bundled emoji.
+    Unicode(&'static str),
+    /// One of the tenant's custom emoji, by normalized name.
+    Custom(String),
+}
+
+/// A shortcode matching neither the bundled set nor the tenant's emoji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownShortcode {
+    pub name: String,
+    pub suggestions: Vec<String>,
+}
+
+impl std::fmt::Display for UnknownShortcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown emoji :{}:", self.name)?;
+        if !self.suggestions.is_empty() {
+            let names: Vec<String> = self.suggestions.iter().map(|s| format!(":{s}:")).collect();
+            write!(f, " (did you mean {}?)", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` as a lone shortcode name, if it is `:name:`.
+pub fn as_shortcode(value: &str) -> Option<String> {
+    let name = value.strip_prefix(':')?.strip_suffix(':')?;
+    is_name(name).then(|| name.to_ascii_lowercase())
+}
+
+/// Shortcodes in `text`, outside backtick code spans and fences.
+pub fn shortcodes(text: &str) -> Vec<Shortcode> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut in_code = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                in_code = !in_code;
+                // A ``` fence toggles once, not three times.
+                while i + 1 < bytes.len() && bytes[i + 1] == b'`' {
+                    i += 1;
+                }
+            }
+            b':' if !in_code && (i == 0 || !is_word_byte(bytes[i - 1])) => {
+                let rest = &text[i + 1..];
+                if let Some(end) = rest.find(':') {
+                    let name = &rest[..end];
+                    let close = i + 1 + end;
+                    let bounded = bytes.get(close + 1).is_none_or(|b| !is_word_byte(*b));
+                    if bounded && is_name(name) && (has_letter(name) || lookup(name).is_some()) {
+                        found.push(Shortcode {
+                            range: i..close + 1,
+                            name: name.to_ascii_lowercase(),
+                        });
+                        i = close + 1;
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    found
+}
+
+/// Resolve `name` against the bundled set, then `custom` (the tenant's
+/// custom emoji names).
+pub fn resolve(name: &str, custom: &[String]) -> Result<Resolved, UnknownShortcode> {
+    let name = name.to_ascii_lowercase();
+    if let Some(emoji) = lookup(&name) {
+        return Ok(Resolved::Unicode(emoji));
+    }
+    if custom.iter().any(|c| c.eq_ignore_ascii_case(&name)) {
+        return Ok(Resolved::Custom(name));
+    }
+    Err(UnknownShortcode {
+        suggestions: suggest(&name, custom),
+        name,
+    })
+}
+
+/// Expand every shortcode in `text`: bundled ones to Unicode, custom ones
+/// to their lowercased `:name:`. Fails on the first unknown shortcode.
+pub fn expand(text: &str, custom: &[String]) -> Result<String, UnknownShortcode> {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for code in shortcodes(text) {
+        out.push_str(&text[last..code.range.start]);
+        match resolve(&code.name, custom)? {
+            Resolved::Unicode(emoji) => out.push_str(emoji),
+            Resolved::Custom(name) => {
+                out.push(':');
+                out.push_str(&name);
+                out.push(':');
+            }
+        }
+        last = code.range.end;
+    }
+    out.push_str(&text[last..]);
+    Ok(out)
+}
+
+/// Whether any shortcode in `text` is missing from the bundled set, so the
+/// tenant's custom emoji are needed to expand it.
+pub fn needs_custom(text: &str) -> bool {
+    shortcodes(text)
+        .iter()
+        .any(|code| lookup(&code.name).is_none())
+}
+
+/// Known names closest to `name`: those within a couple of edits, then
+/// those sharing its prefix.
+pub fn suggest(name: &str, custom: &[String]) -> Vec<String> {
+    let max_distance = if name.len() <= 4 { 1 } else { 2 };
+    let mut scored: Vec<(usize, String)> = BUNDLED
+        .iter()
+        .map(|(shortcode, _)| shortcode.to_string())
+        .chain(custom.iter().map(|c| c.to_ascii_lowercase()))
+        .filter_map(|candidate| {
+            let distance = edit_distance(name, &candidate);
+            if distance <= max_distance {
+                Some((distance, candidate))
+            } else if name.len() >= 3 && candidate.starts_with(name) {
+                Some((max_distance + 1, candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// The normalized name for a new custom emoji, given with or without its
+/// colons. It must be usable as a shortcode in text and not shadow a
+/// bundled one.
+pub fn custom_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let name = name
+        .strip_prefix(':')
+        .and_then(|n| n.strip_suffix(':'))
+        .unwrap_or(name);
+    if !is_name(name) || !has_letter(name) {
+        return Err(format!(
+            "Emoji names are 1-{MAX_NAME_LEN} letters, digits, _, + or -, with at least one letter"
+        ));
+    }
+    let name = name.to_ascii_lowercase();
+    if lookup(&name).is_some() {
+        return Err(format!(":{name}: is a built-in emoji"));
+    }
+    Ok(name)
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'+' | b'-'))
+}
+
+fn has_letter(name: &str) -> bool {
+    name.bytes().any(|b| b.is_ascii_alphabetic())
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'/'
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            row[j + 1] = substitution.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_bundled_shortcodes_and_aliases() {
+        assert_eq!(
+            expand("nice :thumbsup: :+1: :TADA:", &[]).unwrap(),
+            "nice \u{1f44d} \u{1f44d} \u{1f389}"
+        );
+        assert_eq!(expand(":100:", &[]).unwrap(), "\u{1f4af}");
+    }
+
+    #[test]
+    fn custom_emoji_keep_a_normalized_shortcode() {
+        let custom = vec!["party_parrot".to_string()];
+        assert_eq!(
+            expand("yes :Party_Parrot:!", &custom).unwrap(),
+            "yes :party_parrot:!"
+        );
+        assert_eq!(
+            resolve("party_parrot", &custom).unwrap(),
+            Resolved::Custom("party_parrot".to_string())
+        );
+    }
+
+    #[test]
+    fn custom_names_are_normalized_and_checked() {
+        assert_eq!(custom_name(":Party_Parrot:").unwrap(), "party_parrot");
+        assert_eq!(custom_name("shipit").unwrap(), "shipit");
+        assert!(custom_name("thumbsup").is_err());
+        assert!(custom_name("123").is_err());
+        assert!(custom_name("no spaces").is_err());
+        assert!(custom_name("").is_err());
+    }
+
+    #[test]
+    fn leaves_times_urls_and_code_alone() {
+        for text in [
+            "meet at 10:30:00",
+            "key:value:other",
+            "see http://example.com:8080/a:b:",
+            "run `echo :nope:` first",
+            "```\n:nope:\n```",
+        ] {
+            assert!(shortcodes(text).is_empty(), "{text}");
+            assert_eq!(expand(text, &[]).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn unknown_shortcodes_come_with_suggestions() {
+        let err = expand("well :thumbsupp:", &[]).unwrap_err();
+        assert_eq!(err.name, "thumbsupp");
+        assert_eq!(err.suggestions[0], "thumbsup");
+        assert!(err.to_string().contains("did you mean :thumbsup:"));
+
+        let custom = vec!["shipit".to_string()];
+        let err = resolve("shipt", &custom).unwrap_err();
+        assert_eq!(err.suggestions, vec!["shipit".to_string()]);
+        assert!(resolve("qqqqqqqq", &[]).unwrap_err().suggestions.is_empty());
+    }
+
+    #[test]
+    fn lone_shortcode_values() {
+        assert_eq!(as_shortcode(":Fire:"), Some("fire".to_string()));
+        assert_eq!(as_shortcode("\u{1f525}"), None);
+        assert_eq!(as_shortcode(":a b:"), None);
+        assert!(needs_custom("x :shipit:"));
+        assert!(!needs_custom("x :fire:"));
+    }
+}
@@ -0,0 +1,55 @@
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, User};
+use std::collections::HashMap;
+
+use crate::member_import::escape;
+
+/// Export conversation messages as RFC 4180 CSV, one row per message.
+/// Fields that spreadsheet apps would evaluate as formulas are neutralized.
+pub fn export_conversation(messages: &[Message], users: &HashMap<ObjectId, User>) -> Vec<u8> {
+    let mut csv = String::from("timestamp,author,type,message,reactions,attachments,embeds\n");
+    for msg in messages {
+        let attachments = msg
+            .attachments
+            .iter()
+            .map(|a| a.filename.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        csv.push_str(&format!(
+            "{},{},{:?},{},{},{},{}\n",
+            super::timestamp(msg),
+            escape(super::author(users, msg)),
+            msg.message_type,
+            escape(&msg.content),
+            escape(&super::reactions(msg, " ")),
+            escape(&attachments),
+            escape(&super::embeds(msg).join("; "))
+        ));
+    }
+    csv.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_neutralizes_fields() {
+        let now = bson::DateTime::from_millis(0);
+        let msg: Message = bson::from_document(bson::doc! {
+            "tenant_id": ObjectId::new(),
+            "room_id": ObjectId::new(),
+            "author_id": ObjectId::new(),
+            "content": "=SUM(A1)\nsays \"hi\", twice",
+            "created_at": now,
+            "updated_at": now,
+        })
+        .unwrap();
+        let csv = String::from_utf8(export_conversation(&[msg], &HashMap::new())).unwrap();
+        assert_eq!(
+            csv,
+            "timestamp,author,type,message,reactions,attachments,embeds\n\
+             1970-01-01 00:00:00,Unknown,Default,\"'=SUM(A1)\nsays \"\"hi\"\", twice\",,,\n"
+        );
+    }
+}
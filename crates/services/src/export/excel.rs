@@ -21,6 +21,7 @@ pub fn export_conversation(
     worksheet.write_string_with_format(0, 2, "Message", &header_format)?;
     worksheet.write_string_with_format(0, 3, "Type", &header_format)?;
     worksheet.write_string_with_format(0, 4, "Reactions", &header_format)?;
+    worksheet.write_string_with_format(0, 5, "Embeds", &header_format)?;
 
     // Set column widths
     worksheet.set_column_width(0, 20)?;
@@ -28,6 +29,7 @@ pub fn export_conversation(
     worksheet.set_column_width(2, 60)?;
     worksheet.set_column_width(3, 15)?;
     worksheet.set_column_width(4, 20)?;
+    worksheet.set_column_width(5, 40)?;
 
     for (i, msg) in messages.iter().enumerate() {
         let row = (i + 1) as u32;
@@ -53,6 +55,7 @@ pub fn export_conversation(
             .collect::<Vec<_>>()
             .join(", ");
         worksheet.write_string(row, 4, &reactions)?;
+        worksheet.write_string(row, 5, super::embeds(msg).join("\n"))?;
     }
 
     workbook.save_to_buffer()
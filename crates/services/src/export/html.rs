@@ -0,0 +1,124 @@
+use base64::Engine;
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, User};
+use std::collections::HashMap;
+
+/// Largest attachment embedded into an HTML export; bigger ones are listed
+/// by name only.
+pub const MAX_EMBEDDED_ASSET_BYTES: u64 = 5 * 1024 * 1024;
+
+/// An attachment's content, inlined into the page as a `data:` URL.
+pub struct EmbeddedAsset {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Whether an attachment should be fetched for embedding: images up to
+/// [`MAX_EMBEDDED_ASSET_BYTES`].
+pub fn embeds(content_type: &str, size: u64) -> bool {
+    content_type.starts_with("image/") && size <= MAX_EMBEDDED_ASSET_BYTES
+}
+
+/// Export conversation messages as a single self-contained HTML page.
+/// Attachments found in `assets`, keyed by file id, are shown inline;
+/// the rest are listed by name.
+pub fn export_conversation(
+    messages: &[Message],
+    users: &HashMap<ObjectId, User>,
+    assets: &HashMap<ObjectId, EmbeddedAsset>,
+) -> Vec<u8> {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Conversation Export</title>\n<style>\n",
+        "body { font-family: sans-serif; max-width: 48rem; margin: 2rem auto; }\n",
+        "article { border-bottom: 1px solid #ddd; padding: 0.75rem 0; }\n",
+        "header { color: #555; font-size: 0.85rem; }\n",
+        ".content { white-space: pre-wrap; }\n",
+        "img { max-width: 100%; }\n",
+        "</style>\n</head>\n<body>\n<h1>Conversation Export</h1>\n",
+    ));
+    for msg in messages {
+        html.push_str(&format!(
+            "<article>\n<header><strong>{}</strong> <time>{}</time></header>\n<div class=\"content\">{}</div>\n",
+            escape(super::author(users, msg)),
+            super::timestamp(msg),
+            escape(&msg.content)
+        ));
+        for attachment in &msg.attachments {
+            match assets.get(&attachment.file_id) {
+                Some(asset) => html.push_str(&format!(
+                    "<figure><img src=\"data:{};base64,{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>\n",
+                    escape(&asset.content_type),
+                    base64::engine::general_purpose::STANDARD.encode(&asset.bytes),
+                    escape(&attachment.filename),
+                    escape(&attachment.filename)
+                )),
+                None => html.push_str(&format!(
+                    "<p class=\"attachment\">Attachment: {} ({} bytes)</p>\n",
+                    escape(&attachment.filename),
+                    attachment.size
+                )),
+            }
+        }
+        for embed in super::embeds(msg) {
+            html.push_str(&format!("<p class=\"embed\">{}</p>\n", escape(&embed)));
+        }
+        if !msg.reaction_summary.is_empty() {
+            html.push_str(&format!(
+                "<p class=\"reactions\">{}</p>\n",
+                escape(&super::reactions(msg, "  "))
+            ));
+        }
+        html.push_str("</article>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html.into_bytes()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_text_and_inlines_assets() {
+        let now = bson::DateTime::now();
+        let file_id = ObjectId::new();
+        let msg: Message = bson::from_document(bson::doc! {
+            "tenant_id": ObjectId::new(),
+            "room_id": ObjectId::new(),
+            "author_id": ObjectId::new(),
+            "content": "<script>alert('x')</script>",
+            "attachments": [{
+                "file_id": file_id,
+                "filename": "dot.png",
+                "content_type": "image/png",
+                "size": 3_i64,
+                "url": "/api/file/x",
+                "thumbnail_url": null,
+            }],
+            "created_at": now,
+            "updated_at": now,
+        })
+        .unwrap();
+        let assets = HashMap::from([(
+            file_id,
+            EmbeddedAsset {
+                content_type: "image/png".to_string(),
+                bytes: b"png".to_vec(),
+            },
+        )]);
+        let html =
+            String::from_utf8(export_conversation(&[msg], &HashMap::new(), &assets)).unwrap();
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("src=\"data:image/png;base64,cG5n\""));
+    }
+}
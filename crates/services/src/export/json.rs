@@ -0,0 +1,45 @@
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, User};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Export conversation messages as a JSON document:
+/// `{ "messages": [{ id, timestamp, author, type, content, ... }] }`.
+/// Authors appear by display name only, so anonymized exports stay anonymous.
+pub fn export_conversation(
+    messages: &[Message],
+    users: &HashMap<ObjectId, User>,
+) -> Result<Vec<u8>, String> {
+    let messages: Vec<_> = messages
+        .iter()
+        .map(|msg| {
+            json!({
+                "id": msg.id.map(|id| id.to_hex()),
+                "timestamp": msg.created_at.try_to_rfc3339_string().ok(),
+                "author": super::author(users, msg),
+                "type": format!("{:?}", msg.message_type),
+                "content": msg.content,
+                "thread_id": msg.thread_id.map(|id| id.to_hex()),
+                "is_edited": msg.is_edited,
+                "reactions": msg
+                    .reaction_summary
+                    .iter()
+                    .map(|r| json!({ "emoji": r.emoji, "count": r.count }))
+                    .collect::<Vec<_>>(),
+                "attachments": msg
+                    .attachments
+                    .iter()
+                    .map(|a| json!({
+                        "filename": a.filename,
+                        "content_type": a.content_type,
+                        "size": a.size,
+                    }))
+                    .collect::<Vec<_>>(),
+                "embeds": msg.embeds,
+            })
+        })
+        .collect();
+
+    serde_json::to_vec_pretty(&json!({ "messages": messages }))
+        .map_err(|e| format!("JSON export failed: {}", e))
+}
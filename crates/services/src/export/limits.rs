@@ -0,0 +1,78 @@
+//! Per-tenant cap on exports running at once on this instance, so one
+//! tenant's exports can't tie up every worker.
+
+use std::sync::Arc;
+
+use bson::oid::ObjectId;
+use dashmap::{DashMap, mapref::entry::Entry};
+
+pub struct ExportSlots {
+    max_per_tenant: usize,
+    running: DashMap<ObjectId, usize>,
+}
+
+/// One running export. Its slot frees when this is dropped, so a background
+/// export holds it until the task finishes.
+pub struct ExportPermit {
+    slots: Arc<ExportSlots>,
+    tenant_id: ObjectId,
+}
+
+impl ExportSlots {
+    pub fn new(max_per_tenant: u32) -> Self {
+        Self {
+            max_per_tenant: (max_per_tenant as usize).max(1),
+            running: DashMap::new(),
+        }
+    }
+
+    /// A slot for one export in `tenant_id`, or `None` while the tenant
+    /// already has its maximum running.
+    pub fn try_acquire(self: &Arc<Self>, tenant_id: ObjectId) -> Option<ExportPermit> {
+        let mut running = self.running.entry(tenant_id).or_default();
+        if *running >= self.max_per_tenant {
+            return None;
+        }
+        *running += 1;
+        Some(ExportPermit {
+            slots: Arc::clone(self),
+            tenant_id,
+        })
+    }
+
+    pub fn running(&self, tenant_id: &ObjectId) -> usize {
+        self.running.get(tenant_id).map(|n| *n).unwrap_or(0)
+    }
+}
+
+impl Drop for ExportPermit {
+    fn drop(&mut self) {
+        if let Entry::Occupied(mut entry) = self.slots.running.entry(self.tenant_id) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_capped_per_tenant_and_freed_on_drop() {
+        let slots = Arc::new(ExportSlots::new(2));
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+
+        let first = slots.try_acquire(a).unwrap();
+        let _second = slots.try_acquire(a).unwrap();
+        assert!(slots.try_acquire(a).is_none());
+        // Other tenants have their own budget.
+        assert!(slots.try_acquire(b).is_some());
+
+        drop(first);
+        assert_eq!(slots.running(&a), 1);
+        assert!(slots.try_acquire(a).is_some());
+    }
+}
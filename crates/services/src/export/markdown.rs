@@ -0,0 +1,34 @@
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, User};
+use std::collections::HashMap;
+
+/// Export conversation messages as Markdown. Message text is kept as
+/// written, since messages are authored in Markdown already.
+pub fn export_conversation(messages: &[Message], users: &HashMap<ObjectId, User>) -> Vec<u8> {
+    let mut md = String::from("# Conversation Export\n");
+    for msg in messages {
+        md.push_str(&format!(
+            "\n**{}** · {}\n\n",
+            super::author(users, msg),
+            super::timestamp(msg)
+        ));
+        md.push_str(msg.content.trim_end());
+        md.push('\n');
+        for attachment in &msg.attachments {
+            md.push_str(&format!(
+                "\n- Attachment: {} ({} bytes)",
+                attachment.filename, attachment.size
+            ));
+        }
+        for embed in super::embeds(msg) {
+            md.push_str(&format!("\n- {}", embed));
+        }
+        if !msg.attachments.is_empty() || !msg.embeds.is_empty() {
+            md.push('\n');
+        }
+        if !msg.reaction_summary.is_empty() {
+            md.push_str(&format!("\n_Reactions: {}_\n", super::reactions(msg, "  ")));
+        }
+    }
+    md.into_bytes()
+}
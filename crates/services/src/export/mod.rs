@@ -1,2 +1,39 @@
+pub mod csv;
 pub mod excel;
+pub mod html;
+pub mod json;
+pub mod limits;
+pub mod markdown;
 pub mod pdf;
+pub mod redact;
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Message, User};
+use std::collections::HashMap;
+
+fn author<'a>(users: &'a HashMap<ObjectId, User>, msg: &Message) -> &'a str {
+    users
+        .get(&msg.author_id)
+        .map(|u| u.display_name.as_str())
+        .unwrap_or("Unknown")
+}
+
+fn timestamp(msg: &Message) -> String {
+    msg.created_at
+        .to_chrono()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// One line per embed, see [`crate::embeds::describe`].
+fn embeds(msg: &Message) -> Vec<String> {
+    msg.embeds.iter().map(crate::embeds::describe).collect()
+}
+
+fn reactions(msg: &Message, separator: &str) -> String {
+    msg.reaction_summary
+        .iter()
+        .map(|r| format!("{} {}", r.emoji, r.count))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
@@ -27,6 +27,9 @@ pub fn export_conversation(
 
         pdf.add_text(&format!("[{}] {}", timestamp, author), 9.0, true);
         pdf.add_text(&msg.content, 10.0, false);
+        for embed in super::embeds(msg) {
+            pdf.add_text(&embed, 9.0, false);
+        }
 
         if !msg.reaction_summary.is_empty() {
             let reactions: String = msg
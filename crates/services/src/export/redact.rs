@@ -0,0 +1,238 @@
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{Embed, Message, User};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const EMAIL_PLACEHOLDER: &str = "[email]";
+const PHONE_PLACEHOLDER: &str = "[phone]";
+
+/// Redaction stage run before an export is rendered. Replaces every known
+/// identity with a pseudonym and strips emails and phone numbers from message
+/// text, so the exporters themselves stay unaware of anonymization.
+///
+/// Pseudonyms are derived from the tenant and user ids, so the same person
+/// gets the same pseudonym in every export from that tenant.
+pub struct Anonymizer {
+    tenant_id: ObjectId,
+    pseudonyms: HashMap<ObjectId, String>,
+    /// (identity string, pseudonym), longest first so "Ann Lee" wins over "Ann".
+    identities: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    pub fn new(tenant_id: ObjectId, users: &HashMap<ObjectId, User>) -> Self {
+        let mut pseudonyms = HashMap::new();
+        let mut identities = Vec::new();
+        for (id, user) in users {
+            let pseudonym = pseudonym_for(tenant_id, *id);
+            for identity in [&user.display_name, &user.username] {
+                // Very short names would match inside ordinary words.
+                if identity.chars().count() >= 3 {
+                    identities.push((identity.clone(), pseudonym.clone()));
+                }
+            }
+            pseudonyms.insert(*id, pseudonym);
+        }
+        identities.sort_by_key(|i| std::cmp::Reverse(i.0.len()));
+        Self {
+            tenant_id,
+            pseudonyms,
+            identities,
+        }
+    }
+
+    pub fn pseudonym(&self, user_id: ObjectId) -> String {
+        self.pseudonyms
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| pseudonym_for(self.tenant_id, user_id))
+    }
+
+    /// Anonymized copies of `messages` and `users`, ready for any exporter.
+    pub fn apply(
+        &self,
+        messages: &[Message],
+        users: &HashMap<ObjectId, User>,
+    ) -> (Vec<Message>, HashMap<ObjectId, User>) {
+        let messages = messages
+            .iter()
+            .map(|m| {
+                let mut m = m.clone();
+                m.content = self.redact_text(&m.content);
+                for attachment in &mut m.attachments {
+                    attachment.filename = self.redact_text(&attachment.filename);
+                }
+                // Locations and contact cards identify people; key-value
+                // cards are kept, redacted like the message text.
+                m.embeds.retain(|e| matches!(e, Embed::Fields { .. }));
+                for embed in &mut m.embeds {
+                    if let Embed::Fields { title, fields, .. } = embed {
+                        if let Some(title) = title {
+                            *title = self.redact_text(title);
+                        }
+                        for field in fields {
+                            field.value = self.redact_text(&field.value);
+                        }
+                    }
+                }
+                m
+            })
+            .collect();
+        let users = users
+            .iter()
+            .map(|(id, u)| {
+                let mut u = u.clone();
+                let pseudonym = self.pseudonym(*id);
+                u.display_name = pseudonym.clone();
+                u.username = pseudonym;
+                u.email = String::new();
+                u.avatar = None;
+                u.bio = None;
+                u.status = Default::default();
+                u.oauth_providers = Vec::new();
+                (*id, u)
+            })
+            .collect();
+        (messages, users)
+    }
+
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for token in text.split_inclusive(char::is_whitespace) {
+            let word = token.trim_end_matches(char::is_whitespace);
+            let trailing = &token[word.len()..];
+            out.push_str(&redact_pii(word));
+            out.push_str(trailing);
+        }
+        for (identity, pseudonym) in &self.identities {
+            out = replace_word(&out, identity, pseudonym);
+        }
+        out
+    }
+}
+
+fn pseudonym_for(tenant_id: ObjectId, user_id: ObjectId) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tenant_id.bytes());
+    hasher.update(user_id.bytes());
+    let digest = hex::encode(hasher.finalize());
+    format!("User-{}", &digest[..8])
+}
+
+/// Replace a whitespace-delimited word if it is an email or phone number,
+/// keeping any surrounding punctuation.
+fn redact_pii(word: &str) -> String {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '+');
+    if core.is_empty() {
+        return word.to_string();
+    }
+    let placeholder = if is_email(core) {
+        EMAIL_PLACEHOLDER
+    } else if is_phone(core) {
+        PHONE_PLACEHOLDER
+    } else {
+        return word.to_string();
+    };
+    word.replacen(core, placeholder, 1)
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+fn is_phone(s: &str) -> bool {
+    let digits = s.chars().filter(char::is_ascii_digit).count();
+    (7..=15).contains(&digits)
+        && s.chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | '(' | ')'))
+}
+
+/// Replace whole-word occurrences of `needle` (case-insensitive ASCII).
+fn replace_word(haystack: &str, needle: &str, replacement: &str) -> String {
+    let lower = haystack.to_ascii_lowercase();
+    let needle_lower = needle.to_ascii_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+    let mut out = String::with_capacity(haystack.len());
+    let mut last = 0;
+    for (start, _) in lower.match_indices(&needle_lower) {
+        let end = start + needle_lower.len();
+        if start < last
+            || is_word(haystack[..start].chars().next_back())
+            || is_word(haystack[end..].chars().next())
+        {
+            continue;
+        }
+        out.push_str(&haystack[last..start]);
+        out.push_str(replacement);
+        last = end;
+    }
+    out.push_str(&haystack[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anonymizer() -> (Anonymizer, ObjectId) {
+        let tenant_id = ObjectId::new();
+        let user_id = ObjectId::new();
+        let now = bson::DateTime::now();
+        let user: User = bson::from_document(bson::doc! {
+            "_id": user_id,
+            "email": "ann@example.com",
+            "username": "ann_lee",
+            "display_name": "Ann Lee",
+            "avatar": null,
+            "bio": null,
+            "last_active_at": null,
+            "created_at": now,
+            "updated_at": now,
+            "deleted_at": null,
+        })
+        .unwrap();
+        let users = HashMap::from([(user_id, user)]);
+        (Anonymizer::new(tenant_id, &users), user_id)
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_per_tenant() {
+        let tenant_id = ObjectId::new();
+        let user_id = ObjectId::new();
+        assert_eq!(
+            pseudonym_for(tenant_id, user_id),
+            pseudonym_for(tenant_id, user_id)
+        );
+        assert_ne!(
+            pseudonym_for(tenant_id, user_id),
+            pseudonym_for(ObjectId::new(), user_id)
+        );
+    }
+
+    #[test]
+    fn strips_emails_phones_and_names() {
+        let (anon, user_id) = anonymizer();
+        let pseudonym = anon.pseudonym(user_id);
+        let text = "Ping Ann Lee (ann@example.com) or @ann_lee, call +1 555-123-4567.";
+        assert_eq!(
+            anon.redact_text(text),
+            format!("Ping {pseudonym} ([email]) or @{pseudonym}, call +1 [phone].")
+        );
+    }
+
+    #[test]
+    fn leaves_partial_words_alone() {
+        let (anon, _) = anonymizer();
+        assert_eq!(anon.redact_text("Annual review"), "Annual review");
+    }
+}
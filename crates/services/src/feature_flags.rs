@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bson::oid::ObjectId;
+use mongodb::Database;
+use roomler_ai_config::FeatureFlagSettings;
+use roomler_ai_db::models::{FeatureFlag, FeatureFlagOverride};
+use serde::Serialize;
+
+use crate::dao::base::DaoResult;
+use crate::dao::feature_flag::FeatureFlagDao;
+
+/// Where an evaluated flag value came from, most specific last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Config,
+    Global,
+    Tenant,
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FlagValue {
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+/// The resolved flag set for one (tenant, user) pair. Unknown keys evaluate
+/// to `false`, so callers can gate code on a flag before it is defined.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct FlagSet(BTreeMap<String, FlagValue>);
+
+impl FlagSet {
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.0.get(key).is_some_and(|v| v.enabled)
+    }
+
+    pub fn get(&self, key: &str) -> Option<FlagValue> {
+        self.0.get(key).copied()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &FlagValue)> {
+        self.0.iter()
+    }
+}
+
+/// Evaluates feature flags. Precedence, lowest to highest:
+/// `features.defaults` config → `feature_flags` row → tenant override →
+/// user override. Routes read flags through the `Flags` API extractor.
+pub struct FeatureFlagService {
+    pub dao: FeatureFlagDao,
+    defaults: HashMap<String, bool>,
+}
+
+impl FeatureFlagService {
+    pub fn new(db: &Database, settings: &FeatureFlagSettings) -> Self {
+        Self {
+            dao: FeatureFlagDao::new(db),
+            defaults: settings.defaults.clone(),
+        }
+    }
+
+    pub async fn evaluate(
+        &self,
+        tenant_id: Option<ObjectId>,
+        user_id: Option<ObjectId>,
+    ) -> DaoResult<FlagSet> {
+        let flags = self.dao.list_flags().await?;
+        let overrides = match tenant_id {
+            Some(tid) => self.dao.find_overrides(tid, user_id).await?,
+            None => Vec::new(),
+        };
+        Ok(resolve(&self.defaults, &flags, &overrides))
+    }
+
+    /// Single-flag convenience wrapper for code without a `Flags`
+    /// extractor. Storage errors evaluate to `false` so a Mongo hiccup
+    /// never switches an unreleased feature on.
+    pub async fn is_enabled(
+        &self,
+        key: &str,
+        tenant_id: Option<ObjectId>,
+        user_id: Option<ObjectId>,
+    ) -> bool {
+        match self.evaluate(tenant_id, user_id).await {
+            Ok(set) => set.is_enabled(key),
+            Err(e) => {
+                tracing::warn!(key, %e, "Feature flag evaluation failed");
+                false
+            }
+        }
+    }
+
+    /// Whether `key` is defined in config or the `feature_flags` collection.
+    pub async fn is_known(&self, key: &str) -> DaoResult<bool> {
+        if self.defaults.contains_key(key) {
+            return Ok(true);
+        }
+        Ok(self.dao.find_flag(key).await?.is_some())
+    }
+}
+
+/// Pure precedence resolution, split out of [`FeatureFlagService::evaluate`]
+/// so it can be tested without MongoDB. `overrides` must already be scoped
+/// to a single tenant and (at most) a single user.
+pub fn resolve(
+    defaults: &HashMap<String, bool>,
+    flags: &[FeatureFlag],
+    overrides: &[FeatureFlagOverride],
+) -> FlagSet {
+    let mut out = BTreeMap::new();
+
+    for (key, enabled) in defaults {
+        out.insert(
+            key.clone(),
+            FlagValue {
+                enabled: *enabled,
+                source: FlagSource::Config,
+            },
+        );
+    }
+    for flag in flags {
+        out.insert(
+            flag.key.clone(),
+            FlagValue {
+                enabled: flag.enabled,
+                source: FlagSource::Global,
+            },
+        );
+    }
+    // Tenant-level first so user-level rows overwrite them.
+    for o in overrides.iter().filter(|o| o.user_id.is_none()) {
+        out.insert(
+            o.key.clone(),
+            FlagValue {
+                enabled: o.enabled,
+                source: FlagSource::Tenant,
+            },
+        );
+    }
+    for o in overrides.iter().filter(|o| o.user_id.is_some()) {
+        out.insert(
+            o.key.clone(),
+            FlagValue {
+                enabled: o.enabled,
+                source: FlagSource::User,
+            },
+        );
+    }
+
+    FlagSet(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::DateTime;
+
+    fn flag(key: &str, enabled: bool) -> FeatureFlag {
+        FeatureFlag {
+            id: None,
+            key: key.to_string(),
+            description: None,
+            enabled,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    fn ovr(key: &str, user_id: Option<ObjectId>, enabled: bool) -> FeatureFlagOverride {
+        FeatureFlagOverride {
+            id: None,
+            key: key.to_string(),
+            tenant_id: ObjectId::new(),
+            user_id,
+            enabled,
+            set_by: ObjectId::new(),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn unknown_flag_is_disabled() {
+        let set = resolve(&HashMap::new(), &[], &[]);
+        assert!(!set.is_enabled("new_search"));
+        assert!(!set.contains("new_search"));
+    }
+
+    #[test]
+    fn db_flag_overrides_config_default() {
+        let defaults = HashMap::from([("new_search".to_string(), false)]);
+        let set = resolve(&defaults, &[flag("new_search", true)], &[]);
+        assert_eq!(
+            set.get("new_search"),
+            Some(FlagValue {
+                enabled: true,
+                source: FlagSource::Global
+            })
+        );
+    }
+
+    #[test]
+    fn user_override_beats_tenant_override() {
+        let uid = ObjectId::new();
+        // User row listed first to prove ordering in the input doesn't matter.
+        let overrides = [
+            ovr("new_search", Some(uid), true),
+            ovr("new_search", None, false),
+        ];
+        let set = resolve(&HashMap::new(), &[flag("new_search", false)], &overrides);
+        let v = set.get("new_search").unwrap();
+        assert!(v.enabled);
+        assert_eq!(v.source, FlagSource::User);
+    }
+
+    #[test]
+    fn tenant_override_applies_without_user_row() {
+        let defaults = HashMap::from([("new_search".to_string(), true)]);
+        let set = resolve(&defaults, &[], &[ovr("new_search", None, false)]);
+        let v = set.get("new_search").unwrap();
+        assert!(!v.enabled);
+        assert_eq!(v.source, FlagSource::Tenant);
+    }
+}
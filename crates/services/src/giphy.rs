@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::outbound::{Outbound, OutboundClient};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GiphyResponse {
     pub data: Vec<GiphyGif>,
@@ -34,14 +36,14 @@ pub struct GiphyPagination {
 }
 
 pub struct GiphyService {
-    client: reqwest::Client,
+    client: OutboundClient,
     api_key: String,
 }
 
 impl GiphyService {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, outbound: &Outbound) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: outbound.client("giphy"),
             api_key,
         }
     }
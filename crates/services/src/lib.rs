@@ -1,22 +1,70 @@
+pub mod account_deletion;
+pub mod analytics;
+pub mod api_quota;
+pub mod assets;
 pub mod auth;
 pub mod background;
+pub mod bot_tokens;
+pub mod calendar;
+pub mod channel_digest;
 pub mod cloud_storage;
+pub mod conference_limits;
+pub mod conference_lobby;
+pub mod conference_polls;
+pub mod conference_waitlist;
+pub mod connection_auth;
 pub mod dao;
 pub mod document_recognition;
 pub mod email;
+pub mod embeds;
+pub mod emoji;
 pub mod export;
+pub mod feature_flags;
 pub mod giphy;
 pub mod media;
+pub mod member_import;
+pub mod mentions;
+pub mod message_archive;
+pub mod message_retention;
 pub mod oauth;
+pub mod object_storage;
+pub mod onboarding;
+pub mod outbound;
+pub mod plan_usage;
+pub mod presence;
+pub mod profanity;
+pub mod public_address;
 pub mod push;
+pub mod quick_switch;
+pub mod reaction_rules;
+pub mod read_only_schedule;
+pub mod reconciliation;
+pub mod relay_usage;
+pub mod recording_access;
+pub mod recording_clip;
+pub mod recording_playback;
+pub mod recording_upload;
+pub mod sandbox;
+pub mod shared_drafts;
+pub mod sso;
 pub mod stripe;
+pub mod tenant_config;
+pub mod thread_summary;
+pub mod transcription;
+pub mod webhooks;
 
 pub use auth::AuthService;
 pub use background::TaskService;
 pub use dao::*;
 pub use document_recognition::RecognitionService;
 pub use email::EmailService;
+pub use feature_flags::FeatureFlagService;
 pub use giphy::GiphyService;
 pub use oauth::OAuthService;
+pub use object_storage::ObjectStore;
+pub use onboarding::OnboardingService;
 pub use push::PushService;
+pub use recording_upload::RecordingUploadService;
 pub use stripe::StripeService;
+pub use tenant_config::TenantConfigService;
+pub use transcription::TranscriptionService;
@@ -0,0 +1,140 @@
+//! Quality signals of live transcript segments.
+//!
+//! The transcript producer measures each segment's audio and attaches the
+//! result to its [`TranscriptEvent`](super::transcript_feed::TranscriptEvent).
+//! Final segments are logged to the capped `asr_metrics` collection. With
+//! sample retention on, the audio of low-confidence segments from speakers
+//! who agreed to share voice samples is kept as well, to build an
+//! evaluation set for tuning the model. [`SampleRetention`] decides which
+//! segments qualify; the speaker's consent is checked by the caller.
+
+use std::sync::Arc;
+
+use roomler_ai_config::AsrSettings;
+
+/// Level reported for silence, which has no finite dBFS value.
+pub const SILENCE_DBFS: f64 = -96.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentQuality {
+    /// Audio level of the segment; 0 dBFS is full scale.
+    pub rms_dbfs: f64,
+    /// Length of the speech voice activity detection reported, in ms.
+    pub vad_ms: u64,
+    /// How far the detected language's score is ahead of the runner-up.
+    /// Small values mean the language was a close call.
+    pub language_score_delta: Option<f64>,
+    /// The segment's audio as WAV, attached while sample retention is on.
+    pub audio: Option<Arc<Vec<u8>>>,
+}
+
+/// RMS level of 16-bit PCM samples in dBFS, floored at [`SILENCE_DBFS`].
+pub fn rms_dbfs(samples: &[i16]) -> f64 {
+    if samples.is_empty() {
+        return SILENCE_DBFS;
+    }
+    let sum: f64 = samples
+        .iter()
+        .map(|&s| {
+            let s = s as f64 / 32768.0;
+            s * s
+        })
+        .sum();
+    let rms = (sum / samples.len() as f64).sqrt();
+    if rms == 0.0 {
+        return SILENCE_DBFS;
+    }
+    (20.0 * rms.log10()).max(SILENCE_DBFS)
+}
+
+/// Gap between the best and second-best language probabilities a backend
+/// reported. `None` with fewer than two scores.
+pub fn language_score_delta(scores: &[f64]) -> Option<f64> {
+    let mut best = f64::NEG_INFINITY;
+    let mut second = f64::NEG_INFINITY;
+    for &score in scores {
+        if score > best {
+            second = best;
+            best = score;
+        } else if score > second {
+            second = score;
+        }
+    }
+    (scores.len() >= 2).then_some(best - second)
+}
+
+/// Which segments' audio is kept as an evaluation sample.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleRetention {
+    pub enabled: bool,
+    pub max_confidence: f64,
+    pub max_bytes: u64,
+}
+
+impl SampleRetention {
+    pub fn new(settings: &AsrSettings) -> Self {
+        Self {
+            enabled: settings.sample_retention,
+            max_confidence: settings.sample_max_confidence,
+            max_bytes: settings.sample_max_bytes,
+        }
+    }
+
+    /// Whether a segment with `confidence` and `audio_bytes` of audio
+    /// qualifies. Segments without a confidence score never do.
+    pub fn qualifies(&self, confidence: Option<f64>, audio_bytes: usize) -> bool {
+        self.enabled
+            && confidence.is_some_and(|c| c < self.max_confidence)
+            && audio_bytes > 0
+            && audio_bytes as u64 <= self.max_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rms_of_full_scale_square_wave_is_zero_dbfs() {
+        let samples: Vec<i16> = (0..100)
+            .map(|i| if i % 2 == 0 { i16::MAX } else { -i16::MAX })
+            .collect();
+        assert!(rms_dbfs(&samples).abs() < 0.01);
+        let half: Vec<i16> = samples.iter().map(|s| s / 2).collect();
+        assert!((rms_dbfs(&half) + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn silence_is_floored() {
+        assert_eq!(rms_dbfs(&[]), SILENCE_DBFS);
+        assert_eq!(rms_dbfs(&[0; 160]), SILENCE_DBFS);
+        assert_eq!(rms_dbfs(&[1]), SILENCE_DBFS);
+    }
+
+    #[test]
+    fn language_delta_is_best_minus_runner_up() {
+        let delta = language_score_delta(&[0.1, 0.7, 0.15]).unwrap();
+        assert!((delta - 0.55).abs() < 1e-9);
+        assert_eq!(language_score_delta(&[0.9]), None);
+        assert_eq!(language_score_delta(&[0.5, 0.5]), Some(0.0));
+    }
+
+    #[test]
+    fn only_enabled_low_confidence_short_segments_qualify() {
+        let retention = SampleRetention {
+            enabled: true,
+            max_confidence: 0.6,
+            max_bytes: 1000,
+        };
+        assert!(retention.qualifies(Some(0.4), 500));
+        assert!(!retention.qualifies(Some(0.6), 500));
+        assert!(!retention.qualifies(None, 500));
+        assert!(!retention.qualifies(Some(0.4), 1001));
+        assert!(!retention.qualifies(Some(0.4), 0));
+        let off = SampleRetention {
+            enabled: false,
+            ..retention
+        };
+        assert!(!off.qualifies(Some(0.4), 500));
+    }
+}
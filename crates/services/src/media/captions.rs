@@ -0,0 +1,140 @@
+//! Caption tracks for live transcripts.
+//!
+//! Every call offers the `original` track (segments in the language that was
+//! spoken). When the room's `media_settings.caption_languages` is non-empty,
+//! translation is enabled and each listed language is offered as a further
+//! track. Each connection in a call follows at most one track and only
+//! receives `media:transcript` events for it. Connections start on
+//! `original`, or on no track at all when the room has `private_captions`.
+
+use roomler_ai_db::models::MediaSettings;
+
+/// Track carrying untranslated segments; every connection starts on it.
+pub const ORIGINAL_TRACK: &str = "original";
+
+/// Normalizes a BCP 47 language tag (`en`, `pt-BR`, `zh-Hant`) to lowercase,
+/// or `None` when it isn't shaped like one.
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_ascii_lowercase();
+    let mut subtags = tag.split('-');
+    let primary = subtags.next()?;
+    if !(2..=3).contains(&primary.len()) || !primary.bytes().all(|b| b.is_ascii_lowercase()) {
+        return None;
+    }
+    for subtag in subtags {
+        if !(2..=8).contains(&subtag.len()) || !subtag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return None;
+        }
+    }
+    Some(tag)
+}
+
+/// Tracks offered in a room: `original` first, then each valid configured
+/// language once, in configured order.
+pub fn available_tracks(settings: Option<&MediaSettings>) -> Vec<String> {
+    let mut tracks = vec![ORIGINAL_TRACK.to_string()];
+    for language in settings
+        .map(|s| s.caption_languages.as_slice())
+        .unwrap_or_default()
+    {
+        if let Some(language) = normalize_language(language)
+            && !tracks.contains(&language)
+        {
+            tracks.push(language);
+        }
+    }
+    tracks
+}
+
+/// The track a connection follows when it joins the call.
+pub fn initial_track(settings: Option<&MediaSettings>) -> Option<String> {
+    if settings.is_some_and(|s| s.private_captions) {
+        None
+    } else {
+        Some(ORIGINAL_TRACK.to_string())
+    }
+}
+
+/// The offered track matching `requested`, if any.
+pub fn resolve_track(requested: &str, settings: Option<&MediaSettings>) -> Option<String> {
+    let requested = requested.trim();
+    if requested.eq_ignore_ascii_case(ORIGINAL_TRACK) {
+        return Some(ORIGINAL_TRACK.to_string());
+    }
+    let language = normalize_language(requested)?;
+    available_tracks(settings)
+        .into_iter()
+        .find(|t| *t == language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(languages: &[&str]) -> MediaSettings {
+        MediaSettings {
+            audio_enabled: true,
+            video_enabled: true,
+            screen_share_enabled: true,
+            recording_enabled: false,
+            max_participants: None,
+            caption_languages: languages.iter().map(|l| l.to_string()).collect(),
+            private_captions: false,
+        }
+    }
+
+    #[test]
+    fn normalize_accepts_and_lowercases_tags() {
+        assert_eq!(normalize_language("en").as_deref(), Some("en"));
+        assert_eq!(normalize_language(" pt-BR ").as_deref(), Some("pt-br"));
+        assert_eq!(
+            normalize_language("zh-Hant-TW").as_deref(),
+            Some("zh-hant-tw")
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_malformed_tags() {
+        assert_eq!(normalize_language(""), None);
+        assert_eq!(normalize_language("e"), None);
+        assert_eq!(normalize_language("english"), None);
+        assert_eq!(normalize_language("en-"), None);
+        assert_eq!(normalize_language("en_US"), None);
+        assert_eq!(normalize_language("1n"), None);
+    }
+
+    #[test]
+    fn only_original_without_translation() {
+        assert_eq!(available_tracks(None), vec!["original"]);
+        assert_eq!(available_tracks(Some(&settings(&[]))), vec!["original"]);
+    }
+
+    #[test]
+    fn translated_tracks_are_deduplicated_and_validated() {
+        let s = settings(&["de", "DE", "not a tag", "fr-CA"]);
+        assert_eq!(available_tracks(Some(&s)), vec!["original", "de", "fr-ca"]);
+    }
+
+    #[test]
+    fn resolve_matches_offered_tracks_only() {
+        let s = settings(&["de"]);
+        assert_eq!(
+            resolve_track("Original", Some(&s)).as_deref(),
+            Some("original")
+        );
+        assert_eq!(resolve_track("DE", Some(&s)).as_deref(), Some("de"));
+        assert_eq!(resolve_track("fr", Some(&s)), None);
+        assert_eq!(resolve_track("de", None), None);
+    }
+
+    #[test]
+    fn private_captions_start_unsubscribed() {
+        assert_eq!(initial_track(None).as_deref(), Some(ORIGINAL_TRACK));
+        let mut s = settings(&["de"]);
+        assert_eq!(initial_track(Some(&s)).as_deref(), Some(ORIGINAL_TRACK));
+        s.private_captions = true;
+        assert_eq!(initial_track(Some(&s)), None);
+        // Opting in still works for every offered track.
+        assert_eq!(resolve_track("de", Some(&s)).as_deref(), Some("de"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Interim transcripts for speech still in progress.
+//!
+//! Final segments only exist once voice activity detection reports the end
+//! of speech, which leaves captions seconds behind the speaker. With an
+//! interim interval configured, the transcript producer re-runs ASR on the
+//! in-progress buffer every interval and publishes the result with
+//! `is_final: false`; clients show it until the next segment for the same
+//! speaker and track replaces it. [`InterimSchedule`] tracks when that is
+//! due for one speaker.
+
+use std::time::{Duration, Instant};
+
+pub struct InterimSchedule {
+    /// `None` when interim results are off.
+    interval: Option<Duration>,
+    /// When the current utterance was last transcribed, or started.
+    last: Option<Instant>,
+}
+
+impl InterimSchedule {
+    /// `interval_ms` of 0 turns interim results off.
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            interval: (interval_ms > 0).then(|| Duration::from_millis(interval_ms)),
+            last: None,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Speech started; the first interim result is due one interval later.
+    pub fn speech_start(&mut self, now: Instant) {
+        self.last = Some(now);
+    }
+
+    /// Whether to transcribe the in-progress buffer now. Counts the run
+    /// when it is due.
+    pub fn due(&mut self, now: Instant) -> bool {
+        let (Some(interval), Some(last)) = (self.interval, self.last) else {
+            return false;
+        };
+        if now.duration_since(last) < interval {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+
+    /// Speech ended; the final segment takes over and nothing more is due
+    /// until the next utterance.
+    pub fn speech_end(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interim_runs_once_per_interval_while_speaking() {
+        let start = Instant::now();
+        let mut schedule = InterimSchedule::new(500);
+        assert!(!schedule.due(start));
+
+        schedule.speech_start(start);
+        assert!(!schedule.due(start + Duration::from_millis(300)));
+        assert!(schedule.due(start + Duration::from_millis(500)));
+        assert!(!schedule.due(start + Duration::from_millis(700)));
+        assert!(schedule.due(start + Duration::from_millis(1000)));
+
+        schedule.speech_end();
+        assert!(!schedule.due(start + Duration::from_millis(2000)));
+    }
+
+    #[test]
+    fn zero_interval_disables_interim_results() {
+        let start = Instant::now();
+        let mut schedule = InterimSchedule::new(0);
+        assert!(!schedule.enabled());
+        schedule.speech_start(start);
+        assert!(!schedule.due(start + Duration::from_secs(10)));
+    }
+}
@@ -1,3 +1,10 @@
+pub mod asr_quality;
+pub mod captions;
+pub mod interim;
 pub mod room_manager;
 pub mod signaling;
+pub mod simulcast;
+pub mod stats;
+pub mod transcript_feed;
+pub mod transcription_session;
 pub mod worker_pool;
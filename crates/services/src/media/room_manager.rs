@@ -1,19 +1,27 @@
 use bson::oid::ObjectId;
 use dashmap::DashMap;
+use mediasoup::consumer::ConsumerLayers;
 use mediasoup::prelude::*;
+use mediasoup::types::data_structures::SocketFlags;
 use mediasoup::webrtc_transport::{
     WebRtcTransportListenInfos, WebRtcTransportOptions, WebRtcTransportRemoteParameters,
 };
 use roomler_ai_config::MediasoupSettings;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::IpAddr;
 use std::num::NonZero;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OnceCell, mpsc};
 use tracing::{debug, info};
 
+use super::simulcast::{self, ProducerLayers};
+use super::stats::{ParticipantStats, StreamStats, TransportStats};
+use super::transcription_session::{Handover, TranscriptionSession};
 use super::worker_pool::WorkerPool;
+use crate::relay_usage::TransportSample;
 
 /// Holds the DirectTransport + Consumer for an RTP tap (transcription).
 struct RtpTap {
@@ -24,17 +32,23 @@ struct RtpTap {
 /// A media room backed by a mediasoup Router.
 pub struct MediaRoom {
     pub router: Router,
+    /// Index of the worker hosting `router`, for port accounting.
+    pub worker_index: usize,
     /// Keyed by connection_id (UUID per WebSocket connection) so the same user
     /// can join from multiple tabs/devices without overwriting state.
     pub participants: DashMap<String, ParticipantMedia>,
     /// RTP taps for transcription, keyed by producer_id string.
     rtp_taps: DashMap<String, RtpTap>,
+    /// Which ASR model transcribes the call.
+    transcription: TranscriptionSession,
 }
 
 /// A producer with its source label (e.g. "camera", "screen", "audio").
 pub struct ProducerEntry {
     pub producer: Producer,
     pub source: String,
+    /// Simulcast/SVC layers it sends.
+    pub layers: ProducerLayers,
 }
 
 /// Media state for a single participant (one WebSocket connection).
@@ -44,6 +58,20 @@ pub struct ParticipantMedia {
     pub recv_transport: WebRtcTransport,
     pub producers: Vec<ProducerEntry>,
     pub consumers: Vec<Consumer>,
+    /// Caption track this connection receives transcripts for; `None`
+    /// until it opts in when the room has private captions.
+    pub caption_track: Option<String>,
+    /// Handed to the client on join; presenting it from a new connection
+    /// while `reconnecting` takes this media state over.
+    pub reconnect_token: String,
+    /// The connection dropped and the participant is held for a reconnect.
+    pub reconnecting: bool,
+    /// How often the connection asked to receive `media:stats`; `None`
+    /// when it hasn't.
+    pub stats_interval: Option<Duration>,
+    /// Transcription of this connection's microphone alone, turned on with
+    /// `media:transcribe_me`.
+    pub self_transcription: TranscriptionSession,
 }
 
 /// Transport connection details sent to the client.
@@ -60,6 +88,21 @@ pub struct TransportOptions {
 pub struct TransportPair {
     pub send_transport: TransportOptions,
     pub recv_transport: TransportOptions,
+    pub reconnect_token: String,
+}
+
+/// A reconnecting participant moved to a new connection.
+#[derive(Debug, Clone)]
+pub struct ReboundParticipant {
+    pub previous_connection_id: String,
+    /// ICE parameters after an ICE restart, for the client's transports.
+    pub send_ice_parameters: serde_json::Value,
+    pub recv_ice_parameters: serde_json::Value,
+    /// Producers the participant still consumes.
+    pub consumed_producer_ids: Vec<ProducerId>,
+    /// Producers that closed while the participant was away; their
+    /// consumers are gone.
+    pub closed_producer_ids: Vec<ProducerId>,
 }
 
 /// Consumer details sent to the client.
@@ -69,6 +112,35 @@ pub struct ConsumerInfo {
     pub producer_id: String,
     pub kind: String,
     pub rtp_parameters: serde_json::Value,
+    /// Layers the consumer can pick from with `media:set_preferred_layers`.
+    pub layers: ProducerLayers,
+}
+
+/// RTC port usage of one worker, as reported by the admin endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerPortUsage {
+    pub worker_index: usize,
+    pub min_port: u16,
+    pub max_port: u16,
+    /// Ports in the worker's slice (= transports it can hold per protocol).
+    pub capacity: u32,
+    pub transports: u32,
+    pub rooms: u32,
+    /// `transports / capacity`, 0.0–1.0.
+    pub utilization: f64,
+}
+
+/// What a user is doing in a call hosted on this instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallActivity {
+    pub room_id: ObjectId,
+    pub screen_sharing: bool,
+}
+
+/// A short-lived transport handed out for a connectivity preflight.
+struct ProbeTransport {
+    user_id: ObjectId,
+    transport: WebRtcTransport,
 }
 
 /// Manages mediasoup rooms and their media state.
@@ -76,9 +148,17 @@ pub struct RoomManager {
     rooms: DashMap<ObjectId, MediaRoom>,
     /// Tracks which room each connection is in (connection_id -> room_id).
     connection_rooms: DashMap<String, ObjectId>,
+    /// When each room's last participant left; cleared when someone joins.
+    emptied: DashMap<ObjectId, Instant>,
+    /// Router hosting preflight probe transports (and its worker index),
+    /// created on first use.
+    probe_router: OnceCell<(usize, Router)>,
+    /// Live probe transports keyed by transport id.
+    probes: Arc<DashMap<String, ProbeTransport>>,
     worker_pool: Arc<WorkerPool>,
     listen_ip: IpAddr,
     announced_ip: Option<String>,
+    udp_reuse_port: bool,
 }
 
 impl RoomManager {
@@ -97,9 +177,13 @@ impl RoomManager {
         Self {
             rooms: DashMap::new(),
             connection_rooms: DashMap::new(),
+            emptied: DashMap::new(),
+            probe_router: OnceCell::new(),
+            probes: Arc::new(DashMap::new()),
             worker_pool,
             listen_ip,
             announced_ip,
+            udp_reuse_port: settings.udp_reuse_port,
         }
     }
 
@@ -112,7 +196,7 @@ impl RoomManager {
             return Ok(serde_json::to_value(caps)?);
         }
 
-        let worker = self.worker_pool.get_worker();
+        let (worker_index, worker) = self.worker_pool.next_worker();
 
         let media_codecs = media_codecs();
         let router_options = RouterOptions::new(media_codecs);
@@ -128,8 +212,10 @@ impl RoomManager {
             room_id,
             MediaRoom {
                 router,
+                worker_index,
                 participants: DashMap::new(),
                 rtp_taps: DashMap::new(),
+                transcription: TranscriptionSession::default(),
             },
         );
 
@@ -138,6 +224,7 @@ impl RoomManager {
 
     /// Removes a room and all its media state.
     pub fn remove_room(&self, room_id: &ObjectId) -> bool {
+        self.emptied.remove(room_id);
         if let Some((_, room)) = self.rooms.remove(room_id) {
             // Clean up connection_rooms mappings
             let conn_ids: Vec<String> = room
@@ -164,6 +251,50 @@ impl RoomManager {
         self.rooms.len()
     }
 
+    /// Live mediasoup workers and the pool's size.
+    pub fn worker_health(&self) -> (usize, usize) {
+        (
+            self.worker_pool.alive_count(),
+            self.worker_pool.worker_count(),
+        )
+    }
+
+    /// Per-worker RTC port utilization. Every participant holds a send and a
+    /// recv WebRtcTransport, each binding one port from its worker's slice.
+    pub fn port_usage(&self) -> Vec<WorkerPortUsage> {
+        let mut usage: Vec<WorkerPortUsage> = self
+            .worker_pool
+            .port_ranges()
+            .iter()
+            .enumerate()
+            .map(|(worker_index, range)| WorkerPortUsage {
+                worker_index,
+                min_port: *range.start(),
+                max_port: *range.end(),
+                capacity: super::worker_pool::range_len(range),
+                transports: 0,
+                rooms: 0,
+                utilization: 0.0,
+            })
+            .collect();
+
+        for room in self.rooms.iter() {
+            if let Some(worker) = usage.get_mut(room.worker_index) {
+                worker.rooms += 1;
+                worker.transports += 2 * room.participants.len() as u32;
+            }
+        }
+        if let Some((worker_index, _)) = self.probe_router.get()
+            && let Some(worker) = usage.get_mut(*worker_index)
+        {
+            worker.transports += self.probes.len() as u32;
+        }
+        for worker in &mut usage {
+            worker.utilization = f64::from(worker.transports) / f64::from(worker.capacity);
+        }
+        usage
+    }
+
     /// Returns a reference to the rooms DashMap (for WS handler to read router capabilities).
     pub fn rooms_ref(&self) -> &DashMap<ObjectId, MediaRoom> {
         &self.rooms
@@ -175,6 +306,7 @@ impl RoomManager {
         room_id: ObjectId,
         user_id: ObjectId,
         connection_id: String,
+        caption_track: Option<String>,
     ) -> anyhow::Result<TransportPair> {
         let room = self
             .rooms
@@ -186,6 +318,7 @@ impl RoomManager {
 
         let send_opts = transport_to_options(&send_transport);
         let recv_opts = transport_to_options(&recv_transport);
+        let reconnect_token = uuid::Uuid::new_v4().to_string();
 
         room.participants.insert(
             connection_id.clone(),
@@ -195,19 +328,83 @@ impl RoomManager {
                 recv_transport,
                 producers: Vec::new(),
                 consumers: Vec::new(),
+                caption_track,
+                reconnect_token: reconnect_token.clone(),
+                reconnecting: false,
+                stats_interval: None,
+                self_transcription: TranscriptionSession::default(),
             },
         );
 
         self.connection_rooms.insert(connection_id.clone(), room_id);
+        self.emptied.remove(&room_id);
 
         debug!(?room_id, ?user_id, %connection_id, "transports created");
 
         Ok(TransportPair {
             send_transport: send_opts,
             recv_transport: recv_opts,
+            reconnect_token,
         })
     }
 
+    /// Creates a standalone WebRtcTransport, bound to the same IPs and port
+    /// ranges as call transports, that `user_id` can connect to before
+    /// joining a call to test connectivity. It is closed after `ttl`.
+    pub async fn create_probe_transport(
+        &self,
+        user_id: ObjectId,
+        ttl: Duration,
+    ) -> anyhow::Result<TransportOptions> {
+        let (_, router) = self
+            .probe_router
+            .get_or_try_init(|| async {
+                let (worker_index, worker) = self.worker_pool.next_worker();
+                let router = worker
+                    .create_router(RouterOptions::new(media_codecs()))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to create probe router: {}", e))?;
+                Ok::<_, anyhow::Error>((worker_index, router))
+            })
+            .await?;
+
+        let transport = self.create_webrtc_transport(router).await?;
+        let options = transport_to_options(&transport);
+        let transport_id = options.id.clone();
+        self.probes
+            .insert(transport_id.clone(), ProbeTransport { user_id, transport });
+
+        // Dropping the transport closes it and frees its ports.
+        let probes = self.probes.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            if probes.remove(&transport_id).is_some() {
+                debug!(%transport_id, "probe transport expired");
+            }
+        });
+
+        debug!(?user_id, transport_id = %options.id, "probe transport created");
+        Ok(options)
+    }
+
+    /// Completes the DTLS handshake setup of a probe transport created for
+    /// `user_id`.
+    pub async fn connect_probe_transport(
+        &self,
+        user_id: ObjectId,
+        transport_id: &str,
+        dtls_parameters: DtlsParameters,
+    ) -> anyhow::Result<()> {
+        let transport = match self.probes.get(transport_id) {
+            Some(probe) if probe.user_id == user_id => probe.transport.clone(),
+            _ => return Err(anyhow::anyhow!("Probe transport not found")),
+        };
+        transport
+            .connect(WebRtcTransportRemoteParameters { dtls_parameters })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect probe transport: {}", e))
+    }
+
     /// Connects a transport with remote DTLS parameters.
     pub async fn connect_transport(
         &self,
@@ -251,7 +448,9 @@ impl RoomManager {
         Ok(())
     }
 
-    /// Creates a Producer on the participant's send transport.
+    /// Creates a Producer on the participant's send transport, starting
+    /// paused when `paused` is set. Returns its id and the layers its
+    /// encodings send.
     pub async fn produce(
         &self,
         room_id: &ObjectId,
@@ -259,7 +458,8 @@ impl RoomManager {
         kind: MediaKind,
         rtp_parameters: RtpParameters,
         source: String,
-    ) -> anyhow::Result<ProducerId> {
+        paused: bool,
+    ) -> anyhow::Result<(ProducerId, ProducerLayers)> {
         let room = self
             .rooms
             .get(room_id)
@@ -270,7 +470,9 @@ impl RoomManager {
             .get_mut(connection_id)
             .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
 
-        let producer_options = ProducerOptions::new(kind, rtp_parameters);
+        let layers = simulcast::layers(&rtp_parameters.encodings);
+        let mut producer_options = ProducerOptions::new(kind, rtp_parameters);
+        producer_options.paused = paused;
         let producer = participant
             .send_transport
             .produce(producer_options)
@@ -281,10 +483,71 @@ impl RoomManager {
         participant.producers.push(ProducerEntry {
             producer,
             source: source.clone(),
+            layers,
         });
 
-        debug!(?room_id, %connection_id, %producer_id, ?kind, %source, "producer created");
-        Ok(producer_id)
+        debug!(?room_id, %connection_id, %producer_id, ?kind, %source, ?layers, "producer created");
+        Ok((producer_id, layers))
+    }
+
+    /// Creates a Producer that takes over `old_producer_id`'s slot: same
+    /// kind, source, position and paused state. The old producer is closed
+    /// (and its consumers with it) only once the new one exists, so a
+    /// failed replacement leaves the connection as it was. Returns the new
+    /// producer's id, kind and source.
+    pub async fn replace_producer(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        old_producer_id: &ProducerId,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<(ProducerId, MediaKind, String)> {
+        let room = self
+            .rooms
+            .get(room_id)
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+
+        let mut participant = room
+            .participants
+            .get_mut(connection_id)
+            .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
+
+        let slot = participant
+            .producers
+            .iter()
+            .position(|pe| &pe.producer.id() == old_producer_id)
+            .ok_or_else(|| anyhow::anyhow!("Producer not found"))?;
+        let old = &participant.producers[slot].producer;
+        let kind = old.kind();
+        let layers = simulcast::layers(&rtp_parameters.encodings);
+        let mut producer_options = ProducerOptions::new(kind, rtp_parameters);
+        producer_options.paused = old.paused();
+
+        let producer = participant
+            .send_transport
+            .produce(producer_options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to produce: {}", e))?;
+
+        let producer_id = producer.id();
+        let source = participant.producers[slot].source.clone();
+        // Dropping the replaced entry closes the old producer.
+        participant.producers[slot] = ProducerEntry {
+            producer,
+            source: source.clone(),
+            layers,
+        };
+
+        debug!(
+            ?room_id,
+            %connection_id,
+            %old_producer_id,
+            %producer_id,
+            ?kind,
+            %source,
+            "producer replaced"
+        );
+        Ok((producer_id, kind, source))
     }
 
     /// Creates a Consumer on the participant's recv transport for a given producer.
@@ -305,6 +568,10 @@ impl RoomManager {
             return Err(anyhow::anyhow!("Cannot consume: incompatible capabilities"));
         }
 
+        let layers = Self::find_producer(&room, &producer_id)
+            .map(|(_, layers)| layers)
+            .unwrap_or(ProducerLayers::SINGLE);
+
         let mut participant = room
             .participants
             .get_mut(connection_id)
@@ -331,6 +598,7 @@ impl RoomManager {
                 MediaKind::Video => "video".to_string(),
             },
             rtp_parameters: serde_json::to_value(consumer.rtp_parameters())?,
+            layers,
         };
 
         participant.consumers.push(consumer);
@@ -345,6 +613,69 @@ impl RoomManager {
         Ok(info)
     }
 
+    /// Asks for lower (or higher) simulcast/SVC layers on one of the
+    /// connection's consumers; see [`simulcast::preferred_layers`]. Returns
+    /// the layers applied, or `None` when the connection has no such
+    /// consumer.
+    pub async fn set_preferred_layers(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        consumer_id: &ConsumerId,
+        spatial_layer: u8,
+        temporal_layer: Option<u8>,
+    ) -> anyhow::Result<Option<ConsumerLayers>> {
+        let (consumer, producer) = {
+            let Some(room) = self.rooms.get(room_id) else {
+                return Ok(None);
+            };
+            let Some(participant) = room.participants.get(connection_id) else {
+                return Ok(None);
+            };
+            let Some(consumer) = participant
+                .consumers
+                .iter()
+                .find(|c| &c.id() == consumer_id)
+                .cloned()
+            else {
+                return Ok(None);
+            };
+            drop(participant);
+            let producer = Self::find_producer(&room, &consumer.producer_id());
+            (consumer, producer)
+        };
+        let Some((source, layers)) = producer else {
+            return Err(anyhow::anyhow!("Producer not found"));
+        };
+        if !layers.is_layered() {
+            return Err(anyhow::anyhow!("Producer sends a single layer"));
+        }
+
+        let preferred = simulcast::preferred_layers(
+            layers,
+            source.starts_with("screen"),
+            spatial_layer,
+            temporal_layer,
+        );
+        consumer.set_preferred_layers(preferred).await?;
+        debug!(?room_id, %connection_id, %consumer_id, ?preferred, "preferred layers set");
+        Ok(Some(preferred))
+    }
+
+    /// Source and layers of a producer anywhere in the room.
+    fn find_producer(
+        room: &MediaRoom,
+        producer_id: &ProducerId,
+    ) -> Option<(String, ProducerLayers)> {
+        room.participants.iter().find_map(|p| {
+            p.value()
+                .producers
+                .iter()
+                .find(|pe| &pe.producer.id() == producer_id)
+                .map(|pe| (pe.source.clone(), pe.layers))
+        })
+    }
+
     /// Closes a specific producer by ID.
     pub fn close_producer(
         &self,
@@ -364,16 +695,485 @@ impl RoomManager {
         false
     }
 
+    /// Kind and source of one of a connection's producers.
+    pub fn producer_kind_and_source(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        producer_id: &ProducerId,
+    ) -> Option<(MediaKind, String)> {
+        let room = self.rooms.get(room_id)?;
+        let participant = room.participants.get(connection_id)?;
+        participant
+            .producers
+            .iter()
+            .find(|pe| &pe.producer.id() == producer_id)
+            .map(|pe| (pe.producer.kind(), pe.source.clone()))
+    }
+
+    /// Pauses (mutes) or resumes one of a connection's producers. Consumers
+    /// of it are paused along with it. Returns the producer's kind and
+    /// source, or `None` when the connection has no such producer.
+    pub async fn set_producer_paused(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        producer_id: &ProducerId,
+        paused: bool,
+    ) -> anyhow::Result<Option<(MediaKind, String)>> {
+        let found = {
+            let Some(room) = self.rooms.get(room_id) else {
+                return Ok(None);
+            };
+            let Some(participant) = room.participants.get(connection_id) else {
+                return Ok(None);
+            };
+            participant
+                .producers
+                .iter()
+                .find(|pe| &pe.producer.id() == producer_id)
+                .map(|pe| (pe.producer.clone(), pe.source.clone()))
+        };
+        let Some((producer, source)) = found else {
+            return Ok(None);
+        };
+
+        if paused {
+            producer.pause().await?;
+        } else {
+            producer.resume().await?;
+        }
+        debug!(?room_id, %connection_id, %producer_id, paused, "producer pause toggled");
+        Ok(Some((producer.kind(), source)))
+    }
+
+    /// Pauses every microphone producer `user_id` has in the room, on all
+    /// of their connections. Screen-share audio is left alone. Returns the
+    /// paused producers as `(connection_id, producer_id, source)`.
+    pub async fn pause_user_audio(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+    ) -> anyhow::Result<Vec<(String, ProducerId, String)>> {
+        let producers: Vec<(String, Producer, String)> = {
+            let Some(room) = self.rooms.get(room_id) else {
+                return Ok(Vec::new());
+            };
+            room.participants
+                .iter()
+                .filter(|e| &e.value().user_id == user_id)
+                .flat_map(|e| {
+                    let conn_id = e.key().clone();
+                    e.value()
+                        .producers
+                        .iter()
+                        .filter(|pe| {
+                            pe.producer.kind() == MediaKind::Audio
+                                && !pe.source.starts_with("screen")
+                        })
+                        .map(|pe| (conn_id.clone(), pe.producer.clone(), pe.source.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+
+        let mut paused = Vec::with_capacity(producers.len());
+        for (conn_id, producer, source) in producers {
+            producer.pause().await?;
+            paused.push((conn_id, producer.id(), source));
+        }
+        debug!(
+            ?room_id,
+            ?user_id,
+            count = paused.len(),
+            "user audio paused"
+        );
+        Ok(paused)
+    }
+
+    /// Connection IDs `user_id` is in the room with.
+    pub fn user_connection_ids(&self, room_id: &ObjectId, user_id: &ObjectId) -> Vec<String> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.participants
+                    .iter()
+                    .filter(|e| &e.value().user_id == user_id)
+                    .map(|e| e.key().clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Removes a participant's media state from a room.
     pub fn close_participant(&self, room_id: &ObjectId, connection_id: &str) {
         if let Some(room) = self.rooms.get(room_id) {
             // Dropping the ParticipantMedia closes transports/producers/consumers
             room.participants.remove(connection_id);
+            self.mark_if_empty(room_id, &room);
         }
         self.connection_rooms.remove(connection_id);
         debug!(?room_id, %connection_id, "participant media closed");
     }
 
+    /// How long the room has had no participants, if its last one left.
+    pub fn empty_for(&self, room_id: &ObjectId) -> Option<Duration> {
+        self.emptied.get(room_id).map(|since| since.elapsed())
+    }
+
+    fn mark_if_empty(&self, room_id: &ObjectId, room: &MediaRoom) {
+        if room.participants.is_empty() {
+            self.emptied.entry(*room_id).or_insert_with(Instant::now);
+        }
+    }
+
+    /// Holds a participant whose connection dropped, keeping its transports,
+    /// producers and consumers, until it is rebound or closed. Returns false
+    /// when the connection is not in the room's call.
+    pub fn mark_reconnecting(&self, room_id: &ObjectId, connection_id: &str) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
+        };
+        let Some(mut participant) = room.participants.get_mut(connection_id) else {
+            return false;
+        };
+        participant.reconnecting = true;
+        debug!(?room_id, %connection_id, "participant reconnecting");
+        true
+    }
+
+    /// Whether the connection's participant is still waiting to reconnect.
+    pub fn is_reconnecting(&self, room_id: &ObjectId, connection_id: &str) -> bool {
+        self.rooms.get(room_id).is_some_and(|room| {
+            room.participants
+                .get(connection_id)
+                .is_some_and(|p| p.reconnecting)
+        })
+    }
+
+    /// Moves the reconnecting participant of `user_id` holding
+    /// `reconnect_token` to `connection_id` and restarts ICE on its
+    /// transports. Producers and consumers carry over unchanged. Returns
+    /// `None` when no participant is waiting with that token.
+    pub async fn rebind_participant(
+        &self,
+        room_id: &ObjectId,
+        user_id: ObjectId,
+        reconnect_token: &str,
+        connection_id: String,
+    ) -> anyhow::Result<Option<ReboundParticipant>> {
+        let Some(room) = self.rooms.get(room_id) else {
+            return Ok(None);
+        };
+        let previous = room
+            .participants
+            .iter()
+            .find(|e| {
+                let p = e.value();
+                p.reconnecting && p.user_id == user_id && p.reconnect_token == reconnect_token
+            })
+            .map(|e| e.key().clone());
+        let Some((previous, mut participant)) =
+            previous.and_then(|key| room.participants.remove(&key))
+        else {
+            return Ok(None);
+        };
+
+        participant.reconnecting = false;
+        // A stats subscription belongs to the old connection.
+        participant.stats_interval = None;
+        let (closed, open): (Vec<Consumer>, Vec<Consumer>) =
+            participant.consumers.drain(..).partition(|c| c.closed());
+        participant.consumers = open;
+        let consumed_producer_ids = participant
+            .consumers
+            .iter()
+            .map(|c| c.producer_id())
+            .collect();
+        let closed_producer_ids = closed.iter().map(|c| c.producer_id()).collect();
+        let ice = async {
+            let send = participant.send_transport.restart_ice().await?;
+            let recv = participant.recv_transport.restart_ice().await?;
+            anyhow::Ok((serde_json::to_value(send)?, serde_json::to_value(recv)?))
+        }
+        .await;
+
+        room.participants.insert(connection_id.clone(), participant);
+        self.connection_rooms.remove(&previous);
+        self.connection_rooms
+            .insert(connection_id.clone(), *room_id);
+        debug!(?room_id, %previous, %connection_id, "participant rebound");
+
+        let (send_ice_parameters, recv_ice_parameters) = ice?;
+        Ok(Some(ReboundParticipant {
+            previous_connection_id: previous,
+            send_ice_parameters,
+            recv_ice_parameters,
+            consumed_producer_ids,
+            closed_producer_ids,
+        }))
+    }
+
+    /// Switches a participant connection to another caption track, or off
+    /// with `None`. Returns false when the connection is not in the room's
+    /// call.
+    pub fn set_caption_track(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        track: Option<&str>,
+    ) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
+        };
+        let Some(mut participant) = room.participants.get_mut(connection_id) else {
+            return false;
+        };
+        participant.caption_track = track.map(str::to_string);
+        true
+    }
+
+    /// Connections in a room's call that follow the given caption track.
+    pub fn caption_track_connection_ids(&self, room_id: &ObjectId, track: &str) -> Vec<String> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.participants
+                    .iter()
+                    .filter(|e| e.value().caption_track.as_deref() == Some(track))
+                    .map(|e| e.key().clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// RTP statistics of the room's connections, or only of
+    /// `connection_id` when given. Empty when the room isn't hosted here.
+    pub async fn stats(
+        &self,
+        room_id: &ObjectId,
+        connection_id: Option<&str>,
+    ) -> anyhow::Result<Vec<ParticipantStats>> {
+        type Snapshot = (
+            String,
+            ObjectId,
+            [WebRtcTransport; 2],
+            Vec<(Producer, String)>,
+            Vec<Consumer>,
+        );
+        let snapshots: Vec<Snapshot> = {
+            let Some(room) = self.rooms.get(room_id) else {
+                return Ok(Vec::new());
+            };
+            room.participants
+                .iter()
+                .filter(|e| connection_id.is_none_or(|c| c == e.key()))
+                .map(|e| {
+                    let p = e.value();
+                    (
+                        e.key().clone(),
+                        p.user_id,
+                        [p.send_transport.clone(), p.recv_transport.clone()],
+                        p.producers
+                            .iter()
+                            .map(|pe| (pe.producer.clone(), pe.source.clone()))
+                            .collect(),
+                        p.consumers.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut result = Vec::with_capacity(snapshots.len());
+        for (connection_id, user_id, [send, recv], producers, consumers) in snapshots {
+            let mut stats = ParticipantStats {
+                connection_id,
+                user_id: user_id.to_hex(),
+                send_transport: send.get_stats().await?.first().map(TransportStats::from),
+                recv_transport: recv.get_stats().await?.first().map(TransportStats::from),
+                producers: Vec::with_capacity(producers.len()),
+                consumers: Vec::with_capacity(consumers.len()),
+            };
+            for (producer, source) in producers {
+                let s = producer.get_stats().await?;
+                stats
+                    .producers
+                    .push(StreamStats::from_producer(&producer, &source, &s));
+            }
+            for consumer in consumers.iter().filter(|c| !c.closed()) {
+                let s = match consumer.get_stats().await? {
+                    mediasoup::consumer::ConsumerStats::MultipleConsumers(all) => all,
+                    one => vec![one.consumer_stats().clone()],
+                };
+                let s: Vec<_> = s.iter().collect();
+                stats
+                    .consumers
+                    .push(StreamStats::from_consumer(consumer, &s));
+            }
+            result.push(stats);
+        }
+        Ok(result)
+    }
+
+    /// Byte counters of every WebRTC transport in the rooms hosted here,
+    /// for relay accounting. Transports closing meanwhile are left out.
+    pub async fn transport_samples(&self) -> Vec<TransportSample> {
+        let transports: Vec<(ObjectId, WebRtcTransport)> = self
+            .rooms
+            .iter()
+            .flat_map(|room| {
+                let room_id = *room.key();
+                room.participants
+                    .iter()
+                    .flat_map(|p| [p.send_transport.clone(), p.recv_transport.clone()])
+                    .map(|t| (room_id, t))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut samples = Vec::with_capacity(transports.len());
+        for (room_id, transport) in transports {
+            let Ok(stats) = transport.get_stats().await else {
+                continue;
+            };
+            if let Some(stat) = stats.first() {
+                samples.push(TransportSample {
+                    room_id,
+                    transport_id: stat.transport_id.to_string(),
+                    remote_ip: stat.ice_selected_tuple.as_ref().and_then(|t| t.remote_ip()),
+                    bytes_sent: stat.bytes_sent,
+                    bytes_received: stat.bytes_received,
+                });
+            }
+        }
+        samples
+    }
+
+    /// Starts or changes a connection's `media:stats` subscription. Returns
+    /// `None` when the connection is not in the room's call, otherwise
+    /// whether it was subscribed before.
+    pub fn subscribe_stats(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        interval: Option<Duration>,
+    ) -> Option<bool> {
+        let room = self.rooms.get(room_id)?;
+        let mut participant = room.participants.get_mut(connection_id)?;
+        let was = participant.stats_interval.is_some();
+        participant.stats_interval = interval;
+        Some(was)
+    }
+
+    /// The connection's `media:stats` interval, while it is subscribed and
+    /// still in the room's call.
+    pub fn stats_interval(&self, room_id: &ObjectId, connection_id: &str) -> Option<Duration> {
+        self.rooms
+            .get(room_id)?
+            .participants
+            .get(connection_id)?
+            .stats_interval
+    }
+
+    /// Turns the room's transcription on or off, or switches its model,
+    /// letting the previous model drain for `drain`. `None` when the room
+    /// is not hosted here.
+    pub fn toggle_transcription(
+        &self,
+        room_id: &ObjectId,
+        enabled: bool,
+        model: String,
+        drain: Duration,
+    ) -> Option<Handover> {
+        let mut room = self.rooms.get_mut(room_id)?;
+        Some(
+            room.transcription
+                .toggle(enabled, model, drain, Instant::now()),
+        )
+    }
+
+    /// The ASR model new utterances in the room go to; `None` while
+    /// transcription is off or the room is not hosted here.
+    pub fn transcription_model(&self, room_id: &ObjectId) -> Option<String> {
+        self.rooms
+            .get(room_id)?
+            .transcription
+            .model()
+            .map(str::to_string)
+    }
+
+    /// Whether a segment produced by `model` in the room is still current.
+    /// Segments of rooms not hosted here are not judged and pass.
+    pub fn accepts_transcript(&self, room_id: &ObjectId, model: &str) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_none_or(|room| room.transcription.accepts(model, Instant::now()))
+    }
+
+    /// Turns transcription of the connection's own microphone on or off, or
+    /// switches its model, like [`Self::toggle_transcription`] for the
+    /// room. `None` when the connection isn't in the room's call here.
+    pub fn toggle_self_transcription(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        enabled: bool,
+        model: String,
+        drain: Duration,
+    ) -> Option<Handover> {
+        let room = self.rooms.get(room_id)?;
+        let mut participant = room.participants.get_mut(connection_id)?;
+        Some(
+            participant
+                .self_transcription
+                .toggle(enabled, model, drain, Instant::now()),
+        )
+    }
+
+    /// The microphone producers to transcribe one pipeline each, with their
+    /// user and model: those of connections that turned on
+    /// `media:transcribe_me`. Empty while the whole room is transcribed,
+    /// since its pipeline covers them.
+    pub fn self_transcription_producers(
+        &self,
+        room_id: &ObjectId,
+    ) -> Vec<(ObjectId, ProducerId, String)> {
+        let Some(room) = self.rooms.get(room_id) else {
+            return Vec::new();
+        };
+        if room.transcription.model().is_some() {
+            return Vec::new();
+        }
+        room.participants
+            .iter()
+            .filter_map(|e| {
+                let model = e.value().self_transcription.model()?.to_string();
+                let producer = e.value().producers.iter().find(|pe| {
+                    pe.producer.kind() == MediaKind::Audio && !pe.source.starts_with("screen")
+                })?;
+                Some((e.value().user_id, producer.producer.id(), model))
+            })
+            .collect()
+    }
+
+    /// Whether a speaker-scoped segment of `user_id` produced by `model` is
+    /// still current: one of the user's connections transcribes itself with
+    /// it, or is draining it. Rooms not hosted here pass.
+    pub fn accepts_self_transcript(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+        model: &str,
+    ) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return true;
+        };
+        let now = Instant::now();
+        room.participants.iter().any(|e| {
+            &e.value().user_id == user_id && e.value().self_transcription.accepts(model, now)
+        })
+    }
+
     /// Removes ALL participant entries for a given user_id from a room.
     /// Used by HTTP leave endpoint which doesn't have a connection_id.
     pub fn close_participant_by_user(&self, room_id: &ObjectId, user_id: &ObjectId) {
@@ -388,6 +1188,7 @@ impl RoomManager {
                 room.participants.remove(&cid);
                 self.connection_rooms.remove(&cid);
             }
+            self.mark_if_empty(room_id, &room);
         }
         debug!(?room_id, ?user_id, "participant media closed (by user_id)");
     }
@@ -419,6 +1220,20 @@ impl RoomManager {
         result
     }
 
+    /// Active room IDs on this instance.
+    pub fn room_ids(&self) -> Vec<ObjectId> {
+        self.rooms.iter().map(|r| *r.key()).collect()
+    }
+
+    /// Whether anyone in the room is currently producing audio or video.
+    pub fn has_producers(&self, room_id: &ObjectId) -> bool {
+        self.rooms.get(room_id).is_some_and(|room| {
+            room.participants
+                .iter()
+                .any(|p| !p.value().producers.is_empty())
+        })
+    }
+
     /// Returns unique participant user IDs in a room.
     pub fn get_participant_user_ids(&self, room_id: &ObjectId) -> Vec<ObjectId> {
         self.rooms
@@ -436,6 +1251,25 @@ impl RoomManager {
             .unwrap_or_default()
     }
 
+    /// Call activity of every user in a media room on this instance. A user
+    /// connected from several devices counts as screen sharing if any is.
+    pub fn call_activities(&self) -> HashMap<ObjectId, CallActivity> {
+        let mut activities = HashMap::new();
+        for room in self.rooms.iter() {
+            for p in room.participants.iter() {
+                let sharing = p.value().producers.iter().any(|pe| pe.source == "screen");
+                activities
+                    .entry(p.value().user_id)
+                    .and_modify(|a: &mut CallActivity| a.screen_sharing |= sharing)
+                    .or_insert(CallActivity {
+                        room_id: *room.key(),
+                        screen_sharing: sharing,
+                    });
+            }
+        }
+        activities
+    }
+
     /// Returns user IDs of all participants except those with the given connection_id.
     pub fn get_other_participant_user_ids(
         &self,
@@ -562,7 +1396,10 @@ impl RoomManager {
             announced_address: self.announced_ip.clone(),
             port: None,
             port_range: None,
-            flags: None,
+            flags: self.udp_reuse_port.then_some(SocketFlags {
+                ipv6_only: false,
+                udp_reuse_port: true,
+            }),
             send_buffer_size: None,
             recv_buffer_size: None,
             expose_internal_ip: false,
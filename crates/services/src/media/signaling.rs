@@ -23,6 +23,9 @@ pub enum ClientSignal {
         conference_id: String,
         kind: MediaKind,
         rtp_parameters: RtpParameters,
+        /// Simulcast encodings; replace those in `rtp_parameters` when set
+        #[serde(default)]
+        encodings: Option<Vec<RtpEncodingParameters>>,
     },
 
     /// Client requests to consume a remote producer
@@ -40,18 +43,71 @@ pub enum ClientSignal {
         producer_id: String,
     },
 
+    /// Client swaps the device behind one of its producers (camera or
+    /// microphone switch) without closing the slot
+    #[serde(rename = "media:replace_producer")]
+    ReplaceProducer {
+        room_id: String,
+        producer_id: String,
+        rtp_parameters: RtpParameters,
+    },
+
     /// Client leaves the media room
     #[serde(rename = "media:leave")]
     MediaLeave { conference_id: String },
 
+    /// Client asks for other simulcast/SVC layers of a video it consumes
+    #[serde(rename = "media:set_preferred_layers")]
+    SetPreferredLayers {
+        room_id: String,
+        consumer_id: String,
+        spatial_layer: u8,
+        #[serde(default)]
+        temporal_layer: Option<u8>,
+    },
+
+    /// Client mutes one of its producers
+    #[serde(rename = "media:producer_pause")]
+    ProducerPause {
+        room_id: String,
+        producer_id: String,
+    },
+
+    /// Client unmutes one of its producers
+    #[serde(rename = "media:producer_resume")]
+    ProducerResume {
+        room_id: String,
+        producer_id: String,
+    },
+
     /// Client toggles transcription for a conference
     #[serde(rename = "media:transcript_toggle")]
     TranscriptToggle {
-        conference_id: String,
+        room_id: String,
         enabled: bool,
         #[serde(default)]
         model: Option<String>,
     },
+
+    /// Client picks the caption track it receives transcripts for, or
+    /// `null` to stop receiving them
+    #[serde(rename = "media:caption_track")]
+    CaptionTrack {
+        room_id: String,
+        track: Option<String>,
+    },
+
+    /// Client sends an emoji reaction to everyone in the call
+    #[serde(rename = "media:reaction")]
+    Reaction { room_id: String, emoji: String },
+
+    /// Client on a new connection takes over the media state of one that
+    /// dropped, using the token from `media:transport_created`
+    #[serde(rename = "media:rejoin")]
+    Rejoin {
+        room_id: String,
+        reconnect_token: String,
+    },
 }
 
 /// Server -> Client signaling messages (sent over WebSocket).
@@ -61,18 +117,30 @@ pub enum ClientSignal {
 pub enum ServerSignal {
     /// Router RTP capabilities for Device loading
     #[serde(rename = "media:router_capabilities")]
-    RouterCapabilities { rtp_capabilities: serde_json::Value },
+    RouterCapabilities {
+        rtp_capabilities: serde_json::Value,
+        /// Plan/tenant getUserMedia settings; `null` if they couldn't be loaded.
+        media_constraints: serde_json::Value,
+        /// Camera effects the client may offer, with approved backgrounds;
+        /// `null` if they couldn't be loaded.
+        video_effects: serde_json::Value,
+    },
 
     /// Send + recv transport pair created
     #[serde(rename = "media:transport_created")]
     TransportCreated {
         send_transport: Box<super::room_manager::TransportOptions>,
         recv_transport: Box<super::room_manager::TransportOptions>,
+        reconnect_token: String,
     },
 
     /// Producer creation result
     #[serde(rename = "media:produce_result")]
-    ProduceResult { id: String },
+    ProduceResult {
+        id: String,
+        paused: bool,
+        layers: super::simulcast::ProducerLayers,
+    },
 
     /// Consumer created for a remote producer
     #[serde(rename = "media:consumer_created")]
@@ -81,6 +149,15 @@ pub enum ServerSignal {
         producer_id: String,
         kind: String,
         rtp_parameters: serde_json::Value,
+        layers: super::simulcast::ProducerLayers,
+    },
+
+    /// Layers now forwarded to one of this connection's consumers
+    #[serde(rename = "media:preferred_layers_set")]
+    PreferredLayersSet {
+        consumer_id: String,
+        spatial_layer: u8,
+        temporal_layer: Option<u8>,
     },
 
     /// A new producer appeared in the room (notify to trigger consume)
@@ -105,9 +182,48 @@ pub enum ServerSignal {
         user_id: String,
     },
 
+    /// Result of a producer replacement, to the replacing connection
+    #[serde(rename = "media:replace_producer_result")]
+    ReplaceProducerResult {
+        id: String,
+        replaced_producer_id: String,
+    },
+
+    /// A peer's producer was swapped for a new one in the same slot;
+    /// consumers of the old one are closed and the new one should be
+    /// consumed in its place
+    #[serde(rename = "media:producer_replaced")]
+    ProducerReplaced {
+        old_producer_id: String,
+        producer_id: String,
+        user_id: String,
+        connection_id: String,
+        kind: String,
+        source: String,
+    },
+
+    /// A peer muted one of its producers
+    #[serde(rename = "media:producer_paused")]
+    ProducerPaused {
+        producer_id: String,
+        user_id: String,
+        kind: String,
+    },
+
+    /// A peer unmuted one of its producers
+    #[serde(rename = "media:producer_resumed")]
+    ProducerResumed {
+        producer_id: String,
+        user_id: String,
+        kind: String,
+    },
+
     /// Live transcript segment from ASR
     #[serde(rename = "media:transcript")]
     Transcript {
+        room_id: String,
+        /// Caption track the segment belongs to (`original` or a language)
+        track: String,
         user_id: String,
         speaker_name: String,
         text: String,
@@ -120,10 +236,62 @@ pub enum ServerSignal {
     /// Transcription status changed (enabled/disabled)
     #[serde(rename = "media:transcript_status")]
     TranscriptStatus {
-        conference_id: String,
+        room_id: String,
         enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         model: Option<String>,
+        /// Set when a switch or stop hands over from this model.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous_model: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        switched_at: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        drain_ms: Option<u64>,
+    },
+
+    /// Caption track selection confirmed for this connection
+    #[serde(rename = "media:caption_track_selected")]
+    CaptionTrackSelected {
+        room_id: String,
+        track: Option<String>,
+        available_tracks: Vec<String>,
+    },
+
+    /// An emoji reaction from a call participant
+    #[serde(rename = "media:reaction")]
+    Reaction {
+        room_id: String,
+        user_id: String,
+        connection_id: String,
+        emoji: String,
+    },
+
+    /// The dropped connection's media state now belongs to this one
+    #[serde(rename = "media:rejoined")]
+    Rejoined {
+        room_id: String,
+        previous_connection_id: String,
+        send_ice_parameters: serde_json::Value,
+        recv_ice_parameters: serde_json::Value,
+        ice_servers: Vec<serde_json::Value>,
+        closed_producer_ids: Vec<String>,
+    },
+
+    /// A peer's connection dropped; its media is kept for a while
+    #[serde(rename = "media:peer_reconnecting")]
+    PeerReconnecting {
+        room_id: String,
+        user_id: String,
+        connection_id: String,
+    },
+
+    /// A reconnecting peer is back on a new connection
+    #[serde(rename = "media:peer_reconnected")]
+    PeerReconnected {
+        room_id: String,
+        user_id: String,
+        connection_id: String,
+        previous_connection_id: String,
     },
 
     /// Error response
@@ -0,0 +1,196 @@
+//! Simulcast and SVC layers of video producers.
+//!
+//! A producer's encodings say which layers it sends: one spatial layer per
+//! simulcast encoding, or the spatial and temporal layers of a single SVC
+//! encoding's scalability mode (`L3T3`). Consumers on poor links ask for
+//! lower layers with `media:set_preferred_layers`; screen shares keep their
+//! full resolution and drop frame rate instead, so shared text stays
+//! readable.
+
+use mediasoup::consumer::ConsumerLayers;
+use mediasoup::prelude::MediaKind;
+use mediasoup::prelude::RtpEncodingParameters;
+use serde::{Deserialize, Serialize};
+
+/// Most simulcast encodings a video producer may send.
+pub const MAX_ENCODINGS: usize = 3;
+
+/// How many spatial and temporal layers a producer sends; both at least 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProducerLayers {
+    pub spatial_layers: u8,
+    pub temporal_layers: u8,
+}
+
+impl ProducerLayers {
+    /// A single layer: nothing for a consumer to choose between.
+    pub const SINGLE: Self = Self {
+        spatial_layers: 1,
+        temporal_layers: 1,
+    };
+
+    pub fn is_layered(&self) -> bool {
+        self.spatial_layers > 1 || self.temporal_layers > 1
+    }
+}
+
+/// Layers described by a producer's encodings.
+pub fn layers(encodings: &[RtpEncodingParameters]) -> ProducerLayers {
+    let Some(first) = encodings.first() else {
+        return ProducerLayers::SINGLE;
+    };
+    let mode = &first.scalability_mode;
+    let spatial_layers = if encodings.len() > 1 {
+        encodings.len().min(u8::MAX as usize) as u8
+    } else {
+        mode.spatial_layers().get()
+    };
+    ProducerLayers {
+        spatial_layers,
+        temporal_layers: mode.temporal_layers().get(),
+    }
+}
+
+/// Check the encodings a client wants to produce with. Audio sends one
+/// encoding, video at most [`MAX_ENCODINGS`], and simulcast encodings must
+/// be told apart by `rid` or `ssrc`.
+pub fn validate_encodings(
+    kind: MediaKind,
+    encodings: &[RtpEncodingParameters],
+) -> Result<(), &'static str> {
+    match kind {
+        MediaKind::Audio if encodings.len() > 1 => {
+            return Err("Audio producers take a single encoding");
+        }
+        MediaKind::Video if encodings.len() > MAX_ENCODINGS => {
+            return Err("Too many simulcast encodings");
+        }
+        _ => {}
+    }
+    if encodings.len() > 1 {
+        let mut keys = Vec::with_capacity(encodings.len());
+        for encoding in encodings {
+            let key = match (&encoding.rid, encoding.ssrc) {
+                (Some(rid), _) => rid.clone(),
+                (None, Some(ssrc)) => ssrc.to_string(),
+                (None, None) => return Err("Simulcast encodings need a rid or ssrc"),
+            };
+            if keys.contains(&key) {
+                return Err("Simulcast encodings must be distinct");
+            }
+            keys.push(key);
+        }
+    }
+    Ok(())
+}
+
+/// Layers to forward to a consumer that asked for `spatial`/`temporal`,
+/// clamped to what the producer sends. For a screen share, asking for less
+/// than the top spatial layer keeps the resolution and lowers the frame rate
+/// to `temporal` (or the lowest) instead.
+pub fn preferred_layers(
+    layers: ProducerLayers,
+    screen_share: bool,
+    spatial: u8,
+    temporal: Option<u8>,
+) -> ConsumerLayers {
+    let top_spatial = layers.spatial_layers.saturating_sub(1);
+    let top_temporal = layers.temporal_layers.saturating_sub(1);
+    let temporal = temporal.map(|t| t.min(top_temporal));
+    if screen_share && spatial < top_spatial {
+        return ConsumerLayers {
+            spatial_layer: top_spatial,
+            temporal_layer: Some(temporal.unwrap_or(0)),
+        };
+    }
+    ConsumerLayers {
+        spatial_layer: spatial.min(top_spatial),
+        temporal_layer: temporal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoding(rid: Option<&str>, mode: &str) -> RtpEncodingParameters {
+        RtpEncodingParameters {
+            rid: rid.map(str::to_string),
+            scalability_mode: mode.parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn simulcast_has_a_spatial_layer_per_encoding() {
+        let encodings = [
+            encoding(Some("r0"), "L1T3"),
+            encoding(Some("r1"), "L1T3"),
+            encoding(Some("r2"), "L1T3"),
+        ];
+        assert_eq!(
+            layers(&encodings),
+            ProducerLayers {
+                spatial_layers: 3,
+                temporal_layers: 3
+            }
+        );
+    }
+
+    #[test]
+    fn svc_layers_come_from_the_scalability_mode() {
+        assert_eq!(
+            layers(&[encoding(None, "L3T2")]),
+            ProducerLayers {
+                spatial_layers: 3,
+                temporal_layers: 2
+            }
+        );
+        assert!(!layers(&[RtpEncodingParameters::default()]).is_layered());
+        assert_eq!(layers(&[]), ProducerLayers::SINGLE);
+    }
+
+    #[test]
+    fn rejects_ambiguous_or_excess_encodings() {
+        let two = [encoding(Some("a"), "L1T1"), encoding(Some("b"), "L1T1")];
+        assert!(validate_encodings(MediaKind::Video, &two).is_ok());
+        assert!(validate_encodings(MediaKind::Audio, &two).is_err());
+
+        let same = [encoding(Some("a"), "L1T1"), encoding(Some("a"), "L1T1")];
+        assert!(validate_encodings(MediaKind::Video, &same).is_err());
+        let unnamed = [encoding(None, "L1T1"), encoding(None, "L1T1")];
+        assert!(validate_encodings(MediaKind::Video, &unnamed).is_err());
+
+        let four: Vec<_> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|rid| encoding(Some(rid), "L1T1"))
+            .collect();
+        assert!(validate_encodings(MediaKind::Video, &four).is_err());
+    }
+
+    #[test]
+    fn camera_downgrades_resolution_within_range() {
+        let sent = ProducerLayers {
+            spatial_layers: 3,
+            temporal_layers: 3,
+        };
+        let l = preferred_layers(sent, false, 0, Some(1));
+        assert_eq!((l.spatial_layer, l.temporal_layer), (0, Some(1)));
+        let l = preferred_layers(sent, false, 7, Some(9));
+        assert_eq!((l.spatial_layer, l.temporal_layer), (2, Some(2)));
+    }
+
+    #[test]
+    fn screen_share_keeps_resolution_and_drops_frame_rate() {
+        let sent = ProducerLayers {
+            spatial_layers: 2,
+            temporal_layers: 3,
+        };
+        let l = preferred_layers(sent, true, 0, None);
+        assert_eq!((l.spatial_layer, l.temporal_layer), (1, Some(0)));
+        let l = preferred_layers(sent, true, 0, Some(1));
+        assert_eq!((l.spatial_layer, l.temporal_layer), (1, Some(1)));
+        let l = preferred_layers(sent, true, 1, None);
+        assert_eq!((l.spatial_layer, l.temporal_layer), (1, None));
+    }
+}
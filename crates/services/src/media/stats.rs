@@ -0,0 +1,176 @@
+//! RTP statistics of call participants, read from mediasoup.
+//!
+//! For each connection in a call, [`RoomManager::stats`] reports both
+//! WebRTC transports and every producer and consumer: bitrate, packet
+//! loss, round-trip time and codec. Simulcast producers send several RTP
+//! streams; [`StreamStats::from_producer`] folds them into one entry so a
+//! frozen video shows up as a low `score` or high `fraction_lost` without
+//! reading per-layer numbers. Candidate IPs are left out, since the stats
+//! may be shown to other participants.
+//!
+//! [`RoomManager::stats`]: super::room_manager::RoomManager::stats
+
+use mediasoup::consumer::ConsumerStat;
+use mediasoup::prelude::*;
+use mediasoup::producer::ProducerStat;
+use mediasoup::types::data_structures::{DtlsState, IceState};
+use mediasoup::webrtc_transport::WebRtcTransportStat;
+use serde::Serialize;
+
+/// One connection's media in a call.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParticipantStats {
+    pub connection_id: String,
+    pub user_id: String,
+    pub send_transport: Option<TransportStats>,
+    pub recv_transport: Option<TransportStats>,
+    pub producers: Vec<StreamStats>,
+    pub consumers: Vec<StreamStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransportStats {
+    pub id: String,
+    pub ice_state: IceState,
+    pub dtls_state: DtlsState,
+    /// Bits per second, all traffic including RTX and probing.
+    pub recv_bitrate: u32,
+    pub send_bitrate: u32,
+    pub available_outgoing_bitrate: Option<u32>,
+    /// Fraction of RTP packets lost, 0.0-1.0.
+    pub packet_loss_received: Option<f64>,
+    pub packet_loss_sent: Option<f64>,
+}
+
+impl From<&WebRtcTransportStat> for TransportStats {
+    fn from(s: &WebRtcTransportStat) -> Self {
+        Self {
+            id: s.transport_id.to_string(),
+            ice_state: s.ice_state,
+            dtls_state: s.dtls_state,
+            recv_bitrate: s.recv_bitrate,
+            send_bitrate: s.send_bitrate,
+            available_outgoing_bitrate: s.available_outgoing_bitrate,
+            packet_loss_received: s.rtp_packet_loss_received,
+            packet_loss_sent: s.rtp_packet_loss_sent,
+        }
+    }
+}
+
+/// A producer or consumer, summed over its RTP streams.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStats {
+    pub id: String,
+    /// For consumers, the producer consumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub producer_id: Option<String>,
+    pub kind: MediaKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub paused: bool,
+    /// MIME type, e.g. `video/VP8`; `None` before any RTP arrived.
+    pub codec: Option<String>,
+    /// Bits per second.
+    pub bitrate: u32,
+    pub packet_count: u64,
+    pub packets_lost: u64,
+    /// Worst recent loss across the streams, 0.0-1.0.
+    pub fraction_lost: f64,
+    /// Milliseconds; the worst across the streams.
+    pub round_trip_time: Option<f32>,
+    /// Receive jitter in RTP timestamp units; producers only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<u32>,
+    /// mediasoup's 0-10 transmission quality; the worst stream's.
+    pub score: Option<u8>,
+    /// RTP streams summed, e.g. one per simulcast layer.
+    pub streams: usize,
+}
+
+impl StreamStats {
+    pub fn from_producer(producer: &Producer, source: &str, stats: &[ProducerStat]) -> Self {
+        let mut s = Self::empty(
+            producer.id().to_string(),
+            producer.kind(),
+            producer.paused(),
+        );
+        s.source = Some(source.to_string());
+        for stat in stats {
+            s.add(
+                stat.mime_type.as_str(),
+                stat.bitrate,
+                stat.packet_count,
+                stat.packets_lost,
+                stat.fraction_lost,
+                stat.round_trip_time,
+                stat.score,
+            );
+            s.jitter = Some(s.jitter.unwrap_or(0).max(stat.jitter));
+        }
+        s
+    }
+
+    pub fn from_consumer(consumer: &Consumer, stats: &[&ConsumerStat]) -> Self {
+        let mut s = Self::empty(
+            consumer.id().to_string(),
+            consumer.kind(),
+            consumer.paused(),
+        );
+        s.producer_id = Some(consumer.producer_id().to_string());
+        for stat in stats {
+            s.add(
+                stat.mime_type.as_str(),
+                stat.bitrate,
+                stat.packet_count,
+                stat.packets_lost,
+                stat.fraction_lost,
+                stat.round_trip_time,
+                stat.score,
+            );
+        }
+        s
+    }
+
+    fn empty(id: String, kind: MediaKind, paused: bool) -> Self {
+        Self {
+            id,
+            producer_id: None,
+            kind,
+            source: None,
+            paused,
+            codec: None,
+            bitrate: 0,
+            packet_count: 0,
+            packets_lost: 0,
+            fraction_lost: 0.0,
+            round_trip_time: None,
+            jitter: None,
+            score: None,
+            streams: 0,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        codec: &str,
+        bitrate: u32,
+        packet_count: u64,
+        packets_lost: u64,
+        fraction_lost: u8,
+        round_trip_time: Option<f32>,
+        score: u8,
+    ) {
+        self.codec.get_or_insert_with(|| codec.to_string());
+        self.bitrate = self.bitrate.saturating_add(bitrate);
+        self.packet_count += packet_count;
+        self.packets_lost += packets_lost;
+        // RTCP reports loss as a fraction of 256.
+        self.fraction_lost = self.fraction_lost.max(fraction_lost as f64 / 256.0);
+        if let Some(rtt) = round_trip_time {
+            self.round_trip_time = Some(self.round_trip_time.map_or(rtt, |r| r.max(rtt)));
+        }
+        self.score = Some(self.score.map_or(score, |s| s.min(score)));
+        self.streams += 1;
+    }
+}
@@ -0,0 +1,121 @@
+//! In-process feed of live transcript segments.
+//!
+//! Whatever produces ASR output for a call publishes each segment here once
+//! per caption track. The API subscribes twice: once to deliver segments to
+//! the call connections following the track, once to write them to
+//! `transcript_segments` so the transcript outlives the call. Interim
+//! segments (`is_final: false`) are delivered but not stored: each one is
+//! replaced on the client by the speaker's next segment on the track, and
+//! ultimately by the final one. Publishing
+//! never blocks; a subscriber that falls more than [`CAPACITY`] segments
+//! behind skips the oldest ones.
+
+use bson::oid::ObjectId;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::asr_quality::SegmentQuality;
+
+/// Segments buffered per subscriber.
+pub const CAPACITY: usize = 1024;
+
+/// Whose speech the pipeline behind a segment transcribes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptScope {
+    /// Everyone in the call (`media:transcript_toggle`).
+    #[default]
+    Room,
+    /// Only the speaker, who turned it on for themselves
+    /// (`media:transcribe_me`).
+    #[serde(rename = "self")]
+    Speaker,
+}
+
+/// A transcript segment as the ASR pipeline emits it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptEvent {
+    pub room_id: ObjectId,
+    /// Caption track: `original` or a translation language.
+    pub track: String,
+    pub user_id: ObjectId,
+    pub speaker_name: String,
+    pub text: String,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    pub start_time: f64,
+    pub end_time: f64,
+    /// `false` for a partial result on speech still in progress.
+    pub is_final: bool,
+    /// Measurements of the segment's audio, when the producer took them.
+    pub quality: Option<SegmentQuality>,
+    /// The ASR model that produced the segment. Untagged segments are
+    /// always current; tagged ones are dropped once their model stops
+    /// draining after a switch (see `transcription_session`).
+    pub model: Option<String>,
+    /// Speaker-scoped segments stay current while the speaker's own
+    /// transcription does, regardless of the room's.
+    pub scope: TranscriptScope,
+}
+
+pub struct TranscriptFeed {
+    sender: broadcast::Sender<TranscriptEvent>,
+}
+
+impl TranscriptFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    /// Hands `event` to every current subscriber and returns how many there
+    /// were. Segments published with no subscribers are dropped.
+    pub fn publish(&self, event: TranscriptEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TranscriptEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for TranscriptFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(text: &str) -> TranscriptEvent {
+        TranscriptEvent {
+            room_id: ObjectId::new(),
+            track: "original".to_string(),
+            user_id: ObjectId::new(),
+            speaker_name: "Ada".to_string(),
+            text: text.to_string(),
+            language: Some("en".to_string()),
+            confidence: None,
+            start_time: 0.0,
+            end_time: 1.5,
+            is_final: true,
+            quality: None,
+            model: None,
+            scope: TranscriptScope::Room,
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_segments_in_order() {
+        let feed = TranscriptFeed::new();
+        assert_eq!(feed.publish(event("dropped")), 0);
+
+        let mut rx = feed.subscribe();
+        assert_eq!(feed.publish(event("hello")), 1);
+        feed.publish(event("world"));
+        assert_eq!(rx.recv().await.unwrap().text, "hello");
+        assert_eq!(rx.recv().await.unwrap().text, "world");
+    }
+}
@@ -0,0 +1,154 @@
+//! Which ASR model transcribes a call, and the handover when it changes.
+//!
+//! Switching models with `media:transcript_toggle` doesn't tear the old
+//! pipeline down. The transcript producer asks [`TranscriptionSession::model`]
+//! which model new utterances go to, so speech starting after the switch is
+//! sent to the new backend, while utterances already buffered finish on the
+//! old one. The old model drains for a grace period: its segments, tagged
+//! with the model that produced them, are still delivered and stored until
+//! the deadline, and dropped as stale afterwards. Turning transcription off
+//! drains the same way, so the last words spoken aren't lost.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub struct TranscriptionSession {
+    enabled: bool,
+    model: String,
+    /// The model being replaced, and until when its segments count.
+    draining: Option<(String, Instant)>,
+}
+
+/// What a toggle changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Handover {
+    Started,
+    /// Transcription stopped; the previous model drains.
+    Stopped {
+        previous_model: String,
+    },
+    /// New speech goes to the new model; the previous one drains.
+    Switched {
+        previous_model: String,
+    },
+    /// Nothing changed.
+    Unchanged,
+}
+
+impl TranscriptionSession {
+    /// Applies a toggle at `now`, starting a `drain` period for the model
+    /// being replaced or stopped. A drain still running from an earlier
+    /// switch is cut short.
+    pub fn toggle(
+        &mut self,
+        enabled: bool,
+        model: String,
+        drain: Duration,
+        now: Instant,
+    ) -> Handover {
+        let handover = match (self.enabled, enabled) {
+            (false, false) => return Handover::Unchanged,
+            (false, true) => Handover::Started,
+            (true, false) => Handover::Stopped {
+                previous_model: self.model.clone(),
+            },
+            (true, true) if self.model == model => return Handover::Unchanged,
+            (true, true) => Handover::Switched {
+                previous_model: self.model.clone(),
+            },
+        };
+        self.draining = match &handover {
+            Handover::Stopped { previous_model } | Handover::Switched { previous_model } => {
+                Some((previous_model.clone(), now + drain))
+            }
+            _ => None,
+        };
+        self.enabled = enabled;
+        self.model = model;
+        handover
+    }
+
+    /// The model new utterances go to; `None` while transcription is off.
+    pub fn model(&self) -> Option<&str> {
+        self.enabled.then_some(self.model.as_str())
+    }
+
+    /// Whether a segment produced by `model` is still current at `now`:
+    /// from the active model, or from the one being replaced before its
+    /// drain deadline.
+    pub fn accepts(&self, model: &str, now: Instant) -> bool {
+        if self.enabled && self.model == model {
+            return true;
+        }
+        self.draining
+            .as_ref()
+            .is_some_and(|(draining, until)| draining == model && now < *until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAIN: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn switching_drains_the_previous_model() {
+        let start = Instant::now();
+        let mut session = TranscriptionSession::default();
+        assert_eq!(session.model(), None);
+        assert_eq!(
+            session.toggle(true, "small".into(), DRAIN, start),
+            Handover::Started
+        );
+        assert_eq!(session.model(), Some("small"));
+
+        assert_eq!(
+            session.toggle(true, "large".into(), DRAIN, start),
+            Handover::Switched {
+                previous_model: "small".into()
+            }
+        );
+        assert_eq!(session.model(), Some("large"));
+        assert!(session.accepts("large", start));
+        assert!(session.accepts("small", start + Duration::from_secs(9)));
+        assert!(!session.accepts("small", start + DRAIN));
+        assert!(!session.accepts("tiny", start));
+    }
+
+    #[test]
+    fn stopping_drains_and_repeats_change_nothing() {
+        let start = Instant::now();
+        let mut session = TranscriptionSession::default();
+        assert_eq!(
+            session.toggle(false, "small".into(), DRAIN, start),
+            Handover::Unchanged
+        );
+        session.toggle(true, "small".into(), DRAIN, start);
+        assert_eq!(
+            session.toggle(true, "small".into(), DRAIN, start),
+            Handover::Unchanged
+        );
+
+        assert_eq!(
+            session.toggle(false, "small".into(), DRAIN, start),
+            Handover::Stopped {
+                previous_model: "small".into()
+            }
+        );
+        assert_eq!(session.model(), None);
+        assert!(session.accepts("small", start));
+        assert!(!session.accepts("small", start + DRAIN));
+    }
+
+    #[test]
+    fn a_new_switch_cuts_the_earlier_drain_short() {
+        let start = Instant::now();
+        let mut session = TranscriptionSession::default();
+        session.toggle(true, "a".into(), DRAIN, start);
+        session.toggle(true, "b".into(), DRAIN, start);
+        session.toggle(true, "c".into(), DRAIN, start);
+        assert!(!session.accepts("a", start));
+        assert!(session.accepts("b", start));
+    }
+}
@@ -1,24 +1,34 @@
 use mediasoup::worker::{Worker, WorkerSettings};
 use mediasoup::worker_manager::WorkerManager;
 use roomler_ai_config::MediasoupSettings;
+use std::ops::RangeInclusive;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::{error, info};
 
 /// Pool of mediasoup workers with round-robin selection.
 pub struct WorkerPool {
     workers: Vec<Worker>,
+    /// RTC port slice owned by each worker, indexed like `workers`.
+    port_ranges: Vec<RangeInclusive<u16>>,
     next: AtomicUsize,
 }
 
 impl WorkerPool {
     /// Creates a pool of mediasoup workers based on settings.
     pub async fn new(settings: &MediasoupSettings) -> anyhow::Result<Self> {
+        let port_ranges = partition_port_range(
+            settings.rtc_min_port,
+            settings.rtc_max_port,
+            settings.num_workers,
+        )?;
+        validate_capacity(&port_ranges, settings.expected_max_transports)?;
+
         let worker_manager = WorkerManager::new();
-        let mut workers = Vec::with_capacity(settings.num_workers as usize);
+        let mut workers = Vec::with_capacity(port_ranges.len());
 
-        for i in 0..settings.num_workers {
+        for (i, range) in port_ranges.iter().enumerate() {
             let mut worker_settings = WorkerSettings::default();
-            worker_settings.rtc_port_range = settings.rtc_min_port..=settings.rtc_max_port;
+            worker_settings.rtc_port_range = range.clone();
 
             let worker = worker_manager
                 .create_worker(worker_settings)
@@ -32,23 +42,136 @@ impl WorkerPool {
                 })
                 .detach();
 
-            info!(worker_id = %worker.id(), "mediasoup worker {} created", i);
+            info!(
+                worker_id = %worker.id(),
+                ports = %format!("{}-{}", range.start(), range.end()),
+                "mediasoup worker {} created", i
+            );
             workers.push(worker);
         }
 
         Ok(Self {
             workers,
+            port_ranges,
             next: AtomicUsize::new(0),
         })
     }
 
     /// Returns the next worker using round-robin selection.
     pub fn get_worker(&self) -> &Worker {
+        self.next_worker().1
+    }
+
+    /// Like [`get_worker`](Self::get_worker), but also returns the worker's
+    /// index so callers can attribute port usage to it.
+    pub fn next_worker(&self) -> (usize, &Worker) {
         let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.workers.len();
-        &self.workers[idx]
+        (idx, &self.workers[idx])
     }
 
     pub fn worker_count(&self) -> usize {
         self.workers.len()
     }
+
+    /// Workers that haven't died or been closed.
+    pub fn alive_count(&self) -> usize {
+        self.workers.iter().filter(|w| !w.closed()).count()
+    }
+
+    pub fn port_ranges(&self) -> &[RangeInclusive<u16>] {
+        &self.port_ranges
+    }
+}
+
+/// Split `min..=max` into `workers` contiguous, non-overlapping slices.
+/// The first slices absorb the remainder, so sizes differ by at most one.
+pub fn partition_port_range(
+    min: u16,
+    max: u16,
+    workers: u32,
+) -> anyhow::Result<Vec<RangeInclusive<u16>>> {
+    if workers == 0 {
+        anyhow::bail!("mediasoup.num_workers must be at least 1");
+    }
+    if min > max {
+        anyhow::bail!("mediasoup.rtc_min_port ({min}) is above rtc_max_port ({max})");
+    }
+    let total = u32::from(max) - u32::from(min) + 1;
+    if total < workers {
+        anyhow::bail!(
+            "RTC port range {min}-{max} ({total} ports) is smaller than the {workers} workers sharing it"
+        );
+    }
+
+    let base = total / workers;
+    let extra = total % workers;
+    let mut start = u32::from(min);
+    Ok((0..workers)
+        .map(|i| {
+            let len = base + u32::from(i < extra);
+            let range = start as u16..=(start + len - 1) as u16;
+            start += len;
+            range
+        })
+        .collect())
+}
+
+/// Each WebRTC transport binds one UDP and one TCP port from its worker's
+/// slice (separate port spaces), so a slice of N ports holds N transports.
+fn validate_capacity(ranges: &[RangeInclusive<u16>], expected: u32) -> anyhow::Result<()> {
+    let per_worker = expected.div_ceil(ranges.len() as u32);
+    if let Some(smallest) = ranges.iter().map(range_len).min()
+        && smallest < per_worker
+    {
+        anyhow::bail!(
+            "RTC port range too small: each of {} workers needs {} ports for {} expected transports, \
+             but the smallest slice has {}; widen rtc_min_port/rtc_max_port or lower expected_max_transports",
+            ranges.len(),
+            per_worker,
+            expected,
+            smallest
+        );
+    }
+    Ok(())
+}
+
+pub fn range_len(range: &RangeInclusive<u16>) -> u32 {
+    u32::from(*range.end()) - u32::from(*range.start()) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions_evenly_with_remainder_up_front() {
+        let ranges = partition_port_range(40000, 40009, 3).unwrap();
+        assert_eq!(ranges, vec![40000..=40003, 40004..=40006, 40007..=40009]);
+    }
+
+    #[test]
+    fn single_worker_gets_whole_range() {
+        let ranges = partition_port_range(40000, 49999, 1).unwrap();
+        assert_eq!(ranges, vec![40000..=49999]);
+    }
+
+    #[test]
+    fn covers_top_of_port_space() {
+        let ranges = partition_port_range(65534, 65535, 2).unwrap();
+        assert_eq!(ranges, vec![65534..=65534, 65535..=65535]);
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert!(partition_port_range(40000, 40001, 3).is_err());
+        assert!(partition_port_range(40001, 40000, 1).is_err());
+        assert!(partition_port_range(40000, 40001, 0).is_err());
+    }
+
+    #[test]
+    fn capacity_check_uses_per_worker_share() {
+        let ranges = partition_port_range(40000, 40099, 2).unwrap();
+        assert!(validate_capacity(&ranges, 100).is_ok());
+        assert!(validate_capacity(&ranges, 101).is_err());
+    }
 }
@@ -0,0 +1,329 @@
+//! Bulk member import from CSV.
+//!
+//! The file needs a header row with an `email` column; `name` and `roles`
+//! are optional and columns may come in any order. `roles` holds role names
+//! separated by `;` and defaults to `member`. Parsing validates every row up
+//! front; the import itself runs as a background task whose per-row results
+//! are written back as a CSV report.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use validator::ValidateEmail;
+
+/// Most data rows one import can have.
+pub const MAX_ROWS: usize = 1000;
+
+const MAX_NAME_CHARS: usize = 100;
+const DEFAULT_ROLE: &str = "member";
+
+/// A row that passed validation. `line` is its 1-based line in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRow {
+    pub line: usize,
+    pub email: String,
+    pub name: Option<String>,
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowOutcome {
+    /// An existing user was added to the tenant.
+    Added,
+    /// No account uses the email; an invite was created for it.
+    Invited,
+    AlreadyMember,
+    Invalid,
+    Failed,
+}
+
+impl RowOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Invited => "invited",
+            Self::AlreadyMember => "already_member",
+            Self::Invalid => "invalid",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowResult {
+    pub line: usize,
+    pub email: String,
+    pub outcome: RowOutcome,
+    pub detail: Option<String>,
+}
+
+impl RowResult {
+    pub fn invalid(line: usize, email: &str, detail: impl Into<String>) -> Self {
+        Self {
+            line,
+            email: email.to_string(),
+            outcome: RowOutcome::Invalid,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Rows ready to import, and the ones rejected while parsing.
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub rows: Vec<ImportRow>,
+    pub invalid: Vec<RowResult>,
+}
+
+/// Parse and validate an import file. Errors that make the whole file
+/// unusable (no header, no `email` column, too many rows) are returned as
+/// `Err`; problems with single rows end up in `invalid`.
+pub fn parse(input: &str) -> Result<ParsedImport, String> {
+    let mut records = parse_records(input.trim_start_matches('\u{feff}'))?
+        .into_iter()
+        .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()));
+
+    let (_, header) = records.next().ok_or("The file is empty")?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    };
+    let email_col = column("email").ok_or("The header must have an email column")?;
+    let name_col = column("name");
+    let roles_col = column("roles").or_else(|| column("role"));
+
+    let records: Vec<_> = records.collect();
+    if records.len() > MAX_ROWS {
+        return Err(format!("An import can have at most {} rows", MAX_ROWS));
+    }
+
+    let mut parsed = ParsedImport::default();
+    let mut seen = HashSet::new();
+    for (line, fields) in records {
+        let field = |col: Option<usize>| {
+            col.and_then(|c| fields.get(c))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+        let email = field(Some(email_col)).unwrap_or("").to_lowercase();
+        if email.is_empty() {
+            parsed
+                .invalid
+                .push(RowResult::invalid(line, &email, "Missing email"));
+            continue;
+        }
+        if !email.validate_email() {
+            parsed
+                .invalid
+                .push(RowResult::invalid(line, &email, "Invalid email"));
+            continue;
+        }
+        if !seen.insert(email.clone()) {
+            parsed.invalid.push(RowResult::invalid(
+                line,
+                &email,
+                "Duplicate email in this file",
+            ));
+            continue;
+        }
+        let name = field(name_col).map(str::to_string);
+        if name
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_NAME_CHARS)
+        {
+            parsed.invalid.push(RowResult::invalid(
+                line,
+                &email,
+                format!("name must be at most {} characters", MAX_NAME_CHARS),
+            ));
+            continue;
+        }
+        let mut roles: Vec<String> = field(roles_col)
+            .unwrap_or(DEFAULT_ROLE)
+            .split(';')
+            .map(|r| r.trim().to_lowercase())
+            .filter(|r| !r.is_empty())
+            .collect();
+        roles.dedup();
+        if roles.is_empty() {
+            roles.push(DEFAULT_ROLE.to_string());
+        }
+        parsed.rows.push(ImportRow {
+            line,
+            email,
+            name,
+            roles,
+        });
+    }
+    Ok(parsed)
+}
+
+/// The per-row report offered for download, ordered by line.
+pub fn report_csv(results: &[RowResult]) -> String {
+    let mut sorted: Vec<&RowResult> = results.iter().collect();
+    sorted.sort_by_key(|r| r.line);
+    let mut out = String::from("line,email,outcome,detail\n");
+    for r in sorted {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            r.line,
+            escape(&r.email),
+            r.outcome.as_str(),
+            escape(r.detail.as_deref().unwrap_or(""))
+        ));
+    }
+    out
+}
+
+pub(crate) fn escape(field: &str) -> String {
+    // A leading formula character would be evaluated by spreadsheet apps.
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Split RFC 4180 CSV into records tagged with the line they start on.
+/// Quoted fields may contain commas, doubled quotes and line breaks.
+fn parse_records(input: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            '\n' if in_quotes => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quote starting on line {}",
+            record_line
+        ));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_columns_in_any_order_with_defaults() {
+        let parsed =
+            parse("Name,EMAIL,roles\r\nAda,ada@example.com,admin; member\r\n,bob@example.com,\r\n")
+                .unwrap();
+        assert!(parsed.invalid.is_empty());
+        assert_eq!(
+            parsed.rows,
+            vec![
+                ImportRow {
+                    line: 2,
+                    email: "ada@example.com".to_string(),
+                    name: Some("Ada".to_string()),
+                    roles: vec!["admin".to_string(), "member".to_string()],
+                },
+                ImportRow {
+                    line: 3,
+                    email: "bob@example.com".to_string(),
+                    name: None,
+                    roles: vec!["member".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_fields_keep_commas_quotes_and_line_breaks() {
+        let parsed =
+            parse("email,name\n\"c@example.com\",\"Doe, \"\"J\"\"\nJr\"\nd@example.com,D\n")
+                .unwrap();
+        assert_eq!(parsed.rows[0].name.as_deref(), Some("Doe, \"J\"\nJr"));
+        // The quoted line break moves the next record to line 4.
+        assert_eq!(parsed.rows[1].line, 4);
+    }
+
+    #[test]
+    fn bad_rows_are_reported_not_fatal() {
+        let parsed = parse("email\nnot-an-email\n\nA@example.com\na@example.com\n,\n").unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.rows[0].email, "a@example.com");
+        let details: Vec<_> = parsed
+            .invalid
+            .iter()
+            .map(|r| (r.line, r.detail.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            details,
+            vec![
+                (2, "Invalid email".to_string()),
+                (5, "Duplicate email in this file".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unusable_files_are_rejected() {
+        assert!(parse("").is_err());
+        assert!(parse("name,roles\nAda,admin\n").is_err());
+        assert!(parse("email\n\"open@example.com\n").is_err());
+        let many = format!("email\n{}", "x@example.com\n".repeat(MAX_ROWS + 1));
+        assert!(parse(&many).is_err());
+    }
+
+    #[test]
+    fn report_escapes_fields_and_sorts_by_line() {
+        let report = report_csv(&[
+            RowResult::invalid(3, "=cmd@example.com", "Unknown role \"x\""),
+            RowResult {
+                line: 2,
+                email: "a@example.com".to_string(),
+                outcome: RowOutcome::Added,
+                detail: None,
+            },
+        ]);
+        assert_eq!(
+            report,
+            "line,email,outcome,detail\n\
+             2,a@example.com,added,\n\
+             3,'=cmd@example.com,invalid,\"Unknown role \"\"x\"\"\"\n"
+        );
+    }
+}
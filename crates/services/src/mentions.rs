@@ -0,0 +1,92 @@
+//! `@mentions` written in message content.
+//!
+//! `@username` mentions a user, `@channel` and `@everyone` everyone in the
+//! room, `@here` those online. A mention only counts at the start of a word
+//! (`ada@example.com` mentions nobody) and outside backtick code. Usernames
+//! are matched case-insensitively; trailing punctuation such as the dot in
+//! `thanks @ada.` is not part of the name.
+
+use std::collections::HashSet;
+
+/// Longest username looked up; longer runs are not mentions.
+pub const MAX_USERNAME_LEN: usize = 64;
+
+/// Mentions found in a message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Parsed {
+    /// Lowercased usernames, each once, in order of appearance.
+    pub usernames: Vec<String>,
+    /// `@channel` or `@everyone`.
+    pub everyone: bool,
+    /// `@here`.
+    pub here: bool,
+}
+
+/// The mentions in `content`.
+pub fn parse(content: &str) -> Parsed {
+    let bytes = content.as_bytes();
+    let mut parsed = Parsed::default();
+    let mut seen = HashSet::new();
+    let mut in_code = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                in_code = !in_code;
+                // A ``` fence toggles once, not three times.
+                while i + 1 < bytes.len() && bytes[i + 1] == b'`' {
+                    i += 1;
+                }
+            }
+            b'@' if !in_code && (i == 0 || !is_name_byte(bytes[i - 1])) => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && is_name_byte(bytes[end]) {
+                    end += 1;
+                }
+                let name = content[start..end].trim_end_matches(['.', '-']);
+                if !name.is_empty() && name.len() <= MAX_USERNAME_LEN {
+                    match name.to_ascii_lowercase().as_str() {
+                        "channel" | "everyone" => parsed.everyone = true,
+                        "here" => parsed.here = true,
+                        name => {
+                            if seen.insert(name.to_string()) {
+                                parsed.usernames.push(name.to_string());
+                            }
+                        }
+                    }
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parsed
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_users_and_room_mentions() {
+        let parsed = parse("@Ada and @bob_s., see @channel (cc @ada, @here)");
+        assert_eq!(parsed.usernames, vec!["ada", "bob_s"]);
+        assert!(parsed.everyone);
+        assert!(parsed.here);
+        assert_eq!(parse("@everyone").usernames, Vec::<String>::new());
+        assert!(parse("@everyone").everyone);
+    }
+
+    #[test]
+    fn skips_emails_code_and_bare_at_signs() {
+        let parsed = parse("mail ada@example.com, `@bob` or\n```\n@carol\n```\n@ alone @");
+        assert_eq!(parsed, Parsed::default());
+    }
+}
@@ -0,0 +1,232 @@
+//! Monthly archive partitions for old messages.
+//!
+//! Messages older than `message_archive.older_than_days` move from
+//! `messages` into `messages_archive_YYYY_MM`, one collection per calendar
+//! month of `created_at`. `message_archive_partitions` records how many
+//! listable messages each room has in each month so room history can page
+//! across the hot collection and the archives without counting them all
+//! (see [`crate::dao::MessageDao::find_in_room`]).
+
+use std::collections::BTreeMap;
+
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database, IndexModel,
+    error::{ErrorKind, InsertManyError},
+};
+use roomler_ai_config::MessageArchiveSettings;
+use roomler_ai_db::models::{Message, MessageArchivePartition};
+
+use crate::dao::base::DaoResult;
+
+/// `YYYY_MM` (UTC) of a timestamp, the suffix of its archive collection.
+pub fn month_key(at: DateTime) -> String {
+    at.to_chrono().format("%Y_%m").to_string()
+}
+
+/// Split a page (`skip`, `limit`) over consecutive segments of known size,
+/// returning `(segment index, skip within it, limit within it)` for every
+/// segment the page touches.
+pub fn plan_page(counts: &[u64], mut skip: u64, mut limit: u64) -> Vec<(usize, u64, u64)> {
+    let mut reads = Vec::new();
+    for (idx, &count) in counts.iter().enumerate() {
+        if limit == 0 {
+            break;
+        }
+        if skip >= count {
+            skip -= count;
+            continue;
+        }
+        let take = (count - skip).min(limit);
+        reads.push((idx, skip, take));
+        limit -= take;
+        skip = 0;
+    }
+    reads
+}
+
+fn is_duplicate_only(e: &mongodb::error::Error) -> bool {
+    match e.kind.as_ref() {
+        ErrorKind::InsertMany(InsertManyError {
+            write_errors: Some(errors),
+            write_concern_error: None,
+            ..
+        }) => errors.iter().all(|we| we.code == 11000),
+        _ => false,
+    }
+}
+
+pub struct MessageArchiver {
+    db: Database,
+    messages: Collection<Document>,
+    partitions: Collection<MessageArchivePartition>,
+    settings: MessageArchiveSettings,
+}
+
+impl MessageArchiver {
+    pub fn new(db: &Database, settings: &MessageArchiveSettings) -> Self {
+        Self {
+            db: db.clone(),
+            messages: db.collection(Message::COLLECTION),
+            partitions: db.collection(MessageArchivePartition::COLLECTION),
+            settings: settings.clone(),
+        }
+    }
+
+    /// Messages created before this are due for archiving.
+    pub fn cutoff(&self) -> DateTime {
+        let age = chrono::Duration::days(i64::from(self.settings.older_than_days));
+        DateTime::from_chrono(chrono::Utc::now() - age)
+    }
+
+    /// Archive everything older than `cutoff`, batch by batch. Returns how
+    /// many messages were moved.
+    pub async fn sweep(&self, cutoff: DateTime) -> DaoResult<u64> {
+        let batch_size = u64::from(self.settings.batch_size.max(1));
+        let mut moved = 0;
+        loop {
+            let n = self.archive_batch(cutoff).await?;
+            moved += n;
+            if n < batch_size {
+                return Ok(moved);
+            }
+        }
+    }
+
+    /// Move the oldest batch of due messages. Each step is idempotent:
+    /// copies already in an archive are skipped, partition counts are
+    /// recomputed rather than incremented, and originals are only removed
+    /// once their copies exist.
+    pub async fn archive_batch(&self, cutoff: DateTime) -> DaoResult<u64> {
+        let batch: Vec<Document> = self
+            .messages
+            .find(doc! { "created_at": { "$lt": cutoff } })
+            .sort(doc! { "created_at": 1 })
+            .limit(i64::from(self.settings.batch_size.max(1)))
+            .await?
+            .try_collect()
+            .await?;
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let mut by_month: BTreeMap<String, Vec<Document>> = BTreeMap::new();
+        for message in batch {
+            let Ok(created_at) = message.get_datetime("created_at") else {
+                continue;
+            };
+            by_month
+                .entry(month_key(*created_at))
+                .or_default()
+                .push(message);
+        }
+
+        let mut moved = 0;
+        for (month, messages) in by_month {
+            let collection_name = MessageArchivePartition::collection_for(&month);
+            let archive = self.db.collection::<Document>(&collection_name);
+            archive
+                .create_index(
+                    IndexModel::builder()
+                        .keys(doc! { "room_id": 1, "created_at": -1 })
+                        .build(),
+                )
+                .await?;
+
+            match archive.insert_many(&messages).ordered(false).await {
+                Ok(_) => {}
+                Err(e) if is_duplicate_only(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut rooms: BTreeMap<ObjectId, (ObjectId, DateTime, DateTime)> = BTreeMap::new();
+            let mut ids = Vec::with_capacity(messages.len());
+            for message in &messages {
+                let (Ok(id), Ok(room_id), Ok(tenant_id), Ok(created_at)) = (
+                    message.get_object_id("_id"),
+                    message.get_object_id("room_id"),
+                    message.get_object_id("tenant_id"),
+                    message.get_datetime("created_at"),
+                ) else {
+                    continue;
+                };
+                ids.push(id);
+                let entry = rooms
+                    .entry(room_id)
+                    .or_insert((tenant_id, *created_at, *created_at));
+                entry.1 = entry.1.min(*created_at);
+                entry.2 = entry.2.max(*created_at);
+            }
+
+            for (room_id, (tenant_id, oldest_at, newest_at)) in rooms {
+                let count = archive
+                    .count_documents(
+                        doc! { "room_id": room_id, "deleted_at": null, "thread_id": null },
+                    )
+                    .await?;
+                self.partitions
+                    .update_one(
+                        doc! { "room_id": room_id, "month": &month },
+                        doc! {
+                            "$set": {
+                                "tenant_id": tenant_id,
+                                "collection": &collection_name,
+                                "count": count as i64,
+                                "updated_at": DateTime::now(),
+                            },
+                            "$min": { "oldest_at": oldest_at },
+                            "$max": { "newest_at": newest_at },
+                        },
+                    )
+                    .upsert(true)
+                    .await?;
+            }
+
+            let deleted = self
+                .messages
+                .delete_many(doc! { "_id": { "$in": &ids } })
+                .await?;
+            moved += deleted.deleted_count;
+        }
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn month_key_is_utc_year_and_month() {
+        let at = DateTime::parse_rfc3339_str("2023-01-31T23:59:59Z").unwrap();
+        assert_eq!(month_key(at), "2023_01");
+        let at = DateTime::parse_rfc3339_str("2023-12-01T00:00:00Z").unwrap();
+        assert_eq!(month_key(at), "2023_12");
+    }
+
+    #[test]
+    fn plan_page_within_one_segment() {
+        assert_eq!(plan_page(&[50, 30], 0, 25), vec![(0, 0, 25)]);
+        assert_eq!(plan_page(&[50, 30], 25, 25), vec![(0, 25, 25)]);
+    }
+
+    #[test]
+    fn plan_page_spans_segments() {
+        assert_eq!(
+            plan_page(&[10, 5, 30], 8, 25),
+            vec![(0, 8, 2), (1, 0, 5), (2, 0, 18)]
+        );
+    }
+
+    #[test]
+    fn plan_page_skips_empty_and_passed_segments() {
+        assert_eq!(plan_page(&[0, 10, 0, 10], 12, 5), vec![(3, 2, 5)]);
+    }
+
+    #[test]
+    fn plan_page_past_the_end_is_empty() {
+        assert!(plan_page(&[10, 10], 20, 25).is_empty());
+        assert!(plan_page(&[], 0, 25).is_empty());
+    }
+}
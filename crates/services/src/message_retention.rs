@@ -0,0 +1,349 @@
+//! Removing channel messages past a retention policy or the plan's history
+//! cap.
+//!
+//! Every limit comes down to a cutoff: messages created before it go, from
+//! the hot collection and the monthly archive partitions alike (see
+//! [`crate::message_archive`]), together with the replies, reactions and
+//! thread subscriptions of removed messages. With archiving on, each batch
+//! is written to object storage as JSON lines before it is deleted, and
+//! nothing is deleted when that write fails.
+
+use std::sync::Arc;
+
+use bson::{Bson, DateTime, Document, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::{Collection, Database};
+use roomler_ai_config::MessageRetentionSettings;
+use roomler_ai_db::models::{Message, MessageArchivePartition, Reaction, ThreadSubscription};
+use serde::Serialize;
+
+use crate::dao::base::DaoError;
+use crate::message_archive::plan_page;
+use crate::object_storage::{ObjectStore, StorageError};
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PurgeError {
+    #[error(transparent)]
+    Dao(#[from] DaoError),
+    #[error("Failed to archive messages: {0}")]
+    Archive(#[from] StorageError),
+}
+
+impl From<mongodb::error::Error> for PurgeError {
+    fn from(e: mongodb::error::Error) -> Self {
+        Self::Dao(e.into())
+    }
+}
+
+pub type PurgeResult<T> = Result<T, PurgeError>;
+
+/// What a purge removed.
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeReport {
+    /// Messages deleted, replies included.
+    pub purged: u64,
+    /// Object storage keys the removed messages were archived under.
+    pub archived: Vec<String>,
+}
+
+impl PurgeReport {
+    pub fn add(&mut self, other: PurgeReport) {
+        self.purged += other.purged;
+        self.archived.extend(other.archived);
+    }
+}
+
+/// Messages created before this are older than `days`.
+pub fn age_cutoff(now: DateTime, days: u32) -> DateTime {
+    DateTime::from_millis(now.timestamp_millis() - i64::from(days) * MILLIS_PER_DAY)
+}
+
+/// Where a batch removed from `collection`, starting with `first_id`, is
+/// archived.
+pub fn archive_key(tenant_id: ObjectId, collection: &str, first_id: ObjectId) -> String {
+    format!(
+        "retention/{}/{}/{}.jsonl",
+        tenant_id.to_hex(),
+        collection,
+        first_id.to_hex()
+    )
+}
+
+/// One message per line, in relaxed extended JSON.
+pub fn to_json_lines(messages: &[Document]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for message in messages {
+        out.extend_from_slice(
+            Bson::Document(message.clone())
+                .into_relaxed_extjson()
+                .to_string()
+                .as_bytes(),
+        );
+        out.push(b'\n');
+    }
+    out
+}
+
+pub struct MessagePurger {
+    db: Database,
+    messages: Collection<Document>,
+    partitions: Collection<MessageArchivePartition>,
+    store: Arc<ObjectStore>,
+    batch_size: u32,
+}
+
+impl MessagePurger {
+    pub fn new(
+        db: &Database,
+        store: Arc<ObjectStore>,
+        settings: &MessageRetentionSettings,
+    ) -> Self {
+        Self {
+            db: db.clone(),
+            messages: db.collection(Message::COLLECTION),
+            partitions: db.collection(MessageArchivePartition::COLLECTION),
+            store,
+            batch_size: settings.batch_size.max(1),
+        }
+    }
+
+    /// The cutoff that keeps the newest `keep` top-level messages matching
+    /// `scope` (a room or a tenant), archives included, or `None` when
+    /// there aren't more than that.
+    pub async fn count_cutoff(&self, scope: &Document, keep: u64) -> PurgeResult<Option<DateTime>> {
+        let mut listable = scope.clone();
+        listable.insert("deleted_at", Bson::Null);
+        listable.insert("thread_id", Bson::Null);
+
+        // Segments newest to oldest: the hot collection, then each month.
+        let mut collections = vec![Message::COLLECTION.to_string()];
+        let mut counts = vec![self.messages.count_documents(listable.clone()).await?];
+        let mut partition_filter = scope.clone();
+        partition_filter.insert("count", doc! { "$gt": 0 });
+        let mut partitions = self
+            .partitions
+            .find(partition_filter)
+            .sort(doc! { "month": -1 })
+            .await?;
+        while let Some(partition) = partitions.try_next().await? {
+            match (collections.last(), counts.last_mut()) {
+                (Some(last), Some(count)) if *last == partition.collection => {
+                    *count += partition.count
+                }
+                _ => {
+                    collections.push(partition.collection);
+                    counts.push(partition.count);
+                }
+            }
+        }
+        if counts.iter().sum::<u64>() <= keep {
+            return Ok(None);
+        }
+
+        let Some(&(idx, skip, _)) = plan_page(&counts, keep.max(1) - 1, 1).first() else {
+            return Ok(None);
+        };
+        let oldest_kept = self
+            .db
+            .collection::<Document>(&collections[idx])
+            .find_one(listable)
+            .sort(doc! { "created_at": -1 })
+            .skip(skip)
+            .await?;
+        Ok(oldest_kept.and_then(|m| m.get_datetime("created_at").ok().copied()))
+    }
+
+    /// Remove the messages matching `scope` created before `before`.
+    pub async fn purge_before(
+        &self,
+        scope: &Document,
+        before: DateTime,
+        archive: bool,
+    ) -> PurgeResult<PurgeReport> {
+        let mut report = PurgeReport::default();
+        let mut filter = scope.clone();
+        filter.insert("created_at", doc! { "$lt": before });
+        self.purge(Message::COLLECTION, &filter, archive, &mut report)
+            .await?;
+
+        let mut partition_filter = scope.clone();
+        partition_filter.insert("oldest_at", doc! { "$lt": before });
+        let partitions: Vec<MessageArchivePartition> = self
+            .partitions
+            .find(partition_filter)
+            .await?
+            .try_collect()
+            .await?;
+        for partition in partitions {
+            let filter = doc! { "room_id": partition.room_id, "created_at": { "$lt": before } };
+            self.purge(&partition.collection, &filter, archive, &mut report)
+                .await?;
+            self.refresh_partition(&partition).await?;
+        }
+        Ok(report)
+    }
+
+    /// Remove what `filter` matches in `collection`, oldest first, batch by
+    /// batch.
+    async fn purge(
+        &self,
+        collection: &str,
+        filter: &Document,
+        archive: bool,
+        report: &mut PurgeReport,
+    ) -> PurgeResult<()> {
+        let source = self.db.collection::<Document>(collection);
+        loop {
+            let batch: Vec<Document> = source
+                .find(filter.clone())
+                .sort(doc! { "created_at": 1 })
+                .limit(i64::from(self.batch_size))
+                .await?
+                .try_collect()
+                .await?;
+            let done = batch.len() < self.batch_size as usize;
+            if batch.is_empty() {
+                return Ok(());
+            }
+
+            // Replies first, so a failure never leaves them without a root.
+            // They are looked up in the hot collection and this one; replies
+            // archived in a later month than their root stay.
+            let ids: Vec<ObjectId> = batch
+                .iter()
+                .filter_map(|m| m.get_object_id("_id").ok())
+                .collect();
+            let mut reply_collections = vec![Message::COLLECTION];
+            if collection != Message::COLLECTION {
+                reply_collections.push(collection);
+            }
+            for name in reply_collections {
+                let replies: Vec<Document> = self
+                    .db
+                    .collection::<Document>(name)
+                    .find(doc! { "thread_id": { "$in": &ids } })
+                    .await?
+                    .try_collect()
+                    .await?;
+                self.remove(name, replies, archive, report).await?;
+            }
+            self.remove(collection, batch, archive, report).await?;
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn remove(
+        &self,
+        collection: &str,
+        messages: Vec<Document>,
+        archive: bool,
+        report: &mut PurgeReport,
+    ) -> PurgeResult<()> {
+        let ids: Vec<ObjectId> = messages
+            .iter()
+            .filter_map(|m| m.get_object_id("_id").ok())
+            .collect();
+        let (Some(first), Some(&first_id)) = (messages.first(), ids.first()) else {
+            return Ok(());
+        };
+
+        if archive {
+            let tenant_id = first.get_object_id("tenant_id").unwrap_or_default();
+            let key = archive_key(tenant_id, collection, first_id);
+            self.store.put(&key, to_json_lines(&messages)).await?;
+            report.archived.push(key);
+        }
+
+        self.db
+            .collection::<Document>(Reaction::COLLECTION)
+            .delete_many(doc! { "message_id": { "$in": &ids } })
+            .await?;
+        self.db
+            .collection::<Document>(ThreadSubscription::COLLECTION)
+            .delete_many(doc! { "thread_id": { "$in": &ids } })
+            .await?;
+        let deleted = self
+            .db
+            .collection::<Document>(collection)
+            .delete_many(doc! { "_id": { "$in": &ids } })
+            .await?;
+        report.purged += deleted.deleted_count;
+        Ok(())
+    }
+
+    /// Bring a partition's count and oldest message up to date after a
+    /// purge, or drop it once the room has nothing left in that month.
+    async fn refresh_partition(&self, partition: &MessageArchivePartition) -> PurgeResult<()> {
+        let archive = self.db.collection::<Document>(&partition.collection);
+        let key = doc! { "room_id": partition.room_id, "month": &partition.month };
+        let oldest = archive
+            .find_one(doc! { "room_id": partition.room_id })
+            .sort(doc! { "created_at": 1 })
+            .await?;
+        let Some(oldest_at) = oldest.and_then(|m| m.get_datetime("created_at").ok().copied())
+        else {
+            self.partitions.delete_one(key).await?;
+            return Ok(());
+        };
+        let count = archive
+            .count_documents(
+                doc! { "room_id": partition.room_id, "deleted_at": null, "thread_id": null },
+            )
+            .await?;
+        self.partitions
+            .update_one(
+                key,
+                doc! {
+                    "$set": {
+                        "count": count as i64,
+                        "oldest_at": oldest_at,
+                        "updated_at": DateTime::now(),
+                    },
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn age_cutoff_counts_whole_days_back() {
+        let now = DateTime::parse_rfc3339_str("2024-03-10T12:00:00Z").unwrap();
+        assert_eq!(
+            age_cutoff(now, 30),
+            DateTime::parse_rfc3339_str("2024-02-09T12:00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn archived_batches_are_json_lines_under_the_tenant() {
+        let tenant_id = ObjectId::new();
+        let id = ObjectId::new();
+        assert_eq!(
+            archive_key(tenant_id, "messages", id),
+            format!("retention/{}/messages/{}.jsonl", tenant_id, id)
+        );
+
+        let lines = to_json_lines(&[
+            doc! { "_id": id, "content": "first" },
+            doc! { "content": "second" },
+        ]);
+        let text = String::from_utf8(lines).unwrap();
+        let parsed: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0]["_id"]["$oid"], id.to_hex());
+        assert_eq!(parsed[1]["content"], "second");
+    }
+}
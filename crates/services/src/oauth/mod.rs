@@ -2,6 +2,8 @@ use roomler_ai_config::OAuthSettings;
 use serde::Deserialize;
 use thiserror::Error;
 
+use crate::outbound::{Outbound, OutboundClient};
+
 #[derive(Debug, Error)]
 pub enum OAuthError {
     #[error("Provider not configured: {0}")]
@@ -95,14 +97,14 @@ struct MicrosoftUser {
 
 pub struct OAuthService {
     settings: OAuthSettings,
-    client: reqwest::Client,
+    client: OutboundClient,
 }
 
 impl OAuthService {
-    pub fn new(settings: OAuthSettings) -> Self {
+    pub fn new(settings: OAuthSettings, outbound: &Outbound) -> Self {
         Self {
             settings,
-            client: reqwest::Client::new(),
+            client: outbound.client("oauth"),
         }
     }
 
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use bson::{Bson, doc};
+use futures::{AsyncReadExt, AsyncWriteExt, StreamExt, TryStreamExt};
+use mongodb::{
+    Database,
+    error::{ErrorKind, GridFsErrorKind},
+    gridfs::GridFsBucket,
+    options::GridFsBucketOptions,
+};
+use roomler_ai_db::models::StorageProvider;
+
+use super::{ByteStream, ObjectStorage, READ_CHUNK_SIZE, StorageError, StorageResult};
+
+/// Objects in a MongoDB GridFS bucket, keyed by filename. Each write adds a
+/// new revision and removes the older ones once it's complete, so readers
+/// always get a whole object.
+pub struct GridFsStorage {
+    bucket: GridFsBucket,
+}
+
+impl GridFsStorage {
+    pub fn new(db: &Database, bucket_name: &str) -> Self {
+        let options = GridFsBucketOptions::builder()
+            .bucket_name(bucket_name.to_string())
+            .build();
+        Self {
+            bucket: db.gridfs_bucket(options),
+        }
+    }
+
+    async fn revision_ids(&self, key: &str) -> StorageResult<Vec<Bson>> {
+        let files: Vec<_> = self
+            .bucket
+            .find(doc! { "filename": key })
+            .await?
+            .try_collect()
+            .await?;
+        Ok(files.into_iter().map(|f| f.id).collect())
+    }
+}
+
+fn is_file_not_found(e: &mongodb::error::Error) -> bool {
+    matches!(
+        e.kind.as_ref(),
+        ErrorKind::GridFs(GridFsErrorKind::FileNotFound { .. })
+    )
+}
+
+#[async_trait]
+impl ObjectStorage for GridFsStorage {
+    fn provider(&self) -> StorageProvider {
+        StorageProvider::GridFs
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        mut body: ByteStream<'_>,
+        max_size: u64,
+    ) -> StorageResult<u64> {
+        let mut upload = self.bucket.open_upload_stream(key).await?;
+
+        let mut size = 0u64;
+        let mut failure: Option<StorageError> = None;
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    failure = Some(e.into());
+                    break;
+                }
+            };
+            size += chunk.len() as u64;
+            if size > max_size {
+                failure = Some(StorageError::TooLarge { max: max_size });
+                break;
+            }
+            if let Err(e) = upload.write_all(&chunk).await {
+                failure = Some(e.into());
+                break;
+            }
+        }
+        if let Some(e) = failure {
+            // Drops the chunks written so far.
+            upload.abort().await?;
+            return Err(e);
+        }
+        upload.close().await?;
+
+        let new_id = upload.id().clone();
+        for id in self.revision_ids(key).await? {
+            if id != new_id {
+                self.bucket.delete(id).await?;
+            }
+        }
+        Ok(size)
+    }
+
+    async fn get_stream(&self, key: &str) -> StorageResult<ByteStream<'static>> {
+        let download = match self.bucket.open_download_stream_by_name(key).await {
+            Ok(download) => download,
+            Err(e) if is_file_not_found(&e) => return Err(StorageError::NotFound),
+            Err(e) => return Err(e.into()),
+        };
+        let stream = futures::stream::try_unfold(download, |mut download| async move {
+            let mut buf = vec![0; READ_CHUNK_SIZE];
+            let n = download.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            buf.truncate(n);
+            Ok::<_, std::io::Error>(Some((buf, download)))
+        });
+        Ok(stream.boxed())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        for id in self.revision_ids(key).await? {
+            match self.bucket.delete(id).await {
+                Ok(()) => {}
+                Err(e) if is_file_not_found(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use roomler_ai_db::models::StorageProvider;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::{ByteStream, ObjectStorage, READ_CHUNK_SIZE, StorageError, StorageResult};
+
+/// Objects as files under a root directory, keyed by relative path.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    fn provider(&self) -> StorageProvider {
+        StorageProvider::Local
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        mut body: ByteStream<'_>,
+        max_size: u64,
+    ) -> StorageResult<u64> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write next to the target and rename once complete, so readers
+        // never see a half-written object.
+        let partial = path.with_extension(format!("partial-{}", uuid::Uuid::new_v4()));
+        let mut file = tokio::fs::File::create(&partial).await?;
+
+        let mut size = 0u64;
+        let result: StorageResult<()> = async {
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk?;
+                size += chunk.len() as u64;
+                if size > max_size {
+                    return Err(StorageError::TooLarge { max: max_size });
+                }
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await?;
+            Ok(())
+        }
+        .await;
+        drop(file);
+
+        match result {
+            Ok(()) => {
+                tokio::fs::rename(&partial, &path).await?;
+                Ok(size)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn get_stream(&self, key: &str) -> StorageResult<ByteStream<'static>> {
+        let file = match tokio::fs::File::open(self.path(key)).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(StorageError::NotFound);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let stream = futures::stream::try_unfold(file, |mut file| async move {
+            let mut buf = vec![0; READ_CHUNK_SIZE];
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            buf.truncate(n);
+            Ok::<_, std::io::Error>(Some((buf, file)))
+        });
+        Ok(stream.boxed())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(chunks: &[&str]) -> ByteStream<'static> {
+        let chunks: Vec<std::io::Result<Vec<u8>>> =
+            chunks.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        futures::stream::iter(chunks).boxed()
+    }
+
+    async fn read(storage: &LocalStorage, key: &str) -> Vec<u8> {
+        let mut stream = storage.get_stream(key).await.unwrap();
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            out.extend(chunk.unwrap());
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn round_trips_and_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().to_path_buf());
+
+        let size = storage
+            .put_stream("a/b.txt", body(&["hello ", "world"]), 100)
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(read(&storage, "a/b.txt").await, b"hello world");
+
+        storage
+            .put_stream("a/b.txt", body(&["bye"]), 100)
+            .await
+            .unwrap();
+        assert_eq!(read(&storage, "a/b.txt").await, b"bye");
+
+        storage.delete("a/b.txt").await.unwrap();
+        storage.delete("a/b.txt").await.unwrap();
+        assert!(matches!(
+            storage.get_stream("a/b.txt").await,
+            Err(StorageError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn oversized_writes_keep_the_previous_object() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().to_path_buf());
+        storage.put_stream("k", body(&["old"]), 5).await.unwrap();
+
+        let err = storage
+            .put_stream("k", body(&["abc", "def"]), 5)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::TooLarge { max: 5 }));
+        assert_eq!(read(&storage, "k").await, b"old");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
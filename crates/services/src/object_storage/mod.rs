@@ -0,0 +1,210 @@
+pub mod gridfs;
+pub mod local;
+pub mod s3;
+
+use std::{path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream::BoxStream};
+use mongodb::Database;
+use reqwest::Method;
+use roomler_ai_config::{S3Settings, StorageBackend, StorageSettings};
+use roomler_ai_db::models::StorageProvider;
+
+pub use gridfs::GridFsStorage;
+pub use local::LocalStorage;
+pub use s3::{ObjectInfo, S3Storage};
+
+/// Chunks read from or written to an object.
+pub type ByteStream<'a> = BoxStream<'a, std::io::Result<Vec<u8>>>;
+
+/// Chunk size used when reading objects back.
+pub(crate) const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Object not found")]
+    NotFound,
+    #[error("Object exceeds the {max} byte limit")]
+    TooLarge { max: u64 },
+    #[error("Storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("GridFS error: {0}")]
+    Mongo(#[from] mongodb::error::Error),
+    #[error("S3 error: {0}")]
+    S3(String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// A backend that stores opaque objects by key.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    fn provider(&self) -> StorageProvider;
+
+    /// Stream `body` into `key`, replacing any existing object. Fails with
+    /// [`StorageError::TooLarge`] as soon as more than `max_size` bytes
+    /// arrive, leaving the previous object (if any) in place.
+    async fn put_stream(
+        &self,
+        key: &str,
+        body: ByteStream<'_>,
+        max_size: u64,
+    ) -> StorageResult<u64>;
+
+    async fn get_stream(&self, key: &str) -> StorageResult<ByteStream<'static>>;
+
+    /// Remove `key`. Deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> StorageResult<()>;
+}
+
+/// The configured backend for new objects, plus every backend existing
+/// objects may live in. Objects are addressed by `(provider, key)` so
+/// switching `storage.backend` doesn't orphan what was stored before.
+pub struct ObjectStore {
+    local: LocalStorage,
+    gridfs: GridFsStorage,
+    s3: S3Storage,
+    gridfs_bucket: String,
+    default: StorageProvider,
+    max_object_size: u64,
+    presign_ttl: Duration,
+}
+
+impl ObjectStore {
+    pub fn new(
+        db: &Database,
+        local_root: PathBuf,
+        settings: &StorageSettings,
+        s3: &S3Settings,
+    ) -> Self {
+        Self {
+            local: LocalStorage::new(local_root),
+            gridfs: GridFsStorage::new(db, &settings.gridfs_bucket),
+            s3: S3Storage::new(s3),
+            gridfs_bucket: settings.gridfs_bucket.clone(),
+            default: match settings.backend {
+                StorageBackend::Local => StorageProvider::Local,
+                StorageBackend::Gridfs => StorageProvider::GridFs,
+                StorageBackend::S3 => StorageProvider::S3,
+            },
+            max_object_size: settings.max_object_size_bytes,
+            presign_ttl: Duration::from_secs(settings.presign_ttl_secs.max(1)),
+        }
+    }
+
+    /// Backend that new objects are written to.
+    pub fn default_provider(&self) -> StorageProvider {
+        self.default
+    }
+
+    pub fn max_object_size(&self) -> u64 {
+        self.max_object_size
+    }
+
+    /// Bucket name recorded alongside objects written to `provider`.
+    pub fn bucket_name(&self, provider: StorageProvider) -> String {
+        match provider {
+            StorageProvider::GridFs => self.gridfs_bucket.clone(),
+            StorageProvider::S3 => self.s3.bucket().to_string(),
+            StorageProvider::Local | StorageProvider::MinIO => "local".to_string(),
+        }
+    }
+
+    /// Check that the S3 bucket answers, whichever backend is the default.
+    pub async fn ping_s3(&self) -> StorageResult<()> {
+        self.s3.ping().await
+    }
+
+    /// Backend holding objects recorded with `provider`. Rows tagged MinIO
+    /// predate pluggable storage and were written to local disk.
+    pub fn backend(&self, provider: StorageProvider) -> &dyn ObjectStorage {
+        match provider {
+            StorageProvider::GridFs => &self.gridfs,
+            StorageProvider::S3 => &self.s3,
+            StorageProvider::Local | StorageProvider::MinIO => &self.local,
+        }
+    }
+
+    /// A URL the client can `PUT` the object for `key` to, when the default
+    /// backend is S3. Other backends have no direct upload path.
+    pub fn presign_put(&self, key: &str) -> Option<(String, Duration)> {
+        (self.default == StorageProvider::S3).then(|| {
+            (
+                self.s3.presign(&Method::PUT, key, self.presign_ttl),
+                self.presign_ttl,
+            )
+        })
+    }
+
+    /// A URL the client can `GET` the S3 object for `key` from for `ttl`;
+    /// S3 answers range requests itself. `None` for other backends.
+    pub fn presign_get(
+        &self,
+        provider: StorageProvider,
+        key: &str,
+        ttl: Duration,
+    ) -> Option<String> {
+        (provider == StorageProvider::S3).then(|| self.s3.presign(&Method::GET, key, ttl))
+    }
+
+    /// Size and content type of a directly uploaded object.
+    pub async fn head_s3(&self, key: &str) -> StorageResult<ObjectInfo> {
+        self.s3.head(key).await
+    }
+
+    /// Write `bytes` to the default backend, returning where they went.
+    pub async fn put(&self, key: &str, bytes: Vec<u8>) -> StorageResult<StorageProvider> {
+        if bytes.len() as u64 > self.max_object_size {
+            return Err(StorageError::TooLarge {
+                max: self.max_object_size,
+            });
+        }
+        let body = futures::stream::once(async move { Ok(bytes) }).boxed();
+        self.put_stream(key, body)
+            .await
+            .map(|(provider, _)| provider)
+    }
+
+    /// Stream into the default backend, returning where the object went and
+    /// its size.
+    pub async fn put_stream(
+        &self,
+        key: &str,
+        body: ByteStream<'_>,
+    ) -> StorageResult<(StorageProvider, u64)> {
+        let size = self
+            .backend(self.default)
+            .put_stream(key, body, self.max_object_size)
+            .await?;
+        Ok((self.default, size))
+    }
+
+    pub async fn get_stream(
+        &self,
+        provider: StorageProvider,
+        key: &str,
+    ) -> StorageResult<ByteStream<'static>> {
+        self.backend(provider).get_stream(key).await
+    }
+
+    /// Read a whole object into memory, for callers that need the bytes at
+    /// once (e.g. sending a document to Claude).
+    pub async fn get(&self, provider: StorageProvider, key: &str) -> StorageResult<Vec<u8>> {
+        let mut stream = self.get_stream(provider, key).await?;
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            out.extend_from_slice(&chunk?);
+            if out.len() as u64 > self.max_object_size {
+                return Err(StorageError::TooLarge {
+                    max: self.max_object_size,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    pub async fn delete(&self, provider: StorageProvider, key: &str) -> StorageResult<()> {
+        self.backend(provider).delete(key).await
+    }
+}
@@ -0,0 +1,109 @@
+use bson::oid::ObjectId;
+use mongodb::Database;
+use roomler_ai_config::OnboardingSettings;
+use roomler_ai_db::models::OnboardingStep;
+
+use crate::dao::base::{DaoError, DaoResult};
+use crate::dao::message::MessageDao;
+use crate::dao::onboarding::OnboardingDao;
+use crate::dao::room::RoomDao;
+
+/// Provisions new tenants (default channels + welcome message) and tracks
+/// the per-user onboarding checklist that drives the client onboarding UI.
+pub struct OnboardingService {
+    pub dao: OnboardingDao,
+    rooms: RoomDao,
+    messages: MessageDao,
+    settings: OnboardingSettings,
+}
+
+impl OnboardingService {
+    pub fn new(db: &Database, settings: &OnboardingSettings) -> Self {
+        Self {
+            dao: OnboardingDao::new(db),
+            rooms: RoomDao::new(db),
+            messages: MessageDao::new(db),
+            settings: settings.clone(),
+        }
+    }
+
+    /// Create the configured default channels for a freshly created tenant
+    /// and post the welcome message into the first one. The owner is joined
+    /// as the channels' creator.
+    pub async fn provision_tenant(&self, tenant_id: ObjectId, owner_id: ObjectId) -> DaoResult<()> {
+        let mut first_room = None;
+        for (position, name) in self.settings.default_channels.iter().enumerate() {
+            let name = name.trim().trim_start_matches('#');
+            if name.is_empty() {
+                continue;
+            }
+            let room = match self
+                .rooms
+                .create(
+                    tenant_id,
+                    name.to_string(),
+                    None,
+                    owner_id,
+                    true,
+                    None,
+                    None,
+                )
+                .await
+            {
+                Ok(room) => room,
+                // A channel with that name already exists; leave it alone.
+                Err(DaoError::DuplicateKey(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(room_id) = room.id else { continue };
+            self.rooms.set_default(room_id, true).await?;
+            self.rooms
+                .base
+                .update_by_id(
+                    room_id,
+                    bson::doc! { "$set": { "position": position as i32 } },
+                )
+                .await?;
+            first_room.get_or_insert(room_id);
+        }
+
+        if let Some(room_id) = first_room
+            && !self.settings.welcome_message.is_empty()
+        {
+            self.messages
+                .create_system(
+                    tenant_id,
+                    room_id,
+                    owner_id,
+                    self.settings.welcome_message.clone(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Auto-join a new member to the tenant's default channels.
+    pub async fn join_default_rooms(&self, tenant_id: ObjectId, user_id: ObjectId) {
+        if let Err(e) = self.rooms.join_default_rooms(tenant_id, user_id).await {
+            tracing::warn!(%tenant_id, %user_id, %e, "Failed to join default rooms");
+        }
+    }
+
+    /// Join a new member to the channels their invite names.
+    pub async fn join_rooms(&self, tenant_id: ObjectId, user_id: ObjectId, room_ids: &[ObjectId]) {
+        if room_ids.is_empty() {
+            return;
+        }
+        if let Err(e) = self.rooms.join_rooms(tenant_id, room_ids, user_id).await {
+            tracing::warn!(%tenant_id, %user_id, %e, "Failed to join invite rooms");
+        }
+    }
+
+    /// Best-effort checklist update: a storage error is logged, never
+    /// surfaced, so onboarding tracking can't fail the action it observes.
+    pub async fn complete(&self, tenant_id: ObjectId, user_id: ObjectId, step: OnboardingStep) {
+        if let Err(e) = self.dao.complete(tenant_id, user_id, step).await {
+            tracing::warn!(%tenant_id, %user_id, ?step, %e, "Failed to record onboarding step");
+        }
+    }
+}
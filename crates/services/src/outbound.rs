@@ -0,0 +1,578 @@
+//! HTTP calls to third parties (Stripe, Giphy, OAuth providers, SendGrid,
+//! the ASR backend, document recognition and webhooks) go through one
+//! [`Outbound`] registry, so a slow or failing service costs a bounded
+//! amount of time instead of tying up request handlers.
+//!
+//! Every attempt has a timeout. Idempotent calls (`GET`, `HEAD`, `PUT`,
+//! `DELETE`, or anything carrying an `Idempotency-Key`) are retried with
+//! exponential backoff when there is no answer or it is `429` or `5xx`.
+//! Each host has a circuit breaker: after `breaker_failure_threshold`
+//! consecutive failures calls to it fail fast with
+//! [`OutboundError::CircuitOpen`] for `breaker_open_secs`, then a single
+//! trial call decides whether it closes again. Counters are kept per
+//! service, not per host, so tenants' webhook URLs don't show up in them.
+//!
+//! Clients for URLs tenants choose come from
+//! [`Outbound::public_client_with_timeout`]: they only connect to public
+//! addresses (see [`crate::public_address`]) and don't follow redirects,
+//! unless `allow_private_targets` is set.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use reqwest::multipart::Form;
+use reqwest::redirect::Policy;
+use reqwest::{Client, IntoUrl, Method, Request, RequestBuilder, Response, StatusCode, Url};
+use roomler_ai_config::OutboundSettings;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::public_address::{self, PublicResolver};
+
+/// Longest wait between two attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum OutboundError {
+    #[error("{host} is failing, calls to it are paused")]
+    CircuitOpen { host: String },
+    #[error("{host} is not a public address")]
+    PrivateTarget { host: String },
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+/// Shared by every [`OutboundClient`]; cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Outbound {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    http: Client,
+    /// For tenant-chosen URLs: public addresses only, no redirects.
+    public_http: Client,
+    settings: OutboundSettings,
+    /// Keyed by `host:port`.
+    breakers: DashMap<String, Breaker>,
+    services: DashMap<&'static str, Arc<ServiceStats>>,
+}
+
+#[derive(Debug, Default)]
+struct ServiceStats {
+    /// Attempts, retries included.
+    requests: AtomicU64,
+    failures: AtomicU64,
+    retries: AtomicU64,
+    short_circuited: AtomicU64,
+    /// Attempts that got an answer, and their summed latency.
+    answered: AtomicU64,
+    total_ms: AtomicU64,
+}
+
+/// One service's counters since startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceSnapshot {
+    pub service: &'static str,
+    /// Attempts made, retries included.
+    pub requests: u64,
+    /// Attempts without an answer or answered with `429` or `5xx`.
+    pub failures: u64,
+    pub retries: u64,
+    /// Calls refused without trying because the host's breaker was open.
+    pub short_circuited: u64,
+    /// Over attempts that got an answer.
+    pub avg_latency_ms: Option<f64>,
+    /// Hosts of this service whose breaker is open or waiting on a trial.
+    pub open_circuits: u64,
+}
+
+impl Outbound {
+    pub fn new(settings: &OutboundSettings) -> Self {
+        let http = Client::builder()
+            .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)))
+            .build()
+            .unwrap_or_default();
+        let public_http = if settings.allow_private_targets {
+            http.clone()
+        } else {
+            Client::builder()
+                .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)))
+                .dns_resolver(Arc::new(PublicResolver))
+                .redirect(Policy::none())
+                .no_proxy()
+                .build()
+                .unwrap_or_default()
+        };
+        Self {
+            inner: Arc::new(Inner {
+                http,
+                public_http,
+                settings: settings.clone(),
+                breakers: DashMap::new(),
+                services: DashMap::new(),
+            }),
+        }
+    }
+
+    /// A client for `service` with the default timeout.
+    pub fn client(&self, service: &'static str) -> OutboundClient {
+        self.client_with_timeout(
+            service,
+            Duration::from_secs(self.inner.settings.timeout_secs.max(1)),
+        )
+    }
+
+    /// A client for `service` whose attempts time out after `timeout`.
+    pub fn client_with_timeout(&self, service: &'static str, timeout: Duration) -> OutboundClient {
+        self.new_client(service, timeout, false)
+    }
+
+    /// Like [`client`](Self::client), for URLs tenants choose: only public
+    /// addresses are reached.
+    pub fn public_client(&self, service: &'static str) -> OutboundClient {
+        self.public_client_with_timeout(
+            service,
+            Duration::from_secs(self.inner.settings.timeout_secs.max(1)),
+        )
+    }
+
+    /// Like [`client_with_timeout`](Self::client_with_timeout), for URLs
+    /// tenants choose: only public addresses are reached.
+    pub fn public_client_with_timeout(
+        &self,
+        service: &'static str,
+        timeout: Duration,
+    ) -> OutboundClient {
+        self.new_client(service, timeout, !self.inner.settings.allow_private_targets)
+    }
+
+    /// Check a tenant-chosen `url` before it is saved: http(s), and, unless
+    /// `allow_private_targets` is set, resolving only to public addresses.
+    pub async fn check_public_url(&self, url: &str) -> Result<(), String> {
+        if self.inner.settings.allow_private_targets {
+            let parsed = Url::parse(url).map_err(|_| "Invalid url".to_string())?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err("Url must be http or https".to_string());
+            }
+            return Ok(());
+        }
+        public_address::check(url).await
+    }
+
+    fn new_client(&self, service: &'static str, timeout: Duration, public: bool) -> OutboundClient {
+        let stats = self.inner.services.entry(service).or_default().clone();
+        OutboundClient {
+            inner: self.inner.clone(),
+            service,
+            stats,
+            timeout,
+            public,
+        }
+    }
+
+    /// Counters of every service that has a client, by name.
+    pub fn snapshot(&self) -> Vec<ServiceSnapshot> {
+        let now = Instant::now();
+        let mut services: Vec<ServiceSnapshot> = self
+            .inner
+            .services
+            .iter()
+            .map(|entry| {
+                let (service, stats) = (*entry.key(), entry.value());
+                let answered = stats.answered.load(Ordering::Relaxed);
+                let total_ms = stats.total_ms.load(Ordering::Relaxed);
+                ServiceSnapshot {
+                    service,
+                    requests: stats.requests.load(Ordering::Relaxed),
+                    failures: stats.failures.load(Ordering::Relaxed),
+                    retries: stats.retries.load(Ordering::Relaxed),
+                    short_circuited: stats.short_circuited.load(Ordering::Relaxed),
+                    avg_latency_ms: (answered > 0).then(|| total_ms as f64 / answered as f64),
+                    open_circuits: self
+                        .inner
+                        .breakers
+                        .iter()
+                        .filter(|b| b.service == service && b.state(now) != CircuitState::Closed)
+                        .count() as u64,
+                }
+            })
+            .collect();
+        services.sort_by_key(|s| s.service);
+        services
+    }
+}
+
+/// One service's handle on the shared client.
+#[derive(Debug, Clone)]
+pub struct OutboundClient {
+    inner: Arc<Inner>,
+    service: &'static str,
+    stats: Arc<ServiceStats>,
+    timeout: Duration,
+    /// Only public addresses may be reached.
+    public: bool,
+}
+
+impl OutboundClient {
+    pub fn get(&self, url: impl IntoUrl) -> OutboundRequest<'_> {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: impl IntoUrl) -> OutboundRequest<'_> {
+        self.request(Method::POST, url)
+    }
+
+    pub fn request(&self, method: Method, url: impl IntoUrl) -> OutboundRequest<'_> {
+        OutboundRequest {
+            client: self,
+            builder: self.http().request(method, url).timeout(self.timeout),
+        }
+    }
+
+    async fn execute(&self, request: Request) -> Result<Response, OutboundError> {
+        let settings = &self.inner.settings;
+        let host = host_key(request.url());
+        // IP literals aren't resolved, so the resolver can't refuse them.
+        if self.public
+            && let Some(ip) = public_address::literal_ip(request.url())
+            && !public_address::is_public(ip)
+        {
+            return Err(OutboundError::PrivateTarget { host });
+        }
+        let attempts = if is_idempotent(&request) {
+            settings.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut request = request;
+        let mut attempt = 1;
+        loop {
+            if !self.admit(&host) {
+                self.stats.short_circuited.fetch_add(1, Ordering::Relaxed);
+                return Err(OutboundError::CircuitOpen { host });
+            }
+            // Streamed bodies (multipart uploads) can't be copied and so
+            // get a single attempt.
+            let next = (attempt < attempts).then(|| request.try_clone()).flatten();
+
+            self.stats.requests.fetch_add(1, Ordering::Relaxed);
+            let started = Instant::now();
+            let result = self.http().execute(request).await;
+            let failed = match &result {
+                Ok(response) => {
+                    self.stats.answered.fetch_add(1, Ordering::Relaxed);
+                    self.stats
+                        .total_ms
+                        .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+                    is_failure(response.status())
+                }
+                Err(_) => true,
+            };
+            if failed {
+                self.stats.failures.fetch_add(1, Ordering::Relaxed);
+            }
+            self.record(&host, !failed);
+
+            match next {
+                Some(next) if failed => {
+                    self.stats.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(backoff(settings.retry_base_ms, attempt)).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => return result.map_err(OutboundError::from),
+            }
+        }
+    }
+
+    fn http(&self) -> &Client {
+        if self.public {
+            &self.inner.public_http
+        } else {
+            &self.inner.http
+        }
+    }
+
+    fn admit(&self, host: &str) -> bool {
+        self.inner
+            .breakers
+            .entry(host.to_string())
+            .or_insert_with(|| Breaker::new(self.service))
+            .admit(Instant::now(), self.open_for())
+    }
+
+    fn record(&self, host: &str, succeeded: bool) {
+        let Some(mut breaker) = self.inner.breakers.get_mut(host) else {
+            return;
+        };
+        let tripped = breaker.record(
+            succeeded,
+            Instant::now(),
+            self.inner.settings.breaker_failure_threshold.max(1),
+            self.open_for(),
+        );
+        if tripped {
+            tracing::warn!(
+                service = self.service,
+                host,
+                failures = breaker.failures,
+                "Outbound circuit opened"
+            );
+        }
+    }
+
+    fn open_for(&self) -> Duration {
+        Duration::from_secs(self.inner.settings.breaker_open_secs)
+    }
+}
+
+/// A request being built; [`send`](Self::send) runs it through the
+/// client's retries and breaker.
+pub struct OutboundRequest<'a> {
+    client: &'a OutboundClient,
+    builder: RequestBuilder,
+}
+
+impl OutboundRequest<'_> {
+    pub fn header(self, key: &str, value: impl AsRef<str>) -> Self {
+        self.map(|b| b.header(key, value.as_ref()))
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        self.map(|b| b.query(query))
+    }
+
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> Self {
+        self.map(|b| b.form(form))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        self.map(|b| b.json(json))
+    }
+
+    pub fn body(self, body: impl Into<reqwest::Body>) -> Self {
+        self.map(|b| b.body(body))
+    }
+
+    pub fn multipart(self, form: Form) -> Self {
+        self.map(|b| b.multipart(form))
+    }
+
+    pub fn basic_auth(self, username: &str, password: Option<&str>) -> Self {
+        self.map(|b| b.basic_auth(username, password))
+    }
+
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.map(|b| b.bearer_auth(token))
+    }
+
+    pub async fn send(self) -> Result<Response, OutboundError> {
+        let request = self.builder.build()?;
+        self.client.execute(request).await
+    }
+
+    fn map(self, f: impl FnOnce(RequestBuilder) -> RequestBuilder) -> Self {
+        Self {
+            client: self.client,
+            builder: f(self.builder),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircuitState {
+    Closed,
+    Open,
+    /// The open period is over; the next call is a trial.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    /// The service that first called the host.
+    service: &'static str,
+    /// Consecutive failed attempts.
+    failures: u32,
+    /// Set while tripped.
+    open_until: Option<Instant>,
+    /// When the trial call after the open period started. A trial that
+    /// never reports back (its caller went away) is replaced after another
+    /// open period.
+    trial_started: Option<Instant>,
+}
+
+impl Breaker {
+    fn new(service: &'static str) -> Self {
+        Self {
+            service,
+            failures: 0,
+            open_until: None,
+            trial_started: None,
+        }
+    }
+
+    fn state(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether a call may go out now.
+    fn admit(&mut self, now: Instant, open_for: Duration) -> bool {
+        match self.state(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if self
+                    .trial_started
+                    .is_some_and(|started| now.duration_since(started) < open_for)
+                {
+                    return false;
+                }
+                self.trial_started = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Count an attempt's outcome. Returns whether this opened the breaker.
+    fn record(
+        &mut self,
+        succeeded: bool,
+        now: Instant,
+        threshold: u32,
+        open_for: Duration,
+    ) -> bool {
+        if succeeded {
+            *self = Self::new(self.service);
+            return false;
+        }
+        self.failures = self.failures.saturating_add(1);
+        let trial_failed = self.trial_started.is_some();
+        if trial_failed || (self.open_until.is_none() && self.failures >= threshold) {
+            self.open_until = Some(now + open_for);
+            self.trial_started = None;
+            return true;
+        }
+        false
+    }
+}
+
+fn host_key(url: &Url) -> String {
+    format!(
+        "{}:{}",
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    )
+}
+
+/// Whether sending `request` twice does no harm.
+fn is_idempotent(request: &Request) -> bool {
+    matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    ) || request.headers().contains_key("idempotency-key")
+}
+
+/// Answers that count against the host and are worth retrying.
+fn is_failure(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Wait after the `attempt`th failed attempt: `base_ms`, doubled per
+/// attempt, capped at [`MAX_BACKOFF`].
+fn backoff(base_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(base_ms.saturating_mul(factor)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_FOR: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn breaker_opens_after_consecutive_failures() {
+        let now = Instant::now();
+        let mut breaker = Breaker::new("test");
+        assert!(!breaker.record(false, now, 3, OPEN_FOR));
+        assert!(!breaker.record(false, now, 3, OPEN_FOR));
+        // A success in between starts the count over.
+        breaker.record(true, now, 3, OPEN_FOR);
+        assert!(!breaker.record(false, now, 3, OPEN_FOR));
+        assert!(!breaker.record(false, now, 3, OPEN_FOR));
+        assert!(breaker.admit(now, OPEN_FOR));
+        assert!(breaker.record(false, now, 3, OPEN_FOR));
+        assert_eq!(breaker.state(now), CircuitState::Open);
+        assert!(!breaker.admit(now + Duration::from_secs(29), OPEN_FOR));
+        // Late failures of calls already in flight don't extend it.
+        assert!(!breaker.record(false, now, 3, OPEN_FOR));
+    }
+
+    #[test]
+    fn one_trial_call_decides_after_the_open_period() {
+        let now = Instant::now();
+        let mut breaker = Breaker::new("test");
+        breaker.record(false, now, 1, OPEN_FOR);
+
+        let later = now + OPEN_FOR;
+        assert_eq!(breaker.state(later), CircuitState::HalfOpen);
+        assert!(breaker.admit(later, OPEN_FOR));
+        assert!(!breaker.admit(later, OPEN_FOR));
+        // A failed trial opens it for another period.
+        assert!(breaker.record(false, later, 1, OPEN_FOR));
+        assert!(!breaker.admit(later + Duration::from_secs(1), OPEN_FOR));
+
+        let again = later + OPEN_FOR;
+        assert!(breaker.admit(again, OPEN_FOR));
+        breaker.record(true, again, 1, OPEN_FOR);
+        assert_eq!(breaker.state(again), CircuitState::Closed);
+        assert!(breaker.admit(again, OPEN_FOR));
+        assert!(breaker.admit(again, OPEN_FOR));
+    }
+
+    #[test]
+    fn abandoned_trials_are_replaced() {
+        let now = Instant::now();
+        let mut breaker = Breaker::new("test");
+        breaker.record(false, now, 1, OPEN_FOR);
+        let later = now + OPEN_FOR;
+        assert!(breaker.admit(later, OPEN_FOR));
+        assert!(!breaker.admit(later + Duration::from_secs(29), OPEN_FOR));
+        assert!(breaker.admit(later + OPEN_FOR, OPEN_FOR));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(200, 1), Duration::from_millis(200));
+        assert_eq!(backoff(200, 2), Duration::from_millis(400));
+        assert_eq!(backoff(200, 3), Duration::from_millis(800));
+        assert_eq!(backoff(200, 20), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn only_idempotent_requests_are_retried() {
+        let client = Client::new();
+        let request = |builder: RequestBuilder| builder.build().unwrap();
+        assert!(is_idempotent(&request(client.get("http://a.test/"))));
+        assert!(is_idempotent(&request(client.delete("http://a.test/"))));
+        assert!(!is_idempotent(&request(client.post("http://a.test/"))));
+        assert!(is_idempotent(&request(
+            client
+                .post("http://a.test/")
+                .header("Idempotency-Key", "k1")
+        )));
+        assert_eq!(
+            host_key(&Url::parse("https://api.stripe.com/v1/customers").unwrap()),
+            "api.stripe.com:443"
+        );
+    }
+}
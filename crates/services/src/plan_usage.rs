@@ -0,0 +1,53 @@
+//! A tenant's usage measured against its plan's limits. The limits aren't
+//! enforced; being over one, typically after a downgrade, is reported to
+//! the tenant's admins and webhooks so they can trim members or channels.
+
+use roomler_ai_db::models::PlanLimits;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PlanUsage {
+    pub members: u64,
+    /// Live rooms other than DMs.
+    pub channels: u64,
+}
+
+/// Names of the limits `usage` is over, in a fixed order.
+pub fn exceeded_limits(limits: &PlanLimits, usage: &PlanUsage) -> Vec<String> {
+    [
+        ("max_members", usage.members, limits.max_members),
+        ("max_channels", usage.channels, limits.max_channels),
+    ]
+    .into_iter()
+    .filter(|(_, used, max)| *used > u64::from(*max))
+    .map(|(name, _, _)| name.to_string())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roomler_ai_db::models::Plan;
+
+    #[test]
+    fn only_limits_strictly_exceeded_are_reported() {
+        let usage = PlanUsage {
+            members: 10,
+            channels: 6,
+        };
+        assert_eq!(
+            exceeded_limits(&Plan::Free.limits(), &usage),
+            vec!["max_channels"]
+        );
+        assert!(exceeded_limits(&Plan::Pro.limits(), &usage).is_empty());
+
+        let usage = PlanUsage {
+            members: 11,
+            channels: 6,
+        };
+        assert_eq!(
+            exceeded_limits(&Plan::Free.limits(), &usage),
+            vec!["max_members", "max_channels"]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Per-user presence for users connected to this instance.
+//!
+//! A user's presence is what they chose (`online`, `idle`, `dnd`,
+//! `invisible`) decayed by inactivity: an online user with no client
+//! activity for `away_after_secs` shows as idle, and an online or idle user
+//! silent for `offline_after_secs` shows as offline. `dnd` and `invisible`
+//! never decay. Every method returns the new presence only when it changed,
+//! so callers broadcast exactly the transitions.
+
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_config::PresenceSettings;
+use roomler_ai_db::models::Presence;
+
+struct Tracked {
+    chosen: Presence,
+    last_active: Instant,
+    current: Presence,
+}
+
+pub struct PresenceTracker {
+    users: DashMap<ObjectId, Tracked>,
+    away_after: Duration,
+    offline_after: Duration,
+}
+
+impl PresenceTracker {
+    pub fn new(settings: &PresenceSettings) -> Self {
+        Self {
+            users: DashMap::new(),
+            away_after: Duration::from_secs(settings.away_after_secs),
+            offline_after: Duration::from_secs(settings.offline_after_secs),
+        }
+    }
+
+    /// A connection opened. A user's first connection brings them online;
+    /// further ones keep what they chose.
+    pub fn connected(&self, user_id: ObjectId, now: Instant) -> Option<Presence> {
+        let mut entry = self.users.entry(user_id).or_insert_with(|| Tracked {
+            chosen: Presence::Online,
+            last_active: now,
+            current: Presence::Offline,
+        });
+        entry.last_active = now;
+        self.refresh(&mut entry, now)
+    }
+
+    /// The user's last connection on this instance closed.
+    pub fn disconnected(&self, user_id: ObjectId) -> Option<Presence> {
+        let (_, tracked) = self.users.remove(&user_id)?;
+        (tracked.current != Presence::Offline).then_some(Presence::Offline)
+    }
+
+    /// The user did something: an online user who had decayed comes back.
+    pub fn touch(&self, user_id: ObjectId, now: Instant) -> Option<Presence> {
+        let mut entry = self.users.get_mut(&user_id)?;
+        entry.last_active = now;
+        self.refresh(&mut entry, now)
+    }
+
+    /// The user picked a presence. Choosing counts as activity.
+    pub fn set(&self, user_id: ObjectId, chosen: Presence, now: Instant) -> Option<Presence> {
+        let mut entry = self.users.get_mut(&user_id)?;
+        entry.chosen = chosen;
+        entry.last_active = now;
+        self.refresh(&mut entry, now)
+    }
+
+    /// Decay everyone who has been inactive long enough.
+    pub fn sweep(&self, now: Instant) -> Vec<(ObjectId, Presence)> {
+        self.users
+            .iter_mut()
+            .filter_map(|mut entry| {
+                let user_id = *entry.key();
+                self.refresh(&mut entry, now).map(|p| (user_id, p))
+            })
+            .collect()
+    }
+
+    /// Presence of a user connected to this instance.
+    pub fn get(&self, user_id: &ObjectId) -> Option<Presence> {
+        self.users.get(user_id).map(|t| t.current.clone())
+    }
+
+    fn refresh(&self, tracked: &mut Tracked, now: Instant) -> Option<Presence> {
+        let idle = now.saturating_duration_since(tracked.last_active);
+        let presence = match tracked.chosen {
+            Presence::Online | Presence::Idle if idle >= self.offline_after => Presence::Offline,
+            Presence::Online if idle >= self.away_after => Presence::Idle,
+            ref chosen => chosen.clone(),
+        };
+        if presence == tracked.current {
+            return None;
+        }
+        tracked.current = presence.clone();
+        Some(presence)
+    }
+}
+
+/// What users other than `user_id` are shown: invisible users appear offline.
+pub fn visible_to_others(presence: Presence) -> Presence {
+    match presence {
+        Presence::Invisible => Presence::Offline,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> PresenceTracker {
+        PresenceTracker::new(&PresenceSettings {
+            away_after_secs: 60,
+            offline_after_secs: 600,
+            sweep_interval_secs: 10,
+        })
+    }
+
+    #[test]
+    fn online_user_decays_to_idle_then_offline_and_comes_back() {
+        let t = tracker();
+        let user = ObjectId::new();
+        let start = Instant::now();
+
+        assert_eq!(t.connected(user, start), Some(Presence::Online));
+        assert!(t.sweep(start + Duration::from_secs(59)).is_empty());
+        assert_eq!(
+            t.sweep(start + Duration::from_secs(60)),
+            vec![(user, Presence::Idle)]
+        );
+        assert!(t.sweep(start + Duration::from_secs(61)).is_empty());
+        assert_eq!(
+            t.sweep(start + Duration::from_secs(600)),
+            vec![(user, Presence::Offline)]
+        );
+        assert_eq!(
+            t.touch(user, start + Duration::from_secs(601)),
+            Some(Presence::Online)
+        );
+        assert_eq!(t.touch(user, start + Duration::from_secs(602)), None);
+    }
+
+    #[test]
+    fn dnd_and_invisible_do_not_decay() {
+        let t = tracker();
+        let user = ObjectId::new();
+        let start = Instant::now();
+
+        t.connected(user, start);
+        assert_eq!(t.set(user, Presence::Dnd, start), Some(Presence::Dnd));
+        assert!(t.sweep(start + Duration::from_secs(3600)).is_empty());
+        // Another tab keeps the choice.
+        assert_eq!(t.connected(user, start), None);
+        assert_eq!(
+            t.set(user, Presence::Invisible, start),
+            Some(Presence::Invisible)
+        );
+        assert!(t.sweep(start + Duration::from_secs(3600)).is_empty());
+        assert_eq!(t.disconnected(user), Some(Presence::Offline));
+        assert_eq!(t.disconnected(user), None);
+        assert_eq!(t.connected(user, start), Some(Presence::Online));
+        assert_eq!(visible_to_others(Presence::Invisible), Presence::Offline);
+    }
+
+    #[test]
+    fn untracked_users_are_ignored() {
+        let t = tracker();
+        let user = ObjectId::new();
+        assert_eq!(t.touch(user, Instant::now()), None);
+        assert_eq!(t.set(user, Presence::Dnd, Instant::now()), None);
+        assert_eq!(t.get(&user), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! The tenant profanity filter for channel messages. A
+//! [`ProfanityFilter`] names the words to screen for — bundled lists per
+//! language plus the tenant's own — and whether a message containing them
+//! is masked, blocked or flagged for moderators.
+//!
+//! Matching is by whole word, so "class" doesn't match "ass": a word is a
+//! run of Unicode letters and digits, compared case-insensitively with the
+//! casing rules of the filter's languages (Turkish `I` lowercases to `ı`).
+//! An entry ending in `*` also matches longer words starting with it.
+//! Words inside links are left alone.
+
+use std::collections::HashSet;
+
+use roomler_ai_db::models::{ProfanityFilter, ProfanityMode};
+
+/// Most words a tenant can add to either of its lists.
+pub const MAX_WORDS: usize = 500;
+pub const MAX_WORD_LEN: usize = 50;
+pub const MAX_LOCALES: usize = 10;
+
+/// What the filter decided for a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Clean,
+    Masked { content: String, terms: Vec<String> },
+    Blocked { terms: Vec<String> },
+    Flagged { terms: Vec<String> },
+}
+
+/// Screen `content` with `filter`. `default_locale` is the tenant's,
+/// used when the filter names no languages.
+pub fn screen(filter: &ProfanityFilter, default_locale: &str, content: &str) -> Verdict {
+    if filter.mode == ProfanityMode::Off {
+        return Verdict::Clean;
+    }
+    let matcher = Matcher::new(filter, default_locale);
+    let matches = matcher.find(content);
+    if matches.is_empty() {
+        return Verdict::Clean;
+    }
+    let mut terms: Vec<String> = Vec::new();
+    for m in &matches {
+        if !terms.contains(&m.term) {
+            terms.push(m.term.clone());
+        }
+    }
+    match filter.mode {
+        ProfanityMode::Off => Verdict::Clean,
+        ProfanityMode::Mask => Verdict::Masked {
+            content: mask(content, &matches),
+            terms,
+        },
+        ProfanityMode::Block => Verdict::Blocked { terms },
+        ProfanityMode::Flag => Verdict::Flagged { terms },
+    }
+}
+
+/// Check a filter a tenant admin submitted.
+pub fn validate(filter: &ProfanityFilter) -> Result<(), String> {
+    for (name, words) in [
+        ("words", &filter.words),
+        ("allowed_words", &filter.allowed_words),
+    ] {
+        if words.len() > MAX_WORDS {
+            return Err(format!("{} can have at most {} entries", name, MAX_WORDS));
+        }
+        for word in words {
+            let stem = word.strip_suffix('*').unwrap_or(word);
+            if stem.is_empty()
+                || stem.chars().count() > MAX_WORD_LEN
+                || !stem.chars().all(char::is_alphanumeric)
+            {
+                return Err(format!(
+                    "Invalid entry in {}: {:?}; use a single word of up to {} letters or digits, optionally ending in *",
+                    name, word, MAX_WORD_LEN
+                ));
+            }
+        }
+    }
+    if filter.locales.len() > MAX_LOCALES {
+        return Err(format!("locales can have at most {} entries", MAX_LOCALES));
+    }
+    for locale in &filter.locales {
+        let valid = (2..=35).contains(&locale.len())
+            && locale
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!("Invalid locale: {:?}", locale));
+        }
+    }
+    Ok(())
+}
+
+/// One matched word: its byte range in the content and the list entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Match {
+    start: usize,
+    end: usize,
+    term: String,
+}
+
+struct Matcher {
+    /// Lowercase with Turkish and Azerbaijani dotted and dotless `i`.
+    turkic: bool,
+    exact: HashSet<String>,
+    prefixes: Vec<String>,
+    allowed: HashSet<String>,
+}
+
+impl Matcher {
+    fn new(filter: &ProfanityFilter, default_locale: &str) -> Self {
+        let locales: Vec<&str> = if filter.locales.is_empty() {
+            vec![default_locale]
+        } else {
+            filter.locales.iter().map(String::as_str).collect()
+        };
+        let languages: Vec<String> = locales.iter().map(|l| language(l)).collect();
+        let mut matcher = Self {
+            turkic: languages.iter().any(|l| l == "tr" || l == "az"),
+            exact: HashSet::new(),
+            prefixes: Vec::new(),
+            allowed: HashSet::new(),
+        };
+
+        let mut entries: Vec<&str> = filter.words.iter().map(String::as_str).collect();
+        if filter.use_default_list {
+            for lang in &languages {
+                entries.extend(bundled(lang));
+            }
+        }
+        for entry in entries {
+            match entry.strip_suffix('*') {
+                Some(stem) => matcher.prefixes.push(matcher.fold(stem)),
+                None => {
+                    matcher.exact.insert(matcher.fold(entry));
+                }
+            }
+        }
+        matcher.prefixes.retain(|p| !p.is_empty());
+        matcher.allowed = filter
+            .allowed_words
+            .iter()
+            .map(|w| matcher.fold(w))
+            .collect();
+        matcher
+    }
+
+    fn fold(&self, word: &str) -> String {
+        if !self.turkic {
+            return word.trim().to_lowercase();
+        }
+        word.trim()
+            .chars()
+            .map(|c| match c {
+                'I' => 'ı',
+                'İ' => 'i',
+                c => c,
+            })
+            .collect::<String>()
+            .to_lowercase()
+    }
+
+    /// The entry `word` matches, if any.
+    fn term(&self, word: &str) -> Option<String> {
+        let folded = self.fold(word);
+        if self.allowed.contains(&folded) {
+            return None;
+        }
+        if self.exact.contains(&folded) {
+            return Some(folded);
+        }
+        self.prefixes
+            .iter()
+            .find(|p| folded.starts_with(p.as_str()))
+            .map(|p| format!("{}*", p))
+    }
+
+    fn find(&self, content: &str) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut offset = 0;
+        for chunk in content.split_inclusive(char::is_whitespace) {
+            if !chunk.contains("://") {
+                let mut start = None;
+                for (i, c) in chunk.char_indices().chain([(chunk.len(), ' ')]) {
+                    match (start, c.is_alphanumeric()) {
+                        (None, true) => start = Some(i),
+                        (Some(s), false) => {
+                            if let Some(term) = self.term(&chunk[s..i]) {
+                                matches.push(Match {
+                                    start: offset + s,
+                                    end: offset + i,
+                                    term,
+                                });
+                            }
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            offset += chunk.len();
+        }
+        matches
+    }
+}
+
+/// `content` with each matched word replaced by as many asterisks.
+fn mask(content: &str, matches: &[Match]) -> String {
+    let mut masked = String::with_capacity(content.len());
+    let mut last = 0;
+    for m in matches {
+        masked.push_str(&content[last..m.start]);
+        masked.extend(std::iter::repeat_n(
+            '*',
+            content[m.start..m.end].chars().count(),
+        ));
+        last = m.end;
+    }
+    masked.push_str(&content[last..]);
+    masked
+}
+
+/// The language of a locale tag: `de-AT` is `de`.
+fn language(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// The bundled word list of a language; empty for languages without one.
+fn bundled(language: &str) -> &'static [&'static str] {
+    match language {
+        "en" => &[
+            "fuck*",
+            "motherfuck*",
+            "shit*",
+            "bullshit*",
+            "bitch*",
+            "asshole*",
+            "bastard*",
+            "cunt*",
+            "dick",
+            "dickhead*",
+            "wanker*",
+        ],
+        "de" => &[
+            "scheiße",
+            "scheisse",
+            "arschloch*",
+            "fick*",
+            "hurensohn*",
+            "wichser*",
+            "fotze*",
+        ],
+        "fr" => &[
+            "merde", "putain*", "connard*", "salope*", "enculé*", "encule*",
+        ],
+        "es" => &[
+            "mierda",
+            "puta*",
+            "cabrón",
+            "cabron",
+            "gilipollas",
+            "coño",
+            "joder",
+        ],
+        "tr" => &["siktir*", "orospu*", "piç"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(mode: ProfanityMode, words: &[&str]) -> ProfanityFilter {
+        ProfanityFilter {
+            mode,
+            words: words.iter().map(|w| w.to_string()).collect(),
+            ..ProfanityFilter::default()
+        }
+    }
+
+    #[test]
+    fn matches_whole_words_and_prefixes() {
+        let f = filter(ProfanityMode::Mask, &["darn", "heck*"]);
+        assert_eq!(
+            screen(
+                &f,
+                "en-US",
+                "Darn, the class is heckin' SHITTY: https://x.test/darn"
+            ),
+            Verdict::Masked {
+                content: "****, the class is ******' ******: https://x.test/darn".to_string(),
+                terms: vec!["darn".into(), "heck*".into(), "shit*".into()],
+            }
+        );
+        assert_eq!(screen(&f, "en-US", "Darned classic"), Verdict::Clean);
+
+        let f = ProfanityFilter {
+            allowed_words: vec!["Shitake".into()],
+            use_default_list: true,
+            ..filter(ProfanityMode::Flag, &[])
+        };
+        assert_eq!(screen(&f, "en", "shitake risotto"), Verdict::Clean);
+        assert_eq!(
+            screen(&f, "en", "oh shit"),
+            Verdict::Flagged {
+                terms: vec!["shit*".into()]
+            }
+        );
+        assert_eq!(
+            screen(&filter(ProfanityMode::Off, &["darn"]), "en", "darn"),
+            Verdict::Clean
+        );
+    }
+
+    #[test]
+    fn follows_the_filter_languages() {
+        let mut f = filter(ProfanityMode::Block, &[]);
+        assert_eq!(screen(&f, "en-US", "So eine Scheiße"), Verdict::Clean);
+        assert_eq!(
+            screen(&f, "de-AT", "So eine Scheiße"),
+            Verdict::Blocked {
+                terms: vec!["scheiße".into()]
+            }
+        );
+
+        // Turkish lowercases `I` to dotless `ı`.
+        f.words = vec!["ılık".into()];
+        f.locales = vec!["tr".into()];
+        assert_eq!(
+            screen(&f, "en-US", "ILIK"),
+            Verdict::Blocked {
+                terms: vec!["ılık".into()]
+            }
+        );
+        f.locales = vec!["en".into()];
+        assert_eq!(screen(&f, "en-US", "ILIK"), Verdict::Clean);
+    }
+
+    #[test]
+    fn validates_lists() {
+        assert!(validate(&filter(ProfanityMode::Mask, &["word", "stem*", "ñandú"])).is_ok());
+        assert!(validate(&filter(ProfanityMode::Mask, &["two words"])).is_err());
+        assert!(validate(&filter(ProfanityMode::Mask, &["*"])).is_err());
+        let f = ProfanityFilter {
+            locales: vec!["en US".into()],
+            ..ProfanityFilter::default()
+        };
+        assert!(validate(&f).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Tenants choose where webhooks and reaction rule webhooks are sent and
+//! which OpenID Connect provider signs their users in, so those URLs must
+//! not reach into the server's own network: loopback, private, link-local
+//! (where cloud metadata services answer) and other non-public addresses
+//! are refused. A URL is checked with [`check`] when it is saved, and every
+//! request resolves its host through [`PublicResolver`], so a name that
+//! later points inwards is refused too.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// Whether `ip` is reachable on the public internet.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT, 100.64.0.0/10
+        || (a == 100 && b & 0xc0 == 64)
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && b & 0xfe == 18)
+        // Reserved, 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+    let segments = ip.segments();
+    // NAT64, 64:ff9b::/96, carries an IPv4 address
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || segments[0] & 0xfe00 == 0xfc00
+        // Link-local, fe80::/10
+        || segments[0] & 0xffc0 == 0xfe80
+        // Documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+/// Check that `url` is http(s) and its host resolves only to public
+/// addresses.
+pub async fn check(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|_| "Invalid url".to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Url must be http or https".to_string());
+    }
+    if let Some(ip) = literal_ip(&parsed) {
+        return if is_public(ip) {
+            Ok(())
+        } else {
+            Err("Url must point to a public address".to_string())
+        };
+    }
+    let host = parsed.host_str().ok_or_else(|| "Invalid url".to_string())?;
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| format!("Host {host} doesn't resolve"))?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public(addr.ip())) {
+        return Err("Url must point to a public address".to_string());
+    }
+    Ok(())
+}
+
+/// The host of `url` when it is an IP address rather than a name.
+pub(crate) fn literal_ip(url: &Url) -> Option<IpAddr> {
+    let host = url.host_str()?;
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// Resolves names like the system resolver, keeping only public addresses;
+/// a name with none fails to resolve.
+#[derive(Debug)]
+pub(crate) struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} has no public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["93.184.216.34", "2606:2800:220:1::1", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn urls_to_internal_hosts_are_refused() {
+        assert!(check("http://127.0.0.1:8080/hook").await.is_err());
+        assert!(check("http://[::1]/hook").await.is_err());
+        assert!(
+            check("http://169.254.169.254/latest/meta-data")
+                .await
+                .is_err()
+        );
+        assert!(check("http://localhost/hook").await.is_err());
+        assert!(check("ftp://93.184.216.34/hook").await.is_err());
+        assert!(check("https://93.184.216.34/hook").await.is_ok());
+    }
+}
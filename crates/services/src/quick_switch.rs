@@ -0,0 +1,216 @@
+//! Ranking for the Cmd+K quick switcher. The API loads a user's
+//! [`Candidate`]s (channels, DMs, members, recent conferences) once per
+//! [`QuickSwitchCache`] TTL with their popularity already worked out, so
+//! each keystroke only matches and sorts in memory.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use serde::Serialize;
+
+/// Recency halves every this many milliseconds (a week).
+pub const RECENCY_HALF_LIFE_MS: f64 = 7.0 * 24.0 * 3600.0 * 1000.0;
+
+/// Prune expired entries once the cache holds this many users.
+const PRUNE_AT: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateKind {
+    Channel,
+    Dm,
+    Member,
+    Conference,
+}
+
+/// Something the switcher can jump to.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub kind: CandidateKind,
+    /// Room id, or user id for members.
+    pub id: ObjectId,
+    /// What the query is matched against and what is shown.
+    pub label: String,
+    /// Other names that match too, e.g. a member's username.
+    pub aliases: Vec<String>,
+    pub detail: Option<String>,
+    /// Room to open: the room itself, or a member's 1:1 DM if there is one.
+    pub room_id: Option<ObjectId>,
+    /// 0 to 1, relative to the user's other candidates of the same kind.
+    pub popularity: f64,
+    /// When the user last had anything to do with it, in epoch millis.
+    pub last_active_ms: Option<i64>,
+}
+
+/// Candidates built at an instant.
+type CachedCandidates = (Instant, Arc<Vec<Candidate>>);
+
+/// Candidates per (tenant, user), reused for the configured TTL.
+pub struct QuickSwitchCache {
+    ttl: Duration,
+    entries: DashMap<(ObjectId, ObjectId), CachedCandidates>,
+}
+
+impl QuickSwitchCache {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl_secs),
+            entries: DashMap::new(),
+        }
+    }
+
+    pub fn get(&self, tenant_id: ObjectId, user_id: ObjectId) -> Option<Arc<Vec<Candidate>>> {
+        let entry = self.entries.get(&(tenant_id, user_id))?;
+        let (loaded, candidates) = &*entry;
+        (loaded.elapsed() < self.ttl).then(|| candidates.clone())
+    }
+
+    pub fn insert(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+        candidates: Vec<Candidate>,
+    ) -> Arc<Vec<Candidate>> {
+        if self.entries.len() >= PRUNE_AT {
+            self.entries
+                .retain(|_, (loaded, _)| loaded.elapsed() < self.ttl);
+        }
+        let candidates = Arc::new(candidates);
+        self.entries
+            .insert((tenant_id, user_id), (Instant::now(), candidates.clone()));
+        candidates
+    }
+}
+
+/// `ln(1 + count)` scaled so the largest `max` maps to 1.
+pub fn popularity(count: u64, max: u64) -> f64 {
+    if max == 0 {
+        return 0.0;
+    }
+    (count as f64).ln_1p() / (max as f64).ln_1p()
+}
+
+/// 1 for just now, halving every [`RECENCY_HALF_LIFE_MS`]; 0 for never.
+pub fn recency(last_active_ms: Option<i64>, now_ms: i64) -> f64 {
+    match last_active_ms {
+        Some(at) => 0.5f64.powf((now_ms - at).max(0) as f64 / RECENCY_HALF_LIFE_MS),
+        None => 0.0,
+    }
+}
+
+/// How well `query` (already lowercased) matches `name`: 3 for the whole
+/// name, 2 for a prefix, 1 for the prefix of a later word. An empty query
+/// matches everything with 0.
+pub fn match_score(name: &str, query: &str) -> Option<f64> {
+    if query.is_empty() {
+        return Some(0.0);
+    }
+    let name = name.to_lowercase();
+    if name == query {
+        Some(3.0)
+    } else if name.starts_with(query) {
+        Some(2.0)
+    } else if name
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | '.' | ','))
+        .skip(1)
+        .any(|word| word.starts_with(query))
+    {
+        Some(1.0)
+    } else {
+        None
+    }
+}
+
+/// The match tier dominates; within it, recency and popularity decide.
+pub fn score(candidate: &Candidate, query: &str, now_ms: i64) -> Option<f64> {
+    let matched = std::iter::once(&candidate.label)
+        .chain(&candidate.aliases)
+        .filter_map(|name| match_score(name, query))
+        .max_by(f64::total_cmp)?;
+    Some(matched + 0.5 * recency(candidate.last_active_ms, now_ms) + 0.5 * candidate.popularity)
+}
+
+/// The best `limit` candidates for `query`, highest score first.
+pub fn rank<'a>(
+    candidates: &'a [Candidate],
+    query: &str,
+    now_ms: i64,
+    limit: usize,
+) -> Vec<(&'a Candidate, f64)> {
+    let query = query.trim().to_lowercase();
+    let mut ranked: Vec<(&Candidate, f64)> = candidates
+        .iter()
+        .filter_map(|c| score(c, &query, now_ms).map(|s| (c, s)))
+        .collect();
+    ranked.sort_by(|(a, sa), (b, sb)| sb.total_cmp(sa).then_with(|| a.label.cmp(&b.label)));
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(kind: CandidateKind, label: &str, last_active_ms: Option<i64>) -> Candidate {
+        Candidate {
+            kind,
+            id: ObjectId::new(),
+            label: label.to_string(),
+            aliases: Vec::new(),
+            detail: None,
+            room_id: None,
+            popularity: 0.0,
+            last_active_ms,
+        }
+    }
+
+    #[test]
+    fn matches_whole_names_then_prefixes_then_words() {
+        assert_eq!(match_score("Design", "design"), Some(3.0));
+        assert_eq!(match_score("design-review", "des"), Some(2.0));
+        assert_eq!(match_score("Weekly design sync", "des"), Some(1.0));
+        assert_eq!(match_score("undesigned", "des"), None);
+        assert_eq!(match_score("anything", ""), Some(0.0));
+    }
+
+    #[test]
+    fn recent_and_popular_break_ties() {
+        let now = 1_000 * RECENCY_HALF_LIFE_MS as i64;
+        assert_eq!(recency(Some(now), now), 1.0);
+        assert!((recency(Some(now - RECENCY_HALF_LIFE_MS as i64), now) - 0.5).abs() < 1e-9);
+        assert_eq!(recency(None, now), 0.0);
+        assert_eq!(popularity(10, 10), 1.0);
+        assert_eq!(popularity(0, 10), 0.0);
+
+        let stale = candidate(CandidateKind::Channel, "eng-old", Some(0));
+        let fresh = candidate(CandidateKind::Channel, "eng-new", Some(now));
+        let mut popular = candidate(CandidateKind::Dm, "eng-bots", None);
+        popular.popularity = 0.6;
+        let mut exact = candidate(CandidateKind::Member, "Someone", None);
+        exact.aliases.push("eng".to_string());
+        let candidates = [stale, fresh, popular, exact];
+
+        let ranked: Vec<&str> = rank(&candidates, " ENG ", now, 3)
+            .into_iter()
+            .map(|(c, _)| c.label.as_str())
+            .collect();
+        assert_eq!(ranked, ["Someone", "eng-new", "eng-bots"]);
+        assert!(rank(&candidates, "xyz", now, 10).is_empty());
+    }
+
+    #[test]
+    fn cache_expires() {
+        let (tenant, user) = (ObjectId::new(), ObjectId::new());
+        let cache = QuickSwitchCache::new(60);
+        assert!(cache.get(tenant, user).is_none());
+        cache.insert(tenant, user, vec![candidate(CandidateKind::Dm, "a", None)]);
+        assert_eq!(cache.get(tenant, user).unwrap().len(), 1);
+        assert!(cache.get(tenant, ObjectId::new()).is_none());
+
+        let expired = QuickSwitchCache::new(0);
+        expired.insert(tenant, user, Vec::new());
+        assert!(expired.get(tenant, user).is_none());
+    }
+}
@@ -0,0 +1,220 @@
+//! Reaction-triggered automations. Tenants configure
+//! [`ReactionRule`](roomler_ai_db::models::ReactionRule)s; the API matches
+//! them on every reaction add or remove and runs their action.
+//!
+//! Rules can feed each other (a webhook that reacts through the API, a
+//! posted message someone's bot reacts to), so firing is bounded here: a
+//! rule fires at most once per message and user within [`WINDOW`], and at
+//! most `max_fires_per_minute` times overall. Callers also skip reactions
+//! on system messages, which is what rules post.
+
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use roomler_ai_config::ReactionRuleSettings;
+use roomler_ai_db::models::RuleAction;
+use sha2::Sha256;
+
+use crate::outbound::{Outbound, OutboundClient};
+
+/// Window for both the per-rule budget and repeat suppression.
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+pub const MAX_NAME_LEN: usize = 100;
+pub const MAX_CONTENT_LEN: usize = 4000;
+
+/// Prune repeat-suppression entries once there are this many.
+const RECENT_PRUNE_AT: usize = 10_000;
+
+pub struct ReactionRuleEngine {
+    client: OutboundClient,
+    max_fires_per_minute: u32,
+    /// Start of each rule's current window and its firings in it.
+    budgets: DashMap<ObjectId, (Instant, u32)>,
+    /// When each (rule, message, user) last fired.
+    recent: DashMap<(ObjectId, ObjectId, ObjectId), Instant>,
+}
+
+impl ReactionRuleEngine {
+    pub fn new(settings: &ReactionRuleSettings, outbound: &Outbound) -> Self {
+        let client = outbound.public_client_with_timeout(
+            "reaction_rules",
+            Duration::from_secs(settings.webhook_timeout_secs.max(1)),
+        );
+        Self {
+            client,
+            max_fires_per_minute: settings.max_fires_per_minute,
+            budgets: DashMap::new(),
+            recent: DashMap::new(),
+        }
+    }
+
+    /// Whether `rule_id` may fire for `user_id`'s reaction on `message_id`
+    /// now. Counts the firing when it may.
+    pub fn admit(&self, rule_id: ObjectId, message_id: ObjectId, user_id: ObjectId) -> bool {
+        let now = Instant::now();
+        if self.recent.len() >= RECENT_PRUNE_AT {
+            self.recent.retain(|_, at| now.duration_since(*at) < WINDOW);
+        }
+        let key = (rule_id, message_id, user_id);
+        if let Some(at) = self.recent.get(&key)
+            && now.duration_since(*at) < WINDOW
+        {
+            return false;
+        }
+
+        let mut budget = self.budgets.entry(rule_id).or_insert((now, 0));
+        let (started, fired) = &mut *budget;
+        if now.duration_since(*started) >= WINDOW {
+            *started = now;
+            *fired = 0;
+        }
+        if *fired >= self.max_fires_per_minute {
+            return false;
+        }
+        *fired += 1;
+        drop(budget);
+
+        self.recent.insert(key, now);
+        true
+    }
+
+    /// POST `body` to `url`, signed with the rule's `secret`.
+    pub async fn deliver_webhook(
+        &self,
+        url: &str,
+        secret: &str,
+        body: Vec<u8>,
+    ) -> Result<(), String> {
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-Roomler-Signature", signature(secret, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("Webhook request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Webhook answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `body` under `secret`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Fill `{user}`, `{emoji}` and `{message_id}` into a message template.
+pub fn render(template: &str, user: &str, emoji: &str, message_id: ObjectId) -> String {
+    template
+        .replace("{user}", user)
+        .replace("{emoji}", emoji)
+        .replace("{message_id}", &message_id.to_hex())
+}
+
+/// Check a rule's name and action.
+pub fn validate(name: &str, action: &RuleAction) -> Result<(), String> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("name must be 1-{MAX_NAME_LEN} characters"));
+    }
+    match action {
+        RuleAction::Webhook { url } => {
+            let parsed = reqwest::Url::parse(url).map_err(|_| "Invalid webhook url".to_string())?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err("Webhook url must be http or https".to_string());
+            }
+        }
+        RuleAction::PostMessage { content, .. } => {
+            if content.trim().is_empty() || content.chars().count() > MAX_CONTENT_LEN {
+                return Err(format!("content must be 1-{MAX_CONTENT_LEN} characters"));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(max_fires_per_minute: u32) -> ReactionRuleEngine {
+        ReactionRuleEngine::new(
+            &ReactionRuleSettings {
+                max_rules_per_tenant: 50,
+                max_fires_per_minute,
+                webhook_timeout_secs: 5,
+            },
+            &Outbound::new(&roomler_ai_config::OutboundSettings {
+                timeout_secs: 5,
+                connect_timeout_secs: 5,
+                max_attempts: 1,
+                retry_base_ms: 100,
+                breaker_failure_threshold: 5,
+                breaker_open_secs: 30,
+                allow_private_targets: false,
+            }),
+        )
+    }
+
+    #[test]
+    fn repeats_and_bursts_are_suppressed() {
+        let engine = engine(2);
+        let rule = ObjectId::new();
+        let (m1, m2, m3) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let user = ObjectId::new();
+
+        assert!(engine.admit(rule, m1, user));
+        // Toggling the same reaction doesn't fire again.
+        assert!(!engine.admit(rule, m1, user));
+        assert!(engine.admit(rule, m2, user));
+        // Budget spent for this minute.
+        assert!(!engine.admit(rule, m3, user));
+        // Other rules have their own budget.
+        assert!(engine.admit(ObjectId::new(), m3, user));
+    }
+
+    #[test]
+    fn templates_and_actions() {
+        let mid = ObjectId::new();
+        assert_eq!(
+            render("{user} approved with {emoji}", "Ana", "✅", mid),
+            "Ana approved with ✅"
+        );
+        assert!(
+            validate(
+                "ok",
+                &RuleAction::Webhook {
+                    url: "https://example.com/hook".into()
+                }
+            )
+            .is_ok()
+        );
+        assert!(
+            validate(
+                "ok",
+                &RuleAction::Webhook {
+                    url: "ftp://example.com".into()
+                }
+            )
+            .is_err()
+        );
+        assert!(
+            validate(
+                "",
+                &RuleAction::PostMessage {
+                    room_id: None,
+                    content: "x".into()
+                }
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Scheduled read-only windows for rooms.
+//!
+//! A room's `read_only_windows` repeat weekly. While one is active, members
+//! below the window's `min_role` can't post; message creation refuses with
+//! the window's note and end time, and the room response reports it so
+//! clients can disable the composer ahead of time.
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use roomler_ai_db::models::ReadOnlyWindow;
+
+/// Most windows one room can have.
+pub const MAX_WINDOWS: usize = 20;
+
+const MAX_NOTE_CHARS: usize = 200;
+/// UTC-14:00 to UTC+14:00, the range of real-world offsets.
+const MAX_OFFSET_MINUTES: i32 = 14 * 60;
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+const SECS_PER_WEEK: i64 = 7 * SECS_PER_DAY;
+
+/// A window in effect right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveWindow<'a> {
+    pub window: &'a ReadOnlyWindow,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Minutes since midnight for an `HH:MM` time.
+pub fn parse_time(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+pub fn validate(windows: &[ReadOnlyWindow]) -> Result<(), String> {
+    if windows.len() > MAX_WINDOWS {
+        return Err(format!(
+            "A room can have at most {} read-only windows",
+            MAX_WINDOWS
+        ));
+    }
+    for window in windows {
+        if window.weekdays.is_empty() {
+            return Err("weekdays must not be empty".to_string());
+        }
+        if let Some(day) = window.weekdays.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(format!(
+                "Invalid weekday {}; use 1 (Monday) to 7 (Sunday)",
+                day
+            ));
+        }
+        for time in [&window.start, &window.end] {
+            if parse_time(time).is_none() {
+                return Err(format!("Invalid time {:?}; use HH:MM", time));
+            }
+        }
+        if window.utc_offset_minutes.abs() > MAX_OFFSET_MINUTES {
+            return Err("utc_offset_minutes must be within ±840".to_string());
+        }
+        if window
+            .note
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_NOTE_CHARS)
+        {
+            return Err(format!(
+                "note must be at most {} characters",
+                MAX_NOTE_CHARS
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The active window at `now`. When several overlap, the one demanding the
+/// highest role wins.
+pub fn active_window(windows: &[ReadOnlyWindow], now: DateTime<Utc>) -> Option<ActiveWindow<'_>> {
+    windows
+        .iter()
+        .filter_map(|window| {
+            remaining_secs(window, now).map(|secs| ActiveWindow {
+                window,
+                ends_at: now + Duration::seconds(secs),
+            })
+        })
+        .max_by(|a, b| {
+            a.window
+                .min_role
+                .cmp(&b.window.min_role)
+                .then(a.ends_at.cmp(&b.ends_at))
+        })
+}
+
+/// Seconds until `window` ends, if it is active at `now`. Back-to-back
+/// occurrences (e.g. all-day Saturday and Sunday) count as one stretch.
+fn remaining_secs(window: &ReadOnlyWindow, now: DateTime<Utc>) -> Option<i64> {
+    let mut total = 0;
+    // Eight steps cover a window that never closes.
+    for _ in 0..8 {
+        match occurrence_remaining_secs(window, now + Duration::seconds(total)) {
+            Some(secs) => total += secs,
+            None => break,
+        }
+    }
+    (total > 0).then_some(total)
+}
+
+fn occurrence_remaining_secs(window: &ReadOnlyWindow, now: DateTime<Utc>) -> Option<i64> {
+    let start = i64::from(parse_time(&window.start)?) * 60;
+    let end = i64::from(parse_time(&window.end)?) * 60;
+    // An end at or before the start falls on the next day.
+    let length = match (end - start).rem_euclid(SECS_PER_DAY) {
+        0 => SECS_PER_DAY,
+        secs => secs,
+    };
+
+    let local = now + Duration::minutes(i64::from(window.utc_offset_minutes));
+    let week_secs = i64::from(local.weekday().num_days_from_monday()) * SECS_PER_DAY
+        + i64::from(local.num_seconds_from_midnight());
+
+    window
+        .weekdays
+        .iter()
+        .filter(|d| (1..=7).contains(*d))
+        .filter_map(|&day| {
+            let opens = i64::from(day - 1) * SECS_PER_DAY + start;
+            let elapsed = (week_secs - opens).rem_euclid(SECS_PER_WEEK);
+            (elapsed < length).then_some(length - elapsed)
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roomler_ai_db::models::ChannelRole;
+
+    fn window(weekdays: &[u8], start: &str, end: &str) -> ReadOnlyWindow {
+        ReadOnlyWindow {
+            weekdays: weekdays.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+            utc_offset_minutes: 0,
+            min_role: ChannelRole::Moderator,
+            note: None,
+        }
+    }
+
+    /// 2024-01-01 was a Monday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2024-01-{:02}T{:02}:{:02}:00Z", day, hour, minute))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parse_time_accepts_only_hh_mm() {
+        assert_eq!(parse_time("00:00"), Some(0));
+        assert_eq!(parse_time("18:30"), Some(18 * 60 + 30));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("9:00"), None);
+        assert_eq!(parse_time("09:60"), None);
+        assert_eq!(parse_time("0900"), None);
+    }
+
+    #[test]
+    fn same_day_window() {
+        let windows = [window(&[1], "09:00", "17:00")];
+        assert!(active_window(&windows, at(1, 8, 59)).is_none());
+        let active = active_window(&windows, at(1, 9, 0)).unwrap();
+        assert_eq!(active.ends_at, at(1, 17, 0));
+        assert!(active_window(&windows, at(1, 17, 0)).is_none());
+        assert!(active_window(&windows, at(2, 10, 0)).is_none());
+    }
+
+    #[test]
+    fn overnight_window_runs_into_next_day() {
+        // Friday 18:00 to Saturday 08:00.
+        let windows = [window(&[5], "18:00", "08:00")];
+        assert!(active_window(&windows, at(5, 17, 0)).is_none());
+        assert_eq!(
+            active_window(&windows, at(6, 7, 0)).unwrap().ends_at,
+            at(6, 8, 0)
+        );
+        assert!(active_window(&windows, at(6, 8, 0)).is_none());
+    }
+
+    #[test]
+    fn sunday_window_wraps_into_monday() {
+        let windows = [window(&[7], "22:00", "06:00")];
+        let active = active_window(&windows, at(8, 1, 0)).unwrap();
+        assert_eq!(active.ends_at, at(8, 6, 0));
+    }
+
+    #[test]
+    fn equal_start_and_end_is_a_whole_day() {
+        let windows = [window(&[6, 7], "00:00", "00:00")];
+        assert!(active_window(&windows, at(5, 23, 59)).is_none());
+        // Saturday runs straight into Sunday.
+        assert_eq!(
+            active_window(&windows, at(6, 12, 0)).unwrap().ends_at,
+            at(8, 0, 0)
+        );
+        assert!(active_window(&windows, at(8, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn offset_shifts_local_times() {
+        // 09:00-10:00 at UTC+02:00 is 07:00-08:00 UTC.
+        let mut w = window(&[1], "09:00", "10:00");
+        w.utc_offset_minutes = 120;
+        let windows = [w];
+        assert!(active_window(&windows, at(1, 9, 30)).is_none());
+        assert_eq!(
+            active_window(&windows, at(1, 7, 30)).unwrap().ends_at,
+            at(1, 8, 0)
+        );
+    }
+
+    #[test]
+    fn highest_role_wins_when_windows_overlap() {
+        let mut owners_only = window(&[1], "12:00", "13:00");
+        owners_only.min_role = ChannelRole::Owner;
+        let windows = [window(&[1], "09:00", "17:00"), owners_only];
+        let active = active_window(&windows, at(1, 12, 30)).unwrap();
+        assert_eq!(active.window.min_role, ChannelRole::Owner);
+        assert_eq!(active.ends_at, at(1, 13, 0));
+    }
+
+    #[test]
+    fn validate_rejects_bad_windows() {
+        assert!(validate(&[window(&[1, 7], "09:00", "17:00")]).is_ok());
+        assert!(validate(&[window(&[], "09:00", "17:00")]).is_err());
+        assert!(validate(&[window(&[0], "09:00", "17:00")]).is_err());
+        assert!(validate(&[window(&[8], "09:00", "17:00")]).is_err());
+        assert!(validate(&[window(&[1], "9am", "17:00")]).is_err());
+        let mut far = window(&[1], "09:00", "17:00");
+        far.utc_offset_minutes = 15 * 60;
+        assert!(validate(&[far]).is_err());
+        let many = vec![window(&[1], "09:00", "17:00"); MAX_WINDOWS + 1];
+        assert!(validate(&many).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use bson::DateTime;
+use roomler_ai_db::models::Room;
+
+use crate::dao::base::DaoResult;
+use crate::dao::room::RoomDao;
+use crate::media::room_manager::RoomManager;
+
+/// What the startup pass did with each conference left `in_progress`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    /// Recent conferences whose media room was re-created.
+    pub recreated: Vec<String>,
+    /// Conferences marked ended (too old, or the room could not be re-created).
+    pub ended: Vec<String>,
+    /// Participant sessions closed because their connections died with the
+    /// previous process.
+    pub closed_sessions: u64,
+}
+
+impl ReconciliationReport {
+    pub fn is_empty(&self) -> bool {
+        self.recreated.is_empty() && self.ended.is_empty() && self.closed_sessions == 0
+    }
+}
+
+/// Media rooms live only in memory, so after a restart every conference
+/// still marked `in_progress` points at a room that no longer exists.
+/// Conferences started within `recreate_within` get a fresh media room so
+/// participants can reconnect; the rest are ended. Either way, open
+/// participant sessions are closed — clients that reconnect join anew.
+pub async fn reconcile_conferences(
+    rooms: &RoomDao,
+    room_manager: &RoomManager,
+    recreate_within: Duration,
+) -> DaoResult<ReconciliationReport> {
+    let mut report = ReconciliationReport::default();
+    let now_ms = DateTime::now().timestamp_millis();
+
+    for room in rooms.find_in_progress_calls().await? {
+        let Some(rid) = room.id else { continue };
+        if room_manager.has_room(&rid) {
+            continue;
+        }
+
+        report.closed_sessions += rooms.close_open_sessions(rid).await?;
+
+        if is_recent(&room, now_ms, recreate_within) {
+            match room_manager.create_room(rid).await {
+                Ok(_) => {
+                    report.recreated.push(rid.to_hex());
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!(%rid, %e, "Failed to re-create media room, ending call");
+                }
+            }
+        }
+        rooms.end_call(rid).await?;
+        report.ended.push(rid.to_hex());
+    }
+
+    Ok(report)
+}
+
+fn is_recent(room: &Room, now_ms: i64, window: Duration) -> bool {
+    room.actual_start_time
+        .is_some_and(|t| now_ms - t.timestamp_millis() <= window.as_millis() as i64)
+}
@@ -0,0 +1,172 @@
+use bson::{Document, doc, oid::ObjectId};
+use roomler_ai_db::models::{Recording, Room, Visibility, role::permissions};
+
+/// The member asking for a recording, as far as access decisions go.
+#[derive(Debug, Clone)]
+pub struct Viewer {
+    pub user_id: ObjectId,
+    pub role_ids: Vec<ObjectId>,
+    pub permissions: u64,
+    pub is_room_member: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RecordingAccess {
+    None,
+    View,
+    /// View, download and stream regardless of `allow_download`, edit the
+    /// ACL, create share links and delete.
+    Manage,
+}
+
+/// Organizers of the room (or its creator when none are set) and tenant
+/// meeting managers manage every recording in the room.
+pub fn manages_room(room: &Room, viewer: &Viewer) -> bool {
+    permissions::has(viewer.permissions, permissions::MANAGE_MEETINGS)
+        || room.creator_id == viewer.user_id
+        || room.organizer_id == Some(viewer.user_id)
+        || room.co_organizer_ids.contains(&viewer.user_id)
+}
+
+pub fn access(room: &Room, recording: &Recording, viewer: &Viewer) -> RecordingAccess {
+    if manages_room(room, viewer) {
+        return RecordingAccess::Manage;
+    }
+    let acl = &recording.acl;
+    let granted = acl.participant_ids.contains(&viewer.user_id)
+        || acl.user_ids.contains(&viewer.user_id)
+        || acl.role_ids.iter().any(|r| viewer.role_ids.contains(r))
+        || match recording.visibility {
+            Visibility::Private => false,
+            Visibility::Members => viewer.is_room_member,
+            Visibility::Organization => true,
+        };
+    if granted {
+        RecordingAccess::View
+    } else {
+        RecordingAccess::None
+    }
+}
+
+/// Mongo filter matching the recordings [`access`] lets a non-managing
+/// viewer see, so listing can paginate in the database.
+pub fn view_filter(viewer: &Viewer) -> Document {
+    let mut any_of = vec![
+        doc! { "acl.participant_ids": viewer.user_id },
+        doc! { "acl.user_ids": viewer.user_id },
+        doc! { "acl.role_ids": { "$in": &viewer.role_ids } },
+        doc! { "visibility": "organization" },
+    ];
+    if viewer.is_room_member {
+        any_of.push(doc! { "visibility": "members" });
+    }
+    doc! { "$or": any_of }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(creator_id: ObjectId) -> Room {
+        let now = bson::DateTime::now();
+        bson::from_document(doc! {
+            "tenant_id": ObjectId::new(),
+            "parent_id": null,
+            "name": "Standup",
+            "path": "standup",
+            "emoji": null,
+            "topic": null,
+            "purpose": null,
+            "icon": null,
+            "media_settings": null,
+            "conference_settings": null,
+            "conference_status": null,
+            "organizer_id": null,
+            "creator_id": creator_id,
+            "last_message_id": null,
+            "last_activity_at": null,
+            "actual_start_time": null,
+            "actual_end_time": null,
+            "created_at": now,
+            "updated_at": now,
+            "deleted_at": null,
+        })
+        .unwrap()
+    }
+
+    fn recording() -> Recording {
+        let now = bson::DateTime::now();
+        bson::from_document(doc! {
+            "tenant_id": ObjectId::new(),
+            "room_id": ObjectId::new(),
+            "recording_type": "video",
+            "status": "available",
+            "file": {
+                "storage_provider": "local",
+                "bucket": "recordings",
+                "key": "k",
+                "url": "",
+                "content_type": "video/webm",
+                "size": 0_i64,
+                "duration": 0,
+                "resolution": null,
+            },
+            "started_at": now,
+            "ended_at": now,
+            "expires_at": null,
+            "created_at": now,
+            "updated_at": now,
+            "deleted_at": null,
+        })
+        .unwrap()
+    }
+
+    fn viewer() -> Viewer {
+        Viewer {
+            user_id: ObjectId::new(),
+            role_ids: Vec::new(),
+            permissions: permissions::DEFAULT_MEMBER,
+            is_room_member: true,
+        }
+    }
+
+    #[test]
+    fn organizers_and_meeting_managers_manage() {
+        let mut v = viewer();
+        let rec = recording();
+        assert_eq!(access(&room(v.user_id), &rec, &v), RecordingAccess::Manage);
+
+        let room = room(ObjectId::new());
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::None);
+        v.permissions |= permissions::MANAGE_MEETINGS;
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::Manage);
+    }
+
+    #[test]
+    fn participants_and_grants_view() {
+        let room = room(ObjectId::new());
+        let v = viewer();
+        let mut rec = recording();
+        rec.acl.participant_ids.push(v.user_id);
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::View);
+
+        let mut rec = recording();
+        let role_id = ObjectId::new();
+        rec.acl.role_ids.push(role_id);
+        let mut v = viewer();
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::None);
+        v.role_ids.push(role_id);
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::View);
+    }
+
+    #[test]
+    fn members_visibility_requires_room_membership() {
+        let room = room(ObjectId::new());
+        let mut rec = recording();
+        rec.visibility = Visibility::Members;
+        let mut v = viewer();
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::View);
+        v.is_room_member = false;
+        assert_eq!(access(&room, &rec, &v), RecordingAccess::None);
+    }
+}
@@ -0,0 +1,254 @@
+//! Highlights cut from a conference recording with ffmpeg, and the
+//! transcript excerpt posted alongside them.
+//!
+//! Clips are stream copies: ffmpeg starts at the keyframe at or before the
+//! requested start, so a clip may begin up to a keyframe interval early.
+
+use std::{path::Path, process::Stdio, time::Duration};
+
+use bson::DateTime;
+use roomler_ai_db::models::TranscriptSegment;
+
+/// Captions are written once a phrase has been recognized, a few seconds
+/// after it was spoken; the transcript window is shifted by this much.
+pub const TRANSCRIPT_LAG_SECS: f64 = 5.0;
+
+/// Longest transcript excerpt posted with a clip.
+pub const MAX_EXCERPT_CHARS: usize = 1500;
+
+/// Check `start..end` (seconds into the recording) against its duration,
+/// when known, and the longest allowed clip.
+pub fn validate_range(start: f64, end: f64, duration: u32, max_secs: u32) -> Result<(), String> {
+    if !start.is_finite() || !end.is_finite() || start < 0.0 {
+        return Err("start_secs must be zero or more".to_string());
+    }
+    if end <= start {
+        return Err("end_secs must be after start_secs".to_string());
+    }
+    if duration > 0 && end > duration as f64 {
+        return Err(format!(
+            "end_secs is past the end of the recording ({}s)",
+            duration
+        ));
+    }
+    if end - start > max_secs as f64 {
+        return Err(format!("Clips can be at most {} seconds long", max_secs));
+    }
+    Ok(())
+}
+
+/// File extension for a recording's content type, e.g. `webm`.
+pub fn extension(content_type: &str) -> &str {
+    content_type
+        .split(';')
+        .next()
+        .and_then(|t| t.split('/').nth(1))
+        .map(str::trim)
+        .filter(|ext| !ext.is_empty())
+        .unwrap_or("webm")
+}
+
+/// `h:mm:ss`, or `m:ss` under an hour.
+pub fn timestamp(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// When captions for `start..end` of a recording that started at
+/// `started_at` were written.
+pub fn transcript_window(started_at: DateTime, start: f64, end: f64) -> (DateTime, DateTime) {
+    let at = |secs: f64| {
+        DateTime::from_millis(
+            started_at.timestamp_millis() + ((secs + TRANSCRIPT_LAG_SECS) * 1000.0) as i64,
+        )
+    };
+    (at(start), at(end))
+}
+
+/// The segments as a Markdown quote, one line per speaker turn, cut short
+/// at [`MAX_EXCERPT_CHARS`]. None without any text.
+pub fn transcript_excerpt(segments: &[TranscriptSegment]) -> Option<String> {
+    let mut turns: Vec<(&str, String)> = Vec::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match turns.last_mut() {
+            Some((speaker, said)) if *speaker == segment.speaker_name => {
+                said.push(' ');
+                said.push_str(text);
+            }
+            _ => turns.push((&segment.speaker_name, text.to_string())),
+        }
+    }
+    if turns.is_empty() {
+        return None;
+    }
+
+    let excerpt = turns
+        .iter()
+        .map(|(speaker, said)| format!("> **{}:** {}", speaker, said))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if excerpt.chars().count() <= MAX_EXCERPT_CHARS {
+        return Some(excerpt);
+    }
+    let mut cut: String = excerpt.chars().take(MAX_EXCERPT_CHARS - 1).collect();
+    cut.push('…');
+    Some(cut)
+}
+
+/// The message posted with a clip.
+pub fn message(start: f64, end: f64, comment: Option<&str>, excerpt: Option<&str>) -> String {
+    let mut parts = vec![format!(
+        "Recording highlight {}–{}",
+        timestamp(start),
+        timestamp(end)
+    )];
+    parts.extend(
+        comment
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string),
+    );
+    parts.extend(excerpt.map(str::to_string));
+    parts.join("\n\n")
+}
+
+pub fn ffmpeg_args(input: &Path, output: &Path, start: f64, end: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-loglevel".to_string(),
+        "error".to_string(),
+        "-y".to_string(),
+        "-ss".to_string(),
+        format!("{:.3}", start),
+        "-i".to_string(),
+        input.to_string_lossy().into_owned(),
+        "-t".to_string(),
+        format!("{:.3}", end - start),
+        "-c".to_string(),
+        "copy".to_string(),
+        "-avoid_negative_ts".to_string(),
+        "make_zero".to_string(),
+        output.to_string_lossy().into_owned(),
+    ]
+}
+
+/// Run ffmpeg to write `start..end` of `input` to `output`.
+pub async fn cut(
+    ffmpeg: &str,
+    input: &Path,
+    output: &Path,
+    start: f64,
+    end: f64,
+    timeout: Duration,
+) -> Result<(), String> {
+    let run = tokio::process::Command::new(ffmpeg)
+        .args(ffmpeg_args(input, output, start, end))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let out = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| format!("ffmpeg timed out after {}s", timeout.as_secs()))?
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("ffmpeg failed ({}): {}", out.status, stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::oid::ObjectId;
+
+    fn segment(speaker: &str, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            id: None,
+            tenant_id: ObjectId::new(),
+            room_id: ObjectId::new(),
+            track: "original".to_string(),
+            user_id: ObjectId::new(),
+            speaker_name: speaker.to_string(),
+            text: text.to_string(),
+            language: None,
+            confidence: None,
+            start_time: 0.0,
+            end_time: 0.0,
+            created_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        assert!(validate_range(10.0, 20.0, 60, 600).is_ok());
+        // Unknown duration
+        assert!(validate_range(10.0, 20.0, 0, 600).is_ok());
+        assert!(validate_range(-1.0, 20.0, 60, 600).is_err());
+        assert!(validate_range(20.0, 20.0, 60, 600).is_err());
+        assert!(validate_range(10.0, 61.0, 60, 600).is_err());
+        assert!(validate_range(0.0, 31.0, 0, 30).is_err());
+        assert!(validate_range(f64::NAN, 20.0, 60, 600).is_err());
+    }
+
+    #[test]
+    fn timestamps_and_extensions() {
+        assert_eq!(timestamp(65.4), "1:05");
+        assert_eq!(timestamp(3725.0), "1:02:05");
+        assert_eq!(extension("video/webm;codecs=vp8"), "webm");
+        assert_eq!(extension("video/mp4"), "mp4");
+        assert_eq!(extension(""), "webm");
+    }
+
+    #[test]
+    fn excerpts_merge_speaker_turns() {
+        let excerpt = transcript_excerpt(&[
+            segment("Ana", "Shall we ship it?"),
+            segment("Ana", "I think so."),
+            segment("Bo", " "),
+            segment("Bo", "Yes."),
+        ])
+        .unwrap();
+        assert_eq!(
+            excerpt,
+            "> **Ana:** Shall we ship it? I think so.\n> **Bo:** Yes."
+        );
+        assert_eq!(transcript_excerpt(&[segment("Ana", "")]), None);
+
+        let long = transcript_excerpt(&[segment("Ana", &"a".repeat(2 * MAX_EXCERPT_CHARS))]);
+        assert_eq!(long.unwrap().chars().count(), MAX_EXCERPT_CHARS);
+    }
+
+    #[test]
+    fn messages_list_range_comment_and_excerpt() {
+        assert_eq!(
+            message(5.0, 65.0, Some(" The decision "), Some("> **Ana:** Yes.")),
+            "Recording highlight 0:05–1:05\n\nThe decision\n\n> **Ana:** Yes."
+        );
+        assert_eq!(
+            message(5.0, 65.0, Some(""), None),
+            "Recording highlight 0:05–1:05"
+        );
+    }
+
+    #[test]
+    fn ffmpeg_copies_the_range() {
+        let args = ffmpeg_args(Path::new("in.webm"), Path::new("out.webm"), 1.5, 4.0);
+        assert_eq!(
+            args.join(" "),
+            "-hide_banner -loglevel error -y -ss 1.500 -i in.webm -t 2.500 -c copy \
+             -avoid_negative_ts make_zero out.webm"
+        );
+    }
+}
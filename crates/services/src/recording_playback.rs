@@ -0,0 +1,65 @@
+//! Expiring playback URLs for recordings, so a `<video>` element can load
+//! one without the bearer token. Recordings on local storage are served from
+//! `/api/recording/signed/{recording_id}/stream` with `expires` (Unix
+//! seconds) and `sig`, the hex HMAC-SHA256 of recording and expiry;
+//! recordings on S3 get a pre-signed URL from the storage service instead.
+
+use bson::oid::ObjectId;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub fn path(recording_id: ObjectId) -> String {
+    format!("/api/recording/signed/{}/stream", recording_id.to_hex())
+}
+
+/// [`path`] with a signature valid until `now + ttl_secs`.
+pub fn signed_path(secret: &str, recording_id: ObjectId, now: i64, ttl_secs: u64) -> (String, i64) {
+    let expires = now + ttl_secs.max(1) as i64;
+    let sig = hex::encode(mac(secret, recording_id, expires).finalize().into_bytes());
+    (
+        format!("{}?expires={}&sig={}", path(recording_id), expires, sig),
+        expires,
+    )
+}
+
+/// Whether `sig` signs this recording until `expires`, and that is after
+/// `now`.
+pub fn verify(secret: &str, recording_id: ObjectId, expires: i64, sig: &str, now: i64) -> bool {
+    if expires <= now {
+        return false;
+    }
+    let Ok(sig) = hex::decode(sig) else {
+        return false;
+    };
+    mac(secret, recording_id, expires)
+        .verify_slice(&sig)
+        .is_ok()
+}
+
+fn mac(secret: &str, recording_id: ObjectId, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("recording/{}/{}", recording_id.to_hex(), expires).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_paths_verify_until_they_expire() {
+        let id = ObjectId::new();
+        let (path, expires) = signed_path("secret", id, 1_000, 100);
+        assert_eq!(expires, 1_100);
+        assert!(path.starts_with(&format!("/api/recording/signed/{}/stream?", id.to_hex())));
+        let sig = path.rsplit("sig=").next().unwrap();
+
+        assert!(verify("secret", id, expires, sig, 1_099));
+        assert!(!verify("secret", id, expires, sig, 1_100));
+        assert!(!verify("other", id, expires, sig, 1_000));
+        assert!(!verify("secret", ObjectId::new(), expires, sig, 1_000));
+        assert!(!verify("secret", id, expires + 100, sig, 1_000));
+        assert!(!verify("secret", id, expires, "not-hex", 1_000));
+    }
+}
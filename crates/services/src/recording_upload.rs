@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use bson::{DateTime, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{Recording, RecordingPart, RecordingStatus};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use crate::dao::base::DaoError;
+use crate::dao::recording::RecordingDao;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingUploadError {
+    #[error(transparent)]
+    Dao(#[from] DaoError),
+    #[error("Recording storage error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type UploadResult<T> = Result<T, RecordingUploadError>;
+
+/// Multipart recording uploads backed by the local upload directory.
+///
+/// Each part is written next to the final object and recorded in the
+/// recording's segment manifest before the upload call returns, so a
+/// restart never loses more than the part in flight. Completing an upload
+/// concatenates the parts in part-number order, mirroring S3's
+/// CompleteMultipartUpload.
+pub struct RecordingUploadService {
+    pub dao: RecordingDao,
+    root: PathBuf,
+}
+
+/// Outcome of [`RecordingUploadService::recover_interrupted`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Finalized from their uploaded parts and marked `Partial`.
+    pub partial: u64,
+    /// Had no parts at all and were marked `Failed`.
+    pub failed: u64,
+}
+
+impl RecordingUploadService {
+    pub fn new(db: &Database, root: PathBuf) -> Self {
+        Self {
+            dao: RecordingDao::new(db),
+            root,
+        }
+    }
+
+    /// Where the assembled recording lives once completed.
+    pub fn object_path(&self, recording: &Recording) -> PathBuf {
+        self.root.join(&recording.file.key)
+    }
+
+    /// Store one part and persist it in the manifest. Re-uploading a part
+    /// number replaces the earlier copy.
+    pub async fn upload_part(
+        &self,
+        recording: &Recording,
+        part_number: u32,
+        bytes: &[u8],
+    ) -> UploadResult<RecordingPart> {
+        let Some(id) = recording.id else {
+            return Err(DaoError::NotFound.into());
+        };
+        if !matches!(recording.status, RecordingStatus::Processing) {
+            return Err(
+                DaoError::Validation("Recording is no longer being uploaded".to_string()).into(),
+            );
+        }
+        let Some(manifest) = recording.manifest.as_ref() else {
+            return Err(
+                DaoError::Validation("Recording does not accept part uploads".to_string()).into(),
+            );
+        };
+        if part_number == 0 {
+            return Err(DaoError::Validation("part_number starts at 1".to_string()).into());
+        }
+
+        let dir = self.parts_dir(recording, &manifest.upload_id);
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(dir.join(part_file_name(part_number)), bytes).await?;
+
+        let part = RecordingPart {
+            part_number,
+            etag: hex::encode(Sha256::digest(bytes)),
+            size: bytes.len() as u64,
+            uploaded_at: DateTime::now(),
+        };
+        if !self.dao.record_part(id, &part).await? {
+            return Err(
+                DaoError::Validation("Recording is no longer being uploaded".to_string()).into(),
+            );
+        }
+        Ok(part)
+    }
+
+    /// Assemble the uploaded parts into the final object and mark the
+    /// recording `status`. Returns `false` if it was already finalized.
+    pub async fn complete(
+        &self,
+        recording: &Recording,
+        status: RecordingStatus,
+    ) -> UploadResult<bool> {
+        let Some(id) = recording.id else {
+            return Err(DaoError::NotFound.into());
+        };
+        if !matches!(recording.status, RecordingStatus::Processing) {
+            return Ok(false);
+        }
+        let mut parts = recording
+            .manifest
+            .as_ref()
+            .map(|m| m.parts.clone())
+            .unwrap_or_default();
+        parts.sort_by_key(|p| p.part_number);
+
+        let mut size = 0;
+        if let Some(manifest) = recording.manifest.as_ref() {
+            let parts_dir = self.parts_dir(recording, &manifest.upload_id);
+            // Parts are removed once assembled, so a missing directory means
+            // another caller got here first; leave its output alone.
+            if !tokio::fs::try_exists(&parts_dir).await? && !parts.is_empty() {
+                return Ok(false);
+            }
+            let target = self.object_path(recording);
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut out = tokio::fs::File::create(&target).await?;
+            for part in &parts {
+                match tokio::fs::read(parts_dir.join(part_file_name(part.part_number))).await {
+                    Ok(bytes) => {
+                        out.write_all(&bytes).await?;
+                        size += bytes.len() as u64;
+                    }
+                    // A part recorded in the manifest but missing on disk ends
+                    // the usable prefix; anything after it can't be played.
+                    Err(e) => {
+                        tracing::warn!(%id, part = part.part_number, %e, "Recording part missing");
+                        break;
+                    }
+                }
+            }
+            out.flush().await?;
+            let _ = tokio::fs::remove_dir_all(&parts_dir).await;
+        }
+
+        let ended_at = match status {
+            // Best estimate for an interrupted upload: when the last part landed.
+            RecordingStatus::Partial => parts
+                .iter()
+                .map(|p| p.uploaded_at)
+                .max()
+                .unwrap_or(recording.started_at),
+            _ => DateTime::now(),
+        };
+        let duration =
+            (ended_at.timestamp_millis() - recording.started_at.timestamp_millis()).max(0) / 1000;
+
+        Ok(self
+            .dao
+            .complete(id, status, size, duration as u32, ended_at)
+            .await?)
+    }
+
+    /// Finalize every recording still uploading in a room, e.g. when its
+    /// conference ends. Returns the number finalized.
+    pub async fn complete_for_room(&self, room_id: ObjectId) -> UploadResult<u64> {
+        let mut finalized = 0;
+        for recording in self.dao.find_processing(Some(room_id)).await? {
+            if self
+                .complete(&recording, RecordingStatus::Available)
+                .await?
+            {
+                finalized += 1;
+            }
+        }
+        Ok(finalized)
+    }
+
+    /// Run once at startup: any recording still `Processing` was cut off by
+    /// the previous process. Those with parts are closed out as `Partial`,
+    /// the rest are marked `Failed`.
+    pub async fn recover_interrupted(&self) -> UploadResult<RecoveryReport> {
+        let mut report = RecoveryReport::default();
+        for recording in self.dao.find_processing(None).await? {
+            let has_parts = recording
+                .manifest
+                .as_ref()
+                .is_some_and(|m| !m.parts.is_empty());
+            if has_parts {
+                match self.complete(&recording, RecordingStatus::Partial).await {
+                    Ok(true) => report.partial += 1,
+                    Ok(false) => {}
+                    Err(e) => {
+                        tracing::warn!(id = ?recording.id, %e, "Failed to recover recording");
+                    }
+                }
+            } else if let Some(id) = recording.id
+                && self.dao.update_status(id, RecordingStatus::Failed).await?
+            {
+                report.failed += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    fn parts_dir(&self, recording: &Recording, upload_id: &str) -> PathBuf {
+        self.root
+            .join(format!("{}.parts", recording.file.key))
+            .join(upload_id)
+    }
+}
+
+fn part_file_name(part_number: u32) -> String {
+    format!("{part_number:05}")
+}
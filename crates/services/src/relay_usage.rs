@@ -0,0 +1,187 @@
+//! TURN relay accounting. Participants who can't reach the SFU directly
+//! send and receive their media through the TURN server, and every byte it
+//! relays costs money. The SFU doesn't see ICE candidate types, but a
+//! relayed transport's ICE peer is the TURN server's relay address: each
+//! sample, [`RelayMeter`] counts what transports with such a peer sent and
+//! received since the previous sample, per conference.
+
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+
+use bson::oid::ObjectId;
+
+/// One WebRTC transport's byte counters, as read from mediasoup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportSample {
+    pub room_id: ObjectId,
+    pub transport_id: String,
+    /// The ICE peer; `None` before ICE selected a candidate pair.
+    pub remote_ip: Option<IpAddr>,
+    /// Totals since the transport was created.
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Bytes relayed for a conference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelayedBytes {
+    /// From the SFU to participants.
+    pub sent: u64,
+    /// From participants to the SFU.
+    pub received: u64,
+}
+
+impl RelayedBytes {
+    pub fn total(&self) -> u64 {
+        self.sent + self.received
+    }
+}
+
+/// Turns cumulative transport counters into relayed bytes per sample.
+#[derive(Debug, Default)]
+pub struct RelayMeter {
+    last: HashMap<String, (u64, u64)>,
+}
+
+impl RelayMeter {
+    /// Bytes relayed per conference since the previous sample. Every
+    /// transport is tracked, so one that switches to the relay mid-call
+    /// only counts from then on; a transport seen for the first time counts
+    /// from zero. Transports gone since the previous sample are forgotten.
+    pub fn sample(
+        &mut self,
+        samples: &[TransportSample],
+        relay_ips: &HashSet<IpAddr>,
+    ) -> HashMap<ObjectId, RelayedBytes> {
+        let mut relayed: HashMap<ObjectId, RelayedBytes> = HashMap::new();
+        let mut last = HashMap::with_capacity(samples.len());
+        for s in samples {
+            let (prev_sent, prev_received) =
+                self.last.get(&s.transport_id).copied().unwrap_or_default();
+            if s.remote_ip.is_some_and(|ip| relay_ips.contains(&ip)) {
+                let bytes = relayed.entry(s.room_id).or_default();
+                bytes.sent += s.bytes_sent.saturating_sub(prev_sent);
+                bytes.received += s.bytes_received.saturating_sub(prev_received);
+            }
+            last.insert(s.transport_id.clone(), (s.bytes_sent, s.bytes_received));
+        }
+        self.last = last;
+        relayed.retain(|_, bytes| bytes.total() > 0);
+        relayed
+    }
+}
+
+/// Parse `turn.relay_ips`, a comma-separated list of IP addresses.
+pub fn parse_relay_ips(list: &str) -> Result<HashSet<IpAddr>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| {
+            ip.parse()
+                .map_err(|_| format!("Invalid TURN relay address: {:?}", ip))
+        })
+        .collect()
+}
+
+/// Host and port of a TURN url such as `turn:turn.example.com:3478` or
+/// `turns:[2001:db8::1]:5349?transport=tcp`. The port defaults to 3478,
+/// or 5349 for `turns:`.
+pub fn turn_host(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.split_once(':')?;
+    let default_port = match scheme {
+        "turn" => 3478,
+        "turns" => 5349,
+        _ => return None,
+    };
+    let rest = rest.split('?').next().unwrap_or_default();
+    let (host, port) = match rest.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6.split_once(']')?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match rest.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (rest, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+/// Bytes in `mb` megabytes.
+pub fn megabytes(mb: u64) -> u64 {
+    mb.saturating_mul(1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(room_id: ObjectId, id: &str, ip: &str, sent: u64, received: u64) -> TransportSample {
+        TransportSample {
+            room_id,
+            transport_id: id.to_string(),
+            remote_ip: ip.parse().ok(),
+            bytes_sent: sent,
+            bytes_received: received,
+        }
+    }
+
+    #[test]
+    fn counts_relayed_transports_between_samples() {
+        let room = ObjectId::new();
+        let relay_ips = parse_relay_ips("203.0.113.7, 2001:db8::7").unwrap();
+        let mut meter = RelayMeter::default();
+
+        let first = meter.sample(
+            &[
+                sample(room, "a", "203.0.113.7", 1_000, 400),
+                sample(room, "b", "198.51.100.1", 5_000, 5_000),
+                sample(room, "c", "", 0, 0),
+            ],
+            &relay_ips,
+        );
+        assert_eq!(
+            first[&room],
+            RelayedBytes {
+                sent: 1_000,
+                received: 400
+            }
+        );
+
+        // `b` moved to the relay: only its growth since then counts.
+        let second = meter.sample(
+            &[
+                sample(room, "a", "203.0.113.7", 1_500, 400),
+                sample(room, "b", "203.0.113.7", 5_100, 5_050),
+            ],
+            &relay_ips,
+        );
+        assert_eq!(second[&room].total(), 500 + 100 + 50);
+
+        let idle = meter.sample(&[sample(room, "a", "203.0.113.7", 1_500, 400)], &relay_ips);
+        assert!(idle.is_empty());
+    }
+
+    #[test]
+    fn parses_turn_settings() {
+        assert!(parse_relay_ips("").unwrap().is_empty());
+        assert!(parse_relay_ips("turn.example.com").is_err());
+        assert_eq!(
+            turn_host("turn:turn.example.com:3478?transport=tcp"),
+            Some(("turn.example.com".to_string(), 3478))
+        );
+        assert_eq!(
+            turn_host("turns:[2001:db8::1]:443"),
+            Some(("2001:db8::1".to_string(), 443))
+        );
+        assert_eq!(
+            turn_host("turns:turn.example.com"),
+            Some(("turn.example.com".to_string(), 5349))
+        );
+        assert_eq!(turn_host("stun:stun.example.com:3478"), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Sandbox tenants for integration developers.
+//!
+//! A tenant is created as a sandbox or not, and that never changes, so a
+//! reset can't reach production data and the flag can be cached for the
+//! life of the process. A reset wipes the tenant's conversation content
+//! (messages including archived months, shared drafts, reactions,
+//! conference chat, notifications, files with their stored objects and the
+//! custom emoji drawn from them) and keeps members, rooms, roles and configuration.
+
+use std::collections::HashSet;
+
+use bson::{Document, doc, oid::ObjectId};
+use dashmap::DashMap;
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{
+    CallChatMessage, CustomEmoji, File, FileBlob, Message, MessageArchivePartition, Notification,
+    PendingUpload, Reaction, Room, RoomMember, SharedDraft, StorageProvider, ThreadSubscription,
+};
+use serde::Serialize;
+
+use crate::dao::{base::DaoResult, tenant::TenantDao};
+use crate::object_storage::ObjectStore;
+
+/// Which tenants are sandboxes, looked up once per tenant.
+#[derive(Default)]
+pub struct SandboxTenants {
+    known: DashMap<ObjectId, bool>,
+}
+
+impl SandboxTenants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// False for tenants that don't exist.
+    pub async fn is_sandbox(&self, tenants: &TenantDao, tenant_id: ObjectId) -> DaoResult<bool> {
+        if let Some(known) = self.known.get(&tenant_id) {
+            return Ok(*known);
+        }
+        let Some(tenant) = tenants.base.find_one(doc! { "_id": tenant_id }).await? else {
+            return Ok(false);
+        };
+        self.known.insert(tenant_id, tenant.is_sandbox);
+        Ok(tenant.is_sandbox)
+    }
+}
+
+/// What a reset removed.
+#[derive(Debug, Default, Serialize)]
+pub struct ResetReport {
+    pub messages: u64,
+    pub reactions: u64,
+    pub conference_messages: u64,
+    pub notifications: u64,
+    pub files: u64,
+    /// Stored objects deleted; ones that fail to delete are logged and
+    /// left behind.
+    pub objects: u64,
+}
+
+/// Wipe `tenant_id`'s content. The caller checks that it is a sandbox.
+pub async fn reset_tenant(
+    db: &Database,
+    store: &ObjectStore,
+    tenant_id: ObjectId,
+) -> DaoResult<ResetReport> {
+    let by_tenant = doc! { "tenant_id": tenant_id };
+    let mut report = ResetReport::default();
+
+    // Stored objects first, while the rows pointing at them still exist.
+    // Files sharing content share a key, so each is deleted once.
+    let mut keys = HashSet::new();
+    let mut objects: Vec<(StorageProvider, String)> = Vec::new();
+    let mut files = db
+        .collection::<File>(File::COLLECTION)
+        .find(by_tenant.clone())
+        .await?;
+    while let Some(file) = files.try_next().await? {
+        if keys.insert(file.storage_key.clone()) {
+            objects.push((file.storage_provider, file.storage_key));
+        }
+    }
+    let mut uploads = db
+        .collection::<PendingUpload>(PendingUpload::COLLECTION)
+        .find(by_tenant.clone())
+        .await?;
+    while let Some(upload) = uploads.try_next().await? {
+        if keys.insert(upload.storage_key.clone()) {
+            objects.push((StorageProvider::S3, upload.storage_key));
+        }
+    }
+    for (provider, key) in objects {
+        match store.delete(provider, &key).await {
+            Ok(()) => report.objects += 1,
+            Err(e) => tracing::warn!(%e, %key, "Failed to delete sandbox object"),
+        }
+    }
+
+    report.files = delete_all(db, File::COLLECTION, &by_tenant).await?;
+    delete_all(db, FileBlob::COLLECTION, &by_tenant).await?;
+    delete_all(db, PendingUpload::COLLECTION, &by_tenant).await?;
+    // Custom emoji drawn from uploads lose their image with the files.
+    delete_all(
+        db,
+        CustomEmoji::COLLECTION,
+        &doc! { "tenant_id": tenant_id, "file_id": { "$ne": null } },
+    )
+    .await?;
+
+    let partitions: Vec<MessageArchivePartition> = db
+        .collection::<MessageArchivePartition>(MessageArchivePartition::COLLECTION)
+        .find(by_tenant.clone())
+        .await?
+        .try_collect()
+        .await?;
+    let archives: HashSet<String> = partitions.into_iter().map(|p| p.collection).collect();
+    for archive in archives {
+        report.messages += delete_all(db, &archive, &by_tenant).await?;
+    }
+    delete_all(db, MessageArchivePartition::COLLECTION, &by_tenant).await?;
+    report.messages += delete_all(db, Message::COLLECTION, &by_tenant).await?;
+    delete_all(db, ThreadSubscription::COLLECTION, &by_tenant).await?;
+    delete_all(db, SharedDraft::COLLECTION, &by_tenant).await?;
+
+    report.reactions = delete_all(db, Reaction::COLLECTION, &by_tenant).await?;
+    report.conference_messages = delete_all(db, CallChatMessage::COLLECTION, &by_tenant).await?;
+    report.notifications = delete_all(db, Notification::COLLECTION, &by_tenant).await?;
+
+    db.collection::<Document>(Room::COLLECTION)
+        .update_many(
+            by_tenant.clone(),
+            doc! {
+                "$set": { "message_count": 0_i64, "last_message_id": null },
+            },
+        )
+        .await?;
+    db.collection::<Document>(RoomMember::COLLECTION)
+        .update_many(
+            by_tenant.clone(),
+            doc! { "$unset": { "last_read_message_id": "" } },
+        )
+        .await?;
+
+    Ok(report)
+}
+
+async fn delete_all(db: &Database, collection: &str, filter: &Document) -> DaoResult<u64> {
+    let result = db
+        .collection::<Document>(collection)
+        .delete_many(filter.clone())
+        .await?;
+    Ok(result.deleted_count)
+}
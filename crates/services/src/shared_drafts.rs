@@ -0,0 +1,376 @@
+//! Operational transform for [`SharedDraft`](roomler_ai_db::models::SharedDraft)s
+//! and the editors connected to each one.
+//!
+//! An editor sends one [`DraftOp`] at a time with the draft version it was
+//! made against. The server transforms it past every revision applied
+//! since, applies it and sends the result to the other editors. When two
+//! inserts land at the same position, the one the server applied first
+//! comes first. A delete that a concurrent insert splits becomes two
+//! deletes, the later one first, so other editors' text survives.
+
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_db::models::{DraftOp, DraftRevision};
+
+pub const MAX_CONTENT_CHARS: usize = 10_000;
+/// Revisions kept per draft; editors further behind must reload it.
+pub const HISTORY_LIMIT: usize = 200;
+
+/// `op`, made against `base_version`, rebased onto the revisions applied
+/// since. `history` holds the latest revisions, oldest first, up to
+/// `current_version`.
+pub fn rebase(
+    op: DraftOp,
+    base_version: u64,
+    current_version: u64,
+    history: &[DraftRevision],
+) -> Result<Vec<DraftOp>, String> {
+    validate(&op)?;
+    if base_version > current_version {
+        return Err("version is ahead of the draft".to_string());
+    }
+    let missed: Vec<&DraftRevision> = history
+        .iter()
+        .filter(|r| r.version > base_version)
+        .collect();
+    if missed.len() as u64 != current_version - base_version {
+        return Err("Draft has moved on too far; reload it".to_string());
+    }
+    let mut ops = vec![op];
+    for applied in missed.iter().flat_map(|r| &r.ops) {
+        ops = ops.iter().flat_map(|op| transform(op, applied)).collect();
+    }
+    Ok(ops)
+}
+
+/// Reject empty ops and ones no draft could fit.
+fn validate(op: &DraftOp) -> Result<(), String> {
+    let max = MAX_CONTENT_CHARS as u32;
+    let ok = match op {
+        DraftOp::Insert { pos, text } => {
+            !text.is_empty() && *pos <= max && text.chars().count() <= MAX_CONTENT_CHARS
+        }
+        DraftOp::Delete { pos, len } => *len > 0 && *pos <= max && *len <= max,
+    };
+    if !ok {
+        return Err("Invalid op".to_string());
+    }
+    Ok(())
+}
+
+/// `op` adjusted to apply after `applied`, which was made concurrently
+/// and applied first. Empty when nothing is left of it.
+pub fn transform(op: &DraftOp, applied: &DraftOp) -> Vec<DraftOp> {
+    match (op, applied) {
+        (
+            DraftOp::Insert { pos, text },
+            DraftOp::Insert {
+                pos: at,
+                text: added,
+            },
+        ) => {
+            let pos = if *at <= *pos {
+                pos + char_len(added)
+            } else {
+                *pos
+            };
+            vec![DraftOp::Insert {
+                pos,
+                text: text.clone(),
+            }]
+        }
+        (DraftOp::Insert { pos, text }, DraftOp::Delete { pos: at, len }) => {
+            let pos = if *pos <= *at {
+                *pos
+            } else if *pos >= at + len {
+                pos - len
+            } else {
+                *at
+            };
+            vec![DraftOp::Insert {
+                pos,
+                text: text.clone(),
+            }]
+        }
+        (DraftOp::Delete { pos, len }, DraftOp::Insert { pos: at, text }) => {
+            let added = char_len(text);
+            if *at <= *pos {
+                vec![DraftOp::Delete {
+                    pos: pos + added,
+                    len: *len,
+                }]
+            } else if *at >= pos + len {
+                vec![op.clone()]
+            } else {
+                vec![
+                    DraftOp::Delete {
+                        pos: at + added,
+                        len: pos + len - at,
+                    },
+                    DraftOp::Delete {
+                        pos: *pos,
+                        len: at - pos,
+                    },
+                ]
+            }
+        }
+        (
+            DraftOp::Delete { pos, len },
+            DraftOp::Delete {
+                pos: at,
+                len: removed,
+            },
+        ) => {
+            let (end, removed_end) = (pos + len, at + removed);
+            let overlap = end.min(removed_end).saturating_sub(*pos.max(at));
+            let len = len - overlap;
+            if len == 0 {
+                return Vec::new();
+            }
+            let pos = if *pos < *at {
+                *pos
+            } else if *pos >= removed_end {
+                pos - removed
+            } else {
+                *at
+            };
+            vec![DraftOp::Delete { pos, len }]
+        }
+    }
+}
+
+/// `content` with `ops` applied in order.
+pub fn apply(content: &str, ops: &[DraftOp]) -> Result<String, String> {
+    let mut chars: Vec<char> = content.chars().collect();
+    for op in ops {
+        match op {
+            DraftOp::Insert { pos, text } => {
+                let pos = *pos as usize;
+                if pos > chars.len() {
+                    return Err("Insert is past the end of the draft".to_string());
+                }
+                chars.splice(pos..pos, text.chars());
+            }
+            DraftOp::Delete { pos, len } => {
+                let (pos, end) = (*pos as usize, *pos as usize + *len as usize);
+                if end > chars.len() {
+                    return Err("Delete is past the end of the draft".to_string());
+                }
+                chars.drain(pos..end);
+            }
+        }
+    }
+    if chars.len() > MAX_CONTENT_CHARS {
+        return Err(format!(
+            "Drafts can be at most {MAX_CONTENT_CHARS} characters"
+        ));
+    }
+    Ok(chars.into_iter().collect())
+}
+
+fn char_len(text: &str) -> u32 {
+    text.chars().count() as u32
+}
+
+/// Who has each draft open, by connection. Only connections to this
+/// instance are tracked, as with call participants.
+#[derive(Default)]
+pub struct DraftEditors {
+    drafts: DashMap<ObjectId, HashMap<String, ObjectId>>,
+}
+
+impl DraftEditors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the connection wasn't editing the draft already.
+    pub fn join(&self, draft_id: ObjectId, connection_id: &str, user_id: ObjectId) -> bool {
+        self.drafts
+            .entry(draft_id)
+            .or_default()
+            .insert(connection_id.to_string(), user_id)
+            .is_none()
+    }
+
+    /// Returns whether the connection was editing the draft.
+    pub fn leave(&self, draft_id: &ObjectId, connection_id: &str) -> bool {
+        let Some(mut editors) = self.drafts.get_mut(draft_id) else {
+            return false;
+        };
+        let left = editors.remove(connection_id).is_some();
+        let empty = editors.is_empty();
+        drop(editors);
+        if empty {
+            self.drafts
+                .remove_if(draft_id, |_, editors| editors.is_empty());
+        }
+        left
+    }
+
+    /// Take a closed connection out of every draft it had open, returning
+    /// those drafts.
+    pub fn disconnect(&self, connection_id: &str) -> Vec<ObjectId> {
+        let drafts: Vec<ObjectId> = self
+            .drafts
+            .iter()
+            .filter(|entry| entry.value().contains_key(connection_id))
+            .map(|entry| *entry.key())
+            .collect();
+        drafts
+            .into_iter()
+            .filter(|draft_id| self.leave(draft_id, connection_id))
+            .collect()
+    }
+
+    /// Forget a published or discarded draft, returning its connections.
+    pub fn close(&self, draft_id: &ObjectId) -> Vec<String> {
+        self.drafts
+            .remove(draft_id)
+            .map(|(_, editors)| editors.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_editing(&self, draft_id: &ObjectId, connection_id: &str) -> bool {
+        self.drafts
+            .get(draft_id)
+            .is_some_and(|editors| editors.contains_key(connection_id))
+    }
+
+    pub fn connection_ids(&self, draft_id: &ObjectId) -> Vec<String> {
+        self.drafts
+            .get(draft_id)
+            .map(|editors| editors.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Distinct users editing the draft, in a stable order.
+    pub fn user_ids(&self, draft_id: &ObjectId) -> Vec<ObjectId> {
+        let mut users: Vec<ObjectId> = self
+            .drafts
+            .get(draft_id)
+            .map(|editors| editors.values().copied().collect())
+            .unwrap_or_default();
+        users.sort();
+        users.dedup();
+        users
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ins(pos: u32, text: &str) -> DraftOp {
+        DraftOp::Insert {
+            pos,
+            text: text.to_string(),
+        }
+    }
+
+    fn del(pos: u32, len: u32) -> DraftOp {
+        DraftOp::Delete { pos, len }
+    }
+
+    /// Both orders of applying two concurrent ops end in the same text.
+    fn converges(doc: &str, a: DraftOp, b: DraftOp) -> String {
+        let a_first = apply(
+            &apply(doc, std::slice::from_ref(&a)).unwrap(),
+            &transform(&b, &a),
+        )
+        .unwrap();
+        let b_first = apply(
+            &apply(doc, std::slice::from_ref(&b)).unwrap(),
+            &transform(&a, &b),
+        )
+        .unwrap();
+        // With inserts at the same spot the tie goes to whichever the
+        // server applied first, so only compare when positions differ.
+        if !matches!((&a, &b), (DraftOp::Insert { pos: p, .. }, DraftOp::Insert { pos: q, .. }) if p == q)
+        {
+            assert_eq!(a_first, b_first);
+        }
+        a_first
+    }
+
+    #[test]
+    fn concurrent_inserts() {
+        assert_eq!(converges("ac", ins(1, "b"), ins(2, "d")), "abcd");
+        // Same position: the op applied first comes first
+        assert_eq!(
+            apply("x", &[ins(0, "a")])
+                .and_then(|s| apply(&s, &transform(&ins(0, "b"), &ins(0, "a"))))
+                .unwrap(),
+            "abx"
+        );
+    }
+
+    #[test]
+    fn inserts_and_deletes() {
+        assert_eq!(converges("hello world", ins(5, ","), del(6, 5)), "hello, ");
+        assert_eq!(converges("hello world", ins(0, ">"), del(6, 5)), ">hello ");
+        // An insert inside a deleted range survives
+        assert_eq!(converges("abcdef", ins(3, "X"), del(1, 4)), "aXf");
+    }
+
+    #[test]
+    fn overlapping_deletes() {
+        assert_eq!(converges("abcdef", del(1, 3), del(2, 3)), "af");
+        assert_eq!(converges("abcdef", del(0, 2), del(4, 2)), "cd");
+        assert_eq!(transform(&del(1, 2), &del(0, 4)), Vec::new());
+    }
+
+    #[test]
+    fn ops_are_rebased_past_missed_revisions() {
+        let user_id = ObjectId::new();
+        let history = vec![
+            DraftRevision {
+                version: 1,
+                user_id,
+                ops: vec![ins(0, "Hi ")],
+            },
+            DraftRevision {
+                version: 2,
+                user_id,
+                ops: vec![del(3, 1)],
+            },
+        ];
+        // "team" -> v1 "Hi team" -> v2 "Hi eam"; an op made on v0
+        let ops = rebase(ins(4, "!"), 0, 2, &history).unwrap();
+        assert_eq!(apply("Hi eam", &ops).unwrap(), "Hi eam!");
+        assert_eq!(
+            rebase(ins(0, "x"), 2, 2, &history).unwrap(),
+            vec![ins(0, "x")]
+        );
+        assert!(rebase(ins(0, "x"), 3, 2, &history).is_err());
+        assert!(rebase(ins(0, ""), 2, 2, &history).is_err());
+        assert!(rebase(del(u32::MAX, 1), 2, 2, &history).is_err());
+        // v0 is no longer in the kept history
+        assert!(rebase(ins(0, "x"), 0, 3, &history[1..]).is_err());
+    }
+
+    #[test]
+    fn ops_must_fit_the_draft() {
+        assert_eq!(apply("héllo", &[del(1, 1), ins(1, "e")]).unwrap(), "hello");
+        assert!(apply("abc", &[ins(4, "x")]).is_err());
+        assert!(apply("abc", &[del(2, 2)]).is_err());
+        assert!(apply("", &[ins(0, &"a".repeat(MAX_CONTENT_CHARS + 1))]).is_err());
+    }
+
+    #[test]
+    fn editors_are_tracked_by_connection() {
+        let editors = DraftEditors::new();
+        let (draft, user) = (ObjectId::new(), ObjectId::new());
+        assert!(editors.join(draft, "c1", user));
+        assert!(!editors.join(draft, "c1", user));
+        assert!(editors.join(draft, "c2", user));
+        assert_eq!(editors.user_ids(&draft), vec![user]);
+        assert_eq!(editors.disconnect("c1"), vec![draft]);
+        assert!(editors.is_editing(&draft, "c2"));
+        assert!(editors.leave(&draft, "c2"));
+        assert!(editors.connection_ids(&draft).is_empty());
+        assert!(!editors.leave(&draft, "c2"));
+    }
+}
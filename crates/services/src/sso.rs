@@ -0,0 +1,580 @@
+//! Single sign-on through a tenant's own OpenID Connect provider
+//! ([`TenantSso`]). The authorization code flow: [`SsoService::begin`]
+//! issues a signed `state` bound to the tenant and a `nonce`, the user signs
+//! in at the provider, and [`SsoService::authenticate`] trades the code for
+//! an ID token and verifies its signature, issuer, audience, expiry and
+//! nonce. RS/ES/PS-signed tokens are checked against the provider's JWKS,
+//! HS-signed ones against the client secret. Tenants choose the issuer, so
+//! the provider is only reached at public addresses, and a discovery
+//! document naming non-public endpoints is refused.
+//!
+//! A tenant proves it owns an email domain before claiming it with a TXT
+//! record at [`challenge_record`] holding [`challenge_value`], looked up
+//! over DNS-over-HTTPS.
+//!
+//! SAML identity providers are not supported; the common ones (Entra ID,
+//! Okta, Google Workspace, Keycloak) also speak OpenID Connect.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    jwk::JwkSet,
+};
+use roomler_ai_config::SsoSettings;
+use roomler_ai_db::models::TenantSso;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::outbound::{Outbound, OutboundClient};
+
+/// How long a sign-in may take between leaving for the provider and
+/// coming back.
+pub const STATE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Provider metadata and keys are fetched again after this long, or
+/// earlier when a token is signed with a key they don't have.
+const PROVIDER_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// DNS record type of TXT records.
+const TXT: u16 = 16;
+
+const STATE_AUDIENCE: &str = "roomler-sso";
+const LINK_AUDIENCE: &str = "roomler-sso-link";
+
+/// Free mail providers, which no tenant can claim.
+const PUBLIC_EMAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+    "gmx.net",
+    "web.de",
+    "yandex.com",
+    "mail.com",
+];
+
+#[derive(Debug, Error)]
+pub enum SsoError {
+    #[error("Provider discovery failed: {0}")]
+    Discovery(String),
+    #[error("Token exchange failed: {0}")]
+    TokenExchange(String),
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+    #[error("Invalid or expired state parameter")]
+    InvalidState,
+    #[error("DNS lookup failed: {0}")]
+    Dns(String),
+}
+
+/// Who the provider says signed in.
+#[derive(Debug, Clone)]
+pub struct SsoIdentity {
+    /// The provider's stable user id (`sub`).
+    pub subject: String,
+    pub email: String,
+    pub name: Option<String>,
+}
+
+/// A sign-in in progress.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+}
+
+pub struct SsoService {
+    outbound: Outbound,
+    /// Reaches tenants' providers, at public addresses only.
+    client: OutboundClient,
+    /// Reaches the operator's DNS-over-HTTPS resolver.
+    dns_client: OutboundClient,
+    state_secret: String,
+    dns_resolver_url: String,
+    providers: DashMap<String, (Instant, Arc<Provider>)>,
+}
+
+struct Provider {
+    metadata: ProviderMetadata,
+    keys: JwkSet,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateClaims {
+    aud: String,
+    exp: u64,
+    tid: String,
+    nonce: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LinkClaims {
+    aud: String,
+    exp: u64,
+    tid: String,
+    sub: String,
+    email: String,
+    name: Option<String>,
+}
+
+/// A DNS-over-HTTPS JSON answer.
+#[derive(Debug, Deserialize)]
+struct DnsResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DnsAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    preferred_username: Option<String>,
+    name: Option<String>,
+}
+
+impl SsoService {
+    /// `state_secret` signs the `state` parameter.
+    pub fn new(state_secret: &str, settings: &SsoSettings, outbound: &Outbound) -> Self {
+        Self {
+            outbound: outbound.clone(),
+            client: outbound.public_client("sso"),
+            dns_client: outbound.client("sso-dns"),
+            state_secret: state_secret.to_string(),
+            dns_resolver_url: settings.dns_resolver_url.clone(),
+            providers: DashMap::new(),
+        }
+    }
+
+    /// Start a sign-in to `tenant_id`.
+    pub fn begin(&self, tenant_id: ObjectId) -> PendingLogin {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let claims = StateClaims {
+            aud: STATE_AUDIENCE.to_string(),
+            exp: (chrono::Utc::now().timestamp() as u64) + STATE_TTL.as_secs(),
+            tid: tenant_id.to_hex(),
+            nonce: nonce.clone(),
+        };
+        let state = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.state_secret.as_bytes()),
+        )
+        .expect("HS256 signing doesn't fail");
+        PendingLogin { state, nonce }
+    }
+
+    /// The nonce of a sign-in to `tenant_id` started by [`begin`](Self::begin).
+    pub fn verify_state(&self, state: &str, tenant_id: ObjectId) -> Result<String, SsoError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[STATE_AUDIENCE]);
+        validation.leeway = 0;
+        let claims = decode::<StateClaims>(
+            state,
+            &DecodingKey::from_secret(self.state_secret.as_bytes()),
+            &validation,
+        )
+        .map_err(|_| SsoError::InvalidState)?
+        .claims;
+        if claims.tid != tenant_id.to_hex() {
+            return Err(SsoError::InvalidState);
+        }
+        Ok(claims.nonce)
+    }
+
+    /// A short-lived token carrying `identity`, signed in to `tenant_id`,
+    /// for when its address already has an account. The account's owner
+    /// trades it, with their current credentials, for linking the two.
+    pub fn link_token(&self, tenant_id: ObjectId, identity: &SsoIdentity) -> String {
+        let claims = LinkClaims {
+            aud: LINK_AUDIENCE.to_string(),
+            exp: (chrono::Utc::now().timestamp() as u64) + STATE_TTL.as_secs(),
+            tid: tenant_id.to_hex(),
+            sub: identity.subject.clone(),
+            email: identity.email.clone(),
+            name: identity.name.clone(),
+        };
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.state_secret.as_bytes()),
+        )
+        .expect("HS256 signing doesn't fail")
+    }
+
+    /// The identity in a [`link_token`](Self::link_token) for `tenant_id`.
+    pub fn verify_link_token(
+        &self,
+        token: &str,
+        tenant_id: ObjectId,
+    ) -> Result<SsoIdentity, SsoError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[LINK_AUDIENCE]);
+        validation.leeway = 0;
+        let claims = decode::<LinkClaims>(
+            token,
+            &DecodingKey::from_secret(self.state_secret.as_bytes()),
+            &validation,
+        )
+        .map_err(|_| SsoError::InvalidState)?
+        .claims;
+        if claims.tid != tenant_id.to_hex() {
+            return Err(SsoError::InvalidState);
+        }
+        Ok(SsoIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            name: claims.name,
+        })
+    }
+
+    /// Where to send the user to sign in at the provider.
+    pub async fn authorization_url(
+        &self,
+        sso: &TenantSso,
+        redirect_uri: &str,
+        login: &PendingLogin,
+    ) -> Result<String, SsoError> {
+        let provider = self.provider(&sso.issuer, false).await?;
+        let endpoint = &provider.metadata.authorization_endpoint;
+        let separator = if endpoint.contains('?') { '&' } else { '?' };
+        Ok(format!(
+            "{endpoint}{separator}response_type=code&scope=openid+email+profile&client_id={}&redirect_uri={}&state={}&nonce={}",
+            urlencoding::encode(&sso.client_id),
+            urlencoding::encode(redirect_uri),
+            urlencoding::encode(&login.state),
+            urlencoding::encode(&login.nonce),
+        ))
+    }
+
+    /// Trade the provider's `code` for the signed-in user.
+    pub async fn authenticate(
+        &self,
+        sso: &TenantSso,
+        code: &str,
+        redirect_uri: &str,
+        nonce: &str,
+    ) -> Result<SsoIdentity, SsoError> {
+        let provider = self.provider(&sso.issuer, false).await?;
+        let response = self
+            .client
+            .post(&provider.metadata.token_endpoint)
+            .header("Accept", "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", sso.client_id.as_str()),
+                ("client_secret", sso.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| SsoError::TokenExchange(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(SsoError::TokenExchange(format!(
+                "Provider answered {}",
+                response.status()
+            )));
+        }
+        let id_token = response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| SsoError::TokenExchange(e.to_string()))?
+            .id_token
+            .ok_or_else(|| SsoError::TokenExchange("No ID token in the response".to_string()))?;
+
+        let claims = self.verify_id_token(sso, provider, &id_token).await?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(SsoError::InvalidIdToken("nonce doesn't match".to_string()));
+        }
+        if claims.email_verified == Some(false) {
+            return Err(SsoError::InvalidIdToken(
+                "the provider hasn't verified the email address".to_string(),
+            ));
+        }
+        let email = claims
+            .email
+            .or(claims.preferred_username.filter(|u| u.contains('@')))
+            .ok_or_else(|| SsoError::InvalidIdToken("no email address".to_string()))?;
+        Ok(SsoIdentity {
+            subject: claims.sub,
+            email,
+            name: claims.name,
+        })
+    }
+
+    async fn verify_id_token(
+        &self,
+        sso: &TenantSso,
+        mut provider: Arc<Provider>,
+        id_token: &str,
+    ) -> Result<IdClaims, SsoError> {
+        let header =
+            decode_header(id_token).map_err(|e| SsoError::InvalidIdToken(e.to_string()))?;
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                DecodingKey::from_secret(sso.client_secret.as_bytes())
+            }
+            _ => {
+                let find = |keys: &JwkSet| match &header.kid {
+                    Some(kid) => keys.find(kid).cloned(),
+                    None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+                    None => None,
+                };
+                let jwk = match find(&provider.keys) {
+                    Some(jwk) => jwk,
+                    None => {
+                        // The provider may have rotated its keys.
+                        provider = self.provider(&sso.issuer, true).await?;
+                        find(&provider.keys).ok_or_else(|| {
+                            SsoError::InvalidIdToken("signed with an unknown key".to_string())
+                        })?
+                    }
+                };
+                DecodingKey::from_jwk(&jwk).map_err(|e| SsoError::InvalidIdToken(e.to_string()))?
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.metadata.issuer]);
+        validation.set_audience(&[&sso.client_id]);
+        decode::<IdClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| SsoError::InvalidIdToken(e.to_string()))
+    }
+
+    /// Whether `domain` has the TXT record proving ownership with `token`.
+    pub async fn domain_proven(&self, domain: &str, token: &str) -> Result<bool, SsoError> {
+        let expected = challenge_value(token);
+        Ok(self
+            .txt_records(&challenge_record(domain))
+            .await?
+            .iter()
+            .any(|value| value.trim() == expected))
+    }
+
+    /// The TXT records at `name`; none when it doesn't exist.
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, SsoError> {
+        let response: DnsResponse = self
+            .dns_client
+            .get(&self.dns_resolver_url)
+            .header("Accept", "application/dns-json")
+            .query(&[("name", name), ("type", "TXT")])
+            .send()
+            .await
+            .map_err(|e| SsoError::Dns(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| SsoError::Dns(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| SsoError::Dns(e.to_string()))?;
+        // 3 is NXDOMAIN: the name doesn't exist.
+        match response.status {
+            0 | 3 => {}
+            status => return Err(SsoError::Dns(format!("Resolver answered status {status}"))),
+        }
+        Ok(response
+            .answer
+            .iter()
+            .filter(|answer| answer.record_type == TXT)
+            .map(|answer| txt_value(&answer.data))
+            .collect())
+    }
+
+    async fn provider(&self, issuer: &str, refresh: bool) -> Result<Arc<Provider>, SsoError> {
+        if !refresh
+            && let Some(cached) = self.providers.get(issuer)
+            && cached.0.elapsed() < PROVIDER_TTL
+        {
+            return Ok(cached.1.clone());
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| SsoError::Discovery(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| SsoError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| SsoError::Discovery(e.to_string()))?;
+        if metadata.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(SsoError::Discovery(format!(
+                "The provider calls itself {}",
+                metadata.issuer
+            )));
+        }
+        for endpoint in [
+            &metadata.authorization_endpoint,
+            &metadata.token_endpoint,
+            &metadata.jwks_uri,
+        ] {
+            self.outbound
+                .check_public_url(endpoint)
+                .await
+                .map_err(|e| SsoError::Discovery(format!("{endpoint}: {e}")))?;
+        }
+        let keys: JwkSet = self
+            .client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .map_err(|e| SsoError::Discovery(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| SsoError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| SsoError::Discovery(e.to_string()))?;
+
+        let provider = Arc::new(Provider { metadata, keys });
+        self.providers
+            .insert(issuer.to_string(), (Instant::now(), provider.clone()));
+        Ok(provider)
+    }
+}
+
+/// Where the TXT record proving a tenant owns `domain` goes.
+pub fn challenge_record(domain: &str) -> String {
+    format!("_roomler-challenge.{domain}")
+}
+
+/// What the TXT record proving ownership with `token` holds.
+pub fn challenge_value(token: &str) -> String {
+    format!("roomler-domain-verification={token}")
+}
+
+/// A TXT record's text from its presentation form, one or more quoted
+/// strings that are joined.
+fn txt_value(data: &str) -> String {
+    let data = data.trim();
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = data.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => value.extend(chars.next()),
+            c if quoted => value.push(c),
+            _ => {}
+        }
+    }
+    value
+}
+
+/// The lowercase domain of `email`.
+pub fn email_domain(email: &str) -> Option<String> {
+    email
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Lowercase, deduplicated `domains`, or why they can't be claimed.
+pub fn normalize_domains(domains: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for domain in domains {
+        let domain = domain.trim().trim_start_matches('@').to_lowercase();
+        let valid = domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+        if !valid {
+            return Err(format!("{domain:?} is not a domain"));
+        }
+        if PUBLIC_EMAIL_DOMAINS.contains(&domain.as_str()) {
+            return Err(format!("{domain} is a public email provider"));
+        }
+        if !normalized.contains(&domain) {
+            normalized.push(domain);
+        }
+    }
+    if normalized.is_empty() {
+        return Err("At least one email domain is required".to_string());
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domains_are_normalized_and_public_ones_refused() {
+        assert_eq!(
+            normalize_domains(&[
+                "Acme.com".to_string(),
+                "@eu.acme.com".to_string(),
+                "acme.com".to_string()
+            ]),
+            Ok(vec!["acme.com".to_string(), "eu.acme.com".to_string()])
+        );
+        assert!(normalize_domains(&[]).is_err());
+        assert!(normalize_domains(&["localhost".to_string()]).is_err());
+        assert!(normalize_domains(&["a b.com".to_string()]).is_err());
+        assert!(normalize_domains(&["gmail.com".to_string()]).is_err());
+        assert_eq!(email_domain("Jane@Acme.COM"), Some("acme.com".to_string()));
+        assert_eq!(email_domain("jane"), None);
+    }
+
+    #[test]
+    fn txt_records_are_unquoted_and_joined() {
+        assert_eq!(
+            txt_value("\"roomler-domain-verification=abc\""),
+            "roomler-domain-verification=abc"
+        );
+        assert_eq!(
+            txt_value("\"roomler-domain\" \"-verification=abc\""),
+            "roomler-domain-verification=abc"
+        );
+        assert_eq!(txt_value("\"say \\\"hi\\\"\""), "say \"hi\"");
+        assert_eq!(txt_value("unquoted"), "unquoted");
+    }
+}
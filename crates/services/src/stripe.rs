@@ -4,6 +4,9 @@ use roomler_ai_db::models::tenant::{BillingInfo, Plan, PlanLimits, SubscriptionS
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::email::EmailTemplate;
+use crate::outbound::{Outbound, OutboundClient};
+
 // ---- Response / DTO types ------------------------------------------------
 
 #[derive(Debug, Serialize)]
@@ -61,14 +64,14 @@ pub enum StripeError {
 
 pub struct StripeService {
     settings: StripeSettings,
-    client: reqwest::Client,
+    client: OutboundClient,
 }
 
 impl StripeService {
-    pub fn new(settings: &StripeSettings) -> Self {
+    pub fn new(settings: &StripeSettings, outbound: &Outbound) -> Self {
         Self {
             settings: settings.clone(),
-            client: reqwest::Client::new(),
+            client: outbound.client("stripe"),
         }
     }
 
@@ -341,6 +344,70 @@ impl StripeService {
         }
     }
 
+    /// The tenant a webhook event applies to, if it is one we handle.
+    pub async fn event_tenant(
+        db: &mongodb::Database,
+        event: &StripeEvent,
+    ) -> Result<Option<Tenant>, StripeError> {
+        let obj = &event.data.object;
+        let filter = match event.event_type.as_str() {
+            "checkout.session.completed" => {
+                match obj["metadata"]["tenant_id"]
+                    .as_str()
+                    .and_then(|hex| ObjectId::parse_str(hex).ok())
+                {
+                    Some(tenant_id) => doc! { "_id": tenant_id },
+                    None => return Ok(None),
+                }
+            }
+            "customer.subscription.updated" | "customer.subscription.deleted" => {
+                doc! { "billing.subscription_id": obj["id"].as_str().unwrap_or_default() }
+            }
+            "invoice.paid" | "invoice.payment_failed" => {
+                doc! { "billing.subscription_id": obj["subscription"].as_str().unwrap_or_default() }
+            }
+            _ => return Ok(None),
+        };
+        Ok(db
+            .collection::<Tenant>(Tenant::COLLECTION)
+            .find_one(filter)
+            .await?)
+    }
+
+    /// The receipt for an `invoice.paid` event of `tenant`, or `None` for
+    /// other events and invoices nothing was paid on.
+    pub fn invoice_receipt(tenant: &Tenant, event: &StripeEvent) -> Option<EmailTemplate> {
+        let obj = &event.data.object;
+        if event.event_type != "invoice.paid" {
+            return None;
+        }
+        let amount = obj["amount_paid"].as_i64().filter(|a| *a > 0)?;
+        let plan_id = serde_json::to_value(&tenant.plan).ok()?;
+        let plan = Self::get_plans()
+            .into_iter()
+            .find(|p| plan_id == p.id.as_str())
+            .map_or_else(
+                || plan_id.as_str().unwrap_or_default().to_string(),
+                |p| p.name,
+            );
+        // The subscription line covers the period paid for; the invoice's
+        // own period is the one before it.
+        let period_end = obj["lines"]["data"][0]["period"]["end"]
+            .as_i64()
+            .or_else(|| obj["period_end"].as_i64())
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|end| end.format("%Y-%m-%d").to_string());
+        Some(EmailTemplate::BillingReceipt {
+            tenant_name: tenant.name.clone(),
+            plan,
+            amount_cents: amount,
+            currency: obj["currency"].as_str().unwrap_or("usd").to_string(),
+            invoice_number: obj["number"].as_str().map(String::from),
+            period_end,
+            invoice_url: obj["hosted_invoice_url"].as_str().map(String::from),
+        })
+    }
+
     /// Handle a verified webhook event, updating tenant billing state.
     pub async fn handle_webhook_event(
         &self,
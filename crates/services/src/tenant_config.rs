@@ -0,0 +1,719 @@
+//! Tenant configuration as code.
+//!
+//! A [`TenantConfigBundle`] captures how a tenant is set up — settings,
+//! roles and the room tree (categories are rooms with children) — without
+//! any content or member data, so it can be kept in version control and
+//! applied to another tenant. Roles are matched by name and rooms by path,
+//! never by id, and applying only creates or updates: anything present in
+//! the tenant but missing from the bundle is reported and left alone.
+
+use std::collections::{HashMap, HashSet};
+
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{
+    ConferenceSettings, MediaSettings, PermissionOverwrite, ReadOnlyWindow, Role, Room,
+    TenantSettings,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::dao::base::{DaoError, DaoResult};
+use crate::dao::role::RoleDao;
+use crate::dao::room::RoomDao;
+use crate::dao::tenant::TenantDao;
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// `target_type` of permission overwrites that apply to a role.
+const ROLE_TARGET: &str = "role";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfigBundle {
+    pub version: u32,
+    /// Left untouched on apply when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<TenantSettings>,
+    #[serde(default)]
+    pub roles: Vec<RoleConfig>,
+    #[serde(default)]
+    pub rooms: Vec<RoomConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub permissions: u64,
+    #[serde(default)]
+    pub is_mentionable: bool,
+    #[serde(default)]
+    pub is_hoisted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomConfig {
+    pub name: String,
+    /// Path of the parent room (category), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub position: i32,
+    #[serde(default)]
+    pub is_open: bool,
+    #[serde(default)]
+    pub is_read_only: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_only_windows: Vec<ReadOnlyWindow>,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_settings: Option<MediaSettings>,
+    /// Passcodes are never exported, and applying keeps the target's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conference_settings: Option<ConferenceSettings>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub role_overwrites: Vec<RoleOverwriteConfig>,
+}
+
+impl RoomConfig {
+    /// The key rooms are matched on, as stored in `Room::path`.
+    pub fn path(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleOverwriteConfig {
+    pub role: String,
+    #[serde(default)]
+    pub allow: u64,
+    #[serde(default)]
+    pub deny: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    Settings,
+    Role,
+    Room,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Unchanged,
+    /// In the tenant but not the bundle. Never deleted.
+    OnlyInTenant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub section: ConfigSection,
+    pub key: String,
+    pub action: ChangeAction,
+    /// Top-level fields that differ, for updates.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    pub fn has_changes(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| matches!(c.action, ChangeAction::Create | ChangeAction::Update))
+    }
+
+    fn push(&mut self, section: ConfigSection, key: String, fields: Option<Vec<String>>) {
+        let (action, fields) = match fields {
+            None => (ChangeAction::Create, Vec::new()),
+            Some(f) if f.is_empty() => (ChangeAction::Unchanged, f),
+            Some(f) => (ChangeAction::Update, f),
+        };
+        self.changes.push(ConfigChange {
+            section,
+            key,
+            action,
+            fields,
+        });
+    }
+}
+
+/// Snapshot a tenant's configuration. Archived rooms (and anything under
+/// them) and user-specific permission overwrites are left out.
+pub fn build_bundle(
+    settings: &TenantSettings,
+    roles: &[Role],
+    rooms: &[Room],
+) -> TenantConfigBundle {
+    let role_names: HashMap<ObjectId, &str> = roles
+        .iter()
+        .filter_map(|r| Some((r.id?, r.name.as_str())))
+        .collect();
+    let mut live: Vec<&Room> = rooms
+        .iter()
+        .filter(|r| !r.is_archived && r.deleted_at.is_none())
+        .collect();
+    live.sort_by_key(|r| r.path.matches('.').count());
+    // Parents sort first, so a room is kept only if its whole ancestry is.
+    let mut paths: HashMap<ObjectId, &str> = HashMap::new();
+    live.retain(|r| {
+        let keep = r.parent_id.is_none_or(|id| paths.contains_key(&id));
+        if keep && let Some(id) = r.id {
+            paths.insert(id, r.path.as_str());
+        }
+        keep
+    });
+
+    let roles = roles
+        .iter()
+        .map(|r| RoleConfig {
+            name: r.name.clone(),
+            description: r.description.clone(),
+            color: r.color,
+            position: r.position,
+            permissions: r.permissions,
+            is_mentionable: r.is_mentionable,
+            is_hoisted: r.is_hoisted,
+        })
+        .collect();
+
+    let mut rooms: Vec<RoomConfig> = live
+        .into_iter()
+        .map(|r| RoomConfig {
+            name: r.name.clone(),
+            parent: r
+                .parent_id
+                .and_then(|id| paths.get(&id))
+                .map(|p| p.to_string()),
+            emoji: r.emoji.clone(),
+            topic: r.topic.clone(),
+            purpose: r.purpose.clone(),
+            icon: r.icon.clone(),
+            position: r.position,
+            is_open: r.is_open,
+            is_read_only: r.is_read_only,
+            read_only_windows: r.read_only_windows.clone(),
+            is_default: r.is_default,
+            tags: r.tags.clone(),
+            media_settings: r.media_settings.clone(),
+            conference_settings: r.conference_settings.clone().map(|mut c| {
+                c.passcode = None;
+                c
+            }),
+            role_overwrites: r
+                .permission_overwrites
+                .iter()
+                .filter(|o| o.target_type == ROLE_TARGET)
+                .filter_map(|o| {
+                    Some(RoleOverwriteConfig {
+                        role: role_names.get(&o.target_id)?.to_string(),
+                        allow: o.allow,
+                        deny: o.deny,
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+    // Parents before children, so the bundle applies top to bottom.
+    rooms.sort_by_key(|r| (r.path().matches('.').count(), r.position, r.path()));
+
+    TenantConfigBundle {
+        version: BUNDLE_VERSION,
+        settings: Some(settings.clone()),
+        roles,
+        rooms,
+    }
+}
+
+/// Reject bundles that can't be applied on top of `current`: unknown
+/// versions, duplicate keys, and parents or overwrite roles that exist in
+/// neither the bundle nor the tenant.
+pub fn validate(desired: &TenantConfigBundle, current: &TenantConfigBundle) -> Result<(), String> {
+    if desired.version != BUNDLE_VERSION {
+        return Err(format!(
+            "Unsupported bundle version {} (expected {})",
+            desired.version, BUNDLE_VERSION
+        ));
+    }
+
+    let mut role_names = HashSet::new();
+    for role in &desired.roles {
+        if role.name.trim().is_empty() {
+            return Err("Role names must not be empty".to_string());
+        }
+        if !role_names.insert(role.name.as_str()) {
+            return Err(format!("Duplicate role '{}'", role.name));
+        }
+    }
+    role_names.extend(current.roles.iter().map(|r| r.name.as_str()));
+
+    let mut paths = HashSet::new();
+    for room in &desired.rooms {
+        if room.name.trim().is_empty() || room.name.contains('.') {
+            return Err(format!(
+                "Invalid room name '{}': must be non-empty and contain no '.'",
+                room.name
+            ));
+        }
+        if !paths.insert(room.path()) {
+            return Err(format!("Duplicate room '{}'", room.path()));
+        }
+    }
+    let existing: HashSet<String> = current.rooms.iter().map(RoomConfig::path).collect();
+    for room in &desired.rooms {
+        if let Some(parent) = &room.parent
+            && !paths.contains(parent)
+            && !existing.contains(parent)
+        {
+            return Err(format!(
+                "Room '{}' has unknown parent '{}'",
+                room.path(),
+                parent
+            ));
+        }
+        if let Some(o) = room
+            .role_overwrites
+            .iter()
+            .find(|o| !role_names.contains(o.role.as_str()))
+        {
+            return Err(format!(
+                "Room '{}' overwrites unknown role '{}'",
+                room.path(),
+                o.role
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// What applying `desired` to a tenant currently configured as `current`
+/// would do.
+pub fn diff(current: &TenantConfigBundle, desired: &TenantConfigBundle) -> ConfigDiff {
+    let mut out = ConfigDiff::default();
+
+    if let Some(settings) = &desired.settings {
+        let fields = match &current.settings {
+            Some(cur) => changed_fields(cur, settings),
+            None => Vec::new(),
+        };
+        out.push(
+            ConfigSection::Settings,
+            "settings".to_string(),
+            Some(fields),
+        );
+    }
+
+    let roles: HashMap<&str, &RoleConfig> =
+        current.roles.iter().map(|r| (r.name.as_str(), r)).collect();
+    for role in &desired.roles {
+        let fields = roles
+            .get(role.name.as_str())
+            .map(|cur| changed_fields(*cur, role));
+        out.push(ConfigSection::Role, role.name.clone(), fields);
+    }
+    let wanted: HashSet<&str> = desired.roles.iter().map(|r| r.name.as_str()).collect();
+    for role in current
+        .roles
+        .iter()
+        .filter(|r| !wanted.contains(r.name.as_str()))
+    {
+        out.changes
+            .push(only_in_tenant(ConfigSection::Role, role.name.clone()));
+    }
+
+    let rooms: HashMap<String, &RoomConfig> = current.rooms.iter().map(|r| (r.path(), r)).collect();
+    for room in &desired.rooms {
+        let fields = rooms
+            .get(&room.path())
+            .map(|cur| changed_fields(*cur, room));
+        out.push(ConfigSection::Room, room.path(), fields);
+    }
+    let wanted: HashSet<String> = desired.rooms.iter().map(RoomConfig::path).collect();
+    for room in current.rooms.iter().filter(|r| !wanted.contains(&r.path())) {
+        out.changes
+            .push(only_in_tenant(ConfigSection::Room, room.path()));
+    }
+
+    out
+}
+
+fn only_in_tenant(section: ConfigSection, key: String) -> ConfigChange {
+    ConfigChange {
+        section,
+        key,
+        action: ChangeAction::OnlyInTenant,
+        fields: Vec::new(),
+    }
+}
+
+/// Top-level fields whose serialized values differ. Comparing the JSON form
+/// keeps this in step with what the bundle actually contains.
+fn changed_fields<T: Serialize>(current: &T, desired: &T) -> Vec<String> {
+    let to_map = |v: &T| match serde_json::to_value(v) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (current, desired) = (to_map(current), to_map(desired));
+    let mut keys: Vec<&String> = current.keys().chain(desired.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|k| current.get(*k) != desired.get(*k))
+        .cloned()
+        .collect()
+}
+
+/// Exports tenant configuration bundles and applies them.
+pub struct TenantConfigService {
+    tenants: TenantDao,
+    roles: RoleDao,
+    rooms: RoomDao,
+}
+
+impl TenantConfigService {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            tenants: TenantDao::new(db),
+            roles: RoleDao::new(db),
+            rooms: RoomDao::new(db),
+        }
+    }
+
+    pub async fn export(&self, tenant_id: ObjectId) -> DaoResult<TenantConfigBundle> {
+        let tenant = self.tenants.base.find_by_id(tenant_id).await?;
+        let roles = self.roles.find_for_tenant(tenant_id).await?;
+        let rooms = self.rooms.find_by_tenant(tenant_id).await?;
+        Ok(build_bundle(&tenant.settings, &roles, &rooms))
+    }
+
+    /// Validate `desired` against the tenant and describe what applying it
+    /// would change, without changing anything.
+    pub async fn plan(
+        &self,
+        tenant_id: ObjectId,
+        desired: &TenantConfigBundle,
+    ) -> DaoResult<ConfigDiff> {
+        let current = self.export(tenant_id).await?;
+        validate(desired, &current).map_err(DaoError::Validation)?;
+        Ok(diff(&current, desired))
+    }
+
+    /// Create and update what [`plan`](Self::plan) reports. Roles go first
+    /// so room overwrites can reference new ones, then rooms parent-first.
+    /// New rooms are created by `actor_id`.
+    pub async fn apply(
+        &self,
+        tenant_id: ObjectId,
+        actor_id: ObjectId,
+        desired: &TenantConfigBundle,
+    ) -> DaoResult<ConfigDiff> {
+        let plan = self.plan(tenant_id, desired).await?;
+        let pending = |section: ConfigSection, key: &str| {
+            plan.changes.iter().any(|c| {
+                c.section == section
+                    && c.key == key
+                    && matches!(c.action, ChangeAction::Create | ChangeAction::Update)
+            })
+        };
+
+        if let Some(settings) = &desired.settings
+            && pending(ConfigSection::Settings, "settings")
+        {
+            self.tenants
+                .base
+                .update_by_id(
+                    tenant_id,
+                    doc! { "$set": { "settings": bson::to_bson(settings)?, "updated_at": DateTime::now() } },
+                )
+                .await?;
+        }
+
+        let mut role_ids: HashMap<String, ObjectId> = HashMap::new();
+        for role in self.roles.find_for_tenant(tenant_id).await? {
+            if let Some(id) = role.id {
+                role_ids.insert(role.name, id);
+            }
+        }
+        for role in desired.roles.iter() {
+            if !pending(ConfigSection::Role, &role.name) {
+                continue;
+            }
+            let role_id = match role_ids.get(&role.name) {
+                Some(id) => *id,
+                None => {
+                    let created = self
+                        .roles
+                        .create(
+                            tenant_id,
+                            role.name.clone(),
+                            None,
+                            None,
+                            role.permissions,
+                            false,
+                            false,
+                            role.position,
+                        )
+                        .await?;
+                    let id = created.id.ok_or(DaoError::NotFound)?;
+                    role_ids.insert(role.name.clone(), id);
+                    id
+                }
+            };
+            self.roles
+                .base
+                .update_one(
+                    doc! { "_id": role_id, "tenant_id": tenant_id },
+                    doc! { "$set": {
+                        "description": role.description.clone(),
+                        "color": role.color.map(i64::from),
+                        "position": i64::from(role.position),
+                        "permissions": role.permissions as i64,
+                        "is_mentionable": role.is_mentionable,
+                        "is_hoisted": role.is_hoisted,
+                        "updated_at": DateTime::now(),
+                    } },
+                )
+                .await?;
+        }
+
+        let mut rooms: HashMap<String, Room> = self
+            .rooms
+            .find_by_tenant(tenant_id)
+            .await?
+            .into_iter()
+            .map(|r| (r.path.clone(), r))
+            .collect();
+        let mut ordered: Vec<&RoomConfig> = desired.rooms.iter().collect();
+        ordered.sort_by_key(|r| r.path().matches('.').count());
+        for config in ordered {
+            let path = config.path();
+            if !pending(ConfigSection::Room, &path) {
+                continue;
+            }
+            let existing = rooms.get(&path);
+            let passcode = existing
+                .and_then(|r| r.conference_settings.as_ref())
+                .and_then(|c| c.passcode.clone());
+            let room_id = match existing.and_then(|r| r.id) {
+                Some(id) => id,
+                None => {
+                    let parent_id = config
+                        .parent
+                        .as_ref()
+                        .and_then(|p| rooms.get(p))
+                        .and_then(|r| r.id);
+                    let created = self
+                        .rooms
+                        .create(
+                            tenant_id,
+                            config.name.clone(),
+                            parent_id,
+                            actor_id,
+                            config.is_open,
+                            config.media_settings.clone(),
+                            config.conference_settings.clone(),
+                        )
+                        .await?;
+                    let id = created.id.ok_or(DaoError::NotFound)?;
+                    rooms.insert(path.clone(), created);
+                    id
+                }
+            };
+
+            let overwrites: Vec<PermissionOverwrite> = config
+                .role_overwrites
+                .iter()
+                .filter_map(|o| {
+                    Some(PermissionOverwrite {
+                        target_id: *role_ids.get(&o.role)?,
+                        target_type: ROLE_TARGET.to_string(),
+                        allow: o.allow,
+                        deny: o.deny,
+                    })
+                })
+                .collect();
+            // User overwrites aren't part of the bundle; keep them.
+            let user_overwrites = rooms
+                .get(&path)
+                .map(|r| {
+                    r.permission_overwrites
+                        .iter()
+                        .filter(|o| o.target_type != ROLE_TARGET)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let conference_settings = config.conference_settings.clone().map(|mut c| {
+                c.passcode = passcode;
+                c
+            });
+
+            self.rooms
+                .base
+                .update_one(
+                    doc! { "_id": room_id, "tenant_id": tenant_id },
+                    doc! { "$set": {
+                        "emoji": config.emoji.clone(),
+                        "topic": config.topic.clone(),
+                        "purpose": config.purpose.clone(),
+                        "icon": config.icon.clone(),
+                        "position": config.position,
+                        "is_open": config.is_open,
+                        "is_read_only": config.is_read_only,
+                        "read_only_windows": bson::to_bson(&config.read_only_windows)?,
+                        "is_default": config.is_default,
+                        "tags": config.tags.clone(),
+                        "media_settings": bson::to_bson(&config.media_settings)?,
+                        "conference_settings": bson::to_bson(&conference_settings)?,
+                        "permission_overwrites": bson::to_bson(
+                            &overwrites.into_iter().chain(user_overwrites).collect::<Vec<_>>(),
+                        )?,
+                        "updated_at": DateTime::now(),
+                    } },
+                )
+                .await?;
+        }
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(name: &str, permissions: u64) -> RoleConfig {
+        RoleConfig {
+            name: name.to_string(),
+            description: None,
+            color: None,
+            position: 0,
+            permissions,
+            is_mentionable: true,
+            is_hoisted: false,
+        }
+    }
+
+    fn room(name: &str, parent: Option<&str>) -> RoomConfig {
+        RoomConfig {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            emoji: None,
+            topic: None,
+            purpose: None,
+            icon: None,
+            position: 0,
+            is_open: true,
+            is_read_only: false,
+            read_only_windows: Vec::new(),
+            is_default: false,
+            tags: Vec::new(),
+            media_settings: None,
+            conference_settings: None,
+            role_overwrites: Vec::new(),
+        }
+    }
+
+    fn bundle(roles: Vec<RoleConfig>, rooms: Vec<RoomConfig>) -> TenantConfigBundle {
+        TenantConfigBundle {
+            version: BUNDLE_VERSION,
+            settings: None,
+            roles,
+            rooms,
+        }
+    }
+
+    fn action(diff: &ConfigDiff, key: &str) -> ChangeAction {
+        diff.changes.iter().find(|c| c.key == key).unwrap().action
+    }
+
+    #[test]
+    fn diff_reports_creates_updates_and_leftovers() {
+        let current = bundle(
+            vec![role("Admin", 1), role("Legacy", 1)],
+            vec![room("general", None), room("old", None)],
+        );
+        let mut topic = room("general", None);
+        topic.topic = Some("Company-wide".to_string());
+        let desired = bundle(
+            vec![role("Admin", 3), role("Support", 1)],
+            vec![topic, room("eng", None), room("backend", Some("eng"))],
+        );
+
+        let diff = diff(&current, &desired);
+        assert!(diff.has_changes());
+        assert_eq!(action(&diff, "Admin"), ChangeAction::Update);
+        assert_eq!(action(&diff, "Support"), ChangeAction::Create);
+        assert_eq!(action(&diff, "Legacy"), ChangeAction::OnlyInTenant);
+        assert_eq!(action(&diff, "eng.backend"), ChangeAction::Create);
+        assert_eq!(action(&diff, "old"), ChangeAction::OnlyInTenant);
+        let general = diff.changes.iter().find(|c| c.key == "general").unwrap();
+        assert_eq!(general.action, ChangeAction::Update);
+        assert_eq!(general.fields, vec!["topic".to_string()]);
+    }
+
+    #[test]
+    fn identical_bundles_have_no_changes() {
+        let b = bundle(vec![role("Admin", 1)], vec![room("general", None)]);
+        let diff = diff(&b, &b);
+        assert!(!diff.has_changes());
+        assert!(
+            diff.changes
+                .iter()
+                .all(|c| c.action == ChangeAction::Unchanged)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_dangling_references() {
+        let current = bundle(vec![role("Admin", 1)], vec![room("eng", None)]);
+
+        let ok = bundle(Vec::new(), vec![room("backend", Some("eng"))]);
+        assert!(validate(&ok, &current).is_ok());
+
+        let orphan = bundle(Vec::new(), vec![room("backend", Some("ops"))]);
+        assert!(validate(&orphan, &current).is_err());
+
+        let mut overwritten = room("general", None);
+        overwritten.role_overwrites.push(RoleOverwriteConfig {
+            role: "Ghost".to_string(),
+            allow: 0,
+            deny: 1,
+        });
+        assert!(validate(&bundle(Vec::new(), vec![overwritten]), &current).is_err());
+
+        let dup = bundle(vec![role("Ops", 1), role("Ops", 2)], Vec::new());
+        assert!(validate(&dup, &current).is_err());
+
+        let mut future = bundle(Vec::new(), Vec::new());
+        future.version = BUNDLE_VERSION + 1;
+        assert!(validate(&future, &current).is_err());
+    }
+}
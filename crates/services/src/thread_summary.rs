@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use roomler_ai_config::ThreadSummarySettings;
+use roomler_ai_db::models::{AuthorType, Message, ThreadSummary};
+
+/// Only the most recent replies are sent to Claude; enough for a summary
+/// while keeping the prompt well inside the context window.
+const MAX_PROMPT_REPLIES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryDecision {
+    /// The thread is below `min_replies`.
+    TooShort,
+    /// The existing summary is still close enough to the thread.
+    UseCached,
+    Generate,
+}
+
+pub fn decide(
+    settings: &ThreadSummarySettings,
+    reply_count: u32,
+    cached: Option<&ThreadSummary>,
+) -> SummaryDecision {
+    if reply_count < settings.min_replies {
+        return SummaryDecision::TooShort;
+    }
+    match cached {
+        Some(summary)
+            if reply_count.saturating_sub(summary.reply_count)
+                < settings.regenerate_after_replies =>
+        {
+            SummaryDecision::UseCached
+        }
+        _ => SummaryDecision::Generate,
+    }
+}
+
+/// Render the thread as a transcript prompt. System messages (including
+/// earlier summaries) are left out.
+pub fn build_prompt(
+    root: &Message,
+    replies: &[Message],
+    names: &HashMap<ObjectId, String>,
+) -> String {
+    let name = |id: &ObjectId| names.get(id).cloned().unwrap_or_else(|| id.to_hex());
+    let replies: Vec<&Message> = replies
+        .iter()
+        .filter(|m| !matches!(m.author_type, AuthorType::System))
+        .collect();
+    let skipped = replies.len().saturating_sub(MAX_PROMPT_REPLIES);
+
+    let mut prompt = String::from(concat!(
+        "Summarize the following chat thread for someone who hasn't read it. ",
+        "Cover the main topic, decisions made, open questions and action items ",
+        "(with owners when stated). Use short Markdown bullet points and reply ",
+        "with the summary only.\n\n",
+    ));
+    prompt.push_str(&format!(
+        "Original post by {}:\n{}\n\n",
+        name(&root.author_id),
+        root.content
+    ));
+    if skipped > 0 {
+        prompt.push_str(&format!("({skipped} earlier replies omitted)\n"));
+    }
+    prompt.push_str("Replies:\n");
+    for reply in &replies[skipped..] {
+        prompt.push_str(&format!("{}: {}\n", name(&reply.author_id), reply.content));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ThreadSummarySettings {
+        ThreadSummarySettings {
+            min_replies: 20,
+            regenerate_after_replies: 10,
+        }
+    }
+
+    fn cached_at(reply_count: u32) -> ThreadSummary {
+        ThreadSummary {
+            message_id: ObjectId::new(),
+            reply_count,
+            generated_at: bson::DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn short_threads_are_rejected() {
+        assert_eq!(decide(&settings(), 19, None), SummaryDecision::TooShort);
+        assert_eq!(decide(&settings(), 20, None), SummaryDecision::Generate);
+    }
+
+    #[test]
+    fn cache_is_reused_until_enough_new_replies() {
+        let cached = cached_at(25);
+        assert_eq!(
+            decide(&settings(), 34, Some(&cached)),
+            SummaryDecision::UseCached
+        );
+        assert_eq!(
+            decide(&settings(), 35, Some(&cached)),
+            SummaryDecision::Generate
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use reqwest::multipart::{Form, Part};
+use roomler_ai_config::AsrSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::outbound::{Outbound, OutboundClient};
+
+/// Speech-to-text through an OpenAI-compatible transcription endpoint.
+#[derive(Debug, Clone)]
+pub struct TranscriptionService {
+    client: OutboundClient,
+    url: Option<String>,
+    api_key: Option<String>,
+    model: String,
+    stats: Arc<AsrStats>,
+}
+
+/// Request counters shared by every clone of the service.
+#[derive(Debug, Default)]
+struct AsrStats {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    /// Summed latency of successful requests.
+    total_ms: AtomicU64,
+}
+
+/// What an admin sees about the ASR backend.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStatus {
+    pub kind: &'static str,
+    /// Empty when transcription is disabled.
+    pub url: String,
+    pub model: String,
+    pub configured: bool,
+    /// Whether `GET /v1/models` answered; `None` when not configured.
+    pub reachable: Option<bool>,
+    /// Whether the server lists `model`; `None` when it couldn't be asked.
+    pub model_loaded: Option<bool>,
+    pub error: Option<String>,
+    pub requests: u64,
+    pub failures: u64,
+    pub avg_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Present when the server detects it (e.g. `verbose_json` backends).
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl TranscriptionService {
+    pub fn new(settings: &AsrSettings, outbound: &Outbound) -> Self {
+        let client =
+            outbound.client_with_timeout("asr", Duration::from_secs(settings.timeout_secs.max(1)));
+        Self {
+            client,
+            url: Some(settings.url.trim_end_matches('/').to_string()).filter(|u| !u.is_empty()),
+            api_key: settings.api_key.clone().filter(|k| !k.is_empty()),
+            model: settings.model.clone(),
+            stats: Arc::default(),
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.url.is_some()
+    }
+
+    /// Transcribe one audio clip.
+    pub async fn transcribe(
+        &self,
+        audio: Vec<u8>,
+        filename: &str,
+        content_type: &str,
+    ) -> Result<Transcript, String> {
+        let started = Instant::now();
+        self.stats.requests.fetch_add(1, Ordering::Relaxed);
+        let result = self.request(audio, filename, content_type).await;
+        match &result {
+            Ok(_) => {
+                let ms = started.elapsed().as_millis() as u64;
+                self.stats.total_ms.fetch_add(ms, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.stats.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+
+    /// Probe the backend and report it with the request counters, so a
+    /// wrong URL or model shows up before someone records a voice note.
+    pub async fn status(&self) -> BackendStatus {
+        let requests = self.stats.requests.load(Ordering::Relaxed);
+        let failures = self.stats.failures.load(Ordering::Relaxed);
+        let succeeded = requests.saturating_sub(failures);
+        let mut status = BackendStatus {
+            kind: "openai_compatible",
+            url: self.url.clone().unwrap_or_default(),
+            model: self.model.clone(),
+            configured: self.url.is_some(),
+            reachable: None,
+            model_loaded: None,
+            error: None,
+            requests,
+            failures,
+            avg_latency_ms: (succeeded > 0)
+                .then(|| self.stats.total_ms.load(Ordering::Relaxed) as f64 / succeeded as f64),
+        };
+        if self.url.is_some() {
+            match self.models().await {
+                Ok(models) => {
+                    status.reachable = Some(true);
+                    status.model_loaded = Some(models.contains(&self.model));
+                }
+                Err(e) => {
+                    status.reachable = Some(false);
+                    status.error = Some(e);
+                }
+            }
+        }
+        status
+    }
+
+    /// Model ids the backend serves.
+    async fn models(&self) -> Result<Vec<String>, String> {
+        let url = self
+            .url
+            .as_ref()
+            .ok_or_else(|| "ASR is not configured".to_string())?;
+        let mut request = self.client.get(format!("{}/v1/models", url));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("ASR request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("ASR error {}", response.status()));
+        }
+        let list: ModelList = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse ASR model list: {}", e))?;
+        Ok(list.data.into_iter().map(|m| m.id).collect())
+    }
+
+    async fn request(
+        &self,
+        audio: Vec<u8>,
+        filename: &str,
+        content_type: &str,
+    ) -> Result<Transcript, String> {
+        let url = self
+            .url
+            .as_ref()
+            .ok_or_else(|| "ASR is not configured".to_string())?;
+
+        let file = Part::bytes(audio)
+            .file_name(filename.to_string())
+            .mime_str(content_type)
+            .map_err(|e| format!("Invalid audio content type: {}", e))?;
+        let form = Form::new()
+            .text("model", self.model.clone())
+            .text("response_format", "json")
+            .part("file", file);
+
+        let mut request = self
+            .client
+            .post(format!("{}/v1/audio/transcriptions", url))
+            .multipart(form);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("ASR request failed: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("ASR error {}: {}", status, body));
+        }
+        let transcript: Transcript = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse ASR response: {}", e))?;
+        Ok(Transcript {
+            text: transcript.text.trim().to_string(),
+            ..transcript
+        })
+    }
+}
@@ -0,0 +1,135 @@
+//! Tenants' outgoing webhooks. Each subscribed event becomes a
+//! [`WebhookDelivery`](roomler_ai_db::models::WebhookDelivery) that is sent
+//! right away and, while it keeps failing, retried with exponential
+//! backoff until `max_attempts`. Bodies are signed like reaction rule
+//! webhooks: `X-Roomler-Signature` is [`crate::reaction_rules::signature`]
+//! of the body under the webhook's secret.
+
+use std::time::Duration;
+
+use roomler_ai_config::WebhookSettings;
+use roomler_ai_db::models::webhook_events;
+
+use crate::outbound::{Outbound, OutboundClient};
+use crate::reaction_rules::signature;
+
+pub const MAX_NAME_LEN: usize = 100;
+
+/// Longest wait between two attempts.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Response bodies are not kept; errors are cut to this length.
+const MAX_ERROR_LEN: usize = 500;
+
+pub struct WebhookSender {
+    client: OutboundClient,
+}
+
+/// How an attempt went.
+#[derive(Debug, Clone)]
+pub struct AttemptOutcome {
+    /// HTTP status, when the endpoint answered.
+    pub response_status: Option<u16>,
+    /// `None` on a 2xx answer.
+    pub error: Option<String>,
+}
+
+impl AttemptOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl WebhookSender {
+    pub fn new(settings: &WebhookSettings, outbound: &Outbound) -> Self {
+        let client = outbound.public_client_with_timeout(
+            "webhooks",
+            Duration::from_secs(settings.timeout_secs.max(1)),
+        );
+        Self { client }
+    }
+
+    /// POST `body` to `url`, signed with `secret`.
+    pub async fn send(&self, url: &str, secret: &str, event: &str, body: &str) -> AttemptOutcome {
+        let result = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-Roomler-Event", event)
+            .header("X-Roomler-Signature", signature(secret, body.as_bytes()))
+            .body(body.to_string())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => AttemptOutcome {
+                response_status: Some(response.status().as_u16()),
+                error: None,
+            },
+            Ok(response) => AttemptOutcome {
+                response_status: Some(response.status().as_u16()),
+                error: Some(format!("Webhook answered {}", response.status())),
+            },
+            Err(e) => AttemptOutcome {
+                response_status: None,
+                error: Some(truncate(&format!("Webhook request failed: {}", e))),
+            },
+        }
+    }
+}
+
+/// Wait after the `attempt`th failed attempt: `base`, doubled per attempt
+/// after the first, at most [`MAX_BACKOFF`].
+pub fn backoff(base_secs: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(32);
+    Duration::from_secs(base_secs.max(1).saturating_mul(factor)).min(MAX_BACKOFF)
+}
+
+/// Check a webhook's name, url and events.
+pub fn validate(name: &str, url: &str, events: &[String]) -> Result<(), String> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("name must be 1-{MAX_NAME_LEN} characters"));
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|_| "Invalid webhook url".to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Webhook url must be http or https".to_string());
+    }
+    if events.is_empty() {
+        return Err("Subscribe to at least one event".to_string());
+    }
+    if let Some(unknown) = events
+        .iter()
+        .find(|e| !webhook_events::ALL.contains(&e.as_str()))
+    {
+        return Err(format!(
+            "Unknown event {unknown}; expected one of {}",
+            webhook_events::ALL.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn truncate(error: &str) -> String {
+    error.chars().take(MAX_ERROR_LEN).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(30, 1), Duration::from_secs(30));
+        assert_eq!(backoff(30, 2), Duration::from_secs(60));
+        assert_eq!(backoff(30, 4), Duration::from_secs(240));
+        assert_eq!(backoff(30, 40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn events_must_be_known() {
+        let url = "https://example.com/hook";
+        assert!(validate("ci", url, &["message.create".into()]).is_ok());
+        assert!(validate("ci", url, &[]).is_err());
+        assert!(validate("ci", url, &["message.delete".into()]).is_err());
+        assert!(validate("ci", "ftp://example.com", &["member.join".into()]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+
+#[tokio::test]
+async fn deleted_account_is_disabled_then_anonymized() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("delete-me").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tid, room_id),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tid, room_id);
+    let message: Value = app
+        .auth_post(&messages_url, member)
+        .json(&serde_json::json!({ "content": "Goodbye" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_delete("/api/auth/me", member)
+        .json(&serde_json::json!({ "password": "wrong" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    // Owners hand their tenants over first.
+    let resp = app
+        .auth_delete("/api/auth/me", admin)
+        .json(&serde_json::json!({ "password": "Admin123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let resp = app
+        .auth_delete("/api/auth/me", member)
+        .json(&serde_json::json!({ "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["deleted"], true);
+
+    // Tokens and credentials stop working.
+    let resp = app.auth_get("/api/auth/me", member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = app
+        .client
+        .post(app.url("/api/auth/refresh"))
+        .json(&serde_json::json!({ "refresh_token": tenant.member.refresh_token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = app
+        .client
+        .post(app.url("/api/auth/login"))
+        .json(&serde_json::json!({ "email": tenant.member.email, "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // Memberships end; messages stay, under a neutral name.
+    let members: Value = app
+        .auth_get(&format!("/api/tenant/{}/member", tid), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        !members["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|m| m["user_id"] == tenant.member.id.as_str())
+    );
+    let messages: Value = app
+        .auth_get(&messages_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let kept = messages["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["id"] == message["id"])
+        .expect("message kept");
+    assert_eq!(kept["content"], "Goodbye");
+    assert_eq!(kept["author_name"], "Deleted user");
+
+    // The profile is anonymized once the grace period is over.
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let purged = roomler_ai_api::account_deletion::purge_due(&state, bson::DateTime::now())
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+    let later = bson::DateTime::from_chrono(chrono::Utc::now() + chrono::Duration::days(31));
+    let purged = roomler_ai_api::account_deletion::purge_due(&state, later)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    let uid = ObjectId::parse_str(&tenant.member.id).unwrap();
+    let user = app
+        .db
+        .collection::<bson::Document>("users")
+        .find_one(doc! { "_id": uid })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(user.get_str("display_name").unwrap(), "Deleted user");
+    assert_eq!(
+        user.get_str("email").unwrap(),
+        format!("deleted-{}@deleted.invalid", tenant.member.id)
+    );
+    assert!(user.get("password_hash").is_none());
+    assert!(user.get_array("oauth_providers").unwrap().is_empty());
+}
@@ -0,0 +1,222 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn analytics_export_produces_daily_csv() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("analytics1").await;
+    let room_id = tenant.rooms[0].id.clone();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    for i in 1..=3 {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "content": format!("Analytics message {}", i) }))
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let from = (today - chrono::Duration::days(2)).to_string();
+    let to = today.to_string();
+    let path = format!("/api/tenant/{}/export/analytics", tenant.tenant_id);
+
+    // Members without MANAGE_TENANT can't export
+    let resp = app
+        .auth_post(&path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "from": from, "to": to }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Backwards ranges are rejected
+    let resp = app
+        .auth_post(&path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "from": to, "to": from }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_post(&path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "from": from, "to": to }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    let task_id = json["task_id"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(
+                &format!("/api/tenant/{}/task/{}", tenant.tenant_id, task_id),
+                &tenant.admin.access_token,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Completed" => {
+                completed = true;
+                assert_eq!(json["file_name"], format!("analytics-{}-{}.csv", from, to));
+                break;
+            }
+            "Failed" => panic!("Analytics export failed: {:?}", json["error"]),
+            _ => {}
+        }
+    }
+    assert!(
+        completed,
+        "Analytics export did not complete within timeout"
+    );
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/task/{}/download", tenant.tenant_id, task_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let csv = resp.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "date,active_users,messages,conference_minutes");
+    // A row for every day, quiet ones included
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[1], format!("{},0,0,0", from));
+    assert_eq!(lines[3], format!("{},1,3,0", to));
+}
+
+#[tokio::test]
+async fn monthly_analytics_report_can_be_toggled() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("analytics2").await;
+    let path = format!("/api/tenant/{}/analytics/report", tenant.tenant_id);
+
+    let json: Value = app
+        .auth_get(&path, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["enabled"], false);
+    assert!(json["last_sent_for"].is_null());
+
+    let resp = app
+        .auth_put(&path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["enabled"], true);
+
+    let resp = app
+        .auth_put(&path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "enabled": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let json: Value = app
+        .auth_get(&path, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["enabled"], true);
+}
+
+#[tokio::test]
+async fn relay_usage_is_reported_and_alerts_once_over_budget() {
+    let app = TestApp::spawn_with_settings(|s| s.turn.monthly_budget_mb = 1).await;
+    let tenant = app.seed_tenant("relay").await;
+    let tid = bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap();
+    let rid = bson::oid::ObjectId::parse_str(&tenant.rooms[0].id).unwrap();
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let today = chrono::Utc::now().date_naive();
+    let yesterday = today - chrono::Duration::days(1);
+    let record = |date, sent, received| {
+        state.relay_usage.record(
+            tid,
+            rid,
+            date,
+            roomler_ai_services::relay_usage::RelayedBytes { sent, received },
+        )
+    };
+    record(yesterday, 100, 0).await.unwrap();
+    record(today, 500_000, 200_000).await.unwrap();
+
+    // Still under the 1 MB budget.
+    assert!(
+        !roomler_ai_api::relay_usage::check_budget(&state, tid, today)
+            .await
+            .unwrap()
+    );
+    record(today, 300_000, 100_000).await.unwrap();
+    assert!(
+        roomler_ai_api::relay_usage::check_budget(&state, tid, today)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !roomler_ai_api::relay_usage::check_budget(&state, tid, today)
+            .await
+            .unwrap()
+    );
+
+    let path = format!(
+        "/api/tenant/{}/analytics/relay?from={}&to={}",
+        tenant.tenant_id, yesterday, today
+    );
+    let resp = app
+        .auth_get(&path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let report: Value = app
+        .auth_get(&path, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["days"].as_array().unwrap().len(), 2);
+    assert_eq!(report["days"][0]["bytes_sent"], 100);
+    assert_eq!(report["days"][1]["bytes_sent"], 800_000);
+    assert_eq!(report["bytes_sent"], 800_100);
+    assert_eq!(report["bytes_received"], 300_000);
+    assert_eq!(report["top_rooms"][0]["room_id"], tenant.rooms[0].id);
+    assert_eq!(report["top_rooms"][0]["room_name"], "general");
+    assert!(report["month_to_date_bytes"].as_u64().unwrap() >= 1_100_000);
+    assert_eq!(report["monthly_budget_bytes"], 1_000_000);
+}
@@ -0,0 +1,176 @@
+use crate::fixtures::fake_asr;
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn transcription_backend_reports_reachability_and_model() {
+    let asr_url = fake_asr::spawn().await;
+    let url_for_settings = asr_url.clone();
+    let app = TestApp::spawn_with_settings(|s| {
+        s.asr.url = url_for_settings;
+        s.asr.model = "whisper-large".to_string();
+    })
+    .await;
+    let tenant = app.seed_tenant("asrbackend").await;
+    let url = format!(
+        "/api/tenant/{}/admin/transcription/backends",
+        tenant.tenant_id
+    );
+
+    let resp = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    let backend = &json["backends"][0];
+    assert_eq!(backend["url"], asr_url.as_str());
+    assert_eq!(backend["configured"], true);
+    assert_eq!(backend["reachable"], true);
+    // The server is up but doesn't serve the configured model.
+    assert_eq!(backend["model_loaded"], false);
+    assert_eq!(backend["requests"], 0);
+    assert!(backend["avg_latency_ms"].is_null());
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn unconfigured_transcription_backend_is_not_probed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("asrnone").await;
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/admin/transcription/backends",
+                tenant.tenant_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    let backend = &json["backends"][0];
+    assert_eq!(backend["configured"], false);
+    assert!(backend["reachable"].is_null());
+}
+
+#[tokio::test]
+async fn segment_quality_is_logged_and_consented_samples_kept() {
+    use roomler_ai_services::media::{
+        asr_quality::SegmentQuality,
+        transcript_feed::{TranscriptEvent, TranscriptScope},
+    };
+
+    let app = TestApp::spawn_with_settings(|s| s.asr.sample_retention = true).await;
+    let tenant = app.seed_tenant("asrquality").await;
+    let room_oid = bson::oid::ObjectId::parse_str(&tenant.rooms[0].id).unwrap();
+    let admin_oid = bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap();
+    let member_oid = bson::oid::ObjectId::parse_str(&tenant.member.id).unwrap();
+
+    // Only the admin agrees to share voice samples.
+    let resp = app
+        .auth_put("/api/auth/me/preferences", &tenant.admin.access_token)
+        .json(&serde_json::json!({ "privacy": { "share_voice_samples": true } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // A second state over the same database stands in for the ASR pipeline.
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let segment = |user_id, track: &str, text: &str, confidence: f64, is_final| TranscriptEvent {
+        room_id: room_oid,
+        track: track.to_string(),
+        user_id,
+        speaker_name: "Speaker".to_string(),
+        text: text.to_string(),
+        language: Some("en".to_string()),
+        confidence: Some(confidence),
+        start_time: 0.0,
+        end_time: 1.0,
+        is_final,
+        quality: Some(SegmentQuality {
+            rms_dbfs: -32.5,
+            vad_ms: 1000,
+            language_score_delta: Some(0.1),
+            audio: Some(std::sync::Arc::new(b"RIFF fake wav".to_vec())),
+        }),
+        model: None,
+        scope: TranscriptScope::Room,
+    };
+    for event in [
+        segment(admin_oid, "original", "mumbled", 0.3, true),
+        segment(admin_oid, "original", "mumb", 0.2, false),
+        segment(admin_oid, "de", "genuschelt", 0.3, true),
+        segment(admin_oid, "original", "clear speech", 0.95, true),
+        segment(member_oid, "original", "also mumbled", 0.3, true),
+    ] {
+        state.transcript_feed.publish(event);
+    }
+
+    // Final segments of the original track are logged, once each.
+    let metrics = app.db.collection::<bson::Document>("asr_metrics");
+    let mut logged = 0;
+    for _ in 0..50 {
+        logged = metrics.count_documents(bson::doc! {}).await.unwrap();
+        if logged == 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(logged, 3);
+    let metric = metrics
+        .find_one(bson::doc! { "user_id": member_oid })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(metric.get_f64("rms_dbfs").unwrap(), -32.5);
+
+    // Only the consenting speaker's low-confidence segment is kept.
+    let url = format!(
+        "/api/tenant/{}/admin/transcription/samples",
+        tenant.tenant_id
+    );
+    let samples: Vec<Value> = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0]["text"], "mumbled");
+    assert_eq!(samples[0]["user_id"], tenant.admin.id);
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!("{}/{}/audio", url, samples[0]["id"].as_str().unwrap()),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "audio/wav");
+    assert_eq!(&resp.bytes().await.unwrap()[..], b"RIFF fake wav");
+}
@@ -0,0 +1,180 @@
+use crate::fixtures::{seed::SeededTenant, test_app::TestApp};
+use reqwest::multipart;
+use serde_json::Value;
+
+async fn upload(
+    app: &TestApp,
+    tenant: &SeededTenant,
+    name: &str,
+    mime: &str,
+    bytes: &[u8],
+) -> Value {
+    let room_id = tenant.rooms[0].id.clone();
+    let part = multipart::Part::bytes(bytes.to_vec())
+        .file_name(name.to_string())
+        .mime_str(mime)
+        .unwrap();
+    let form = multipart::Form::new()
+        .part("file", part)
+        .text("room_id", room_id);
+    let resp = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/file/upload", tenant.tenant_id)))
+        .header(
+            "Authorization",
+            format!("Bearer {}", tenant.admin.access_token),
+        )
+        .multipart(form)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn assets_are_served_by_content_hash_with_immutable_caching() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("asset1").await;
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/join",
+            tenant.tenant_id, tenant.rooms[0].id
+        ),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let image = upload(
+        &app,
+        &tenant,
+        "avatar.png",
+        "image/png",
+        b"not really a png",
+    )
+    .await;
+    let checksum = image["checksum"].as_str().unwrap();
+    let asset_url = image["asset_url"].as_str().unwrap();
+    assert_eq!(
+        asset_url,
+        format!("/api/asset/{}/{}", tenant.tenant_id, checksum)
+    );
+
+    // No auth needed
+    let resp = app.client.get(app.url(asset_url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let headers = resp.headers();
+    assert_eq!(headers["content-type"], "image/png");
+    assert!(
+        headers["cache-control"]
+            .to_str()
+            .unwrap()
+            .starts_with("public, max-age=")
+    );
+    assert!(
+        headers["cache-control"]
+            .to_str()
+            .unwrap()
+            .ends_with("immutable")
+    );
+    let etag = headers["etag"].to_str().unwrap().to_string();
+    assert_eq!(etag, format!("\"{}\"", checksum));
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), b"not really a png");
+
+    let resp = app
+        .client
+        .get(app.url(asset_url))
+        .header("If-None-Match", &etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 304);
+
+    // Non-images stay behind the authenticated download route
+    let doc = upload(&app, &tenant, "notes.txt", "text/plain", b"private notes").await;
+    assert!(doc.get("asset_url").is_none());
+    let resp = app
+        .client
+        .get(app.url(&format!(
+            "/api/asset/{}/{}",
+            tenant.tenant_id,
+            doc["checksum"].as_str().unwrap()
+        )))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn private_tenants_require_signed_asset_urls() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("asset2").await;
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/join",
+            tenant.tenant_id, tenant.rooms[0].id
+        ),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let image = upload(&app, &tenant, "emoji.png", "image/png", b"party parrot").await;
+    let public_url = image["asset_url"].as_str().unwrap().to_string();
+    let settings = format!("/api/tenant/{}/assets", tenant.tenant_id);
+
+    // Members without MANAGE_TENANT can't change it
+    let resp = app
+        .auth_put(&settings, &tenant.member.access_token)
+        .json(&serde_json::json!({ "private": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_put(&settings, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "private": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app.client.get(app.url(&public_url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let json: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/file/{}",
+                tenant.tenant_id,
+                image["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let signed_url = json["asset_url"].as_str().unwrap();
+    assert!(signed_url.starts_with(&format!("{}?expires=", public_url)));
+
+    let resp = app.client.get(app.url(signed_url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(
+        resp.headers()["cache-control"]
+            .to_str()
+            .unwrap()
+            .starts_with("private, max-age=")
+    );
+
+    // A tampered signature is rejected
+    let tampered = format!("{}0", signed_url);
+    let resp = app.client.get(app.url(&tampered)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -0,0 +1,204 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+async fn audit(app: &TestApp, tenant_id: &str, token: &str, query: &str) -> Value {
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/audit?{}", tenant_id, query), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn admin_actions_are_recorded_with_actor_target_and_ip() {
+    let app =
+        TestApp::spawn_with_settings(|s| s.app.trusted_proxies = "127.0.0.1".to_string()).await;
+    let tenant = app.seed_tenant("audit1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    // Room create + delete
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tid), admin)
+        .json(&serde_json::json!({ "name": "audited" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let resp = app
+        .auth_delete(&format!("/api/tenant/{}/room/{}", tid, room_id), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Invite create + revoke
+    let invite: Value = app
+        .auth_post(&format!("/api/tenant/{}/invite", tid), admin)
+        .json(&serde_json::json!({ "target_email": "guest@audit1.test" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let invite_id = invite["id"].as_str().unwrap();
+    let resp = app
+        .auth_delete(&format!("/api/tenant/{}/invite/{}", tid, invite_id), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Role create + assign
+    let role: Value = app
+        .auth_post(&format!("/api/tenant/{}/role", tid), admin)
+        .json(&serde_json::json!({ "name": "auditor", "permissions": 1 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let role_id = role["id"].as_str().unwrap();
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/role/{}/assign/{}",
+                tid, role_id, tenant.member.id
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Members can't read the log
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit", tid),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Member remove, from a known client address
+    let resp = app
+        .auth_delete(
+            &format!("/api/tenant/{}/member/{}", tid, tenant.member.id),
+            admin,
+        )
+        .header("X-Forwarded-For", "203.0.113.9")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let all = audit(&app, tid, admin, "per_page=100").await;
+    let actions: Vec<&str> = all["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    for expected in [
+        "room.create",
+        "room.delete",
+        "invite.create",
+        "invite.revoke",
+        "role.create",
+        "member.role_assign",
+        "member.remove",
+    ] {
+        assert!(
+            actions.contains(&expected),
+            "missing {expected}: {actions:?}"
+        );
+    }
+    // Newest first
+    assert_eq!(actions[0], "member.remove");
+
+    let removed = audit(&app, tid, admin, "action=member.remove").await;
+    assert_eq!(removed["total"], 1);
+    let entry = &removed["items"][0];
+    assert_eq!(entry["actor_id"], tenant.admin.id.as_str());
+    assert_eq!(entry["actor_type"], "user");
+    assert_eq!(entry["target_type"], "member");
+    assert_eq!(entry["target_id"], tenant.member.id.as_str());
+    assert_eq!(entry["ip"], "203.0.113.9");
+    assert!(entry["created_at"].as_str().is_some());
+
+    let deleted = audit(
+        &app,
+        tid,
+        admin,
+        &format!("target_type=room&target_id={}", room_id),
+    )
+    .await;
+    assert_eq!(deleted["total"], 2);
+    assert_eq!(deleted["items"][0]["action"], "room.delete");
+    assert_eq!(deleted["items"][0]["changes"][0]["old_value"], "audited");
+
+    // The removed member lost access
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room", tid),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn audit_log_is_scoped_to_the_tenant() {
+    let app = TestApp::spawn().await;
+    let a = app.seed_tenant("audit2a").await;
+    let b = app.seed_tenant("audit2b").await;
+
+    let a_rooms = audit(
+        &app,
+        &a.tenant_id,
+        &a.admin.access_token,
+        "action=room.create",
+    )
+    .await;
+    assert!(a_rooms["total"].as_u64().unwrap() > 0);
+    assert!(
+        a_rooms["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|e| e["actor_id"] == a.admin.id.as_str())
+    );
+
+    // Admin of another tenant is not a member here
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit", a.tenant_id),
+            &b.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?from=yesterday", a.tenant_id),
+            &a.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
@@ -642,6 +642,159 @@ async fn webhook_invoice_payment_failed_sets_past_due() {
     assert_eq!(billing.get_str("status").unwrap(), "past_due");
 }
 
+// ---------------------------------------------------------------------------
+// Billing events to tenant webhooks
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn billing_changes_reach_webhooks_and_limit_changes_are_audited() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.stripe.webhook_secret = "whsec_test_secret_for_billing_tests".to_string();
+    })
+    .await;
+    let seeded = app.seed_tenant("billing-events").await;
+    let tid = &seeded.tenant_id;
+    let token = &seeded.admin.access_token;
+    let (hook_url, received) = spawn_receiver().await;
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/webhook", tid), token)
+        .json(&serde_json::json!({
+            "name": "Billing",
+            "url": hook_url,
+            "events": [
+                "billing.plan_change",
+                "billing.subscription_change",
+                "billing.limits_change",
+            ],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+
+    let status = send_stripe_event(
+        &app,
+        serde_json::json!({
+            "type": "checkout.session.completed",
+            "data": { "object": {
+                "metadata": { "tenant_id": tid, "plan": "pro" },
+                "subscription": "sub_events_123",
+                "customer": "cus_events_456",
+            } },
+        }),
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    // On Pro, grow one channel past the free plan's limit.
+    let tenant_oid = bson::oid::ObjectId::parse_str(tid).unwrap();
+    let channels = app
+        .db
+        .collection::<bson::Document>("rooms")
+        .count_documents(bson::doc! {
+            "tenant_id": tenant_oid,
+            "room_type": { "$ne": "dm" },
+            "deleted_at": null,
+        })
+        .await
+        .unwrap();
+    assert!(channels <= 6);
+    let mut extra = Vec::new();
+    for n in channels..6 {
+        let room: Value = app
+            .auth_post(&format!("/api/tenant/{}/room", tid), token)
+            .json(&serde_json::json!({ "name": format!("extra-{n}") }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        extra.push(room["id"].as_str().unwrap().to_string());
+    }
+
+    let status = send_stripe_event(
+        &app,
+        serde_json::json!({
+            "type": "customer.subscription.deleted",
+            "data": { "object": { "id": "sub_events_123" } },
+        }),
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    let events = wait_for_events(&received, 5).await;
+    let find = |event: &str, pred: &dyn Fn(&Value) -> bool| {
+        events
+            .iter()
+            .find(|p| p["event"] == event && pred(&p["data"]))
+            .unwrap_or_else(|| panic!("No {event} in {events:?}"))
+            .clone()
+    };
+    let upgrade = find("billing.plan_change", &|d| d["new_plan"] == "pro");
+    assert_eq!(upgrade["data"]["old_plan"], "free");
+    assert_eq!(upgrade["data"]["downgrade"], false);
+    let downgrade = find("billing.plan_change", &|d| d["new_plan"] == "free");
+    assert_eq!(downgrade["data"]["downgrade"], true);
+    assert_eq!(downgrade["data"]["limits"]["max_channels"], 5);
+    find("billing.subscription_change", &|d| {
+        d["old_status"].is_null() && d["new_status"] == "active"
+    });
+    find("billing.subscription_change", &|d| {
+        d["old_status"] == "active" && d["new_status"] == "canceled"
+    });
+    let over = find("billing.limits_change", &|_| true);
+    assert_eq!(over["tenant_id"], tid.as_str());
+    assert_eq!(over["data"]["plan"], "free");
+    assert_eq!(
+        over["data"]["exceeded"],
+        serde_json::json!(["max_channels"])
+    );
+    assert_eq!(over["data"]["usage"]["channels"], 6);
+
+    // An admin deleting a channel brings the tenant back within its plan.
+    let room_id = extra.pop().expect("the test creates at least one room");
+    let resp = app
+        .auth_delete(&format!("/api/tenant/{}/room/{}", tid, room_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let events = wait_for_events(&received, 6).await;
+    let within = events
+        .iter()
+        .filter(|p| p["event"] == "billing.limits_change")
+        .find(|p| p["data"]["exceeded"] == serde_json::json!([]))
+        .expect("limits_change back within the plan");
+    assert_eq!(
+        within["data"]["previously_exceeded"],
+        serde_json::json!(["max_channels"])
+    );
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?action=billing.limits_change", tid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["total"], 2);
+    assert_eq!(audit["items"][0]["actor_type"], "system");
+    assert_eq!(
+        audit["items"][0]["changes"][0]["new_value"],
+        serde_json::json!([])
+    );
+    assert_eq!(
+        audit["items"][1]["changes"][0]["new_value"],
+        serde_json::json!(["max_channels"])
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Helper: compute HMAC-SHA256 hex digest
 // ---------------------------------------------------------------------------
@@ -655,3 +808,59 @@ fn compute_hmac_sha256(secret: &str, message: &str) -> String {
     mac.update(message.as_bytes());
     hex::encode(mac.finalize().into_bytes())
 }
+
+/// Sign `payload` like Stripe does and POST it to the webhook endpoint.
+async fn send_stripe_event(app: &TestApp, payload: Value) -> u16 {
+    let payload_bytes = serde_json::to_vec(&payload).unwrap();
+    let timestamp = "1234567890";
+    let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
+    let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
+    app.client
+        .post(app.url("/api/stripe/webhook"))
+        .header("Content-Type", "application/json")
+        .header("stripe-signature", format!("t={},v1={}", timestamp, sig))
+        .body(payload_bytes)
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+type Received = std::sync::Arc<std::sync::Mutex<Vec<Value>>>;
+
+/// Tenant webhook endpoint keeping every payload it receives.
+async fn spawn_receiver() -> (String, Received) {
+    use axum::{Json, Router, http::StatusCode, routing::post};
+
+    let received: Received = Default::default();
+    let recorded = received.clone();
+    let router = Router::new().route(
+        "/hook",
+        post(move |Json(payload): Json<Value>| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(payload);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}/hook", addr), received)
+}
+
+/// Wait until at least `count` payloads arrived.
+async fn wait_for_events(received: &Received, count: usize) -> Vec<Value> {
+    for _ in 0..50 {
+        let events = received.lock().unwrap().clone();
+        if events.len() >= count {
+            return events;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    received.lock().unwrap().clone()
+}
@@ -0,0 +1,212 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn bot_tokens_post_into_their_room_until_revoked() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bots").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let room_id = &tenant.rooms[0].id;
+    let bots_url = format!("/api/tenant/{}/bot", tid);
+
+    let resp = app
+        .auth_post(&bots_url, token)
+        .json(&serde_json::json!({ "name": "CI", "room_id": room_id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let bot: Value = resp.json().await.unwrap();
+    let bot_token = bot["token"].as_str().unwrap().to_string();
+    assert!(bot_token.ends_with(bot["token_hint"].as_str().unwrap()));
+
+    let resp = app
+        .auth_get(&bots_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // No user account needed, only the token.
+    let hook_url = app.url(&format!("/api/hook/{}", bot_token));
+    let resp = app
+        .client
+        .post(&hook_url)
+        .json(&serde_json::json!({
+            "title": "Build #42 passed",
+            "url": "https://ci.example.com/42",
+            "text": "All 311 tests green",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let message: Value = resp.json().await.unwrap();
+    assert_eq!(
+        message["content"],
+        "**[Build #42 passed](https://ci.example.com/42)**\nAll 311 tests green"
+    );
+    assert_eq!(message["author_id"], bot["id"]);
+    assert_eq!(message["room_id"], room_id.as_str());
+
+    let resp = app
+        .client
+        .post(&hook_url)
+        .json(&serde_json::json!({ "text": "  " }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Room members see the bot by name.
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let posted = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["id"] == message["id"])
+        .unwrap();
+    assert_eq!(posted["author_name"], "CI");
+
+    // The token is never shown again.
+    let bots: Vec<Value> = app
+        .auth_get(&bots_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(bots.len(), 1);
+    assert!(bots[0].get("token").is_none());
+    assert!(bots[0]["last_used_at"].is_string());
+
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", bots_url, bot["id"].as_str().unwrap()),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .client
+        .post(&hook_url)
+        .json(&serde_json::json!({ "text": "too late" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = app
+        .client
+        .post(app.url("/api/hook/rbt_unknown"))
+        .json(&serde_json::json!({ "text": "hi" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn bots_cannot_target_other_tenants_rooms() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("botsa").await;
+    let other = app.seed_tenant("botsb").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/bot", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "CI", "room_id": other.rooms[0].id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn bot_api_calls_are_metered_against_the_daily_quota() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bot-quota").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let developer_url = format!("/api/tenant/{}/developer", tid);
+
+    let bot: Value = app
+        .auth_post(&format!("/api/tenant/{}/bot", tid), token)
+        .json(&serde_json::json!({ "name": "CI", "room_id": tenant.rooms[0].id }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let hook_url = app.url(&format!("/api/hook/{}", bot["token"].as_str().unwrap()));
+
+    let resp = app
+        .auth_put(&developer_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "daily_cap": 2 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(&developer_url, token)
+        .json(&serde_json::json!({ "daily_cap": 2 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let post = || {
+        app.client
+            .post(&hook_url)
+            .json(&serde_json::json!({ "text": "Deployed" }))
+            .send()
+    };
+    let resp = post().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    assert_eq!(resp.headers()["x-quota-limit"], "2");
+    assert_eq!(resp.headers()["x-quota-remaining"], "1");
+    assert!(resp.headers().contains_key("x-quota-reset"));
+    let resp = post().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    assert_eq!(resp.headers()["x-quota-remaining"], "0");
+
+    let resp = post().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 429);
+    assert_eq!(resp.headers()["x-quota-remaining"], "0");
+    assert!(resp.headers().contains_key("retry-after"));
+
+    let usage: Value = app
+        .auth_get(&format!("{}/usage", developer_url), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["plan_limit"], 1_000);
+    assert_eq!(usage["daily_cap"], 2);
+    assert_eq!(usage["limit"], 2);
+    assert_eq!(usage["used"], 3);
+    assert_eq!(usage["remaining"], 0);
+    let tokens = usage["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["bot_id"], bot["id"]);
+    assert_eq!(tokens[0]["calls"], 3);
+}
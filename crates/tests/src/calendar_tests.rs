@@ -0,0 +1,237 @@
+use crate::fixtures::test_app::TestApp;
+use bson::doc;
+use serde_json::{Value, json};
+
+/// Create an open conference room scheduled `start_hours` from now for an
+/// hour, and return its id.
+async fn scheduled_room(
+    app: &TestApp,
+    tenant_id: &str,
+    token: &str,
+    name: &str,
+    start_hours: i64,
+) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{tenant_id}/room"), token)
+        .json(&json!({
+            "name": name,
+            "is_open": true,
+            "media_settings": { "audio_enabled": true, "video_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+    let start = chrono::Utc::now() + chrono::Duration::hours(start_hours);
+    app.db
+        .collection::<bson::Document>("rooms")
+        .update_one(
+            doc! { "_id": bson::oid::ObjectId::parse_str(&room_id).unwrap() },
+            doc! { "$set": {
+                "conference_settings.scheduled_start": bson::DateTime::from_chrono(start),
+                "conference_settings.scheduled_end":
+                    bson::DateTime::from_chrono(start + chrono::Duration::hours(1)),
+            } },
+        )
+        .await
+        .unwrap();
+    room_id
+}
+
+/// A calendar with its folded lines joined back up.
+async fn calendar(resp: reqwest::Response) -> String {
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(
+        resp.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/calendar")
+    );
+    resp.text().await.unwrap().replace("\r\n ", "")
+}
+
+#[tokio::test]
+async fn conferences_download_as_ics() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("icscorp").await;
+    let room_id = scheduled_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Quarterly Planning",
+        24,
+    )
+    .await;
+    let url = format!(
+        "/api/tenant/{}/conference/{}/ics",
+        tenant.tenant_id, room_id
+    );
+
+    // Not a member yet
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.member.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        resp.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .contains(".ics")
+    );
+    let ics = calendar(resp).await;
+    assert!(ics.contains("METHOD:PUBLISH\r\n"));
+    assert!(ics.contains(&format!("UID:{room_id}@roomler\r\n")));
+    assert!(ics.contains("SUMMARY:Quarterly Planning\r\n"));
+    assert!(ics.contains(&format!(":mailto:{}\r\n", tenant.admin.email)));
+    assert!(ics.contains("ORGANIZER;CN="));
+    // Organizers see everyone invited
+    assert!(ics.contains("ATTENDEE;CN="));
+    assert!(ics.contains(&format!(":mailto:{}\r\n", tenant.member.email)));
+
+    // Other members see only themselves besides the organizer
+    let ics = calendar(
+        app.auth_get(&url, &tenant.member.access_token)
+            .send()
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(ics.matches("ATTENDEE;").count(), 1);
+    assert!(ics.contains(&format!(":mailto:{}\r\n", tenant.member.email)));
+
+    // A room without a schedule has nothing to download
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&json!({ "name": "Ad Hoc", "media_settings": { "audio_enabled": true } }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/conference/{}/ics",
+                tenant.tenant_id,
+                room["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn calendar_feed_lists_upcoming_conferences() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("icsfeed").await;
+    let upcoming = scheduled_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Roadmap Review",
+        2,
+    )
+    .await;
+    let past = scheduled_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Last Week",
+        -24 * 7,
+    )
+    .await;
+    let hidden = scheduled_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Not Invited",
+        4,
+    )
+    .await;
+    for room_id in [&upcoming, &past] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let resp = app
+        .auth_post("/api/auth/me/calendar-feed", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let feed: Value = resp.json().await.unwrap();
+    let feed_url = feed["url"].as_str().unwrap().to_string();
+    assert!(
+        feed["webcal_url"]
+            .as_str()
+            .unwrap()
+            .starts_with("webcal://")
+    );
+    let token = feed_url.rsplit('/').next().unwrap().to_string();
+    // Asking again returns the same feed
+    let again: Value = app
+        .auth_post("/api/auth/me/calendar-feed", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(again["url"], feed_url.as_str());
+
+    let feed_path = app.url(&format!("/api/calendar/{token}"));
+    let anon = reqwest::Client::new();
+    let ics = calendar(anon.get(&feed_path).send().await.unwrap()).await;
+    assert!(ics.contains(&format!("UID:{upcoming}@roomler\r\n")));
+    assert!(!ics.contains(&format!("UID:{past}@roomler")));
+    assert!(!ics.contains(&format!("UID:{hidden}@roomler")));
+    assert!(ics.contains(&format!(":mailto:{}\r\n", tenant.member.email)));
+
+    let resp = anon
+        .get(app.url("/api/calendar/not-a-feed"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // Turning the feed off
+    let resp = app
+        .auth_delete("/api/auth/me/calendar-feed", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    let resp = anon.get(&feed_path).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
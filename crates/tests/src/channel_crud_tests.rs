@@ -191,3 +191,212 @@ async fn cross_tenant_room_get_forbidden() {
 
     assert_eq!(resp.status().as_u16(), 403);
 }
+
+#[tokio::test]
+async fn channel_roles_gate_room_and_message_actions() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("chroles").await;
+    let room = &tenant.rooms[0];
+    let room_url = format!("/api/tenant/{}/room/{}", tenant.tenant_id, room.id);
+    let messages_url = format!("{}/message", room_url);
+    let role_url = format!("{}/member/{}/role", room_url, tenant.member.id);
+    let member_token = &tenant.member.access_token;
+
+    app.auth_post(&format!("{}/join", room_url), member_token)
+        .send()
+        .await
+        .unwrap();
+
+    let post = |token: &str, content: &str| {
+        app.auth_post(&messages_url, token)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+    };
+    let rename = |token: &str| {
+        app.auth_put(&room_url, token)
+            .json(&serde_json::json!({ "topic": "moderated" }))
+            .send()
+    };
+
+    // Plain members can post but not moderate
+    let resp = post(member_token, "hello").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let message: Value = resp.json().await.unwrap();
+    let message_id = message["id"].as_str().unwrap().to_string();
+    let resp = rename(member_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(
+            &format!("{}/{}/pin", messages_url, message_id),
+            member_token,
+        )
+        .json(&serde_json::json!({ "pinned": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Promoted to moderator
+    let resp = app
+        .auth_put(&role_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "role": "moderator" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["channel_role"], "moderator");
+
+    let resp = rename(member_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_put(
+            &format!("{}/{}/pin", messages_url, message_id),
+            member_token,
+        )
+        .json(&serde_json::json!({ "pinned": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    // ...but deleting the room takes an owner, and the creator can't be demoted
+    let resp = app
+        .auth_delete(&room_url, member_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(
+            &format!("{}/member/{}/role", room_url, tenant.admin.id),
+            member_token,
+        )
+        .json(&serde_json::json!({ "role": "guest" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Demoted to guest: read-only, and can't leave to shed the role
+    let resp = app
+        .auth_put(&role_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "role": "guest" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = post(member_token, "still here?").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_get(&messages_url, member_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_post(&format!("{}/leave", room_url), member_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(&format!("{}/member", room_url), &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    let roles: Vec<(String, String)> = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            (
+                m["user_id"].as_str().unwrap().to_string(),
+                m["channel_role"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert!(roles.contains(&(tenant.admin.id.clone(), "owner".to_string())));
+    assert!(roles.contains(&(tenant.member.id.clone(), "guest".to_string())));
+
+    // Revoking restores a plain member
+    let resp = app
+        .auth_delete(&role_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = post(member_token, "back").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn read_only_window_blocks_posting_below_min_role() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("chrow").await;
+    let room = &tenant.rooms[0];
+    let room_url = format!("/api/tenant/{}/room/{}", tenant.tenant_id, room.id);
+    let messages_url = format!("{}/message", room_url);
+    let member_token = &tenant.member.access_token;
+    let admin_token = &tenant.admin.access_token;
+
+    app.auth_post(&format!("{}/join", room_url), member_token)
+        .send()
+        .await
+        .unwrap();
+
+    let post = |token: &str| {
+        app.auth_post(&messages_url, token)
+            .json(&serde_json::json!({ "content": "hello" }))
+            .send()
+    };
+    let set_windows = |windows: Value| {
+        app.auth_put(&room_url, admin_token)
+            .json(&serde_json::json!({ "read_only_windows": windows }))
+            .send()
+    };
+
+    // Every day, all day
+    let resp = set_windows(serde_json::json!([{
+        "weekdays": [1, 2, 3, 4, 5, 6, 7],
+        "start": "00:00",
+        "end": "00:00",
+        "min_role": "moderator",
+        "note": "Announcements only",
+    }]))
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = post(member_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("Announcements only"));
+
+    // The creator owns the room and posts through the window
+    let resp = post(admin_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app.auth_get(&room_url, member_token).send().await.unwrap();
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["read_only_windows"].as_array().unwrap().len(), 1);
+    assert_eq!(json["can_post"], false);
+    assert_eq!(json["active_read_only_window"]["min_role"], "moderator");
+    assert!(json["active_read_only_window"]["ends_at"].is_string());
+
+    let resp = set_windows(serde_json::json!([{
+        "weekdays": [8],
+        "start": "09:00",
+        "end": "17:00",
+    }]))
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Clearing the schedule reopens the room
+    let resp = set_windows(serde_json::json!([])).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = post(member_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
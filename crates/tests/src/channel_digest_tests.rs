@@ -0,0 +1,156 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn channel_digest_is_configured_and_posted_once_a_day() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("digest").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let quiet_room_id = &tenant.rooms[1].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    for token in [admin, member] {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), token)
+            .send()
+            .await
+            .unwrap();
+    }
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tid, room_id);
+    let post = |token: &str, body: Value| app.auth_post(&messages_url, token).json(&body).send();
+
+    let root: Value = post(
+        admin,
+        serde_json::json!({ "content": "Release plan for Friday" }),
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let root_id = root["id"].as_str().unwrap();
+    for reply in ["Sounds good", "I'll prep the notes"] {
+        post(
+            member,
+            serde_json::json!({ "content": reply, "thread_id": root_id }),
+        )
+        .await
+        .unwrap();
+    }
+    let popular: Value = post(member, serde_json::json!({ "content": "Ship it" }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    app.auth_post(
+        &format!(
+            "{}/{}/reaction",
+            messages_url,
+            popular["id"].as_str().unwrap()
+        ),
+        admin,
+    )
+    .json(&serde_json::json!({ "emoji": "\u{1f44d}" }))
+    .send()
+    .await
+    .unwrap();
+
+    let digest_url = format!("/api/tenant/{}/room/{}/digest", tid, room_id);
+    let resp = app.auth_get(&digest_url, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["enabled"], false);
+
+    // Configuring takes the moderator role.
+    let resp = app
+        .auth_put(&digest_url, member)
+        .json(&serde_json::json!({ "hour_utc": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(&digest_url, admin)
+        .json(&serde_json::json!({ "hour_utc": 24 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = app
+        .auth_put(&digest_url, admin)
+        .json(&serde_json::json!({ "hour_utc": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["enabled"], true);
+    assert_eq!(json["hour_utc"], 0);
+    assert_eq!(json["summarize"], false);
+
+    // A channel with nothing new gets no digest.
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/room/{}/digest", tid, quiet_room_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "hour_utc": 0, "summarize": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let now = chrono::Utc::now();
+    let posted = roomler_ai_api::channel_digests::post_due(&state, now)
+        .await
+        .unwrap();
+    assert_eq!(posted, 1);
+    let posted = roomler_ai_api::channel_digests::post_due(&state, now)
+        .await
+        .unwrap();
+    assert_eq!(posted, 0);
+
+    let messages: Value = app
+        .auth_get(&messages_url, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let digest = messages["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| {
+            m["content"]
+                .as_str()
+                .unwrap()
+                .starts_with("**Daily digest**")
+        })
+        .expect("digest posted");
+    let content = digest["content"].as_str().unwrap();
+    assert!(content.contains("4 messages in the last 24 hours"));
+    assert!(content.contains("\"Release plan for Friday\" — 2 replies"));
+    assert!(content.contains("\"Ship it\" — 1 reaction"));
+
+    let json: Value = app
+        .auth_get(&digest_url, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["last_posted_on"], now.format("%Y-%m-%d").to_string());
+
+    let resp = app.auth_delete(&digest_url, admin).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["enabled"], false);
+}
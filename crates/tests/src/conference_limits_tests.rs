@@ -0,0 +1,341 @@
+use crate::fixtures::{
+    seed::{SeededTenant, SeededUser},
+    test_app::TestApp,
+};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+type Ws =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+#[tokio::test]
+async fn conference_is_auto_ended_at_max_duration() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.conference_limits.check_interval_secs = 1;
+        s.conference_limits.warn_before_secs = 0;
+        for plan in [
+            &mut s.conference_limits.free,
+            &mut s.conference_limits.pro,
+            &mut s.conference_limits.business,
+            &mut s.conference_limits.enterprise,
+        ] {
+            plan.max_duration_secs = Some(0);
+            plan.idle_timeout_secs = None;
+        }
+    })
+    .await;
+    let tenant = app.seed_tenant("conflimit").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "Too Long" }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/start",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The sweep runs every second; give it a few ticks.
+    let mut status = Value::Null;
+    for _ in 0..10 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let json: Value = app
+            .auth_get(
+                &format!("/api/tenant/{}/room/{}", tenant.tenant_id, room_id),
+                &tenant.admin.access_token,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        status = json["conference_status"].clone();
+        if status == "ended" {
+            break;
+        }
+    }
+    assert_eq!(status, "ended");
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let items = json["items"].as_array().unwrap();
+    assert!(items.iter().any(|m| {
+        m["content"]
+            .as_str()
+            .unwrap_or("")
+            .contains("ended automatically")
+    }));
+}
+
+/// A call in a fresh room whose Free plan allows one participant.
+async fn capped_call(app: &TestApp, tenant: &SeededTenant, waitlist: bool) -> String {
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "Capped" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/call/settings",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "waitlist_enabled": waitlist }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/start",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    room_id
+}
+
+async fn spawn_capped() -> TestApp {
+    TestApp::spawn_with_settings(|s| s.conference_limits.free.max_participants = Some(1)).await
+}
+
+/// Register a user and add them to the tenant.
+async fn extra_member(app: &TestApp, tenant: &SeededTenant, slug: &str) -> SeededUser {
+    let user = app
+        .register_user(
+            &format!("extra@{}.test", slug),
+            &format!("{}_extra", slug),
+            &format!("{} Extra", slug),
+            "Extra123!",
+            None,
+            None,
+        )
+        .await;
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/member", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "user_id": user.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    user
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    expect_event(&mut ws, "connected").await;
+    ws
+}
+
+async fn send(ws: &mut Ws, msg_type: &str, room_id: &str) {
+    let msg = serde_json::json!({ "type": msg_type, "data": { "room_id": room_id } });
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// Skip messages until one of `msg_type` arrives.
+async fn expect_event(ws: &mut Ws, msg_type: &str) -> Value {
+    let wait = async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value =
+                serde_json::from_str(msg.to_text().unwrap_or("")).unwrap_or_default();
+            if parsed["type"] == msg_type {
+                return parsed;
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .unwrap_or_else(|_| panic!("no {msg_type} received"))
+}
+
+async fn call_join(
+    app: &TestApp,
+    tenant: &SeededTenant,
+    room_id: &str,
+    token: &str,
+) -> reqwest::Response {
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/join",
+            tenant.tenant_id, room_id
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn full_conference_turns_joiners_away_but_not_organizers() {
+    let app = spawn_capped().await;
+    let tenant = app.seed_tenant("capfull").await;
+    let extra = extra_member(&app, &tenant, "capfull").await;
+    let room_id = capped_call(&app, &tenant, false).await;
+
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    send(&mut member_ws, "media:join", &room_id).await;
+    expect_event(&mut member_ws, "media:transport_created").await;
+
+    let resp = call_join(&app, &tenant, &room_id, &extra.access_token).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let mut extra_ws = connect(&app, &extra.access_token).await;
+    send(&mut extra_ws, "media:join", &room_id).await;
+    let error = expect_event(&mut extra_ws, "media:error").await;
+    assert_eq!(error["data"]["message"], "Conference is full");
+
+    let resp = call_join(&app, &tenant, &room_id, &tenant.admin.access_token).await;
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn waiters_are_admitted_when_a_slot_frees() {
+    let app = spawn_capped().await;
+    let tenant = app.seed_tenant("capwait").await;
+    let extra = extra_member(&app, &tenant, "capwait").await;
+    let room_id = capped_call(&app, &tenant, true).await;
+
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    send(&mut member_ws, "media:join", &room_id).await;
+    expect_event(&mut member_ws, "media:transport_created").await;
+
+    let mut extra_ws = connect(&app, &extra.access_token).await;
+    let json: Value = call_join(&app, &tenant, &room_id, &extra.access_token)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["joined"], false);
+    assert_eq!(json["status"], "waitlisted");
+    assert_eq!(json["position"], 1);
+    let waitlisted = expect_event(&mut extra_ws, "conference:waitlisted").await;
+    assert_eq!(waitlisted["data"]["position"], 1);
+
+    let waiting: Vec<Value> = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/waitlist",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(waiting.len(), 1);
+    assert_eq!(waiting[0]["user_id"], extra.id.as_str());
+
+    // Attendees can't see or skip the queue
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/waitlist",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    send(&mut member_ws, "media:leave", &room_id).await;
+    let admitted = expect_event(&mut extra_ws, "conference:admitted").await;
+    assert_eq!(admitted["data"]["room_id"], room_id.as_str());
+
+    let json: Value = call_join(&app, &tenant, &room_id, &extra.access_token)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["joined"], true);
+}
+
+#[tokio::test]
+async fn organizer_can_admit_past_the_cap() {
+    let app = spawn_capped().await;
+    let tenant = app.seed_tenant("capadmit").await;
+    let extra = extra_member(&app, &tenant, "capadmit").await;
+    let room_id = capped_call(&app, &tenant, true).await;
+
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    send(&mut member_ws, "media:join", &room_id).await;
+    expect_event(&mut member_ws, "media:transport_created").await;
+
+    let mut extra_ws = connect(&app, &extra.access_token).await;
+    send(&mut extra_ws, "media:join", &room_id).await;
+    expect_event(&mut extra_ws, "conference:waitlisted").await;
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/waitlist/{}/admit",
+                tenant.tenant_id, room_id, extra.id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    expect_event(&mut extra_ws, "conference:admitted").await;
+
+    send(&mut extra_ws, "media:join", &room_id).await;
+    expect_event(&mut extra_ws, "media:transport_created").await;
+}
@@ -0,0 +1,295 @@
+use crate::fixtures::{
+    seed::{SeededTenant, SeededUser},
+    test_app::TestApp,
+};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+type Ws =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// A call in a fresh room with its lobby on, organized by the admin.
+async fn lobby_call(app: &TestApp, tenant: &SeededTenant) -> String {
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "Lobby" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    set_lobby(app, tenant, &room_id, true).await;
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/start",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    room_id
+}
+
+async fn set_lobby(app: &TestApp, tenant: &SeededTenant, room_id: &str, enabled: bool) {
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/call/settings",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "lobby_enabled": enabled }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["lobby_enabled"], enabled);
+}
+
+/// Register a user and add them to the tenant.
+async fn extra_member(app: &TestApp, tenant: &SeededTenant, slug: &str) -> SeededUser {
+    let user = app
+        .register_user(
+            &format!("extra@{}.test", slug),
+            &format!("{}_extra", slug),
+            &format!("{} Extra", slug),
+            "Extra123!",
+            None,
+            None,
+        )
+        .await;
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/member", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "user_id": user.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    user
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    expect_event(&mut ws, "connected").await;
+    ws
+}
+
+async fn send(ws: &mut Ws, msg_type: &str, room_id: &str) {
+    let msg = serde_json::json!({ "type": msg_type, "data": { "room_id": room_id } });
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// Skip messages until one of `msg_types` arrives.
+async fn expect_any(ws: &mut Ws, msg_types: &[&str]) -> Value {
+    let wait = async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value =
+                serde_json::from_str(msg.to_text().unwrap_or("")).unwrap_or_default();
+            if msg_types.iter().any(|t| parsed["type"] == *t) {
+                return parsed;
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .unwrap_or_else(|_| panic!("none of {msg_types:?} received"))
+}
+
+async fn expect_event(ws: &mut Ws, msg_type: &str) -> Value {
+    expect_any(ws, &[msg_type]).await
+}
+
+async fn decide(
+    app: &TestApp,
+    tenant: &SeededTenant,
+    room_id: &str,
+    user_id: &str,
+    decision: &str,
+    token: &str,
+) -> u16 {
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/lobby/{}/{}",
+            tenant.tenant_id, room_id, user_id, decision
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap()
+    .status()
+    .as_u16()
+}
+
+#[tokio::test]
+async fn lobby_holds_joiners_until_an_organizer_admits() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("lobbyadmit").await;
+    let room_id = lobby_call(&app, &tenant).await;
+    let member_id = tenant.member.id.as_str();
+
+    let mut admin_ws = connect(&app, &tenant.admin.access_token).await;
+    send(&mut admin_ws, "media:join", &room_id).await;
+    expect_event(&mut admin_ws, "media:transport_created").await;
+
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    send(&mut member_ws, "media:join", &room_id).await;
+    let waiting = expect_any(
+        &mut member_ws,
+        &["media:lobby_waiting", "media:transport_created"],
+    )
+    .await;
+    assert_eq!(waiting["type"], "media:lobby_waiting");
+
+    let request = expect_event(&mut admin_ws, "media:join_request").await;
+    assert_eq!(request["data"]["room_id"], room_id.as_str());
+    assert_eq!(request["data"]["user_id"], member_id);
+
+    let lobby_url = format!(
+        "/api/tenant/{}/room/{}/call/lobby",
+        tenant.tenant_id, room_id
+    );
+    let pending: Vec<Value> = app
+        .auth_get(&lobby_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0]["user_id"], member_id);
+
+    // Attendees can't see the lobby or let themselves in
+    let resp = app
+        .auth_get(&lobby_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let status = decide(
+        &app,
+        &tenant,
+        &room_id,
+        member_id,
+        "admit",
+        &tenant.member.access_token,
+    )
+    .await;
+    assert_eq!(status, 403);
+
+    let status = decide(
+        &app,
+        &tenant,
+        &room_id,
+        member_id,
+        "admit",
+        &tenant.admin.access_token,
+    )
+    .await;
+    assert_eq!(status, 200);
+    let admitted = expect_event(&mut member_ws, "media:admitted").await;
+    assert_eq!(admitted["data"]["room_id"], room_id.as_str());
+
+    send(&mut member_ws, "media:join", &room_id).await;
+    expect_event(&mut member_ws, "media:transport_created").await;
+
+    // Nobody is waiting any more
+    let status = decide(
+        &app,
+        &tenant,
+        &room_id,
+        member_id,
+        "admit",
+        &tenant.admin.access_token,
+    )
+    .await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn denied_joiners_are_turned_away_and_opening_the_lobby_admits_the_rest() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("lobbydeny").await;
+    let extra = extra_member(&app, &tenant, "lobbydeny").await;
+    let room_id = lobby_call(&app, &tenant).await;
+
+    // Joining over REST waits in the lobby too
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    let json: Value = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["joined"], false);
+    assert_eq!(json["status"], "lobby");
+
+    let mut extra_ws = connect(&app, &extra.access_token).await;
+    send(&mut extra_ws, "media:join", &room_id).await;
+    expect_event(&mut extra_ws, "media:lobby_waiting").await;
+
+    let status = decide(
+        &app,
+        &tenant,
+        &room_id,
+        &tenant.member.id,
+        "deny",
+        &tenant.admin.access_token,
+    )
+    .await;
+    assert_eq!(status, 200);
+    let denied = expect_event(&mut member_ws, "media:join_denied").await;
+    assert_eq!(denied["data"]["room_id"], room_id.as_str());
+
+    set_lobby(&app, &tenant, &room_id, false).await;
+    expect_event(&mut extra_ws, "media:admitted").await;
+    send(&mut extra_ws, "media:join", &room_id).await;
+    expect_event(&mut extra_ws, "media:transport_created").await;
+
+    let pending: Vec<Value> = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/lobby",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(pending.is_empty());
+}
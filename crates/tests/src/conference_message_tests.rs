@@ -293,3 +293,413 @@ async fn cannot_chat_in_ended_call() {
 
     assert_eq!(resp.status().as_u16(), 200);
 }
+
+/// Minimal OpenAI-compatible ASR server that transcribes everything the same.
+async fn spawn_fake_asr() -> String {
+    use axum::{Json, Router, routing::post};
+
+    let router = Router::new().route(
+        "/v1/audio/transcriptions",
+        post(|| async {
+            Json(serde_json::json!({ "text": " Meet you in the lobby. ", "language": "en" }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn voice_note_form(content_type: &str) -> reqwest::multipart::Form {
+    let audio = reqwest::multipart::Part::bytes(vec![0x1a, 0x45, 0xdf, 0xa3, 0, 1, 2, 3])
+        .file_name("note.webm")
+        .mime_str(content_type)
+        .unwrap();
+    reqwest::multipart::Form::new()
+        .part("file", audio)
+        .text("duration_ms", "1500")
+}
+
+#[tokio::test]
+async fn voice_note_is_transcribed_and_listed() {
+    let asr_url = spawn_fake_asr().await;
+    let app = TestApp::spawn_with_settings(|s| s.asr.url = asr_url).await;
+    let tenant = app.seed_tenant("confvoice1").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Voice Notes",
+    )
+    .await;
+    let voice_url = format!(
+        "/api/tenant/{}/room/{}/call/message/voice",
+        tenant.tenant_id, room_id
+    );
+
+    let resp = app
+        .auth_post(&voice_url, &tenant.admin.access_token)
+        .multipart(voice_note_form("audio/webm"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    let note = &json["voice_note"];
+    assert_eq!(note["transcript"], "Meet you in the lobby.");
+    assert_eq!(note["transcript_language"], "en");
+    assert_eq!(note["transcript_status"], "completed");
+    assert_eq!(note["duration_ms"], 1500);
+    assert_eq!(note["content_type"], "audio/webm");
+
+    // The audio is downloadable like any room file
+    let audio_url = note["url"].as_str().unwrap();
+    let resp = app
+        .auth_get(audio_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.bytes().await.unwrap().len(), 8);
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/message",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0]["voice_note"]["transcript"],
+        "Meet you in the lobby."
+    );
+
+    // Only audio is accepted
+    let resp = app
+        .auth_post(&voice_url, &tenant.admin.access_token)
+        .multipart(voice_note_form("text/plain"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn voice_note_without_asr_is_delivered_untranscribed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("confvoice2").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Voice Notes",
+    )
+    .await;
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/message/voice",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .multipart(voice_note_form("audio/ogg"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["voice_note"]["transcript_status"], "unavailable");
+    assert!(json["voice_note"]["transcript"].is_null());
+    assert!(!json["voice_note"]["url"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn conference_chat_retention_discards_and_purges() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("confmsgret").await;
+    let token = &tenant.admin.access_token;
+    let settings_url = format!("/api/tenant/{}/conference-chat-retention", tenant.tenant_id);
+
+    // Only MANAGE_TENANT can change retention.
+    let resp = app
+        .auth_put(&settings_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "discard_at_call_end": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(&settings_url, token)
+        .json(&serde_json::json!({ "retention_days": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = app
+        .auth_put(&settings_url, token)
+        .json(&serde_json::json!({ "retention_days": 7, "discard_at_call_end": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let room_id =
+        create_room_and_start_call(&app, &tenant.tenant_id, token, "Retention Test").await;
+    let call_url = |path: &str| {
+        format!(
+            "/api/tenant/{}/room/{}/call/{}",
+            tenant.tenant_id, room_id, path
+        )
+    };
+    async fn chat_total(app: &TestApp, url: &str, token: &str) -> u64 {
+        let json: Value = app
+            .auth_get(url, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        json["total"].as_u64().unwrap()
+    }
+    let messages_url = call_url("message");
+
+    // Without a keep, the call's chat goes when the call ends.
+    app.auth_post(&call_url("message"), token)
+        .json(&serde_json::json!({ "content": "gone soon" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(chat_total(&app, &messages_url, token).await, 1);
+    app.auth_post(&call_url("end"), token).send().await.unwrap();
+    assert_eq!(chat_total(&app, &messages_url, token).await, 0);
+
+    // Only organizers may keep it.
+    let resp = app
+        .auth_put(&call_url("message/keep"), &tenant.member.access_token)
+        .json(&serde_json::json!({ "keep": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(&call_url("message/keep"), token)
+        .json(&serde_json::json!({ "keep": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    app.auth_post(&call_url("start"), token)
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&call_url("message"), token)
+        .json(&serde_json::json!({ "content": "kept" }))
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&call_url("end"), token).send().await.unwrap();
+    assert_eq!(chat_total(&app, &messages_url, token).await, 1);
+
+    // Kept chat still expires after the retention period.
+    let now_ms = bson::DateTime::now().timestamp_millis();
+    app.db
+        .collection::<bson::Document>("call_chat_messages")
+        .insert_one(bson::doc! {
+            "tenant_id": bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap(),
+            "room_id": bson::oid::ObjectId::parse_str(&room_id).unwrap(),
+            "author_id": bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap(),
+            "display_name": "Admin",
+            "content": "last month",
+            "created_at": bson::DateTime::from_millis(now_ms - 30 * 86_400_000),
+        })
+        .await
+        .unwrap();
+    assert_eq!(chat_total(&app, &messages_url, token).await, 2);
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let purged = roomler_ai_api::conference_chat::purge_expired(&state)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    assert_eq!(chat_total(&app, &messages_url, token).await, 1);
+}
+
+#[tokio::test]
+async fn room_retention_overrides_exempt_or_shorten_purges() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("confmsgovr").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/conference-chat-retention", tid),
+            token,
+        )
+        .json(&serde_json::json!({ "retention_days": 30 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let mut rooms = Vec::new();
+    for name in ["legal", "hr", "general"] {
+        let room: Value = app
+            .auth_post(&format!("/api/tenant/{}/room", tid), token)
+            .json(&serde_json::json!({ "name": name }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        rooms.push(room["id"].as_str().unwrap().to_string());
+    }
+    let (legal, hr, general) = (&rooms[0], &rooms[1], &rooms[2]);
+    let retention_url =
+        |rid: &str| format!("/api/tenant/{}/room/{}/conference-chat-retention", tid, rid);
+
+    // Only MANAGE_TENANT can set overrides, and they must mean something.
+    let resp = app
+        .auth_put(&retention_url(legal), &tenant.member.access_token)
+        .json(&serde_json::json!({ "exempt": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    for body in [
+        serde_json::json!({}),
+        serde_json::json!({ "exempt": true, "retention_days": 3 }),
+        serde_json::json!({ "retention_days": 30 }),
+        serde_json::json!({ "retention_days": 0 }),
+    ] {
+        let resp = app
+            .auth_put(&retention_url(hr), token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{body}");
+    }
+
+    let resp = app
+        .auth_put(&retention_url(legal), token)
+        .json(&serde_json::json!({ "exempt": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = app
+        .auth_put(&retention_url(hr), token)
+        .json(&serde_json::json!({ "retention_days": 3 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["override"]["retention_days"], 3);
+    assert_eq!(json["tenant_retention_days"], 30);
+    assert_eq!(json["effective_retention_days"], 3);
+
+    // A 10-day-old and a 40-day-old message in every room.
+    let chat = app.db.collection::<bson::Document>("call_chat_messages");
+    let now_ms = bson::DateTime::now().timestamp_millis();
+    for rid in &rooms {
+        for days in [10, 40] {
+            chat.insert_one(bson::doc! {
+                "tenant_id": bson::oid::ObjectId::parse_str(tid).unwrap(),
+                "room_id": bson::oid::ObjectId::parse_str(rid).unwrap(),
+                "author_id": bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap(),
+                "display_name": "Admin",
+                "content": format!("{days} days ago"),
+                "created_at": bson::DateTime::from_millis(now_ms - days * 86_400_000),
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let purged = roomler_ai_api::conference_chat::purge_expired(&state)
+        .await
+        .unwrap();
+    assert_eq!(purged, 3);
+    let left = |rid: &str| {
+        chat.count_documents(bson::doc! { "room_id": bson::oid::ObjectId::parse_str(rid).unwrap() })
+    };
+    assert_eq!(left(legal).await.unwrap(), 2);
+    assert_eq!(left(hr).await.unwrap(), 0);
+    assert_eq!(left(general).await.unwrap(), 1);
+
+    // Each purge is audited against its room.
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?action=room.chat_purge", tid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["total"], 2);
+    let entry = audit["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["target_id"] == hr.as_str())
+        .unwrap();
+    assert_eq!(entry["actor_type"], "system");
+    assert_eq!(entry["changes"][0]["field"], "purged");
+    assert_eq!(entry["changes"][0]["new_value"], 2);
+    assert_eq!(entry["changes"][1]["new_value"], 3);
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?action=room.retention_update", tid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["total"], 2);
+
+    // Clearing the override puts the room back under the tenant's period.
+    let json: Value = app
+        .auth_delete(&retention_url(legal), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(json["override"].is_null());
+    assert_eq!(json["effective_retention_days"], 30);
+    let purged = roomler_ai_api::conference_chat::purge_expired(&state)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    assert_eq!(left(legal).await.unwrap(), 1);
+}
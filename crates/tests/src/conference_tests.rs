@@ -3,7 +3,8 @@ use futures::{SinkExt, StreamExt};
 use serde_json::Value;
 use tokio_tungstenite::tungstenite::Message;
 
-/// Read the next WS message, skipping room:call_* notification messages.
+/// Read the next WS message, skipping room:call_* notifications and
+/// presence updates.
 async fn next_media_msg(
     ws: &mut tokio_tungstenite::WebSocketStream<
         tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
@@ -13,7 +14,7 @@ async fn next_media_msg(
         let msg = ws.next().await.unwrap().unwrap();
         let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
         let msg_type = parsed["type"].as_str().unwrap_or("");
-        if !msg_type.starts_with("room:") {
+        if !msg_type.starts_with("room:") && msg_type != "presence:update" {
             return parsed;
         }
     }
@@ -1665,10 +1666,1512 @@ async fn ws_disconnect_notifies_peers_with_peer_left() {
 
     // User 1 should receive peer_left
     tokio::time::sleep(std::time::Duration::from_millis(300)).await;
-    let msg = ws1.next().await.unwrap().unwrap();
-    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    let parsed = next_media_msg(&mut ws1).await;
     assert_eq!(parsed["type"], "media:peer_left");
     assert_eq!(parsed["data"]["user_id"], tenant.member.id);
 
     ws1.close(None).await.ok();
 }
+
+#[tokio::test]
+async fn restart_reconciles_in_progress_conferences() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("confreconcile").await;
+    let token = &tenant.admin.access_token;
+
+    let recent = create_room_and_start_call(&app, &tenant.tenant_id, token, "Recent").await;
+    let stale = create_room_and_start_call(&app, &tenant.tenant_id, token, "Stale").await;
+    for room_id in [&recent, &stale] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    // Push the stale conference's start well outside the re-create window.
+    let day_ago =
+        bson::DateTime::from_millis(bson::DateTime::now().timestamp_millis() - 86_400_000);
+    app.db
+        .collection::<bson::Document>("rooms")
+        .update_one(
+            bson::doc! { "_id": bson::oid::ObjectId::parse_str(&stale).unwrap() },
+            bson::doc! { "$set": { "actual_start_time": day_ago } },
+        )
+        .await
+        .unwrap();
+
+    // A fresh AppState over the same database stands in for a restarted server.
+    let restarted = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let recent_oid = bson::oid::ObjectId::parse_str(&recent).unwrap();
+    assert!(restarted.room_manager.has_room(&recent_oid));
+
+    let room = |room_id: String| {
+        let app = &app;
+        let url = format!("/api/tenant/{}/room/{}", tenant.tenant_id, room_id);
+        async move {
+            app.auth_get(&url, token)
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        }
+    };
+    let json = room(recent.clone()).await;
+    assert_eq!(json["conference_status"], "in_progress");
+    assert_eq!(json["participant_count"], 0);
+    let json = room(stale.clone()).await;
+    assert_eq!(json["conference_status"], "ended");
+    assert_eq!(json["participant_count"], 0);
+
+    // Dangling sessions were closed, so nobody is listed as a participant.
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/participant",
+                tenant.tenant_id, recent
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let parts: Vec<Value> = resp.json().await.unwrap();
+    assert!(parts.is_empty());
+}
+
+#[tokio::test]
+async fn join_info_is_public_by_meeting_code() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("joininfo").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "All Hands",
+            "media_settings": { "audio_enabled": true, "video_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let code = room["meeting_code"].as_str().unwrap();
+
+    // No auth: a fresh client without the fixture's cookies.
+    let anon = reqwest::Client::new();
+    let url = app.url(&format!("/api/join/{}/info", code));
+
+    let resp = anon.get(&url).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["meeting_code"], code);
+    assert_eq!(json["subject"], "All Hands");
+    assert_eq!(json["status"], "not_started");
+    assert_eq!(json["passcode_required"], false);
+    assert_eq!(json["tenant"]["slug"], tenant.tenant_slug.as_str());
+
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/start",
+            tenant.tenant_id, room_id
+        ),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let json: Value = anon.get(&url).send().await.unwrap().json().await.unwrap();
+    assert_eq!(json["status"], "in_progress");
+
+    let resp = anon
+        .get(app.url("/api/join/000-000-000/info"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn prejoin_payload_depends_on_who_asks() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.turn.url = Some("turn:turn.example.com:3478".to_string());
+        s.turn.shared_secret = Some("secret".to_string());
+    })
+    .await;
+    let tenant = app.seed_tenant("prejoin").await;
+
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "Standup",
+            "media_settings": { "audio_enabled": true, "video_enabled": true, "screen_share_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let code = room["meeting_code"].as_str().unwrap();
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/call/settings",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "lobby_enabled": true,
+            "mute_on_entry": true,
+            "attendee_screen_share": false,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let path = format!("/api/join/{}/prejoin", code);
+
+    // Not signed in: the meeting, but nothing to join with.
+    let anon = reqwest::Client::new();
+    let json: Value = anon
+        .get(app.url(&path))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["meeting_code"], code);
+    assert_eq!(json["subject"], "Standup");
+    assert_eq!(json["join_as"], "guest");
+    assert_eq!(json["can_join"], false);
+    assert_eq!(json["guest_link_enabled"], false);
+    assert!(json["room_id"].is_null());
+    assert_eq!(json["ice_servers"], serde_json::json!([]));
+    assert_eq!(json["display_name"]["required"], true);
+    assert_eq!(json["waiting_room"]["lobby_enabled"], true);
+    assert_eq!(json["waiting_room"]["participants"], 0);
+
+    let json: Value = app
+        .auth_get(&path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "member");
+    assert_eq!(json["tenant_id"], tenant.tenant_id.as_str());
+    assert_eq!(json["room_id"], room_id);
+    assert_eq!(json["display_name"]["required"], false);
+    assert_eq!(json["display_name"]["suggested"], "prejoin Member");
+    assert!(
+        json["ice_servers"][0]["urls"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("turn:turn.example.com:3478"))
+    );
+    let caps = &json["capabilities"];
+    assert_eq!(caps["audio"], true);
+    assert_eq!(caps["screen_share"], false);
+    assert_eq!(caps["start_muted"], true);
+    assert_eq!(caps["can_unmute"], true);
+    assert_eq!(json["waiting_room"]["will_wait_in_lobby"], true);
+
+    // The in-call controls don't bind the organizer.
+    let json: Value = app
+        .auth_get(&path, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "organizer");
+    assert_eq!(json["capabilities"]["screen_share"], true);
+    assert_eq!(json["capabilities"]["start_muted"], false);
+    assert_eq!(json["waiting_room"]["will_wait_in_lobby"], false);
+
+    // An account outside the tenant can't join.
+    let outsider = app
+        .register_user(
+            "outsider@prejoin.test",
+            "prejoin_outsider",
+            "Outsider",
+            "Outsider123!",
+            None,
+            None,
+        )
+        .await;
+    let json: Value = app
+        .auth_get(&path, &outsider.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "signed_in");
+    assert_eq!(json["can_join"], false);
+    assert!(json["tenant_id"].is_null());
+    assert_eq!(json["ice_servers"], serde_json::json!([]));
+
+    // With a guest link, its key lets anyone else in as a guest.
+    let link: Value = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/guest-link",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let key = link["key"].as_str().unwrap();
+    let json: Value = anon
+        .get(app.url(&path))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["guest_link_enabled"], true);
+    assert_eq!(json["can_join"], false);
+    assert!(json["guest_join_url"].is_null());
+    assert_eq!(json["ice_servers"], serde_json::json!([]));
+
+    let json: Value = anon
+        .get(app.url(&format!("{path}?guest={key}")))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "guest");
+    assert_eq!(json["can_join"], true);
+    assert_eq!(
+        json["guest_join_url"],
+        format!("/api/guest/conference/{code}/join")
+    );
+    assert!(json["room_id"].is_null());
+    assert!(!json["ice_servers"].as_array().unwrap().is_empty());
+
+    let json: Value = app
+        .auth_get(&format!("{path}?guest={key}"), &outsider.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "signed_in");
+    assert_eq!(json["can_join"], true);
+    assert_eq!(json["display_name"]["required"], true);
+    assert_eq!(json["display_name"]["suggested"], "Outsider");
+
+    let json: Value = anon
+        .get(app.url(&format!("{path}?guest=wrong")))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["can_join"], false);
+    assert_eq!(json["ice_servers"], serde_json::json!([]));
+
+    let resp = anon
+        .get(app.url("/api/join/000-000-000/prejoin"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn meeting_code_resolves_to_the_conference() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("resolve").await;
+
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "Quarterly Review",
+            "media_settings": { "audio_enabled": true, "video_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let code = room["meeting_code"].as_str().unwrap();
+    app.db
+        .collection::<bson::Document>("rooms")
+        .update_one(
+            bson::doc! { "_id": bson::oid::ObjectId::parse_str(room_id).unwrap() },
+            bson::doc! { "$set": { "conference_settings": {
+                "passcode": "4321",
+                "lobby_enabled": true,
+            } } },
+        )
+        .await
+        .unwrap();
+    let path = format!("/api/conference/code/{}", code);
+
+    // Members need no passcode
+    let resp = app
+        .auth_get(&path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["tenant_id"], tenant.tenant_id.as_str());
+    assert_eq!(json["tenant_slug"], "resolve");
+    assert_eq!(json["room_id"], room_id);
+    assert_eq!(json["join_url"], format!("/join/{}", code));
+    assert_eq!(json["join_as"], "member");
+    assert_eq!(json["waiting_room"]["will_wait_in_lobby"], true);
+
+    let json: Value = app
+        .auth_get(&path, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["join_as"], "organizer");
+    assert_eq!(json["waiting_room"]["will_wait_in_lobby"], false);
+
+    // Everyone else does
+    let anon = reqwest::Client::new();
+    for query in ["", "?passcode=0000"] {
+        let resp = anon
+            .get(app.url(&format!("{path}{query}")))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 403);
+    }
+    let resp = anon
+        .get(app.url(&format!("{path}?passcode=4321")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["join_as"], "guest");
+    assert_eq!(json["room_id"], room_id);
+
+    let resp = anon
+        .get(app.url("/api/conference/code/000-000-000"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn joining_a_call_updates_rich_presence() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("richpresence").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Presence",
+    )
+    .await;
+
+    // The member stays outside the call and watches presence.
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws_member, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    ws_member.next().await; // connected
+
+    let (mut ws_admin, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+
+    let mut update = Value::Null;
+    for _ in 0..5 {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_member.next())
+            .await
+            .expect("member should receive presence:update")
+            .unwrap()
+            .unwrap();
+        let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        // Status changes (the admin coming online) carry `presence`.
+        if parsed["type"] == "presence:update" && parsed["data"].get("presence").is_none() {
+            update = parsed;
+            break;
+        }
+    }
+    assert_eq!(update["data"]["user_id"], tenant.admin.id);
+    assert_eq!(update["data"]["activity"]["in_call"], true);
+    assert_eq!(update["data"]["activity"]["room_id"], room_id);
+    assert_eq!(update["data"]["activity"]["screen_sharing"], false);
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/member", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let items = json["items"].as_array().unwrap();
+    let admin = items
+        .iter()
+        .find(|m| m["user_id"] == tenant.admin.id)
+        .unwrap();
+    assert_eq!(admin["activity"]["in_call"], true);
+    let member = items
+        .iter()
+        .find(|m| m["user_id"] == tenant.member.id)
+        .unwrap();
+    assert_eq!(member["activity"]["in_call"], false);
+
+    ws_admin.close(None).await.ok();
+    ws_member.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn caption_track_selection_is_limited_to_offered_tracks() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("captions1").await;
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant.tenant_id), token)
+        .json(&serde_json::json!({
+            "name": "Captions",
+            "media_settings": { "caption_languages": ["de"] },
+        }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/start",
+            tenant.tenant_id, room_id
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let (mut ws, _) = ws_join_media(&app.addr, token, &room_id).await;
+
+    async fn select(
+        ws: &mut tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+        room_id: &str,
+        track: &str,
+    ) -> Value {
+        ws.send(Message::Text(
+            serde_json::to_string(&serde_json::json!({
+                "type": "media:caption_track",
+                "data": { "room_id": room_id, "track": track }
+            }))
+            .unwrap()
+            .into(),
+        ))
+        .await
+        .unwrap();
+        loop {
+            let parsed = next_media_msg(ws).await;
+            let msg_type = parsed["type"].as_str().unwrap_or("");
+            if msg_type.starts_with("media:caption_track") || msg_type == "media:error" {
+                return parsed;
+            }
+        }
+    }
+
+    let selected = select(&mut ws, &room_id, "DE").await;
+    assert_eq!(selected["type"], "media:caption_track_selected");
+    assert_eq!(selected["data"]["track"], "de");
+    assert_eq!(
+        selected["data"]["available_tracks"],
+        serde_json::json!(["original", "de"])
+    );
+
+    let rejected = select(&mut ws, &room_id, "fr").await;
+    assert_eq!(rejected["type"], "media:error");
+
+    let selected = select(&mut ws, &room_id, "original").await;
+    assert_eq!(selected["data"]["track"], "original");
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn private_captions_are_opt_in_per_connection() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("captions2").await;
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant.tenant_id), token)
+        .json(&serde_json::json!({
+            "name": "Private Captions",
+            "media_settings": { "private_captions": true },
+        }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/start",
+            tenant.tenant_id, room_id
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let (mut ws, _) = ws_join_media(&app.addr, token, &room_id).await;
+
+    async fn select(
+        ws: &mut tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+        room_id: &str,
+        track: Value,
+    ) -> Value {
+        ws.send(Message::Text(
+            serde_json::to_string(&serde_json::json!({
+                "type": "media:caption_track",
+                "data": { "room_id": room_id, "track": track }
+            }))
+            .unwrap()
+            .into(),
+        ))
+        .await
+        .unwrap();
+        loop {
+            let parsed = next_media_msg(ws).await;
+            let msg_type = parsed["type"].as_str().unwrap_or("");
+            if msg_type.starts_with("media:caption_track") || msg_type == "media:error" {
+                return parsed;
+            }
+        }
+    }
+
+    // Opt in, then back out.
+    let selected = select(&mut ws, &room_id, serde_json::json!("original")).await;
+    assert_eq!(selected["type"], "media:caption_track_selected");
+    assert_eq!(selected["data"]["track"], "original");
+    let selected = select(&mut ws, &room_id, Value::Null).await;
+    assert_eq!(selected["type"], "media:caption_track_selected");
+    assert!(selected["data"]["track"].is_null());
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn conference_events_feed_lists_call_timeline_in_order() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("confevents").await;
+    let token = &tenant.admin.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, token, "Events").await;
+
+    let (mut ws, _) = ws_join_media(&app.addr, token, &room_id).await;
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({
+            "type": "media:transcript_toggle",
+            "data": { "room_id": room_id, "enabled": true }
+        }))
+        .unwrap()
+        .into(),
+    ))
+    .await
+    .unwrap();
+    loop {
+        let parsed = next_media_msg(&mut ws).await;
+        if parsed["type"] == "media:transcript_status" {
+            assert_eq!(parsed["data"]["enabled"], true);
+            break;
+        }
+    }
+    ws.close(None).await.ok();
+    // Disconnect cleanup runs after the socket closes.
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    let url = format!(
+        "/api/tenant/{}/room/{}/call/event",
+        tenant.tenant_id, room_id
+    );
+    let json: Value = app
+        .auth_get(&url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let types: Vec<&str> = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            "call_started",
+            "participant_joined",
+            "transcript_toggled",
+            "participant_left"
+        ]
+    );
+    assert_eq!(json["items"][1]["user_id"], tenant.admin.id);
+    assert_eq!(json["items"][2]["data"]["enabled"], true);
+    assert_eq!(json["items"][3]["data"]["reason"], "disconnected");
+    assert!(json["next_after"].is_null());
+
+    // Paging resumes after the given event
+    let first_id = json["items"][0]["id"].as_str().unwrap();
+    let json: Value = app
+        .auth_get(&format!("{}?after={}&limit=1", url, first_id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+    assert_eq!(json["items"][0]["type"], "participant_joined");
+    assert_eq!(json["next_after"], json["items"][0]["id"]);
+}
+
+#[tokio::test]
+async fn live_transcript_is_persisted_for_the_current_call() {
+    use roomler_ai_services::media::transcript_feed::{TranscriptEvent, TranscriptScope};
+
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("transcript").await;
+    let room = &tenant.rooms[0];
+    let room_oid = bson::oid::ObjectId::parse_str(&room.id).unwrap();
+    let admin_oid = bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap();
+    let token = &tenant.admin.access_token;
+    let url = format!(
+        "/api/tenant/{}/room/{}/call/transcript",
+        tenant.tenant_id, room.id
+    );
+
+    // A segment from an earlier call, before the current one started.
+    let now_ms = bson::DateTime::now().timestamp_millis();
+    app.db
+        .collection::<bson::Document>("transcript_segments")
+        .insert_one(bson::doc! {
+            "tenant_id": bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap(),
+            "room_id": room_oid,
+            "track": "original",
+            "user_id": admin_oid,
+            "speaker_name": "Admin",
+            "text": "last week",
+            "language": "en",
+            "confidence": bson::Bson::Null,
+            "start_time": 0.0,
+            "end_time": 1.0,
+            "created_at": bson::DateTime::from_millis(now_ms - 86_400_000),
+        })
+        .await
+        .unwrap();
+    app.db
+        .collection::<bson::Document>("rooms")
+        .update_one(
+            bson::doc! { "_id": room_oid },
+            bson::doc! { "$set": { "actual_start_time": bson::DateTime::from_millis(now_ms - 60_000) } },
+        )
+        .await
+        .unwrap();
+
+    // The feed lives in AppState; a second one over the same database
+    // stands in for the ASR pipeline of the serving instance.
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    // Interim results are delivered live but only final segments are kept.
+    let segments = [("hel", false), ("hello", true), ("everyone", true)];
+    for (i, (text, is_final)) in segments.into_iter().enumerate() {
+        state.transcript_feed.publish(TranscriptEvent {
+            room_id: room_oid,
+            track: "original".to_string(),
+            user_id: admin_oid,
+            speaker_name: "Admin".to_string(),
+            text: text.to_string(),
+            language: Some("en".to_string()),
+            confidence: Some(0.9),
+            start_time: i as f64,
+            end_time: i as f64 + 0.8,
+            is_final,
+            quality: None,
+            model: None,
+            scope: TranscriptScope::Room,
+        });
+    }
+
+    let mut json = Value::Null;
+    for _ in 0..50 {
+        json = app
+            .auth_get(&url, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if json["items"]
+            .as_array()
+            .is_some_and(|items| items.len() == 2)
+        {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(json["track"], "original");
+    assert_eq!(items[0]["text"], "hello");
+    assert_eq!(items[1]["text"], "everyone");
+    assert_eq!(items[1]["speaker_name"], "Admin");
+
+    let resp = app
+        .auth_get(&format!("{}?track=xx", url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+/// Switching the ASR model mid-call marks the switch point in
+/// media:transcript_status and lets the previous model drain.
+#[tokio::test]
+async fn transcript_model_switch_marks_handover() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("asrswitch").await;
+    let token = &tenant.admin.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, token, "Switch").await;
+    let (mut ws, _) = ws_join_media(&app.addr, token, &room_id).await;
+
+    let toggle = serde_json::json!({ "room_id": room_id, "enabled": true, "model": "small" });
+    ws_send(&mut ws, "media:transcript_toggle", toggle).await;
+    let status = next_of_type(&mut ws, "media:transcript_status").await;
+    assert_eq!(status["data"]["model"], "small");
+    assert!(status["data"]["previous_model"].is_null());
+
+    let toggle = serde_json::json!({ "room_id": room_id, "enabled": true, "model": "large" });
+    ws_send(&mut ws, "media:transcript_toggle", toggle).await;
+    let status = next_of_type(&mut ws, "media:transcript_status").await;
+    assert_eq!(status["data"]["model"], "large");
+    assert_eq!(status["data"]["previous_model"], "small");
+    assert_eq!(status["data"]["drain_ms"], 15_000);
+    assert!(status["data"]["switched_at"].is_string());
+
+    // Stopping drains the model in use as well.
+    let toggle = serde_json::json!({ "room_id": room_id, "enabled": false });
+    ws_send(&mut ws, "media:transcript_toggle", toggle).await;
+    let status = next_of_type(&mut ws, "media:transcript_status").await;
+    assert_eq!(status["data"]["enabled"], false);
+    assert_eq!(status["data"]["previous_model"], "large");
+
+    ws.close(None).await.ok();
+}
+
+/// media:transcribe_me turns on transcription of the sender's microphone
+/// alone, announced to the call with `scope: self`.
+#[tokio::test]
+async fn transcribe_me_is_scoped_to_the_speaker() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("transcribeme").await;
+    let token = &tenant.admin.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, token, "Self").await;
+    let (mut ws, _) = ws_join_media(&app.addr, token, &room_id).await;
+
+    let toggle = serde_json::json!({ "room_id": room_id, "enabled": true, "model": "small" });
+    ws_send(&mut ws, "media:transcribe_me", toggle).await;
+    let status = next_of_type(&mut ws, "media:transcript_status").await;
+    assert_eq!(status["data"]["scope"], "self");
+    assert_eq!(status["data"]["user_id"], tenant.admin.id);
+    assert_eq!(status["data"]["enabled"], true);
+    assert_eq!(status["data"]["model"], "small");
+
+    let toggle = serde_json::json!({ "room_id": room_id, "enabled": false });
+    ws_send(&mut ws, "media:transcribe_me", toggle).await;
+    let status = next_of_type(&mut ws, "media:transcript_status").await;
+    assert_eq!(status["data"]["scope"], "self");
+    assert_eq!(status["data"]["enabled"], false);
+    assert_eq!(status["data"]["previous_model"], "small");
+
+    // Only participants of the call can transcribe themselves.
+    let other = bson::oid::ObjectId::new().to_hex();
+    let toggle = serde_json::json!({ "room_id": other, "enabled": true });
+    ws_send(&mut ws, "media:transcribe_me", toggle).await;
+    let error = next_of_type(&mut ws, "media:error").await;
+    assert_eq!(error["data"]["message"], "Not in this call");
+
+    ws.close(None).await.ok();
+}
+
+/// media:replace_producer only swaps producers the connection owns; an
+/// unknown producer is rejected and nothing is broadcast to peers.
+#[tokio::test]
+async fn replace_producer_rejects_unknown_producer() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("replprod").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Device Switch",
+    )
+    .await;
+
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/join",
+            tenant.tenant_id, room_id
+        ),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let (mut ws, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({
+            "type": "media:replace_producer",
+            "data": {
+                "room_id": room_id,
+                "producer_id": "00000000-0000-0000-0000-000000000000",
+                "rtp_parameters": {
+                    "codecs": [{
+                        "mimeType": "video/VP8",
+                        "clockRate": 90000,
+                        "payloadType": 96,
+                    }],
+                    "encodings": [{ "ssrc": 4242 }],
+                }
+            }
+        }))
+        .unwrap()
+        .into(),
+    ))
+    .await
+    .unwrap();
+
+    let parsed = tokio::time::timeout(std::time::Duration::from_secs(5), next_media_msg(&mut ws))
+        .await
+        .expect("timeout waiting for response");
+    assert_eq!(parsed["type"], "media:error");
+    let error_msg = parsed["data"]["message"].as_str().unwrap();
+    assert!(
+        error_msg.contains("Producer not found") || error_msg.contains("rtp_parameters"),
+        "got: {}",
+        error_msg
+    );
+
+    ws.close(None).await.ok();
+}
+
+/// Organizers change in-call controls; attendees are held to them and are
+/// told about changes over the WebSocket.
+#[tokio::test]
+async fn call_settings_gate_chat_and_reactions_for_attendees() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callsettings").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, admin, "Call Settings").await;
+    let url = |path: &str| {
+        format!(
+            "/api/tenant/{}/room/{}/call/{}",
+            tenant.tenant_id, room_id, path
+        )
+    };
+    for token in [admin, member] {
+        app.auth_post(&url("join"), token).send().await.unwrap();
+    }
+    let (mut ws, _) = ws_join_media(&app.addr, member, &room_id).await;
+
+    let settings: Value = app
+        .auth_get(&url("settings"), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(settings["chat_enabled"], true);
+    assert_eq!(settings["mute_on_entry"], false);
+
+    let resp = app
+        .auth_put(&url("settings"), member)
+        .json(&serde_json::json!({ "chat_enabled": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_put(&url("settings"), admin)
+        .json(&serde_json::json!({ "chat_enabled": false, "reactions_enabled": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let settings: Value = resp.json().await.unwrap();
+    assert_eq!(settings["chat_enabled"], false);
+    assert_eq!(settings["allow_unmute"], true);
+
+    let updated = loop {
+        let msg = ws.next().await.unwrap().unwrap();
+        let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        if parsed["type"] == "room:call_settings_updated" {
+            break parsed;
+        }
+    };
+    assert_eq!(updated["data"]["settings"]["reactions_enabled"], false);
+
+    // Chat is closed to attendees but not to organizers.
+    let resp = app
+        .auth_post(&url("message"), member)
+        .json(&serde_json::json!({ "content": "hello?" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_post(&url("message"), admin)
+        .json(&serde_json::json!({ "content": "Questions at the end, please" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({
+            "type": "media:reaction",
+            "data": { "room_id": room_id, "emoji": "👏" }
+        }))
+        .unwrap()
+        .into(),
+    ))
+    .await
+    .unwrap();
+    let reply = loop {
+        let parsed = next_media_msg(&mut ws).await;
+        let msg_type = parsed["type"].as_str().unwrap_or("");
+        if msg_type == "media:reaction" || msg_type == "media:error" {
+            break parsed;
+        }
+    };
+    assert_eq!(reply["type"], "media:error");
+    assert_eq!(
+        reply["data"]["message"],
+        "Reactions are disabled by the organizer"
+    );
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn dropped_participant_rejoins_within_grace_period() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.reconnect_grace_secs = 30).await;
+    let tenant = app.seed_tenant("confreconnect").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Reconnect",
+    )
+    .await;
+
+    let (mut ws_admin, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+    let (ws_member, transport) =
+        ws_join_media(&app.addr, &tenant.member.access_token, &room_id).await;
+    let token = transport["data"]["reconnect_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // The member's network drops: peers hear it is reconnecting, not gone.
+    drop(ws_member);
+    let parsed = next_media_msg(&mut ws_admin).await;
+    assert_eq!(parsed["type"], "media:peer_reconnecting");
+    assert_eq!(parsed["data"]["user_id"], tenant.member.id);
+    let previous = parsed["data"]["connection_id"].clone();
+
+    let rejoin = serde_json::json!({
+        "type": "media:rejoin",
+        "data": { "room_id": room_id, "reconnect_token": token }
+    });
+
+    // The token only works for the user it was issued to.
+    ws_admin
+        .send(Message::Text(rejoin.to_string().into()))
+        .await
+        .unwrap();
+    assert_eq!(next_media_msg(&mut ws_admin).await["type"], "media:error");
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws_member, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    ws_member.next().await; // connected
+    ws_member
+        .send(Message::Text(rejoin.to_string().into()))
+        .await
+        .unwrap();
+    let rejoined = next_media_msg(&mut ws_member).await;
+    assert_eq!(rejoined["type"], "media:rejoined");
+    assert_eq!(rejoined["data"]["previous_connection_id"], previous);
+    assert!(rejoined["data"]["send_ice_parameters"].is_object());
+    assert!(rejoined["data"]["recv_ice_parameters"].is_object());
+
+    let parsed = next_media_msg(&mut ws_admin).await;
+    assert_eq!(parsed["type"], "media:peer_reconnected");
+    assert_eq!(parsed["data"]["previous_connection_id"], previous);
+    assert_ne!(parsed["data"]["connection_id"], previous);
+
+    // Rebinding is one-shot.
+    ws_member
+        .send(Message::Text(rejoin.to_string().into()))
+        .await
+        .unwrap();
+    assert_eq!(next_media_msg(&mut ws_member).await["type"], "media:error");
+
+    ws_member.close(None).await.ok();
+    ws_admin.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn set_preferred_layers_needs_one_of_your_consumers() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("layers1").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Layers",
+    )
+    .await;
+    let (mut ws, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+
+    let request = |consumer_id: &str| {
+        serde_json::json!({
+            "type": "media:set_preferred_layers",
+            "data": { "room_id": room_id, "consumer_id": consumer_id, "spatial_layer": 0 }
+        })
+    };
+    ws.send(Message::Text(request("not-a-uuid").to_string().into()))
+        .await
+        .unwrap();
+    let parsed = next_media_msg(&mut ws).await;
+    assert_eq!(parsed["type"], "media:error");
+    assert_eq!(parsed["data"]["message"], "Invalid consumer_id");
+
+    let unknown = uuid::Uuid::new_v4().to_string();
+    ws.send(Message::Text(request(&unknown).to_string().into()))
+        .await
+        .unwrap();
+    let parsed = next_media_msg(&mut ws).await;
+    assert_eq!(parsed["type"], "media:error");
+    assert_eq!(parsed["data"]["message"], "Consumer not found");
+
+    ws.close(None).await.ok();
+}
+
+type TestWs =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn ws_send(ws: &mut TestWs, msg_type: &str, data: Value) {
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({ "type": msg_type, "data": data }))
+            .unwrap()
+            .into(),
+    ))
+    .await
+    .unwrap();
+}
+
+/// Next `msg_type` or `media:error` message, skipping anything else.
+async fn next_of_type(ws: &mut TestWs, msg_type: &str) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let parsed = next_media_msg(ws).await;
+            if parsed["type"] == msg_type || parsed["type"] == "media:error" {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("timeout waiting for message")
+}
+
+/// Organizers run polls in the call; votes stream to them, and everyone
+/// gets the final tally and the quiz answer when the poll ends.
+#[tokio::test]
+async fn organizers_run_polls_and_quizzes_in_calls() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callpolls").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, admin, "Polls").await;
+    let url = |path: &str| {
+        format!(
+            "/api/tenant/{}/room/{}/call/{}",
+            tenant.tenant_id, room_id, path
+        )
+    };
+    for token in [admin, member] {
+        app.auth_post(&url("join"), token).send().await.unwrap();
+    }
+    let (mut admin_ws, _) = ws_join_media(&app.addr, admin, &room_id).await;
+    let (mut member_ws, _) = ws_join_media(&app.addr, member, &room_id).await;
+
+    let poll = serde_json::json!({
+        "room_id": room_id,
+        "question": "Which port does TURN use?",
+        "options": ["3478", "8080"],
+        "correct_option": 0,
+    });
+    ws_send(&mut member_ws, "media:poll_start", poll.clone()).await;
+    let reply = next_of_type(&mut member_ws, "media:poll_start").await;
+    assert_eq!(reply["type"], "media:error");
+    assert_eq!(reply["data"]["message"], "Only organizers can start polls");
+
+    ws_send(&mut admin_ws, "media:poll_start", poll).await;
+    let started = next_of_type(&mut member_ws, "media:poll_start").await;
+    assert_eq!(started["type"], "media:poll_start");
+    assert_eq!(started["data"]["is_quiz"], true);
+    // Neither the answer nor the tally is given away while it's open.
+    assert!(started["data"].get("correct_option").is_none());
+    assert!(started["data"].get("counts").is_none());
+    let poll_id = started["data"]["id"].as_str().unwrap().to_string();
+    next_of_type(&mut admin_ws, "media:poll_start").await;
+
+    let vote = serde_json::json!({ "room_id": room_id, "poll_id": poll_id, "option": 1 });
+    ws_send(&mut member_ws, "media:poll_vote", vote.clone()).await;
+    let voted = next_of_type(&mut member_ws, "media:poll_voted").await;
+    assert_eq!(voted["data"]["option"], 1);
+    let results = next_of_type(&mut admin_ws, "media:poll_results").await;
+    assert_eq!(results["data"]["counts"], serde_json::json!([0, 1]));
+
+    let polls: Vec<Value> = app
+        .auth_get(&url("poll"), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(polls.len(), 1);
+    assert!(polls[0].get("counts").is_none());
+    let polls: Vec<Value> = app
+        .auth_get(&url("poll"), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(polls[0]["counts"], serde_json::json!([0, 1]));
+
+    ws_send(
+        &mut admin_ws,
+        "media:poll_end",
+        serde_json::json!({ "room_id": room_id, "poll_id": poll_id }),
+    )
+    .await;
+    let ended = next_of_type(&mut member_ws, "media:poll_ended").await;
+    assert_eq!(ended["data"]["status"], "closed");
+    assert_eq!(ended["data"]["counts"], serde_json::json!([0, 1]));
+    assert_eq!(ended["data"]["correct_option"], 0);
+
+    ws_send(&mut member_ws, "media:poll_vote", vote).await;
+    let reply = next_of_type(&mut member_ws, "media:poll_voted").await;
+    assert_eq!(reply["data"]["message"], "The poll is closed");
+
+    // The final tally stays in the call's event feed.
+    let events: Value = app
+        .auth_get(&url("event"), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let poll_ended = events["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["type"] == "poll_ended")
+        .unwrap();
+    assert_eq!(poll_ended["data"]["counts"], serde_json::json!([0, 1]));
+
+    admin_ws.close(None).await.ok();
+    member_ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn empty_conference_ends_after_grace_period() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.empty_room_grace_secs = 1).await;
+    let tenant = app.seed_tenant("confempty").await;
+    let room_id =
+        create_room_and_start_call(&app, &tenant.tenant_id, &tenant.admin.access_token, "Empty")
+            .await;
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+    let room_url = format!("/api/tenant/{}/room/{}", tenant.tenant_id, room_id);
+    let room = async || -> Value {
+        app.auth_get(&room_url, &tenant.admin.access_token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    };
+
+    let (mut admin_ws, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+    let (mut member_ws, _) = ws_join_media(&app.addr, &tenant.member.access_token, &room_id).await;
+    let room_data = serde_json::json!({ "room_id": room_id });
+
+    // Emptied, then rejoined before the grace period ran out.
+    ws_send(&mut member_ws, "media:leave", room_data.clone()).await;
+    ws_send(&mut admin_ws, "media:leave", room_data.clone()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    ws_send(&mut admin_ws, "media:join", room_data.clone()).await;
+    let joined = next_of_type(&mut admin_ws, "media:transport_created").await;
+    assert_eq!(joined["type"], "media:transport_created");
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert_eq!(room().await["conference_status"], "in_progress");
+
+    // The last participant's connection drops, leaving its session open.
+    drop(admin_ws);
+    let closed = next_of_type(&mut member_ws, "media:room_closed").await;
+    assert_eq!(closed["type"], "media:room_closed");
+    assert_eq!(closed["data"]["room_id"], room_id);
+    assert_eq!(closed["data"]["reason"], "empty");
+
+    let json = room().await;
+    assert_eq!(json["conference_status"], "ended");
+    assert_eq!(json["participant_count"], 0);
+
+    member_ws.close(None).await.ok();
+}
+
+/// Participants raise their hand; organizers lower it, ask to mute, or
+/// mute them outright, and everyone in the call sees the new state.
+#[tokio::test]
+async fn raised_hands_and_organizer_mutes_reach_the_call() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callhands").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, admin, "Hands").await;
+    let url = |path: &str| {
+        format!(
+            "/api/tenant/{}/room/{}/call/{}",
+            tenant.tenant_id, room_id, path
+        )
+    };
+    for token in [admin, member] {
+        app.auth_post(&url("join"), token).send().await.unwrap();
+    }
+    let (mut admin_ws, _) = ws_join_media(&app.addr, admin, &room_id).await;
+    let (mut member_ws, _) = ws_join_media(&app.addr, member, &room_id).await;
+    let room_data = serde_json::json!({ "room_id": room_id });
+    let member_data = serde_json::json!({ "room_id": room_id, "user_id": tenant.member.id });
+
+    ws_send(&mut member_ws, "media:raise_hand", room_data.clone()).await;
+    let raised = next_of_type(&mut admin_ws, "media:participant_state").await;
+    assert_eq!(raised["data"]["user_id"], tenant.member.id);
+    assert_eq!(raised["data"]["is_hand_raised"], true);
+    next_of_type(&mut member_ws, "media:participant_state").await;
+
+    // Only organizers moderate others.
+    ws_send(
+        &mut member_ws,
+        "media:force_mute",
+        serde_json::json!({ "room_id": room_id, "user_id": tenant.admin.id }),
+    )
+    .await;
+    let reply = next_of_type(&mut member_ws, "media:participant_state").await;
+    assert_eq!(reply["type"], "media:error");
+    assert_eq!(reply["data"]["message"], "Only organizers can mute others");
+
+    ws_send(&mut admin_ws, "media:lower_hand", member_data.clone()).await;
+    let lowered = next_of_type(&mut member_ws, "media:participant_state").await;
+    assert_eq!(lowered["data"]["is_hand_raised"], false);
+    assert_eq!(lowered["data"]["changed_by"], tenant.admin.id);
+    next_of_type(&mut admin_ws, "media:participant_state").await;
+
+    ws_send(&mut admin_ws, "media:mute_request", member_data.clone()).await;
+    let request = next_of_type(&mut member_ws, "media:mute_request").await;
+    assert_eq!(request["data"]["requested_by"], tenant.admin.id);
+
+    ws_send(&mut admin_ws, "media:force_mute", member_data).await;
+    let muted = next_of_type(&mut member_ws, "media:participant_state").await;
+    assert_eq!(muted["data"]["is_muted"], true);
+    next_of_type(&mut admin_ws, "media:participant_state").await;
+
+    let parts: Vec<Value> = app
+        .auth_get(&url("participant"), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let part = parts
+        .iter()
+        .find(|p| p["user_id"] == tenant.member.id.as_str())
+        .unwrap();
+    assert_eq!(part["is_muted"], true);
+    assert_eq!(part["is_hand_raised"], false);
+
+    admin_ws.close(None).await.ok();
+    member_ws.close(None).await.ok();
+}
+
+/// Organizers see every connection's RTP stats, others only their own,
+/// and a subscribed connection gets its stats pushed as `media:stats`.
+#[tokio::test]
+async fn conference_stats_are_scoped_and_pushed_on_request() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callstats").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, admin, "Stats").await;
+    for token in [admin, member] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+    let (admin_ws, _) = ws_join_media(&app.addr, admin, &room_id).await;
+    let (mut member_ws, _) = ws_join_media(&app.addr, member, &room_id).await;
+
+    let url = format!(
+        "/api/tenant/{}/conference/{}/stats",
+        tenant.tenant_id, room_id
+    );
+    let stats = |token: &str| {
+        let req = app.auth_get(&url, token);
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status().as_u16(), 200);
+            resp.json::<Value>().await.unwrap()
+        }
+    };
+    let json = stats(admin).await;
+    assert_eq!(json["conference_id"], room_id);
+    let participants = json["participants"].as_array().unwrap();
+    assert_eq!(participants.len(), 2);
+    assert_eq!(participants[0]["send_transport"]["ice_state"], "new");
+    let json = stats(member).await;
+    let participants = json["participants"].as_array().unwrap();
+    assert_eq!(participants.len(), 1);
+    assert_eq!(participants[0]["user_id"], tenant.member.id);
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/conference/{}/stats",
+                tenant.tenant_id, tenant.rooms[0].id
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    ws_send(
+        &mut member_ws,
+        "media:stats_subscribe",
+        serde_json::json!({ "room_id": room_id, "interval_ms": 1000 }),
+    )
+    .await;
+    let pushed = next_of_type(&mut member_ws, "media:stats").await;
+    assert_eq!(pushed["type"], "media:stats");
+    assert_eq!(pushed["data"]["stats"]["user_id"], tenant.member.id);
+    assert!(pushed["data"]["stats"]["recv_transport"].is_object());
+
+    ws_send(
+        &mut member_ws,
+        "media:stats_unsubscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    ws_send(
+        &mut member_ws,
+        "media:stats_subscribe",
+        serde_json::json!({ "room_id": tenant.rooms[0].id }),
+    )
+    .await;
+    let reply = next_of_type(&mut member_ws, "media:error").await;
+    assert_eq!(reply["data"]["message"], "Not in this call");
+
+    drop(admin_ws);
+    member_ws.close(None).await.ok();
+}
@@ -0,0 +1,76 @@
+use crate::fixtures::test_app::TestApp;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn latency_probe_is_acked_and_aggregated() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("dlvone").await;
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.admin.access_token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    ws.next().await; // connected msg
+
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({
+            "type": "latency:probe",
+            "data": { "id": "p1", "tenant_id": tenant.tenant_id, "rtt_ms": 42 }
+        }))
+        .unwrap()
+        .into(),
+    ))
+    .await
+    .unwrap();
+
+    let msg = ws.next().await.unwrap().unwrap();
+    let ack: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(ack["type"], "latency:ack");
+    assert_eq!(ack["data"]["id"], "p1");
+    assert!(ack["data"]["server_ts"].is_i64());
+
+    // A message broadcast records one fan-out sample for the tenant.
+    let room_id = &tenant.rooms[0].id;
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "content": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/delivery-metrics", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let metrics: Value = resp.json().await.unwrap();
+    assert_eq!(metrics["probe_rtt"]["count"], 1);
+    assert_eq!(metrics["probe_rtt"]["max_ms"], 42.0);
+    assert!(metrics["fanout"]["count"].as_u64().unwrap() >= 1);
+}
+
+#[tokio::test]
+async fn delivery_metrics_require_manage_tenant() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("dlvtwo").await;
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/delivery-metrics", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -0,0 +1,231 @@
+use crate::fixtures::{seed::SeededUser, test_app::TestApp};
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+
+/// Register a user and add them to the tenant as a plain member.
+async fn seed_extra_member(app: &TestApp, tenant_id: &str, slug: &str) -> SeededUser {
+    let user = app
+        .register_user(
+            &format!("extra@{}.test", slug),
+            &format!("{}_extra", slug),
+            &format!("{} Extra", slug),
+            "Extra123!",
+            None,
+            None,
+        )
+        .await;
+
+    let tid = ObjectId::parse_str(tenant_id).unwrap();
+    let role: bson::Document = app
+        .db
+        .collection::<bson::Document>("roles")
+        .find_one(doc! { "tenant_id": tid, "name": "member" })
+        .await
+        .unwrap()
+        .expect("member role not found");
+    let now = bson::DateTime::now();
+    app.db
+        .collection::<bson::Document>("tenant_members")
+        .insert_one(doc! {
+            "tenant_id": tid,
+            "user_id": ObjectId::parse_str(&user.id).unwrap(),
+            "nickname": bson::Bson::Null,
+            "role_ids": [role.get_object_id("_id").unwrap()],
+            "joined_at": now,
+            "is_pending": false,
+            "is_muted": false,
+            "notification_override": bson::Bson::Null,
+            "invited_by": bson::Bson::Null,
+            "last_seen_at": bson::Bson::Null,
+            "created_at": now,
+            "updated_at": now,
+        })
+        .await
+        .unwrap();
+
+    user
+}
+
+async fn open_dm(app: &TestApp, tenant_id: &str, token: &str, user_ids: &[&str]) -> Value {
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/dm", tenant_id), token)
+        .json(&serde_json::json!({ "user_ids": user_ids }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn open_dm_is_idempotent_per_participant_set() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("dm1").await;
+
+    let dm = open_dm(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        &[&tenant.admin.id],
+    )
+    .await;
+    assert_eq!(dm["is_group"], false);
+    assert_eq!(dm["participants"].as_array().unwrap().len(), 2);
+
+    // Same pair, opened from the other side
+    let again = open_dm(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        &[&tenant.member.id],
+    )
+    .await;
+    assert_eq!(again["id"], dm["id"]);
+
+    for user in [&tenant.admin, &tenant.member] {
+        let list: Vec<Value> = app
+            .auth_get(
+                &format!("/api/tenant/{}/dm", tenant.tenant_id),
+                &user.access_token,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["id"], dm["id"]);
+    }
+
+    // DMs stay out of the room list
+    let rooms: Vec<Value> = app
+        .auth_get(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(rooms.iter().all(|r| r["id"] != dm["id"]));
+}
+
+#[tokio::test]
+async fn group_dm_messages_are_visible_to_participants_only() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("dm2").await;
+    let extra = seed_extra_member(&app, &tenant.tenant_id, "dm2").await;
+
+    // A 1:1 DM between member and extra; the admin is not part of it
+    let dm = open_dm(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        &[&extra.id],
+    )
+    .await;
+    let dm_id = dm["id"].as_str().unwrap();
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, dm_id);
+
+    let resp = app
+        .auth_post(&messages_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "content": "just between us" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let json: Value = app
+        .auth_get(&messages_url, &extra.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["items"][0]["content"], "just between us");
+
+    // Tenant admins don't get in either
+    let resp = app
+        .auth_get(&messages_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(&messages_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "hello?" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, dm_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}", tenant.tenant_id, dm_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Adding the admin makes it a different, group conversation
+    let group = open_dm(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        &[&tenant.member.id, &extra.id],
+    )
+    .await;
+    assert_ne!(group["id"], dm["id"]);
+    assert_eq!(group["is_group"], true);
+}
+
+#[tokio::test]
+async fn open_dm_validates_participants() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("dm3").await;
+    let outsider = app
+        .register_user(
+            "outsider@dm3.test",
+            "outsider_dm3",
+            "Outsider",
+            "Outsider123!",
+            None,
+            None,
+        )
+        .await;
+
+    let url = format!("/api/tenant/{}/dm", tenant.tenant_id);
+
+    let resp = app
+        .auth_post(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "user_ids": [&tenant.member.id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_post(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "user_ids": [&outsider.id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
@@ -0,0 +1,422 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::fixtures::test_app::TestApp;
+use roomler_ai_api::{email_queue, state::AppState};
+use roomler_ai_config::{EmailProvider, Settings, SmtpTls};
+use roomler_ai_db::models::BackgroundTask;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+/// A minimal SMTP server keeping the recipient and data of each message
+/// it accepts. It refuses the next `rejects` messages with a temporary
+/// error.
+struct FakeSmtp {
+    port: u16,
+    messages: Arc<Mutex<Vec<(String, String)>>>,
+    rejects: Arc<AtomicUsize>,
+}
+
+impl FakeSmtp {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let smtp = Self {
+            port: listener.local_addr().unwrap().port(),
+            messages: Arc::default(),
+            rejects: Arc::default(),
+        };
+        let (messages, rejects) = (smtp.messages.clone(), smtp.rejects.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (messages, rejects) = (messages.clone(), rejects.clone());
+                tokio::spawn(async move {
+                    let mut conn = BufReader::new(stream);
+                    conn.get_mut().write_all(b"220 fake\r\n").await.unwrap();
+                    let mut to = String::new();
+                    loop {
+                        let mut line = String::new();
+                        if conn.read_line(&mut line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let reply = if let Some(rcpt) = line.strip_prefix("RCPT TO:") {
+                            to = rcpt.trim().trim_matches(['<', '>']).to_string();
+                            "250 ok"
+                        } else if line.starts_with("DATA") {
+                            conn.get_mut().write_all(b"354 go on\r\n").await.unwrap();
+                            let mut data = String::new();
+                            while !data.ends_with("\r\n.\r\n") {
+                                if conn.read_line(&mut data).await.unwrap() == 0 {
+                                    return;
+                                }
+                            }
+                            if rejects
+                                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                                    n.checked_sub(1)
+                                })
+                                .is_ok()
+                            {
+                                "451 try again later"
+                            } else {
+                                messages.lock().unwrap().push((to.clone(), data));
+                                "250 queued"
+                            }
+                        } else if line.starts_with("QUIT") {
+                            "221 bye"
+                        } else {
+                            "250 ok"
+                        };
+                        conn.get_mut()
+                            .write_all(format!("{reply}\r\n").as_bytes())
+                            .await
+                            .unwrap();
+                        if reply.starts_with("221") {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        smtp
+    }
+
+    fn configure(&self, settings: &mut Settings) {
+        settings.email.provider = EmailProvider::Smtp;
+        settings.email.smtp_host = "127.0.0.1".to_string();
+        settings.email.smtp_port = self.port;
+        settings.email.smtp_tls = SmtpTls::None;
+    }
+
+    /// The messages accepted so far, as (recipient, data).
+    fn take(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.messages.lock().unwrap())
+    }
+}
+
+async fn state(app: &TestApp) -> AppState {
+    AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap()
+}
+
+async fn email_tasks(app: &TestApp, mut filter: bson::Document) -> Vec<BackgroundTask> {
+    use futures::TryStreamExt;
+    filter.insert("category", "email");
+    app.db
+        .collection::<BackgroundTask>("background_tasks")
+        .find(filter)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap()
+}
+
+async fn make_due(app: &TestApp) {
+    app.db
+        .collection::<bson::Document>("background_tasks")
+        .update_many(
+            bson::doc! { "category": "email", "status": "pending" },
+            bson::doc! { "$set": { "next_attempt_at": bson::DateTime::now() } },
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn password_reset_email_is_queued_retried_and_resets_once() {
+    let smtp = FakeSmtp::start().await;
+    let app = TestApp::spawn_with_settings(|s| smtp.configure(s)).await;
+    let tenant = app.seed_tenant("emailreset").await;
+    let state = state(&app).await;
+
+    // Registering queued an activation email per user.
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 2);
+    let sent = smtp.take();
+    assert_eq!(sent.len(), 2);
+    assert!(
+        sent.iter()
+            .all(|(_, data)| data.contains("Subject: Activate your Roomler account"))
+    );
+
+    // Unknown addresses get the same answer and no email.
+    for email in [tenant.admin.email.as_str(), "nobody@example.com"] {
+        let resp = app
+            .client
+            .post(app.url("/api/auth/password/forgot"))
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+    let queued = email_tasks(&app, bson::doc! { "status": "pending" }).await;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].params["to"], tenant.admin.email.as_str());
+
+    // A refused delivery is kept for a retry after the backoff.
+    smtp.rejects.store(1, Ordering::SeqCst);
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 1);
+    assert!(smtp.take().is_empty());
+    let task = &email_tasks(&app, bson::doc! { "status": "pending" }).await[0];
+    assert_eq!(task.attempts, 1);
+    assert!(task.error.as_deref().unwrap().contains("451"));
+    assert!(task.next_attempt_at.unwrap() > bson::DateTime::now());
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 0);
+
+    make_due(&app).await;
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 1);
+    let sent = smtp.take();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, tenant.admin.email);
+    assert!(sent[0].1.contains("Subject: Reset your Roomler password"));
+    assert!(
+        email_tasks(&app, bson::doc! { "status": { "$ne": "completed" } })
+            .await
+            .is_empty()
+    );
+
+    let code = app
+        .db
+        .collection::<bson::Document>("activation_codes")
+        .find_one(bson::doc! { "purpose": "password_reset" })
+        .await
+        .unwrap()
+        .unwrap();
+    let token = code.get_str("token").unwrap();
+    let reset = |token: &str| {
+        app.client
+            .post(app.url("/api/auth/password/reset"))
+            .json(&serde_json::json!({
+                "user_id": tenant.admin.id,
+                "token": token,
+                "password": "N3w-password!",
+            }))
+            .send()
+    };
+    assert_eq!(reset("wrong").await.unwrap().status().as_u16(), 400);
+    assert_eq!(reset(token).await.unwrap().status().as_u16(), 200);
+    app.login_user(&tenant.admin.email, "N3w-password!").await;
+    assert_eq!(reset(token).await.unwrap().status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn email_preferences_opt_out_and_mentions_are_digested() {
+    let smtp = FakeSmtp::start().await;
+    let app = TestApp::spawn_with_settings(|s| smtp.configure(s)).await;
+    let tenant = app.seed_tenant("emailprefs").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let state = state(&app).await;
+    email_queue::send_due(&state).await.unwrap();
+    smtp.take();
+
+    let prefs: Value = app
+        .auth_get("/api/auth/me/preferences", member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        prefs["email"],
+        serde_json::json!({ "invites": true, "mention_digest": true, "billing_receipts": true })
+    );
+    let prefs: Value = app
+        .auth_put("/api/auth/me/preferences", member)
+        .json(&serde_json::json!({ "email": { "invites": false } }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(prefs["email"]["invites"], false);
+    assert_eq!(prefs["email"]["mention_digest"], true);
+
+    // The member opted out of invites; an address with no account didn't.
+    for email in [tenant.member.email.as_str(), "newcomer@example.com"] {
+        let resp = app
+            .auth_post(&format!("/api/tenant/{}/invite", tid), admin)
+            .json(&serde_json::json!({ "target_email": email }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 201);
+    }
+    let queued = email_tasks(&app, bson::doc! { "status": "pending" }).await;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].params["to"], "newcomer@example.com");
+    assert_eq!(queued[0].params["template"]["template"], "invite_created");
+
+    // Queued emails aren't the user's tasks.
+    let tasks: Value = app
+        .auth_get(&format!("/api/tenant/{}/task", tid), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        tasks["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|t| t["category"] != "email")
+    );
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 1);
+    smtp.take();
+
+    // Mentions of an offline user are collected into one digest, sent later.
+    for token in [admin, member] {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), token)
+            .send()
+            .await
+            .unwrap();
+    }
+    for _ in 0..2 {
+        let resp = app
+            .auth_post(
+                &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+                admin,
+            )
+            .json(&serde_json::json!({
+                "content": "Can you review this?",
+                "mentions": { "users": [tenant.member.id], "everyone": false, "here": false },
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let digests = email_tasks(&app, bson::doc! { "task_type": "mention_digest" }).await;
+    assert_eq!(digests.len(), 1);
+    assert!(digests[0].next_attempt_at.unwrap() > bson::DateTime::now());
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 0);
+
+    make_due(&app).await;
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 1);
+    let sent = smtp.take();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, tenant.member.email);
+    assert!(sent[0].1.contains("Subject: You have 2 unread mentions in"));
+}
+
+#[tokio::test]
+async fn email_change_is_confirmed_from_the_new_address() {
+    let smtp = FakeSmtp::start().await;
+    let app = TestApp::spawn_with_settings(|s| smtp.configure(s)).await;
+    let tenant = app.seed_tenant("emailchange").await;
+    let token = &tenant.member.access_token;
+    let state = state(&app).await;
+    email_queue::send_due(&state).await.unwrap();
+    smtp.take();
+
+    let change = |email: &str, password: &str| {
+        app.auth_put("/api/auth/me/email", token)
+            .json(&serde_json::json!({ "email": email, "password": password }))
+            .send()
+    };
+    let status = |email: &str, password: &str| {
+        let resp = change(email, password);
+        async move { resp.await.unwrap().status().as_u16() }
+    };
+    assert_eq!(status("new@emailchange.test", "wrong").await, 401);
+    assert_eq!(status("not an email", "Member123!").await, 422);
+    assert_eq!(status(&tenant.member.email, "Member123!").await, 422);
+    assert_eq!(status(&tenant.admin.email, "Member123!").await, 409);
+
+    let resp = change("new@emailchange.test", "Member123!").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["email"], tenant.member.email.as_str());
+    assert_eq!(body["pending"]["email"], "new@emailchange.test");
+
+    assert_eq!(email_queue::send_due(&state).await.unwrap(), 1);
+    let sent = smtp.take();
+    assert_eq!(sent[0].0, "new@emailchange.test");
+    assert!(
+        sent[0]
+            .1
+            .contains("Subject: Confirm your new Roomler email")
+    );
+
+    let code_token = || async {
+        app.db
+            .collection::<bson::Document>("activation_codes")
+            .find_one(bson::doc! { "purpose": "email_change" })
+            .await
+            .unwrap()
+            .map(|code| code.get_str("token").unwrap().to_string())
+    };
+    let confirm = |token: String| {
+        app.client
+            .post(app.url("/api/auth/email/confirm"))
+            .json(&serde_json::json!({ "user_id": tenant.member.id, "token": token }))
+            .send()
+    };
+
+    // A canceled change can't be confirmed.
+    let canceled = code_token().await.unwrap();
+    let resp = app
+        .auth_delete("/api/auth/me/email", token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    let body: Value = app
+        .auth_get("/api/auth/me/email", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["pending"].is_null());
+    assert_eq!(confirm(canceled).await.unwrap().status().as_u16(), 400);
+
+    assert_eq!(status("New.Me@emailchange.test", "Member123!").await, 202);
+    let pending = code_token().await.unwrap();
+    assert_eq!(
+        confirm("wrong".to_string())
+            .await
+            .unwrap()
+            .status()
+            .as_u16(),
+        400
+    );
+    assert_eq!(
+        confirm(pending.clone()).await.unwrap().status().as_u16(),
+        200
+    );
+    assert_eq!(confirm(pending).await.unwrap().status().as_u16(), 400);
+
+    app.login_user("New.Me@emailchange.test", "Member123!")
+        .await;
+    let resp = app
+        .client
+        .post(app.url("/api/auth/login"))
+        .json(&serde_json::json!({ "email": tenant.member.email, "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // The old address is told about the change.
+    email_queue::send_due(&state).await.unwrap();
+    let sent = smtp.take();
+    let notice = sent
+        .iter()
+        .find(|(to, _)| to == &tenant.member.email)
+        .unwrap();
+    assert!(notice.1.contains("Subject: Your Roomler email was changed"));
+}
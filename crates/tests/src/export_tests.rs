@@ -1,9 +1,11 @@
 use crate::fixtures::test_app::TestApp;
+use reqwest::multipart;
 use serde_json::Value;
 
 #[tokio::test]
 async fn export_conversation_creates_background_task() {
-    let app = TestApp::spawn().await;
+    // Force the background path regardless of room size.
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
     let tenant = app.seed_tenant("export1").await;
     let room_id = tenant.rooms[0].id.clone();
 
@@ -42,15 +44,19 @@ async fn export_conversation_creates_background_task() {
         .await
         .unwrap();
 
-    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.status().as_u16(), 202);
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["status"], "pending");
-    assert!(json["task_id"].as_str().unwrap().len() > 0);
+    let task_id = json["task_id"].as_str().unwrap();
+    assert!(task_id.len() > 0);
+    let task_url = format!("/api/tenant/{}/task/{}", tenant.tenant_id, task_id);
+    assert_eq!(json["task_url"], task_url);
+    assert_eq!(json["download_url"], format!("{}/download", task_url));
 }
 
 #[tokio::test]
 async fn export_task_completes_and_download_works() {
-    let app = TestApp::spawn().await;
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
     let tenant = app.seed_tenant("export2").await;
     let room_id = tenant.rooms[0].id.clone();
 
@@ -146,7 +152,7 @@ async fn export_task_completes_and_download_works() {
 
 #[tokio::test]
 async fn list_background_tasks() {
-    let app = TestApp::spawn().await;
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
     let tenant = app.seed_tenant("tasklist").await;
     let room_id = tenant.rooms[0].id.clone();
 
@@ -194,3 +200,233 @@ async fn list_background_tasks() {
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["total"], 2);
 }
+
+#[tokio::test]
+async fn small_export_is_returned_inline() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("exportsync").await;
+    let room_id = tenant.rooms[0].id.clone();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .json(&serde_json::json!({ "content": "Inline export" }))
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/export/conversation", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "room_id": room_id }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(
+        resp.headers()
+            .get("content-type")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("spreadsheetml")
+    );
+    let body = resp.bytes().await.unwrap();
+    assert_eq!(&body[..2], b"PK");
+
+    // Nothing was queued.
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/task", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 0);
+}
+
+#[tokio::test]
+async fn export_formats_are_selectable() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("exportfmt").await;
+    let room_id = tenant.rooms[0].id.clone();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .json(&serde_json::json!({ "content": "Ship it, <b>today</b>" }))
+    .send()
+    .await
+    .unwrap();
+
+    let export = |format: &'static str| {
+        app.auth_post(
+            &format!("/api/tenant/{}/export/conversation", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "room_id": room_id, "format": format }))
+        .send()
+    };
+
+    let resp = export("json").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["messages"][0]["content"], "Ship it, <b>today</b>");
+
+    let resp = export("csv").await.unwrap();
+    assert!(
+        resp.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .ends_with(".csv\"")
+    );
+    let csv = resp.text().await.unwrap();
+    assert!(csv.starts_with("timestamp,author,type,message,reactions,attachments,embeds\n"));
+    assert!(csv.contains("\"Ship it, <b>today</b>\""));
+
+    let md = export("markdown").await.unwrap().text().await.unwrap();
+    assert!(md.starts_with("# Conversation Export"));
+    assert!(md.contains("Ship it, <b>today</b>"));
+
+    let resp = export("html").await.unwrap();
+    assert!(
+        resp.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html")
+    );
+    let html = resp.text().await.unwrap();
+    assert!(html.contains("Ship it, &lt;b&gt;today&lt;/b&gt;"));
+
+    let resp = export("docx").await.unwrap();
+    assert!(resp.status().is_client_error());
+}
+
+#[tokio::test]
+async fn html_export_runs_in_background_with_embedded_images() {
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
+    let tenant = app.seed_tenant("exporthtml").await;
+    let room_id = tenant.rooms[0].id.clone();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let form = multipart::Form::new()
+        .part(
+            "file",
+            multipart::Part::bytes(b"fake-png".to_vec())
+                .file_name("chart.png")
+                .mime_str("image/png")
+                .unwrap(),
+        )
+        .text("room_id", room_id.clone());
+    let upload: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/file/upload", tenant.tenant_id)))
+        .header(
+            "Authorization",
+            format!("Bearer {}", tenant.admin.access_token),
+        )
+        .multipart(form)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let file_id = upload["id"].as_str().unwrap();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .json(&serde_json::json!({ "content": "Latest chart", "attachment_ids": [file_id] }))
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/export/conversation", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "room_id": room_id, "format": "html" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    let task_url = json["task_url"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let task: Value = app
+            .auth_get(&task_url, &tenant.admin.access_token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match task["status"].as_str().unwrap() {
+            "Completed" => {
+                assert_eq!(task["progress"], 100);
+                assert!(task["file_name"].as_str().unwrap().ends_with(".html"));
+                completed = true;
+                break;
+            }
+            "Failed" => panic!("Export task failed: {:?}", task["error"]),
+            _ => {}
+        }
+    }
+    assert!(completed, "Export task did not complete within timeout");
+
+    let resp = app
+        .auth_get(
+            json["download_url"].as_str().unwrap(),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(
+        resp.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html")
+    );
+    let html = resp.text().await.unwrap();
+    assert!(html.contains("Latest chart"));
+    // "fake-png", base64-encoded.
+    assert!(html.contains("src=\"data:image/png;base64,ZmFrZS1wbmc=\""));
+}
@@ -0,0 +1,172 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+/// Insert a global flag row directly, the way an operator would seed one.
+async fn define_flag(app: &TestApp, key: &str, enabled: bool) {
+    let now = bson::DateTime::now();
+    app.db
+        .collection::<bson::Document>("feature_flags")
+        .insert_one(bson::doc! {
+            "key": key,
+            "description": bson::Bson::Null,
+            "enabled": enabled,
+            "created_at": now,
+            "updated_at": now,
+        })
+        .await
+        .expect("Failed to insert feature flag");
+}
+
+async fn flag_state(app: &TestApp, tenant_id: &str, token: &str, key: &str) -> Option<Value> {
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/feature-flag", tenant_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let flags: Vec<Value> = resp.json().await.unwrap();
+    flags.into_iter().find(|f| f["key"] == key)
+}
+
+#[tokio::test]
+async fn tenant_override_flips_flag_for_members() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ffone").await;
+    define_flag(&app, "new_search", false).await;
+
+    let before = flag_state(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        "new_search",
+    )
+    .await
+    .expect("flag should be listed");
+    assert_eq!(before["enabled"], false);
+    assert_eq!(before["source"], "global");
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/feature-flag/new_search", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let after = flag_state(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        "new_search",
+    )
+    .await
+    .unwrap();
+    assert_eq!(after["enabled"], true);
+    assert_eq!(after["source"], "tenant");
+}
+
+#[tokio::test]
+async fn user_override_wins_over_tenant_override() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("fftwo").await;
+    define_flag(&app, "new_search", false).await;
+    let path = format!("/api/tenant/{}/feature-flag/new_search", tenant.tenant_id);
+
+    app.auth_put(&path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_put(&path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "enabled": false, "user_id": tenant.member.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let member_view = flag_state(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        "new_search",
+    )
+    .await
+    .unwrap();
+    assert_eq!(member_view["enabled"], false);
+    assert_eq!(member_view["source"], "user");
+
+    let admin_view = flag_state(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "new_search",
+    )
+    .await
+    .unwrap();
+    assert_eq!(admin_view["enabled"], true);
+
+    // Clearing the user override falls back to the tenant value.
+    let resp = app
+        .auth_delete(
+            &format!("{}?user_id={}", path, tenant.member.id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["cleared"], true);
+
+    let member_view = flag_state(
+        &app,
+        &tenant.tenant_id,
+        &tenant.member.access_token,
+        "new_search",
+    )
+    .await
+    .unwrap();
+    assert_eq!(member_view["enabled"], true);
+}
+
+#[tokio::test]
+async fn member_cannot_flip_flags() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ffthree").await;
+    define_flag(&app, "new_search", false).await;
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/feature-flag/new_search", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn unknown_flag_cannot_be_overridden() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("fffour").await;
+
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/feature-flag/does_not_exist",
+                tenant.tenant_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
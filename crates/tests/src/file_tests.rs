@@ -288,3 +288,249 @@ async fn list_files_in_room() {
     let items = json["items"].as_array().unwrap();
     assert_eq!(items.len(), 2);
 }
+
+#[tokio::test]
+async fn gridfs_backend_stores_and_streams_files() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.storage.backend = roomler_ai_config::StorageBackend::Gridfs;
+        s.storage.max_object_size_bytes = 32;
+    })
+    .await;
+    let tenant = app.seed_tenant("filegridfs").await;
+    let room_id = tenant.rooms[0].id.clone();
+    let upload_url = app.url(&format!(
+        "/api/tenant/{}/room/{}/file/upload",
+        tenant.tenant_id, room_id
+    ));
+    let form = |content: &[u8]| {
+        multipart::Form::new().part(
+            "file",
+            multipart::Part::bytes(content.to_vec())
+                .file_name("notes.txt")
+                .mime_str("text/plain")
+                .unwrap(),
+        )
+    };
+
+    let resp = app
+        .client
+        .post(&upload_url)
+        .bearer_auth(&tenant.admin.access_token)
+        .multipart(form(b"stored in mongo"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["size"], 15);
+
+    // The bytes live in the GridFS bucket, not on disk.
+    let stored = app
+        .db
+        .collection::<bson::Document>("uploads.files")
+        .count_documents(bson::doc! {})
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+
+    let resp = app
+        .auth_get(json["url"].as_str().unwrap(), &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().await.unwrap(), "stored in mongo");
+
+    // Uploads over the size guard are rejected and leave nothing behind.
+    let resp = app
+        .client
+        .post(&upload_url)
+        .bearer_auth(&tenant.admin.access_token)
+        .multipart(form(&[b'x'; 64]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let stored = app
+        .db
+        .collection::<bson::Document>("uploads.files")
+        .count_documents(bson::doc! {})
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[tokio::test]
+async fn direct_upload_presign_requires_s3_and_valid_metadata() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("filepresignlocal").await;
+    let presign_url = format!("/api/tenant/{}/file/presign", tenant.tenant_id);
+    let request = serde_json::json!({
+        "room_id": tenant.rooms[0].id,
+        "filename": "notes.txt",
+        "content_type": "text/plain",
+        "size": 12,
+    });
+
+    // The local backend has nowhere for a client to upload to.
+    let resp = app
+        .auth_post(&presign_url, &tenant.admin.access_token)
+        .json(&request)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let app = TestApp::spawn_with_settings(|s| {
+        s.storage.backend = roomler_ai_config::StorageBackend::S3;
+        s.storage.max_object_size_bytes = 32;
+    })
+    .await;
+    let tenant = app.seed_tenant("filepresigns3").await;
+    let presign_url = format!("/api/tenant/{}/file/presign", tenant.tenant_id);
+    let request = serde_json::json!({
+        "room_id": tenant.rooms[0].id,
+        "filename": "notes.txt",
+        "content_type": "text/plain",
+        "size": 12,
+    });
+
+    let resp = app
+        .auth_post(&presign_url, &tenant.admin.access_token)
+        .json(&request)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["method"], "PUT");
+    assert_eq!(json["headers"]["Content-Type"], "text/plain");
+    let url = json["url"].as_str().unwrap();
+    assert!(url.contains(&format!("/room/{}/", tenant.rooms[0].id)));
+    assert!(url.contains("X-Amz-Signature="));
+    let pending = app
+        .db
+        .collection::<bson::Document>("pending_uploads")
+        .count_documents(bson::doc! {})
+        .await
+        .unwrap();
+    assert_eq!(pending, 1);
+
+    for (field, value) in [
+        ("size", serde_json::json!(64)),
+        ("size", serde_json::json!(0)),
+        ("content_type", serde_json::json!("not a mime type")),
+        ("filename", serde_json::json!("  ")),
+    ] {
+        let mut bad = request.clone();
+        bad[field] = value;
+        let resp = app
+            .auth_post(&presign_url, &tenant.admin.access_token)
+            .json(&bad)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{} should be rejected", field);
+    }
+
+    // Only the user who requested an upload can confirm it.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/file/confirm", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "upload_id": json["upload_id"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn duplicate_uploads_share_stored_content() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.storage.backend = roomler_ai_config::StorageBackend::Gridfs;
+    })
+    .await;
+    let tenant = app.seed_tenant("filededup").await;
+    let room_id = tenant.rooms[0].id.clone();
+    let upload_url = app.url(&format!(
+        "/api/tenant/{}/room/{}/file/upload",
+        tenant.tenant_id, room_id
+    ));
+    let upload = |filename: &'static str| {
+        app.client
+            .post(&upload_url)
+            .bearer_auth(&tenant.admin.access_token)
+            .multipart(
+                multipart::Form::new().part(
+                    "file",
+                    multipart::Part::bytes(b"same bytes".to_vec())
+                        .file_name(filename)
+                        .mime_str("text/plain")
+                        .unwrap(),
+                ),
+            )
+            .send()
+    };
+    let stored_objects = || async {
+        app.db
+            .collection::<bson::Document>("uploads.files")
+            .count_documents(bson::doc! {})
+            .await
+            .unwrap()
+    };
+
+    let first: Value = upload("first.txt").await.unwrap().json().await.unwrap();
+    assert_eq!(
+        first["checksum"],
+        "58100dc8fc06562ce3e578231dc948e083520ee49c4b4ee5a5a28bb4b4003feb"
+    );
+    assert!(first.get("duplicate_of").is_none());
+
+    let resp = upload("second.txt").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let second: Value = resp.json().await.unwrap();
+    assert_eq!(second["checksum"], first["checksum"]);
+    assert_eq!(second["duplicate_of"]["file_id"], first["id"]);
+    assert_eq!(second["duplicate_of"]["filename"], "first.txt");
+    assert_eq!(second["duplicate_of"]["uploaded_by"], first["uploaded_by"]);
+    assert_eq!(stored_objects().await, 1);
+
+    // Deleting one copy keeps the content for the other.
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/file/{}",
+                tenant.tenant_id,
+                first["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_get(second["url"].as_str().unwrap(), &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.text().await.unwrap(), "same bytes");
+
+    // Deleting the last one removes it.
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/file/{}",
+                tenant.tenant_id,
+                second["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(stored_objects().await, 0);
+}
@@ -0,0 +1,16 @@
+use axum::{Json, Router, routing::get};
+
+/// Start an OpenAI-compatible ASR server that serves `whisper-small` only,
+/// returning its base URL.
+pub async fn spawn() -> String {
+    let router = Router::new().route(
+        "/v1/models",
+        get(|| async { Json(serde_json::json!({ "data": [{ "id": "whisper-small" }] })) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
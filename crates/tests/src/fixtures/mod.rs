@@ -1,2 +1,3 @@
+pub mod fake_asr;
 pub mod seed;
 pub mod test_app;
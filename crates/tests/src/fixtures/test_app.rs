@@ -1,7 +1,7 @@
 use mongodb::{Client, Database, options::ClientOptions};
-use roomler_ai_api::{build_router, state::AppState};
+use roomler_ai_api::{build_router, conference_limits, state::AppState};
 use roomler_ai_config::Settings;
-use roomler_ai_db::indexes::ensure_indexes;
+use roomler_ai_db::indexes::IndexHealth;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 
@@ -32,6 +32,10 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        // Fixture tenants create their own rooms; onboarding tests opt back in.
+        settings.onboarding.default_channels.clear();
+        // Webhook tests deliver to fake servers on loopback.
+        settings.outbound.allow_private_targets = true;
 
         let client_options = ClientOptions::parse(&settings.database.url)
             .await
@@ -40,11 +44,15 @@ impl TestApp {
             Client::with_options(client_options).expect("Failed to create MongoDB client");
         let db = mongo_client.database(&db_name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
-
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
             .expect("Failed to create AppState");
+        assert_eq!(
+            app_state.indexes.run().await,
+            IndexHealth::Ready,
+            "Failed to create indexes"
+        );
+        conference_limits::spawn(app_state.clone());
         let app = build_router(app_state);
 
         let listener = TcpListener::bind("127.0.0.1:0")
@@ -92,6 +100,10 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        // Fixture tenants create their own rooms; onboarding tests opt back in.
+        settings.onboarding.default_channels.clear();
+        // Webhook tests deliver to fake servers on loopback.
+        settings.outbound.allow_private_targets = true;
 
         // Apply caller's customizations
         mutator(&mut settings);
@@ -103,11 +115,15 @@ impl TestApp {
             Client::with_options(client_options).expect("Failed to create MongoDB client");
         let db = mongo_client.database(&db_name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
-
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
             .expect("Failed to create AppState");
+        assert_eq!(
+            app_state.indexes.run().await,
+            IndexHealth::Ready,
+            "Failed to create indexes"
+        );
+        conference_limits::spawn(app_state.clone());
         let app = build_router(app_state);
 
         let listener = TcpListener::bind("127.0.0.1:0")
@@ -149,6 +165,10 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        // Fixture tenants create their own rooms; onboarding tests opt back in.
+        settings.onboarding.default_channels.clear();
+        // Webhook tests deliver to fake servers on loopback.
+        settings.outbound.allow_private_targets = true;
 
         // Configure fake OAuth provider credentials
         settings.oauth.base_url = "http://localhost:5001".to_string();
@@ -170,11 +190,15 @@ impl TestApp {
             Client::with_options(client_options).expect("Failed to create MongoDB client");
         let db = mongo_client.database(&db_name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
-
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
             .expect("Failed to create AppState");
+        assert_eq!(
+            app_state.indexes.run().await,
+            IndexHealth::Ready,
+            "Failed to create indexes"
+        );
+        conference_limits::spawn(app_state.clone());
         let app = build_router(app_state);
 
         let listener = TcpListener::bind("127.0.0.1:0")
@@ -242,12 +266,14 @@ fn test_settings() -> Settings {
             static_dir: None,
             cors_origins: vec![],
             frontend_url: "http://localhost:5173".to_string(),
+            trusted_proxies: String::new(),
         },
         database: roomler_ai_config::DatabaseSettings {
             url: "mongodb://localhost:27019".to_string(),
             name: "roomler_ai_test".to_string(),
             max_pool_size: Some(5),
             min_pool_size: Some(1),
+            index_build: roomler_ai_config::IndexBuildStrategy::Background,
         },
         jwt: roomler_ai_config::JwtSettings {
             secret: "test-secret-key-for-jwt-signing-minimum-32-chars".to_string(),
@@ -255,6 +281,12 @@ fn test_settings() -> Settings {
             refresh_token_ttl_secs: 604800,
             issuer: "roomler-ai".to_string(),
         },
+        internal_auth: roomler_ai_config::InternalAuthSettings {
+            secret: "test-secret-key-for-internal-service-tokens".to_string(),
+            issuer: "roomler-ai-internal".to_string(),
+            audience: "roomler-ai-api".to_string(),
+            token_ttl_secs: 300,
+        },
         redis: roomler_ai_config::RedisSettings {
             url: "redis://127.0.0.1:6379".to_string(),
         },
@@ -271,6 +303,10 @@ fn test_settings() -> Settings {
             announced_ip: "127.0.0.1".to_string(),
             rtc_min_port: 40000,
             rtc_max_port: 40100,
+            expected_max_transports: 50,
+            udp_reuse_port: false,
+            reconnect_grace_secs: 0,
+            empty_room_grace_secs: 300,
         },
         turn: roomler_ai_config::TurnSettings {
             url: None,
@@ -278,12 +314,28 @@ fn test_settings() -> Settings {
             password: None,
             shared_secret: None,
             force_relay: None,
+            relay_ips: String::new(),
+            accounting_interval_secs: 0,
+            monthly_budget_mb: 0,
         },
         claude: roomler_ai_config::ClaudeSettings {
             api_key: None,
             model: "claude-sonnet-4-5-20250929".to_string(),
             max_tokens: 4096,
         },
+        asr: roomler_ai_config::AsrSettings {
+            url: String::new(),
+            api_key: None,
+            model: "whisper-1".to_string(),
+            timeout_secs: 30,
+            max_voice_note_bytes: 10 * 1024 * 1024,
+            interim_interval_ms: 0,
+            metrics_max_bytes: 1024 * 1024,
+            sample_retention: false,
+            sample_max_confidence: 0.6,
+            sample_max_bytes: 1024 * 1024,
+            switch_drain_ms: 15_000,
+        },
         oauth: roomler_ai_config::OAuthSettings {
             base_url: "http://localhost:5001".to_string(),
             google: roomler_ai_config::OAuthProviderSettings {
@@ -318,15 +370,158 @@ fn test_settings() -> Settings {
             api_key: String::new(),
         },
         email: roomler_ai_config::EmailSettings {
+            provider: roomler_ai_config::EmailProvider::Sendgrid,
             api_key: String::new(),
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_username: String::new(),
+            smtp_password: String::new(),
+            smtp_tls: roomler_ai_config::SmtpTls::Starttls,
             from_email: "test@roomler.ai".to_string(),
             from_name: "Roomler Test".to_string(),
             activation_token_ttl_minutes: 5,
+            password_reset_ttl_minutes: 60,
+            email_change_ttl_minutes: 60,
+            max_attempts: 5,
+            retry_base_secs: 60,
+            queue_interval_secs: 10,
+            mention_digest_delay_secs: 900,
         },
         push: roomler_ai_config::PushSettings {
             vapid_public_key: String::new(),
             vapid_private_key: String::new(),
             contact: "mailto:test@roomler.ai".to_string(),
         },
+        features: roomler_ai_config::FeatureFlagSettings::default(),
+        onboarding: roomler_ai_config::OnboardingSettings {
+            default_channels: Vec::new(),
+            welcome_message: String::new(),
+        },
+        conference_limits: roomler_ai_config::ConferenceLimitSettings {
+            check_interval_secs: 30,
+            warn_before_secs: 300,
+            free: Default::default(),
+            pro: Default::default(),
+            business: Default::default(),
+            enterprise: Default::default(),
+        },
+        reconciliation: roomler_ai_config::ReconciliationSettings {
+            recreate_within_secs: 900,
+        },
+        thread_summary: roomler_ai_config::ThreadSummarySettings {
+            min_replies: 20,
+            regenerate_after_replies: 10,
+        },
+        storage: roomler_ai_config::StorageSettings {
+            backend: roomler_ai_config::StorageBackend::Local,
+            gridfs_bucket: "uploads".to_string(),
+            max_object_size_bytes: 100 * 1024 * 1024,
+            presign_ttl_secs: 900,
+        },
+        message_archive: roomler_ai_config::MessageArchiveSettings {
+            enabled: false,
+            older_than_days: 365,
+            batch_size: 1000,
+            check_interval_secs: 3600,
+        },
+        ws: roomler_ai_config::WsSettings {
+            replay_buffer_size: 256,
+            resume_window_secs: 300,
+            auth_expiry_warning_secs: 60,
+            session_limit_policy: roomler_ai_config::SessionLimitPolicy::EvictOldest,
+        },
+        conference_chat: roomler_ai_config::ConferenceChatSettings {
+            purge_interval_secs: 3600,
+        },
+        message_retention: roomler_ai_config::MessageRetentionSettings {
+            purge_interval_secs: 3600,
+            batch_size: 2,
+        },
+        presence: roomler_ai_config::PresenceSettings {
+            away_after_secs: 300,
+            offline_after_secs: 3600,
+            sweep_interval_secs: 30,
+        },
+        // Generous budgets: every test registers and logs in from 127.0.0.1.
+        rate_limit: roomler_ai_config::RateLimitSettings {
+            enabled: true,
+            api: roomler_ai_config::RateBudget {
+                burst: 1000,
+                per_minute: 6000,
+            },
+            login: roomler_ai_config::RateBudget {
+                burst: 1000,
+                per_minute: 6000,
+            },
+            invite: roomler_ai_config::RateBudget {
+                burst: 1000,
+                per_minute: 6000,
+            },
+            sandbox: roomler_ai_config::RateBudget {
+                burst: 1000,
+                per_minute: 6000,
+            },
+            public: roomler_ai_config::RateBudget {
+                burst: 1000,
+                per_minute: 6000,
+            },
+        },
+        export: roomler_ai_config::ExportSettings {
+            sync_max_messages: 500,
+            max_concurrent_per_tenant: 2,
+        },
+        follow_up: roomler_ai_config::FollowUpSettings {
+            reminder_interval_secs: 60,
+        },
+        channel_digest: roomler_ai_config::ChannelDigestSettings {
+            sweep_interval_secs: 300,
+        },
+        account_deletion: roomler_ai_config::AccountDeletionSettings {
+            grace_days: 30,
+            sweep_interval_secs: 3600,
+        },
+        reaction_rules: roomler_ai_config::ReactionRuleSettings {
+            max_rules_per_tenant: 50,
+            max_fires_per_minute: 30,
+            webhook_timeout_secs: 5,
+        },
+        webhooks: roomler_ai_config::WebhookSettings {
+            max_webhooks_per_tenant: 20,
+            timeout_secs: 5,
+            max_attempts: 3,
+            retry_base_secs: 30,
+            retry_interval_secs: 15,
+        },
+        quick_switch: roomler_ai_config::QuickSwitchSettings { cache_ttl_secs: 30 },
+        analytics: roomler_ai_config::AnalyticsSettings {
+            report_interval_secs: 3600,
+            max_range_days: 366,
+        },
+        assets: roomler_ai_config::AssetSettings {
+            signing_secret: "test-asset-signing-secret".to_string(),
+            signed_url_ttl_secs: 3600,
+        },
+        // No ffmpeg in CI; clips fail at the cut, after validation.
+        recording_clips: roomler_ai_config::RecordingClipSettings {
+            ffmpeg_path: "roomler-test-missing-ffmpeg".to_string(),
+            max_clip_secs: 600,
+            timeout_secs: 30,
+        },
+        recording_playback: roomler_ai_config::RecordingPlaybackSettings {
+            signing_secret: "test-playback-signing-secret".to_string(),
+            url_ttl_secs: 3600,
+        },
+        outbound: roomler_ai_config::OutboundSettings {
+            timeout_secs: 10,
+            connect_timeout_secs: 5,
+            max_attempts: 3,
+            retry_base_ms: 10,
+            breaker_failure_threshold: 5,
+            breaker_open_secs: 30,
+            allow_private_targets: true,
+        },
+        sso: roomler_ai_config::SsoSettings {
+            dns_resolver_url: "https://cloudflare-dns.com/dns-query".to_string(),
+        },
     }
 }
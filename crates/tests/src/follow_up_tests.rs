@@ -0,0 +1,115 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn follow_ups_are_listed_for_assignee_and_reminded_once() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("followup").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), admin)
+        .send()
+        .await
+        .unwrap();
+    let room_url = format!("/api/tenant/{}/room/{}/call/follow-up", tid, room_id);
+
+    let resp = app
+        .auth_post(&room_url, admin)
+        .json(&serde_json::json!({
+            "title": "Send the meeting notes",
+            "assignee_id": tenant.member.id,
+            "due_at": "2020-01-01T09:00:00Z",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["status"], "open");
+    let follow_up_id = created["id"].as_str().unwrap().to_string();
+
+    // Assignees must belong to the tenant.
+    let resp = app
+        .auth_post(&room_url, admin)
+        .json(&serde_json::json!({
+            "title": "Nobody",
+            "assignee_id": bson::oid::ObjectId::new().to_hex(),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // The assignee sees it in their list without being in the room.
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/follow-up?status=open", tid),
+            member,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let mine: Value = resp.json().await.unwrap();
+    assert_eq!(mine["total"], 1);
+    assert_eq!(mine["items"][0]["title"], "Send the meeting notes");
+    let resp = app.auth_get(&room_url, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let sent = roomler_ai_api::follow_ups::send_due_reminders(&state)
+        .await
+        .unwrap();
+    assert_eq!(sent, 1);
+    let sent = roomler_ai_api::follow_ups::send_due_reminders(&state)
+        .await
+        .unwrap();
+    assert_eq!(sent, 0);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    let messages: Value = resp.json().await.unwrap();
+    assert!(messages["items"].as_array().unwrap().iter().any(|m| {
+        m["content"]
+            .as_str()
+            .unwrap()
+            .contains("Send the meeting notes")
+    }));
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/follow-up/{}", tid, follow_up_id),
+            member,
+        )
+        .json(&serde_json::json!({ "status": "done" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let done: Value = resp.json().await.unwrap();
+    assert_eq!(done["status"], "done");
+    assert!(done["completed_at"].is_string());
+    assert!(done["reminded_at"].is_string());
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/follow-up?status=open", tid),
+            member,
+        )
+        .send()
+        .await
+        .unwrap();
+    let mine: Value = resp.json().await.unwrap();
+    assert_eq!(mine["total"], 0);
+}
@@ -0,0 +1,350 @@
+use crate::fixtures::test_app::TestApp;
+use bson::doc;
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+type Ws =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn send(ws: &mut Ws, msg_type: &str, room_id: &str) {
+    let msg = json!({ "type": msg_type, "data": { "room_id": room_id } });
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// Skip messages until `msg_type` arrives.
+async fn expect_event(ws: &mut Ws, msg_type: &str) -> Value {
+    let wait = async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value =
+                serde_json::from_str(msg.to_text().unwrap_or("")).unwrap_or_default();
+            if parsed["type"] == msg_type {
+                return parsed;
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .unwrap_or_else(|_| panic!("{msg_type} not received"))
+}
+
+#[tokio::test]
+async fn guest_link_lets_guests_into_one_conference() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("guestcorp").await;
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&json!({
+            "name": "Customer Call",
+            "media_settings": { "audio_enabled": true, "video_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let code = room["meeting_code"].as_str().unwrap();
+    let link_url = format!(
+        "/api/tenant/{}/room/{}/call/guest-link",
+        tenant.tenant_id, room_id
+    );
+    let join_url = app.url(&format!("/api/guest/conference/{code}/join"));
+    let anon = reqwest::Client::new();
+
+    // No guest link yet
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": "Visitor", "key": "guess" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(&link_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(&link_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let link: Value = resp.json().await.unwrap();
+    let key = link["key"].as_str().unwrap().to_string();
+    assert!(
+        link["url"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("/join/{code}?guest={key}"))
+    );
+    // Asking again returns the same link
+    let again: Value = app
+        .auth_post(&link_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(again["key"], key.as_str());
+
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": "Visitor", "key": "guess" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": "  ", "key": key }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": " Visitor ", "key": key }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let guest: Value = resp.json().await.unwrap();
+    let token = guest["token"].as_str().unwrap().to_string();
+    assert_eq!(guest["room_id"], room_id);
+    assert_eq!(guest["display_name"], "Visitor");
+
+    // The token is no use outside the conference
+    let resp = app.auth_get("/api/tenant", &token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let user_ws = format!("ws://{}/ws?token={}", app.addr, token);
+    assert!(tokio_tungstenite::connect_async(&user_ws).await.is_err());
+
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/start",
+            tenant.tenant_id, room_id
+        ),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let guest_ws = format!("ws://{}/ws?token={}&role=guest", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&guest_ws).await.unwrap();
+    expect_event(&mut ws, "connected").await;
+
+    // Only their own conference, and only its media signaling
+    send(&mut ws, "media:join", &tenant.rooms[0].id).await;
+    expect_event(&mut ws, "media:error").await;
+    send(&mut ws, "typing:start", room_id).await;
+    let error = expect_event(&mut ws, "media:error").await;
+    assert!(
+        error["data"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not available to guests")
+    );
+
+    send(&mut ws, "media:join", room_id).await;
+    expect_event(&mut ws, "media:transport_created").await;
+
+    let participants: Vec<Value> = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/participant",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let listed = participants
+        .iter()
+        .find(|p| p["is_external"] == true)
+        .expect("guest listed as a participant");
+    assert_eq!(listed["display_name"], "Visitor");
+
+    // Guests aren't room members
+    let members: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/member", tenant.tenant_id, room_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!members.to_string().contains("Visitor"));
+
+    // Conference chat, both ways
+    let chat_url = app.url(&format!("/api/guest/conference/{code}/message"));
+    let resp = anon
+        .post(&chat_url)
+        .bearer_auth(&token)
+        .json(&json!({ "content": "Hello from outside" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let event = expect_event(&mut ws, "call:message:create").await;
+    assert_eq!(event["data"]["display_name"], "Visitor");
+
+    let messages: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/message",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(messages["items"][0]["content"], "Hello from outside");
+
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/message",
+            tenant.tenant_id, room_id
+        ),
+        &tenant.member.access_token,
+    )
+    .json(&json!({ "content": "Welcome" }))
+    .send()
+    .await
+    .unwrap();
+    let messages: Value = anon
+        .get(&chat_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(messages["total"], 2);
+
+    // A guest token doesn't reach another conference's chat
+    let resp = anon
+        .get(app.url("/api/guest/conference/000-000-000/message"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // Turning guest access off
+    let resp = app
+        .auth_delete(&link_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    let resp = anon
+        .post(&chat_url)
+        .bearer_auth(&token)
+        .json(&json!({ "content": "Still here?" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": "Visitor", "key": key }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    assert!(tokio_tungstenite::connect_async(&guest_ws).await.is_err());
+}
+
+#[tokio::test]
+async fn guests_need_the_passcode() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("guestpass").await;
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&json!({
+            "name": "Board Meeting",
+            "media_settings": { "audio_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let link: Value = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/guest-link",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    app.db
+        .collection::<bson::Document>("rooms")
+        .update_one(
+            doc! { "_id": bson::oid::ObjectId::parse_str(room_id).unwrap() },
+            doc! { "$set": { "conference_settings.passcode": "1234" } },
+        )
+        .await
+        .unwrap();
+    let join_url = app.url(&format!(
+        "/api/guest/conference/{}/join",
+        link["meeting_code"].as_str().unwrap()
+    ));
+    let anon = reqwest::Client::new();
+
+    for passcode in [None, Some("0000")] {
+        let resp = anon
+            .post(&join_url)
+            .json(&json!({ "display_name": "Auditor", "key": link["key"], "passcode": passcode }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 403);
+    }
+    let resp = anon
+        .post(&join_url)
+        .json(&json!({ "display_name": "Auditor", "key": link["key"], "passcode": "1234" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
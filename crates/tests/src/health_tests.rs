@@ -0,0 +1,92 @@
+use crate::fixtures::fake_asr;
+use crate::fixtures::test_app::TestApp;
+use roomler_ai_config::StorageBackend;
+use serde_json::Value;
+
+async fn ready(app: &TestApp) -> (u16, Value) {
+    let resp = app
+        .client
+        .get(app.url("/health/ready"))
+        .send()
+        .await
+        .unwrap();
+    (resp.status().as_u16(), resp.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn readiness_reports_each_dependency() {
+    let app = TestApp::spawn().await;
+
+    let resp = app
+        .client
+        .get(app.url("/health/live"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let live: Value = resp.json().await.unwrap();
+    assert_eq!(live["status"], "ok");
+
+    let (status, body) = ready(&app).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+    let checks = &body["checks"];
+    assert_eq!(checks["mongo"]["status"], "ok");
+    assert!(checks["mongo"]["latency_ms"].is_u64());
+    assert_eq!(checks["mediasoup"]["status"], "ok");
+    let workers = app.settings.mediasoup.num_workers;
+    assert_eq!(
+        checks["mediasoup"]["detail"],
+        format!("{workers} of {workers} workers alive")
+    );
+    // Local storage and no ASR backend: nothing to ask.
+    assert_eq!(checks["s3"]["status"], "disabled");
+    assert!(checks["s3"]["latency_ms"].is_null());
+    assert_eq!(checks["transcription"]["status"], "disabled");
+}
+
+#[tokio::test]
+async fn transcription_only_degrades_while_s3_fails_readiness() {
+    let asr_url = fake_asr::spawn().await;
+    let asr = asr_url.clone();
+    let app = TestApp::spawn_with_settings(|s| {
+        s.asr.url = asr;
+        s.asr.model = "whisper-large".to_string();
+    })
+    .await;
+    let (status, body) = ready(&app).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "degraded");
+    let transcription = &body["checks"]["transcription"];
+    assert_eq!(transcription["status"], "failed");
+    assert!(
+        transcription["error"]
+            .as_str()
+            .unwrap()
+            .contains("whisper-large")
+    );
+
+    // Nothing listens on the S3 endpoint.
+    let closed = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let app = TestApp::spawn_with_settings(|s| {
+        s.asr.url = asr_url;
+        s.asr.model = "whisper-small".to_string();
+        s.storage.backend = StorageBackend::S3;
+        s.s3.endpoint = format!("http://127.0.0.1:{closed}");
+    })
+    .await;
+    let (status, body) = ready(&app).await;
+    assert_eq!(status, 503);
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["checks"]["s3"]["status"], "failed");
+    assert!(body["checks"]["s3"]["error"].is_string());
+    assert_eq!(body["checks"]["transcription"]["status"], "ok");
+    assert_eq!(body["checks"]["mongo"]["status"], "ok");
+
+    // The older endpoint only looks at indexes.
+    let resp = app.client.get(app.url("/health")).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
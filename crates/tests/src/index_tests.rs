@@ -0,0 +1,126 @@
+use crate::fixtures::test_app::TestApp;
+use mongodb::{IndexModel, options::IndexOptions};
+use roomler_ai_config::IndexBuildStrategy;
+use roomler_ai_db::indexes::{IndexBuilder, IndexHealth};
+use serde_json::Value;
+
+#[tokio::test]
+async fn index_builds_are_reported_and_only_required_failures_are_unhealthy() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("indexes").await;
+    let url = format!("/api/tenant/{}/admin/indexes", tenant.tenant_id);
+
+    let report: Value = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["health"], "ready");
+    assert_eq!(report["strategy"], "background");
+    assert_eq!(report["ready"], report["total"]);
+    let slug = report["indexes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["collection"] == "tenants" && i["name"] == "slug_1")
+        .unwrap();
+    assert_eq!(slug["required"], true);
+    assert_eq!(slug["state"], "ready");
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let health: Value = app
+        .client
+        .get(app.url("/health"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["indexes"], "ready");
+
+    // An existing index with other options is replaced, not left in place.
+    let tenants = app.db.collection::<bson::Document>("tenants");
+    tenants.drop_index("owner_id_1").await.unwrap();
+    tenants
+        .create_index(
+            IndexModel::builder()
+                .keys(bson::doc! { "owner_id": 1 })
+                .options(IndexOptions::builder().sparse(true).build())
+                .build(),
+        )
+        .await
+        .unwrap();
+    let builder = IndexBuilder::new(app.db.clone(), IndexBuildStrategy::Rolling);
+    assert_eq!(builder.run().await, IndexHealth::Ready);
+    let mut existing = tenants.list_indexes().await.unwrap();
+    while existing.advance().await.unwrap() {
+        let index = existing.deserialize_current().unwrap();
+        if index.keys == bson::doc! { "owner_id": 1 } {
+            assert_eq!(index.options.and_then(|o| o.sparse), None);
+        }
+    }
+
+    // A unique index that can't be built makes the instance unhealthy; an
+    // optional one doesn't.
+    let rooms = app.db.collection::<bson::Document>("rooms");
+    rooms.drop_index("meeting_code_1").await.unwrap();
+    rooms
+        .insert_many([
+            bson::doc! { "meeting_code": "dup" },
+            bson::doc! { "meeting_code": "dup" },
+        ])
+        .await
+        .unwrap();
+    let builder = IndexBuilder::new(app.db.clone(), IndexBuildStrategy::Background);
+    let health = builder.run().await;
+    assert_eq!(health, IndexHealth::Failed);
+    assert!(!health.is_healthy());
+    let report = builder.report();
+    assert_eq!(report.failed, 1);
+    let failed = report.indexes.iter().find(|i| i.error.is_some()).unwrap();
+    assert_eq!(failed.name, "meeting_code_1");
+    assert!(failed.required);
+    assert!(IndexHealth::Degraded.is_healthy());
+}
+
+#[tokio::test]
+async fn duplicate_message_nonces_are_cleared_before_the_unique_index() {
+    let app = TestApp::spawn().await;
+    let messages = app.db.collection::<bson::Document>("messages");
+    messages
+        .drop_index("room_id_1_author_id_1_nonce_1")
+        .await
+        .unwrap();
+    let (room_id, author_id) = (bson::oid::ObjectId::new(), bson::oid::ObjectId::new());
+    let ids: Vec<bson::oid::ObjectId> = (0..3).map(|_| bson::oid::ObjectId::new()).collect();
+    messages
+        .insert_many(ids.iter().map(|id| {
+            bson::doc! { "_id": id, "room_id": room_id, "author_id": author_id, "nonce": "dup" }
+        }))
+        .await
+        .unwrap();
+
+    let builder = IndexBuilder::new(app.db.clone(), IndexBuildStrategy::Background);
+    assert_eq!(builder.run().await, IndexHealth::Ready);
+    let filter = bson::doc! { "room_id": room_id, "nonce": "dup" };
+    assert_eq!(messages.count_documents(filter.clone()).await.unwrap(), 1);
+    let kept = messages.find_one(filter).await.unwrap().unwrap();
+    assert_eq!(kept.get_object_id("_id").unwrap(), ids[0]);
+    assert_eq!(
+        messages
+            .count_documents(bson::doc! { "room_id": room_id })
+            .await
+            .unwrap(),
+        3
+    );
+}
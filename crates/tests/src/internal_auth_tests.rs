@@ -0,0 +1,57 @@
+use crate::fixtures::test_app::TestApp;
+use roomler_ai_services::auth::internal::InternalAuthService;
+use serde_json::Value;
+
+#[tokio::test]
+async fn service_tokens_authenticate_internal_calls() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("svcauth").await;
+    let user = &tenant.admin;
+    let url = "/api/internal/whoami";
+
+    let worker = InternalAuthService::new(app.settings.internal_auth.clone());
+    let token = worker.issue("asr", &["roomler-ai-api"]).unwrap();
+    let resp = app.auth_get(url, &token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["service"], "asr");
+    assert_eq!(json["audience"], serde_json::json!(["roomler-ai-api"]));
+
+    // No token, a user's access token, a token for another service, and a
+    // token signed with the user JWT key are all turned away.
+    let resp = app.client.get(app.url(url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = app.auth_get(url, &user.access_token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let token = worker.issue("asr", &["recorder"]).unwrap();
+    let resp = app.auth_get(url, &token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let mut forged = app.settings.internal_auth.clone();
+    forged.secret = app.settings.jwt.secret.clone();
+    let token = InternalAuthService::new(forged)
+        .issue("asr", &["roomler-ai-api"])
+        .unwrap();
+    let resp = app.auth_get(url, &token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // Service tokens don't work as user tokens either.
+    let token = worker.issue("asr", &["roomler-ai-api"]).unwrap();
+    let resp = app.auth_get("/api/auth/me", &token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn service_tokens_are_rejected_without_a_signing_key() {
+    let app = TestApp::spawn_with_settings(|s| s.internal_auth.secret = String::new()).await;
+    let mut worker_settings = app.settings.internal_auth.clone();
+    worker_settings.secret = "a-worker-guessing-the-key".to_string();
+    let token = InternalAuthService::new(worker_settings)
+        .issue("asr", &["roomler-ai-api"])
+        .unwrap();
+    let resp = app
+        .auth_get("/api/internal/whoami", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+}
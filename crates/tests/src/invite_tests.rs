@@ -660,6 +660,261 @@ async fn test_concurrent_accept() {
     );
 }
 
+#[tokio::test]
+async fn test_concurrent_accept_stops_at_max_uses() {
+    let app = TestApp::spawn().await;
+    let seeded = app.seed_tenant("inv19").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/invite", seeded.tenant_id),
+            &seeded.admin.access_token,
+        )
+        .json(&serde_json::json!({ "max_uses": 2 }))
+        .send()
+        .await
+        .unwrap();
+    let invite: Value = resp.json().await.unwrap();
+    assert_eq!(invite["remaining_uses"].as_u64(), Some(2));
+    let code = invite["code"].as_str().unwrap().to_string();
+
+    let mut users = Vec::new();
+    for i in 0..5 {
+        users.push(
+            app.register_user(
+                &format!("r{}@inv19.test", i),
+                &format!("inv19_r{}", i),
+                &format!("Racer {}", i),
+                "Pass123!",
+                None,
+                None,
+            )
+            .await,
+        );
+    }
+
+    let accepts = users.iter().map(|u| {
+        app.auth_post(&format!("/api/invite/{}/accept", code), &u.access_token)
+            .send()
+    });
+    let statuses: Vec<u16> = futures::future::join_all(accepts)
+        .await
+        .into_iter()
+        .map(|r| r.unwrap().status().as_u16())
+        .collect();
+    assert_eq!(
+        statuses.iter().filter(|&&s| s == 200).count(),
+        2,
+        "statuses: {:?}",
+        statuses
+    );
+
+    // Only the winners became members
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/member", seeded.tenant_id),
+            &seeded.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["items"].as_array().unwrap().len(), 4);
+
+    let resp = reqwest::Client::new()
+        .get(app.url(&format!("/api/invite/{}", code)))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["is_valid"].as_bool(), Some(false));
+    assert_eq!(body["status"].as_str(), Some("exhausted"));
+    assert_eq!(body["remaining_uses"].as_u64(), Some(0));
+}
+
+#[tokio::test]
+async fn test_invite_joins_target_channels() {
+    let app = TestApp::spawn().await;
+    let seeded = app.seed_tenant("inv20").await;
+    let engineering = &seeded.rooms[1];
+    let invite_url = format!("/api/tenant/{}/invite", seeded.tenant_id);
+
+    // Unknown channels are refused
+    let resp = app
+        .auth_post(&invite_url, &seeded.admin.access_token)
+        .json(&serde_json::json!({ "join_room_ids": [bson::oid::ObjectId::new().to_hex()] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_post(&invite_url, &seeded.admin.access_token)
+        .json(&serde_json::json!({
+            "max_uses": 3,
+            "join_room_ids": [engineering.id],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let invite: Value = resp.json().await.unwrap();
+    assert_eq!(
+        invite["join_room_ids"][0].as_str(),
+        Some(engineering.id.as_str())
+    );
+    let code = invite["code"].as_str().unwrap();
+
+    let resp = reqwest::Client::new()
+        .get(app.url(&format!("/api/invite/{}", code)))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["rooms"], serde_json::json!(["engineering"]));
+    assert_eq!(body["max_uses"].as_u64(), Some(3));
+    assert_eq!(body["remaining_uses"].as_u64(), Some(3));
+    assert!(body["expires_at"].is_string());
+
+    let new_user = app
+        .register_user(
+            "new@inv20.test",
+            "inv20_new",
+            "New User",
+            "Pass123!",
+            None,
+            None,
+        )
+        .await;
+    let resp = app
+        .auth_post(
+            &format!("/api/invite/{}/accept", code),
+            &new_user.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room", seeded.tenant_id),
+            &new_user.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let rooms: Vec<Value> = resp.json().await.unwrap();
+    let names: Vec<&str> = rooms.iter().map(|r| r["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"engineering"), "rooms: {:?}", names);
+    assert!(!names.contains(&"random"), "rooms: {:?}", names);
+
+    let resp = reqwest::Client::new()
+        .get(app.url(&format!("/api/invite/{}", code)))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["remaining_uses"].as_u64(), Some(2));
+}
+
+#[tokio::test]
+async fn test_invite_private_channels_need_inviter_membership() {
+    let app = TestApp::spawn().await;
+    let seeded = app.seed_tenant("inv21").await;
+    let invite_url = format!("/api/tenant/{}/invite", seeded.tenant_id);
+    let room_url = format!("/api/tenant/{}/room", seeded.tenant_id);
+
+    // A private channel the inviter isn't in can't be handed out
+    let private: Value = app
+        .auth_post(&room_url, &seeded.member.access_token)
+        .json(&serde_json::json!({ "name": "hidden" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_post(&invite_url, &seeded.admin.access_token)
+        .json(&serde_json::json!({ "join_room_ids": [private["id"]] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // One the inviter is in can, but its name isn't shown publicly
+    let secret: Value = app
+        .auth_post(&room_url, &seeded.admin.access_token)
+        .json(&serde_json::json!({ "name": "secret" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let secret_id = secret["id"].as_str().unwrap();
+    let resp = app
+        .auth_post(&invite_url, &seeded.admin.access_token)
+        .json(&serde_json::json!({ "join_room_ids": [secret_id, seeded.rooms[1].id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let invite: Value = resp.json().await.unwrap();
+    let code = invite["code"].as_str().unwrap();
+    let body: Value = reqwest::Client::new()
+        .get(app.url(&format!("/api/invite/{}", code)))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["rooms"], serde_json::json!(["engineering"]));
+
+    // Once the inviter leaves, accepting no longer joins it
+    let resp = app
+        .auth_post(
+            &format!("{}/{}/leave", room_url, secret_id),
+            &seeded.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let new_user = app
+        .register_user(
+            "new@inv21.test",
+            "inv21_new",
+            "New User",
+            "Pass123!",
+            None,
+            None,
+        )
+        .await;
+    let resp = app
+        .auth_post(
+            &format!("/api/invite/{}/accept", code),
+            &new_user.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let rooms: Vec<Value> = app
+        .auth_get(&room_url, &new_user.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<&str> = rooms.iter().map(|r| r["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"engineering"), "rooms: {:?}", names);
+    assert!(!names.contains(&"secret"), "rooms: {:?}", names);
+}
+
 // ─── Permission Tests ───────────────────────────────────────────
 
 #[tokio::test]
@@ -720,3 +975,144 @@ async fn test_revoke_invite_no_permission() {
         .unwrap();
     assert_eq!(resp.status().as_u16(), 403);
 }
+
+// ─── Bulk Import ────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_member_import_adds_existing_users_and_invites_the_rest() {
+    let app = TestApp::spawn().await;
+    let seeded = app.seed_tenant("invimport").await;
+    let token = &seeded.admin.access_token;
+    let url = format!("/api/tenant/{}/member/import", seeded.tenant_id);
+    let outsider = app
+        .register_user(
+            "outsider@invimport.test",
+            "invimport_outsider",
+            "Outsider",
+            "Outsider123!",
+            None,
+            None,
+        )
+        .await;
+
+    // Members without INVITE_MEMBERS can't import.
+    let resp = app
+        .auth_post(&url, &seeded.member.access_token)
+        .body("email\nsomeone@invimport.test\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // A file without an email column is rejected outright.
+    let resp = app
+        .auth_post(&url, token)
+        .body("name\nNobody\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let csv = "email,name,roles\n\
+               Outsider@invimport.test,Outsider,member\n\
+               new@invimport.test,\"Newbie, Jr\",\n\
+               member@invimport.test,,member\n\
+               not-an-email,,\n\
+               x@invimport.test,,nonexistent\n";
+    let resp = app
+        .auth_post(&url, token)
+        .header("Content-Type", "text/csv")
+        .body(csv)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["rows"], 5);
+    assert_eq!(json["invalid"], 2);
+    let task_id = json["task_id"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(
+                &format!("/api/tenant/{}/task/{}", seeded.tenant_id, task_id),
+                token,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Completed" => {
+                completed = true;
+                break;
+            }
+            "Failed" => panic!("Import task failed: {:?}", json["error"]),
+            _ => {}
+        }
+    }
+    assert!(completed, "Import task did not complete within timeout");
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/task/{}/download", seeded.tenant_id, task_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let report = resp.text().await.unwrap();
+    let outcomes: Vec<(&str, &str, &str)> = report
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let mut fields = line.splitn(4, ',');
+            (
+                fields.next().unwrap(),
+                fields.next().unwrap(),
+                fields.next().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("2", "outsider@invimport.test", "added"),
+            ("3", "new@invimport.test", "invited"),
+            ("4", "member@invimport.test", "already_member"),
+            ("5", "not-an-email", "invalid"),
+            ("6", "x@invimport.test", "invalid"),
+        ]
+    );
+
+    // The existing user is now a member; the new address holds an invite.
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}", seeded.tenant_id),
+            &outsider.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let invites: Value = app
+        .auth_get(&format!("/api/tenant/{}/invite", seeded.tenant_id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        invites["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|i| i["target_email"] == "new@invimport.test")
+    );
+}
@@ -1,27 +1,91 @@
 pub mod fixtures;
 
+#[cfg(test)]
+mod account_deletion_tests;
+#[cfg(test)]
+mod analytics_tests;
+#[cfg(test)]
+mod asr_backend_tests;
+#[cfg(test)]
+mod asset_tests;
+#[cfg(test)]
+mod audit_tests;
 #[cfg(test)]
 mod auth_tests;
 #[cfg(test)]
+mod bot_tests;
+#[cfg(test)]
+mod calendar_tests;
+#[cfg(test)]
 mod channel_crud_tests;
 #[cfg(test)]
+mod channel_digest_tests;
+#[cfg(test)]
 mod channel_tests;
 #[cfg(test)]
+mod conference_limits_tests;
+#[cfg(test)]
+mod conference_lobby_tests;
+#[cfg(test)]
 mod conference_message_tests;
 #[cfg(test)]
 mod conference_tests;
 #[cfg(test)]
+mod delivery_metrics_tests;
+#[cfg(test)]
+mod dm_tests;
+#[cfg(test)]
+mod email_tests;
+#[cfg(test)]
 mod export_tests;
 #[cfg(test)]
+mod feature_flag_tests;
+#[cfg(test)]
 mod file_tests;
 #[cfg(test)]
+mod follow_up_tests;
+#[cfg(test)]
+mod guest_tests;
+#[cfg(test)]
+mod health_tests;
+#[cfg(test)]
+mod index_tests;
+#[cfg(test)]
+mod internal_auth_tests;
+#[cfg(test)]
+mod media_constraints_tests;
+#[cfg(test)]
+mod media_ports_tests;
+#[cfg(test)]
+mod message_retention_tests;
+#[cfg(test)]
 mod message_tests;
 #[cfg(test)]
 mod multi_tenancy_tests;
 #[cfg(test)]
+mod onboarding_tests;
+#[cfg(test)]
+mod outbound_tests;
+#[cfg(test)]
+mod preflight_tests;
+#[cfg(test)]
+mod profanity_tests;
+#[cfg(test)]
+mod public_channel_tests;
+#[cfg(test)]
+mod quick_switch_tests;
+#[cfg(test)]
+mod reaction_rule_tests;
+#[cfg(test)]
 mod reaction_tests;
 #[cfg(test)]
 mod recording_tests;
+#[cfg(test)]
+mod sso_tests;
+#[cfg(test)]
+mod tenant_config_tests;
+#[cfg(test)]
+mod video_effects_tests;
 
 #[cfg(test)]
 mod agent_tests;
@@ -47,3 +111,15 @@ mod rate_limit_tests;
 mod remote_control_tests;
 #[cfg(test)]
 mod role_tests;
+#[cfg(test)]
+mod sandbox_tests;
+#[cfg(test)]
+mod search_tests;
+#[cfg(test)]
+mod session_limit_tests;
+#[cfg(test)]
+mod shared_draft_tests;
+#[cfg(test)]
+mod webhook_tests;
+#[cfg(test)]
+mod ws_auth_tests;
@@ -0,0 +1,57 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn media_constraints_default_to_plan_and_clamp_overrides() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("mediaconstraints").await;
+    let url = format!("/api/tenant/{}/media-constraints", tenant.tenant_id);
+
+    // Members can read; a new tenant is on the Free plan.
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["effective"]["max_video_width"], 640);
+    assert_eq!(json["effective"]["max_video_height"], 360);
+    assert_eq!(json["effective"]["max_frame_rate"], 15);
+    assert_eq!(json["effective"]["echo_cancellation"], true);
+
+    // Overrides can tighten but never exceed the plan.
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({
+            "noise_suppression": false,
+            "max_video_width": 320,
+            "max_frame_rate": 60,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["effective"]["noise_suppression"], false);
+    assert_eq!(json["effective"]["max_video_width"], 320);
+    assert_eq!(json["effective"]["max_video_height"], 360);
+    assert_eq!(json["effective"]["max_frame_rate"], 15);
+    assert_eq!(json["overrides"]["max_frame_rate"], 60);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "max_video_height": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_put(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "max_video_width": 320 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
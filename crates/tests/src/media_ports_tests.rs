@@ -0,0 +1,37 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn media_ports_reports_worker_slices_and_usage() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.mediasoup.num_workers = 1;
+        s.mediasoup.rtc_min_port = 40000;
+        s.mediasoup.rtc_max_port = 40099;
+        s.mediasoup.expected_max_transports = 10;
+    })
+    .await;
+    let tenant = app.seed_tenant("mediaports").await;
+    let url = format!("/api/tenant/{}/admin/media-ports", tenant.tenant_id);
+
+    let resp = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    let workers = json["workers"].as_array().unwrap();
+    assert_eq!(workers.len(), 1);
+    assert_eq!(workers[0]["min_port"], 40000);
+    assert_eq!(workers[0]["max_port"], 40099);
+    assert_eq!(json["total_capacity"], 100);
+    assert_eq!(json["total_transports"], 0);
+
+    // Members without MANAGE_TENANT are rejected.
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
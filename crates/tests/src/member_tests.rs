@@ -1,5 +1,7 @@
 use crate::fixtures::test_app::TestApp;
+use futures::{SinkExt, StreamExt};
 use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
 
 #[tokio::test]
 async fn list_room_members_returns_paginated_items_with_user_details() {
@@ -216,3 +218,312 @@ async fn create_message_with_everyone_mention() {
     );
     assert_eq!(msg["content"].as_str().unwrap(), "Attention @everyone!");
 }
+
+#[tokio::test]
+async fn content_mentions_are_stored_notified_and_listed_in_the_inbox() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("inbox").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    for token in [admin, member] {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), token)
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, member);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    ws.next().await; // connected
+
+    let post = |content: String| {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "content": content }))
+        .send()
+    };
+    let named: Value = post(format!(
+        "Hi @{}. Not admin@example.com or `@{}`",
+        tenant.member.username.to_uppercase(),
+        tenant.admin.username
+    ))
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    assert_eq!(
+        named["mentions"]["users"],
+        serde_json::json!([tenant.member.id])
+    );
+    assert_eq!(named["mentions"]["everyone"], false);
+
+    let event = loop {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws.next())
+            .await
+            .expect("Timed out waiting for notification:mention")
+            .unwrap()
+            .unwrap();
+        let event: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        if event["type"] == "notification:mention" {
+            break event;
+        }
+    };
+    assert_eq!(event["data"]["message_id"], named["id"]);
+    assert_eq!(event["data"]["room_id"], room_id.as_str());
+    assert_eq!(event["data"]["author_id"], tenant.admin.id);
+
+    let channel: Value = post("@channel standup in 5".to_string())
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(channel["mentions"]["everyone"], true);
+    post("No one in particular".to_string()).await.unwrap();
+
+    let inbox_url = format!("/api/tenant/{}/mentions", tid);
+    let inbox: Value = app
+        .auth_get(&inbox_url, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inbox["total"], 2);
+    assert_eq!(inbox["unread_count"], 2);
+    assert_eq!(inbox["items"][0]["message"]["id"], channel["id"]);
+    assert_eq!(inbox["items"][0]["unread"], true);
+    assert_eq!(inbox["items"][0]["room_name"], "general");
+    assert_eq!(inbox["items"][1]["message"]["id"], named["id"]);
+
+    // Reading the room clears the counter but keeps the history.
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/read", tid, room_id),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+    let inbox: Value = app
+        .auth_get(&inbox_url, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inbox["total"], 2);
+    assert_eq!(inbox["unread_count"], 0);
+    assert_eq!(inbox["items"][0]["unread"], false);
+
+    // Authors aren't mentioned by their own @channel.
+    let own: Value = app
+        .auth_get(&inbox_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(own["total"], 0);
+}
+
+#[tokio::test]
+async fn presence_is_tracked_and_scoped_to_tenant_members() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("presence1").await;
+    let outsider = app.seed_tenant("presence2").await.admin;
+
+    type Ws = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+    async fn connect(app: &TestApp, token: &str) -> Ws {
+        let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+        let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+        ws.next().await; // connected
+        ws
+    }
+    async fn next_json(ws: &mut Ws) -> Value {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws.next())
+            .await
+            .expect("Timed out waiting for WS message")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(msg.to_text().unwrap()).unwrap()
+    }
+    async fn send_json(ws: &mut Ws, value: Value) {
+        ws.send(Message::Text(value.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    let mut ws_outsider = connect(&app, &outsider.access_token).await;
+    let mut ws_admin = connect(&app, &tenant.admin.access_token).await;
+    let mut ws_member = connect(&app, &tenant.member.access_token).await;
+
+    let update = next_json(&mut ws_admin).await;
+    assert_eq!(update["type"], "presence:update");
+    assert_eq!(update["data"]["user_id"], tenant.member.id);
+    assert_eq!(update["data"]["presence"], "online");
+
+    send_json(
+        &mut ws_member,
+        serde_json::json!({ "type": "presence:update", "data": { "presence": "dnd" } }),
+    )
+    .await;
+    let update = next_json(&mut ws_admin).await;
+    assert_eq!(update["data"]["presence"], "dnd");
+    let own = next_json(&mut ws_member).await;
+    assert_eq!(own["data"]["presence"], "dnd");
+
+    // Nothing about another tenant's members reaches the outsider.
+    send_json(&mut ws_outsider, serde_json::json!({ "type": "ping" })).await;
+    assert_eq!(next_json(&mut ws_outsider).await["type"], "pong");
+
+    let url = format!("/api/tenant/{}/presence", tenant.tenant_id);
+    let presence: Value = app
+        .auth_get(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let of = |presence: &Value, id: &str| {
+        presence
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["user_id"] == id)
+            .unwrap()["presence"]
+            .clone()
+    };
+    assert_eq!(of(&presence, &tenant.admin.id), "online");
+    assert_eq!(of(&presence, &tenant.member.id), "dnd");
+
+    let resp = app
+        .auth_get(&url, &outsider.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Invisible users look offline to everyone but themselves.
+    send_json(
+        &mut ws_member,
+        serde_json::json!({ "type": "presence:update", "data": { "presence": "invisible" } }),
+    )
+    .await;
+    assert_eq!(
+        next_json(&mut ws_admin).await["data"]["presence"],
+        "offline"
+    );
+    assert_eq!(
+        next_json(&mut ws_member).await["data"]["presence"],
+        "invisible"
+    );
+    for (token, expected) in [
+        (&tenant.admin.access_token, "offline"),
+        (&tenant.member.access_token, "invisible"),
+    ] {
+        let presence: Value = app
+            .auth_get(&url, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(of(&presence, &tenant.member.id), expected);
+    }
+
+    // Disconnecting takes a visible user offline.
+    ws_admin.close(None).await.ok();
+    let update = next_json(&mut ws_member).await;
+    assert_eq!(update["data"]["user_id"], tenant.admin.id);
+    assert_eq!(update["data"]["presence"], "offline");
+
+    ws_member.close(None).await.ok();
+    ws_outsider.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn room_members_filter_by_role_status_and_name() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("memberfilter").await;
+    let room_url = format!(
+        "/api/tenant/{}/room/{}",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let admin = &tenant.admin.access_token;
+    for token in [admin, &tenant.member.access_token] {
+        app.auth_post(&format!("{}/join", room_url), token)
+            .send()
+            .await
+            .unwrap();
+    }
+    let resp = app
+        .auth_put(
+            &format!("{}/member/{}/role", room_url, tenant.member.id),
+            admin,
+        )
+        .json(&serde_json::json!({ "role": "guest" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let list = |query: &str| {
+        app.auth_get(&format!("{}/member?{}", room_url, query), admin)
+            .send()
+    };
+    let usernames = |json: &Value| -> Vec<String> {
+        json["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["username"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let json: Value = list("role=guest").await.unwrap().json().await.unwrap();
+    assert_eq!(usernames(&json), vec![tenant.member.username.clone()]);
+    assert_eq!(json["total"], 1);
+
+    // Nobody is connected, so everyone is offline.
+    let json: Value = list("status=online").await.unwrap().json().await.unwrap();
+    assert_eq!(json["total"], 0);
+    let json: Value = list("status=offline&per_page=1&page=2")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 2);
+    assert_eq!(json["total_pages"], 2);
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+
+    let query = format!("q={}", tenant.admin.username.to_uppercase());
+    let json: Value = list(&query).await.unwrap().json().await.unwrap();
+    assert_eq!(usernames(&json), vec![tenant.admin.username.clone()]);
+
+    let json: Value = list("count_only=true&role=guest,member")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(json["total"].as_u64().unwrap() >= 1);
+    assert!(json.get("items").is_none());
+
+    for query in ["role=admin", "status=invisible"] {
+        let resp = list(query).await.unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{}", query);
+    }
+}
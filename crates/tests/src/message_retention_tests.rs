@@ -0,0 +1,344 @@
+use crate::fixtures::test_app::TestApp;
+use bson::oid::ObjectId;
+use futures::TryStreamExt;
+use serde_json::Value;
+
+const DAY_MS: i64 = 86_400_000;
+
+async fn post_message(app: &TestApp, url: &str, token: &str, body: Value) -> String {
+    let json: Value = app
+        .auth_post(url, token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    json["id"].as_str().unwrap().to_string()
+}
+
+async fn backdate(app: &TestApp, message_id: &str, days: i64) {
+    let created_at =
+        bson::DateTime::from_millis(bson::DateTime::now().timestamp_millis() - days * DAY_MS);
+    app.db
+        .collection::<bson::Document>("messages")
+        .update_one(
+            bson::doc! { "_id": ObjectId::parse_str(message_id).unwrap() },
+            bson::doc! { "$set": { "created_at": created_at } },
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn retention_policies_purge_old_and_excess_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgretention").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let (aged, capped) = (&tenant.rooms[0].id, &tenant.rooms[1].id);
+    for rid in [aged, capped] {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, rid), token)
+            .send()
+            .await
+            .unwrap();
+    }
+    let tenant_url = format!("/api/tenant/{}/message-retention", tid);
+    let room_url = |rid: &str| format!("/api/tenant/{}/room/{}/message-retention", tid, rid);
+    let messages_url = |rid: &str| format!("/api/tenant/{}/room/{}/message", tid, rid);
+
+    // Only MANAGE_TENANT can set policies, and limits must be positive.
+    let resp = app
+        .auth_put(&tenant_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "max_age_days": 30 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    for body in [
+        serde_json::json!({ "max_age_days": 0 }),
+        serde_json::json!({ "max_count": 0 }),
+    ] {
+        let resp = app
+            .auth_put(&room_url(capped), token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{body}");
+    }
+
+    let json: Value = app
+        .auth_put(&tenant_url, token)
+        .json(&serde_json::json!({ "max_age_days": 30, "archive": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["max_age_days"], 30);
+    assert_eq!(json["archive"], true);
+    assert_eq!(json["plan_max_messages"], 5000);
+
+    let json: Value = app
+        .auth_put(&room_url(capped), token)
+        .json(&serde_json::json!({ "max_count": 2 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["override"]["max_count"], 2);
+    assert_eq!(json["tenant"]["max_age_days"], 30);
+    assert!(json["effective"]["max_age_days"].is_null());
+    assert_eq!(json["effective"]["max_count"], 2);
+
+    // An old thread with a fresh reply and a reaction, next to a recent
+    // message, in the room under the tenant's policy.
+    let old_id = post_message(
+        &app,
+        &messages_url(aged),
+        token,
+        serde_json::json!({ "content": "old" }),
+    )
+    .await;
+    post_message(
+        &app,
+        &messages_url(aged),
+        token,
+        serde_json::json!({ "content": "reply", "thread_id": &old_id }),
+    )
+    .await;
+    app.auth_post(
+        &format!("{}/{}/reaction", messages_url(aged), old_id),
+        token,
+    )
+    .json(&serde_json::json!({ "emoji": "\u{1f44d}" }))
+    .send()
+    .await
+    .unwrap();
+    backdate(&app, &old_id, 40).await;
+    post_message(
+        &app,
+        &messages_url(aged),
+        token,
+        serde_json::json!({ "content": "recent" }),
+    )
+    .await;
+
+    // Four old messages in the room keeping its newest two.
+    for n in 0..4 {
+        let id = post_message(
+            &app,
+            &messages_url(capped),
+            token,
+            serde_json::json!({ "content": format!("message {n}") }),
+        )
+        .await;
+        backdate(&app, &id, 100 - n).await;
+    }
+
+    let resp = app
+        .auth_post(&format!("{}/run", tenant_url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    let task_id = json["task_id"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(&format!("/api/tenant/{}/task/{}", tid, task_id), token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Completed" => {
+                completed = true;
+                break;
+            }
+            "Failed" => panic!("Retention run failed: {:?}", json["error"]),
+            _ => {}
+        }
+    }
+    assert!(completed, "Retention run did not complete within timeout");
+
+    let messages = app.db.collection::<bson::Document>("messages");
+    let left = |rid: &str| {
+        messages.count_documents(bson::doc! { "room_id": ObjectId::parse_str(rid).unwrap() })
+    };
+    assert_eq!(left(aged).await.unwrap(), 1);
+    assert_eq!(left(capped).await.unwrap(), 2);
+    let reactions = app
+        .db
+        .collection::<bson::Document>("reactions")
+        .count_documents(bson::doc! { "message_id": ObjectId::parse_str(&old_id).unwrap() })
+        .await
+        .unwrap();
+    assert_eq!(reactions, 0);
+
+    let json: Value = app
+        .auth_get(&messages_url(capped), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 2);
+
+    // Each purge is audited against its room; only the tenant's policy
+    // archives.
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?action=room.message_purge", tid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["total"], 2);
+    let entry = |rid: &str| {
+        audit["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["target_id"] == rid)
+            .unwrap()
+            .clone()
+    };
+    let aged_entry = entry(aged);
+    assert_eq!(aged_entry["actor_type"], "system");
+    assert_eq!(aged_entry["changes"][0]["field"], "purged");
+    assert_eq!(aged_entry["changes"][0]["new_value"], 2);
+    assert_eq!(aged_entry["changes"][1]["new_value"], 2);
+    let capped_entry = entry(capped);
+    assert_eq!(capped_entry["changes"][0]["new_value"], 2);
+    assert_eq!(capped_entry["changes"][1]["new_value"], 0);
+
+    // Clearing the override puts the room back under the tenant's policy.
+    let json: Value = app
+        .auth_delete(&room_url(capped), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(json["override"].is_null());
+    assert_eq!(json["effective"]["max_age_days"], 30);
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let purged = roomler_ai_api::message_retention::purge_expired(&state)
+        .await
+        .unwrap();
+    assert_eq!(purged, 2);
+    assert_eq!(left(capped).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn plan_history_cap_counts_archived_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgcap").await;
+    let tid = ObjectId::parse_str(&tenant.tenant_id).unwrap();
+    let room_id = ObjectId::parse_str(&tenant.rooms[0].id).unwrap();
+    let author_id = ObjectId::parse_str(&tenant.admin.id).unwrap();
+
+    // One message more than the free plan keeps, an hour apart, with the
+    // older half moved to the monthly archives.
+    let now_ms = bson::DateTime::now().timestamp_millis();
+    let docs: Vec<bson::Document> = (0..5001i64)
+        .map(|n| {
+            bson::doc! {
+                "tenant_id": tid,
+                "room_id": room_id,
+                "author_id": author_id,
+                "content": format!("message {n}"),
+                "created_at": bson::DateTime::from_millis(now_ms - n * 3_600_000),
+            }
+        })
+        .collect();
+    let messages = app.db.collection::<bson::Document>("messages");
+    messages.insert_many(docs).await.unwrap();
+
+    let archiver = roomler_ai_services::message_archive::MessageArchiver::new(
+        &app.db,
+        &app.settings.message_archive,
+    );
+    let moved = archiver
+        .sweep(bson::DateTime::from_millis(now_ms - 2500 * 3_600_000))
+        .await
+        .unwrap();
+    assert!(moved > 0);
+
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    let report = roomler_ai_api::message_retention::purge_tenant(&state, tid)
+        .await
+        .unwrap();
+    assert_eq!(report.purged, 1);
+    assert!(report.archived.is_empty());
+
+    let partitions: Vec<roomler_ai_db::models::MessageArchivePartition> = app
+        .db
+        .collection("message_archive_partitions")
+        .find(bson::doc! { "room_id": room_id })
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let archived: u64 = partitions.iter().map(|p| p.count).sum();
+    let hot = messages
+        .count_documents(bson::doc! { "room_id": room_id })
+        .await
+        .unwrap();
+    assert_eq!(hot + archived, 5000);
+    for partition in &partitions {
+        let gone = app
+            .db
+            .collection::<bson::Document>(&partition.collection)
+            .count_documents(bson::doc! { "content": "message 5000" })
+            .await
+            .unwrap();
+        assert_eq!(gone, 0);
+    }
+
+    let audit: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/audit?action=tenant.message_purge",
+                tenant.tenant_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["total"], 1);
+    assert!(audit["items"][0]["target_id"].is_null());
+
+    // Already within the cap.
+    let purged = roomler_ai_api::message_retention::purge_expired(&state)
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+}
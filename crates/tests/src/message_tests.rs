@@ -110,6 +110,61 @@ async fn update_message() {
     assert_eq!(json["is_edited"], true);
 }
 
+#[tokio::test]
+async fn message_shortcodes_are_expanded_or_rejected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgemoji").await;
+    let room_id = &tenant.rooms[0].id;
+    let url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_post(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "ship it :Rocket: at 10:30:00 `:nope:`" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let msg: Value = resp.json().await.unwrap();
+    assert_eq!(msg["content"], "ship it \u{1f680} at 10:30:00 `:nope:`");
+    let message_id = msg["id"].as_str().unwrap();
+
+    // Unknown shortcodes are rejected with suggestions
+    let resp = app
+        .auth_post(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "great :thumbsupp:" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let err: Value = resp.json().await.unwrap();
+    assert!(
+        err["message"].as_str().unwrap().contains(":thumbsup:"),
+        "{err}"
+    );
+
+    // Edits are expanded too
+    let resp = app
+        .auth_put(
+            &format!("{}/{}", url, message_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "content": "shipped :tada:" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["content"], "shipped \u{1f389}");
+}
+
 #[tokio::test]
 async fn delete_message_soft_deletes() {
     let app = TestApp::spawn().await;
@@ -171,6 +226,101 @@ async fn delete_message_soft_deletes() {
     assert_eq!(json["items"].as_array().unwrap().len(), 0);
 }
 
+/// A cross-post lands in every target room at once, or nowhere when any
+/// target forbids posting; edits and the author's delete reach all copies.
+#[tokio::test]
+async fn cross_posts_are_checked_linked_and_kept_in_sync() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("xpost").await;
+    let token = &tenant.member.access_token;
+    let [origin, other, read_only] = [0, 1, 2].map(|i| tenant.rooms[i].id.as_str());
+    let messages =
+        |room_id: &str| format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+    let count = async |room_id: &str| -> u64 {
+        let json: Value = app
+            .auth_get(&messages(room_id), token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        json["total"].as_u64().unwrap()
+    };
+
+    app.auth_put(
+        &format!("/api/tenant/{}/room/{}", tenant.tenant_id, read_only),
+        &tenant.admin.access_token,
+    )
+    .json(&serde_json::json!({ "is_read_only": true }))
+    .send()
+    .await
+    .unwrap();
+
+    // One forbidden target stops the whole post.
+    let resp = app
+        .auth_post(&messages(origin), token)
+        .json(&serde_json::json!({
+            "content": "release notes",
+            "cross_post_room_ids": [other, read_only],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    assert_eq!(count(origin).await, 0);
+    assert_eq!(count(other).await, 0);
+
+    let resp = app
+        .auth_post(&messages(origin), token)
+        .json(&serde_json::json!({
+            "content": "release notes",
+            "cross_post_room_ids": [other, other, origin],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let msg: Value = resp.json().await.unwrap();
+    let group_id = msg["cross_post_group_id"].as_str().unwrap();
+    let copies = msg["cross_posts"].as_array().unwrap();
+    assert_eq!(copies.len(), 1);
+    assert_eq!(copies[0]["room_id"], other);
+    let copy_id = copies[0]["message_id"].as_str().unwrap();
+
+    // Editing either copy edits both.
+    let resp = app
+        .auth_put(&format!("{}/{}", messages(other), copy_id), token)
+        .json(&serde_json::json!({ "content": "release notes v2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = app
+        .auth_get(&messages(origin), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["items"][0]["content"], "release notes v2");
+    assert_eq!(json["items"][0]["cross_post_group_id"], group_id);
+    assert_eq!(json["items"][0]["is_edited"], true);
+
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", messages(origin), msg["id"].as_str().unwrap()),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(count(origin).await, 0);
+    assert_eq!(count(other).await, 0);
+}
+
 #[tokio::test]
 async fn message_broadcast_excludes_sender_reaches_member() {
     let app = TestApp::spawn().await;
@@ -201,13 +351,12 @@ async fn message_broadcast_excludes_sender_reaches_member() {
     let (mut ws_admin, _) = tokio_tungstenite::connect_async(&ws_url_admin)
         .await
         .unwrap();
+    ws_admin.next().await; // connected
     let (mut ws_member, _) = tokio_tungstenite::connect_async(&ws_url_member)
         .await
         .unwrap();
-
-    // Drain "connected" messages
-    ws_admin.next().await;
-    ws_member.next().await;
+    ws_member.next().await; // connected
+    ws_admin.next().await; // presence:update — the member came online
 
     // Admin sends a message via HTTP
     let resp = app
@@ -258,3 +407,804 @@ async fn message_broadcast_excludes_sender_reaches_member() {
     ws_admin.close(None).await.ok();
     ws_member.close(None).await.ok();
 }
+
+#[tokio::test]
+async fn thread_summary_requires_min_replies_and_claude() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.thread_summary.min_replies = 2;
+    })
+    .await;
+    let tenant = app.seed_tenant("threadsum").await;
+    let room_id = &tenant.rooms[0].id;
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let root: Value = app
+        .auth_post(&messages_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "Where should the offsite be?" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let root_id = root["id"].as_str().unwrap();
+    let summarize_url = format!("{}/{}/thread/summarize", messages_url, root_id);
+
+    let reply = |content: &'static str| {
+        app.auth_post(&messages_url, &tenant.admin.access_token)
+            .json(&serde_json::json!({ "content": content, "thread_id": root_id }))
+            .send()
+    };
+
+    reply("Lisbon?").await.unwrap();
+    let resp = app
+        .auth_post(&summarize_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Long enough now, but the test config has no Claude API key.
+    reply("Lisbon works, booking next week.").await.unwrap();
+    let resp = app
+        .auth_post(&summarize_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn typing_and_read_receipts_respect_privacy_settings() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgprivacy").await;
+    let room_id = &tenant.rooms[0].id;
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    // Defaults hide nothing.
+    let resp = app
+        .auth_get("/api/auth/me/preferences", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["privacy"]["hide_typing"], false);
+    assert_eq!(json["notifications"]["email"], true);
+
+    let resp = app
+        .auth_put("/api/auth/me/preferences", &tenant.member.access_token)
+        .json(&serde_json::json!({
+            "privacy": { "hide_typing": true, "hide_read_receipts": true },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["privacy"]["hide_typing"], true);
+    assert_eq!(json["privacy"]["hide_read_receipts"], true);
+    assert_eq!(json["privacy"]["hide_presence"], false);
+
+    let ws_url_admin = format!("ws://{}/ws?token={}", app.addr, tenant.admin.access_token);
+    let ws_url_member = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws_admin, _) = tokio_tungstenite::connect_async(&ws_url_admin)
+        .await
+        .unwrap();
+    ws_admin.next().await;
+    let (mut ws_member, _) = tokio_tungstenite::connect_async(&ws_url_member)
+        .await
+        .unwrap();
+    ws_member.next().await;
+    ws_admin.next().await; // presence:update — the member came online
+
+    let post_message = |content: &'static str| {
+        let app = &app;
+        let url = messages_url.clone();
+        let token = tenant.admin.access_token.clone();
+        async move {
+            let resp = app
+                .auth_post(&url, &token)
+                .json(&serde_json::json!({ "content": content }))
+                .send()
+                .await
+                .unwrap();
+            let json: Value = resp.json().await.unwrap();
+            json["id"].as_str().unwrap().to_string()
+        }
+    };
+    let mark_read = |message_id: String| {
+        let app = &app;
+        let url = format!("{}/read", messages_url);
+        let token = tenant.member.access_token.clone();
+        async move {
+            let resp = app
+                .auth_post(&url, &token)
+                .json(&serde_json::json!({ "message_ids": [message_id] }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 200);
+        }
+    };
+
+    // Member types and reads with both hidden: admin only gets its pong.
+    let first = post_message("first").await;
+    ws_member
+        .send(Message::Text(
+            serde_json::to_string(&serde_json::json!({
+                "type": "typing:start",
+                "data": { "room_id": room_id },
+            }))
+            .unwrap()
+            .into(),
+        ))
+        .await
+        .unwrap();
+    mark_read(first).await;
+    ws_admin
+        .send(Message::Text(
+            serde_json::to_string(&serde_json::json!({ "type": "ping" }))
+                .unwrap()
+                .into(),
+        ))
+        .await
+        .unwrap();
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), ws_admin.next())
+        .await
+        .expect("Timed out waiting for pong")
+        .unwrap()
+        .unwrap();
+    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(parsed["type"], "pong");
+
+    // With read receipts visible again the admin is told.
+    app.auth_put("/api/auth/me/preferences", &tenant.member.access_token)
+        .json(&serde_json::json!({ "privacy": { "hide_read_receipts": false } }))
+        .send()
+        .await
+        .unwrap();
+    let second = post_message("second").await;
+    mark_read(second.clone()).await;
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_admin.next())
+        .await
+        .expect("Timed out waiting for read receipt")
+        .unwrap()
+        .unwrap();
+    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(parsed["type"], "message:read");
+    assert_eq!(parsed["data"]["user_id"], tenant.member.id);
+    assert_eq!(parsed["data"]["message_ids"][0], second);
+
+    ws_admin.close(None).await.ok();
+    ws_member.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn room_read_marker_drives_unread_counts() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("readmarker").await;
+    let room_id = &tenant.rooms[0].id;
+    let rooms_url = format!("/api/tenant/{}/room", tenant.tenant_id);
+    let read_url = format!("{}/{}/read", rooms_url, room_id);
+
+    app.auth_post(
+        &format!("{}/{}/join", rooms_url, room_id),
+        &tenant.member.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let mut ids = Vec::new();
+    for i in 1..=3 {
+        let resp = app
+            .auth_post(
+                &format!("{}/{}/message", rooms_url, room_id),
+                &tenant.admin.access_token,
+            )
+            .json(&serde_json::json!({ "content": format!("unread {}", i) }))
+            .send()
+            .await
+            .unwrap();
+        let json: Value = resp.json().await.unwrap();
+        ids.push(json["id"].as_str().unwrap().to_string());
+    }
+
+    let unread_in_listing = |token: String| {
+        let app = &app;
+        let url = rooms_url.clone();
+        let room_id = room_id.clone();
+        async move {
+            let rooms: Value = app
+                .auth_get(&url, &token)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            rooms
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|r| r["id"] == room_id.as_str())
+                .unwrap()["unread_count"]
+                .clone()
+        }
+    };
+
+    // The author's own messages never count as unread.
+    assert_eq!(
+        unread_in_listing(tenant.member.access_token.clone()).await,
+        3
+    );
+    assert_eq!(
+        unread_in_listing(tenant.admin.access_token.clone()).await,
+        0
+    );
+
+    let resp = app
+        .auth_post(&read_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "message_id": ids[1] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["advanced"], true);
+    assert_eq!(json["unread_count"], 1);
+
+    // The marker never moves back.
+    let json: Value = app
+        .auth_post(&read_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "message_id": ids[0] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["advanced"], false);
+    assert_eq!(json["last_read_message_id"], ids[0]);
+    assert_eq!(
+        unread_in_listing(tenant.member.access_token.clone()).await,
+        1
+    );
+
+    // Without a body everything is read.
+    let json: Value = app
+        .auth_post(&read_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["advanced"], true);
+    assert_eq!(json["last_read_message_id"], ids[2]);
+    assert_eq!(json["unread_count"], 0);
+
+    let json: Value = app
+        .auth_get(
+            &format!("{}/{}/message/unread-count", rooms_url, room_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["count"], 0);
+
+    // Only room members have a marker.
+    let resp = app
+        .auth_post(
+            &format!("{}/{}/read", rooms_url, tenant.rooms[1].id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn ws_resume_replays_events_missed_while_disconnected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wsresume").await;
+    let room_id = &tenant.rooms[0].id;
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.member.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.admin.access_token);
+    type Ws = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+    async fn next_of(ws: &mut Ws, types: &[&str]) -> Value {
+        loop {
+            let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws.next())
+                .await
+                .expect("Timed out waiting for WS message")
+                .unwrap()
+                .unwrap();
+            let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            if types.contains(&parsed["type"].as_str().unwrap_or("")) {
+                return parsed;
+            }
+        }
+    }
+    let post = |content: &str| {
+        app.auth_post(&messages_url, &tenant.member.access_token)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+    };
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    let connected = next_of(&mut ws, &["connected"]).await;
+    let stream_id = connected["stream_id"].as_str().unwrap().to_string();
+    assert!(!stream_id.is_empty());
+
+    post("first").await.unwrap();
+    let first = next_of(&mut ws, &["message:create"]).await;
+    assert_eq!(first["data"]["content"], "first");
+    let last_seq = first["seq"].as_u64().unwrap();
+
+    ws.close(None).await.ok();
+    drop(ws);
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    post("second").await.unwrap();
+    post("third").await.unwrap();
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    let connected = next_of(&mut ws, &["connected"]).await;
+    assert_eq!(connected["stream_id"], stream_id.as_str());
+    assert!(connected["seq"].as_u64().unwrap() >= last_seq + 2);
+
+    ws.send(Message::Text(
+        serde_json::json!({ "type": "resume", "last_seq": last_seq, "stream_id": stream_id })
+            .to_string()
+            .into(),
+    ))
+    .await
+    .unwrap();
+    let mut replayed = Vec::new();
+    let mut previous_seq = last_seq;
+    let done = loop {
+        let msg = next_of(&mut ws, &["message:create", "resume:ok", "resume:refetch"]).await;
+        if msg["type"] != "message:create" {
+            break msg;
+        }
+        let seq = msg["seq"].as_u64().unwrap();
+        assert!(seq > previous_seq);
+        previous_seq = seq;
+        replayed.push(msg["data"]["content"].as_str().unwrap().to_string());
+    };
+    assert_eq!(done["type"], "resume:ok");
+    assert_eq!(replayed, vec!["second", "third"]);
+
+    // A stream this instance doesn't know can't be resumed.
+    ws.send(Message::Text(
+        serde_json::json!({ "type": "resume", "last_seq": 1, "stream_id": "elsewhere" })
+            .to_string()
+            .into(),
+    ))
+    .await
+    .unwrap();
+    let refetch = next_of(&mut ws, &["resume:ok", "resume:refetch"]).await;
+    assert_eq!(refetch["type"], "resume:refetch");
+    assert_eq!(refetch["data"]["stream_id"], stream_id.as_str());
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn thread_subscriptions_track_unread_replies() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("threadsub").await;
+    let room_id = &tenant.rooms[0].id;
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+    let subscribed_url = format!("/api/tenant/{}/thread/subscribed", tenant.tenant_id);
+
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let root: Value = app
+        .auth_post(&messages_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "Release checklist" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let root_id = root["id"].as_str().unwrap().to_string();
+    let reply = |token: &str, content: &str| {
+        app.auth_post(&messages_url, token)
+            .json(&serde_json::json!({ "content": content, "thread_id": root_id }))
+            .send()
+    };
+    let subscribed = |token: &str| {
+        let req = app.auth_get(&subscribed_url, token);
+        async move {
+            req.send()
+                .await
+                .unwrap()
+                .json::<Vec<Value>>()
+                .await
+                .unwrap()
+        }
+    };
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.admin.access_token);
+    let (mut ws_admin, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    // The member's reply subscribes both of them; the author hears about it
+    let first: Value = reply(&tenant.member.access_token, "Docs are done")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let update = loop {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_admin.next())
+            .await
+            .expect("Timed out waiting for thread:update")
+            .unwrap()
+            .unwrap();
+        let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        if parsed["type"] == "thread:update" {
+            break parsed;
+        }
+    };
+    assert_eq!(update["data"]["thread_id"], root_id.as_str());
+    assert_eq!(update["data"]["reply_count"], 1);
+    assert_eq!(update["data"]["activity_count"], 1);
+
+    let threads = subscribed(&tenant.admin.access_token).await;
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["thread_id"], root_id.as_str());
+    assert_eq!(threads[0]["unread_count"], 1);
+    assert_eq!(threads[0]["root"]["thread_activity"], 1);
+    // Their own reply isn't unread for the member
+    assert!(subscribed(&tenant.member.access_token).await.is_empty());
+
+    // Replying marks the thread read for the replier
+    reply(&tenant.admin.access_token, "Thanks").await.unwrap();
+    assert!(subscribed(&tenant.admin.access_token).await.is_empty());
+    let threads = subscribed(&tenant.member.access_token).await;
+    assert_eq!(threads[0]["unread_count"], 1);
+
+    let thread_url = format!("{}/{}/thread", messages_url, root_id);
+    app.auth_post(&format!("{}/read", thread_url), &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert!(subscribed(&tenant.member.access_token).await.is_empty());
+
+    // Editing a reply counts as activity
+    app.auth_put(
+        &format!("{}/{}", messages_url, first["id"].as_str().unwrap()),
+        &tenant.member.access_token,
+    )
+    .json(&serde_json::json!({ "content": "Docs and changelog are done" }))
+    .send()
+    .await
+    .unwrap();
+    let update = loop {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_admin.next())
+            .await
+            .expect("Timed out waiting for thread:update")
+            .unwrap()
+            .unwrap();
+        let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        if parsed["type"] == "thread:update" {
+            break parsed;
+        }
+    };
+    assert_eq!(update["data"]["reply_count"], 2);
+    assert_eq!(update["data"]["activity_count"], 3);
+
+    // Unfollowing drops the thread even with new replies
+    let resp = app
+        .auth_delete(
+            &format!("{}/subscription", thread_url),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    reply(&tenant.member.access_token, "One more thing")
+        .await
+        .unwrap();
+    assert!(subscribed(&tenant.admin.access_token).await.is_empty());
+
+    // Following again brings it back, counting from where they left off
+    app.auth_put(
+        &format!("{}/subscription", thread_url),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    reply(&tenant.member.access_token, "Shipped").await.unwrap();
+    let threads = subscribed(&tenant.admin.access_token).await;
+    assert_eq!(threads[0]["unread_count"], 2);
+
+    // Only top-level messages have threads to follow
+    let resp = app
+        .auth_put(
+            &format!(
+                "{}/{}/thread/subscription",
+                messages_url,
+                first["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    ws_admin.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn message_embeds_are_validated_and_exported() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgembed").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    let url = format!("/api/tenant/{}/room/{}/message", tid, room_id);
+
+    let embeds = serde_json::json!([
+        { "type": "location", "latitude": 48.2082, "longitude": 16.3738, "name": "Office" },
+        { "type": "contact", "name": "Ada", "email": "ada@example.com" },
+        {
+            "type": "fields",
+            "title": "Deploy",
+            "fields": [{ "name": "Status", "value": "green", "inline": true }],
+        },
+    ]);
+    let resp = app
+        .auth_post(&url, token)
+        .json(&serde_json::json!({ "content": "Meet here", "embeds": embeds }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["embeds"], embeds);
+
+    let list: Value = app
+        .auth_get(&url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["items"][0]["embeds"][2]["fields"][0]["value"], "green");
+
+    for invalid in [
+        serde_json::json!([{ "type": "location", "latitude": 120.0, "longitude": 0.0 }]),
+        serde_json::json!([{ "type": "contact", "name": "No way to reach" }]),
+        serde_json::json!([{ "type": "fields", "fields": [] }]),
+        serde_json::json!(vec![embeds[0].clone(); 11]),
+    ] {
+        let resp = app
+            .auth_post(&url, token)
+            .json(&serde_json::json!({ "content": "Bad", "embeds": invalid }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{}", invalid);
+    }
+
+    let md = app
+        .auth_post(&format!("/api/tenant/{}/export/conversation", tid), token)
+        .json(&serde_json::json!({ "room_id": room_id, "format": "markdown" }))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(md.contains("- Location: Office (48.2082, 16.3738)"));
+    assert!(md.contains("- Contact: Ada (ada@example.com)"));
+    assert!(md.contains("- Deploy: Status: green"));
+}
+
+#[tokio::test]
+async fn retried_sends_with_a_nonce_return_the_original() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgnonce").await;
+    let room_id = &tenant.rooms[0].id;
+    let other_room_id = &tenant.rooms[1].id;
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        for room in [room_id, other_room_id] {
+            app.auth_post(
+                &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room),
+                token,
+            )
+            .send()
+            .await
+            .unwrap();
+        }
+    }
+    let url = |room: &str| format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room);
+
+    let ws_url_member = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws_member, _) = tokio_tungstenite::connect_async(&ws_url_member)
+        .await
+        .unwrap();
+    ws_member.next().await; // connected
+
+    let send = |room: String, token: String, nonce: &'static str| {
+        let request = app
+            .auth_post(&room, &token)
+            .json(&serde_json::json!({ "content": "Exactly once", "nonce": nonce }));
+        async move { request.send().await.unwrap() }
+    };
+
+    let resp = send(url(room_id), tenant.admin.access_token.clone(), "send-1").await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let original: Value = resp.json().await.unwrap();
+    assert_eq!(original["nonce"], "send-1");
+
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_member.next())
+        .await
+        .expect("Timed out waiting for WS message")
+        .unwrap()
+        .unwrap();
+    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(parsed["type"], "message:create");
+    assert_eq!(parsed["data"]["nonce"], "send-1");
+
+    // The retry gets the original back, without a copy or a broadcast
+    let retry: Value = send(url(room_id), tenant.admin.access_token.clone(), "send-1")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(retry["id"], original["id"]);
+    assert_eq!(retry["nonce"], "send-1");
+    ws_member
+        .send(Message::Text(
+            serde_json::to_string(&serde_json::json!({ "type": "ping" }))
+                .unwrap()
+                .into(),
+        ))
+        .await
+        .unwrap();
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), ws_member.next())
+        .await
+        .expect("Timed out waiting for pong")
+        .unwrap()
+        .unwrap();
+    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(parsed["type"], "pong");
+
+    // Nonces are scoped to the author and the channel
+    for (room, token) in [
+        (other_room_id, &tenant.admin.access_token),
+        (room_id, &tenant.member.access_token),
+    ] {
+        let copy: Value = send(url(room), token.clone(), "send-1")
+            .await
+            .json()
+            .await
+            .unwrap();
+        assert_ne!(copy["id"], original["id"]);
+    }
+
+    let list: Value = app
+        .auth_get(&url(room_id), &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["total"], 2);
+
+    // Only the origin copy of a cross-post holds the nonce
+    let cross_post: Value = app
+        .auth_post(&url(room_id), &tenant.admin.access_token)
+        .json(&serde_json::json!({
+            "content": "Everywhere once",
+            "nonce": "send-2",
+            "cross_post_room_ids": [other_room_id],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(cross_post["nonce"], "send-2");
+    let copy_id = cross_post["cross_posts"][0]["message_id"].clone();
+    let retry: Value = app
+        .auth_post(&url(room_id), &tenant.admin.access_token)
+        .json(&serde_json::json!({
+            "content": "Everywhere once",
+            "nonce": "send-2",
+            "cross_post_room_ids": [other_room_id],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(retry["id"], cross_post["id"]);
+    assert_eq!(retry["cross_posts"][0]["message_id"], copy_id);
+    let other: Value = send(
+        url(other_room_id),
+        tenant.admin.access_token.clone(),
+        "send-2",
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    assert_ne!(other["id"], copy_id);
+
+    let resp = app
+        .auth_post(&url(room_id), &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "Too long", "nonce": "n".repeat(65) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // A deleted original isn't posted again
+    app.auth_delete(
+        &format!("{}/{}", url(room_id), original["id"].as_str().unwrap()),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let resp = send(url(room_id), tenant.admin.access_token.clone(), "send-1").await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    ws_member.close(None).await.ok();
+}
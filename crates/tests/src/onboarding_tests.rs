@@ -0,0 +1,173 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+/// Default channels deliberately avoid the names `seed_tenant` creates.
+async fn spawn_with_onboarding() -> TestApp {
+    TestApp::spawn_with_settings(|s| {
+        s.onboarding.default_channels = vec!["lobby".to_string(), "#watercooler".to_string()];
+        s.onboarding.welcome_message = "Welcome aboard!".to_string();
+    })
+    .await
+}
+
+async fn room_id_by_name(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/room", tenant_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let rooms: Vec<Value> = resp.json().await.unwrap();
+    rooms
+        .iter()
+        .find(|r| r["name"] == name)
+        .unwrap_or_else(|| panic!("room {name} not provisioned"))["id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn onboarding(app: &TestApp, tenant_id: &str, token: &str) -> Value {
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/onboarding", tenant_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+fn step_done(checklist: &Value, key: &str) -> bool {
+    checklist["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["key"] == key)
+        .unwrap()["done"]
+        .as_bool()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn tenant_creation_provisions_default_channels_and_welcome() {
+    let app = spawn_with_onboarding().await;
+    let tenant = app.seed_tenant("onbone").await;
+
+    let lobby = room_id_by_name(&app, &tenant.tenant_id, &tenant.admin.access_token, "lobby").await;
+    room_id_by_name(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "watercooler",
+    )
+    .await;
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, lobby),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let items = body["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["content"], "Welcome aboard!");
+}
+
+#[tokio::test]
+async fn added_member_auto_joins_default_channels() {
+    let app = spawn_with_onboarding().await;
+    let tenant = app.seed_tenant("onbtwo").await;
+    let newcomer = app
+        .register_user(
+            "newcomer@onbtwo.test",
+            "onbtwo_newcomer",
+            "Newcomer",
+            "Newcomer123!",
+            None,
+            None,
+        )
+        .await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/member", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "user_id": newcomer.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+
+    let lobby = room_id_by_name(&app, &tenant.tenant_id, &tenant.admin.access_token, "lobby").await;
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/member", tenant.tenant_id, lobby),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    let ids: Vec<&str> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|m| m["user_id"].as_str())
+        .collect();
+    assert!(ids.contains(&newcomer.id.as_str()));
+}
+
+#[tokio::test]
+async fn checklist_tracks_join_and_message_then_dismisses() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("onbthree").await;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.member.access_token;
+
+    let fresh = onboarding(&app, &tenant.tenant_id, token).await;
+    assert!(!step_done(&fresh, "joined_channel"));
+    assert_eq!(fresh["completed"], false);
+    assert_eq!(fresh["dismissed"], false);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "hi all" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let progress = onboarding(&app, &tenant.tenant_id, token).await;
+    assert!(step_done(&progress, "joined_channel"));
+    assert!(step_done(&progress, "sent_message"));
+    assert!(!step_done(&progress, "started_call"));
+    assert_eq!(progress["completed"], false);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/onboarding/dismiss", tenant.tenant_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let dismissed: Value = resp.json().await.unwrap();
+    assert_eq!(dismissed["dismissed"], true);
+    assert!(step_done(&dismissed, "sent_message"));
+}
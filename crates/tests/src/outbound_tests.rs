@@ -0,0 +1,114 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+/// ASR server whose `/v1/models` always answers 503, counting the calls.
+async fn spawn_failing_asr() -> (String, Arc<AtomicUsize>) {
+    use axum::{Router, http::StatusCode, routing::get};
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let router = Router::new().route(
+        "/v1/models",
+        get(move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}", addr), hits)
+}
+
+async fn asr_stats(app: &TestApp, url: &str, token: &str) -> Value {
+    let body: Value = app
+        .auth_get(url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    body["services"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["service"] == "asr")
+        .unwrap()
+        .clone()
+}
+
+#[tokio::test]
+async fn failing_host_is_retried_then_short_circuited() {
+    let (asr_url, hits) = spawn_failing_asr().await;
+    let app = TestApp::spawn_with_settings(|s| {
+        s.asr.url = asr_url;
+        s.outbound.max_attempts = 3;
+        s.outbound.breaker_failure_threshold = 3;
+        s.outbound.breaker_open_secs = 60;
+    })
+    .await;
+    let tenant = app.seed_tenant("outbound").await;
+    let url = format!("/api/tenant/{}/admin/outbound", tenant.tenant_id);
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let asr = asr_stats(&app, &url, &tenant.admin.access_token).await;
+    assert_eq!(asr["requests"], 0);
+    assert!(asr["avg_latency_ms"].is_null());
+
+    // The model list is a GET, so each failure is retried.
+    let resp = app
+        .client
+        .get(app.url("/health/ready"))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["checks"]["transcription"]["status"], "failed");
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    let asr = asr_stats(&app, &url, &tenant.admin.access_token).await;
+    assert_eq!(asr["requests"], 3);
+    assert_eq!(asr["failures"], 3);
+    assert_eq!(asr["retries"], 2);
+    assert_eq!(asr["open_circuits"], 1);
+    assert!(asr["avg_latency_ms"].is_number());
+
+    // The breaker is open: the backend isn't asked again.
+    let resp = app
+        .client
+        .get(app.url("/health/ready"))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    let transcription = &body["checks"]["transcription"];
+    assert_eq!(transcription["status"], "failed");
+    assert!(
+        transcription["error"]
+            .as_str()
+            .unwrap()
+            .contains("calls to it are paused")
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    let asr = asr_stats(&app, &url, &tenant.admin.access_token).await;
+    assert_eq!(asr["requests"], 3);
+    assert_eq!(asr["short_circuited"], 1);
+}
@@ -248,3 +248,95 @@ async fn total_count_and_total_pages_correct() {
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["items"].as_array().unwrap().len(), 2);
 }
+
+#[tokio::test]
+async fn paginate_messages_across_archive_partitions() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("pagearchive").await;
+    let room_id = &tenant.rooms[0].id;
+
+    let ids = seed_messages(
+        &app,
+        &tenant.tenant_id,
+        room_id,
+        &tenant.admin.access_token,
+        12,
+    )
+    .await;
+
+    // Backdate the first 8 messages: 4 into each of two old months.
+    let messages = app.db.collection::<bson::Document>("messages");
+    for (i, id) in ids.iter().take(8).enumerate() {
+        let month = if i < 4 { "2023-01" } else { "2023-02" };
+        let at = bson::DateTime::parse_rfc3339_str(format!("{}-1{}T12:00:00Z", month, i)).unwrap();
+        messages
+            .update_one(
+                bson::doc! { "_id": bson::oid::ObjectId::parse_str(id).unwrap() },
+                bson::doc! { "$set": { "created_at": at } },
+            )
+            .await
+            .unwrap();
+    }
+
+    let mut settings = app.settings.message_archive.clone();
+    settings.batch_size = 3;
+    let archiver = roomler_ai_services::message_archive::MessageArchiver::new(&app.db, &settings);
+    let moved = archiver.sweep(archiver.cutoff()).await.unwrap();
+    assert_eq!(moved, 8);
+    assert_eq!(messages.count_documents(bson::doc! {}).await.unwrap(), 4);
+    for name in ["messages_archive_2023_01", "messages_archive_2023_02"] {
+        let count = app
+            .db
+            .collection::<bson::Document>(name)
+            .count_documents(bson::doc! {})
+            .await
+            .unwrap();
+        assert_eq!(count, 4, "{}", name);
+    }
+
+    // Re-running finds nothing left to move
+    assert_eq!(archiver.sweep(archiver.cutoff()).await.unwrap(), 0);
+
+    // Pages run newest first through the hot collection and then each month
+    let mut listed = Vec::new();
+    for page in 1..=3 {
+        let resp = app
+            .auth_get(
+                &format!(
+                    "/api/tenant/{}/room/{}/message?page={}&per_page=5",
+                    tenant.tenant_id, room_id, page
+                ),
+                &tenant.admin.access_token,
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        let json: Value = resp.json().await.unwrap();
+        assert_eq!(json["total"], 12);
+        assert_eq!(json["total_pages"], 3);
+        for item in json["items"].as_array().unwrap() {
+            listed.push(item["id"].as_str().unwrap().to_string());
+        }
+    }
+    let expected: Vec<String> = ids.iter().rev().cloned().collect();
+    assert_eq!(listed, expected);
+
+    // A cursor inside an archived month counts only what's before it
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/message?before=2023-02-15T00:00:00Z",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["total"], 5);
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items[0]["id"], ids[4].as_str());
+    assert_eq!(items[4]["id"], ids[0].as_str());
+}
@@ -3,7 +3,8 @@ use serde_json::Value;
 
 #[tokio::test]
 async fn export_conversation_as_pdf() {
-    let app = TestApp::spawn().await;
+    // Force the background path regardless of room size.
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
     let tenant = app.seed_tenant("pdfexp").await;
     let room_id = tenant.rooms[0].id.clone();
 
@@ -40,7 +41,7 @@ async fn export_conversation_as_pdf() {
         .await
         .unwrap();
 
-    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.status().as_u16(), 202);
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["status"], "pending");
     let task_id = json["task_id"].as_str().unwrap().to_string();
@@ -158,3 +159,84 @@ async fn recognize_file_returns_error_without_api_key() {
     let json: Value = resp.json().await.unwrap();
     assert!(json["message"].as_str().unwrap().contains("not configured"));
 }
+
+#[tokio::test]
+async fn anonymized_pdf_export_hides_identities() {
+    let app = TestApp::spawn_with_settings(|s| s.export.sync_max_messages = 0).await;
+    let tenant = app.seed_tenant("anonexp").await;
+    let room_id = tenant.rooms[0].id.clone();
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+        &tenant.admin.access_token,
+    )
+    .json(&serde_json::json!({
+        "content": "I'm anonexp Admin, mail admin@anonexp.test or call 555-123-4567",
+    }))
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/export/conversation-pdf", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "room_id": room_id, "anonymize": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    let task_id = json["task_id"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(
+                &format!("/api/tenant/{}/task/{}", tenant.tenant_id, task_id),
+                &tenant.admin.access_token,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Completed" => {
+                completed = true;
+                break;
+            }
+            "Failed" => panic!("PDF export failed: {:?}", json["error"]),
+            _ => {}
+        }
+    }
+    assert!(completed, "PDF export did not complete within timeout");
+
+    let body = app
+        .auth_get(
+            &format!("/api/tenant/{}/task/{}/download", tenant.tenant_id, task_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    let text = String::from_utf8_lossy(&body);
+    assert!(!text.contains("anonexp Admin"));
+    assert!(!text.contains("admin@anonexp.test"));
+    assert!(!text.contains("555-123-4567"));
+    assert!(text.contains("User-"));
+    assert!(text.contains("[email]"));
+}
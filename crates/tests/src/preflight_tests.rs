@@ -0,0 +1,90 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn preflight_returns_probe_transport_and_stores_reports() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.mediasoup.num_workers = 1;
+        s.mediasoup.rtc_min_port = 40100;
+        s.mediasoup.rtc_max_port = 40199;
+        s.mediasoup.expected_max_transports = 10;
+        s.turn.url = Some("turn:turn.example.com:3478".to_string());
+        s.turn.shared_secret = Some("secret".to_string());
+    })
+    .await;
+    let tenant = app.seed_tenant("preflight").await;
+    let base = format!("/api/tenant/{}/conference/preflight", tenant.tenant_id);
+
+    let resp = app
+        .auth_get(&base, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    let probe_id = json["probe_transport"]["id"].as_str().unwrap().to_string();
+    assert!(
+        json["probe_transport"]["ice_candidates"]
+            .as_array()
+            .is_some()
+    );
+    assert_eq!(json["expected_ports"]["udp"]["min"], 40100);
+    assert_eq!(json["expected_ports"]["tcp"]["max"], 40199);
+    let urls = json["ice_servers"][0]["urls"].as_array().unwrap();
+    assert!(
+        urls.iter()
+            .any(|u| u == "turn:turn.example.com:3478?transport=tcp")
+    );
+
+    // The probe holds a port until it expires.
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/admin/media-ports", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let ports: Value = resp.json().await.unwrap();
+    assert_eq!(ports["total_transports"], 1);
+
+    let resp = app
+        .auth_post(&format!("{}/report", base), &tenant.member.access_token)
+        .json(&serde_json::json!({
+            "probe_transport_id": probe_id,
+            "udp_ok": false,
+            "tcp_ok": true,
+            "selected_candidate_type": "host",
+            "microphone_ok": true,
+            "errors": ["udp: ICE timed out"],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let report: Value = resp.json().await.unwrap();
+    assert_eq!(report["connected"], true);
+    assert_eq!(report["user_id"], tenant.member.id);
+
+    // Support (MANAGE_TENANT) can read the reports; members can't.
+    let resp = app
+        .auth_get(
+            &format!("{}/report?user_id={}", base, tenant.member.id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let list: Value = resp.json().await.unwrap();
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["items"][0]["probe_transport_id"], probe_id);
+    assert_eq!(list["items"][0]["errors"][0], "udp: ICE timed out");
+
+    let resp = app
+        .auth_get(&format!("{}/report", base), &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -0,0 +1,256 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn profanity_filter_masks_blocks_and_flags_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("profanity").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let filter_url = format!("/api/tenant/{}/profanity-filter", tid);
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tid, tenant.rooms[0].id);
+
+    let filter: Value = app
+        .auth_get(&filter_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(filter["mode"], "off");
+
+    let set_filter = |body: Value, token: &str| app.auth_put(&filter_url, token).json(&body).send();
+    let resp = set_filter(
+        serde_json::json!({ "mode": "mask", "words": ["two words"] }),
+        admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = set_filter(
+        serde_json::json!({ "mode": "mask" }),
+        &tenant.member.access_token,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let post = |content: &str| {
+        app.auth_post(&messages_url, admin)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+    };
+    let resp = set_filter(
+        serde_json::json!({ "mode": "mask", "words": ["frak*"], "allowed_words": ["shitake"] }),
+        admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = post("Frakking printer, shit! Shitake for lunch?")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        json["content"],
+        "******** printer, ****! Shitake for lunch?"
+    );
+
+    // Edits are screened too.
+    let edit_url = format!("{}/{}", messages_url, json["id"].as_str().unwrap());
+    let json: Value = app
+        .auth_put(&edit_url, admin)
+        .json(&serde_json::json!({ "content": "frak it" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["content"], "**** it");
+
+    set_filter(serde_json::json!({ "mode": "block" }), admin)
+        .await
+        .unwrap();
+    let resp = post("What the fuck").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    set_filter(serde_json::json!({ "mode": "flag" }), admin)
+        .await
+        .unwrap();
+    let flagged: Value = post("Oh shit").await.unwrap().json().await.unwrap();
+    assert_eq!(flagged["content"], "Oh shit");
+
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let report: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/analytics/moderation?from={}&to={}",
+                tid, today, today
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["masked"], 2);
+    assert_eq!(report["blocked"], 1);
+    assert_eq!(report["flagged"], 1);
+    assert_eq!(report["days"][0]["date"], today);
+    assert_eq!(report["top_terms"][0]["term"], "frak*");
+    assert_eq!(report["top_terms"][0]["count"], 2);
+    assert_eq!(report["flagged_messages"][0]["message_id"], flagged["id"]);
+    assert_eq!(
+        report["flagged_messages"][0]["terms"],
+        serde_json::json!(["shit*"])
+    );
+
+    // Bot hook posts go through the same filter.
+    let bot: Value = app
+        .auth_post(&format!("/api/tenant/{}/bot", tid), admin)
+        .json(&serde_json::json!({ "name": "CI", "room_id": tenant.rooms[0].id }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let hook_url = app.url(&format!("/api/hook/{}", bot["token"].as_str().unwrap()));
+    set_filter(
+        serde_json::json!({ "mode": "mask", "words": ["frak*"] }),
+        admin,
+    )
+    .await
+    .unwrap();
+    let json: Value = app
+        .client
+        .post(&hook_url)
+        .json(&serde_json::json!({ "text": "Frakking flaky test" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["content"], "******** flaky test");
+    set_filter(serde_json::json!({ "mode": "block" }), admin)
+        .await
+        .unwrap();
+    let resp = app
+        .client
+        .post(&hook_url)
+        .json(&serde_json::json!({ "text": "Build is fucked" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn profanity_filter_screens_conference_chat_from_members_and_guests() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("profanitycall").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tid), admin)
+        .json(&serde_json::json!({
+            "name": "Customer Call",
+            "media_settings": { "audio_enabled": true, "video_enabled": true },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let code = room["meeting_code"].as_str().unwrap();
+
+    let resp = app
+        .auth_put(&format!("/api/tenant/{}/profanity-filter", tid), admin)
+        .json(&serde_json::json!({ "mode": "mask", "words": ["frak*"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let json: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/message", tid, room_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "content": "Frakking echo" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["content"], "******** echo");
+
+    let link: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/guest-link", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let anon = reqwest::Client::new();
+    let guest: Value = anon
+        .post(app.url(&format!("/api/guest/conference/{code}/join")))
+        .json(&serde_json::json!({ "display_name": "Visitor", "key": link["key"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = guest["token"].as_str().unwrap();
+    let chat_url = app.url(&format!("/api/guest/conference/{code}/message"));
+    let json: Value = anon
+        .post(&chat_url)
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "content": "Frak, my mic" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["content"], "****, my mic");
+
+    app.auth_put(&format!("/api/tenant/{}/profanity-filter", tid), admin)
+        .json(&serde_json::json!({ "mode": "block" }))
+        .send()
+        .await
+        .unwrap();
+    let resp = anon
+        .post(&chat_url)
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "content": "What the fuck" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/message", tid, room_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "content": "What the fuck" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
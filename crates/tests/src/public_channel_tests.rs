@@ -0,0 +1,183 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn public_channel_is_readable_without_signing_in_until_revoked() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("publicchan").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let rid = &tenant.rooms[0].id;
+    let share_url = format!("/api/tenant/{}/room/{}/public", tid, rid);
+    app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, rid), token)
+        .send()
+        .await
+        .unwrap();
+    for n in 0..3 {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/message", tid, rid), token)
+            .json(&serde_json::json!({ "content": format!("announcement {n}") }))
+            .send()
+            .await
+            .unwrap();
+    }
+    // Newer system and deleted messages stay off the public pages.
+    let messages = app.db.collection::<bson::Document>("messages");
+    let room_oid = bson::oid::ObjectId::parse_str(rid).unwrap();
+    let latest = messages
+        .find_one(bson::doc! { "room_id": room_oid })
+        .await
+        .unwrap()
+        .unwrap();
+    for (message_type, deleted_at) in [
+        ("system_join", bson::Bson::Null),
+        ("default", bson::Bson::DateTime(bson::DateTime::now())),
+    ] {
+        let mut hidden = latest.clone();
+        hidden.insert("_id", bson::oid::ObjectId::new());
+        hidden.insert("message_type", message_type);
+        hidden.insert("content", "hidden");
+        hidden.insert("created_at", bson::DateTime::now());
+        hidden.insert("deleted_at", deleted_at);
+        messages.insert_one(hidden).await.unwrap();
+    }
+
+    // Only MANAGE_TENANT can publish a channel.
+    let resp = app
+        .auth_put(&share_url, &tenant.member.access_token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let json: Value = app
+        .auth_put(&share_url, token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["public"], true);
+    assert_eq!(json["indexable"], false);
+    let public_token = json["token"].as_str().unwrap().to_string();
+    assert!(json["url"].as_str().unwrap().ends_with(&public_token));
+    let public_url = format!("/api/public/channel/{}", public_token);
+
+    let resp = app.client.get(app.url(&public_url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["x-robots-tag"], "noindex, nofollow");
+    assert_eq!(resp.headers()["cache-control"], "no-store");
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["name"], tenant.rooms[0].name.as_str());
+    assert_eq!(json["robots"], "noindex, nofollow");
+
+    let resp = app
+        .client
+        .get(app.url(&format!("{}/message?per_page=2", public_url)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["total"], 3);
+    assert_eq!(json["total_pages"], 2);
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["content"], "announcement 2");
+    // No member data on the public page.
+    for item in items {
+        assert!(item.get("author_id").is_none());
+        assert!(item.get("author_name").is_none());
+        assert!(item.get("mentions").is_none());
+    }
+
+    // Sharing again keeps the link and opts into indexing.
+    let json: Value = app
+        .auth_put(&share_url, token)
+        .json(&serde_json::json!({ "indexable": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["token"], public_token.as_str());
+    let resp = app.client.get(app.url(&public_url)).send().await.unwrap();
+    assert_eq!(resp.headers()["x-robots-tag"], "index, follow");
+
+    // Revoking takes effect at once.
+    let json: Value = app
+        .auth_delete(&share_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["public"], false);
+    for url in [public_url.clone(), format!("{}/message", public_url)] {
+        let resp = app.client.get(app.url(&url)).send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 404);
+    }
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?target_id={}", tid, rid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = audit["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert!(actions.contains(&"room.public_share"));
+    assert!(actions.contains(&"room.public_unshare"));
+}
+
+#[tokio::test]
+async fn public_channel_views_are_rate_limited() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.rate_limit.public = roomler_ai_config::RateBudget {
+            burst: 2,
+            per_minute: 1,
+        }
+    })
+    .await;
+    let tenant = app.seed_tenant("publicrate").await;
+    let json: Value = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/public",
+                tenant.tenant_id, tenant.rooms[0].id
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = app.url(&format!(
+        "/api/public/channel/{}",
+        json["token"].as_str().unwrap()
+    ));
+
+    for _ in 0..2 {
+        let resp = app.client.get(&url).send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+    let resp = app.client.get(&url).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 429);
+    assert!(resp.headers().contains_key("retry-after"));
+}
@@ -0,0 +1,73 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+async fn quick_switch(app: &TestApp, tid: &str, query: &str, token: &str) -> Vec<Value> {
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/quick-switch?{}", tid, query),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn quick_switch_blends_channels_dms_and_members() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("qswitch").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/dm", tid), admin)
+        .json(&serde_json::json!({ "user_ids": [tenant.member.id] }))
+        .send()
+        .await
+        .unwrap();
+    let dm: Value = resp.json().await.unwrap();
+    let dm_id = dm["id"].as_str().unwrap();
+
+    let items = quick_switch(&app, tid, "q=eng", admin).await;
+    assert_eq!(items[0]["kind"], "channel");
+    assert_eq!(items[0]["label"], "engineering");
+    assert_eq!(items[0]["room_id"], tenant.rooms[1].id.as_str());
+
+    // Members match by display name or username and lead to their DM.
+    let items = quick_switch(&app, tid, "q=qswitch_m", admin).await;
+    let member = items.iter().find(|i| i["kind"] == "member").unwrap();
+    assert_eq!(member["id"], tenant.member.id.as_str());
+    assert_eq!(member["detail"], "@qswitch_member");
+    assert_eq!(member["room_id"], dm_id);
+    let items = quick_switch(&app, tid, "q=qswitch%20m", admin).await;
+    let dm_item = items.iter().find(|i| i["kind"] == "dm").unwrap();
+    assert_eq!(dm_item["id"], dm_id);
+    assert_eq!(dm_item["label"], "qswitch Member");
+    // The caller isn't offered to themselves.
+    assert!(items.iter().all(|i| i["id"] != tenant.admin.id.as_str()));
+
+    // An empty query lists recent things, up to the limit.
+    assert_eq!(quick_switch(&app, tid, "q=&limit=2", admin).await.len(), 2);
+    assert!(
+        quick_switch(&app, tid, "q=nothing-like-this", admin)
+            .await
+            .is_empty()
+    );
+
+    // Open channels show up for members who haven't joined them.
+    let items = quick_switch(&app, tid, "q=rand", &tenant.member.access_token).await;
+    assert_eq!(items[0]["label"], "random");
+
+    let other = app.seed_tenant("qswitch2").await;
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/quick-switch?q=eng", tid),
+            &other.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -1,51 +1,155 @@
 use crate::fixtures::test_app::TestApp;
+use roomler_ai_config::RateBudget;
+
+async fn login_attempt(app: &TestApp, ip: &str) -> reqwest::Response {
+    app.client
+        .post(app.url("/api/auth/login"))
+        .header("X-Forwarded-For", ip)
+        .json(&serde_json::json!({
+            "email": "nobody@test.com",
+            "password": "Wrong123!",
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn login_is_rate_limited_per_ip_with_retry_after() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.app.trusted_proxies = "127.0.0.1".to_string();
+        s.rate_limit.login = RateBudget {
+            burst: 3,
+            per_minute: 60,
+        };
+    })
+    .await;
+
+    for _ in 0..3 {
+        let resp = login_attempt(&app, "203.0.113.7").await;
+        assert_eq!(resp.status().as_u16(), 401);
+    }
+
+    let resp = login_attempt(&app, "203.0.113.7").await;
+    assert_eq!(resp.status().as_u16(), 429);
+    assert_eq!(resp.headers()["retry-after"], "1");
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "too_many_requests");
+
+    // Another client has its own bucket
+    let resp = login_attempt(&app, "198.51.100.2").await;
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // One token per second refills
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let resp = login_attempt(&app, "203.0.113.7").await;
+    assert_eq!(resp.status().as_u16(), 401);
+}
 
 #[tokio::test]
-async fn rate_limit_returns_429_after_burst() {
-    let app = TestApp::spawn().await;
-
-    // The rate limiter allows burst of 60 requests, then 1 per second.
-    // Send 65 rapid requests to /health (a lightweight unauthenticated endpoint).
-    let mut statuses = Vec::new();
-    for _ in 0..65 {
-        let resp = app.client.get(app.url("/health")).send().await.unwrap();
-        statuses.push(resp.status().as_u16());
+async fn forwarded_addresses_count_only_from_trusted_proxies() {
+    let budget = RateBudget {
+        burst: 2,
+        per_minute: 60,
+    };
+    // Without a trusted proxy the header is ignored, so varying it doesn't
+    // buy a fresh bucket.
+    let app = TestApp::spawn_with_settings(|s| s.rate_limit.login = budget).await;
+    for ip in ["203.0.113.1", "203.0.113.2"] {
+        assert_eq!(login_attempt(&app, ip).await.status().as_u16(), 401);
     }
+    let resp = login_attempt(&app, "203.0.113.3").await;
+    assert_eq!(resp.status().as_u16(), 429);
 
-    // Count how many got 429
-    let rate_limited = statuses.iter().filter(|&&s| s == 429).count();
-    let successful = statuses.iter().filter(|&&s| s == 200).count();
-
-    // We expect at least 60 successful (the burst) and at least 1 rate-limited
-    assert!(
-        successful >= 60,
-        "Expected at least 60 successful requests, got {}",
-        successful
-    );
-    assert!(
-        rate_limited >= 1,
-        "Expected at least 1 rate-limited (429) response, got 0. All {} requests succeeded.",
-        statuses.len()
-    );
+    // Behind one, entries the client prepends are skipped: the right-most
+    // untrusted hop is the client.
+    let app = TestApp::spawn_with_settings(|s| {
+        s.app.trusted_proxies = "127.0.0.0/8, 10.0.0.0/8".to_string();
+        s.rate_limit.login = budget;
+    })
+    .await;
+    for spoofed in ["198.51.100.1", "198.51.100.2"] {
+        let chain = format!("{spoofed}, 203.0.113.7, 10.1.2.3");
+        assert_eq!(login_attempt(&app, &chain).await.status().as_u16(), 401);
+    }
+    let resp = login_attempt(&app, "198.51.100.3, 203.0.113.7").await;
+    assert_eq!(resp.status().as_u16(), 429);
+    let resp = login_attempt(&app, "203.0.113.8").await;
+    assert_eq!(resp.status().as_u16(), 401);
 }
 
 #[tokio::test]
-async fn rate_limit_recovers_after_wait() {
-    let app = TestApp::spawn().await;
+async fn api_is_rate_limited_per_user() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.rate_limit.api = RateBudget {
+            burst: 10,
+            per_minute: 1,
+        };
+    })
+    .await;
+
+    // Registration and login are keyed by IP, not by the users below
+    let alice = app
+        .register_user("alice@test.com", "alice", "Alice", "Alice123!", None, None)
+        .await;
+    let bob = app
+        .register_user("bob@test.com", "bob", "Bob", "Bob12345!", None, None)
+        .await;
 
-    // Exhaust the burst
-    for _ in 0..62 {
-        app.client.get(app.url("/health")).send().await.unwrap();
+    for _ in 0..10 {
+        let resp = app
+            .auth_get("/api/tenant", &alice.access_token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
     }
+    let resp = app
+        .auth_get("/api/tenant", &alice.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 429);
+    assert!(resp.headers().contains_key("retry-after"));
 
-    // Wait for token replenishment (1 token per second, wait 2s for safety)
-    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    // Bob is unaffected by Alice's usage
+    let resp = app
+        .auth_get("/api/tenant", &bob.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
 
-    // Should succeed again
+    // Health stays outside the limiter
     let resp = app.client.get(app.url("/health")).send().await.unwrap();
-    assert_eq!(
-        resp.status().as_u16(),
-        200,
-        "Request should succeed after rate limit recovery"
-    );
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn meeting_code_lookups_share_the_invite_budget() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.rate_limit.invite = RateBudget {
+            burst: 3,
+            per_minute: 1,
+        };
+    })
+    .await;
+
+    for path in [
+        "/api/join/000-000-000/info",
+        "/api/join/000-000-001/prejoin",
+        "/api/conference/code/000-000-002",
+    ] {
+        let resp = app.client.get(app.url(path)).send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 404);
+    }
+
+    // Guessing codes runs out of the one budget, whichever route asks.
+    let resp = app
+        .client
+        .get(app.url("/api/join/000-000-003/prejoin"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 429);
 }
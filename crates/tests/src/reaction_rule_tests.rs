@@ -0,0 +1,275 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+type Deliveries = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+/// Webhook receiver recording each delivery's signature header and body.
+async fn spawn_receiver() -> (String, Deliveries) {
+    use axum::{Router, body::Bytes, http::HeaderMap, routing::post};
+
+    let deliveries: Deliveries = Arc::default();
+    let recorded = deliveries.clone();
+    let router = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let recorded = recorded.clone();
+            async move {
+                let signature = headers["x-roomler-signature"].to_str().unwrap().to_string();
+                recorded.lock().unwrap().push((signature, body.to_vec()));
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}/hook", addr), deliveries)
+}
+
+async fn messages(app: &TestApp, tid: &str, room_id: &str, token: &str) -> Vec<Value> {
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    json["items"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn reaction_rules_post_messages_and_call_webhooks_once() {
+    let (hook_url, deliveries) = spawn_receiver().await;
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("reactrule").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let approvals = &tenant.rooms[0].id;
+    let log = &tenant.rooms[1].id;
+    for room_id in [approvals, log] {
+        app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), token)
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let rules_url = format!("/api/tenant/{}/reaction-rule", tid);
+    let resp = app
+        .auth_post(&rules_url, token)
+        .json(&serde_json::json!({
+            "name": "Log approvals",
+            "room_id": approvals,
+            "emoji": "✅",
+            "action": {
+                "type": "post_message",
+                "room_id": log,
+                "content": "{user} approved {message_id}",
+            },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let resp = app
+        .auth_post(&rules_url, token)
+        .json(&serde_json::json!({
+            "name": "Notify CI",
+            "emoji": "✅",
+            "action": { "type": "webhook", "url": hook_url },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let webhook_rule: Value = resp.json().await.unwrap();
+    let secret = webhook_rule["secret"].as_str().unwrap().to_string();
+
+    // Only tenant managers see and change rules.
+    let resp = app
+        .auth_get(&rules_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, approvals),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "Ship v2?" }))
+        .send()
+        .await
+        .unwrap();
+    let message: Value = resp.json().await.unwrap();
+    let message_id = message["id"].as_str().unwrap().to_string();
+    let reaction_url = format!(
+        "/api/tenant/{}/room/{}/message/{}/reaction",
+        tid, approvals, message_id
+    );
+
+    let resp = app
+        .auth_post(&reaction_url, token)
+        .json(&serde_json::json!({ "emoji": "✅" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let expected = format!("approved {}", message_id);
+    let mut logged = false;
+    for _ in 0..30 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        logged = messages(&app, tid, log, token)
+            .await
+            .iter()
+            .any(|m| m["content"].as_str().unwrap().ends_with(&expected));
+        if logged && !deliveries.lock().unwrap().is_empty() {
+            break;
+        }
+    }
+    assert!(logged);
+    {
+        let deliveries = deliveries.lock().unwrap();
+        assert_eq!(deliveries.len(), 1);
+        let (signature, body) = &deliveries[0];
+        assert_eq!(
+            signature,
+            &roomler_ai_services::reaction_rules::signature(&secret, body)
+        );
+        let event: Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(event["event"], "reaction.add");
+        assert_eq!(event["message_id"], message_id.as_str());
+        assert_eq!(event["message_content"], "Ship v2?");
+    }
+
+    // Toggling the reaction off and on again doesn't fire the rules twice.
+    app.auth_delete(&format!("{}/%E2%9C%85", reaction_url), token)
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&reaction_url, token)
+        .json(&serde_json::json!({ "emoji": "✅" }))
+        .send()
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(deliveries.lock().unwrap().len(), 1);
+    let approvals_logged = messages(&app, tid, log, token)
+        .await
+        .iter()
+        .filter(|m| m["content"].as_str().unwrap().ends_with(&expected))
+        .count();
+    assert_eq!(approvals_logged, 1);
+
+    let rule_url = format!("{}/{}", rules_url, webhook_rule["id"].as_str().unwrap());
+    let resp = app
+        .auth_put(&rule_url, token)
+        .json(&serde_json::json!({ "enabled": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let updated: Value = resp.json().await.unwrap();
+    assert_eq!(updated["enabled"], false);
+
+    let resp = app.auth_delete(&rule_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app.auth_get(&rules_url, token).send().await.unwrap();
+    let rules: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["name"], "Log approvals");
+}
+
+#[tokio::test]
+async fn room_integrations_hide_secrets_from_members() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("roomintegr").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let (watched, target, other) = (
+        &tenant.rooms[0].id,
+        &tenant.rooms[1].id,
+        &tenant.rooms[2].id,
+    );
+
+    let rules_url = format!("/api/tenant/{}/reaction-rule", tid);
+    for rule in [
+        serde_json::json!({
+            "name": "Watch room",
+            "room_id": watched,
+            "emoji": "👀",
+            "action": { "type": "webhook", "url": "http://127.0.0.1:9/hook" },
+        }),
+        serde_json::json!({
+            "name": "Post elsewhere",
+            "room_id": other,
+            "emoji": "✅",
+            "action": { "type": "post_message", "room_id": target, "content": "done" },
+        }),
+    ] {
+        let resp = app
+            .auth_post(&rules_url, admin)
+            .json(&rule)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 201);
+    }
+
+    let integrations = |room_id: &str| format!("/api/tenant/{}/room/{}/integrations", tid, room_id);
+
+    let resp = app
+        .auth_get(&integrations(watched), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["can_manage"], true);
+    let rules = json["reaction_rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["name"], "Watch room");
+    assert!(rules[0]["secret"].is_string());
+
+    // Rules posting into a room show up there too.
+    let resp = app
+        .auth_get(&integrations(target), admin)
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["reaction_rules"][0]["name"], "Post elsewhere");
+
+    // Members outside the room can't look; inside, they see no secrets.
+    let resp = app
+        .auth_get(&integrations(watched), member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tid, watched),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+    let resp = app
+        .auth_get(&integrations(watched), member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["can_manage"], false);
+    assert_eq!(json["reaction_rules"][0]["name"], "Watch room");
+    assert!(json["reaction_rules"][0].get("secret").is_none());
+}
@@ -1,4 +1,5 @@
 use crate::fixtures::test_app::TestApp;
+use reqwest::multipart;
 use serde_json::Value;
 
 /// Helper: seed tenant, join room, create a message, return (app, tenant, room_id, message_id)
@@ -359,3 +360,229 @@ async fn thread_replies_are_returned() {
         assert_eq!(item["thread_id"], message_id);
     }
 }
+
+#[tokio::test]
+async fn shortcode_reactions_are_normalized() {
+    let (app, tenant, room_id, message_id) = setup_with_message().await;
+    let tid = &tenant.tenant_id;
+    let url = format!(
+        "/api/tenant/{}/room/{}/message/{}/reaction",
+        tid, room_id, message_id
+    );
+    let token = &tenant.admin.access_token;
+
+    app.db
+        .collection::<bson::Document>("custom_emojis")
+        .insert_one(bson::doc! {
+            "tenant_id": bson::oid::ObjectId::parse_str(tid).unwrap(),
+            "name": "shipit",
+            "image_url": "https://cdn.example.com/shipit.png",
+            "is_animated": false,
+            "creator_id": bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap(),
+            "allowed_role_ids": bson::Bson::Null,
+            "created_at": bson::DateTime::now(),
+            "updated_at": bson::DateTime::now(),
+        })
+        .await
+        .unwrap();
+
+    let react = |emoji: &str| {
+        app.auth_post(&url, token)
+            .json(&serde_json::json!({ "emoji": emoji }))
+            .send()
+    };
+
+    assert_eq!(react(":thumbsup:").await.unwrap().status().as_u16(), 200);
+    // Same emoji under its Unicode form or an alias
+    assert_eq!(react("\u{1f44d}").await.unwrap().status().as_u16(), 409);
+    assert_eq!(react(":+1:").await.unwrap().status().as_u16(), 409);
+    assert_eq!(react(":ShipIt:").await.unwrap().status().as_u16(), 200);
+
+    let resp = react(":shipt:").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let err: Value = resp.json().await.unwrap();
+    assert!(
+        err["message"].as_str().unwrap().contains(":shipit:"),
+        "{err}"
+    );
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let mut emojis: Vec<&str> = json["items"][0]["reaction_summary"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["emoji"].as_str().unwrap())
+        .collect();
+    emojis.sort();
+    assert_eq!(emojis, vec![":shipit:", "\u{1f44d}"]);
+
+    // Removable by shortcode
+    let resp = app
+        .auth_delete(&format!("{}/:+1:", url), token)
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["removed"], true);
+}
+
+#[tokio::test]
+async fn custom_emoji_can_be_registered_and_reacted_with() {
+    let (app, tenant, room_id, message_id) = setup_with_message().await;
+    let tid = &tenant.tenant_id;
+    let emoji_url = format!("/api/tenant/{}/emoji", tid);
+
+    let upload = |name: &'static str, mime: &'static str| {
+        let part = multipart::Part::bytes(format!("{name} bytes").into_bytes())
+            .file_name(name)
+            .mime_str(mime)
+            .unwrap();
+        app.client
+            .post(app.url(&format!("/api/tenant/{}/file/upload", tid)))
+            .header(
+                "Authorization",
+                format!("Bearer {}", tenant.member.access_token),
+            )
+            .multipart(
+                multipart::Form::new()
+                    .part("file", part)
+                    .text("room_id", room_id.clone()),
+            )
+            .send()
+    };
+    let image: Value = upload("parrot.gif", "image/gif")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let text: Value = upload("notes.txt", "text/plain")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let create = |name: &str, file: &Value| {
+        app.auth_post(&emoji_url, &tenant.member.access_token)
+            .json(&serde_json::json!({ "name": name, "file_id": file["id"] }))
+            .send()
+    };
+    // Not an image, a bundled name, an invalid name
+    assert_eq!(create("notes", &text).await.unwrap().status().as_u16(), 422);
+    assert_eq!(
+        create(":tada:", &image).await.unwrap().status().as_u16(),
+        422
+    );
+    assert_eq!(
+        create("party parrot", &image)
+            .await
+            .unwrap()
+            .status()
+            .as_u16(),
+        422
+    );
+
+    let resp = create(":Party_Parrot:", &image).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let emoji: Value = resp.json().await.unwrap();
+    assert_eq!(emoji["name"], "party_parrot");
+    assert_eq!(emoji["shortcode"], ":party_parrot:");
+    assert_eq!(emoji["is_animated"], true);
+    assert_eq!(emoji["image_url"], image["asset_url"]);
+    let emoji_id = emoji["id"].as_str().unwrap().to_string();
+
+    // Names are unique per tenant
+    assert_eq!(
+        create("party_parrot", &image)
+            .await
+            .unwrap()
+            .status()
+            .as_u16(),
+        409
+    );
+
+    let list: Value = app
+        .auth_get(&emoji_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // React by id, then the same emoji by shortcode is a duplicate
+    let reaction_url = format!(
+        "/api/tenant/{}/room/{}/message/{}/reaction",
+        tid, room_id, message_id
+    );
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "custom_emoji_id": emoji_id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "emoji": ":party_parrot:" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "custom_emoji_id": bson::oid::ObjectId::new().to_hex() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let summary = &json["items"][0]["reaction_summary"][0];
+    assert_eq!(summary["emoji"], ":party_parrot:");
+    assert_eq!(summary["count"], 1);
+    assert_eq!(summary["custom_emoji_id"], emoji_id.as_str());
+    assert_eq!(summary["image_url"], image["asset_url"]);
+
+    // A tenant manager can remove someone else's
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", emoji_url, emoji_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let list: Value = app
+        .auth_get(&emoji_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(list.as_array().unwrap().is_empty());
+}
@@ -49,6 +49,36 @@ async fn create_recording_for_room() {
     assert_eq!(json["room_id"], room_id);
     assert_eq!(json["recording_type"], "Video");
     assert_eq!(json["status"], "Processing");
+
+    // Tenant members outside the room can't record it until they join.
+    let url = format!(
+        "/api/tenant/{}/room/{}/recording",
+        tenant.tenant_id, room_id
+    );
+    let resp = app
+        .auth_post(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "recording_type": "audio" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_post(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "recording_type": "audio" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
 }
 
 #[tokio::test]
@@ -151,3 +181,621 @@ async fn delete_recording() {
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["deleted"], true);
 }
+
+/// Create a room and a recording in it, returning `(room_id, recording_id)`.
+async fn create_recording(app: &TestApp, tenant_id: &str, token: &str) -> (String, String) {
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": "Chunked Recording" }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/recording", tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "recording_type": "video" }))
+        .send()
+        .await
+        .unwrap();
+    let rec: Value = resp.json().await.unwrap();
+    (room_id, rec["id"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn upload_parts_and_complete_recording() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec4").await;
+    let token = &tenant.admin.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, token).await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording/{}",
+        tenant.tenant_id, room_id, rec_id
+    );
+
+    // Members who may watch it can't upload or finish it
+    let resp = app
+        .auth_put(&format!("{}/access", base), token)
+        .json(&serde_json::json!({ "user_ids": [tenant.member.id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let member = &tenant.member.access_token;
+    let resp = app
+        .auth_put(&format!("{}/part/1", base), member)
+        .body("forged")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_post(&format!("{}/complete", base), member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Parts may arrive out of order; a re-upload replaces the earlier copy.
+    for (n, body) in [(2, "world"), (1, "hi"), (1, "hello ")] {
+        let resp = app
+            .auth_put(&format!("{}/part/{}", base, n), token)
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        let part: Value = resp.json().await.unwrap();
+        assert_eq!(part["part_number"], n);
+        assert_eq!(part["size"], body.len());
+    }
+
+    let resp = app
+        .auth_post(&format!("{}/complete", base), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["status"], "Available");
+    assert_eq!(json["size"], "hello world".len());
+    assert_eq!(json["part_count"], 2);
+
+    // Completing twice is rejected, as is uploading more parts.
+    let resp = app
+        .auth_post(&format!("{}/complete", base), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+    let resp = app
+        .auth_put(&format!("{}/part/3", base), token)
+        .body("late")
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_client_error());
+}
+
+#[tokio::test]
+async fn interrupted_recordings_are_recovered() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec5").await;
+    let token = &tenant.admin.access_token;
+    let (room_id, with_parts) = create_recording(&app, &tenant.tenant_id, token).await;
+    let (_, without_parts) = create_recording(&app, &tenant.tenant_id, token).await;
+
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/recording/{}/part/1",
+                tenant.tenant_id, room_id, with_parts
+            ),
+            token,
+        )
+        .body("chunk")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Simulate the boot-time job of a restarted server.
+    let uploads = roomler_ai_services::RecordingUploadService::new(
+        &app.db,
+        roomler_ai_api::routes::file::upload_dir(),
+    );
+    let report = uploads.recover_interrupted().await.unwrap();
+    assert_eq!(report.partial, 1);
+    assert_eq!(report.failed, 1);
+
+    let status = |id: &str| {
+        let dao = &uploads.dao;
+        let id = bson::oid::ObjectId::parse_str(id).unwrap();
+        async move { dao.base.find_by_id(id).await.unwrap() }
+    };
+    let rec = status(&with_parts).await;
+    assert!(matches!(
+        rec.status,
+        roomler_ai_db::models::RecordingStatus::Partial
+    ));
+    assert_eq!(rec.file.size, "chunk".len() as u64);
+    let rec = status(&without_parts).await;
+    assert!(matches!(
+        rec.status,
+        roomler_ai_db::models::RecordingStatus::Failed
+    ));
+
+    // A second pass has nothing left to do.
+    let report = uploads.recover_interrupted().await.unwrap();
+    assert_eq!(report, Default::default());
+}
+
+#[tokio::test]
+async fn recording_access_is_limited_to_participants_and_grants() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec6").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, admin).await;
+    let list = format!(
+        "/api/tenant/{}/room/{}/recording",
+        tenant.tenant_id, room_id
+    );
+    let base = format!("{}/{}", list, rec_id);
+
+    app.auth_put(&format!("{}/part/1", base), admin)
+        .body("hello world")
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&format!("{}/complete", base), admin)
+        .send()
+        .await
+        .unwrap();
+
+    // The member was not in the call: the recording is hidden.
+    let json: Value = app
+        .auth_get(&list, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 0);
+    let resp = app
+        .auth_get(&format!("{}/stream", base), member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // Only organizers manage access.
+    let grant = serde_json::json!({ "user_ids": [tenant.member.id] });
+    let resp = app
+        .auth_put(&format!("{}/access", base), member)
+        .json(&grant)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    let resp = app
+        .auth_put(&format!("{}/access", base), admin)
+        .json(&grant)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let acl: Value = resp.json().await.unwrap();
+    assert_eq!(acl["user_ids"][0], tenant.member.id);
+    assert_eq!(acl["participant_ids"][0], tenant.admin.id);
+
+    let json: Value = app
+        .auth_get(&list, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 1);
+    let resp = app
+        .auth_get(&format!("{}/stream", base), member)
+        .header("Range", "bytes=0-4")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 206);
+    assert_eq!(
+        resp.headers()["content-range"].to_str().unwrap(),
+        "bytes 0-4/11"
+    );
+    assert_eq!(resp.text().await.unwrap(), "hello");
+
+    // A granted viewer still can't manage.
+    let resp = app
+        .auth_post(&format!("{}/share", base), member)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn recording_streams_need_allow_download() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec6nodl").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, admin).await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording/{}",
+        tenant.tenant_id, room_id, rec_id
+    );
+    app.auth_put(&format!("{}/part/1", base), admin)
+        .body("hello world")
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&format!("{}/complete", base), admin)
+        .send()
+        .await
+        .unwrap();
+
+    let acl: Value = app
+        .auth_put(&format!("{}/access", base), admin)
+        .json(&serde_json::json!({ "user_ids": [tenant.member.id], "allow_download": false }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(acl["allow_download"], false);
+    assert_eq!(acl["user_ids"][0], tenant.member.id);
+
+    // The viewer sees the recording but can't fetch it any way.
+    let json: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/recording",
+                tenant.tenant_id, room_id
+            ),
+            member,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["total"], 1);
+    for path in ["download", "stream", "playback-url"] {
+        let resp = app
+            .auth_get(&format!("{}/{}", base, path), member)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 403, "{path}");
+    }
+    // Organizers still can.
+    let resp = app
+        .auth_get(&format!("{}/stream", base), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let link: Value = app
+        .auth_post(&format!("{}/share", base), admin)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let shared = format!("/api/recording/shared/{}", link["token"].as_str().unwrap());
+    let resp = app.client.get(app.url(&shared)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .client
+        .get(app.url(&format!("{}/stream", shared)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Turning downloads back on restores playback.
+    app.auth_put(&format!("{}/access", base), admin)
+        .json(&serde_json::json!({ "user_ids": [tenant.member.id], "allow_download": true }))
+        .send()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_get(&format!("{}/stream", base), member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn recording_share_links_expire_and_can_be_revoked() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec7").await;
+    let admin = &tenant.admin.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, admin).await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording/{}",
+        tenant.tenant_id, room_id, rec_id
+    );
+    app.auth_put(&format!("{}/part/1", base), admin)
+        .body("shared")
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&format!("{}/complete", base), admin)
+        .send()
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_post(&format!("{}/share", base), admin)
+        .json(&serde_json::json!({ "expires_in_secs": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let link: Value = app
+        .auth_post(&format!("{}/share", base), admin)
+        .json(&serde_json::json!({ "expires_in_secs": 3600 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = link["token"].as_str().unwrap();
+    assert!(link["expires_at"].is_string());
+
+    // No auth needed for a live link.
+    let resp = app
+        .client
+        .get(app.url(&format!("/api/recording/shared/{}/stream", token)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().await.unwrap(), "shared");
+
+    let resp = app
+        .auth_delete(&format!("{}/share/{}", base, token), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .client
+        .get(app.url(&format!("/api/recording/shared/{}", token)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // Expired links stop working even before they're revoked.
+    let recordings = roomler_ai_services::dao::recording::RecordingDao::new(&app.db);
+    let rec_oid = bson::oid::ObjectId::parse_str(&rec_id).unwrap();
+    let past = bson::DateTime::from_millis(bson::DateTime::now().timestamp_millis() - 1000);
+    recordings
+        .add_share_link(
+            rec_oid,
+            &roomler_ai_db::models::RecordingShareLink {
+                token: "expired-token".to_string(),
+                created_by: bson::oid::ObjectId::parse_str(&tenant.admin.id).unwrap(),
+                expires_at: Some(past),
+                created_at: past,
+            },
+        )
+        .await
+        .unwrap();
+    let resp = app
+        .client
+        .get(app.url("/api/recording/shared/expired-token/stream"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn recording_playback_urls_are_signed_and_seekable() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec8").await;
+    let admin = &tenant.admin.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, admin).await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording/{}",
+        tenant.tenant_id, room_id, rec_id
+    );
+
+    // Nothing to play while the upload is still open.
+    let resp = app
+        .auth_get(&format!("{}/playback-url", base), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    app.auth_put(&format!("{}/part/1", base), admin)
+        .body("0123456789")
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&format!("{}/complete", base), admin)
+        .send()
+        .await
+        .unwrap();
+
+    let playback: Value = app
+        .auth_get(&format!("{}/playback-url", base), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = playback["url"].as_str().unwrap();
+    assert!(url.starts_with(&format!("/api/recording/signed/{}/stream?", rec_id)));
+    assert!(playback["expires_at"].is_string());
+
+    // The signed URL needs no auth and serves ranges, so a player can seek.
+    let resp = app
+        .client
+        .get(app.url(url))
+        .header("Range", "bytes=4-6")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 206);
+    assert_eq!(resp.headers()["content-type"], "video/webm");
+    assert_eq!(resp.headers()["content-range"], "bytes 4-6/10");
+    assert_eq!(resp.text().await.unwrap(), "456");
+    let resp = app.client.get(app.url(url)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-length"], "10");
+    assert_eq!(resp.text().await.unwrap(), "0123456789");
+
+    // A tampered or expired signature is refused.
+    let tampered = url.replace("expires=", "expires=1");
+    let resp = app.client.get(app.url(&tampered)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let (expired, _) = roomler_ai_services::recording_playback::signed_path(
+        &app.settings.recording_playback.signing_secret,
+        bson::oid::ObjectId::parse_str(&rec_id).unwrap(),
+        chrono::Utc::now().timestamp() - 7200,
+        3600,
+    );
+    let resp = app.client.get(app.url(&expired)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn recording_clips_are_validated_and_cut_in_the_background() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec8").await;
+    let token = &tenant.admin.access_token;
+    let (room_id, rec_id) = create_recording(&app, &tenant.tenant_id, token).await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording/{}",
+        tenant.tenant_id, room_id, rec_id
+    );
+    let clip = |start: f64, end: f64| {
+        app.auth_post(&format!("{}/clip", base), token)
+            .json(&serde_json::json!({ "start_secs": start, "end_secs": end }))
+    };
+
+    // Nothing to cut until the upload is complete.
+    let resp = clip(0.0, 5.0).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    app.auth_put(&format!("{}/part/1", base), token)
+        .body("not really a video")
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(&format!("{}/complete", base), token)
+        .send()
+        .await
+        .unwrap();
+
+    let resp = clip(5.0, 5.0).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = clip(0.0, 601.0).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // The test config points at a missing ffmpeg, so the cut itself fails.
+    let resp = clip(0.0, 5.0).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let json: Value = resp.json().await.unwrap();
+    let task_url = json["task_url"].as_str().unwrap().to_string();
+
+    let mut failed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(&task_url, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Failed" => {
+                failed = true;
+                assert!(json["error"].as_str().unwrap().contains("ffmpeg"));
+                break;
+            }
+            "Completed" => panic!("Clip completed without ffmpeg"),
+            _ => {}
+        }
+    }
+    assert!(failed, "Clip task did not finish within timeout");
+
+    let clips: Value = app
+        .auth_get(&format!("{}/clip", base), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(clips, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn recording_participants_are_added_per_recording() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec9").await;
+    let admin = &tenant.admin.access_token;
+    let (room_id, first) = create_recording(&app, &tenant.tenant_id, admin).await;
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/recording",
+                tenant.tenant_id, room_id
+            ),
+            admin,
+        )
+        .json(&serde_json::json!({ "recording_type": "audio" }))
+        .send()
+        .await
+        .unwrap();
+    let second: Value = resp.json().await.unwrap();
+
+    let dao = roomler_ai_services::dao::recording::RecordingDao::new(&app.db);
+    let id = |id: &str| bson::oid::ObjectId::parse_str(id).unwrap();
+    let member = id(&tenant.member.id);
+    assert!(
+        dao.add_recording_participants(id(&first), &[member])
+            .await
+            .unwrap()
+    );
+
+    // Only the recording being finished gets them, not others in the room
+    let first = dao.base.find_by_id(id(&first)).await.unwrap();
+    assert!(first.acl.participant_ids.contains(&member));
+    let second = dao
+        .base
+        .find_by_id(id(second["id"].as_str().unwrap()))
+        .await
+        .unwrap();
+    assert!(!second.acl.participant_ids.contains(&member));
+}
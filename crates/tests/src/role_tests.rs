@@ -256,3 +256,106 @@ async fn non_member_cannot_list_roles() {
         "Non-member should get 403 Forbidden when listing roles"
     );
 }
+
+#[tokio::test]
+async fn custom_role_grants_selected_permissions() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("role7").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let names: Vec<Value> = app
+        .auth_get(&format!("/api/tenant/{}/role/permissions", tid), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let bit = |name: &str| {
+        names.iter().find(|p| p["name"] == name).unwrap()["bit"]
+            .as_u64()
+            .unwrap()
+    };
+    assert_eq!(names.len(), 24);
+
+    let invite_url = format!("/api/tenant/{}/invite", tid);
+    let resp = app
+        .auth_post(&invite_url, member)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let json: Value = resp.json().await.unwrap();
+    assert!(json["message"].as_str().unwrap().contains("INVITE_MEMBERS"));
+
+    // Members can't manage roles until a role says so.
+    let create_role = |token: &str, name: &str, perms: u64| {
+        app.auth_post(&format!("/api/tenant/{}/role", tid), token)
+            .json(&serde_json::json!({ "name": name, "permissions": perms }))
+            .send()
+    };
+    let recruiter_perms = bit("INVITE_MEMBERS") | bit("MANAGE_ROLES");
+    let resp = create_role(member, "recruiter", recruiter_perms)
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = create_role(admin, "unknown", 1 << 40).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let role: Value = create_role(admin, "recruiter", recruiter_perms)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/role/{}/assign/{}",
+            tid,
+            role["id"].as_str().unwrap(),
+            tenant.member.id
+        ),
+        admin,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let resp = app
+        .auth_post(&invite_url, member)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+
+    // They can hand out only what they hold themselves.
+    let resp = create_role(member, "manager", bit("MANAGE_TENANT"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = create_role(member, "inviter", bit("INVITE_MEMBERS"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let roles: Vec<Value> = app
+        .auth_get(&format!("/api/tenant/{}/role", tid), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let owner = roles.iter().find(|r| r["name"] == "owner").unwrap();
+    let resp = app
+        .auth_post(&invite_url, member)
+        .json(&serde_json::json!({ "assign_role_ids": [owner["id"]] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
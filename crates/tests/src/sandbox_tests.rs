@@ -0,0 +1,84 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn sandbox_tenant_is_marked_and_can_be_reset() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sbxprod").await;
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_post("/api/tenant", token)
+        .json(&serde_json::json!({
+            "name": "Sandbox Corp",
+            "slug": "sbx",
+            "sandbox": true,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let sandbox: Value = resp.json().await.unwrap();
+    assert_eq!(sandbox["sandbox"], true);
+    let sid = sandbox["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room", sid), token)
+        .json(&serde_json::json!({ "name": "playground", "is_open": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["x-roomler-sandbox"], "true");
+    let room: Value = resp.json().await.unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    app.auth_post(&format!("/api/tenant/{}/room/{}/join", sid, room_id), token)
+        .send()
+        .await
+        .unwrap();
+    let messages_url = format!("/api/tenant/{}/room/{}/message", sid, room_id);
+    for i in 0..3 {
+        let resp = app
+            .auth_post(&messages_url, token)
+            .json(&serde_json::json!({ "content": format!("test {}", i) }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    // Production tenants are neither marked nor resettable.
+    let prod_url = format!("/api/tenant/{}/sandbox/reset", tenant.tenant_id);
+    let resp = app.auth_post(&prod_url, token).send().await.unwrap();
+    assert!(resp.headers().get("x-roomler-sandbox").is_none());
+    assert_eq!(resp.status().as_u16(), 409);
+    let resp = app
+        .auth_post(&prod_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/sandbox/reset", sid), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let report: Value = resp.json().await.unwrap();
+    assert_eq!(report["messages"], 3);
+
+    let resp = app.auth_get(&messages_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["total"], 0);
+
+    // Rooms survive the reset.
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/room/{}", sid, room_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
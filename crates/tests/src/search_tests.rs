@@ -0,0 +1,91 @@
+use crate::fixtures::test_app::TestApp;
+use bson::oid::ObjectId;
+use serde_json::Value;
+
+async fn insert_segment(app: &TestApp, tenant_id: &str, room_id: &str, track: &str, text: &str) {
+    app.db
+        .collection::<bson::Document>("transcript_segments")
+        .insert_one(bson::doc! {
+            "tenant_id": ObjectId::parse_str(tenant_id).unwrap(),
+            "room_id": ObjectId::parse_str(room_id).unwrap(),
+            "track": track,
+            "user_id": ObjectId::new(),
+            "speaker_name": "Speaker",
+            "text": text,
+            "language": "en",
+            "confidence": bson::Bson::Null,
+            "start_time": 12.5,
+            "end_time": 14.0,
+            "created_at": bson::DateTime::now(),
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn search_includes_call_transcripts_and_scopes_to_a_channel() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("search").await;
+    let tid = &tenant.tenant_id;
+    let general = &tenant.rooms[0].id;
+    let engineering = &tenant.rooms[1].id;
+    let token = &tenant.admin.access_token;
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tid, general),
+        token,
+    )
+    .json(&serde_json::json!({ "content": "Roadmap draft is up" }))
+    .send()
+    .await
+    .unwrap();
+    insert_segment(&app, tid, general, "original", "let's go over the roadmap").await;
+    // Translations repeat what was said; only the original is searched.
+    insert_segment(&app, tid, general, "de", "roadmap besprechen").await;
+    insert_segment(&app, tid, engineering, "original", "the roadmap slipped").await;
+
+    let search = |scope: Option<&str>| {
+        let mut url = format!("/api/tenant/{}/search?q=roadmap", tid);
+        if let Some(room_id) = scope {
+            url.push_str(&format!("&room_id={}", room_id));
+        }
+        app.auth_get(&url, token).send()
+    };
+
+    let json: Value = search(None).await.unwrap().json().await.unwrap();
+    assert_eq!(json["messages"].as_array().unwrap().len(), 1);
+    let transcripts = json["transcripts"].as_array().unwrap();
+    assert_eq!(transcripts.len(), 2);
+    let hit = transcripts
+        .iter()
+        .find(|t| t["room_id"] == general.as_str())
+        .unwrap();
+    assert_eq!(hit["result_type"], "transcript");
+    assert_eq!(hit["room_name"], "general");
+    assert_eq!(hit["speaker_name"], "Speaker");
+    assert_eq!(hit["content_preview"], "let's go over the roadmap");
+    assert_eq!(
+        hit["link"],
+        format!("/tenant/{}/room/{}/call", tid, general)
+    );
+
+    // A channel's search covers its own messages and transcripts.
+    let json: Value = search(Some(general)).await.unwrap().json().await.unwrap();
+    assert_eq!(json["messages"].as_array().unwrap().len(), 1);
+    let transcripts = json["transcripts"].as_array().unwrap();
+    assert_eq!(transcripts.len(), 1);
+    assert_eq!(transcripts[0]["room_id"], general.as_str());
+    assert!(json["rooms"].as_array().unwrap().is_empty());
+
+    let json: Value = search(Some(engineering))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(json["messages"].as_array().unwrap().is_empty());
+    assert_eq!(json["transcripts"].as_array().unwrap().len(), 1);
+
+    let resp = search(Some("not-an-id")).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
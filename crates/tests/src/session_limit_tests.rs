@@ -0,0 +1,121 @@
+use crate::fixtures::test_app::TestApp;
+use futures::StreamExt;
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// The next JSON event, or `{ "closed": code }` for a close frame.
+async fn next(ws: &mut WsStream) -> Value {
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Close(frame) => {
+                return serde_json::json!({ "closed": u16::from(frame.unwrap().code) });
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn connect(app: &TestApp, token: &str) -> WsStream {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    assert_eq!(next(&mut ws).await["type"], "connected");
+    ws
+}
+
+async fn sessions(app: &TestApp, token: &str) -> Value {
+    app.auth_get("/api/auth/me/sessions", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn oldest_session_is_evicted_past_the_plan_limit() {
+    let app = TestApp::spawn().await;
+    let user = app
+        .register_user(
+            "many@tabs.test",
+            "many_tabs",
+            "Tabs",
+            "Passw0rd!",
+            None,
+            None,
+        )
+        .await;
+    let token = &user.access_token;
+
+    let json = sessions(&app, token).await;
+    assert_eq!(json["active"], 0);
+    assert_eq!(json["policy"], "evict_oldest");
+    let limit = json["limit"].as_u64().unwrap() as usize;
+
+    let mut connections = Vec::new();
+    for _ in 0..limit {
+        connections.push(connect(&app, token).await);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let json = sessions(&app, token).await;
+    assert_eq!(json["active"], limit);
+    let oldest = json["items"][0]["connection_id"].clone();
+
+    let _newest = connect(&app, token).await;
+    let evicted = next(&mut connections[0]).await;
+    assert_eq!(evicted["type"], "session:evicted");
+    assert_eq!(evicted["data"]["limit"], limit);
+    assert!(evicted["data"]["message"].is_string());
+    assert_eq!(next(&mut connections[0]).await["closed"], 4002);
+
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let json = sessions(&app, token).await;
+    assert_eq!(json["active"], limit);
+    let ids: Vec<&Value> = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| &s["connection_id"])
+        .collect();
+    assert!(!ids.contains(&&oldest));
+}
+
+#[tokio::test]
+async fn new_session_is_rejected_past_the_plan_limit() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.ws.session_limit_policy = roomler_ai_config::SessionLimitPolicy::Reject;
+    })
+    .await;
+    let user = app
+        .register_user(
+            "strict@tabs.test",
+            "strict_tabs",
+            "Tabs",
+            "Passw0rd!",
+            None,
+            None,
+        )
+        .await;
+    let token = &user.access_token;
+    let limit = sessions(&app, token).await["limit"].as_u64().unwrap() as usize;
+
+    let mut connections = Vec::new();
+    for _ in 0..limit {
+        connections.push(connect(&app, token).await);
+    }
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut extra, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    let rejected = next(&mut extra).await;
+    assert_eq!(rejected["type"], "session:rejected");
+    assert_eq!(next(&mut extra).await["closed"], 4002);
+
+    let json = sessions(&app, token).await;
+    assert_eq!(json["active"], limit);
+    assert_eq!(json["policy"], "reject");
+}
@@ -0,0 +1,205 @@
+use crate::fixtures::test_app::TestApp;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+type Ws =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn next_of(ws: &mut Ws, types: &[&str]) -> Value {
+    loop {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws.next())
+            .await
+            .expect("Timed out waiting for WS message")
+            .unwrap()
+            .unwrap();
+        let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        if types.contains(&parsed["type"].as_str().unwrap_or("")) {
+            return parsed;
+        }
+    }
+}
+
+async fn send(ws: &mut Ws, msg_type: &str, data: Value) {
+    ws.send(Message::Text(
+        serde_json::to_string(&serde_json::json!({ "type": msg_type, "data": data }))
+            .unwrap()
+            .into(),
+    ))
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn shared_drafts_are_co_edited_and_published() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("drafts").await;
+    let room_id = &tenant.rooms[0].id;
+    let drafts_url = format!("/api/tenant/{}/room/{}/draft", tenant.tenant_id, room_id);
+
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let resp = app
+        .auth_post(&drafts_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "Release today" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let draft: Value = resp.json().await.unwrap();
+    let draft_id = draft["id"].as_str().unwrap().to_string();
+    assert_eq!(draft["version"], 0);
+
+    let list: Value = app
+        .auth_get(&drafts_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // Both open it over the WebSocket
+    let mut admin_ws = connect(&app, &tenant.admin.access_token).await;
+    let mut member_ws = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut admin_ws,
+        "draft:join",
+        serde_json::json!({ "draft_id": &draft_id }),
+    )
+    .await;
+    let state = next_of(&mut admin_ws, &["draft:state"]).await;
+    assert_eq!(state["data"]["content"], "Release today");
+    send(
+        &mut member_ws,
+        "draft:join",
+        serde_json::json!({ "draft_id": &draft_id }),
+    )
+    .await;
+    next_of(&mut member_ws, &["draft:state"]).await;
+    let presence = next_of(&mut admin_ws, &["draft:presence"]).await;
+    assert_eq!(presence["data"]["editor_ids"].as_array().unwrap().len(), 2);
+
+    // Two edits made against version 0
+    send(
+        &mut admin_ws,
+        "draft:op",
+        serde_json::json!({
+            "draft_id": &draft_id,
+            "version": 0,
+            "op": { "kind": "insert", "pos": 13, "text": " at 5pm" },
+        }),
+    )
+    .await;
+    let ack = next_of(&mut admin_ws, &["draft:ack"]).await;
+    assert_eq!(ack["data"]["version"], 1);
+    let op = next_of(&mut member_ws, &["draft:op"]).await;
+    assert_eq!(op["data"]["version"], 1);
+
+    send(
+        &mut member_ws,
+        "draft:op",
+        serde_json::json!({
+            "draft_id": &draft_id,
+            "version": 0,
+            "op": { "kind": "insert", "pos": 0, "text": "Heads up: " },
+        }),
+    )
+    .await;
+    let ack = next_of(&mut member_ws, &["draft:ack"]).await;
+    assert_eq!(ack["data"]["version"], 2);
+    next_of(&mut admin_ws, &["draft:op"]).await;
+
+    let draft: Value = app
+        .auth_get(
+            &format!("{}/{}", drafts_url, draft_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(draft["content"], "Heads up: Release today at 5pm");
+    assert_eq!(draft["version"], 2);
+
+    // Edits against a version the draft hasn't reached are refused
+    send(
+        &mut member_ws,
+        "draft:op",
+        serde_json::json!({
+            "draft_id": &draft_id,
+            "version": 7,
+            "op": { "kind": "delete", "pos": 0, "len": 1 },
+        }),
+    )
+    .await;
+    next_of(&mut member_ws, &["draft:error"]).await;
+
+    // The member can't discard the admin's draft
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", drafts_url, draft_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(
+            &format!("{}/{}/publish", drafts_url, draft_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let message: Value = resp.json().await.unwrap();
+    assert_eq!(message["content"], "Heads up: Release today at 5pm");
+    assert_eq!(message["author_id"], tenant.member.id);
+
+    let created = next_of(&mut admin_ws, &["message:create"]).await;
+    assert_eq!(created["data"]["id"], message["id"]);
+    let closed = next_of(&mut admin_ws, &["draft:closed"]).await;
+    assert_eq!(closed["data"]["reason"], "published");
+    assert_eq!(closed["data"]["message_id"], message["id"]);
+
+    let resp = app
+        .auth_post(
+            &format!("{}/{}/publish", drafts_url, draft_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let list: Value = app
+        .auth_get(&drafts_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(list.as_array().unwrap().is_empty());
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    next_of(&mut ws, &["connected"]).await;
+    ws
+}
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::fixtures::test_app::TestApp;
+use bson::doc;
+use serde_json::{Value, json};
+
+const CLIENT_ID: &str = "roomler-test";
+const CLIENT_SECRET: &str = "sso-test-secret";
+
+/// OpenID Connect provider that signs ID tokens with the client secret.
+/// The authorization code is `{email}|{nonce}`, so a test picks who signs
+/// in and which nonce the token carries.
+async fn spawn_idp() -> String {
+    use axum::{Form, Json, Router, routing::get, routing::post};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+    let discovery = json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/authorize"),
+        "token_endpoint": format!("{issuer}/token"),
+        "jwks_uri": format!("{issuer}/jwks"),
+    });
+    let token_issuer = issuer.clone();
+    let router = Router::new()
+        .route(
+            "/.well-known/openid-configuration",
+            get(move || async move { Json(discovery) }),
+        )
+        .route("/jwks", get(|| async { Json(json!({ "keys": [] })) }))
+        .route(
+            "/token",
+            post(move |Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(form["client_secret"], CLIENT_SECRET);
+                let (email, nonce) = form["code"].split_once('|').unwrap();
+                let claims = json!({
+                    "iss": token_issuer,
+                    "aud": CLIENT_ID,
+                    "sub": format!("idp-{email}"),
+                    "exp": chrono::Utc::now().timestamp() + 300,
+                    "nonce": nonce,
+                    "email": email,
+                    "email_verified": true,
+                    "name": "Jane Doe",
+                });
+                let id_token = encode(
+                    &Header::default(),
+                    &claims,
+                    &EncodingKey::from_secret(CLIENT_SECRET.as_bytes()),
+                )
+                .unwrap();
+                Json(json!({ "access_token": "x", "token_type": "Bearer", "id_token": id_token }))
+            }),
+        );
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    issuer
+}
+
+/// DNS-over-HTTPS resolver answering TXT queries from `records`, by name.
+async fn spawn_resolver(records: Arc<Mutex<HashMap<String, String>>>) -> String {
+    use axum::{Json, Router, extract::Query, routing::get};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/dns-query", listener.local_addr().unwrap());
+    let router = Router::new().route(
+        "/dns-query",
+        get(
+            move |Query(query): Query<HashMap<String, String>>| async move {
+                assert_eq!(query["type"], "TXT");
+                let answer: Vec<Value> = records
+                    .lock()
+                    .unwrap()
+                    .get(&query["name"])
+                    .map(|value| json!({ "name": query["name"], "type": 16, "TTL": 60, "data": format!("\"{value}\"") }))
+                    .into_iter()
+                    .collect();
+                let status = if answer.is_empty() { 3 } else { 0 };
+                Json(json!({ "Status": status, "Answer": answer }))
+            },
+        ),
+    );
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    url
+}
+
+/// Start a sign-in; returns the `state` and `nonce` sent to the provider.
+async fn begin_login(app: &TestApp, client: &reqwest::Client, slug: &str) -> (String, String) {
+    let resp = client
+        .get(app.url(&format!("/api/oauth/sso/{slug}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 307);
+    let location = reqwest::Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    assert!(location.path().ends_with("/authorize"));
+    let params: HashMap<String, String> = location.query_pairs().into_owned().collect();
+    assert_eq!(params["client_id"], CLIENT_ID);
+    let cookie = resp.headers()["set-cookie"].to_str().unwrap();
+    assert!(cookie.starts_with(&format!("roomler_sso_state={}", params["state"])));
+    (params["state"].clone(), params["nonce"].clone())
+}
+
+#[tokio::test]
+async fn sso_provisions_members_and_captures_the_domain() {
+    let issuer = spawn_idp().await;
+    let records = Arc::new(Mutex::new(HashMap::new()));
+    let resolver = spawn_resolver(records.clone()).await;
+    let app = TestApp::spawn_with_settings(|s| s.sso.dns_resolver_url = resolver).await;
+    let tenant = app.seed_tenant("ssocorp").await;
+    let url = format!("/api/tenant/{}/sso", tenant.tenant_id);
+    let config = json!({
+        "issuer": issuer,
+        "client_id": CLIENT_ID,
+        "client_secret": CLIENT_SECRET,
+        "domains": ["SSOCorp.test"],
+        "enforce": true,
+    });
+
+    // Not on the free plan
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&config)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    app.db
+        .collection::<bson::Document>("tenants")
+        .update_one(
+            doc! { "_id": bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap() },
+            doc! { "$set": { "plan": "business" } },
+        )
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_put(&url, &tenant.member.access_token)
+        .json(&config)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Domains must be verified, and public ones never
+    for domains in [json!(["ssocorp.test"]), json!(["gmail.com"])] {
+        let mut body = config.clone();
+        body["domains"] = domains;
+        let resp = app
+            .auth_put(&url, &tenant.admin.access_token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+
+    // Proving the domain with a TXT record
+    let domain_url = format!("{url}/domain");
+    let resp = app
+        .auth_post(&domain_url, &tenant.member.access_token)
+        .json(&json!({ "domain": "SSOCorp.test" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_post(&domain_url, &tenant.admin.access_token)
+        .json(&json!({ "domain": "SSOCorp.test" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let domain: Value = resp.json().await.unwrap();
+    assert_eq!(domain["record_name"], "_roomler-challenge.ssocorp.test");
+    assert_eq!(domain["verified"], false);
+    let verify_url = format!("{domain_url}/ssocorp.test/verify");
+    let resp = app
+        .auth_post(&verify_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    // A record with another token doesn't count
+    records.lock().unwrap().insert(
+        "_roomler-challenge.ssocorp.test".to_string(),
+        "roomler-domain-verification=guess".to_string(),
+    );
+    let resp = app
+        .auth_post(&verify_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    records.lock().unwrap().insert(
+        "_roomler-challenge.ssocorp.test".to_string(),
+        domain["record_value"].as_str().unwrap().to_string(),
+    );
+    let resp = app
+        .auth_post(&verify_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let domain: Value = resp.json().await.unwrap();
+    assert_eq!(domain["verified"], true);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&config)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["domains"], json!(["ssocorp.test"]));
+    assert_eq!(body["has_client_secret"], true);
+    assert_eq!(body["active"], true);
+    assert!(body.get("client_secret").is_none());
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/sso?email=jane@ssocorp.test"))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["tenant_slug"], "ssocorp");
+    assert_eq!(body["enforced"], true);
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let (state, nonce) = begin_login(&app, &client, "ssocorp").await;
+    let code = format!("jane@ssocorp.test|{nonce}");
+    let callback = app.url("/api/oauth/sso/ssocorp/callback");
+
+    // Without the state cookie the callback is refused
+    let resp = client
+        .get(&callback)
+        .query(&[("code", &code), ("state", &state)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    // A token for another sign-in's nonce is refused
+    let resp = client
+        .get(&callback)
+        .query(&[("code", "jane@ssocorp.test|other"), ("state", &state)])
+        .header("cookie", format!("roomler_sso_state={state}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = client
+        .get(&callback)
+        .query(&[("code", &code), ("state", &state)])
+        .header("cookie", format!("roomler_sso_state={state}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 302);
+    let location = reqwest::Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    let token = location
+        .query_pairs()
+        .find(|(k, _)| k == "token")
+        .unwrap()
+        .1
+        .into_owned();
+
+    // Provisioned into the tenant on the first sign-in
+    let resp = app.auth_get("/api/tenant", &token).send().await.unwrap();
+    let tenants: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(tenants.len(), 1);
+    assert_eq!(tenants[0]["slug"], "ssocorp");
+
+    // An existing account isn't taken over by the provider vouching for
+    // its address: its owner links it with their password first
+    let (state, nonce) = begin_login(&app, &client, "ssocorp").await;
+    let resp = client
+        .get(&callback)
+        .query(&[
+            ("code", format!("member@ssocorp.test|{nonce}")),
+            ("state", state.clone()),
+        ])
+        .header("cookie", format!("roomler_sso_state={state}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 302);
+    let location = reqwest::Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    let params: HashMap<String, String> = location.query_pairs().into_owned().collect();
+    assert!(!params.contains_key("token"));
+    let link_url = app.url("/api/oauth/sso/ssocorp/link");
+    let resp = app
+        .client
+        .post(&link_url)
+        .json(&json!({ "token": params["sso_link"], "password": "Wrong123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = app
+        .client
+        .post(&link_url)
+        .json(&json!({ "token": "forged", "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+    let resp = app
+        .client
+        .post(&link_url)
+        .json(&json!({ "token": params["sso_link"], "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["user"]["email"], "member@ssocorp.test");
+
+    // Linked, the account signs in through the provider
+    let (state, nonce) = begin_login(&app, &client, "ssocorp").await;
+    let resp = client
+        .get(&callback)
+        .query(&[
+            ("code", format!("member@ssocorp.test|{nonce}")),
+            ("state", state.clone()),
+        ])
+        .header("cookie", format!("roomler_sso_state={state}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 302);
+    let location = reqwest::Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    assert!(location.query_pairs().any(|(k, _)| k == "token"));
+
+    // The captured domain can't use passwords any more
+    let resp = app
+        .client
+        .post(app.url("/api/auth/login"))
+        .json(&json!({ "email": "member@ssocorp.test", "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .client
+        .post(app.url("/api/auth/register"))
+        .json(&json!({
+            "email": "new@ssocorp.test",
+            "username": "newuser",
+            "display_name": "New User",
+            "password": "Password123!",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Turning SSO off releases the domain
+    let resp = app
+        .auth_delete(&url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    let resp = app
+        .client
+        .post(app.url("/api/auth/login"))
+        .json(&json!({ "email": "member@ssocorp.test", "password": "Member123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn sso_issuer_must_be_a_public_address() {
+    let app = TestApp::spawn_with_settings(|s| s.outbound.allow_private_targets = false).await;
+    let tenant = app.seed_tenant("ssointernal").await;
+    app.db
+        .collection::<bson::Document>("tenants")
+        .update_one(
+            doc! { "_id": bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap() },
+            doc! { "$set": { "plan": "business" } },
+        )
+        .await
+        .unwrap();
+    let url = format!("/api/tenant/{}/sso", tenant.tenant_id);
+
+    for issuer in [
+        "http://127.0.0.1:9",
+        "http://localhost",
+        "http://10.0.0.5/realms/corp",
+        "http://169.254.169.254",
+        "ftp://93.184.216.34",
+    ] {
+        let resp = app
+            .auth_put(&url, &tenant.admin.access_token)
+            .json(&json!({
+                "issuer": issuer,
+                "client_id": CLIENT_ID,
+                "client_secret": CLIENT_SECRET,
+                "domains": [],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{issuer}");
+        let body: Value = resp.json().await.unwrap();
+        assert!(
+            body["message"].as_str().unwrap().starts_with("issuer:"),
+            "{issuer}: {body}"
+        );
+    }
+}
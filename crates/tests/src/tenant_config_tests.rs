@@ -0,0 +1,143 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+fn actions(diff: &Value, action: &str) -> Vec<String> {
+    diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|c| c["action"] == action)
+        .map(|c| c["key"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn tenant_config_exports_and_applies_to_another_tenant() {
+    let app = TestApp::spawn().await;
+    let source = app.seed_tenant("configsrc").await;
+    let target = app.seed_tenant("configdst").await;
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/config/export", source.tenant_id),
+            &source.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let mut bundle: Value = resp.json().await.unwrap();
+    assert_eq!(bundle["version"], 1);
+    assert!(
+        bundle["rooms"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|r| r["name"] == "engineering")
+    );
+
+    bundle["roles"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!({
+            "name": "Support",
+            "permissions": 1,
+        }));
+    bundle["rooms"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!({
+            "name": "backend",
+            "parent": "engineering",
+            "topic": "APIs",
+            "is_open": true,
+            "role_overwrites": [{ "role": "Support", "allow": 128 }],
+        }));
+
+    let diff_url = format!("/api/tenant/{}/config/diff", target.tenant_id);
+    let resp = app
+        .auth_post(&diff_url, &target.admin.access_token)
+        .json(&bundle)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let diff: Value = resp.json().await.unwrap();
+    let created = actions(&diff, "create");
+    assert!(created.contains(&"Support".to_string()));
+    assert!(created.contains(&"engineering.backend".to_string()));
+
+    // Members can't read or change the configuration.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/config/apply", target.tenant_id),
+            &target.member.access_token,
+        )
+        .json(&bundle)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/config/apply", target.tenant_id),
+            &target.admin.access_token,
+        )
+        .json(&bundle)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let applied: Value = resp.json().await.unwrap();
+    assert_eq!(applied["applied"], true);
+
+    // Applying again is a no-op.
+    let resp = app
+        .auth_post(&diff_url, &target.admin.access_token)
+        .json(&bundle)
+        .send()
+        .await
+        .unwrap();
+    let diff: Value = resp.json().await.unwrap();
+    assert!(actions(&diff, "create").is_empty());
+    assert!(actions(&diff, "update").is_empty());
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/config/export?format=yaml", target.tenant_id),
+            &target.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(
+        resp.headers()["content-type"].to_str().unwrap(),
+        "application/yaml"
+    );
+    let yaml = resp.text().await.unwrap();
+    assert!(yaml.contains("name: backend"));
+    assert!(yaml.contains("parent: engineering"));
+    assert!(yaml.contains("role: Support"));
+}
+
+#[tokio::test]
+async fn tenant_config_rejects_dangling_parents() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("configbad").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/config/apply", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "version": 1,
+            "rooms": [{ "name": "orphan", "parent": "nowhere" }],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
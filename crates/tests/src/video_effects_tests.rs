@@ -0,0 +1,144 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use reqwest::multipart;
+use serde_json::Value;
+
+#[tokio::test]
+async fn video_effects_follow_plan_and_approved_backgrounds() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("videoeffects").await;
+    let tid = &tenant.tenant_id;
+    let room_id = tenant.rooms[0].id.clone();
+    let url = format!("/api/tenant/{}/video-effects", tid);
+    let background_url = format!("{}/background", url);
+
+    // A new tenant is on the Free plan: no effects, the Free video cap.
+    let json: Value = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["effective"]["background_blur"], false);
+    assert_eq!(json["effective"]["virtual_backgrounds"], false);
+    assert_eq!(json["effective"]["max_resolution"]["width"], 640);
+    assert_eq!(json["effective"]["max_resolution"]["height"], 360);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tid, room_id),
+        &tenant.admin.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let part = multipart::Part::bytes(b"beach bytes".to_vec())
+        .file_name("beach.png")
+        .mime_str("image/png")
+        .unwrap();
+    let image: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/file/upload", tid)))
+        .header(
+            "Authorization",
+            format!("Bearer {}", tenant.admin.access_token),
+        )
+        .multipart(
+            multipart::Form::new()
+                .part("file", part)
+                .text("room_id", room_id),
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let create = |token: &str| {
+        app.auth_post(&background_url, token)
+            .json(&serde_json::json!({ "name": "Beach", "file_id": image["id"] }))
+            .send()
+    };
+
+    // Virtual backgrounds need a plan that includes them.
+    assert_eq!(
+        create(&tenant.admin.access_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16(),
+        403
+    );
+    let tenant_oid = ObjectId::parse_str(tid).unwrap();
+    app.db
+        .collection::<bson::Document>("tenants")
+        .update_one(
+            doc! { "_id": tenant_oid },
+            doc! { "$set": { "plan": "business" } },
+        )
+        .await
+        .unwrap();
+
+    // Only tenant managers approve backgrounds.
+    assert_eq!(
+        create(&tenant.member.access_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16(),
+        403
+    );
+    let resp = create(&tenant.admin.access_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let background: Value = resp.json().await.unwrap();
+    assert_eq!(background["name"], "Beach");
+    assert_eq!(background["image_url"], image["asset_url"]);
+
+    let json: Value = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["effective"]["background_blur"], true);
+    assert_eq!(json["effective"]["virtual_backgrounds"], true);
+    assert_eq!(json["effective"]["max_resolution"]["width"], 1920);
+    assert_eq!(json["effective"]["backgrounds"][0]["id"], background["id"]);
+
+    // Turning virtual backgrounds off hides the approved images.
+    let json: Value = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "virtual_backgrounds": false }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["effective"]["virtual_backgrounds"], false);
+    assert_eq!(json["effective"]["background_blur"], true);
+    assert!(
+        json["effective"]["backgrounds"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(json["plan_defaults"]["virtual_backgrounds"], true);
+
+    let delete_url = format!("{}/{}", background_url, background["id"].as_str().unwrap());
+    let resp = app
+        .auth_delete(&delete_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_delete(&delete_url, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
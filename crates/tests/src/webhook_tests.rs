@@ -0,0 +1,273 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+/// (event header, signature header, body) of each request received.
+type Received = Arc<Mutex<Vec<(String, String, Vec<u8>)>>>;
+
+/// Webhook endpoint that answers 500 to the first request and 200 after.
+async fn spawn_receiver() -> (String, Received) {
+    use axum::{
+        Router,
+        body::Bytes,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+    };
+
+    let received: Received = Arc::default();
+    let recorded = received.clone();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let router = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let recorded = recorded.clone();
+            let calls = calls.clone();
+            async move {
+                let header = |name: &str| headers[name].to_str().unwrap().to_string();
+                recorded.lock().unwrap().push((
+                    header("x-roomler-event"),
+                    header("x-roomler-signature"),
+                    body.to_vec(),
+                ));
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    StatusCode::OK
+                }
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}/hook", addr), received)
+}
+
+/// Poll the delivery log until it has `count` entries whose newest is no
+/// longer waiting on its first attempt.
+async fn deliveries(app: &TestApp, url: &str, token: &str, count: usize) -> Vec<Value> {
+    let mut items = Vec::new();
+    for _ in 0..50 {
+        let json: Value = app
+            .auth_get(url, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        items = json["items"].as_array().unwrap().clone();
+        if items.len() == count && items[0]["attempts"].as_u64() > Some(0) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    items
+}
+
+#[tokio::test]
+async fn webhooks_are_signed_logged_and_retried() {
+    let (hook_url, received) = spawn_receiver().await;
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("webhooks").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let room_id = &tenant.rooms[0].id;
+
+    let webhooks_url = format!("/api/tenant/{}/webhook", tid);
+    let resp = app
+        .auth_post(&webhooks_url, token)
+        .json(&serde_json::json!({
+            "name": "CI",
+            "url": hook_url,
+            "events": ["message.delete"],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_post(&webhooks_url, token)
+        .json(&serde_json::json!({
+            "name": "CI",
+            "url": hook_url,
+            "events": ["member.join", "message.create"],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let webhook: Value = resp.json().await.unwrap();
+    let secret = webhook["secret"].as_str().unwrap().to_string();
+    let webhook_url = format!("{}/{}", webhooks_url, webhook["id"].as_str().unwrap());
+    let log_url = format!("{}/delivery", webhook_url);
+
+    let resp = app
+        .auth_get(&webhooks_url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // The first delivery fails and is kept for a retry.
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tid, room_id),
+        &tenant.member.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+    let items = deliveries(&app, &log_url, token, 1).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["event"], "member.join");
+    assert_eq!(items[0]["status"], "pending");
+    assert_eq!(items[0]["attempts"], 1);
+    assert_eq!(items[0]["response_status"], 500);
+    assert!(items[0]["next_attempt_at"].is_string());
+    assert_eq!(
+        items[0]["payload"]["data"]["user_id"],
+        tenant.member.id.as_str()
+    );
+
+    // Once due, the sweep sends it again.
+    app.db
+        .collection::<bson::Document>("webhook_deliveries")
+        .update_many(
+            bson::doc! {},
+            bson::doc! { "$set": { "next_attempt_at": bson::DateTime::now() } },
+        )
+        .await
+        .unwrap();
+    let state = roomler_ai_api::state::AppState::new(app.db.clone(), app.settings.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        roomler_ai_api::webhooks::retry_due(&state).await.unwrap(),
+        1
+    );
+    let items = deliveries(&app, &log_url, token, 1).await;
+    assert_eq!(items[0]["status"], "succeeded");
+    assert_eq!(items[0]["attempts"], 2);
+    assert_eq!(items[0]["response_status"], 200);
+    assert!(items[0]["error"].is_null());
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "Deploy done" }))
+        .send()
+        .await
+        .unwrap();
+    let message: Value = resp.json().await.unwrap();
+    let items = deliveries(&app, &log_url, token, 2).await;
+    assert_eq!(items[0]["event"], "message.create");
+    assert_eq!(items[0]["status"], "succeeded");
+    {
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        let (event, signature, body) = &received[2];
+        assert_eq!(event, "message.create");
+        assert_eq!(
+            signature,
+            &roomler_ai_services::reaction_rules::signature(&secret, body)
+        );
+        let payload: Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["event"], "message.create");
+        assert_eq!(payload["tenant_id"], tid.as_str());
+        assert_eq!(payload["data"]["id"], message["id"]);
+        assert_eq!(payload["data"]["content"], "Deploy done");
+    }
+
+    // Disabled webhooks get nothing.
+    let resp = app
+        .auth_put(&webhook_url, token)
+        .json(&serde_json::json!({ "enabled": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+        token,
+    )
+    .json(&serde_json::json!({ "content": "Quiet" }))
+    .send()
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(received.lock().unwrap().len(), 3);
+
+    let resp = app.auth_delete(&webhook_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app.auth_get(&log_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    let webhooks: Vec<Value> = app
+        .auth_get(&webhooks_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(webhooks.is_empty());
+}
+
+#[tokio::test]
+async fn webhooks_to_internal_addresses_are_refused() {
+    let app = TestApp::spawn_with_settings(|s| s.outbound.allow_private_targets = false).await;
+    let tenant = app.seed_tenant("webhookssrf").await;
+    let token = &tenant.admin.access_token;
+    let webhooks_url = format!("/api/tenant/{}/webhook", tenant.tenant_id);
+    let rules_url = format!("/api/tenant/{}/reaction-rule", tenant.tenant_id);
+
+    for url in [
+        "http://127.0.0.1:9/hook",
+        "http://localhost/hook",
+        "http://10.0.0.5/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/hook",
+    ] {
+        let resp = app
+            .auth_post(&webhooks_url, token)
+            .json(&serde_json::json!({
+                "name": "Internal",
+                "url": url,
+                "events": ["message.create"],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{url}");
+
+        let resp = app
+            .auth_post(&rules_url, token)
+            .json(&serde_json::json!({
+                "name": "Internal",
+                "room_id": tenant.rooms[0].id,
+                "emoji": "👀",
+                "action": { "type": "webhook", "url": url },
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{url}");
+    }
+
+    let webhooks: Vec<Value> = app
+        .auth_get(&webhooks_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(webhooks.is_empty());
+}
@@ -0,0 +1,78 @@
+use crate::fixtures::test_app::TestApp;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// The next JSON event, or `{ "closed": code }` for a close frame.
+async fn next(ws: &mut WsStream) -> Value {
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Close(frame) => {
+                return serde_json::json!({ "closed": u16::from(frame.unwrap().code) });
+            }
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn ws_token_is_refreshed_in_place_and_closed_on_expiry() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.jwt.access_token_ttl_secs = 5;
+        s.ws.auth_expiry_warning_secs = 4;
+    })
+    .await;
+    let user = app
+        .register_user("ws@auth.test", "ws_auth", "WS", "Passw0rd!", None, None)
+        .await;
+    let other = app
+        .register_user(
+            "other@auth.test",
+            "ws_other",
+            "Other",
+            "Passw0rd!",
+            None,
+            None,
+        )
+        .await;
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, user.access_token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    let connected = next(&mut ws).await;
+    assert_eq!(connected["type"], "connected");
+    let expires_at = connected["expires_at"].as_i64().unwrap();
+
+    let warning = next(&mut ws).await;
+    assert_eq!(warning["type"], "auth:expiring");
+    assert_eq!(warning["data"]["expires_at"], expires_at);
+
+    let send = |token: &str| {
+        Message::Text(
+            serde_json::json!({ "type": "auth:refresh", "data": { "token": token } })
+                .to_string()
+                .into(),
+        )
+    };
+    // Another user's token doesn't take over the connection.
+    ws.send(send(&other.access_token)).await.unwrap();
+    let error = next(&mut ws).await;
+    assert_eq!(error["type"], "auth:error");
+
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    let fresh = app.login_user("ws@auth.test", "Passw0rd!").await;
+    ws.send(send(&fresh.access_token)).await.unwrap();
+    let refreshed = next(&mut ws).await;
+    assert_eq!(refreshed["type"], "auth:refreshed");
+    assert!(refreshed["data"]["expires_at"].as_i64().unwrap() > expires_at);
+
+    // Without another refresh the connection is warned again, then closed.
+    assert_eq!(next(&mut ws).await["type"], "auth:expiring");
+    assert_eq!(next(&mut ws).await["type"], "auth:expired");
+    assert_eq!(next(&mut ws).await["closed"], 4001);
+}
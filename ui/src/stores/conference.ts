@@ -11,6 +11,15 @@ interface RemoteStream {
   source: string
 }
 
+interface MediaConstraints {
+  echo_cancellation: boolean
+  noise_suppression: boolean
+  auto_gain_control: boolean
+  max_video_width: number
+  max_video_height: number
+  max_frame_rate: number
+}
+
 interface AnalyserEntry {
   analyser: AnalyserNode
   source: MediaStreamAudioSourceNode
@@ -35,6 +44,7 @@ export const useConferenceStore = defineStore('conference', () => {
   const isMuted = ref(false)
   const isVideoOn = ref(true)
   const isScreenSharing = ref(false)
+  const mediaConstraints = ref<MediaConstraints | null>(null)
 
   // --- Device selection ---
   const availableDevices = ref<MediaDeviceInfo[]>([])
@@ -118,6 +128,7 @@ export const useConferenceStore = defineStore('conference', () => {
 
     const dev = new Device()
     await dev.load({ routerRtpCapabilities: capsMsg.rtp_capabilities })
+    mediaConstraints.value = capsMsg.media_constraints ?? null
     device.value = dev
 
     const forceRelay = !!transportMsg.force_relay
@@ -249,9 +260,26 @@ export const useConferenceStore = defineStore('conference', () => {
   }
 
   async function produceLocalMedia() {
+    const c = mediaConstraints.value
+    const audio: MediaTrackConstraints = c
+      ? {
+          echoCancellation: c.echo_cancellation,
+          noiseSuppression: c.noise_suppression,
+          autoGainControl: c.auto_gain_control,
+        }
+      : {}
+    const video: MediaTrackConstraints = c
+      ? {
+          width: { ideal: c.max_video_width, max: c.max_video_width },
+          height: { ideal: c.max_video_height, max: c.max_video_height },
+          frameRate: { ideal: c.max_frame_rate, max: c.max_frame_rate },
+        }
+      : {}
+    if (selectedAudioDeviceId.value) audio.deviceId = { exact: selectedAudioDeviceId.value }
+    if (selectedVideoDeviceId.value) video.deviceId = { exact: selectedVideoDeviceId.value }
     const stream = await navigator.mediaDevices.getUserMedia({
-      audio: selectedAudioDeviceId.value ? { deviceId: { exact: selectedAudioDeviceId.value } } : true,
-      video: selectedVideoDeviceId.value ? { deviceId: { exact: selectedVideoDeviceId.value } } : true,
+      audio: Object.keys(audio).length ? audio : true,
+      video: Object.keys(video).length ? video : true,
     })
     localStream.value = stream
 
@@ -482,6 +510,7 @@ export const useConferenceStore = defineStore('conference', () => {
     tenantId.value = null
     roomId.value = null
     roomName.value = null
+    mediaConstraints.value = null
   }
 
   // ============ Active Speaker Detection ============
@@ -612,6 +641,7 @@ export const useConferenceStore = defineStore('conference', () => {
     isMuted,
     isVideoOn,
     isScreenSharing,
+    mediaConstraints,
 
     // Device selection
     availableDevices,
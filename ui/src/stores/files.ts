@@ -12,6 +12,14 @@ interface FileEntry {
   created_at: string
   room_id?: string
   room_name?: string
+  checksum?: string
+  duplicate_of?: {
+    file_id: string
+    filename: string
+    uploaded_by: string
+    uploaded_by_name?: string
+    uploaded_at: string
+  }
 }
 
 export const useFileStore = defineStore('files', () => {